@@ -0,0 +1,422 @@
+//! pagi-cli: administration client for a running PAGI gateway.
+//!
+//! Everything goes over the gateway's HTTP API, so the CLI works against a remote gateway and
+//! needs no access to its data directory. The gateway and key come from `--url` / `--api-key`,
+//! else `PAGI_URL` (default `http://127.0.0.1:8001`) and `PAGI_API_KEY`; the tenant goals and KB
+//! operations run as comes from `--tenant`, else `PAGI_TENANT_ID` (a tenant-bound key needs none).
+//! Responses are printed as JSON; a failed request prints the gateway's error and exits 1.
+
+use futures_util::StreamExt;
+use pagi_core::Goal;
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::time::Duration;
+
+const DEFAULT_URL: &str = "http://127.0.0.1:8001";
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(300);
+
+const USAGE: &str = "\
+usage: pagi-cli [--url <gateway>] [--api-key <key>] [--tenant <id>] [--agent <id>] <command>
+
+commands:
+  status                                  gateway identity (/v1/status)
+  verify                                  pre-flight against the gateway: health, identity, KB slots, API key
+  execute <goal> [--async] [--dry-run]    run a Goal given as JSON, @file or - (stdin)
+  goal <intent> [--context <json>] [--async] [--dry-run]
+                                          run the blueprint plan for <intent> (AutonomousGoal)
+  skill <name> [--payload <json>]         run one skill (ExecuteSkill)
+  job <job_id>                            status of a background job
+  kb get <slot> <key>                     read a KB record of the tenant
+  kb put <slot> <key> <value>             write one (<value> as JSON or text, @file, or -)
+  kb delete <slot> <key>                  remove one
+  kb query <slot> <query>                 KnowledgeQuery lookup
+  kb status                               status of the 8 knowledge bases
+  skills                                  registered skills with their health
+  blueprints                              blueprint intents and their plans
+  logs                                    follow the gateway log stream
+  export [--slots 1,3] [--out <file>]     every record of slots 1-8 as one JSON document
+  import <file>                           write an export document's records into the gateway
+  eval <suite> [--label <text>]           run an eval suite: a name from the gateway's PAGI_EVALS_DIR,
+                                          or the suite as JSON, @file or -; exits 1 if a case fails
+  evals [<suite>]                         every suite's latest score, or one suite's score trend";
+
+/// Flags that take a value; anything else starting with `--` is a switch.
+const VALUE_FLAGS: &[&str] = &["--url", "--api-key", "--tenant", "--agent", "--context", "--payload", "--slots", "--out", "--label"];
+
+/// Command line split into positional arguments, valued flags and switches.
+#[derive(Debug, Default, PartialEq)]
+struct Args {
+    positional: Vec<String>,
+    flags: HashMap<String, String>,
+    switches: Vec<String>,
+}
+
+impl Args {
+    fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut parsed = Args::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            if arg == "-" || !arg.starts_with("--") {
+                parsed.positional.push(arg);
+            } else if let Some((name, value)) = arg.split_once('=') {
+                parsed.flags.insert(name.to_string(), value.to_string());
+            } else if VALUE_FLAGS.contains(&arg.as_str()) {
+                let value = args.next().ok_or_else(|| format!("{} needs a value", arg))?;
+                parsed.flags.insert(arg, value);
+            } else {
+                parsed.switches.push(arg);
+            }
+        }
+        Ok(parsed)
+    }
+
+    fn flag(&self, name: &str) -> Option<&str> {
+        self.flags.get(name).map(String::as_str)
+    }
+
+    fn switch(&self, name: &str) -> bool {
+        self.switches.iter().any(|s| s == name)
+    }
+
+    fn arg(&self, i: usize, what: &str) -> Result<&str, String> {
+        self.positional.get(i).map(String::as_str).ok_or_else(|| format!("missing <{}>\n\n{}", what, USAGE))
+    }
+}
+
+/// Text of `arg`: `@path` reads a file, `-` reads stdin, anything else is itself.
+fn read_arg(arg: &str) -> Result<String, String> {
+    if arg == "-" {
+        let mut text = String::new();
+        std::io::stdin().read_to_string(&mut text).map_err(|e| format!("cannot read stdin: {}", e))?;
+        Ok(text)
+    } else if let Some(path) = arg.strip_prefix('@') {
+        std::fs::read_to_string(path).map_err(|e| format!("cannot read {}: {}", path, e))
+    } else {
+        Ok(arg.to_string())
+    }
+}
+
+fn parse_json(text: &str, what: &str) -> Result<serde_json::Value, String> {
+    serde_json::from_str(text).map_err(|e| format!("{} is not valid JSON: {}", what, e))
+}
+
+fn parse_slot(arg: &str) -> Result<u8, String> {
+    arg.parse::<u8>().ok().filter(|s| (1..=8).contains(s)).ok_or_else(|| format!("slot must be 1-8, got {:?}", arg))
+}
+
+/// The gateway and the key to call it with.
+struct Gateway {
+    url: String,
+    api_key: Option<String>,
+    http: reqwest::Client,
+}
+
+impl Gateway {
+    fn request(&self, method: reqwest::Method, path: &str) -> reqwest::RequestBuilder {
+        let request = self.http.request(method, format!("{}{}", self.url, path));
+        match &self.api_key {
+            Some(key) => request.header("X-API-Key", key),
+            None => request,
+        }
+    }
+
+    /// Sends `request` and reads the JSON answer; a non-2xx answer is an error with its message.
+    async fn send(&self, request: reqwest::RequestBuilder) -> Result<serde_json::Value, String> {
+        let res = request
+            .timeout(REQUEST_TIMEOUT)
+            .send()
+            .await
+            .map_err(|e| format!("cannot reach the gateway at {}: {}", self.url, e.without_url()))?;
+        let status = res.status();
+        let text = res.text().await.map_err(|e| format!("reading the response failed: {}", e))?;
+        let body = serde_json::from_str(&text).unwrap_or(serde_json::Value::String(text));
+        if status.is_success() {
+            return Ok(body);
+        }
+        let message = body
+            .get("error")
+            .or_else(|| body.get("message"))
+            .and_then(|e| e.as_str())
+            .map(str::to_string)
+            .unwrap_or_else(|| body.to_string());
+        Err(format!("{} {}", status, message))
+    }
+
+    async fn get(&self, path: &str) -> Result<serde_json::Value, String> {
+        self.send(self.request(reqwest::Method::GET, path)).await
+    }
+
+    async fn post(&self, path: &str, body: &serde_json::Value) -> Result<serde_json::Value, String> {
+        self.send(self.request(reqwest::Method::POST, path).json(body)).await
+    }
+}
+
+/// The caller's context for goals.
+struct Session {
+    gateway: Gateway,
+    tenant_id: Option<String>,
+    agent_id: Option<String>,
+}
+
+impl Session {
+    /// Runs `goal` through `/v1/execute`.
+    async fn execute(&self, goal: Goal, args: &Args) -> Result<serde_json::Value, String> {
+        let mut query = Vec::new();
+        if args.switch("--async") {
+            query.push("async=true");
+        }
+        if args.switch("--dry-run") {
+            query.push("dry_run=true");
+        }
+        let path = if query.is_empty() { "/v1/execute".to_string() } else { format!("/v1/execute?{}", query.join("&")) };
+        let body = serde_json::json!({
+            "tenant_id": self.tenant_id.clone().unwrap_or_default(),
+            "agent_id": self.agent_id,
+            "goal": goal,
+        });
+        self.gateway.post(&path, &body).await
+    }
+
+    /// A `MemoryOp` on `knowledge/{slot}/{key}`.
+    async fn knowledge_op(&self, slot: &str, key: &str, value: Option<serde_json::Value>, delete: bool) -> Result<serde_json::Value, String> {
+        let path = format!("knowledge/{}/{}", parse_slot(slot)?, key);
+        self.execute(Goal::MemoryOp { path, value, delete }, &Args::default()).await
+    }
+}
+
+async fn kb(session: &Session, args: &Args) -> Result<serde_json::Value, String> {
+    match args.arg(1, "kb command")? {
+        "get" => session.knowledge_op(args.arg(2, "slot")?, args.arg(3, "key")?, None, false).await,
+        "put" => {
+            let text = read_arg(args.arg(4, "value")?)?;
+            // JSON is stored as JSON; anything else as the text itself.
+            let value = serde_json::from_str(&text).unwrap_or(serde_json::Value::String(text));
+            session.knowledge_op(args.arg(2, "slot")?, args.arg(3, "key")?, Some(value), false).await
+        }
+        "delete" => session.knowledge_op(args.arg(2, "slot")?, args.arg(3, "key")?, None, true).await,
+        "query" => {
+            let goal = Goal::QueryKnowledge { slot_id: parse_slot(args.arg(2, "slot")?)?, query: args.arg(3, "query")?.to_string() };
+            session.execute(goal, args).await
+        }
+        "status" => session.gateway.get("/api/v1/kb-status").await,
+        other => Err(format!("unknown kb command {:?}\n\n{}", other, USAGE)),
+    }
+}
+
+/// Follows `/api/v1/logs`, printing each log line until the gateway closes the stream.
+async fn follow_logs(gateway: &Gateway) -> Result<(), String> {
+    let res = gateway
+        .request(reqwest::Method::GET, "/api/v1/logs")
+        .header("Accept", "text/event-stream")
+        .send()
+        .await
+        .map_err(|e| format!("cannot reach the gateway at {}: {}", gateway.url, e.without_url()))?;
+    if !res.status().is_success() {
+        return Err(format!("log stream answered {}", res.status()));
+    }
+    let mut stream = res.bytes_stream();
+    let mut buffer = String::new();
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk.map_err(|e| format!("log stream failed: {}", e))?;
+        buffer.push_str(&String::from_utf8_lossy(&chunk));
+        while let Some(end) = buffer.find('\n') {
+            let line: String = buffer.drain(..=end).collect();
+            // SSE: `data:` lines carry the log line; comments and keepalives are skipped.
+            if let Some(data) = line.trim_end_matches(['\r', '\n']).strip_prefix("data:") {
+                let data = data.strip_prefix(' ').unwrap_or(data);
+                if data != "keepalive" {
+                    println!("{}", data);
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn checking(what: &str) {
+    print!("Checking {}... ", what);
+    // Shown before the request, which may take a while or fail.
+    let _ = std::io::stdout().flush();
+}
+
+/// Checks the gateway the way `pagi-gateway --verify` checks a local install: it answers, says
+/// who it is, every knowledge base is connected, and the key is accepted.
+async fn verify(gateway: &Gateway) -> Result<(), String> {
+    checking(&gateway.url);
+    gateway.get("/api/v1/health").await.map_err(|e| format!("gateway DOWN: {}", e))?;
+    println!("OK");
+
+    checking("gateway identity");
+    let status = gateway.get("/v1/status").await.map_err(|e| format!("/v1/status failed: {}", e))?;
+    println!("OK ({}, llm_mode {})", status["app_name"].as_str().unwrap_or("?"), status["llm_mode"].as_str().unwrap_or("?"));
+
+    checking("API key");
+    let whoami = gateway.get("/api/v1/auth/whoami").await.map_err(|e| format!("API key REJECTED: {}", e))?;
+    match whoami["caller"]["role"].as_str() {
+        Some(role) => println!("OK ({}, role {})", whoami["caller"]["name"].as_str().unwrap_or("?"), role),
+        None => println!("OK (gateway is open: no keys enforced)"),
+    }
+
+    checking("knowledge bases");
+    let kb = gateway.get("/api/v1/kb-status").await.map_err(|e| format!("/api/v1/kb-status failed: {}", e))?;
+    if kb["all_connected"] != true {
+        let down: Vec<String> = kb["knowledge_bases"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter(|s| s["connected"] != true)
+            .map(|s| s["name"].as_str().unwrap_or("?").to_string())
+            .collect();
+        return Err(format!("knowledge bases DISCONNECTED: {}", down.join(", ")));
+    }
+    println!("OK ({} entries)", kb["total_entries"]);
+
+    println!("\n✅ SUCCESS: gateway at {} is ready.", gateway.url);
+    Ok(())
+}
+
+/// Runs an eval suite through `/api/v1/evals/run` and prints the run; a failing case is an error.
+async fn eval(session: &Session, args: &Args) -> Result<(), String> {
+    let arg = args.arg(1, "suite")?;
+    // A bare word names a suite file on the gateway; anything else is the suite itself.
+    let suite = if arg == "-" || arg.starts_with('@') || arg.trim_start().starts_with('{') {
+        parse_json(&read_arg(arg)?, "the suite")?
+    } else {
+        serde_json::Value::String(arg.to_string())
+    };
+    let body = serde_json::json!({
+        "suite": suite,
+        "label": args.flag("--label"),
+        "tenant_id": session.tenant_id,
+        "agent_id": session.agent_id,
+    });
+    let output = session.gateway.post("/api/v1/evals/run", &body).await?;
+    println!("{}", serde_json::to_string_pretty(&output).unwrap_or_default());
+    let run = &output["run"];
+    let (passed, total) = (run["passed"].as_u64().unwrap_or(0), run["total"].as_u64().unwrap_or(0));
+    let delta = output["delta"].as_f64().map_or(String::new(), |d| format!(" ({:+.3} vs previous run)", d));
+    eprintln!(
+        "{}: score {:.3}{}, {}/{} case(s) passed",
+        run["suite"].as_str().unwrap_or("?"),
+        run["score"].as_f64().unwrap_or(0.0),
+        delta,
+        passed,
+        total
+    );
+    if passed < total {
+        return Err(format!("{} eval case(s) failed", total - passed));
+    }
+    Ok(())
+}
+
+async fn run(args: Args) -> Result<(), String> {
+    let env = |name: &str| std::env::var(name).ok().map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+    let url = args.flag("--url").map(str::to_string).or_else(|| env("PAGI_URL")).unwrap_or_else(|| DEFAULT_URL.to_string());
+    let session = Session {
+        gateway: Gateway {
+            url: url.trim_end_matches('/').to_string(),
+            api_key: args.flag("--api-key").map(str::to_string).or_else(|| env("PAGI_API_KEY")),
+            http: reqwest::Client::new(),
+        },
+        tenant_id: args.flag("--tenant").map(str::to_string).or_else(|| env("PAGI_TENANT_ID")),
+        agent_id: args.flag("--agent").map(str::to_string),
+    };
+    let gateway = &session.gateway;
+    let output = match args.arg(0, "command")? {
+        "status" => gateway.get("/v1/status").await?,
+        "verify" => return verify(gateway).await.map_err(|e| format!("PRE-FLIGHT FAILED: {}", e)),
+        "execute" => {
+            let goal = parse_json(&read_arg(args.arg(1, "goal")?)?, "the goal")?;
+            let goal: Goal = serde_json::from_value(goal).map_err(|e| format!("not a Goal: {}", e))?;
+            session.execute(goal, &args).await?
+        }
+        "goal" => {
+            let context = args.flag("--context").map(|c| read_arg(c).and_then(|c| parse_json(&c, "--context"))).transpose()?;
+            let goal = Goal::AutonomousGoal { intent: args.arg(1, "intent")?.to_string(), context };
+            session.execute(goal, &args).await?
+        }
+        "skill" => {
+            let payload = args.flag("--payload").map(|p| read_arg(p).and_then(|p| parse_json(&p, "--payload"))).transpose()?;
+            session.execute(Goal::ExecuteSkill { name: args.arg(1, "name")?.to_string(), payload }, &args).await?
+        }
+        "job" => gateway.get(&format!("/v1/jobs/{}", args.arg(1, "job_id")?)).await?,
+        "kb" => kb(&session, &args).await?,
+        "skills" => gateway.get("/v1/skills").await?,
+        "blueprints" => gateway.get("/api/v1/blueprints").await?,
+        "logs" => return follow_logs(gateway).await,
+        "export" => {
+            let path = match args.flag("--slots") {
+                Some(slots) => format!("/api/v1/knowledge/export?slots={}", slots),
+                None => "/api/v1/knowledge/export".to_string(),
+            };
+            let export = gateway.get(&path).await?;
+            let count = export["records"].as_array().map_or(0, Vec::len);
+            match args.flag("--out") {
+                Some(out) => {
+                    let text = serde_json::to_string_pretty(&export).unwrap_or_default();
+                    std::fs::write(out, text).map_err(|e| format!("cannot write {}: {}", out, e))?;
+                    eprintln!("Exported {} record(s) to {}", count, out);
+                    return Ok(());
+                }
+                None => export,
+            }
+        }
+        "import" => {
+            let export = parse_json(&read_arg(&format!("@{}", args.arg(1, "file")?))?, "the export file")?;
+            gateway.post("/api/v1/knowledge/import", &export).await?
+        }
+        "eval" => return eval(&session, &args).await,
+        "evals" => match args.positional.get(1) {
+            Some(suite) => gateway.get(&format!("/api/v1/evals/{}/runs", suite)).await?,
+            None => gateway.get("/api/v1/evals").await?,
+        },
+        "help" => {
+            println!("{}", USAGE);
+            return Ok(());
+        }
+        other => return Err(format!("unknown command {:?}\n\n{}", other, USAGE)),
+    };
+    println!("{}", serde_json::to_string_pretty(&output).unwrap_or_default());
+    Ok(())
+}
+
+#[tokio::main]
+async fn main() {
+    // Load .env file if present, for PAGI_URL / PAGI_API_KEY / PAGI_TENANT_ID.
+    let _ = dotenvy::dotenv();
+    let args = match Args::parse(std::env::args().skip(1)) {
+        Ok(args) if !args.positional.is_empty() && !args.switch("--help") => args,
+        Ok(_) => {
+            println!("{}", USAGE);
+            return;
+        }
+        Err(e) => {
+            eprintln!("❌ {}", e);
+            std::process::exit(2);
+        }
+    };
+    if let Err(e) = run(args).await {
+        eprintln!("❌ {}", e);
+        std::process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn arguments_split_into_positionals_flags_and_switches() {
+        let args = Args::parse(
+            ["--tenant", "acme", "goal", "respond to lead", "--context={\"lead_id\":\"l1\"}", "--async", "-"].map(String::from),
+        )
+        .unwrap();
+        assert_eq!(args.positional, ["goal", "respond to lead", "-"]);
+        assert_eq!(args.flag("--tenant"), Some("acme"));
+        assert_eq!(args.flag("--context"), Some("{\"lead_id\":\"l1\"}"));
+        assert!(args.switch("--async"));
+        assert!(!args.switch("--dry-run"));
+        assert_eq!(Args::parse(["status", "--url"].map(String::from)).unwrap_err(), "--url needs a value");
+        assert!(parse_slot("9").is_err());
+    }
+}
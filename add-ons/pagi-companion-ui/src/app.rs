@@ -6,7 +6,7 @@ use pagi_core::{
 };
 use pagi_skills::{
     CommunityPulse, CommunityScraper, DraftResponse, KnowledgeInsert, KnowledgePruner,
-    KnowledgeQuery, LeadCapture, ModelRouter, ResearchAudit, SalesCloser,
+    KnowledgeQuery, LeadCapture, ModelRouter, ParseInboundMessage, ResearchAudit, SalesCloser,
 };
 use std::path::Path;
 use std::sync::Arc;
@@ -32,23 +32,22 @@ pub fn build_orchestrator(storage_dir: &Path) -> Result<Arc<Orchestrator>, Box<d
     registry.register(Arc::new(ResearchAudit::new(Arc::clone(&knowledge))));
     registry.register(Arc::new(CommunityScraper::new(Arc::clone(&knowledge))));
     registry.register(Arc::new(SalesCloser::new(Arc::clone(&knowledge))));
+    registry.register(Arc::new(ParseInboundMessage::new(
+        Arc::clone(&memory),
+        Arc::new(ModelRouter::new()),
+    )));
     registry.register(Arc::new(KnowledgePruner::new(Arc::clone(&knowledge))));
 
     let blueprint_path = std::env::var("PAGI_BLUEPRINT_PATH")
         .unwrap_or_else(|_| "config/blueprint.json".to_string());
     let blueprint = Arc::new(BlueprintRegistry::load_json_path(&blueprint_path));
 
-    Ok(Arc::new(Orchestrator::with_blueprint(
-        Arc::new(registry),
-        blueprint,
-    )))
+    Ok(Arc::new(
+        Orchestrator::with_blueprint(Arc::new(registry), blueprint).with_memory(memory),
+    ))
 }
 
 /// Default tenant for add-on UIs.
 pub fn default_tenant() -> TenantContext {
-    TenantContext {
-        tenant_id: "pagi-companion-ui".to_string(),
-        correlation_id: None,
-        agent_id: None,
-    }
+    TenantContext::new("pagi-companion-ui")
 }
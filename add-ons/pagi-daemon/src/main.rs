@@ -3,14 +3,11 @@
 //! A long-running daemon that periodically checks agent inboxes (KB_SOMA)
 //! and triggers background work without requiring synchronous polling.
 
-use pagi_core::{CoreConfig, EventRecord, KnowledgeStore};
-use pagi_skills::ModelRouter;
-use std::{collections::HashSet, path::Path as StdPath, sync::Arc, time::Duration};
+use pagi_core::{AgentSkill, AgentTickTracker, CoreConfig, InboxRetry, KbType, KnowledgeStore, Redactor, TenantContext};
+use pagi_skills::{LlmProviders, ModelRouter};
+use std::{path::Path as StdPath, sync::Arc};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
-/// Default tick rate to avoid slamming LLM APIs.
-const DEFAULT_TICK_RATE_SECS: u64 = 5;
-
 #[tokio::main]
 async fn main() {
     // Load .env file if present (before any env::var calls)
@@ -25,38 +22,61 @@ async fn main() {
         .with(tracing_subscriber::fmt::layer())
         .init();
 
-    let config = Arc::new(CoreConfig::load().expect("load CoreConfig"));
-    let tick_rate = std::env::var("PAGI_TICK_RATE_SECS")
-        .ok()
-        .and_then(|s| s.parse::<u64>().ok())
-        .unwrap_or(DEFAULT_TICK_RATE_SECS)
-        .max(1);
+    let config = match CoreConfig::load() {
+        Ok(config) => Arc::new(config),
+        Err(e) => {
+            eprintln!("[pagi-daemon] Cannot load config: {}", e);
+            std::process::exit(1);
+        }
+    };
+    pagi_core::init_secrets(&config).await.expect("load secrets");
+    let tick_rate = config.tick_rate();
 
     let storage = StdPath::new(&config.storage_path);
     // NOTE: sled is single-writer; gateway and daemon must not open the same DB path concurrently.
-    // Run the daemon against a separate copy/path, configurable via env.
+    // Run the daemon against a separate copy/path, configurable via env (or set
+    // `storage_backend = "sqlite"`, which allows sharing `pagi_knowledge`).
     let knowledge_path = std::env::var("PAGI_DAEMON_KNOWLEDGE_PATH")
         .map(std::path::PathBuf::from)
         .unwrap_or_else(|_| storage.join("pagi_knowledge_daemon"));
 
-    let knowledge = Arc::new(KnowledgeStore::open_path(&knowledge_path).expect("open daemon pagi_knowledge"));
+    let redactor = Redactor::from_config(&config.redaction);
+    let mut knowledge_store = KnowledgeStore::open_with_backend(&knowledge_path, config.storage_backend)
+        .expect("open daemon pagi_knowledge")
+        .with_max_value_bytes(config.limits.max_kb_value_bytes)
+        .with_encrypted_slots(&config.encryption.slots);
+    if let Some(redactor) = &redactor {
+        knowledge_store = knowledge_store.with_redaction(redactor.clone());
+    }
+    let knowledge = Arc::new(knowledge_store);
     knowledge.pagi_init_kb_metadata().ok();
+    // Agents known only by their KB_SOMA inbox (from before the agent registry) keep being served.
+    if let Err(e) = knowledge.register_inbox_agents() {
+        tracing::warn!(error = %e, "agent registry migration failed");
+    }
 
-    // Router used to generate agent responses.
-    let model_router = Arc::new(ModelRouter::with_knowledge(Arc::clone(&knowledge)));
+    // Router used to generate agent responses (model aliases fail over and budgets apply like the gateway's).
+    let mut model_router = ModelRouter::with_knowledge(Arc::clone(&knowledge))
+        .with_providers(Arc::new(LlmProviders::from_config(&config)))
+        .with_budgets(config.token_budgets.clone());
+    if let Some(redactor) = redactor {
+        model_router = model_router.with_redaction(redactor);
+    }
+    let model_router = Arc::new(model_router);
 
     tracing::info!(
-        tick_rate_secs = tick_rate,
+        tick_rate_secs = tick_rate.as_secs(),
         storage_path = %config.storage_path,
         "Pagi daemon started"
     );
 
-    let mut interval = tokio::time::interval(Duration::from_secs(tick_rate));
+    let mut interval = tokio::time::interval(tick_rate);
+    let mut agent_ticks = AgentTickTracker::default();
 
     loop {
         tokio::select! {
             _ = interval.tick() => {
-                if let Err(e) = tick(Arc::clone(&knowledge), Arc::clone(&model_router)).await {
+                if let Err(e) = tick(Arc::clone(&knowledge), Arc::clone(&model_router), &mut agent_ticks).await {
                     tracing::warn!(error = %e, "daemon tick failed");
                 }
             }
@@ -71,37 +91,59 @@ async fn main() {
 async fn tick(
     knowledge: Arc<KnowledgeStore>,
     model_router: Arc<ModelRouter>,
+    agent_ticks: &mut AgentTickTracker,
 ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    // Discover active agents by scanning KB_SOMA inbox keys: inbox/{agent_id}/...
-    let soma_slot = pagi_core::KbType::Soma.slot_id();
-    let keys = knowledge.scan_keys(soma_slot)?;
-    let mut agents: HashSet<String> = HashSet::new();
-    for k in keys {
-        if let Some(rest) = k.strip_prefix("inbox/") {
-            if let Some((agent_id, _tail)) = rest.split_once('/') {
-                if !agent_id.trim().is_empty() {
-                    agents.insert(agent_id.to_string());
-                }
+    // Topic broadcasts: copy new topic messages into their subscribers' inboxes.
+    knowledge.heartbeat_fan_out_topics();
+    // Serve the registered, active agents (KB_PNEUMA agents/{agent_id}) whose schedule is due
+    // (not paused, inside their active hours, past their tick interval).
+    for agent_id in knowledge.due_agent_ids(agent_ticks)? {
+        // AUTO-POLL: check inbox for the newest message that is unprocessed and not waiting out a
+        // retry backoff.
+        let now = knowledge.now_ms();
+        let inbox = knowledge.get_agent_messages_with_keys(&agent_id, 25)?;
+        if let Some((inbox_key, msg)) = inbox.iter().find(|(_, m)| m.is_ready(now)) {
+            let mut processed = msg.clone();
+            processed.is_processed = true;
+            // Topic broadcasts and auto-replies are delivered, not answered (no ping-pong).
+            if msg.topic.is_some() || msg.payload.get("type").and_then(|v| v.as_str()) == Some("agent_auto_reply") {
+                knowledge.insert(KbType::Soma.slot_id(), inbox_key, &processed.to_bytes())?;
+                continue;
             }
-        }
-    }
-
-    for agent_id in agents {
-        // AUTO-POLL: check inbox
-        let messages = knowledge.get_agent_messages(&agent_id, 1)?;
-        if let Some(msg) = messages.first() {
-            // Trigger response generation for the agent.
-            let prompt = format!(
-                "You are agent_id={}. You have a new inbox message from {}. Message payload: {}\n\nRespond appropriately.",
-                agent_id,
-                msg.from_agent_id,
-                msg.payload
-            );
-
-            let generated = model_router
-                .generate_text_raw(&prompt)
+            // Trigger response generation for the agent, with the same context assembly as chat
+            // (identity, Kardia relationship, mental state, thread history).
+            let reply = knowledge.build_auto_reply_prompt(&agent_id, msg);
+            let ctx = TenantContext {
+                tenant_id: msg.from_agent_id.clone(),
+                correlation_id: Some(msg.id.clone()),
+                agent_id: Some(agent_id.clone()),
+                ..Default::default()
+            };
+            let generated = match model_router
+                .execute(
+                    &ctx,
+                    Some(serde_json::json!({
+                        "prompt": reply.prompt,
+                        "system_prompt": reply.system_prompt,
+                    })),
+                )
                 .await
-                .unwrap_or_else(|e| format!("[daemon] generation failed: {}", e));
+            {
+                Ok(result) => result
+                    .get("generated")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or_default()
+                    .to_string(),
+                // Retried with backoff; dead-lettered (KB_SOMA inbox_dlq/...) after repeated failures.
+                Err(e) => {
+                    if let Some(InboxRetry::DeadLettered { attempts }) =
+                        knowledge.record_inbox_failure(inbox_key, &e.to_string())?
+                    {
+                        tracing::warn!(agent_id = %agent_id, message_id = %msg.id, attempts, error = %e, "auto-reply dead-lettered");
+                    }
+                    continue;
+                }
+            };
 
             // Deliver response back to sender as an inter-agent message.
             knowledge.push_agent_message(
@@ -113,9 +155,10 @@ async fn tick(
                     "text": generated,
                 }),
             )?;
+            knowledge.insert(KbType::Soma.slot_id(), inbox_key, &processed.to_bytes())?;
 
             // Reflection: write a Chronos event for the agent.
-            let reflection = EventRecord::now(
+            let reflection = knowledge.event(
                 "Chronos",
                 format!("Auto-replied to message {} from {}", msg.id, msg.from_agent_id),
             )
@@ -125,8 +168,8 @@ async fn tick(
         } else {
             // If no inbox message exists, check Pneuma for background tasks.
             // Minimal v1: if a key `pneuma/{agent_id}/background_task` exists, run it through the router.
-            let pneuma_slot = pagi_core::KbType::Pneuma.slot_id();
-            let bg_key = format!("pneuma/{}/background_task", agent_id);
+            let pneuma_slot = KbType::Pneuma.slot_id();
+            let bg_key = pagi_core::keys::background_task_key(&agent_id);
             if let Ok(Some(bytes)) = knowledge.get(pneuma_slot, &bg_key) {
                 if let Ok(task) = String::from_utf8(bytes) {
                     if !task.trim().is_empty() {
@@ -139,7 +182,7 @@ async fn tick(
                             .generate_text_raw(&prompt)
                             .await
                             .unwrap_or_else(|e| format!("[daemon] background generation failed: {}", e));
-                        let reflection = EventRecord::now(
+                        let reflection = knowledge.event(
                             "Chronos",
                             format!("Background task ticked: {}", generated),
                         )
@@ -0,0 +1,75 @@
+//! Channel adapters: messaging services end users talk to an agent over.
+//!
+//! An adapter maps each conversation on the service to a tenant, an agent and a chat session,
+//! runs every inbound message through the gateway's chat pipeline ([`reply`]: Mission Directive
+//! with Soma/Kardia context, session history, intent routing, ModelRouter) and sends the reply
+//! back as it streams. Messages are rate limited per conversation.
+//!
+//! - [`telegram`]: a Telegram bot, long-polling `getUpdates` or receiving webhooks (`[channels.telegram]`)
+
+pub mod telegram;
+
+use futures_util::{Stream, StreamExt};
+
+use crate::{chat_event_stream, default_deadline_ms, AppState, ChatRequest};
+
+/// One inbound message, mapped to the conversation it belongs to.
+#[derive(Debug, Clone)]
+pub struct InboundMessage {
+    pub tenant_id: String,
+    pub agent_id: String,
+    /// Chat session the exchange is recorded under, stable per conversation.
+    pub session_id: String,
+    pub text: String,
+}
+
+/// The reply so far.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReplyUpdate {
+    /// Text generated so far; more follows.
+    Partial(String),
+    /// The whole reply (or the error shown instead); always the last update.
+    Done { text: String, status: String },
+}
+
+/// Runs `message` through the chat pipeline, yielding the reply as it grows.
+pub fn reply(state: AppState, message: InboundMessage) -> impl Stream<Item = ReplyUpdate> + Send + 'static {
+    let req: Result<ChatRequest, _> = serde_json::from_value(serde_json::json!({
+        "prompt": message.text,
+        "user_alias": message.tenant_id,
+        "agent_id": message.agent_id,
+        "session_id": message.session_id,
+    }));
+    let correlation_id = uuid::Uuid::new_v4().to_string();
+    async_stream::stream! {
+        let req = match req {
+            Ok(req) => req,
+            Err(e) => {
+                yield ReplyUpdate::Done { text: format!("Could not read the message: {}", e), status: "error".to_string() };
+                return;
+            }
+        };
+        let mut events = Box::pin(chat_event_stream(state, req, correlation_id, default_deadline_ms()));
+        let mut text = String::new();
+        let mut error = None;
+        while let Some((kind, data)) = events.next().await {
+            match kind {
+                "delta" => {
+                    text.push_str(data["text"].as_str().unwrap_or_default());
+                    yield ReplyUpdate::Partial(text.clone());
+                }
+                "error" => error = data["error"].as_str().map(str::to_string),
+                "done" => {
+                    let status = data["status"].as_str().unwrap_or("ok").to_string();
+                    let text = match (text.is_empty(), error.take()) {
+                        (true, Some(error)) => error,
+                        _ => std::mem::take(&mut text),
+                    };
+                    yield ReplyUpdate::Done { text, status };
+                    return;
+                }
+                _ => {}
+            }
+        }
+    }
+}
@@ -0,0 +1,291 @@
+//! Telegram bot channel (`[channels.telegram]`).
+//!
+//! Each Telegram chat is one conversation: it talks to the tenant and agent its `chats` entry
+//! names (else the channel's), in the chat session `telegram-{chat_id}`. Text messages run through
+//! the chat pipeline; the reply is sent as soon as text arrives and edited as it grows, and long
+//! replies continue in further messages. Messages over the chat's `rate_limit` are answered with a
+//! retry hint instead.
+//!
+//! Updates arrive by long polling (`mode = "polling"`, started with the gateway) or by webhook
+//! (`mode = "webhook"`): register `/api/v1/channels/telegram/webhook` with `setWebhook` and a
+//! `secret_token`, which Telegram sends back as `X-Telegram-Bot-Api-Secret-Token`. That header is
+//! the credential, so the route needs no API key.
+//!
+//! Routes:
+//! - `POST /api/v1/channels/telegram/webhook` – one Telegram `Update`; 200 once accepted (the reply
+//!   is sent in the background), 401 for a bad secret token, 404 unless the bot is on in webhook mode
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use axum::{
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    Extension, Json,
+};
+use futures_util::StreamExt;
+use pagi_core::{RateLimiter, TelegramConfig, TelegramMode};
+use serde::Deserialize;
+
+use super::{reply, InboundMessage, ReplyUpdate};
+use crate::AppState;
+
+/// Telegram refuses longer messages.
+const TELEGRAM_MAX_CHARS: usize = 4096;
+/// Least time between edits of a reply while it streams (Telegram throttles edits).
+const EDIT_INTERVAL: Duration = Duration::from_millis(1200);
+/// How long one `getUpdates` call waits for updates.
+const POLL_TIMEOUT_SECS: u64 = 30;
+/// Pause after a failed `getUpdates` call.
+const POLL_RETRY_DELAY: Duration = Duration::from_secs(5);
+const SECRET_TOKEN_HEADER: &str = "X-Telegram-Bot-Api-Secret-Token";
+
+/// The fields PAGI uses from a Telegram `Update`.
+#[derive(Debug, Deserialize)]
+pub struct Update {
+    pub update_id: i64,
+    #[serde(default)]
+    pub message: Option<Message>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Message {
+    pub chat: Chat,
+    #[serde(default)]
+    pub text: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Chat {
+    pub id: i64,
+}
+
+/// The bot: its configuration, Bot API client and per-chat rate limits.
+pub struct TelegramBot {
+    config: TelegramConfig,
+    http: reqwest::Client,
+    limiter: RateLimiter,
+}
+
+impl TelegramBot {
+    pub fn new(config: TelegramConfig) -> Self {
+        Self {
+            config,
+            http: reqwest::Client::new(),
+            limiter: RateLimiter::new(),
+        }
+    }
+
+    /// Calls Bot API `method`; its `result`, or the API's description of the failure.
+    async fn call(&self, token: &str, method: &str, body: serde_json::Value, timeout: Duration) -> Result<serde_json::Value, String> {
+        let url = format!("{}/bot{}/{}", self.config.api_url(), token, method);
+        let res = self
+            .http
+            .post(&url)
+            .timeout(timeout)
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| format!("{} failed: {}", method, e.without_url()))?;
+        let answer: serde_json::Value = res.json().await.map_err(|e| format!("{} answered no JSON: {}", method, e))?;
+        if answer["ok"] == true {
+            Ok(answer["result"].clone())
+        } else {
+            Err(format!("{} failed: {}", method, answer["description"].as_str().unwrap_or("no description")))
+        }
+    }
+
+    async fn send(&self, token: &str, method: &str, body: serde_json::Value) -> Option<serde_json::Value> {
+        match self.call(token, method, body, Duration::from_secs(15)).await {
+            Ok(result) => Some(result),
+            Err(e) => {
+                tracing::warn!(target: "pagi::channels", error = %e, "Telegram call failed");
+                None
+            }
+        }
+    }
+
+    /// Answers one update: text messages go through the chat pipeline, anything else is ignored.
+    pub async fn handle_update(&self, state: AppState, token: &str, update: Update) {
+        let Some(message) = update.message else {
+            return;
+        };
+        let chat_id = message.chat.id;
+        let Some((tenant_id, agent_id)) = self.config.route_for(&chat_id.to_string()) else {
+            tracing::debug!(target: "pagi::channels", chat_id, "Telegram chat is not listed; ignored");
+            return;
+        };
+        let Some(text) = message.text.map(|t| t.trim().to_string()).filter(|t| !t.is_empty()) else {
+            self.send(token, "sendMessage", serde_json::json!({ "chat_id": chat_id, "text": "I can only read text messages." }))
+                .await;
+            return;
+        };
+        if text == "/start" {
+            let hello = "Hi! Send me a message and I will answer.";
+            self.send(token, "sendMessage", serde_json::json!({ "chat_id": chat_id, "text": hello })).await;
+            return;
+        }
+        if let Err(retry_after) = self.limiter.check(&chat_id.to_string(), self.config.rate_limit()) {
+            let wait = format!("You are sending messages too fast. Try again in {}s.", retry_after.as_secs().max(1));
+            self.send(token, "sendMessage", serde_json::json!({ "chat_id": chat_id, "text": wait })).await;
+            return;
+        }
+        self.send(token, "sendChatAction", serde_json::json!({ "chat_id": chat_id, "action": "typing" })).await;
+
+        let inbound = InboundMessage {
+            tenant_id,
+            agent_id,
+            session_id: format!("telegram-{}", chat_id),
+            text,
+        };
+        tracing::info!(target: "pagi::channels", chat_id, tenant_id = %inbound.tenant_id, agent_id = %inbound.agent_id, "Telegram message received");
+        let mut shown = ShownReply::new(chat_id);
+        let mut updates = Box::pin(reply(state, inbound));
+        while let Some(update) = updates.next().await {
+            match update {
+                ReplyUpdate::Partial(text) if shown.message_id.is_none() || shown.last_edit.elapsed() >= EDIT_INTERVAL => {
+                    shown.show(self, token, &text).await;
+                }
+                ReplyUpdate::Partial(_) => {}
+                ReplyUpdate::Done { text, status } => {
+                    let chunks = split_message(&text);
+                    let mut rest = chunks.iter();
+                    if let Some(first) = rest.next() {
+                        shown.show(self, token, first).await;
+                    }
+                    for chunk in rest {
+                        self.send(token, "sendMessage", serde_json::json!({ "chat_id": chat_id, "text": chunk })).await;
+                    }
+                    tracing::debug!(target: "pagi::channels", chat_id, status = %status, chars = text.chars().count(), "Telegram reply sent");
+                }
+            }
+        }
+    }
+}
+
+/// The message a reply is shown in: sent with the first text, then edited.
+struct ShownReply {
+    chat_id: i64,
+    message_id: Option<i64>,
+    text: String,
+    last_edit: Instant,
+}
+
+impl ShownReply {
+    fn new(chat_id: i64) -> Self {
+        Self {
+            chat_id,
+            message_id: None,
+            text: String::new(),
+            last_edit: Instant::now(),
+        }
+    }
+
+    async fn show(&mut self, bot: &TelegramBot, token: &str, text: &str) {
+        let text: String = text.chars().take(TELEGRAM_MAX_CHARS).collect();
+        // Telegram refuses empty messages and edits that change nothing.
+        if text.trim().is_empty() || text == self.text {
+            return;
+        }
+        match self.message_id {
+            None => {
+                let sent = bot.send(token, "sendMessage", serde_json::json!({ "chat_id": self.chat_id, "text": text })).await;
+                self.message_id = sent.and_then(|m| m["message_id"].as_i64());
+            }
+            Some(message_id) => {
+                let body = serde_json::json!({ "chat_id": self.chat_id, "message_id": message_id, "text": text });
+                bot.send(token, "editMessageText", body).await;
+            }
+        }
+        self.text = text;
+        self.last_edit = Instant::now();
+    }
+}
+
+/// `text` in pieces Telegram accepts, split at line breaks where possible.
+fn split_message(text: &str) -> Vec<String> {
+    let mut chunks = Vec::new();
+    let mut rest: Vec<char> = text.chars().collect();
+    while rest.len() > TELEGRAM_MAX_CHARS {
+        let window = &rest[..TELEGRAM_MAX_CHARS];
+        let cut = window.iter().rposition(|&c| c == '\n').filter(|&i| i > 0).unwrap_or(TELEGRAM_MAX_CHARS);
+        chunks.push(rest[..cut].iter().collect());
+        rest.drain(..cut);
+    }
+    chunks.push(rest.into_iter().collect());
+    chunks
+}
+
+/// Long-polls `getUpdates` and answers each update in the background, until the gateway stops.
+pub async fn poll(state: AppState, bot: Arc<TelegramBot>) {
+    let Some(token) = pagi_core::secret(bot.config.bot_token_env()) else {
+        tracing::warn!(target: "pagi::channels", secret = bot.config.bot_token_env(), "Telegram bot token is not set; polling not started");
+        return;
+    };
+    tracing::info!(target: "pagi::channels", "Telegram bot polling for updates");
+    let mut offset = 0i64;
+    loop {
+        let body = serde_json::json!({ "offset": offset, "timeout": POLL_TIMEOUT_SECS, "allowed_updates": ["message"] });
+        let updates = match bot.call(&token, "getUpdates", body, Duration::from_secs(POLL_TIMEOUT_SECS + 10)).await {
+            Ok(updates) => updates,
+            Err(e) => {
+                tracing::warn!(target: "pagi::channels", error = %e, "Telegram getUpdates failed");
+                tokio::time::sleep(POLL_RETRY_DELAY).await;
+                continue;
+            }
+        };
+        for update in updates.as_array().into_iter().flatten() {
+            let Ok(update) = serde_json::from_value::<Update>(update.clone()) else {
+                continue;
+            };
+            offset = offset.max(update.update_id + 1);
+            let (state, bot, token) = (state.clone(), Arc::clone(&bot), token.clone());
+            tokio::spawn(async move { bot.handle_update(state, &token, update).await });
+        }
+    }
+}
+
+/// POST /api/v1/channels/telegram/webhook
+pub async fn webhook(
+    State(state): State<AppState>,
+    Extension(bot): Extension<Arc<TelegramBot>>,
+    headers: HeaderMap,
+    Json(update): Json<Update>,
+) -> StatusCode {
+    if !bot.config.enabled || bot.config.mode != TelegramMode::Webhook {
+        return StatusCode::NOT_FOUND;
+    }
+    let (Some(token), Some(secret)) = (
+        pagi_core::secret(bot.config.bot_token_env()),
+        pagi_core::secret(bot.config.webhook_secret_env()),
+    ) else {
+        tracing::warn!(target: "pagi::channels", "Telegram bot token or webhook secret is not set");
+        return StatusCode::SERVICE_UNAVAILABLE;
+    };
+    let given = headers.get(SECRET_TOKEN_HEADER).and_then(|v| v.to_str().ok()).unwrap_or_default();
+    // Digests are compared so the time taken says nothing about the secret.
+    let digest = |s: &str| ring::digest::digest(&ring::digest::SHA256, s.as_bytes());
+    if digest(given).as_ref() != digest(&secret).as_ref() {
+        tracing::warn!(target: "pagi::channels", "Telegram webhook secret token rejected");
+        return StatusCode::UNAUTHORIZED;
+    }
+    tokio::spawn(async move { bot.handle_update(state, &token, update).await });
+    StatusCode::OK
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn long_replies_split_at_line_breaks() {
+        assert_eq!(split_message("short"), vec!["short".to_string()]);
+        let long = format!("{}\n{}", "a".repeat(4000), "b".repeat(200));
+        let chunks = split_message(&long);
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks[0], "a".repeat(4000));
+        assert_eq!(chunks[1], format!("\n{}", "b".repeat(200)));
+        let unbroken = "c".repeat(5000);
+        assert_eq!(split_message(&unbroken).iter().map(|c| c.len()).collect::<Vec<_>>(), vec![4096, 904]);
+    }
+}
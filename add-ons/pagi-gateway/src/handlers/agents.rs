@@ -0,0 +1,311 @@
+//! Agent lifecycle: the agents this instance runs, registered in KB_PNEUMA (`agents/{agent_id}`).
+//!
+//! The Heartbeat serves the registered, active agents (their inbox auto-replies and background
+//! tasks), each on its own schedule: a tick interval, an active-hours window and a `paused` flag
+//! for quiescing one agent. Archiving an agent takes it out of that loop but keeps its record,
+//! persona, inbox and history. Agents that only existed through their inbox are registered once at
+//! startup. `POST`, `PUT`, `PATCH` and `DELETE` need the `kb_admin` permission (see `auth`).
+//!
+//! Routes:
+//! - `GET /api/v1/agents` – every agent with its health (pending inbox messages, last activity);
+//!   `?include_archived=true` lists archived agents too
+//! - `POST /api/v1/agents` – create `{ agent_id, display_name?, persona?, enabled_skills? }`;
+//!   `persona` is a persona profile (see `/api/v1/agents/:id/persona`)
+//! - `GET /api/v1/agents/:id` – one agent with its health and persona
+//! - `PATCH /api/v1/agents/:id` – update its schedule
+//!   `{ paused?, tick_interval_secs?, active_hours?: { start: "09:00", end: "18:00", utc_offset_minutes? } }`;
+//!   `null` clears the interval or window
+//! - `DELETE /api/v1/agents/:id` – archive it
+//! - `GET /api/v1/agents/:id/subscriptions` – the topics the agent is subscribed to
+//! - `PUT /api/v1/agents/:id/subscriptions/:topic` – subscribe it; messages published to the
+//!   topic from then on (e.g. via `message_agent` with `topic`) reach its inbox
+//! - `DELETE /api/v1/agents/:id/subscriptions/:topic` – unsubscribe it
+//! - `GET /api/v1/agents/:id/dlq` – inbox messages the Heartbeat gave up on after repeated failed
+//!   auto-replies (with `attempts` and `last_error`), newest first; `?limit=` (default 50)
+//! - `POST /api/v1/agents/:id/dlq/:message_id/requeue` – put one back in the inbox for a fresh
+//!   round of attempts
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use pagi_core::{is_valid_agent_id, is_valid_topic_name, AgentRecord, AgentScheduleUpdate, KnowledgeStore, PersonaProfile};
+use serde::Deserialize;
+use std::sync::Arc;
+
+use crate::AppState;
+
+fn error(status: StatusCode, message: impl Into<String>) -> (StatusCode, Json<serde_json::Value>) {
+    (status, Json(serde_json::json!({ "status": "error", "error": message.into() })))
+}
+
+/// The agent's record with its health (`health` is null when it could not be read).
+fn agent_json(knowledge: &KnowledgeStore, agent: &AgentRecord) -> serde_json::Value {
+    let mut json = serde_json::json!(agent);
+    json["health"] = match knowledge.agent_health(&agent.agent_id) {
+        Ok(health) => serde_json::json!(health),
+        Err(e) => {
+            tracing::warn!(target: "pagi::agents", agent_id = %agent.agent_id, error = %e, "Agent health unavailable");
+            serde_json::Value::Null
+        }
+    };
+    json
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ListAgentsQuery {
+    #[serde(default)]
+    pub include_archived: bool,
+}
+
+/// GET /api/v1/agents
+pub async fn list_agents(
+    State(state): State<AppState>,
+    Query(query): Query<ListAgentsQuery>,
+) -> (StatusCode, Json<serde_json::Value>) {
+    let knowledge = Arc::clone(&state.knowledge);
+    let listed = tokio::task::spawn_blocking(move || {
+        knowledge.list_agents().map(|agents| {
+            agents
+                .iter()
+                .filter(|a| query.include_archived || a.is_active())
+                .map(|a| agent_json(&knowledge, a))
+                .collect::<Vec<_>>()
+        })
+    })
+    .await;
+    match listed {
+        Ok(Ok(agents)) => (
+            StatusCode::OK,
+            Json(serde_json::json!({ "status": "ok", "count": agents.len(), "agents": agents })),
+        ),
+        Ok(Err(e)) => error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+        Err(e) => error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateAgentRequest {
+    pub agent_id: String,
+    #[serde(default)]
+    pub display_name: Option<String>,
+    #[serde(default)]
+    pub persona: Option<PersonaProfile>,
+    #[serde(default)]
+    pub enabled_skills: Vec<String>,
+}
+
+/// POST /api/v1/agents
+pub async fn create_agent(
+    State(state): State<AppState>,
+    Json(req): Json<CreateAgentRequest>,
+) -> (StatusCode, Json<serde_json::Value>) {
+    if !is_valid_agent_id(&req.agent_id) {
+        return error(StatusCode::BAD_REQUEST, format!("invalid agent id {:?}", req.agent_id));
+    }
+    let known = state.orchestrator.skill_names();
+    let unknown: Vec<&str> = req
+        .enabled_skills
+        .iter()
+        .filter(|s| !known.contains(s))
+        .map(String::as_str)
+        .collect();
+    if !unknown.is_empty() {
+        return error(StatusCode::BAD_REQUEST, format!("unknown skills: {}", unknown.join(", ")));
+    }
+    if let Some(Err(e)) = req.persona.as_ref().map(PersonaProfile::validate) {
+        return error(StatusCode::BAD_REQUEST, e.to_string());
+    }
+
+    let knowledge = Arc::clone(&state.knowledge);
+    let agent = AgentRecord::new(&req.agent_id, req.display_name.as_deref(), req.enabled_skills);
+    let created = tokio::task::spawn_blocking(move || {
+        let Some(agent) = knowledge.create_agent(&agent).map_err(|e| e.to_string())? else {
+            return Ok(None);
+        };
+        let persona = match &req.persona {
+            Some(profile) => Some(knowledge.put_persona_profile(&agent.agent_id, profile).map_err(|e| e.to_string())?),
+            None => None,
+        };
+        Ok::<_, String>(Some((agent_json(&knowledge, &agent), persona)))
+    })
+    .await;
+    match created {
+        Ok(Ok(Some((mut agent, persona)))) => {
+            tracing::info!(target: "pagi::agents", agent_id = %req.agent_id, "Agent created");
+            agent["persona"] = serde_json::json!(persona);
+            (StatusCode::CREATED, Json(serde_json::json!({ "status": "ok", "agent": agent })))
+        }
+        Ok(Ok(None)) => error(StatusCode::CONFLICT, format!("agent {:?} already exists", req.agent_id)),
+        Ok(Err(e)) => error(StatusCode::INTERNAL_SERVER_ERROR, e),
+        Err(e) => error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    }
+}
+
+/// GET /api/v1/agents/:id
+pub async fn get_agent(
+    State(state): State<AppState>,
+    Path(agent_id): Path<String>,
+) -> (StatusCode, Json<serde_json::Value>) {
+    let knowledge = Arc::clone(&state.knowledge);
+    let id = agent_id.clone();
+    let loaded = tokio::task::spawn_blocking(move || {
+        knowledge.get_agent(&id).map(|agent| {
+            let mut json = agent_json(&knowledge, &agent);
+            json["persona"] = serde_json::json!(knowledge.get_persona_profile(&id));
+            json
+        })
+    })
+    .await;
+    match loaded {
+        Ok(Some(agent)) => (StatusCode::OK, Json(serde_json::json!({ "status": "ok", "agent": agent }))),
+        Ok(None) => error(StatusCode::NOT_FOUND, format!("agent {:?} not found", agent_id)),
+        Err(e) => error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    }
+}
+
+/// PATCH /api/v1/agents/:id
+pub async fn update_schedule(
+    State(state): State<AppState>,
+    Path(agent_id): Path<String>,
+    Json(update): Json<AgentScheduleUpdate>,
+) -> (StatusCode, Json<serde_json::Value>) {
+    if let Err(e) = update.validate() {
+        return error(StatusCode::BAD_REQUEST, e.to_string());
+    }
+    let knowledge = Arc::clone(&state.knowledge);
+    let id = agent_id.clone();
+    match tokio::task::spawn_blocking(move || knowledge.update_agent_schedule(&id, &update)).await {
+        Ok(Ok(Some(agent))) => {
+            tracing::info!(target: "pagi::agents", agent_id = %agent_id, paused = agent.paused, "Agent schedule updated");
+            (StatusCode::OK, Json(serde_json::json!({ "status": "ok", "agent": agent })))
+        }
+        Ok(Ok(None)) => error(StatusCode::NOT_FOUND, format!("agent {:?} not found", agent_id)),
+        Ok(Err(e)) => error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+        Err(e) => error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    }
+}
+
+/// DELETE /api/v1/agents/:id
+pub async fn archive_agent(
+    State(state): State<AppState>,
+    Path(agent_id): Path<String>,
+) -> (StatusCode, Json<serde_json::Value>) {
+    let knowledge = Arc::clone(&state.knowledge);
+    let id = agent_id.clone();
+    match tokio::task::spawn_blocking(move || knowledge.archive_agent(&id)).await {
+        Ok(Ok(Some(agent))) => {
+            tracing::info!(target: "pagi::agents", agent_id = %agent_id, "Agent archived");
+            (StatusCode::OK, Json(serde_json::json!({ "status": "ok", "agent": agent })))
+        }
+        Ok(Ok(None)) => error(StatusCode::NOT_FOUND, format!("agent {:?} not found", agent_id)),
+        Ok(Err(e)) => error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+        Err(e) => error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DeadLetterQuery {
+    #[serde(default = "default_dead_letter_limit")]
+    pub limit: usize,
+}
+
+fn default_dead_letter_limit() -> usize {
+    50
+}
+
+/// GET /api/v1/agents/:id/dlq
+pub async fn list_dead_letters(
+    State(state): State<AppState>,
+    Path(agent_id): Path<String>,
+    Query(query): Query<DeadLetterQuery>,
+) -> (StatusCode, Json<serde_json::Value>) {
+    let knowledge = Arc::clone(&state.knowledge);
+    let limit = query.limit.clamp(1, 500);
+    match tokio::task::spawn_blocking(move || knowledge.get_dead_letters(&agent_id, limit)).await {
+        Ok(Ok(messages)) => (
+            StatusCode::OK,
+            Json(serde_json::json!({ "status": "ok", "count": messages.len(), "messages": messages })),
+        ),
+        Ok(Err(e)) => error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+        Err(e) => error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    }
+}
+
+/// POST /api/v1/agents/:id/dlq/:message_id/requeue
+pub async fn requeue_dead_letter(
+    State(state): State<AppState>,
+    Path((agent_id, message_id)): Path<(String, String)>,
+) -> (StatusCode, Json<serde_json::Value>) {
+    let knowledge = Arc::clone(&state.knowledge);
+    let (id, msg_id) = (agent_id.clone(), message_id.clone());
+    match tokio::task::spawn_blocking(move || knowledge.requeue_dead_letter(&id, &msg_id)).await {
+        Ok(Ok(Some(message))) => {
+            tracing::info!(target: "pagi::agents", agent_id = %agent_id, message_id = %message_id, "Dead letter requeued");
+            (StatusCode::OK, Json(serde_json::json!({ "status": "ok", "message": message })))
+        }
+        Ok(Ok(None)) => error(
+            StatusCode::NOT_FOUND,
+            format!("agent {:?} has no dead letter {:?}", agent_id, message_id),
+        ),
+        Ok(Err(e)) => error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+        Err(e) => error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    }
+}
+
+/// GET /api/v1/agents/:id/subscriptions
+pub async fn list_subscriptions(
+    State(state): State<AppState>,
+    Path(agent_id): Path<String>,
+) -> (StatusCode, Json<serde_json::Value>) {
+    let knowledge = Arc::clone(&state.knowledge);
+    match tokio::task::spawn_blocking(move || knowledge.agent_subscriptions(&agent_id)).await {
+        Ok(Ok(subscriptions)) => (
+            StatusCode::OK,
+            Json(serde_json::json!({ "status": "ok", "count": subscriptions.len(), "subscriptions": subscriptions })),
+        ),
+        Ok(Err(e)) => error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+        Err(e) => error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    }
+}
+
+/// PUT /api/v1/agents/:id/subscriptions/:topic
+pub async fn subscribe(
+    State(state): State<AppState>,
+    Path((agent_id, topic)): Path<(String, String)>,
+) -> (StatusCode, Json<serde_json::Value>) {
+    if !is_valid_agent_id(&agent_id) {
+        return error(StatusCode::BAD_REQUEST, format!("invalid agent id {:?}", agent_id));
+    }
+    if !is_valid_topic_name(&topic) {
+        return error(StatusCode::BAD_REQUEST, format!("invalid topic name {:?}", topic));
+    }
+    let knowledge = Arc::clone(&state.knowledge);
+    match tokio::task::spawn_blocking(move || knowledge.subscribe_agent(&agent_id, &topic)).await {
+        Ok(Ok(subscription)) => {
+            tracing::info!(target: "pagi::agents", agent_id = %subscription.agent_id, topic = %subscription.topic, "Agent subscribed to topic");
+            (StatusCode::OK, Json(serde_json::json!({ "status": "ok", "subscription": subscription })))
+        }
+        Ok(Err(e)) => error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+        Err(e) => error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    }
+}
+
+/// DELETE /api/v1/agents/:id/subscriptions/:topic
+pub async fn unsubscribe(
+    State(state): State<AppState>,
+    Path((agent_id, topic)): Path<(String, String)>,
+) -> (StatusCode, Json<serde_json::Value>) {
+    let knowledge = Arc::clone(&state.knowledge);
+    let (id, name) = (agent_id.clone(), topic.clone());
+    match tokio::task::spawn_blocking(move || knowledge.unsubscribe_agent(&id, &name)).await {
+        Ok(Ok(true)) => (StatusCode::OK, Json(serde_json::json!({ "status": "ok", "agent_id": agent_id, "topic": topic }))),
+        Ok(Ok(false)) => error(
+            StatusCode::NOT_FOUND,
+            format!("agent {:?} is not subscribed to {:?}", agent_id, topic),
+        ),
+        Ok(Err(e)) => error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+        Err(e) => error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    }
+}
@@ -0,0 +1,187 @@
+//! Human-in-the-loop approvals: skill calls an Ethos rule marked `require_approval`.
+//!
+//! Such a call suspends its dispatch: `/v1/execute` answers `approval_required` with an
+//! `approval_id`, and the call is kept as a pending approval in KB_ETHOS together with the goal
+//! that reached it. Approving it resumes the dispatch as a background job (see `jobs`) under the
+//! original correlation id, where the approved call passes the Ethos gate once; denying it closes
+//! it, and the call stays blocked. Every decision is logged to the agent's Chronos history, with
+//! the deciding key's name as `decided_by` unless the body names someone. Decisions need the
+//! `kb_admin` permission (see `auth`).
+//!
+//! Routes:
+//! - `GET /api/v1/approvals` – newest first; `?status=pending|approved|denied|executed`, `?limit=`
+//! - `GET /api/v1/approvals/:id` – one approval, with its payload and goal
+//! - `POST /api/v1/approvals/:id/approve` – `{ decided_by?, note? }`; answers the resumed `job_id`
+//! - `POST /api/v1/approvals/:id/deny` – `{ decided_by?, note? }`
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Extension, Json,
+};
+use pagi_core::{ApprovalRecord, ApprovalStatus, TenantContext};
+use serde::Deserialize;
+use std::sync::Arc;
+
+use super::auth::Caller;
+use crate::AppState;
+
+const APPROVALS_DEFAULT_LIMIT: usize = 100;
+const APPROVALS_MAX_LIMIT: usize = 1000;
+
+fn error(status: StatusCode, message: impl Into<String>) -> (StatusCode, Json<serde_json::Value>) {
+    (status, Json(serde_json::json!({ "status": "error", "error": message.into() })))
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ApprovalsQuery {
+    #[serde(default)]
+    pub status: Option<ApprovalStatus>,
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+/// GET /api/v1/approvals
+pub async fn list_approvals(
+    State(state): State<AppState>,
+    Query(query): Query<ApprovalsQuery>,
+) -> (StatusCode, Json<serde_json::Value>) {
+    let knowledge = Arc::clone(&state.knowledge);
+    let limit = query.limit.unwrap_or(APPROVALS_DEFAULT_LIMIT).clamp(1, APPROVALS_MAX_LIMIT);
+    match tokio::task::spawn_blocking(move || knowledge.list_approvals(query.status, limit)).await {
+        Ok(Ok(approvals)) => (
+            StatusCode::OK,
+            Json(serde_json::json!({ "status": "ok", "count": approvals.len(), "approvals": approvals })),
+        ),
+        Ok(Err(e)) => error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+        Err(e) => error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    }
+}
+
+/// GET /api/v1/approvals/:id
+pub async fn get_approval(
+    State(state): State<AppState>,
+    Path(approval_id): Path<String>,
+) -> (StatusCode, Json<serde_json::Value>) {
+    let knowledge = Arc::clone(&state.knowledge);
+    let id = approval_id.clone();
+    match tokio::task::spawn_blocking(move || knowledge.get_approval(&id)).await {
+        Ok(Some(approval)) => (StatusCode::OK, Json(serde_json::json!({ "status": "ok", "approval": approval }))),
+        Ok(None) => error(StatusCode::NOT_FOUND, format!("approval {:?} not found", approval_id)),
+        Err(e) => error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct DecisionRequest {
+    /// Who decided (default: the calling key's name, else `operator`).
+    #[serde(default)]
+    pub decided_by: Option<String>,
+    #[serde(default)]
+    pub note: Option<String>,
+}
+
+/// POST /api/v1/approvals/:id/approve
+pub async fn approve(
+    State(state): State<AppState>,
+    caller: Option<Extension<Caller>>,
+    Path(approval_id): Path<String>,
+    body: Option<Json<DecisionRequest>>,
+) -> (StatusCode, Json<serde_json::Value>) {
+    let approval = match decide(&state, caller, &approval_id, true, body).await {
+        Ok(approval) => approval,
+        Err(response) => return response,
+    };
+    let Some(goal) = approval.goal.clone() else {
+        return (StatusCode::OK, Json(serde_json::json!({ "status": "ok", "approval": approval, "job_id": null })));
+    };
+    let ctx = TenantContext {
+        tenant_id: approval.tenant_id.clone(),
+        correlation_id: Some(approval.correlation_id.clone()),
+        agent_id: Some(approval.agent_id.clone()),
+        ..Default::default()
+    };
+    let job = match super::jobs::start_job(&state, ctx, goal) {
+        Ok(job) => job,
+        Err(e) => return error(StatusCode::INTERNAL_SERVER_ERROR, format!("approved, but resuming failed: {}", e)),
+    };
+    let approval = state
+        .knowledge
+        .update_approval(&approval_id, |a| a.job_id = Some(job.job_id.clone()))
+        .ok()
+        .flatten()
+        .unwrap_or(approval);
+    tracing::info!(target: "pagi::ethos", approval_id = %approval_id, job_id = %job.job_id, "Approved call resumed");
+    (
+        StatusCode::OK,
+        Json(serde_json::json!({ "status": "ok", "approval": approval, "job_id": job.job_id })),
+    )
+}
+
+/// POST /api/v1/approvals/:id/deny
+pub async fn deny(
+    State(state): State<AppState>,
+    caller: Option<Extension<Caller>>,
+    Path(approval_id): Path<String>,
+    body: Option<Json<DecisionRequest>>,
+) -> (StatusCode, Json<serde_json::Value>) {
+    match decide(&state, caller, &approval_id, false, body).await {
+        Ok(approval) => (StatusCode::OK, Json(serde_json::json!({ "status": "ok", "approval": approval }))),
+        Err(response) => response,
+    }
+}
+
+/// Records the decision on a pending approval and logs it to Chronos: 404 when unknown, 409 when
+/// already decided.
+async fn decide(
+    state: &AppState,
+    caller: Option<Extension<Caller>>,
+    approval_id: &str,
+    approve: bool,
+    body: Option<Json<DecisionRequest>>,
+) -> Result<ApprovalRecord, (StatusCode, Json<serde_json::Value>)> {
+    let Json(req) = body.unwrap_or_default();
+    let decided_by = req
+        .decided_by
+        .map(|d| d.trim().to_string())
+        .filter(|d| !d.is_empty())
+        .or_else(|| caller.map(|Extension(c)| c.name))
+        .unwrap_or_else(|| "operator".to_string());
+    let knowledge = Arc::clone(&state.knowledge);
+    let id = approval_id.to_string();
+    let decided = tokio::task::spawn_blocking(move || match knowledge.get_approval(&id) {
+        None => Err((StatusCode::NOT_FOUND, format!("approval {:?} not found", id))),
+        Some(current) if current.status != ApprovalStatus::Pending => Err((
+            StatusCode::CONFLICT,
+            format!("approval {} is already {}", id, current.status.as_str()),
+        )),
+        // A decision racing this one fails the store's own pending check.
+        Some(_) => match knowledge.decide_approval(&id, approve, &decided_by, req.note.as_deref()) {
+            Ok(Some(approval)) => Ok(approval),
+            Ok(None) => Err((StatusCode::NOT_FOUND, format!("approval {:?} not found", id))),
+            Err(e) => Err((StatusCode::CONFLICT, e.to_string())),
+        },
+    })
+    .await
+    .map_err(|e| error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let approval = decided.map_err(|(status, message)| error(status, message))?;
+    let verdict = approval.status.as_str();
+    let mut summary = format!(
+        "Approval {} {} by {}: {}",
+        approval.approval_id,
+        verdict,
+        approval.decided_by.as_deref().unwrap_or("operator"),
+        approval.skill
+    );
+    if let Some(note) = &approval.note {
+        summary.push_str(&format!(" ({})", note));
+    }
+    let event = state.knowledge.event("Ethos", summary)
+        .with_skill(approval.skill.clone())
+        .with_outcome(verdict);
+    if state.knowledge.append_chronos_event(&approval.agent_id, &event).is_err() {
+        tracing::warn!(target: "pagi::chronos", "Failed to append Chronos event");
+    }
+    tracing::info!(target: "pagi::ethos", approval_id = %approval.approval_id, verdict, "Approval decided");
+    Ok(approval)
+}
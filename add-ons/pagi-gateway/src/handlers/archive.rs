@@ -0,0 +1,158 @@
+//! Archive tier: move cold Chronos/Logos records to compressed segment files.
+//!
+//! Set `PAGI_ARCHIVE_AFTER_DAYS` to archive records older than that many days once an hour
+//! (slots from `PAGI_ARCHIVE_SLOTS`, default `3,4`). Archived records remain readable through
+//! recall/search and can be restored.
+//!
+//! Routes:
+//! - `GET /api/v1/archive/segments` – list segments (`?slot_id=4`)
+//! - `POST /api/v1/archive/run` – archive now `{ slot_id, older_than_days }`
+//! - `POST /api/v1/archive/restore` – restore `{ slot_id, key }` or `{ segment_id }`
+
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    Json,
+};
+use pagi_core::{KbType, KnowledgeStore};
+use serde::Deserialize;
+use std::sync::Arc;
+
+use crate::AppState;
+
+const DAY_MS: i64 = 24 * 60 * 60 * 1000;
+
+/// Background archival settings from the environment.
+#[derive(Debug, Clone)]
+pub struct ArchiveConfig {
+    pub older_than_days: u64,
+    pub slots: Vec<u8>,
+}
+
+impl ArchiveConfig {
+    /// None unless `PAGI_ARCHIVE_AFTER_DAYS` is set to a positive number.
+    pub fn from_env() -> Option<Self> {
+        let older_than_days = std::env::var("PAGI_ARCHIVE_AFTER_DAYS")
+            .ok()
+            .and_then(|s| s.trim().parse::<u64>().ok())
+            .filter(|d| *d > 0)?;
+        let slots = std::env::var("PAGI_ARCHIVE_SLOTS")
+            .ok()
+            .map(|s| {
+                s.split(',')
+                    .filter_map(|p| p.trim().parse::<u8>().ok())
+                    .filter(|n| (1..=8).contains(n))
+                    .collect::<Vec<_>>()
+            })
+            .filter(|v| !v.is_empty())
+            .unwrap_or_else(|| vec![KbType::Logos.slot_id(), KbType::Chronos.slot_id()]);
+        Some(Self { older_than_days, slots })
+    }
+}
+
+fn cutoff_ms(now_ms: i64, older_than_days: u64) -> i64 {
+    now_ms.saturating_sub((older_than_days as i64).saturating_mul(DAY_MS))
+}
+
+/// Hourly archival loop (spawned when [`ArchiveConfig::from_env`] is Some).
+pub async fn archive_loop(knowledge: Arc<KnowledgeStore>, config: ArchiveConfig) {
+    tracing::info!(target: "pagi::daemon", ?config, "Archive loop started");
+    let mut interval = tokio::time::interval(std::time::Duration::from_secs(60 * 60));
+    loop {
+        interval.tick().await;
+        let knowledge = Arc::clone(&knowledge);
+        let config = config.clone();
+        let result = tokio::task::spawn_blocking(move || {
+            let cutoff = cutoff_ms(knowledge.now_ms(), config.older_than_days);
+            for slot_id in config.slots {
+                if let Err(e) = knowledge.archive_older_than(slot_id, cutoff) {
+                    tracing::warn!(target: "pagi::daemon", slot_id, error = %e, "Archival failed");
+                }
+            }
+        })
+        .await;
+        if let Err(e) = result {
+            tracing::warn!(target: "pagi::daemon", error = %e, "Archive task panicked");
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SegmentsQuery {
+    pub slot_id: Option<u8>,
+}
+
+/// GET /api/v1/archive/segments
+pub async fn list_segments(
+    State(state): State<AppState>,
+    Query(q): Query<SegmentsQuery>,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+    let segments = state
+        .knowledge
+        .list_archive_segments(q.slot_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(Json(serde_json::json!({
+        "status": "ok",
+        "count": segments.len(),
+        "segments": segments,
+    })))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RunArchiveRequest {
+    pub slot_id: u8,
+    pub older_than_days: u64,
+}
+
+/// POST /api/v1/archive/run
+pub async fn run_archive(
+    State(state): State<AppState>,
+    Json(req): Json<RunArchiveRequest>,
+) -> (StatusCode, Json<serde_json::Value>) {
+    let knowledge = Arc::clone(&state.knowledge);
+    let cutoff = cutoff_ms(knowledge.now_ms(), req.older_than_days);
+    let result = tokio::task::spawn_blocking(move || knowledge.archive_older_than(req.slot_id, cutoff)).await;
+    match result {
+        Ok(Ok(report)) => (StatusCode::OK, Json(serde_json::json!({ "status": "ok", "report": report }))),
+        Ok(Err(e)) => (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({ "status": "error", "error": e.to_string() })),
+        ),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({ "status": "error", "error": e.to_string() })),
+        ),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum RestoreRequest {
+    Key { slot_id: u8, key: String },
+    Segment { segment_id: String },
+}
+
+/// POST /api/v1/archive/restore
+pub async fn restore(
+    State(state): State<AppState>,
+    Json(req): Json<RestoreRequest>,
+) -> (StatusCode, Json<serde_json::Value>) {
+    let result = match &req {
+        RestoreRequest::Key { slot_id, key } => state
+            .knowledge
+            .restore_archived(*slot_id, key)
+            .map(usize::from),
+        RestoreRequest::Segment { segment_id } => state.knowledge.restore_archive_segment(segment_id),
+    };
+    match result {
+        Ok(0) => (
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({ "status": "error", "error": "nothing archived under that key or segment" })),
+        ),
+        Ok(restored) => (StatusCode::OK, Json(serde_json::json!({ "status": "ok", "restored": restored }))),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({ "status": "error", "error": e.to_string() })),
+        ),
+    }
+}
@@ -0,0 +1,112 @@
+//! Dispatch audit log: every orchestrator dispatch is recorded by pagi-core
+//! (`KnowledgeStore::append_audit_record`); this module serves it for compliance reviews.
+//!
+//! Records older than `PAGI_AUDIT_RETENTION_DAYS` (default 90; `0` keeps everything) are pruned
+//! by the Heartbeat.
+//!
+//! Routes:
+//! - `GET /api/v1/audit` – records newest first, filtered by `tenant`, `agent`, `skill`,
+//!   `goal_type`, `outcome`, `from` / `to` (Unix ms) and paged with `limit` / `cursor`
+
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    Json,
+};
+use pagi_core::{AuditOutcome, AuditQuery, KnowledgeStore};
+use serde::Deserialize;
+
+use crate::AppState;
+
+const DAY_MS: i64 = 24 * 60 * 60 * 1000;
+
+#[derive(Debug, Default, Deserialize)]
+pub struct AuditParams {
+    #[serde(default)]
+    pub tenant: Option<String>,
+    #[serde(default)]
+    pub agent: Option<String>,
+    #[serde(default)]
+    pub skill: Option<String>,
+    #[serde(default)]
+    pub goal_type: Option<String>,
+    #[serde(default)]
+    pub outcome: Option<String>,
+    /// Unix ms, inclusive.
+    #[serde(default)]
+    pub from: Option<i64>,
+    /// Unix ms, exclusive.
+    #[serde(default)]
+    pub to: Option<i64>,
+    #[serde(default)]
+    pub cursor: Option<String>,
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+/// GET /api/v1/audit
+pub async fn list_audit(
+    State(state): State<AppState>,
+    Query(params): Query<AuditParams>,
+) -> (StatusCode, Json<serde_json::Value>) {
+    let outcome = match params.outcome.as_deref().filter(|o| !o.trim().is_empty()) {
+        None => None,
+        Some(o) => match AuditOutcome::parse(o) {
+            Some(outcome) => Some(outcome),
+            None => {
+                return (
+                    StatusCode::BAD_REQUEST,
+                    Json(serde_json::json!({
+                        "status": "error",
+                        "error": "outcome must be ok, error, cancelled, timeout or throttled",
+                    })),
+                )
+            }
+        },
+    };
+    let non_empty = |s: Option<String>| s.map(|s| s.trim().to_string()).filter(|s| !s.is_empty());
+    let query = AuditQuery {
+        tenant_id: non_empty(params.tenant),
+        agent_id: non_empty(params.agent),
+        skill: non_empty(params.skill),
+        goal_type: non_empty(params.goal_type),
+        outcome,
+        from_ms: params.from,
+        to_ms: params.to,
+        cursor: non_empty(params.cursor),
+        limit: params.limit,
+    };
+    match state.knowledge.query_audit(&query) {
+        Ok(page) => (
+            StatusCode::OK,
+            Json(serde_json::json!({
+                "status": "ok",
+                "count": page.records.len(),
+                "records": page.records,
+                "next_cursor": page.next_cursor,
+            })),
+        ),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({ "status": "error", "error": e.to_string() })),
+        ),
+    }
+}
+
+/// Days audit records are kept (`PAGI_AUDIT_RETENTION_DAYS`, default 90); None keeps them all.
+pub fn retention_days() -> Option<u64> {
+    let days = std::env::var("PAGI_AUDIT_RETENTION_DAYS")
+        .ok()
+        .and_then(|s| s.trim().parse::<u64>().ok())
+        .unwrap_or(90);
+    (days > 0).then_some(days)
+}
+
+/// Removes records past the retention period. Returns how many were removed.
+pub fn prune_expired(knowledge: &KnowledgeStore) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
+    let Some(days) = retention_days() else {
+        return Ok(0);
+    };
+    let now_ms = knowledge.now_ms();
+    Ok(knowledge.prune_audit_records(now_ms.saturating_sub((days as i64).saturating_mul(DAY_MS)))?)
+}
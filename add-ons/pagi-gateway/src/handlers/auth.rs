@@ -0,0 +1,391 @@
+//! Role-based access to gateway routes, and management of the API keys that carry the roles.
+//!
+//! Enforcement is on once an API key exists in KB_ETHOS, `PAGI_API_KEY` is set (it acts as an
+//! `admin` key, for bootstrapping and existing deployments) or `[oidc]` names an issuer. Every
+//! route but `/api/v1/health`, `/api/v1/ready` and the signed `/api/v1/ingest/:source`,
+//! `/api/v1/chat/slack/commands` and `/api/v1/channels/telegram/webhook` then needs `X-API-Key: <key>` or `Authorization: Bearer <key>` (401
+//! without a valid key), and the caller's role must grant what the route needs (403 otherwise).
+//! With `[oidc]`, the bearer may also be a JWT from the SSO provider (see `crate::oidc`); its
+//! claims give the role and the tenant.
+//!
+//!
+//! - `vault_read` – `/v1/vault/read`, `/api/v1/sovereign-status`
+//! - `key_admin` – `/api/v1/auth/keys…`
+//! - `config_admin` – `/api/v1/admin/reload-config`
+//! - `execute` – `/v1/execute`, cancel, trace replay, eval runs, `/api/v1/chat`, `/api/v1/chat/stream`,
+//!   `/api/v1/ws`, `/api/v1/leads/:id/status`
+//! - `read` – other `GET`s, `/api/v1/auth/whoami` and the Ethos dry run (`POST /api/v1/ethos/evaluate`)
+//! - `kb_admin` – every other write
+//!
+//! A caller bound to a tenant (a `tenant` key, or a token with a tenant claim) is limited to it:
+//! routes with a `:tenant_id` must name it, `/v1/execute` and chat run as it (the body's tenant
+//! may be left out, and may not name another), document uploads and lead transitions stay
+//! within it, jobs must belong to it, and routes not about a tenant are refused.
+//!
+//! Routes:
+//! - `GET /api/v1/auth/whoami` – the caller (`auth_enabled: false` while enforcement is off)
+//! - `GET /api/v1/auth/keys` – every key, newest first (secrets are never returned)
+//! - `POST /api/v1/auth/keys` – `{ name, role, tenant_id? }`; answers the `secret`, once
+//! - `GET /api/v1/auth/keys/:key_id`
+//! - `DELETE /api/v1/auth/keys/:key_id` – revoke (the key stops authenticating at once)
+
+use axum::{
+    extract::{MatchedPath, Path, Request, State},
+    http::{HeaderMap, Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Extension, Json,
+};
+use pagi_core::{ApiKeyRecord, OidcPrincipal, Permission, Role};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+use crate::oidc::OidcVerifier;
+use crate::AppState;
+
+/// Routes a tenant-bound caller may use without a `:tenant_id`: whoami, and routes whose handlers
+/// check the tenant.
+const TENANT_CHECKED_ROUTES: [&str; 10] = [
+    "/api/v1/auth/whoami",
+    "/v1/status",
+    "/v1/skills",
+    "/v1/execute",
+    "/v1/jobs/:job_id",
+    "/api/v1/chat",
+    "/api/v1/chat/stream",
+    "/api/v1/kb/:slot/documents",
+    "/api/v1/kb/:slot/documents/:document_id",
+    "/api/v1/leads/:id/status",
+];
+
+fn error(status: StatusCode, message: impl Into<String>) -> (StatusCode, Json<serde_json::Value>) {
+    (status, Json(serde_json::json!({ "status": "error", "error": message.into() })))
+}
+
+/// Who made a request (absent while enforcement is off).
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct Caller {
+    /// `api_key`, `env` (`PAGI_API_KEY`) or `oidc`.
+    pub(crate) via: &'static str,
+    /// Key id, or the token's `sub`.
+    pub(crate) id: String,
+    pub(crate) name: String,
+    pub(crate) role: Role,
+    /// Tenant the caller is bound to.
+    pub(crate) tenant_id: Option<String>,
+}
+
+impl Caller {
+    /// `PAGI_API_KEY`, as an admin key.
+    fn env_admin() -> Self {
+        Self {
+            via: "env",
+            id: "env".to_string(),
+            name: "PAGI_API_KEY".to_string(),
+            role: Role::Admin,
+            tenant_id: None,
+        }
+    }
+
+    fn api_key(key: ApiKeyRecord) -> Self {
+        Self {
+            via: "api_key",
+            id: key.key_id,
+            name: key.name,
+            role: key.role,
+            tenant_id: key.tenant_id,
+        }
+    }
+
+    fn oidc(principal: OidcPrincipal) -> Self {
+        Self {
+            via: "oidc",
+            id: principal.subject,
+            name: principal.name,
+            role: principal.role,
+            tenant_id: principal.tenant_id,
+        }
+    }
+
+    fn refused_for_tenant(&self) -> (StatusCode, Json<serde_json::Value>) {
+        error(
+            StatusCode::FORBIDDEN,
+            format!("{} may only act for tenant {:?}", self.name, self.tenant_id.as_deref().unwrap_or("")),
+        )
+    }
+}
+
+/// The tenant a request acts for: a caller bound to a tenant acts for its own (when `requested`
+/// is None or empty) and may not name another; other callers act for `requested`.
+pub(crate) fn acting_tenant(
+    caller: Option<&Extension<Caller>>,
+    requested: Option<&str>,
+) -> Result<Option<String>, (StatusCode, Json<serde_json::Value>)> {
+    let requested = requested.filter(|t| !t.is_empty());
+    match caller.and_then(|Extension(c)| c.tenant_id.as_deref().map(|own| (c, own))) {
+        Some((_, own)) if requested.is_none_or(|t| t == own) => Ok(Some(own.to_string())),
+        Some((c, _)) => Err(c.refused_for_tenant()),
+        None => Ok(requested.map(str::to_string)),
+    }
+}
+
+/// Refuses a caller bound to another tenant; every other caller passes.
+pub(crate) fn check_tenant(
+    caller: Option<&Extension<Caller>>,
+    tenant_id: &str,
+) -> Result<(), (StatusCode, Json<serde_json::Value>)> {
+    match caller {
+        Some(Extension(c)) if c.tenant_id.as_deref().is_some_and(|own| own != tenant_id) => Err(c.refused_for_tenant()),
+        _ => Ok(()),
+    }
+}
+
+/// What `method` on the route `path` (as matched, e.g. `/v1/jobs/:job_id`) needs; None for
+/// public routes.
+pub(crate) fn required_permission(method: &Method, path: &str) -> Option<Permission> {
+    Some(match path {
+        // Probes, or signed by the provider instead (see `handlers::ingest`, `handlers::slack`, `channels::telegram`).
+        "/api/v1/health"
+        | "/api/v1/ready"
+        | "/api/v1/ingest/:source"
+        | "/api/v1/chat/slack/commands"
+        | "/api/v1/channels/telegram/webhook" => return None,
+        "/v1/vault/read" | "/api/v1/sovereign-status" => Permission::VaultRead,
+        "/api/v1/auth/whoami" => Permission::Read,
+        p if p.starts_with("/api/v1/auth/") => Permission::KeyAdmin,
+        "/v1/execute"
+        | "/v1/execute/:correlation_id/cancel"
+        | "/v1/research/trace/:trace_id/replay"
+        | "/api/v1/evals/run"
+        | "/api/v1/chat"
+        | "/api/v1/chat/stream"
+        | "/api/v1/ws"
+        | "/api/v1/leads/:id/status" => {
+            Permission::Execute
+        }
+        "/api/v1/ethos/evaluate" => Permission::Read,
+        // Every tenant's records (or recorded request and response bodies) at once.
+        "/api/v1/knowledge/export" | "/api/v1/recorder/exchanges" | "/api/v1/recorder/exchanges/:id" => {
+            Permission::KbAdmin
+        }
+        "/api/v1/admin/reload-config" => Permission::ConfigAdmin,
+        _ if method == Method::GET || method == Method::HEAD => Permission::Read,
+        _ => Permission::KbAdmin,
+    })
+}
+
+fn presented_key(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get("X-API-Key")
+        .and_then(|v| v.to_str().ok())
+        .or_else(|| {
+            headers
+                .get(axum::http::header::AUTHORIZATION)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|s| s.strip_prefix("Bearer "))
+        })
+        .map(str::trim)
+        .filter(|k| !k.is_empty())
+}
+
+fn env_api_key() -> Option<String> {
+    pagi_core::secret(pagi_core::SECRET_API_KEY)
+        .map(|k| k.trim().to_string())
+        .filter(|k| !k.is_empty())
+}
+
+/// Whether `presented` is the `PAGI_API_KEY` secret. Like stored keys, it is matched by SHA-256
+/// digest rather than by plain text, so comparison timing says nothing about the secret itself.
+fn is_env_key(env_key: &str, presented: &str) -> bool {
+    pagi_core::hash_api_key(env_key) == pagi_core::hash_api_key(presented)
+}
+
+/// The `:tenant_id` segment of `uri_path`, when the matched route has one.
+fn route_tenant<'a>(matched: &str, uri_path: &'a str) -> Option<&'a str> {
+    matched
+        .split('/')
+        .zip(uri_path.split('/'))
+        .find(|(pattern, _)| *pattern == ":tenant_id")
+        .map(|(_, value)| value)
+}
+
+/// Axum route layer: authenticates the key and checks its role against the route (see the
+/// module docs). The key is handed to handlers as an `Extension<Caller>`.
+pub async fn authorize(State(state): State<AppState>, mut req: Request, next: Next) -> Response {
+    let matched = req
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|p| p.as_str().to_string())
+        .unwrap_or_else(|| req.uri().path().to_string());
+    let Some(permission) = required_permission(req.method(), &matched) else {
+        return next.run(req).await;
+    };
+    let env_key = env_api_key();
+    if env_key.is_none() && state.oidc.is_none() && !state.knowledge.has_api_keys() {
+        return next.run(req).await;
+    }
+    let Some(presented) = presented_key(req.headers()) else {
+        return error(StatusCode::UNAUTHORIZED, "Missing API key (X-API-Key or Authorization: Bearer)").into_response();
+    };
+    let caller = match &state.oidc {
+        _ if env_key.as_deref().is_some_and(|key| is_env_key(key, presented)) => Caller::env_admin(),
+        Some(oidc) if OidcVerifier::is_token(presented) => {
+            let claims = match oidc.verify(presented).await {
+                Ok(claims) => claims,
+                Err(reason) => {
+                    tracing::info!(target: "pagi::auth", reason = %reason, route = %matched, "Token refused");
+                    return error(StatusCode::UNAUTHORIZED, format!("Invalid token: {}", reason)).into_response();
+                }
+            };
+            match oidc.config().principal(&claims) {
+                Ok(principal) => Caller::oidc(principal),
+                Err(reason) => return error(StatusCode::FORBIDDEN, reason).into_response(),
+            }
+        }
+        _ => match state.knowledge.authenticate_api_key(presented) {
+            Some(key) => Caller::api_key(key),
+            None => return error(StatusCode::UNAUTHORIZED, "Invalid or revoked API key").into_response(),
+        },
+    };
+    let tenant = route_tenant(&matched, req.uri().path());
+    let allowed = caller.role.allows(permission)
+        && match (caller.tenant_id.as_deref(), tenant) {
+            (None, _) => true,
+            (Some(own), Some(tenant)) => own == tenant,
+            (Some(_), None) => TENANT_CHECKED_ROUTES.contains(&matched.as_str()),
+        };
+    if !allowed {
+        tracing::info!(
+            target: "pagi::auth",
+            caller = %caller.id,
+            via = caller.via,
+            role = caller.role.as_str(),
+            permission = permission.as_str(),
+            route = %matched,
+            "Request refused"
+        );
+        if !caller.role.allows(permission) {
+            let message = format!("role {} lacks the {} permission", caller.role.as_str(), permission.as_str());
+            return error(StatusCode::FORBIDDEN, message).into_response();
+        }
+        if tenant.is_some() {
+            return caller.refused_for_tenant().into_response();
+        }
+        let message = format!("{} {} is not available to tenant-bound callers", req.method(), matched);
+        return error(StatusCode::FORBIDDEN, message).into_response();
+    }
+    req.extensions_mut().insert(caller);
+    next.run(req).await
+}
+
+/// GET /api/v1/auth/whoami
+pub async fn whoami(caller: Option<Extension<Caller>>) -> Json<serde_json::Value> {
+    match caller {
+        Some(Extension(caller)) => {
+            let permissions: Vec<&str> = [
+                Permission::Read,
+                Permission::Execute,
+                Permission::VaultRead,
+                Permission::KbAdmin,
+                Permission::KeyAdmin,
+                Permission::ConfigAdmin,
+            ]
+            .into_iter()
+            .filter(|p| caller.role.allows(*p))
+            .map(Permission::as_str)
+            .collect();
+            Json(serde_json::json!({ "status": "ok", "auth_enabled": true, "caller": caller, "permissions": permissions }))
+        }
+        None => Json(serde_json::json!({ "status": "ok", "auth_enabled": false })),
+    }
+}
+
+/// GET /api/v1/auth/keys
+pub async fn list_keys(State(state): State<AppState>) -> (StatusCode, Json<serde_json::Value>) {
+    let knowledge = Arc::clone(&state.knowledge);
+    match tokio::task::spawn_blocking(move || knowledge.list_api_keys()).await {
+        Ok(Ok(keys)) => (
+            StatusCode::OK,
+            Json(serde_json::json!({ "status": "ok", "count": keys.len(), "keys": keys })),
+        ),
+        Ok(Err(e)) => error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+        Err(e) => error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateKeyRequest {
+    pub name: String,
+    pub role: Role,
+    #[serde(default)]
+    pub tenant_id: Option<String>,
+}
+
+/// POST /api/v1/auth/keys
+pub async fn create_key(
+    State(state): State<AppState>,
+    caller: Option<Extension<Caller>>,
+    Json(req): Json<CreateKeyRequest>,
+) -> (StatusCode, Json<serde_json::Value>) {
+    if req.name.trim().is_empty() {
+        return error(StatusCode::BAD_REQUEST, "name must not be empty");
+    }
+    let tenant_id = req
+        .tenant_id
+        .as_deref()
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+        .map(str::to_string);
+    if let Err(message) = req.role.check_tenant_binding(tenant_id.as_deref()) {
+        return error(StatusCode::BAD_REQUEST, message);
+    }
+    let knowledge = Arc::clone(&state.knowledge);
+    let created_by = caller.map(|Extension(c)| c.name);
+    let created = tokio::task::spawn_blocking(move || {
+        knowledge.create_api_key(req.name.trim(), req.role, tenant_id.as_deref(), created_by.as_deref())
+    })
+    .await;
+    match created {
+        Ok(Ok((key, secret))) => {
+            tracing::info!(target: "pagi::auth", key_id = %key.key_id, role = key.role.as_str(), "API key issued");
+            (
+                StatusCode::CREATED,
+                Json(serde_json::json!({ "status": "ok", "key": key, "secret": secret })),
+            )
+        }
+        Ok(Err(e)) => error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+        Err(e) => error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    }
+}
+
+/// GET /api/v1/auth/keys/:key_id
+pub async fn get_key(
+    State(state): State<AppState>,
+    Path(key_id): Path<String>,
+) -> (StatusCode, Json<serde_json::Value>) {
+    let knowledge = Arc::clone(&state.knowledge);
+    let id = key_id.clone();
+    match tokio::task::spawn_blocking(move || knowledge.get_api_key(&id)).await {
+        Ok(Some(key)) => (StatusCode::OK, Json(serde_json::json!({ "status": "ok", "key": key }))),
+        Ok(None) => error(StatusCode::NOT_FOUND, format!("API key {:?} not found", key_id)),
+        Err(e) => error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    }
+}
+
+/// DELETE /api/v1/auth/keys/:key_id
+pub async fn revoke_key(
+    State(state): State<AppState>,
+    Path(key_id): Path<String>,
+) -> (StatusCode, Json<serde_json::Value>) {
+    let knowledge = Arc::clone(&state.knowledge);
+    let id = key_id.clone();
+    match tokio::task::spawn_blocking(move || knowledge.revoke_api_key(&id)).await {
+        Ok(Ok(Some(key))) => {
+            tracing::info!(target: "pagi::auth", key_id = %key.key_id, "API key revoked");
+            (StatusCode::OK, Json(serde_json::json!({ "status": "ok", "key": key })))
+        }
+        Ok(Ok(None)) => error(StatusCode::NOT_FOUND, format!("API key {:?} not found", key_id)),
+        Ok(Err(e)) => error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+        Err(e) => error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    }
+}
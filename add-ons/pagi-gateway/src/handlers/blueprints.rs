@@ -0,0 +1,114 @@
+//! Blueprint CRUD: edit `AutonomousGoal` intents at runtime without restarting the gateway.
+//!
+//! Edits go through `BlueprintRegistry::upsert_intent` / `remove_intent` and persist in KB-5
+//! (`blueprints/{intent}`), overlaying `PAGI_BLUEPRINT_PATH`. `POST /api/v1/blueprints/reload`
+//! re-reads the file and re-applies the KB-5 overlay.
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use pagi_core::{IntentSpec, PlanStep};
+
+use crate::AppState;
+
+fn error(status: StatusCode, message: impl Into<String>) -> (StatusCode, Json<serde_json::Value>) {
+    (
+        status,
+        Json(serde_json::json!({ "status": "error", "error": message.into() })),
+    )
+}
+
+fn intent_json(intent: &str, steps: &[PlanStep]) -> serde_json::Value {
+    serde_json::json!({ "intent": intent, "steps": steps })
+}
+
+/// GET /api/v1/blueprints – all intents with their steps, sorted by name.
+pub async fn list_blueprints(State(state): State<AppState>) -> Json<serde_json::Value> {
+    let blueprint = state.orchestrator.blueprint();
+    let mut names = blueprint.intent_names();
+    names.sort();
+    let intents: Vec<serde_json::Value> = names
+        .iter()
+        .filter_map(|name| blueprint.plan_for_intent(name).map(|p| intent_json(name, &p.steps)))
+        .collect();
+    Json(serde_json::json!({
+        "status": "ok",
+        "count": intents.len(),
+        "intents": intents,
+    }))
+}
+
+/// GET /api/v1/blueprints/:intent
+pub async fn get_blueprint(
+    State(state): State<AppState>,
+    Path(intent): Path<String>,
+) -> Result<Json<serde_json::Value>, (StatusCode, Json<serde_json::Value>)> {
+    let plan = state
+        .orchestrator
+        .blueprint()
+        .plan_for_intent(&intent)
+        .ok_or_else(|| error(StatusCode::NOT_FOUND, format!("unknown intent: {}", intent)))?;
+    Ok(Json(serde_json::json!({ "status": "ok", "blueprint": intent_json(&intent, &plan.steps) })))
+}
+
+/// POST /api/v1/blueprints/:intent – body is the same shape as a blueprint.json intent:
+/// `["SkillA", "SkillB"]` or `{ "steps": [...], "policy": { ... } }`. Every skill must be registered
+/// and every sub-plan step (`{ "intent": "..." }`) must name an existing intent without cycles.
+pub async fn upsert_blueprint(
+    State(state): State<AppState>,
+    Path(intent): Path<String>,
+    Json(spec): Json<IntentSpec>,
+) -> Result<Json<serde_json::Value>, (StatusCode, Json<serde_json::Value>)> {
+    let steps = spec.into_steps();
+    let registered = state.orchestrator.skill_names();
+    let mut unknown: Vec<&str> = steps
+        .iter()
+        .flat_map(|s| s.candidates())
+        .filter(|s| !registered.iter().any(|r| r == s))
+        .collect();
+    unknown.dedup();
+    if !unknown.is_empty() {
+        return Err(error(
+            StatusCode::BAD_REQUEST,
+            format!("unregistered skills: {}", unknown.join(", ")),
+        ));
+    }
+    state
+        .orchestrator
+        .blueprint()
+        .upsert_intent(&intent, steps.clone())
+        .map_err(|e| error(StatusCode::BAD_REQUEST, e.to_string()))?;
+    tracing::info!(target: "pagi::gateway", intent = %intent, "Blueprint intent upserted");
+    Ok(Json(serde_json::json!({ "status": "ok", "blueprint": intent_json(&intent, &steps) })))
+}
+
+/// DELETE /api/v1/blueprints/:intent
+pub async fn delete_blueprint(
+    State(state): State<AppState>,
+    Path(intent): Path<String>,
+) -> Result<Json<serde_json::Value>, (StatusCode, Json<serde_json::Value>)> {
+    let existed = state
+        .orchestrator
+        .blueprint()
+        .remove_intent(&intent)
+        .map_err(|e| error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    if !existed {
+        return Err(error(StatusCode::NOT_FOUND, format!("unknown intent: {}", intent)));
+    }
+    tracing::info!(target: "pagi::gateway", intent = %intent, "Blueprint intent removed");
+    Ok(Json(serde_json::json!({ "status": "ok", "removed": intent })))
+}
+
+/// POST /api/v1/blueprints/reload – re-read the blueprint file and KB-5 overlay.
+pub async fn reload_blueprints(
+    State(state): State<AppState>,
+) -> Result<Json<serde_json::Value>, (StatusCode, Json<serde_json::Value>)> {
+    let count = state
+        .orchestrator
+        .blueprint()
+        .reload()
+        .map_err(|e| error(StatusCode::UNPROCESSABLE_ENTITY, e.to_string()))?;
+    Ok(Json(serde_json::json!({ "status": "ok", "intents": count })))
+}
@@ -0,0 +1,38 @@
+//! Request body size limits from `[limits]`: every route reads at most `max_body_bytes`, unless
+//! `route_body_bytes` names the route (by pattern, e.g. `/api/v1/sandbox/files/*path`).
+//!
+//! A body whose `Content-Length` is over the limit gets 413 before the handler runs. Bodies
+//! without one (chunked) are cut off at the limit, and the handler's extractor answers 413.
+
+use axum::{
+    body::Body,
+    extract::{MatchedPath, Request, State},
+    http::{header, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+
+use crate::AppState;
+
+/// Axum route layer enforcing the route's body limit.
+pub async fn limit_body(State(state): State<AppState>, req: Request, next: Next) -> Response {
+    let route = req
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|p| p.as_str().to_string())
+        .unwrap_or_else(|| req.uri().path().to_string());
+    let limit = state.config.get().limits.body_limit(&route);
+    let declared = req
+        .headers()
+        .get(header::CONTENT_LENGTH)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<u64>().ok());
+    if let Some(len) = declared.filter(|&len| len > limit as u64) {
+        tracing::info!(target: "pagi::gateway", route = %route, bytes = len, limit, "Request body refused");
+        let message = format!("request body is {} bytes, the limit for {} is {} bytes", len, route, limit);
+        let body = serde_json::json!({ "status": "error", "error": message, "limit_bytes": limit });
+        return (StatusCode::PAYLOAD_TOO_LARGE, Json(body)).into_response();
+    }
+    next.run(req.map(|body| Body::new(http_body_util::Limited::new(body, limit)))).await
+}
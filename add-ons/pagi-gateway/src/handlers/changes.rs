@@ -0,0 +1,66 @@
+//! Knowledge change feed: KB inserts, updates and removals pushed to the dashboard as they happen,
+//! instead of polling the slots.
+//!
+//! Events carry the slot, key and kind, never the value; read the record when it matters.
+//! A subscriber that falls more than `CHANGE_CHANNEL_CAPACITY` events behind gets a `lagged`
+//! event with the number skipped and should reload what it shows.
+//!
+//! Routes:
+//! - `GET /api/v1/knowledge/changes` – SSE `change` events `{ seq, slot_id, key, kind, timestamp_ms }`
+//!   (`?slot_id=3` for one slot)
+
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::sse::{Event, KeepAlive, Sse},
+    Json,
+};
+use serde::Deserialize;
+use std::time::Duration;
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::AppState;
+
+type ApiError = (StatusCode, Json<serde_json::Value>);
+
+fn error(status: StatusCode, message: impl Into<String>) -> ApiError {
+    (status, Json(serde_json::json!({ "status": "error", "error": message.into() })))
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ChangesQuery {
+    /// Slot 1–9 to follow; every slot when absent.
+    #[serde(default)]
+    pub slot_id: Option<u8>,
+}
+
+/// GET /api/v1/knowledge/changes
+pub async fn knowledge_changes(
+    State(state): State<AppState>,
+    Query(query): Query<ChangesQuery>,
+) -> Result<Sse<impl futures_util::Stream<Item = Result<Event, std::convert::Infallible>> + Send + 'static>, ApiError> {
+    use async_stream::stream;
+
+    // Subscribe before responding so no write made after the response is missed.
+    let mut rx = match query.slot_id {
+        Some(slot) if (1..=9).contains(&slot) => state.knowledge.subscribe(slot),
+        Some(_) => return Err(error(StatusCode::BAD_REQUEST, "slot_id must be 1-9")),
+        None => state.knowledge.subscribe_all(),
+    };
+    let stream = stream! {
+        loop {
+            match rx.recv().await {
+                Ok(change) => match serde_json::to_string(&change) {
+                    Ok(data) => yield Ok(Event::default().event("change").data(data)),
+                    Err(e) => tracing::warn!(target: "pagi::gateway", error = %e, "Failed to encode KB change"),
+                },
+                Err(RecvError::Lagged(skipped)) => {
+                    let data = serde_json::json!({ "skipped": skipped });
+                    yield Ok(Event::default().event("lagged").data(data.to_string()));
+                }
+                Err(RecvError::Closed) => break,
+            }
+        }
+    };
+    Ok(Sse::new(stream).keep_alive(KeepAlive::new().interval(Duration::from_secs(15)).text("keepalive")))
+}
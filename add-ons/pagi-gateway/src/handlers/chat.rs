@@ -6,8 +6,16 @@
 //!
 //! Non-streaming: `Orchestrator::dispatch(ModelRouter)` with `system_prompt` + `prompt`.
 //! Streaming: `ModelRouter::stream_generate(Some(system_directive), user_prompt, ...)`.
+//!
+//! RAG mode (`rag: true`): the prompt is embedded and the tenant's most similar records in KB-3
+//! (or the `rag_slots` the control panel leaves on) are added to the system prompt as numbered
+//! sources; the response lists them under `sources` so the UI can render citations. With a
+//! `[rag] reranker`, a larger candidate pool is retrieved and reordered before the top-k are
+//! cited; `retrieval` reports every candidate's scores.
 
-use pagi_core::{KnowledgeStore, MentalState};
+use pagi_core::{KnowledgeStore, MentalState, Orchestrator, RagConfig, RerankerKind, SemanticHit};
+use pagi_skills::{rerank, ModelRouter};
+use serde::Serialize;
 
 /// Optional: builds a single prompt string with Soma/Kardia prefix (for flows that do not use
 /// a separate system message). The main chat path uses `KnowledgeStore::build_system_directive` instead.
@@ -67,3 +75,192 @@ pub fn build_prompt_with_soma_kardia(
 
     format!("{}{}", system_prefix, user_prompt)
 }
+
+/// Sources retrieved for a RAG chat (`rag: true`) when the request names no `rag_top_k`.
+pub const DEFAULT_RAG_TOP_K: usize = 5;
+pub const MAX_RAG_TOP_K: usize = 20;
+/// Upper bound of `[rag] candidate_pool`.
+const MAX_RAG_CANDIDATES: usize = 100;
+/// Characters of each source put into the prompt.
+const RAG_SNIPPET_CHARS: usize = 800;
+
+/// A record retrieved for a RAG chat, as cited in the prompt (`[n]`) and returned to the UI.
+#[derive(Debug, Clone, Serialize)]
+pub struct RagSource {
+    /// Citation number in the prompt.
+    pub n: usize,
+    pub slot_id: u8,
+    pub key: String,
+    /// Similarity to the prompt.
+    pub score: f32,
+    /// Reranker score (0.0–1.0) when `[rag] reranker` is set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rerank_score: Option<f32>,
+    pub snippet: String,
+    /// Document (`doc/{id}` chunks) or feed entry the record came from, when known.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub link: Option<String>,
+}
+
+/// Retrieval for one chat: the prompt is embedded and the most similar records of the tenant in
+/// each searched slot are kept, best first.
+#[derive(Debug, Clone, Default)]
+pub struct RagContext {
+    pub slots: Vec<u8>,
+    pub sources: Vec<RagSource>,
+    /// Method that ordered the candidates (`lexical` when the LLM scorer fell back).
+    pub reranker: RerankerKind,
+    /// Every candidate retrieved, in final order, for tuning the pool and thresholds.
+    pub candidates: Vec<RagCandidate>,
+}
+
+/// A retrieved record and what became of it.
+#[derive(Debug, Clone, Serialize)]
+pub struct RagCandidate {
+    pub slot_id: u8,
+    pub key: String,
+    pub similarity: f32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rerank_score: Option<f32>,
+    #[serde(flatten)]
+    pub outcome: CandidateOutcome,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(tag = "outcome", rename_all = "snake_case")]
+pub enum CandidateOutcome {
+    /// Put into the prompt as source `[n]`.
+    Cited { n: usize },
+    BelowMinSimilarity,
+    BelowMinRerankScore,
+    /// Scored well enough, but `top_k` better candidates were cited.
+    BeyondTopK,
+}
+
+impl RagContext {
+    /// Retrieval trace returned with the chat: the method and every candidate's scores.
+    pub fn trace(&self) -> serde_json::Value {
+        serde_json::json!({ "slots": self.slots, "reranker": self.reranker, "candidates": self.candidates })
+    }
+
+    /// Section appended to the system prompt: the numbered sources and how to cite them.
+    pub fn to_prompt_section(&self) -> String {
+        if self.sources.is_empty() {
+            return "[Knowledge retrieval: no stored records matched this question. Say so if the answer depends on them.]"
+                .to_string();
+        }
+        let mut section = String::from(
+            "[Retrieved knowledge: answer from these sources where they apply and cite them as [n]. \
+             Do not cite sources you did not use.]",
+        );
+        for source in &self.sources {
+            section.push_str(&format!("\n[{}] (KB-{} {}) {}", source.n, source.slot_id, source.key, source.snippet));
+        }
+        section
+    }
+}
+
+/// Slots a RAG chat searches: `requested` (default KB-3 Logos) without the ones switched off in
+/// the control panel.
+pub fn rag_slots(orchestrator: &Orchestrator, requested: Option<&[u8]>) -> Vec<u8> {
+    let requested = requested.filter(|s| !s.is_empty()).unwrap_or(&[3]);
+    let mut slots = Vec::new();
+    for &slot in requested {
+        if orchestrator.pagi_kb_active(slot) && !slots.contains(&slot) {
+            slots.push(slot);
+        }
+    }
+    slots
+}
+
+/// Embeds `prompt`, retrieves the tenant's most similar records across `slots` and keeps the
+/// `top_k` best after `config`'s similarity threshold, reranker and rerank threshold. Every
+/// candidate looked at is kept in `candidates` with its scores.
+pub async fn retrieve(
+    knowledge: &KnowledgeStore,
+    router: &ModelRouter,
+    tenant_id: &str,
+    prompt: &str,
+    slots: Vec<u8>,
+    top_k: usize,
+    config: &RagConfig,
+) -> Result<RagContext, Box<dyn std::error::Error + Send + Sync>> {
+    let top_k = top_k.clamp(1, MAX_RAG_TOP_K);
+    if slots.is_empty() {
+        return Ok(RagContext { reranker: config.reranker, ..Default::default() });
+    }
+    let pool = match config.reranker {
+        RerankerKind::None => top_k,
+        _ => config.candidate_pool.clamp(top_k, MAX_RAG_CANDIDATES),
+    };
+    let embedding = router.embedding(prompt, None).await?;
+    let kb = knowledge.tenant(tenant_id)?;
+    let mut hits = Vec::new();
+    for &slot_id in &slots {
+        hits.extend(kb.semantic_search(slot_id, &embedding, pool)?);
+    }
+    hits.sort_by(|a, b| b.score.total_cmp(&a.score).then_with(|| a.key.cmp(&b.key)));
+    hits.truncate(pool);
+    let (mut hits, below): (Vec<_>, Vec<_>) = hits.into_iter().partition(|hit| hit.score >= config.min_similarity);
+
+    let mut method = RerankerKind::None;
+    let mut rerank_scores = vec![None; hits.len()];
+    if config.reranker != RerankerKind::None && !hits.is_empty() {
+        let texts: Vec<(&str, f32)> = hits.iter().map(|hit| (hit.content.as_str(), hit.score)).collect();
+        let reranked = rerank(config.reranker, router, prompt, &texts).await;
+        method = reranked.method;
+        let mut scored: Vec<_> = hits.into_iter().zip(reranked.scores).collect();
+        scored.sort_by(|(a, a_score), (b, b_score)| b_score.total_cmp(a_score).then_with(|| b.score.total_cmp(&a.score)));
+        (hits, rerank_scores) = scored.into_iter().map(|(hit, score)| (hit, Some(score))).unzip();
+    }
+
+    let mut sources = Vec::new();
+    let mut candidates = Vec::new();
+    for (hit, rerank_score) in hits.into_iter().zip(rerank_scores) {
+        let outcome = if rerank_score.is_some_and(|s| s < config.min_rerank_score) {
+            CandidateOutcome::BelowMinRerankScore
+        } else if sources.len() >= top_k {
+            CandidateOutcome::BeyondTopK
+        } else {
+            CandidateOutcome::Cited { n: sources.len() + 1 }
+        };
+        candidates.push(RagCandidate {
+            slot_id: hit.slot_id,
+            key: hit.key.clone(),
+            similarity: hit.score,
+            rerank_score,
+            outcome,
+        });
+        if let CandidateOutcome::Cited { n } = outcome {
+            sources.push(source(n, hit, rerank_score));
+        }
+    }
+    candidates.extend(below.into_iter().map(|hit| RagCandidate {
+        slot_id: hit.slot_id,
+        key: hit.key,
+        similarity: hit.score,
+        rerank_score: None,
+        outcome: CandidateOutcome::BelowMinSimilarity,
+    }));
+    Ok(RagContext { slots, sources, reranker: method, candidates })
+}
+
+fn source(n: usize, hit: SemanticHit, rerank_score: Option<f32>) -> RagSource {
+    let text = |field: &str| hit.metadata.get(field).and_then(|v| v.as_str()).map(str::to_string);
+    let snippet = match hit.content.char_indices().nth(RAG_SNIPPET_CHARS) {
+        Some((cut, _)) => format!("{}…", &hit.content[..cut]),
+        None => hit.content.clone(),
+    };
+    RagSource {
+        n,
+        slot_id: hit.slot_id,
+        title: text("title").or_else(|| text("filename")),
+        link: text("link"),
+        key: hit.key,
+        score: hit.score,
+        rerank_score,
+        snippet: snippet.split_whitespace().collect::<Vec<_>>().join(" "),
+    }
+}
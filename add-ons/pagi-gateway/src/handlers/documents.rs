@@ -0,0 +1,167 @@
+//! Document uploads: PDF, DOCX, Markdown and text files extracted, chunked and embedded into a
+//! knowledge slot (`DocumentIngest`), for retrieval over whole documents.
+//!
+//! Each file becomes a header record `doc/{id}` and its chunks `doc/{id}/{index}`, stored within
+//! the tenant (`tenant_id` query parameter, or the caller's own tenant). The id is a hash of the
+//! file: uploading the same file again replaces it. Uploads are bounded by `[limits]`; raise
+//! `route_body_bytes` for `/api/v1/kb/:slot/documents` to accept files over `max_body_bytes`.
+//!
+//! Routes:
+//! - `POST /api/v1/kb/:slot/documents` – multipart upload, one or more `file` parts;
+//!   query `chunk_chars?`, `overlap?`, `model?`, `tenant_id?`
+//! - `GET /api/v1/kb/:slot/documents` – documents in the slot
+//! - `DELETE /api/v1/kb/:slot/documents/:document_id` – remove a document and its chunks
+
+use axum::{
+    extract::{Multipart, Path, Query, State},
+    http::StatusCode,
+    Extension, Json,
+};
+use pagi_core::DEFAULT_TENANT_ID;
+use pagi_skills::{DocumentError, DocumentIngest, IngestOptions, DEFAULT_CHUNK_CHARS, DEFAULT_OVERLAP_CHARS};
+use serde::Deserialize;
+use std::sync::Arc;
+
+use super::auth::{acting_tenant, Caller};
+use crate::AppState;
+
+#[derive(Debug, Default, Deserialize)]
+pub struct DocumentQuery {
+    #[serde(default)]
+    pub tenant_id: Option<String>,
+    /// Target chunk size in characters (default 1200).
+    #[serde(default)]
+    pub chunk_chars: Option<usize>,
+    /// Characters each chunk repeats from the previous one (default 200).
+    #[serde(default)]
+    pub overlap: Option<usize>,
+    /// Embedding model override (default: `PAGI_EMBEDDINGS_MODEL`).
+    #[serde(default)]
+    pub model: Option<String>,
+}
+
+fn error(status: StatusCode, message: impl Into<String>) -> (StatusCode, Json<serde_json::Value>) {
+    (status, Json(serde_json::json!({ "status": "error", "error": message.into() })))
+}
+
+fn document_status(e: &DocumentError) -> StatusCode {
+    match e {
+        DocumentError::Unsupported(_) => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+        DocumentError::Extract(_) | DocumentError::Rejected(_) => StatusCode::UNPROCESSABLE_ENTITY,
+        DocumentError::Embedding(_) => StatusCode::BAD_GATEWAY,
+        DocumentError::Store(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+fn tenant(
+    caller: Option<&Extension<Caller>>,
+    query: &DocumentQuery,
+) -> Result<String, (StatusCode, Json<serde_json::Value>)> {
+    Ok(acting_tenant(caller, query.tenant_id.as_deref())?.unwrap_or_else(|| DEFAULT_TENANT_ID.to_string()))
+}
+
+fn slot(slot: u8) -> Result<u8, (StatusCode, Json<serde_json::Value>)> {
+    if (1..=8).contains(&slot) {
+        Ok(slot)
+    } else {
+        Err(error(StatusCode::BAD_REQUEST, format!("slot {} cannot hold documents (slots 1-8 only)", slot)))
+    }
+}
+
+/// POST /api/v1/kb/:slot/documents
+pub async fn upload_documents(
+    State(state): State<AppState>,
+    Path(slot_id): Path<u8>,
+    Query(query): Query<DocumentQuery>,
+    caller: Option<Extension<Caller>>,
+    mut multipart: Multipart,
+) -> (StatusCode, Json<serde_json::Value>) {
+    let (tenant_id, slot_id) = match (tenant(caller.as_ref(), &query), slot(slot_id)) {
+        (Ok(tenant_id), Ok(slot_id)) => (tenant_id, slot_id),
+        (Err(e), _) | (_, Err(e)) => return e,
+    };
+    let options = IngestOptions {
+        chunk_chars: query.chunk_chars.unwrap_or(DEFAULT_CHUNK_CHARS),
+        overlap_chars: query.overlap.unwrap_or(DEFAULT_OVERLAP_CHARS),
+        embedding_model: query.model.map(|m| m.trim().to_string()).filter(|m| !m.is_empty()),
+    };
+    let ingest = DocumentIngest::new(Arc::clone(&state.knowledge));
+    let mut documents = Vec::new();
+    loop {
+        let field = match multipart.next_field().await {
+            Ok(Some(field)) => field,
+            Ok(None) => break,
+            Err(e) => return error(e.status(), e.body_text()),
+        };
+        // Form fields other than files are ignored.
+        let Some(filename) = field.file_name().map(str::to_string) else {
+            continue;
+        };
+        let content_type = field.content_type().map(str::to_string);
+        let bytes = match field.bytes().await {
+            Ok(bytes) => bytes,
+            Err(e) => return error(e.status(), e.body_text()),
+        };
+        match ingest
+            .ingest(&tenant_id, slot_id, &filename, content_type.as_deref(), bytes.to_vec(), &options)
+            .await
+        {
+            Ok(document) => documents.push(document),
+            Err(e) => {
+                let (status, Json(mut body)) = error(document_status(&e), e.to_string());
+                body["filename"] = serde_json::json!(filename);
+                // Files before the failing one are stored.
+                body["documents"] = serde_json::json!(documents);
+                return (status, Json(body));
+            }
+        }
+    }
+    if documents.is_empty() {
+        return error(StatusCode::BAD_REQUEST, "no file in the upload (send multipart parts with a filename)");
+    }
+    (
+        StatusCode::CREATED,
+        Json(serde_json::json!({ "status": "ok", "count": documents.len(), "documents": documents })),
+    )
+}
+
+/// GET /api/v1/kb/:slot/documents
+pub async fn list_documents(
+    State(state): State<AppState>,
+    Path(slot_id): Path<u8>,
+    Query(query): Query<DocumentQuery>,
+    caller: Option<Extension<Caller>>,
+) -> (StatusCode, Json<serde_json::Value>) {
+    let (tenant_id, slot_id) = match (tenant(caller.as_ref(), &query), slot(slot_id)) {
+        (Ok(tenant_id), Ok(slot_id)) => (tenant_id, slot_id),
+        (Err(e), _) | (_, Err(e)) => return e,
+    };
+    match DocumentIngest::new(Arc::clone(&state.knowledge)).list(&tenant_id, slot_id) {
+        Ok(documents) => (
+            StatusCode::OK,
+            Json(serde_json::json!({ "status": "ok", "count": documents.len(), "documents": documents })),
+        ),
+        Err(e) => error(document_status(&e), e.to_string()),
+    }
+}
+
+/// DELETE /api/v1/kb/:slot/documents/:document_id
+pub async fn delete_document(
+    State(state): State<AppState>,
+    Path((slot_id, document_id)): Path<(u8, String)>,
+    Query(query): Query<DocumentQuery>,
+    caller: Option<Extension<Caller>>,
+) -> (StatusCode, Json<serde_json::Value>) {
+    let (tenant_id, slot_id) = match (tenant(caller.as_ref(), &query), slot(slot_id)) {
+        (Ok(tenant_id), Ok(slot_id)) => (tenant_id, slot_id),
+        (Err(e), _) | (_, Err(e)) => return e,
+    };
+    match DocumentIngest::new(Arc::clone(&state.knowledge)).remove(&tenant_id, slot_id, &document_id) {
+        Ok(0) => error(StatusCode::NOT_FOUND, format!("no document {} in slot {}", document_id, slot_id)),
+        Ok(records) => (
+            StatusCode::OK,
+            Json(serde_json::json!({ "status": "ok", "removed": document_id, "records": records })),
+        ),
+        Err(e) => error(document_status(&e), e.to_string()),
+    }
+}
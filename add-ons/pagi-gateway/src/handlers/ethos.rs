@@ -0,0 +1,161 @@
+//! Ethos policy rules (v2): scoped allow / deny / require-approval rules over skill payloads,
+//! replacing the keyword scan of the legacy `policy/default` record.
+//!
+//! Each `PUT` saves the rule set as the next version in KB_ETHOS, with every version kept
+//! ([`KnowledgeStore::put_policy_rules`](pagi_core::KnowledgeStore::put_policy_rules)). The
+//! orchestrator evaluates the current version before every direct skill call and every plan step;
+//! a blocked plan step halts the chain, or is skipped or replaced by a fallback per its step policy
+//! (`on_blocked`). `DELETE` drops the rule set, so the legacy keyword policy applies again (to
+//! direct `ExecuteSkill` calls only).
+//! `PUT` and `DELETE` need the `kb_admin` permission (see `auth`).
+//!
+//! Routes:
+//! - `GET /api/v1/ethos/rules` – the current rule set (`rules: null` when the legacy policy applies);
+//!   `?version=N` returns that saved version instead
+//! - `PUT /api/v1/ethos/rules` – save `{ rules, default_action?, note? }` as the next version
+//! - `DELETE /api/v1/ethos/rules` – fall back to the legacy keyword policy
+//! - `GET /api/v1/ethos/rules/versions` – version history (`version`, `updated_at_ms`, `note`, rule count)
+//! - `POST /api/v1/ethos/evaluate` – dry-run `{ skill, payload? }` against the policy in force
+
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    Json,
+};
+use pagi_core::{PolicyDecision, PolicyRule, PolicyRules, RuleAction};
+use serde::Deserialize;
+use std::sync::Arc;
+
+use crate::AppState;
+
+fn error(status: StatusCode, message: impl Into<String>) -> (StatusCode, Json<serde_json::Value>) {
+    (status, Json(serde_json::json!({ "status": "error", "error": message.into() })))
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct RulesQuery {
+    #[serde(default)]
+    pub version: Option<u32>,
+}
+
+/// GET /api/v1/ethos/rules
+pub async fn get_rules(
+    State(state): State<AppState>,
+    Query(query): Query<RulesQuery>,
+) -> (StatusCode, Json<serde_json::Value>) {
+    let knowledge = Arc::clone(&state.knowledge);
+    let loaded = tokio::task::spawn_blocking(move || match query.version {
+        Some(version) => knowledge.get_policy_rules_version(version).ok_or(version),
+        None => Ok(knowledge.get_policy_rules().unwrap_or_default()),
+    })
+    .await;
+    match loaded {
+        Ok(Ok(rules)) if rules.version == 0 => (
+            StatusCode::OK,
+            Json(serde_json::json!({ "status": "ok", "rules": null, "legacy": true })),
+        ),
+        Ok(Ok(rules)) => (
+            StatusCode::OK,
+            Json(serde_json::json!({ "status": "ok", "rules": rules, "legacy": false })),
+        ),
+        Ok(Err(version)) => error(StatusCode::NOT_FOUND, format!("no policy rules version {}", version)),
+        Err(e) => error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PutRulesRequest {
+    pub rules: Vec<PolicyRule>,
+    #[serde(default)]
+    pub default_action: RuleAction,
+    #[serde(default)]
+    pub note: Option<String>,
+}
+
+/// PUT /api/v1/ethos/rules
+pub async fn put_rules(
+    State(state): State<AppState>,
+    Json(req): Json<PutRulesRequest>,
+) -> (StatusCode, Json<serde_json::Value>) {
+    let rules = PolicyRules {
+        note: req.note,
+        ..PolicyRules::new(req.rules, req.default_action)
+    };
+    if let Err(e) = rules.validate() {
+        return error(StatusCode::BAD_REQUEST, e.to_string());
+    }
+    let knowledge = Arc::clone(&state.knowledge);
+    match tokio::task::spawn_blocking(move || knowledge.put_policy_rules(&rules)).await {
+        Ok(Ok(saved)) => {
+            tracing::info!(target: "pagi::ethos", version = saved.version, rules = saved.rules.len(), "Ethos policy rules saved");
+            (StatusCode::OK, Json(serde_json::json!({ "status": "ok", "rules": saved })))
+        }
+        Ok(Err(e)) => error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+        Err(e) => error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    }
+}
+
+/// DELETE /api/v1/ethos/rules
+pub async fn clear_rules(State(state): State<AppState>) -> (StatusCode, Json<serde_json::Value>) {
+    let knowledge = Arc::clone(&state.knowledge);
+    match tokio::task::spawn_blocking(move || knowledge.clear_policy_rules()).await {
+        Ok(Ok(cleared)) => {
+            tracing::info!(target: "pagi::ethos", cleared, "Ethos policy rules cleared; legacy policy applies");
+            (StatusCode::OK, Json(serde_json::json!({ "status": "ok", "cleared": cleared })))
+        }
+        Ok(Err(e)) => error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+        Err(e) => error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    }
+}
+
+/// GET /api/v1/ethos/rules/versions
+pub async fn list_versions(State(state): State<AppState>) -> (StatusCode, Json<serde_json::Value>) {
+    let knowledge = Arc::clone(&state.knowledge);
+    match tokio::task::spawn_blocking(move || knowledge.policy_rule_versions()).await {
+        Ok(Ok(versions)) => (
+            StatusCode::OK,
+            Json(serde_json::json!({
+                "status": "ok",
+                "count": versions.len(),
+                "versions": versions
+                    .iter()
+                    .map(|v| serde_json::json!({
+                        "version": v.version,
+                        "updated_at_ms": v.updated_at_ms,
+                        "note": v.note,
+                        "rule_count": v.rules.len(),
+                    }))
+                    .collect::<Vec<_>>(),
+            })),
+        ),
+        Ok(Err(e)) => error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+        Err(e) => error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct EvaluateRequest {
+    pub skill: String,
+    #[serde(default)]
+    pub payload: Option<serde_json::Value>,
+}
+
+/// POST /api/v1/ethos/evaluate
+pub async fn evaluate(
+    State(state): State<AppState>,
+    Json(req): Json<EvaluateRequest>,
+) -> (StatusCode, Json<serde_json::Value>) {
+    let knowledge = Arc::clone(&state.knowledge);
+    let decided = tokio::task::spawn_blocking(move || match knowledge.active_ethos_policy() {
+        Some(policy) => policy.evaluate(&req.skill, req.payload.as_ref()),
+        None => PolicyDecision::allowed(),
+    })
+    .await;
+    match decided {
+        Ok(decision) => (
+            StatusCode::OK,
+            Json(serde_json::json!({ "status": "ok", "allowed": decision.is_allowed(), "decision": decision })),
+        ),
+        Err(e) => error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    }
+}
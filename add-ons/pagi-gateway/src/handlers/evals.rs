@@ -0,0 +1,163 @@
+//! Eval suites: run a suite of goals against the live skills, blueprint and prompt templates, score
+//! the outputs against the suite's assertions (`contains`, `not_contains`, `regex`, `equals`,
+//! `llm_judge`) and keep every run in KB-3 so a suite's score can be followed over time (see
+//! `pagi_core::EvalSuite` for the format).
+//!
+//! `suite` is either the suite itself or the name of a JSON file in `PAGI_EVALS_DIR` (default
+//! `config/evals`). Goals run for real, side effects included, without a deadline. Running needs
+//! the `execute` permission (see `auth`).
+//!
+//! Routes:
+//! - `POST /api/v1/evals/run` – `{ suite, label?, tenant_id?, agent_id? }`; answers the run with
+//!   the suite's previous run and the score `delta` to it
+//! - `GET /api/v1/evals` – every suite with the summary of its latest run
+//! - `GET /api/v1/evals/:suite/runs` – the suite's score trend, newest first (`?limit=`, default 50)
+//! - `GET /api/v1/evals/:suite/runs/:run_id` – one run with its per-case results
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Extension, Json,
+};
+use pagi_core::{keys, EvalSuite, TenantContext, DEFAULT_TENANT_ID};
+use serde::Deserialize;
+use std::sync::Arc;
+
+use super::auth::Caller;
+use crate::AppState;
+
+const DEFAULT_RUNS_LIMIT: usize = 50;
+
+fn error(status: StatusCode, message: impl Into<String>) -> Response {
+    (status, Json(serde_json::json!({ "status": "error", "error": message.into() }))).into_response()
+}
+
+fn evals_dir() -> std::path::PathBuf {
+    std::env::var("PAGI_EVALS_DIR")
+        .ok()
+        .filter(|s| !s.trim().is_empty())
+        .unwrap_or_else(|| "config/evals".to_string())
+        .into()
+}
+
+/// The suite in `body`: inline, or `{name}.json` from [`evals_dir`]. Errors carry their status.
+fn load_suite(suite: serde_json::Value) -> Result<EvalSuite, (StatusCode, String)> {
+    let suite = match suite {
+        serde_json::Value::String(name) => {
+            if !keys::is_valid_tenant_id(&name) {
+                return Err((StatusCode::BAD_REQUEST, format!("invalid suite name {:?}", name)));
+            }
+            let path = evals_dir().join(format!("{}.json", name));
+            let text = std::fs::read_to_string(&path)
+                .map_err(|_| (StatusCode::NOT_FOUND, format!("no eval suite {} ({})", name, path.display())))?;
+            serde_json::from_str::<EvalSuite>(&text)
+                .map_err(|e| (StatusCode::UNPROCESSABLE_ENTITY, format!("{}: {}", path.display(), e)))?
+        }
+        value => serde_json::from_value::<EvalSuite>(value)
+            .map_err(|e| (StatusCode::UNPROCESSABLE_ENTITY, format!("invalid eval suite: {}", e)))?,
+    };
+    suite.validate().map_err(|e| (StatusCode::UNPROCESSABLE_ENTITY, e.to_string()))?;
+    Ok(suite)
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RunEvalRequest {
+    /// The suite (object) or the name of a suite file (string).
+    pub suite: serde_json::Value,
+    /// What changed since the last run (e.g. `prompt v3`).
+    #[serde(default)]
+    pub label: Option<String>,
+    /// Tenant the goals run as (default `default`).
+    #[serde(default)]
+    pub tenant_id: Option<String>,
+    #[serde(default)]
+    pub agent_id: Option<String>,
+}
+
+/// POST /api/v1/evals/run
+pub async fn run_eval(
+    State(state): State<AppState>,
+    caller: Option<Extension<Caller>>,
+    Json(req): Json<RunEvalRequest>,
+) -> Response {
+    if !state.orchestrator.pagi_skills_enabled() {
+        return error(StatusCode::SERVICE_UNAVAILABLE, "Skills execution is disabled by the control panel.");
+    }
+    let tenant_id = match super::auth::acting_tenant(caller.as_ref(), req.tenant_id.as_deref()) {
+        Ok(tenant_id) => tenant_id.unwrap_or_else(|| DEFAULT_TENANT_ID.to_string()),
+        Err(denied) => return denied.into_response(),
+    };
+    let suite = match load_suite(req.suite) {
+        Ok(suite) => suite,
+        Err((status, message)) => return error(status, message),
+    };
+    let previous = match state.knowledge.eval_runs(&suite.name, 1) {
+        Ok(runs) => runs.into_iter().next(),
+        Err(e) => return error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    };
+    let ctx = TenantContext {
+        tenant_id,
+        correlation_id: None,
+        agent_id: req.agent_id,
+        ..Default::default()
+    };
+    match state.orchestrator.run_eval(&ctx, &suite, req.label).await {
+        Ok(run) => {
+            let delta = previous.as_ref().map(|p| run.score - p.score);
+            Json(serde_json::json!({ "status": "ok", "run": run, "previous": previous, "delta": delta })).into_response()
+        }
+        Err(e) => error(StatusCode::UNPROCESSABLE_ENTITY, e.to_string()),
+    }
+}
+
+/// GET /api/v1/evals
+pub async fn list_evals(State(state): State<AppState>) -> Response {
+    let knowledge = Arc::clone(&state.knowledge);
+    match tokio::task::spawn_blocking(move || knowledge.eval_suites()).await {
+        Ok(Ok(suites)) => {
+            Json(serde_json::json!({ "status": "ok", "count": suites.len(), "suites": suites })).into_response()
+        }
+        Ok(Err(e)) => error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+        Err(e) => error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct RunsQuery {
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+/// GET /api/v1/evals/:suite/runs
+pub async fn list_runs(
+    State(state): State<AppState>,
+    Path(suite): Path<String>,
+    Query(query): Query<RunsQuery>,
+) -> Response {
+    let knowledge = Arc::clone(&state.knowledge);
+    let limit = query.limit.unwrap_or(DEFAULT_RUNS_LIMIT);
+    let name = suite.clone();
+    match tokio::task::spawn_blocking(move || knowledge.eval_runs(&name, limit)).await {
+        Ok(Ok(runs)) => Json(serde_json::json!({
+            "status": "ok",
+            "suite": suite,
+            "count": runs.len(),
+            "runs": runs,
+        }))
+        .into_response(),
+        Ok(Err(e)) => error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+        Err(e) => error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    }
+}
+
+/// GET /api/v1/evals/:suite/runs/:run_id
+pub async fn get_run(State(state): State<AppState>, Path((suite, run_id)): Path<(String, String)>) -> Response {
+    let knowledge = Arc::clone(&state.knowledge);
+    match tokio::task::spawn_blocking(move || knowledge.get_eval_run(&suite, &run_id)).await {
+        Ok(Ok(Some(run))) => Json(serde_json::json!({ "status": "ok", "run": run })).into_response(),
+        Ok(Ok(None)) => error(StatusCode::NOT_FOUND, "no such eval run"),
+        Ok(Err(e)) => error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+        Err(e) => error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    }
+}
@@ -0,0 +1,158 @@
+//! Knowledge export and import: every record of slots 1–8 as one JSON document, for moving
+//! knowledge between gateways or keeping a portable copy (`pagi-cli export` / `pagi-cli import`).
+//!
+//! Unlike snapshots, which copy the store's trees on the gateway's own disk, an export travels
+//! with the caller and can be imported into any gateway. Shadow (slot 9) is never exported; its
+//! values are sealed with the vault key of the gateway that wrote them. Values that are UTF-8 text
+//! are exported as `value`, anything else base64-encoded as `value_b64`.
+//!
+//! Both routes need the `kb_admin` permission (see `auth`). A large import may need a
+//! `[limits] route_body_bytes` entry for `/api/v1/knowledge/import`.
+//!
+//! Routes:
+//! - `GET /api/v1/knowledge/export` – the export document (`?slots=1,3` for some slots)
+//! - `POST /api/v1/knowledge/import` – write an export document's records (existing keys are overwritten)
+
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    Json,
+};
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+use crate::AppState;
+
+/// `format` of an export document.
+pub const EXPORT_FORMAT: &str = "pagi-knowledge-export";
+/// Failed records listed in an import response, at most.
+const MAX_REPORTED_FAILURES: usize = 50;
+
+fn error(status: StatusCode, message: impl Into<String>) -> (StatusCode, Json<serde_json::Value>) {
+    (status, Json(serde_json::json!({ "status": "error", "error": message.into() })))
+}
+
+/// One exported record.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportedRecord {
+    pub slot_id: u8,
+    pub key: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value_b64: Option<String>,
+}
+
+impl ExportedRecord {
+    fn new(slot_id: u8, key: String, bytes: Vec<u8>) -> Self {
+        match String::from_utf8(bytes) {
+            Ok(text) => Self { slot_id, key, value: Some(text), value_b64: None },
+            Err(e) => Self {
+                slot_id,
+                key,
+                value: None,
+                value_b64: Some(base64::engine::general_purpose::STANDARD.encode(e.into_bytes())),
+            },
+        }
+    }
+
+    fn bytes(&self) -> Result<Vec<u8>, String> {
+        match (&self.value, &self.value_b64) {
+            (Some(text), None) => Ok(text.clone().into_bytes()),
+            (None, Some(b64)) => base64::engine::general_purpose::STANDARD
+                .decode(b64)
+                .map_err(|e| format!("value_b64 is not base64: {}", e)),
+            _ => Err("a record needs exactly one of value and value_b64".to_string()),
+        }
+    }
+}
+
+/// An export document.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KnowledgeExport {
+    pub format: String,
+    #[serde(default)]
+    pub exported_at_ms: i64,
+    pub records: Vec<ExportedRecord>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ExportQuery {
+    /// Comma-separated slots 1–8; all of them when absent.
+    #[serde(default)]
+    pub slots: Option<String>,
+}
+
+/// GET /api/v1/knowledge/export
+pub async fn export_knowledge(
+    State(state): State<AppState>,
+    Query(query): Query<ExportQuery>,
+) -> Result<Json<KnowledgeExport>, (StatusCode, Json<serde_json::Value>)> {
+    let slots: Vec<u8> = match query.slots.as_deref().filter(|s| !s.trim().is_empty()) {
+        None => (1..=8).collect(),
+        Some(list) => list
+            .split(',')
+            .map(|s| s.trim().parse::<u8>().ok().filter(|n| (1..=8).contains(n)))
+            .collect::<Option<_>>()
+            .ok_or_else(|| error(StatusCode::BAD_REQUEST, "slots must be a comma-separated list of 1-8"))?,
+    };
+    let knowledge = Arc::clone(&state.knowledge);
+    let records = tokio::task::spawn_blocking(move || -> Result<Vec<ExportedRecord>, String> {
+        let mut records = Vec::new();
+        for slot_id in slots {
+            let mut entries = knowledge.scan_kv(slot_id).map_err(|e| e.to_string())?;
+            entries.sort_by(|a, b| a.0.cmp(&b.0));
+            records.extend(entries.into_iter().map(|(key, bytes)| ExportedRecord::new(slot_id, key, bytes)));
+        }
+        Ok(records)
+    })
+    .await
+    .map_err(|e| error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+    .map_err(|e| error(StatusCode::INTERNAL_SERVER_ERROR, e))?;
+    tracing::info!(target: "pagi::gateway", records = records.len(), "Knowledge exported");
+    Ok(Json(KnowledgeExport {
+        format: EXPORT_FORMAT.to_string(),
+        exported_at_ms: state.knowledge.now_ms(),
+        records,
+    }))
+}
+
+/// POST /api/v1/knowledge/import
+pub async fn import_knowledge(
+    State(state): State<AppState>,
+    Json(export): Json<KnowledgeExport>,
+) -> (StatusCode, Json<serde_json::Value>) {
+    if export.format != EXPORT_FORMAT {
+        return error(StatusCode::BAD_REQUEST, format!("format must be {:?}", EXPORT_FORMAT));
+    }
+    if let Some(record) = export.records.iter().find(|r| !(1..=8).contains(&r.slot_id)) {
+        return error(StatusCode::BAD_REQUEST, format!("record {:?} has slot_id {}; only 1-8 import", record.key, record.slot_id));
+    }
+    let knowledge = Arc::clone(&state.knowledge);
+    let outcome = tokio::task::spawn_blocking(move || {
+        let mut imported = 0usize;
+        let mut failed = Vec::new();
+        for record in &export.records {
+            match record.bytes().and_then(|bytes| knowledge.insert(record.slot_id, &record.key, &bytes).map_err(|e| e.to_string())) {
+                Ok(_) => imported += 1,
+                Err(e) => failed.push(serde_json::json!({ "slot_id": record.slot_id, "key": record.key, "error": e })),
+            }
+        }
+        (imported, failed)
+    })
+    .await;
+    match outcome {
+        Ok((imported, mut failed)) => {
+            tracing::info!(target: "pagi::gateway", imported, failed = failed.len(), "Knowledge imported");
+            let failed_count = failed.len();
+            failed.truncate(MAX_REPORTED_FAILURES);
+            let status = if failed_count == 0 { "ok" } else { "partial" };
+            (
+                StatusCode::OK,
+                Json(serde_json::json!({ "status": status, "imported": imported, "failed_count": failed_count, "failed": failed })),
+            )
+        }
+        Err(e) => error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    }
+}
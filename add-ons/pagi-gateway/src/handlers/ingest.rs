@@ -0,0 +1,329 @@
+//! Inbound webhooks: form and scheduling providers post leads straight to LeadCapture.
+//!
+//! Each source is configured under `[ingest.sources.<name>]` (see [`IngestSourceConfig`]). A
+//! request is checked against the source's signing secret, normalized by its adapter, turned into
+//! lead fields by the source's `map` (or the adapter's default template) and run as
+//! `Goal::IngestData` for the source's tenant. The route needs no API key: providers cannot send
+//! one, so the signature is the credential.
+//!
+//! Adapters:
+//! - `typeform`: `form_response` answers by field `ref` (or id) under `answers`, plus the first
+//!   `email` and `phone`; signed with `Typeform-Signature: sha256={base64}` over the body. The
+//!   signature carries no timestamp, so deliveries are deduplicated on their `event_id` instead:
+//!   a replayed delivery gets the first one's response and captures nothing
+//! - `calendly`: `invitee.created` (other events are ignored) as `email`, `name`, `phone`,
+//!   `meeting`, `start_time`, and the booking questions as `answers` and `message`; signed with
+//!   `Calendly-Webhook-Signature: t={secs},v1={hex}` over `{t}.{body}`
+//! - `generic`: the JSON object as is; signed like PAGI's outbound webhooks (`X-Pagi-Timestamp`
+//!   in ms, `X-Pagi-Signature: sha256={hex}` over `{timestamp}.{body}`)
+//!
+//! Routes:
+//! - `POST /api/v1/ingest/:source` – 201 with the captured `lead_id`; 401 for a bad signature,
+//!   404 for an unknown source
+
+use axum::{
+    body::Bytes,
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    Json,
+};
+use base64::Engine;
+use pagi_core::{
+    apply_map, Goal, IdempotencyClaim, IdempotencyRecord, IngestAdapter, IngestSourceConfig, PayloadMap, TenantContext,
+    DEFAULT_TENANT_ID,
+};
+use ring::hmac;
+
+use crate::AppState;
+
+/// How old a signed timestamp may be (Calendly and generic sources), against replays.
+const SIGNATURE_TOLERANCE_MS: i64 = 5 * 60 * 1000;
+
+fn error(status: StatusCode, message: impl Into<String>) -> (StatusCode, Json<serde_json::Value>) {
+    (status, Json(serde_json::json!({ "status": "error", "error": message.into() })))
+}
+
+fn header<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers.get(name).and_then(|v| v.to_str().ok()).map(str::trim)
+}
+
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+fn within_tolerance(timestamp_ms: i64, now_ms: i64) -> bool {
+    (now_ms - timestamp_ms).abs() <= SIGNATURE_TOLERANCE_MS
+}
+
+/// Checks the adapter's signature header over `body`; the reason when it does not verify.
+fn verify_signature(
+    adapter: IngestAdapter,
+    headers: &HeaderMap,
+    body: &[u8],
+    secret: &str,
+    now_ms: i64,
+) -> Result<(), &'static str> {
+    let key = hmac::Key::new(hmac::HMAC_SHA256, secret.as_bytes());
+    let (message, signature) = match adapter {
+        IngestAdapter::Typeform => {
+            let signature = header(headers, "Typeform-Signature")
+                .and_then(|s| s.strip_prefix("sha256="))
+                .ok_or("missing Typeform-Signature")?;
+            let signature = base64::engine::general_purpose::STANDARD
+                .decode(signature)
+                .map_err(|_| "malformed Typeform-Signature")?;
+            (body.to_vec(), signature)
+        }
+        IngestAdapter::Calendly => {
+            let value = header(headers, "Calendly-Webhook-Signature").ok_or("missing Calendly-Webhook-Signature")?;
+            let part = |name: &str| value.split(',').find_map(|p| p.trim().strip_prefix(name));
+            let timestamp = part("t=").ok_or("malformed Calendly-Webhook-Signature")?;
+            let secs: i64 = timestamp.parse().map_err(|_| "malformed Calendly-Webhook-Signature")?;
+            if !within_tolerance(secs.saturating_mul(1000), now_ms) {
+                return Err("stale Calendly-Webhook-Signature timestamp");
+            }
+            let signature = part("v1=").and_then(decode_hex).ok_or("malformed Calendly-Webhook-Signature")?;
+            ([timestamp.as_bytes(), b".", body].concat(), signature)
+        }
+        IngestAdapter::Generic => {
+            let timestamp = header(headers, "X-Pagi-Timestamp").ok_or("missing X-Pagi-Timestamp")?;
+            let ms: i64 = timestamp.parse().map_err(|_| "malformed X-Pagi-Timestamp")?;
+            if !within_tolerance(ms, now_ms) {
+                return Err("stale X-Pagi-Timestamp");
+            }
+            let signature = header(headers, "X-Pagi-Signature")
+                .and_then(|s| s.strip_prefix("sha256="))
+                .and_then(decode_hex)
+                .ok_or("missing or malformed X-Pagi-Signature")?;
+            ([timestamp.as_bytes(), b".", body].concat(), signature)
+        }
+    };
+    hmac::verify(&key, &message, &signature).map_err(|_| "signature does not match")
+}
+
+/// A Typeform answer's value: the field under the answer's `type`, with choices as their labels.
+fn typeform_value(answer: &serde_json::Value) -> serde_json::Value {
+    let kind = answer["type"].as_str().unwrap_or_default();
+    let value = &answer[kind];
+    match (value.get("label"), value.get("labels")) {
+        (Some(label), _) => label.clone(),
+        (_, Some(labels)) => labels.clone(),
+        _ => value.clone(),
+    }
+}
+
+/// The adapter's view of a payload, or None for events the adapter ignores.
+fn normalize(adapter: IngestAdapter, payload: serde_json::Value) -> Option<serde_json::Value> {
+    match adapter {
+        IngestAdapter::Generic => Some(payload),
+        IngestAdapter::Typeform => {
+            let response = &payload["form_response"];
+            let mut answers = serde_json::Map::new();
+            let mut first_of_type = serde_json::Map::new();
+            for answer in response["answers"].as_array().into_iter().flatten() {
+                let field = &answer["field"];
+                let Some(key) = field["ref"].as_str().or_else(|| field["id"].as_str()) else {
+                    continue;
+                };
+                let value = typeform_value(answer);
+                if let Some(kind) = answer["type"].as_str() {
+                    first_of_type.entry(kind.to_string()).or_insert_with(|| value.clone());
+                }
+                answers.insert(key.to_string(), value);
+            }
+            Some(serde_json::json!({
+                "form_id": response["form_id"],
+                "response_id": response["token"],
+                "submitted_at": response["submitted_at"],
+                "hidden": response["hidden"],
+                "email": first_of_type.get("email"),
+                "phone": first_of_type.get("phone_number"),
+                "answers": answers,
+            }))
+        }
+        IngestAdapter::Calendly => {
+            if payload["event"] != "invitee.created" {
+                return None;
+            }
+            let invitee = &payload["payload"];
+            let qa: Vec<(&str, &str)> = invitee["questions_and_answers"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|qa| Some((qa["question"].as_str()?, qa["answer"].as_str()?)))
+                .collect();
+            let message = qa.iter().map(|(q, a)| format!("{}: {}", q, a)).collect::<Vec<_>>().join("\n");
+            Some(serde_json::json!({
+                "email": invitee["email"],
+                "name": invitee["name"],
+                "phone": invitee["text_reminder_number"],
+                "meeting": invitee["scheduled_event"]["name"],
+                "start_time": invitee["scheduled_event"]["start_time"],
+                "answers": qa.iter().map(|(q, a)| (q.to_string(), serde_json::json!(a))).collect::<serde_json::Map<_, _>>(),
+                "message": (!message.is_empty()).then_some(message),
+            }))
+        }
+    }
+}
+
+/// Lead fields of a normalized payload when the source has no `map` (None: pass it through).
+fn default_map(adapter: IngestAdapter) -> Option<PayloadMap> {
+    let fields: &[(&str, &str)] = match adapter {
+        IngestAdapter::Generic => return None,
+        IngestAdapter::Typeform => &[
+            ("email", "$.email"),
+            ("phone", "$.phone"),
+            ("name", "$.answers.name"),
+            ("message", "$.answers.message"),
+            ("answers", "$.answers"),
+            ("hidden", "$.hidden"),
+            ("form_id", "$.form_id"),
+            ("submitted_at", "$.submitted_at"),
+        ],
+        IngestAdapter::Calendly => &[
+            ("email", "$.email"),
+            ("name", "$.name"),
+            ("phone", "$.phone"),
+            ("message", "$.message"),
+            ("meeting", "$.meeting"),
+            ("start_time", "$.start_time"),
+            ("answers", "$.answers"),
+        ],
+    };
+    Some(fields.iter().map(|(field, selector)| (field.to_string(), serde_json::json!(selector))).collect())
+}
+
+/// Lead fields for `payload` from source `name`: mapped, with nulls dropped and `source` set.
+fn lead_fields(name: &str, source: &IngestSourceConfig, normalized: serde_json::Value) -> Option<serde_json::Value> {
+    let map = match &source.map {
+        map if map.is_empty() => default_map(source.adapter_for(name)),
+        map => Some(map.clone()),
+    };
+    let mapped = match map {
+        Some(map) => apply_map(&map, &normalized),
+        None => normalized,
+    };
+    let serde_json::Value::Object(mut fields) = mapped else {
+        return None;
+    };
+    fields.retain(|_, v| !v.is_null());
+    if fields.is_empty() {
+        return None;
+    }
+    fields.entry("source").or_insert_with(|| serde_json::json!(name));
+    Some(serde_json::Value::Object(fields))
+}
+
+/// The id a delivery is deduplicated on: Typeform's `event_id`. Calendly and generic signatures
+/// are timestamped instead (see [`SIGNATURE_TOLERANCE_MS`]).
+fn delivery_id(adapter: IngestAdapter, payload: &serde_json::Value) -> Option<&str> {
+    match adapter {
+        IngestAdapter::Typeform => payload.get("event_id").and_then(|v| v.as_str()).filter(|id| !id.is_empty()),
+        IngestAdapter::Calendly | IngestAdapter::Generic => None,
+    }
+}
+
+/// POST /api/v1/ingest/:source
+pub async fn ingest(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> (StatusCode, Json<serde_json::Value>) {
+    let config = state.config.get();
+    let Some(source) = config.ingest.sources.get(&name) else {
+        return error(StatusCode::NOT_FOUND, format!("unknown ingest source {:?}", name));
+    };
+    let adapter = source.adapter_for(&name);
+    if !source.allow_unsigned {
+        let Some(secret) = source.secret_env.as_deref().and_then(pagi_core::secret) else {
+            tracing::warn!(target: "pagi::ingest", source = %name, "Ingest source has no signing secret configured");
+            return error(StatusCode::SERVICE_UNAVAILABLE, format!("ingest source {:?} has no signing secret", name));
+        };
+        if let Err(reason) = verify_signature(adapter, &headers, &body, &secret, state.knowledge.now_ms()) {
+            tracing::warn!(target: "pagi::ingest", source = %name, reason, "Ingest signature rejected");
+            return error(StatusCode::UNAUTHORIZED, reason);
+        }
+    }
+    let payload: serde_json::Value = match serde_json::from_slice(&body) {
+        Ok(payload) => payload,
+        Err(e) => return error(StatusCode::BAD_REQUEST, format!("body is not JSON: {}", e)),
+    };
+    let delivery = delivery_id(adapter, &payload).map(|id| format!("ingest/{}/{}", name, id));
+    if adapter == IngestAdapter::Typeform && !source.allow_unsigned && delivery.is_none() {
+        return error(StatusCode::BAD_REQUEST, "missing event_id");
+    }
+    if delivery.as_ref().is_some_and(|key| key.len() > crate::MAX_IDEMPOTENCY_KEY_LEN || key.chars().any(char::is_control)) {
+        return error(StatusCode::BAD_REQUEST, "malformed event_id");
+    }
+    let fingerprint = IdempotencyRecord::fingerprint(&payload);
+    let Some(normalized) = normalize(adapter, payload) else {
+        return (StatusCode::OK, Json(serde_json::json!({ "status": "ignored", "source": name })));
+    };
+    let Some(fields) = lead_fields(&name, source, normalized) else {
+        return error(StatusCode::UNPROCESSABLE_ENTITY, "the payload maps to no lead fields");
+    };
+    let tenant_id = source.tenant_id.clone().unwrap_or_else(|| DEFAULT_TENANT_ID.to_string());
+
+    // Replay protection: the first delivery of an event runs; repeats get its cached response.
+    if let Some(key) = &delivery {
+        match state
+            .knowledge
+            .claim_idempotency_key(&tenant_id, key, &fingerprint, crate::idempotency_ttl_ms())
+        {
+            Ok(IdempotencyClaim::Claimed) => {}
+            Ok(IdempotencyClaim::Completed(cached)) => {
+                tracing::info!(target: "pagi::ingest", source = %name, key = %key, "Ingest delivery replayed");
+                let status = cached["status_code"]
+                    .as_u64()
+                    .and_then(|code| StatusCode::from_u16(code as u16).ok())
+                    .unwrap_or(StatusCode::OK);
+                return (status, Json(cached["body"].clone()));
+            }
+            Ok(IdempotencyClaim::InProgress) => {
+                return error(StatusCode::CONFLICT, "this delivery is still being processed");
+            }
+            Ok(IdempotencyClaim::Mismatch) => {
+                return error(StatusCode::UNPROCESSABLE_ENTITY, "event_id was already used for a different payload");
+            }
+            Err(e) => {
+                // Fail closed: without the claim a replay could capture the lead twice.
+                tracing::warn!(target: "pagi::ingest", source = %name, error = %e, "Ingest delivery claim failed");
+                return error(StatusCode::SERVICE_UNAVAILABLE, "could not record the delivery; retry later");
+            }
+        }
+    }
+
+    let ctx = TenantContext::new(tenant_id.clone());
+    let (status, body) = match crate::run_goal(&state, ctx, Goal::IngestData { payload: Some(fields) }).await {
+        Ok(body) => {
+            let status = match body["status"].as_str() {
+                Some("saved") => StatusCode::CREATED,
+                Some("approval_required") => StatusCode::ACCEPTED,
+                Some("policy_violation") => StatusCode::FORBIDDEN,
+                _ => StatusCode::INTERNAL_SERVER_ERROR,
+            };
+            (status, body)
+        }
+        Err(e) => {
+            // Nothing was captured; free the delivery so the provider's retry runs.
+            if let Some(key) = &delivery {
+                let _ = state.knowledge.release_idempotency_key(&tenant_id, key);
+            }
+            return error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string());
+        }
+    };
+    if let Some(key) = &delivery {
+        let cached = serde_json::json!({ "status_code": status.as_u16(), "body": body });
+        if let Err(e) = state.knowledge.complete_idempotency_key(&tenant_id, key, &cached) {
+            tracing::warn!(target: "pagi::ingest", source = %name, error = %e, "Failed to cache ingest response");
+        }
+    }
+    (status, Json(body))
+}
@@ -0,0 +1,131 @@
+//! Background goal jobs: `POST /v1/execute?async=true` answers `202` with a `job_id` right away
+//! and runs the goal in a background task instead of holding the request open.
+//!
+//! The job lives in KB_SOMA (`job/{job_id}`): `queued`, `running` with the plan's top-level step
+//! progress, then `succeeded`, `failed`, `cancelled` or `timed_out` with the result or error.
+//! Finished jobs are pruned by the Heartbeat after `JOB_RETENTION_MS`. A job runs under its
+//! `correlation_id` (the request's, else the job id), so `/v1/execute/:correlation_id/cancel`
+//! stops it and live step events can be followed over the WebSocket channel. Only a client
+//! `X-Request-Timeout-Ms` bounds a job; the server's request timeout does not.
+//!
+//! Routes:
+//! - `GET /v1/jobs/:job_id` – the job: `status`, `steps_completed` / `steps_total`,
+//!   `current_step`, and `result` or `error` once finished
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Extension, Json,
+};
+use pagi_core::{DeadlineExceeded, Goal, GoalCancelled, GoalJob, JobStatus, StepProgress, TenantContext};
+use std::sync::Arc;
+use tokio::sync::broadcast;
+
+use super::auth::Caller;
+use crate::{deadline_body, run_goal, AppState};
+
+fn error(status: StatusCode, message: impl Into<String>) -> (StatusCode, Json<serde_json::Value>) {
+    (status, Json(serde_json::json!({ "status": "error", "error": message.into() })))
+}
+
+/// Stores a queued job for `goal` and starts running it in the background.
+pub(crate) fn start_job(state: &AppState, ctx: TenantContext, goal: Goal) -> Result<GoalJob, String> {
+    let job_id = uuid::Uuid::new_v4().simple().to_string();
+    let correlation_id = ctx.correlation_id.clone().unwrap_or_else(|| job_id.clone());
+    let job = GoalJob::new(&job_id, &ctx.tenant_id, ctx.resolved_agent_id(), &correlation_id, goal.kind());
+    let job = state
+        .knowledge
+        .create_job(&job)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("job {} already exists", job_id))?;
+    let ctx = TenantContext {
+        correlation_id: Some(correlation_id),
+        ..ctx
+    };
+    tokio::spawn(run_job(state.clone(), ctx, goal, job_id));
+    Ok(job)
+}
+
+async fn run_job(state: AppState, ctx: TenantContext, goal: Goal, job_id: String) {
+    let correlation_id = ctx.correlation_id.clone().unwrap_or_default();
+    // Subscribed before dispatch, and drained before the final update, so every step is counted.
+    let mut progress = state.orchestrator.subscribe_progress();
+    let started_at_ms = state.knowledge.now_ms();
+    update(&state, &job_id, |job| {
+        job.status = JobStatus::Running;
+        job.started_at_ms = Some(started_at_ms);
+    });
+    let run = run_goal(&state, ctx, goal);
+    tokio::pin!(run);
+    let outcome = loop {
+        tokio::select! {
+            outcome = &mut run => break outcome,
+            event = progress.recv() => match event {
+                Ok(event) => record_progress(&state, &job_id, &correlation_id, &event),
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    tracing::warn!(target: "pagi::gateway", job_id = %job_id, skipped, "Job progress lagged");
+                }
+                Err(broadcast::error::RecvError::Closed) => {}
+            },
+        }
+    };
+    while let Ok(event) = progress.try_recv() {
+        record_progress(&state, &job_id, &correlation_id, &event);
+    }
+    let (status, result, error) = job_outcome(outcome);
+    let finished_at_ms = state.knowledge.now_ms();
+    update(&state, &job_id, |job| job.finish(status, result.clone(), error.clone(), finished_at_ms));
+    tracing::info!(target: "pagi::gateway", job_id = %job_id, status = ?status, "Background job finished");
+}
+
+fn record_progress(state: &AppState, job_id: &str, correlation_id: &str, event: &StepProgress) {
+    if event.correlation_id.as_deref() == Some(correlation_id) {
+        update(state, job_id, |job| job.apply_progress(event));
+    }
+}
+
+fn update(state: &AppState, job_id: &str, apply: impl Fn(&mut GoalJob)) {
+    if let Err(e) = state.knowledge.update_job(job_id, apply) {
+        tracing::warn!(target: "pagi::gateway", job_id = %job_id, error = %e, "Failed to update job");
+    }
+}
+
+/// Final status, result and error of a run, with the same bodies `/v1/execute` would answer.
+fn job_outcome(
+    outcome: Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>>,
+) -> (JobStatus, Option<serde_json::Value>, Option<String>) {
+    match outcome {
+        // Pre-check rejections (ReflectShadow key, Ethos policy) come back as error bodies.
+        Ok(result) => match result.get("status").and_then(|s| s.as_str()) {
+            Some("error" | "policy_violation" | "approval_required") => {
+                let error = result.get("error").and_then(|e| e.as_str()).map(str::to_string);
+                (JobStatus::Failed, Some(result), error)
+            }
+            _ => (JobStatus::Succeeded, Some(result), None),
+        },
+        Err(e) => match e.downcast_ref::<DeadlineExceeded>() {
+            Some(exceeded) => (JobStatus::TimedOut, Some(deadline_body(exceeded)), Some(e.to_string())),
+            None if e.is::<GoalCancelled>() => (JobStatus::Cancelled, None, Some(e.to_string())),
+            None => (JobStatus::Failed, None, Some(e.to_string())),
+        },
+    }
+}
+
+/// GET /v1/jobs/:job_id
+pub async fn get_job(
+    State(state): State<AppState>,
+    caller: Option<Extension<Caller>>,
+    Path(job_id): Path<String>,
+) -> (StatusCode, Json<serde_json::Value>) {
+    let knowledge = Arc::clone(&state.knowledge);
+    let id = job_id.clone();
+    match tokio::task::spawn_blocking(move || knowledge.get_job(&id)).await {
+        Ok(Some(job)) => match super::auth::check_tenant(caller.as_ref(), &job.tenant_id) {
+            Ok(()) => (StatusCode::OK, Json(serde_json::json!({ "status": "ok", "job": job }))),
+            // Another tenant's job is reported as missing.
+            Err(_) => error(StatusCode::NOT_FOUND, format!("job {:?} not found", job_id)),
+        },
+        Ok(None) => error(StatusCode::NOT_FOUND, format!("job {:?} not found", job_id)),
+        Err(e) => error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    }
+}
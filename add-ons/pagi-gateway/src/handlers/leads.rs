@@ -0,0 +1,68 @@
+//! Lead pipeline: move captured leads between `new`, `contacted`, `qualified` and `closed`.
+//!
+//! A transition runs as `Goal::MemoryOp` on `leads/{id}/status`, so it passes the Ethos gate and
+//! is logged to Chronos ("Lead {id} moved from {from} to {to}"). The lead is looked up within the
+//! tenant (`tenant_id` query parameter, or the caller's own tenant).
+//!
+//! Routes:
+//! - `POST /api/v1/leads/:id/status` – `{ status }`; 404 for an unknown lead, 409 when the
+//!   pipeline does not allow the move (e.g. `closed` → `qualified`)
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Extension, Json,
+};
+use pagi_core::{Goal, LeadStatus, TenantContext, DEFAULT_TENANT_ID};
+use serde::Deserialize;
+
+use super::auth::{acting_tenant, Caller};
+use crate::AppState;
+
+#[derive(Debug, Default, Deserialize)]
+pub struct LeadQuery {
+    #[serde(default)]
+    pub tenant_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LeadStatusRequest {
+    pub status: LeadStatus,
+}
+
+/// POST /api/v1/leads/:id/status
+pub async fn update_lead_status(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Query(query): Query<LeadQuery>,
+    caller: Option<Extension<Caller>>,
+    Json(req): Json<LeadStatusRequest>,
+) -> (StatusCode, Json<serde_json::Value>) {
+    let tenant_id = match acting_tenant(caller.as_ref(), query.tenant_id.as_deref()) {
+        Ok(tenant_id) => tenant_id.unwrap_or_else(|| DEFAULT_TENANT_ID.to_string()),
+        Err(e) => return e,
+    };
+    let ctx = TenantContext::new(tenant_id);
+    let goal = Goal::MemoryOp {
+        path: format!("leads/{}/status", id),
+        value: Some(serde_json::json!(req.status)),
+        delete: false,
+    };
+    match crate::run_goal(&state, ctx, goal).await {
+        Ok(body) => {
+            let status = match body["status"].as_str() {
+                Some("updated") => StatusCode::OK,
+                Some("not_found") => StatusCode::NOT_FOUND,
+                Some("invalid_transition") => StatusCode::CONFLICT,
+                Some("approval_required") => StatusCode::ACCEPTED,
+                Some("policy_violation") => StatusCode::FORBIDDEN,
+                _ => StatusCode::INTERNAL_SERVER_ERROR,
+            };
+            (status, Json(body))
+        }
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({ "status": "error", "error": e.to_string() })),
+        ),
+    }
+}
@@ -0,0 +1,289 @@
+//! MCP (Model Context Protocol) server: exposes the skill registry as tools over stdio.
+//!
+//! Started with `pagi-gateway --mcp` instead of the HTTP server. Messages are newline-delimited
+//! JSON-RPC 2.0 on stdin/stdout (logs go to stderr). Supported methods:
+//! - `initialize` / `ping`
+//! - `tools/list` – one tool per skill the MCP tenant can run; description and input schema come
+//!   from the skill's KB-5 SkillRecord (`skills/{slug}`) when there is one
+//! - `tools/call` – dispatched as `Goal::ExecuteSkill { name, payload: arguments }`; skill errors
+//!   are returned as tool results with `isError: true`
+//!
+//! The tenant is `PAGI_MCP_TENANT_ID` (default `mcp`), so tenant policies apply to MCP clients.
+
+use pagi_core::{Goal, KnowledgeStore, Orchestrator, SkillRecord, TenantContext};
+use std::sync::Arc;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt};
+
+/// Protocol revision offered when the client does not ask for one.
+pub const MCP_PROTOCOL_VERSION: &str = "2024-11-05";
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+pub struct McpServer {
+    orchestrator: Arc<Orchestrator>,
+    knowledge: Arc<KnowledgeStore>,
+    tenant_id: String,
+}
+
+impl McpServer {
+    pub fn new(orchestrator: Arc<Orchestrator>, knowledge: Arc<KnowledgeStore>, tenant_id: impl Into<String>) -> Self {
+        Self {
+            orchestrator,
+            knowledge,
+            tenant_id: tenant_id.into(),
+        }
+    }
+
+    /// Tenant from `PAGI_MCP_TENANT_ID` (default `mcp`).
+    pub fn from_env(orchestrator: Arc<Orchestrator>, knowledge: Arc<KnowledgeStore>) -> Self {
+        let tenant_id = std::env::var("PAGI_MCP_TENANT_ID").unwrap_or_else(|_| "mcp".to_string());
+        Self::new(orchestrator, knowledge, tenant_id)
+    }
+
+    /// Serves stdin/stdout until stdin closes.
+    pub async fn run_stdio(&self) -> std::io::Result<()> {
+        let mut lines = tokio::io::BufReader::new(tokio::io::stdin()).lines();
+        let mut stdout = tokio::io::stdout();
+        tracing::info!(target: "pagi::gateway", tenant_id = %self.tenant_id, "MCP server listening on stdio");
+        while let Some(line) = lines.next_line().await? {
+            if line.trim().is_empty() {
+                continue;
+            }
+            if let Some(response) = self.handle_line(&line).await {
+                stdout.write_all(response.to_string().as_bytes()).await?;
+                stdout.write_all(b"\n").await?;
+                stdout.flush().await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Handles one JSON-RPC message (or batch). Returns None for notifications.
+    pub async fn handle_line(&self, line: &str) -> Option<serde_json::Value> {
+        match serde_json::from_str::<serde_json::Value>(line) {
+            Ok(serde_json::Value::Array(batch)) => {
+                let mut responses = Vec::new();
+                for message in batch {
+                    responses.extend(self.handle_message(message).await);
+                }
+                (!responses.is_empty()).then_some(serde_json::Value::Array(responses))
+            }
+            Ok(message) => self.handle_message(message).await,
+            Err(e) => Some(error_response(serde_json::Value::Null, PARSE_ERROR, &e.to_string())),
+        }
+    }
+
+    async fn handle_message(&self, message: serde_json::Value) -> Option<serde_json::Value> {
+        let id = message.get("id").cloned();
+        let Some(method) = message.get("method").and_then(|m| m.as_str()) else {
+            // Responses to server-initiated requests are not used; anything else is malformed.
+            return id.map(|id| error_response(id, INVALID_REQUEST, "missing method"));
+        };
+        // Notifications (no id) such as `notifications/initialized` need no answer.
+        let id = id?;
+        let params = message.get("params").cloned().unwrap_or(serde_json::Value::Null);
+        let result = match method {
+            "initialize" => Ok(self.initialize(&params)),
+            "ping" => Ok(serde_json::json!({})),
+            "tools/list" => Ok(serde_json::json!({ "tools": self.tools() })),
+            "tools/call" => self.call_tool(&id, &params).await,
+            _ => Err((METHOD_NOT_FOUND, format!("method not found: {}", method))),
+        };
+        Some(match result {
+            Ok(result) => serde_json::json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err((code, message)) => error_response(id, code, &message),
+        })
+    }
+
+    fn initialize(&self, params: &serde_json::Value) -> serde_json::Value {
+        let version = params
+            .get("protocolVersion")
+            .and_then(|v| v.as_str())
+            .unwrap_or(MCP_PROTOCOL_VERSION);
+        serde_json::json!({
+            "protocolVersion": version,
+            "capabilities": { "tools": { "listChanged": false } },
+            "serverInfo": { "name": "pagi-gateway", "version": env!("CARGO_PKG_VERSION") },
+        })
+    }
+
+    /// Tool manifests for the skills the MCP tenant can run.
+    pub fn tools(&self) -> Vec<serde_json::Value> {
+        let records = self.knowledge.get_skills();
+        self.orchestrator
+            .skill_names_for(&self.tenant_id)
+            .into_iter()
+            .map(|name| {
+                let record = records.iter().find(|r| matches_skill(&r.slug, &name));
+                tool_manifest(&name, record)
+            })
+            .collect()
+    }
+
+    async fn call_tool(
+        &self,
+        id: &serde_json::Value,
+        params: &serde_json::Value,
+    ) -> Result<serde_json::Value, (i64, String)> {
+        let Some(name) = params.get("name").and_then(|n| n.as_str()) else {
+            return Err((INVALID_PARAMS, "tools/call requires a tool name".to_string()));
+        };
+        if !self.orchestrator.skill_names_for(&self.tenant_id).iter().any(|s| s == name) {
+            return Err((INVALID_PARAMS, format!("unknown tool: {}", name)));
+        }
+        let ctx = TenantContext {
+            tenant_id: self.tenant_id.clone(),
+            correlation_id: Some(format!("mcp-{}", id.to_string().trim_matches('"'))),
+            agent_id: None,
+            ..Default::default()
+        };
+        let goal = Goal::ExecuteSkill {
+            name: name.to_string(),
+            payload: params.get("arguments").cloned(),
+        };
+        let (text, is_error) = match self.orchestrator.dispatch(&ctx, goal).await {
+            Ok(output) => (serde_json::to_string_pretty(&output).unwrap_or_default(), false),
+            Err(e) => {
+                tracing::warn!(target: "pagi::gateway", tool = name, error = %e, "MCP tool call failed");
+                (e.to_string(), true)
+            }
+        };
+        Ok(serde_json::json!({
+            "content": [{ "type": "text", "text": text }],
+            "isError": is_error,
+        }))
+    }
+}
+
+fn error_response(id: serde_json::Value, code: i64, message: &str) -> serde_json::Value {
+    serde_json::json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } })
+}
+
+/// KB-5 slugs are snake_case (`recall_past_actions`); registry names may be CamelCase (`ModelRouter`).
+fn matches_skill(slug: &str, name: &str) -> bool {
+    slug == name || slug == to_snake_case(name)
+}
+
+fn to_snake_case(name: &str) -> String {
+    let mut out = String::with_capacity(name.len() + 4);
+    for (i, c) in name.chars().enumerate() {
+        if c.is_ascii_uppercase() {
+            if i > 0 {
+                out.push('_');
+            }
+            out.push(c.to_ascii_lowercase());
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+fn tool_manifest(name: &str, record: Option<&SkillRecord>) -> serde_json::Value {
+    let description = record
+        .map(|r| r.description.clone())
+        .unwrap_or_else(|| format!("PAGI skill {}.", name));
+    let input_schema = record
+        .map(SkillRecord::input_schema)
+        .unwrap_or_else(|| serde_json::json!({ "type": "object" }));
+    serde_json::json!({ "name": name, "description": description, "inputSchema": input_schema })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pagi_core::{AgentSkill, SkillRegistry};
+
+    struct Echo;
+
+    #[async_trait::async_trait]
+    impl AgentSkill for Echo {
+        fn name(&self) -> &str {
+            "recall_past_actions"
+        }
+
+        async fn execute(
+            &self,
+            _ctx: &TenantContext,
+            payload: Option<serde_json::Value>,
+        ) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>> {
+            match payload.as_ref().and_then(|p| p.get("fail")) {
+                Some(_) => Err("boom".into()),
+                None => Ok(serde_json::json!({ "echo": payload })),
+            }
+        }
+    }
+
+    fn server() -> McpServer {
+        let knowledge = Arc::new(KnowledgeStore::open_temporary().unwrap());
+        pagi_core::initialize_core_skills(&knowledge).unwrap();
+        let mut registry = SkillRegistry::new();
+        registry.register(Arc::new(Echo));
+        McpServer::new(Arc::new(Orchestrator::new(Arc::new(registry))), knowledge, "mcp-test")
+    }
+
+    #[tokio::test]
+    async fn lists_tools_from_skill_records_and_calls_them() {
+        let server = server();
+        let init = server
+            .handle_line(r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{"protocolVersion":"2025-03-26"}}"#)
+            .await
+            .unwrap();
+        assert_eq!(init["result"]["protocolVersion"], "2025-03-26");
+        assert!(server.handle_line(r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#).await.is_none());
+
+        let list = server.handle_line(r#"{"jsonrpc":"2.0","id":2,"method":"tools/list"}"#).await.unwrap();
+        let tool = &list["result"]["tools"][0];
+        assert_eq!(tool["name"], "recall_past_actions");
+        assert!(tool["description"].as_str().unwrap().contains("KB_CHRONOS"));
+        assert_eq!(tool["inputSchema"]["properties"]["limit"]["type"], "number");
+
+        let call = server
+            .handle_line(r#"{"jsonrpc":"2.0","id":3,"method":"tools/call","params":{"name":"recall_past_actions","arguments":{"limit":2}}}"#)
+            .await
+            .unwrap();
+        assert_eq!(call["result"]["isError"], false);
+        assert!(call["result"]["content"][0]["text"].as_str().unwrap().contains("\"limit\": 2"));
+
+        let failed = server
+            .handle_line(r#"{"jsonrpc":"2.0","id":4,"method":"tools/call","params":{"name":"recall_past_actions","arguments":{"fail":true}}}"#)
+            .await
+            .unwrap();
+        assert_eq!(failed["result"]["isError"], true);
+        assert_eq!(failed["result"]["content"][0]["text"], "boom");
+    }
+
+    #[tokio::test]
+    async fn protocol_errors_use_json_rpc_codes() {
+        let server = server();
+        let unknown = server
+            .handle_line(r#"{"jsonrpc":"2.0","id":1,"method":"tools/call","params":{"name":"Nope"}}"#)
+            .await
+            .unwrap();
+        assert_eq!(unknown["error"]["code"], INVALID_PARAMS);
+        let missing = server.handle_line(r#"{"jsonrpc":"2.0","id":2,"method":"resources/list"}"#).await.unwrap();
+        assert_eq!(missing["error"]["code"], METHOD_NOT_FOUND);
+        let garbage = server.handle_line("{not json").await.unwrap();
+        assert_eq!(garbage["error"]["code"], PARSE_ERROR);
+        assert_eq!(garbage["id"], serde_json::Value::Null);
+    }
+
+    #[test]
+    fn skill_record_hints_become_json_schema() {
+        let record = SkillRecord {
+            slug: "write_sandbox_file".to_string(),
+            description: String::new(),
+            schema: serde_json::json!({
+                "path": "string (required; within research_sandbox/)",
+                "append": "boolean (optional; default false)"
+            }),
+        };
+        let schema = record.input_schema();
+        assert_eq!(schema["properties"]["append"]["type"], "boolean");
+        assert_eq!(schema["required"], serde_json::json!(["path"]));
+        assert!(matches_skill("model_router", "ModelRouter"));
+    }
+}
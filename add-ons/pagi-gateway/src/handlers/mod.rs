@@ -1,3 +1,77 @@
 //! Gateway request handlers. Chat is wired to PAGI Core context (Soma, Kardia, Ethos, Shadow).
+//! The API recorder middleware captures sampled exchanges to KB-8 for integration debugging.
+//! Standing queries turn KB lookups into subscriptions that alert when their results change.
+//! The archive endpoints move cold records to compressed segment files and restore them.
+//! The audit endpoint pages through the per-dispatch audit log for compliance reviews.
+//! Re-embedding jobs regenerate stored vectors after the embedding model changes.
+//! The lead pipeline endpoint moves captured leads between statuses (logged to Chronos).
+//! Document uploads extract, chunk and embed PDF, DOCX and Markdown files into a slot.
+//! The knowledge change feed streams KB inserts and removals to the dashboard over SSE.
+//! Snapshots copy the knowledge store to timestamped directories and roll slots or all of it back.
+//! Knowledge export and import move the records of slots 1–8 between gateways as one JSON document.
+//! Schedules are delayed or recurring goals the Heartbeat dispatches when due.
+//! Slack slash commands run as autonomous goals and post their results back to the channel.
+//! Ingest sources turn signed Typeform, Calendly or generic JSON webhooks into captured leads.
+//! Webhooks notify external services of lead captures, drafts, policy violations and maintenance.
+//! Scrape sources are pages the Heartbeat refreshes into a slot once they go stale.
+//! Background jobs run `/v1/execute?async=true` goals off the request and report their status.
+//! Tenant endpoints expose each tenant's view of the skill registry; `/v1/skills` adds health.
+//! Ethos policy rules scope allow, deny and require-approval decisions to skills and payload fields.
+//! Approvals hold calls that need a human decision and resume them once approved.
+//! Prompt templates let operators retune the agent's prompts (with version history) at runtime.
+//! Agent lifecycle endpoints register, list and archive the agents the Heartbeat serves.
+//! Persona profiles set each agent's system prompt, tone, boundaries and preferred model.
+//! Session history returns the conversation turns chat recorded under a session id.
+//! The usage endpoint reports a tenant's LLM token usage against its monthly budget.
+//! Domain packs install versioned bundles of blueprints, templates, KB seeds and policies per agent.
+//! The MCP module serves the skill registry as tools over stdio (`--mcp`) instead of HTTP.
+//! The console (`--repl`) runs goals typed in a shorthand against the stores, tracing plan steps.
+//! The WebSocket channel carries chat, goal progress and control-panel state on one connection.
+//! The sandbox endpoints expose `research_sandbox/` as a remote workspace with a change feed.
+//! The auth layer checks each route against the role of the caller's API key; keys are managed here too.
+//! The config reload endpoint re-reads CoreConfig and applies the fields that can change while running.
+//! The readiness probe checks the stores, vault, LLM providers, dispatch queue and disk before traffic is sent.
+//! The body limit layer refuses request bodies over the route's `[limits]` size.
+//! The request log layer logs method, path, status, latency and body sizes of each request.
+//! Trace replay re-runs a recorded goal trace against the current skills and diffs each step.
+//! Eval suites score goal outputs against assertions and keep each suite's score trend in KB-3.
 
+pub mod agents;
+pub mod approvals;
+pub mod archive;
+pub mod audit;
+pub mod auth;
+pub mod blueprints;
+pub mod body_limit;
+pub mod changes;
 pub mod chat;
+pub mod documents;
+pub mod ethos;
+pub mod evals;
+pub mod export;
+pub mod ingest;
+pub mod jobs;
+pub mod leads;
+pub mod mcp;
+pub mod packs;
+pub mod personas;
+pub mod prompts;
+pub mod readiness;
+pub mod recorder;
+pub mod reembed;
+pub mod reload;
+pub mod request_log;
+pub mod repl;
+pub mod sandbox;
+pub mod schedules;
+pub mod sessions;
+pub mod skills;
+pub mod slack;
+pub mod snapshots;
+pub mod sources;
+pub mod standing_queries;
+pub mod tenants;
+pub mod traces;
+pub mod usage;
+pub mod webhooks;
+pub mod ws;
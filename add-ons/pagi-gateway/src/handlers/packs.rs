@@ -0,0 +1,103 @@
+//! Domain packs: install, upgrade and remove bundles of blueprints, templates, KB seeds and
+//! policies per agent.
+//!
+//! Packs are applied by pagi-core ([`PackRegistry`]); intents go through the gateway's live
+//! blueprint registry, so they take effect without a restart. Offline installs use
+//! `pagi-gateway --install-pack <path> [--agent <id>] [--force]`.
+//!
+//! Routes:
+//! - `GET /api/v1/packs` – installed packs
+//! - `POST /api/v1/packs` – install or upgrade `{ pack, agent_id?, force? }`; 409 on a downgrade
+//!   without `force`
+//! - `DELETE /api/v1/packs/:agent_id/:name` – uninstall (reverts intents and KB keys)
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use pagi_core::{DomainPack, InstalledPack, PackDowngrade, PackRegistry, DEFAULT_AGENT_ID};
+use serde::Deserialize;
+use std::sync::Arc;
+
+use crate::AppState;
+
+#[derive(Debug, Deserialize)]
+pub struct InstallPack {
+    pub pack: DomainPack,
+    #[serde(default)]
+    pub agent_id: Option<String>,
+    /// Reinstall the same version or allow a downgrade.
+    #[serde(default)]
+    pub force: bool,
+}
+
+fn packs(state: &AppState) -> PackRegistry {
+    PackRegistry::new(Arc::clone(&state.knowledge))
+}
+
+fn error(status: StatusCode, message: impl std::fmt::Display) -> (StatusCode, Json<serde_json::Value>) {
+    (
+        status,
+        Json(serde_json::json!({ "status": "error", "error": message.to_string() })),
+    )
+}
+
+/// Installed pack without the replaced KB values.
+fn summary(pack: &InstalledPack) -> serde_json::Value {
+    serde_json::json!({
+        "name": pack.name,
+        "version": pack.version,
+        "agent_id": pack.agent_id,
+        "description": pack.description,
+        "installed_at_ms": pack.installed_at_ms,
+        "updated_at_ms": pack.updated_at_ms,
+        "intents": pack.intents,
+        "kb_keys": pack.kb.iter().map(|k| serde_json::json!({ "slot": k.slot, "key": k.key })).collect::<Vec<_>>(),
+    })
+}
+
+/// GET /api/v1/packs
+pub async fn list_packs(State(state): State<AppState>) -> Result<Json<serde_json::Value>, StatusCode> {
+    let installed = packs(&state).list().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(Json(serde_json::json!({
+        "status": "ok",
+        "count": installed.len(),
+        "packs": installed.iter().map(summary).collect::<Vec<_>>(),
+    })))
+}
+
+/// POST /api/v1/packs
+pub async fn install_pack(
+    State(state): State<AppState>,
+    Json(req): Json<InstallPack>,
+) -> Result<Json<serde_json::Value>, (StatusCode, Json<serde_json::Value>)> {
+    let agent_id = req
+        .agent_id
+        .filter(|a| !a.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_AGENT_ID.to_string());
+    let skills = state.orchestrator.skill_names();
+    let report = packs(&state)
+        .install(&req.pack, &agent_id, state.orchestrator.blueprint(), Some(&skills), req.force)
+        .map_err(|e| {
+            let status = if e.downcast_ref::<PackDowngrade>().is_some() {
+                StatusCode::CONFLICT
+            } else {
+                StatusCode::BAD_REQUEST
+            };
+            error(status, e)
+        })?;
+    Ok(Json(serde_json::json!({ "status": "ok", "install": report })))
+}
+
+/// DELETE /api/v1/packs/:agent_id/:name
+pub async fn uninstall_pack(
+    State(state): State<AppState>,
+    Path((agent_id, name)): Path<(String, String)>,
+) -> Result<Json<serde_json::Value>, (StatusCode, Json<serde_json::Value>)> {
+    let removed = packs(&state)
+        .uninstall(&agent_id, &name, state.orchestrator.blueprint())
+        .map_err(|e| error(StatusCode::INTERNAL_SERVER_ERROR, e))?
+        .ok_or_else(|| error(StatusCode::NOT_FOUND, format!("pack {} is not installed for {}", name, agent_id)))?;
+    Ok(Json(serde_json::json!({ "status": "ok", "removed": summary(&removed) })))
+}
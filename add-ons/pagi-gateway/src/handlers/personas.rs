@@ -0,0 +1,80 @@
+//! Persona profiles: how each agent speaks (system prompt, tone, boundaries) and which model and
+//! temperature it speaks with by default.
+//!
+//! A profile lives in KB_PNEUMA under `pneuma/{agent_id}/persona_profile`
+//! ([`KnowledgeStore::put_persona_profile`](pagi_core::KnowledgeStore::put_persona_profile)). It
+//! goes into the agent's Mission Directive, so every chat and Heartbeat generation for the agent
+//! carries it, and fills in `model` / `temperature` when a chat request leaves them unset. `PUT`
+//! and `DELETE` need the `kb_admin` permission (see `auth`).
+//!
+//! Routes:
+//! - `GET /api/v1/agents/:id/persona` – the agent's profile
+//! - `PUT /api/v1/agents/:id/persona` – save
+//!   `{ system_prompt, tone?, boundaries?, preferred_model?, temperature? }`
+//! - `DELETE /api/v1/agents/:id/persona` – remove it (the agent falls back to its plain-text persona)
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use pagi_core::PersonaProfile;
+use std::sync::Arc;
+
+use crate::AppState;
+
+fn error(status: StatusCode, message: impl Into<String>) -> (StatusCode, Json<serde_json::Value>) {
+    (status, Json(serde_json::json!({ "status": "error", "error": message.into() })))
+}
+
+/// GET /api/v1/agents/:id/persona
+pub async fn get_persona(
+    State(state): State<AppState>,
+    Path(agent_id): Path<String>,
+) -> (StatusCode, Json<serde_json::Value>) {
+    let knowledge = Arc::clone(&state.knowledge);
+    let id = agent_id.clone();
+    match tokio::task::spawn_blocking(move || knowledge.get_persona_profile(&id)).await {
+        Ok(Some(persona)) => (StatusCode::OK, Json(serde_json::json!({ "status": "ok", "persona": persona }))),
+        Ok(None) => error(StatusCode::NOT_FOUND, format!("agent {:?} has no persona profile", agent_id)),
+        Err(e) => error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    }
+}
+
+/// PUT /api/v1/agents/:id/persona
+pub async fn put_persona(
+    State(state): State<AppState>,
+    Path(agent_id): Path<String>,
+    Json(profile): Json<PersonaProfile>,
+) -> (StatusCode, Json<serde_json::Value>) {
+    if agent_id.is_empty() || agent_id.contains('/') {
+        return error(StatusCode::BAD_REQUEST, format!("invalid agent id {:?}", agent_id));
+    }
+    if let Err(e) = profile.validate() {
+        return error(StatusCode::BAD_REQUEST, e.to_string());
+    }
+    let knowledge = Arc::clone(&state.knowledge);
+    match tokio::task::spawn_blocking(move || knowledge.put_persona_profile(&agent_id, &profile)).await {
+        Ok(Ok(persona)) => {
+            tracing::info!(target: "pagi::personas", agent_id = %persona.agent_id, "Persona profile saved");
+            (StatusCode::OK, Json(serde_json::json!({ "status": "ok", "persona": persona })))
+        }
+        Ok(Err(e)) => error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+        Err(e) => error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    }
+}
+
+/// DELETE /api/v1/agents/:id/persona
+pub async fn delete_persona(
+    State(state): State<AppState>,
+    Path(agent_id): Path<String>,
+) -> (StatusCode, Json<serde_json::Value>) {
+    let knowledge = Arc::clone(&state.knowledge);
+    let id = agent_id.clone();
+    match tokio::task::spawn_blocking(move || knowledge.remove_persona_profile(&id)).await {
+        Ok(Ok(true)) => (StatusCode::OK, Json(serde_json::json!({ "status": "ok", "removed": agent_id }))),
+        Ok(Ok(false)) => error(StatusCode::NOT_FOUND, format!("agent {:?} has no persona profile", agent_id)),
+        Ok(Err(e)) => error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+        Err(e) => error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    }
+}
@@ -0,0 +1,136 @@
+//! Prompt templates: the prompts the agent speaks with (system persona, heartbeat status updates,
+//! Thalamus routing, ...), tunable without recompiling.
+//!
+//! Templates use `{{variable}}` placeholders and live in KB_PNEUMA with every saved version kept
+//! ([`KnowledgeStore::put_prompt`](pagi_core::KnowledgeStore::put_prompt)). A prompt never saved
+//! is served as its built-in default (version 0). Overrides of a built-in may only use the
+//! variables its call site fills in. `PUT` needs the `kb_admin` permission (see `auth`).
+//!
+//! Routes:
+//! - `GET /api/v1/prompts` – every prompt (current versions)
+//! - `GET /api/v1/prompts/:name` – the current version, its variables and its history;
+//!   `?version=N` returns that saved version instead
+//! - `PUT /api/v1/prompts/:name` – save `{ template, description? }` as the next version
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use pagi_core::{is_valid_prompt_name, PromptTemplate};
+use serde::Deserialize;
+use std::sync::Arc;
+
+use crate::AppState;
+
+fn error(status: StatusCode, message: impl Into<String>) -> (StatusCode, Json<serde_json::Value>) {
+    (status, Json(serde_json::json!({ "status": "error", "error": message.into() })))
+}
+
+fn prompt_json(prompt: &PromptTemplate) -> serde_json::Value {
+    serde_json::json!({
+        "name": prompt.name,
+        "description": prompt.description,
+        "version": prompt.version,
+        "updated_at_ms": prompt.updated_at_ms,
+        "builtin": prompt.version == 0,
+        "variables": prompt.variables(),
+        "template": prompt.template,
+    })
+}
+
+/// GET /api/v1/prompts
+pub async fn list_prompts(State(state): State<AppState>) -> (StatusCode, Json<serde_json::Value>) {
+    let knowledge = Arc::clone(&state.knowledge);
+    match tokio::task::spawn_blocking(move || knowledge.list_prompts()).await {
+        Ok(Ok(prompts)) => (
+            StatusCode::OK,
+            Json(serde_json::json!({
+                "status": "ok",
+                "count": prompts.len(),
+                "prompts": prompts.iter().map(prompt_json).collect::<Vec<_>>(),
+            })),
+        ),
+        Ok(Err(e)) => error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+        Err(e) => error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct PromptQuery {
+    #[serde(default)]
+    pub version: Option<u32>,
+}
+
+/// GET /api/v1/prompts/:name
+pub async fn get_prompt(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    Query(query): Query<PromptQuery>,
+) -> (StatusCode, Json<serde_json::Value>) {
+    if !is_valid_prompt_name(&name) {
+        return error(StatusCode::BAD_REQUEST, format!("invalid prompt name {:?}", name));
+    }
+    let knowledge = Arc::clone(&state.knowledge);
+    let key = name.clone();
+    let loaded = tokio::task::spawn_blocking(move || {
+        knowledge.prompt_versions(&key).map(|versions| (knowledge.get_prompt(&key), versions))
+    })
+    .await;
+    let (current, versions) = match loaded.map_err(|e| e.to_string()).and_then(|r| r.map_err(|e| e.to_string())) {
+        Ok(loaded) => loaded,
+        Err(e) => return error(StatusCode::INTERNAL_SERVER_ERROR, e),
+    };
+    let prompt = match query.version {
+        Some(0) => PromptTemplate::builtin(&name),
+        Some(version) => versions.iter().find(|p| p.version == version).cloned(),
+        None => current,
+    };
+    let Some(prompt) = prompt else {
+        return match query.version {
+            Some(version) => error(StatusCode::NOT_FOUND, format!("prompt {:?} has no version {}", name, version)),
+            None => error(StatusCode::NOT_FOUND, format!("prompt {:?} not found", name)),
+        };
+    };
+    (
+        StatusCode::OK,
+        Json(serde_json::json!({
+            "status": "ok",
+            "prompt": prompt_json(&prompt),
+            "versions": versions
+                .iter()
+                .map(|p| serde_json::json!({ "version": p.version, "updated_at_ms": p.updated_at_ms }))
+                .collect::<Vec<_>>(),
+        })),
+    )
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PutPromptRequest {
+    pub template: String,
+    /// Kept from the previous version when absent.
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+/// PUT /api/v1/prompts/:name
+pub async fn put_prompt(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    Json(req): Json<PutPromptRequest>,
+) -> (StatusCode, Json<serde_json::Value>) {
+    if let Err(e) = PromptTemplate::validate(&name, &req.template) {
+        return error(StatusCode::BAD_REQUEST, e.to_string());
+    }
+    let knowledge = Arc::clone(&state.knowledge);
+    let saved = tokio::task::spawn_blocking(move || knowledge.put_prompt(&name, &req.template, req.description.as_deref()))
+        .await;
+    match saved {
+        Ok(Ok(prompt)) => {
+            tracing::info!(target: "pagi::prompts", prompt = %prompt.name, version = prompt.version, "Prompt template saved");
+            (StatusCode::OK, Json(serde_json::json!({ "status": "ok", "prompt": prompt_json(&prompt) })))
+        }
+        Ok(Err(e)) => error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+        Err(e) => error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    }
+}
@@ -0,0 +1,185 @@
+//! Readiness probe: whether this gateway can serve traffic now, for Kubernetes readiness probes
+//! and load balancers. `/api/v1/health` stays a liveness check that only says the process is up.
+//!
+//! Checks, each `ok`, `warn` (reported, still ready) or `fail` (503):
+//! - `knowledge` – every tree (slots 1–9) opens and answers a read
+//! - `shadow_vault` – fails when `PAGI_SHADOW_KEY` is set but the ShadowStore did not open, or
+//!   slots in `[encryption]` cannot be read because the vault is locked
+//! - `llm_providers` – with `llm_mode = "live"`, fails when every `[llm_providers]` entry is
+//!   marked down. Uses the health recorded from real calls; the probe sends no requests.
+//! - `dispatch_queue` – fails while every execution slot is busy and the queue is full (new
+//!   goals would get 429)
+//! - `disk` – fails when the filesystem of `storage_path` has less than
+//!   `PAGI_READY_MIN_FREE_MB` (default 500) MB free
+//!
+//! The route is public, like `/api/v1/health`, so probes need no API key.
+//!
+//! Routes:
+//! - `GET /api/v1/ready` – 200 `{ status: "ready", checks }`, or 503 `{ status: "not_ready", failures, checks }`
+
+use axum::{extract::State, http::StatusCode, Json};
+use serde::Serialize;
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::AppState;
+
+const DEFAULT_MIN_FREE_MB: u64 = 500;
+
+/// Outcome of one check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckStatus {
+    Ok,
+    Warn,
+    Fail,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Check {
+    pub name: &'static str,
+    pub status: CheckStatus,
+    /// Why the check warned or failed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    pub details: serde_json::Value,
+}
+
+impl Check {
+    fn new(name: &'static str, details: serde_json::Value) -> Self {
+        Self { name, status: CheckStatus::Ok, reason: None, details }
+    }
+
+    fn warn(mut self, reason: impl Into<String>) -> Self {
+        self.status = CheckStatus::Warn;
+        self.reason = Some(reason.into());
+        self
+    }
+
+    fn fail(mut self, reason: impl Into<String>) -> Self {
+        self.status = CheckStatus::Fail;
+        self.reason = Some(reason.into());
+        self
+    }
+}
+
+fn min_free_bytes() -> u64 {
+    std::env::var("PAGI_READY_MIN_FREE_MB")
+        .ok()
+        .and_then(|s| s.trim().parse::<u64>().ok())
+        .unwrap_or(DEFAULT_MIN_FREE_MB)
+        .saturating_mul(1024 * 1024)
+}
+
+/// Bytes available to unprivileged writers on the filesystem holding `path`.
+#[cfg(unix)]
+fn available_bytes(path: &Path) -> std::io::Result<u64> {
+    use std::os::unix::ffi::OsStrExt;
+    let path = std::ffi::CString::new(path.as_os_str().as_bytes())?;
+    // SAFETY: `path` is NUL-terminated and `stat` is a plain C struct statvfs fills in.
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statvfs(path.as_ptr(), &mut stat) } != 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    // The field types differ between platforms.
+    #[allow(clippy::unnecessary_cast)]
+    Ok((stat.f_bavail as u64).saturating_mul(stat.f_frsize as u64))
+}
+
+#[cfg(not(unix))]
+fn available_bytes(_path: &Path) -> std::io::Result<u64> {
+    Err(std::io::Error::new(std::io::ErrorKind::Unsupported, "free space is only checked on Unix"))
+}
+
+fn disk_check(storage_path: &Path, min_free: u64) -> Check {
+    let mb = |bytes: u64| bytes / (1024 * 1024);
+    match available_bytes(storage_path) {
+        Ok(free) => {
+            let check = Check::new("disk", serde_json::json!({ "free_mb": mb(free), "min_free_mb": mb(min_free) }));
+            if free < min_free {
+                check.fail(format!("{} MB free under {}, below {} MB", mb(free), storage_path.display(), mb(min_free)))
+            } else {
+                check
+            }
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::Unsupported => {
+            Check::new("disk", serde_json::Value::Null).warn(e.to_string())
+        }
+        Err(e) => Check::new("disk", serde_json::Value::Null).fail(format!("cannot read free space of {}: {}", storage_path.display(), e)),
+    }
+}
+
+/// GET /api/v1/ready
+pub async fn ready(State(state): State<AppState>) -> (StatusCode, Json<serde_json::Value>) {
+    let config = state.config.get();
+    let mut checks = Vec::new();
+
+    let knowledge = Arc::clone(&state.knowledge);
+    let storage_path = config.storage_path.clone();
+    let (failed_trees, disk) = tokio::task::spawn_blocking(move || {
+        (knowledge.probe_trees(), disk_check(Path::new(&storage_path), min_free_bytes()))
+    })
+    .await
+    .unwrap_or_else(|e| (vec![(0, e.to_string())], Check::new("disk", serde_json::Value::Null).fail(e.to_string())));
+    let trees = Check::new("knowledge", serde_json::json!({ "failed_slots": failed_trees.iter().map(|(slot, _)| slot).collect::<Vec<_>>() }));
+    checks.push(match failed_trees.first() {
+        Some((slot, error)) => trees.fail(format!("slot {} does not answer: {}", slot, error)),
+        None => trees,
+    });
+
+    let unlocked = state.knowledge.vault().is_unlocked();
+    let key_set = pagi_core::secret(pagi_core::SECRET_SHADOW_KEY).is_some();
+    let store_open = state.shadow_store.read().await.is_some();
+    let encrypted: Vec<u8> = (1..=8).filter(|slot| state.knowledge.is_slot_encrypted(*slot)).collect();
+    let vault = Check::new(
+        "shadow_vault",
+        serde_json::json!({ "state": if unlocked { "unlocked" } else { "locked" }, "shadow_store": store_open, "encrypted_slots": encrypted }),
+    );
+    checks.push(if key_set && !store_open {
+        vault.fail("PAGI_SHADOW_KEY is set but the ShadowStore did not open")
+    } else if !unlocked && !encrypted.is_empty() {
+        vault.fail(format!("the vault is locked, so encrypted slots {:?} cannot be read", encrypted))
+    } else {
+        vault
+    });
+
+    let providers = state.llm_providers.health();
+    let down: Vec<&str> = providers.iter().filter(|p| p.status == "down").map(|p| p.provider.as_str()).collect();
+    let llm = Check::new("llm_providers", serde_json::json!({ "llm_mode": config.llm_mode, "configured": providers.len(), "down": down }));
+    checks.push(if config.llm_mode != "live" || down.is_empty() {
+        llm
+    } else if down.len() == providers.len() {
+        llm.fail(format!("every LLM provider is down: {}", down.join(", ")))
+    } else {
+        llm.warn(format!("LLM provider(s) down: {}", down.join(", ")))
+    });
+
+    checks.push(match state.orchestrator.dispatch_queue().map(|q| q.stats()) {
+        Some(stats) => {
+            let queue = Check::new("dispatch_queue", serde_json::to_value(&stats).unwrap_or_default());
+            if stats.running >= stats.max_concurrent && stats.queued >= stats.max_queued {
+                queue.fail(format!("all {} execution slots busy and {} goal(s) queued (max {})", stats.running, stats.queued, stats.max_queued))
+            } else {
+                queue
+            }
+        }
+        None => Check::new("dispatch_queue", serde_json::Value::Null),
+    });
+
+    checks.push(disk);
+
+    let failures: Vec<String> = checks
+        .iter()
+        .filter(|c| c.status == CheckStatus::Fail)
+        .map(|c| format!("{}: {}", c.name, c.reason.as_deref().unwrap_or_default()))
+        .collect();
+    if failures.is_empty() {
+        (StatusCode::OK, Json(serde_json::json!({ "status": "ready", "checks": checks })))
+    } else {
+        tracing::debug!(target: "pagi::gateway", failures = ?failures, "Readiness check failed");
+        (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(serde_json::json!({ "status": "not_ready", "failures": failures, "checks": checks })),
+        )
+    }
+}
@@ -0,0 +1,606 @@
+//! API Recorder: opt-in request/response capture for debugging integrations.
+//!
+//! When `PAGI_API_RECORDER=true`, sampled exchanges are written to **KB-8** (Internal Research)
+//! under `api_recorder/{timestamp_ms}_{uuid}` and can be browsed via `GET /api/v1/recorder/exchanges`.
+//!
+//! Configuration (all env, all optional):
+//! - `PAGI_API_RECORDER_SAMPLE_RATE` – default sampling rate for every route (0.0–1.0, default `1.0`).
+//! - `PAGI_API_RECORDER_ROUTES` – per-route overrides, e.g. `/api/v1/chat=1.0,/v1/execute=0.1`
+//!   (longest matching path prefix wins; `0` disables a route).
+//! - `PAGI_API_RECORDER_MAX_BODY` – max bytes read and captured per body (default `16384`); larger
+//!   bodies stream through untouched and the exchange is recorded as `truncated`.
+//! - `PAGI_API_RECORDER_REDACT` – extra header / JSON field names to redact (comma-separated).
+//! - `PAGI_API_RECORDER_RETENTION` – max stored exchanges (default `500`); oldest are pruned first.
+//! - `PAGI_API_RECORDER_TTL_SECS` – optional max age of stored exchanges.
+
+use axum::{
+    body::{Body, Bytes},
+    extract::{Path, Query, Request, State},
+    http::{header, HeaderMap, StatusCode},
+    middleware::Next,
+    response::Response,
+};
+use futures_util::StreamExt;
+use pagi_core::KnowledgeStore;
+use serde::Deserialize;
+use std::sync::Arc;
+use std::time::Instant;
+
+use crate::AppState;
+
+/// KB-8 (Internal Research) holds recorded exchanges alongside research traces.
+const KB_SLOT_API_RECORDER: u8 = 8;
+/// Key prefix for recorded exchanges in KB-8.
+pub const API_RECORDER_PREFIX: &str = "api_recorder/";
+/// The browse endpoint itself is never recorded.
+pub const API_RECORDER_ROUTE: &str = "/api/v1/recorder/exchanges";
+
+const REDACTED: &str = "[REDACTED]";
+const DEFAULT_MAX_BODY: usize = 16 * 1024;
+const DEFAULT_RETENTION: usize = 500;
+const DEFAULT_REDACT: &[&str] = &[
+    "authorization",
+    "proxy-authorization",
+    "cookie",
+    "set-cookie",
+    "x-api-key",
+    "api_key",
+    "apikey",
+    "password",
+    "secret",
+    "token",
+];
+
+/// Recorder settings (see module docs for the env variables).
+#[derive(Debug, Clone)]
+pub struct RecorderConfig {
+    pub default_sample_rate: f64,
+    /// `(path_prefix, rate)` overrides; the longest matching prefix wins.
+    pub route_sample_rates: Vec<(String, f64)>,
+    pub max_body_bytes: usize,
+    /// Lower-cased header / JSON field names whose values are replaced with `[REDACTED]`.
+    pub redact: Vec<String>,
+    pub retention: usize,
+    pub ttl_secs: Option<u64>,
+}
+
+impl Default for RecorderConfig {
+    fn default() -> Self {
+        Self {
+            default_sample_rate: 1.0,
+            route_sample_rates: Vec::new(),
+            max_body_bytes: DEFAULT_MAX_BODY,
+            redact: DEFAULT_REDACT.iter().map(|s| s.to_string()).collect(),
+            retention: DEFAULT_RETENTION,
+            ttl_secs: None,
+        }
+    }
+}
+
+impl RecorderConfig {
+    /// Returns `None` unless the recorder is enabled via `PAGI_API_RECORDER`.
+    pub fn from_env() -> Option<Self> {
+        let enabled = std::env::var("PAGI_API_RECORDER")
+            .map(|v| matches!(v.trim().to_lowercase().as_str(), "1" | "true" | "yes" | "on"))
+            .unwrap_or(false);
+        if !enabled {
+            return None;
+        }
+        let mut config = Self::default();
+        if let Some(rate) = env_parse::<f64>("PAGI_API_RECORDER_SAMPLE_RATE") {
+            config.default_sample_rate = rate.clamp(0.0, 1.0);
+        }
+        if let Ok(routes) = std::env::var("PAGI_API_RECORDER_ROUTES") {
+            config.route_sample_rates = parse_route_rates(&routes);
+        }
+        if let Some(max) = env_parse::<usize>("PAGI_API_RECORDER_MAX_BODY") {
+            config.max_body_bytes = max;
+        }
+        if let Ok(extra) = std::env::var("PAGI_API_RECORDER_REDACT") {
+            config.redact.extend(
+                extra
+                    .split(',')
+                    .map(|s| s.trim().to_lowercase())
+                    .filter(|s| !s.is_empty()),
+            );
+        }
+        if let Some(retention) = env_parse::<usize>("PAGI_API_RECORDER_RETENTION") {
+            config.retention = retention.max(1);
+        }
+        config.ttl_secs = env_parse::<u64>("PAGI_API_RECORDER_TTL_SECS");
+        Some(config)
+    }
+
+    /// Sampling rate for `path`: longest matching route prefix, else the default rate.
+    pub fn sample_rate_for(&self, path: &str) -> f64 {
+        self.route_sample_rates
+            .iter()
+            .filter(|(prefix, _)| path.starts_with(prefix.as_str()))
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map(|(_, rate)| *rate)
+            .unwrap_or(self.default_sample_rate)
+    }
+
+    fn should_redact(&self, name: &str) -> bool {
+        let name = name.to_lowercase();
+        self.redact.iter().any(|r| name.contains(r.as_str()))
+    }
+}
+
+fn env_parse<T: std::str::FromStr>(key: &str) -> Option<T> {
+    std::env::var(key).ok().and_then(|s| s.trim().parse::<T>().ok())
+}
+
+/// Parses `"/api/v1/chat=1.0,/v1/execute=0.1"` into `(prefix, rate)` pairs. Invalid entries are skipped.
+fn parse_route_rates(spec: &str) -> Vec<(String, f64)> {
+    spec.split(',')
+        .filter_map(|entry| {
+            let (path, rate) = entry.split_once('=')?;
+            let rate = rate.trim().parse::<f64>().ok()?;
+            let path = path.trim();
+            if path.is_empty() {
+                return None;
+            }
+            Some((path.to_string(), rate.clamp(0.0, 1.0)))
+        })
+        .collect()
+}
+
+/// Middleware state: recorder settings plus the store recorded exchanges are written to.
+#[derive(Clone)]
+pub struct ApiRecorder {
+    pub config: Arc<RecorderConfig>,
+    pub knowledge: Arc<KnowledgeStore>,
+}
+
+impl ApiRecorder {
+    pub fn new(config: RecorderConfig, knowledge: Arc<KnowledgeStore>) -> Self {
+        Self {
+            config: Arc::new(config),
+            knowledge,
+        }
+    }
+
+    fn sampled(&self, path: &str) -> bool {
+        let rate = self.config.sample_rate_for(path);
+        if rate >= 1.0 {
+            return true;
+        }
+        if rate <= 0.0 {
+            return false;
+        }
+        // uuid v4 is random; reuse it instead of pulling in a rand dependency.
+        let roll = (uuid::Uuid::new_v4().as_u128() % 10_000) as f64 / 10_000.0;
+        roll < rate
+    }
+
+    /// Writes one exchange to KB-8 and applies retention. Errors are logged, never surfaced.
+    fn store(&self, exchange: &serde_json::Value) {
+        let ts = exchange.get("timestamp_ms").and_then(|v| v.as_i64()).unwrap_or(0);
+        let id = exchange.get("id").and_then(|v| v.as_str()).unwrap_or_default();
+        let key = format!("{}{:013}_{}", API_RECORDER_PREFIX, ts, id);
+        let bytes = serde_json::to_vec(exchange).unwrap_or_default();
+        if let Err(e) = self.knowledge.insert(KB_SLOT_API_RECORDER, &key, &bytes) {
+            tracing::warn!(target: "pagi::recorder", error = %e, "API recorder: failed to store exchange");
+            return;
+        }
+        if let Err(e) = prune(&self.knowledge, &self.config, ts) {
+            tracing::warn!(target: "pagi::recorder", error = %e, "API recorder: retention prune failed");
+        }
+    }
+}
+
+/// Removes exchanges beyond `retention` (oldest first) and older than `ttl_secs`.
+fn prune(knowledge: &KnowledgeStore, config: &RecorderConfig, now_ms: i64) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
+    let mut keys: Vec<String> = knowledge
+        .scan_keys(KB_SLOT_API_RECORDER)?
+        .into_iter()
+        .filter(|k| k.starts_with(API_RECORDER_PREFIX))
+        .collect();
+    // Zero-padded timestamps make lexicographic order chronological.
+    keys.sort();
+    let cutoff_ms = config.ttl_secs.map(|ttl| now_ms - (ttl as i64) * 1000);
+    let overflow = keys.len().saturating_sub(config.retention);
+    let mut removed = 0;
+    for (i, key) in keys.iter().enumerate() {
+        let expired = cutoff_ms.is_some_and(|cutoff| key_timestamp(key).is_some_and(|ts| ts < cutoff));
+        if i < overflow || expired {
+            knowledge.remove(KB_SLOT_API_RECORDER, key)?;
+            removed += 1;
+        }
+    }
+    Ok(removed)
+}
+
+fn key_timestamp(key: &str) -> Option<i64> {
+    key.strip_prefix(API_RECORDER_PREFIX)?
+        .split('_')
+        .next()?
+        .parse()
+        .ok()
+}
+
+fn redact_headers(headers: &HeaderMap, config: &RecorderConfig) -> serde_json::Value {
+    let mut out = serde_json::Map::new();
+    for (name, value) in headers {
+        let v = if config.should_redact(name.as_str()) {
+            REDACTED.to_string()
+        } else {
+            value.to_str().unwrap_or("<binary>").to_string()
+        };
+        out.insert(name.as_str().to_string(), serde_json::Value::String(v));
+    }
+    serde_json::Value::Object(out)
+}
+
+fn redact_json(value: &mut serde_json::Value, config: &RecorderConfig) {
+    match value {
+        serde_json::Value::Object(map) => {
+            for (k, v) in map.iter_mut() {
+                if config.should_redact(k) {
+                    *v = serde_json::Value::String(REDACTED.to_string());
+                } else {
+                    redact_json(v, config);
+                }
+            }
+        }
+        serde_json::Value::Array(items) => {
+            for v in items.iter_mut() {
+                redact_json(v, config);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Renders a captured body: JSON is redacted and kept structured; anything over
+/// `max_body_bytes` (after redaction) is stored as a truncated string. `cut_off` bodies were not
+/// read to the end: JSON among them cannot be parsed (so not redacted) and keeps no content.
+fn capture_body(bytes: &[u8], cut_off: bool, config: &RecorderConfig) -> serde_json::Value {
+    if bytes.is_empty() && !cut_off {
+        return serde_json::Value::Null;
+    }
+    let text = if cut_off {
+        if bytes.iter().find(|b| !b.is_ascii_whitespace()).is_some_and(|b| matches!(b, b'{' | b'[')) {
+            return serde_json::json!({ "truncated": true, "original_bytes": null, "content": null });
+        }
+        String::from_utf8_lossy(bytes).into_owned()
+    } else {
+        match serde_json::from_slice::<serde_json::Value>(bytes) {
+            Ok(mut json) => {
+                redact_json(&mut json, config);
+                let rendered = json.to_string();
+                if rendered.len() <= config.max_body_bytes {
+                    return json;
+                }
+                rendered
+            }
+            Err(_) => String::from_utf8_lossy(bytes).into_owned(),
+        }
+    };
+    if text.len() <= config.max_body_bytes && !cut_off {
+        return serde_json::Value::String(text);
+    }
+    let mut end = config.max_body_bytes.min(text.len());
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    serde_json::json!({
+        "truncated": true,
+        "original_bytes": (!cut_off).then_some(bytes.len()),
+        "content": &text[..end],
+    })
+}
+
+/// A body read up to a byte limit.
+struct CappedBody {
+    /// What was read: all of it, or the first chunks when `cut_off`.
+    bytes: Bytes,
+    /// The body went past the limit (or failed) before its end.
+    cut_off: bool,
+    /// The bytes read followed by the unread rest, to pass on.
+    body: Body,
+}
+
+/// Reads `body` until it ends or passes `limit` bytes; the rest is not buffered but streamed on
+/// through [`CappedBody::body`].
+async fn read_capped(body: Body, limit: usize) -> CappedBody {
+    let mut stream = body.into_data_stream();
+    let mut read = Vec::new();
+    loop {
+        match stream.next().await {
+            None => {
+                let bytes = Bytes::from(read);
+                return CappedBody {
+                    body: Body::from(bytes.clone()),
+                    bytes,
+                    cut_off: false,
+                };
+            }
+            Some(Ok(chunk)) => {
+                read.extend_from_slice(&chunk);
+                if read.len() > limit {
+                    let bytes = Bytes::from(read);
+                    let head = futures_util::stream::once(std::future::ready(Ok(bytes.clone())));
+                    return CappedBody {
+                        body: Body::from_stream(head.chain(stream)),
+                        bytes,
+                        cut_off: true,
+                    };
+                }
+            }
+            Some(Err(e)) => {
+                tracing::warn!(target: "pagi::recorder", error = %e, "API recorder: failed to read body");
+                let bytes = Bytes::from(read);
+                return CappedBody {
+                    body: Body::from_stream(futures_util::stream::iter([Ok(bytes.clone()), Err(e)])),
+                    bytes,
+                    cut_off: true,
+                };
+            }
+        }
+    }
+}
+
+fn is_event_stream(headers: &HeaderMap) -> bool {
+    headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.starts_with("text/event-stream"))
+        .unwrap_or(false)
+}
+
+/// Axum middleware: records sampled request/response exchanges. Streaming (SSE) responses
+/// pass through untouched and are recorded without a response body; bodies over
+/// `max_body_bytes` are only read that far and the exchange is marked `truncated`.
+pub async fn record_exchange(State(recorder): State<ApiRecorder>, req: Request, next: Next) -> Response {
+    let path = req.uri().path().to_string();
+    if path.starts_with(API_RECORDER_ROUTE) || !recorder.sampled(&path) {
+        return next.run(req).await;
+    }
+
+    let started = Instant::now();
+    let config = &recorder.config;
+    let (parts, body) = req.into_parts();
+    let request_body = read_capped(body, config.max_body_bytes).await;
+    let mut exchange = serde_json::json!({
+        "id": uuid::Uuid::new_v4().simple().to_string(),
+        "timestamp_ms": recorder.knowledge.now_ms(),
+        "method": parts.method.as_str(),
+        "path": path,
+        "query": parts.uri.query(),
+        "request": {
+            "headers": redact_headers(&parts.headers, config),
+            "body": capture_body(&request_body.bytes, request_body.cut_off, config),
+        },
+    });
+    let mut truncated = request_body.cut_off;
+
+    let response = next.run(Request::from_parts(parts, request_body.body)).await;
+
+    let (parts, body) = response.into_parts();
+    let (response, response_body) = if is_event_stream(&parts.headers) {
+        (Response::from_parts(parts, body), serde_json::json!("<event-stream>"))
+    } else {
+        let captured = read_capped(body, config.max_body_bytes).await;
+        truncated |= captured.cut_off;
+        let rendered = capture_body(&captured.bytes, captured.cut_off, config);
+        (Response::from_parts(parts, captured.body), rendered)
+    };
+
+    exchange["status"] = serde_json::json!(response.status().as_u16());
+    exchange["duration_ms"] = serde_json::json!(started.elapsed().as_millis() as u64);
+    exchange["response"] = serde_json::json!({
+        "headers": redact_headers(response.headers(), config),
+        "body": response_body,
+    });
+    exchange["truncated"] = serde_json::json!(truncated);
+
+    let recorder = recorder.clone();
+    tokio::task::spawn_blocking(move || recorder.store(&exchange));
+    response
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ExchangeQuery {
+    /// Max exchanges to return (default 50, capped at 500).
+    pub limit: Option<usize>,
+    /// Only return exchanges whose path starts with this prefix.
+    pub path: Option<String>,
+}
+
+/// Returns recorded exchanges from KB-8, newest first.
+pub fn recent_exchanges(
+    knowledge: &KnowledgeStore,
+    limit: usize,
+    path_prefix: Option<&str>,
+) -> Result<Vec<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>> {
+    let mut entries: Vec<(String, serde_json::Value)> = knowledge
+        .scan_kv(KB_SLOT_API_RECORDER)?
+        .into_iter()
+        .filter(|(k, _)| k.starts_with(API_RECORDER_PREFIX))
+        .filter_map(|(k, bytes)| serde_json::from_slice::<serde_json::Value>(&bytes).ok().map(|v| (k, v)))
+        .filter(|(_, v)| match path_prefix {
+            Some(p) => v.get("path").and_then(|x| x.as_str()).is_some_and(|x| x.starts_with(p)),
+            None => true,
+        })
+        .collect();
+    entries.sort_by(|a, b| b.0.cmp(&a.0));
+    Ok(entries.into_iter().take(limit).map(|(_, v)| v).collect())
+}
+
+/// GET /api/v1/recorder/exchanges – browse recent recorded exchanges (newest first).
+pub async fn list_exchanges(
+    State(state): State<AppState>,
+    Query(q): Query<ExchangeQuery>,
+) -> Result<axum::Json<serde_json::Value>, StatusCode> {
+    let limit = q.limit.unwrap_or(50).clamp(1, 500);
+    let exchanges = recent_exchanges(&state.knowledge, limit, q.path.as_deref())
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(axum::Json(serde_json::json!({
+        "status": "ok",
+        "enabled": RecorderConfig::from_env().is_some(),
+        "count": exchanges.len(),
+        "exchanges": exchanges,
+    })))
+}
+
+/// GET /api/v1/recorder/exchanges/:id – a single recorded exchange by id.
+pub async fn get_exchange(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<axum::Json<serde_json::Value>, StatusCode> {
+    let suffix = format!("_{}", id);
+    let found = state
+        .knowledge
+        .scan_kv(KB_SLOT_API_RECORDER)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .into_iter()
+        .find(|(k, _)| k.starts_with(API_RECORDER_PREFIX) && k.ends_with(&suffix))
+        .and_then(|(_, bytes)| serde_json::from_slice::<serde_json::Value>(&bytes).ok());
+    found.map(axum::Json).ok_or(StatusCode::NOT_FOUND)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn route_rates_use_longest_prefix() {
+        let config = RecorderConfig {
+            default_sample_rate: 0.5,
+            route_sample_rates: parse_route_rates("/api=0.2, /api/v1/chat=1.0, bogus, /x=nope"),
+            ..RecorderConfig::default()
+        };
+        assert_eq!(config.route_sample_rates.len(), 2);
+        assert_eq!(config.sample_rate_for("/api/v1/chat"), 1.0);
+        assert_eq!(config.sample_rate_for("/api/v1/health"), 0.2);
+        assert_eq!(config.sample_rate_for("/v1/execute"), 0.5);
+    }
+
+    #[test]
+    fn capture_body_redacts_and_truncates() {
+        let config = RecorderConfig {
+            max_body_bytes: 128,
+            ..RecorderConfig::default()
+        };
+        let body = serde_json::json!({"user": "a", "nested": {"api_key": "sk-123"}, "password": "x"});
+        let captured = capture_body(body.to_string().as_bytes(), false, &config);
+        assert_eq!(captured["nested"]["api_key"], REDACTED);
+        assert_eq!(captured["password"], REDACTED);
+        assert_eq!(captured["user"], "a");
+
+        let big = "é".repeat(100);
+        let captured = capture_body(big.as_bytes(), false, &config);
+        assert_eq!(captured["truncated"], true);
+        assert!(captured["content"].as_str().unwrap().len() <= 128);
+
+        // A cut-off JSON body cannot be redacted, so none of it is kept.
+        let captured = capture_body(br#"{"password":"hunter2","#, true, &config);
+        assert_eq!(captured["truncated"], true);
+        assert!(captured["content"].is_null());
+    }
+
+    #[test]
+    fn prune_applies_retention_and_ttl() {
+        let dir = std::env::temp_dir().join(format!("pagi_recorder_prune_{}", uuid::Uuid::new_v4().simple()));
+        let knowledge = KnowledgeStore::open_path(&dir).unwrap();
+        for ts in [1_000i64, 2_000, 3_000, 4_000] {
+            let key = format!("{}{:013}_{}", API_RECORDER_PREFIX, ts, ts);
+            knowledge.insert(KB_SLOT_API_RECORDER, &key, b"{}").unwrap();
+        }
+        let config = RecorderConfig {
+            retention: 3,
+            ttl_secs: Some(2),
+            ..RecorderConfig::default()
+        };
+        // Retention drops ts=1000; TTL (cutoff 2000) drops nothing further since 2000 is not < 2000.
+        assert_eq!(prune(&knowledge, &config, 4_000).unwrap(), 1);
+        let remaining = recent_exchanges(&knowledge, 10, None).unwrap();
+        assert_eq!(remaining.len(), 3);
+        drop(knowledge);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn middleware_records_redacted_exchange() {
+        use axum::{routing::post, Router};
+        use tower::ServiceExt;
+
+        let dir = std::env::temp_dir().join(format!("pagi_recorder_mw_{}", uuid::Uuid::new_v4().simple()));
+        let knowledge = Arc::new(KnowledgeStore::open_path(&dir).unwrap());
+        let recorder = ApiRecorder::new(RecorderConfig::default(), Arc::clone(&knowledge));
+        let app = Router::new()
+            .route("/echo", post(|body: String| async move { body }))
+            .layer(axum::middleware::from_fn_with_state(recorder, record_exchange));
+
+        let req = axum::http::Request::builder()
+            .method("POST")
+            .uri("/echo?x=1")
+            .header("authorization", "Bearer secret")
+            .header("content-type", "application/json")
+            .body(Body::from(r#"{"prompt":"hi","token":"abc"}"#))
+            .unwrap();
+        let res = app.oneshot(req).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        let echoed = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(&echoed[..], br#"{"prompt":"hi","token":"abc"}"#);
+
+        let mut recorded = Vec::new();
+        for _ in 0..50 {
+            recorded = recent_exchanges(&knowledge, 10, Some("/echo")).unwrap();
+            if !recorded.is_empty() {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        assert_eq!(recorded.len(), 1);
+        let ex = &recorded[0];
+        assert_eq!(ex["status"], 200);
+        assert_eq!(ex["query"], "x=1");
+        assert_eq!(ex["request"]["headers"]["authorization"], REDACTED);
+        assert_eq!(ex["request"]["body"]["token"], REDACTED);
+        assert_eq!(ex["response"]["body"]["prompt"], "hi");
+    }
+
+    #[tokio::test]
+    async fn middleware_streams_bodies_past_the_cap() {
+        use axum::{routing::post, Router};
+        use tower::ServiceExt;
+
+        let dir = std::env::temp_dir().join(format!("pagi_recorder_cap_{}", uuid::Uuid::new_v4().simple()));
+        let knowledge = Arc::new(KnowledgeStore::open_path(&dir).unwrap());
+        let config = RecorderConfig {
+            max_body_bytes: 1024,
+            ..RecorderConfig::default()
+        };
+        let recorder = ApiRecorder::new(config, Arc::clone(&knowledge));
+        let app = Router::new()
+            .route("/echo", post(|body: String| async move { body }))
+            .layer(axum::middleware::from_fn_with_state(recorder, record_exchange));
+
+        let chunks = (0..64).map(|_| Ok::<_, std::io::Error>(Bytes::from("z".repeat(1024))));
+        let req = axum::http::Request::builder()
+            .method("POST")
+            .uri("/echo")
+            .body(Body::from_stream(futures_util::stream::iter(chunks)))
+            .unwrap();
+        let res = app.oneshot(req).await.unwrap();
+        let echoed = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(echoed.len(), 64 * 1024);
+
+        let mut recorded = Vec::new();
+        for _ in 0..50 {
+            recorded = recent_exchanges(&knowledge, 10, Some("/echo")).unwrap();
+            if !recorded.is_empty() {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        let ex = &recorded[0];
+        assert_eq!(ex["truncated"], true);
+        assert_eq!(ex["request"]["body"]["truncated"], true);
+        assert_eq!(ex["request"]["body"]["content"].as_str().unwrap().len(), 1024);
+        assert_eq!(ex["response"]["body"]["truncated"], true);
+    }
+}
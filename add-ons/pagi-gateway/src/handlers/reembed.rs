@@ -0,0 +1,176 @@
+//! Bulk re-embedding after an embedding model change: regenerates the vectors of records in the
+//! selected slots with the current provider (`Reembedder`), updating them in place.
+//!
+//! Jobs run in the background with at most `concurrency` embedding requests in flight
+//! (default `PAGI_REEMBED_CONCURRENCY`, 4) and checkpoint after every batch. Jobs left running by
+//! a stopped gateway are marked `interrupted` at startup and can be resumed. The same job runs
+//! from the command line with `--reembed` (gateway stopped).
+//!
+//! Starting and resuming jobs needs the `kb_admin` permission (see `auth`).
+//!
+//! Routes:
+//! - `POST /api/v1/knowledge/reembed` – start a job `{ slots?, model?, concurrency?, force? }`
+//! - `GET /api/v1/knowledge/reembed` – list jobs, newest first
+//! - `GET /api/v1/knowledge/reembed/:job_id` – job progress
+//! - `POST /api/v1/knowledge/reembed/:job_id/resume` – resume an interrupted or failed job
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use pagi_core::{KbType, KnowledgeStore, ReembedJob, ReembedStatus};
+use pagi_skills::{Reembedder, DEFAULT_REEMBED_CONCURRENCY};
+use serde::Deserialize;
+use std::sync::Arc;
+
+use crate::AppState;
+
+/// Concurrency of jobs that do not set one (`PAGI_REEMBED_CONCURRENCY`, default 4).
+pub fn default_concurrency() -> usize {
+    std::env::var("PAGI_REEMBED_CONCURRENCY")
+        .ok()
+        .and_then(|s| s.trim().parse::<usize>().ok())
+        .filter(|n| *n > 0)
+        .unwrap_or(DEFAULT_REEMBED_CONCURRENCY)
+}
+
+/// Slots 1–8 to re-embed (default KB-3 Logos). Slot 9 is encrypted and holds no vectors.
+pub fn validate_slots(slots: Option<Vec<u8>>) -> Result<Vec<u8>, String> {
+    let slots = slots
+        .filter(|s| !s.is_empty())
+        .unwrap_or_else(|| vec![KbType::Logos.slot_id()]);
+    if let Some(bad) = slots.iter().find(|s| !(1..=8).contains(*s)) {
+        return Err(format!("slot {} cannot be re-embedded (slots 1-8 only)", bad));
+    }
+    let mut unique = Vec::with_capacity(slots.len());
+    for slot in slots {
+        if !unique.contains(&slot) {
+            unique.push(slot);
+        }
+    }
+    Ok(unique)
+}
+
+/// Job JSON with its `progress` (0.0–1.0).
+pub fn job_json(job: &ReembedJob) -> serde_json::Value {
+    let mut value = serde_json::to_value(job).unwrap_or_default();
+    value["progress"] = serde_json::json!(job.progress());
+    value
+}
+
+fn error(status: StatusCode, message: impl Into<String>) -> (StatusCode, Json<serde_json::Value>) {
+    (status, Json(serde_json::json!({ "status": "error", "error": message.into() })))
+}
+
+fn running_job(knowledge: &KnowledgeStore) -> Result<Option<ReembedJob>, String> {
+    Ok(knowledge
+        .list_reembed_jobs()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .find(|j| j.status == ReembedStatus::Running))
+}
+
+fn spawn_job(knowledge: Arc<KnowledgeStore>, job: ReembedJob) {
+    tokio::spawn(async move {
+        Reembedder::new(knowledge).run(job, |_| {}).await;
+    });
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct StartReembedRequest {
+    #[serde(default)]
+    pub slots: Option<Vec<u8>>,
+    /// Embedding model override (default: `PAGI_EMBEDDINGS_MODEL`).
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default)]
+    pub concurrency: Option<usize>,
+    /// Re-embed records already produced by the target model.
+    #[serde(default)]
+    pub force: bool,
+}
+
+/// POST /api/v1/knowledge/reembed
+pub async fn start_reembed(
+    State(state): State<AppState>,
+    Json(req): Json<StartReembedRequest>,
+) -> (StatusCode, Json<serde_json::Value>) {
+    let slots = match validate_slots(req.slots) {
+        Ok(slots) => slots,
+        Err(e) => return error(StatusCode::BAD_REQUEST, e),
+    };
+    match running_job(&state.knowledge) {
+        Ok(Some(job)) => return error(StatusCode::CONFLICT, format!("re-embedding job {} is already running", job.id)),
+        Ok(None) => {}
+        Err(e) => return error(StatusCode::INTERNAL_SERVER_ERROR, e),
+    }
+    let model = req.model.map(|m| m.trim().to_string()).filter(|m| !m.is_empty());
+    let concurrency = req.concurrency.unwrap_or_else(default_concurrency);
+    let job = match Reembedder::new(Arc::clone(&state.knowledge)).create_job(slots, model, concurrency, req.force) {
+        Ok(job) => job,
+        Err(e) => return error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    };
+    let body = serde_json::json!({ "status": "accepted", "job": job_json(&job) });
+    spawn_job(Arc::clone(&state.knowledge), job);
+    (StatusCode::ACCEPTED, Json(body))
+}
+
+/// GET /api/v1/knowledge/reembed
+pub async fn list_reembed_jobs(
+    State(state): State<AppState>,
+) -> (StatusCode, Json<serde_json::Value>) {
+    match state.knowledge.list_reembed_jobs() {
+        Ok(jobs) => (
+            StatusCode::OK,
+            Json(serde_json::json!({
+                "status": "ok",
+                "count": jobs.len(),
+                "jobs": jobs.iter().map(job_json).collect::<Vec<_>>(),
+            })),
+        ),
+        Err(e) => error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    }
+}
+
+/// GET /api/v1/knowledge/reembed/:job_id
+pub async fn get_reembed_job(
+    State(state): State<AppState>,
+    Path(job_id): Path<String>,
+) -> (StatusCode, Json<serde_json::Value>) {
+    match state.knowledge.get_reembed_job(&job_id) {
+        Ok(Some(job)) => (StatusCode::OK, Json(serde_json::json!({ "status": "ok", "job": job_json(&job) }))),
+        Ok(None) => error(StatusCode::NOT_FOUND, format!("no re-embedding job {}", job_id)),
+        Err(e) => error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    }
+}
+
+/// POST /api/v1/knowledge/reembed/:job_id/resume
+pub async fn resume_reembed_job(
+    State(state): State<AppState>,
+    Path(job_id): Path<String>,
+) -> (StatusCode, Json<serde_json::Value>) {
+    let mut job = match state.knowledge.get_reembed_job(&job_id) {
+        Ok(Some(job)) => job,
+        Ok(None) => return error(StatusCode::NOT_FOUND, format!("no re-embedding job {}", job_id)),
+        Err(e) => return error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    };
+    if job.is_finished() {
+        return error(StatusCode::CONFLICT, format!("re-embedding job {} is already completed", job.id));
+    }
+    match running_job(&state.knowledge) {
+        Ok(Some(running)) => {
+            return error(StatusCode::CONFLICT, format!("re-embedding job {} is already running", running.id))
+        }
+        Ok(None) => {}
+        Err(e) => return error(StatusCode::INTERNAL_SERVER_ERROR, e),
+    }
+    // Claim it before the task starts so a second resume is refused.
+    job.status = ReembedStatus::Running;
+    if let Err(e) = state.knowledge.save_reembed_job(&job) {
+        return error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string());
+    }
+    let body = serde_json::json!({ "status": "accepted", "job": job_json(&job) });
+    spawn_job(Arc::clone(&state.knowledge), job);
+    (StatusCode::ACCEPTED, Json(body))
+}
@@ -0,0 +1,97 @@
+//! Config reload: re-reads CoreConfig from the sources the gateway started with (`--config`,
+//! `--env`, `PAGI__` variables, `--set`) and applies what can change while running.
+//!
+//! Hot-reloadable: `slot_labels`, `tick_rate_secs` (the Heartbeat picks it up on its next tick),
+//! `frontend_enabled`, `chat.notify` (the events that notify webhooks) and `logging.requests`
+//! (per-request logging). Any other field that differs from the running config is listed under
+//! `requires_restart` and keeps its running value. A config that fails to load or validate
+//! changes nothing.
+//!
+//! The route needs the `config_admin` permission (see `auth`). After a reload the control state
+//! is broadcast as a `FullState` message, so connected dashboards refresh.
+//!
+//! Routes:
+//! - `POST /api/v1/admin/reload-config` – `{ status, applied, requires_restart }`
+
+use axum::{extract::State, http::StatusCode, Json};
+use pagi_core::CoreConfig;
+
+use crate::AppState;
+
+/// Names of the fields that differ between `running` and `loaded`: top-level keys, or
+/// `key.field` where both sides are tables.
+fn changed_fields(running: &CoreConfig, loaded: &CoreConfig) -> Vec<String> {
+    let (Ok(serde_json::Value::Object(running)), Ok(serde_json::Value::Object(loaded))) =
+        (serde_json::to_value(running), serde_json::to_value(loaded))
+    else {
+        return Vec::new();
+    };
+    let mut keys: Vec<&String> = running.keys().chain(loaded.keys()).collect();
+    keys.sort();
+    keys.dedup();
+    let mut changed = Vec::new();
+    for key in keys {
+        match (running.get(key), loaded.get(key)) {
+            (a, b) if a == b => {}
+            (Some(serde_json::Value::Object(a)), Some(serde_json::Value::Object(b))) => {
+                let mut fields: Vec<&String> = a.keys().chain(b.keys()).collect();
+                fields.sort();
+                fields.dedup();
+                changed.extend(fields.into_iter().filter(|f| a.get(*f) != b.get(*f)).map(|f| format!("{}.{}", key, f)));
+            }
+            _ => changed.push(key.clone()),
+        }
+    }
+    changed
+}
+
+/// POST /api/v1/admin/reload-config
+pub async fn reload_config(State(state): State<AppState>) -> (StatusCode, Json<serde_json::Value>) {
+    let loaded = match CoreConfig::load_from(state.config.sources()) {
+        Ok(config) => config,
+        Err(e) => {
+            tracing::warn!(target: "pagi::gateway", error = %e, "Config reload refused");
+            return (
+                StatusCode::UNPROCESSABLE_ENTITY,
+                Json(serde_json::json!({ "status": "error", "error": e.to_string() })),
+            );
+        }
+    };
+    let running = state.config.get();
+    let mut next = CoreConfig::clone(&running);
+    let mut applied = Vec::new();
+    if next.slot_labels != loaded.slot_labels {
+        next.slot_labels = loaded.slot_labels.clone();
+        applied.push("slot_labels");
+    }
+    if next.tick_rate_secs != loaded.tick_rate_secs {
+        next.tick_rate_secs = loaded.tick_rate_secs;
+        applied.push("tick_rate_secs");
+    }
+    if next.frontend_enabled != loaded.frontend_enabled {
+        next.frontend_enabled = loaded.frontend_enabled;
+        applied.push("frontend_enabled");
+    }
+    if next.chat.notify != loaded.chat.notify {
+        next.chat.notify = loaded.chat.notify.clone();
+        state.orchestrator.set_chat_notifications(next.chat.notify.clone());
+        applied.push("chat.notify");
+    }
+    if next.logging.requests != loaded.logging.requests {
+        next.logging.requests = loaded.logging.requests;
+        applied.push("logging.requests");
+    }
+    let requires_restart = changed_fields(&next, &loaded);
+    state.config.set(next);
+    state.orchestrator.broadcast_control_state();
+    tracing::info!(
+        target: "pagi::gateway",
+        applied = ?applied,
+        requires_restart = ?requires_restart,
+        "Config reloaded"
+    );
+    (
+        StatusCode::OK,
+        Json(serde_json::json!({ "status": "ok", "applied": applied, "requires_restart": requires_restart })),
+    )
+}
@@ -0,0 +1,493 @@
+//! Interactive console: goals typed in a shorthand, run against the gateway's own stores.
+//!
+//! Started with `pagi-gateway --repl` instead of the HTTP server, like `--mcp`. The stores are
+//! opened directly, so a gateway serving the same `storage_path` must be stopped first. Each goal
+//! runs through the orchestrator as it would over `/v1/execute`; plan steps are printed as they
+//! start and finish, then the result is pretty-printed. Logs go to stderr (`RUST_LOG`, default
+//! `warn`). Commands:
+//! - `exec <Skill> [key=value ...]` – ExecuteSkill; values are JSON where they parse (`slot=1`,
+//!   `tags=["a"]`) and strings otherwise (quote them to keep `"1"` a string); `a.b=1` nests, and a
+//!   single `{...}` argument is the whole payload
+//! - `goal <intent> [key=value ...]` – AutonomousGoal with the pairs as context
+//! - `query <slot> <key>` – QueryKnowledge
+//! - `get <path>`, `set <path> <value>`, `del <path>` – MemoryOp (`knowledge/{slot}/{key}`, `leads/{id}`, ...)
+//! - `json <goal>` – any goal as JSON (`{"Custom": "ping"}`)
+//! - `plan <command>` – the plan preview of a goal command, without running it
+//! - `skills`, `intents`, `tenant [id]`, `agent [id]`, `history`, `!!`, `!<n>`, `help`, `quit`
+//!
+//! The tenant is `PAGI_REPL_TENANT_ID` (default `default`). History is kept across sessions in
+//! `{storage_path}/repl_history`.
+
+use std::io::Write as _;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Instant;
+
+use pagi_core::{Goal, Orchestrator, StepProgress, TenantContext, DEFAULT_TENANT_ID};
+use tokio::io::AsyncBufReadExt;
+use tokio::sync::broadcast;
+
+/// History entries kept, oldest dropped first.
+const MAX_HISTORY: usize = 1000;
+
+const HELP: &str = "\
+Goals:
+  exec <Skill> [key=value ...]    run a skill (values are JSON where they parse; a.b=1 nests)
+  exec <Skill> {json}             run a skill with a JSON payload
+  goal <intent> [key=value ...]   run an intent's plan with the pairs as context
+  query <slot> <key>              read a key from a KB slot
+  get <path> / set <path> <value> / del <path>
+                                  memory operations (knowledge/{slot}/{key}, vault/{tree}/{key}, leads/...)
+  json <goal>                     any goal as JSON
+  plan <command>                  preview the plan of a goal command without running it
+Console:
+  skills, intents                 what the tenant can run
+  tenant [id], agent [id]         show or switch the tenant / agent goals run as
+  history, !!, !<n>               list or repeat earlier commands
+  help, quit";
+
+/// One console command.
+#[derive(Debug)]
+pub enum Command {
+    Run(Goal),
+    Plan(Goal),
+    Skills,
+    Intents,
+    Tenant(Option<String>),
+    Agent(Option<String>),
+    History,
+    Help,
+    Quit,
+}
+
+/// One word of a command line. `quoted` when any of it was in quotes, so `"1"` stays a string.
+#[derive(Debug, Clone, Default, PartialEq)]
+struct Word {
+    text: String,
+    quoted: bool,
+}
+
+/// Splits a line at whitespace outside quotes and outside `{...}` / `[...]` JSON.
+fn split_words(line: &str) -> Result<Vec<Word>, String> {
+    fn word(current: &mut Option<Word>) -> &mut Word {
+        current.get_or_insert_with(Word::default)
+    }
+    let mut words = Vec::new();
+    let mut current: Option<Word> = None;
+    let mut quote: Option<char> = None;
+    let mut depth = 0usize;
+    let mut in_json_string = false;
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        if let Some(q) = quote {
+            match c {
+                c if c == q => quote = None,
+                '\\' if q == '"' => word(&mut current).text.extend(chars.next()),
+                c => word(&mut current).text.push(c),
+            }
+            continue;
+        }
+        if depth > 0 {
+            // JSON is kept verbatim; only its brackets and strings are tracked.
+            word(&mut current).text.push(c);
+            match c {
+                '\\' if in_json_string => word(&mut current).text.extend(chars.next()),
+                '"' => in_json_string = !in_json_string,
+                '{' | '[' if !in_json_string => depth += 1,
+                '}' | ']' if !in_json_string => depth -= 1,
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            c if c.is_whitespace() => words.extend(current.take()),
+            '"' | '\'' => {
+                quote = Some(c);
+                word(&mut current).quoted = true;
+            }
+            '{' | '[' => {
+                depth = 1;
+                word(&mut current).text.push(c);
+            }
+            c => word(&mut current).text.push(c),
+        }
+    }
+    if quote.is_some() {
+        return Err("unclosed quote".to_string());
+    }
+    if depth > 0 {
+        return Err("unclosed { or [".to_string());
+    }
+    words.extend(current);
+    Ok(words)
+}
+
+/// A value as typed: JSON where it parses (unless quoted), else the text itself.
+fn typed_value(text: &str, quoted: bool) -> serde_json::Value {
+    if quoted {
+        return serde_json::Value::String(text.to_string());
+    }
+    serde_json::from_str(text).unwrap_or_else(|_| serde_json::Value::String(text.to_string()))
+}
+
+/// `key=value` words as an object (dotted keys nest), or a single `{...}` word as the object.
+fn payload(words: &[Word]) -> Result<Option<serde_json::Value>, String> {
+    match words {
+        [] => return Ok(None),
+        [only] if !only.quoted && only.text.starts_with('{') => {
+            return serde_json::from_str(&only.text)
+                .map(Some)
+                .map_err(|e| format!("payload is not JSON: {}", e));
+        }
+        _ => {}
+    }
+    let mut payload = serde_json::Value::Null;
+    for w in words {
+        let Some((key, value)) = w.text.split_once('=').filter(|(key, _)| !key.is_empty()) else {
+            return Err(format!("expected key=value, got {:?}", w.text));
+        };
+        let mut target = &mut payload;
+        for part in key.split('.') {
+            if !target.is_object() {
+                *target = serde_json::json!({});
+            }
+            target = &mut target[part];
+        }
+        *target = typed_value(value, w.quoted);
+    }
+    Ok(Some(payload))
+}
+
+fn one<'a>(words: &'a [Word], usage: &str) -> Result<&'a str, String> {
+    match words {
+        [w] => Ok(&w.text),
+        _ => Err(format!("usage: {}", usage)),
+    }
+}
+
+/// Parses one line; None for a blank line.
+pub fn parse_command(line: &str) -> Result<Option<Command>, String> {
+    let line = line.trim();
+    if line.is_empty() {
+        return Ok(None);
+    }
+    let (verb, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+    let rest = rest.trim();
+    let words = if verb == "json" || verb == "plan" { Vec::new() } else { split_words(rest)? };
+    let optional = |words: &[Word]| words.first().map(|w| w.text.clone());
+    let command = match verb {
+        "exec" => {
+            let (name, args) = words.split_first().ok_or("usage: exec <Skill> [key=value ...]")?;
+            Command::Run(Goal::ExecuteSkill {
+                name: name.text.clone(),
+                payload: payload(args)?,
+            })
+        }
+        "goal" => {
+            let (intent, args) = words.split_first().ok_or("usage: goal <intent> [key=value ...]")?;
+            Command::Run(Goal::AutonomousGoal {
+                intent: intent.text.clone(),
+                context: payload(args)?,
+            })
+        }
+        "query" => match words.as_slice() {
+            [slot, key] => Command::Run(Goal::QueryKnowledge {
+                slot_id: slot.text.parse().map_err(|_| format!("slot must be 1-8, got {:?}", slot.text))?,
+                query: key.text.clone(),
+            }),
+            _ => return Err("usage: query <slot> <key>".to_string()),
+        },
+        "get" | "del" => Command::Run(Goal::MemoryOp {
+            path: one(&words, &format!("{} <path>", verb))?.to_string(),
+            value: None,
+            delete: verb == "del",
+        }),
+        "set" => match words.as_slice() {
+            [path, value] => Command::Run(Goal::MemoryOp {
+                path: path.text.clone(),
+                value: Some(typed_value(&value.text, value.quoted)),
+                delete: false,
+            }),
+            _ => return Err("usage: set <path> <value>".to_string()),
+        },
+        "json" => Command::Run(serde_json::from_str(rest).map_err(|e| format!("not a goal: {}", e))?),
+        "plan" => match parse_command(rest)? {
+            Some(Command::Run(goal)) => Command::Plan(goal),
+            _ => return Err("usage: plan <exec|goal|query|get|set|del|json ...>".to_string()),
+        },
+        "skills" => Command::Skills,
+        "intents" => Command::Intents,
+        "tenant" => Command::Tenant(optional(&words)),
+        "agent" => Command::Agent(optional(&words)),
+        "history" => Command::History,
+        "help" | "?" => Command::Help,
+        "quit" | "exit" => Command::Quit,
+        other => return Err(format!("unknown command {:?} (try `help`)", other)),
+    };
+    Ok(Some(command))
+}
+
+/// Replaces `!!` with the last command and `!<n>` with command `n` (1-based, as `history` lists them).
+pub fn expand_history(line: &str, history: &[String]) -> Result<String, String> {
+    let line = line.trim();
+    let Some(reference) = line.strip_prefix('!') else {
+        return Ok(line.to_string());
+    };
+    let entry = if reference == "!" {
+        history.last()
+    } else {
+        reference.parse::<usize>().ok().and_then(|n| n.checked_sub(1)).and_then(|i| history.get(i))
+    };
+    entry.cloned().ok_or_else(|| format!("no history entry {}", line))
+}
+
+/// The console: where goals run and what has been typed.
+pub struct Repl {
+    orchestrator: Arc<Orchestrator>,
+    tenant_id: String,
+    agent_id: Option<String>,
+    history_path: PathBuf,
+    history: Vec<String>,
+}
+
+impl Repl {
+    /// Loads the history kept at `history_path`, if any.
+    pub fn new(orchestrator: Arc<Orchestrator>, tenant_id: impl Into<String>, history_path: PathBuf) -> Self {
+        let mut history: Vec<String> = std::fs::read_to_string(&history_path)
+            .map(|text| text.lines().filter(|l| !l.trim().is_empty()).map(str::to_string).collect())
+            .unwrap_or_default();
+        if history.len() > MAX_HISTORY {
+            history.drain(..history.len() - MAX_HISTORY);
+            if let Err(e) = std::fs::write(&history_path, history.join("\n") + "\n") {
+                tracing::warn!(target: "pagi::gateway", path = %history_path.display(), error = %e, "Console history not trimmed");
+            }
+        }
+        Self {
+            orchestrator,
+            tenant_id: tenant_id.into(),
+            agent_id: None,
+            history_path,
+            history,
+        }
+    }
+
+    /// Tenant from `PAGI_REPL_TENANT_ID` (default `default`), history under `storage_path`.
+    pub fn from_env(orchestrator: Arc<Orchestrator>, storage_path: &Path) -> Self {
+        let tenant_id = std::env::var("PAGI_REPL_TENANT_ID")
+            .ok()
+            .filter(|t| !t.trim().is_empty())
+            .unwrap_or_else(|| DEFAULT_TENANT_ID.to_string());
+        Self::new(orchestrator, tenant_id, storage_path.join("repl_history"))
+    }
+
+    /// Reads commands from stdin until `quit` or end of input.
+    pub async fn run_stdio(&mut self) -> std::io::Result<()> {
+        let mut lines = tokio::io::BufReader::new(tokio::io::stdin()).lines();
+        println!("PAGI console (tenant {}). `help` lists commands, `quit` leaves.", self.tenant_id);
+        loop {
+            print!("pagi:{}> ", self.tenant_id);
+            std::io::stdout().flush()?;
+            let Some(line) = lines.next_line().await? else {
+                println!();
+                return Ok(());
+            };
+            let line = match expand_history(&line, &self.history) {
+                Ok(line) => line,
+                Err(e) => {
+                    println!("error: {}", e);
+                    continue;
+                }
+            };
+            if line.is_empty() {
+                continue;
+            }
+            self.remember(&line);
+            match parse_command(&line) {
+                Ok(Some(Command::Quit)) => return Ok(()),
+                Ok(Some(command)) => self.run(command).await,
+                Ok(None) => {}
+                Err(e) => println!("error: {}", e),
+            }
+        }
+    }
+
+    fn remember(&mut self, line: &str) {
+        if self.history.last().map(String::as_str) == Some(line) {
+            return;
+        }
+        self.history.push(line.to_string());
+        let appended = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.history_path)
+            .and_then(|mut file| writeln!(file, "{}", line));
+        if let Err(e) = appended {
+            tracing::warn!(target: "pagi::gateway", path = %self.history_path.display(), error = %e, "Console history not saved");
+        }
+    }
+
+    fn context(&self, correlation_id: String) -> TenantContext {
+        TenantContext {
+            tenant_id: self.tenant_id.clone(),
+            correlation_id: Some(correlation_id),
+            agent_id: self.agent_id.clone(),
+            ..Default::default()
+        }
+    }
+
+    async fn run(&mut self, command: Command) {
+        match command {
+            Command::Run(goal) => self.run_goal(goal).await,
+            Command::Plan(goal) => {
+                let preview = self.orchestrator.plan_preview(&self.context("repl-plan".to_string()), &goal);
+                println!("{}", serde_json::to_string_pretty(&preview).unwrap_or_default());
+            }
+            Command::Skills => {
+                let mut names = self.orchestrator.skill_names_for(&self.tenant_id);
+                names.sort();
+                names.iter().for_each(|name| println!("  {}", name));
+            }
+            Command::Intents => {
+                let mut names = self.orchestrator.blueprint().intent_names();
+                names.sort();
+                names.iter().for_each(|name| println!("  {}", name));
+            }
+            Command::Tenant(Some(tenant_id)) => self.tenant_id = tenant_id,
+            Command::Tenant(None) => println!("tenant {}", self.tenant_id),
+            Command::Agent(Some(agent_id)) => self.agent_id = Some(agent_id),
+            Command::Agent(None) => println!("agent {}", self.context(String::new()).resolved_agent_id()),
+            Command::History => {
+                for (i, line) in self.history.iter().enumerate() {
+                    println!("{:>5}  {}", i + 1, line);
+                }
+            }
+            Command::Help => println!("{}", HELP),
+            Command::Quit => {}
+        }
+    }
+
+    /// Dispatches `goal`, tracing its plan steps, and prints the outcome.
+    async fn run_goal(&self, goal: Goal) {
+        let correlation_id = format!("repl-{}", uuid::Uuid::new_v4());
+        let ctx = self.context(correlation_id.clone());
+        let started = Instant::now();
+        // Subscribed before dispatch, and drained before the result, so every step is seen.
+        let mut progress = self.orchestrator.subscribe_progress();
+        let run = self.orchestrator.dispatch(&ctx, goal);
+        tokio::pin!(run);
+        let outcome = loop {
+            tokio::select! {
+                outcome = &mut run => break outcome,
+                event = progress.recv() => match event {
+                    Ok(event) => print_step(&correlation_id, &event),
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => println!("  ({} step events missed)", skipped),
+                    Err(broadcast::error::RecvError::Closed) => {}
+                },
+            }
+        };
+        while let Ok(event) = progress.try_recv() {
+            print_step(&correlation_id, &event);
+        }
+        match outcome {
+            Ok(output) => println!("{}", serde_json::to_string_pretty(&output).unwrap_or_default()),
+            Err(e) => println!("error: {}", e),
+        }
+        println!("({} ms)", started.elapsed().as_millis());
+    }
+}
+
+/// One trace line for a step of this dispatch, indented by sub-plan depth.
+fn print_step(correlation_id: &str, event: &StepProgress) {
+    if event.correlation_id.as_deref() != Some(correlation_id) {
+        return;
+    }
+    let status = serde_json::to_value(event.status).ok();
+    let mut line = format!(
+        "{}[{}/{}] {} {}",
+        "  ".repeat(event.depth),
+        event.index + 1,
+        event.total,
+        event.step,
+        status.as_ref().and_then(|s| s.as_str()).unwrap_or("?"),
+    );
+    if let Some(skill) = &event.executed_skill {
+        line.push_str(&format!(" (ran {})", skill));
+    }
+    if let Some(error) = &event.error {
+        line.push_str(&format!(": {}", error));
+    }
+    println!("{}", line);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn goal(line: &str) -> serde_json::Value {
+        match parse_command(line) {
+            Ok(Some(Command::Run(goal))) => serde_json::to_value(goal).unwrap(),
+            other => panic!("{:?} parsed as {:?}", line, other),
+        }
+    }
+
+    #[test]
+    fn shorthand_goals_parse_into_goals() {
+        assert_eq!(
+            goal("exec KnowledgeQuery slot=1 key=brand_voice"),
+            serde_json::json!({ "ExecuteSkill": { "name": "KnowledgeQuery", "payload": { "slot": 1, "key": "brand_voice" } } })
+        );
+        assert_eq!(
+            goal(r#"exec SendEmail to.name="Ada Lovelace" to.id='7' tags=["a", "b c"] urgent=true"#),
+            serde_json::json!({ "ExecuteSkill": { "name": "SendEmail", "payload": {
+                "to": { "name": "Ada Lovelace", "id": "7" }, "tags": ["a", "b c"], "urgent": true
+            } } })
+        );
+        assert_eq!(
+            goal(r#"exec Echo {"text": "hi there", "n": [1, {"m": 2}]}"#),
+            serde_json::json!({ "ExecuteSkill": { "name": "Echo", "payload": { "text": "hi there", "n": [1, { "m": 2 }] } } })
+        );
+        assert_eq!(goal("exec Ping"), serde_json::json!({ "ExecuteSkill": { "name": "Ping", "payload": null } }));
+        assert_eq!(
+            goal("goal draft_reply lead_id=L1"),
+            serde_json::json!({ "AutonomousGoal": { "intent": "draft_reply", "context": { "lead_id": "L1" } } })
+        );
+        assert_eq!(goal("query 1 brand_voice"), serde_json::json!({ "QueryKnowledge": { "slot_id": 1, "query": "brand_voice" } }));
+        assert_eq!(
+            goal(r#"set knowledge/1/tone "warm, direct""#),
+            serde_json::json!({ "MemoryOp": { "path": "knowledge/1/tone", "value": "warm, direct", "delete": false } })
+        );
+        assert_eq!(
+            goal("del leads/L1"),
+            serde_json::json!({ "MemoryOp": { "path": "leads/L1", "value": null, "delete": true } })
+        );
+        assert_eq!(goal(r#"json {"Custom": "ping"}"#), serde_json::json!({ "Custom": "ping" }));
+        assert!(matches!(parse_command("plan goal draft_reply"), Ok(Some(Command::Plan(Goal::AutonomousGoal { .. })))));
+        assert!(matches!(parse_command("   "), Ok(None)));
+    }
+
+    #[test]
+    fn malformed_commands_are_explained() {
+        for (line, error) in [
+            ("exec", "usage: exec"),
+            ("exec Echo text", "expected key=value"),
+            ("exec Echo text=\"open", "unclosed quote"),
+            ("exec Echo {\"a\": [1}", "unclosed"),
+            ("query one key", "slot must be 1-8"),
+            ("plan skills", "usage: plan"),
+            ("frobnicate", "unknown command"),
+        ] {
+            let message = parse_command(line).err().unwrap_or_default();
+            assert!(message.contains(error), "{:?} gave {:?}", line, message);
+        }
+    }
+
+    #[test]
+    fn history_references_expand() {
+        let history = vec!["skills".to_string(), "query 1 tone".to_string()];
+        assert_eq!(expand_history("!!", &history).unwrap(), "query 1 tone");
+        assert_eq!(expand_history("!1", &history).unwrap(), "skills");
+        assert_eq!(expand_history(" intents ", &history).unwrap(), "intents");
+        assert!(expand_history("!3", &history).is_err());
+        assert!(expand_history("!0", &history).is_err());
+    }
+}
@@ -0,0 +1,51 @@
+//! Request logging: one `pagi::http` event per HTTP request with method, path, status, latency
+//! and body sizes, while `[logging] requests` is on (the default; it follows config reloads).
+//!
+//! Sizes come from `Content-Length` and the response body's known length; streamed bodies count
+//! as 0. A response's `X-Correlation-Id` is logged as the event's `correlation_id`. Health and
+//! readiness probes are logged at debug level so they do not drown the rest.
+
+use axum::{
+    body::HttpBody,
+    extract::{Request, State},
+    http::header,
+    middleware::Next,
+    response::Response,
+};
+
+use crate::LiveConfig;
+
+/// Probe routes, logged at debug level.
+const QUIET_PATHS: &[&str] = &["/api/v1/health", "/api/v1/ready"];
+
+/// Axum layer logging each request once its response is ready.
+pub async fn log_request(State(config): State<LiveConfig>, req: Request, next: Next) -> Response {
+    if !config.get().logging.requests {
+        return next.run(req).await;
+    }
+    let method = req.method().clone();
+    let path = req.uri().path().to_string();
+    let request_bytes = req
+        .headers()
+        .get(header::CONTENT_LENGTH)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(0);
+    let started = std::time::Instant::now();
+    let response = next.run(req).await;
+    let latency_ms = started.elapsed().as_secs_f64() * 1000.0;
+    let status = response.status().as_u16();
+    let response_bytes = response.body().size_hint().exact().unwrap_or(0);
+    let correlation_id = response
+        .headers()
+        .get("x-correlation-id")
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default()
+        .to_string();
+    if QUIET_PATHS.contains(&path.as_str()) {
+        tracing::debug!(target: "pagi::http", method = %method, path = %path, status, latency_ms, request_bytes, response_bytes, correlation_id, "{} {} {}", method, path, status);
+    } else {
+        tracing::info!(target: "pagi::http", method = %method, path = %path, status, latency_ms, request_bytes, response_bytes, correlation_id, "{} {} {}", method, path, status);
+    }
+    response
+}
@@ -0,0 +1,337 @@
+//! Research sandbox workspace: `research_sandbox/` over HTTP for the Studio workspace editor and
+//! remote operators. These are the same files the fs skills and the maintenance scan work on.
+//!
+//! Every path goes through [`PathJail`]. Writes and deletes must pass the Ethos policy for
+//! `write_sandbox_file` and are recorded in Chronos; they need the `kb_admin` permission (see
+//! `auth`). `PAGI_RESEARCH_SANDBOX` moves the root.
+//!
+//! Routes:
+//! - `GET /api/v1/sandbox/files` – list files (`?dir=notes`)
+//! - `GET /api/v1/sandbox/files/*path` – read one UTF-8 file
+//! - `PUT /api/v1/sandbox/files/*path` – write `{ content, append? }`
+//! - `DELETE /api/v1/sandbox/files/*path` – delete one file
+//! - `GET /api/v1/sandbox/events` – SSE `change` events `{ path, change, size, modified_ms }`
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::sse::{Event, KeepAlive, Sse},
+    Json,
+};
+use pagi_core::{AlignmentResult, DEFAULT_AGENT_ID};
+use pagi_skills::{PathJail, SANDBOX_WRITE_SKILL_NAME};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use crate::{research_sandbox_root, AppState};
+
+/// Largest file the API reads or writes.
+pub const MAX_SANDBOX_FILE_BYTES: usize = 1024 * 1024;
+/// Most files one listing returns.
+const MAX_LISTED_FILES: usize = 5_000;
+
+type ApiError = (StatusCode, Json<serde_json::Value>);
+
+fn error(status: StatusCode, message: impl Into<String>) -> ApiError {
+    (status, Json(serde_json::json!({ "status": "error", "error": message.into() })))
+}
+
+fn jail() -> PathJail {
+    PathJail::new(research_sandbox_root())
+}
+
+fn resolve(jail: &PathJail, path: &str) -> Result<std::path::PathBuf, ApiError> {
+    jail.resolve(path).map_err(|e| error(StatusCode::BAD_REQUEST, e))
+}
+
+fn io_error(e: std::io::Error) -> ApiError {
+    match e.kind() {
+        std::io::ErrorKind::NotFound => error(StatusCode::NOT_FOUND, "file not found"),
+        _ => error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    }
+}
+
+fn modified_ms(meta: &std::fs::Metadata) -> i64 {
+    meta.modified()
+        .ok()
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or(0)
+}
+
+/// Size and modification time of one sandbox file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct FileStamp {
+    size: u64,
+    modified_ms: i64,
+}
+
+/// Regular files under `dir` by jail-relative path. Symlinks are skipped, so nothing outside the
+/// root is listed.
+fn snapshot(jail: &PathJail, dir: &std::path::Path) -> BTreeMap<String, FileStamp> {
+    let mut files = BTreeMap::new();
+    let mut stack = vec![dir.to_path_buf()];
+    while let Some(dir) = stack.pop() {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+            if file_type.is_dir() {
+                stack.push(entry.path());
+            } else if file_type.is_file() {
+                if files.len() >= MAX_LISTED_FILES {
+                    return files;
+                }
+                if let Ok(meta) = entry.metadata() {
+                    files.insert(
+                        jail.relative(&entry.path()),
+                        FileStamp {
+                            size: meta.len(),
+                            modified_ms: modified_ms(&meta),
+                        },
+                    );
+                }
+            }
+        }
+    }
+    files
+}
+
+/// `(path, change, stamp)` for every file created, modified or deleted between two snapshots.
+fn diff<'a>(
+    before: &'a BTreeMap<String, FileStamp>,
+    after: &'a BTreeMap<String, FileStamp>,
+) -> Vec<(&'a str, &'static str, Option<FileStamp>)> {
+    let mut changes = Vec::new();
+    for (path, stamp) in after {
+        match before.get(path) {
+            None => changes.push((path.as_str(), "created", Some(*stamp))),
+            Some(old) if old != stamp => changes.push((path.as_str(), "modified", Some(*stamp))),
+            Some(_) => {}
+        }
+    }
+    for path in before.keys().filter(|p| !after.contains_key(*p)) {
+        changes.push((path.as_str(), "deleted", None));
+    }
+    changes
+}
+
+/// Ethos check for a sandbox write or delete; a violation is recorded in Chronos and is a 403.
+fn ethos_check(state: &AppState, path: &str, content: &str) -> Result<(), ApiError> {
+    let Some(policy) = state.knowledge.active_ethos_policy() else {
+        return Ok(());
+    };
+    let intended = serde_json::json!({ "path": path, "content": content });
+    match policy.evaluate(SANDBOX_WRITE_SKILL_NAME, Some(&intended)).alignment() {
+        AlignmentResult::Pass => Ok(()),
+        AlignmentResult::Fail { reason } => {
+            let violation = state.knowledge.event("Ethos", format!("Policy Violation: {}", reason))
+                .with_skill("sandbox_api")
+                .with_outcome("blocked");
+            let _ = state.knowledge.append_chronos_event(DEFAULT_AGENT_ID, &violation);
+            tracing::warn!(target: "pagi::ethos", path = %path, reason = %reason, "Ethos: sandbox change blocked");
+            Err((
+                StatusCode::FORBIDDEN,
+                Json(serde_json::json!({ "status": "policy_violation", "error": reason, "path": path })),
+            ))
+        }
+    }
+}
+
+fn record_change(state: &AppState, path: &str, change: &str) {
+    let event = state.knowledge.event("Oikos", format!("Sandbox file {}: {}", change, path))
+        .with_skill("sandbox_api")
+        .with_outcome(change);
+    if state.knowledge.append_chronos_event(DEFAULT_AGENT_ID, &event).is_err() {
+        tracing::warn!(target: "pagi::chronos", "Failed to append Chronos event");
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ListParams {
+    /// Directory to list, relative to the sandbox root (default: the root).
+    #[serde(default)]
+    pub dir: Option<String>,
+}
+
+/// GET /api/v1/sandbox/files
+pub async fn list_files(
+    Query(params): Query<ListParams>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let jail = jail();
+    let dir = match params.dir.as_deref().map(str::trim).filter(|d| !d.is_empty() && *d != ".") {
+        Some(dir) => resolve(&jail, dir)?,
+        None => jail.canonical_root().map_err(|e| error(StatusCode::INTERNAL_SERVER_ERROR, e))?,
+    };
+    let files = snapshot(&jail, &dir);
+    let entries: Vec<serde_json::Value> = files
+        .iter()
+        .map(|(path, stamp)| serde_json::json!({ "path": path, "size": stamp.size, "modified_ms": stamp.modified_ms }))
+        .collect();
+    Ok(Json(serde_json::json!({
+        "status": "ok",
+        "count": entries.len(),
+        "truncated": entries.len() >= MAX_LISTED_FILES,
+        "files": entries,
+    })))
+}
+
+/// GET /api/v1/sandbox/files/*path
+pub async fn read_file(Path(path): Path<String>) -> Result<Json<serde_json::Value>, ApiError> {
+    let jail = jail();
+    let target = resolve(&jail, &path)?;
+    let meta = std::fs::metadata(&target).map_err(io_error)?;
+    if !meta.is_file() {
+        return Err(error(StatusCode::BAD_REQUEST, "path is not a file"));
+    }
+    if meta.len() > MAX_SANDBOX_FILE_BYTES as u64 {
+        return Err(error(StatusCode::PAYLOAD_TOO_LARGE, "file is too large to open"));
+    }
+    let bytes = std::fs::read(&target).map_err(io_error)?;
+    let content = String::from_utf8(bytes)
+        .map_err(|_| error(StatusCode::UNPROCESSABLE_ENTITY, "only UTF-8 text files can be opened"))?;
+    Ok(Json(serde_json::json!({
+        "status": "ok",
+        "path": jail.relative(&target),
+        "content": content,
+        "size": meta.len(),
+        "modified_ms": modified_ms(&meta),
+    })))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WriteFile {
+    pub content: String,
+    /// Append instead of replacing the file.
+    #[serde(default)]
+    pub append: bool,
+}
+
+/// PUT /api/v1/sandbox/files/*path
+pub async fn write_file(
+    State(state): State<AppState>,
+    Path(path): Path<String>,
+    Json(req): Json<WriteFile>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    if req.content.len() > MAX_SANDBOX_FILE_BYTES {
+        return Err(error(StatusCode::PAYLOAD_TOO_LARGE, "content is too large"));
+    }
+    ethos_check(&state, &path, &req.content)?;
+    let jail = jail();
+    let target = jail
+        .resolve_for_write(&path)
+        .map_err(|e| error(StatusCode::BAD_REQUEST, e))?;
+    if target.is_dir() {
+        return Err(error(StatusCode::BAD_REQUEST, "path is a directory"));
+    }
+    let created = !target.exists();
+    if req.append {
+        use std::io::Write;
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&target)
+            .map_err(io_error)?;
+        file.write_all(req.content.as_bytes()).map_err(io_error)?;
+    } else {
+        std::fs::write(&target, req.content.as_bytes()).map_err(io_error)?;
+    }
+    let meta = std::fs::metadata(&target).map_err(io_error)?;
+    let rel = jail.relative(&target);
+    let change = if created { "created" } else { "modified" };
+    record_change(&state, &rel, change);
+    tracing::info!(target: "pagi::gateway", path = %rel, change, bytes = req.content.len(), "Sandbox file written");
+    Ok(Json(serde_json::json!({
+        "status": "ok",
+        "path": rel,
+        "change": change,
+        "bytes_written": req.content.len(),
+        "size": meta.len(),
+        "modified_ms": modified_ms(&meta),
+    })))
+}
+
+/// DELETE /api/v1/sandbox/files/*path
+pub async fn delete_file(
+    State(state): State<AppState>,
+    Path(path): Path<String>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    ethos_check(&state, &path, "")?;
+    let jail = jail();
+    let target = resolve(&jail, &path)?;
+    let meta = std::fs::symlink_metadata(&target).map_err(io_error)?;
+    if meta.is_dir() {
+        return Err(error(StatusCode::BAD_REQUEST, "path is a directory"));
+    }
+    std::fs::remove_file(&target).map_err(io_error)?;
+    let rel = jail.relative(&target);
+    record_change(&state, &rel, "deleted");
+    tracing::info!(target: "pagi::gateway", path = %rel, "Sandbox file deleted");
+    Ok(Json(serde_json::json!({ "status": "ok", "path": rel, "change": "deleted" })))
+}
+
+/// How often the change feed rescans the sandbox (`PAGI_SANDBOX_POLL_MS`, default 2000).
+fn poll_interval() -> Duration {
+    let ms = std::env::var("PAGI_SANDBOX_POLL_MS")
+        .ok()
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .unwrap_or(2_000)
+        .max(100);
+    Duration::from_millis(ms)
+}
+
+/// GET /api/v1/sandbox/events – one `change` event per file created, modified or deleted, from
+/// any source (this API, skills or the local disk), found by rescanning the sandbox.
+pub async fn sandbox_events(
+) -> Result<Sse<impl futures_util::Stream<Item = Result<Event, std::convert::Infallible>> + Send + 'static>, ApiError> {
+    use async_stream::stream;
+
+    let jail = jail();
+    let root = jail.canonical_root().map_err(|e| error(StatusCode::INTERNAL_SERVER_ERROR, e))?;
+    let interval = poll_interval();
+    let stream = stream! {
+        let mut known = snapshot(&jail, &root);
+        loop {
+            tokio::time::sleep(interval).await;
+            let current = snapshot(&jail, &root);
+            for (path, change, stamp) in diff(&known, &current) {
+                let data = serde_json::json!({
+                    "path": path,
+                    "change": change,
+                    "size": stamp.map(|s| s.size),
+                    "modified_ms": stamp.map(|s| s.modified_ms),
+                });
+                yield Ok(Event::default().event("change").data(data.to_string()));
+            }
+            known = current;
+        }
+    };
+    Ok(Sse::new(stream).keep_alive(KeepAlive::new().interval(Duration::from_secs(15)).text("keepalive")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diff_reports_created_modified_and_deleted() {
+        let stamp = |size, modified_ms| FileStamp { size, modified_ms };
+        let before = BTreeMap::from([
+            ("a.md".to_string(), stamp(1, 10)),
+            ("b.md".to_string(), stamp(2, 10)),
+            ("c.md".to_string(), stamp(3, 10)),
+        ]);
+        let after = BTreeMap::from([
+            ("a.md".to_string(), stamp(1, 10)),
+            ("b.md".to_string(), stamp(5, 20)),
+            ("d.md".to_string(), stamp(4, 30)),
+        ]);
+        let changes: Vec<(&str, &str)> = diff(&before, &after).into_iter().map(|(p, c, _)| (p, c)).collect();
+        assert_eq!(changes, vec![("b.md", "modified"), ("d.md", "created"), ("c.md", "deleted")]);
+        assert!(diff(&after, &after).is_empty());
+    }
+}
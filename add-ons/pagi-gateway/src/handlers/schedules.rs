@@ -0,0 +1,120 @@
+//! Scheduled goals: delayed and recurring goals run by the Heartbeat.
+//!
+//! Entries are stored in KB_OIKOS by pagi-core ([`SchedulerRegistry`]). The Heartbeat calls
+//! [`SchedulerRegistry::run_due`] each tick and logs every run to the agent's Chronos history.
+//!
+//! Routes:
+//! - `GET /api/v1/schedules` – list
+//! - `POST /api/v1/schedules` – create `{ name, goal, tenant_id, agent_id?, schedule }` (cron or
+//!   `@every 30m`) or `{ ..., run_at_ms }` / `{ ..., delay_secs }` for a one-shot goal
+//! - `GET /api/v1/schedules/:id` – fetch one
+//! - `DELETE /api/v1/schedules/:id` – remove
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use pagi_core::{Goal, ScheduledGoal, SchedulerRegistry, TenantContext};
+use serde::Deserialize;
+use std::sync::Arc;
+
+use crate::AppState;
+
+#[derive(Debug, Deserialize)]
+pub struct CreateSchedule {
+    pub name: String,
+    pub goal: Goal,
+    pub tenant_id: String,
+    #[serde(default)]
+    pub agent_id: Option<String>,
+    /// Recurring schedule (cron or `@every`).
+    #[serde(default)]
+    pub schedule: Option<String>,
+    /// One-shot: run at this time (ms since epoch).
+    #[serde(default)]
+    pub run_at_ms: Option<i64>,
+    /// One-shot: run this many seconds from now.
+    #[serde(default)]
+    pub delay_secs: Option<u64>,
+}
+
+fn scheduler(state: &AppState) -> SchedulerRegistry {
+    SchedulerRegistry::new(Arc::clone(&state.knowledge))
+}
+
+fn bad_request(error: impl std::fmt::Display) -> (StatusCode, Json<serde_json::Value>) {
+    (
+        StatusCode::BAD_REQUEST,
+        Json(serde_json::json!({ "status": "error", "error": error.to_string() })),
+    )
+}
+
+/// GET /api/v1/schedules
+pub async fn list_schedules(State(state): State<AppState>) -> Result<Json<serde_json::Value>, StatusCode> {
+    let entries = scheduler(&state).list().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(Json(serde_json::json!({
+        "status": "ok",
+        "count": entries.len(),
+        "schedules": entries,
+    })))
+}
+
+/// POST /api/v1/schedules
+pub async fn create_schedule(
+    State(state): State<AppState>,
+    Json(req): Json<CreateSchedule>,
+) -> (StatusCode, Json<serde_json::Value>) {
+    let ctx = TenantContext {
+        tenant_id: req.tenant_id,
+        correlation_id: None,
+        agent_id: req.agent_id,
+        ..Default::default()
+    };
+    let now_ms = state.knowledge.now_ms();
+    let run_at_ms = req.run_at_ms.or_else(|| {
+        req.delay_secs
+            .map(|secs| now_ms.saturating_add(secs.saturating_mul(1000).min(i64::MAX as u64) as i64))
+    });
+    let entry = match (req.schedule, run_at_ms) {
+        (Some(schedule), None) => match ScheduledGoal::recurring(req.name, &schedule, req.goal, ctx, now_ms) {
+            Ok(entry) => entry,
+            Err(e) => return bad_request(e),
+        },
+        (None, Some(run_at_ms)) => ScheduledGoal::delayed(req.name, run_at_ms, req.goal, ctx, now_ms),
+        _ => return bad_request("set exactly one of schedule, run_at_ms or delay_secs"),
+    };
+    match scheduler(&state).set(&entry) {
+        Ok(()) => (
+            StatusCode::CREATED,
+            Json(serde_json::json!({ "status": "ok", "schedule": entry })),
+        ),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({ "status": "error", "error": e.to_string() })),
+        ),
+    }
+}
+
+/// GET /api/v1/schedules/:id
+pub async fn get_schedule(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+    let entry = scheduler(&state).get(&id).ok_or(StatusCode::NOT_FOUND)?;
+    Ok(Json(serde_json::json!({ "status": "ok", "schedule": entry })))
+}
+
+/// DELETE /api/v1/schedules/:id
+pub async fn delete_schedule(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+    let removed = scheduler(&state)
+        .remove(&id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    if !removed {
+        return Err(StatusCode::NOT_FOUND);
+    }
+    Ok(Json(serde_json::json!({ "status": "ok", "removed": id })))
+}
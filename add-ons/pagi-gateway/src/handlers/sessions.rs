@@ -0,0 +1,80 @@
+//! Conversation sessions: the turns chat recorded under a `session_id`.
+//!
+//! Every chat exchange (`/api/v1/chat`, `/api/v1/chat/stream`, the WebSocket `chat` message) is
+//! stored as the next turn of its session in KB_CHRONOS, keyed by tenant (the chat's
+//! `user_alias`), agent and session ([`ConversationManager`](pagi_core::ConversationManager)).
+//! The session's recent turns and relevant older ones are added to the next chat prompt; the
+//! Heartbeat summarizes older turns into digests, which stand in for them in that prompt.
+//!
+//! Routes:
+//! - `GET /api/v1/sessions/:id/history?user_alias=&agent_id=&limit=` – the session's latest
+//!   `limit` turns (default 50), oldest first, with the session's metadata and its digests
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use pagi_core::{is_valid_session_id, ConversationManager, DEFAULT_AGENT_ID, SCAN_MAX_LIMIT};
+use serde::Deserialize;
+use std::sync::Arc;
+
+use crate::AppState;
+
+/// Turns returned when the query does not set `limit`.
+const DEFAULT_HISTORY_LIMIT: usize = 50;
+
+fn error(status: StatusCode, message: impl Into<String>) -> (StatusCode, Json<serde_json::Value>) {
+    (status, Json(serde_json::json!({ "status": "error", "error": message.into() })))
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct HistoryQuery {
+    /// Tenant of the session, as sent in the chat request (default `studio-user`).
+    #[serde(default)]
+    pub user_alias: Option<String>,
+    #[serde(default)]
+    pub agent_id: Option<String>,
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+/// GET /api/v1/sessions/:id/history
+pub async fn get_history(
+    State(state): State<AppState>,
+    Path(session_id): Path<String>,
+    Query(query): Query<HistoryQuery>,
+) -> (StatusCode, Json<serde_json::Value>) {
+    if !is_valid_session_id(&session_id) {
+        return error(StatusCode::BAD_REQUEST, format!("invalid session id {:?}", session_id));
+    }
+    let tenant_id = query.user_alias.filter(|s| !s.is_empty()).unwrap_or_else(|| "studio-user".to_string());
+    let agent_id = query.agent_id.filter(|s| !s.is_empty()).unwrap_or_else(|| DEFAULT_AGENT_ID.to_string());
+    let limit = query.limit.unwrap_or(DEFAULT_HISTORY_LIMIT).clamp(1, SCAN_MAX_LIMIT);
+    let manager = ConversationManager::new(Arc::clone(&state.knowledge));
+    let id = session_id.clone();
+    let (tenant, agent) = (tenant_id.clone(), agent_id.clone());
+    let loaded = tokio::task::spawn_blocking(move || {
+        manager
+            .history(&tenant, &agent, &id, limit)
+            .and_then(|turns| Ok((manager.session(&tenant, &agent, &id), turns, manager.digests(&tenant, &agent, &id)?)))
+    })
+    .await;
+    let (session, turns, digests) = match loaded.map_err(|e| e.to_string()).and_then(|r| r.map_err(|e| e.to_string())) {
+        Ok(loaded) => loaded,
+        Err(e) => return error(StatusCode::INTERNAL_SERVER_ERROR, e),
+    };
+    let Some(session) = session else {
+        return error(StatusCode::NOT_FOUND, format!("session {:?} not found", session_id));
+    };
+    (
+        StatusCode::OK,
+        Json(serde_json::json!({
+            "status": "ok",
+            "session": session,
+            "count": turns.len(),
+            "turns": turns,
+            "digests": digests,
+        })),
+    )
+}
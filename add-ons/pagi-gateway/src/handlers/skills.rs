@@ -0,0 +1,38 @@
+//! Skill catalogue with execution health.
+//!
+//! Routes:
+//! - `GET /v1/skills` – registered skills with their rolling success rate, average latency and
+//!   last error (`?tenant_id=acme` lists that tenant's view of the registry)
+
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use serde::Deserialize;
+
+use crate::AppState;
+
+#[derive(Debug, Deserialize)]
+pub struct SkillsQuery {
+    #[serde(default)]
+    pub tenant_id: Option<String>,
+}
+
+/// GET /v1/skills
+pub async fn list_skills(State(state): State<AppState>, Query(query): Query<SkillsQuery>) -> Json<serde_json::Value> {
+    let stats = state.orchestrator.skill_stats();
+    let names = state.orchestrator.skill_names_for(query.tenant_id.as_deref().unwrap_or_default());
+    let skills: Vec<serde_json::Value> = names
+        .iter()
+        .map(|name| match stats.get(name) {
+            Some(s) => s.summary(),
+            None => serde_json::json!({ "skill": name, "total_calls": 0, "healthy": true }),
+        })
+        .collect();
+    Json(serde_json::json!({
+        "status": "ok",
+        "tenant_id": query.tenant_id,
+        "count": skills.len(),
+        "skills": skills,
+    }))
+}
@@ -0,0 +1,155 @@
+//! Slack slash-command bridge: `/pagi <what to do>` in Slack runs as an `AutonomousGoal`.
+//!
+//! Enabled with `[chat.slack_commands]`. Slack signs each request with the app's signing secret
+//! (`X-Slack-Signature: v0={hex}` over `v0:{timestamp}:{body}`); the signature is the credential, so
+//! the route needs no API key. Slack wants an answer within 3 seconds, so the goal is acknowledged
+//! right away and runs in the background; its result is posted to the command's `response_url`.
+//!
+//! Routes:
+//! - `POST /api/v1/chat/slack/commands` – Slack's form-encoded slash-command payload; 401 for a bad
+//!   signature, 404 when the bridge is off
+
+use axum::{
+    body::Bytes,
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    Json,
+};
+use pagi_core::{Goal, TenantContext, DEFAULT_TENANT_ID};
+use ring::hmac;
+use serde::Deserialize;
+
+use crate::AppState;
+
+/// How old `X-Slack-Request-Timestamp` may be, against replays.
+const SIGNATURE_TOLERANCE_SECS: i64 = 5 * 60;
+/// Slack truncates longer messages.
+const SLACK_MAX_CHARS: usize = 3000;
+
+/// The fields PAGI uses from Slack's slash-command payload.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct SlackCommand {
+    pub command: String,
+    pub text: String,
+    pub user_id: String,
+    pub user_name: String,
+    pub channel_id: String,
+    pub team_id: String,
+    pub response_url: String,
+}
+
+fn ephemeral(text: impl Into<String>) -> Json<serde_json::Value> {
+    Json(serde_json::json!({ "response_type": "ephemeral", "text": text.into() }))
+}
+
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+/// Checks Slack's request signature; the reason when it does not verify.
+fn verify_slack_signature(headers: &HeaderMap, body: &[u8], secret: &str, now_secs: i64) -> Result<(), &'static str> {
+    let header = |name: &str| headers.get(name).and_then(|v| v.to_str().ok()).map(str::trim);
+    let timestamp = header("X-Slack-Request-Timestamp").ok_or("missing X-Slack-Request-Timestamp")?;
+    let secs: i64 = timestamp.parse().map_err(|_| "malformed X-Slack-Request-Timestamp")?;
+    if (now_secs - secs).abs() > SIGNATURE_TOLERANCE_SECS {
+        return Err("stale X-Slack-Request-Timestamp");
+    }
+    let signature = header("X-Slack-Signature")
+        .and_then(|s| s.strip_prefix("v0="))
+        .and_then(decode_hex)
+        .ok_or("missing or malformed X-Slack-Signature")?;
+    let message = [b"v0:", timestamp.as_bytes(), b":", body].concat();
+    let key = hmac::Key::new(hmac::HMAC_SHA256, secret.as_bytes());
+    hmac::verify(&key, &message, &signature).map_err(|_| "signature does not match")
+}
+
+/// The message posted back to Slack for a goal's outcome.
+fn result_text(intent: &str, result: &Result<serde_json::Value, String>) -> String {
+    let text = match result {
+        Ok(body) => match body["status"].as_str() {
+            Some("policy_violation") => format!(
+                "\"{}\" was blocked by policy: {}",
+                intent,
+                body["reason"].as_str().unwrap_or("no reason given")
+            ),
+            Some("approval_required") => format!("\"{}\" is waiting for approval", intent),
+            _ => match ["generated", "summary", "message"].iter().find_map(|k| body[*k].as_str()) {
+                Some(text) => text.to_string(),
+                None => format!("\"{}\" finished:\n```{}```", intent, body),
+            },
+        },
+        Err(e) => format!("\"{}\" failed: {}", intent, e),
+    };
+    text.chars().take(SLACK_MAX_CHARS).collect()
+}
+
+/// POST /api/v1/chat/slack/commands
+pub async fn slack_command(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> (StatusCode, Json<serde_json::Value>) {
+    let config = state.config.get();
+    let bridge = &config.chat.slack_commands;
+    if !bridge.enabled {
+        return (StatusCode::NOT_FOUND, ephemeral("Slash commands are not enabled"));
+    }
+    let Some(secret) = pagi_core::secret(bridge.signing_secret_env()) else {
+        tracing::warn!(target: "pagi::chat", secret = bridge.signing_secret_env(), "Slack signing secret is not set");
+        return (StatusCode::SERVICE_UNAVAILABLE, ephemeral("Slack signing secret is not configured"));
+    };
+    let now_secs = state.knowledge.clock().now_secs() as i64;
+    if let Err(reason) = verify_slack_signature(&headers, &body, &secret, now_secs) {
+        tracing::warn!(target: "pagi::chat", reason, "Slack command signature rejected");
+        return (StatusCode::UNAUTHORIZED, ephemeral(reason));
+    }
+    let command: SlackCommand = match serde_urlencoded::from_bytes(&body) {
+        Ok(command) => command,
+        Err(e) => return (StatusCode::BAD_REQUEST, ephemeral(format!("Malformed slash command: {}", e))),
+    };
+    let intent = command.text.trim().to_string();
+    if intent.is_empty() {
+        return (StatusCode::OK, ephemeral(format!("Usage: {} <what to do>", command.command)));
+    }
+
+    let correlation_id = uuid::Uuid::new_v4().to_string();
+    let ctx = TenantContext {
+        tenant_id: bridge.tenant_id.clone().unwrap_or_else(|| DEFAULT_TENANT_ID.to_string()),
+        correlation_id: Some(correlation_id.clone()),
+        agent_id: bridge.agent_id.clone(),
+        ..Default::default()
+    };
+    let goal = Goal::AutonomousGoal {
+        intent: intent.clone(),
+        context: Some(serde_json::json!({
+            "prompt": intent,
+            "source": "slack",
+            "command": command.command,
+            "user_id": command.user_id,
+            "user_name": command.user_name,
+            "channel_id": command.channel_id,
+            "team_id": command.team_id,
+        })),
+    };
+    tracing::info!(target: "pagi::chat", user = %command.user_id, correlation_id = %correlation_id, "Slack command dispatched");
+    let ack = format!("Working on \"{}\" (correlation id {})", intent, correlation_id);
+    let response_url = command.response_url;
+    tokio::spawn(async move {
+        let result = crate::run_goal(&state, ctx, goal).await.map_err(|e| e.to_string());
+        if response_url.is_empty() {
+            return;
+        }
+        let reply = serde_json::json!({ "response_type": "in_channel", "text": result_text(&intent, &result) });
+        if let Err(e) = reqwest::Client::new().post(&response_url).json(&reply).send().await {
+            tracing::warn!(target: "pagi::chat", error = %e, "Failed to post the goal result back to Slack");
+        }
+    });
+    (StatusCode::OK, ephemeral(ack))
+}
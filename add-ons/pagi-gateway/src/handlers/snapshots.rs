@@ -0,0 +1,138 @@
+//! Knowledge snapshots: point-in-time copies of every sled tree under
+//! `{storage_path}/snapshots/{id}/`, for rolling back after a bad autonomous run corrupts knowledge.
+//!
+//! Set `PAGI_SNAPSHOT_INTERVAL_HOURS` to have the Heartbeat take a snapshot whenever the newest
+//! one is older than that. Retention: the newest `PAGI_SNAPSHOT_KEEP` (default 7) are kept; older
+//! ones are deleted, or kept until `PAGI_SNAPSHOT_MAX_AGE_DAYS` when that is set. Every restore
+//! first snapshots the state it replaces. The same operations run from the command line with
+//! `--snapshot` and `--restore-snapshot <id> [--slot <n>]` (gateway stopped).
+//!
+//! Listing needs the `read` permission, the other routes `kb_admin` (see `auth`).
+//!
+//! Routes:
+//! - `GET /api/v1/knowledge/snapshots` – list snapshots, newest first
+//! - `POST /api/v1/knowledge/snapshots` – take a snapshot now
+//! - `POST /api/v1/knowledge/snapshots/:id/restore` – roll back `{ slot_id? }` (default: whole store)
+//! - `DELETE /api/v1/knowledge/snapshots/:id` – delete a snapshot
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use pagi_core::{KnowledgeStore, SnapshotManager, SnapshotReason, SnapshotRetention, SNAPSHOT_DIR_NAME};
+use serde::Deserialize;
+use std::sync::Arc;
+
+use crate::AppState;
+
+/// Retention from `PAGI_SNAPSHOT_KEEP` and `PAGI_SNAPSHOT_MAX_AGE_DAYS`.
+pub fn retention_from_env() -> SnapshotRetention {
+    let default = SnapshotRetention::default();
+    SnapshotRetention {
+        keep_last: std::env::var("PAGI_SNAPSHOT_KEEP")
+            .ok()
+            .and_then(|s| s.trim().parse::<usize>().ok())
+            .filter(|n| *n > 0)
+            .unwrap_or(default.keep_last),
+        max_age_days: std::env::var("PAGI_SNAPSHOT_MAX_AGE_DAYS")
+            .ok()
+            .and_then(|s| s.trim().parse::<u64>().ok())
+            .filter(|d| *d > 0),
+    }
+}
+
+/// Scheduled snapshot interval; None unless `PAGI_SNAPSHOT_INTERVAL_HOURS` is a positive number.
+pub fn interval_from_env() -> Option<std::time::Duration> {
+    std::env::var("PAGI_SNAPSHOT_INTERVAL_HOURS")
+        .ok()
+        .and_then(|s| s.trim().parse::<u64>().ok())
+        .filter(|h| *h > 0)
+        .map(|h| std::time::Duration::from_secs(h * 60 * 60))
+}
+
+/// Manager for the snapshots of the store at `{storage_path}/pagi_knowledge`, with the retention
+/// (and, when configured, the schedule) from the environment.
+pub fn manager(knowledge: Arc<KnowledgeStore>, storage_path: &str) -> SnapshotManager {
+    let manager = SnapshotManager::new(knowledge, std::path::Path::new(storage_path).join(SNAPSHOT_DIR_NAME))
+        .with_retention(retention_from_env());
+    match interval_from_env() {
+        Some(interval) => manager.with_interval(interval),
+        None => manager,
+    }
+}
+
+fn error(status: StatusCode, message: impl Into<String>) -> (StatusCode, Json<serde_json::Value>) {
+    (status, Json(serde_json::json!({ "status": "error", "error": message.into() })))
+}
+
+/// GET /api/v1/knowledge/snapshots
+pub async fn list_snapshots(State(state): State<AppState>) -> (StatusCode, Json<serde_json::Value>) {
+    let manager = manager(Arc::clone(&state.knowledge), &state.config.get().storage_path);
+    match manager.list() {
+        Ok(snapshots) => (
+            StatusCode::OK,
+            Json(serde_json::json!({
+                "status": "ok",
+                "count": snapshots.len(),
+                "retention": manager.retention(),
+                "snapshots": snapshots,
+            })),
+        ),
+        Err(e) => error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    }
+}
+
+/// POST /api/v1/knowledge/snapshots
+pub async fn create_snapshot(
+    State(state): State<AppState>,
+) -> (StatusCode, Json<serde_json::Value>) {
+    let manager = manager(Arc::clone(&state.knowledge), &state.config.get().storage_path);
+    match tokio::task::spawn_blocking(move || manager.create(SnapshotReason::Manual)).await {
+        Ok(Ok(info)) => (StatusCode::CREATED, Json(serde_json::json!({ "status": "ok", "snapshot": info }))),
+        Ok(Err(e)) => error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+        Err(e) => error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct RestoreSnapshotRequest {
+    /// Slot 1–9 to roll back; the whole store when absent.
+    #[serde(default)]
+    pub slot_id: Option<u8>,
+}
+
+/// POST /api/v1/knowledge/snapshots/:id/restore
+pub async fn restore_snapshot(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    body: Option<Json<RestoreSnapshotRequest>>,
+) -> (StatusCode, Json<serde_json::Value>) {
+    let req = body.map(|Json(b)| b).unwrap_or_default();
+    if req.slot_id.is_some_and(|s| !(1..=9).contains(&s)) {
+        return error(StatusCode::BAD_REQUEST, "slot_id must be 1-9");
+    }
+    let manager = manager(Arc::clone(&state.knowledge), &state.config.get().storage_path);
+    match manager.get(&id) {
+        Ok(Some(_)) => {}
+        Ok(None) => return error(StatusCode::NOT_FOUND, format!("no snapshot {}", id)),
+        Err(e) => return error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    }
+    match tokio::task::spawn_blocking(move || manager.restore(&id, req.slot_id)).await {
+        Ok(Ok(report)) => (StatusCode::OK, Json(serde_json::json!({ "status": "ok", "report": report }))),
+        Ok(Err(e)) => error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+        Err(e) => error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    }
+}
+
+/// DELETE /api/v1/knowledge/snapshots/:id
+pub async fn delete_snapshot(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> (StatusCode, Json<serde_json::Value>) {
+    match manager(Arc::clone(&state.knowledge), &state.config.get().storage_path).delete(&id) {
+        Ok(true) => (StatusCode::OK, Json(serde_json::json!({ "status": "ok", "deleted": id }))),
+        Ok(false) => error(StatusCode::NOT_FOUND, format!("no snapshot {}", id)),
+        Err(e) => error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    }
+}
@@ -0,0 +1,114 @@
+//! CRUD endpoints for scrape sources.
+//!
+//! The handlers validate requests and read or write entries through pagi-core's
+//! [`SourceRegistry`]; refreshing due sources is the Heartbeat's job, not theirs.
+//!
+//! Routes:
+//! - `GET /api/v1/sources` – list, with `fresh` and `next_refresh_ms`
+//! - `POST /api/v1/sources` – register `{ url, every, slot_id?, name?, tenant_id?, agent_id? }`
+//!   (`every` like `6h`; slot 5 and the default tenant when omitted)
+//! - `GET /api/v1/sources/:id` – fetch one
+//! - `DELETE /api/v1/sources/:id` – remove
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use pagi_core::{ScrapeSource, SourceRegistry, TenantContext, DEFAULT_TENANT_ID};
+use serde::Deserialize;
+use std::sync::Arc;
+
+use crate::AppState;
+
+/// Slot written when a source names none: CommunityScraper's Community Pulse (KB-5).
+const DEFAULT_SOURCE_SLOT: u8 = 5;
+
+#[derive(Debug, Deserialize)]
+pub struct CreateSource {
+    pub url: String,
+    /// Refresh interval (`30m`, `6h`, `1d`).
+    pub every: String,
+    #[serde(default)]
+    pub slot_id: Option<u8>,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub tenant_id: Option<String>,
+    #[serde(default)]
+    pub agent_id: Option<String>,
+}
+
+fn sources(state: &AppState) -> SourceRegistry {
+    SourceRegistry::new(Arc::clone(&state.knowledge))
+}
+
+/// GET /api/v1/sources
+pub async fn list_sources(State(state): State<AppState>) -> Result<Json<serde_json::Value>, StatusCode> {
+    let entries = sources(&state).list().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let now = state.knowledge.now_ms();
+    Ok(Json(serde_json::json!({
+        "status": "ok",
+        "count": entries.len(),
+        "stale": entries.iter().filter(|s| s.enabled && !s.is_fresh(now)).count(),
+        "sources": entries.iter().map(|s| s.status(now)).collect::<Vec<_>>(),
+    })))
+}
+
+/// POST /api/v1/sources
+pub async fn create_source(
+    State(state): State<AppState>,
+    Json(req): Json<CreateSource>,
+) -> (StatusCode, Json<serde_json::Value>) {
+    let ctx = TenantContext {
+        tenant_id: req.tenant_id.unwrap_or_else(|| DEFAULT_TENANT_ID.to_string()),
+        correlation_id: None,
+        agent_id: req.agent_id,
+        ..Default::default()
+    };
+    let name = req.name.unwrap_or_else(|| req.url.clone());
+    let slot_id = req.slot_id.unwrap_or(DEFAULT_SOURCE_SLOT);
+    let now = state.knowledge.now_ms();
+    let source = match ScrapeSource::new(name, req.url, slot_id, &req.every, ctx, now) {
+        Ok(source) => source,
+        Err(e) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(serde_json::json!({ "status": "error", "error": e.to_string() })),
+            )
+        }
+    };
+    match sources(&state).set(&source) {
+        Ok(()) => (
+            StatusCode::CREATED,
+            Json(serde_json::json!({ "status": "ok", "source": source.status(now) })),
+        ),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({ "status": "error", "error": e.to_string() })),
+        ),
+    }
+}
+
+/// GET /api/v1/sources/:id
+pub async fn get_source(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+    let source = sources(&state).get(&id).ok_or(StatusCode::NOT_FOUND)?;
+    Ok(Json(serde_json::json!({ "status": "ok", "source": source.status(state.knowledge.now_ms()) })))
+}
+
+/// DELETE /api/v1/sources/:id
+pub async fn delete_source(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+    let removed = sources(&state)
+        .remove(&id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    if !removed {
+        return Err(StatusCode::NOT_FOUND);
+    }
+    Ok(Json(serde_json::json!({ "status": "ok", "removed": id })))
+}
@@ -0,0 +1,207 @@
+//! Standing queries: saved KnowledgeQuery subscriptions with change alerts.
+//!
+//! Queries are stored in KB_SOMA by pagi-core (`KnowledgeStore::set_standing_query`). The Heartbeat
+//! calls [`run_standing_queries`] each tick; when a query's results change, an alert is delivered
+//! to the configured target (agent inbox or webhook). Webhook URLs may not point at loopback,
+//! link-local, private or cloud metadata addresses (checked at registration and again before each
+//! delivery); deliveries are sent from spawned tasks so a slow endpoint never holds up the Heartbeat.
+//!
+//! Routes:
+//! - `GET /api/v1/standing-queries` – list
+//! - `POST /api/v1/standing-queries` – create `{ name, slot_id, selector, notify }`
+//! - `GET /api/v1/standing-queries/:id` – fetch one
+//! - `DELETE /api/v1/standing-queries/:id` – remove
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use pagi_core::{
+    check_webhook_url, webhook_client, AlertTarget, KnowledgeStore, QuerySelector, StandingQuery, StandingQueryChange,
+};
+use serde::Deserialize;
+use std::sync::Arc;
+
+use crate::AppState;
+
+/// Sender id used for inbox alerts.
+pub const STANDING_QUERY_SENDER: &str = "standing_query";
+/// `payload.type` of inbox alerts (the Heartbeat does not auto-reply to these).
+pub const STANDING_QUERY_ALERT_TYPE: &str = "standing_query_alert";
+
+#[derive(Debug, Deserialize)]
+pub struct CreateStandingQuery {
+    pub name: String,
+    pub slot_id: u8,
+    pub selector: QuerySelector,
+    pub notify: AlertTarget,
+}
+
+/// GET /api/v1/standing-queries
+pub async fn list_standing_queries(
+    State(state): State<AppState>,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+    let queries = state
+        .knowledge
+        .list_standing_queries()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(Json(serde_json::json!({
+        "status": "ok",
+        "count": queries.len(),
+        "queries": queries,
+    })))
+}
+
+/// POST /api/v1/standing-queries
+pub async fn create_standing_query(
+    State(state): State<AppState>,
+    Json(req): Json<CreateStandingQuery>,
+) -> (StatusCode, Json<serde_json::Value>) {
+    if !(1..=8).contains(&req.slot_id) {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({ "status": "error", "error": "slot_id must be 1-8" })),
+        );
+    }
+    if let AlertTarget::Webhook { url } = &req.notify {
+        if let Err(e) = check_webhook_url(url).await {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(serde_json::json!({ "status": "error", "error": e })),
+            );
+        }
+    }
+    let query = StandingQuery::new(req.name, req.slot_id, req.selector, req.notify, state.knowledge.now_ms());
+    match state.knowledge.set_standing_query(&query) {
+        Ok(()) => (
+            StatusCode::CREATED,
+            Json(serde_json::json!({ "status": "ok", "query": query })),
+        ),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({ "status": "error", "error": e.to_string() })),
+        ),
+    }
+}
+
+/// GET /api/v1/standing-queries/:id
+pub async fn get_standing_query(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+    let query = state.knowledge.get_standing_query(&id).ok_or(StatusCode::NOT_FOUND)?;
+    Ok(Json(serde_json::json!({ "status": "ok", "query": query })))
+}
+
+/// DELETE /api/v1/standing-queries/:id
+pub async fn delete_standing_query(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+    let removed = state
+        .knowledge
+        .remove_standing_query(&id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    if !removed {
+        return Err(StatusCode::NOT_FOUND);
+    }
+    Ok(Json(serde_json::json!({ "status": "ok", "removed": id })))
+}
+
+/// Posts `payload` to `url` after re-checking it (DNS may have changed since registration).
+async fn deliver_webhook(query_id: String, url: String, payload: serde_json::Value) {
+    let result = match check_webhook_url(&url).await {
+        Ok(()) => webhook_client()
+            .post(&url)
+            .json(&payload)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map(|_| ())
+            .map_err(|e| e.to_string()),
+        Err(e) => Err(e),
+    };
+    if let Err(e) = result {
+        tracing::warn!(
+            target: "pagi::standing_query",
+            query_id = %query_id,
+            url = %url,
+            error = %e,
+            "Standing query webhook delivery failed"
+        );
+    }
+}
+
+fn alert_payload(change: &StandingQueryChange) -> serde_json::Value {
+    serde_json::json!({
+        "type": STANDING_QUERY_ALERT_TYPE,
+        "query_id": change.query.id,
+        "name": change.query.name,
+        "slot_id": change.query.slot_id,
+        "selector": change.query.selector,
+        "match_count": change.match_count,
+        "previous_fingerprint": change.previous_fingerprint,
+        "fingerprint": change.query.last_fingerprint,
+        "changed_at_ms": change.query.last_changed_ms,
+        "snapshot": change.snapshot,
+    })
+}
+
+/// Re-evaluates standing queries and delivers alerts for changed results. Called by the Heartbeat.
+/// Webhooks are posted from spawned tasks; delivery failures are logged, and the new fingerprint
+/// is kept so a dead webhook does not re-alert forever.
+pub async fn run_standing_queries(knowledge: &Arc<KnowledgeStore>) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
+    let changes = knowledge.check_standing_queries()?;
+    for change in &changes {
+        let payload = alert_payload(change);
+        match &change.query.notify {
+            AlertTarget::Inbox { agent_id } => {
+                knowledge.push_agent_message(STANDING_QUERY_SENDER, agent_id, &payload)?;
+            }
+            AlertTarget::Webhook { url } => {
+                tokio::spawn(deliver_webhook(change.query.id.clone(), url.clone(), payload));
+            }
+        }
+    }
+    Ok(changes.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn inbox_alert_fires_only_when_results_change() {
+        let dir = std::env::temp_dir().join(format!("pagi_standing_query_{}", uuid::Uuid::new_v4().simple()));
+        let knowledge = Arc::new(KnowledgeStore::open_path(&dir).unwrap());
+        let query = StandingQuery::new(
+            "competitor watch",
+            3,
+            QuerySelector::Prefix { prefix: "research/competitorX".into() },
+            AlertTarget::Inbox { agent_id: "analyst".into() },
+            knowledge.now_ms(),
+        );
+        knowledge.set_standing_query(&query).unwrap();
+
+        // Baseline, then no change.
+        assert_eq!(run_standing_queries(&knowledge).await.unwrap(), 0);
+        assert_eq!(run_standing_queries(&knowledge).await.unwrap(), 0);
+
+        knowledge.insert(3, "research/competitorX/pricing", b"{\"tier\":\"pro\"}").unwrap();
+        knowledge.insert(3, "research/other", b"ignored").unwrap();
+        assert_eq!(run_standing_queries(&knowledge).await.unwrap(), 1);
+        assert_eq!(run_standing_queries(&knowledge).await.unwrap(), 0);
+
+        let inbox = knowledge.get_agent_messages("analyst", 10).unwrap();
+        assert_eq!(inbox.len(), 1);
+        assert_eq!(inbox[0].from_agent_id, STANDING_QUERY_SENDER);
+        assert_eq!(inbox[0].payload["type"], STANDING_QUERY_ALERT_TYPE);
+        assert_eq!(inbox[0].payload["snapshot"]["research/competitorX/pricing"]["tier"], "pro");
+
+        let stored = knowledge.get_standing_query(&query.id).unwrap();
+        assert!(stored.last_changed_ms.is_some());
+        drop(knowledge);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,103 @@
+//! Per-tenant skill registries: which skills a tenant may run, plus tenant-scoped skill config.
+//!
+//! Policies load from `PAGI_TENANTS_PATH` (default `config/tenants.json`, optional) and can be
+//! replaced at runtime. A tenant's `skill_config.ModelRouter` (`api_url`, `api_key_env`, `model`)
+//! gives that tenant its own ModelRouter instance, metered against the same token budgets.
+//!
+//! Routes:
+//! - `GET /api/v1/tenants/:tenant_id/skills` – skills visible to the tenant and its policy
+//! - `POST /api/v1/tenants/:tenant_id/policy` – replace the tenant's policy (runtime only)
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use pagi_core::{KnowledgeStore, TenantSkillPolicy, TenantSkillRegistry, TokenBudgetConfig};
+use pagi_skills::{LlmApiConfig, ModelRouter};
+use std::sync::Arc;
+
+use crate::AppState;
+
+const MODEL_ROUTER: &str = "ModelRouter";
+
+/// Loads tenant policies (missing default file = no policies) and registers tenant-scoped
+/// ModelRouter instances for tenants that configure one.
+pub fn load_tenant_registry(knowledge: &Arc<KnowledgeStore>, budgets: &TokenBudgetConfig) -> Arc<TenantSkillRegistry> {
+    let explicit = std::env::var("PAGI_TENANTS_PATH").ok();
+    let path = explicit.clone().unwrap_or_else(|| "config/tenants.json".to_string());
+    let tenants = if explicit.is_some() || std::path::Path::new(&path).exists() {
+        TenantSkillRegistry::load_json_path(&path).unwrap_or_else(|e| {
+            tracing::warn!(target: "pagi::gateway", path = %path, error = %e, "Failed to load tenant policies");
+            TenantSkillRegistry::new()
+        })
+    } else {
+        TenantSkillRegistry::new()
+    };
+    for tenant_id in tenants.tenant_ids() {
+        register_tenant_skills(&tenants, &tenant_id, knowledge, budgets);
+    }
+    Arc::new(tenants)
+}
+
+/// Builds tenant-specific skill instances from the tenant's `skill_config`.
+fn register_tenant_skills(
+    tenants: &TenantSkillRegistry,
+    tenant_id: &str,
+    knowledge: &Arc<KnowledgeStore>,
+    budgets: &TokenBudgetConfig,
+) {
+    let Some(config) = tenants.skill_config(tenant_id, MODEL_ROUTER) else {
+        return;
+    };
+    match serde_json::from_value::<LlmApiConfig>(config) {
+        Ok(api) => {
+            tenants.register_for_tenant(
+                tenant_id,
+                Arc::new(
+                    ModelRouter::with_knowledge(Arc::clone(knowledge))
+                        .with_api_config(api)
+                        .with_budgets(budgets.clone()),
+                ),
+            );
+            tracing::info!(target: "pagi::gateway", tenant_id, "Tenant-scoped ModelRouter registered");
+        }
+        Err(e) => {
+            tracing::warn!(target: "pagi::gateway", tenant_id, error = %e, "Invalid ModelRouter config for tenant");
+        }
+    }
+}
+
+fn tenant_registry(state: &AppState) -> Result<&Arc<TenantSkillRegistry>, StatusCode> {
+    state.orchestrator.tenants().ok_or(StatusCode::NOT_FOUND)
+}
+
+/// GET /api/v1/tenants/:tenant_id/skills
+pub async fn get_tenant_skills(
+    State(state): State<AppState>,
+    Path(tenant_id): Path<String>,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+    let tenants = tenant_registry(&state)?;
+    Ok(Json(serde_json::json!({
+        "status": "ok",
+        "tenant_id": tenant_id,
+        "skills": state.orchestrator.skill_names_for(&tenant_id),
+        "policy": tenants.policy(&tenant_id),
+    })))
+}
+
+/// POST /api/v1/tenants/:tenant_id/policy
+pub async fn set_tenant_policy(
+    State(state): State<AppState>,
+    Path(tenant_id): Path<String>,
+    Json(policy): Json<TenantSkillPolicy>,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+    let tenants = tenant_registry(&state)?;
+    tenants.set_policy(tenant_id.clone(), policy);
+    register_tenant_skills(tenants, &tenant_id, &state.knowledge, &state.config.get().token_budgets);
+    Ok(Json(serde_json::json!({
+        "status": "ok",
+        "tenant_id": tenant_id,
+        "skills": state.orchestrator.skill_names_for(&tenant_id),
+    })))
+}
@@ -0,0 +1,91 @@
+//! Trace replay: re-runs an `AutonomousGoal` trace that ResearchAudit stored in KB-8 against the
+//! current skills and blueprint, and answers each step's outcome next to the recorded one.
+//!
+//! `mode: "recorded"` (the default) calls every skill step again with its recorded input, to
+//! reproduce a bug deterministically; `mode: "chained"` runs the plan again from the recorded
+//! context, to regression-test blueprint or skill changes (see `pagi_core::ReplayMode`). Skills
+//! run for real: steps with side effects (lead capture, KB writes, messages) repeat them. The
+//! replay is not stored as a new trace. Needs the `execute` permission (see `auth`).
+//!
+//! Routes:
+//! - `POST /v1/research/trace/:trace_id/replay` – `{ mode?, tenant_id?, agent_id? }`; answers
+//!   `{ status, trace_id, replay }` with `replay.steps[].diff` `same`, `changed`, `added` or `removed`
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Extension, Json,
+};
+use pagi_core::{CancellationToken, InvalidTrace, ReplayMode, TenantContext, Throttled, DEFAULT_TENANT_ID};
+use serde::Deserialize;
+
+use super::auth::Caller;
+use crate::AppState;
+
+const KB_SLOT_INTERNAL_RESEARCH: u8 = 8;
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ReplayRequest {
+    #[serde(default)]
+    pub mode: ReplayMode,
+    /// Tenant the skills run as (default `default`).
+    #[serde(default)]
+    pub tenant_id: Option<String>,
+    #[serde(default)]
+    pub agent_id: Option<String>,
+}
+
+fn error(status: StatusCode, message: impl Into<String>) -> Response {
+    (status, Json(serde_json::json!({ "status": "error", "error": message.into() }))).into_response()
+}
+
+/// POST /v1/research/trace/:trace_id/replay
+pub async fn replay_trace(
+    State(state): State<AppState>,
+    caller: Option<Extension<Caller>>,
+    Path(trace_id): Path<String>,
+    body: Option<Json<ReplayRequest>>,
+) -> Response {
+    let Json(req) = body.unwrap_or_default();
+    if !state.orchestrator.pagi_skills_enabled() {
+        return error(StatusCode::SERVICE_UNAVAILABLE, "Skills execution is disabled by the control panel.");
+    }
+    let tenant_id = match super::auth::acting_tenant(caller.as_ref(), req.tenant_id.as_deref()) {
+        Ok(tenant_id) => tenant_id.unwrap_or_else(|| DEFAULT_TENANT_ID.to_string()),
+        Err(denied) => return denied.into_response(),
+    };
+    let stored = match state.knowledge.get_or_archived(KB_SLOT_INTERNAL_RESEARCH, &trace_id) {
+        Ok(Some(bytes)) => bytes,
+        Ok(None) => return error(StatusCode::NOT_FOUND, format!("no trace {}", trace_id)),
+        Err(e) => return error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    };
+    let trace = match serde_json::from_slice::<serde_json::Value>(&stored) {
+        Ok(record) => record.get("trace").cloned().unwrap_or_default(),
+        Err(e) => return error(StatusCode::INTERNAL_SERVER_ERROR, format!("stored trace is not JSON: {}", e)),
+    };
+    let ctx = TenantContext {
+        tenant_id,
+        // Without one, `require_approval` rules block the step instead of filing an approval.
+        correlation_id: None,
+        agent_id: req.agent_id,
+        ..Default::default()
+    };
+    let token = CancellationToken::new();
+    match state.orchestrator.replay_trace(&ctx, &trace, req.mode, &token).await {
+        Ok(replay) => {
+            tracing::info!(
+                target: "pagi::gateway",
+                trace_id = %trace_id,
+                changed_steps = replay.changed_steps,
+                "Trace replay finished"
+            );
+            Json(serde_json::json!({ "status": "ok", "trace_id": trace_id, "replay": replay })).into_response()
+        }
+        Err(e) if e.is::<InvalidTrace>() => error(StatusCode::UNPROCESSABLE_ENTITY, e.to_string()),
+        Err(e) => match e.downcast_ref::<Throttled>() {
+            Some(throttled) => crate::throttled_response(throttled),
+            None => error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+        },
+    }
+}
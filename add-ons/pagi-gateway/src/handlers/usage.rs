@@ -0,0 +1,64 @@
+//! LLM token usage per tenant and its monthly budget.
+//!
+//! The ModelRouter adds every live generation's tokens to the tenant's record for the month in
+//! KB_SOMA ([`KnowledgeStore::record_token_usage`](pagi_core::KnowledgeStore::record_token_usage))
+//! and refuses generations once `[token_budgets]` is used up.
+//!
+//! Routes:
+//! - `GET /api/v1/usage/:tenant_id` – this month's usage (totals and per agent), the budget and
+//!   what is left of it, plus every earlier month on record
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use pagi_core::{keys, usage_month, TokenUsageRecord};
+use std::sync::Arc;
+
+use crate::AppState;
+
+/// GET /api/v1/usage/:tenant_id
+pub async fn get_usage(
+    State(state): State<AppState>,
+    Path(tenant_id): Path<String>,
+) -> (StatusCode, Json<serde_json::Value>) {
+    if !keys::is_valid_tenant_id(&tenant_id) {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({ "status": "error", "error": format!("invalid tenant id {:?}", tenant_id) })),
+        );
+    }
+    let knowledge = Arc::clone(&state.knowledge);
+    let id = tenant_id.clone();
+    let listed = tokio::task::spawn_blocking(move || knowledge.list_token_usage(&id)).await;
+    let months = match listed.map_err(|e| e.to_string()).and_then(|r| r.map_err(|e| e.to_string())) {
+        Ok(months) => months,
+        Err(error) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({ "status": "error", "error": error })),
+            )
+        }
+    };
+    let month = usage_month(state.knowledge.now_ms());
+    let current = months.iter().find(|m| m.month == month).cloned().unwrap_or_else(|| TokenUsageRecord {
+        tenant_id: tenant_id.clone(),
+        month: month.clone(),
+        ..Default::default()
+    });
+    let budget = state.config.get().token_budgets.monthly_limit(&tenant_id);
+    (
+        StatusCode::OK,
+        Json(serde_json::json!({
+            "status": "ok",
+            "tenant_id": tenant_id,
+            "month": month,
+            "budget_tokens": budget,
+            "remaining_tokens": budget.map(|b| b.saturating_sub(current.total_tokens)),
+            "exceeded": budget.is_some_and(|b| current.total_tokens >= b),
+            "usage": current,
+            "history": months,
+        })),
+    )
+}
@@ -0,0 +1,122 @@
+//! Outbound webhooks: external services notified of `lead.captured`, `draft.generated`,
+//! `policy.violated` and `maintenance.opened`.
+//!
+//! Entries and their delivery queue are stored in KB_OIKOS by pagi-core ([`WebhookRegistry`]).
+//! Each tick the Heartbeat spawns [`WebhookRegistry::deliver_due`], which POSTs due deliveries
+//! signed with the hook's secret and retries failures with backoff. URLs resolving to loopback,
+//! link-local, private or metadata addresses are refused with 400 (see `[webhooks]`).
+//!
+//! Routes:
+//! - `GET /api/v1/webhooks` – list (secrets are never returned)
+//! - `POST /api/v1/webhooks` – register `{ url, events?, name?, tenant_id?, secret? }` (all
+//!   events and tenants when omitted); answers the `secret`, generated unless given, once
+//! - `GET /api/v1/webhooks/:id` – fetch one
+//! - `DELETE /api/v1/webhooks/:id` – remove, with its queued deliveries
+//! - `GET /api/v1/webhooks/:id/deliveries` – queued and failed deliveries, oldest first
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use pagi_core::{DeliveryStatus, InvalidWebhook, Webhook, WebhookEvent, WebhookRegistry};
+use serde::Deserialize;
+use std::sync::Arc;
+
+use crate::AppState;
+
+#[derive(Debug, Deserialize)]
+pub struct CreateWebhook {
+    pub url: String,
+    #[serde(default)]
+    pub events: Vec<WebhookEvent>,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub tenant_id: Option<String>,
+    /// HMAC signing secret (at least 16 characters); generated when omitted.
+    #[serde(default)]
+    pub secret: Option<String>,
+}
+
+fn webhooks(state: &AppState) -> WebhookRegistry {
+    WebhookRegistry::new(Arc::clone(&state.knowledge)).with_private_hosts(state.config.get().webhooks.allow_private_hosts)
+}
+
+/// GET /api/v1/webhooks
+pub async fn list_webhooks(State(state): State<AppState>) -> Result<Json<serde_json::Value>, StatusCode> {
+    let entries = webhooks(&state).list().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(Json(serde_json::json!({
+        "status": "ok",
+        "count": entries.len(),
+        "events": WebhookEvent::ALL,
+        "webhooks": entries.iter().map(Webhook::status).collect::<Vec<_>>(),
+    })))
+}
+
+/// POST /api/v1/webhooks
+pub async fn create_webhook(
+    State(state): State<AppState>,
+    Json(req): Json<CreateWebhook>,
+) -> (StatusCode, Json<serde_json::Value>) {
+    let name = req.name.unwrap_or_else(|| req.url.clone());
+    let now = state.knowledge.now_ms();
+    let webhook = match Webhook::new(name, req.url, req.events, req.tenant_id, req.secret, now) {
+        Ok(webhook) => webhook,
+        Err(e) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(serde_json::json!({ "status": "error", "error": e.to_string() })),
+            )
+        }
+    };
+    match webhooks(&state).register(&webhook).await {
+        Ok(()) => (
+            StatusCode::CREATED,
+            Json(serde_json::json!({ "status": "ok", "webhook": webhook.status(), "secret": webhook.secret })),
+        ),
+        Err(e) => {
+            let status = if e.is::<InvalidWebhook>() { StatusCode::BAD_REQUEST } else { StatusCode::INTERNAL_SERVER_ERROR };
+            (status, Json(serde_json::json!({ "status": "error", "error": e.to_string() })))
+        }
+    }
+}
+
+/// GET /api/v1/webhooks/:id
+pub async fn get_webhook(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+    let webhook = webhooks(&state).get(&id).ok_or(StatusCode::NOT_FOUND)?;
+    Ok(Json(serde_json::json!({ "status": "ok", "webhook": webhook.status() })))
+}
+
+/// DELETE /api/v1/webhooks/:id
+pub async fn delete_webhook(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+    let removed = webhooks(&state)
+        .remove(&id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    if !removed {
+        return Err(StatusCode::NOT_FOUND);
+    }
+    Ok(Json(serde_json::json!({ "status": "ok", "removed": id })))
+}
+
+/// GET /api/v1/webhooks/:id/deliveries
+pub async fn list_deliveries(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+    let registry = webhooks(&state);
+    registry.get(&id).ok_or(StatusCode::NOT_FOUND)?;
+    let deliveries = registry.deliveries(&id).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(Json(serde_json::json!({
+        "status": "ok",
+        "pending": deliveries.iter().filter(|d| d.status == DeliveryStatus::Pending).count(),
+        "failed": deliveries.iter().filter(|d| d.status == DeliveryStatus::Failed).count(),
+        "deliveries": deliveries,
+    })))
+}
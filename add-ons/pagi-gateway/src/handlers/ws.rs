@@ -0,0 +1,619 @@
+//! WebSocket channel: chat, goal execution with live plan progress, and control-panel state on
+//! one bidirectional connection.
+//!
+//! `GET /api/v1/ws?agent_id=<id>&user_alias=<alias>` upgrades to a WebSocket bound to one agent
+//! (default `default`) for its lifetime; requests naming another agent are rejected. Every
+//! message is a JSON text frame with a `type`.
+//!
+//! Client → server (`id` is chosen by the client and echoed on every related event; one is
+//! generated when omitted):
+//! - `chat` `{ id?, prompt, model?, temperature?, max_tokens?, persona? }`
+//! - `execute` `{ id?, goal, tenant_id? }` – runs a goal like `POST /v1/execute`
+//! - `cancel` `{ id }` – cancels a running `chat` or `execute`
+//! - `control` `{ message }` – applies a `ControlPanelMessage`
+//!
+//! Server → client:
+//! - `ready` `{ agent_id, control }` – once, after the upgrade
+//! - `delta`, `usage`, `error`, `done` – chat events, same data as `/api/v1/chat/stream`
+//! - `progress` – a plan step of a running `execute` changed state ([`StepProgress`] fields)
+//! - `result` `{ status, result?, error? }` – `execute` finished
+//! - `control` `{ state }` – control state (`FullState`) after a control message from any client or a config reload
+//! - `error` `{ id?, error }` – the message was rejected
+//!
+//! Framing is a minimal RFC 6455 server (text, ping/pong, close and fragmented messages; no
+//! extensions) on top of hyper's connection upgrade.
+
+use axum::{
+    extract::{Query, Request, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+};
+use futures_util::StreamExt;
+use pagi_core::{ControlPanelMessage, DeadlineExceeded, Goal, GoalCancelled, StepProgress, TenantContext, Throttled, DEFAULT_AGENT_ID};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, PoisonError};
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::sync::{broadcast, mpsc};
+
+use crate::{chat_event_stream, deadline_body, default_deadline_ms, run_goal, AppState, ChatRequest};
+
+/// Largest accepted client message (after reassembling fragments).
+pub const MAX_MESSAGE_BYTES: usize = 1 << 20;
+
+#[derive(Debug, Default, Deserialize)]
+pub struct WsParams {
+    #[serde(default)]
+    pub agent_id: Option<String>,
+    /// Tenant for `chat` and `execute` (default `studio-user`, as in `/api/v1/chat`).
+    #[serde(default)]
+    pub user_alias: Option<String>,
+}
+
+/// What a connection is bound to for its lifetime.
+#[derive(Debug, Clone)]
+pub struct Binding {
+    pub agent_id: String,
+    pub user_alias: Option<String>,
+}
+
+impl From<WsParams> for Binding {
+    fn from(params: WsParams) -> Self {
+        Self {
+            agent_id: params
+                .agent_id
+                .filter(|a| !a.trim().is_empty())
+                .unwrap_or_else(|| DEFAULT_AGENT_ID.to_string()),
+            user_alias: params.user_alias.filter(|u| !u.trim().is_empty()),
+        }
+    }
+}
+
+/// GET /api/v1/ws
+pub async fn ws_upgrade(
+    State(state): State<AppState>,
+    Query(params): Query<WsParams>,
+    mut req: Request,
+) -> Response {
+    let accept = match handshake_accept(req.headers()) {
+        Ok(accept) => accept,
+        Err(reason) => return (StatusCode::BAD_REQUEST, reason).into_response(),
+    };
+    let binding = Binding::from(params);
+    let on_upgrade = hyper::upgrade::on(&mut req);
+    tokio::spawn(async move {
+        match on_upgrade.await {
+            Ok(upgraded) => serve(state, binding, hyper_util::rt::TokioIo::new(upgraded)).await,
+            Err(e) => tracing::warn!(target: "pagi::gateway", error = %e, "WebSocket upgrade failed"),
+        }
+    });
+    Response::builder()
+        .status(StatusCode::SWITCHING_PROTOCOLS)
+        .header(header::UPGRADE, "websocket")
+        .header(header::CONNECTION, "Upgrade")
+        .header(header::SEC_WEBSOCKET_ACCEPT, accept)
+        .body(axum::body::Body::empty())
+        .unwrap()
+}
+
+/// Validates an RFC 6455 opening handshake and returns the `Sec-WebSocket-Accept` value.
+fn handshake_accept(headers: &HeaderMap) -> Result<String, &'static str> {
+    let has_token = |name: header::HeaderName, token: &str| {
+        headers
+            .get_all(name)
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .flat_map(|v| v.split(','))
+            .any(|v| v.trim().eq_ignore_ascii_case(token))
+    };
+    if !has_token(header::UPGRADE, "websocket") || !has_token(header::CONNECTION, "upgrade") {
+        return Err("expected a WebSocket upgrade request");
+    }
+    if headers.get(header::SEC_WEBSOCKET_VERSION).map(|v| v.as_bytes()) != Some(b"13") {
+        return Err("unsupported Sec-WebSocket-Version (expected 13)");
+    }
+    let key = headers
+        .get(header::SEC_WEBSOCKET_KEY)
+        .and_then(|v| v.to_str().ok())
+        .ok_or("missing Sec-WebSocket-Key")?;
+    Ok(frame::accept_key(key.trim()))
+}
+
+/// Client ids of this connection's running requests, by correlation id.
+type Running = Arc<Mutex<HashMap<String, String>>>;
+
+/// Runs one connection until the client closes it or the transport fails. Running requests are
+/// cancelled when it ends.
+pub async fn serve<S>(state: AppState, binding: Binding, io: S)
+where
+    S: AsyncRead + AsyncWrite + Send + 'static,
+{
+    let (reader, writer) = tokio::io::split(io);
+    let (out, out_rx) = mpsc::channel::<frame::Outbound>(64);
+    let writer = tokio::spawn(frame::write_loop(writer, out_rx));
+    let running: Running = Arc::default();
+    tracing::info!(target: "pagi::gateway", agent_id = %binding.agent_id, "WebSocket connected");
+
+    let ready = serde_json::json!({ "agent_id": binding.agent_id, "control": state.orchestrator.control_state() });
+    let _ = out.send(text("ready", None, ready)).await;
+    let control = tokio::spawn(forward_control(state.orchestrator.subscribe_control(), out.clone()));
+
+    let mut reader = frame::FrameReader::new(reader);
+    loop {
+        match reader.next().await {
+            Ok(Some(frame::Message::Text(message))) => handle_message(&state, &binding, &running, &out, &message).await,
+            Ok(Some(frame::Message::Binary(_))) => {
+                let _ = out
+                    .send(text("error", None, serde_json::json!({ "error": "binary messages are not supported" })))
+                    .await;
+            }
+            Ok(Some(frame::Message::Ping(payload))) => {
+                let _ = out.send(frame::Outbound::Pong(payload)).await;
+            }
+            Ok(Some(frame::Message::Pong(_))) => {}
+            Ok(Some(frame::Message::Close)) => {
+                let _ = out.send(frame::Outbound::Close(frame::CLOSE_NORMAL)).await;
+                break;
+            }
+            Ok(None) => break,
+            Err(e) => {
+                tracing::warn!(target: "pagi::gateway", error = %e, "WebSocket protocol error");
+                let code = match e.kind() {
+                    std::io::ErrorKind::InvalidData => Some(frame::CLOSE_PROTOCOL_ERROR),
+                    std::io::ErrorKind::OutOfMemory => Some(frame::CLOSE_TOO_BIG),
+                    _ => None,
+                };
+                if let Some(code) = code {
+                    let _ = out.send(frame::Outbound::Close(code)).await;
+                }
+                break;
+            }
+        }
+    }
+
+    control.abort();
+    let correlation_ids: Vec<String> = running
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .keys()
+        .cloned()
+        .collect();
+    for correlation_id in correlation_ids {
+        state.orchestrator.cancel(&correlation_id);
+    }
+    drop(out);
+    let _ = writer.await;
+    tracing::info!(target: "pagi::gateway", agent_id = %binding.agent_id, "WebSocket closed");
+}
+
+/// A server message: `{ "type": kind, "id": id, ..data }` (`data` under `"data"` when it is not
+/// an object).
+fn text(kind: &str, id: Option<&str>, data: serde_json::Value) -> frame::Outbound {
+    let mut message = serde_json::Map::new();
+    message.insert("type".to_string(), kind.into());
+    if let Some(id) = id {
+        message.insert("id".to_string(), id.into());
+    }
+    match data {
+        serde_json::Value::Object(fields) => message.extend(fields),
+        serde_json::Value::Null => {}
+        other => {
+            message.insert("data".to_string(), other);
+        }
+    }
+    frame::Outbound::Text(serde_json::Value::Object(message).to_string())
+}
+
+fn rejected(id: Option<&str>, error: impl std::fmt::Display) -> frame::Outbound {
+    text("error", id, serde_json::json!({ "error": error.to_string() }))
+}
+
+#[derive(Deserialize)]
+struct Envelope {
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    id: Option<String>,
+    #[serde(default)]
+    agent_id: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ExecuteMessage {
+    goal: Goal,
+    #[serde(default)]
+    tenant_id: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ControlMessage {
+    message: ControlPanelMessage,
+}
+
+async fn handle_message(
+    state: &AppState,
+    binding: &Binding,
+    running: &Running,
+    out: &mpsc::Sender<frame::Outbound>,
+    message: &str,
+) {
+    let value: serde_json::Value = match serde_json::from_str(message) {
+        Ok(value) => value,
+        Err(e) => {
+            let _ = out.send(rejected(None, format!("invalid JSON: {}", e))).await;
+            return;
+        }
+    };
+    let envelope: Envelope = match serde_json::from_value(value.clone()) {
+        Ok(envelope) => envelope,
+        Err(e) => {
+            let _ = out.send(rejected(None, e)).await;
+            return;
+        }
+    };
+    let id = envelope
+        .id
+        .filter(|id| !id.is_empty())
+        .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+    if let Some(agent_id) = envelope.agent_id.filter(|a| !a.is_empty() && *a != binding.agent_id) {
+        let error = format!("connection is bound to agent {}, not {}", binding.agent_id, agent_id);
+        let _ = out.send(rejected(Some(&id), error)).await;
+        return;
+    }
+    let tenant_id = binding.user_alias.clone().unwrap_or_else(|| "studio-user".to_string());
+
+    let outcome = match envelope.kind.as_str() {
+        "chat" => serde_json::from_value::<ChatRequest>(value).map(|mut req| {
+            req.agent_id = Some(binding.agent_id.clone());
+            req.user_alias = req.user_alias.or(Some(tenant_id));
+            let Some(correlation_id) = start(running, &id) else {
+                return Err(format!("request {} is already running", id));
+            };
+            let (state, running, out, id) = (state.clone(), Arc::clone(running), out.clone(), id.clone());
+            tokio::spawn(async move {
+                let mut events = Box::pin(chat_event_stream(state, req, correlation_id.clone(), default_deadline_ms()));
+                while let Some((kind, data)) = events.next().await {
+                    if out.send(text(kind, Some(&id), data)).await.is_err() {
+                        break;
+                    }
+                }
+                finish(&running, &correlation_id);
+            });
+            Ok(())
+        }),
+        "execute" => serde_json::from_value::<ExecuteMessage>(value).map(|req| {
+            let Some(correlation_id) = start(running, &id) else {
+                return Err(format!("request {} is already running", id));
+            };
+            let ctx = TenantContext {
+                tenant_id: req.tenant_id.filter(|t| !t.is_empty()).unwrap_or(tenant_id),
+                correlation_id: Some(correlation_id.clone()),
+                agent_id: Some(binding.agent_id.clone()),
+                deadline_ms: default_deadline_ms(),
+            };
+            let (state, running, out, id) = (state.clone(), Arc::clone(running), out.clone(), id.clone());
+            tokio::spawn(async move {
+                // Subscribed before dispatch, and drained before `result`, so every step is seen.
+                let mut progress = state.orchestrator.subscribe_progress();
+                let run = run_goal(&state, ctx, req.goal);
+                tokio::pin!(run);
+                let outcome = loop {
+                    tokio::select! {
+                        outcome = &mut run => break outcome,
+                        event = progress.recv() => match event {
+                            Ok(event) => forward_progress(&out, &id, &correlation_id, event).await,
+                            Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                                tracing::warn!(target: "pagi::gateway", skipped, "WebSocket progress lagged");
+                            }
+                            Err(broadcast::error::RecvError::Closed) => {}
+                        },
+                    }
+                };
+                while let Ok(event) = progress.try_recv() {
+                    forward_progress(&out, &id, &correlation_id, event).await;
+                }
+                finish(&running, &correlation_id);
+                let _ = out.send(text("result", Some(&id), execute_result(outcome))).await;
+            });
+            Ok(())
+        }),
+        "cancel" => {
+            let correlation_id = running
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .iter()
+                .find(|(_, client_id)| **client_id == id)
+                .map(|(correlation_id, _)| correlation_id.clone());
+            Ok(match correlation_id {
+                Some(correlation_id) if state.orchestrator.cancel(&correlation_id) => Ok(()),
+                _ => Err(format!("no running request {}", id)),
+            })
+        }
+        "control" => serde_json::from_value::<ControlMessage>(value).map(|control| {
+            state.orchestrator.pagi_apply_control_signal(control.message);
+            Ok(())
+        }),
+        other => Ok(Err(format!("unknown message type {:?}", other))),
+    };
+    let error = match outcome {
+        Ok(Ok(())) => return,
+        Ok(Err(error)) => error,
+        Err(e) => e.to_string(),
+    };
+    let _ = out.send(rejected(Some(&id), error)).await;
+}
+
+/// Registers a request under a fresh correlation id; `None` when `id` is already running.
+fn start(running: &Running, id: &str) -> Option<String> {
+    let mut running = running.lock().unwrap_or_else(PoisonError::into_inner);
+    if running.values().any(|client_id| client_id == id) {
+        return None;
+    }
+    let correlation_id = uuid::Uuid::new_v4().to_string();
+    running.insert(correlation_id.clone(), id.to_string());
+    Some(correlation_id)
+}
+
+fn finish(running: &Running, correlation_id: &str) {
+    running.lock().unwrap_or_else(PoisonError::into_inner).remove(correlation_id);
+}
+
+/// `result` data for a finished `execute`, with the same statuses as `/v1/execute`.
+fn execute_result(outcome: Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>>) -> serde_json::Value {
+    match outcome {
+        Ok(result) => {
+            let status = result.get("status").and_then(|s| s.as_str()).unwrap_or("ok").to_string();
+            serde_json::json!({ "status": status, "result": result })
+        }
+        Err(e) => match e.downcast_ref::<Throttled>() {
+            Some(throttled) => serde_json::json!({
+                "status": throttled.reason,
+                "error": throttled.to_string(),
+                "retry_after_secs": throttled.retry_after_secs(),
+            }),
+            None => match e.downcast_ref::<DeadlineExceeded>() {
+                Some(exceeded) => deadline_body(exceeded),
+                None if e.is::<GoalCancelled>() => serde_json::json!({ "status": "cancelled", "error": e.to_string() }),
+                None => serde_json::json!({ "status": "error", "error": e.to_string() }),
+            },
+        },
+    }
+}
+
+/// Sends `event` as a `progress` message when it belongs to the request `correlation_id`.
+async fn forward_progress(out: &mpsc::Sender<frame::Outbound>, id: &str, correlation_id: &str, event: StepProgress) {
+    if event.correlation_id.as_deref() == Some(correlation_id) {
+        let data = serde_json::to_value(&event).unwrap_or_default();
+        let _ = out.send(text("progress", Some(id), data)).await;
+    }
+}
+
+async fn forward_control(mut control: broadcast::Receiver<ControlPanelMessage>, out: mpsc::Sender<frame::Outbound>) {
+    loop {
+        match control.recv().await {
+            Ok(state) => {
+                if out.send(text("control", None, serde_json::json!({ "state": state }))).await.is_err() {
+                    return;
+                }
+            }
+            Err(broadcast::error::RecvError::Lagged(_)) => continue,
+            Err(broadcast::error::RecvError::Closed) => return,
+        }
+    }
+}
+
+/// RFC 6455 framing for the server side: reads masked client frames, writes unmasked frames.
+pub(crate) mod frame {
+    use base64::Engine;
+    use std::io;
+    use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+    use tokio::sync::mpsc;
+
+    const ACCEPT_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+    pub const OP_CONTINUATION: u8 = 0x0;
+    pub const OP_TEXT: u8 = 0x1;
+    pub const OP_BINARY: u8 = 0x2;
+    pub const OP_CLOSE: u8 = 0x8;
+    pub const OP_PING: u8 = 0x9;
+    pub const OP_PONG: u8 = 0xA;
+
+    pub const CLOSE_NORMAL: u16 = 1000;
+    pub const CLOSE_PROTOCOL_ERROR: u16 = 1002;
+    pub const CLOSE_TOO_BIG: u16 = 1009;
+
+    /// `Sec-WebSocket-Accept` for a client's `Sec-WebSocket-Key`.
+    pub fn accept_key(key: &str) -> String {
+        let digest = ring::digest::digest(
+            &ring::digest::SHA1_FOR_LEGACY_USE_ONLY,
+            format!("{}{}", key, ACCEPT_GUID).as_bytes(),
+        );
+        base64::engine::general_purpose::STANDARD.encode(digest.as_ref())
+    }
+
+    /// A complete client message.
+    #[derive(Debug, PartialEq)]
+    pub enum Message {
+        Text(String),
+        Binary(Vec<u8>),
+        Ping(Vec<u8>),
+        Pong(Vec<u8>),
+        Close,
+    }
+
+    /// Frames queued for the writer task.
+    #[derive(Debug)]
+    pub enum Outbound {
+        Text(String),
+        Pong(Vec<u8>),
+        /// Sends a close frame and stops writing.
+        Close(u16),
+    }
+
+    fn invalid(reason: &str) -> io::Error {
+        io::Error::new(io::ErrorKind::InvalidData, reason.to_string())
+    }
+
+    /// Reassembles fragmented messages; control frames may arrive between fragments.
+    pub struct FrameReader<R> {
+        inner: R,
+        partial: Option<(u8, Vec<u8>)>,
+    }
+
+    impl<R: AsyncRead + Unpin> FrameReader<R> {
+        pub fn new(inner: R) -> Self {
+            Self { inner, partial: None }
+        }
+
+        /// Next complete message; `None` when the client disconnected. Protocol violations fail
+        /// with `InvalidData`, oversized messages with `OutOfMemory`.
+        pub async fn next(&mut self) -> io::Result<Option<Message>> {
+            loop {
+                let mut head = [0u8; 2];
+                match self.inner.read_exact(&mut head).await {
+                    Ok(_) => {}
+                    Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+                    Err(e) => return Err(e),
+                }
+                let fin = head[0] & 0x80 != 0;
+                let opcode = head[0] & 0x0F;
+                if head[0] & 0x70 != 0 {
+                    return Err(invalid("reserved bits set without a negotiated extension"));
+                }
+                if head[1] & 0x80 == 0 {
+                    return Err(invalid("client frames must be masked"));
+                }
+                let len = match head[1] & 0x7F {
+                    126 => self.inner.read_u16().await? as u64,
+                    127 => self.inner.read_u64().await?,
+                    n => n as u64,
+                };
+                let is_control = opcode & 0x8 != 0;
+                if is_control && (len > 125 || !fin) {
+                    return Err(invalid("control frames must be short and unfragmented"));
+                }
+                let buffered = self.partial.as_ref().map_or(0, |(_, data)| data.len());
+                if len as usize > super::MAX_MESSAGE_BYTES.saturating_sub(buffered) {
+                    return Err(io::Error::new(io::ErrorKind::OutOfMemory, "message too large"));
+                }
+                let mut mask = [0u8; 4];
+                self.inner.read_exact(&mut mask).await?;
+                let mut payload = vec![0u8; len as usize];
+                self.inner.read_exact(&mut payload).await?;
+                for (i, byte) in payload.iter_mut().enumerate() {
+                    *byte ^= mask[i % 4];
+                }
+
+                let (opcode, payload) = match opcode {
+                    OP_CLOSE => return Ok(Some(Message::Close)),
+                    OP_PING => return Ok(Some(Message::Ping(payload))),
+                    OP_PONG => return Ok(Some(Message::Pong(payload))),
+                    OP_TEXT | OP_BINARY if self.partial.is_some() => {
+                        return Err(invalid("new message before the previous one finished"))
+                    }
+                    OP_TEXT | OP_BINARY if !fin => {
+                        self.partial = Some((opcode, payload));
+                        continue;
+                    }
+                    OP_TEXT | OP_BINARY => (opcode, payload),
+                    OP_CONTINUATION => {
+                        let (first, mut data) = self.partial.take().ok_or_else(|| invalid("unexpected continuation"))?;
+                        data.extend_from_slice(&payload);
+                        if !fin {
+                            self.partial = Some((first, data));
+                            continue;
+                        }
+                        (first, data)
+                    }
+                    _ => return Err(invalid("unknown opcode")),
+                };
+                return match opcode {
+                    OP_TEXT => String::from_utf8(payload)
+                        .map(|s| Some(Message::Text(s)))
+                        .map_err(|_| invalid("text message is not UTF-8")),
+                    _ => Ok(Some(Message::Binary(payload))),
+                };
+            }
+        }
+    }
+
+    /// Encodes one unmasked, unfragmented frame.
+    pub fn encode(opcode: u8, payload: &[u8]) -> Vec<u8> {
+        let mut frame = Vec::with_capacity(payload.len() + 10);
+        frame.push(0x80 | opcode);
+        match payload.len() {
+            len @ 0..=125 => frame.push(len as u8),
+            len @ 126..=0xFFFF => {
+                frame.push(126);
+                frame.extend_from_slice(&(len as u16).to_be_bytes());
+            }
+            len => {
+                frame.push(127);
+                frame.extend_from_slice(&(len as u64).to_be_bytes());
+            }
+        }
+        frame.extend_from_slice(payload);
+        frame
+    }
+
+    /// Writes queued frames until the queue closes or a close frame is sent.
+    pub async fn write_loop<W: AsyncWrite + Unpin>(mut writer: W, mut queue: mpsc::Receiver<Outbound>) {
+        while let Some(outbound) = queue.recv().await {
+            let (frame, last) = match outbound {
+                Outbound::Text(text) => (encode(OP_TEXT, text.as_bytes()), false),
+                Outbound::Pong(payload) => (encode(OP_PONG, &payload), false),
+                Outbound::Close(code) => (encode(OP_CLOSE, &code.to_be_bytes()), true),
+            };
+            if writer.write_all(&frame).await.is_err() || writer.flush().await.is_err() {
+                return;
+            }
+            if last {
+                break;
+            }
+        }
+        let _ = writer.shutdown().await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::frame::*;
+
+    #[test]
+    fn accept_key_matches_rfc_example() {
+        assert_eq!(accept_key("dGhlIHNhbXBsZSBub25jZQ=="), "s3pPLMBiTxaQ9kYGzzhZRbK+xOo=");
+    }
+
+    fn masked(first: u8, payload: &[u8]) -> Vec<u8> {
+        let mask = [0x37, 0xfa, 0x21, 0x3d];
+        let mut frame = vec![first, 0x80 | payload.len() as u8];
+        frame.extend_from_slice(&mask);
+        frame.extend(payload.iter().enumerate().map(|(i, b)| b ^ mask[i % 4]));
+        frame
+    }
+
+    #[tokio::test]
+    async fn reader_reassembles_fragments_around_control_frames() {
+        let mut bytes = masked(OP_TEXT, b"Hel");
+        bytes.extend(masked(0x80 | OP_PING, b"p"));
+        bytes.extend(masked(0x80 | OP_CONTINUATION, b"lo"));
+        bytes.extend(masked(0x80 | OP_CLOSE, &[0x03, 0xe8]));
+        let mut reader = FrameReader::new(bytes.as_slice());
+        assert_eq!(reader.next().await.unwrap(), Some(Message::Ping(b"p".to_vec())));
+        assert_eq!(reader.next().await.unwrap(), Some(Message::Text("Hello".to_string())));
+        assert_eq!(reader.next().await.unwrap(), Some(Message::Close));
+        assert_eq!(reader.next().await.unwrap(), None);
+
+        let unmasked = [0x80 | OP_TEXT, 0x01, b'x'];
+        let err = FrameReader::new(&unmasked[..]).next().await.unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn encode_uses_extended_lengths() {
+        assert_eq!(encode(OP_TEXT, b"hi"), [0x81, 2, b'h', b'i']);
+        let long = encode(OP_BINARY, &[0u8; 300]);
+        assert_eq!(&long[..4], &[0x82, 126, 0x01, 0x2c]);
+        assert_eq!(long.len(), 304);
+    }
+}
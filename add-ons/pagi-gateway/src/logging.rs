@@ -0,0 +1,214 @@
+//! Gateway log events: every tracing event becomes a [`LogEvent`] with its level, target, message
+//! and fields, plus the `correlation_id` and tenant of the event or the dispatch span it ran in.
+//!
+//! Events go to the `/api/v1/logs` SSE stream (as text lines, or JSON with `?format=json`), and,
+//! with `[logging] format = "json"`, to stdout as one JSON object per line instead of the
+//! human-readable format.
+
+use pagi_core::system_now_ms;
+use serde::Serialize;
+use std::io::Write;
+use tokio::sync::broadcast;
+use tracing::field::{Field, Visit};
+use tracing_subscriber::fmt::writer::{BoxMakeWriter, MakeWriter};
+use tracing_subscriber::layer::Context;
+use tracing_subscriber::registry::LookupSpan;
+
+type Fields = serde_json::Map<String, serde_json::Value>;
+
+/// One log event.
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct LogEvent {
+    pub ts_ms: i64,
+    pub level: String,
+    pub target: String,
+    pub message: String,
+    #[serde(skip_serializing_if = "serde_json::Map::is_empty")]
+    pub fields: Fields,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub correlation_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tenant: Option<String>,
+}
+
+impl LogEvent {
+    /// Stands in for events a slow `/api/v1/logs` client missed.
+    pub fn dropped(count: u64) -> Self {
+        Self {
+            ts_ms: system_now_ms(),
+            level: "WARN".to_string(),
+            target: "pagi::gateway".to_string(),
+            message: format!("... {} log lines dropped", count),
+            fields: Fields::new(),
+            correlation_id: None,
+            tenant: None,
+        }
+    }
+
+    /// `LEVEL [target] message`, the line of the text format.
+    pub fn text(&self) -> String {
+        format!("{} [{}] {}", self.level, self.target, self.message)
+    }
+
+    pub fn json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+}
+
+/// Collects an event's or span's fields; `message` is kept apart.
+struct FieldCollector<'a> {
+    message: &'a mut String,
+    fields: &'a mut Fields,
+}
+
+impl FieldCollector<'_> {
+    fn insert(&mut self, field: &Field, value: serde_json::Value) {
+        if field.name() == "message" {
+            *self.message = match value {
+                serde_json::Value::String(s) => s,
+                other => other.to_string(),
+            };
+        } else {
+            self.fields.insert(field.name().to_string(), value);
+        }
+    }
+}
+
+impl Visit for FieldCollector<'_> {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.insert(field, value.into());
+    }
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.insert(field, value.into());
+    }
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.insert(field, value.into());
+    }
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.insert(field, value.into());
+    }
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.insert(field, value.into());
+    }
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.insert(field, format!("{:?}", value).into());
+    }
+}
+
+/// Fields of a span, kept for the events inside it.
+struct SpanFields(Fields);
+
+/// Removes `key` from `fields` as a non-empty string.
+fn take_str(fields: &mut Fields, key: &str) -> Option<String> {
+    match fields.remove(key)? {
+        serde_json::Value::String(s) if !s.is_empty() => Some(s),
+        serde_json::Value::String(_) => None,
+        other => Some(other.to_string()),
+    }
+}
+
+fn span_str(fields: &Fields, key: &str) -> Option<String> {
+    fields.get(key).and_then(|v| v.as_str()).filter(|s| !s.is_empty()).map(str::to_string)
+}
+
+/// Turns tracing events into [`LogEvent`]s for the SSE stream, and writes them as JSON lines
+/// when given a writer.
+pub(crate) struct LogLayer {
+    tx: broadcast::Sender<LogEvent>,
+    json_out: Option<BoxMakeWriter>,
+}
+
+impl LogLayer {
+    pub fn new(tx: broadcast::Sender<LogEvent>) -> Self {
+        Self { tx, json_out: None }
+    }
+
+    /// Also writes every event to `writer` as one JSON object per line.
+    pub fn with_json_output(mut self, writer: BoxMakeWriter) -> Self {
+        self.json_out = Some(writer);
+        self
+    }
+}
+
+impl<S> tracing_subscriber::Layer<S> for LogLayer
+where
+    S: tracing::Subscriber + for<'span> LookupSpan<'span>,
+{
+    fn on_new_span(&self, attrs: &tracing::span::Attributes<'_>, id: &tracing::span::Id, ctx: Context<'_, S>) {
+        let mut fields = Fields::new();
+        attrs.record(&mut FieldCollector { message: &mut String::new(), fields: &mut fields });
+        if let Some(span) = ctx.span(id) {
+            span.extensions_mut().insert(SpanFields(fields));
+        }
+    }
+
+    fn on_record(&self, id: &tracing::span::Id, values: &tracing::span::Record<'_>, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(id) {
+            if let Some(SpanFields(fields)) = span.extensions_mut().get_mut::<SpanFields>() {
+                values.record(&mut FieldCollector { message: &mut String::new(), fields });
+            }
+        }
+    }
+
+    fn on_event(&self, event: &tracing::Event<'_>, ctx: Context<'_, S>) {
+        let mut message = String::new();
+        let mut fields = Fields::new();
+        event.record(&mut FieldCollector { message: &mut message, fields: &mut fields });
+        let mut correlation_id = take_str(&mut fields, "correlation_id");
+        let mut tenant = take_str(&mut fields, "tenant_id").or_else(|| take_str(&mut fields, "tenant"));
+        if let Some(scope) = ctx.event_scope(event) {
+            for span in scope {
+                if correlation_id.is_some() && tenant.is_some() {
+                    break;
+                }
+                if let Some(SpanFields(span_fields)) = span.extensions().get::<SpanFields>() {
+                    correlation_id = correlation_id.or_else(|| span_str(span_fields, "correlation_id"));
+                    tenant = tenant.or_else(|| span_str(span_fields, "tenant_id"));
+                }
+            }
+        }
+        let event = LogEvent {
+            ts_ms: system_now_ms(),
+            level: event.metadata().level().to_string(),
+            target: event.metadata().target().to_string(),
+            message,
+            fields,
+            correlation_id,
+            tenant,
+        };
+        if let Some(writer) = &self.json_out {
+            let _ = writeln!(writer.make_writer(), "{}", event.json());
+        }
+        let _ = self.tx.send(event);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    #[test]
+    fn events_carry_fields_and_the_dispatch_span_context() {
+        let (tx, mut rx) = broadcast::channel(8);
+        let subscriber = tracing_subscriber::registry().with(LogLayer::new(tx));
+        tracing::subscriber::with_default(subscriber, || {
+            let span = tracing::info_span!("dispatch", tenant_id = "acme", correlation_id = "");
+            let _entered = span.enter();
+            span.record("correlation_id", "req-1");
+            tracing::info!(target: "pagi::test", skill = "ModelRouter", attempts = 2u64, "Skill {} ran", "ModelRouter");
+            tracing::warn!(target: "pagi::test", correlation_id = "req-2", tenant = "beta", "Overridden");
+        });
+
+        let event = rx.try_recv().unwrap();
+        assert_eq!(event.text(), "INFO [pagi::test] Skill ModelRouter ran");
+        assert_eq!((event.correlation_id.as_deref(), event.tenant.as_deref()), (Some("req-1"), Some("acme")));
+        let json: serde_json::Value = serde_json::from_str(&event.json()).unwrap();
+        assert_eq!(json["fields"], serde_json::json!({ "skill": "ModelRouter", "attempts": 2 }));
+        assert_eq!(json["level"], "INFO");
+
+        let event = rx.try_recv().unwrap();
+        assert_eq!((event.correlation_id.as_deref(), event.tenant.as_deref()), (Some("req-2"), Some("beta")));
+        assert!(event.fields.is_empty(), "lifted fields are not repeated: {:?}", event.fields);
+    }
+}
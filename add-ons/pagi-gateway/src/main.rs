@@ -1,14 +1,22 @@
 //! Axum-based API Gateway: entry point for UAC. Config-driven via CoreConfig.
 //! Chat is wired through handlers::chat with Soma+Kardia context injection (Sovereign Brain).
+//! Channel adapters (`channels`) bring the same chat to messaging services such as Telegram.
 
+mod channels;
 mod handlers;
+mod logging;
+mod oidc;
+mod server;
+mod simulation;
+mod telemetry;
 
 use axum::{
     body::Body,
     extract::{Path, State},
     extract::Json,
+    Extension,
     response::{sse::{Event, Sse}, IntoResponse, Response},
-    routing::{get, post},
+    routing::{delete, get, post, put},
     Router,
 };
 use axum::http::{HeaderMap, Method, StatusCode};
@@ -16,21 +24,22 @@ use futures_util::stream::StreamExt;
 use std::time::Duration;
 use tokio::sync::broadcast;
 use tower_http::cors::{AllowOrigin, CorsLayer};
-use tracing::field::Visit;
-use tracing_subscriber::layer::Context;
 use pagi_core::{
-    initialize_core_identity, initialize_core_skills, initialize_ethos_policy, AlignmentResult, BlueprintRegistry, CoreConfig, EventRecord, Goal, KbRecord, KbType,
-    KnowledgeStore, MentalState, MemoryManager, Orchestrator, RelationRecord, ShadowStore, ShadowStoreHandle, SkillRegistry, SovereignState, TenantContext,
+    system_now_ms, check_capabilities, initialize_core_identity, initialize_core_skills, initialize_ethos_policy, AgentMessage, AgentSkill, BlueprintRegistry, CoreConfig, DbLocked, DeadlineExceeded, DispatchQueue, DispatchQueueConfig, DomainPack, IdempotencyClaim, IntentAction, IntentRouter, IntentRoutingConfig, EventRecord, Goal, GoalCancelled, KbType,
+    KnowledgeStore, MemoryManager, Orchestrator, PackInstallStatus, PackRegistry, SchedulerRegistry, ScraperConfig, EmailConfig, ChatConfig, CalendarConfig, ShadowStore, SourceRegistry, WebhookEvent, WebhookRegistry, SnapshotManager, SnapshotReason, ShadowStoreHandle, SkillRegistry, FrozenClock, SovereignState, TenantContext,
+    translate_error, validate_blueprint, AgentTickTracker, ConversationManager, InboxRetry, RuleAction, SkillError, Throttled, EthosBlocked, Redactor, UserFacingError,
 };
+use handlers::auth::Caller;
 use pagi_skills::{
-    BioGateSync, EthosSync, ModelRouter, OikosTaskGovernor, ReflectShadowSkill,
+    BioGateSync, CommunityScraper, DraftResponse, EthosSync, ExtractEntities, FeedIngest, GraphQuery, KnowledgeQuery, KnowledgeSearch, LeadFollowUp, LlmProviders, ModelRouter,
+    OikosTaskGovernor, ParseInboundMessage, Reembedder, ReflectShadowSkill, SalesCloser, SendEmail, NotifyChat, CalendarSkill,
 };
 use std::path::Path as StdPath;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicU64, Ordering};
 use tower_http::services::{ServeDir, ServeFile};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
-use std::collections::{BTreeMap, HashSet};
+use std::collections::BTreeMap;
 
 static HEARTBEAT_TICK_COUNT: AtomicU64 = AtomicU64::new(0);
 
@@ -38,87 +47,330 @@ const TRUST_RESOLUTION_REWARD: f32 = 0.05;
 const TRUST_STALE_DECAY_PENALTY: f32 = 0.02;
 const TRUST_STALE_DECAY_TICKS: u64 = 50;
 
-/// Captures the "message" field from a tracing event.
-struct MessageCollector<'a>(&'a mut String);
+/// Exits with the lock diagnosis (owning process, or stale-lock guidance) when a sled DB cannot be
+/// opened at startup.
+fn exit_on_open_error<E: std::error::Error + 'static>(name: &str, e: E) -> ! {
+    match DbLocked::find(&e) {
+        Some(locked) => eprintln!("❌ Cannot open {}: {}", name, locked),
+        None => eprintln!("❌ Cannot open {}: {}", name, e),
+    }
+    std::process::exit(101)
+}
 
-impl Visit for MessageCollector<'_> {
-    fn record_str(&mut self, field: &tracing::field::Field, value: &str) {
-        if field.name() == "message" {
-            *self.0 = value.to_string();
-        }
+/// Skills registered by the gateway. Sovereign Brain: only ReflectShadow, BioGateSync,
+/// OikosTaskGovernor, EthosSync (+ ModelRouter for chat, ParseInboundMessage for lead triage, and
+/// DraftResponse, SalesCloser, CommunityScraper and FeedIngest for the default blueprint intents,
+/// KnowledgeQuery for `QueryKnowledge` goals and single-key lookups, KnowledgeSearch for
+/// full-text lookups across the KB, GraphQuery for the KB-3 knowledge graph,
+/// ExtractEntities to feed it, LeadFollowUp for leads stuck in `contacted`, SendEmail to
+/// deliver responses, NotifyChat for Slack and Discord, and CalendarSkill to book consultations).
+#[allow(clippy::too_many_arguments)]
+fn build_skill_registry(
+    knowledge: &Arc<KnowledgeStore>,
+    memory: &Arc<MemoryManager>,
+    shadow_store: &ShadowStoreHandle,
+    model_router: &Arc<ModelRouter>,
+    scraper: &ScraperConfig,
+    email: &EmailConfig,
+    chat: &ChatConfig,
+    calendar: &CalendarConfig,
+) -> SkillRegistry {
+    let mut registry = SkillRegistry::new();
+    // The shared router, so chat, skills, and the Heartbeat see one LLM quality window.
+    registry.register(Arc::clone(model_router) as Arc<dyn AgentSkill>);
+    registry.register(Arc::new(BioGateSync::new(Arc::clone(knowledge))));
+    registry.register(Arc::new(EthosSync::new(Arc::clone(knowledge))));
+    registry.register(Arc::new(OikosTaskGovernor::new(Arc::clone(knowledge))));
+    registry.register(Arc::new(ReflectShadowSkill::new(
+        Arc::clone(knowledge),
+        Arc::clone(shadow_store),
+        Arc::clone(model_router),
+    )));
+    registry.register(Arc::new(ParseInboundMessage::new(
+        Arc::clone(memory),
+        Arc::clone(model_router),
+    )));
+    registry.register(Arc::new(DraftResponse::new(Arc::clone(memory), Arc::clone(knowledge))));
+    registry.register(Arc::new(SalesCloser::new(Arc::clone(knowledge))));
+    registry.register(Arc::new(CommunityScraper::new(Arc::clone(knowledge)).with_config(scraper.clone())));
+    registry.register(Arc::new(FeedIngest::new(Arc::clone(knowledge)).with_config(scraper.clone())));
+    registry.register(Arc::new(KnowledgeQuery::new(Arc::clone(knowledge))));
+    registry.register(Arc::new(KnowledgeSearch::new(Arc::clone(knowledge))));
+    registry.register(Arc::new(GraphQuery::new(Arc::clone(knowledge))));
+    registry.register(Arc::new(ExtractEntities::new(
+        Arc::clone(knowledge),
+        Arc::clone(memory),
+        Arc::clone(model_router),
+    )));
+    registry.register(Arc::new(LeadFollowUp::new(Arc::clone(memory), Arc::clone(model_router))));
+    registry.register(Arc::new(SendEmail::new(Arc::clone(knowledge), Arc::clone(memory)).with_config(email.clone())));
+    registry.register(Arc::new(NotifyChat::new(chat.clone())));
+    registry.register(Arc::new(CalendarSkill::new(Arc::clone(knowledge), Arc::clone(memory)).with_config(calendar.clone())));
+    registry
+}
+
+fn blueprint_path() -> String {
+    std::env::var("PAGI_BLUEPRINT_PATH").unwrap_or_else(|_| "config/blueprint.json".to_string())
+}
+
+/// Names of the skills the gateway registers, built against scratch stores so it can run next to
+/// a live gateway.
+fn registered_skill_names() -> Result<Vec<String>, String> {
+    let scratch = std::env::temp_dir().join(format!("pagi_check_blueprint_{}", std::process::id()));
+    let skills = {
+        let knowledge = Arc::new(KnowledgeStore::open_path(scratch.join("kb")).map_err(|e| e.to_string())?);
+        let memory = Arc::new(MemoryManager::open_path(scratch.join("vault")).map_err(|e| e.to_string())?);
+        let model_router = Arc::new(ModelRouter::with_knowledge(Arc::clone(&knowledge)));
+        let shadow_store: ShadowStoreHandle = Arc::new(tokio::sync::RwLock::new(None));
+        build_skill_registry(&knowledge, &memory, &shadow_store, &model_router, &ScraperConfig::default(), &EmailConfig::default(), &ChatConfig::default(), &CalendarConfig::default())
+            .skill_names()
+    };
+    let _ = std::fs::remove_dir_all(&scratch);
+    Ok(skills)
+}
+
+/// `--check-blueprint [path]`: validates the blueprint (default `PAGI_BLUEPRINT_PATH`) against the
+/// gateway's skill registry and prints every issue with its line and column. Uses scratch stores
+/// so it can run next to a live gateway.
+fn run_check_blueprint(path: Option<String>) -> Result<(), String> {
+    let path = path.unwrap_or_else(blueprint_path);
+    let text = std::fs::read_to_string(&path).map_err(|e| format!("{}: {}", path, e))?;
+    let skills = registered_skill_names()?;
+
+    let issues = validate_blueprint(&text, Some(&skills));
+    if issues.is_empty() {
+        println!("✅ {}: OK", path);
+        return Ok(());
     }
-    fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn std::fmt::Debug) {
-        if field.name() == "message" {
-            *self.0 = format!("{:?}", value);
-        }
+    for issue in &issues {
+        println!("{}: {}", path, issue);
     }
+    Err(format!("{} issue(s) in {}", issues.len(), path))
 }
 
-/// Sends each tracing event as a line to a broadcast channel for SSE log streaming.
-#[derive(Clone)]
-struct LogBroadcastLayer {
-    tx: broadcast::Sender<String>,
+/// `--print-config`: the effective config after every layer, secret-looking values masked.
+fn run_print_config() -> Result<(), String> {
+    let sources = pagi_core::ConfigSources::current();
+    let config = CoreConfig::load_from(&sources).map_err(|e| e.to_string())?;
+    println!("# Effective configuration; layers in load order, later ones win:");
+    for layer in sources.describe() {
+        println!("#   {}", layer);
+    }
+    print!("{}", config.masked_toml()?);
+    Ok(())
+}
+
+/// `--install-pack <path> [--agent <id>] [--force]`: installs or upgrades a domain pack (file or
+/// directory) into the local knowledge store. The store is locked while the gateway runs; use
+/// `POST /api/v1/packs` against a live gateway instead.
+fn run_install_pack(path: &str, agent_id: &str, force: bool) -> Result<(), String> {
+    let pack = DomainPack::load_path(path).map_err(|e| e.to_string())?;
+    let skills = registered_skill_names()?;
+    let config = CoreConfig::load().map_err(|e| format!("Config load failed: {}", e))?;
+    let knowledge = Arc::new(
+        KnowledgeStore::open_with_backend(StdPath::new(&config.storage_path).join("pagi_knowledge"), config.storage_backend)
+            .map_err(|e| format!("pagi_knowledge LOCKED or inaccessible: {}", e))?,
+    );
+    let blueprint = BlueprintRegistry::try_load_json_path(blueprint_path())
+        .map_err(|e| e.to_string())?
+        .with_store(Arc::clone(&knowledge));
+    let report = PackRegistry::new(knowledge)
+        .install(&pack, agent_id, &blueprint, Some(&skills), force)
+        .map_err(|e| e.to_string())?;
+    if report.status == PackInstallStatus::Unchanged {
+        println!(
+            "✅ {} {} is already installed for agent {} (use --force to reinstall)",
+            report.name, report.version, report.agent_id
+        );
+        return Ok(());
+    }
+    let from = report
+        .previous_version
+        .as_deref()
+        .map(|v| format!(" (was {})", v))
+        .unwrap_or_default();
+    println!(
+        "✅ {} {} for agent {}: {:?}{}",
+        report.name, report.version, report.agent_id, report.status, from
+    );
+    println!("   intents: {}", report.intents.join(", "));
+    if !report.removed_intents.is_empty() {
+        println!("   reverted intents: {}", report.removed_intents.join(", "));
+    }
+    println!("   KB keys: {} written, {} reverted", report.kb_keys, report.removed_kb_keys);
+    Ok(())
 }
 
-impl LogBroadcastLayer {
-    fn new(tx: broadcast::Sender<String>) -> Self {
-        Self { tx }
+/// `--reembed [--slots 3,4] [--model <name>] [--concurrency <n>] [--force] [--resume <job_id>]`:
+/// regenerates stored embeddings with the current embedding provider and prints progress per
+/// batch. The store is locked while the gateway runs; use `POST /api/v1/knowledge/reembed`
+/// against a live gateway instead.
+async fn run_reembed(args: &[String]) -> Result<(), String> {
+    let flag = |name: &str| {
+        args.iter()
+            .position(|a| a == name)
+            .and_then(|j| args.get(j + 1))
+            .filter(|a| !a.starts_with("--"))
+            .cloned()
+    };
+    let config = CoreConfig::load().map_err(|e| format!("Config load failed: {}", e))?;
+    let knowledge = Arc::new(
+        KnowledgeStore::open_with_backend(StdPath::new(&config.storage_path).join("pagi_knowledge"), config.storage_backend)
+            .map_err(|e| format!("pagi_knowledge LOCKED or inaccessible: {}", e))?,
+    );
+    knowledge.interrupt_running_reembed_jobs().map_err(|e| e.to_string())?;
+    let reembedder = Reembedder::new(Arc::clone(&knowledge));
+    let job = match flag("--resume") {
+        Some(id) => {
+            let job = knowledge
+                .get_reembed_job(&id)
+                .map_err(|e| e.to_string())?
+                .ok_or_else(|| format!("no re-embedding job {}", id))?;
+            if job.is_finished() {
+                return Err(format!("re-embedding job {} is already completed", id));
+            }
+            job
+        }
+        None => {
+            let slots = flag("--slots")
+                .map(|s| {
+                    s.split(',')
+                        .map(|p| p.trim().parse::<u8>().map_err(|_| format!("invalid slot: {}", p)))
+                        .collect::<Result<Vec<_>, _>>()
+                })
+                .transpose()?;
+            let slots = handlers::reembed::validate_slots(slots)?;
+            let concurrency = match flag("--concurrency") {
+                Some(n) => n.parse::<usize>().map_err(|_| format!("invalid concurrency: {}", n))?,
+                None => handlers::reembed::default_concurrency(),
+            };
+            reembedder
+                .create_job(slots, flag("--model"), concurrency, args.iter().any(|a| a == "--force"))
+                .map_err(|e| e.to_string())?
+        }
+    };
+    println!(
+        "Re-embedding job {}: slots {:?}, model {}, {} records, concurrency {}",
+        job.id, job.slots, job.embedding_model, job.total, job.concurrency
+    );
+    let job = reembedder
+        .run(job, |job| {
+            println!(
+                "   {}/{} ({:.0}%): {} updated, {} skipped, {} failed",
+                job.processed,
+                job.total,
+                job.progress() * 100.0,
+                job.updated,
+                job.skipped,
+                job.failed
+            )
+        })
+        .await;
+    if !job.is_finished() {
+        return Err(format!(
+            "job {} stopped: {} (resume with --reembed --resume {})",
+            job.id,
+            job.error.as_deref().unwrap_or("unknown error"),
+            job.id
+        ));
+    }
+    println!(
+        "✅ Re-embedded {} records ({} already current, {} failed) with {}",
+        job.updated, job.skipped, job.failed, job.embedding_model
+    );
+    if let Some(e) = job.error.as_deref() {
+        println!("   last error: {}", e);
     }
+    Ok(())
 }
 
-impl<S> tracing_subscriber::Layer<S> for LogBroadcastLayer
-where
-    S: tracing::Subscriber,
-{
-    fn on_event(&self, event: &tracing::Event<'_>, _ctx: Context<'_, S>) {
-        let mut message = String::new();
-        event.record(&mut MessageCollector(&mut message));
-        let line = format!(
-            "{} [{}] {}",
-            event.metadata().level(),
-            event.metadata().target(),
-            message
+/// `--snapshot`: takes a knowledge snapshot. `--restore-snapshot <id> [--slot <n>]`: rolls one
+/// slot (or the whole store) back to a snapshot, after snapshotting the current state. The store
+/// is locked while the gateway runs; use `/api/v1/knowledge/snapshots` against a live gateway.
+fn run_snapshot_command(args: &[String]) -> Result<(), String> {
+    let flag = |name: &str| {
+        args.iter()
+            .position(|a| a == name)
+            .and_then(|j| args.get(j + 1))
+            .filter(|a| !a.starts_with("--"))
+            .cloned()
+    };
+    let config = CoreConfig::load().map_err(|e| format!("Config load failed: {}", e))?;
+    let knowledge = Arc::new(
+        KnowledgeStore::open_with_backend(StdPath::new(&config.storage_path).join("pagi_knowledge"), config.storage_backend)
+            .map_err(|e| format!("pagi_knowledge LOCKED or inaccessible: {}", e))?,
+    );
+    let manager = handlers::snapshots::manager(knowledge, &config.storage_path);
+    if !args.iter().any(|a| a == "--restore-snapshot") {
+        let info = manager.create(SnapshotReason::Manual).map_err(|e| e.to_string())?;
+        println!(
+            "✅ Snapshot {}: {} trees, {} records, {} bytes in {}",
+            info.id,
+            info.trees.len(),
+            info.entries(),
+            info.bytes,
+            manager.dir().join(&info.id).display()
         );
-        let _ = self.tx.send(line);
+        return Ok(());
     }
+    let id = flag("--restore-snapshot").ok_or("usage: --restore-snapshot <id> [--slot <n>]")?;
+    let slot = flag("--slot")
+        .map(|s| s.parse::<u8>().map_err(|_| format!("invalid slot: {}", s)))
+        .transpose()?;
+    let report = manager.restore(&id, slot).map_err(|e| e.to_string())?;
+    println!(
+        "✅ Restored {} from snapshot {} ({} records); previous state saved as snapshot {}",
+        slot.map(|s| format!("slot {}", s)).unwrap_or_else(|| "the whole store".to_string()),
+        report.snapshot_id,
+        report.entries,
+        report.pre_restore_snapshot_id
+    );
+    Ok(())
 }
 
-/// Pre-flight check: verify all 8 KBs are accessible and port is available.
+/// Pre-flight check: verify all 8 KBs are accessible and the configured listen addresses are free.
 fn run_verify() -> Result<(), String> {
     let config = CoreConfig::load().map_err(|e| format!("Config load failed: {}", e))?;
     let storage = StdPath::new(&config.storage_path);
     let vault_path = storage.join("pagi_vault");
     let kb_path = storage.join("pagi_knowledge");
 
-    // 1. Check MemoryManager (pagi_vault Sled)
+    // 1. Check MemoryManager (pagi_vault)
     print!("Checking pagi_vault... ");
-    let vault = MemoryManager::open_path(&vault_path).map_err(|e| format!("pagi_vault LOCKED or inaccessible: {}", e))?;
+    let vault = MemoryManager::open_with_backend(&vault_path, config.storage_backend).map_err(|e| format!("pagi_vault LOCKED or inaccessible: {}", e))?;
     drop(vault);
     println!("OK");
 
-    // 2. Check KnowledgeStore (pagi_knowledge Sled with 8 trees)
+    // 2. Check KnowledgeStore (pagi_knowledge with 8 trees)
     print!("Checking pagi_knowledge (8 KBs)... ");
-    let kb = KnowledgeStore::open_path(&kb_path).map_err(|e| format!("pagi_knowledge LOCKED or inaccessible: {}", e))?;
+    let kb = KnowledgeStore::open_with_backend(&kb_path, config.storage_backend).map_err(|e| format!("pagi_knowledge LOCKED or inaccessible: {}", e))?;
     for slot in 1..=8 {
         kb.get(slot, "__verify_probe__").map_err(|e| format!("KB slot {} failed: {}", slot, e))?;
     }
     drop(kb);
     println!("OK (all 8 slots accessible)");
 
-    // 3. Check port availability (Gateway hard-locked to 8001)
-    let port = 8001u16;
-    print!("Checking port {}... ", port);
-    let addr = std::net::SocketAddr::from(([127, 0, 0, 1], port));
-    match std::net::TcpListener::bind(addr) {
-        Ok(listener) => {
-            drop(listener);
-            println!("OK (available)");
-        }
-        Err(e) => {
-            return Err(format!("Port {} BLOCKED: {}", port, e));
+    // 3. Check that every configured address (`bind_addresses`, `port`) is free
+    for addr in config.listen_addrs()? {
+        print!("Checking {}... ", addr);
+        match std::net::TcpListener::bind(addr) {
+            Ok(listener) => {
+                drop(listener);
+                println!("OK (available)");
+            }
+            Err(e) => {
+                return Err(format!("{} BLOCKED: {}", addr, e));
+            }
         }
     }
 
+    // 4. Check the `[tls]` certificate and key load
+    if config.tls.enabled() {
+        print!("Checking TLS certificate... ");
+        server::tls_acceptor(&config.tls)?;
+        println!("OK");
+    }
+
     println!("\n✅ SUCCESS: All systems GO. Ready to start gateway.");
     Ok(())
 }
@@ -132,6 +384,70 @@ async fn main() {
 
     // Handle --verify flag for pre-flight check
     let args: Vec<String> = std::env::args().collect();
+    // `--config <path>`, `--env <name>` and `--set key=value` layer over the config file and
+    // PAGI__* variables for every CoreConfig::load below.
+    match pagi_core::ConfigSources::from_args(&args) {
+        Ok(sources) => sources.install(),
+        Err(e) => {
+            eprintln!("❌ {}", e);
+            std::process::exit(2);
+        }
+    }
+    if args.iter().any(|a| a == "--print-config") {
+        match run_print_config() {
+            Ok(()) => std::process::exit(0),
+            Err(e) => {
+                eprintln!("❌ CONFIG INVALID: {}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+    if let Some(i) = args.iter().position(|a| a == "--check-blueprint") {
+        let path = args.get(i + 1).filter(|a| !a.starts_with("--")).cloned();
+        match run_check_blueprint(path) {
+            Ok(()) => std::process::exit(0),
+            Err(e) => {
+                eprintln!("❌ BLUEPRINT CHECK FAILED: {}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+    if let Some(i) = args.iter().position(|a| a == "--install-pack") {
+        let flag = |name: &str| args.iter().position(|a| a == name).and_then(|j| args.get(j + 1)).cloned();
+        let result = match args.get(i + 1).filter(|a| !a.starts_with("--")) {
+            Some(path) => run_install_pack(
+                path,
+                &flag("--agent").unwrap_or_else(|| pagi_core::DEFAULT_AGENT_ID.to_string()),
+                args.iter().any(|a| a == "--force"),
+            ),
+            None => Err("usage: --install-pack <path> [--agent <id>] [--force]".to_string()),
+        };
+        match result {
+            Ok(()) => std::process::exit(0),
+            Err(e) => {
+                eprintln!("❌ PACK INSTALL FAILED: {}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+    if args.iter().any(|a| a == "--reembed") {
+        match run_reembed(&args).await {
+            Ok(()) => std::process::exit(0),
+            Err(e) => {
+                eprintln!("❌ RE-EMBEDDING FAILED: {}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+    if args.iter().any(|a| a == "--snapshot" || a == "--restore-snapshot") {
+        match run_snapshot_command(&args) {
+            Ok(()) => std::process::exit(0),
+            Err(e) => {
+                eprintln!("❌ SNAPSHOT FAILED: {}", e);
+                std::process::exit(1);
+            }
+        }
+    }
     if args.iter().any(|a| a == "--verify") {
         match run_verify() {
             Ok(()) => std::process::exit(0),
@@ -142,29 +458,98 @@ async fn main() {
         }
     }
 
+    // --mcp: serve the skill registry to MCP clients over stdio instead of HTTP. Stdout carries
+    // the protocol, so logs go to stderr.
+    let mcp_mode = args.iter().any(|a| a == "--mcp");
+    // --repl: an interactive console on the terminal instead of HTTP (see handlers/repl.rs); logs
+    // go to stderr and default to warnings so they do not bury the results.
+    let repl_mode = args.iter().any(|a| a == "--repl");
+
+    let mut config = match CoreConfig::load() {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("❌ Cannot load config: {}", e);
+            std::process::exit(1);
+        }
+    };
+    // `[simulation]`: throwaway stores, a frozen clock and seeded mock LLM replies.
+    let simulation = simulation::Simulation::from_config(&config.simulation).unwrap_or_else(|e| {
+        eprintln!("❌ {}", e);
+        std::process::exit(1)
+    });
+    if let Some(sim) = &simulation {
+        config.storage_path = sim.storage_path.to_string_lossy().into_owned();
+    }
+    let config = Arc::new(config);
+
     let (log_tx, _) = broadcast::channel(1000);
-    let log_layer = LogBroadcastLayer::new(log_tx.clone());
+    let log_writer = if mcp_mode || repl_mode {
+        tracing_subscriber::fmt::writer::BoxMakeWriter::new(std::io::stderr)
+    } else {
+        tracing_subscriber::fmt::writer::BoxMakeWriter::new(std::io::stdout)
+    };
+    // `[logging] format = "json"`: the log layer writes JSON lines in place of the text format.
+    let (fmt_layer, log_layer) = match config.logging.format {
+        pagi_core::LogFormat::Json => (None, logging::LogLayer::new(log_tx.clone()).with_json_output(log_writer)),
+        pagi_core::LogFormat::Text => (
+            Some(tracing_subscriber::fmt::layer().with_writer(log_writer)),
+            logging::LogLayer::new(log_tx.clone()),
+        ),
+    };
+
+    // OTLP export of dispatch/plan-step spans (see telemetry.rs); off unless an endpoint is set.
+    let tracer_provider = telemetry::init_tracer_provider().unwrap_or_else(|e| {
+        eprintln!("[pagi-gateway] OpenTelemetry export disabled: {}", e);
+        None
+    });
 
     tracing_subscriber::registry()
         .with(tracing_subscriber::EnvFilter::new(
-            std::env::var("RUST_LOG").unwrap_or_else(|_| "info".into()),
+            std::env::var("RUST_LOG").unwrap_or_else(|_| if repl_mode { "warn" } else { "info" }.into()),
         ))
-        .with(tracing_subscriber::fmt::layer())
+        .with(fmt_layer)
         .with(log_layer)
+        .with(tracer_provider.as_ref().map(telemetry::layer))
         .init();
 
-    let config = Arc::new(CoreConfig::load().expect("load CoreConfig"));
+    // `[secrets]`: keys and provider credentials are loaded before anything reads them.
+    if let Err(e) = pagi_core::init_secrets(&config).await {
+        eprintln!("❌ Cannot load secrets: {}", e);
+        std::process::exit(1);
+    }
     let storage = StdPath::new(&config.storage_path);
     let memory_path = storage.join("pagi_vault");
     let knowledge_path = storage.join("pagi_knowledge");
 
-    let memory = Arc::new(
-        MemoryManager::open_path(&memory_path).expect("open pagi_vault"),
-    );
-    let knowledge = Arc::new(
-        KnowledgeStore::open_path(&knowledge_path).expect("open pagi_knowledge"),
-    );
+    let mut memory_store =
+        MemoryManager::open_with_backend(&memory_path, config.storage_backend).unwrap_or_else(|e| exit_on_open_error("pagi_vault", e));
+    // `[redaction]`: PII is masked before Chronos storage and live LLM calls, each masking audited.
+    let redactor = Redactor::from_config(&config.redaction);
+    let mut knowledge_store = KnowledgeStore::open_with_backend(&knowledge_path, config.storage_backend)
+        .unwrap_or_else(|e| exit_on_open_error("pagi_knowledge", e))
+        .with_max_value_bytes(config.limits.max_kb_value_bytes)
+        .with_encrypted_slots(&config.encryption.slots);
+    if let Some(redactor) = &redactor {
+        knowledge_store = knowledge_store.with_redaction(redactor.clone());
+    }
+    if let Some(sim) = &simulation {
+        knowledge_store = knowledge_store.with_clock(Arc::clone(&sim.clock) as Arc<dyn pagi_core::Clock>);
+        memory_store = memory_store.with_clock(Arc::clone(&sim.clock) as Arc<dyn pagi_core::Clock>);
+        tracing::info!(
+            target: "pagi::gateway",
+            storage_path = %config.storage_path,
+            start_ms = config.simulation.start_ms,
+            "Simulation mode: frozen clock, mock LLM fixtures, temporary stores"
+        );
+    }
+    let knowledge = Arc::new(knowledge_store);
+    let memory = Arc::new(memory_store);
     knowledge.pagi_init_kb_metadata().ok(); // ensure 8 trees have metadata
+    match knowledge.interrupt_running_reembed_jobs() {
+        Ok(0) => {}
+        Ok(n) => tracing::warn!(target: "pagi::gateway", jobs = n, "Re-embedding jobs interrupted by the last shutdown (resume via /api/v1/knowledge/reembed/:job_id/resume)"),
+        Err(e) => tracing::warn!(target: "pagi::gateway", error = %e, "Could not check re-embedding jobs"),
+    }
     
     // Bootstrap core identity if KB-1 is empty (Mission Genesis)
     match initialize_core_identity(&knowledge) {
@@ -190,7 +575,7 @@ async fn main() {
     let _pneuma_ok = pagi_core::verify_identity(&knowledge).complete;
     tracing::info!("[Cognitive Architecture] Pneuma (Vision) active. Oikos (Context) ready (Sovereign skills only).");
 
-    let shadow_store: ShadowStoreHandle = if std::env::var("PAGI_SHADOW_KEY").is_ok() {
+    let shadow_store: ShadowStoreHandle = if pagi_core::secret(pagi_core::SECRET_SHADOW_KEY).is_some() {
         let shadow_path = storage.join("pagi_shadow");
         match ShadowStore::open_path(&shadow_path) {
             Ok(store) => {
@@ -206,119 +591,466 @@ async fn main() {
         Arc::new(tokio::sync::RwLock::new(None))
     };
 
-    // Sovereign Brain: only ReflectShadow, BioGateSync, OikosTaskGovernor, EthosSync (+ ModelRouter for chat)
-    let mut registry = SkillRegistry::new();
-    let model_router = Arc::new(ModelRouter::with_knowledge(Arc::clone(&knowledge)));
-    registry.register(Arc::new(ModelRouter::with_knowledge(Arc::clone(&knowledge))));
-    registry.register(Arc::new(BioGateSync::new(Arc::clone(&knowledge))));
-    registry.register(Arc::new(EthosSync::new(Arc::clone(&knowledge))));
-    registry.register(Arc::new(OikosTaskGovernor::new(Arc::clone(&knowledge))));
-    registry.register(Arc::new(ReflectShadowSkill::new(
-        Arc::clone(&knowledge),
-        Arc::clone(&shadow_store),
-        Arc::clone(&model_router),
-    )));
+    // Model aliases with provider failover; health is reported by /v1/status. Token usage is
+    // metered per tenant against `[token_budgets]` (GET /api/v1/usage/:tenant_id).
+    let llm_providers = Arc::new(LlmProviders::from_config(&config));
+    let mut model_router = ModelRouter::with_knowledge(Arc::clone(&knowledge))
+        .with_providers(Arc::clone(&llm_providers))
+        .with_budgets(config.token_budgets.clone());
+    if let Some(redactor) = redactor {
+        model_router = model_router.with_redaction(redactor);
+    }
+    if let Some(sim) = &simulation {
+        model_router = model_router.with_fixtures(Arc::clone(&sim.fixtures));
+    }
+    let model_router = Arc::new(model_router);
+    let registry = build_skill_registry(&knowledge, &memory, &shadow_store, &model_router, &config.scraper, &config.email, &config.chat, &config.calendar);
+
+    let blueprint_path = blueprint_path();
+    // A blueprint that fails validation, or whose intents name skills this gateway does not
+    // register (capabilities contract), stops startup. Check it at deploy time with --check-blueprint.
+    let blueprint = BlueprintRegistry::try_load_json_path(&blueprint_path).unwrap_or_else(|e| {
+        eprintln!("❌ {}", e);
+        std::process::exit(1)
+    });
+    if let Ok(text) = std::fs::read_to_string(&blueprint_path) {
+        for issue in validate_blueprint(&text, None) {
+            tracing::warn!(target: "pagi::gateway", path = %blueprint_path, "Blueprint: {}", issue);
+        }
+    }
+    if let Err(e) = check_capabilities(&blueprint_path, &blueprint, &registry.skill_names()) {
+        eprintln!("❌ {}", e);
+        std::process::exit(1)
+    }
+    // Runtime edits (POST/DELETE /api/v1/blueprints/:intent) persist in KB-5 and overlay the file.
+    let blueprint = Arc::new(blueprint.with_store(Arc::clone(&knowledge)));
+    // Per-tenant skill view: allow/deny lists and tenant-scoped skills (config/tenants.json).
+    let tenants = handlers::tenants::load_tenant_registry(&knowledge, &config.token_budgets);
+    // Admission control: concurrent goal cap and per-tenant rate limits (429 + Retry-After).
+    let queue_config = DispatchQueueConfig::from_env();
+    tracing::info!(
+        target: "pagi::gateway",
+        max_concurrent = queue_config.max_concurrent,
+        max_queued = queue_config.max_queued,
+        rate_limit = ?queue_config.default_limit,
+        "Dispatch queue configured"
+    );
+    // Chat intent routing: free-form chat that names a blueprint intent runs its plan.
+    let intent_config = IntentRoutingConfig::from_env();
+    tracing::info!(
+        target: "pagi::gateway",
+        enabled = intent_config.enabled,
+        run_threshold = intent_config.run_threshold,
+        confirm_threshold = intent_config.confirm_threshold,
+        llm_fallback = intent_config.llm_fallback,
+        "Chat intent routing configured"
+    );
+    let mut intent_router = IntentRouter::new(intent_config);
+    let examples_path = std::env::var("PAGI_INTENT_EXAMPLES_PATH")
+        .unwrap_or_else(|_| "config/intent_examples.json".to_string());
+    if StdPath::new(&examples_path).exists() {
+        match IntentRouter::load_examples(&examples_path) {
+            Ok(examples) => intent_router = intent_router.with_examples(examples),
+            Err(e) => tracing::warn!(target: "pagi::gateway", path = %examples_path, error = %e, "Failed to load intent examples"),
+        }
+    }
+    let orchestrator = Arc::new(
+        Orchestrator::with_blueprint(Arc::new(registry), Arc::clone(&blueprint))
+            .with_knowledge(Arc::clone(&knowledge))
+            .with_memory(Arc::clone(&memory))
+            .with_tenants(tenants)
+            .with_dispatch_queue(Arc::new(DispatchQueue::new(queue_config)))
+            .with_intent_router(Arc::new(intent_router))
+            .with_chat_notifications(config.chat.notify.clone())
+            .with_skill_limits(config.limits.clone()),
+    );
 
-    let blueprint_path = std::env::var("PAGI_BLUEPRINT_PATH")
-        .unwrap_or_else(|_| "config/blueprint.json".to_string());
-    let blueprint = Arc::new(BlueprintRegistry::load_json_path(&blueprint_path));
-    let orchestrator = Arc::new(Orchestrator::with_blueprint(
-        Arc::new(registry),
-        Arc::clone(&blueprint),
-    ));
+    if mcp_mode {
+        let server = handlers::mcp::McpServer::from_env(Arc::clone(&orchestrator), Arc::clone(&knowledge));
+        let result = server.run_stdio().await;
+        shutdown_tracer_provider(tracer_provider);
+        if let Err(e) = result {
+            eprintln!("❌ MCP server stopped: {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+    if repl_mode {
+        let mut repl = handlers::repl::Repl::from_env(Arc::clone(&orchestrator), storage);
+        let result = repl.run_stdio().await;
+        shutdown_tracer_provider(tracer_provider);
+        if let Err(e) = result {
+            eprintln!("❌ Console stopped: {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    // Agents that predate the registry (known only by their KB_SOMA inbox) are registered once,
+    // so the Heartbeat keeps serving them.
+    match knowledge.register_inbox_agents() {
+        Ok(0) => {}
+        Ok(n) => tracing::info!(target: "pagi::agents", registered = n, "Registered agents found in KB_SOMA inboxes"),
+        Err(e) => tracing::warn!(target: "pagi::agents", error = %e, "Agent registry migration failed"),
+    }
+
+    // The running config; `POST /api/v1/admin/reload-config` swaps in reloaded fields.
+    let live_config = LiveConfig::new(Arc::clone(&config));
 
     // Heartbeat (Autonomous Orchestrator): in-process background task so we can share
     // the same Sled-backed KnowledgeStore without cross-process lock contention.
-    // Tick rate is configurable via env `PAGI_TICK_RATE_SECS`.
-    let tick_rate = std::env::var("PAGI_TICK_RATE_SECS")
-        .ok()
-        .and_then(|s| s.parse::<u64>().ok())
-        .unwrap_or(5)
-        .max(1);
+    // Tick rate is `tick_rate_secs` (or env `PAGI_TICK_RATE_SECS`), and follows config reloads.
+    // Scheduled snapshots (opt-in via PAGI_SNAPSHOT_INTERVAL_HOURS) ride on the Heartbeat.
+    let snapshots = handlers::snapshots::interval_from_env()
+        .map(|_| Arc::new(handlers::snapshots::manager(Arc::clone(&knowledge), &config.storage_path)));
+    // Lead follow-ups (opt-in via PAGI_LEAD_FOLLOW_UP_DAYS): draft for leads stuck in `contacted`.
+    let lead_follow_up = lead_follow_up_days_from_env().map(|days| (Arc::clone(&memory), days));
     tokio::spawn(heartbeat_loop(
         Arc::clone(&knowledge),
         Arc::clone(&model_router),
-        std::time::Duration::from_secs(tick_rate),
+        Arc::clone(&orchestrator),
+        snapshots,
+        lead_follow_up,
+        live_config.clone(),
+        simulation.map(|sim| sim.clock),
     ));
     
-    let app = build_app(AppState {
-        config: Arc::clone(&config),
+    // Archive tier (opt-in via PAGI_ARCHIVE_AFTER_DAYS): move cold Chronos/Logos records to
+    // compressed segment files under {storage_path}/archive.
+    if let Some(archive_config) = handlers::archive::ArchiveConfig::from_env() {
+        tokio::spawn(handlers::archive::archive_loop(Arc::clone(&knowledge), archive_config));
+    }
+
+    // `[oidc]`: SSO bearer tokens, accepted next to API keys.
+    let oidc = match oidc::OidcVerifier::from_config(&config.oidc) {
+        Ok(oidc) => oidc.map(Arc::new),
+        Err(e) => {
+            eprintln!("❌ {}", e);
+            std::process::exit(1)
+        }
+    };
+    if let Some(issuer) = config.oidc.issuer.as_deref().filter(|_| oidc.is_some()) {
+        tracing::info!(target: "pagi::auth", issuer = %issuer, "OIDC bearer tokens accepted");
+    }
+
+    let state = AppState {
+        config: live_config,
         orchestrator,
         knowledge,
         log_tx,
-        model_router,
         shadow_store: Arc::clone(&shadow_store),
-    });
+        llm_providers,
+        oidc,
+    };
+    // `[channels.telegram]` in polling mode: the bot fetches its updates itself.
+    let telegram = &config.channels.telegram;
+    if telegram.enabled && telegram.mode == pagi_core::TelegramMode::Polling {
+        let bot = Arc::new(channels::telegram::TelegramBot::new(telegram.clone()));
+        tokio::spawn(channels::telegram::poll(state.clone(), bot));
+    }
+    let app = build_app(state);
 
-    // PORT LOCKOUT: Hard-bind to 127.0.0.1:8001 only (Sovereign architecture). No 0.0.0.0.
-    const GATEWAY_PORT: u16 = 8001;
-    let port = GATEWAY_PORT;
-    let app_name = config.app_name.clone();
-    let addr = std::net::SocketAddr::from(([127, 0, 0, 1], port));
-    tracing::info!("{} listening on {} (port locked)", app_name, addr);
-    axum::serve(
-        tokio::net::TcpListener::bind(addr).await.unwrap(),
-        app,
-    )
-    .await
-    .unwrap();
+    // `bind_addresses` and `port` (default 127.0.0.1:8001), HTTPS when `[tls]` is set.
+    let listening = match (config.listen_addrs(), server::tls_acceptor(&config.tls)) {
+        (Ok(addrs), Ok(tls)) => server::bind(&addrs).await.map(|listeners| (addrs, listeners, tls)),
+        (Err(e), _) | (_, Err(e)) => Err(e),
+    };
+    let (addrs, listeners, tls) = match listening {
+        Ok(listening) => listening,
+        Err(e) => {
+            eprintln!("❌ {}", e);
+            std::process::exit(1)
+        }
+    };
+    let scheme = if tls.is_some() { "https" } else { "http" };
+    for addr in &addrs {
+        tracing::info!("{} listening on {}://{}", config.app_name, scheme, addr);
+    }
+    if let Err(e) = server::serve(listeners, app, tls).await {
+        tracing::error!(target: "pagi::gateway", error = %e, "Gateway stopped serving");
+    }
+    shutdown_tracer_provider(tracer_provider);
+}
+
+/// Flushes spans still batched for OTLP export.
+fn shutdown_tracer_provider(provider: Option<opentelemetry_sdk::trace::SdkTracerProvider>) {
+    if let Some(provider) = provider {
+        if let Err(e) = provider.shutdown() {
+            eprintln!("[pagi-gateway] OpenTelemetry shutdown failed: {}", e);
+        }
+    }
 }
 
 async fn heartbeat_loop(
     knowledge: Arc<KnowledgeStore>,
     model_router: Arc<ModelRouter>,
-    tick: std::time::Duration,
+    orchestrator: Arc<Orchestrator>,
+    snapshots: Option<Arc<SnapshotManager>>,
+    lead_follow_up: Option<(Arc<MemoryManager>, u64)>,
+    config: LiveConfig,
+    simulated_clock: Option<Arc<FrozenClock>>,
 ) {
+    let mut tick = config.get().tick_rate();
     tracing::info!(
         target: "pagi::daemon",
         tick_rate_secs = tick.as_secs(),
         "Heartbeat loop started"
     );
     let mut interval = tokio::time::interval(tick);
+    let scheduler = SchedulerRegistry::new(Arc::clone(&knowledge));
+    let sources = SourceRegistry::new(Arc::clone(&knowledge));
+    let mut webhook_batch: Option<tokio::task::JoinHandle<()>> = None;
+    // Per-agent tick intervals are measured from when this loop last served each agent.
+    let mut agent_ticks = AgentTickTracker::default();
+    let mut last_follow_up: Option<std::time::Instant> = None;
     loop {
         interval.tick().await;
-        if let Err(e) = heartbeat_tick(Arc::clone(&knowledge), Arc::clone(&model_router)).await {
+        let reloaded_tick = config.get().tick_rate();
+        if reloaded_tick != tick {
+            tracing::info!(target: "pagi::daemon", tick_rate_secs = reloaded_tick.as_secs(), "Heartbeat tick rate changed");
+            tick = reloaded_tick;
+            interval = tokio::time::interval_at(tokio::time::Instant::now() + tick, tick);
+        }
+        // Simulation mode: each tick is one tick interval of simulated time.
+        if let Some(clock) = &simulated_clock {
+            clock.advance(tick.as_millis() as i64);
+        }
+        if let Err(e) = heartbeat_tick(Arc::clone(&knowledge), Arc::clone(&model_router), &orchestrator, &mut agent_ticks).await {
             tracing::warn!(target: "pagi::daemon", error = %e, "Heartbeat tick failed");
         }
+        // Standing queries: re-evaluate saved subscriptions and alert on changed results.
+        if let Err(e) = handlers::standing_queries::run_standing_queries(&knowledge).await {
+            tracing::warn!(target: "pagi::daemon", error = %e, "Standing query evaluation failed");
+        }
+        // Scheduled goals: dispatch delayed/recurring goals that are due (runs logged to Chronos).
+        if let Err(e) = scheduler.run_due(&orchestrator).await {
+            tracing::warn!(target: "pagi::daemon", error = %e, "Scheduled goal evaluation failed");
+        }
+        // Scrape sources: refresh stale pages into their slots (last success/error kept per source).
+        if let Err(e) = sources.run_due(&orchestrator).await {
+            tracing::warn!(target: "pagi::daemon", error = %e, "Scrape source refresh failed");
+        }
+        // Webhooks: deliver queued event notifications (failures retried with backoff) off the
+        // Heartbeat, one batch at a time, so slow endpoints hold up neither agents nor schedules.
+        if webhook_batch.as_ref().is_none_or(|batch| batch.is_finished()) {
+            let webhooks = WebhookRegistry::new(Arc::clone(&knowledge))
+                .with_private_hosts(config.get().webhooks.allow_private_hosts);
+            webhook_batch = Some(tokio::spawn(async move {
+                if let Err(e) = webhooks.deliver_due().await {
+                    tracing::warn!(target: "pagi::daemon", error = %e, "Webhook delivery failed");
+                }
+            }));
+        }
+        // Conversation memory: summarize old session turns into digests (KB_CHRONOS session_digest/...).
+        if let Err(e) = consolidate_conversations(&knowledge, &model_router).await {
+            tracing::warn!(target: "pagi::daemon", error = %e, "Conversation consolidation failed");
+        }
+        // Knowledge snapshots: take one when the newest is older than the configured interval.
+        if let Some(snapshots) = &snapshots {
+            let snapshots = Arc::clone(snapshots);
+            match tokio::task::spawn_blocking(move || snapshots.run_due()).await {
+                Ok(Err(e)) => tracing::warn!(target: "pagi::daemon", error = %e, "Scheduled snapshot failed"),
+                Err(e) => tracing::warn!(target: "pagi::daemon", error = %e, "Snapshot task panicked"),
+                Ok(Ok(_)) => {}
+            }
+        }
+        // Lead follow-ups: at most hourly, draft for each tenant's leads stuck in `contacted`.
+        if let Some((memory, days)) = &lead_follow_up {
+            if last_follow_up.is_none_or(|at| at.elapsed() >= LEAD_FOLLOW_UP_INTERVAL) {
+                last_follow_up = Some(std::time::Instant::now());
+                if let Err(e) = run_lead_follow_ups(memory, &orchestrator, *days).await {
+                    tracing::warn!(target: "pagi::daemon", error = %e, "Lead follow-up run failed");
+                }
+            }
+        }
+    }
+}
+
+/// How often the Heartbeat looks for stale leads.
+const LEAD_FOLLOW_UP_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60 * 60);
+
+/// Days a lead may sit in `contacted` before the Heartbeat drafts a follow-up; None unless
+/// `PAGI_LEAD_FOLLOW_UP_DAYS` is set to a positive number.
+fn lead_follow_up_days_from_env() -> Option<u64> {
+    std::env::var("PAGI_LEAD_FOLLOW_UP_DAYS")
+        .ok()
+        .and_then(|s| s.trim().parse::<u64>().ok())
+        .filter(|d| *d > 0)
+}
+
+/// Runs `LeadFollowUp` for every tenant with leads (through the orchestrator, so Ethos applies).
+async fn run_lead_follow_ups(
+    memory: &MemoryManager,
+    orchestrator: &Orchestrator,
+    stale_days: u64,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    for tenant_id in memory.lead_tenants()? {
+        let ctx = TenantContext::new(tenant_id);
+        let goal = Goal::ExecuteSkill {
+            name: "LeadFollowUp".to_string(),
+            payload: Some(serde_json::json!({ "stale_days": stale_days })),
+        };
+        match orchestrator.dispatch(&ctx, goal).await {
+            Ok(result) if result["count"].as_u64().unwrap_or(0) > 0 => {
+                tracing::info!(target: "pagi::daemon", tenant_id = %ctx.tenant_id, drafted = %result["count"], "Drafted lead follow-ups");
+            }
+            Ok(_) => {}
+            Err(e) => tracing::warn!(target: "pagi::daemon", tenant_id = %ctx.tenant_id, error = %e, "LeadFollowUp failed"),
+        }
+    }
+    Ok(())
+}
+
+/// Conversation digests written per heartbeat tick (each is one ModelRouter call).
+const CONSOLIDATION_BATCHES_PER_TICK: usize = 4;
+
+/// Summarizes due conversation turns via the ModelRouter ([`ConversationManager::consolidate`]),
+/// metered to each session's tenant and agent.
+async fn consolidate_conversations(
+    knowledge: &Arc<KnowledgeStore>,
+    model_router: &Arc<ModelRouter>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let manager = ConversationManager::new(Arc::clone(knowledge));
+    let digests = manager
+        .consolidate(CONSOLIDATION_BATCHES_PER_TICK, |session, prompt| {
+            let model_router = Arc::clone(model_router);
+            async move {
+                let ctx = TenantContext {
+                    tenant_id: session.tenant_id,
+                    correlation_id: Some(session.session_id),
+                    agent_id: Some(session.agent_id),
+                    ..Default::default()
+                };
+                let result = model_router.execute(&ctx, Some(serde_json::json!({ "prompt": prompt }))).await?;
+                Ok(result
+                    .get("generated")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or_default()
+                    .to_string())
+            }
+        })
+        .await?;
+    if !digests.is_empty() {
+        tracing::debug!(target: "pagi::daemon", digests = digests.len(), "Consolidated conversation turns");
+    }
+    Ok(())
+}
+
+/// Generates a persona-aware auto-reply to `msg` for `agent_id` via [`KnowledgeStore::build_auto_reply_prompt`].
+async fn generate_auto_reply(
+    knowledge: &KnowledgeStore,
+    model_router: &ModelRouter,
+    agent_id: &str,
+    msg: &AgentMessage,
+) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+    let reply = knowledge.build_auto_reply_prompt(agent_id, msg);
+    let ctx = TenantContext {
+        tenant_id: msg.from_agent_id.clone(),
+        correlation_id: Some(msg.id.clone()),
+        agent_id: Some(agent_id.to_string()),
+        ..Default::default()
+    };
+    let mut payload = serde_json::json!({
+        "prompt": reply.prompt,
+        "system_prompt": reply.system_prompt,
+    });
+    if let Some(profile) = knowledge.get_persona_profile(agent_id) {
+        profile.apply_generation_defaults(&mut payload);
     }
+    let result = model_router.execute(&ctx, Some(payload)).await?;
+    Ok(result
+        .get("generated")
+        .and_then(|v| v.as_str())
+        .unwrap_or_default()
+        .to_string())
+}
+
+/// Runs a background task prompt for `agent_id`. An agent with a persona profile speaks through
+/// it (Mission Directive as system prompt, its model and temperature); others get the raw prompt.
+async fn generate_background_task(
+    knowledge: &KnowledgeStore,
+    model_router: &ModelRouter,
+    agent_id: &str,
+    prompt: &str,
+) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+    let Some(profile) = knowledge.get_persona_profile(agent_id) else {
+        return model_router.generate_text_raw(prompt).await;
+    };
+    let mut payload = serde_json::json!({
+        "prompt": prompt,
+        "system_prompt": knowledge.build_system_directive(agent_id, agent_id),
+    });
+    profile.apply_generation_defaults(&mut payload);
+    let ctx = TenantContext {
+        tenant_id: pagi_core::DEFAULT_TENANT_ID.to_string(),
+        correlation_id: None,
+        agent_id: Some(agent_id.to_string()),
+        ..Default::default()
+    };
+    let result = model_router.execute(&ctx, Some(payload)).await?;
+    Ok(result
+        .get("generated")
+        .and_then(|v| v.as_str())
+        .unwrap_or_default()
+        .to_string())
 }
 
 async fn heartbeat_tick(
     knowledge: Arc<KnowledgeStore>,
     model_router: Arc<ModelRouter>,
+    orchestrator: &Orchestrator,
+    agent_ticks: &mut AgentTickTracker,
 ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     // Proactive Oikos monitoring: every 10 ticks, scan the physical workspace state
     // (research_sandbox/) and proactively inject maintenance prompts.
     let tick_n = HEARTBEAT_TICK_COUNT.fetch_add(1, Ordering::Relaxed) + 1;
     if tick_n % 10 == 0 {
-        if let Err(e) = maybe_run_oikos_guardian(Arc::clone(&knowledge), tick_n).await {
+        if let Err(e) = maybe_run_oikos_guardian(Arc::clone(&knowledge), orchestrator, tick_n).await {
             tracing::warn!(target: "pagi::daemon", error = %e, "Oikos guardian scan failed");
         }
-    }
-
-    // Discover active agents by scanning KB_SOMA inbox keys: inbox/{agent_id}/...
-    let soma_slot = KbType::Soma.slot_id();
-    let keys = knowledge.scan_keys(soma_slot)?;
-    let mut agents: HashSet<String> = HashSet::new();
-    for k in keys {
-        if let Some(rest) = k.strip_prefix("inbox/") {
-            if let Some((agent_id, _tail)) = rest.split_once('/') {
-                if !agent_id.trim().is_empty() {
-                    agents.insert(agent_id.to_string());
-                }
-            }
+        // Expired `/v1/execute` idempotency records (KB_SOMA idempotency/...).
+        match knowledge.prune_idempotency_keys() {
+            Ok(0) => {}
+            Ok(n) => tracing::debug!(target: "pagi::daemon", removed = n, "Pruned expired idempotency keys"),
+            Err(e) => tracing::warn!(target: "pagi::daemon", error = %e, "Idempotency key pruning failed"),
+        }
+        // Expired ModelRouter replies (KB_SOMA llm_cache/...).
+        match knowledge.prune_llm_cache() {
+            Ok(0) => {}
+            Ok(n) => tracing::debug!(target: "pagi::daemon", removed = n, "Pruned expired LLM cache entries"),
+            Err(e) => tracing::warn!(target: "pagi::daemon", error = %e, "LLM cache pruning failed"),
+        }
+        // Background jobs finished more than JOB_RETENTION_MS ago (KB_SOMA job/...).
+        match knowledge.prune_jobs() {
+            Ok(0) => {}
+            Ok(n) => tracing::debug!(target: "pagi::daemon", removed = n, "Pruned finished background jobs"),
+            Err(e) => tracing::warn!(target: "pagi::daemon", error = %e, "Background job pruning failed"),
+        }
+        match handlers::audit::prune_expired(&knowledge) {
+            Ok(0) => {}
+            Ok(n) => tracing::debug!(target: "pagi::daemon", removed = n, "Pruned expired audit records"),
+            Err(e) => tracing::warn!(target: "pagi::daemon", error = %e, "Audit record pruning failed"),
         }
     }
 
-    for agent_id in agents {
+    // Topic broadcasts: copy new topic messages into their subscribers' inboxes.
+    knowledge.heartbeat_fan_out_topics();
+
+    // Serve the registered, active agents (KB_PNEUMA agents/{agent_id}) whose schedule is due:
+    // paused agents, agents outside their active hours and agents within their tick interval wait.
+    let soma_slot = KbType::Soma.slot_id();
+    for agent_id in knowledge.due_agent_ids(agent_ticks)? {
         // AUTO-POLL: check inbox.
-        // We fetch a small batch so we can skip already-processed messages without getting stuck.
+        // We fetch a small batch so we can skip already-processed messages (and ones waiting out a
+        // retry backoff) without getting stuck.
         let inbox = knowledge.get_agent_messages_with_keys(&agent_id, 25)?;
+        let now = knowledge.now_ms();
         if let Some((inbox_key, msg)) = inbox
             .into_iter()
-            .find(|(_k, m)| !m.is_processed)
+            .find(|(_k, m)| m.is_ready(now))
         {
-            // Stop infinite ping-pong: never auto-reply to an auto-reply.
+            // Stop infinite ping-pong: never auto-reply to an auto-reply (or a standing query / LLM quality alert),
+            // nor to a topic broadcast (every subscriber would answer the publisher).
             // Still ACK it so it doesn't remain "unprocessed" forever.
             let msg_type = msg
                 .payload
@@ -326,44 +1058,52 @@ async fn heartbeat_tick(
                 .and_then(|o| o.get("type"))
                 .and_then(|v| v.as_str())
                 .unwrap_or("");
-            if msg_type == "agent_auto_reply" {
+            if msg.topic.is_some()
+                || msg_type == "agent_auto_reply"
+                || msg_type == handlers::standing_queries::STANDING_QUERY_ALERT_TYPE
+                || msg_type == pagi_skills::LLM_QUALITY_ALERT_TYPE
+            {
                 let mut updated = msg.clone();
                 updated.is_processed = true;
                 knowledge.insert(soma_slot, &inbox_key, &updated.to_bytes())?;
                 continue;
             }
 
-            // Cognitive Governor: effective MentalState (Kardia + Soma/BioGate physical load).
-            let mental = knowledge.get_effective_mental_state(&agent_id);
-            let prompt_base = format!(
-                "You are agent_id={}. You have a new inbox message from {}. Message payload: {}\n\nRespond appropriately.",
-                agent_id,
-                msg.from_agent_id,
-                msg.payload
-            );
-            let prompt = if mental.needs_empathetic_tone() {
-                format!(
-                    "{}. {}",
-                    MentalState::EMPATHETIC_SYSTEM_INSTRUCTION,
-                    prompt_base
-                )
-            } else if mental.has_physical_load_adjustment() {
-                format!(
-                    "{}. {}",
-                    MentalState::PHYSICAL_LOAD_SYSTEM_INSTRUCTION,
-                    prompt_base
-                )
-            } else {
-                prompt_base
+            // Same context assembly as chat: Mission Directive toward the sender (Pneuma identity,
+            // Kardia relationship, mental state) plus the agent's template with thread history.
+            // A failed generation leaves the message for a later attempt with backoff, and
+            // dead-letters it (KB_SOMA inbox_dlq/...) after INBOX_MAX_ATTEMPTS failures.
+            let generated = match generate_auto_reply(&knowledge, &model_router, &agent_id, &msg).await {
+                Ok(generated) => generated,
+                Err(e) => {
+                    match knowledge.record_inbox_failure(&inbox_key, &e.to_string())? {
+                        Some(InboxRetry::DeadLettered { attempts }) => tracing::warn!(
+                            target: "pagi::daemon",
+                            agent_id = %agent_id,
+                            message_id = %msg.id,
+                            attempts,
+                            error = %e,
+                            "Auto-reply failed; message dead-lettered"
+                        ),
+                        Some(InboxRetry::Scheduled { attempts, next_attempt_ms }) => tracing::debug!(
+                            target: "pagi::daemon",
+                            agent_id = %agent_id,
+                            message_id = %msg.id,
+                            attempts,
+                            next_attempt_ms,
+                            error = %e,
+                            "Auto-reply failed; will retry"
+                        ),
+                        None => {}
+                    }
+                    continue;
+                }
             };
 
-            let generated = model_router
-                .generate_text_raw(&prompt)
-                .await
-                .unwrap_or_else(|e| format!("[heartbeat] generation failed: {}", e));
-
-            // Deliver response back to sender as an inter-agent message.
-            knowledge.push_agent_message(
+            // Reply to the sender, ACK the original (preserving KB_SOMA history) and reflect in
+            // Chronos in one transaction, so a crash cannot leave the message unprocessed after
+            // its reply went out (or processed without one).
+            let reply = knowledge.message(
                 &agent_id,
                 &msg.from_agent_id,
                 &serde_json::json!({
@@ -371,39 +1111,46 @@ async fn heartbeat_tick(
                     "in_reply_to": msg.id,
                     "text": generated,
                 }),
-            )?;
-
-            // ACK: mark the original inbox message as processed (preserve KB_SOMA history).
+            );
             let mut updated = msg.clone();
             updated.is_processed = true;
-            knowledge.insert(soma_slot, &inbox_key, &updated.to_bytes())?;
-
-            // Reflection: write a Chronos event for the agent.
-            let reflection = EventRecord::now(
+            let reflection = knowledge.event(
                 "Chronos",
                 format!("Auto-replied to message {} from {}", msg.id, msg.from_agent_id),
             )
             .with_skill("heartbeat")
             .with_outcome("auto_reply_sent");
-            let _ = knowledge.append_chronos_event(&agent_id, &reflection);
+            let chronos_slot = KbType::Chronos.slot_id();
+            let event_key = pagi_core::keys::event_key(
+                &agent_id,
+                reflection.timestamp_ms,
+                &uuid::Uuid::new_v4().simple().to_string(),
+            );
+            knowledge.transaction_multi(&[soma_slot, chronos_slot], |tx| {
+                let soma = tx.slot(soma_slot)?;
+                soma.insert(&reply.inbox_key(), &reply.to_bytes())?;
+                soma.insert(&inbox_key, &updated.to_bytes())?;
+                tx.slot(chronos_slot)?.insert(&event_key, &reflection.to_bytes())?;
+                Ok(())
+            })?;
         } else {
             // If no inbox message exists, check Pneuma for background tasks.
             // Minimal v1: if a key `pneuma/{agent_id}/background_task` exists, run it through the router.
             let pneuma_slot = KbType::Pneuma.slot_id();
-            let bg_key = format!("pneuma/{}/background_task", agent_id);
+            let bg_key = pagi_core::keys::background_task_key(&agent_id);
             if let Ok(Some(bytes)) = knowledge.get(pneuma_slot, &bg_key) {
                 if let Ok(task) = String::from_utf8(bytes) {
                     if !task.trim().is_empty() {
-                        let prompt = format!(
-                            "You are agent_id={}. Background task: {}\n\nProvide a short status update.",
-                            agent_id,
-                            task
-                        );
-                        let generated = model_router
-                            .generate_text_raw(&prompt)
+                        let prompt = pagi_core::render_prompt(
+                            Some(knowledge.as_ref()),
+                            pagi_core::HEARTBEAT_BACKGROUND_TASK_PROMPT,
+                            &[("agent_id", &agent_id), ("task", &task)],
+                        )
+                        .unwrap_or_default();
+                        let generated = generate_background_task(&knowledge, &model_router, &agent_id, &prompt)
                             .await
                             .unwrap_or_else(|e| format!("[heartbeat] background generation failed: {}", e));
-                        let reflection = EventRecord::now(
+                        let reflection = knowledge.event(
                             "Chronos",
                             format!("Background task ticked: {}", generated),
                         )
@@ -421,6 +1168,7 @@ async fn heartbeat_tick(
 
 async fn maybe_run_oikos_guardian(
     _knowledge: Arc<KnowledgeStore>,
+    _orchestrator: &Orchestrator,
     _tick_n: u64,
 ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     // Sovereign architecture: no workspace_analyzer/sandbox scan. Oikos tasks are
@@ -429,6 +1177,7 @@ async fn maybe_run_oikos_guardian(
     #[allow(unreachable_code)]
     {
     let knowledge = _knowledge;
+    let orchestrator = _orchestrator;
     let tick_n = _tick_n;
     let issues = tokio::task::spawn_blocking(|| scan_research_sandbox_for_all_issues())
         .await
@@ -494,7 +1243,7 @@ async fn maybe_run_oikos_guardian(
         }
 
         // CHRONOS: record resolution.
-        let reflection = EventRecord::now(
+        let reflection = knowledge.event(
             "Chronos",
             format!("Task Resolved (Oikos guardian): {}", issue_key),
         )
@@ -554,13 +1303,21 @@ async fn maybe_run_oikos_guardian(
         )?;
 
         // CHRONOS: record initiation.
-        let reflection = EventRecord::now(
+        let reflection = knowledge.event(
             "Chronos",
             format!("Initiated proactive maintenance (Oikos guardian): {}", issue_key),
         )
         .with_skill("heartbeat")
         .with_outcome("proactive_maintenance_initiated");
         let _ = knowledge.append_chronos_event("SAGE_BOT", &reflection);
+        let opened = serde_json::json!({ "agent_id": "SAGE_BOT", "issue_key": issue_key, "task": task });
+        let ctx = TenantContext {
+            tenant_id: pagi_core::DEFAULT_TENANT_ID.to_string(),
+            correlation_id: None,
+            agent_id: Some("SAGE_BOT".to_string()),
+            ..Default::default()
+        };
+        orchestrator.emit_webhook(&ctx, WebhookEvent::MaintenanceOpened, opened);
 
         tracing::info!(
             target: "pagi::daemon",
@@ -609,13 +1366,6 @@ async fn maybe_run_oikos_guardian(
     }
 }
 
-fn now_ms() -> i64 {
-    std::time::SystemTime::now()
-        .duration_since(std::time::UNIX_EPOCH)
-        .map(|d| d.as_millis() as i64)
-        .unwrap_or(0)
-}
-
 /// Adjust DEV_BOT's trust_score in KB_KARDIA from SAGE_BOT's perspective.
 ///
 /// Uses (owner_agent_id, target_id) = ("SAGE_BOT", "DEV_BOT") so SAGE_BOT has a
@@ -627,18 +1377,15 @@ fn bump_kardia_trust(
     delta: f32,
     chronos_reflection: &str,
 ) -> Result<f32, Box<dyn std::error::Error + Send + Sync>> {
-    let mut rel = knowledge
-        .get_kardia_relation(owner_agent_id, target_id)
-        .unwrap_or_else(|| RelationRecord::new(target_id));
-
-    rel.trust_score = (rel.trust_score + delta).clamp(0.0, 1.0);
-    rel.last_updated_ms = now_ms();
-    knowledge
-        .set_kardia_relation(owner_agent_id, &rel)
+    // Compare-and-swap, so a concurrent bump (or sentiment update) is not overwritten.
+    let rel = knowledge
+        .update_kardia_relation(owner_agent_id, target_id, |rel| {
+            rel.trust_score = (rel.trust_score + delta).clamp(0.0, 1.0);
+        })
         .map_err(|e| -> Box<dyn std::error::Error + Send + Sync> { Box::new(e) })?;
 
     // CHRONOS LOGGING: write a Kardia-sourced event for observability/audit.
-    let event = EventRecord::now("Kardia", chronos_reflection)
+    let event = knowledge.event("Kardia", chronos_reflection)
         .with_skill("heartbeat")
         .with_outcome("kardia_trust_calibrated");
     let _ = knowledge.append_chronos_event(owner_agent_id, &event);
@@ -727,7 +1474,10 @@ fn scan_research_sandbox_for_all_issues(
     Ok(issues)
 }
 
-fn research_sandbox_root() -> std::path::PathBuf {
+pub(crate) fn research_sandbox_root() -> std::path::PathBuf {
+    if let Some(dir) = std::env::var("PAGI_RESEARCH_SANDBOX").ok().filter(|s| !s.trim().is_empty()) {
+        return std::path::PathBuf::from(dir.trim());
+    }
     // Prefer a working-directory-relative path (run from workspace root).
     // Fall back to `CARGO_MANIFEST_DIR/../..` (workspace root) for safety.
     let cwd = std::env::current_dir().unwrap_or_else(|_| std::path::PathBuf::from("."));
@@ -756,8 +1506,22 @@ fn frontend_root_dir() -> std::path::PathBuf {
         .join("pagi-frontend")
 }
 
+/// Hides the UI routes while `frontend_enabled` is off.
+async fn frontend_gate(
+    State(config): State<LiveConfig>,
+    req: axum::extract::Request,
+    next: axum::middleware::Next,
+) -> axum::response::Response {
+    if config.get().frontend_enabled {
+        next.run(req).await
+    } else {
+        StatusCode::NOT_FOUND.into_response()
+    }
+}
+
 fn build_app(state: AppState) -> Router {
-    let frontend_enabled = state.config.frontend_enabled;
+    let live_config = state.config.clone();
+    let knowledge = Arc::clone(&state.knowledge);
 
     // CORS: allow UI origins so the "brain" is reachable. No mock; UI must talk to this gateway only.
     let cors = CorsLayer::new()
@@ -777,47 +1541,255 @@ fn build_app(state: AppState) -> Router {
         .allow_headers(tower_http::cors::Any)
         .expose_headers(tower_http::cors::Any);
 
-    let mut app = Router::new()
+    let mut routes = Router::new()
         .route("/v1/status", get(status))
         .route("/v1/execute", post(execute))
+        .route("/v1/execute/:correlation_id/cancel", post(cancel_execution))
+        .route("/v1/jobs/:job_id", get(handlers::jobs::get_job))
+        .route("/v1/skills", get(handlers::skills::list_skills))
         .route("/v1/research/trace/:trace_id", get(get_research_trace))
+        .route("/v1/research/trace/:trace_id/replay", post(handlers::traces::replay_trace))
         .route("/api/v1/health", get(health))
+        .route("/api/v1/ready", get(handlers::readiness::ready))
         .route("/api/v1/logs", get(logs_stream))
         .route("/api/v1/chat", post(chat))
+        .route("/api/v1/chat/stream", post(chat_events))
+        .route("/api/v1/ws", get(handlers::ws::ws_upgrade))
         .route("/api/v1/kardia/:user_id", get(get_kardia_relation))
         .route("/api/v1/kb-status", get(kb_status))
+        .route("/api/v1/knowledge/storage", get(kb_storage))
         .route("/api/v1/sovereign-status", get(sovereign_status))
         .route("/v1/vault/read", post(vault_read))
-        .with_state(state);
-
-    if frontend_enabled {
-        let frontend_dir = frontend_root_dir();
-        let index_file = frontend_dir.join("index.html");
-        let assets_dir = frontend_dir.join("assets");
-
-        // Map `/` -> `pagi-frontend/index.html`
-        app = app.route_service("/", ServeFile::new(index_file));
-
-        // Map `/assets/*` -> `pagi-frontend/assets/*` (CSS, images, etc.)
-        if assets_dir.exists() {
-            app = app.nest_service("/assets", ServeDir::new(assets_dir));
-        }
-
-        // Map `/ui/*` -> `pagi-frontend/*` (app.js, assets, and any other files)
-        app = app.nest_service("/ui", ServeDir::new(frontend_dir));
-    }
-
-    app.layer(cors)
-}
-
-#[derive(Clone)]
+        .route(handlers::recorder::API_RECORDER_ROUTE, get(handlers::recorder::list_exchanges))
+        .route("/api/v1/recorder/exchanges/:id", get(handlers::recorder::get_exchange))
+        .route("/api/v1/blueprints", get(handlers::blueprints::list_blueprints))
+        .route("/api/v1/blueprints/reload", post(handlers::blueprints::reload_blueprints))
+        .route(
+            "/api/v1/blueprints/:intent",
+            get(handlers::blueprints::get_blueprint)
+                .post(handlers::blueprints::upsert_blueprint)
+                .delete(handlers::blueprints::delete_blueprint),
+        )
+        .route(
+            "/api/v1/standing-queries",
+            get(handlers::standing_queries::list_standing_queries)
+                .post(handlers::standing_queries::create_standing_query),
+        )
+        .route(
+            "/api/v1/schedules",
+            get(handlers::schedules::list_schedules).post(handlers::schedules::create_schedule),
+        )
+        .route(
+            "/api/v1/schedules/:id",
+            get(handlers::schedules::get_schedule).delete(handlers::schedules::delete_schedule),
+        )
+        .route("/api/v1/sources", get(handlers::sources::list_sources).post(handlers::sources::create_source))
+        .route(
+            "/api/v1/sources/:id",
+            get(handlers::sources::get_source).delete(handlers::sources::delete_source),
+        )
+        .route("/api/v1/packs", get(handlers::packs::list_packs).post(handlers::packs::install_pack))
+        .route("/api/v1/packs/:agent_id/:name", delete(handlers::packs::uninstall_pack))
+        .route("/api/v1/tenants/:tenant_id/skills", get(handlers::tenants::get_tenant_skills))
+        .route("/api/v1/tenants/:tenant_id/policy", post(handlers::tenants::set_tenant_policy))
+        .route("/api/v1/usage/:tenant_id", get(handlers::usage::get_usage))
+        .route("/api/v1/prompts", get(handlers::prompts::list_prompts))
+        .route("/api/v1/evals", get(handlers::evals::list_evals))
+        .route("/api/v1/evals/run", post(handlers::evals::run_eval))
+        .route("/api/v1/evals/:suite/runs", get(handlers::evals::list_runs))
+        .route("/api/v1/evals/:suite/runs/:run_id", get(handlers::evals::get_run))
+        .route(
+            "/api/v1/ethos/rules",
+            get(handlers::ethos::get_rules)
+                .put(handlers::ethos::put_rules)
+                .delete(handlers::ethos::clear_rules),
+        )
+        .route("/api/v1/ethos/rules/versions", get(handlers::ethos::list_versions))
+        .route("/api/v1/ethos/evaluate", post(handlers::ethos::evaluate))
+        .route("/api/v1/approvals", get(handlers::approvals::list_approvals))
+        .route("/api/v1/approvals/:id", get(handlers::approvals::get_approval))
+        .route("/api/v1/approvals/:id/approve", post(handlers::approvals::approve))
+        .route("/api/v1/approvals/:id/deny", post(handlers::approvals::deny))
+        .route("/api/v1/sessions/:id/history", get(handlers::sessions::get_history))
+        .route("/api/v1/agents", get(handlers::agents::list_agents).post(handlers::agents::create_agent))
+        .route(
+            "/api/v1/agents/:id",
+            get(handlers::agents::get_agent)
+                .patch(handlers::agents::update_schedule)
+                .delete(handlers::agents::archive_agent),
+        )
+        .route(
+            "/api/v1/agents/:id/persona",
+            get(handlers::personas::get_persona)
+                .put(handlers::personas::put_persona)
+                .delete(handlers::personas::delete_persona),
+        )
+        .route("/api/v1/agents/:id/subscriptions", get(handlers::agents::list_subscriptions))
+        .route(
+            "/api/v1/agents/:id/subscriptions/:topic",
+            put(handlers::agents::subscribe).delete(handlers::agents::unsubscribe),
+        )
+        .route("/api/v1/agents/:id/dlq", get(handlers::agents::list_dead_letters))
+        .route(
+            "/api/v1/agents/:id/dlq/:message_id/requeue",
+            post(handlers::agents::requeue_dead_letter),
+        )
+        .route(
+            "/api/v1/prompts/:name",
+            get(handlers::prompts::get_prompt).put(handlers::prompts::put_prompt),
+        )
+        .route("/api/v1/archive/segments", get(handlers::archive::list_segments))
+        .route("/api/v1/archive/run", post(handlers::archive::run_archive))
+        .route("/api/v1/archive/restore", post(handlers::archive::restore))
+        .route("/api/v1/audit", get(handlers::audit::list_audit))
+        .route(
+            "/api/v1/knowledge/reembed",
+            get(handlers::reembed::list_reembed_jobs).post(handlers::reembed::start_reembed),
+        )
+        .route("/api/v1/knowledge/reembed/:job_id", get(handlers::reembed::get_reembed_job))
+        .route("/api/v1/knowledge/reembed/:job_id/resume", post(handlers::reembed::resume_reembed_job))
+        .route("/api/v1/knowledge/changes", get(handlers::changes::knowledge_changes))
+        .route(
+            "/api/v1/kb/:slot/documents",
+            get(handlers::documents::list_documents).post(handlers::documents::upload_documents),
+        )
+        .route("/api/v1/kb/:slot/documents/:document_id", delete(handlers::documents::delete_document))
+        .route("/api/v1/leads/:id/status", post(handlers::leads::update_lead_status))
+        .route("/api/v1/ingest/:source", post(handlers::ingest::ingest))
+        .route("/api/v1/chat/slack/commands", post(handlers::slack::slack_command))
+        .route(
+            "/api/v1/channels/telegram/webhook",
+            post(channels::telegram::webhook).layer(Extension(Arc::new(channels::telegram::TelegramBot::new(
+                state.config.get().channels.telegram.clone(),
+            )))),
+        )
+        .route("/api/v1/webhooks", get(handlers::webhooks::list_webhooks).post(handlers::webhooks::create_webhook))
+        .route(
+            "/api/v1/webhooks/:id",
+            get(handlers::webhooks::get_webhook).delete(handlers::webhooks::delete_webhook),
+        )
+        .route("/api/v1/webhooks/:id/deliveries", get(handlers::webhooks::list_deliveries))
+        .route(
+            "/api/v1/knowledge/snapshots",
+            get(handlers::snapshots::list_snapshots).post(handlers::snapshots::create_snapshot),
+        )
+        .route("/api/v1/knowledge/snapshots/:id", delete(handlers::snapshots::delete_snapshot))
+        .route("/api/v1/knowledge/snapshots/:id/restore", post(handlers::snapshots::restore_snapshot))
+        .route("/api/v1/knowledge/export", get(handlers::export::export_knowledge))
+        .route("/api/v1/knowledge/import", post(handlers::export::import_knowledge))
+        .route("/api/v1/admin/reload-config", post(handlers::reload::reload_config))
+        .route("/api/v1/sandbox/files", get(handlers::sandbox::list_files))
+        .route(
+            "/api/v1/sandbox/files/*path",
+            get(handlers::sandbox::read_file)
+                .put(handlers::sandbox::write_file)
+                .delete(handlers::sandbox::delete_file),
+        )
+        .route("/api/v1/sandbox/events", get(handlers::sandbox::sandbox_events))
+        .route(
+            "/api/v1/standing-queries/:id",
+            get(handlers::standing_queries::get_standing_query)
+                .delete(handlers::standing_queries::delete_standing_query),
+        )
+        .route("/api/v1/auth/whoami", get(handlers::auth::whoami))
+        .route("/api/v1/auth/keys", get(handlers::auth::list_keys).post(handlers::auth::create_key))
+        .route(
+            "/api/v1/auth/keys/:key_id",
+            get(handlers::auth::get_key).delete(handlers::auth::revoke_key),
+        )
+        // Body size limits ([limits]); axum's own 2 MB default gives way to them.
+        .route_layer(axum::middleware::from_fn_with_state(state.clone(), handlers::body_limit::limit_body))
+        .layer(axum::extract::DefaultBodyLimit::disable());
+
+    // Opt-in API recorder (PAGI_API_RECORDER=true): samples exchanges into KB-8. It sits inside
+    // `authorize`, so unauthenticated and refused requests are never recorded.
+    if let Some(recorder_config) = handlers::recorder::RecorderConfig::from_env() {
+        tracing::info!(target: "pagi::recorder", ?recorder_config, "API recorder enabled");
+        let recorder = handlers::recorder::ApiRecorder::new(recorder_config, knowledge);
+        routes = routes.route_layer(axum::middleware::from_fn_with_state(
+            recorder,
+            handlers::recorder::record_exchange,
+        ));
+    }
+
+    // Role-based access: every route above is checked against the caller's API key (runs
+    // before the body limit, so unauthenticated bodies are not read).
+    let mut app = routes
+        .route_layer(axum::middleware::from_fn_with_state(state.clone(), handlers::auth::authorize))
+        .with_state(state);
+
+    // The UI is always routed but answers 404 while `frontend_enabled` is off, so a config
+    // reload can switch it.
+    let frontend_dir = frontend_root_dir();
+    let index_file = frontend_dir.join("index.html");
+    let assets_dir = frontend_dir.join("assets");
+
+    // Map `/` -> `pagi-frontend/index.html`
+    let mut frontend = Router::new().route_service("/", ServeFile::new(index_file));
+
+    // Map `/assets/*` -> `pagi-frontend/assets/*` (CSS, images, etc.)
+    if assets_dir.exists() {
+        frontend = frontend.nest_service("/assets", ServeDir::new(assets_dir));
+    }
+
+    // Map `/ui/*` -> `pagi-frontend/*` (app.js, assets, and any other files)
+    frontend = frontend
+        .nest_service("/ui", ServeDir::new(frontend_dir))
+        .layer(axum::middleware::from_fn_with_state(live_config.clone(), frontend_gate));
+    app = app.merge(frontend);
+
+    // Outside auth and the body limit, so refused requests are logged too.
+    app.layer(axum::middleware::from_fn_with_state(live_config, handlers::request_log::log_request))
+        .layer(cors)
+}
+
+#[derive(Clone)]
 pub(crate) struct AppState {
-    pub(crate) config: Arc<CoreConfig>,
+    pub(crate) config: LiveConfig,
     pub(crate) orchestrator: Arc<Orchestrator>,
     pub(crate) knowledge: Arc<KnowledgeStore>,
-    pub(crate) log_tx: broadcast::Sender<String>,
-    pub(crate) model_router: Arc<ModelRouter>,
+    pub(crate) log_tx: broadcast::Sender<logging::LogEvent>,
     pub(crate) shadow_store: ShadowStoreHandle,
+    /// Providers behind model aliases, shared with the ModelRouter (health for /v1/status).
+    pub(crate) llm_providers: Arc<LlmProviders>,
+    /// Validates SSO bearer tokens when `[oidc]` names an issuer.
+    pub(crate) oidc: Option<Arc<oidc::OidcVerifier>>,
+}
+
+/// The running config. A reload (`POST /api/v1/admin/reload-config`) swaps in a copy with the
+/// hot-reloadable fields replaced; the rest keep their startup values until a restart.
+#[derive(Clone)]
+pub(crate) struct LiveConfig {
+    current: Arc<std::sync::RwLock<Arc<CoreConfig>>>,
+    /// Where reloads read the config from.
+    sources: Arc<pagi_core::ConfigSources>,
+}
+
+impl LiveConfig {
+    pub(crate) fn new(config: impl Into<Arc<CoreConfig>>) -> Self {
+        Self {
+            current: Arc::new(std::sync::RwLock::new(config.into())),
+            sources: Arc::new(pagi_core::ConfigSources::current()),
+        }
+    }
+
+    #[cfg(test)]
+    pub(crate) fn with_sources(mut self, sources: pagi_core::ConfigSources) -> Self {
+        self.sources = Arc::new(sources);
+        self
+    }
+
+    pub(crate) fn get(&self) -> Arc<CoreConfig> {
+        Arc::clone(&self.current.read().unwrap_or_else(std::sync::PoisonError::into_inner))
+    }
+
+    pub(crate) fn set(&self, config: CoreConfig) {
+        *self.current.write().unwrap_or_else(std::sync::PoisonError::into_inner) = Arc::new(config);
+    }
+
+    pub(crate) fn sources(&self) -> &pagi_core::ConfigSources {
+        &self.sources
+    }
 }
 
 /// GET /api/v1/health – liveness check. Returns Sovereign identity so UI can verify it is not talking to a Sandbox.
@@ -843,50 +1815,66 @@ async fn kb_status(State(state): State<AppState>) -> axum::Json<serde_json::Valu
     }))
 }
 
-/// GET /api/v1/sovereign-status – full cross-layer state for the Sovereign Dashboard.
-/// When the dashboard cannot open Sled (e.g. gateway holds the lock), it can fetch this endpoint instead.
-/// If PAGI_API_KEY is set, the request must include header `X-API-Key: <key>` or `Authorization: Bearer <key>`.
-async fn sovereign_status(
-    State(state): State<AppState>,
-    headers: HeaderMap,
-) -> Result<axum::Json<SovereignState>, (StatusCode, &'static str)> {
-    if let Ok(expect_key) = std::env::var("PAGI_API_KEY") {
-        let expect_key = expect_key.trim().to_string();
-        if !expect_key.is_empty() {
-            let provided = headers
-                .get("X-API-Key")
-                .and_then(|v| v.to_str().ok())
-                .map(|s| s.trim())
-                .or_else(|| {
-                    headers
-                        .get(axum::http::header::AUTHORIZATION)
-                        .and_then(|v| v.to_str().ok())
-                        .and_then(|s| s.strip_prefix("Bearer "))
-                        .map(|s| s.trim())
-                });
-            if provided.as_ref() != Some(&expect_key.as_str()) {
-                return Err((StatusCode::UNAUTHORIZED, "Missing or invalid PAGI_API_KEY"));
-            }
+/// GET /api/v1/knowledge/storage – per-slot stored vs raw value sizes (record-level compression).
+async fn kb_storage(State(state): State<AppState>) -> (StatusCode, axum::Json<serde_json::Value>) {
+    let knowledge = Arc::clone(&state.knowledge);
+    match tokio::task::spawn_blocking(move || knowledge.storage_stats()).await {
+        Ok(Ok(slots)) => {
+            let stored_bytes: u64 = slots.iter().map(|s| s.stored_bytes).sum();
+            let raw_bytes: u64 = slots.iter().map(|s| s.raw_bytes).sum();
+            (
+                StatusCode::OK,
+                axum::Json(serde_json::json!({
+                    "status": "ok",
+                    "stored_bytes": stored_bytes,
+                    "raw_bytes": raw_bytes,
+                    "slots": slots,
+                })),
+            )
         }
+        Ok(Err(e)) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            axum::Json(serde_json::json!({ "status": "error", "error": e.to_string() })),
+        ),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            axum::Json(serde_json::json!({ "status": "error", "error": e.to_string() })),
+        ),
     }
+}
+
+/// GET /api/v1/sovereign-status – full cross-layer state for the Sovereign Dashboard.
+/// When the dashboard cannot open Sled (e.g. gateway holds the lock), it can fetch this endpoint instead.
+/// Needs the `vault_read` permission once API keys are enforced (see `handlers::auth`).
+async fn sovereign_status(State(state): State<AppState>) -> axum::Json<SovereignState> {
     const AGENT_ID: &str = "default";
-    let sovereign = state.knowledge.get_full_sovereign_state(AGENT_ID);
-    Ok(axum::Json(sovereign))
+    axum::Json(state.knowledge.get_full_sovereign_state(AGENT_ID))
+}
+
+#[derive(serde::Deserialize)]
+struct LogsQuery {
+    /// `text` or `json`; `[logging] format` when absent.
+    #[serde(default)]
+    format: Option<pagi_core::LogFormat>,
 }
 
-/// GET /api/v1/logs – Server-Sent Events stream of gateway logs (tracing output).
+/// GET /api/v1/logs – Server-Sent Events stream of gateway logs (tracing output), one event per
+/// log line, or per JSON object (see `logging::LogEvent`) with `?format=json`.
 async fn logs_stream(
     State(state): State<AppState>,
+    axum::extract::Query(query): axum::extract::Query<LogsQuery>,
 ) -> Sse<impl futures_util::Stream<Item = Result<Event, std::convert::Infallible>> + Send + 'static> {
     use async_stream::stream;
+    let json = query.format.unwrap_or(state.config.get().logging.format) == pagi_core::LogFormat::Json;
+    let data = move |event: &logging::LogEvent| if json { event.json() } else { event.text() };
     let mut rx = state.log_tx.subscribe();
     let stream = stream! {
         loop {
             tokio::select! {
                 r = rx.recv() => match r {
-                    Ok(line) => yield Ok(Event::default().data(line)),
+                    Ok(event) => yield Ok(Event::default().data(data(&event))),
                     Err(broadcast::error::RecvError::Lagged(n)) => {
-                        yield Ok(Event::default().data(format!("... {} log lines dropped", n)));
+                        yield Ok(Event::default().data(data(&logging::LogEvent::dropped(n))));
                     }
                     Err(broadcast::error::RecvError::Closed) => break,
                 },
@@ -919,8 +1907,7 @@ async fn vault_read(
         .get(HEADER_KEY)
         .and_then(|v| v.to_str().ok())
         .map(|s| s.trim().replace([' ', '\n'], ""));
-    let env_key = std::env::var("PAGI_SHADOW_KEY")
-        .ok()
+    let env_key = pagi_core::secret(pagi_core::SECRET_SHADOW_KEY)
         .map(|s| s.trim().replace([' ', '\n'], ""));
     if client_key.as_ref() != env_key.as_ref() || env_key.is_none() {
         return Err((StatusCode::FORBIDDEN, "Missing or invalid X-Pagi-Shadow-Key"));
@@ -947,29 +1934,48 @@ async fn vault_read(
     Ok(axum::Json(json))
 }
 
-/// GET /v1/status – app identity and slot labels from config.
+/// GET /v1/status – app identity and slot labels from config, plus LLM provider health.
 async fn status(State(state): State<AppState>) -> axum::Json<serde_json::Value> {
-    let labels: std::collections::HashMap<u8, String> = state.config.slot_labels_map();
+    let config = state.config.get();
+    let labels: std::collections::HashMap<u8, String> = config.slot_labels_map();
     let labels_json: std::collections::HashMap<String, String> = labels
         .into_iter()
         .map(|(k, v)| (k.to_string(), v))
         .collect();
     axum::Json(serde_json::json!({
-        "app_name": state.config.app_name,
-        "port": state.config.port,
-        "llm_mode": state.config.llm_mode,
+        "app_name": config.app_name,
+        "port": config.port,
+        "llm_mode": config.llm_mode,
         "slot_labels": labels_json,
+        "dispatch_queue": state.orchestrator.dispatch_queue().map(|q| q.stats()),
+        "llm_providers": state.llm_providers.health(),
     }))
 }
 
 #[derive(serde::Deserialize)]
 struct ExecuteRequest {
+    /// May be left out by a tenant-bound caller, which always runs as its tenant.
+    #[serde(default)]
     tenant_id: String,
     correlation_id: Option<String>,
     /// Agent instance ID for multi-agent mode. Chronos and Kardia are keyed by this. Default: "default".
     #[serde(default)]
     agent_id: Option<String>,
     goal: Goal,
+    /// Same as the `Idempotency-Key` header (the header wins when both are set).
+    #[serde(default)]
+    idempotency_key: Option<String>,
+}
+
+/// Query params for POST /v1/execute
+#[derive(serde::Deserialize, Default)]
+struct ExecuteParams {
+    /// Preview the plan (skills, Ethos checks, estimated payloads) without running any skill.
+    #[serde(default)]
+    dry_run: bool,
+    /// Run the goal as a background job: answer 202 with its `job_id` (see `/v1/jobs/:job_id`).
+    #[serde(default, rename = "async")]
+    run_async: bool,
 }
 
 /// Chat request from the Studio UI frontend
@@ -991,96 +1997,444 @@ struct ChatRequest {
     max_tokens: Option<u32>,
     #[serde(default)]
     persona: Option<String>,
+    /// Let the model call the tenant's skills as tools (`Goal::ToolChat`); not streamed.
+    #[serde(default)]
+    tools: bool,
+    #[serde(default)]
+    max_tool_iterations: Option<u32>,
+    /// Conversation session: the exchange is recorded as its next turn and its history goes into
+    /// the prompt. A new session is started (and its id returned) when absent.
+    #[serde(default)]
+    session_id: Option<String>,
+    /// Retrieval-augmented answer: the tenant's records most similar to the prompt go into the
+    /// system prompt, and the response lists them as `sources`.
+    #[serde(default)]
+    rag: bool,
+    /// Slots searched in RAG mode (default `[3]`); slots off in the control panel are skipped.
+    #[serde(default)]
+    rag_slots: Option<Vec<u8>>,
+    #[serde(default)]
+    rag_top_k: Option<usize>,
+}
+
+/// Seconds a `/v1/execute` response (or a deduplicated ingest delivery) stays cached under its
+/// idempotency key (`PAGI_IDEMPOTENCY_TTL_SECS`, default 24h).
+fn idempotency_ttl_ms() -> i64 {
+    std::env::var("PAGI_IDEMPOTENCY_TTL_SECS")
+        .ok()
+        .and_then(|v| v.trim().parse::<i64>().ok())
+        .unwrap_or(24 * 60 * 60)
+        .saturating_mul(1000)
+}
+
+/// Longest accepted idempotency key.
+const MAX_IDEMPOTENCY_KEY_LEN: usize = 255;
+
+/// Client time budget for a request, in milliseconds from arrival.
+const REQUEST_TIMEOUT_HEADER: &str = "x-request-timeout-ms";
+
+/// Server cap on a request's time budget (`PAGI_REQUEST_TIMEOUT_SECS`, default 300; `0` = none).
+/// Also the budget of requests that send no timeout header.
+fn max_request_timeout() -> Option<Duration> {
+    let secs = std::env::var("PAGI_REQUEST_TIMEOUT_SECS")
+        .ok()
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .unwrap_or(300);
+    (secs > 0).then(|| Duration::from_secs(secs))
+}
+
+/// Default deadline (Unix ms) for requests without a client budget, e.g. WebSocket frames.
+pub(crate) fn default_deadline_ms() -> Option<i64> {
+    max_request_timeout().map(|cap| system_now_ms().saturating_add(cap.as_millis() as i64))
+}
+
+/// Malformed `X-Request-Timeout-Ms` header; answered with a 400.
+struct BadTimeoutHeader;
+
+impl IntoResponse for BadTimeoutHeader {
+    fn into_response(self) -> Response {
+        (
+            StatusCode::BAD_REQUEST,
+            axum::Json(serde_json::json!({
+                "status": "error",
+                "error": "X-Request-Timeout-Ms must be a positive number of milliseconds",
+            })),
+        )
+            .into_response()
+    }
+}
+
+/// Deadline (Unix ms) from the `X-Request-Timeout-Ms` header, capped by the server maximum.
+fn request_deadline_ms(headers: &HeaderMap) -> Result<Option<i64>, BadTimeoutHeader> {
+    let budget = match headers.get(REQUEST_TIMEOUT_HEADER) {
+        None => None,
+        Some(v) => match v.to_str().ok().and_then(|s| s.trim().parse::<u64>().ok()) {
+            Some(ms) if ms > 0 => Some(Duration::from_millis(ms)),
+            _ => return Err(BadTimeoutHeader),
+        },
+    };
+    let budget = match (budget, max_request_timeout()) {
+        (Some(b), Some(cap)) => Some(b.min(cap)),
+        (b, cap) => b.or(cap),
+    };
+    Ok(budget.map(|b| system_now_ms().saturating_add(b.as_millis() as i64)))
+}
+
+/// Response body for a dispatch that ran out of time.
+pub(crate) fn deadline_body(e: &DeadlineExceeded) -> serde_json::Value {
+    serde_json::json!({
+        "status": "timeout",
+        "error": e.to_string(),
+        "error_code": "deadline_exceeded",
+        "retryable": true,
+        "completed_steps": e.completed_steps,
+        "deadline_ms": e.deadline_ms,
+    })
+}
+
+/// `/v1/execute` response: 504 for a timed-out run, 202 for an accepted background job, 200 otherwise.
+fn execute_response(body: serde_json::Value) -> Response {
+    let status = match body.get("status").and_then(|s| s.as_str()) {
+        Some("timeout") => StatusCode::GATEWAY_TIMEOUT,
+        Some("accepted") => StatusCode::ACCEPTED,
+        _ => StatusCode::OK,
+    };
+    (status, axum::Json(body)).into_response()
 }
 
 async fn execute(
     State(state): State<AppState>,
-    Json(req): Json<ExecuteRequest>,
-) -> axum::Json<serde_json::Value> {
+    axum::extract::Query(params): axum::extract::Query<ExecuteParams>,
+    headers: HeaderMap,
+    caller: Option<Extension<Caller>>,
+    Json(mut req): Json<ExecuteRequest>,
+) -> Response {
+    // A tenant-bound caller (tenant key, SSO token with a tenant claim) runs as its own tenant.
+    req.tenant_id = match handlers::auth::acting_tenant(caller.as_ref(), Some(&req.tenant_id)) {
+        Ok(Some(tenant_id)) => tenant_id,
+        Ok(None) => {
+            let body = serde_json::json!({ "status": "error", "error": "tenant_id is required" });
+            return (StatusCode::BAD_REQUEST, axum::Json(body)).into_response();
+        }
+        Err(denied) => return denied.into_response(),
+    };
+    if params.dry_run {
+        let ctx = TenantContext {
+            tenant_id: req.tenant_id,
+            correlation_id: req.correlation_id,
+            agent_id: req.agent_id,
+            ..Default::default()
+        };
+        let preview = state.orchestrator.plan_preview(&ctx, &req.goal);
+        return axum::Json(serde_json::json!({
+            "status": if preview.valid { "ok" } else { "invalid" },
+            "dry_run": true,
+            "preview": preview,
+        }))
+        .into_response();
+    }
+    let idempotency_key = headers
+        .get("idempotency-key")
+        .and_then(|v| v.to_str().ok())
+        .map(|s| s.trim().to_string())
+        .or(req.idempotency_key.clone())
+        .filter(|k| !k.is_empty());
+    if let Some(key) = &idempotency_key {
+        if key.len() > MAX_IDEMPOTENCY_KEY_LEN || key.chars().any(char::is_control) {
+            return (
+                StatusCode::BAD_REQUEST,
+                axum::Json(serde_json::json!({
+                    "status": "error",
+                    "error": format!("Idempotency-Key must be at most {} printable characters", MAX_IDEMPOTENCY_KEY_LEN),
+                })),
+            )
+                .into_response();
+        }
+    }
+    let deadline_ms = match request_deadline_ms(&headers) {
+        Ok(deadline_ms) => deadline_ms,
+        Err(bad) => return bad.into_response(),
+    };
+    // Background jobs outlive the request: only a client budget bounds them.
+    let deadline_ms = deadline_ms.filter(|_| !params.run_async || headers.contains_key(REQUEST_TIMEOUT_HEADER));
     tracing::info!("Skill execution started");
-    let agent_id = req.agent_id.as_deref().filter(|s| !s.is_empty()).unwrap_or(pagi_core::DEFAULT_AGENT_ID);
-    let is_kb_query = matches!(req.goal, Goal::QueryKnowledge { .. });
     let ctx = TenantContext {
         tenant_id: req.tenant_id,
         correlation_id: req.correlation_id,
-        agent_id: Some(agent_id.to_string()),
+        agent_id: req.agent_id,
+        deadline_ms,
+    };
+
+    // Idempotency: the first request with a key runs; retries get its cached response.
+    let tenant_id = ctx.tenant_id.clone();
+    let claimed = match &idempotency_key {
+        Some(key) => {
+            let fingerprint = pagi_core::IdempotencyRecord::fingerprint(&serde_json::json!({
+                "agent_id": ctx.resolved_agent_id(),
+                "goal": req.goal,
+            }));
+            match state
+                .knowledge
+                .claim_idempotency_key(&tenant_id, key, &fingerprint, idempotency_ttl_ms())
+            {
+                Ok(IdempotencyClaim::Claimed) => Some(key.as_str()),
+                Ok(IdempotencyClaim::Completed(response)) => {
+                    tracing::info!(target: "pagi::gateway", tenant_id = %tenant_id, key = %key, "Idempotent replay");
+                    let mut res = execute_response(response);
+                    res.headers_mut()
+                        .insert("idempotent-replayed", axum::http::HeaderValue::from_static("true"));
+                    return res;
+                }
+                Ok(IdempotencyClaim::InProgress) => {
+                    return (
+                        StatusCode::CONFLICT,
+                        axum::Json(serde_json::json!({
+                            "status": "error",
+                            "error": "A request with this Idempotency-Key is still running",
+                        })),
+                    )
+                        .into_response();
+                }
+                Ok(IdempotencyClaim::Mismatch) => {
+                    return (
+                        StatusCode::UNPROCESSABLE_ENTITY,
+                        axum::Json(serde_json::json!({
+                            "status": "error",
+                            "error": "Idempotency-Key was already used for a different request",
+                        })),
+                    )
+                        .into_response();
+                }
+                Err(e) => {
+                    // Fail open: running without the cache beats rejecting the request.
+                    tracing::warn!(target: "pagi::gateway", error = %e, "Idempotency key claim failed");
+                    None
+                }
+            }
+        }
+        None => None,
+    };
+
+    if params.run_async {
+        let body = match handlers::jobs::start_job(&state, ctx, req.goal) {
+            Ok(job) => serde_json::json!({
+                "status": "accepted",
+                "job_id": job.job_id,
+                "correlation_id": job.correlation_id,
+            }),
+            Err(e) => {
+                if let Some(key) = claimed {
+                    let _ = state.knowledge.release_idempotency_key(&tenant_id, key);
+                }
+                return (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    axum::Json(serde_json::json!({ "status": "error", "error": e })),
+                )
+                    .into_response();
+            }
+        };
+        // Retries with the same key get the same job.
+        if let Some(key) = claimed {
+            if let Err(e) = state.knowledge.complete_idempotency_key(&tenant_id, key, &body) {
+                tracing::warn!(target: "pagi::gateway", error = %e, "Failed to cache idempotent response");
+            }
+        }
+        return execute_response(body);
+    }
+
+    let body = match run_goal(&state, ctx, req.goal).await {
+        Ok(result) => result,
+        Err(e) => match e.downcast_ref::<Throttled>() {
+            Some(throttled) => {
+                // Nothing ran, so the client may retry with the same key.
+                if let Some(key) = claimed {
+                    let _ = state.knowledge.release_idempotency_key(&tenant_id, key);
+                }
+                return throttled_response(throttled);
+            }
+            None => match e.downcast_ref::<DeadlineExceeded>() {
+                Some(exceeded) => deadline_body(exceeded),
+                None if e.is::<GoalCancelled>() => serde_json::json!({
+                    "error": e.to_string(),
+                    "status": "cancelled"
+                }),
+                None => match e.downcast_ref::<SkillError>() {
+                    Some(violation) => serde_json::json!({
+                        "error": e.to_string(),
+                        "status": "error",
+                        "error_code": violation.kind(),
+                        "skill": violation.skill(),
+                    }),
+                    None => serde_json::json!({
+                        "error": e.to_string(),
+                        "status": "error"
+                    }),
+                },
+            },
+        },
+    };
+    // Failed, cancelled and timed-out runs are cached too: their steps may already have had side effects.
+    if let Some(key) = claimed {
+        if let Err(e) = state.knowledge.complete_idempotency_key(&tenant_id, key, &body) {
+            tracing::warn!(target: "pagi::gateway", error = %e, "Failed to cache idempotent response");
+        }
+    }
+    execute_response(body)
+}
+
+/// Runs a goal the way `/v1/execute` does: the ReflectShadow session-key check (its rejection is
+/// an `Ok` JSON body with an error `status`), dispatch, and the Chronos episode on success. Ethos
+/// blocks, which the orchestrator raises for the goal's skill call or any plan step, are such
+/// bodies too (`approval_required` ones keep the goal on their approval for resuming); other
+/// dispatch errors are returned as is.
+pub(crate) async fn run_goal(
+    state: &AppState,
+    ctx: TenantContext,
+    goal: Goal,
+) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>> {
+    let agent_id = ctx.resolved_agent_id().to_string();
+    let is_kb_query = matches!(goal, Goal::QueryKnowledge { .. });
+    // Approvals are matched by correlation id, so every run has one.
+    let correlation_id = ctx
+        .correlation_id
+        .clone()
+        .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+    let ctx = TenantContext {
+        agent_id: Some(agent_id.clone()),
+        correlation_id: Some(correlation_id),
+        ..ctx
     };
 
     // ReflectShadow: require session_key to match PAGI_SHADOW_KEY (vault must be explicitly opened)
-    if let Goal::ExecuteSkill { ref name, ref payload } = req.goal {
+    if let Goal::ExecuteSkill { ref name, ref payload } = goal {
         if name == "ReflectShadow" {
             let client_key = payload
                 .as_ref()
                 .and_then(|p| p.get("session_key"))
                 .and_then(|v| v.as_str())
                 .map(|s| s.trim().replace([' ', '\n'], ""));
-            let env_key = std::env::var("PAGI_SHADOW_KEY")
-                .ok()
+            let env_key = pagi_core::secret(pagi_core::SECRET_SHADOW_KEY)
                 .map(|s| s.trim().replace([' ', '\n'], ""));
             if client_key.as_ref() != env_key.as_ref() || env_key.is_none() {
-                return axum::Json(serde_json::json!({
+                return Ok(serde_json::json!({
                     "status": "error",
                     "error": "ReflectShadow requires valid session_key (X-Pagi-Shadow-Key / PAGI_SHADOW_KEY)"
                 }));
             }
         }
+    }
 
-        // ETHOS pre-execution check: consult KB_ETHOS before ExecuteSkill
-        let content_to_scan = payload
-            .as_ref()
-            .map(|p| {
-                p.get("content")
-                    .and_then(|v| v.as_str())
-                    .unwrap_or("")
-                    .to_string()
-            })
-            .unwrap_or_else(|| payload.as_ref().map(|p| p.to_string()).unwrap_or_default());
-        if let Some(policy) = state.knowledge.get_ethos_policy() {
-            match policy.allows(name, &content_to_scan) {
-                AlignmentResult::Fail { reason } => {
-                    let violation = EventRecord::now("Ethos", format!("Policy Violation: {}", reason))
-                        .with_skill(name.clone())
-                        .with_outcome("blocked");
-                    let _ = state.knowledge.append_chronos_event(agent_id, &violation);
-                    tracing::warn!(
-                        target: "pagi::ethos",
-                        skill = %name,
-                        reason = %reason,
-                        "Ethos: execution blocked"
-                    );
-                    return axum::Json(serde_json::json!({
-                        "status": "policy_violation",
-                        "error": reason,
-                        "skill": name,
-                    }));
+    let result = match state.orchestrator.dispatch(&ctx, goal.clone()).await {
+        Ok(result) => result,
+        Err(e) => {
+            let Some(blocked) = e.downcast_ref::<EthosBlocked>() else {
+                return Err(e);
+            };
+            // A suspended call: keep the goal so approving it can resume the dispatch.
+            if let Some(approval_id) = blocked.approval_id.as_deref().filter(|_| blocked.requires_approval()) {
+                let attached = state.knowledge.update_approval(approval_id, |approval| {
+                    if approval.goal.is_none() {
+                        approval.goal = Some(goal.clone());
+                    }
+                });
+                if let Err(e) = attached {
+                    tracing::warn!(target: "pagi::ethos", approval_id, error = %e, "Failed to attach goal to approval");
                 }
-                AlignmentResult::Pass => {}
             }
+            return Ok(ethos_blocked_body(state, &agent_id, blocked));
         }
+    };
+    if is_kb_query {
+        tracing::info!("KB search success");
     }
-
-    match state.orchestrator.dispatch(&ctx, req.goal.clone()).await {
-        Ok(result) => {
-            if is_kb_query {
-                tracing::info!("KB search success");
-            }
-            // Episodic memory: log successful execution to KB_CHRONOS (the Historian)
-            if let Some(event) = chronos_event_from_goal_and_result(&req.goal, &result) {
-                if state.knowledge.append_chronos_event(agent_id, &event).is_err() {
-                    tracing::warn!(target: "pagi::chronos", "Failed to append Chronos event");
-                }
-            }
-            axum::Json(result)
+    // Episodic memory: log successful execution to KB_CHRONOS (the Historian)
+    if let Some(event) = chronos_event_from_goal_and_result(&state.knowledge, &goal, &result) {
+        if state.knowledge.append_chronos_event(&agent_id, &event).is_err() {
+            tracing::warn!(target: "pagi::chronos", "Failed to append Chronos event");
         }
-        Err(e) => axum::Json(serde_json::json!({
-            "error": e.to_string(),
-            "status": "error"
+    }
+    Ok(result)
+}
+
+/// Body for a skill call the Ethos policy stopped (`policy_violation`, or `approval_required` with
+/// the pending approval's id, plus the trace of a halted plan); the block is recorded in the
+/// agent's Chronos history.
+fn ethos_blocked_body(state: &AppState, agent_id: &str, blocked: &EthosBlocked) -> serde_json::Value {
+    let EthosBlocked {
+        skill,
+        decision,
+        approval_id,
+        steps,
+    } = blocked;
+    let reason = decision.reason.clone().unwrap_or_else(|| "blocked by Ethos policy".to_string());
+    let (status, event) = match decision.action {
+        RuleAction::RequireApproval => (
+            "approval_required",
+            state.knowledge.event("Ethos", format!("Approval Required: {}", reason)).with_outcome("approval_required"),
+        ),
+        _ => (
+            "policy_violation",
+            state.knowledge.event("Ethos", format!("Policy Violation: {}", reason)).with_outcome("blocked"),
+        ),
+    };
+    let _ = state.knowledge.append_chronos_event(agent_id, &event.with_skill(skill.as_str()));
+    tracing::warn!(
+        target: "pagi::ethos",
+        skill = %skill,
+        rule = decision.rule_id.as_deref().unwrap_or("-"),
+        approval_id = approval_id.as_deref().unwrap_or("-"),
+        reason = %reason,
+        "Ethos: execution blocked"
+    );
+    let mut body = serde_json::json!({
+        "status": status,
+        "error": reason,
+        "skill": skill,
+        "rule_id": decision.rule_id,
+        "approval_id": approval_id,
+    });
+    if !steps.is_empty() {
+        body["steps"] = serde_json::json!(steps);
+    }
+    body
+}
+
+/// 429 with `Retry-After` for a request rejected by the dispatch queue.
+pub(crate) fn throttled_response(throttled: &Throttled) -> Response {
+    let retry_after = throttled.retry_after_secs();
+    (
+        StatusCode::TOO_MANY_REQUESTS,
+        [(axum::http::header::RETRY_AFTER, retry_after.to_string())],
+        axum::Json(serde_json::json!({
+            "status": throttled.reason,
+            "error": throttled.to_string(),
+            "retry_after_secs": retry_after,
         })),
+    )
+        .into_response()
+}
+
+/// POST /v1/execute/:correlation_id/cancel – stops the in-flight request that was submitted with
+/// this `correlation_id` (between plan steps, or mid-skill for HTTP-bound skills).
+async fn cancel_execution(
+    State(state): State<AppState>,
+    Path(correlation_id): Path<String>,
+) -> (axum::http::StatusCode, axum::Json<serde_json::Value>) {
+    if state.orchestrator.cancel(&correlation_id) {
+        (
+            axum::http::StatusCode::OK,
+            axum::Json(serde_json::json!({ "status": "cancelled", "correlation_id": correlation_id })),
+        )
+    } else {
+        (
+            axum::http::StatusCode::NOT_FOUND,
+            axum::Json(serde_json::json!({ "status": "not_found", "correlation_id": correlation_id })),
+        )
     }
 }
 
 /// Builds an episodic EventRecord for KB_CHRONOS from the executed goal and its result.
-fn chronos_event_from_goal_and_result(goal: &Goal, result: &serde_json::Value) -> Option<EventRecord> {
+fn chronos_event_from_goal_and_result(knowledge: &KnowledgeStore, goal: &Goal, result: &serde_json::Value) -> Option<EventRecord> {
     let (source_kb, reflection, skill_name, outcome) = match goal {
         Goal::ExecuteSkill { name, .. } => {
             let outcome = result
@@ -1107,27 +2461,38 @@ fn chronos_event_from_goal_and_result(goal: &Goal, result: &serde_json::Value) -
             Some("CommunityScraper".to_string()),
             result.get("event").and_then(|v| v.as_str()).map(|s| s.to_string()),
         ),
-        Goal::MemoryOp { path, .. } => (
-            "Chronos",
-            format!("Memory operation on path: {}", path),
+        Goal::AutonomousGoal { intent, .. } => (
+            "Pneuma",
+            format!("Autonomous goal: {}", intent),
             None,
             result.get("status").and_then(|v| v.as_str()).map(|s| s.to_string()),
         ),
-        Goal::AutonomousGoal { intent, .. } => (
+        Goal::Sequence(goals) | Goal::Parallel(goals) => (
             "Pneuma",
-            format!("Autonomous goal: {}", intent),
+            format!("{} of {} goals", goal.kind(), goals.len()),
             None,
             result.get("status").and_then(|v| v.as_str()).map(|s| s.to_string()),
         ),
+        Goal::ToolChat { .. } => (
+            "Soma",
+            format!(
+                "Tool chat: {} tool call(s) in {} turn(s)",
+                result.get("tool_calls").and_then(|v| v.as_u64()).unwrap_or(0),
+                result.get("iterations").and_then(|v| v.as_u64()).unwrap_or(0)
+            ),
+            Some("ModelRouter".to_string()),
+            result.get("status").and_then(|v| v.as_str()).map(|s| s.to_string()),
+        ),
         Goal::GenerateFinalResponse { context_id } => (
             "Soma",
             format!("Generated final response for context: {}", context_id),
             Some("ModelRouter".to_string()),
             result.get("generated").and_then(|v| v.as_str()).map(|s| s.chars().take(80).chain(std::iter::once('…')).collect::<String>()),
         ),
+        // MemoryOp is logged by the orchestrator, blocked and failed operations included.
         _ => return None,
     };
-    let mut event = EventRecord::now(source_kb, reflection);
+    let mut event = knowledge.event(source_kb, reflection);
     if let Some(s) = skill_name {
         event = event.with_skill(s);
     }
@@ -1142,58 +2507,107 @@ fn chronos_event_from_goal_and_result(goal: &Goal, result: &serde_json::Value) -
 /// and state.knowledge.build_system_directive() are the only path. Supports streaming and JSON.
 async fn chat(
     State(state): State<AppState>,
-    Json(req): Json<ChatRequest>,
+    headers: HeaderMap,
+    caller: Option<Extension<Caller>>,
+    Json(mut req): Json<ChatRequest>,
 ) -> Response {
+    if let Err(denied) = chat_as_caller(&mut req, caller.as_ref()) {
+        return denied.into_response();
+    }
     tracing::info!("Chat request received: {} chars, stream: {}", req.prompt.len(), req.stream);
-    
-    if req.stream {
+    let deadline_ms = match request_deadline_ms(&headers) {
+        Ok(deadline_ms) => deadline_ms,
+        Err(bad) => return bad.into_response(),
+    };
+    let session_id = match chat_session_id(&req) {
+        Ok(session_id) => session_id,
+        Err(error) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                axum::Json(serde_json::json!({ "status": "error", "error": error })),
+            )
+                .into_response()
+        }
+    };
+
+    if req.stream && !req.tools {
         // Streaming mode - return SSE stream
-        chat_streaming(state, req).await
+        chat_streaming(state, req, session_id, deadline_ms).await
     } else {
         // Non-streaming mode - return JSON
-        chat_json(state, req).await.into_response()
+        chat_json(state, req, session_id, deadline_ms).await
     }
 }
 
+/// A tenant-bound caller chats as its tenant: `user_alias` (the chat's tenant) defaults to it, and
+/// may not name another.
+fn chat_as_caller(
+    req: &mut ChatRequest,
+    caller: Option<&Extension<Caller>>,
+) -> Result<(), (StatusCode, axum::Json<serde_json::Value>)> {
+    req.user_alias = handlers::auth::acting_tenant(caller, req.user_alias.as_deref())?;
+    Ok(())
+}
+
 /// Non-streaming chat handler - returns JSON response.
 /// Builds Sovereign system directive (Identity/Soma/Kardia/Ethos/Oikos) and sends only user prompt to ModelRouter.
+/// A run that outlives `deadline_ms` is a 504 with `status: "timeout"`.
 async fn chat_json(
     state: AppState,
     req: ChatRequest,
-) -> axum::Json<serde_json::Value> {
+    session_id: String,
+    deadline_ms: Option<i64>,
+) -> Response {
     let user_id = req.user_alias.as_deref().unwrap_or("studio-user");
     let agent_id = req.agent_id.as_deref().filter(|s| !s.is_empty()).unwrap_or(pagi_core::DEFAULT_AGENT_ID);
     let ctx = TenantContext {
         tenant_id: user_id.to_string(),
         correlation_id: Some(uuid::Uuid::new_v4().to_string()),
         agent_id: Some(agent_id.to_string()),
+        deadline_ms,
     };
 
-    // Sovereign: dynamic system prompt from KnowledgeStore (no generic sandbox/research-assistant)
-    let system_directive = state.knowledge.build_system_directive(agent_id, user_id);
+    if let Some(reply) = intent_reply(&state, &ctx, &req.prompt).await {
+        save_to_memory(&state, &ctx, &session_id, &req.prompt, &reply.text);
+        return axum::Json(serde_json::json!({
+            "status": reply.status,
+            "response": reply.text,
+            "intent": reply.intent,
+            "correlation_id": ctx.correlation_id,
+            "session_id": session_id,
+        }))
+        .into_response();
+    }
+
+    // Sovereign: dynamic system prompt from KnowledgeStore (no generic sandbox/research-assistant),
+    // followed by the session's conversation context
+    let mut system_directive = chat_system_prompt(&state, &ctx, &session_id, &req.prompt);
+    let rag = chat_rag(&state, &ctx, &req, &mut system_directive).await;
 
     // Orchestrator::dispatch with ModelRouter — system_prompt + raw user prompt
-    let goal = Goal::ExecuteSkill {
-        name: "ModelRouter".to_string(),
-        payload: Some(serde_json::json!({
-            "prompt": req.prompt,
-            "system_prompt": system_directive,
-            "model": req.model,
-            "temperature": req.temperature,
-            "max_tokens": req.max_tokens,
-            "persona": req.persona,
-        })),
+    let goal = if req.tools {
+        Goal::ToolChat {
+            prompt: req.prompt.clone(),
+            system_prompt: Some(system_directive),
+            max_iterations: req.max_tool_iterations,
+        }
+    } else {
+        Goal::ExecuteSkill {
+            name: "ModelRouter".to_string(),
+            payload: Some(chat_payload(&state, &ctx, &req, system_directive)),
+        }
     };
     
     match state.orchestrator.dispatch(&ctx, goal).await {
         Ok(result) => {
             let generated = result.get("generated")
+                .or_else(|| result.get("answer"))
                 .and_then(|v| v.as_str())
                 .unwrap_or("No response generated")
                 .to_string();
             
-            // Save to KB-4 (Memory) for conversation history
-            save_to_memory(&state.knowledge, &req.prompt, &generated);
+            // Record the turn in KB-4 (Chronos) for conversation history
+            save_to_memory(&state, &ctx, &session_id, &req.prompt, &generated);
             
             tracing::info!("Chat response generated successfully");
             axum::Json(serde_json::json!({
@@ -1204,36 +2618,63 @@ async fn chat_json(
                     result.get("mode").and_then(|v| v.as_str()).unwrap_or("unknown")
                 ),
                 "model": req.model.unwrap_or_else(|| "default".to_string()),
+                "session_id": session_id,
+                "retrieval": rag.as_ref().map(|r| r.trace()),
+                "sources": rag.map(|r| r.sources),
                 "raw_result": result
             }))
+            .into_response()
         }
         Err(e) => {
-            tracing::error!("Chat error: {}", e);
-            axum::Json(serde_json::json!({
-                "status": "error",
-                "error": e.to_string(),
-                "response": format!("Error: {}", e)
-            }))
+            let user_error = report_chat_error(&state.knowledge, &ctx, e.as_ref());
+            let (status_code, status) = if user_error.code == "deadline_exceeded" {
+                (StatusCode::GATEWAY_TIMEOUT, "timeout")
+            } else {
+                (StatusCode::OK, "error")
+            };
+            (
+                status_code,
+                axum::Json(serde_json::json!({
+                    "status": status,
+                    "error": user_error.message,
+                    "error_code": user_error.code,
+                    "retryable": user_error.retryable,
+                    "correlation_id": user_error.correlation_id,
+                    "session_id": session_id,
+                    "response": user_error.display_message()
+                })),
+            )
+                .into_response()
         }
     }
 }
 
 /// Streaming chat handler - returns plain-text stream of tokens.
-/// Builds Sovereign system directive and sends [system, user] to ModelRouter (no sandbox prompt).
+/// Builds Sovereign system directive and streams [system, user] through
+/// `Orchestrator::dispatch_stream(ModelRouter)`, forwarding `delta` chunks as text.
 async fn chat_streaming(
     state: AppState,
     req: ChatRequest,
+    session_id: String,
+    deadline_ms: Option<i64>,
 ) -> Response {
     use async_stream::stream;
     
     let user_id = req.user_alias.as_deref().unwrap_or("studio-user");
     let agent_id = req.agent_id.as_deref().filter(|s| !s.is_empty()).unwrap_or(pagi_core::DEFAULT_AGENT_ID);
-    let system_directive = state.knowledge.build_system_directive(agent_id, user_id);
+    let correlation_id = uuid::Uuid::new_v4().to_string();
+    let ctx = TenantContext {
+        tenant_id: user_id.to_string(),
+        correlation_id: Some(correlation_id.clone()),
+        agent_id: Some(agent_id.to_string()),
+        deadline_ms,
+    };
+    let mut system_directive = chat_system_prompt(&state, &ctx, &session_id, &req.prompt);
+    // The raw text stream has no room for `sources`; `/api/v1/chat/stream` sends them as an event.
+    chat_rag(&state, &ctx, &req, &mut system_directive).await;
 
-    let model = req.model.clone();
-    let temperature = req.temperature;
-    let max_tokens = req.max_tokens;
     let knowledge = Arc::clone(&state.knowledge);
+    let session_id_header = session_id.clone();
     
     tracing::info!(
         target: "pagi::chat",
@@ -1242,50 +2683,51 @@ async fn chat_streaming(
         req.prompt.len(),
         system_directive.len()
     );
-    
-    // Check if we're in mock mode
-    let is_live = std::env::var("PAGI_LLM_MODE").as_deref() == Ok("live");
+
+    let goal = Goal::ExecuteSkill {
+        name: "ModelRouter".to_string(),
+        payload: Some(chat_payload(&state, &ctx, &req, system_directive)),
+    };
     
     let stream = stream! {
+        if let Some(reply) = intent_reply(&state, &ctx, &req.prompt).await {
+            save_to_memory(&state, &ctx, &session_id, &req.prompt, &reply.text);
+            yield reply.text;
+            return;
+        }
         let mut accumulated_response = String::new();
         
-        if is_live {
-            // Live streaming from OpenRouter — [system (Mission Directive), user]
-            match state.model_router.stream_generate(
-                Some(&system_directive),
-                &req.prompt,
-                model.as_deref(),
-                temperature,
-                max_tokens,
-            ).await {
-                Ok(mut rx) => {
-                    while let Some(chunk) = rx.recv().await {
-                        accumulated_response.push_str(&chunk);
-                        yield chunk;
+        match state.orchestrator.dispatch_stream(&ctx, goal).await {
+            Ok(mut rx) => {
+                while let Some(chunk) = rx.recv().await {
+                    if chunk.get("cancelled").and_then(|v| v.as_bool()) == Some(true) {
+                        break;
+                    }
+                    if let Some(delta) = chunk.get("delta").and_then(|v| v.as_str()) {
+                        accumulated_response.push_str(delta);
+                        yield delta.to_string();
+                    } else if accumulated_response.is_empty() {
+                        // Non-streaming final chunk (e.g. skills disabled): emit it whole.
+                        let text = chunk
+                            .get("generated")
+                            .or_else(|| chunk.get("message"))
+                            .and_then(|v| v.as_str())
+                            .map(|s| s.to_string())
+                            .unwrap_or_else(|| chunk.to_string());
+                        accumulated_response.push_str(&text);
+                        yield text;
                     }
-                }
-                Err(e) => {
-                    tracing::error!(
-                        target: "pagi::chat",
-                        "[Chat] Stream generation error: {}",
-                        e
-                    );
-                    yield format!("[Error: {}]", e);
                 }
             }
-        } else {
-            // Mock streaming - word by word with delays
-            let mut rx = state.model_router.mock_stream_generate(&req.prompt);
-            while let Some(chunk) = rx.recv().await {
-                accumulated_response.push_str(&chunk);
-                yield chunk;
+            Err(e) => {
+                let user_error = report_chat_error(&knowledge, &ctx, e.as_ref());
+                yield user_error.display_message();
             }
         }
         
-        // Save completed response to KB-4 (Memory) - use original user prompt for history
-        let user_prompt = req.prompt.clone();
+        // Record the completed turn in KB-4 (Chronos) - use original user prompt for history
         if !accumulated_response.is_empty() {
-            save_to_memory(&knowledge, &user_prompt, &accumulated_response);
+            save_to_memory(&state, &ctx, &session_id, &req.prompt, &accumulated_response);
             tracing::info!(
                 target: "pagi::chat",
                 "[Chat] Streaming complete. Saved {} chars to KB-4 (Memory)",
@@ -1303,44 +2745,380 @@ async fn chat_streaming(
         .header("Content-Type", "text/plain; charset=utf-8")
         .header("Cache-Control", "no-cache")
         .header("Connection", "keep-alive")
+        .header("X-Correlation-Id", correlation_id)
+        .header("X-Session-Id", session_id_header)
         .body(body)
         .unwrap()
 }
 
-/// Saves a conversation exchange to KB-4 (Memory) for context recall
-fn save_to_memory(knowledge: &Arc<KnowledgeStore>, prompt: &str, response: &str) {
-    let memory_slot = KbType::Chronos.slot_id();
-    let conversation_id = uuid::Uuid::new_v4().to_string();
-    
-    let record = KbRecord::with_metadata(
-        format!("User: {}\n\nAssistant: {}", prompt, response),
-        serde_json::json!({
-            "type": "conversation",
-            "prompt_len": prompt.len(),
-            "response_len": response.len(),
-            "timestamp": std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .map(|d| d.as_millis() as i64)
-                .unwrap_or(0),
-        }),
-    );
-    
-    if let Err(e) = knowledge.insert_record(memory_slot, &conversation_id, &record) {
-        tracing::warn!(
-            target: "pagi::chat",
-            "[Chat] Failed to save conversation to KB-4: {}",
-            e
-        );
+/// POST /api/v1/chat/stream – streaming chat as typed Server-Sent Events (the raw-text
+/// `stream: true` variant of `/api/v1/chat` is unchanged). Events, each with a JSON `data` object:
+/// - `intent` `{ intent, confidence, action, source, result? }` – the message ran (`action: run`)
+///   or offers (`action: confirm`) a blueprint intent instead of going to the model; the reply
+///   text follows as one `delta`
+/// - `sources` `{ slots, reranker, candidates, sources: [{ n, slot_id, key, score, rerank_score?,
+///   snippet, title?, link? }] }` – RAG mode (`rag: true`): the records put into the prompt and
+///   the retrieval trace, before the first `delta`
+/// - `delta` `{ text }` – generated tokens
+/// - `usage` `{ prompt_tokens, completion_tokens, total_tokens }` – when the provider reports it
+/// - `error` `{ error, error_code, retryable, correlation_id }` – dispatch failed, was cancelled
+///   or ran past the `X-Request-Timeout-Ms` budget (`error_code: deadline_exceeded`)
+/// - `done` `{ status, model, mode, latency_ms, correlation_id, session_id, chars }` – always the
+///   last event; `status` is `ok`, `error`, `cancelled` or `timeout`
+async fn chat_events(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    caller: Option<Extension<Caller>>,
+    Json(mut req): Json<ChatRequest>,
+) -> Response {
+    if let Err(denied) = chat_as_caller(&mut req, caller.as_ref()) {
+        return denied.into_response();
     }
+    let deadline_ms = match request_deadline_ms(&headers) {
+        Ok(deadline_ms) => deadline_ms,
+        Err(bad) => return bad.into_response(),
+    };
+    let correlation_id = uuid::Uuid::new_v4().to_string();
+    let events = chat_event_stream(state, req, correlation_id, deadline_ms);
+    Sse::new(events.map(|(kind, data)| Ok::<_, std::convert::Infallible>(chat_event(kind, data)))).into_response()
 }
 
-const KB_SLOT_INTERNAL_RESEARCH: u8 = 8;
+/// Typed chat events for one request, shared by `/api/v1/chat/stream` (SSE) and `/api/v1/ws`:
+/// `(kind, data)` pairs as documented on [`chat_events`], ending with `done`. The request can be
+/// cancelled through the orchestrator under `correlation_id`, and is cut off at `deadline_ms`.
+pub(crate) fn chat_event_stream(
+    state: AppState,
+    req: ChatRequest,
+    correlation_id: String,
+    deadline_ms: Option<i64>,
+) -> impl futures_util::Stream<Item = (&'static str, serde_json::Value)> + Send + 'static {
+    use async_stream::stream;
 
-/// Query params for GET /api/v1/kardia/:user_id
-#[derive(serde::Deserialize)]
-struct KardiaQuery {
-    #[serde(default)]
-    agent_id: Option<String>,
+    let started = std::time::Instant::now();
+    let user_id = req.user_alias.as_deref().unwrap_or("studio-user");
+    let agent_id = req.agent_id.as_deref().filter(|s| !s.is_empty()).unwrap_or(pagi_core::DEFAULT_AGENT_ID);
+    let session = chat_session_id(&req);
+    let ctx = TenantContext {
+        tenant_id: user_id.to_string(),
+        correlation_id: Some(correlation_id.clone()),
+        agent_id: Some(agent_id.to_string()),
+        deadline_ms,
+    };
+
+    stream! {
+        let session_id = match session {
+            Ok(session_id) => session_id,
+            Err(error) => {
+                yield ("error", serde_json::json!({
+                    "error": error,
+                    "error_code": "invalid_session_id",
+                    "retryable": false,
+                    "correlation_id": correlation_id,
+                }));
+                yield ("done", serde_json::json!({
+                    "status": "error",
+                    "model": serde_json::Value::Null,
+                    "mode": serde_json::Value::Null,
+                    "latency_ms": started.elapsed().as_millis() as u64,
+                    "correlation_id": correlation_id,
+                    "session_id": serde_json::Value::Null,
+                    "chars": 0,
+                }));
+                return;
+            }
+        };
+        if let Some(reply) = intent_reply(&state, &ctx, &req.prompt).await {
+            save_to_memory(&state, &ctx, &session_id, &req.prompt, &reply.text);
+            yield ("intent", reply.intent);
+            yield ("delta", serde_json::json!({ "text": reply.text }));
+            yield ("done", serde_json::json!({
+                "status": reply.status,
+                "model": serde_json::Value::Null,
+                "mode": "intent",
+                "latency_ms": started.elapsed().as_millis() as u64,
+                "correlation_id": correlation_id,
+                "session_id": session_id,
+                "chars": reply.text.chars().count(),
+            }));
+            return;
+        }
+        let mut system_directive = chat_system_prompt(&state, &ctx, &session_id, &req.prompt);
+        if let Some(rag) = chat_rag(&state, &ctx, &req, &mut system_directive).await {
+            let mut event = rag.trace();
+            event["sources"] = serde_json::json!(rag.sources);
+            yield ("sources", event);
+        }
+        let goal = Goal::ExecuteSkill {
+            name: "ModelRouter".to_string(),
+            payload: Some(chat_payload(&state, &ctx, &req, system_directive)),
+        };
+        let mut generated = String::new();
+        let mut status = "ok";
+        let mut final_chunk = serde_json::Value::Null;
+
+        match state.orchestrator.dispatch_stream(&ctx, goal).await {
+            Ok(mut rx) => {
+                while let Some(chunk) = rx.recv().await {
+                    match chunk.get("status").and_then(|v| v.as_str()) {
+                        Some("cancelled") => {
+                            status = "cancelled";
+                            yield ("error", serde_json::json!({
+                                "error": "The request was cancelled.",
+                                "error_code": "cancelled",
+                                "retryable": true,
+                                "correlation_id": correlation_id,
+                            }));
+                            break;
+                        }
+                        Some("deadline_exceeded") => {
+                            status = "timeout";
+                            yield ("error", serde_json::json!({
+                                "error": "The request ran out of time.",
+                                "error_code": "deadline_exceeded",
+                                "retryable": true,
+                                "correlation_id": correlation_id,
+                            }));
+                            break;
+                        }
+                        _ => {}
+                    }
+                    if let Some(delta) = chunk.get("delta").and_then(|v| v.as_str()) {
+                        generated.push_str(delta);
+                        yield ("delta", serde_json::json!({ "text": delta }));
+                        continue;
+                    }
+                    // Final chunk. Non-streaming skills (e.g. skills disabled) only send this one.
+                    if generated.is_empty() {
+                        let text = chunk
+                            .get("generated")
+                            .or_else(|| chunk.get("message"))
+                            .and_then(|v| v.as_str())
+                            .map(|s| s.to_string())
+                            .unwrap_or_else(|| chunk.to_string());
+                        generated.push_str(&text);
+                        yield ("delta", serde_json::json!({ "text": text }));
+                    }
+                    if let Some(usage) = chunk.get("token_usage") {
+                        yield ("usage", usage.clone());
+                    }
+                    final_chunk = chunk;
+                }
+            }
+            Err(e) => {
+                let user_error = report_chat_error(&state.knowledge, &ctx, e.as_ref());
+                status = if user_error.code == "deadline_exceeded" { "timeout" } else { "error" };
+                yield ("error", serde_json::json!({
+                    "error": user_error.message,
+                    "error_code": user_error.code,
+                    "retryable": user_error.retryable,
+                    "correlation_id": user_error.correlation_id,
+                }));
+            }
+        }
+
+        if status == "ok" && !generated.is_empty() {
+            save_to_memory(&state, &ctx, &session_id, &req.prompt, &generated);
+        }
+        let model = final_chunk.get("model").cloned().or_else(|| req.model.clone().map(serde_json::Value::from));
+        let mut done = serde_json::json!({
+            "status": status,
+            "model": model,
+            "mode": final_chunk.get("mode"),
+            "latency_ms": started.elapsed().as_millis() as u64,
+            "correlation_id": correlation_id,
+            "session_id": session_id,
+            "chars": generated.chars().count(),
+        });
+        if let Some(adjustment) = final_chunk.get("generation_adjustment") {
+            done["generation_adjustment"] = adjustment.clone();
+        }
+        yield ("done", done);
+    }
+}
+
+fn chat_event(kind: &str, data: serde_json::Value) -> Event {
+    Event::default().event(kind).data(data.to_string())
+}
+
+/// Logs a chat dispatch failure in full (tracing + Chronos) and returns the friendly
+/// translation shown to the user, tagged with the request's correlation id.
+fn report_chat_error(
+    knowledge: &Arc<KnowledgeStore>,
+    ctx: &TenantContext,
+    err: &(dyn std::error::Error + Send + Sync + 'static),
+) -> UserFacingError {
+    let correlation_id = ctx
+        .correlation_id
+        .clone()
+        .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+    let user_error = translate_error(err).with_correlation_id(correlation_id.clone());
+    tracing::error!(
+        target: "pagi::chat",
+        correlation_id = %correlation_id,
+        error_code = user_error.code,
+        error = %err,
+        "[Chat] Dispatch failed"
+    );
+    let event = knowledge.event("Chronos", format!("Chat failed [{}]: {}", correlation_id, err))
+        .with_skill("chat")
+        .with_outcome(format!("error:{}", user_error.code));
+    if let Err(e) = knowledge.append_chronos_event(ctx.resolved_agent_id(), &event) {
+        tracing::warn!(target: "pagi::chat", error = %e, "Failed to record chat error in Chronos");
+    }
+    user_error
+}
+
+/// Chat reply for a message routed to a blueprint intent (`Orchestrator::route_chat`).
+struct IntentReply {
+    /// "ok" or "error".
+    status: &'static str,
+    text: String,
+    /// The match (`intent`, `confidence`, `action`, `source`), plus `result` when the plan ran.
+    intent: serde_json::Value,
+}
+
+/// Runs or offers a blueprint intent when the intent router maps `prompt` to one; None means
+/// plain chat. A `run` dispatches `AutonomousGoal` through `run_goal` with the chat message as
+/// context; a `confirm` asks the user, and their "yes" arrives here as the `run`.
+async fn intent_reply(state: &AppState, ctx: &TenantContext, prompt: &str) -> Option<IntentReply> {
+    let found = state.orchestrator.route_chat(ctx, prompt).await?;
+    let mut intent = serde_json::to_value(&found).unwrap_or_default();
+    if found.action == IntentAction::Confirm {
+        return Some(IntentReply {
+            status: "ok",
+            text: found.confirmation_prompt(),
+            intent,
+        });
+    }
+    let goal = Goal::AutonomousGoal {
+        intent: found.intent.clone(),
+        context: Some(serde_json::json!({ "prompt": found.message, "source": "chat" })),
+    };
+    match run_goal(state, ctx.clone(), goal).await {
+        Ok(result) => {
+            let text = ["generated", "summary", "message"]
+                .iter()
+                .find_map(|k| result.get(*k).and_then(|v| v.as_str()))
+                .map(|s| s.to_string())
+                .unwrap_or_else(|| {
+                    let steps = result.get("plan_steps").and_then(|v| v.as_array()).map_or(0, |a| a.len());
+                    format!("Ran \"{}\" ({} steps).", found.intent, steps)
+                });
+            intent["result"] = result;
+            Some(IntentReply { status: "ok", text, intent })
+        }
+        Err(e) => {
+            let user_error = report_chat_error(&state.knowledge, ctx, e.as_ref());
+            intent["error_code"] = serde_json::json!(user_error.code);
+            Some(IntentReply {
+                status: "error",
+                text: user_error.display_message(),
+                intent,
+            })
+        }
+    }
+}
+
+/// Conversation sessions over the gateway's store, split by the control panel's memory weights.
+fn conversations(state: &AppState) -> ConversationManager {
+    ConversationManager::new(Arc::clone(&state.knowledge)).with_memory_weights(state.orchestrator.pagi_memory_weights())
+}
+
+/// The request's conversation session, or a new one when it names none.
+fn chat_session_id(req: &ChatRequest) -> Result<String, String> {
+    match req.session_id.as_deref().map(str::trim).filter(|s| !s.is_empty()) {
+        Some(id) if pagi_core::is_valid_session_id(id) => Ok(id.to_string()),
+        Some(id) => Err(format!("invalid session_id {:?}", id)),
+        None => Ok(uuid::Uuid::new_v4().simple().to_string()),
+    }
+}
+
+/// Mission Directive for the chat (the tenant is the user), followed by the session's context
+/// window for `prompt` (recent turns plus relevant older ones).
+fn chat_system_prompt(state: &AppState, ctx: &TenantContext, session_id: &str, prompt: &str) -> String {
+    let agent_id = ctx.resolved_agent_id();
+    let mut system_directive = state.knowledge.build_system_directive(agent_id, &ctx.tenant_id);
+    match conversations(state).context(&ctx.tenant_id, agent_id, session_id, prompt) {
+        Ok(context) if !context.is_empty() => {
+            system_directive.push_str("\n\n");
+            system_directive.push_str(&context.to_prompt_section());
+        }
+        Ok(_) => {}
+        Err(e) => tracing::warn!(target: "pagi::chat", session_id, error = %e, "[Chat] Failed to load conversation context"),
+    }
+    system_directive
+}
+
+/// RAG mode: retrieves the sources for the prompt and appends them to `system_directive`. None
+/// when the request is not in RAG mode; a failed retrieval is logged and answered without sources.
+async fn chat_rag(
+    state: &AppState,
+    ctx: &TenantContext,
+    req: &ChatRequest,
+    system_directive: &mut String,
+) -> Option<handlers::chat::RagContext> {
+    if !req.rag {
+        return None;
+    }
+    let slots = handlers::chat::rag_slots(&state.orchestrator, req.rag_slots.as_deref());
+    let top_k = req.rag_top_k.unwrap_or(handlers::chat::DEFAULT_RAG_TOP_K);
+    let router = ModelRouter::new();
+    let rag = match handlers::chat::retrieve(&state.knowledge, &router, &ctx.tenant_id, &req.prompt, slots, top_k, &state.config.get().rag).await {
+        Ok(rag) => rag,
+        Err(e) => {
+            tracing::warn!(target: "pagi::chat", error = %e, "[Chat] RAG retrieval failed; answering without sources");
+            return Some(Default::default());
+        }
+    };
+    tracing::info!(
+        target: "pagi::chat",
+        slots = ?rag.slots,
+        reranker = ?rag.reranker,
+        candidates = rag.candidates.len(),
+        sources = rag.sources.len(),
+        "[Chat] RAG sources retrieved"
+    );
+    system_directive.push_str("\n\n");
+    system_directive.push_str(&rag.to_prompt_section());
+    Some(rag)
+}
+
+/// ModelRouter payload for a chat; the agent's persona profile fills in the model and
+/// temperature the request leaves unset.
+fn chat_payload(state: &AppState, ctx: &TenantContext, req: &ChatRequest, system_directive: String) -> serde_json::Value {
+    let mut payload = serde_json::json!({
+        "prompt": req.prompt,
+        "system_prompt": system_directive,
+        "model": req.model,
+        "temperature": req.temperature,
+        "max_tokens": req.max_tokens,
+        "persona": req.persona,
+    });
+    if let Some(profile) = state.knowledge.get_persona_profile(ctx.resolved_agent_id()) {
+        profile.apply_generation_defaults(&mut payload);
+    }
+    payload
+}
+
+/// Records a chat exchange as the session's next turn in KB-4 (Chronos).
+fn save_to_memory(state: &AppState, ctx: &TenantContext, session_id: &str, prompt: &str, response: &str) {
+    if let Err(e) = conversations(state).record_turn(&ctx.tenant_id, ctx.resolved_agent_id(), session_id, prompt, response) {
+        tracing::warn!(
+            target: "pagi::chat",
+            session_id,
+            "[Chat] Failed to save conversation turn to KB-4: {}",
+            e
+        );
+    }
+}
+
+const KB_SLOT_INTERNAL_RESEARCH: u8 = 8;
+
+/// Query params for GET /api/v1/kardia/:user_id
+#[derive(serde::Deserialize)]
+struct KardiaQuery {
+    #[serde(default)]
+    agent_id: Option<String>,
 }
 
 /// Returns the current relation/sentiment record for a user from KB_KARDIA (for UI and verification).
@@ -1369,7 +3147,7 @@ async fn get_research_trace(
 ) -> Result<axum::Json<serde_json::Value>, axum::http::StatusCode> {
     let value = state
         .knowledge
-        .get(KB_SLOT_INTERNAL_RESEARCH, &trace_id)
+        .get_or_archived(KB_SLOT_INTERNAL_RESEARCH, &trace_id)
         .map_err(|_| axum::http::StatusCode::INTERNAL_SERVER_ERROR)?
         .and_then(|b| String::from_utf8(b).ok());
     let value = value.ok_or(axum::http::StatusCode::NOT_FOUND)?;
@@ -1381,20 +3159,20 @@ async fn get_research_trace(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use pagi_core::PolicyRecord;
+    use pagi_core::{KbRecord, PolicyRecord};
+    use pagi_skills::{
+        AnalyzeSentiment, CommunityPulse, KnowledgeInsert, KnowledgePruner, KnowledgeQuery, LeadCapture, LlmMode,
+        RecallPastActions, ResearchAudit, WriteSandboxFile,
+    };
     use axum::body::Body;
     use axum::http::{Request, StatusCode};
     use tower::ServiceExt;
 
-    fn test_log_tx() -> broadcast::Sender<String> {
+    fn test_log_tx() -> broadcast::Sender<logging::LogEvent> {
         let (tx, _) = broadcast::channel(1);
         tx
     }
 
-    fn test_model_router() -> Arc<ModelRouter> {
-        Arc::new(ModelRouter::new())
-    }
-
     fn test_shadow_store() -> ShadowStoreHandle {
         Arc::new(tokio::sync::RwLock::new(None))
     }
@@ -1407,6 +3185,28 @@ mod tests {
             llm_mode: "mock".to_string(),
             frontend_enabled: false,
             slot_labels: std::collections::HashMap::new(),
+            storage_backend: Default::default(),
+            llm_providers: Default::default(),
+            model_aliases: Default::default(),
+            token_budgets: Default::default(),
+            redaction: Default::default(),
+            oidc: Default::default(),
+            limits: Default::default(),
+            encryption: Default::default(),
+            secrets: Default::default(),
+            scraper: Default::default(),
+            rag: Default::default(),
+            ingest: Default::default(),
+            email: Default::default(),
+            chat: Default::default(),
+            channels: Default::default(),
+            calendar: Default::default(),
+            tick_rate_secs: None,
+            bind_addresses: Vec::new(),
+            tls: Default::default(),
+            logging: Default::default(),
+            simulation: Default::default(),
+            webhooks: Default::default(),
         }
     }
 
@@ -1424,7 +3224,39 @@ mod tests {
             ]
             .into_iter()
             .collect(),
+            storage_backend: Default::default(),
+            llm_providers: [(
+                "local".to_string(),
+                pagi_core::LlmProviderConfig {
+                    kind: pagi_core::LlmProviderKind::Ollama,
+                    ..Default::default()
+                },
+            )]
+            .into_iter()
+            .collect(),
+            model_aliases: Default::default(),
+            token_budgets: Default::default(),
+            redaction: Default::default(),
+            oidc: Default::default(),
+            limits: Default::default(),
+            encryption: Default::default(),
+            secrets: Default::default(),
+            scraper: Default::default(),
+            rag: Default::default(),
+            ingest: Default::default(),
+            email: Default::default(),
+            chat: Default::default(),
+            channels: Default::default(),
+            calendar: Default::default(),
+            tick_rate_secs: None,
+            bind_addresses: Vec::new(),
+            tls: Default::default(),
+            logging: Default::default(),
+            simulation: Default::default(),
+            webhooks: Default::default(),
         };
+        let llm_providers = Arc::new(LlmProviders::from_config(&config));
+        llm_providers.record_failure("local", "local request failed: connection refused");
         let knowledge = Arc::new(
             KnowledgeStore::open_path("./data/pagi_knowledge_status_test").unwrap(),
         );
@@ -1434,12 +3266,13 @@ mod tests {
         let app = Router::new()
             .route("/v1/status", get(status))
             .with_state(AppState {
-                config: Arc::new(config),
+                config: LiveConfig::new(config),
                 orchestrator,
                 knowledge,
                 log_tx: test_log_tx(),
-                model_router: test_model_router(),
                 shadow_store: test_shadow_store(),
+                llm_providers,
+                oidc: None,
             });
         let req = Request::builder()
             .method("GET")
@@ -1455,6 +3288,207 @@ mod tests {
         assert_eq!(json["llm_mode"], "mock");
         assert_eq!(json["slot_labels"]["1"], "Legal Compliance");
         assert_eq!(json["slot_labels"]["2"], "Marketing Tone");
+        let local = &json["llm_providers"][0];
+        assert_eq!((local["provider"].as_str(), local["kind"].as_str()), (Some("local"), Some("ollama")));
+        assert_eq!((local["status"].as_str(), local["failures"].as_u64()), (Some("up"), Some(1)));
+    }
+
+    #[tokio::test]
+    async fn test_usage_reports_month_against_budget() {
+        let knowledge = Arc::new(KnowledgeStore::open_temporary().unwrap());
+        knowledge.record_token_usage("acme", "sales", 600, 200, false).unwrap();
+        knowledge.record_token_usage("acme", "support", 100, 100, true).unwrap();
+        let config = CoreConfig {
+            token_budgets: pagi_core::TokenBudgetConfig {
+                monthly_tokens: None,
+                tenants: [("acme".to_string(), 1_000)].into_iter().collect(),
+            },
+            ..test_config()
+        };
+        let app = Router::new()
+            .route("/api/v1/usage/:tenant_id", get(handlers::usage::get_usage))
+            .with_state(AppState {
+                config: LiveConfig::new(config),
+                orchestrator: Arc::new(Orchestrator::new(Arc::new(SkillRegistry::new()))),
+                knowledge,
+                log_tx: test_log_tx(),
+                shadow_store: test_shadow_store(),
+                llm_providers: Default::default(),
+                oidc: None,
+            });
+        let get_json = |uri: &'static str| {
+            let app = app.clone();
+            async move {
+                let res = app.oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap()).await.unwrap();
+                let status = res.status();
+                let bytes = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+                (status, serde_json::from_slice::<serde_json::Value>(&bytes).unwrap())
+            }
+        };
+
+        let (status, json) = get_json("/api/v1/usage/acme").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(json["usage"]["total_tokens"], 1_000);
+        assert_eq!(json["usage"]["estimated_tokens"], 200);
+        assert_eq!(json["usage"]["by_agent"]["sales"]["total_tokens"], 800);
+        assert_eq!((json["budget_tokens"].as_u64(), json["remaining_tokens"].as_u64()), (Some(1_000), Some(0)));
+        assert_eq!(json["exceeded"], true);
+        assert_eq!(json["history"].as_array().unwrap().len(), 1);
+
+        // Unmetered tenant without usage: zero counts, no budget.
+        let (_, json) = get_json("/api/v1/usage/globex").await;
+        assert_eq!(json["usage"]["total_tokens"], 0);
+        assert!(json["budget_tokens"].is_null());
+        assert_eq!(json["exceeded"], false);
+
+        let (status, _) = get_json("/api/v1/usage/bad%20id").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_prompt_routes_save_versions_and_fall_back_to_builtins() {
+        let knowledge = Arc::new(KnowledgeStore::open_temporary().unwrap());
+        let app = Router::new()
+            .route("/api/v1/prompts", get(handlers::prompts::list_prompts))
+            .route(
+                "/api/v1/prompts/:name",
+                get(handlers::prompts::get_prompt).put(handlers::prompts::put_prompt),
+            )
+            .with_state(AppState {
+                config: LiveConfig::new(test_config()),
+                orchestrator: Arc::new(Orchestrator::new(Arc::new(SkillRegistry::new()))),
+                knowledge: Arc::clone(&knowledge),
+                log_tx: test_log_tx(),
+                shadow_store: test_shadow_store(),
+                llm_providers: Default::default(),
+                oidc: None,
+            });
+        let call = |method: &str, uri: &str, body: Option<serde_json::Value>| {
+            let app = app.clone();
+            let request = Request::builder()
+                .method(method)
+                .uri(uri)
+                .header("content-type", "application/json")
+                .body(body.map_or_else(Body::empty, |b| Body::from(b.to_string())))
+                .unwrap();
+            async move {
+                let res = app.oneshot(request).await.unwrap();
+                let status = res.status();
+                let bytes = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+                (status, serde_json::from_slice::<serde_json::Value>(&bytes).unwrap())
+            }
+        };
+        let uri = "/api/v1/prompts/heartbeat_background_task";
+
+        let (status, json) = call("GET", uri, None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(json["prompt"]["builtin"], true);
+        assert_eq!(json["prompt"]["variables"], serde_json::json!(["agent_id", "task"]));
+
+        let (status, _) = call("PUT", uri, Some(serde_json::json!({ "template": "{{task}} {{mood}}" }))).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        for template in ["Agent {{agent_id}}: {{task}}", "Status of {{ task }}?"] {
+            let (status, _) = call("PUT", uri, Some(serde_json::json!({ "template": template }))).await;
+            assert_eq!(status, StatusCode::OK);
+        }
+        let (_, json) = call("GET", uri, None).await;
+        assert_eq!(json["prompt"]["version"], 2);
+        assert_eq!(json["versions"].as_array().unwrap().len(), 2);
+        let (_, json) = call("GET", &format!("{}?version=1", uri), None).await;
+        assert_eq!(json["prompt"]["template"], "Agent {{agent_id}}: {{task}}");
+        let (status, _) = call("GET", &format!("{}?version=9", uri), None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(
+            pagi_core::render_prompt(Some(&knowledge), pagi_core::HEARTBEAT_BACKGROUND_TASK_PROMPT, &[("agent_id", "a"), ("task", "t")])
+                .unwrap(),
+            "Status of t?"
+        );
+
+        let (_, json) = call("GET", "/api/v1/prompts", None).await;
+        assert!(json["prompts"].as_array().unwrap().iter().any(|p| p["name"] == "system_persona"));
+        let (status, _) = call("GET", "/api/v1/prompts/missing", None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_eval_runs_are_scored_and_listed_per_suite() {
+        let knowledge = Arc::new(KnowledgeStore::open_temporary().unwrap());
+        let mut registry = SkillRegistry::new();
+        registry.register(Arc::new(ModelRouter::new()));
+        let app = Router::new()
+            .route("/api/v1/evals", get(handlers::evals::list_evals))
+            .route("/api/v1/evals/run", post(handlers::evals::run_eval))
+            .route("/api/v1/evals/:suite/runs", get(handlers::evals::list_runs))
+            .route("/api/v1/evals/:suite/runs/:run_id", get(handlers::evals::get_run))
+            .with_state(AppState {
+                config: LiveConfig::new(test_config()),
+                orchestrator: Arc::new(Orchestrator::new(Arc::new(registry)).with_knowledge(Arc::clone(&knowledge))),
+                knowledge,
+                log_tx: test_log_tx(),
+                shadow_store: test_shadow_store(),
+                llm_providers: Default::default(),
+                oidc: None,
+            });
+        let call = |method: &str, uri: &str, body: Option<serde_json::Value>| {
+            let app = app.clone();
+            let request = Request::builder()
+                .method(method)
+                .uri(uri)
+                .header("content-type", "application/json")
+                .body(body.map_or_else(Body::empty, |b| Body::from(b.to_string())))
+                .unwrap();
+            async move {
+                let res = app.oneshot(request).await.unwrap();
+                let status = res.status();
+                let bytes = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+                (status, serde_json::from_slice::<serde_json::Value>(&bytes).unwrap())
+            }
+        };
+        let suite = serde_json::json!({
+            "name": "greeting",
+            "cases": [{
+                "name": "says something",
+                "goal": { "ExecuteSkill": { "name": "ModelRouter", "payload": { "prompt": "Say hello" } } },
+                "assertions": [
+                    { "type": "regex", "path": "$.generated", "pattern": "\\S" },
+                    { "type": "equals", "path": "$.missing", "value": 1 }
+                ]
+            }]
+        });
+
+        let (status, json) = call("POST", "/api/v1/evals/run", Some(serde_json::json!({ "suite": suite, "label": "v1" }))).await;
+        assert_eq!(status, StatusCode::OK, "{}", json);
+        assert_eq!(json["run"]["score"], 0.5);
+        assert_eq!((json["run"]["passed"].as_u64(), json["run"]["total"].as_u64()), (Some(0), Some(1)));
+        assert!(json["delta"].is_null());
+        let first_run = json["run"]["run_id"].as_str().unwrap().to_string();
+
+        let (_, json) = call("POST", "/api/v1/evals/run", Some(serde_json::json!({ "suite": suite }))).await;
+        assert_eq!(json["previous"]["run_id"], first_run.as_str());
+        assert_eq!(json["delta"], 0.0);
+
+        let (_, json) = call("GET", "/api/v1/evals/greeting/runs", None).await;
+        assert_eq!(json["count"], 2);
+        assert_eq!(json["runs"][1]["label"], "v1");
+        let (_, json) = call("GET", "/api/v1/evals", None).await;
+        assert_eq!(json["suites"][0]["suite"], "greeting");
+        let (status, json) = call("GET", &format!("/api/v1/evals/greeting/runs/{}", first_run), None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(json["run"]["cases"][0]["assertions"][1]["passed"], false);
+        let (status, _) = call("GET", "/api/v1/evals/greeting/runs/missing", None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        let (status, _) = call("POST", "/api/v1/evals/run", Some(serde_json::json!({ "suite": "no_such_suite" }))).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        let (status, json) = call("POST", "/api/v1/evals/run", Some(serde_json::json!({ "suite": { "name": "empty", "cases": [] } }))).await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY, "{}", json);
+
+        // The shipped suites parse and validate.
+        let dir = StdPath::new(env!("CARGO_MANIFEST_DIR")).join("../../config/evals");
+        for entry in std::fs::read_dir(dir).unwrap() {
+            let text = std::fs::read_to_string(entry.unwrap().path()).unwrap();
+            serde_json::from_str::<pagi_core::EvalSuite>(&text).unwrap().validate().unwrap();
+        }
     }
 
     #[tokio::test]
@@ -1469,12 +3503,13 @@ mod tests {
         let app = Router::new()
             .route("/v1/execute", post(execute))
             .with_state(AppState {
-                config: Arc::new(test_config()),
+                config: LiveConfig::new(test_config()),
                 orchestrator,
                 knowledge,
                 log_tx: test_log_tx(),
-                model_router: test_model_router(),
                 shadow_store: test_shadow_store(),
+                llm_providers: Default::default(),
+                oidc: None,
             });
 
         let body = serde_json::json!({
@@ -1503,969 +3538,3930 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn test_frontend_index_served_when_enabled() {
-        let knowledge = Arc::new(
-            KnowledgeStore::open_path("./data/pagi_frontend_index_test").unwrap(),
-        );
-        let orchestrator = Arc::new(Orchestrator::new(Arc::new(SkillRegistry::new())));
-
-        let config = CoreConfig {
-            app_name: "Test UI".to_string(),
-            port: 0,
-            storage_path: "./data".to_string(),
-            llm_mode: "mock".to_string(),
-            frontend_enabled: true,
-            slot_labels: std::collections::HashMap::new(),
+    async fn test_execute_memory_op_lists_and_updates_captured_leads() {
+        let memory = Arc::new(MemoryManager::open_temporary().unwrap());
+        let mut registry = SkillRegistry::new();
+        registry.register(Arc::new(LeadCapture::new(Arc::clone(&memory))));
+        let app = Router::new()
+            .route("/v1/execute", post(execute))
+            .with_state(AppState {
+                config: LiveConfig::new(test_config()),
+                orchestrator: Arc::new(Orchestrator::new(Arc::new(registry)).with_memory(Arc::clone(&memory))),
+                knowledge: Arc::new(KnowledgeStore::open_temporary().unwrap()),
+                log_tx: test_log_tx(),
+                shadow_store: test_shadow_store(),
+                llm_providers: Default::default(),
+                oidc: None,
+            });
+        let execute_goal = |tenant_id: &str, goal: serde_json::Value| {
+            let body = serde_json::json!({ "tenant_id": tenant_id, "goal": goal });
+            let req = Request::builder()
+                .method("POST")
+                .uri("/v1/execute")
+                .header("content-type", "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap();
+            let app = app.clone();
+            async move {
+                let res = app.oneshot(req).await.unwrap();
+                assert_eq!(res.status(), StatusCode::OK);
+                serde_json::from_slice::<serde_json::Value>(&axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap())
+                    .unwrap()
+            }
         };
 
-        let app = build_app(AppState {
-            config: Arc::new(config),
-            orchestrator,
-            knowledge: Arc::clone(&knowledge),
-            log_tx: test_log_tx(),
-            model_router: Arc::new(ModelRouter::with_knowledge(Arc::clone(&knowledge))),
-            shadow_store: test_shadow_store(),
-        });
+        let captured = execute_goal(
+            "lead-tenant",
+            serde_json::json!({ "IngestData": { "payload": { "email": "lead@example.com", "message": "Quote please" } } }),
+        )
+        .await;
+        let lead_id = captured["lead_id"].as_str().unwrap().to_string();
 
-        let req = Request::builder()
-            .method("GET")
-            .uri("/")
-            .body(Body::empty())
-            .unwrap();
+        let updated = execute_goal(
+            "lead-tenant",
+            serde_json::json!({ "MemoryOp": { "path": format!("leads/{}/status", lead_id), "value": "contacted" } }),
+        )
+        .await;
+        assert_eq!(updated["status"], "updated");
+        assert_eq!(updated["lead"]["email"], "lead@example.com");
 
-        let res = app.oneshot(req).await.unwrap();
-        assert_eq!(res.status(), StatusCode::OK);
+        let listed = execute_goal(
+            "lead-tenant",
+            serde_json::json!({ "MemoryOp": { "path": "leads", "value": { "status": "contacted" } } }),
+        )
+        .await;
+        assert_eq!(listed["leads"][0]["id"], lead_id.as_str());
+        assert_eq!(listed["leads"][0]["status"], "contacted");
+        assert!(listed["next_cursor"].is_null());
 
-        let bytes = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
-        let body = String::from_utf8_lossy(&bytes);
-        assert!(body.contains("PAGI Gateway UI"), "Drop-In UI title should be present");
-        assert!(
-            body.contains("Drop your AI Studio") || body.contains("pagi-frontend"),
-            "Drop-In UI hint should be reachable when enabled; got body len {}",
-            body.len()
-        );
+        let other = execute_goal("other-tenant", serde_json::json!({ "MemoryOp": { "path": format!("leads/{}", lead_id), "value": null } })).await;
+        assert_eq!(other["status"], "not_found");
     }
 
     #[tokio::test]
-    async fn test_kb1_brand_voice_retrieve() {
-        let knowledge = Arc::new(
-            KnowledgeStore::open_path("./data/pagi_knowledge_test")
-                .unwrap(),
-        );
-        knowledge
-            .insert(1, "brand_voice", b"Friendly and professional")
-            .unwrap();
-
-        let mut registry = SkillRegistry::new();
-        registry.register(Arc::new(KnowledgeQuery::new(Arc::clone(&knowledge))));
-        let orchestrator = Arc::new(Orchestrator::new(Arc::new(registry)));
+    async fn test_lead_status_endpoint_enforces_the_pipeline_and_logs_to_chronos() {
+        let memory = Arc::new(MemoryManager::open_temporary().unwrap());
+        let knowledge = Arc::new(KnowledgeStore::open_temporary().unwrap());
+        let acme = TenantContext::new("acme");
+        let lead = memory.put_lead(&acme, pagi_core::LeadRecord::default()).unwrap();
         let app = Router::new()
-            .route("/v1/execute", post(execute))
+            .route("/api/v1/leads/:id/status", post(handlers::leads::update_lead_status))
             .with_state(AppState {
-            config: Arc::new(test_config()),
-            orchestrator,
-            knowledge,
-            log_tx: test_log_tx(),
-            model_router: test_model_router(),
-            shadow_store: test_shadow_store(),
-        });
-
-        let body = serde_json::json!({
-            "tenant_id": "test-tenant",
-            "goal": {
-                "QueryKnowledge": { "slot_id": 1, "query": "brand_voice" }
+                config: LiveConfig::new(test_config()),
+                orchestrator: Arc::new(
+                    Orchestrator::new(Arc::new(SkillRegistry::new()))
+                        .with_knowledge(Arc::clone(&knowledge))
+                        .with_memory(Arc::clone(&memory)),
+                ),
+                knowledge: Arc::clone(&knowledge),
+                log_tx: test_log_tx(),
+                shadow_store: test_shadow_store(),
+                llm_providers: Default::default(),
+                oidc: None,
+            });
+        let move_lead = |tenant_id: &str, lead_id: &str, status: &str| {
+            let req = Request::builder()
+                .method("POST")
+                .uri(format!("/api/v1/leads/{}/status?tenant_id={}", lead_id, tenant_id))
+                .header("content-type", "application/json")
+                .body(Body::from(serde_json::json!({ "status": status }).to_string()))
+                .unwrap();
+            let app = app.clone();
+            async move {
+                let res = app.oneshot(req).await.unwrap();
+                let status = res.status();
+                let body = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+                (status, serde_json::from_slice::<serde_json::Value>(&body).unwrap_or_default())
             }
-        });
-        let req = Request::builder()
-            .method("POST")
-            .uri("/v1/execute")
-            .header("content-type", "application/json")
-            .body(Body::from(serde_json::to_string(&body).unwrap()))
-            .unwrap();
-        let res = app.oneshot(req).await.unwrap();
-        assert_eq!(res.status(), StatusCode::OK);
-        let bytes = axum::body::to_bytes(res.into_body(), usize::MAX)
-            .await
-            .unwrap();
-        let json: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
-        assert_eq!(json["status"], "ok");
-        assert_eq!(json["skill"], "KnowledgeQuery");
-        assert_eq!(json["slot_id"], 1);
-        assert_eq!(json["query_key"], "brand_voice");
-        assert_eq!(json["value"], "Friendly and professional");
+        };
+
+        let (status, body) = move_lead("acme", &lead.id, "contacted").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!((body["from"].as_str(), body["to"].as_str()), (Some("new"), Some("contacted")));
+        assert_eq!(move_lead("acme", &lead.id, "closed").await.0, StatusCode::OK);
+        let (status, body) = move_lead("acme", &lead.id, "qualified").await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert_eq!(body["status"], "invalid_transition");
+        assert_eq!(move_lead("beta", &lead.id, "contacted").await.0, StatusCode::NOT_FOUND);
+        assert_eq!(move_lead("acme", &lead.id, "archived").await.0, StatusCode::UNPROCESSABLE_ENTITY);
+
+        let events = knowledge.get_recent_chronos_events("default", 20).unwrap();
+        let moved = format!("Lead {} moved from contacted to closed", lead.id);
+        assert!(events.iter().any(|e| e.reflection == moved && e.outcome.as_deref() == Some("updated")));
     }
 
     #[tokio::test]
-    async fn test_chronos_episodic_memory_and_recall_past_actions() {
-        let knowledge = Arc::new(
-            KnowledgeStore::open_path("./data/pagi_chronos_recall_test").unwrap(),
-        );
-        knowledge.insert(1, "test_key", b"test_value").unwrap();
+    async fn test_execute_idempotency_key_replays_instead_of_dispatching_again() {
+        let memory = Arc::new(MemoryManager::new().unwrap());
+        let knowledge = Arc::new(KnowledgeStore::open_temporary().unwrap());
         let mut registry = SkillRegistry::new();
-        registry.register(Arc::new(KnowledgeQuery::new(Arc::clone(&knowledge))));
-        registry.register(Arc::new(RecallPastActions::new(Arc::clone(&knowledge))));
-        let orchestrator = Arc::new(Orchestrator::new(Arc::new(registry)));
+        registry.register(Arc::new(LeadCapture::new(Arc::clone(&memory))));
         let app = Router::new()
             .route("/v1/execute", post(execute))
             .with_state(AppState {
-                config: Arc::new(test_config()),
-                orchestrator,
+                config: LiveConfig::new(test_config()),
+                orchestrator: Arc::new(Orchestrator::new(Arc::new(registry))),
                 knowledge: Arc::clone(&knowledge),
                 log_tx: test_log_tx(),
-                model_router: test_model_router(),
                 shadow_store: test_shadow_store(),
+                llm_providers: Default::default(),
+                oidc: None,
             });
+        let send = |key: Option<&str>, body: serde_json::Value| {
+            let mut req = Request::builder()
+                .method("POST")
+                .uri("/v1/execute")
+                .header("content-type", "application/json");
+            if let Some(key) = key {
+                req = req.header("Idempotency-Key", key);
+            }
+            app.clone().oneshot(req.body(Body::from(body.to_string())).unwrap())
+        };
+        let lead = |email: &str| {
+            serde_json::json!({
+                "tenant_id": "idem-tenant",
+                "goal": { "IngestData": { "payload": { "email": email, "message": "Customer inquiry" } } }
+            })
+        };
+        let json = |res: Response| async move {
+            serde_json::from_slice::<serde_json::Value>(&axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap())
+                .unwrap()
+        };
 
-        let query_body = serde_json::json!({
-            "tenant_id": "test-tenant",
-            "goal": { "QueryKnowledge": { "slot_id": 1, "query": "test_key" } }
-        });
-        let query_req = Request::builder()
-            .method("POST")
-            .uri("/v1/execute")
-            .header("content-type", "application/json")
-            .body(Body::from(serde_json::to_string(&query_body).unwrap()))
-            .unwrap();
-        let query_res = app.clone().oneshot(query_req).await.unwrap();
-        assert_eq!(query_res.status(), StatusCode::OK);
+        let first = send(Some("lead-1"), lead("a@example.com")).await.unwrap();
+        assert_eq!(first.status(), StatusCode::OK);
+        assert!(first.headers().get("idempotent-replayed").is_none());
+        let first = json(first).await;
+        assert_eq!(first["status"], "saved");
+
+        // Network retry: the cached response, not a second lead.
+        let retry = send(Some("lead-1"), lead("a@example.com")).await.unwrap();
+        assert_eq!(retry.status(), StatusCode::OK);
+        assert_eq!(retry.headers()["idempotent-replayed"], "true");
+        assert_eq!(json(retry).await["lead_id"], first["lead_id"]);
+
+        // Same key, different request.
+        let reused = send(Some("lead-1"), lead("b@example.com")).await.unwrap();
+        assert_eq!(reused.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+        // The body field works like the header; no key means no caching.
+        let mut with_field = lead("c@example.com");
+        with_field["idempotency_key"] = serde_json::json!("lead-2");
+        let a = json(send(None, with_field.clone()).await.unwrap()).await;
+        let b = json(send(None, with_field).await.unwrap()).await;
+        assert_eq!(a["lead_id"], b["lead_id"]);
+        let c = json(send(None, lead("c@example.com")).await.unwrap()).await;
+        assert_ne!(c["lead_id"], a["lead_id"]);
+
+        let record = knowledge.get_idempotency_record("idem-tenant", "lead-1").unwrap();
+        assert_eq!(record.response.unwrap()["lead_id"], first["lead_id"]);
+
+        let too_long = "k".repeat(MAX_IDEMPOTENCY_KEY_LEN + 1);
+        let res = send(Some(&too_long), lead("d@example.com")).await.unwrap();
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    }
 
-        let recall_body = serde_json::json!({
-            "tenant_id": "test-tenant",
-            "goal": {
-                "ExecuteSkill": {
-                    "name": "recall_past_actions",
-                    "payload": { "limit": 5 }
-                }
+    #[tokio::test]
+    async fn test_execute_request_timeout_header_returns_504_when_exceeded() {
+        struct Slow;
+
+        #[async_trait::async_trait]
+        impl AgentSkill for Slow {
+            fn name(&self) -> &str {
+                "Slow"
             }
-        });
-        let recall_req = Request::builder()
-            .method("POST")
-            .uri("/v1/execute")
-            .header("content-type", "application/json")
-            .body(Body::from(serde_json::to_string(&recall_body).unwrap()))
-            .unwrap();
-        let recall_res = app.oneshot(recall_req).await.unwrap();
-        assert_eq!(recall_res.status(), StatusCode::OK);
-        let recall_bytes = axum::body::to_bytes(recall_res.into_body(), usize::MAX).await.unwrap();
-        let recall_json: serde_json::Value = serde_json::from_slice(&recall_bytes).unwrap();
-        assert_eq!(recall_json["status"], "ok");
-        assert_eq!(recall_json["skill"], "recall_past_actions");
-        let events = recall_json["events"].as_array().expect("events array");
-        assert!(!events.is_empty(), "Chronos should have at least one event after QueryKnowledge");
-        let has_query_event = events
-            .iter()
-            .any(|e| e["reflection"].as_str().unwrap_or("").contains("Queried"));
-        assert!(
-            has_query_event,
-            "Chronos should contain the QueryKnowledge event; got events: {:?}",
-            events
-        );
+
+            async fn execute(
+                &self,
+                _ctx: &TenantContext,
+                _payload: Option<serde_json::Value>,
+            ) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>> {
+                tokio::time::sleep(Duration::from_secs(5)).await;
+                Ok(serde_json::json!({ "status": "ok" }))
+            }
+        }
+
+        let mut registry = SkillRegistry::new();
+        registry.register(Arc::new(Slow));
+        let app = Router::new()
+            .route("/v1/execute", post(execute))
+            .with_state(AppState {
+                config: LiveConfig::new(test_config()),
+                orchestrator: Arc::new(Orchestrator::new(Arc::new(registry))),
+                knowledge: Arc::new(KnowledgeStore::open_temporary().unwrap()),
+                log_tx: test_log_tx(),
+                shadow_store: test_shadow_store(),
+                llm_providers: Default::default(),
+                oidc: None,
+            });
+        let send = |timeout: &str| {
+            let body = serde_json::json!({
+                "tenant_id": "deadline-tenant",
+                "goal": { "ExecuteSkill": { "name": "Slow", "payload": null } }
+            });
+            app.clone().oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/v1/execute")
+                    .header("content-type", "application/json")
+                    .header(REQUEST_TIMEOUT_HEADER, timeout)
+                    .body(Body::from(body.to_string()))
+                    .unwrap(),
+            )
+        };
+
+        let started = std::time::Instant::now();
+        let res = send("100").await.unwrap();
+        assert!(started.elapsed() < Duration::from_secs(2));
+        assert_eq!(res.status(), StatusCode::GATEWAY_TIMEOUT);
+        let body: serde_json::Value =
+            serde_json::from_slice(&axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap()).unwrap();
+        assert_eq!(body["status"], "timeout");
+        assert_eq!(body["error_code"], "deadline_exceeded");
+        assert_eq!(body["retryable"], true);
+        assert_eq!(body["completed_steps"], 0);
+
+        for bad in ["soon", "0", "-5"] {
+            assert_eq!(send(bad).await.unwrap().status(), StatusCode::BAD_REQUEST, "{:?}", bad);
+        }
     }
 
     #[tokio::test]
-    async fn test_ethos_blocks_write_sandbox_with_mock_secret_and_logs_violation() {
-        let knowledge = Arc::new(
-            KnowledgeStore::open_path("./data/pagi_ethos_violation_test").unwrap(),
-        );
-        knowledge.set_ethos_policy(&PolicyRecord::default()).unwrap();
+    async fn test_audit_endpoint_lists_dispatches_with_filters_and_cursor() {
+        struct Echo;
+
+        #[async_trait::async_trait]
+        impl AgentSkill for Echo {
+            fn name(&self) -> &str {
+                "Echo"
+            }
+
+            async fn execute(
+                &self,
+                _ctx: &TenantContext,
+                _payload: Option<serde_json::Value>,
+            ) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>> {
+                Ok(serde_json::json!({ "status": "ok" }))
+            }
+        }
+
         let mut registry = SkillRegistry::new();
-        registry.register(Arc::new(WriteSandboxFile::new()));
-        registry.register(Arc::new(RecallPastActions::new(Arc::clone(&knowledge))));
-        let orchestrator = Arc::new(Orchestrator::new(Arc::new(registry)));
+        registry.register(Arc::new(Echo));
+        let knowledge = Arc::new(KnowledgeStore::open_temporary().unwrap());
         let app = Router::new()
             .route("/v1/execute", post(execute))
+            .route("/api/v1/audit", get(handlers::audit::list_audit))
             .with_state(AppState {
-                config: Arc::new(test_config()),
-                orchestrator,
+                config: LiveConfig::new(test_config()),
+                orchestrator: Arc::new(Orchestrator::new(Arc::new(registry)).with_knowledge(Arc::clone(&knowledge))),
+                knowledge,
+                log_tx: test_log_tx(),
+                shadow_store: test_shadow_store(),
+                llm_providers: Default::default(),
+                oidc: None,
+            });
+        for (tenant, skill) in [("audit-a", "Echo"), ("audit-a", "Missing"), ("audit-b", "Echo"), ("audit-a", "Echo")] {
+            let body = serde_json::json!({
+                "tenant_id": tenant,
+                "goal": { "ExecuteSkill": { "name": skill, "payload": null } }
+            });
+            app.clone()
+                .oneshot(
+                    Request::builder()
+                        .method("POST")
+                        .uri("/v1/execute")
+                        .header("content-type", "application/json")
+                        .body(Body::from(body.to_string()))
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+        }
+        let get_audit = |query: String| {
+            let app = app.clone();
+            async move {
+                let res = app
+                    .oneshot(Request::builder().uri(format!("/api/v1/audit?{}", query)).body(Body::empty()).unwrap())
+                    .await
+                    .unwrap();
+                let status = res.status();
+                let body: serde_json::Value =
+                    serde_json::from_slice(&axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap()).unwrap();
+                (status, body)
+            }
+        };
+
+        let (status, body) = get_audit("tenant=audit-a&skill=Echo".to_string()).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["count"], 2);
+        assert_eq!(body["records"][0]["outcome"], "ok");
+        assert_eq!(body["records"][0]["goal_type"], "ExecuteSkill");
+
+        let (_, body) = get_audit("outcome=error".to_string()).await;
+        assert_eq!(body["count"], 1);
+        assert_eq!(body["records"][0]["skill"], "Missing");
+
+        let (_, first) = get_audit("limit=3".to_string()).await;
+        assert_eq!(first["count"], 3);
+        let cursor = first["next_cursor"].as_str().unwrap().to_string();
+        let (_, second) = get_audit(format!("limit=3&cursor={}", cursor.replace('/', "%2F"))).await;
+        assert_eq!(second["count"], 1);
+        assert!(second["next_cursor"].is_null());
+        assert_eq!(second["records"][0]["tenant_id"], "audit-a");
+
+        let (status, _) = get_audit("outcome=maybe".to_string()).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_reembed_job_runs_in_background_and_reports_progress() {
+        let knowledge = Arc::new(KnowledgeStore::open_temporary().unwrap());
+        let slot_id = KbType::Logos.slot_id();
+        for i in 0..5 {
+            let record = KbRecord::with_embedding(
+                format!("note {}", i),
+                serde_json::json!({ "embedding_model": "retired-model" }),
+                vec![0.5, 0.5],
+                knowledge.now_ms(),
+            );
+            knowledge.insert_record(slot_id, &format!("research/{}", i), &record).unwrap();
+        }
+        let app = Router::new()
+            .route(
+                "/api/v1/knowledge/reembed",
+                get(handlers::reembed::list_reembed_jobs).post(handlers::reembed::start_reembed),
+            )
+            .route("/api/v1/knowledge/reembed/:job_id", get(handlers::reembed::get_reembed_job))
+            .route("/api/v1/knowledge/reembed/:job_id/resume", post(handlers::reembed::resume_reembed_job))
+            .with_state(AppState {
+                config: LiveConfig::new(test_config()),
+                orchestrator: Arc::new(Orchestrator::new(Arc::new(SkillRegistry::new()))),
                 knowledge: Arc::clone(&knowledge),
                 log_tx: test_log_tx(),
-                model_router: test_model_router(),
                 shadow_store: test_shadow_store(),
+                llm_providers: Default::default(),
+                oidc: None,
             });
+        let call = |method: &str, uri: String, body: Option<serde_json::Value>| {
+            let app = app.clone();
+            let request = Request::builder()
+                .method(method)
+                .uri(uri)
+                .header("content-type", "application/json")
+                .body(body.map_or_else(Body::empty, |b| Body::from(b.to_string())))
+                .unwrap();
+            async move {
+                let res = app.oneshot(request).await.unwrap();
+                let status = res.status();
+                let body: serde_json::Value =
+                    serde_json::from_slice(&axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap()).unwrap();
+                (status, body)
+            }
+        };
 
-        let write_body = serde_json::json!({
-            "tenant_id": "test-tenant",
-            "goal": {
-                "ExecuteSkill": {
-                    "name": "write_sandbox_file",
-                    "payload": {
-                        "path": "ethos_test.txt",
-                        "content": "Do not store: api_key=sk-12345 and password=secret123"
-                    }
-                }
+        let (status, _) = call("POST", "/api/v1/knowledge/reembed".into(), Some(serde_json::json!({ "slots": [9] }))).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let (status, body) = call(
+            "POST",
+            "/api/v1/knowledge/reembed".into(),
+            Some(serde_json::json!({ "slots": [3], "concurrency": 2 })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::ACCEPTED);
+        assert_eq!(body["job"]["total"], 5);
+        let job_id = body["job"]["id"].as_str().unwrap().to_string();
+
+        let mut job = serde_json::Value::Null;
+        for _ in 0..100 {
+            let (_, body) = call("GET", format!("/api/v1/knowledge/reembed/{}", job_id), None).await;
+            job = body["job"].clone();
+            if job["status"] == "completed" {
+                break;
             }
-        });
-        let write_req = Request::builder()
-            .method("POST")
-            .uri("/v1/execute")
-            .header("content-type", "application/json")
-            .body(Body::from(serde_json::to_string(&write_body).unwrap()))
-            .unwrap();
-        let write_res = app.clone().oneshot(write_req).await.unwrap();
-        assert_eq!(write_res.status(), StatusCode::OK);
-        let write_bytes = axum::body::to_bytes(write_res.into_body(), usize::MAX).await.unwrap();
-        let write_json: serde_json::Value = serde_json::from_slice(&write_bytes).unwrap();
-        assert_eq!(
-            write_json["status"],
-            "policy_violation",
-            "Ethos should block write when content contains sensitive keywords; got: {:?}",
-            write_json
-        );
-        assert!(write_json["error"].as_str().unwrap_or("").contains("sensitive") || write_json["error"].as_str().unwrap_or("").contains("keyword"));
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        assert_eq!(job["status"], "completed");
+        assert_eq!(job["updated"], 5);
+        assert_eq!(job["progress"], 1.0);
+        let record = knowledge.get_record(slot_id, "research/3").unwrap().unwrap();
+        assert_eq!(record.metadata["embedding_model"], job["embedding_model"]);
+        assert_eq!(record.embedding.unwrap().len(), 64);
+
+        let (status, _) = call("POST", format!("/api/v1/knowledge/reembed/{}/resume", job_id), None).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        let (_, list) = call("GET", "/api/v1/knowledge/reembed".into(), None).await;
+        assert_eq!(list["count"], 1);
+    }
 
-        let recall_body = serde_json::json!({
-            "tenant_id": "test-tenant",
-            "goal": {
-                "ExecuteSkill": {
-                    "name": "recall_past_actions",
-                    "payload": { "limit": 5 }
-                }
+    #[tokio::test]
+    async fn test_snapshot_routes_take_list_and_roll_back_a_slot() {
+        let storage = std::env::temp_dir().join(format!("pagi_snapshot_api_test_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&storage);
+        let knowledge = Arc::new(KnowledgeStore::open_path(storage.join("pagi_knowledge")).unwrap());
+        let slot_id = KbType::Logos.slot_id();
+        knowledge.insert_record(slot_id, "fact", &KbRecord::new("good", knowledge.now_ms())).unwrap();
+        let app = Router::new()
+            .route(
+                "/api/v1/knowledge/snapshots",
+                get(handlers::snapshots::list_snapshots).post(handlers::snapshots::create_snapshot),
+            )
+            .route("/api/v1/knowledge/snapshots/:id", delete(handlers::snapshots::delete_snapshot))
+            .route("/api/v1/knowledge/snapshots/:id/restore", post(handlers::snapshots::restore_snapshot))
+            .with_state(AppState {
+                config: LiveConfig::new(CoreConfig {
+                    storage_path: storage.to_string_lossy().into_owned(),
+                    ..test_config()
+                }),
+                orchestrator: Arc::new(Orchestrator::new(Arc::new(SkillRegistry::new()))),
+                knowledge: Arc::clone(&knowledge),
+                log_tx: test_log_tx(),
+                shadow_store: test_shadow_store(),
+                llm_providers: Default::default(),
+                oidc: None,
+            });
+        let call = |method: &str, uri: String, body: Option<serde_json::Value>| {
+            let app = app.clone();
+            let request = Request::builder()
+                .method(method)
+                .uri(uri)
+                .header("content-type", "application/json")
+                .body(body.map_or_else(Body::empty, |b| Body::from(b.to_string())))
+                .unwrap();
+            async move {
+                let res = app.oneshot(request).await.unwrap();
+                let status = res.status();
+                let body: serde_json::Value =
+                    serde_json::from_slice(&axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap()).unwrap();
+                (status, body)
             }
-        });
-        let recall_req = Request::builder()
-            .method("POST")
-            .uri("/v1/execute")
-            .header("content-type", "application/json")
-            .body(Body::from(serde_json::to_string(&recall_body).unwrap()))
+        };
+
+        let (status, body) = call("POST", "/api/v1/knowledge/snapshots".into(), None).await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(body["snapshot"]["reason"], "manual");
+        let id = body["snapshot"]["id"].as_str().unwrap().to_string();
+        assert!(storage.join("snapshots").join(&id).join("manifest.json").exists());
+
+        knowledge.insert_record(slot_id, "fact", &KbRecord::new("corrupted", knowledge.now_ms())).unwrap();
+        let (status, _) = call(
+            "POST",
+            format!("/api/v1/knowledge/snapshots/{}/restore", id),
+            Some(serde_json::json!({ "slot_id": 12 })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let (status, _) = call("POST", "/api/v1/knowledge/snapshots/nope/restore".into(), None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        let (status, body) = call(
+            "POST",
+            format!("/api/v1/knowledge/snapshots/{}/restore", id),
+            Some(serde_json::json!({ "slot_id": slot_id })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["report"]["slot_id"], slot_id);
+        assert_eq!(knowledge.get_record(slot_id, "fact").unwrap().unwrap().content, "good");
+
+        let (_, list) = call("GET", "/api/v1/knowledge/snapshots".into(), None).await;
+        assert_eq!(list["count"], 2);
+        assert_eq!(list["snapshots"][0]["reason"], "pre_restore");
+        let (status, _) = call("DELETE", format!("/api/v1/knowledge/snapshots/{}", id), None).await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, _) = call("DELETE", format!("/api/v1/knowledge/snapshots/{}", id), None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        drop(app);
+        drop(knowledge);
+        let _ = std::fs::remove_dir_all(&storage);
+    }
+
+    #[tokio::test]
+    async fn test_knowledge_changes_streams_writes_for_the_slot() {
+        let knowledge = Arc::new(KnowledgeStore::open_temporary().unwrap());
+        let logos = KbType::Logos.slot_id();
+        let app = Router::new()
+            .route("/api/v1/knowledge/changes", get(handlers::changes::knowledge_changes))
+            .with_state(AppState {
+                config: LiveConfig::new(test_config()),
+                orchestrator: Arc::new(Orchestrator::new(Arc::new(SkillRegistry::new()))),
+                knowledge: Arc::clone(&knowledge),
+                log_tx: test_log_tx(),
+                shadow_store: test_shadow_store(),
+                llm_providers: Default::default(),
+                oidc: None,
+            });
+        let request = |uri: &str| Request::builder().uri(uri).body(Body::empty()).unwrap();
+
+        let res = app.clone().oneshot(request("/api/v1/knowledge/changes?slot_id=12")).await.unwrap();
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+        let res = app.oneshot(request(&format!("/api/v1/knowledge/changes?slot_id={}", logos))).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+
+        knowledge.insert_record(logos, "fact", &KbRecord::new("first", knowledge.now_ms())).unwrap();
+        knowledge.insert_record(KbType::Oikos.slot_id(), "task", &KbRecord::new("other slot", knowledge.now_ms())).unwrap();
+        knowledge.insert_record(logos, "fact", &KbRecord::new("second", knowledge.now_ms())).unwrap();
+        knowledge.remove(logos, "fact").unwrap();
+        // Dropping the last store handle closes the feed, which ends the stream.
+        drop(knowledge);
+
+        let body = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+        let events = sse_events(&body);
+        let kinds: Vec<&str> = events.iter().map(|(_, data)| data["kind"].as_str().unwrap()).collect();
+        assert_eq!(kinds, ["insert", "update", "remove"]);
+        assert!(events.iter().all(|(event, data)| event == "change" && data["slot_id"] == 3 && data["key"] == "fact"));
+        assert!(events.iter().all(|(_, data)| data.get("content").is_none()));
+    }
+
+    #[tokio::test]
+    async fn test_sandbox_workspace_api_reads_writes_and_deletes_within_the_jail() {
+        let root = std::env::temp_dir().join(format!("pagi_sandbox_api_test_{}", std::process::id()));
+        std::env::set_var("PAGI_RESEARCH_SANDBOX", &root);
+        let knowledge = Arc::new(KnowledgeStore::open_temporary().unwrap());
+        knowledge.set_ethos_policy(&PolicyRecord::default()).unwrap();
+        let app = Router::new()
+            .route("/api/v1/sandbox/files", get(handlers::sandbox::list_files))
+            .route(
+                "/api/v1/sandbox/files/*path",
+                get(handlers::sandbox::read_file)
+                    .put(handlers::sandbox::write_file)
+                    .delete(handlers::sandbox::delete_file),
+            )
+            .with_state(AppState {
+                config: LiveConfig::new(test_config()),
+                orchestrator: Arc::new(Orchestrator::new(Arc::new(SkillRegistry::new()))),
+                knowledge: Arc::clone(&knowledge),
+                log_tx: test_log_tx(),
+                shadow_store: test_shadow_store(),
+                llm_providers: Default::default(),
+                oidc: None,
+            });
+        let send = |method: &str, uri: &str, body: Option<serde_json::Value>| {
+            let req = Request::builder()
+                .method(method)
+                .uri(uri)
+                .header("content-type", "application/json")
+                .body(body.map_or_else(Body::empty, |b| Body::from(b.to_string())))
+                .unwrap();
+            app.clone().oneshot(req)
+        };
+        let json = |res: Response| async move {
+            serde_json::from_slice::<serde_json::Value>(&axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap())
+                .unwrap()
+        };
+
+        let res = send("PUT", "/api/v1/sandbox/files/notes/todo.txt", Some(serde_json::json!({ "content": "TODO: tidy" })))
+            .await
             .unwrap();
-        let recall_res = app.oneshot(recall_req).await.unwrap();
-        assert_eq!(recall_res.status(), StatusCode::OK);
-        let recall_bytes = axum::body::to_bytes(recall_res.into_body(), usize::MAX).await.unwrap();
-        let recall_json: serde_json::Value = serde_json::from_slice(&recall_bytes).unwrap();
-        let events = recall_json["events"].as_array().expect("events array");
-        let has_violation = events
-            .iter()
-            .any(|e| e["reflection"].as_str().unwrap_or("").contains("Policy Violation"));
-        assert!(
-            has_violation,
-            "Chronos should contain a Policy Violation event; got events: {:?}",
-            events
-        );
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(json(res).await["change"], "created");
+        assert_eq!(std::fs::read_to_string(root.join("notes/todo.txt")).unwrap(), "TODO: tidy");
+
+        let list = json(send("GET", "/api/v1/sandbox/files", None).await.unwrap()).await;
+        assert_eq!(list["files"][0]["path"], "notes/todo.txt");
+        let file = json(send("GET", "/api/v1/sandbox/files/notes/todo.txt", None).await.unwrap()).await;
+        assert_eq!(file["content"], "TODO: tidy");
+
+        // Ethos: sensitive content is refused and nothing is written.
+        let res = send("PUT", "/api/v1/sandbox/files/creds.txt", Some(serde_json::json!({ "content": "password=hunter2" })))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::FORBIDDEN);
+        assert_eq!(json(res).await["status"], "policy_violation");
+        assert!(!root.join("creds.txt").exists());
+
+        // PathJail: traversal is rejected.
+        let res = send("GET", "/api/v1/sandbox/files/notes/%2E%2E/%2E%2E/etc/passwd", None).await.unwrap();
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+        let res = send("DELETE", "/api/v1/sandbox/files/notes/todo.txt", None).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        let res = send("GET", "/api/v1/sandbox/files/notes/todo.txt", None).await.unwrap();
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+
+        let outcomes: Vec<String> = knowledge
+            .get_recent_chronos_events(pagi_core::DEFAULT_AGENT_ID, 10)
+            .unwrap()
+            .into_iter()
+            .filter_map(|e| e.outcome)
+            .collect();
+        assert!(outcomes.contains(&"created".to_string()) && outcomes.contains(&"deleted".to_string()));
+        assert!(outcomes.contains(&"blocked".to_string()));
+        let _ = std::fs::remove_dir_all(root);
     }
 
     #[tokio::test]
-    async fn test_kardia_sentiment_stored_and_chat_injects_context() {
+    async fn test_execute_dry_run_previews_without_executing() {
         let knowledge = Arc::new(
-            KnowledgeStore::open_path("./data/pagi_kardia_verify_test").unwrap(),
+            KnowledgeStore::open_path("./data/pagi_knowledge_dry_run_test").unwrap(),
         );
-        let mut registry = SkillRegistry::new();
-        registry.register(Arc::new(AnalyzeSentiment::new(Arc::clone(&knowledge))));
-        registry.register(Arc::new(ModelRouter::with_knowledge(Arc::clone(&knowledge))));
-        let orchestrator = Arc::new(Orchestrator::new(Arc::new(registry)));
+        let orchestrator = Arc::new(Orchestrator::new(Arc::new(SkillRegistry::new())));
         let app = Router::new()
             .route("/v1/execute", post(execute))
-            .route("/api/v1/kardia/:user_id", get(get_kardia_relation))
-            .route("/api/v1/chat", post(chat))
             .with_state(AppState {
-                config: Arc::new(test_config()),
+                config: LiveConfig::new(test_config()),
                 orchestrator,
-                knowledge: Arc::clone(&knowledge),
+                knowledge,
                 log_tx: test_log_tx(),
-                model_router: Arc::new(ModelRouter::with_knowledge(Arc::clone(&knowledge))),
                 shadow_store: test_shadow_store(),
+                llm_providers: Default::default(),
+                oidc: None,
             });
 
-        let sentiment_body = serde_json::json!({
-            "tenant_id": "kardia-verify-user",
-            "goal": {
-                "ExecuteSkill": {
-                    "name": "analyze_sentiment",
-                    "payload": {
-                        "user_id": "kardia-verify-user",
-                        "messages": ["I am so angry", "This is terrible", "Nothing works"]
-                    }
-                }
-            }
+        let body = serde_json::json!({
+            "tenant_id": "test-tenant",
+            "goal": { "IngestData": { "payload": { "email": "lead@example.com" } } }
         });
-        let sentiment_req = Request::builder()
+        let req = Request::builder()
             .method("POST")
-            .uri("/v1/execute")
+            .uri("/v1/execute?dry_run=true")
             .header("content-type", "application/json")
-            .body(Body::from(serde_json::to_string(&sentiment_body).unwrap()))
+            .body(Body::from(serde_json::to_string(&body).unwrap()))
             .unwrap();
-        let sentiment_res = app.clone().oneshot(sentiment_req).await.unwrap();
-        assert_eq!(sentiment_res.status(), StatusCode::OK);
-        let sentiment_json: serde_json::Value = serde_json::from_slice(
-            &axum::body::to_bytes(sentiment_res.into_body(), usize::MAX).await.unwrap(),
-        )
-        .unwrap();
-        assert_eq!(sentiment_json["status"], "ok");
-        assert_eq!(sentiment_json["last_sentiment"], "angry");
+        let res = app.oneshot(req).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        let bytes = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(json["dry_run"], true);
+        assert_eq!(json["status"], "invalid");
+        assert_eq!(json["preview"]["steps"][0]["skill"], "LeadCapture");
+        assert_eq!(json["preview"]["steps"][0]["available"], false);
+        assert_eq!(json["preview"]["steps"][0]["input"]["email"], "lead@example.com");
+    }
 
-        let kardia_req = Request::builder()
-            .method("GET")
-            .uri("/api/v1/kardia/kardia-verify-user")
-            .body(Body::empty())
-            .unwrap();
-        let kardia_res = app.clone().oneshot(kardia_req).await.unwrap();
-        assert_eq!(kardia_res.status(), StatusCode::OK);
-        let kardia_json: serde_json::Value = serde_json::from_slice(
-            &axum::body::to_bytes(kardia_res.into_body(), usize::MAX).await.unwrap(),
-        )
-        .unwrap();
-        assert_eq!(kardia_json["last_sentiment"], "angry");
-        assert_eq!(kardia_json["user_id"], "kardia-verify-user");
-
-        let chat_body = serde_json::json!({
-            "prompt": "How would you describe our current working relationship?",
-            "stream": false,
-            "user_alias": "kardia-verify-user"
+    #[tokio::test]
+    async fn test_execute_rate_limited_returns_429() {
+        let knowledge = Arc::new(
+            KnowledgeStore::open_path("./data/pagi_rate_limit_test").unwrap(),
+        );
+        let queue = DispatchQueue::new(DispatchQueueConfig {
+            default_limit: Some(pagi_core::RateLimit { per_minute: 1, burst: 1 }),
+            ..Default::default()
         });
-        let chat_req = Request::builder()
+        let orchestrator = Arc::new(
+            Orchestrator::new(Arc::new(SkillRegistry::new())).with_dispatch_queue(Arc::new(queue)),
+        );
+        let app = Router::new()
+            .route("/v1/execute", post(execute))
+            .with_state(AppState {
+                config: LiveConfig::new(test_config()),
+                orchestrator,
+                knowledge,
+                log_tx: test_log_tx(),
+                shadow_store: test_shadow_store(),
+                llm_providers: Default::default(),
+                oidc: None,
+            });
+
+        let body = serde_json::json!({ "tenant_id": "acme", "goal": { "Custom": "ping" } }).to_string();
+        let request = || {
+            Request::builder()
+                .method("POST")
+                .uri("/v1/execute")
+                .header("content-type", "application/json")
+                .body(Body::from(body.clone()))
+                .unwrap()
+        };
+        let res = app.clone().oneshot(request()).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        let res = app.oneshot(request()).await.unwrap();
+        assert_eq!(res.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(res.headers()["retry-after"], "60");
+        let bytes = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(json["status"], "rate_limited");
+        assert_eq!(json["retry_after_secs"], 60);
+    }
+
+    #[tokio::test]
+    async fn test_cancel_unknown_execution_returns_not_found() {
+        let knowledge = Arc::new(
+            KnowledgeStore::open_path("./data/pagi_cancel_test").unwrap(),
+        );
+        let orchestrator = Arc::new(Orchestrator::new(Arc::new(SkillRegistry::new())));
+        let app = Router::new()
+            .route("/v1/execute/:correlation_id/cancel", post(cancel_execution))
+            .with_state(AppState {
+                config: LiveConfig::new(test_config()),
+                orchestrator,
+                knowledge,
+                log_tx: test_log_tx(),
+                shadow_store: test_shadow_store(),
+                llm_providers: Default::default(),
+                oidc: None,
+            });
+
+        let req = Request::builder()
             .method("POST")
-            .uri("/api/v1/chat")
-            .header("content-type", "application/json")
-            .body(Body::from(serde_json::to_string(&chat_body).unwrap()))
+            .uri("/v1/execute/no-such-request/cancel")
+            .body(Body::empty())
             .unwrap();
-        let chat_res = app.oneshot(chat_req).await.unwrap();
-        assert_eq!(chat_res.status(), StatusCode::OK);
-        let chat_json: serde_json::Value = serde_json::from_slice(
-            &axum::body::to_bytes(chat_res.into_body(), usize::MAX).await.unwrap(),
-        )
-        .unwrap();
-        assert_eq!(chat_json["status"], "ok");
-        assert!(chat_json.get("response").and_then(|v| v.as_str()).unwrap_or("").len() > 0);
+        let res = app.oneshot(req).await.unwrap();
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+        let bytes = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(json["status"], "not_found");
+        assert_eq!(json["correlation_id"], "no-such-request");
     }
 
     #[tokio::test]
-    async fn test_kb2_insert_and_retrieve_welcome_template() {
+    async fn test_skills_lists_execution_stats() {
         let knowledge = Arc::new(
-            KnowledgeStore::open_path("./data/pagi_kb2_test").unwrap(),
+            KnowledgeStore::open_path("./data/pagi_skills_stats_test").unwrap(),
         );
         let mut registry = SkillRegistry::new();
-        registry.register(Arc::new(KnowledgeInsert::new(Arc::clone(&knowledge))));
         registry.register(Arc::new(KnowledgeQuery::new(Arc::clone(&knowledge))));
         let orchestrator = Arc::new(Orchestrator::new(Arc::new(registry)));
+        orchestrator.skill_stats().record("KnowledgeQuery", true, 12, None);
+        orchestrator.skill_stats().record("KnowledgeQuery", false, 30, Some("slot missing".into()));
         let app = Router::new()
-            .route("/v1/execute", post(execute))
+            .route("/v1/skills", get(handlers::skills::list_skills))
             .with_state(AppState {
-            config: Arc::new(test_config()),
-            orchestrator,
-            knowledge,
-            log_tx: test_log_tx(),
-            model_router: test_model_router(),
-            shadow_store: test_shadow_store(),
-        });
+                config: LiveConfig::new(test_config()),
+                orchestrator,
+                knowledge,
+                log_tx: test_log_tx(),
+                shadow_store: test_shadow_store(),
+                llm_providers: Default::default(),
+                oidc: None,
+            });
 
-        let insert_body = serde_json::json!({
-            "tenant_id": "test-tenant",
-            "goal": {
-                "ExecuteSkill": {
-                    "name": "KnowledgeInsert",
-                    "payload": {
-                        "slot_id": 2,
-                        "key": "welcome_email_template",
-                        "value": "Welcome! We're glad you reached out. A team member will follow up within 24 hours."
-                    }
-                }
-            }
-        });
-        let insert_req = Request::builder()
-            .method("POST")
-            .uri("/v1/execute")
-            .header("content-type", "application/json")
-            .body(Body::from(serde_json::to_string(&insert_body).unwrap()))
-            .unwrap();
-        let insert_res = app.clone().oneshot(insert_req).await.unwrap();
-        assert_eq!(insert_res.status(), StatusCode::OK);
-        let insert_bytes = axum::body::to_bytes(insert_res.into_body(), usize::MAX)
+        let req = Request::builder().uri("/v1/skills").body(Body::empty()).unwrap();
+        let res = app.oneshot(req).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        let bytes = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        let skill = &json["skills"][0];
+        assert_eq!(skill["skill"], "KnowledgeQuery");
+        assert_eq!(skill["total_calls"], 2);
+        assert_eq!(skill["success_rate"], 0.5);
+        assert_eq!(skill["avg_latency_ms"], 21);
+        assert_eq!(skill["last_error"], "slot missing");
+    }
+
+    #[tokio::test]
+    async fn test_create_schedule_validates_and_persists() {
+        let knowledge = Arc::new(
+            KnowledgeStore::open_path("./data/pagi_schedules_test").unwrap(),
+        );
+        let orchestrator = Arc::new(Orchestrator::new(Arc::new(SkillRegistry::new())));
+        let app = Router::new()
+            .route(
+                "/api/v1/schedules",
+                get(handlers::schedules::list_schedules).post(handlers::schedules::create_schedule),
+            )
+            .with_state(AppState {
+                config: LiveConfig::new(test_config()),
+                orchestrator,
+                knowledge: Arc::clone(&knowledge),
+                log_tx: test_log_tx(),
+                shadow_store: test_shadow_store(),
+                llm_providers: Default::default(),
+                oidc: None,
+            });
+        let post = |body: serde_json::Value| {
+            Request::builder()
+                .method("POST")
+                .uri("/api/v1/schedules")
+                .header("content-type", "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap()
+        };
+        let goal = serde_json::json!({ "UpdateKnowledgeSlot": { "slot_id": 5, "source_url": "https://example.com", "source_html": null } });
+
+        let res = app
+            .clone()
+            .oneshot(post(serde_json::json!({ "name": "bad", "goal": goal, "tenant_id": "acme", "schedule": "every morning" })))
             .await
             .unwrap();
-        let insert_json: serde_json::Value = serde_json::from_slice(&insert_bytes).unwrap();
-        assert_eq!(insert_json["status"], "ok");
-        assert_eq!(insert_json["skill"], "KnowledgeInsert");
-        assert_eq!(insert_json["slot_id"], 2);
-        assert_eq!(insert_json["key"], "welcome_email_template");
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
 
-        let query_body = serde_json::json!({
-            "tenant_id": "test-tenant",
-            "goal": {
-                "QueryKnowledge": { "slot_id": 2, "query": "welcome_email_template" }
-            }
-        });
-        let query_req = Request::builder()
-            .method("POST")
-            .uri("/v1/execute")
-            .header("content-type", "application/json")
-            .body(Body::from(serde_json::to_string(&query_body).unwrap()))
-            .unwrap();
-        let query_res = app.oneshot(query_req).await.unwrap();
-        assert_eq!(query_res.status(), StatusCode::OK);
-        let query_bytes = axum::body::to_bytes(query_res.into_body(), usize::MAX)
+        let res = app
+            .oneshot(post(serde_json::json!({ "name": "slot 5 refresh", "goal": goal, "tenant_id": "acme", "schedule": "0 7 * * *" })))
             .await
             .unwrap();
-        let query_json: serde_json::Value = serde_json::from_slice(&query_bytes).unwrap();
-        assert_eq!(query_json["status"], "ok");
-        assert_eq!(query_json["skill"], "KnowledgeQuery");
-        assert_eq!(query_json["value"], "Welcome! We're glad you reached out. A team member will follow up within 24 hours.");
+        assert_eq!(res.status(), StatusCode::CREATED);
+        let bytes = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        let id = json["schedule"]["id"].as_str().unwrap();
+        let stored = SchedulerRegistry::new(Arc::clone(&knowledge)).get(id).unwrap();
+        assert_eq!(stored.schedule.as_deref(), Some("0 7 * * *"));
+        assert_eq!(stored.ctx.tenant_id, "acme");
+        assert_eq!(stored.next_run_ms.unwrap() % (24 * 60 * 60 * 1000), 7 * 60 * 60 * 1000);
+        SchedulerRegistry::new(knowledge).remove(id).unwrap();
     }
 
     #[tokio::test]
-    async fn test_draft_response_includes_brand_voice_and_local_event() {
-        let memory = Arc::new(MemoryManager::open_path("./data/pagi_vault_draft_test").unwrap());
-        let knowledge = Arc::new(
-            KnowledgeStore::open_path("./data/pagi_knowledge_draft_test").unwrap(),
+    async fn test_webhooks_receive_signed_lead_captured_events() {
+        let received = Arc::new(std::sync::Mutex::new(Vec::<(axum::http::HeaderMap, Vec<u8>)>::new()));
+        let sink = Arc::clone(&received);
+        let receiver = Router::new().route(
+            "/hook",
+            post(move |headers: axum::http::HeaderMap, body: axum::body::Bytes| {
+                let sink = Arc::clone(&sink);
+                async move {
+                    sink.lock().unwrap().push((headers, body.to_vec()));
+                    StatusCode::NO_CONTENT
+                }
+            }),
         );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let hook_url = format!("http://{}/hook", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, receiver).await.unwrap() });
 
-        // Set Brand Voice in KB-1
-        knowledge.insert(1, "brand_voice", b"Warm, neighborly, and helpful").unwrap();
-
-        // Set Local Event in KB-5 via CommunityPulse
+        let knowledge = Arc::new(KnowledgeStore::open_temporary().unwrap());
+        let memory = Arc::new(MemoryManager::open_temporary().unwrap());
         let mut registry = SkillRegistry::new();
         registry.register(Arc::new(LeadCapture::new(Arc::clone(&memory))));
-        registry.register(Arc::new(KnowledgeInsert::new(Arc::clone(&knowledge))));
-        registry.register(Arc::new(CommunityPulse::new(Arc::clone(&knowledge))));
-        registry.register(Arc::new(DraftResponse::new(
-            Arc::clone(&memory),
-            Arc::clone(&knowledge),
-        )));
-        let orchestrator = Arc::new(Orchestrator::new(Arc::new(registry)));
+        let orchestrator = Arc::new(Orchestrator::new(Arc::new(registry)).with_knowledge(Arc::clone(&knowledge)));
         let app = Router::new()
-            .route("/v1/execute", post(execute))
+            .route("/api/v1/webhooks", get(handlers::webhooks::list_webhooks).post(handlers::webhooks::create_webhook))
             .with_state(AppState {
-            config: Arc::new(test_config()),
-            orchestrator,
-            knowledge,
-            log_tx: test_log_tx(),
-            model_router: test_model_router(),
-            shadow_store: test_shadow_store(),
-        });
-
-        // 1. Capture a lead to get lead_id (IngestData)
-        let lead_body = serde_json::json!({
-            "tenant_id": "test-tenant",
-            "goal": {
-                "IngestData": {
-                    "payload": { "email": "customer@example.com", "message": "Interested in services" }
-                }
-            }
-        });
-        let lead_req = Request::builder()
-            .method("POST")
-            .uri("/v1/execute")
-            .header("content-type", "application/json")
-            .body(Body::from(serde_json::to_string(&lead_body).unwrap()))
+                // The receiver listens on loopback.
+                config: LiveConfig::new(CoreConfig {
+                    webhooks: pagi_core::WebhooksConfig { allow_private_hosts: true },
+                    ..test_config()
+                }),
+                orchestrator: Arc::clone(&orchestrator),
+                knowledge: Arc::clone(&knowledge),
+                log_tx: test_log_tx(),
+                shadow_store: test_shadow_store(),
+                llm_providers: Default::default(),
+                oidc: None,
+            });
+        let body = serde_json::json!({ "url": hook_url, "events": ["lead.captured"], "tenant_id": "acme" });
+        let res = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/api/v1/webhooks")
+                    .header("content-type", "application/json")
+                    .body(Body::from(body.to_string()))
+                    .unwrap(),
+            )
+            .await
             .unwrap();
-        let lead_res = app.clone().oneshot(lead_req).await.unwrap();
-        assert_eq!(lead_res.status(), StatusCode::OK);
-        let lead_bytes = axum::body::to_bytes(lead_res.into_body(), usize::MAX).await.unwrap();
-        let lead_json: serde_json::Value = serde_json::from_slice(&lead_bytes).unwrap();
-        let lead_id = lead_json["lead_id"].as_str().unwrap().to_string();
-
-        // 2. Set Community Pulse (e.g. Strawberry Festival) in KB-5
-        let pulse_body = serde_json::json!({
-            "tenant_id": "test-tenant",
-            "goal": {
-                "ExecuteSkill": {
-                    "name": "CommunityPulse",
-                    "payload": {
-                        "location": "Stockdale",
-                        "trend": "rainy week",
-                        "event": "Strawberry Festival this weekend"
-                    }
-                }
-            }
-        });
-        let pulse_req = Request::builder()
-            .method("POST")
-            .uri("/v1/execute")
-            .header("content-type", "application/json")
-            .body(Body::from(serde_json::to_string(&pulse_body).unwrap()))
+        assert_eq!(res.status(), StatusCode::CREATED);
+        let created: serde_json::Value =
+            serde_json::from_slice(&axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap()).unwrap();
+        let secret = created["secret"].as_str().unwrap().to_string();
+        let res = app
+            .oneshot(Request::builder().uri("/api/v1/webhooks").body(Body::empty()).unwrap())
+            .await
             .unwrap();
-        let pulse_res = app.clone().oneshot(pulse_req).await.unwrap();
-        assert_eq!(pulse_res.status(), StatusCode::OK);
-
-        // 3. Execute AssembleContext (draft for this context_id)
-        let draft_body = serde_json::json!({
-            "tenant_id": "test-tenant",
-            "goal": {
-                "AssembleContext": { "context_id": lead_id }
+        let listed: serde_json::Value =
+            serde_json::from_slice(&axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap()).unwrap();
+        assert_eq!(listed["count"], 1);
+        assert!(listed["webhooks"][0].get("secret").is_none());
+
+        let ingest = |tenant_id: &str| {
+            let ctx = TenantContext::new(tenant_id.to_string());
+            let orchestrator = Arc::clone(&orchestrator);
+            async move {
+                let payload = serde_json::json!({ "email": "lead@example.com", "message": "Quote please" });
+                orchestrator.dispatch(&ctx, Goal::IngestData { payload: Some(payload) }).await.unwrap()
             }
-        });
-        let draft_req = Request::builder()
-            .method("POST")
-            .uri("/v1/execute")
-            .header("content-type", "application/json")
-            .body(Body::from(serde_json::to_string(&draft_body).unwrap()))
+        };
+        let captured = ingest("acme").await;
+        ingest("beta").await;
+        let attempts = WebhookRegistry::new(Arc::clone(&knowledge))
+            .with_private_hosts(true)
+            .deliver_due()
+            .await
             .unwrap();
-        let draft_res = app.oneshot(draft_req).await.unwrap();
-        assert_eq!(draft_res.status(), StatusCode::OK);
-        let draft_bytes = axum::body::to_bytes(draft_res.into_body(), usize::MAX).await.unwrap();
-        let draft_json: serde_json::Value = serde_json::from_slice(&draft_bytes).unwrap();
-        assert_eq!(draft_json["status"], "ok");
-        assert_eq!(draft_json["skill"], "DraftResponse");
-
-        let draft_text = draft_json["draft"].as_str().unwrap();
-        assert!(draft_text.contains("Warm, neighborly, and helpful"), "draft should include Brand Voice from KB-1");
-        assert!(draft_text.contains("Strawberry Festival this weekend"), "draft should include Local Event from KB-5");
-        assert!(draft_text.contains("Local Context:"), "draft should include Local Context section");
+        assert_eq!(attempts.len(), 1);
+        assert!(attempts[0].ok, "{:?}", attempts[0].error);
+
+        let received = received.lock().unwrap();
+        let (headers, body) = &received[0];
+        assert_eq!(headers["x-pagi-event"], "lead.captured");
+        let timestamp: i64 = headers["x-pagi-timestamp"].to_str().unwrap().parse().unwrap();
+        assert_eq!(headers["x-pagi-signature"].to_str().unwrap(), pagi_core::sign_webhook(&secret, timestamp, body));
+        let event: serde_json::Value = serde_json::from_slice(body).unwrap();
+        assert_eq!(event["tenant_id"], "acme");
+        assert_eq!(event["data"]["result"]["lead_id"], captured["lead_id"]);
     }
 
     #[tokio::test]
-    async fn test_generate_final_response_chain_returns_generated_string() {
-        let memory = Arc::new(
-            MemoryManager::open_path("./data/pagi_vault_generate_test").unwrap(),
-        );
-        let knowledge = Arc::new(
-            KnowledgeStore::open_path("./data/pagi_knowledge_generate_test").unwrap(),
-        );
-        knowledge.insert(1, "brand_voice", b"Warm and professional").unwrap();
-
+    async fn test_ingest_verifies_source_signatures_and_captures_mapped_leads() {
+        use base64::Engine;
+        std::env::set_var("PAGI_TEST_INGEST_TYPEFORM_SECRET", "tf-signing-secret");
+        let memory = Arc::new(MemoryManager::open_temporary().unwrap());
         let mut registry = SkillRegistry::new();
         registry.register(Arc::new(LeadCapture::new(Arc::clone(&memory))));
-        registry.register(Arc::new(DraftResponse::new(
-            Arc::clone(&memory),
-            Arc::clone(&knowledge),
-        )));
-        registry.register(Arc::new(ModelRouter::new()));
-        let orchestrator = Arc::new(Orchestrator::new(Arc::new(registry)));
+        let mut config = test_config();
+        config.ingest.sources.insert(
+            "typeform".to_string(),
+            pagi_core::IngestSourceConfig {
+                secret_env: Some("PAGI_TEST_INGEST_TYPEFORM_SECRET".to_string()),
+                tenant_id: Some("acme".to_string()),
+                ..Default::default()
+            },
+        );
+        config.ingest.sources.insert(
+            "calendly".to_string(),
+            pagi_core::IngestSourceConfig { allow_unsigned: true, ..Default::default() },
+        );
         let app = Router::new()
-            .route("/v1/execute", post(execute))
+            .route("/api/v1/ingest/:source", post(handlers::ingest::ingest))
             .with_state(AppState {
-            config: Arc::new(test_config()),
-            orchestrator,
-            knowledge,
-            log_tx: test_log_tx(),
-            model_router: test_model_router(),
-            shadow_store: test_shadow_store(),
-        });
-
-        // 1. Capture a lead (IngestData)
-        let lead_body = serde_json::json!({
-            "tenant_id": "test-tenant",
-            "goal": {
-                "IngestData": {
-                    "payload": { "email": "guest@example.com", "message": "Hello" }
-                }
+                config: LiveConfig::new(config),
+                orchestrator: Arc::new(Orchestrator::new(Arc::new(registry))),
+                knowledge: Arc::new(KnowledgeStore::open_temporary().unwrap()),
+                log_tx: test_log_tx(),
+                shadow_store: test_shadow_store(),
+                llm_providers: Default::default(),
+                oidc: None,
+            });
+        let post = |source: &str, body: &str, signature: Option<&str>| {
+            let mut req = Request::builder()
+                .method("POST")
+                .uri(format!("/api/v1/ingest/{}", source))
+                .header("content-type", "application/json");
+            if let Some(signature) = signature {
+                req = req.header("Typeform-Signature", signature);
             }
-        });
-        let lead_req = Request::builder()
-            .method("POST")
-            .uri("/v1/execute")
-            .header("content-type", "application/json")
-            .body(Body::from(serde_json::to_string(&lead_body).unwrap()))
-            .unwrap();
-        let lead_res = app.clone().oneshot(lead_req).await.unwrap();
-        assert_eq!(lead_res.status(), StatusCode::OK);
-        let lead_bytes = axum::body::to_bytes(lead_res.into_body(), usize::MAX).await.unwrap();
-        let lead_json: serde_json::Value = serde_json::from_slice(&lead_bytes).unwrap();
-        let lead_id = lead_json["lead_id"].as_str().unwrap().to_string();
+            req.body(Body::from(body.to_string())).unwrap()
+        };
 
-        // 2. Generate final response (AssembleContext -> ModelRouter chain)
-        let gen_body = serde_json::json!({
-            "tenant_id": "test-tenant",
-            "goal": {
-                "GenerateFinalResponse": { "context_id": lead_id }
+        let sign = |body: &str| {
+            let key = ring::hmac::Key::new(ring::hmac::HMAC_SHA256, b"tf-signing-secret");
+            format!("sha256={}", base64::engine::general_purpose::STANDARD.encode(ring::hmac::sign(&key, body.as_bytes())))
+        };
+        let body = serde_json::json!({
+            "event_id": "01HZX3K9Q2",
+            "event_type": "form_response",
+            "form_response": {
+                "form_id": "lT4Z3j",
+                "token": "a3a12ec67a1365927098a606107fac15",
+                "answers": [
+                    { "type": "text", "text": "Ada Lovelace", "field": { "id": "f1", "ref": "name", "type": "short_text" } },
+                    { "type": "email", "email": "ada@example.com", "field": { "id": "f2", "type": "email" } },
+                    { "type": "choice", "choice": { "label": "Roof repair" }, "field": { "id": "f3", "ref": "service", "type": "multiple_choice" } }
+                ]
             }
-        });
-        let gen_req = Request::builder()
-            .method("POST")
-            .uri("/v1/execute")
-            .header("content-type", "application/json")
-            .body(Body::from(serde_json::to_string(&gen_body).unwrap()))
-            .unwrap();
-        let gen_res = app.oneshot(gen_req).await.unwrap();
-        assert_eq!(gen_res.status(), StatusCode::OK);
-        let gen_bytes = axum::body::to_bytes(gen_res.into_body(), usize::MAX).await.unwrap();
-        let gen_json: serde_json::Value = serde_json::from_slice(&gen_bytes).unwrap();
-
-        assert_eq!(gen_json["status"], "ok");
-        assert_eq!(gen_json["goal"], "GenerateFinalResponse");
-        assert_eq!(gen_json["context_id"], lead_id);
-        let generated = gen_json["generated"].as_str().expect("response must contain 'generated' string");
-        assert!(!generated.is_empty(), "generated text must not be empty");
-        assert!(
-            generated.contains("Generated") || generated.contains("personalized") || generated.contains("Thank you"),
-            "generated should be LLM-style output, not just the raw mock draft template"
-        );
+        })
+        .to_string();
+        let signature = sign(&body);
+
+        let res = app.clone().oneshot(post("typeform", &body, Some("sha256=bm90IHRoZSBzaWduYXR1cmU="))).await.unwrap();
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+        let res = app.clone().oneshot(post("jotform", &body, Some(&signature))).await.unwrap();
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+        let res = app.clone().oneshot(post("typeform", &body, Some(&signature))).await.unwrap();
+        assert_eq!(res.status(), StatusCode::CREATED);
+        let saved: serde_json::Value =
+            serde_json::from_slice(&axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap()).unwrap();
+        let acme = TenantContext::new("acme");
+        let lead = memory.get_lead(&acme, saved["lead_id"].as_str().unwrap()).unwrap().unwrap();
+        assert_eq!(lead.fields["email"], "ada@example.com");
+        assert_eq!(lead.fields["name"], "Ada Lovelace");
+        assert_eq!(lead.fields["answers"]["service"], "Roof repair");
+        assert_eq!(lead.fields["source"], "typeform");
+
+        // A replayed delivery answers like the first one and captures nothing new.
+        let res = app.clone().oneshot(post("typeform", &body, Some(&signature))).await.unwrap();
+        assert_eq!(res.status(), StatusCode::CREATED);
+        let replayed: serde_json::Value =
+            serde_json::from_slice(&axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap()).unwrap();
+        assert_eq!(replayed["lead_id"], saved["lead_id"]);
+        assert_eq!(memory.list_leads(&acme, &Default::default(), None).unwrap().leads.len(), 1);
+        // The same event_id with another (validly signed) payload is refused, as is a delivery without one.
+        let altered = body.replace("Ada Lovelace", "Charles Babbage");
+        let res = app.clone().oneshot(post("typeform", &altered, Some(&sign(&altered)))).await.unwrap();
+        assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        let unidentified = body.replace("\"event_id\":\"01HZX3K9Q2\",", "");
+        let res = app.clone().oneshot(post("typeform", &unidentified, Some(&sign(&unidentified)))).await.unwrap();
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+        let canceled = serde_json::json!({ "event": "invitee.canceled", "payload": { "email": "ada@example.com" } });
+        let res = app.oneshot(post("calendly", &canceled.to_string(), None)).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
     }
 
     #[tokio::test]
-    async fn test_autonomous_goal_respond_to_lead_triggers_generation_chain() {
-        let memory = Arc::new(
-            MemoryManager::open_path("./data/pagi_vault_autonomous_test").unwrap(),
+    async fn test_slack_commands_run_goals_and_post_results_back() {
+        let received = Arc::new(std::sync::Mutex::new(Vec::<(String, serde_json::Value)>::new()));
+        let sink = Arc::clone(&received);
+        let receiver = Router::new().route(
+            "/:hook",
+            post(move |Path(hook): Path<String>, Json(body): Json<serde_json::Value>| {
+                let sink = Arc::clone(&sink);
+                async move {
+                    sink.lock().unwrap().push((hook, body));
+                    StatusCode::OK
+                }
+            }),
         );
-        let knowledge = Arc::new(
-            KnowledgeStore::open_path("./data/pagi_knowledge_autonomous_test").unwrap(),
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, receiver).await.unwrap() });
+        std::env::set_var("PAGI_TEST_SLACK_SIGNING_SECRET", "slack-signing-secret");
+        std::env::set_var("PAGI_TEST_SLACK_OPS_WEBHOOK", format!("{}/ops", base_url));
+
+        let mut config = test_config();
+        config.chat.channels.insert(
+            "ops".to_string(),
+            pagi_core::ChatChannelConfig {
+                kind: pagi_core::ChatKind::Slack,
+                webhook_env: "PAGI_TEST_SLACK_OPS_WEBHOOK".to_string(),
+            },
         );
-        knowledge.insert(1, "brand_voice", b"Friendly and local").unwrap();
-
+        config.chat.default_channel = Some("ops".to_string());
+        config.chat.notify = vec![pagi_core::WebhookEvent::GoalCompleted];
+        config.chat.slack_commands = pagi_core::SlackCommandConfig {
+            enabled: true,
+            signing_secret_env: Some("PAGI_TEST_SLACK_SIGNING_SECRET".to_string()),
+            tenant_id: Some("acme".to_string()),
+            agent_id: None,
+        };
         let mut registry = SkillRegistry::new();
-        registry.register(Arc::new(LeadCapture::new(Arc::clone(&memory))));
-        registry.register(Arc::new(DraftResponse::new(
-            Arc::clone(&memory),
-            Arc::clone(&knowledge),
-        )));
-        registry.register(Arc::new(SalesCloser::new(Arc::clone(&knowledge))));
         registry.register(Arc::new(ModelRouter::new()));
-        registry.register(Arc::new(ResearchAudit::new(Arc::clone(&knowledge))));
-        let orchestrator = Arc::new(Orchestrator::new(Arc::new(registry)));
+        registry.register(Arc::new(NotifyChat::new(config.chat.clone())));
+        let mut intents = std::collections::HashMap::new();
+        intents.insert("draft a greeting".to_string(), vec!["ModelRouter".to_string()]);
+        let orchestrator = Orchestrator::with_blueprint(Arc::new(registry), Arc::new(BlueprintRegistry::from_intents(intents)))
+            .with_chat_notifications(config.chat.notify.clone());
         let app = Router::new()
-            .route("/v1/execute", post(execute))
-            .route("/v1/research/trace/:trace_id", get(get_research_trace))
+            .route("/api/v1/chat/slack/commands", post(handlers::slack::slack_command))
             .with_state(AppState {
-            config: Arc::new(test_config()),
-            orchestrator,
-            knowledge,
-            log_tx: test_log_tx(),
-            model_router: test_model_router(),
-            shadow_store: test_shadow_store(),
-        });
+                config: LiveConfig::new(config),
+                orchestrator: Arc::new(orchestrator),
+                knowledge: Arc::new(KnowledgeStore::open_temporary().unwrap()),
+                log_tx: test_log_tx(),
+                shadow_store: test_shadow_store(),
+                llm_providers: Default::default(),
+                oidc: None,
+            });
+        let body = serde_urlencoded::to_string([
+            ("command", "/pagi".to_string()),
+            ("text", "draft a greeting".to_string()),
+            ("user_id", "U123".to_string()),
+            ("team_id", "T1".to_string()),
+            ("response_url", format!("{}/response", base_url)),
+        ])
+        .unwrap();
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+            .to_string();
+        let key = ring::hmac::Key::new(ring::hmac::HMAC_SHA256, b"slack-signing-secret");
+        let tag = ring::hmac::sign(&key, format!("v0:{}:{}", timestamp, body).as_bytes());
+        let signature = format!("v0={}", tag.as_ref().iter().map(|b| format!("{:02x}", b)).collect::<String>());
+        let post = |signature: &str| {
+            Request::builder()
+                .method("POST")
+                .uri("/api/v1/chat/slack/commands")
+                .header("content-type", "application/x-www-form-urlencoded")
+                .header("X-Slack-Request-Timestamp", &timestamp)
+                .header("X-Slack-Signature", signature)
+                .body(Body::from(body.clone()))
+                .unwrap()
+        };
 
-        // 1. Capture a lead (IngestData)
-        let lead_body = serde_json::json!({
-            "tenant_id": "test-tenant",
-            "goal": {
-                "IngestData": {
-                    "payload": { "email": "neighbor@town.com", "message": "Interested in events" }
-                }
-            }
-        });
-        let lead_req = Request::builder()
-            .method("POST")
-            .uri("/v1/execute")
-            .header("content-type", "application/json")
-            .body(Body::from(serde_json::to_string(&lead_body).unwrap()))
-            .unwrap();
-        let lead_res = app.clone().oneshot(lead_req).await.unwrap();
-        assert_eq!(lead_res.status(), StatusCode::OK);
-        let lead_bytes = axum::body::to_bytes(lead_res.into_body(), usize::MAX).await.unwrap();
-        let lead_json: serde_json::Value = serde_json::from_slice(&lead_bytes).unwrap();
-        let lead_id = lead_json["lead_id"].as_str().unwrap().to_string();
+        let res = app.clone().oneshot(post("v0=00ff")).await.unwrap();
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+        let res = app.oneshot(post(&signature)).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        let ack: serde_json::Value =
+            serde_json::from_slice(&axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap()).unwrap();
+        assert_eq!(ack["response_type"], "ephemeral");
 
-        // 2. AutonomousGoal "respond to lead" with context.lead_id
-        let autonomous_body = serde_json::json!({
-            "tenant_id": "test-tenant",
-            "goal": {
-                "AutonomousGoal": {
-                    "intent": "respond to lead",
-                    "context": { "lead_id": lead_id }
-                }
+        for _ in 0..100 {
+            if received.lock().unwrap().len() >= 2 {
+                break;
             }
-        });
-        let autonomous_req = Request::builder()
-            .method("POST")
-            .uri("/v1/execute")
-            .header("content-type", "application/json")
-            .body(Body::from(serde_json::to_string(&autonomous_body).unwrap()))
-            .unwrap();
-        let autonomous_res = app.clone().oneshot(autonomous_req).await.unwrap();
-        assert_eq!(autonomous_res.status(), StatusCode::OK);
-        let autonomous_bytes = axum::body::to_bytes(autonomous_res.into_body(), usize::MAX).await.unwrap();
-        let auto_json: serde_json::Value = serde_json::from_slice(&autonomous_bytes).unwrap();
-
-        assert_eq!(auto_json["goal"], "AutonomousGoal");
-        assert_eq!(auto_json["intent"], "respond to lead");
-        assert_eq!(
-            auto_json["plan_steps"],
-            serde_json::json!(["DraftResponse", "SalesCloser", "ModelRouter"])
-        );
-        let generated = auto_json["generated"].as_str().expect("response must contain 'generated' from chain");
-        assert!(!generated.is_empty());
-        assert!(
-            generated.contains("Generated") || generated.contains("personalized") || generated.contains("Thank you"),
-            "autonomous chain should produce LLM-style generated text"
-        );
-        let trace_id = auto_json["trace_id"].as_str().expect("ResearchAudit should return trace_id");
-        assert!(!trace_id.is_empty());
-
-        // 3. Retrieve Thought Log from research endpoint
-        let trace_req = Request::builder()
-            .method("GET")
-            .uri(format!("/v1/research/trace/{}", trace_id))
-            .body(Body::empty())
-            .unwrap();
-        let trace_res = app.oneshot(trace_req).await.unwrap();
-        assert_eq!(trace_res.status(), StatusCode::OK);
-        let trace_bytes = axum::body::to_bytes(trace_res.into_body(), usize::MAX).await.unwrap();
-        let trace_json: serde_json::Value = serde_json::from_slice(&trace_bytes).unwrap();
-        assert_eq!(trace_json["trace_id"], trace_id);
-        let trace_inner = &trace_json["trace"];
-        assert_eq!(trace_inner["intent"], "respond to lead");
-        assert_eq!(
-            trace_inner["plan_steps"],
-            serde_json::json!(["DraftResponse", "SalesCloser", "ModelRouter"])
-        );
-        let steps = trace_inner["steps"].as_array().expect("trace should have steps array");
-        assert_eq!(steps.len(), 3, "respond to lead has three steps");
-        assert_eq!(steps[0]["skill"], "DraftResponse");
-        assert_eq!(steps[1]["skill"], "SalesCloser");
-        assert_eq!(steps[2]["skill"], "ModelRouter");
-        assert!(trace_inner.get("final_result").is_some(), "trace should have final_result");
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        let received = received.lock().unwrap();
+        let (_, reply) = received.iter().find(|(hook, _)| hook == "response").expect("goal result posted to response_url");
+        assert_eq!(reply["response_type"], "in_channel");
+        assert!(!reply["text"].as_str().unwrap().contains("failed"), "{}", reply["text"]);
+        let (_, notice) = received.iter().find(|(hook, _)| hook == "ops").expect("goal.completed posted to the ops channel");
+        assert_eq!(notice["text"], "[acme] Goal completed: \"draft a greeting\" (1 step(s))");
     }
 
     #[tokio::test]
-    async fn test_community_scraper_extracts_event_and_saves_to_kb5() {
-        let knowledge = Arc::new(
-            KnowledgeStore::open_path("./data/pagi_knowledge_scraper_test").unwrap(),
+    async fn test_telegram_webhook_answers_chats_through_the_chat_pipeline() {
+        let calls = Arc::new(std::sync::Mutex::new(Vec::<(String, serde_json::Value)>::new()));
+        let sink = Arc::clone(&calls);
+        let telegram_api = Router::new().route(
+            "/:bot/:method",
+            post(move |Path((_bot, method)): Path<(String, String)>, Json(body): Json<serde_json::Value>| {
+                let sink = Arc::clone(&sink);
+                async move {
+                    sink.lock().unwrap().push((method, body));
+                    axum::Json(serde_json::json!({ "ok": true, "result": { "message_id": 7 } }))
+                }
+            }),
         );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let api_url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, telegram_api).await.unwrap() });
+        std::env::set_var("PAGI_TEST_TELEGRAM_TOKEN", "123:abc");
+        std::env::set_var("PAGI_TEST_TELEGRAM_SECRET", "tg-webhook-secret");
+
+        let mut config = test_config();
+        config.channels.telegram = pagi_core::TelegramConfig {
+            enabled: true,
+            mode: pagi_core::TelegramMode::Webhook,
+            bot_token_env: Some("PAGI_TEST_TELEGRAM_TOKEN".to_string()),
+            webhook_secret_env: Some("PAGI_TEST_TELEGRAM_SECRET".to_string()),
+            api_url: Some(api_url),
+            tenant_id: Some("acme".to_string()),
+            rate_limit: Some("1/1".to_string()),
+            ..Default::default()
+        };
         let mut registry = SkillRegistry::new();
-        registry.register(Arc::new(CommunityScraper::new(Arc::clone(&knowledge))));
-        registry.register(Arc::new(KnowledgeQuery::new(Arc::clone(&knowledge))));
-        let orchestrator = Arc::new(Orchestrator::new(Arc::new(registry)));
+        registry.register(Arc::new(ModelRouter::new()));
+        let knowledge = Arc::new(KnowledgeStore::open_temporary().unwrap());
+        let bot = Arc::new(channels::telegram::TelegramBot::new(config.channels.telegram.clone()));
         let app = Router::new()
-            .route("/v1/execute", post(execute))
+            .route("/api/v1/channels/telegram/webhook", post(channels::telegram::webhook).layer(Extension(bot)))
             .with_state(AppState {
-            config: Arc::new(test_config()),
-            orchestrator,
-            knowledge,
-            log_tx: test_log_tx(),
-            model_router: test_model_router(),
-            shadow_store: test_shadow_store(),
-        });
+                config: LiveConfig::new(config),
+                orchestrator: Arc::new(Orchestrator::new(Arc::new(registry))),
+                knowledge: Arc::clone(&knowledge),
+                log_tx: test_log_tx(),
+                shadow_store: test_shadow_store(),
+                llm_providers: Default::default(),
+                oidc: None,
+            });
+        let post = |secret: &str, update_id: i64| {
+            let update = serde_json::json!({
+                "update_id": update_id,
+                "message": { "message_id": update_id, "chat": { "id": 4242, "type": "private" }, "text": "Hello there" }
+            });
+            Request::builder()
+                .method("POST")
+                .uri("/api/v1/channels/telegram/webhook")
+                .header("content-type", "application/json")
+                .header("X-Telegram-Bot-Api-Secret-Token", secret)
+                .body(Body::from(update.to_string()))
+                .unwrap()
+        };
+        let sent_texts = |calls: &Arc<std::sync::Mutex<Vec<(String, serde_json::Value)>>>| {
+            calls
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|(method, _)| method == "sendMessage" || method == "editMessageText")
+                .map(|(_, body)| body["text"].as_str().unwrap_or_default().to_string())
+                .collect::<Vec<_>>()
+        };
 
-        let mock_html = r#"<!DOCTYPE html>
-<html><body>
-<h1>Stockdale Fair 2025</h1>
-<h2>Local events this weekend</h2>
-<article><h2>Farmers Market Sunday</h2></article>
-</body></html>"#;
+        let res = app.clone().oneshot(post("wrong", 1)).await.unwrap();
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+        let res = app.clone().oneshot(post("tg-webhook-secret", 2)).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        for _ in 0..100 {
+            if !sent_texts(&calls).is_empty() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        let replies = sent_texts(&calls);
+        assert!(!replies.is_empty(), "the reply is sent to the chat");
+        assert!(calls.lock().unwrap().iter().all(|(_, body)| body["chat_id"] == 4242));
+        let conversations = ConversationManager::new(Arc::clone(&knowledge));
+        let history = || conversations.history("acme", pagi_core::DEFAULT_AGENT_ID, "telegram-4242", 10).unwrap();
+        for _ in 0..100 {
+            if !history().is_empty() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        let turns = history();
+        assert_eq!(turns.len(), 1, "the exchange is recorded in the chat's session");
+        assert_eq!(turns[0].user, "Hello there");
 
-        let scrape_body = serde_json::json!({
-            "tenant_id": "test-tenant",
-            "goal": {
-                "ExecuteSkill": {
-                    "name": "CommunityScraper",
-                    "payload": {
-                        "url": "https://example.com/local-news",
-                        "html": mock_html
-                    }
-                }
+        let res = app.oneshot(post("tg-webhook-secret", 3)).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        for _ in 0..100 {
+            if sent_texts(&calls).iter().any(|t| t.contains("too fast")) {
+                break;
             }
-        });
-        let scrape_req = Request::builder()
-            .method("POST")
-            .uri("/v1/execute")
-            .header("content-type", "application/json")
-            .body(Body::from(serde_json::to_string(&scrape_body).unwrap()))
-            .unwrap();
-        let scrape_res = app.clone().oneshot(scrape_req).await.unwrap();
-        assert_eq!(scrape_res.status(), StatusCode::OK);
-        let scrape_bytes = axum::body::to_bytes(scrape_res.into_body(), usize::MAX).await.unwrap();
-        let scrape_json: serde_json::Value = serde_json::from_slice(&scrape_bytes).unwrap();
-        assert_eq!(scrape_json["status"], "ok");
-        assert_eq!(scrape_json["skill"], "CommunityScraper");
-        assert_eq!(scrape_json["slot_id"], 5);
-        assert!(scrape_json["event"].as_str().unwrap().contains("Stockdale Fair 2025"));
-        assert!(scrape_json["event"].as_str().unwrap().contains("Local events this weekend"));
-        assert!(scrape_json["event"].as_str().unwrap().contains("Farmers Market Sunday"));
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        assert!(sent_texts(&calls).iter().any(|t| t.contains("too fast")), "the second message is over the chat's rate limit");
+    }
 
-        let query_body = serde_json::json!({
-            "tenant_id": "test-tenant",
-            "goal": {
-                "QueryKnowledge": { "slot_id": 5, "query": "current_pulse" }
+    #[tokio::test]
+    async fn test_knowledge_export_imports_into_another_gateway() {
+        let source = Arc::new(KnowledgeStore::open_temporary().unwrap());
+        let target = Arc::new(KnowledgeStore::open_temporary().unwrap());
+        source.insert(3, "research/roofing", b"Metal roofs last 50 years").unwrap();
+        source.insert(3, "research/raw", &[0xff, 0x00, 0x7f]).unwrap();
+        source.insert(4, "fact", b"kept out of a slot 3 export").unwrap();
+        let router = |knowledge: &Arc<KnowledgeStore>| {
+            Router::new()
+                .route("/api/v1/knowledge/export", get(handlers::export::export_knowledge))
+                .route("/api/v1/knowledge/import", post(handlers::export::import_knowledge))
+                .with_state(AppState {
+                    config: LiveConfig::new(test_config()),
+                    orchestrator: Arc::new(Orchestrator::new(Arc::new(SkillRegistry::new()))),
+                    knowledge: Arc::clone(knowledge),
+                    log_tx: test_log_tx(),
+                    shadow_store: test_shadow_store(),
+                    llm_providers: Default::default(),
+                    oidc: None,
+                })
+        };
+        let call = |app: Router, method: &str, uri: &str, body: Option<serde_json::Value>| {
+            let request = Request::builder()
+                .method(method)
+                .uri(uri)
+                .header("content-type", "application/json")
+                .body(body.map_or_else(Body::empty, |b| Body::from(b.to_string())))
+                .unwrap();
+            async move {
+                let res = app.oneshot(request).await.unwrap();
+                let status = res.status();
+                let body: serde_json::Value =
+                    serde_json::from_slice(&axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap()).unwrap();
+                (status, body)
             }
-        });
-        let query_req = Request::builder()
-            .method("POST")
-            .uri("/v1/execute")
-            .header("content-type", "application/json")
-            .body(Body::from(serde_json::to_string(&query_body).unwrap()))
-            .unwrap();
-        let query_res = app.oneshot(query_req).await.unwrap();
-        assert_eq!(query_res.status(), StatusCode::OK);
-        let query_bytes = axum::body::to_bytes(query_res.into_body(), usize::MAX).await.unwrap();
-        let query_json: serde_json::Value = serde_json::from_slice(&query_bytes).unwrap();
-        assert_eq!(query_json["status"], "ok");
-        assert_eq!(query_json["slot_id"], 5);
-        assert_eq!(query_json["query_key"], "current_pulse");
-        let value = query_json["value"].as_str().expect("current_pulse value");
-        let pulse: serde_json::Value = serde_json::from_str(value).unwrap();
-        assert_eq!(pulse["location"], "Stockdale");
-        assert_eq!(pulse["trend"], "Scraped");
-        assert!(pulse["event"].as_str().unwrap().contains("Stockdale Fair 2025"));
+        };
+
+        let (status, _) = call(router(&source), "GET", "/api/v1/knowledge/export?slots=3,9", None).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let (status, export) = call(router(&source), "GET", "/api/v1/knowledge/export?slots=3", None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(export["format"], "pagi-knowledge-export");
+        let records = export["records"].as_array().unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0]["key"], "research/raw");
+        assert_eq!(records[0]["value_b64"], "/wB/");
+        assert_eq!(records[1]["value"], "Metal roofs last 50 years");
+
+        let (status, report) = call(router(&target), "POST", "/api/v1/knowledge/import", Some(export)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(report["imported"], 2);
+        assert_eq!(target.get(3, "research/raw").unwrap().unwrap(), vec![0xff, 0x00, 0x7f]);
+        assert_eq!(target.get(3, "research/roofing").unwrap().unwrap(), b"Metal roofs last 50 years".to_vec());
+        assert!(target.get(4, "fact").unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_requests_are_logged_with_status_and_sizes() {
+        let (tx, mut rx) = broadcast::channel(16);
+        let _guard = tracing::subscriber::set_default(tracing_subscriber::registry().with(logging::LogLayer::new(tx)));
+        let live = LiveConfig::new(test_config());
+        let app = Router::new()
+            .route("/api/v1/echo", post(|body: String| async move { body }))
+            .route("/api/v1/health", get(health))
+            .layer(axum::middleware::from_fn_with_state(live.clone(), handlers::request_log::log_request));
+        let echo = || Request::builder().method("POST").uri("/api/v1/echo").header("content-length", "5").body(Body::from("hello")).unwrap();
+
+        let res = app.clone().oneshot(echo()).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        let event = rx.try_recv().unwrap();
+        assert_eq!((event.target.as_str(), event.level.as_str()), ("pagi::http", "INFO"));
+        assert_eq!(event.text(), "INFO [pagi::http] POST /api/v1/echo 200");
+        for (field, value) in [("method", serde_json::json!("POST")), ("path", "/api/v1/echo".into()), ("status", 200.into()), ("request_bytes", 5.into()), ("response_bytes", 5.into())] {
+            assert_eq!(event.fields[field], value, "{}", field);
+        }
+        assert!(event.fields["latency_ms"].is_f64());
+
+        app.clone().oneshot(Request::builder().uri("/api/v1/health").body(Body::empty()).unwrap()).await.unwrap();
+        assert_eq!(rx.try_recv().unwrap().level, "DEBUG", "probes are logged at debug level");
+
+        let mut config = test_config();
+        config.logging.requests = false;
+        live.set(config);
+        app.oneshot(echo()).await.unwrap();
+        assert!(rx.try_recv().is_err(), "request logging follows the live config");
+    }
+
+    #[tokio::test]
+    async fn test_ready_reports_failed_checks_with_503() {
+        let queue = Arc::new(DispatchQueue::new(DispatchQueueConfig { max_concurrent: 1, max_queued: 0, ..Default::default() }));
+        let orchestrator = Arc::new(Orchestrator::new(Arc::new(SkillRegistry::new())).with_dispatch_queue(Arc::clone(&queue)));
+        let mut config = test_config();
+        config.storage_path = std::env::temp_dir().to_string_lossy().into_owned();
+        config.llm_providers = [("local".to_string(), pagi_core::LlmProviderConfig { kind: pagi_core::LlmProviderKind::Ollama, ..Default::default() })]
+            .into_iter()
+            .collect();
+        let llm_providers = Arc::new(LlmProviders::from_config(&config));
+        let live = LiveConfig::new(config);
+        let app = Router::new()
+            .route("/api/v1/ready", get(handlers::readiness::ready))
+            .with_state(AppState {
+                config: live.clone(),
+                orchestrator,
+                knowledge: Arc::new(KnowledgeStore::open_temporary().unwrap()),
+                log_tx: test_log_tx(),
+                shadow_store: test_shadow_store(),
+                llm_providers: Arc::clone(&llm_providers),
+                oidc: None,
+            });
+        let ready = || async {
+            let res = app.clone().oneshot(Request::builder().uri("/api/v1/ready").body(Body::empty()).unwrap()).await.unwrap();
+            let status = res.status();
+            let bytes = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+            (status, serde_json::from_slice::<serde_json::Value>(&bytes).unwrap())
+        };
+
+        let (status, json) = ready().await;
+        assert_eq!(status, StatusCode::OK, "{}", json);
+        let names: Vec<&str> = json["checks"].as_array().unwrap().iter().filter_map(|c| c["name"].as_str()).collect();
+        assert_eq!(names, ["knowledge", "shadow_vault", "llm_providers", "dispatch_queue", "disk"]);
+
+        for _ in 0..pagi_skills::DOWN_AFTER_FAILURES {
+            llm_providers.record_failure("local", "connection refused");
+        }
+        let (status, json) = ready().await;
+        assert_eq!(status, StatusCode::OK, "a mock gateway does not need its providers: {}", json);
+        let mut config = CoreConfig::clone(&live.get());
+        config.llm_mode = "live".to_string();
+        live.set(config);
+        let _permit = queue.admit("acme").await.unwrap();
+        let (status, json) = ready().await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(json["status"], "not_ready");
+        let failures: Vec<&str> = json["failures"].as_array().unwrap().iter().filter_map(|f| f.as_str()).collect();
+        assert_eq!(failures.len(), 2, "{:?}", failures);
+        assert!(failures[0].starts_with("llm_providers: every LLM provider is down: local"), "{:?}", failures);
+        assert!(failures[1].starts_with("dispatch_queue: all 1 execution slots busy"), "{:?}", failures);
+    }
+
+    #[tokio::test]
+    async fn test_reload_config_applies_hot_fields_and_reports_the_rest() {
+        let dir = std::env::temp_dir().join(format!("pagi_reload_config_test_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("gateway.toml");
+        std::fs::write(
+            &path,
+            "app_name = \"Test Gateway\"\nport = 8002\nllm_mode = \"live\"\ntick_rate_secs = 2\n[slot_labels]\n1 = \"Brand Voice\"\n",
+        )
+        .unwrap();
+        let orchestrator = Arc::new(Orchestrator::new(Arc::new(SkillRegistry::new())));
+        let mut control = orchestrator.subscribe_control();
+        let config = LiveConfig::new(test_config()).with_sources(pagi_core::ConfigSources {
+            file: Some(path.to_string_lossy().into_owned()),
+            ..Default::default()
+        });
+        let app = Router::new()
+            .route("/v1/status", get(status))
+            .route("/api/v1/admin/reload-config", post(handlers::reload::reload_config))
+            .with_state(AppState {
+                config: config.clone(),
+                orchestrator,
+                knowledge: Arc::new(KnowledgeStore::open_temporary().unwrap()),
+                log_tx: test_log_tx(),
+                shadow_store: test_shadow_store(),
+                llm_providers: Default::default(),
+                oidc: None,
+            });
+        let reload = || Request::builder().method("POST").uri("/api/v1/admin/reload-config").body(Body::empty()).unwrap();
+
+        let res = app.clone().oneshot(reload()).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        let bytes = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(json["applied"], serde_json::json!(["slot_labels", "tick_rate_secs"]));
+        assert_eq!(json["requires_restart"], serde_json::json!(["llm_mode", "port"]));
+        assert_eq!(config.get().tick_rate(), std::time::Duration::from_secs(2));
+        assert_eq!((config.get().port, config.get().llm_mode.as_str()), (8001, "mock"), "restart-only fields keep running values");
+        assert!(matches!(control.try_recv(), Ok(pagi_core::ControlPanelMessage::FullState { .. })));
+
+        let res = app.clone().oneshot(Request::builder().uri("/v1/status").body(Body::empty()).unwrap()).await.unwrap();
+        let bytes = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(json["slot_labels"]["1"], "Brand Voice");
+
+        std::fs::write(&path, "app_name = \"Test Gateway\"\nport = 8001\nllm_mode = \"fast\"\n").unwrap();
+        let res = app.oneshot(reload()).await.unwrap();
+        assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(config.get().slot_labels.get("1").map(String::as_str), Some("Brand Voice"), "a bad config changes nothing");
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_sources_are_registered_and_listed_with_freshness() {
+        let knowledge = Arc::new(KnowledgeStore::open_temporary().unwrap());
+        let orchestrator = Arc::new(Orchestrator::new(Arc::new(SkillRegistry::new())));
+        let app = Router::new()
+            .route("/api/v1/sources", get(handlers::sources::list_sources).post(handlers::sources::create_source))
+            .with_state(AppState {
+                config: LiveConfig::new(test_config()),
+                orchestrator,
+                knowledge: Arc::clone(&knowledge),
+                log_tx: test_log_tx(),
+                shadow_store: test_shadow_store(),
+                llm_providers: Default::default(),
+                oidc: None,
+            });
+        let post = |body: serde_json::Value| {
+            Request::builder()
+                .method("POST")
+                .uri("/api/v1/sources")
+                .header("content-type", "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap()
+        };
+
+        let res = app
+            .clone()
+            .oneshot(post(serde_json::json!({ "url": "https://example.com/news", "every": "sometimes" })))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+        let res = app
+            .clone()
+            .oneshot(post(serde_json::json!({ "url": "https://example.com/news", "every": "6h", "tenant_id": "acme" })))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::CREATED);
+
+        let res = app
+            .oneshot(Request::builder().uri("/api/v1/sources").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let bytes = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!((json["count"].as_u64(), json["stale"].as_u64()), (Some(1), Some(1)));
+        let source = &json["sources"][0];
+        assert_eq!(source["slot_id"], 5);
+        assert_eq!(source["interval_ms"], 6 * 60 * 60 * 1000);
+        assert_eq!(source["ctx"]["tenant_id"], "acme");
+        assert_eq!(source["fresh"], false);
+        assert!(source["last_success_ms"].is_null());
+        let stored = SourceRegistry::new(knowledge).get(source["id"].as_str().unwrap()).unwrap();
+        assert_eq!(stored.url, "https://example.com/news");
+    }
+
+    #[tokio::test]
+    async fn test_documents_are_uploaded_chunked_and_removed() {
+        let knowledge = Arc::new(KnowledgeStore::open_temporary().unwrap());
+        let orchestrator = Arc::new(Orchestrator::new(Arc::new(SkillRegistry::new())));
+        let app = Router::new()
+            .route(
+                "/api/v1/kb/:slot/documents",
+                get(handlers::documents::list_documents).post(handlers::documents::upload_documents),
+            )
+            .route("/api/v1/kb/:slot/documents/:document_id", delete(handlers::documents::delete_document))
+            .with_state(AppState {
+                config: LiveConfig::new(test_config()),
+                orchestrator,
+                knowledge: Arc::clone(&knowledge),
+                log_tx: test_log_tx(),
+                shadow_store: test_shadow_store(),
+                llm_providers: Default::default(),
+                oidc: None,
+            });
+        let upload = |uri: &str, filename: &str, content_type: &str, content: &str| {
+            let body = format!(
+                "--XBOUNDARY\r\nContent-Disposition: form-data; name=\"file\"; filename=\"{}\"\r\nContent-Type: {}\r\n\r\n{}\r\n--XBOUNDARY--\r\n",
+                filename, content_type, content
+            );
+            Request::builder()
+                .method("POST")
+                .uri(uri)
+                .header("content-type", "multipart/form-data; boundary=XBOUNDARY")
+                .body(Body::from(body))
+                .unwrap()
+        };
+        let json = |res: axum::response::Response| async move {
+            let bytes = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+            serde_json::from_slice::<serde_json::Value>(&bytes).unwrap()
+        };
+
+        let policy = "# Travel policy\n\nBook economy for flights under six hours.\n\n".repeat(40);
+        let res = app
+            .clone()
+            .oneshot(upload("/api/v1/kb/3/documents?tenant_id=acme&chunk_chars=400", "travel.md", "text/plain", &policy))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::CREATED);
+        let body = json(res).await;
+        let document = &body["documents"][0];
+        assert_eq!((document["format"].as_str(), document["filename"].as_str()), (Some("markdown"), Some("travel.md")));
+        let chunks = document["chunks"].as_u64().unwrap();
+        assert!(chunks > 3);
+        let document_id = document["document_id"].as_str().unwrap().to_string();
+        let kb = knowledge.tenant("acme").unwrap();
+        let chunk = kb.get_record(3, &format!("doc/{}/00000", document_id)).unwrap().unwrap();
+        assert!(chunk.content.starts_with("Travel policy"));
+        assert!(chunk.embedding.is_some());
+
+        let res = app
+            .clone()
+            .oneshot(upload("/api/v1/kb/3/documents", "photo.png", "image/png", "PNG"))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+        let res = app
+            .clone()
+            .oneshot(upload("/api/v1/kb/9/documents", "a.txt", "text/plain", "hello"))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+        let res = app
+            .clone()
+            .oneshot(Request::builder().uri("/api/v1/kb/3/documents?tenant_id=acme").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(json(res).await["count"], 1);
+        let delete = |uri: String| Request::builder().method("DELETE").uri(uri).body(Body::empty()).unwrap();
+        let res = app
+            .clone()
+            .oneshot(delete(format!("/api/v1/kb/3/documents/{}?tenant_id=acme", document_id)))
+            .await
+            .unwrap();
+        assert_eq!(json(res).await["records"].as_u64(), Some(chunks + 1));
+        let res = app
+            .oneshot(delete(format!("/api/v1/kb/3/documents/{}?tenant_id=acme", document_id)))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_frontend_index_served_when_enabled() {
+        let knowledge = Arc::new(
+            KnowledgeStore::open_path("./data/pagi_frontend_index_test").unwrap(),
+        );
+        let orchestrator = Arc::new(Orchestrator::new(Arc::new(SkillRegistry::new())));
+
+        let config = CoreConfig {
+            app_name: "Test UI".to_string(),
+            port: 0,
+            storage_path: "./data".to_string(),
+            llm_mode: "mock".to_string(),
+            frontend_enabled: true,
+            slot_labels: std::collections::HashMap::new(),
+            storage_backend: Default::default(),
+            llm_providers: Default::default(),
+            model_aliases: Default::default(),
+            token_budgets: Default::default(),
+            redaction: Default::default(),
+            oidc: Default::default(),
+            limits: Default::default(),
+            encryption: Default::default(),
+            secrets: Default::default(),
+            scraper: Default::default(),
+            rag: Default::default(),
+            ingest: Default::default(),
+            email: Default::default(),
+            chat: Default::default(),
+            channels: Default::default(),
+            calendar: Default::default(),
+            tick_rate_secs: None,
+            bind_addresses: Vec::new(),
+            tls: Default::default(),
+            logging: Default::default(),
+            simulation: Default::default(),
+            webhooks: Default::default(),
+        };
+
+        let app = build_app(AppState {
+            config: LiveConfig::new(config),
+            orchestrator,
+            knowledge: Arc::clone(&knowledge),
+            log_tx: test_log_tx(),
+            shadow_store: test_shadow_store(),
+            llm_providers: Default::default(),
+            oidc: None,
+        });
+
+        let req = Request::builder()
+            .method("GET")
+            .uri("/")
+            .body(Body::empty())
+            .unwrap();
+
+        let res = app.oneshot(req).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let bytes = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+        let body = String::from_utf8_lossy(&bytes);
+        assert!(body.contains("PAGI Gateway UI"), "Drop-In UI title should be present");
+        assert!(
+            body.contains("Drop your AI Studio") || body.contains("pagi-frontend"),
+            "Drop-In UI hint should be reachable when enabled; got body len {}",
+            body.len()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_kb1_brand_voice_retrieve() {
+        let knowledge = Arc::new(
+            KnowledgeStore::open_path("./data/pagi_knowledge_test")
+                .unwrap(),
+        );
+        knowledge
+            .insert(1, "brand_voice", b"Friendly and professional")
+            .unwrap();
+
+        let mut registry = SkillRegistry::new();
+        registry.register(Arc::new(KnowledgeQuery::new(Arc::clone(&knowledge))));
+        let orchestrator = Arc::new(Orchestrator::new(Arc::new(registry)));
+        let app = Router::new()
+            .route("/v1/execute", post(execute))
+            .with_state(AppState {
+            config: LiveConfig::new(test_config()),
+            orchestrator,
+            knowledge,
+            log_tx: test_log_tx(),
+            shadow_store: test_shadow_store(),
+            llm_providers: Default::default(),
+            oidc: None,
+        });
+
+        // Records written straight to the store are the default tenant's.
+        let body = serde_json::json!({
+            "tenant_id": "default",
+            "goal": {
+                "QueryKnowledge": { "slot_id": 1, "query": "brand_voice" }
+            }
+        });
+        let req = Request::builder()
+            .method("POST")
+            .uri("/v1/execute")
+            .header("content-type", "application/json")
+            .body(Body::from(serde_json::to_string(&body).unwrap()))
+            .unwrap();
+        let res = app.oneshot(req).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        let bytes = axum::body::to_bytes(res.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(json["status"], "ok");
+        assert_eq!(json["skill"], "KnowledgeQuery");
+        assert_eq!(json["slot_id"], 1);
+        assert_eq!(json["query_key"], "brand_voice");
+        assert_eq!(json["value"], "Friendly and professional");
+    }
+
+    #[tokio::test]
+    async fn test_chronos_episodic_memory_and_recall_past_actions() {
+        let knowledge = Arc::new(
+            KnowledgeStore::open_path("./data/pagi_chronos_recall_test").unwrap(),
+        );
+        knowledge.insert(1, "test_key", b"test_value").unwrap();
+        let mut registry = SkillRegistry::new();
+        registry.register(Arc::new(KnowledgeQuery::new(Arc::clone(&knowledge))));
+        registry.register(Arc::new(RecallPastActions::new(Arc::clone(&knowledge))));
+        let orchestrator = Arc::new(Orchestrator::new(Arc::new(registry)));
+        let app = Router::new()
+            .route("/v1/execute", post(execute))
+            .with_state(AppState {
+                config: LiveConfig::new(test_config()),
+                orchestrator,
+                knowledge: Arc::clone(&knowledge),
+                log_tx: test_log_tx(),
+                shadow_store: test_shadow_store(),
+                llm_providers: Default::default(),
+                oidc: None,
+            });
+
+        let query_body = serde_json::json!({
+            "tenant_id": "test-tenant",
+            "goal": { "QueryKnowledge": { "slot_id": 1, "query": "test_key" } }
+        });
+        let query_req = Request::builder()
+            .method("POST")
+            .uri("/v1/execute")
+            .header("content-type", "application/json")
+            .body(Body::from(serde_json::to_string(&query_body).unwrap()))
+            .unwrap();
+        let query_res = app.clone().oneshot(query_req).await.unwrap();
+        assert_eq!(query_res.status(), StatusCode::OK);
+
+        let recall_body = serde_json::json!({
+            "tenant_id": "test-tenant",
+            "goal": {
+                "ExecuteSkill": {
+                    "name": "recall_past_actions",
+                    "payload": { "limit": 5 }
+                }
+            }
+        });
+        let recall_req = Request::builder()
+            .method("POST")
+            .uri("/v1/execute")
+            .header("content-type", "application/json")
+            .body(Body::from(serde_json::to_string(&recall_body).unwrap()))
+            .unwrap();
+        let recall_res = app.oneshot(recall_req).await.unwrap();
+        assert_eq!(recall_res.status(), StatusCode::OK);
+        let recall_bytes = axum::body::to_bytes(recall_res.into_body(), usize::MAX).await.unwrap();
+        let recall_json: serde_json::Value = serde_json::from_slice(&recall_bytes).unwrap();
+        assert_eq!(recall_json["status"], "ok");
+        assert_eq!(recall_json["skill"], "recall_past_actions");
+        let events = recall_json["events"].as_array().expect("events array");
+        assert!(!events.is_empty(), "Chronos should have at least one event after QueryKnowledge");
+        let has_query_event = events
+            .iter()
+            .any(|e| e["reflection"].as_str().unwrap_or("").contains("Queried"));
+        assert!(
+            has_query_event,
+            "Chronos should contain the QueryKnowledge event; got events: {:?}",
+            events
+        );
+    }
+
+    #[tokio::test]
+    async fn test_ethos_blocks_write_sandbox_with_mock_secret_and_logs_violation() {
+        let knowledge = Arc::new(
+            KnowledgeStore::open_path("./data/pagi_ethos_violation_test").unwrap(),
+        );
+        knowledge.set_ethos_policy(&PolicyRecord::default()).unwrap();
+        let mut registry = SkillRegistry::new();
+        registry.register(Arc::new(WriteSandboxFile::new()));
+        registry.register(Arc::new(RecallPastActions::new(Arc::clone(&knowledge))));
+        // The Ethos gate lives in the orchestrator's dispatch, which reads KB_ETHOS.
+        let orchestrator = Arc::new(Orchestrator::new(Arc::new(registry)).with_knowledge(Arc::clone(&knowledge)));
+        let app = Router::new()
+            .route("/v1/execute", post(execute))
+            .with_state(AppState {
+                config: LiveConfig::new(test_config()),
+                orchestrator,
+                knowledge: Arc::clone(&knowledge),
+                log_tx: test_log_tx(),
+                shadow_store: test_shadow_store(),
+                llm_providers: Default::default(),
+                oidc: None,
+            });
+
+        let write_body = serde_json::json!({
+            "tenant_id": "test-tenant",
+            "goal": {
+                "ExecuteSkill": {
+                    "name": "write_sandbox_file",
+                    "payload": {
+                        "path": "ethos_test.txt",
+                        "content": "Do not store: api_key=sk-12345 and password=secret123"
+                    }
+                }
+            }
+        });
+        let write_req = Request::builder()
+            .method("POST")
+            .uri("/v1/execute")
+            .header("content-type", "application/json")
+            .body(Body::from(serde_json::to_string(&write_body).unwrap()))
+            .unwrap();
+        let write_res = app.clone().oneshot(write_req).await.unwrap();
+        assert_eq!(write_res.status(), StatusCode::OK);
+        let write_bytes = axum::body::to_bytes(write_res.into_body(), usize::MAX).await.unwrap();
+        let write_json: serde_json::Value = serde_json::from_slice(&write_bytes).unwrap();
+        assert_eq!(
+            write_json["status"],
+            "policy_violation",
+            "Ethos should block write when content contains sensitive keywords; got: {:?}",
+            write_json
+        );
+        assert!(write_json["error"].as_str().unwrap_or("").contains("sensitive") || write_json["error"].as_str().unwrap_or("").contains("keyword"));
+
+        let recall_body = serde_json::json!({
+            "tenant_id": "test-tenant",
+            "goal": {
+                "ExecuteSkill": {
+                    "name": "recall_past_actions",
+                    "payload": { "limit": 5 }
+                }
+            }
+        });
+        let recall_req = Request::builder()
+            .method("POST")
+            .uri("/v1/execute")
+            .header("content-type", "application/json")
+            .body(Body::from(serde_json::to_string(&recall_body).unwrap()))
+            .unwrap();
+        let recall_res = app.oneshot(recall_req).await.unwrap();
+        assert_eq!(recall_res.status(), StatusCode::OK);
+        let recall_bytes = axum::body::to_bytes(recall_res.into_body(), usize::MAX).await.unwrap();
+        let recall_json: serde_json::Value = serde_json::from_slice(&recall_bytes).unwrap();
+        let events = recall_json["events"].as_array().expect("events array");
+        let has_violation = events
+            .iter()
+            .any(|e| e["reflection"].as_str().unwrap_or("").contains("Policy Violation"));
+        assert!(
+            has_violation,
+            "Chronos should contain a Policy Violation event; got events: {:?}",
+            events
+        );
+    }
+
+    #[tokio::test]
+    async fn test_kardia_sentiment_stored_and_chat_injects_context() {
+        let knowledge = Arc::new(
+            KnowledgeStore::open_path("./data/pagi_kardia_verify_test").unwrap(),
+        );
+        let mut registry = SkillRegistry::new();
+        registry.register(Arc::new(AnalyzeSentiment::new(Arc::clone(&knowledge))));
+        registry.register(Arc::new(ModelRouter::with_knowledge(Arc::clone(&knowledge))));
+        let orchestrator = Arc::new(Orchestrator::new(Arc::new(registry)));
+        let app = Router::new()
+            .route("/v1/execute", post(execute))
+            .route("/api/v1/kardia/:user_id", get(get_kardia_relation))
+            .route("/api/v1/chat", post(chat))
+            .with_state(AppState {
+                config: LiveConfig::new(test_config()),
+                orchestrator,
+                knowledge: Arc::clone(&knowledge),
+                log_tx: test_log_tx(),
+                shadow_store: test_shadow_store(),
+                llm_providers: Default::default(),
+                oidc: None,
+            });
+
+        let sentiment_body = serde_json::json!({
+            "tenant_id": "kardia-verify-user",
+            "goal": {
+                "ExecuteSkill": {
+                    "name": "analyze_sentiment",
+                    "payload": {
+                        "user_id": "kardia-verify-user",
+                        "messages": ["I am so angry", "This is terrible", "Nothing works"]
+                    }
+                }
+            }
+        });
+        let sentiment_req = Request::builder()
+            .method("POST")
+            .uri("/v1/execute")
+            .header("content-type", "application/json")
+            .body(Body::from(serde_json::to_string(&sentiment_body).unwrap()))
+            .unwrap();
+        let sentiment_res = app.clone().oneshot(sentiment_req).await.unwrap();
+        assert_eq!(sentiment_res.status(), StatusCode::OK);
+        let sentiment_json: serde_json::Value = serde_json::from_slice(
+            &axum::body::to_bytes(sentiment_res.into_body(), usize::MAX).await.unwrap(),
+        )
+        .unwrap();
+        assert_eq!(sentiment_json["status"], "ok");
+        assert_eq!(sentiment_json["last_sentiment"], "angry");
+
+        let kardia_req = Request::builder()
+            .method("GET")
+            .uri("/api/v1/kardia/kardia-verify-user")
+            .body(Body::empty())
+            .unwrap();
+        let kardia_res = app.clone().oneshot(kardia_req).await.unwrap();
+        assert_eq!(kardia_res.status(), StatusCode::OK);
+        let kardia_json: serde_json::Value = serde_json::from_slice(
+            &axum::body::to_bytes(kardia_res.into_body(), usize::MAX).await.unwrap(),
+        )
+        .unwrap();
+        assert_eq!(kardia_json["last_sentiment"], "angry");
+        assert_eq!(kardia_json["user_id"], "kardia-verify-user");
+
+        let chat_body = serde_json::json!({
+            "prompt": "How would you describe our current working relationship?",
+            "stream": false,
+            "user_alias": "kardia-verify-user"
+        });
+        let chat_req = Request::builder()
+            .method("POST")
+            .uri("/api/v1/chat")
+            .header("content-type", "application/json")
+            .body(Body::from(serde_json::to_string(&chat_body).unwrap()))
+            .unwrap();
+        let chat_res = app.oneshot(chat_req).await.unwrap();
+        assert_eq!(chat_res.status(), StatusCode::OK);
+        let chat_json: serde_json::Value = serde_json::from_slice(
+            &axum::body::to_bytes(chat_res.into_body(), usize::MAX).await.unwrap(),
+        )
+        .unwrap();
+        assert_eq!(chat_json["status"], "ok");
+        assert!(chat_json.get("response").and_then(|v| v.as_str()).unwrap_or("").len() > 0);
+    }
+
+    #[tokio::test]
+    async fn test_kb2_insert_and_retrieve_welcome_template() {
+        let knowledge = Arc::new(
+            KnowledgeStore::open_path("./data/pagi_kb2_test").unwrap(),
+        );
+        let mut registry = SkillRegistry::new();
+        registry.register(Arc::new(KnowledgeInsert::new(Arc::clone(&knowledge))));
+        registry.register(Arc::new(KnowledgeQuery::new(Arc::clone(&knowledge))));
+        let orchestrator = Arc::new(Orchestrator::new(Arc::new(registry)));
+        let app = Router::new()
+            .route("/v1/execute", post(execute))
+            .with_state(AppState {
+            config: LiveConfig::new(test_config()),
+            orchestrator,
+            knowledge,
+            log_tx: test_log_tx(),
+            shadow_store: test_shadow_store(),
+            llm_providers: Default::default(),
+            oidc: None,
+        });
+
+        let insert_body = serde_json::json!({
+            "tenant_id": "test-tenant",
+            "goal": {
+                "ExecuteSkill": {
+                    "name": "KnowledgeInsert",
+                    "payload": {
+                        "slot_id": 2,
+                        "key": "welcome_email_template",
+                        "value": "Welcome! We're glad you reached out. A team member will follow up within 24 hours."
+                    }
+                }
+            }
+        });
+        let insert_req = Request::builder()
+            .method("POST")
+            .uri("/v1/execute")
+            .header("content-type", "application/json")
+            .body(Body::from(serde_json::to_string(&insert_body).unwrap()))
+            .unwrap();
+        let insert_res = app.clone().oneshot(insert_req).await.unwrap();
+        assert_eq!(insert_res.status(), StatusCode::OK);
+        let insert_bytes = axum::body::to_bytes(insert_res.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let insert_json: serde_json::Value = serde_json::from_slice(&insert_bytes).unwrap();
+        assert_eq!(insert_json["status"], "ok");
+        assert_eq!(insert_json["skill"], "KnowledgeInsert");
+        assert_eq!(insert_json["slot_id"], 2);
+        assert_eq!(insert_json["key"], "welcome_email_template");
+
+        let query_body = serde_json::json!({
+            "tenant_id": "test-tenant",
+            "goal": {
+                "QueryKnowledge": { "slot_id": 2, "query": "welcome_email_template" }
+            }
+        });
+        let query_req = Request::builder()
+            .method("POST")
+            .uri("/v1/execute")
+            .header("content-type", "application/json")
+            .body(Body::from(serde_json::to_string(&query_body).unwrap()))
+            .unwrap();
+        let query_res = app.oneshot(query_req).await.unwrap();
+        assert_eq!(query_res.status(), StatusCode::OK);
+        let query_bytes = axum::body::to_bytes(query_res.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let query_json: serde_json::Value = serde_json::from_slice(&query_bytes).unwrap();
+        assert_eq!(query_json["status"], "ok");
+        assert_eq!(query_json["skill"], "KnowledgeQuery");
+        assert_eq!(query_json["value"], "Welcome! We're glad you reached out. A team member will follow up within 24 hours.");
+    }
+
+    #[tokio::test]
+    async fn test_draft_response_includes_brand_voice_and_local_event() {
+        let memory = Arc::new(MemoryManager::open_path("./data/pagi_vault_draft_test").unwrap());
+        let knowledge = Arc::new(
+            KnowledgeStore::open_path("./data/pagi_knowledge_draft_test").unwrap(),
+        );
+
+        // Set Brand Voice in KB-1
+        knowledge.insert(1, "brand_voice", b"Warm, neighborly, and helpful").unwrap();
+
+        // Set Local Event in KB-5 via CommunityPulse
+        let mut registry = SkillRegistry::new();
+        registry.register(Arc::new(LeadCapture::new(Arc::clone(&memory))));
+        registry.register(Arc::new(KnowledgeInsert::new(Arc::clone(&knowledge))));
+        registry.register(Arc::new(CommunityPulse::new(Arc::clone(&knowledge))));
+        registry.register(Arc::new(DraftResponse::new(
+            Arc::clone(&memory),
+            Arc::clone(&knowledge),
+        )));
+        let orchestrator = Arc::new(Orchestrator::new(Arc::new(registry)));
+        let app = Router::new()
+            .route("/v1/execute", post(execute))
+            .with_state(AppState {
+            config: LiveConfig::new(test_config()),
+            orchestrator,
+            knowledge,
+            log_tx: test_log_tx(),
+            shadow_store: test_shadow_store(),
+            llm_providers: Default::default(),
+            oidc: None,
+        });
+
+        // 1. Capture a lead to get lead_id (IngestData)
+        let lead_body = serde_json::json!({
+            "tenant_id": "test-tenant",
+            "goal": {
+                "IngestData": {
+                    "payload": { "email": "customer@example.com", "message": "Interested in services" }
+                }
+            }
+        });
+        let lead_req = Request::builder()
+            .method("POST")
+            .uri("/v1/execute")
+            .header("content-type", "application/json")
+            .body(Body::from(serde_json::to_string(&lead_body).unwrap()))
+            .unwrap();
+        let lead_res = app.clone().oneshot(lead_req).await.unwrap();
+        assert_eq!(lead_res.status(), StatusCode::OK);
+        let lead_bytes = axum::body::to_bytes(lead_res.into_body(), usize::MAX).await.unwrap();
+        let lead_json: serde_json::Value = serde_json::from_slice(&lead_bytes).unwrap();
+        let lead_id = lead_json["lead_id"].as_str().unwrap().to_string();
+
+        // 2. Set Community Pulse (e.g. Strawberry Festival) in KB-5
+        let pulse_body = serde_json::json!({
+            "tenant_id": "test-tenant",
+            "goal": {
+                "ExecuteSkill": {
+                    "name": "CommunityPulse",
+                    "payload": {
+                        "location": "Stockdale",
+                        "trend": "rainy week",
+                        "event": "Strawberry Festival this weekend"
+                    }
+                }
+            }
+        });
+        let pulse_req = Request::builder()
+            .method("POST")
+            .uri("/v1/execute")
+            .header("content-type", "application/json")
+            .body(Body::from(serde_json::to_string(&pulse_body).unwrap()))
+            .unwrap();
+        let pulse_res = app.clone().oneshot(pulse_req).await.unwrap();
+        assert_eq!(pulse_res.status(), StatusCode::OK);
+
+        // 3. Execute AssembleContext (draft for this context_id)
+        let draft_body = serde_json::json!({
+            "tenant_id": "test-tenant",
+            "goal": {
+                "AssembleContext": { "context_id": lead_id }
+            }
+        });
+        let draft_req = Request::builder()
+            .method("POST")
+            .uri("/v1/execute")
+            .header("content-type", "application/json")
+            .body(Body::from(serde_json::to_string(&draft_body).unwrap()))
+            .unwrap();
+        let draft_res = app.oneshot(draft_req).await.unwrap();
+        assert_eq!(draft_res.status(), StatusCode::OK);
+        let draft_bytes = axum::body::to_bytes(draft_res.into_body(), usize::MAX).await.unwrap();
+        let draft_json: serde_json::Value = serde_json::from_slice(&draft_bytes).unwrap();
+        assert_eq!(draft_json["status"], "ok");
+        assert_eq!(draft_json["skill"], "DraftResponse");
+
+        let draft_text = draft_json["draft"].as_str().unwrap();
+        assert!(draft_text.contains("Warm, neighborly, and helpful"), "draft should include Brand Voice from KB-1");
+        assert!(draft_text.contains("Strawberry Festival this weekend"), "draft should include Local Event from KB-5");
+        assert!(draft_text.contains("Local Context:"), "draft should include Local Context section");
+    }
+
+    #[tokio::test]
+    async fn test_generate_final_response_chain_returns_generated_string() {
+        let memory = Arc::new(
+            MemoryManager::open_path("./data/pagi_vault_generate_test").unwrap(),
+        );
+        let knowledge = Arc::new(
+            KnowledgeStore::open_path("./data/pagi_knowledge_generate_test").unwrap(),
+        );
+        knowledge.insert(1, "brand_voice", b"Warm and professional").unwrap();
+
+        let mut registry = SkillRegistry::new();
+        registry.register(Arc::new(LeadCapture::new(Arc::clone(&memory))));
+        registry.register(Arc::new(DraftResponse::new(
+            Arc::clone(&memory),
+            Arc::clone(&knowledge),
+        )));
+        registry.register(Arc::new(ModelRouter::new()));
+        let orchestrator = Arc::new(Orchestrator::new(Arc::new(registry)));
+        let app = Router::new()
+            .route("/v1/execute", post(execute))
+            .with_state(AppState {
+            config: LiveConfig::new(test_config()),
+            orchestrator,
+            knowledge,
+            log_tx: test_log_tx(),
+            shadow_store: test_shadow_store(),
+            llm_providers: Default::default(),
+            oidc: None,
+        });
+
+        // 1. Capture a lead (IngestData)
+        let lead_body = serde_json::json!({
+            "tenant_id": "test-tenant",
+            "goal": {
+                "IngestData": {
+                    "payload": { "email": "guest@example.com", "message": "Hello" }
+                }
+            }
+        });
+        let lead_req = Request::builder()
+            .method("POST")
+            .uri("/v1/execute")
+            .header("content-type", "application/json")
+            .body(Body::from(serde_json::to_string(&lead_body).unwrap()))
+            .unwrap();
+        let lead_res = app.clone().oneshot(lead_req).await.unwrap();
+        assert_eq!(lead_res.status(), StatusCode::OK);
+        let lead_bytes = axum::body::to_bytes(lead_res.into_body(), usize::MAX).await.unwrap();
+        let lead_json: serde_json::Value = serde_json::from_slice(&lead_bytes).unwrap();
+        let lead_id = lead_json["lead_id"].as_str().unwrap().to_string();
+
+        // 2. Generate final response (AssembleContext -> ModelRouter chain)
+        let gen_body = serde_json::json!({
+            "tenant_id": "test-tenant",
+            "goal": {
+                "GenerateFinalResponse": { "context_id": lead_id }
+            }
+        });
+        let gen_req = Request::builder()
+            .method("POST")
+            .uri("/v1/execute")
+            .header("content-type", "application/json")
+            .body(Body::from(serde_json::to_string(&gen_body).unwrap()))
+            .unwrap();
+        let gen_res = app.oneshot(gen_req).await.unwrap();
+        assert_eq!(gen_res.status(), StatusCode::OK);
+        let gen_bytes = axum::body::to_bytes(gen_res.into_body(), usize::MAX).await.unwrap();
+        let gen_json: serde_json::Value = serde_json::from_slice(&gen_bytes).unwrap();
+
+        assert_eq!(gen_json["status"], "ok");
+        assert_eq!(gen_json["goal"], "GenerateFinalResponse");
+        assert_eq!(gen_json["context_id"], lead_id);
+        let generated = gen_json["generated"].as_str().expect("response must contain 'generated' string");
+        assert!(!generated.is_empty(), "generated text must not be empty");
+        assert!(
+            generated.contains("Generated") || generated.contains("personalized") || generated.contains("Thank you"),
+            "generated should be LLM-style output, not just the raw mock draft template"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_autonomous_goal_respond_to_lead_triggers_generation_chain() {
+        let memory = Arc::new(
+            MemoryManager::open_path("./data/pagi_vault_autonomous_test").unwrap(),
+        );
+        let knowledge = Arc::new(
+            KnowledgeStore::open_path("./data/pagi_knowledge_autonomous_test").unwrap(),
+        );
+        knowledge.insert(1, "brand_voice", b"Friendly and local").unwrap();
+
+        let mut registry = SkillRegistry::new();
+        registry.register(Arc::new(LeadCapture::new(Arc::clone(&memory))));
+        registry.register(Arc::new(DraftResponse::new(
+            Arc::clone(&memory),
+            Arc::clone(&knowledge),
+        )));
+        registry.register(Arc::new(SalesCloser::new(Arc::clone(&knowledge))));
+        registry.register(Arc::new(ModelRouter::new()));
+        registry.register(Arc::new(ResearchAudit::new(Arc::clone(&knowledge))));
+        let orchestrator = Arc::new(Orchestrator::new(Arc::new(registry)));
+        let app = Router::new()
+            .route("/v1/execute", post(execute))
+            .route("/v1/research/trace/:trace_id", get(get_research_trace))
+            .route("/v1/research/trace/:trace_id/replay", post(handlers::traces::replay_trace))
+            .with_state(AppState {
+            config: LiveConfig::new(test_config()),
+            orchestrator,
+            knowledge,
+            log_tx: test_log_tx(),
+            shadow_store: test_shadow_store(),
+            llm_providers: Default::default(),
+            oidc: None,
+        });
+
+        // 1. Capture a lead (IngestData)
+        let lead_body = serde_json::json!({
+            "tenant_id": "test-tenant",
+            "goal": {
+                "IngestData": {
+                    "payload": { "email": "neighbor@town.com", "message": "Interested in events" }
+                }
+            }
+        });
+        let lead_req = Request::builder()
+            .method("POST")
+            .uri("/v1/execute")
+            .header("content-type", "application/json")
+            .body(Body::from(serde_json::to_string(&lead_body).unwrap()))
+            .unwrap();
+        let lead_res = app.clone().oneshot(lead_req).await.unwrap();
+        assert_eq!(lead_res.status(), StatusCode::OK);
+        let lead_bytes = axum::body::to_bytes(lead_res.into_body(), usize::MAX).await.unwrap();
+        let lead_json: serde_json::Value = serde_json::from_slice(&lead_bytes).unwrap();
+        let lead_id = lead_json["lead_id"].as_str().unwrap().to_string();
+
+        // 2. AutonomousGoal "respond to lead" with context.lead_id
+        let autonomous_body = serde_json::json!({
+            "tenant_id": "test-tenant",
+            "goal": {
+                "AutonomousGoal": {
+                    "intent": "respond to lead",
+                    "context": { "lead_id": lead_id }
+                }
+            }
+        });
+        let autonomous_req = Request::builder()
+            .method("POST")
+            .uri("/v1/execute")
+            .header("content-type", "application/json")
+            .body(Body::from(serde_json::to_string(&autonomous_body).unwrap()))
+            .unwrap();
+        let autonomous_res = app.clone().oneshot(autonomous_req).await.unwrap();
+        assert_eq!(autonomous_res.status(), StatusCode::OK);
+        let autonomous_bytes = axum::body::to_bytes(autonomous_res.into_body(), usize::MAX).await.unwrap();
+        let auto_json: serde_json::Value = serde_json::from_slice(&autonomous_bytes).unwrap();
+
+        assert_eq!(auto_json["goal"], "AutonomousGoal");
+        assert_eq!(auto_json["intent"], "respond to lead");
+        assert_eq!(
+            auto_json["plan_steps"],
+            serde_json::json!(["DraftResponse", "SalesCloser", "ModelRouter"])
+        );
+        let generated = auto_json["generated"].as_str().expect("response must contain 'generated' from chain");
+        assert!(!generated.is_empty());
+        assert!(
+            generated.contains("Generated") || generated.contains("personalized") || generated.contains("Thank you"),
+            "autonomous chain should produce LLM-style generated text"
+        );
+        let trace_id = auto_json["trace_id"].as_str().expect("ResearchAudit should return trace_id");
+        assert!(!trace_id.is_empty());
+
+        // 3. Retrieve Thought Log from research endpoint
+        let trace_req = Request::builder()
+            .method("GET")
+            .uri(format!("/v1/research/trace/{}", trace_id))
+            .body(Body::empty())
+            .unwrap();
+        let trace_res = app.clone().oneshot(trace_req).await.unwrap();
+        assert_eq!(trace_res.status(), StatusCode::OK);
+        let trace_bytes = axum::body::to_bytes(trace_res.into_body(), usize::MAX).await.unwrap();
+        let trace_json: serde_json::Value = serde_json::from_slice(&trace_bytes).unwrap();
+        assert_eq!(trace_json["trace_id"], trace_id);
+        let trace_inner = &trace_json["trace"];
+        assert_eq!(trace_inner["intent"], "respond to lead");
+        assert_eq!(
+            trace_inner["plan_steps"],
+            serde_json::json!(["DraftResponse", "SalesCloser", "ModelRouter"])
+        );
+        let steps = trace_inner["steps"].as_array().expect("trace should have steps array");
+        assert_eq!(steps.len(), 3, "respond to lead has three steps");
+        assert_eq!(steps[0]["skill"], "DraftResponse");
+        assert_eq!(steps[1]["skill"], "SalesCloser");
+        assert_eq!(steps[2]["skill"], "ModelRouter");
+        assert!(trace_inner.get("final_result").is_some(), "trace should have final_result");
+
+        // 4. Replay it step by step against the same skills
+        let replay = |uri: String, body: serde_json::Value| {
+            Request::builder()
+                .method("POST")
+                .uri(uri)
+                .header("content-type", "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap()
+        };
+        let replay_res = app
+            .clone()
+            .oneshot(replay(format!("/v1/research/trace/{}/replay", trace_id), serde_json::json!({ "tenant_id": "test-tenant" })))
+            .await
+            .unwrap();
+        assert_eq!(replay_res.status(), StatusCode::OK);
+        let replay_bytes = axum::body::to_bytes(replay_res.into_body(), usize::MAX).await.unwrap();
+        let replay_json: serde_json::Value = serde_json::from_slice(&replay_bytes).unwrap();
+        assert_eq!(replay_json["replay"]["mode"], "recorded");
+        let replayed: Vec<&str> = replay_json["replay"]["steps"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|s| s["step"].as_str().unwrap())
+            .collect();
+        assert_eq!(replayed, ["DraftResponse", "SalesCloser", "ModelRouter"]);
+        assert_eq!(replay_json["replay"]["steps"][0]["diff"], "same", "{}", replay_json);
+
+        let missing = app
+            .oneshot(replay("/v1/research/trace/no-such-trace/replay".to_string(), serde_json::json!({ "mode": "chained" })))
+            .await
+            .unwrap();
+        assert_eq!(missing.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_community_scraper_extracts_event_and_saves_to_kb5() {
+        let knowledge = Arc::new(
+            KnowledgeStore::open_path("./data/pagi_knowledge_scraper_test").unwrap(),
+        );
+        let mut registry = SkillRegistry::new();
+        registry.register(Arc::new(CommunityScraper::new(Arc::clone(&knowledge))));
+        registry.register(Arc::new(KnowledgeQuery::new(Arc::clone(&knowledge))));
+        let orchestrator = Arc::new(Orchestrator::new(Arc::new(registry)));
+        let app = Router::new()
+            .route("/v1/execute", post(execute))
+            .with_state(AppState {
+            config: LiveConfig::new(test_config()),
+            orchestrator,
+            knowledge,
+            log_tx: test_log_tx(),
+            shadow_store: test_shadow_store(),
+            llm_providers: Default::default(),
+            oidc: None,
+        });
+
+        let mock_html = r#"<!DOCTYPE html>
+<html><body>
+<h1>Stockdale Fair 2025</h1>
+<h2>Local events this weekend</h2>
+<article><h2>Farmers Market Sunday</h2></article>
+</body></html>"#;
+
+        let scrape_body = serde_json::json!({
+            "tenant_id": "test-tenant",
+            "goal": {
+                "ExecuteSkill": {
+                    "name": "CommunityScraper",
+                    "payload": {
+                        "url": "https://example.com/local-news",
+                        "html": mock_html
+                    }
+                }
+            }
+        });
+        let scrape_req = Request::builder()
+            .method("POST")
+            .uri("/v1/execute")
+            .header("content-type", "application/json")
+            .body(Body::from(serde_json::to_string(&scrape_body).unwrap()))
+            .unwrap();
+        let scrape_res = app.clone().oneshot(scrape_req).await.unwrap();
+        assert_eq!(scrape_res.status(), StatusCode::OK);
+        let scrape_bytes = axum::body::to_bytes(scrape_res.into_body(), usize::MAX).await.unwrap();
+        let scrape_json: serde_json::Value = serde_json::from_slice(&scrape_bytes).unwrap();
+        assert_eq!(scrape_json["status"], "ok");
+        assert_eq!(scrape_json["skill"], "CommunityScraper");
+        assert_eq!(scrape_json["slot_id"], 5);
+        assert!(scrape_json["event"].as_str().unwrap().contains("Stockdale Fair 2025"));
+        assert!(scrape_json["event"].as_str().unwrap().contains("Local events this weekend"));
+        assert!(scrape_json["event"].as_str().unwrap().contains("Farmers Market Sunday"));
+
+        let query_body = serde_json::json!({
+            "tenant_id": "default",
+            "goal": {
+                "QueryKnowledge": { "slot_id": 5, "query": "current_pulse" }
+            }
+        });
+        let query_req = Request::builder()
+            .method("POST")
+            .uri("/v1/execute")
+            .header("content-type", "application/json")
+            .body(Body::from(serde_json::to_string(&query_body).unwrap()))
+            .unwrap();
+        let query_res = app.oneshot(query_req).await.unwrap();
+        assert_eq!(query_res.status(), StatusCode::OK);
+        let query_bytes = axum::body::to_bytes(query_res.into_body(), usize::MAX).await.unwrap();
+        let query_json: serde_json::Value = serde_json::from_slice(&query_bytes).unwrap();
+        assert_eq!(query_json["status"], "ok");
+        assert_eq!(query_json["slot_id"], 5);
+        assert_eq!(query_json["query_key"], "current_pulse");
+        let value = query_json["value"].as_str().expect("current_pulse value");
+        let pulse: serde_json::Value = serde_json::from_str(value).unwrap();
+        assert_eq!(pulse["location"], "Stockdale");
+        assert_eq!(pulse["trend"], "Scraped");
+        assert!(pulse["event"].as_str().unwrap().contains("Stockdale Fair 2025"));
     }
 
     #[tokio::test]
     async fn test_refresh_local_context_dispatches_community_scraper() {
         let knowledge = Arc::new(
-            KnowledgeStore::open_path("./data/pagi_knowledge_refresh_test").unwrap(),
+            KnowledgeStore::open_path("./data/pagi_knowledge_refresh_test").unwrap(),
+        );
+        let mut registry = SkillRegistry::new();
+        registry.register(Arc::new(CommunityScraper::new(Arc::clone(&knowledge))));
+        registry.register(Arc::new(KnowledgeQuery::new(Arc::clone(&knowledge))));
+        let orchestrator = Arc::new(Orchestrator::new(Arc::new(registry)));
+        let app = Router::new()
+            .route("/v1/execute", post(execute))
+            .with_state(AppState {
+            config: LiveConfig::new(test_config()),
+            orchestrator,
+            knowledge,
+            log_tx: test_log_tx(),
+            shadow_store: test_shadow_store(),
+            llm_providers: Default::default(),
+            oidc: None,
+        });
+
+        let mock_html = r#"<html><body><h1>Fall Festival Next Week</h1></body></html>"#;
+        let body = serde_json::json!({
+            "tenant_id": "test-tenant",
+            "goal": {
+                "UpdateKnowledgeSlot": {
+                    "slot_id": 5,
+                    "source_url": "https://example.com/news",
+                    "source_html": mock_html
+                }
+            }
+        });
+        let req = Request::builder()
+            .method("POST")
+            .uri("/v1/execute")
+            .header("content-type", "application/json")
+            .body(Body::from(serde_json::to_string(&body).unwrap()))
+            .unwrap();
+        let res = app.oneshot(req).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        let bytes = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(json["status"], "ok");
+        assert_eq!(json["skill"], "CommunityScraper");
+        assert!(json["event"].as_str().unwrap().contains("Fall Festival Next Week"));
+    }
+
+    #[tokio::test]
+    async fn test_sales_closer_cta_in_final_response() {
+        let memory = Arc::new(
+            MemoryManager::open_path("./data/pagi_vault_sales_test").unwrap(),
+        );
+        let knowledge = Arc::new(
+            KnowledgeStore::open_path("./data/pagi_knowledge_sales_test").unwrap(),
+        );
+        knowledge.insert(1, "brand_voice", b"Warm and professional").unwrap();
+        knowledge
+            .insert(2, "closing_strategy", b"Book a free consultation today")
+            .unwrap();
+
+        let mut registry = SkillRegistry::new();
+        registry.register(Arc::new(LeadCapture::new(Arc::clone(&memory))));
+        registry.register(Arc::new(DraftResponse::new(
+            Arc::clone(&memory),
+            Arc::clone(&knowledge),
+        )));
+        registry.register(Arc::new(SalesCloser::new(Arc::clone(&knowledge))));
+        registry.register(Arc::new(ModelRouter::new()));
+        let orchestrator = Arc::new(Orchestrator::new(Arc::new(registry)));
+        let app = Router::new()
+            .route("/v1/execute", post(execute))
+            .with_state(AppState {
+            config: LiveConfig::new(test_config()),
+            orchestrator,
+            knowledge,
+            log_tx: test_log_tx(),
+            shadow_store: test_shadow_store(),
+            llm_providers: Default::default(),
+            oidc: None,
+        });
+
+        let lead_body = serde_json::json!({
+            "tenant_id": "test-tenant",
+            "goal": {
+                "IngestData": {
+                    "payload": { "email": "lead@example.com", "message": "Interested in services" }
+                }
+            }
+        });
+        let lead_req = Request::builder()
+            .method("POST")
+            .uri("/v1/execute")
+            .header("content-type", "application/json")
+            .body(Body::from(serde_json::to_string(&lead_body).unwrap()))
+            .unwrap();
+        let lead_res = app.clone().oneshot(lead_req).await.unwrap();
+        assert_eq!(lead_res.status(), StatusCode::OK);
+        let lead_bytes = axum::body::to_bytes(lead_res.into_body(), usize::MAX).await.unwrap();
+        let lead_json: serde_json::Value = serde_json::from_slice(&lead_bytes).unwrap();
+        let lead_id = lead_json["lead_id"].as_str().unwrap().to_string();
+
+        let auto_body = serde_json::json!({
+            "tenant_id": "test-tenant",
+            "goal": {
+                "AutonomousGoal": {
+                    "intent": "respond to lead",
+                    "context": { "lead_id": lead_id }
+                }
+            }
+        });
+        let auto_req = Request::builder()
+            .method("POST")
+            .uri("/v1/execute")
+            .header("content-type", "application/json")
+            .body(Body::from(serde_json::to_string(&auto_body).unwrap()))
+            .unwrap();
+        let auto_res = app.oneshot(auto_req).await.unwrap();
+        assert_eq!(auto_res.status(), StatusCode::OK);
+        let auto_bytes = axum::body::to_bytes(auto_res.into_body(), usize::MAX).await.unwrap();
+        let auto_json: serde_json::Value = serde_json::from_slice(&auto_bytes).unwrap();
+        let generated = auto_json["generated"].as_str().expect("generated");
+        assert!(
+            generated.to_lowercase().contains("free consultation"),
+            "final generated response should include the KB-2 sales push (free consultation); got: {}",
+            generated
+        );
+    }
+
+    #[tokio::test]
+    async fn test_blueprint_alternate_intent_summarize_news() {
+        let knowledge = Arc::new(
+            KnowledgeStore::open_path("./data/pagi_knowledge_blueprint_test").unwrap(),
+        );
+        let mut registry = SkillRegistry::new();
+        registry.register(Arc::new(CommunityScraper::new(Arc::clone(&knowledge))));
+        registry.register(Arc::new(ModelRouter::new()));
+
+        let mut intents = std::collections::HashMap::new();
+        intents.insert(
+            "summarize news".to_string(),
+            vec!["CommunityScraper".to_string(), "ModelRouter".to_string()],
+        );
+        let blueprint = Arc::new(BlueprintRegistry::from_intents(intents));
+        let orchestrator = Arc::new(Orchestrator::with_blueprint(
+            Arc::new(registry),
+            Arc::clone(&blueprint),
+        ));
+        let app = Router::new()
+            .route("/v1/execute", post(execute))
+            .with_state(AppState {
+            config: LiveConfig::new(test_config()),
+            orchestrator,
+            knowledge,
+            log_tx: test_log_tx(),
+            shadow_store: test_shadow_store(),
+            llm_providers: Default::default(),
+            oidc: None,
+        });
+
+        let body = serde_json::json!({
+            "tenant_id": "test-tenant",
+            "goal": {
+                "AutonomousGoal": {
+                    "intent": "summarize news",
+                    "context": {
+                        "slot_id": 5,
+                        "html": "<html><body><h1>Local Election Results</h1><h2>Budget approved</h2></body></html>"
+                    }
+                }
+            }
+        });
+        let req = Request::builder()
+            .method("POST")
+            .uri("/v1/execute")
+            .header("content-type", "application/json")
+            .body(Body::from(serde_json::to_string(&body).unwrap()))
+            .unwrap();
+        let res = app.oneshot(req).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        let bytes = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(json["goal"], "AutonomousGoal");
+        assert_eq!(json["intent"], "summarize news");
+        assert_eq!(
+            json["plan_steps"],
+            serde_json::json!(["CommunityScraper", "ModelRouter"])
+        );
+        let generated = json["generated"].as_str().expect("generated");
+        assert!(
+            generated.contains("Election") || generated.contains("Budget") || generated.contains("personalized"),
+            "generated should reflect scraped content or mock; got: {}",
+            generated
+        );
+    }
+
+    /// Capabilities contract: the default blueprint and the shipped `config/blueprint.json` only
+    /// name skills the standard gateway wiring registers (a renamed skill fails here).
+    #[test]
+    fn test_capabilities_contract_holds_for_default_and_shipped_blueprints() {
+        let skills = registered_skill_names().unwrap();
+        if let Err(e) = check_capabilities("default blueprint", &BlueprintRegistry::default_blueprint(), &skills) {
+            panic!("{}", e);
+        }
+        let shipped = StdPath::new(env!("CARGO_MANIFEST_DIR")).join("../../config/blueprint.json");
+        assert!(shipped.exists(), "{}", shipped.display());
+        let blueprint = BlueprintRegistry::try_load_json_path(&shipped).unwrap();
+        if let Err(e) = check_capabilities("config/blueprint.json", &blueprint, &skills) {
+            panic!("{}", e);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_dispatch_exports_root_span_with_plan_step_children_and_propagates_context() {
+        use opentelemetry_sdk::trace::{InMemorySpanExporter, SdkTracerProvider};
+        use pagi_core::{Plan, PlanStep};
+
+        /// Returns the `traceparent` header ModelRouter would send from inside the step.
+        struct Traced(&'static str);
+
+        #[async_trait::async_trait]
+        impl AgentSkill for Traced {
+            fn name(&self) -> &str {
+                self.0
+            }
+
+            async fn execute(
+                &self,
+                _ctx: &TenantContext,
+                _payload: Option<serde_json::Value>,
+            ) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>> {
+                let headers = pagi_skills::trace_context_headers();
+                let traceparent = headers.get("traceparent").and_then(|v| v.to_str().ok());
+                Ok(serde_json::json!({ "traceparent": traceparent }))
+            }
+        }
+
+        let exporter = InMemorySpanExporter::default();
+        let provider = SdkTracerProvider::builder().with_simple_exporter(exporter.clone()).build();
+        telemetry::install(&provider);
+        let subscriber = tracing_subscriber::registry().with(telemetry::layer(&provider));
+        let _default = tracing::subscriber::set_default(subscriber);
+
+        let mut registry = SkillRegistry::new();
+        registry.register(Arc::new(Traced("First")));
+        registry.register(Arc::new(Traced("Second")));
+        let plans = std::collections::HashMap::from([(
+            "traced intent".to_string(),
+            Plan {
+                steps: vec![PlanStep::new("First"), PlanStep::new("Second")],
+            },
+        )]);
+        let orchestrator =
+            Orchestrator::with_blueprint(Arc::new(registry), Arc::new(BlueprintRegistry::from_plans(plans)));
+        let ctx = TenantContext {
+            tenant_id: "otel".into(),
+            correlation_id: Some("corr-otel".into()),
+            agent_id: None,
+            ..Default::default()
+        };
+        let result = orchestrator
+            .dispatch(&ctx, Goal::AutonomousGoal { intent: "traced intent".into(), context: None })
+            .await
+            .unwrap();
+
+        let spans = exporter.get_finished_spans().unwrap();
+        let root = spans.iter().find(|s| s.name == "dispatch AutonomousGoal").expect("dispatch span");
+        assert!(!spans.iter().any(|s| s.span_context.span_id() == root.parent_span_id));
+        assert!(root
+            .attributes
+            .iter()
+            .any(|kv| kv.key.as_str() == "correlation_id" && kv.value.as_str() == "corr-otel"));
+        let trace_id = root.span_context.trace_id();
+        let steps: Vec<_> = spans.iter().filter(|s| s.name.starts_with("step ")).collect();
+        assert_eq!(steps.len(), 2);
+        for step in &steps {
+            assert_eq!(step.span_context.trace_id(), trace_id);
+            assert_eq!(step.parent_span_id, root.span_context.span_id());
+        }
+        // The last step's outgoing headers name the Second step span as parent.
+        let second = steps.iter().find(|s| s.name == "step Second").unwrap();
+        let traceparent = result["traceparent"].as_str().expect("traceparent injected");
+        assert_eq!(traceparent, format!("00-{}-{}-01", trace_id, second.span_context.span_id()));
+    }
+
+    #[test]
+    fn test_report_chat_error_hides_details_and_records_chronos() {
+        let knowledge = Arc::new(
+            KnowledgeStore::open_path("./data/pagi_knowledge_chat_error_test").unwrap(),
+        );
+        let ctx = TenantContext {
+            tenant_id: "t".into(),
+            correlation_id: Some("corr-42".into()),
+            agent_id: Some("chat_error_agent".into()),
+            ..Default::default()
+        };
+        let err: Box<dyn std::error::Error + Send + Sync> =
+            "OpenRouter API error (429 Too Many Requests): upstream quota key=sk-secret".into();
+        let user_error = report_chat_error(&knowledge, &ctx, err.as_ref());
+        assert_eq!(user_error.code, "rate_limited");
+        assert!(user_error.retryable);
+        assert_eq!(user_error.correlation_id.as_deref(), Some("corr-42"));
+        assert!(!user_error.display_message().contains("sk-secret"));
+        assert!(user_error.display_message().contains("corr-42"));
+
+        let events = knowledge.get_recent_chronos_events("chat_error_agent", 5).unwrap();
+        let event = events.iter().find(|e| e.reflection.contains("corr-42")).unwrap();
+        assert!(event.reflection.contains("sk-secret"));
+        assert_eq!(event.outcome.as_deref(), Some("error:rate_limited"));
+    }
+
+    /// Parses an SSE body into (event, data) pairs.
+    fn sse_events(body: &[u8]) -> Vec<(String, serde_json::Value)> {
+        String::from_utf8_lossy(body)
+            .split("\n\n")
+            .filter_map(|frame| {
+                let field = |name: &str| {
+                    frame
+                        .lines()
+                        .find_map(|l| l.strip_prefix(name).map(|v| v.trim_start().to_string()))
+                };
+                Some((field("event:")?, serde_json::from_str(&field("data:")?).ok()?))
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_chat_stream_emits_typed_events() {
+        let knowledge = Arc::new(KnowledgeStore::open_temporary().unwrap());
+        let chat_app = |registry: SkillRegistry| {
+            Router::new()
+                .route("/api/v1/chat/stream", post(chat_events))
+                .with_state(AppState {
+                    config: LiveConfig::new(test_config()),
+                    orchestrator: Arc::new(Orchestrator::new(Arc::new(registry))),
+                    knowledge: Arc::clone(&knowledge),
+                    log_tx: test_log_tx(),
+                    shadow_store: test_shadow_store(),
+                    llm_providers: Default::default(),
+                    oidc: None,
+                })
+        };
+        let request = || {
+            Request::builder()
+                .method("POST")
+                .uri("/api/v1/chat/stream")
+                .header("content-type", "application/json")
+                .body(Body::from(r#"{"prompt":"hi","user_alias":"sse-user"}"#))
+                .unwrap()
+        };
+
+        let mut registry = SkillRegistry::new();
+        registry.register(Arc::new(ModelRouter::with_mode(LlmMode::Mock)));
+        let res = chat_app(registry).oneshot(request()).await.unwrap();
+        assert_eq!(res.headers()["content-type"], "text/event-stream");
+        let events = sse_events(&axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap());
+        let (last, deltas) = events.split_last().unwrap();
+        assert!(!deltas.is_empty());
+        assert!(deltas.iter().all(|(kind, data)| kind == "delta" && data["text"].is_string()));
+        assert_eq!(last.0, "done");
+        assert_eq!(last.1["status"], "ok");
+        assert_eq!(last.1["model"], "mock");
+        assert!(last.1["latency_ms"].is_u64());
+        let streamed: String = deltas.iter().map(|(_, d)| d["text"].as_str().unwrap()).collect();
+        assert_eq!(last.1["chars"], streamed.chars().count());
+
+        // No ModelRouter registered: a translated error event, then done.
+        let res = chat_app(SkillRegistry::new()).oneshot(request()).await.unwrap();
+        let events = sse_events(&axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap());
+        let kinds: Vec<&str> = events.iter().map(|(k, _)| k.as_str()).collect();
+        assert_eq!(kinds, ["error", "done"]);
+        assert_eq!(events[0].1["error_code"], "skill_unavailable");
+        assert_eq!(events[1].1["status"], "error");
+        assert_eq!(events[0].1["correlation_id"], events[1].1["correlation_id"]);
+    }
+
+    #[tokio::test]
+    async fn test_rag_chat_cites_the_tenants_most_similar_records() {
+        /// Keeps every system prompt it was sent.
+        struct Recorder(Arc<std::sync::Mutex<Vec<String>>>);
+
+        #[async_trait::async_trait]
+        impl AgentSkill for Recorder {
+            fn name(&self) -> &str {
+                "ModelRouter"
+            }
+
+            async fn execute(
+                &self,
+                _ctx: &TenantContext,
+                payload: Option<serde_json::Value>,
+            ) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>> {
+                let payload = payload.unwrap_or_default();
+                self.0.lock().unwrap().push(payload["system_prompt"].as_str().unwrap_or("").to_string());
+                Ok(serde_json::json!({ "status": "ok", "generated": "Economy, see [1]." }))
+            }
+        }
+
+        let knowledge = Arc::new(KnowledgeStore::open_temporary().unwrap());
+        let router = ModelRouter::with_mode(LlmMode::Mock);
+        let question = "Which class do we book for short flights?";
+        for (tenant, key, content) in [
+            ("rag-user", "doc/travel/00000", question),
+            ("rag-user", "doc/travel/00001", "Expense reports are due on the fifth."),
+            ("other-tenant", "doc/secret/00000", question),
+        ] {
+            let embedding = router.embedding(content, None).await.unwrap();
+            let record = KbRecord::with_embedding(content, serde_json::json!({ "filename": "travel.md" }), embedding, knowledge.now_ms());
+            knowledge.tenant(tenant).unwrap().insert_record(3, key, &record).unwrap();
+        }
+        let prompts = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let mut registry = SkillRegistry::new();
+        registry.register(Arc::new(Recorder(Arc::clone(&prompts))));
+        let orchestrator = Arc::new(Orchestrator::new(Arc::new(registry)));
+        let app = Router::new().route("/api/v1/chat", post(chat)).with_state(AppState {
+            config: LiveConfig::new(test_config()),
+            orchestrator: Arc::clone(&orchestrator),
+            knowledge: Arc::clone(&knowledge),
+            log_tx: test_log_tx(),
+            shadow_store: test_shadow_store(),
+            llm_providers: Default::default(),
+            oidc: None,
+        });
+        let ask = |body: serde_json::Value| {
+            let app = app.clone();
+            async move {
+                let request = Request::builder()
+                    .method("POST")
+                    .uri("/api/v1/chat")
+                    .header("content-type", "application/json")
+                    .body(Body::from(body.to_string()))
+                    .unwrap();
+                let res = app.oneshot(request).await.unwrap();
+                let bytes = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+                serde_json::from_slice::<serde_json::Value>(&bytes).unwrap()
+            }
+        };
+
+        let body = ask(serde_json::json!({ "prompt": question, "user_alias": "rag-user", "rag": true, "rag_top_k": 2 })).await;
+        let sources = body["sources"].as_array().unwrap();
+        assert_eq!(sources.len(), 2);
+        assert_eq!((sources[0]["n"].as_u64(), sources[0]["key"].as_str()), (Some(1), Some("doc/travel/00000")));
+        assert_eq!(sources[0]["title"], "travel.md");
+        assert!(sources.iter().all(|s| s["key"] != "doc/secret/00000"));
+        let prompt = prompts.lock().unwrap().last().unwrap().clone();
+        assert!(prompt.contains(&format!("[1] (KB-3 doc/travel/00000) {}", question)));
+
+        // Without `rag` there are no sources; with KB-3 switched off nothing is retrieved.
+        let body = ask(serde_json::json!({ "prompt": question, "user_alias": "rag-user" })).await;
+        assert!(body["sources"].is_null());
+        assert!(!prompts.lock().unwrap().last().unwrap().contains("[1] (KB-3"));
+        orchestrator.pagi_apply_control_signal(pagi_core::ControlPanelMessage::KbState { index: 2, active: false });
+        let body = ask(serde_json::json!({ "prompt": question, "user_alias": "rag-user", "rag": true })).await;
+        assert_eq!(body["sources"], serde_json::json!([]));
+    }
+
+    #[tokio::test]
+    async fn test_rag_rerank_orders_the_candidate_pool_and_reports_scores() {
+        let knowledge = KnowledgeStore::open_temporary().unwrap();
+        let router = ModelRouter::with_mode(LlmMode::Mock);
+        let question = "How are annual plan refunds calculated?";
+        for (key, content) in [
+            ("a", "Annual plan refunds are calculated pro rata from the cancellation date."),
+            ("b", "The office plants are watered on Mondays."),
+            ("c", question),
+        ] {
+            let embedding = router.embedding(content, None).await.unwrap();
+            let record = KbRecord::with_embedding(content, serde_json::json!({}), embedding, knowledge.now_ms());
+            knowledge.tenant("rerank").unwrap().insert_record(3, key, &record).unwrap();
+        }
+        let config = pagi_core::RagConfig {
+            reranker: pagi_core::RerankerKind::Llm,
+            candidate_pool: 10,
+            min_similarity: -1.0,
+            min_rerank_score: 0.45,
+        };
+        let rag = handlers::chat::retrieve(&knowledge, &router, "rerank", question, vec![3], 1, &config).await.unwrap();
+        // Mock mode cannot score with the LLM.
+        assert_eq!(rag.reranker, pagi_core::RerankerKind::Lexical);
+        let trace = rag.trace();
+        let candidates = trace["candidates"].as_array().unwrap();
+        assert_eq!(candidates.len(), 3, "the whole pool is reported: {}", trace);
+        assert_eq!((candidates[0]["key"].as_str(), candidates[0]["outcome"].as_str()), (Some("c"), Some("cited")));
+        assert_eq!(candidates[1]["key"], "a");
+        assert_eq!(candidates[1]["outcome"], "beyond_top_k");
+        assert_eq!(candidates[2]["key"], "b");
+        assert_eq!(candidates[2]["outcome"], "below_min_rerank_score");
+        assert!(candidates.iter().all(|c| c["rerank_score"].is_number() && c["similarity"].is_number()));
+        assert_eq!(rag.sources.len(), 1);
+        assert_eq!(rag.sources[0].rerank_score, Some(1.0));
+
+        let strict = pagi_core::RagConfig { min_similarity: 0.99, ..config };
+        let rag = handlers::chat::retrieve(&knowledge, &router, "rerank", question, vec![3], 3, &strict).await.unwrap();
+        assert_eq!(rag.sources.len(), 1);
+        assert_eq!(
+            rag.candidates.iter().filter(|c| c.outcome == handlers::chat::CandidateOutcome::BelowMinSimilarity).count(),
+            2
         );
+    }
+
+    #[tokio::test]
+    async fn test_chat_sessions_carry_history_into_the_prompt() {
+        /// Answers with a fixed text and keeps every system prompt it was sent.
+        struct Recorder(Arc<std::sync::Mutex<Vec<String>>>);
+
+        #[async_trait::async_trait]
+        impl AgentSkill for Recorder {
+            fn name(&self) -> &str {
+                "ModelRouter"
+            }
+
+            async fn execute(
+                &self,
+                _ctx: &TenantContext,
+                payload: Option<serde_json::Value>,
+            ) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>> {
+                let payload = payload.unwrap_or_default();
+                let system_prompt = payload["system_prompt"].as_str().unwrap_or("").to_string();
+                self.0.lock().unwrap().push(system_prompt);
+                Ok(serde_json::json!({ "status": "ok", "generated": "Noted." }))
+            }
+        }
+
+        let knowledge = Arc::new(KnowledgeStore::open_temporary().unwrap());
+        let prompts = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let mut registry = SkillRegistry::new();
+        registry.register(Arc::new(Recorder(Arc::clone(&prompts))));
+        let app = Router::new()
+            .route("/api/v1/chat", post(chat))
+            .route("/api/v1/sessions/:id/history", get(handlers::sessions::get_history))
+            .with_state(AppState {
+                config: LiveConfig::new(test_config()),
+                orchestrator: Arc::new(Orchestrator::new(Arc::new(registry))),
+                knowledge: Arc::clone(&knowledge),
+                log_tx: test_log_tx(),
+                shadow_store: test_shadow_store(),
+                llm_providers: Default::default(),
+                oidc: None,
+            });
+        let call = |method: &str, uri: &str, body: Option<serde_json::Value>| {
+            let app = app.clone();
+            let request = Request::builder()
+                .method(method)
+                .uri(uri)
+                .header("content-type", "application/json")
+                .body(body.map_or_else(Body::empty, |b| Body::from(b.to_string())))
+                .unwrap();
+            async move {
+                let res = app.oneshot(request).await.unwrap();
+                let status = res.status();
+                let bytes = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+                (status, serde_json::from_slice::<serde_json::Value>(&bytes).unwrap())
+            }
+        };
+        let chat_in = |session_id: Option<&str>, prompt: &str| {
+            serde_json::json!({ "prompt": prompt, "user_alias": "session-user", "session_id": session_id })
+        };
+
+        let (_, first) = call("POST", "/api/v1/chat", Some(chat_in(None, "My favourite colour is teal."))).await;
+        assert_eq!(first["status"], "ok");
+        let session_id = first["session_id"].as_str().unwrap().to_string();
+        call("POST", "/api/v1/chat", Some(chat_in(Some(&session_id), "What should I paint?"))).await;
+        let (_, other) = call("POST", "/api/v1/chat", Some(chat_in(Some("other"), "Which colour did I pick?"))).await;
+        assert_eq!(other["session_id"], "other");
+
+        let prompts = prompts.lock().unwrap().clone();
+        assert!(!prompts[0].contains("Conversation so far"));
+        assert!(prompts[1].contains("Conversation so far:\nUser: My favourite colour is teal.\nAssistant: Noted."));
+        // A new session starts without recent turns but recalls the relevant older one.
+        assert!(!prompts[2].contains("Conversation so far"));
+        assert!(prompts[2].contains("Relevant earlier conversation:\nUser: My favourite colour is teal."));
+
+        let uri = format!("/api/v1/sessions/{}/history?user_alias=session-user", session_id);
+        let (status, history) = call("GET", &uri, None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(history["session"]["turns"], 2);
+        assert_eq!(history["turns"][1]["turn"], 2);
+        assert_eq!(history["turns"][1]["user"], "What should I paint?");
+        let (status, _) = call("GET", &format!("/api/v1/sessions/{}/history", session_id), None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        let (status, _) = call("POST", "/api/v1/chat", Some(chat_in(Some("a/b"), "hi"))).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_persona_profiles_shape_agent_chats() {
+        /// Answers with a fixed text and keeps every payload it was sent.
+        struct Recorder(Arc<std::sync::Mutex<Vec<serde_json::Value>>>);
+
+        #[async_trait::async_trait]
+        impl AgentSkill for Recorder {
+            fn name(&self) -> &str {
+                "ModelRouter"
+            }
+
+            async fn execute(
+                &self,
+                _ctx: &TenantContext,
+                payload: Option<serde_json::Value>,
+            ) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>> {
+                self.0.lock().unwrap().push(payload.unwrap_or_default());
+                Ok(serde_json::json!({ "status": "ok", "generated": "Booked." }))
+            }
+        }
+
+        let payloads = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let mut registry = SkillRegistry::new();
+        registry.register(Arc::new(Recorder(Arc::clone(&payloads))));
+        let app = Router::new()
+            .route("/api/v1/chat", post(chat))
+            .route(
+                "/api/v1/agents/:id/persona",
+                get(handlers::personas::get_persona)
+                    .put(handlers::personas::put_persona)
+                    .delete(handlers::personas::delete_persona),
+            )
+            .with_state(AppState {
+                config: LiveConfig::new(test_config()),
+                orchestrator: Arc::new(Orchestrator::new(Arc::new(registry))),
+                knowledge: Arc::new(KnowledgeStore::open_temporary().unwrap()),
+                log_tx: test_log_tx(),
+                shadow_store: test_shadow_store(),
+                llm_providers: Default::default(),
+                oidc: None,
+            });
+        let call = |method: &str, uri: &str, body: Option<serde_json::Value>| {
+            let app = app.clone();
+            let request = Request::builder()
+                .method(method)
+                .uri(uri)
+                .header("content-type", "application/json")
+                .body(body.map_or_else(Body::empty, |b| Body::from(b.to_string())))
+                .unwrap();
+            async move {
+                let res = app.oneshot(request).await.unwrap();
+                let status = res.status();
+                let bytes = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+                (status, serde_json::from_slice::<serde_json::Value>(&bytes).unwrap())
+            }
+        };
+
+        let (status, _) = call("GET", "/api/v1/agents/plumber/persona", None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        let profile = serde_json::json!({
+            "system_prompt": "You book jobs for a local plumbing company.",
+            "tone": "friendly and brief",
+            "boundaries": ["Never quote prices"],
+            "preferred_model": "fast-model",
+            "temperature": 0.3,
+        });
+        let (status, saved) = call("PUT", "/api/v1/agents/plumber/persona", Some(profile)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(saved["persona"]["agent_id"], "plumber");
+        let (status, bad) = call("PUT", "/api/v1/agents/plumber/persona", Some(serde_json::json!({ "system_prompt": " " }))).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{}", bad);
+
+        let chat_in = |agent_id: &str, model: Option<&str>| {
+            serde_json::json!({ "prompt": "Can you come Tuesday?", "agent_id": agent_id, "model": model })
+        };
+        call("POST", "/api/v1/chat", Some(chat_in("plumber", None))).await;
+        call("POST", "/api/v1/chat", Some(chat_in("plumber", Some("big-model")))).await;
+        call("POST", "/api/v1/chat", Some(chat_in("default", None))).await;
+        let (status, _) = call("DELETE", "/api/v1/agents/plumber/persona", None).await;
+        assert_eq!(status, StatusCode::OK);
+        call("POST", "/api/v1/chat", Some(chat_in("plumber", None))).await;
+
+        let payloads = payloads.lock().unwrap().clone();
+        let system_prompt = payloads[0]["system_prompt"].as_str().unwrap();
+        assert!(system_prompt.contains(
+            "Persona: You book jobs for a local plumbing company.\nTone: friendly and brief\nBoundaries (never cross these):\n- Never quote prices"
+        ));
+        assert_eq!(payloads[0]["model"], "fast-model");
+        assert!((payloads[0]["temperature"].as_f64().unwrap() - 0.3).abs() < 1e-6);
+        // The request's own model wins; other agents and removed profiles are untouched.
+        assert_eq!(payloads[1]["model"], "big-model");
+        assert!(payloads[2]["model"].is_null());
+        assert!(!payloads[2]["system_prompt"].as_str().unwrap().contains("plumbing"));
+        assert!(payloads[3]["model"].is_null());
+    }
+
+    #[tokio::test]
+    async fn test_agent_routes_create_list_and_archive() {
+        let knowledge = Arc::new(KnowledgeStore::open_temporary().unwrap());
+        let mut registry = SkillRegistry::new();
+        registry.register(Arc::new(ModelRouter::with_mode(LlmMode::Mock)));
+        let app = Router::new()
+            .route("/api/v1/agents", get(handlers::agents::list_agents).post(handlers::agents::create_agent))
+            .route(
+                "/api/v1/agents/:id",
+                get(handlers::agents::get_agent)
+                    .patch(handlers::agents::update_schedule)
+                    .delete(handlers::agents::archive_agent),
+            )
+            .with_state(AppState {
+                config: LiveConfig::new(test_config()),
+                orchestrator: Arc::new(Orchestrator::new(Arc::new(registry))),
+                knowledge: Arc::clone(&knowledge),
+                log_tx: test_log_tx(),
+                shadow_store: test_shadow_store(),
+                llm_providers: Default::default(),
+                oidc: None,
+            });
+        let call = |method: &str, uri: &str, body: Option<serde_json::Value>| {
+            let app = app.clone();
+            let request = Request::builder()
+                .method(method)
+                .uri(uri)
+                .header("content-type", "application/json")
+                .body(body.map_or_else(Body::empty, |b| Body::from(b.to_string())))
+                .unwrap();
+            async move {
+                let res = app.oneshot(request).await.unwrap();
+                let status = res.status();
+                let bytes = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+                (status, serde_json::from_slice::<serde_json::Value>(&bytes).unwrap())
+            }
+        };
+
+        let plumber = serde_json::json!({
+            "agent_id": "plumber",
+            "display_name": "Plumbing desk",
+            "persona": { "system_prompt": "You book plumbing jobs." },
+            "enabled_skills": ["ModelRouter"],
+        });
+        let (status, created) = call("POST", "/api/v1/agents", Some(plumber.clone())).await;
+        assert_eq!(status, StatusCode::CREATED, "{}", created);
+        assert_eq!(created["agent"]["persona"]["agent_id"], "plumber");
+        let (status, _) = call("POST", "/api/v1/agents", Some(plumber)).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        let (status, _) = call("POST", "/api/v1/agents", Some(serde_json::json!({ "agent_id": "x", "enabled_skills": ["Nope"] }))).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        call("POST", "/api/v1/agents", Some(serde_json::json!({ "agent_id": "electrician" }))).await;
+        knowledge.push_agent_message("electrician", "plumber", &serde_json::json!({ "text": "job for you" })).unwrap();
+
+        let (_, listed) = call("GET", "/api/v1/agents", None).await;
+        assert_eq!(listed["count"], 2);
+        let plumber = listed["agents"].as_array().unwrap().iter().find(|a| a["agent_id"] == "plumber").unwrap();
+        assert_eq!(plumber["health"]["pending_messages"], 1);
+        let (_, one) = call("GET", "/api/v1/agents/plumber", None).await;
+        assert_eq!(one["agent"]["persona"]["system_prompt"], "You book plumbing jobs.");
+
+        // Quiesce the electrician: still active, but the Heartbeat skips it until resumed.
+        let schedule = serde_json::json!({
+            "paused": true,
+            "active_hours": { "start": "09:00", "end": "18:00" },
+        });
+        let (status, paused) = call("PATCH", "/api/v1/agents/electrician", Some(schedule)).await;
+        assert_eq!(status, StatusCode::OK, "{}", paused);
+        assert_eq!(paused["agent"]["paused"], true);
+        assert_eq!(paused["agent"]["active_hours"]["end"], "18:00");
+        assert_eq!(knowledge.due_agent_ids(&mut AgentTickTracker::default()).unwrap(), ["plumber"]);
+        let (status, resumed) =
+            call("PATCH", "/api/v1/agents/electrician", Some(serde_json::json!({ "paused": false, "active_hours": null }))).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(resumed["agent"]["active_hours"], serde_json::Value::Null);
+        let (status, _) = call("PATCH", "/api/v1/agents/electrician", Some(serde_json::json!({ "tick_interval_secs": 0 }))).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let (status, _) = call("PATCH", "/api/v1/agents/missing", Some(serde_json::json!({ "paused": true }))).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        let (status, archived) = call("DELETE", "/api/v1/agents/plumber", None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(archived["agent"]["status"], "archived");
+        assert_eq!(knowledge.active_agent_ids().unwrap(), ["electrician"]);
+        let (_, listed) = call("GET", "/api/v1/agents", None).await;
+        assert_eq!(listed["count"], 1);
+        let (_, listed) = call("GET", "/api/v1/agents?include_archived=true", None).await;
+        assert_eq!(listed["count"], 2);
+        let (status, _) = call("DELETE", "/api/v1/agents/missing", None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_topic_subscriptions_fan_out_message_agent_broadcasts() {
+        let knowledge = Arc::new(KnowledgeStore::open_temporary().unwrap());
+        let app = Router::new()
+            .route("/api/v1/agents/:id/subscriptions", get(handlers::agents::list_subscriptions))
+            .route(
+                "/api/v1/agents/:id/subscriptions/:topic",
+                put(handlers::agents::subscribe).delete(handlers::agents::unsubscribe),
+            )
+            .with_state(AppState {
+                config: LiveConfig::new(test_config()),
+                orchestrator: Arc::new(Orchestrator::new(Arc::new(SkillRegistry::new()))),
+                knowledge: Arc::clone(&knowledge),
+                log_tx: test_log_tx(),
+                shadow_store: test_shadow_store(),
+                llm_providers: Default::default(),
+                oidc: None,
+            });
+        let call = |method: &str, uri: &str| {
+            let app = app.clone();
+            let request = Request::builder().method(method).uri(uri).body(Body::empty()).unwrap();
+            async move {
+                let res = app.oneshot(request).await.unwrap();
+                let status = res.status();
+                let bytes = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+                (status, serde_json::from_slice::<serde_json::Value>(&bytes).unwrap())
+            }
+        };
+
+        for agent in ["dev-1", "dev-2"] {
+            let (status, _) = call("PUT", &format!("/api/v1/agents/{}/subscriptions/maintenance", agent)).await;
+            assert_eq!(status, StatusCode::OK);
+        }
+        let (status, _) = call("PUT", "/api/v1/agents/dev-1/subscriptions/bad%2Ftopic").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let ctx = TenantContext {
+            tenant_id: "default".to_string(),
+            correlation_id: None,
+            agent_id: Some("SAGE_BOT".to_string()),
+            ..Default::default()
+        };
+        let published = pagi_skills::MessageAgent::new(Arc::clone(&knowledge))
+            .execute(&ctx, Some(serde_json::json!({ "topic": "maintenance", "message": { "text": "Restarting at 02:00" } })))
+            .await
+            .unwrap();
+        assert_eq!(published["subscribers"], 2);
+        assert_eq!(knowledge.heartbeat_fan_out_topics(), 2);
+        assert_eq!(knowledge.get_agent_messages("dev-2", 5).unwrap()[0].text(), "Restarting at 02:00");
+
+        let (_, listed) = call("GET", "/api/v1/agents/dev-1/subscriptions").await;
+        assert_eq!(listed["subscriptions"][0]["topic"], "maintenance");
+        let (status, _) = call("DELETE", "/api/v1/agents/dev-1/subscriptions/maintenance").await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, _) = call("DELETE", "/api/v1/agents/dev-1/subscriptions/maintenance").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_dead_letter_routes_list_and_requeue() {
+        let knowledge = Arc::new(KnowledgeStore::open_temporary().unwrap());
+        let app = Router::new()
+            .route("/api/v1/agents/:id/dlq", get(handlers::agents::list_dead_letters))
+            .route(
+                "/api/v1/agents/:id/dlq/:message_id/requeue",
+                post(handlers::agents::requeue_dead_letter),
+            )
+            .with_state(AppState {
+                config: LiveConfig::new(test_config()),
+                orchestrator: Arc::new(Orchestrator::new(Arc::new(SkillRegistry::new()))),
+                knowledge: Arc::clone(&knowledge),
+                log_tx: test_log_tx(),
+                shadow_store: test_shadow_store(),
+                llm_providers: Default::default(),
+                oidc: None,
+            });
+        let call = |method: &str, uri: &str| {
+            let app = app.clone();
+            let request = Request::builder().method(method).uri(uri).body(Body::empty()).unwrap();
+            async move {
+                let res = app.oneshot(request).await.unwrap();
+                let status = res.status();
+                let bytes = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+                (status, serde_json::from_slice::<serde_json::Value>(&bytes).unwrap())
+            }
+        };
+
+        let id = knowledge.push_agent_message("sales", "support", &serde_json::json!({ "text": "help" })).unwrap();
+        let (key, _) = knowledge.get_agent_messages_with_keys("support", 1).unwrap().remove(0);
+        for _ in 0..pagi_core::INBOX_MAX_ATTEMPTS {
+            knowledge.record_inbox_failure(&key, "provider unavailable").unwrap();
+        }
+
+        let (status, listed) = call("GET", "/api/v1/agents/support/dlq").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(listed["count"], 1);
+        assert_eq!(listed["messages"][0]["last_error"], "provider unavailable");
+        assert_eq!(listed["messages"][0]["attempts"], pagi_core::INBOX_MAX_ATTEMPTS);
+
+        let (status, requeued) = call("POST", &format!("/api/v1/agents/support/dlq/{}/requeue", id)).await;
+        assert_eq!(status, StatusCode::OK, "{}", requeued);
+        assert_eq!(knowledge.get_agent_messages("support", 5).unwrap()[0].id, id);
+        let (_, listed) = call("GET", "/api/v1/agents/support/dlq").await;
+        assert_eq!(listed["count"], 0);
+        let (status, _) = call("POST", &format!("/api/v1/agents/support/dlq/{}/requeue", id)).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_async_execute_runs_goal_as_background_job() {
+        let knowledge = Arc::new(KnowledgeStore::open_temporary().unwrap());
+        let mut registry = SkillRegistry::new();
+        registry.register(Arc::new(ModelRouter::with_mode(LlmMode::Mock)));
+        let plans = std::collections::HashMap::from([(
+            "daily digest".to_string(),
+            pagi_core::Plan { steps: vec![pagi_core::PlanStep::new("ModelRouter")] },
+        )]);
+        let orchestrator = Orchestrator::with_blueprint(Arc::new(registry), Arc::new(BlueprintRegistry::from_plans(plans)));
+        let app = Router::new()
+            .route("/v1/execute", post(execute))
+            .route("/v1/jobs/:job_id", get(handlers::jobs::get_job))
+            .with_state(AppState {
+                config: LiveConfig::new(test_config()),
+                orchestrator: Arc::new(orchestrator),
+                knowledge: Arc::clone(&knowledge),
+                log_tx: test_log_tx(),
+                shadow_store: test_shadow_store(),
+                llm_providers: Default::default(),
+                oidc: None,
+            });
+        let call = |method: &str, uri: &str, body: Option<serde_json::Value>| {
+            let app = app.clone();
+            let request = Request::builder()
+                .method(method)
+                .uri(uri)
+                .header("content-type", "application/json")
+                .body(body.map_or_else(Body::empty, |b| Body::from(b.to_string())))
+                .unwrap();
+            async move {
+                let res = app.oneshot(request).await.unwrap();
+                let status = res.status();
+                let bytes = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+                (status, serde_json::from_slice::<serde_json::Value>(&bytes).unwrap())
+            }
+        };
+
+        let goal = serde_json::json!({
+            "tenant_id": "acme",
+            "goal": { "AutonomousGoal": { "intent": "daily digest", "context": { "prompt": "Summarize the day" } } },
+        });
+        let (status, accepted) = call("POST", "/v1/execute?async=true", Some(goal)).await;
+        assert_eq!(status, StatusCode::ACCEPTED, "{}", accepted);
+        let job_id = accepted["job_id"].as_str().unwrap().to_string();
+        assert_eq!(accepted["correlation_id"], job_id.as_str());
+
+        let mut job = serde_json::Value::Null;
+        for _ in 0..100 {
+            let (status, body) = call("GET", &format!("/v1/jobs/{}", job_id), None).await;
+            assert_eq!(status, StatusCode::OK);
+            job = body["job"].clone();
+            if job["finished_at_ms"].is_i64() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        assert_eq!(job["status"], "succeeded", "{}", job);
+        assert_eq!(job["goal"], "AutonomousGoal");
+        assert_eq!(job["tenant_id"], "acme");
+        assert_eq!((job["steps_completed"].as_u64(), job["steps_total"].as_u64()), (Some(1), Some(1)));
+        assert!(job["result"].is_object());
+
+        let (status, _) = call("GET", "/v1/jobs/missing", None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_ethos_rule_routes_version_rules_and_gate_execute() {
+        struct Echo;
+
+        #[async_trait::async_trait]
+        impl AgentSkill for Echo {
+            fn name(&self) -> &str {
+                "Echo"
+            }
+
+            async fn execute(
+                &self,
+                _ctx: &TenantContext,
+                _payload: Option<serde_json::Value>,
+            ) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>> {
+                Ok(serde_json::json!({ "status": "ok", "echoed": true }))
+            }
+        }
+
+        let knowledge = Arc::new(KnowledgeStore::open_temporary().unwrap());
+        knowledge.set_ethos_policy(&PolicyRecord::default()).unwrap();
+        let mut registry = SkillRegistry::new();
+        registry.register(Arc::new(Echo));
+        let app = Router::new()
+            .route("/v1/execute", post(execute))
+            .route(
+                "/api/v1/ethos/rules",
+                get(handlers::ethos::get_rules)
+                    .put(handlers::ethos::put_rules)
+                    .delete(handlers::ethos::clear_rules),
+            )
+            .route("/api/v1/ethos/rules/versions", get(handlers::ethos::list_versions))
+            .route("/api/v1/ethos/evaluate", post(handlers::ethos::evaluate))
+            .with_state(AppState {
+                config: LiveConfig::new(test_config()),
+                orchestrator: Arc::new(Orchestrator::new(Arc::new(registry)).with_knowledge(Arc::clone(&knowledge))),
+                knowledge: Arc::clone(&knowledge),
+                log_tx: test_log_tx(),
+                shadow_store: test_shadow_store(),
+                llm_providers: Default::default(),
+                oidc: None,
+            });
+        let call = |method: &str, uri: &str, body: Option<serde_json::Value>| {
+            let app = app.clone();
+            let request = Request::builder()
+                .method(method)
+                .uri(uri)
+                .header("content-type", "application/json")
+                .body(body.map_or_else(Body::empty, |b| Body::from(b.to_string())))
+                .unwrap();
+            async move {
+                let res = app.oneshot(request).await.unwrap();
+                let status = res.status();
+                let bytes = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+                (status, serde_json::from_slice::<serde_json::Value>(&bytes).unwrap())
+            }
+        };
+        let echo = |content: &str| {
+            Some(serde_json::json!({
+                "tenant_id": "acme",
+                "goal": { "ExecuteSkill": { "name": "Echo", "payload": { "content": content } } },
+            }))
+        };
+
+        // Legacy keyword policy: any mention of "token" is blocked.
+        let (_, body) = call("GET", "/api/v1/ethos/rules", None).await;
+        assert_eq!(body["legacy"], true);
+        let (_, body) = call("POST", "/v1/execute", echo("rotate the token tomorrow")).await;
+        assert_eq!(body["status"], "policy_violation", "{}", body);
+
+        let rules = serde_json::json!({
+            "note": "scoped rules",
+            "rules": [
+                {
+                    "id": "no-secrets",
+                    "conditions": [{ "path": "$.content", "regex": "(?i)api[_-]?key\\s*=" }],
+                    "action": "deny",
+                    "priority": 10
+                },
+                { "id": "payments", "skills": ["Pay*"], "action": "require_approval" }
+            ]
+        });
+        let (status, body) = call("PUT", "/api/v1/ethos/rules", Some(rules.clone())).await;
+        assert_eq!(status, StatusCode::OK, "{}", body);
+        assert_eq!(body["rules"]["version"], 1);
+        let bad = serde_json::json!({ "rules": [{ "id": "r", "conditions": [{ "regex": "(" }], "action": "deny" }] });
+        let (status, _) = call("PUT", "/api/v1/ethos/rules", Some(bad)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let (_, body) = call("POST", "/v1/execute", echo("rotate the token tomorrow")).await;
+        assert_eq!(body["echoed"], true, "{}", body);
+        let (_, body) = call("POST", "/v1/execute", echo("api_key=sk-1")).await;
+        assert_eq!(body["status"], "policy_violation");
+        assert_eq!(body["rule_id"], "no-secrets");
+        let (_, body) = call(
+            "POST",
+            "/api/v1/ethos/evaluate",
+            Some(serde_json::json!({ "skill": "PaymentSend", "payload": { "amount": 5 } })),
+        )
+        .await;
+        assert_eq!(body["allowed"], false);
+        assert_eq!(body["decision"]["action"], "require_approval");
+
+        let (_, body) = call("PUT", "/api/v1/ethos/rules", Some(rules)).await;
+        assert_eq!(body["rules"]["version"], 2);
+        let (_, body) = call("GET", "/api/v1/ethos/rules/versions", None).await;
+        assert_eq!(body["count"], 2);
+        assert_eq!(body["versions"][0]["note"], "scoped rules");
+        let (_, body) = call("GET", "/api/v1/ethos/rules?version=1", None).await;
+        assert_eq!(body["rules"]["version"], 1);
+        let (status, _) = call("GET", "/api/v1/ethos/rules?version=9", None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        let (_, body) = call("DELETE", "/api/v1/ethos/rules", None).await;
+        assert_eq!(body["cleared"], true);
+        let (_, body) = call("GET", "/api/v1/ethos/rules", None).await;
+        assert_eq!(body["legacy"], true);
+    }
+
+    #[tokio::test]
+    async fn test_approval_routes_suspend_and_resume_blocked_calls() {
+        struct Payment(Arc<std::sync::atomic::AtomicU32>);
+
+        #[async_trait::async_trait]
+        impl AgentSkill for Payment {
+            fn name(&self) -> &str {
+                "PaymentSend"
+            }
+
+            async fn execute(
+                &self,
+                _ctx: &TenantContext,
+                _payload: Option<serde_json::Value>,
+            ) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>> {
+                self.0.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                Ok(serde_json::json!({ "status": "ok", "paid": true }))
+            }
+        }
+
+        let knowledge = Arc::new(KnowledgeStore::open_temporary().unwrap());
+        let rule: pagi_core::PolicyRule = serde_json::from_value(serde_json::json!({
+            "id": "payments",
+            "skills": ["Pay*"],
+            "action": "require_approval"
+        }))
+        .unwrap();
+        knowledge
+            .put_policy_rules(&pagi_core::PolicyRules::new(vec![rule], RuleAction::Allow))
+            .unwrap();
+        let calls = Arc::new(std::sync::atomic::AtomicU32::new(0));
+        let mut registry = SkillRegistry::new();
+        registry.register(Arc::new(Payment(Arc::clone(&calls))));
+        let app = Router::new()
+            .route("/v1/execute", post(execute))
+            .route("/v1/jobs/:job_id", get(handlers::jobs::get_job))
+            .route("/api/v1/approvals", get(handlers::approvals::list_approvals))
+            .route("/api/v1/approvals/:id", get(handlers::approvals::get_approval))
+            .route("/api/v1/approvals/:id/approve", post(handlers::approvals::approve))
+            .route("/api/v1/approvals/:id/deny", post(handlers::approvals::deny))
+            .with_state(AppState {
+                config: LiveConfig::new(test_config()),
+                orchestrator: Arc::new(Orchestrator::new(Arc::new(registry)).with_knowledge(Arc::clone(&knowledge))),
+                knowledge: Arc::clone(&knowledge),
+                log_tx: test_log_tx(),
+                shadow_store: test_shadow_store(),
+                llm_providers: Default::default(),
+                oidc: None,
+            });
+        let call = |method: &str, uri: &str, body: Option<serde_json::Value>| {
+            let app = app.clone();
+            let request = Request::builder()
+                .method(method)
+                .uri(uri)
+                .header("content-type", "application/json")
+                .body(body.map_or_else(Body::empty, |b| Body::from(b.to_string())))
+                .unwrap();
+            async move {
+                let res = app.oneshot(request).await.unwrap();
+                let status = res.status();
+                let bytes = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+                (status, serde_json::from_slice::<serde_json::Value>(&bytes).unwrap())
+            }
+        };
+        let pay = |correlation_id: &str| {
+            Some(serde_json::json!({
+                "tenant_id": "acme",
+                "agent_id": "billing",
+                "correlation_id": correlation_id,
+                "goal": { "ExecuteSkill": { "name": "PaymentSend", "payload": { "amount": 5 } } },
+            }))
+        };
+
+        let (_, body) = call("POST", "/v1/execute", pay("pay-1")).await;
+        assert_eq!(body["status"], "approval_required", "{}", body);
+        let approval_id = body["approval_id"].as_str().unwrap().to_string();
+        let (_, body) = call("GET", "/api/v1/approvals?status=pending", None).await;
+        assert_eq!(body["count"], 1);
+        assert_eq!(body["approvals"][0]["approval_id"], approval_id.as_str());
+        assert_eq!(body["approvals"][0]["goal"]["ExecuteSkill"]["name"], "PaymentSend");
+        assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 0);
+
+        // Approving resumes the dispatch as a background job.
+        let (status, body) = call(
+            "POST",
+            &format!("/api/v1/approvals/{}/approve", approval_id),
+            Some(serde_json::json!({ "decided_by": "alice", "note": "invoice 42" })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK, "{}", body);
+        let job_id = body["job_id"].as_str().unwrap().to_string();
+        let mut job = serde_json::Value::Null;
+        for _ in 0..100 {
+            job = call("GET", &format!("/v1/jobs/{}", job_id), None).await.1["job"].clone();
+            if job["finished_at_ms"].is_i64() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        assert_eq!(job["status"], "succeeded", "{}", job);
+        assert_eq!(job["result"]["paid"], true);
+        assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 1);
+        let (_, body) = call("GET", &format!("/api/v1/approvals/{}", approval_id), None).await;
+        assert_eq!(body["approval"]["status"], "executed");
+        assert_eq!(body["approval"]["job_id"], job_id.as_str());
+        let (status, _) = call("POST", &format!("/api/v1/approvals/{}/deny", approval_id), None).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+
+        // Denied calls stay blocked under their correlation id.
+        let (_, body) = call("POST", "/v1/execute", pay("pay-2")).await;
+        let denied_id = body["approval_id"].as_str().unwrap().to_string();
+        let (status, _) = call("POST", &format!("/api/v1/approvals/{}/deny", denied_id), None).await;
+        assert_eq!(status, StatusCode::OK);
+        let (_, body) = call("POST", "/v1/execute", pay("pay-2")).await;
+        assert_eq!(body["status"], "policy_violation", "{}", body);
+        assert!(body["error"].as_str().unwrap().contains("denied by operator"));
+        let (status, _) = call("POST", "/api/v1/approvals/nope/approve", None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        // Chronos holds both decisions.
+        let events = knowledge.get_recent_chronos_events("billing", 20).unwrap();
+        assert!(events.iter().any(|e| e.outcome.as_deref() == Some("approved") && e.reflection.contains("alice")));
+        assert!(events.iter().any(|e| e.outcome.as_deref() == Some("denied")));
+    }
+
+    #[tokio::test]
+    async fn test_chat_routes_intents_to_autonomous_goals() {
+        let knowledge = Arc::new(KnowledgeStore::open_temporary().unwrap());
+        let mut registry = SkillRegistry::new();
+        registry.register(Arc::new(ModelRouter::with_mode(LlmMode::Mock)));
+        let plans = std::collections::HashMap::from([(
+            "summarize news".to_string(),
+            pagi_core::Plan { steps: vec![pagi_core::PlanStep::new("ModelRouter")] },
+        )]);
+        let examples = std::collections::HashMap::from([(
+            "summarize news".to_string(),
+            vec!["refresh the local events".to_string()],
+        )]);
+        let orchestrator = Orchestrator::with_blueprint(Arc::new(registry), Arc::new(BlueprintRegistry::from_plans(plans)))
+            .with_intent_router(Arc::new(IntentRouter::new(IntentRoutingConfig::default()).with_examples(examples)));
+        let app = Router::new()
+            .route("/api/v1/chat", post(chat))
+            .route("/api/v1/chat/stream", post(chat_events))
+            .with_state(AppState {
+                config: LiveConfig::new(test_config()),
+                orchestrator: Arc::new(orchestrator),
+                knowledge: Arc::clone(&knowledge),
+                log_tx: test_log_tx(),
+                shadow_store: test_shadow_store(),
+                llm_providers: Default::default(),
+                oidc: None,
+            });
+        let post_chat = |uri: &'static str, prompt: &str| {
+            Request::builder()
+                .method("POST")
+                .uri(uri)
+                .header("content-type", "application/json")
+                .body(Body::from(serde_json::json!({ "prompt": prompt, "user_alias": "intent-user" }).to_string()))
+                .unwrap()
+        };
+        let chat_json = |res: Response| async move {
+            let bytes = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+            serde_json::from_slice::<serde_json::Value>(&bytes).unwrap()
+        };
+
+        // Clear match: the plan runs.
+        let body = chat_json(app.clone().oneshot(post_chat("/api/v1/chat", "go refresh the local events")).await.unwrap()).await;
+        assert_eq!(body["status"], "ok");
+        assert_eq!(body["intent"]["intent"], "summarize news");
+        assert_eq!(body["intent"]["action"], "run");
+        assert_eq!(body["intent"]["result"]["goal"], "AutonomousGoal");
+        assert_eq!(body["intent"]["result"]["plan_steps"], serde_json::json!(["ModelRouter"]));
+        assert!(body["response"].as_str().is_some_and(|r| !r.is_empty()));
+
+        // Partial match: asked first, run on "yes".
+        let body = chat_json(app.clone().oneshot(post_chat("/api/v1/chat", "any local events?")).await.unwrap()).await;
+        assert_eq!(body["intent"]["action"], "confirm");
+        assert!(body["response"].as_str().unwrap().contains("Reply \"yes\""));
+        assert!(body["intent"].get("result").is_none());
+        let body = chat_json(app.clone().oneshot(post_chat("/api/v1/chat", "yes")).await.unwrap()).await;
+        assert_eq!(body["intent"]["action"], "run");
+        assert_eq!(body["intent"]["source"], "confirmation");
+        assert_eq!(body["intent"]["result"]["intent"], "summarize news");
+
+        // Everything else is plain chat.
+        let body = chat_json(app.clone().oneshot(post_chat("/api/v1/chat", "hi")).await.unwrap()).await;
+        assert!(body.get("intent").is_none());
+        assert!(body["raw_result"].is_object());
+
+        // Typed stream: `intent`, the reply as one delta, `done`.
+        let res = app.oneshot(post_chat("/api/v1/chat/stream", "refresh the local events")).await.unwrap();
+        let events = sse_events(&axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap());
+        let kinds: Vec<&str> = events.iter().map(|(k, _)| k.as_str()).collect();
+        assert_eq!(kinds, ["intent", "delta", "done"]);
+        assert_eq!(events[0].1["action"], "run");
+        assert_eq!(events[2].1["mode"], "intent");
+        assert_eq!(events[2].1["status"], "ok");
+    }
+
+    #[tokio::test]
+    async fn test_ws_handshake_and_session_streams_chat_progress_and_control() {
+        use handlers::ws::frame::{OP_CLOSE, OP_TEXT};
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let knowledge = Arc::new(KnowledgeStore::open_temporary().unwrap());
         let mut registry = SkillRegistry::new();
-        registry.register(Arc::new(CommunityScraper::new(Arc::clone(&knowledge))));
-        registry.register(Arc::new(KnowledgeQuery::new(Arc::clone(&knowledge))));
-        let orchestrator = Arc::new(Orchestrator::new(Arc::new(registry)));
-        let app = Router::new()
-            .route("/v1/execute", post(execute))
-            .with_state(AppState {
-            config: Arc::new(test_config()),
-            orchestrator,
+        registry.register(Arc::new(ModelRouter::with_mode(LlmMode::Mock)));
+        let mut intents = std::collections::HashMap::new();
+        intents.insert("ws demo".to_string(), vec!["ModelRouter".to_string()]);
+        let blueprint = Arc::new(BlueprintRegistry::from_intents(intents));
+        let state = AppState {
+            config: LiveConfig::new(test_config()),
+            orchestrator: Arc::new(Orchestrator::with_blueprint(Arc::new(registry), blueprint)),
             knowledge,
             log_tx: test_log_tx(),
-            model_router: test_model_router(),
             shadow_store: test_shadow_store(),
-        });
+            llm_providers: Default::default(),
+            oidc: None,
+        };
 
-        let mock_html = r#"<html><body><h1>Fall Festival Next Week</h1></body></html>"#;
-        let body = serde_json::json!({
-            "tenant_id": "test-tenant",
-            "goal": {
-                "UpdateKnowledgeSlot": {
-                    "slot_id": 5,
-                    "source_url": "https://example.com/news",
-                    "source_html": mock_html
+        let app = Router::new()
+            .route("/api/v1/ws", get(handlers::ws::ws_upgrade))
+            .with_state(state.clone());
+        let handshake = |upgrade: &str| {
+            Request::builder()
+                .uri("/api/v1/ws?agent_id=sales")
+                .header("upgrade", upgrade)
+                .header("connection", "keep-alive, Upgrade")
+                .header("sec-websocket-version", "13")
+                .header("sec-websocket-key", "dGhlIHNhbXBsZSBub25jZQ==")
+                .body(Body::empty())
+                .unwrap()
+        };
+        let res = app.clone().oneshot(handshake("websocket")).await.unwrap();
+        assert_eq!(res.status(), StatusCode::SWITCHING_PROTOCOLS);
+        assert_eq!(res.headers()["sec-websocket-accept"], "s3pPLMBiTxaQ9kYGzzhZRbK+xOo=");
+        let res = app.oneshot(handshake("h2c")).await.unwrap();
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+        // The session itself, over an in-memory stream.
+        let (mut client, server) = tokio::io::duplex(1 << 16);
+        let binding = handlers::ws::Binding {
+            agent_id: "sales".to_string(),
+            user_alias: None,
+        };
+        let session = tokio::spawn(handlers::ws::serve(state.clone(), binding, server));
+        async fn send(client: &mut tokio::io::DuplexStream, opcode: u8, payload: &[u8]) {
+            let mask = [1u8, 2, 3, 4];
+            let mut frame = vec![0x80 | opcode];
+            match payload.len() {
+                len @ 0..=125 => frame.push(0x80 | len as u8),
+                len => {
+                    frame.push(0x80 | 126);
+                    frame.extend_from_slice(&(len as u16).to_be_bytes());
                 }
             }
-        });
-        let req = Request::builder()
-            .method("POST")
-            .uri("/v1/execute")
-            .header("content-type", "application/json")
-            .body(Body::from(serde_json::to_string(&body).unwrap()))
-            .unwrap();
-        let res = app.oneshot(req).await.unwrap();
-        assert_eq!(res.status(), StatusCode::OK);
-        let bytes = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
-        let json: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
-        assert_eq!(json["status"], "ok");
-        assert_eq!(json["skill"], "CommunityScraper");
-        assert!(json["event"].as_str().unwrap().contains("Fall Festival Next Week"));
+            frame.extend_from_slice(&mask);
+            frame.extend(payload.iter().enumerate().map(|(i, b)| b ^ mask[i % 4]));
+            client.write_all(&frame).await.unwrap();
+        }
+        async fn recv(client: &mut tokio::io::DuplexStream) -> (u8, Vec<u8>) {
+            let mut head = [0u8; 2];
+            client.read_exact(&mut head).await.unwrap();
+            let len = match head[1] {
+                126 => client.read_u16().await.unwrap() as usize,
+                127 => client.read_u64().await.unwrap() as usize,
+                n => n as usize,
+            };
+            let mut payload = vec![0u8; len];
+            client.read_exact(&mut payload).await.unwrap();
+            (head[0] & 0x0F, payload)
+        }
+        async fn recv_json(client: &mut tokio::io::DuplexStream) -> serde_json::Value {
+            let (opcode, payload) = recv(client).await;
+            assert_eq!(opcode, OP_TEXT);
+            serde_json::from_slice(&payload).unwrap()
+        }
+        async fn send_json(client: &mut tokio::io::DuplexStream, message: serde_json::Value) {
+            send(client, OP_TEXT, message.to_string().as_bytes()).await;
+        }
+
+        let ready = recv_json(&mut client).await;
+        assert_eq!(ready["type"], "ready");
+        assert_eq!(ready["agent_id"], "sales");
+        assert_eq!(ready["control"]["FullState"]["skills_enabled"], true);
+
+        send_json(
+            &mut client,
+            serde_json::json!({
+                "type": "execute",
+                "id": "run-1",
+                "goal": { "AutonomousGoal": { "intent": "ws demo", "context": { "prompt": "hi" } } }
+            }),
+        )
+        .await;
+        let mut progress = Vec::new();
+        let result = loop {
+            let message = recv_json(&mut client).await;
+            assert_eq!(message["id"], "run-1");
+            match message["type"].as_str().unwrap() {
+                "progress" => {
+                    assert_eq!(message["agent_id"], "sales");
+                    progress.push((message["index"].as_u64().unwrap(), message["status"].as_str().unwrap().to_string()));
+                }
+                "result" => break message,
+                other => panic!("unexpected {}: {}", other, message),
+            }
+        };
+        assert_eq!(progress, [(0, "started".to_string()), (0, "completed".to_string())]);
+        assert_eq!(result["status"], "ok");
+        assert_eq!(result["result"]["goal"], "AutonomousGoal");
+
+        send_json(&mut client, serde_json::json!({ "type": "chat", "id": "c1", "prompt": "hello" })).await;
+        let mut kinds = Vec::new();
+        loop {
+            let message = recv_json(&mut client).await;
+            assert_eq!(message["id"], "c1");
+            kinds.push(message["type"].as_str().unwrap().to_string());
+            if message["type"] == "done" {
+                assert_eq!(message["status"], "ok");
+                break;
+            }
+        }
+        assert!(kinds[..kinds.len() - 1].iter().all(|k| k == "delta"), "{:?}", kinds);
+
+        send_json(
+            &mut client,
+            serde_json::json!({ "type": "chat", "id": "c2", "prompt": "hi", "agent_id": "support" }),
+        )
+        .await;
+        let rejected = recv_json(&mut client).await;
+        assert_eq!((rejected["type"].as_str(), rejected["id"].as_str()), (Some("error"), Some("c2")));
+        assert!(rejected["error"].as_str().unwrap().contains("bound to agent sales"));
+
+        send_json(&mut client, serde_json::json!({ "type": "control", "message": { "SkillsEnabled": false } })).await;
+        let control = recv_json(&mut client).await;
+        assert_eq!(control["type"], "control");
+        assert_eq!(control["state"]["FullState"]["skills_enabled"], false);
+        assert!(!state.orchestrator.pagi_skills_enabled());
+
+        send(&mut client, OP_CLOSE, &1000u16.to_be_bytes()).await;
+        assert_eq!(recv(&mut client).await.0, OP_CLOSE);
+        session.await.unwrap();
     }
 
     #[tokio::test]
-    async fn test_sales_closer_cta_in_final_response() {
-        let memory = Arc::new(
-            MemoryManager::open_path("./data/pagi_vault_sales_test").unwrap(),
-        );
+    async fn test_blueprint_crud_routes_edit_intents_at_runtime() {
         let knowledge = Arc::new(
-            KnowledgeStore::open_path("./data/pagi_knowledge_sales_test").unwrap(),
+            KnowledgeStore::open_path("./data/pagi_knowledge_blueprint_crud_test").unwrap(),
         );
-        knowledge.insert(1, "brand_voice", b"Warm and professional").unwrap();
-        knowledge
-            .insert(2, "closing_strategy", b"Book a free consultation today")
-            .unwrap();
-
         let mut registry = SkillRegistry::new();
-        registry.register(Arc::new(LeadCapture::new(Arc::clone(&memory))));
-        registry.register(Arc::new(DraftResponse::new(
-            Arc::clone(&memory),
-            Arc::clone(&knowledge),
-        )));
-        registry.register(Arc::new(SalesCloser::new(Arc::clone(&knowledge))));
+        registry.register(Arc::new(KnowledgeQuery::new(Arc::clone(&knowledge))));
         registry.register(Arc::new(ModelRouter::new()));
-        let orchestrator = Arc::new(Orchestrator::new(Arc::new(registry)));
+        let blueprint = Arc::new(BlueprintRegistry::empty().with_store(Arc::clone(&knowledge)));
+        let orchestrator = Arc::new(Orchestrator::with_blueprint(Arc::new(registry), blueprint));
         let app = Router::new()
-            .route("/v1/execute", post(execute))
+            .route("/api/v1/blueprints", get(handlers::blueprints::list_blueprints))
+            .route(
+                "/api/v1/blueprints/:intent",
+                get(handlers::blueprints::get_blueprint)
+                    .post(handlers::blueprints::upsert_blueprint)
+                    .delete(handlers::blueprints::delete_blueprint),
+            )
             .with_state(AppState {
-            config: Arc::new(test_config()),
-            orchestrator,
-            knowledge,
-            log_tx: test_log_tx(),
-            model_router: test_model_router(),
-            shadow_store: test_shadow_store(),
-        });
+                config: LiveConfig::new(test_config()),
+                orchestrator,
+                knowledge,
+                log_tx: test_log_tx(),
+                shadow_store: test_shadow_store(),
+                llm_providers: Default::default(),
+                oidc: None,
+            });
 
-        let lead_body = serde_json::json!({
-            "tenant_id": "test-tenant",
-            "goal": {
-                "IngestData": {
-                    "payload": { "email": "lead@example.com", "message": "Interested in services" }
-                }
-            }
-        });
-        let lead_req = Request::builder()
-            .method("POST")
-            .uri("/v1/execute")
-            .header("content-type", "application/json")
-            .body(Body::from(serde_json::to_string(&lead_body).unwrap()))
+        let send = |method: &str, uri: &str, body: Option<serde_json::Value>| {
+            Request::builder()
+                .method(method)
+                .uri(uri)
+                .header("content-type", "application/json")
+                .body(body.map(|b| Body::from(b.to_string())).unwrap_or_else(Body::empty))
+                .unwrap()
+        };
+
+        let res = app
+            .clone()
+            .oneshot(send("POST", "/api/v1/blueprints/draft%20reply", Some(serde_json::json!(["KnowledgeQuery", "ModelRouter"]))))
+            .await
             .unwrap();
-        let lead_res = app.clone().oneshot(lead_req).await.unwrap();
-        assert_eq!(lead_res.status(), StatusCode::OK);
-        let lead_bytes = axum::body::to_bytes(lead_res.into_body(), usize::MAX).await.unwrap();
-        let lead_json: serde_json::Value = serde_json::from_slice(&lead_bytes).unwrap();
-        let lead_id = lead_json["lead_id"].as_str().unwrap().to_string();
+        assert_eq!(res.status(), StatusCode::OK);
 
-        let auto_body = serde_json::json!({
-            "tenant_id": "test-tenant",
-            "goal": {
-                "AutonomousGoal": {
-                    "intent": "respond to lead",
-                    "context": { "lead_id": lead_id }
-                }
-            }
-        });
-        let auto_req = Request::builder()
-            .method("POST")
-            .uri("/v1/execute")
-            .header("content-type", "application/json")
-            .body(Body::from(serde_json::to_string(&auto_body).unwrap()))
+        let res = app
+            .clone()
+            .oneshot(send("POST", "/api/v1/blueprints/bad", Some(serde_json::json!(["NoSuchSkill"]))))
+            .await
             .unwrap();
-        let auto_res = app.oneshot(auto_req).await.unwrap();
-        assert_eq!(auto_res.status(), StatusCode::OK);
-        let auto_bytes = axum::body::to_bytes(auto_res.into_body(), usize::MAX).await.unwrap();
-        let auto_json: serde_json::Value = serde_json::from_slice(&auto_bytes).unwrap();
-        let generated = auto_json["generated"].as_str().expect("generated");
-        assert!(
-            generated.to_lowercase().contains("free consultation"),
-            "final generated response should include the KB-2 sales push (free consultation); got: {}",
-            generated
-        );
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+        let res = app.clone().oneshot(send("GET", "/api/v1/blueprints", None)).await.unwrap();
+        let bytes = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(json["count"], 1);
+        assert_eq!(json["intents"][0]["intent"], "draft reply");
+
+        let res = app.clone().oneshot(send("DELETE", "/api/v1/blueprints/draft%20reply", None)).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        let res = app.oneshot(send("GET", "/api/v1/blueprints/draft%20reply", None)).await.unwrap();
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
     }
 
     #[tokio::test]
-    async fn test_blueprint_alternate_intent_summarize_news() {
-        let knowledge = Arc::new(
-            KnowledgeStore::open_path("./data/pagi_knowledge_blueprint_test").unwrap(),
-        );
+    async fn test_pack_routes_install_upgrade_and_uninstall() {
+        let knowledge = Arc::new(KnowledgeStore::open_temporary().unwrap());
         let mut registry = SkillRegistry::new();
-        registry.register(Arc::new(CommunityScraper::new(Arc::clone(&knowledge))));
         registry.register(Arc::new(ModelRouter::new()));
-
-        let mut intents = std::collections::HashMap::new();
-        intents.insert(
-            "summarize news".to_string(),
-            vec!["CommunityScraper".to_string(), "ModelRouter".to_string()],
-        );
-        let blueprint = Arc::new(BlueprintRegistry::from_intents(intents));
-        let orchestrator = Arc::new(Orchestrator::with_blueprint(
-            Arc::new(registry),
-            Arc::clone(&blueprint),
-        ));
+        let blueprint = Arc::new(BlueprintRegistry::empty().with_store(Arc::clone(&knowledge)));
+        let orchestrator = Arc::new(Orchestrator::with_blueprint(Arc::new(registry), blueprint));
         let app = Router::new()
-            .route("/v1/execute", post(execute))
+            .route("/api/v1/packs", get(handlers::packs::list_packs).post(handlers::packs::install_pack))
+            .route("/api/v1/packs/:agent_id/:name", delete(handlers::packs::uninstall_pack))
             .with_state(AppState {
-            config: Arc::new(test_config()),
-            orchestrator,
-            knowledge,
-            log_tx: test_log_tx(),
-            model_router: test_model_router(),
-            shadow_store: test_shadow_store(),
-        });
-
-        let body = serde_json::json!({
-            "tenant_id": "test-tenant",
-            "goal": {
-                "AutonomousGoal": {
-                    "intent": "summarize news",
-                    "context": {
-                        "slot_id": 5,
-                        "html": "<html><body><h1>Local Election Results</h1><h2>Budget approved</h2></body></html>"
+                config: LiveConfig::new(test_config()),
+                orchestrator: Arc::clone(&orchestrator),
+                knowledge,
+                log_tx: test_log_tx(),
+                shadow_store: test_shadow_store(),
+                llm_providers: Default::default(),
+                oidc: None,
+            });
+        let send = |method: &str, uri: &str, body: Option<serde_json::Value>| {
+            Request::builder()
+                .method(method)
+                .uri(uri)
+                .header("content-type", "application/json")
+                .body(body.map(|b| Body::from(b.to_string())).unwrap_or_else(Body::empty))
+                .unwrap()
+        };
+        let install = |version: &str| {
+            send(
+                "POST",
+                "/api/v1/packs",
+                Some(serde_json::json!({
+                    "agent_id": "sales",
+                    "pack": {
+                        "name": "quotes",
+                        "version": version,
+                        "blueprints": { "quote job": ["ModelRouter"] },
+                        "templates": { "persona": "Quoting assistant" }
                     }
-                }
-            }
-        });
-        let req = Request::builder()
-            .method("POST")
-            .uri("/v1/execute")
-            .header("content-type", "application/json")
-            .body(Body::from(serde_json::to_string(&body).unwrap()))
-            .unwrap();
-        let res = app.oneshot(req).await.unwrap();
+                })),
+            )
+        };
+
+        let res = app.clone().oneshot(install("1.0.0")).await.unwrap();
         assert_eq!(res.status(), StatusCode::OK);
+        assert!(orchestrator.blueprint().plan_for_intent("quote job").is_some());
+
+        let res = app.clone().oneshot(install("1.1.0")).await.unwrap();
         let bytes = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
         let json: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
-        assert_eq!(json["goal"], "AutonomousGoal");
-        assert_eq!(json["intent"], "summarize news");
-        assert_eq!(
-            json["plan_steps"],
-            serde_json::json!(["CommunityScraper", "ModelRouter"])
-        );
-        let generated = json["generated"].as_str().expect("generated");
-        assert!(
-            generated.contains("Election") || generated.contains("Budget") || generated.contains("personalized"),
-            "generated should reflect scraped content or mock; got: {}",
-            generated
-        );
+        assert_eq!(json["install"]["status"], "upgraded");
+        assert_eq!(json["install"]["previous_version"], "1.0.0");
+
+        let res = app.clone().oneshot(install("0.9.0")).await.unwrap();
+        assert_eq!(res.status(), StatusCode::CONFLICT);
+
+        let res = app.clone().oneshot(send("GET", "/api/v1/packs", None)).await.unwrap();
+        let bytes = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(json["count"], 1);
+        assert_eq!(json["packs"][0]["version"], "1.1.0");
+
+        let res = app.clone().oneshot(send("DELETE", "/api/v1/packs/sales/quotes", None)).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        assert!(orchestrator.blueprint().plan_for_intent("quote job").is_none());
+        let res = app.oneshot(send("DELETE", "/api/v1/packs/sales/quotes", None)).await.unwrap();
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
     }
 
     #[tokio::test]
@@ -2498,12 +7494,13 @@ mod tests {
         let app = Router::new()
             .route("/v1/execute", post(execute))
             .with_state(AppState {
-                config: Arc::new(test_config()),
+                config: LiveConfig::new(test_config()),
                 orchestrator,
                 knowledge: Arc::clone(&knowledge),
                 log_tx: test_log_tx(),
-                model_router: test_model_router(),
                 shadow_store: test_shadow_store(),
+                llm_providers: Default::default(),
+                oidc: None,
             });
 
         let prune_body = serde_json::json!({
@@ -2539,4 +7536,291 @@ mod tests {
         assert!(knowledge.get(5, "stale_pulse").unwrap().is_none());
         assert!(knowledge.get(8, "old-trace-id").unwrap().is_none());
     }
+
+    #[tokio::test]
+    async fn test_simulated_clock_drives_heartbeat_and_pruner() {
+        use pagi_core::Clock;
+        let start = pagi_core::SIMULATION_DEFAULT_START_MS;
+        let clock = Arc::new(FrozenClock::new(start));
+        let knowledge = Arc::new(
+            KnowledgeStore::open_temporary()
+                .unwrap()
+                .with_clock(Arc::clone(&clock) as Arc<dyn pagi_core::Clock>),
+        );
+        let fixtures: pagi_skills::MockFixtures = serde_json::from_value(serde_json::json!({
+            "fixtures": [{ "contains": ["invoice"], "responses": ["The invoice is on its way."] }]
+        }))
+        .unwrap();
+        let model_router = Arc::new(ModelRouter::with_knowledge(Arc::clone(&knowledge)).with_fixtures(Arc::new(fixtures)));
+        let orchestrator = Orchestrator::new(Arc::new(SkillRegistry::new()));
+        let mut support = pagi_core::AgentRecord::new("support", None, Vec::new());
+        support.tick_interval_secs = Some(60);
+        knowledge.create_agent(&support).unwrap();
+        let mut ticks = AgentTickTracker::default();
+
+        knowledge.push_agent_message("sales", "support", &serde_json::json!({ "text": "Where is my invoice?" })).unwrap();
+        heartbeat_tick(Arc::clone(&knowledge), Arc::clone(&model_router), &orchestrator, &mut ticks).await.unwrap();
+        let replies = knowledge.get_agent_messages("sales", 5).unwrap();
+        assert_eq!(replies[0].payload["text"], "The invoice is on its way.");
+        assert_eq!(knowledge.get_recent_chronos_events("support", 1).unwrap()[0].timestamp_ms, start);
+
+        // The agent's 60s tick interval is measured on the frozen clock, not the wall clock.
+        knowledge.push_agent_message("sales", "support", &serde_json::json!({ "text": "Another invoice?" })).unwrap();
+        heartbeat_tick(Arc::clone(&knowledge), Arc::clone(&model_router), &orchestrator, &mut ticks).await.unwrap();
+        assert_eq!(knowledge.get_agent_messages("sales", 5).unwrap().len(), 1);
+        clock.advance(60_000);
+        heartbeat_tick(Arc::clone(&knowledge), Arc::clone(&model_router), &orchestrator, &mut ticks).await.unwrap();
+        assert_eq!(knowledge.get_agent_messages("sales", 5).unwrap().len(), 2);
+
+        let trust = bump_kardia_trust(&knowledge, "SAGE_BOT", "DEV_BOT", -0.1, "Stale maintenance").unwrap();
+        let relation = knowledge.get_kardia_relation("SAGE_BOT", "DEV_BOT").unwrap();
+        assert_eq!((relation.trust_score, relation.last_updated_ms), (trust, start + 60_000));
+
+        // A pulse 12 hours old survives a one-day retention until the clock moves a day on.
+        let pulse_at = (clock.now_secs() - 12 * 3600).to_string();
+        knowledge.insert(5, "pulse", format!(r#"{{"updated_at": {}}}"#, pulse_at).as_bytes()).unwrap();
+        let pruner = KnowledgePruner::new(Arc::clone(&knowledge));
+        let ctx = TenantContext::new("test");
+        let payload = serde_json::json!({ "kb5_max_age_days": 1, "kb8_max_age_days": 0 });
+        assert_eq!(pruner.execute(&ctx, Some(payload.clone())).await.unwrap()["kb5_pruned"], 0);
+        clock.advance(24 * 3600 * 1000);
+        assert_eq!(pruner.execute(&ctx, Some(payload)).await.unwrap()["kb5_pruned"], 1);
+    }
+
+    #[tokio::test]
+    async fn test_api_key_roles_gate_routes() {
+        let state = AppState {
+            config: LiveConfig::new(test_config()),
+            orchestrator: Arc::new(Orchestrator::new(Arc::new(SkillRegistry::new()))),
+            knowledge: Arc::new(KnowledgeStore::open_temporary().unwrap()),
+            log_tx: test_log_tx(),
+            shadow_store: test_shadow_store(),
+            llm_providers: Default::default(),
+            oidc: None,
+        };
+        let app = Router::new()
+            .route("/v1/status", get(status))
+            .route("/v1/execute", post(execute))
+            .route("/api/v1/usage/:tenant_id", get(handlers::usage::get_usage))
+            .route("/api/v1/auth/whoami", get(handlers::auth::whoami))
+            .route("/api/v1/auth/keys", get(handlers::auth::list_keys).post(handlers::auth::create_key))
+            .route("/api/v1/auth/keys/:key_id", delete(handlers::auth::revoke_key))
+            .route(handlers::recorder::API_RECORDER_ROUTE, get(handlers::recorder::list_exchanges))
+            .route("/api/v1/recorder/exchanges/:id", get(handlers::recorder::get_exchange))
+            .route_layer(axum::middleware::from_fn_with_state(state.clone(), handlers::auth::authorize))
+            .with_state(state);
+        let call = |method: &str, uri: &str, key: Option<&str>, body: Option<serde_json::Value>| {
+            let mut req = Request::builder().method(method).uri(uri);
+            if let Some(key) = key {
+                req = req.header("X-API-Key", key);
+            }
+            let body = match body {
+                Some(b) => {
+                    req = req.header("content-type", "application/json");
+                    Body::from(b.to_string())
+                }
+                None => Body::empty(),
+            };
+            let app = app.clone();
+            let req = req.body(body).unwrap();
+            async move {
+                let res = app.oneshot(req).await.unwrap();
+                let status = res.status();
+                let bytes = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+                (status, serde_json::from_slice::<serde_json::Value>(&bytes).unwrap_or_default())
+            }
+        };
+
+        // No keys yet: the gateway is open, so the first admin key can be issued.
+        let (status, json) = call("GET", "/api/v1/auth/whoami", None, None).await;
+        assert_eq!((status, json["auth_enabled"].as_bool()), (StatusCode::OK, Some(false)));
+        let (status, json) =
+            call("POST", "/api/v1/auth/keys", None, Some(serde_json::json!({ "name": "root", "role": "admin" }))).await;
+        assert_eq!(status, StatusCode::CREATED);
+        let admin = json["secret"].as_str().unwrap().to_string();
+        assert!(admin.starts_with("pagi_"));
+
+        // From now on a key is required.
+        assert_eq!(call("GET", "/v1/status", None, None).await.0, StatusCode::UNAUTHORIZED);
+        assert_eq!(call("GET", "/v1/status", Some("pagi_nope"), None).await.0, StatusCode::UNAUTHORIZED);
+        let (status, json) = call("GET", "/api/v1/auth/whoami", Some(&admin), None).await;
+        assert_eq!((status, json["caller"]["role"].as_str()), (StatusCode::OK, Some("admin")));
+
+        let issue = |body: serde_json::Value| call("POST", "/api/v1/auth/keys", Some(&admin), Some(body));
+        let (status, json) = issue(serde_json::json!({ "name": "bad", "role": "tenant" })).await;
+        assert_eq!((status, json["status"].as_str()), (StatusCode::BAD_REQUEST, Some("error")));
+        let readonly = issue(serde_json::json!({ "name": "dash", "role": "readonly" })).await.1;
+        let readonly = readonly["secret"].as_str().unwrap().to_string();
+        let tenant = issue(serde_json::json!({ "name": "acme", "role": "tenant", "tenant_id": "acme" })).await.1;
+        let (tenant_id, tenant) = (
+            tenant["key"]["key_id"].as_str().unwrap().to_string(),
+            tenant["secret"].as_str().unwrap().to_string(),
+        );
+
+        // Readonly: reads only.
+        assert_eq!(call("GET", "/v1/status", Some(&readonly), None).await.0, StatusCode::OK);
+        let goal = |tenant_id: &str| {
+            serde_json::json!({ "tenant_id": tenant_id, "goal": { "QueryKnowledge": { "slot_id": 1, "query": "x" } } })
+        };
+        let (status, json) = call("POST", "/v1/execute", Some(&readonly), Some(goal("acme"))).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert_eq!(json["error"], "role readonly lacks the execute permission");
+        assert_eq!(call("GET", "/api/v1/auth/keys", Some(&readonly), None).await.0, StatusCode::FORBIDDEN);
+        // Recorded exchanges carry every tenant's bodies, like the knowledge export.
+        for path in ["/api/v1/recorder/exchanges", "/api/v1/recorder/exchanges/abc"] {
+            assert_eq!(call("GET", path, Some(&readonly), None).await.0, StatusCode::FORBIDDEN);
+            assert_eq!(call("GET", path, Some(&tenant), None).await.0, StatusCode::FORBIDDEN);
+        }
+
+        // Tenant: its own tenant only, and no key management.
+        assert_eq!(call("GET", "/api/v1/usage/acme", Some(&tenant), None).await.0, StatusCode::OK);
+        assert_eq!(call("GET", "/api/v1/usage/globex", Some(&tenant), None).await.0, StatusCode::FORBIDDEN);
+        assert_eq!(call("POST", "/v1/execute", Some(&tenant), Some(goal("acme"))).await.0, StatusCode::OK);
+        assert_eq!(call("POST", "/v1/execute", Some(&tenant), Some(goal("globex"))).await.0, StatusCode::FORBIDDEN);
+        assert_eq!(call("GET", "/api/v1/auth/whoami", Some(&tenant), None).await.0, StatusCode::OK);
+        assert_eq!(call("GET", "/api/v1/auth/keys", Some(&tenant), None).await.0, StatusCode::FORBIDDEN);
+
+        // Listings never carry secrets; a revoked key stops working at once.
+        let (_, json) = call("GET", "/api/v1/auth/keys", Some(&admin), None).await;
+        assert_eq!(json["count"], 3);
+        assert!(!json.to_string().contains(&tenant));
+        let (status, json) = call("DELETE", &format!("/api/v1/auth/keys/{}", tenant_id), Some(&admin), None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(json["key"]["revoked_at_ms"].is_i64());
+        assert_eq!(call("GET", "/api/v1/usage/acme", Some(&tenant), None).await.0, StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_oidc_tokens_map_claims_to_role_and_tenant() {
+        use oidc::tests::{serve_issuer, TestSigner};
+        let signer = TestSigner::new("sso-1");
+        let jwks = Arc::new(std::sync::Mutex::new(serde_json::json!({ "keys": [signer.jwk()] })));
+        let issuer = serve_issuer(jwks).await;
+        let oidc_config = pagi_core::OidcConfig {
+            issuer: Some(issuer.clone()),
+            audiences: vec!["pagi-gateway".to_string()],
+            roles_claim: "realm_access.roles".to_string(),
+            role_map: [("pagi-ops".to_string(), pagi_core::Role::Operator)].into_iter().collect(),
+            ..Default::default()
+        };
+        let state = AppState {
+            config: LiveConfig::new(test_config()),
+            orchestrator: Arc::new(Orchestrator::new(Arc::new(SkillRegistry::new()))),
+            knowledge: Arc::new(KnowledgeStore::open_temporary().unwrap()),
+            log_tx: test_log_tx(),
+            shadow_store: test_shadow_store(),
+            llm_providers: Default::default(),
+            oidc: oidc::OidcVerifier::from_config(&oidc_config).unwrap().map(Arc::new),
+        };
+        let app = Router::new()
+            .route("/v1/execute", post(execute))
+            .route("/api/v1/usage/:tenant_id", get(handlers::usage::get_usage))
+            .route("/api/v1/auth/whoami", get(handlers::auth::whoami))
+            .route_layer(axum::middleware::from_fn_with_state(state.clone(), handlers::auth::authorize))
+            .with_state(state);
+        let exp = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_secs() as i64 + 300;
+        let token = |claims: serde_json::Value| {
+            let mut all = serde_json::json!({ "iss": issuer, "aud": "pagi-gateway", "sub": "u-42", "exp": exp });
+            all.as_object_mut().unwrap().extend(claims.as_object().unwrap().clone());
+            signer.sign(&all)
+        };
+        let call = |method: &str, uri: &str, token: &str, body: Option<serde_json::Value>| {
+            let req = Request::builder()
+                .method(method)
+                .uri(uri)
+                .header("Authorization", format!("Bearer {}", token))
+                .header("content-type", "application/json")
+                .body(body.map_or_else(Body::empty, |b| Body::from(b.to_string())))
+                .unwrap();
+            let app = app.clone();
+            async move {
+                let res = app.oneshot(req).await.unwrap();
+                let status = res.status();
+                let bytes = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+                (status, serde_json::from_slice::<serde_json::Value>(&bytes).unwrap_or_default())
+            }
+        };
+
+        // OIDC alone turns enforcement on.
+        let anonymous = Request::builder().uri("/api/v1/auth/whoami").body(Body::empty()).unwrap();
+        assert_eq!(app.clone().oneshot(anonymous).await.unwrap().status(), StatusCode::UNAUTHORIZED);
+
+        let ops = token(serde_json::json!({
+            "preferred_username": "dana", "tenant_id": "acme", "realm_access": { "roles": ["offline_access", "pagi-ops"] }
+        }));
+        let (status, json) = call("GET", "/api/v1/auth/whoami", &ops, None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(json["caller"], serde_json::json!({ "via": "oidc", "id": "u-42", "name": "dana", "role": "operator", "tenant_id": "acme" }));
+
+        // The tenant comes from the token: the body may leave it out, and may not name another.
+        let goal = serde_json::json!({ "QueryKnowledge": { "slot_id": 1, "query": "x" } });
+        let (status, _) = call("POST", "/v1/execute", &ops, Some(serde_json::json!({ "goal": goal }))).await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, json) =
+            call("POST", "/v1/execute", &ops, Some(serde_json::json!({ "tenant_id": "globex", "goal": goal }))).await;
+        assert_eq!((status, json["error"].as_str()), (StatusCode::FORBIDDEN, Some("dana may only act for tenant \"acme\"")));
+        assert_eq!(call("GET", "/api/v1/usage/acme", &ops, None).await.0, StatusCode::OK);
+        assert_eq!(call("GET", "/api/v1/usage/globex", &ops, None).await.0, StatusCode::FORBIDDEN);
+
+        // Tokens without a mapped role, and bad tokens, are refused.
+        let (status, json) = call("GET", "/api/v1/auth/whoami", &token(serde_json::json!({ "roles": ["viewer"] })), None).await;
+        assert_eq!((status, json["error"].as_str()), (StatusCode::FORBIDDEN, Some("token grants no role (realm_access.roles claim)")));
+        let other_audience = token(serde_json::json!({ "aud": "billing", "realm_access": { "roles": ["admin"] } }));
+        let (status, json) = call("GET", "/api/v1/auth/whoami", &other_audience, None).await;
+        assert_eq!((status, json["error"].as_str()), (StatusCode::UNAUTHORIZED, Some("Invalid token: token is for another audience")));
+    }
+
+    #[tokio::test]
+    async fn test_body_limits_refuse_oversized_requests_per_route() {
+        let mut config = test_config();
+        config.limits.max_body_bytes = 256;
+        config.limits.route_body_bytes.insert("/v1/execute".to_string(), 64 * 1024);
+        let state = AppState {
+            config: LiveConfig::new(config),
+            orchestrator: Arc::new(Orchestrator::new(Arc::new(SkillRegistry::new()))),
+            knowledge: Arc::new(KnowledgeStore::open_temporary().unwrap()),
+            log_tx: test_log_tx(),
+            shadow_store: test_shadow_store(),
+            llm_providers: Default::default(),
+            oidc: None,
+        };
+        let app = Router::new()
+            .route("/v1/execute", post(execute))
+            .route("/echo", post(|Json(body): Json<serde_json::Value>| async move { Json(body) }))
+            .route_layer(axum::middleware::from_fn_with_state(state.clone(), handlers::body_limit::limit_body))
+            .layer(axum::extract::DefaultBodyLimit::disable())
+            .with_state(state);
+        let call = |uri: &str, body: serde_json::Value, declare_length: bool| {
+            let body = body.to_string();
+            let mut req = Request::builder().method("POST").uri(uri).header("content-type", "application/json");
+            if declare_length {
+                req = req.header("content-length", body.len());
+            }
+            let req = req.body(Body::from(body)).unwrap();
+            let app = app.clone();
+            async move {
+                let res = app.oneshot(req).await.unwrap();
+                let status = res.status();
+                let bytes = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+                (status, serde_json::from_slice::<serde_json::Value>(&bytes).unwrap_or_default())
+            }
+        };
+        let padding = "x".repeat(1024);
+
+        let (status, json) = call("/echo", serde_json::json!({ "note": "small" }), true).await;
+        assert_eq!((status, json["note"].as_str()), (StatusCode::OK, Some("small")));
+        // Declared lengths over the limit are refused before the handler runs.
+        let (status, json) = call("/echo", serde_json::json!({ "note": padding }), true).await;
+        assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+        assert_eq!(json["limit_bytes"], 256);
+        assert!(json["error"].as_str().unwrap().contains("the limit for /echo is 256 bytes"));
+        // Bodies without a length are cut off at the limit.
+        let (status, _) = call("/echo", serde_json::json!({ "note": padding }), false).await;
+        assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+
+        // The route override lets larger bodies through.
+        let goal = serde_json::json!({ "QueryKnowledge": { "slot_id": 1, "query": "x" } });
+        let body = serde_json::json!({ "tenant_id": "t1", "goal": goal, "note": padding });
+        assert_eq!(call("/v1/execute", body, true).await.0, StatusCode::OK);
+    }
 }
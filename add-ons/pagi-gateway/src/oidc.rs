@@ -0,0 +1,352 @@
+//! OIDC bearer tokens: validates JWTs from the `[oidc]` issuer against its published signing keys.
+//!
+//! Keys come from the JWKS endpoint (`jwks_uri`, or the one named by
+//! `{issuer}/.well-known/openid-configuration`) and are cached. They are refetched every
+//! `jwks_refresh_secs`, and early (at most once a minute) when a token names a `kid` the cache
+//! lacks, so the provider can rotate keys without a gateway restart. RS256/384/512 and ES256/384
+//! are accepted; `none` and shared-secret (`HS*`) tokens never are. A valid token names the issuer
+//! and one of the `audiences`, and is inside `exp`/`nbf` (± `leeway_secs`). Its claims then map to
+//! a role and a tenant (`OidcConfig::principal`).
+
+use base64::Engine;
+use pagi_core::{Clock, OidcConfig, SystemClock};
+use ring::signature;
+use serde::Deserialize;
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
+
+/// Least time between two JWKS fetches for unknown `kid`s, and before retrying a failed fetch.
+const REFETCH_BACKOFF: Duration = Duration::from_secs(60);
+
+/// One key of the provider's JWKS.
+#[derive(Debug, Clone, Deserialize)]
+struct Jwk {
+    kty: String,
+    #[serde(default)]
+    kid: Option<String>,
+    #[serde(default)]
+    alg: Option<String>,
+    #[serde(default, rename = "use")]
+    usage: Option<String>,
+    /// RSA modulus and exponent.
+    #[serde(default)]
+    n: Option<String>,
+    #[serde(default)]
+    e: Option<String>,
+    /// EC curve and point.
+    #[serde(default)]
+    crv: Option<String>,
+    #[serde(default)]
+    x: Option<String>,
+    #[serde(default)]
+    y: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct JwkSet {
+    keys: Vec<Jwk>,
+}
+
+#[derive(Deserialize)]
+struct JwtHeader {
+    alg: String,
+    #[serde(default)]
+    kid: Option<String>,
+}
+
+#[derive(Default)]
+struct KeyCache {
+    keys: Vec<Jwk>,
+    fetched_at: Option<Instant>,
+    failed_at: Option<Instant>,
+    /// Last fetch for a `kid` the cache lacked.
+    unknown_kid_at: Option<Instant>,
+}
+
+/// Validates bearer JWTs for the gateway's auth layer.
+pub(crate) struct OidcVerifier {
+    config: OidcConfig,
+    /// `iss` to expect, without a trailing slash.
+    issuer: String,
+    http: reqwest::Client,
+    cache: RwLock<KeyCache>,
+}
+
+impl OidcVerifier {
+    /// The verifier `[oidc]` asks for; None when OIDC is off, Err when it is misconfigured.
+    pub(crate) fn from_config(config: &OidcConfig) -> Result<Option<Self>, String> {
+        if !config.is_enabled() {
+            return Ok(None);
+        }
+        if config.audiences.iter().all(|a| a.trim().is_empty()) {
+            return Err("[oidc] needs at least one entry in audiences".to_string());
+        }
+        Ok(Some(Self {
+            issuer: normalize_issuer(config.issuer.as_deref().unwrap_or_default()),
+            config: config.clone(),
+            http: reqwest::Client::builder()
+                .timeout(Duration::from_secs(10))
+                .build()
+                .unwrap_or_default(),
+            cache: RwLock::default(),
+        }))
+    }
+
+    pub(crate) fn config(&self) -> &OidcConfig {
+        &self.config
+    }
+
+    /// Whether `credential` looks like a JWT rather than an API key.
+    pub(crate) fn is_token(credential: &str) -> bool {
+        credential.starts_with("eyJ") && credential.split('.').count() == 3
+    }
+
+    /// The claims of `token` once its signature, issuer, audience and lifetime check out; Err
+    /// says why it is refused.
+    pub(crate) async fn verify(&self, token: &str) -> Result<serde_json::Value, String> {
+        let mut parts = token.split('.');
+        let (Some(header_b64), Some(claims_b64), Some(signature_b64), None) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            return Err("malformed token".to_string());
+        };
+        let header: JwtHeader =
+            serde_json::from_slice(&decode_segment(header_b64)?).map_err(|_| "malformed token header".to_string())?;
+        let family = match header.alg.as_str() {
+            "RS256" | "RS384" | "RS512" => "RSA",
+            "ES256" | "ES384" => "EC",
+            other => return Err(format!("unsupported token algorithm {:?}", other)),
+        };
+        let key = self.signing_key(header.kid.as_deref(), &header.alg, family).await?;
+        let signed = &token[..header_b64.len() + 1 + claims_b64.len()];
+        verify_signature(&key, &header.alg, signed.as_bytes(), &decode_segment(signature_b64)?)?;
+        let claims: serde_json::Value =
+            serde_json::from_slice(&decode_segment(claims_b64)?).map_err(|_| "malformed token claims".to_string())?;
+        self.check_claims(&claims, SystemClock.now_secs() as i64)?;
+        Ok(claims)
+    }
+
+    fn check_claims(&self, claims: &serde_json::Value, now_secs: i64) -> Result<(), String> {
+        let issuer = claims.get("iss").and_then(|v| v.as_str()).map(normalize_issuer);
+        if issuer.as_deref() != Some(self.issuer.as_str()) {
+            return Err("token is from another issuer".to_string());
+        }
+        let audiences: Vec<&str> = match claims.get("aud") {
+            Some(serde_json::Value::String(aud)) => vec![aud.as_str()],
+            Some(serde_json::Value::Array(auds)) => auds.iter().filter_map(|a| a.as_str()).collect(),
+            _ => Vec::new(),
+        };
+        if !audiences.iter().any(|aud| self.config.audiences.iter().any(|a| a == aud)) {
+            return Err("token is for another audience".to_string());
+        }
+        let leeway = self.config.leeway_secs as i64;
+        let exp = claims.get("exp").and_then(|v| v.as_i64()).ok_or("token has no exp claim")?;
+        if now_secs > exp.saturating_add(leeway) {
+            return Err("token expired".to_string());
+        }
+        if let Some(nbf) = claims.get("nbf").and_then(|v| v.as_i64()) {
+            if now_secs.saturating_add(leeway) < nbf {
+                return Err("token not yet valid".to_string());
+            }
+        }
+        Ok(())
+    }
+
+    /// The cached key for `kid`, refetching the JWKS when it is due or lacks the key.
+    async fn signing_key(&self, kid: Option<&str>, alg: &str, family: &str) -> Result<Jwk, String> {
+        let refresh = Duration::from_secs(self.config.jwks_refresh_secs.max(1));
+        let recent = |at: Option<Instant>| at.is_some_and(|t| t.elapsed() < REFETCH_BACKOFF);
+        let (due, found, may_refetch) = {
+            let cache = self.cache.read().unwrap_or_else(|e| e.into_inner());
+            let failing = recent(cache.failed_at);
+            let stale = cache.fetched_at.is_none_or(|t| t.elapsed() >= refresh);
+            let found = find_key(&cache.keys, kid, alg, family);
+            (stale && !failing, found, !failing && !recent(cache.unknown_kid_at))
+        };
+        if due || (found.is_none() && may_refetch) {
+            if !due {
+                self.cache.write().unwrap_or_else(|e| e.into_inner()).unknown_kid_at = Some(Instant::now());
+            }
+            if let Err(e) = self.refresh().await {
+                self.cache.write().unwrap_or_else(|e| e.into_inner()).failed_at = Some(Instant::now());
+                tracing::warn!(target: "pagi::auth", error = %e, "JWKS refresh failed");
+            }
+            let cache = self.cache.read().unwrap_or_else(|e| e.into_inner());
+            if let Some(key) = find_key(&cache.keys, kid, alg, family) {
+                return Ok(key);
+            }
+        }
+        found.ok_or_else(|| format!("no {} signing key {:?} in the issuer's JWKS", family, kid.unwrap_or("")))
+    }
+
+    async fn refresh(&self) -> Result<(), String> {
+        let jwks_uri = match self.config.jwks_uri.as_deref().map(str::trim).filter(|u| !u.is_empty()) {
+            Some(uri) => uri.to_string(),
+            None => {
+                let discovery: serde_json::Value =
+                    self.get_json(&format!("{}/.well-known/openid-configuration", self.issuer)).await?;
+                discovery
+                    .get("jwks_uri")
+                    .and_then(|u| u.as_str())
+                    .ok_or("discovery document has no jwks_uri")?
+                    .to_string()
+            }
+        };
+        let set: JwkSet = self.get_json(&jwks_uri).await?;
+        tracing::info!(target: "pagi::auth", keys = set.keys.len(), jwks_uri = %jwks_uri, "JWKS fetched");
+        let mut cache = self.cache.write().unwrap_or_else(|e| e.into_inner());
+        cache.keys = set.keys;
+        cache.fetched_at = Some(Instant::now());
+        cache.failed_at = None;
+        Ok(())
+    }
+
+    async fn get_json<T: serde::de::DeserializeOwned>(&self, url: &str) -> Result<T, String> {
+        let res = self.http.get(url).send().await.map_err(|e| format!("{}: {}", url, e))?;
+        if !res.status().is_success() {
+            return Err(format!("{}: HTTP {}", url, res.status()));
+        }
+        res.json().await.map_err(|e| format!("{}: {}", url, e))
+    }
+}
+
+fn normalize_issuer(issuer: &str) -> String {
+    issuer.trim().trim_end_matches('/').to_string()
+}
+
+fn decode_segment(segment: &str) -> Result<Vec<u8>, String> {
+    base64::engine::general_purpose::URL_SAFE_NO_PAD
+        .decode(segment.trim_end_matches('='))
+        .map_err(|_| "malformed token".to_string())
+}
+
+/// A signing key of `family` for `alg`: the one named `kid`, or the first when the token names none.
+fn find_key(keys: &[Jwk], kid: Option<&str>, alg: &str, family: &str) -> Option<Jwk> {
+    keys.iter()
+        .filter(|k| k.kty == family && k.usage.as_deref() != Some("enc"))
+        .filter(|k| k.alg.as_deref().is_none_or(|a| a == alg))
+        .find(|k| kid.is_none() || k.kid.as_deref() == kid)
+        .cloned()
+}
+
+fn verify_signature(key: &Jwk, alg: &str, signed: &[u8], sig: &[u8]) -> Result<(), String> {
+    let component = |value: &Option<String>| decode_segment(value.as_deref().unwrap_or_default());
+    let verified = match alg {
+        "RS256" | "RS384" | "RS512" => {
+            let params = match alg {
+                "RS256" => &signature::RSA_PKCS1_2048_8192_SHA256,
+                "RS384" => &signature::RSA_PKCS1_2048_8192_SHA384,
+                _ => &signature::RSA_PKCS1_2048_8192_SHA512,
+            };
+            let (n, e) = (component(&key.n)?, component(&key.e)?);
+            signature::RsaPublicKeyComponents { n: &n, e: &e }.verify(params, signed, sig)
+        }
+        _ => {
+            let (params, curve) = match alg {
+                "ES256" => (&signature::ECDSA_P256_SHA256_FIXED, "P-256"),
+                _ => (&signature::ECDSA_P384_SHA384_FIXED, "P-384"),
+            };
+            if key.crv.as_deref() != Some(curve) {
+                return Err(format!("{} needs a {} key", alg, curve));
+            }
+            // Uncompressed SEC1 point.
+            let mut point = vec![0x04];
+            point.extend(component(&key.x)?);
+            point.extend(component(&key.y)?);
+            signature::UnparsedPublicKey::new(params, &point).verify(signed, sig)
+        }
+    };
+    verified.map_err(|_| "token signature does not verify".to_string())
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use axum::{routing::get, Json, Router};
+    use ring::rand::SystemRandom;
+    use ring::signature::{EcdsaKeyPair, KeyPair};
+    use std::sync::Arc;
+
+    /// An ES256 signing key and its JWK.
+    pub(crate) struct TestSigner {
+        pair: EcdsaKeyPair,
+        pub(crate) kid: String,
+    }
+
+    impl TestSigner {
+        pub(crate) fn new(kid: &str) -> Self {
+            let rng = SystemRandom::new();
+            let alg = &signature::ECDSA_P256_SHA256_FIXED_SIGNING;
+            let pkcs8 = EcdsaKeyPair::generate_pkcs8(alg, &rng).unwrap();
+            Self {
+                pair: EcdsaKeyPair::from_pkcs8(alg, pkcs8.as_ref(), &rng).unwrap(),
+                kid: kid.to_string(),
+            }
+        }
+
+        pub(crate) fn jwk(&self) -> serde_json::Value {
+            let point = self.pair.public_key().as_ref();
+            let b64 = |bytes: &[u8]| base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(bytes);
+            serde_json::json!({
+                "kty": "EC", "crv": "P-256", "alg": "ES256", "use": "sig", "kid": self.kid,
+                "x": b64(&point[1..33]), "y": b64(&point[33..]),
+            })
+        }
+
+        pub(crate) fn sign(&self, claims: &serde_json::Value) -> String {
+            let b64 = |bytes: &[u8]| base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(bytes);
+            let header = serde_json::json!({ "alg": "ES256", "typ": "JWT", "kid": self.kid });
+            let signed = format!("{}.{}", b64(header.to_string().as_bytes()), b64(claims.to_string().as_bytes()));
+            let sig = self.pair.sign(&SystemRandom::new(), signed.as_bytes()).unwrap();
+            format!("{}.{}", signed, b64(sig.as_ref()))
+        }
+    }
+
+    /// Serves discovery and a JWKS (swappable, to rotate keys) on a local port; returns the issuer.
+    pub(crate) async fn serve_issuer(jwks: Arc<std::sync::Mutex<serde_json::Value>>) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let issuer = format!("http://{}", listener.local_addr().unwrap());
+        let discovery = serde_json::json!({ "issuer": issuer, "jwks_uri": format!("{}/keys", issuer) });
+        let app = Router::new()
+            .route("/.well-known/openid-configuration", get(move || async move { Json(discovery) }))
+            .route("/keys", get(move || async move { Json(jwks.lock().unwrap().clone()) }));
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        issuer
+    }
+
+    #[tokio::test]
+    async fn tokens_verify_against_the_discovered_jwks_and_follow_rotation() {
+        let old = TestSigner::new("k1");
+        let jwks = Arc::new(std::sync::Mutex::new(serde_json::json!({ "keys": [old.jwk()] })));
+        let issuer = serve_issuer(Arc::clone(&jwks)).await;
+        let verifier = OidcVerifier::from_config(&OidcConfig {
+            issuer: Some(format!("{}/", issuer)),
+            audiences: vec!["pagi".to_string()],
+            ..OidcConfig::default()
+        })
+        .unwrap()
+        .unwrap();
+        let now = SystemClock.now_secs() as i64;
+        let claims = |aud: &str, exp: i64| serde_json::json!({ "iss": issuer, "aud": [aud], "sub": "u1", "exp": exp });
+
+        let token = old.sign(&claims("pagi", now + 300));
+        assert!(OidcVerifier::is_token(&token));
+        assert_eq!(verifier.verify(&token).await.unwrap()["sub"], "u1");
+        assert_eq!(verifier.verify(&old.sign(&claims("other", now + 300))).await.unwrap_err(), "token is for another audience");
+        assert_eq!(verifier.verify(&old.sign(&claims("pagi", now - 3600))).await.unwrap_err(), "token expired");
+        let mut forged = token.clone();
+        forged.replace_range(forged.len() - 4.., "AAAA");
+        assert_eq!(verifier.verify(&forged).await.unwrap_err(), "token signature does not verify");
+        let unsigned = format!("eyJhbGciOiJub25lIn0.{}.", token.split('.').nth(1).unwrap());
+        assert!(verifier.verify(&unsigned).await.unwrap_err().contains("unsupported token algorithm"));
+
+        // The provider rotates its key: the unknown kid triggers a refetch.
+        let new = TestSigner::new("k2");
+        *jwks.lock().unwrap() = serde_json::json!({ "keys": [new.jwk()] });
+        assert!(verifier.verify(&new.sign(&claims("pagi", now + 300))).await.is_ok());
+        // A second unknown kid within the backoff is refused without another fetch.
+        let unknown = TestSigner::new("k3");
+        *jwks.lock().unwrap() = serde_json::json!({ "keys": [unknown.jwk()] });
+        assert!(verifier.verify(&unknown.sign(&claims("pagi", now + 300))).await.unwrap_err().contains("\"k3\""));
+    }
+}
@@ -0,0 +1,120 @@
+//! Listeners: the gateway serves the same app on every address of `bind_addresses` (default
+//! `127.0.0.1:{port}`), over HTTPS when `[tls]` names a certificate and key, else plain HTTP.
+//!
+//! All addresses are bound before anything is served, so a taken port fails startup instead of
+//! leaving the gateway half up. HTTPS connections negotiate HTTP/2 or HTTP/1.1 (ALPN) and keep
+//! WebSocket upgrades working.
+
+use axum::Router;
+use hyper_util::rt::{TokioExecutor, TokioIo};
+use pagi_core::TlsConfig;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::net::TcpListener;
+use tokio_rustls::rustls;
+use tokio_rustls::rustls::pki_types::pem::PemObject;
+use tokio_rustls::rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use tokio_rustls::TlsAcceptor;
+
+/// The HTTPS acceptor for `[tls]`, or None when it is not configured.
+pub(crate) fn tls_acceptor(config: &TlsConfig) -> Result<Option<TlsAcceptor>, String> {
+    let (Some(cert_path), Some(key_path)) = (config.cert_path.as_deref(), config.key_path.as_deref()) else {
+        return Ok(None);
+    };
+    let read = |path: &str| std::fs::read(path).map_err(|e| format!("tls: cannot read {}: {}", path, e));
+    let certs = CertificateDer::pem_slice_iter(&read(cert_path)?)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("tls: {} is not a PEM certificate chain: {}", cert_path, e))?;
+    if certs.is_empty() {
+        return Err(format!("tls: no certificate in {}", cert_path));
+    }
+    let key = PrivateKeyDer::from_pem_slice(&read(key_path)?)
+        .map_err(|e| format!("tls: no PEM private key in {}: {}", key_path, e))?;
+    let mut server = rustls::ServerConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+        .with_safe_default_protocol_versions()
+        .map_err(|e| format!("tls: {}", e))?
+        .with_no_client_auth()
+        .with_single_cert(certs, key)
+        .map_err(|e| format!("tls: {} does not fit {}: {}", key_path, cert_path, e))?;
+    server.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+    Ok(Some(TlsAcceptor::from(Arc::new(server))))
+}
+
+/// Binds every address, failing on the first that cannot be bound.
+pub(crate) async fn bind(addrs: &[SocketAddr]) -> Result<Vec<TcpListener>, String> {
+    let mut listeners = Vec::with_capacity(addrs.len());
+    for addr in addrs {
+        listeners.push(TcpListener::bind(addr).await.map_err(|e| format!("cannot listen on {}: {}", addr, e))?);
+    }
+    Ok(listeners)
+}
+
+/// Serves `app` on every listener until one of them fails.
+pub(crate) async fn serve(listeners: Vec<TcpListener>, app: Router, tls: Option<TlsAcceptor>) -> std::io::Result<()> {
+    let mut servers = tokio::task::JoinSet::new();
+    for listener in listeners {
+        let app = app.clone();
+        match tls.clone() {
+            Some(acceptor) => servers.spawn(serve_tls(listener, acceptor, app)),
+            None => servers.spawn(async move { axum::serve(listener, app).await }),
+        };
+    }
+    match servers.join_next().await {
+        Some(Ok(result)) => result,
+        Some(Err(e)) => Err(std::io::Error::other(e)),
+        None => Ok(()),
+    }
+}
+
+async fn serve_tls(listener: TcpListener, acceptor: TlsAcceptor, app: Router) -> std::io::Result<()> {
+    loop {
+        let (tcp, peer) = match listener.accept().await {
+            Ok(conn) => conn,
+            Err(e) => {
+                // Per-connection errors (e.g. reset before accept, out of descriptors) are not fatal.
+                tracing::debug!(target: "pagi::gateway", error = %e, "accept failed");
+                tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+                continue;
+            }
+        };
+        let acceptor = acceptor.clone();
+        let service = hyper_util::service::TowerToHyperService::new(app.clone());
+        tokio::spawn(async move {
+            let stream = match acceptor.accept(tcp).await {
+                Ok(stream) => stream,
+                Err(e) => {
+                    tracing::debug!(target: "pagi::gateway", peer = %peer, error = %e, "TLS handshake failed");
+                    return;
+                }
+            };
+            // Errors here are clients going away mid-connection.
+            let _ = hyper_util::server::conn::auto::Builder::new(TokioExecutor::new())
+                .serve_connection_with_upgrades(TokioIo::new(stream), service)
+                .await;
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tls_acceptor_reports_unusable_files() {
+        assert!(tls_acceptor(&TlsConfig::default()).unwrap().is_none(), "no [tls] = plain HTTP");
+        let dir = std::env::temp_dir().join(format!("pagi_tls_test_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let cert = dir.join("cert.pem");
+        std::fs::write(&cert, "not a certificate").unwrap();
+        let config = |cert: &std::path::Path, key: &str| TlsConfig {
+            cert_path: Some(cert.to_string_lossy().into_owned()),
+            key_path: Some(key.to_string()),
+        };
+        let error = tls_acceptor(&config(&cert, "/nonexistent/key.pem")).err().unwrap();
+        assert!(error.contains("no certificate in"), "{}", error);
+        std::fs::write(&cert, "-----BEGIN CERTIFICATE-----\nMIIB\n-----END CERTIFICATE-----\n").unwrap();
+        let error = tls_acceptor(&config(&cert, "/nonexistent/key.pem")).err().unwrap();
+        assert!(error.contains("cannot read /nonexistent/key.pem"), "{}", error);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,39 @@
+//! Simulation mode (`[simulation] enabled = true`): a deterministic gateway for CI and local dev.
+//!
+//! The memory vault and knowledge store are opened in a fresh directory under the OS temp dir
+//! (left in place for inspection), the knowledge store runs on a [`FrozenClock`] that the
+//! Heartbeat moves one tick interval per tick, and the ModelRouter answers every generation
+//! from seeded mock fixtures (see `pagi_skills::MockFixtures`), never a live provider.
+
+use pagi_core::{FrozenClock, SimulationConfig};
+use pagi_skills::MockFixtures;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+pub struct Simulation {
+    pub clock: Arc<FrozenClock>,
+    pub fixtures: Arc<MockFixtures>,
+    /// Replaces `storage_path` for the run.
+    pub storage_path: PathBuf,
+}
+
+impl Simulation {
+    /// None unless `[simulation]` is enabled; fails when the fixtures file does not load.
+    pub fn from_config(config: &SimulationConfig) -> Result<Option<Self>, String> {
+        if !config.enabled {
+            return Ok(None);
+        }
+        let mut fixtures = match &config.fixtures_path {
+            Some(path) => MockFixtures::load(path).map_err(|e| format!("[simulation] fixtures: {}", e))?,
+            None => MockFixtures::default(),
+        };
+        if let Some(seed) = config.seed {
+            fixtures = fixtures.with_seed(seed);
+        }
+        Ok(Some(Self {
+            clock: Arc::new(FrozenClock::new(config.start_ms)),
+            fixtures: Arc::new(fixtures),
+            storage_path: std::env::temp_dir().join(format!("pagi_simulation_{}", uuid::Uuid::new_v4().simple())),
+        }))
+    }
+}
@@ -0,0 +1,56 @@
+//! OpenTelemetry export of the orchestrator's dispatch spans.
+//!
+//! Enabled when `OTEL_EXPORTER_OTLP_ENDPOINT` (or `OTEL_EXPORTER_OTLP_TRACES_ENDPOINT`) is set,
+//! e.g. `http://localhost:4318` for a local Jaeger. Spans go out as OTLP/HTTP protobuf under
+//! `OTEL_SERVICE_NAME` (default `pagi-gateway`), and the W3C trace-context propagator is installed so
+//! ModelRouter's LLM calls carry `traceparent` headers. Unset, tracing stays local (logs only).
+
+use opentelemetry::trace::TracerProvider as _;
+use opentelemetry_sdk::trace::SdkTracerProvider;
+use opentelemetry_sdk::Resource;
+
+const ENV_OTLP_ENDPOINT: &str = "OTEL_EXPORTER_OTLP_ENDPOINT";
+const ENV_OTLP_TRACES_ENDPOINT: &str = "OTEL_EXPORTER_OTLP_TRACES_ENDPOINT";
+const ENV_SERVICE_NAME: &str = "OTEL_SERVICE_NAME";
+const DEFAULT_SERVICE_NAME: &str = "pagi-gateway";
+
+/// Whether an OTLP endpoint is configured.
+pub fn otlp_configured() -> bool {
+    [ENV_OTLP_ENDPOINT, ENV_OTLP_TRACES_ENDPOINT]
+        .iter()
+        .any(|var| std::env::var(var).is_ok_and(|v| !v.trim().is_empty()))
+}
+
+/// Builds the OTLP tracer provider and installs it (and the trace-context propagator) globally.
+/// None when no endpoint is configured. Keep the provider and call
+/// [`SdkTracerProvider::shutdown`] before exiting so batched spans are flushed.
+pub fn init_tracer_provider() -> Result<Option<SdkTracerProvider>, Box<dyn std::error::Error + Send + Sync>> {
+    if !otlp_configured() {
+        return Ok(None);
+    }
+    let exporter = opentelemetry_otlp::SpanExporter::builder().with_http().build()?;
+    let service_name = std::env::var(ENV_SERVICE_NAME)
+        .ok()
+        .filter(|s| !s.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_SERVICE_NAME.to_string());
+    let provider = SdkTracerProvider::builder()
+        .with_batch_exporter(exporter)
+        .with_resource(Resource::builder().with_service_name(service_name).build())
+        .build();
+    install(&provider);
+    Ok(Some(provider))
+}
+
+/// Makes `provider` the global tracer provider and installs the W3C trace-context propagator.
+pub fn install(provider: &SdkTracerProvider) {
+    opentelemetry::global::set_text_map_propagator(opentelemetry_sdk::propagation::TraceContextPropagator::new());
+    opentelemetry::global::set_tracer_provider(provider.clone());
+}
+
+/// `tracing` layer that turns spans into OpenTelemetry spans on `provider`.
+pub fn layer<S>(provider: &SdkTracerProvider) -> tracing_opentelemetry::OpenTelemetryLayer<S, opentelemetry_sdk::trace::Tracer>
+where
+    S: tracing::Subscriber + for<'span> tracing_subscriber::registry::LookupSpan<'span>,
+{
+    tracing_opentelemetry::layer().with_tracer(provider.tracer(DEFAULT_SERVICE_NAME))
+}
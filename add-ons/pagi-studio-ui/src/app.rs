@@ -3,11 +3,11 @@
 
 use pagi_core::{
     BlueprintRegistry, ControlPanelMessage, KnowledgeStore, MemoryManager, Orchestrator,
-    SkillRegistry, TenantContext,
+    SkillRegistry, StorageBackend, TenantContext,
 };
 use pagi_skills::{
     CommunityPulse, CommunityScraper, DraftResponse, KnowledgeInsert, KnowledgePruner,
-    KnowledgeQuery, LeadCapture, ModelRouter, ResearchAudit, SalesCloser,
+    KnowledgeQuery, LeadCapture, ModelRouter, ParseInboundMessage, ResearchAudit, SalesCloser,
 };
 use std::path::Path;
 use std::sync::Arc;
@@ -28,11 +28,29 @@ pub fn build_studio_stack(
 ) -> Result<(StudioStack, TenantContext), Box<dyn std::error::Error>> {
     let memory_path = storage_dir.join("pagi_vault");
     let knowledge_path = storage_dir.join("pagi_knowledge");
+    // Same backend as the gateway; with "sqlite" both can run against the same storage dir.
+    let backend = match std::env::var("PAGI__STORAGE_BACKEND").as_deref() {
+        Ok("sqlite") => StorageBackend::Sqlite,
+        _ => StorageBackend::Sled,
+    };
 
-    let memory = Arc::new(MemoryManager::open_path(&memory_path)?);
-    let knowledge = Arc::new(KnowledgeStore::open_path(&knowledge_path)?);
+    let memory = Arc::new(MemoryManager::open_with_backend(&memory_path, backend)?);
+    let knowledge = Arc::new(KnowledgeStore::open_with_backend(&knowledge_path, backend)?);
     knowledge.pagi_init_kb_metadata().ok(); // ensure 8 trees have metadata
+    Ok(assemble_stack(memory, knowledge))
+}
 
+/// Stack for running next to a gateway that holds `storage_dir`: knowledge is opened read-only
+/// (see `KnowledgeStore::open_read_only`) and prompts/responses go to a temporary vault.
+pub fn build_studio_stack_read_only(
+    storage_dir: &Path,
+) -> Result<(StudioStack, TenantContext), Box<dyn std::error::Error>> {
+    let memory = Arc::new(MemoryManager::open_temporary()?);
+    let knowledge = Arc::new(KnowledgeStore::open_read_only(storage_dir.join("pagi_knowledge"))?);
+    Ok(assemble_stack(memory, knowledge))
+}
+
+fn assemble_stack(memory: Arc<MemoryManager>, knowledge: Arc<KnowledgeStore>) -> (StudioStack, TenantContext) {
     let mut registry = SkillRegistry::new();
     registry.register(Arc::new(LeadCapture::new(Arc::clone(&memory))));
     registry.register(Arc::new(KnowledgeQuery::new(Arc::clone(&knowledge))));
@@ -46,6 +64,10 @@ pub fn build_studio_stack(
     registry.register(Arc::new(ResearchAudit::new(Arc::clone(&knowledge))));
     registry.register(Arc::new(CommunityScraper::new(Arc::clone(&knowledge))));
     registry.register(Arc::new(SalesCloser::new(Arc::clone(&knowledge))));
+    registry.register(Arc::new(ParseInboundMessage::new(
+        Arc::clone(&memory),
+        Arc::new(ModelRouter::new()),
+    )));
     registry.register(Arc::new(KnowledgePruner::new(Arc::clone(&knowledge))));
 
     let blueprint_path = std::env::var("PAGI_BLUEPRINT_PATH")
@@ -54,18 +76,14 @@ pub fn build_studio_stack(
 
     let registry = Arc::new(registry);
     let skill_names = registry.skill_names();
-    let orchestrator = Arc::new(Orchestrator::with_blueprint(registry, blueprint));
+    let orchestrator = Arc::new(Orchestrator::with_blueprint(registry, blueprint).with_memory(Arc::clone(&memory)));
 
     let (control_tx, control_rx) = mpsc::channel(64);
     orchestrator.clone().spawn_control_listener(control_rx);
 
-    let ctx = TenantContext {
-        tenant_id: "pagi-studio-ui".to_string(),
-        correlation_id: None,
-        agent_id: None,
-    };
+    let ctx = TenantContext::new("pagi-studio-ui");
 
-    Ok((
+    (
         StudioStack {
             orchestrator,
             memory,
@@ -74,7 +92,7 @@ pub fn build_studio_stack(
             control_tx,
         },
         ctx,
-    ))
+    )
 }
 
 /// Paths in short-term memory for Studio prompt/response (AGI state).
@@ -6,7 +6,7 @@
 pub mod app;
 pub mod config;
 
-pub use app::{build_studio_stack, StudioStack, MEMORY_PROMPT_PATH, MEMORY_RESPONSE_PATH};
+pub use app::{build_studio_stack, build_studio_stack_read_only, StudioStack, MEMORY_PROMPT_PATH, MEMORY_RESPONSE_PATH};
 pub use config::StudioConfig;
 
 pub fn init() {}
\ No newline at end of file
@@ -7,8 +7,8 @@ use axum::{
     routing::{get, post},
     Json, Router,
 };
-use pagi_core::{ControlPanelMessage, TenantContext};
-use pagi_studio_ui::build_studio_stack;
+use pagi_core::{ControlPanelMessage, DbLocked, TenantContext};
+use pagi_studio_ui::{build_studio_stack, build_studio_stack_read_only};
 use std::path::PathBuf;
 use std::sync::Arc;
 use tower_http::services::{ServeDir, ServeFile};
@@ -28,19 +28,29 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     let (stack, ctx) = match build_studio_stack(&storage) {
         Ok(ok) => ok,
         Err(e) => {
-            let msg = e.to_string();
-            if msg.contains("lock") || msg.contains("locked") || msg.contains("access the file") {
-                eprintln!("PAGI Studio UI server: cannot open database (already in use).");
-                eprintln!("  Storage path: {}", storage.display());
-                eprintln!();
-                eprintln!("  If pagi-gateway is running, it holds the lock on data/pagi_vault and data/pagi_knowledge.");
-                eprintln!("  Either:");
-                eprintln!("    1. Stop the gateway, then run this server again (standalone mode), or");
-                eprintln!("    2. Use the Vite dev server for the UI instead: npm run dev in add-ons/pagi-studio-ui/assets/studio-interface");
-                eprintln!("       and keep the gateway on 8001 for the API.");
-                std::process::exit(101);
+            // Stale locks from a crashed process are recovered inside open_path; this is a live holder.
+            if let Some(locked) = DbLocked::find(e.as_ref()) {
+                eprintln!("PAGI Studio UI server: cannot open database: {}", locked);
+                // Another process writes the store; read its knowledge (latest snapshot for sled) instead.
+                match build_studio_stack_read_only(&storage) {
+                    Ok(ok) => {
+                        eprintln!("PAGI Studio UI server: knowledge opened read-only; writes to it will fail.");
+                        ok
+                    }
+                    Err(read_only_err) => {
+                        eprintln!("PAGI Studio UI server: read-only fallback failed: {}", read_only_err);
+                        if locked.owner.as_ref().is_some_and(|o| o.process.starts_with("pagi-gateway")) {
+                            eprintln!();
+                            eprintln!("  The gateway is running. Either stop it and run this server again (standalone mode), or");
+                            eprintln!("  use the Vite dev server for the UI (npm run dev in add-ons/pagi-studio-ui/assets/studio-interface)");
+                            eprintln!("  and keep the gateway on 8001 for the API.");
+                        }
+                        std::process::exit(101);
+                    }
+                }
+            } else {
+                return Err(std::io::Error::other(e.to_string()).into());
             }
-            return Err(std::io::Error::new(std::io::ErrorKind::Other, e.to_string()).into());
         }
     };
     let stack = Arc::new(stack);
@@ -0,0 +1,314 @@
+//! Sled lock ownership: who holds a database directory, stale-lock detection, and recovery.
+//!
+//! Sled takes an OS file lock on `{path}/db`, released when the holder exits — but on some
+//! platforms (notably Windows) a crashed process can keep it briefly, and the raw
+//! "could not acquire lock" error does not say who holds it. [`open_db`] records the opener in
+//! `{path}/pagi.owner.json` (pid, process name, heartbeat). When a later open hits the lock:
+//! - the recorded owner is still alive → fail with [`DbLocked`] naming that process;
+//! - the owner is gone (or never recorded) → the lock is stale; retry while the OS releases it,
+//!   then take over the owner record. If the lock never clears, [`DbLocked`] says so.
+
+use crate::clock::system_now_ms;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Owner record written inside each sled directory.
+pub const DB_OWNER_FILE: &str = "pagi.owner.json";
+
+/// How often a live owner refreshes `heartbeat_ms`.
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(10);
+/// A record whose heartbeat is older than this belongs to a dead process even if its pid was
+/// reused by another one.
+const STALE_AFTER_MS: u64 = 60_000;
+/// Retries (and delay between them) while a dead owner's lock is released by the OS.
+const RELEASE_RETRIES: u32 = 5;
+const RELEASE_BACKOFF: Duration = Duration::from_millis(200);
+
+/// Process that opened a database.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DbOwner {
+    pub pid: u32,
+    /// Executable name (e.g. `pagi-gateway`).
+    pub process: String,
+    pub started_ms: u64,
+    pub heartbeat_ms: u64,
+}
+
+impl DbOwner {
+    fn current() -> Self {
+        let now = system_now_ms() as u64;
+        Self {
+            pid: std::process::id(),
+            process: std::env::current_exe()
+                .ok()
+                .and_then(|p| p.file_stem().map(|s| s.to_string_lossy().into_owned()))
+                .unwrap_or_else(|| "unknown".to_string()),
+            started_ms: now,
+            heartbeat_ms: now,
+        }
+    }
+
+    /// Whether the recorded process is still running and heartbeating.
+    pub fn is_alive(&self) -> bool {
+        if self.pid == std::process::id() {
+            return true;
+        }
+        (system_now_ms() as u64).saturating_sub(self.heartbeat_ms) <= STALE_AFTER_MS && process_alive(self.pid)
+    }
+}
+
+/// A database directory is locked by another process (or another handle in this process).
+#[derive(Debug)]
+pub struct DbLocked {
+    pub path: PathBuf,
+    /// Last recorded owner, if any.
+    pub owner: Option<DbOwner>,
+    /// Whether that owner is still running.
+    pub owner_alive: bool,
+}
+
+impl fmt::Display for DbLocked {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let path = self.path.display();
+        match &self.owner {
+            Some(owner) if owner.pid == std::process::id() => write!(
+                f,
+                "database {} is already open in this process ({} pid {}); reuse the existing handle",
+                path, owner.process, owner.pid
+            ),
+            Some(owner) if self.owner_alive => write!(
+                f,
+                "database {} is in use by {} (pid {}, heartbeat {}s ago). Stop that process, or point this one at a different storage path",
+                path,
+                owner.process,
+                owner.pid,
+                (system_now_ms() as u64).saturating_sub(owner.heartbeat_ms) / 1000
+            ),
+            Some(owner) => write!(
+                f,
+                "database {} is still locked although its last owner {} (pid {}) has exited. Another process holds the lock; find it with {}",
+                path,
+                owner.process,
+                owner.pid,
+                lock_holder_hint(&self.path)
+            ),
+            None => write!(
+                f,
+                "database {} is locked by a process that left no owner record; find it with {}",
+                path,
+                lock_holder_hint(&self.path)
+            ),
+        }
+    }
+}
+
+impl std::error::Error for DbLocked {}
+
+impl DbLocked {
+    /// Finds the lock diagnosis in an error returned by a store's `open_path` (as `sled::Error`,
+    /// boxed, or in its source chain).
+    pub fn find<'a>(err: &'a (dyn std::error::Error + 'static)) -> Option<&'a DbLocked> {
+        let io = match err.downcast_ref::<sled::Error>() {
+            Some(sled::Error::Io(io)) => Some(io),
+            _ => err.downcast_ref::<std::io::Error>(),
+        };
+        err.downcast_ref::<DbLocked>()
+            .or_else(|| io.and_then(|io| io.get_ref()).and_then(|e| e.downcast_ref::<DbLocked>()))
+            .or_else(|| err.source().and_then(DbLocked::find))
+    }
+}
+
+/// Opens a sled database, recording this process as its owner. Lock failures are retried when
+/// the previous owner is gone and otherwise returned as `sled::Error::Io` wrapping [`DbLocked`].
+pub fn open_db<P: AsRef<Path>>(path: P) -> sled::Result<sled::Db> {
+    let path = path.as_ref();
+    let previous = read_owner(path);
+    let mut attempt = 0;
+    let db = loop {
+        match sled::open(path) {
+            Ok(db) => break db,
+            Err(e) if is_lock_error(&e) => {
+                let owner_alive = previous.as_ref().is_some_and(DbOwner::is_alive);
+                if owner_alive || attempt >= RELEASE_RETRIES {
+                    return Err(locked(path, previous, owner_alive));
+                }
+                attempt += 1;
+                tracing::warn!(
+                    target: "pagi::storage",
+                    path = %path.display(),
+                    owner_pid = previous.as_ref().map(|o| o.pid),
+                    attempt,
+                    "Database lock held but its owner is gone; waiting for the stale lock to clear"
+                );
+                std::thread::sleep(RELEASE_BACKOFF);
+            }
+            Err(e) => return Err(e),
+        }
+    };
+    if attempt > 0 {
+        tracing::warn!(
+            target: "pagi::storage",
+            path = %path.display(),
+            previous_pid = previous.as_ref().map(|o| o.pid),
+            retries = attempt,
+            "Recovered database from a stale lock left by an exited process"
+        );
+    }
+    let owner = DbOwner::current();
+    match write_owner(path, &owner) {
+        Ok(()) => spawn_heartbeat(path.to_path_buf(), owner),
+        Err(e) => {
+            tracing::warn!(target: "pagi::storage", path = %path.display(), error = %e, "Failed to write database owner record");
+        }
+    }
+    Ok(db)
+}
+
+fn locked(path: &Path, owner: Option<DbOwner>, owner_alive: bool) -> sled::Error {
+    let err = DbLocked {
+        path: path.to_path_buf(),
+        owner,
+        owner_alive,
+    };
+    sled::Error::Io(std::io::Error::other(err))
+}
+
+fn is_lock_error(err: &sled::Error) -> bool {
+    match err {
+        sled::Error::Io(io) => io.to_string().contains("could not acquire lock"),
+        _ => false,
+    }
+}
+
+/// Reads the owner record of a database directory (None if missing or unreadable).
+pub fn read_owner(path: &Path) -> Option<DbOwner> {
+    let bytes = std::fs::read(path.join(DB_OWNER_FILE)).ok()?;
+    serde_json::from_slice(&bytes).ok()
+}
+
+fn write_owner(path: &Path, owner: &DbOwner) -> std::io::Result<()> {
+    let tmp = path.join(format!("{}.tmp", DB_OWNER_FILE));
+    std::fs::write(&tmp, serde_json::to_vec_pretty(owner).map_err(std::io::Error::other)?)?;
+    std::fs::rename(tmp, path.join(DB_OWNER_FILE))
+}
+
+/// Refreshes the heartbeat until the record is taken over (or removed with its directory).
+fn spawn_heartbeat(path: PathBuf, mut owner: DbOwner) {
+    let spawned = std::thread::Builder::new()
+        .name("pagi-db-heartbeat".to_string())
+        .spawn(move || loop {
+            std::thread::sleep(HEARTBEAT_INTERVAL);
+            let ours = read_owner(&path).is_some_and(|o| o.pid == owner.pid && o.started_ms == owner.started_ms);
+            if !ours {
+                return;
+            }
+            owner.heartbeat_ms = system_now_ms() as u64;
+            if write_owner(&path, &owner).is_err() {
+                return;
+            }
+        });
+    if let Err(e) = spawned {
+        tracing::warn!(target: "pagi::storage", error = %e, "Failed to start database heartbeat");
+    }
+}
+
+fn lock_holder_hint(path: &Path) -> String {
+    if cfg!(windows) {
+        format!("Resource Monitor (CPU → Associated Handles → search \"{}\")", path.join("db").display())
+    } else {
+        format!("`lsof {}`", path.join("db").display())
+    }
+}
+
+#[cfg(unix)]
+fn process_alive(pid: u32) -> bool {
+    // Signal 0 only checks existence; EPERM means it exists under another user.
+    let rc = unsafe { libc::kill(pid as libc::pid_t, 0) };
+    rc == 0 || std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+#[cfg(windows)]
+fn process_alive(pid: u32) -> bool {
+    use windows_sys::Win32::Foundation::{CloseHandle, STILL_ACTIVE};
+    use windows_sys::Win32::System::Threading::{GetExitCodeProcess, OpenProcess, PROCESS_QUERY_LIMITED_INFORMATION};
+    unsafe {
+        let handle = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, 0, pid);
+        if handle.is_null() {
+            return false;
+        }
+        let mut code = 0u32;
+        let ok = GetExitCodeProcess(handle, &mut code) != 0;
+        CloseHandle(handle);
+        ok && code == STILL_ACTIVE as u32
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+fn process_alive(_pid: u32) -> bool {
+    true // cannot tell; never treat a lock as stale
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Pid of a process that has already exited.
+    fn dead_pid() -> u32 {
+        let mut child = std::process::Command::new(if cfg!(windows) { "cmd" } else { "true" })
+            .args(if cfg!(windows) { &["/C", "exit"][..] } else { &[][..] })
+            .spawn()
+            .unwrap();
+        let pid = child.id();
+        child.wait().unwrap();
+        pid
+    }
+
+    #[test]
+    fn second_open_reports_owner() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = open_db(dir.path()).unwrap();
+        let owner = read_owner(dir.path()).unwrap();
+        assert_eq!(owner.pid, std::process::id());
+
+        let err = open_db(dir.path()).unwrap_err();
+        let locked = DbLocked::find(&err).expect("lock diagnosis");
+        assert_eq!(locked.owner.as_ref(), Some(&owner));
+        assert!(err.to_string().contains("already open in this process"));
+        drop(db);
+    }
+
+    #[test]
+    fn stale_owner_is_replaced() {
+        let dir = tempfile::tempdir().unwrap();
+        drop(open_db(dir.path()).unwrap());
+        let stale = DbOwner {
+            pid: dead_pid(),
+            process: "pagi-gateway".to_string(),
+            started_ms: 1,
+            heartbeat_ms: 1,
+        };
+        assert!(!stale.is_alive());
+        write_owner(dir.path(), &stale).unwrap();
+
+        let _db = open_db(dir.path()).unwrap();
+        assert_eq!(read_owner(dir.path()).unwrap().pid, std::process::id());
+    }
+
+    #[test]
+    fn live_owner_message_names_process() {
+        let err = DbLocked {
+            path: PathBuf::from("data/pagi_knowledge"),
+            owner: Some(DbOwner {
+                pid: 4242,
+                process: "pagi-gateway".to_string(),
+                started_ms: system_now_ms() as u64,
+                heartbeat_ms: system_now_ms() as u64,
+            }),
+            owner_alive: true,
+        };
+        let msg = err.to_string();
+        assert!(msg.contains("in use by pagi-gateway (pid 4242"));
+    }
+}
@@ -0,0 +1,734 @@
+//! KB key naming: the prefix of every structured keyspace, builders that produce its keys, parsers
+//! that read them back, and the per-slot check [`KnowledgeStore::insert`](crate::KnowledgeStore::insert)
+//! applies before writing.
+//!
+//! | Slot | Keyspace | Key |
+//! |------|----------|-----|
+//! | 1 Pneuma | agent settings | `pneuma/{agent_id}/{setting}` (`persona`, `persona_profile`, `auto_reply_template`, `background_task`) |
+//! | 1 Pneuma | agent registry | `agents/{agent_id}` |
+//! | 1 Pneuma | prompt templates | `prompts/{name}` (current), `prompt_versions/{name}/{version:06}` |
+//! | 2 Oikos | governed tasks | `oikos/tasks/{task_id}` |
+//! | 3 Logos | knowledge graph | `graph/entity/{entity_id}`, `graph/out/{subject}/{predicate}/{object}`, `graph/in/{object}/{predicate}/{subject}` |
+//! | 3 Logos | eval runs | `eval_runs/{suite}/{timestamp_ms}_{run_id}`, `eval_suites/{suite}` (latest run summary) |
+//! | 4 Chronos | episodic events | `event/{agent_id}/{timestamp_ms}_{id}` |
+//! | 4 Chronos | conversation sessions | `session/{tenant_id}/{agent_id}/{session_id}` |
+//! | 4 Chronos | conversation turns | `session_turn/{tenant_id}/{agent_id}/{session_id}/{turn:06}` |
+//! | 4 Chronos | conversation digests | `session_digest/{tenant_id}/{agent_id}/{session_id}/{last_turn:06}` |
+//! | 5 Techne | skill manifests | `skills/{slug}` |
+//! | 6 Ethos | policy rules | `policy/rules` (current), `policy_versions/{version:06}` |
+//! | 6 Ethos | approvals | `approval/{approval_id}` |
+//! | 6 Ethos | gateway API keys | `api_key/{key_id}`, `api_key_hash/{sha256}` (→ key id) |
+//! | 7 Kardia | relations | `relation/{owner_agent_id}/{target_id}` |
+//! | 7 Kardia | people | `people/{name_slug}` |
+//! | 8 Soma | inbox | `inbox/{target_agent_id}/{timestamp_ms}_{id}` |
+//! | 8 Soma | inbox dead letters | `inbox_dlq/{target_agent_id}/{timestamp_ms}_{id}` |
+//! | 8 Soma | topic messages | `topic/{topic}/{timestamp_ms}_{id}` |
+//! | 8 Soma | topic subscriptions | `subscription/{agent_id}/{topic}` |
+//! | 8 Soma | background goal jobs | `job/{job_id}` |
+//! | 8 Soma | idempotent requests | `idempotency/{tenant_id}/{key}` |
+//! | 8 Soma | LLM token usage | `usage/{tenant_id}/{YYYY-MM}` |
+//! | 8 Soma | cached LLM replies | `llm_cache/{hash}` |
+//! | 8 Soma | email delivery log | `email_log/{tenant_id}/{timestamp_ms}_{id}` |
+//! | 8 Soma | calendar bookings | `calendar_booking/{tenant_id}/{timestamp_ms}_{id}` |
+//! | 9 Shadow | emotional anchors | `anchor/{label}` |
+//! | 1–8 | tenant records | `tenant/{tenant_id}/{key}` (any key above, for a tenant other than `default`) |
+//!
+//! Stream timestamps are zero-padded to 13 digits, so key order is time order within an agent's
+//! `event/`, `inbox/`, `inbox_dlq/`, `topic/`, `email_log/`, `calendar_booking/` or `eval_runs/` prefix (reverse prefix scans read newest first).
+//!
+//! Agent ids, task ids and slugs are single segments (no `/`), so prefix scans stay exact. Other
+//! keys are free-form; a key that starts with one of these prefixes in its slot must match the
+//! format or the insert is rejected with [`InvalidKey`].
+
+use crate::knowledge::KbType;
+use crate::shared::DEFAULT_AGENT_ID;
+use std::fmt;
+
+pub const PNEUMA_AGENT_PREFIX: &str = "pneuma/";
+pub const AGENT_PREFIX: &str = "agents/";
+pub const PROMPT_PREFIX: &str = "prompts/";
+pub const PROMPT_VERSION_PREFIX: &str = "prompt_versions/";
+pub const TASK_PREFIX: &str = "oikos/tasks/";
+pub const GRAPH_ENTITY_PREFIX: &str = "graph/entity/";
+pub const GRAPH_OUT_PREFIX: &str = "graph/out/";
+pub const GRAPH_IN_PREFIX: &str = "graph/in/";
+pub const EVAL_RUN_PREFIX: &str = "eval_runs/";
+pub const EVAL_SUITE_PREFIX: &str = "eval_suites/";
+pub const EVENT_PREFIX: &str = "event/";
+pub const SESSION_PREFIX: &str = "session/";
+pub const SESSION_TURN_PREFIX: &str = "session_turn/";
+pub const SESSION_DIGEST_PREFIX: &str = "session_digest/";
+pub const SKILL_PREFIX: &str = "skills/";
+pub const POLICY_RULES_KEY: &str = "policy/rules";
+pub const POLICY_VERSION_PREFIX: &str = "policy_versions/";
+pub const APPROVAL_PREFIX: &str = "approval/";
+pub const API_KEY_PREFIX: &str = "api_key/";
+pub const API_KEY_HASH_PREFIX: &str = "api_key_hash/";
+pub const RELATION_PREFIX: &str = "relation/";
+pub const PEOPLE_PREFIX: &str = "people/";
+pub const INBOX_PREFIX: &str = "inbox/";
+pub const INBOX_DLQ_PREFIX: &str = "inbox_dlq/";
+pub const TOPIC_PREFIX: &str = "topic/";
+pub const SUBSCRIPTION_PREFIX: &str = "subscription/";
+pub const JOB_PREFIX: &str = "job/";
+pub const IDEMPOTENCY_PREFIX: &str = "idempotency/";
+pub const USAGE_PREFIX: &str = "usage/";
+pub const LLM_CACHE_PREFIX: &str = "llm_cache/";
+pub const EMAIL_LOG_PREFIX: &str = "email_log/";
+pub const CALENDAR_BOOKING_PREFIX: &str = "calendar_booking/";
+pub const ANCHOR_PREFIX: &str = "anchor/";
+pub const TENANT_PREFIX: &str = "tenant/";
+
+/// Tenant whose records keep un-namespaced keys (everything written before tenants had their own
+/// keyspace).
+pub const DEFAULT_TENANT_ID: &str = "default";
+
+/// Agent setting names under `pneuma/{agent_id}/`.
+pub const PERSONA_SETTING: &str = "persona";
+pub const PERSONA_PROFILE_SETTING: &str = "persona_profile";
+pub const AUTO_REPLY_TEMPLATE_SETTING: &str = "auto_reply_template";
+pub const BACKGROUND_TASK_SETTING: &str = "background_task";
+
+/// A key rejected by [`validate_key`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidKey {
+    pub slot_id: u8,
+    pub key: String,
+    pub reason: String,
+}
+
+impl fmt::Display for InvalidKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid KB-{} key {:?}: {}", self.slot_id, self.key, self.reason)
+    }
+}
+
+impl std::error::Error for InvalidKey {}
+
+/// A parsed `inbox/` or `event/` key: `{prefix}{agent_id}/{timestamp_ms}_{id}`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StreamKey {
+    pub agent_id: String,
+    pub timestamp_ms: i64,
+    pub id: String,
+}
+
+fn agent_or_default(agent_id: &str) -> &str {
+    if agent_id.is_empty() {
+        DEFAULT_AGENT_ID
+    } else {
+        agent_id
+    }
+}
+
+/// `pneuma/{agent_id}/{setting}`.
+pub fn agent_setting_key(agent_id: &str, setting: &str) -> String {
+    format!("{}{}/{}", PNEUMA_AGENT_PREFIX, agent_or_default(agent_id), setting)
+}
+
+/// Pneuma key holding an agent's own persona (overrides the shared `core_persona` for that agent).
+pub fn agent_persona_key(agent_id: &str) -> String {
+    agent_setting_key(agent_id, PERSONA_SETTING)
+}
+
+/// Pneuma key holding an agent's structured [`PersonaProfile`](crate::PersonaProfile); it takes
+/// precedence over the plain-text persona.
+pub fn agent_persona_profile_key(agent_id: &str) -> String {
+    agent_setting_key(agent_id, PERSONA_PROFILE_SETTING)
+}
+
+/// Pneuma key holding an agent's own auto-reply template.
+pub fn auto_reply_template_key(agent_id: &str) -> String {
+    agent_setting_key(agent_id, AUTO_REPLY_TEMPLATE_SETTING)
+}
+
+/// Pneuma key the Heartbeat polls for an agent's background task.
+pub fn background_task_key(agent_id: &str) -> String {
+    agent_setting_key(agent_id, BACKGROUND_TASK_SETTING)
+}
+
+/// `oikos/tasks/{task_id}`.
+pub fn task_key(task_id: &str) -> String {
+    format!("{}{}", TASK_PREFIX, task_id)
+}
+
+/// `event/{agent_id}/` – scan prefix for one agent's Chronos events.
+pub fn event_prefix(agent_id: &str) -> String {
+    format!("{}{}/", EVENT_PREFIX, agent_or_default(agent_id))
+}
+
+/// `event/{agent_id}/{timestamp_ms:013}_{id}`.
+pub fn event_key(agent_id: &str, timestamp_ms: i64, id: &str) -> String {
+    format!("{}{:013}_{}", event_prefix(agent_id), timestamp_ms, id)
+}
+
+pub fn parse_event_key(key: &str) -> Option<StreamKey> {
+    parse_stream_key(key.strip_prefix(EVENT_PREFIX)?)
+}
+
+/// `graph/entity/{entity_id}`.
+pub fn graph_entity_key(entity_id: &str) -> String {
+    format!("{}{}", GRAPH_ENTITY_PREFIX, entity_id)
+}
+
+/// `graph/out/{subject}/` – scan prefix for the relations an entity is the subject of.
+pub fn graph_out_prefix(subject: &str) -> String {
+    format!("{}{}/", GRAPH_OUT_PREFIX, subject)
+}
+
+/// `graph/out/{subject}/{predicate}/{object}`.
+pub fn graph_out_key(subject: &str, predicate: &str, object: &str) -> String {
+    format!("{}{}/{}", graph_out_prefix(subject), predicate, object)
+}
+
+/// `graph/in/{object}/` – scan prefix for the relations pointing at an entity.
+pub fn graph_in_prefix(object: &str) -> String {
+    format!("{}{}/", GRAPH_IN_PREFIX, object)
+}
+
+/// `graph/in/{object}/{predicate}/{subject}` – reverse copy of a `graph/out/` relation.
+pub fn graph_in_key(object: &str, predicate: &str, subject: &str) -> String {
+    format!("{}{}/{}", graph_in_prefix(object), predicate, subject)
+}
+
+/// `skills/{slug}`.
+pub fn skill_key(slug: &str) -> String {
+    format!("{}{}", SKILL_PREFIX, slug)
+}
+
+/// Key for relation records in **KB_KARDIA**: `relation/{owner_agent_id}/{target_id}`.
+/// In multi-agent mode each agent has its own view of relations (to users and other agents);
+/// an empty owner means the default agent.
+pub fn relation_key(owner_agent_id: &str, target_id: &str) -> String {
+    format!("{}{}/{}", RELATION_PREFIX, agent_or_default(owner_agent_id), target_id)
+}
+
+/// `(owner_agent_id, target_id)` from a relation key.
+pub fn parse_relation_key(key: &str) -> Option<(String, String)> {
+    let (owner, target) = key.strip_prefix(RELATION_PREFIX)?.split_once('/')?;
+    (!owner.is_empty() && !target.is_empty()).then(|| (owner.to_string(), target.to_string()))
+}
+
+/// `people/{name_slug}`.
+pub fn person_key(name_slug: &str) -> String {
+    format!("{}{}", PEOPLE_PREFIX, name_slug)
+}
+
+/// `inbox/{agent_id}/` – scan prefix for one agent's inbox.
+pub fn inbox_prefix(agent_id: &str) -> String {
+    format!("{}{}/", INBOX_PREFIX, agent_id)
+}
+
+/// `inbox/{agent_id}/{timestamp_ms:013}_{id}`.
+pub fn inbox_key(agent_id: &str, timestamp_ms: i64, id: &str) -> String {
+    format!("{}{:013}_{}", inbox_prefix(agent_id), timestamp_ms, id)
+}
+
+pub fn parse_inbox_key(key: &str) -> Option<StreamKey> {
+    parse_stream_key(key.strip_prefix(INBOX_PREFIX)?)
+}
+
+/// `inbox_dlq/{agent_id}/` – scan prefix for one agent's dead-lettered messages.
+pub fn inbox_dlq_prefix(agent_id: &str) -> String {
+    format!("{}{}/", INBOX_DLQ_PREFIX, agent_id)
+}
+
+/// `inbox_dlq/{agent_id}/{timestamp_ms:013}_{id}` – same suffix as the message's inbox key.
+pub fn inbox_dlq_key(agent_id: &str, timestamp_ms: i64, id: &str) -> String {
+    format!("{}{:013}_{}", inbox_dlq_prefix(agent_id), timestamp_ms, id)
+}
+
+pub fn parse_inbox_dlq_key(key: &str) -> Option<StreamKey> {
+    parse_stream_key(key.strip_prefix(INBOX_DLQ_PREFIX)?)
+}
+
+/// `topic/{topic}/` – scan prefix for one topic's messages.
+pub fn topic_prefix(topic: &str) -> String {
+    format!("{}{}/", TOPIC_PREFIX, topic)
+}
+
+/// `topic/{topic}/{timestamp_ms:013}_{id}`.
+pub fn topic_key(topic: &str, timestamp_ms: i64, id: &str) -> String {
+    format!("{}{:013}_{}", topic_prefix(topic), timestamp_ms, id)
+}
+
+/// Parses a `topic/` key; the returned `agent_id` is the topic name.
+pub fn parse_topic_key(key: &str) -> Option<StreamKey> {
+    parse_stream_key(key.strip_prefix(TOPIC_PREFIX)?)
+}
+
+/// `subscription/{agent_id}/` – scan prefix for one agent's topic subscriptions.
+pub fn subscriptions_prefix(agent_id: &str) -> String {
+    format!("{}{}/", SUBSCRIPTION_PREFIX, agent_or_default(agent_id))
+}
+
+/// `subscription/{agent_id}/{topic}`.
+pub fn subscription_key(agent_id: &str, topic: &str) -> String {
+    format!("{}{}", subscriptions_prefix(agent_id), topic)
+}
+
+/// `job/{job_id}`.
+pub fn job_key(job_id: &str) -> String {
+    format!("{}{}", JOB_PREFIX, job_id)
+}
+
+/// `idempotency/{tenant_id}/{key}`. The client's key is kept as is (it may contain `/`).
+pub fn idempotency_key(tenant_id: &str, key: &str) -> String {
+    format!("{}{}/{}", IDEMPOTENCY_PREFIX, tenant_id, key)
+}
+
+/// `usage/{tenant_id}/` – scan prefix for one tenant's monthly usage.
+pub fn usage_prefix(tenant_id: &str) -> String {
+    format!("{}{}/", USAGE_PREFIX, tenant_id)
+}
+
+/// `usage/{tenant_id}/{month}`, month as `YYYY-MM` (so key order is month order).
+pub fn usage_key(tenant_id: &str, month: &str) -> String {
+    format!("{}{}", usage_prefix(tenant_id), month)
+}
+
+/// `llm_cache/{hash}`.
+pub fn llm_cache_key(hash: &str) -> String {
+    format!("{}{}", LLM_CACHE_PREFIX, hash)
+}
+
+/// `email_log/{tenant_id}/` – scan prefix for one tenant's email deliveries.
+pub fn email_log_prefix(tenant_id: &str) -> String {
+    format!("{}{}/", EMAIL_LOG_PREFIX, tenant_id)
+}
+
+/// `email_log/{tenant_id}/{timestamp_ms:013}_{id}`.
+pub fn email_log_key(tenant_id: &str, timestamp_ms: i64, id: &str) -> String {
+    format!("{}{:013}_{}", email_log_prefix(tenant_id), timestamp_ms, id)
+}
+
+/// Parses an `email_log/` key; the returned `agent_id` is the tenant id.
+pub fn parse_email_log_key(key: &str) -> Option<StreamKey> {
+    parse_stream_key(key.strip_prefix(EMAIL_LOG_PREFIX)?)
+}
+
+/// `calendar_booking/{tenant_id}/` – scan prefix for one tenant's calendar bookings.
+pub fn calendar_booking_prefix(tenant_id: &str) -> String {
+    format!("{}{}/", CALENDAR_BOOKING_PREFIX, tenant_id)
+}
+
+/// `calendar_booking/{tenant_id}/{timestamp_ms:013}_{id}`.
+pub fn calendar_booking_key(tenant_id: &str, timestamp_ms: i64, id: &str) -> String {
+    format!("{}{:013}_{}", calendar_booking_prefix(tenant_id), timestamp_ms, id)
+}
+
+/// Parses a `calendar_booking/` key; the returned `agent_id` is the tenant id.
+pub fn parse_calendar_booking_key(key: &str) -> Option<StreamKey> {
+    parse_stream_key(key.strip_prefix(CALENDAR_BOOKING_PREFIX)?)
+}
+
+/// `eval_runs/{suite}/` – scan prefix for one eval suite's runs.
+pub fn eval_runs_prefix(suite: &str) -> String {
+    format!("{}{}/", EVAL_RUN_PREFIX, suite)
+}
+
+/// `eval_runs/{suite}/{timestamp_ms:013}_{run_id}`.
+pub fn eval_run_key(suite: &str, timestamp_ms: i64, run_id: &str) -> String {
+    format!("{}{:013}_{}", eval_runs_prefix(suite), timestamp_ms, run_id)
+}
+
+/// Parses an `eval_runs/` key; the returned `agent_id` is the suite name.
+pub fn parse_eval_run_key(key: &str) -> Option<StreamKey> {
+    parse_stream_key(key.strip_prefix(EVAL_RUN_PREFIX)?)
+}
+
+/// `eval_suites/{suite}` – summary of the suite's latest run.
+pub fn eval_suite_key(suite: &str) -> String {
+    format!("{}{}", EVAL_SUITE_PREFIX, suite)
+}
+
+/// `session/{tenant_id}/{agent_id}/{session_id}` – a conversation session's metadata.
+pub fn session_key(tenant_id: &str, agent_id: &str, session_id: &str) -> String {
+    format!("{}{}/{}/{}", SESSION_PREFIX, tenant_id, agent_or_default(agent_id), session_id)
+}
+
+/// `session_turn/{tenant_id}/{agent_id}/` – scan prefix for an agent's turns across sessions.
+pub fn agent_session_turns_prefix(tenant_id: &str, agent_id: &str) -> String {
+    format!("{}{}/{}/", SESSION_TURN_PREFIX, tenant_id, agent_or_default(agent_id))
+}
+
+/// `session_turn/{tenant_id}/{agent_id}/{session_id}/` – scan prefix for one session's turns.
+pub fn session_turns_prefix(tenant_id: &str, agent_id: &str, session_id: &str) -> String {
+    format!("{}{}/", agent_session_turns_prefix(tenant_id, agent_id), session_id)
+}
+
+/// `session_turn/{tenant_id}/{agent_id}/{session_id}/{turn:06}` (zero-padded, so key order is
+/// turn order).
+pub fn session_turn_key(tenant_id: &str, agent_id: &str, session_id: &str, turn: u32) -> String {
+    format!("{}{:06}", session_turns_prefix(tenant_id, agent_id, session_id), turn)
+}
+
+/// `session_digest/{tenant_id}/{agent_id}/` – scan prefix for an agent's digests across sessions.
+pub fn agent_session_digests_prefix(tenant_id: &str, agent_id: &str) -> String {
+    format!("{}{}/{}/", SESSION_DIGEST_PREFIX, tenant_id, agent_or_default(agent_id))
+}
+
+/// `session_digest/{tenant_id}/{agent_id}/{session_id}/` – scan prefix for one session's digests.
+pub fn session_digests_prefix(tenant_id: &str, agent_id: &str, session_id: &str) -> String {
+    format!("{}{}/", agent_session_digests_prefix(tenant_id, agent_id), session_id)
+}
+
+/// `session_digest/{tenant_id}/{agent_id}/{session_id}/{last_turn:06}` – the digest of the turns
+/// up to `last_turn` (key order is turn order).
+pub fn session_digest_key(tenant_id: &str, agent_id: &str, session_id: &str, last_turn: u32) -> String {
+    format!("{}{:06}", session_digests_prefix(tenant_id, agent_id, session_id), last_turn)
+}
+
+/// `agents/{agent_id}` – a registered agent.
+pub fn agent_key(agent_id: &str) -> String {
+    format!("{}{}", AGENT_PREFIX, agent_id)
+}
+
+/// `prompts/{name}` – the current version of a prompt template.
+pub fn prompt_key(name: &str) -> String {
+    format!("{}{}", PROMPT_PREFIX, name)
+}
+
+/// `prompt_versions/{name}/` – scan prefix for every saved version of a prompt template.
+pub fn prompt_versions_prefix(name: &str) -> String {
+    format!("{}{}/", PROMPT_VERSION_PREFIX, name)
+}
+
+/// `prompt_versions/{name}/{version:06}` (zero-padded, so key order is version order).
+pub fn prompt_version_key(name: &str, version: u32) -> String {
+    format!("{}{:06}", prompt_versions_prefix(name), version)
+}
+
+/// `policy_versions/{version:06}` (zero-padded, so key order is version order).
+pub fn policy_version_key(version: u32) -> String {
+    format!("{}{:06}", POLICY_VERSION_PREFIX, version)
+}
+
+/// `approval/{approval_id}`.
+pub fn approval_key(approval_id: &str) -> String {
+    format!("{}{}", APPROVAL_PREFIX, approval_id)
+}
+
+/// `api_key/{key_id}`.
+pub fn api_key_key(key_id: &str) -> String {
+    format!("{}{}", API_KEY_PREFIX, key_id)
+}
+
+/// `api_key_hash/{sha256}`: the key id of the key whose secret hashes to `hash`.
+pub fn api_key_hash_key(hash: &str) -> String {
+    format!("{}{}", API_KEY_HASH_PREFIX, hash)
+}
+
+/// `anchor/{label}`.
+pub fn anchor_key(label: &str) -> String {
+    format!("{}{}", ANCHOR_PREFIX, label)
+}
+
+/// Key prefix of a tenant's records: `tenant/{tenant_id}/`, or empty for the default tenant.
+pub fn tenant_prefix(tenant_id: &str) -> String {
+    if tenant_id.is_empty() || tenant_id == DEFAULT_TENANT_ID {
+        String::new()
+    } else {
+        format!("{}{}/", TENANT_PREFIX, tenant_id)
+    }
+}
+
+/// Stored key of a tenant's `key`: `tenant/{tenant_id}/{key}`, or `key` for the default tenant.
+pub fn tenant_key(tenant_id: &str, key: &str) -> String {
+    format!("{}{}", tenant_prefix(tenant_id), key)
+}
+
+/// `(tenant_id, key)` from a stored key; un-namespaced keys belong to the default tenant.
+pub fn parse_tenant_key(key: &str) -> Option<(String, String)> {
+    match key.strip_prefix(TENANT_PREFIX) {
+        Some(rest) => {
+            let (tenant, key) = rest.split_once('/')?;
+            (is_segment(tenant) && !key.is_empty()).then(|| (tenant.to_string(), key.to_string()))
+        }
+        None => Some((DEFAULT_TENANT_ID.to_string(), key.to_string())),
+    }
+}
+
+/// A tenant id is one segment of ASCII letters, digits, `-`, `_` and `.`, at most 64 long.
+pub fn is_valid_tenant_id(tenant_id: &str) -> bool {
+    !tenant_id.is_empty()
+        && tenant_id.len() <= 64
+        && tenant_id.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+}
+
+fn parse_stream_key(rest: &str) -> Option<StreamKey> {
+    let (agent_id, tail) = rest.split_once('/')?;
+    let (ts, id) = tail.split_once('_')?;
+    if agent_id.is_empty() || id.is_empty() || id.contains('/') {
+        return None;
+    }
+    Some(StreamKey {
+        agent_id: agent_id.to_string(),
+        timestamp_ms: ts.parse().ok()?,
+        id: id.to_string(),
+    })
+}
+
+/// A non-empty segment without `/`.
+pub(crate) fn is_segment(s: &str) -> bool {
+    !s.is_empty() && !s.contains('/')
+}
+
+/// Checks `key` against the keyspace it claims in `slot_id` (see the module docs).
+pub fn validate_key(slot_id: u8, key: &str) -> Result<(), InvalidKey> {
+    let invalid = |reason: &str| InvalidKey {
+        slot_id,
+        key: key.to_string(),
+        reason: reason.to_string(),
+    };
+    if key.trim().is_empty() {
+        return Err(invalid("key must not be empty"));
+    }
+    if key.chars().any(char::is_control) {
+        return Err(invalid("key must not contain control characters"));
+    }
+    // A tenant's key follows the same rules as the un-namespaced key it wraps.
+    if let Some(rest) = key.strip_prefix(TENANT_PREFIX) {
+        return match rest.split_once('/') {
+            Some((tenant, inner))
+                if is_valid_tenant_id(tenant) && tenant != DEFAULT_TENANT_ID && !inner.starts_with(TENANT_PREFIX) =>
+            {
+                validate_key(slot_id, inner).map_err(|e| invalid(&e.reason))
+            }
+            _ => Err(invalid(expected_format(key))),
+        };
+    }
+    let ok = match KbType::from_slot_id(slot_id) {
+        Some(KbType::Pneuma) => match key.strip_prefix(PNEUMA_AGENT_PREFIX) {
+            Some(rest) => rest
+                .split_once('/')
+                .is_some_and(|(agent, setting)| is_segment(agent) && !setting.is_empty()),
+            None => {
+                key.strip_prefix(AGENT_PREFIX).is_none_or(is_segment)
+                    && key.strip_prefix(PROMPT_PREFIX).is_none_or(is_segment)
+                    && key.strip_prefix(PROMPT_VERSION_PREFIX).is_none_or(|rest| {
+                        rest.split_once('/').is_some_and(|(name, version)| {
+                            is_segment(name) && !version.is_empty() && version.bytes().all(|b| b.is_ascii_digit())
+                        })
+                    })
+            }
+        },
+        Some(KbType::Oikos) => key.strip_prefix(TASK_PREFIX).is_none_or(is_segment),
+        Some(KbType::Logos) => {
+            key.strip_prefix(GRAPH_ENTITY_PREFIX).is_none_or(is_segment)
+                && [GRAPH_OUT_PREFIX, GRAPH_IN_PREFIX].iter().all(|prefix| {
+                    key.strip_prefix(prefix).is_none_or(|rest| {
+                        let parts: Vec<&str> = rest.split('/').collect();
+                        parts.len() == 3 && parts.iter().all(|p| !p.is_empty())
+                    })
+                })
+                && (!key.starts_with(EVAL_RUN_PREFIX) || parse_eval_run_key(key).is_some())
+                && key.strip_prefix(EVAL_SUITE_PREFIX).is_none_or(is_segment)
+        }
+        Some(KbType::Chronos) => {
+            (!key.starts_with(EVENT_PREFIX) || parse_event_key(key).is_some())
+                && key.strip_prefix(SESSION_PREFIX).is_none_or(|rest| {
+                    let parts: Vec<&str> = rest.split('/').collect();
+                    parts.len() == 3 && parts.iter().all(|p| !p.is_empty())
+                })
+                && [SESSION_TURN_PREFIX, SESSION_DIGEST_PREFIX].iter().all(|prefix| {
+                    key.strip_prefix(prefix).is_none_or(|rest| {
+                        let parts: Vec<&str> = rest.split('/').collect();
+                        parts.len() == 4
+                            && parts.iter().all(|p| !p.is_empty())
+                            && parts[3].bytes().all(|b| b.is_ascii_digit())
+                    })
+                })
+        }
+        Some(KbType::Techne) => key.strip_prefix(SKILL_PREFIX).is_none_or(is_segment),
+        Some(KbType::Ethos) => {
+            key.strip_prefix(POLICY_VERSION_PREFIX)
+                .is_none_or(|version| !version.is_empty() && version.bytes().all(|b| b.is_ascii_digit()))
+                && key.strip_prefix(APPROVAL_PREFIX).is_none_or(is_segment)
+                && key.strip_prefix(API_KEY_PREFIX).is_none_or(is_segment)
+                && key.strip_prefix(API_KEY_HASH_PREFIX).is_none_or(is_segment)
+        }
+        Some(KbType::Kardia) => {
+            (!key.starts_with(RELATION_PREFIX) || parse_relation_key(key).is_some())
+                && key.strip_prefix(PEOPLE_PREFIX).is_none_or(is_segment)
+        }
+        Some(KbType::Soma) => {
+            (!key.starts_with(INBOX_PREFIX) || parse_inbox_key(key).is_some())
+                && (!key.starts_with(INBOX_DLQ_PREFIX) || parse_inbox_dlq_key(key).is_some())
+                && (!key.starts_with(TOPIC_PREFIX) || parse_topic_key(key).is_some())
+                && key
+                    .strip_prefix(SUBSCRIPTION_PREFIX)
+                    .is_none_or(|rest| rest.split_once('/').is_some_and(|(a, t)| is_segment(a) && is_segment(t)))
+                && key
+                    .strip_prefix(IDEMPOTENCY_PREFIX)
+                    .is_none_or(|rest| rest.split_once('/').is_some_and(|(t, k)| is_segment(t) && !k.is_empty()))
+                && key
+                    .strip_prefix(USAGE_PREFIX)
+                    .is_none_or(|rest| rest.split_once('/').is_some_and(|(t, m)| is_segment(t) && is_segment(m)))
+                && key.strip_prefix(LLM_CACHE_PREFIX).is_none_or(is_segment)
+                && key.strip_prefix(JOB_PREFIX).is_none_or(is_segment)
+                && (!key.starts_with(EMAIL_LOG_PREFIX) || parse_email_log_key(key).is_some())
+                && (!key.starts_with(CALENDAR_BOOKING_PREFIX) || parse_calendar_booking_key(key).is_some())
+        }
+        Some(KbType::Shadow) => key.strip_prefix(ANCHOR_PREFIX).is_none_or(|l| !l.is_empty()),
+        _ => true,
+    };
+    if ok {
+        Ok(())
+    } else {
+        Err(invalid(expected_format(key)))
+    }
+}
+
+fn expected_format(key: &str) -> &'static str {
+    [
+        (PNEUMA_AGENT_PREFIX, "expected pneuma/{agent_id}/{setting}"),
+        (AGENT_PREFIX, "expected agents/{agent_id}"),
+        (PROMPT_PREFIX, "expected prompts/{name}"),
+        (PROMPT_VERSION_PREFIX, "expected prompt_versions/{name}/{version}"),
+        (TASK_PREFIX, "expected oikos/tasks/{task_id}"),
+        (GRAPH_ENTITY_PREFIX, "expected graph/entity/{entity_id}"),
+        (GRAPH_OUT_PREFIX, "expected graph/out/{subject}/{predicate}/{object}"),
+        (GRAPH_IN_PREFIX, "expected graph/in/{object}/{predicate}/{subject}"),
+        (EVAL_RUN_PREFIX, "expected eval_runs/{suite}/{timestamp_ms}_{run_id}"),
+        (EVAL_SUITE_PREFIX, "expected eval_suites/{suite}"),
+        (EVENT_PREFIX, "expected event/{agent_id}/{timestamp_ms}_{id}"),
+        (SESSION_PREFIX, "expected session/{tenant_id}/{agent_id}/{session_id}"),
+        (SESSION_TURN_PREFIX, "expected session_turn/{tenant_id}/{agent_id}/{session_id}/{turn}"),
+        (SESSION_DIGEST_PREFIX, "expected session_digest/{tenant_id}/{agent_id}/{session_id}/{last_turn}"),
+        (SKILL_PREFIX, "expected skills/{slug}"),
+        (POLICY_VERSION_PREFIX, "expected policy_versions/{version}"),
+        (APPROVAL_PREFIX, "expected approval/{approval_id}"),
+        (API_KEY_HASH_PREFIX, "expected api_key_hash/{sha256}"),
+        (API_KEY_PREFIX, "expected api_key/{key_id}"),
+        (RELATION_PREFIX, "expected relation/{owner_agent_id}/{target_id}"),
+        (PEOPLE_PREFIX, "expected people/{name_slug}"),
+        (INBOX_PREFIX, "expected inbox/{agent_id}/{timestamp_ms}_{id}"),
+        (INBOX_DLQ_PREFIX, "expected inbox_dlq/{agent_id}/{timestamp_ms}_{id}"),
+        (TOPIC_PREFIX, "expected topic/{topic}/{timestamp_ms}_{id}"),
+        (SUBSCRIPTION_PREFIX, "expected subscription/{agent_id}/{topic}"),
+        (JOB_PREFIX, "expected job/{job_id}"),
+        (IDEMPOTENCY_PREFIX, "expected idempotency/{tenant_id}/{key}"),
+        (USAGE_PREFIX, "expected usage/{tenant_id}/{month}"),
+        (LLM_CACHE_PREFIX, "expected llm_cache/{hash}"),
+        (EMAIL_LOG_PREFIX, "expected email_log/{tenant_id}/{timestamp_ms}_{id}"),
+        (CALENDAR_BOOKING_PREFIX, "expected calendar_booking/{tenant_id}/{timestamp_ms}_{id}"),
+        (ANCHOR_PREFIX, "expected anchor/{label}"),
+        (TENANT_PREFIX, "expected tenant/{tenant_id}/{key} with a tenant other than default"),
+    ]
+    .iter()
+    .find(|(prefix, _)| key.starts_with(prefix))
+    .map_or("malformed key", |(_, format)| format)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builders_round_trip_through_parsers() {
+        let inbox = inbox_key("sales", 1_700_000_000_000, "abc123");
+        assert_eq!(inbox, "inbox/sales/1700000000000_abc123");
+        assert_eq!(
+            parse_inbox_key(&inbox),
+            Some(StreamKey {
+                agent_id: "sales".to_string(),
+                timestamp_ms: 1_700_000_000_000,
+                id: "abc123".to_string(),
+            })
+        );
+        assert_eq!(event_key("", 5, "x"), "event/default/0000000000005_x");
+        assert_eq!(parse_event_key("event/default/5_x").unwrap().agent_id, "default");
+        assert!(event_key("a", 999, "x") < event_key("a", 1_000, "x"));
+        assert_eq!(
+            parse_relation_key(&relation_key("", "user@example.com")),
+            Some(("default".to_string(), "user@example.com".to_string()))
+        );
+        assert!(inbox_key("a", 1, "x").starts_with(&inbox_prefix("a")));
+        assert!(!inbox_key("ab", 1, "x").starts_with(&inbox_prefix("a")));
+    }
+
+    #[test]
+    fn validation_is_per_slot() {
+        let soma = KbType::Soma.slot_id();
+        assert!(validate_key(soma, &inbox_key("sales", 1, "x")).is_ok());
+        assert!(validate_key(soma, "inbox/sales").is_err());
+        assert!(validate_key(soma, "inbox/sales/not-a-timestamp_x").is_err());
+        assert!(validate_key(soma, "inbox//1_x").is_err());
+        assert!(validate_key(soma, &inbox_dlq_key("sales", 1, "x")).is_ok());
+        assert!(validate_key(soma, "inbox_dlq/sales").is_err());
+        assert!(validate_key(soma, &idempotency_key("acme", "retry/1")).is_ok());
+        assert!(validate_key(soma, "idempotency/acme").is_err());
+        assert!(validate_key(soma, "idempotency/acme/").is_err());
+        assert!(validate_key(soma, &usage_key("acme", "2024-03")).is_ok());
+        assert!(validate_key(soma, "usage/acme").is_err());
+        assert!(validate_key(soma, "usage/acme/2024-03/x").is_err());
+        assert!(validate_key(soma, &email_log_key("acme", 1, "x")).is_ok());
+        assert!(validate_key(soma, "email_log/acme").is_err());
+        assert!(validate_key(soma, &calendar_booking_key("acme", 1, "x")).is_ok());
+        assert!(validate_key(soma, "calendar_booking/acme").is_err());
+        assert!(validate_key(soma, &llm_cache_key("00ff")).is_ok());
+        assert!(validate_key(soma, &topic_key("maintenance", 1, "x")).is_ok());
+        assert!(validate_key(soma, "topic/maintenance").is_err());
+        assert!(validate_key(soma, &subscription_key("dev-1", "maintenance")).is_ok());
+        assert!(validate_key(soma, "subscription/dev-1/a/b").is_err());
+        assert!(validate_key(soma, "llm_cache/a/b").is_err());
+        assert!(validate_key(soma, &job_key("0f3a")).is_ok());
+        assert!(validate_key(soma, "job/a/b").is_err());
+        // Same prefix in a slot that does not own the keyspace is free-form.
+        assert!(validate_key(KbType::Logos.slot_id(), "inbox/anything").is_ok());
+
+        let kardia = KbType::Kardia.slot_id();
+        assert!(validate_key(kardia, "relation/default").is_err());
+        assert!(validate_key(kardia, "people/a/b").is_err());
+        assert!(validate_key(kardia, "mental_state").is_ok());
+
+        let pneuma = KbType::Pneuma.slot_id();
+        assert!(validate_key(pneuma, "pneuma/sales").is_err());
+        assert!(validate_key(pneuma, &prompt_version_key("greeting", 3)).is_ok());
+        assert!(validate_key(pneuma, "prompt_versions/greeting/v3").is_err());
+        assert!(validate_key(pneuma, "prompts/a/b").is_err());
+        assert!(validate_key(pneuma, &agent_key("sales")).is_ok());
+        assert!(validate_key(pneuma, "agents/sales/eu").is_err());
+        assert!(validate_key(KbType::Oikos.slot_id(), "oikos/tasks/").is_err());
+        let ethos = KbType::Ethos.slot_id();
+        assert!(validate_key(ethos, &policy_version_key(2)).is_ok());
+        assert!(validate_key(ethos, "policy_versions/v2").is_err());
+        assert!(validate_key(ethos, POLICY_RULES_KEY).is_ok());
+        assert!(validate_key(ethos, &approval_key("0f3a")).is_ok());
+        assert!(validate_key(ethos, "approval/a/b").is_err());
+        let chronos = KbType::Chronos.slot_id();
+        assert!(validate_key(chronos, "event/a/1_x").is_ok());
+        assert!(validate_key(chronos, &session_turn_key("acme", "sales", "s1", 2)).is_ok());
+        assert!(validate_key(chronos, &session_key("acme", "", "s1")).is_ok());
+        assert!(validate_key(chronos, "session/acme/sales").is_err());
+        assert!(validate_key(chronos, "session_turn/acme/sales/s1/two").is_err());
+        assert!(validate_key(chronos, &session_digest_key("acme", "sales", "s1", 10)).is_ok());
+        assert!(validate_key(chronos, "session_digest/acme/sales/s1").is_err());
+        assert!(validate_key(KbType::Shadow.slot_id(), "anchor/").is_err());
+        let logos = KbType::Logos.slot_id();
+        assert!(validate_key(logos, &graph_entity_key("acme")).is_ok());
+        assert!(validate_key(logos, "graph/entity/acme/eu").is_err());
+        assert!(validate_key(logos, &graph_out_key("acme", "interested_in", "backups")).is_ok());
+        assert!(validate_key(logos, &graph_in_key("backups", "interested_in", "acme")).is_ok());
+        assert!(validate_key(logos, "graph/out/acme/interested_in").is_err());
+        assert!(validate_key(logos, "graph/in/backups//acme").is_err());
+        assert!(validate_key(logos, &eval_run_key("respond_to_lead", 1, "r1")).is_ok());
+        assert!(validate_key(logos, "eval_runs/respond_to_lead").is_err());
+        assert!(validate_key(logos, &eval_suite_key("respond_to_lead")).is_ok());
+        assert!(validate_key(logos, "eval_suites/a/b").is_err());
+        assert!(validate_key(3, " ").is_err());
+        assert!(validate_key(3, "line\nbreak").is_err());
+
+        assert!(validate_key(soma, &tenant_key("acme", &inbox_key("sales", 1, "x"))).is_ok());
+        assert!(validate_key(soma, "tenant/acme/inbox/sales").is_err());
+        assert!(validate_key(3, "tenant/default/x").is_err());
+        assert!(validate_key(3, "tenant/a b/x").is_err());
+        assert!(validate_key(3, "tenant/acme/tenant/beta/x").is_err());
+        assert_eq!(tenant_key(DEFAULT_TENANT_ID, "x"), "x");
+        assert_eq!(parse_tenant_key(&tenant_key("acme", "notes/1")), Some(("acme".to_string(), "notes/1".to_string())));
+
+        let err = validate_key(soma, "inbox/sales").unwrap_err();
+        assert_eq!(err.to_string(), "invalid KB-8 key \"inbox/sales\": expected inbox/{agent_id}/{timestamp_ms}_{id}");
+    }
+}
@@ -0,0 +1,245 @@
+//! Agent registry in KB_PNEUMA (`agents/{agent_id}`): the agents this instance runs.
+//!
+//! An agent is created with a display name and the skills it is set up to use (its persona lives
+//! next to it as a [`PersonaProfile`](super::PersonaProfile)). The Heartbeat serves the registered,
+//! active agents ([`KnowledgeStore::active_agent_ids`](crate::KnowledgeStore::active_agent_ids));
+//! archiving an agent takes it out of that loop but keeps its record, inbox and history.
+//!
+//! Each agent also carries its own Heartbeat schedule: a tick interval (it is served at most that
+//! often), an active-hours window (e.g. `09:00`–`18:00` at UTC+1) and a `paused` flag that quiesces
+//! it without archiving. [`AgentTickTracker`] applies them tick by tick.
+
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+
+/// An agent id is one segment of ASCII letters, digits, `-`, `_` and `.`, at most 64 long.
+pub fn is_valid_agent_id(agent_id: &str) -> bool {
+    crate::keys::is_valid_tenant_id(agent_id)
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AgentStatus {
+    #[default]
+    Active,
+    Archived,
+}
+
+/// A registered agent.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct AgentRecord {
+    pub agent_id: String,
+    pub display_name: String,
+    /// Skills the agent is set up to use (empty: whatever its tenant allows).
+    #[serde(default)]
+    pub enabled_skills: Vec<String>,
+    #[serde(default)]
+    pub status: AgentStatus,
+    pub created_at_ms: i64,
+    #[serde(default)]
+    pub archived_at_ms: Option<i64>,
+    /// Serve the agent at most every this many seconds (unset: every Heartbeat tick).
+    #[serde(default)]
+    pub tick_interval_secs: Option<u64>,
+    /// Only serve the agent inside this daily window (unset: around the clock).
+    #[serde(default)]
+    pub active_hours: Option<ActiveHours>,
+    /// Skipped by the Heartbeat until resumed; unlike archiving, still listed as active.
+    #[serde(default)]
+    pub paused: bool,
+}
+
+impl AgentRecord {
+    /// A new active agent; `display_name` defaults to the id.
+    pub fn new(agent_id: &str, display_name: Option<&str>, enabled_skills: Vec<String>) -> Self {
+        Self {
+            agent_id: agent_id.to_string(),
+            display_name: display_name
+                .map(str::trim)
+                .filter(|n| !n.is_empty())
+                .unwrap_or(agent_id)
+                .to_string(),
+            enabled_skills,
+            status: AgentStatus::Active,
+            created_at_ms: 0,
+            archived_at_ms: None,
+            tick_interval_secs: None,
+            active_hours: None,
+            paused: false,
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.status == AgentStatus::Active
+    }
+
+    /// Whether the Heartbeat should serve the agent at `now_ms`, given when it last did: active,
+    /// not paused, inside its active hours and at least its tick interval since `last_tick_ms`.
+    pub fn is_due(&self, now_ms: i64, last_tick_ms: Option<i64>) -> bool {
+        if !self.is_active() || self.paused {
+            return false;
+        }
+        if let Some(window) = &self.active_hours {
+            if !window.contains(now_ms) {
+                return false;
+            }
+        }
+        match (self.tick_interval_secs, last_tick_ms) {
+            (Some(secs), Some(last)) => now_ms.saturating_sub(last) >= (secs as i64).saturating_mul(1000),
+            _ => true,
+        }
+    }
+
+    /// Applies `update` (fields it leaves out stay as they are); check it with
+    /// [`AgentScheduleUpdate::validate`] first.
+    pub fn apply_schedule(&mut self, update: &AgentScheduleUpdate) {
+        if let Some(paused) = update.paused {
+            self.paused = paused;
+        }
+        if let Some(interval) = update.tick_interval_secs {
+            self.tick_interval_secs = interval;
+        }
+        if let Some(window) = &update.active_hours {
+            self.active_hours = window.clone();
+        }
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        serde_json::to_vec(self).unwrap_or_default()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        serde_json::from_slice(bytes).ok()
+    }
+}
+
+/// What an agent has been up to, for listings.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct AgentHealth {
+    /// Unprocessed messages among the latest in its inbox.
+    pub pending_messages: usize,
+    /// Timestamp of its latest Chronos event.
+    pub last_activity_ms: Option<i64>,
+}
+
+/// Why an agent schedule was rejected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidAgentSchedule(pub String);
+
+impl fmt::Display for InvalidAgentSchedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid agent schedule: {}", self.0)
+    }
+}
+
+impl std::error::Error for InvalidAgentSchedule {}
+
+/// A daily window, `start` to `end` as `HH:MM` local to `utc_offset_minutes`. A window whose end
+/// is before its start runs past midnight (`22:00`–`06:00`).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ActiveHours {
+    pub start: String,
+    pub end: String,
+    /// e.g. `60` for UTC+1, `-300` for UTC-5.
+    #[serde(default)]
+    pub utc_offset_minutes: i32,
+}
+
+impl ActiveHours {
+    pub fn validate(&self) -> Result<(), InvalidAgentSchedule> {
+        let start = parse_minute_of_day(&self.start)?;
+        let end = parse_minute_of_day(&self.end)?;
+        if start == end {
+            return Err(InvalidAgentSchedule("active_hours start and end must differ".to_string()));
+        }
+        if self.utc_offset_minutes.abs() > 14 * 60 {
+            return Err(InvalidAgentSchedule(format!(
+                "utc_offset_minutes {} is out of range",
+                self.utc_offset_minutes
+            )));
+        }
+        Ok(())
+    }
+
+    /// Whether `now_ms` falls inside the window (an invalid window never does).
+    pub fn contains(&self, now_ms: i64) -> bool {
+        let (Ok(start), Ok(end)) = (parse_minute_of_day(&self.start), parse_minute_of_day(&self.end)) else {
+            return false;
+        };
+        let minute = (now_ms.div_euclid(60_000) + i64::from(self.utc_offset_minutes)).rem_euclid(24 * 60) as u32;
+        if start < end {
+            (start..end).contains(&minute)
+        } else {
+            minute >= start || minute < end
+        }
+    }
+}
+
+/// `HH:MM` (00:00–23:59) as minutes since midnight.
+fn parse_minute_of_day(s: &str) -> Result<u32, InvalidAgentSchedule> {
+    let invalid = || InvalidAgentSchedule(format!("{:?} is not a HH:MM time", s));
+    let (h, m) = s.split_once(':').ok_or_else(invalid)?;
+    if h.len() != 2 || m.len() != 2 {
+        return Err(invalid());
+    }
+    let (h, m): (u32, u32) = (h.parse().map_err(|_| invalid())?, m.parse().map_err(|_| invalid())?);
+    if h > 23 || m > 59 {
+        return Err(invalid());
+    }
+    Ok(h * 60 + m)
+}
+
+/// A change to an agent's schedule; absent fields are left alone and `null` clears the interval
+/// or window.
+#[derive(Debug, Clone, Default, Deserialize, PartialEq, Eq)]
+pub struct AgentScheduleUpdate {
+    #[serde(default)]
+    pub paused: Option<bool>,
+    #[serde(default, deserialize_with = "present")]
+    pub tick_interval_secs: Option<Option<u64>>,
+    #[serde(default, deserialize_with = "present")]
+    pub active_hours: Option<Option<ActiveHours>>,
+}
+
+impl AgentScheduleUpdate {
+    pub fn validate(&self) -> Result<(), InvalidAgentSchedule> {
+        if let Some(Some(0)) = self.tick_interval_secs {
+            return Err(InvalidAgentSchedule("tick_interval_secs must be at least 1".to_string()));
+        }
+        match &self.active_hours {
+            Some(Some(window)) => window.validate(),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Tells a field sent as `null` (`Some(None)`) from one left out (`None`, via `#[serde(default)]`).
+fn present<'de, D, T>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
+/// When the Heartbeat last served each agent, kept in memory by the loop that runs it.
+#[derive(Debug, Default)]
+pub struct AgentTickTracker {
+    last_tick_ms: HashMap<String, i64>,
+}
+
+impl AgentTickTracker {
+    /// Ids of the `agents` due at `now_ms` ([`AgentRecord::is_due`]), recording this tick for them.
+    pub fn due(&mut self, agents: &[AgentRecord], now_ms: i64) -> Vec<String> {
+        let due: Vec<String> = agents
+            .iter()
+            .filter(|a| a.is_due(now_ms, self.last_tick_ms.get(&a.agent_id).copied()))
+            .map(|a| a.agent_id.clone())
+            .collect();
+        for agent_id in &due {
+            self.last_tick_ms.insert(agent_id.clone(), now_ms);
+        }
+        due
+    }
+}
@@ -0,0 +1,124 @@
+//! Human-in-the-loop approvals in KB_ETHOS (`approval/{approval_id}`): skill calls an Ethos rule
+//! marked `require_approval`.
+//!
+//! The first time such a call comes up it becomes a `pending` approval and the dispatch stops
+//! there. An operator then approves or denies it. An approval grants exactly that call (tenant,
+//! correlation id, skill and payload) once: the dispatch is resumed by running its goal again under
+//! the same correlation id, and when the call reaches the Ethos gate the approval is consumed
+//! (`executed`). Steps of a plan that ran before the blocked one run again on resume.
+
+use crate::shared::Goal;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ApprovalStatus {
+    #[default]
+    Pending,
+    Approved,
+    Denied,
+    /// Approved, and the granted call has run.
+    Executed,
+}
+
+impl ApprovalStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ApprovalStatus::Pending => "pending",
+            ApprovalStatus::Approved => "approved",
+            ApprovalStatus::Denied => "denied",
+            ApprovalStatus::Executed => "executed",
+        }
+    }
+}
+
+/// A skill call waiting for (or given) a human decision.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ApprovalRecord {
+    pub approval_id: String,
+    pub tenant_id: String,
+    pub agent_id: String,
+    /// Correlation id of the suspended dispatch; the resumed run reuses it.
+    pub correlation_id: String,
+    pub skill: String,
+    #[serde(default)]
+    pub payload: Option<serde_json::Value>,
+    /// Ethos rule that asked for approval.
+    #[serde(default)]
+    pub rule_id: Option<String>,
+    #[serde(default)]
+    pub reason: Option<String>,
+    /// Goal to dispatch again once approved (set by the gateway for the goal it ran).
+    #[serde(default)]
+    pub goal: Option<Goal>,
+    #[serde(default)]
+    pub status: ApprovalStatus,
+    pub created_at_ms: i64,
+    #[serde(default)]
+    pub decided_at_ms: Option<i64>,
+    #[serde(default)]
+    pub decided_by: Option<String>,
+    #[serde(default)]
+    pub note: Option<String>,
+    /// Background job that resumed the dispatch.
+    #[serde(default)]
+    pub job_id: Option<String>,
+    #[serde(default)]
+    pub executed_at_ms: Option<i64>,
+}
+
+impl ApprovalRecord {
+    /// A pending approval for one call; the id and `created_at_ms` are stamped when it is stored.
+    pub fn new(
+        tenant_id: &str,
+        agent_id: &str,
+        correlation_id: &str,
+        skill: &str,
+        payload: Option<serde_json::Value>,
+    ) -> Self {
+        Self {
+            tenant_id: tenant_id.to_string(),
+            agent_id: agent_id.to_string(),
+            correlation_id: correlation_id.to_string(),
+            skill: skill.to_string(),
+            payload,
+            ..Self::default()
+        }
+    }
+
+    /// Whether `other` is a request for the same call.
+    pub fn same_call(&self, other: &ApprovalRecord) -> bool {
+        self.tenant_id == other.tenant_id
+            && self.correlation_id == other.correlation_id
+            && self.skill == other.skill
+            && self.payload == other.payload
+    }
+
+    /// Why a denied call is blocked.
+    pub fn denial_reason(&self) -> String {
+        format!(
+            "approval {} was denied by {}",
+            self.approval_id,
+            self.decided_by.as_deref().unwrap_or("an operator")
+        )
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        serde_json::to_vec(self).unwrap_or_default()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        serde_json::from_slice(bytes).ok()
+    }
+}
+
+/// Outcome of [`KnowledgeStore::approval_checkpoint`](crate::KnowledgeStore::approval_checkpoint).
+#[derive(Debug, Clone)]
+pub enum ApprovalCheckpoint {
+    /// An operator approved this call; it may run.
+    Granted(ApprovalRecord),
+    /// The call waits for a decision (newly requested, or already pending).
+    Pending(ApprovalRecord),
+    /// An operator denied this call.
+    Denied(ApprovalRecord),
+}
@@ -0,0 +1,127 @@
+//! Archive tier: cold KB records moved out of sled into gzip-compressed JSONL segment files.
+//!
+//! `KnowledgeStore::archive_older_than` writes one segment per run under
+//! `{storage_path}/archive/{tree}/{segment_id}.jsonl.gz` and keeps an index (key → segment) in a
+//! dedicated sled tree, so archived records stay readable (`get_or_archived`,
+//! `scan_kv_with_archive`) and can be restored on demand. This module holds the record types and
+//! segment file I/O; the store methods live in `store.rs`.
+
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+/// Directory (next to the sled DB) that holds segment files.
+pub const ARCHIVE_DIR_NAME: &str = "archive";
+
+/// One archived key/value pair (one JSONL line in a segment).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchivedEntry {
+    pub key: String,
+    /// Stored value (KB values are UTF-8 JSON).
+    pub value: String,
+    pub timestamp_ms: i64,
+}
+
+/// Index metadata for one segment file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchiveSegment {
+    pub id: String,
+    pub slot_id: u8,
+    /// Path of the segment file relative to the archive directory.
+    pub file: String,
+    /// Entries written to the segment.
+    pub count: usize,
+    /// Entries not yet restored; the file is deleted when this reaches 0.
+    pub live: usize,
+    pub min_timestamp_ms: i64,
+    pub max_timestamp_ms: i64,
+    pub created_at_ms: i64,
+}
+
+impl ArchiveSegment {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        serde_json::to_vec(self).unwrap_or_default()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        serde_json::from_slice(bytes).ok()
+    }
+}
+
+/// Result of one archival run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchiveReport {
+    pub slot_id: u8,
+    /// Segment written by this run (None when nothing was old enough).
+    pub segment_id: Option<String>,
+    pub archived: usize,
+}
+
+/// Record timestamp used for age checks: `timestamp_ms` (EventRecord), `timestamp` (KbRecord),
+/// or `created_at_ms`. Records without one are never archived.
+pub fn record_timestamp_ms(value: &[u8]) -> Option<i64> {
+    let json: serde_json::Value = serde_json::from_slice(value).ok()?;
+    ["timestamp_ms", "timestamp", "created_at_ms"]
+        .iter()
+        .find_map(|field| json.get(*field).and_then(|v| v.as_i64()))
+}
+
+/// Writes `entries` as a gzip JSONL segment and fsyncs it. Returns the file path.
+pub fn write_segment(path: &Path, entries: &[ArchivedEntry]) -> std::io::Result<PathBuf> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let file = std::fs::File::create(path)?;
+    let mut encoder = GzEncoder::new(file, Compression::default());
+    for entry in entries {
+        serde_json::to_writer(&mut encoder, entry)?;
+        encoder.write_all(b"\n")?;
+    }
+    encoder.finish()?.sync_all()?;
+    Ok(path.to_path_buf())
+}
+
+/// Reads every entry of a segment file.
+pub fn read_segment(path: &Path) -> std::io::Result<Vec<ArchivedEntry>> {
+    let file = std::fs::File::open(path)?;
+    let mut entries = Vec::new();
+    for line in BufReader::new(GzDecoder::new(file)).lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        entries.push(serde_json::from_str(&line)?);
+    }
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn segment_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("kb4_memory").join("seg.jsonl.gz");
+        let entries = vec![
+            ArchivedEntry { key: "a".into(), value: "{\"x\":1}".into(), timestamp_ms: 1 },
+            ArchivedEntry { key: "b".into(), value: "{\"x\":2}".into(), timestamp_ms: 2 },
+        ];
+        write_segment(&path, &entries).unwrap();
+        let read = read_segment(&path).unwrap();
+        assert_eq!(read.len(), 2);
+        assert_eq!(read[1].key, "b");
+        assert_eq!(read[1].value, "{\"x\":2}");
+    }
+
+    #[test]
+    fn timestamps_from_known_fields() {
+        assert_eq!(record_timestamp_ms(br#"{"timestamp_ms": 42}"#), Some(42));
+        assert_eq!(record_timestamp_ms(br#"{"timestamp": 7, "content": "x"}"#), Some(7));
+        assert_eq!(record_timestamp_ms(br#"{"content": "x"}"#), None);
+        assert_eq!(record_timestamp_ms(b"not json"), None);
+    }
+}
@@ -0,0 +1,167 @@
+//! Dispatch audit log: one normalized record per top-level `Orchestrator` dispatch (tenant,
+//! agent, goal type, skill, duration, outcome, error), kept for compliance reviews. PII
+//! redactions are noted here too, as `Redaction` records counting what was masked.
+//!
+//! Records live in a dedicated sled tree keyed by `{timestamp_ms:013}/{sequence:020}` (the
+//! sequence keeps records of one millisecond in write order), so time ranges are key ranges and
+//! pages are read newest first. This module holds the record and query types;
+//! the store methods (`append_audit_record`, `query_audit`, `prune_audit_records`) live in
+//! `store.rs`.
+
+use super::redaction::RedactionReport;
+use serde::{Deserialize, Serialize};
+
+/// Sled tree holding the audit records.
+pub const AUDIT_TREE: &str = "audit_log";
+/// Page size when a query does not set one.
+pub const AUDIT_DEFAULT_LIMIT: usize = 50;
+/// Largest page a query can ask for.
+pub const AUDIT_MAX_LIMIT: usize = 500;
+/// Longest error text kept in a record.
+pub const AUDIT_MAX_ERROR_LEN: usize = 500;
+/// `goal_type` of the records noting a PII redaction (`KnowledgeStore::record_redaction`).
+pub const REDACTION_GOAL_TYPE: &str = "Redaction";
+
+/// How a dispatch ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditOutcome {
+    Ok,
+    Error,
+    Cancelled,
+    /// The request deadline passed.
+    Timeout,
+    /// Rejected by the dispatch queue before anything ran.
+    Throttled,
+}
+
+impl AuditOutcome {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuditOutcome::Ok => "ok",
+            AuditOutcome::Error => "error",
+            AuditOutcome::Cancelled => "cancelled",
+            AuditOutcome::Timeout => "timeout",
+            AuditOutcome::Throttled => "throttled",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "ok" => Some(AuditOutcome::Ok),
+            "error" => Some(AuditOutcome::Error),
+            "cancelled" => Some(AuditOutcome::Cancelled),
+            "timeout" => Some(AuditOutcome::Timeout),
+            "throttled" => Some(AuditOutcome::Throttled),
+            _ => None,
+        }
+    }
+}
+
+/// One dispatch, as written to the audit log.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AuditRecord {
+    pub id: String,
+    /// When the dispatch started (Unix ms).
+    pub timestamp_ms: i64,
+    pub tenant_id: String,
+    pub agent_id: String,
+    #[serde(default)]
+    pub correlation_id: Option<String>,
+    /// `Goal::kind()`, e.g. `ExecuteSkill` or `AutonomousGoal`.
+    pub goal_type: String,
+    /// Skill the goal ran directly (None for plans and composite goals).
+    #[serde(default)]
+    pub skill: Option<String>,
+    /// Blueprint intent of an `AutonomousGoal`.
+    #[serde(default)]
+    pub intent: Option<String>,
+    pub duration_ms: u64,
+    pub outcome: AuditOutcome,
+    /// Error text (truncated to [`AUDIT_MAX_ERROR_LEN`]) when the dispatch failed.
+    #[serde(default)]
+    pub error: Option<String>,
+    /// Values masked per PII kind (`Redaction` records only).
+    #[serde(default, skip_serializing_if = "RedactionReport::is_empty")]
+    pub redactions: RedactionReport,
+}
+
+impl AuditRecord {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        serde_json::to_vec(self).unwrap_or_default()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        serde_json::from_slice(bytes).ok()
+    }
+
+    /// `error` cut to [`AUDIT_MAX_ERROR_LEN`] characters.
+    pub fn truncate_error(error: &str) -> String {
+        if error.chars().count() <= AUDIT_MAX_ERROR_LEN {
+            return error.to_string();
+        }
+        let mut out: String = error.chars().take(AUDIT_MAX_ERROR_LEN).collect();
+        out.push('…');
+        out
+    }
+}
+
+/// Filters and paging for `KnowledgeStore::query_audit`. All filters are optional and combine
+/// with AND; `from_ms` is inclusive and `to_ms` exclusive.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AuditQuery {
+    #[serde(default)]
+    pub tenant_id: Option<String>,
+    #[serde(default)]
+    pub agent_id: Option<String>,
+    #[serde(default)]
+    pub skill: Option<String>,
+    #[serde(default)]
+    pub goal_type: Option<String>,
+    #[serde(default)]
+    pub outcome: Option<AuditOutcome>,
+    #[serde(default)]
+    pub from_ms: Option<i64>,
+    #[serde(default)]
+    pub to_ms: Option<i64>,
+    /// `next_cursor` of the previous page.
+    #[serde(default)]
+    pub cursor: Option<String>,
+    /// Page size (default [`AUDIT_DEFAULT_LIMIT`], at most [`AUDIT_MAX_LIMIT`]).
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+impl AuditQuery {
+    pub fn matches(&self, record: &AuditRecord) -> bool {
+        let eq = |filter: &Option<String>, value: &str| filter.as_deref().is_none_or(|f| f == value);
+        eq(&self.tenant_id, &record.tenant_id)
+            && eq(&self.agent_id, &record.agent_id)
+            && self
+                .skill
+                .as_deref()
+                .is_none_or(|s| record.skill.as_deref().is_some_and(|r| r.eq_ignore_ascii_case(s)))
+            && self
+                .goal_type
+                .as_deref()
+                .is_none_or(|g| record.goal_type.eq_ignore_ascii_case(g))
+            && self.outcome.is_none_or(|o| o == record.outcome)
+    }
+
+    pub fn page_size(&self) -> usize {
+        self.limit.unwrap_or(AUDIT_DEFAULT_LIMIT).clamp(1, AUDIT_MAX_LIMIT)
+    }
+}
+
+/// One page of audit records, newest first.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditPage {
+    pub records: Vec<AuditRecord>,
+    /// Pass as `cursor` to get the next (older) page; None on the last page.
+    pub next_cursor: Option<String>,
+}
+
+/// Tree key for a record written at `timestamp_ms` with sled sequence number `sequence`.
+pub(crate) fn audit_key(timestamp_ms: i64, sequence: u64) -> String {
+    format!("{:013}/{:020}", timestamp_ms.max(0), sequence)
+}
@@ -0,0 +1,264 @@
+//! Gateway API keys in KB_ETHOS: each key has a role, and optionally a tenant it is bound to.
+//!
+//! Keys are issued once (`pagi_…`) and only their SHA-256 hash is kept (`api_key_hash/{hash}` →
+//! key id), next to the key's record (`api_key/{key_id}`). Revoking a key drops its hash, so it
+//! no longer authenticates; the record stays for the audit trail.
+//!
+//! | Role | Permissions |
+//! |------|-------------|
+//! | `admin` | everything, including vault reads and key management |
+//! | `operator` | read, execute, KB administration (rules, approvals, agents, snapshots, …) |
+//! | `readonly` | read |
+//! | `tenant` | read and execute, for its own tenant only |
+//!
+//! Behind SSO, the gateway also accepts JWTs from an OIDC provider (`[oidc]`, [`OidcConfig`]);
+//! their claims map to a role and a tenant the same way ([`OidcConfig::principal`]).
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+
+/// Prefix of every issued key, so leaked keys are easy to recognise (and to scan for).
+pub const API_KEY_SECRET_PREFIX: &str = "pagi_";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    Admin,
+    Operator,
+    Readonly,
+    /// Bound to the key's `tenant_id`.
+    Tenant,
+}
+
+impl Role {
+    /// Every role, most permissive first.
+    pub const ALL: [Role; 4] = [Role::Admin, Role::Operator, Role::Tenant, Role::Readonly];
+
+    pub fn parse(s: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|r| r.as_str().eq_ignore_ascii_case(s.trim()))
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Role::Admin => "admin",
+            Role::Operator => "operator",
+            Role::Readonly => "readonly",
+            Role::Tenant => "tenant",
+        }
+    }
+
+    pub fn allows(self, permission: Permission) -> bool {
+        match self {
+            Role::Admin => true,
+            Role::Operator => matches!(permission, Permission::Read | Permission::Execute | Permission::KbAdmin),
+            Role::Readonly => permission == Permission::Read,
+            Role::Tenant => matches!(permission, Permission::Read | Permission::Execute),
+        }
+    }
+
+    /// Checks a new key's tenant binding: `tenant` keys need one, other roles take none.
+    pub fn check_tenant_binding(self, tenant_id: Option<&str>) -> Result<(), String> {
+        match (self, tenant_id) {
+            (Role::Tenant, None) => Err("a tenant key needs a tenant_id".to_string()),
+            (Role::Tenant, Some(tenant)) if tenant.contains('/') => Err(format!("invalid tenant id {:?}", tenant)),
+            (Role::Tenant, Some(_)) | (_, None) => Ok(()),
+            (_, Some(_)) => Err(format!("only tenant keys are bound to a tenant, not {} keys", self.as_str())),
+        }
+    }
+}
+
+/// What a gateway route needs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Permission {
+    /// Status, listings and other GETs.
+    Read,
+    /// Running goals and chat.
+    Execute,
+    /// Shadow vault and sovereign state.
+    VaultRead,
+    /// Changes to rules, prompts, agents, approvals, snapshots and other KB state.
+    KbAdmin,
+    /// Issuing and revoking API keys.
+    KeyAdmin,
+    /// Reloading the gateway config.
+    ConfigAdmin,
+}
+
+impl Permission {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Permission::Read => "read",
+            Permission::Execute => "execute",
+            Permission::VaultRead => "vault_read",
+            Permission::KbAdmin => "kb_admin",
+            Permission::KeyAdmin => "key_admin",
+            Permission::ConfigAdmin => "config_admin",
+        }
+    }
+}
+
+/// An issued API key (without its secret).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiKeyRecord {
+    pub key_id: String,
+    /// Human label (`ci-deploy`, `acme-backend`).
+    pub name: String,
+    pub role: Role,
+    /// Tenant the key acts for; required for (and only used by) the `tenant` role.
+    #[serde(default)]
+    pub tenant_id: Option<String>,
+    /// First characters of the secret, to tell keys apart in listings.
+    pub key_prefix: String,
+    pub created_at_ms: i64,
+    #[serde(default)]
+    pub created_by: Option<String>,
+    #[serde(default)]
+    pub revoked_at_ms: Option<i64>,
+}
+
+impl ApiKeyRecord {
+    pub fn is_active(&self) -> bool {
+        self.revoked_at_ms.is_none()
+    }
+
+    /// Whether the key grants `permission`, for a request about `tenant_id` when it names one. A
+    /// tenant key is refused for requests that do not name its tenant.
+    pub fn allows(&self, permission: Permission, tenant_id: Option<&str>) -> bool {
+        if !self.is_active() || !self.role.allows(permission) {
+            return false;
+        }
+        match (self.role, self.tenant_id.as_deref()) {
+            (Role::Tenant, Some(own)) => tenant_id == Some(own),
+            (Role::Tenant, None) => false,
+            _ => true,
+        }
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        serde_json::to_vec(self).unwrap_or_default()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        serde_json::from_slice(bytes).ok()
+    }
+}
+
+/// Hex SHA-256 of a key secret (what KB_ETHOS stores instead of the secret).
+pub fn hash_api_key(secret: &str) -> String {
+    Sha256::digest(secret.trim().as_bytes())
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// `[oidc]`: JWTs from an OIDC provider, accepted by the gateway next to API keys.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OidcConfig {
+    /// Expected `iss`; None = OIDC off. Env `PAGI__OIDC__ISSUER`.
+    pub issuer: Option<String>,
+    /// Accepted `aud` values; a token must name one of them (required with `issuer`).
+    pub audiences: Vec<String>,
+    /// JWKS endpoint (default: `jwks_uri` from `{issuer}/.well-known/openid-configuration`).
+    pub jwks_uri: Option<String>,
+    /// Signing keys are refetched this often, and when a token names an unknown `kid`.
+    pub jwks_refresh_secs: u64,
+    /// Clock skew allowed on `exp` and `nbf`.
+    pub leeway_secs: u64,
+    /// Claim naming the caller's tenant; dots reach into objects. A token with it is bound to
+    /// that tenant, whatever its role.
+    pub tenant_claim: String,
+    /// Claim listing the caller's roles (array or space-separated string), e.g.
+    /// `realm_access.roles` for Keycloak.
+    pub roles_claim: String,
+    /// Provider role → PAGI role (`"pagi-admins" = "admin"`). Values named after a PAGI role map
+    /// to it without an entry.
+    pub role_map: HashMap<String, Role>,
+    /// Role of a token whose claims map to none (None = refused).
+    pub default_role: Option<Role>,
+}
+
+impl Default for OidcConfig {
+    fn default() -> Self {
+        Self {
+            issuer: None,
+            audiences: Vec::new(),
+            jwks_uri: None,
+            jwks_refresh_secs: 3600,
+            leeway_secs: 60,
+            tenant_claim: "tenant_id".to_string(),
+            roles_claim: "roles".to_string(),
+            role_map: HashMap::new(),
+            default_role: None,
+        }
+    }
+}
+
+/// Who a validated token speaks for.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OidcPrincipal {
+    /// `sub`.
+    pub subject: String,
+    /// `preferred_username`, `email` or `sub`.
+    pub name: String,
+    /// The most permissive role the claims map to.
+    pub role: Role,
+    pub tenant_id: Option<String>,
+}
+
+impl OidcConfig {
+    pub fn is_enabled(&self) -> bool {
+        self.issuer.as_deref().is_some_and(|i| !i.trim().is_empty())
+    }
+
+    /// Maps the claims of a validated token to a principal; Err says why it gets none.
+    pub fn principal(&self, claims: &serde_json::Value) -> Result<OidcPrincipal, String> {
+        let subject = claims
+            .get("sub")
+            .and_then(|s| s.as_str())
+            .filter(|s| !s.is_empty())
+            .ok_or("token has no sub claim")?
+            .to_string();
+        let name = ["preferred_username", "email"]
+            .iter()
+            .find_map(|c| claims.get(*c).and_then(|v| v.as_str()).filter(|v| !v.is_empty()))
+            .unwrap_or(&subject)
+            .to_string();
+        let granted: Vec<Role> = match claim_path(claims, &self.roles_claim) {
+            Some(serde_json::Value::Array(values)) => values.iter().filter_map(|v| v.as_str()).map(str::to_string).collect(),
+            Some(serde_json::Value::String(s)) => s.split_whitespace().map(str::to_string).collect(),
+            _ => Vec::new(),
+        }
+        .iter()
+        .filter_map(|value| self.role_map.get(value).copied().or_else(|| Role::parse(value)))
+        .collect();
+        let role = Role::ALL
+            .into_iter()
+            .find(|r| granted.contains(r))
+            .or(self.default_role)
+            .ok_or_else(|| format!("token grants no role ({} claim)", self.roles_claim))?;
+        let tenant_id = claim_path(claims, &self.tenant_claim)
+            .and_then(|v| v.as_str())
+            .map(str::trim)
+            .filter(|t| !t.is_empty())
+            .map(str::to_string);
+        match (role, tenant_id.as_deref()) {
+            (Role::Tenant, None) => return Err(format!("tenant role without a {} claim", self.tenant_claim)),
+            (_, Some(tenant)) if tenant.contains('/') => return Err(format!("invalid tenant id {:?}", tenant)),
+            _ => {}
+        }
+        Ok(OidcPrincipal {
+            subject,
+            name,
+            role,
+            tenant_id,
+        })
+    }
+}
+
+/// The claim at a dotted `path` (`realm_access.roles`).
+fn claim_path<'a>(claims: &'a serde_json::Value, path: &str) -> Option<&'a serde_json::Value> {
+    path.split('.').try_fold(claims, |value, part| value.get(part))
+}
@@ -3,6 +3,7 @@
 //! This module ensures the Orchestrator has essential identity and configuration
 //! data from first boot, establishing the "Mission Genesis" for the system.
 
+use crate::keys;
 use super::store::{KbRecord, KbType, KnowledgeStore, PolicyRecord, SkillRecord, ETHOS_DEFAULT_POLICY_KEY};
 use std::sync::Arc;
 
@@ -57,6 +58,7 @@ pub fn initialize_core_identity(store: &Arc<KnowledgeStore>) -> Result<bool, sle
             "immutable": false,
             "tags": ["mission", "identity", "core"]
         }),
+        store.now_ms(),
     );
     store.insert_record(identity_slot, IDENTITY_MISSION_KEY, &mission)?;
     
@@ -75,6 +77,7 @@ pub fn initialize_core_identity(store: &Arc<KnowledgeStore>) -> Result<bool, sle
             "priority_count": 6,
             "tags": ["priorities", "research", "architecture"]
         }),
+        store.now_ms(),
     );
     store.insert_record(identity_slot, IDENTITY_PRIORITIES_KEY, &priorities)?;
     
@@ -99,6 +102,7 @@ pub fn initialize_core_identity(store: &Arc<KnowledgeStore>) -> Result<bool, sle
             ],
             "tags": ["persona", "voice", "communication"]
         }),
+        store.now_ms(),
     );
     store.insert_record(identity_slot, IDENTITY_PERSONA_KEY, &persona)?;
     
@@ -121,6 +125,7 @@ pub fn initialize_core_identity(store: &Arc<KnowledgeStore>) -> Result<bool, sle
             "status": "active",
             "tags": ["goals", "2026", "research", "roadmap"]
         }),
+        store.now_ms(),
     );
     store.insert_record(identity_slot, IDENTITY_GOALS_KEY, &goals)?;
     
@@ -141,14 +146,19 @@ pub fn initialize_core_skills(store: &Arc<KnowledgeStore>) -> Result<bool, sled:
     let mut inserted_any = false;
 
     // --- fs_workspace_analyzer ---
-    let key = "skills/fs_workspace_analyzer";
+    let key = &keys::skill_key("fs_workspace_analyzer");
     if store.get(skills_slot, key)?.is_none() {
         let record = SkillRecord {
             slug: "fs_workspace_analyzer".to_string(),
-            description: "Provides a high-level tree view of the local Rust workspace, identifying crates and key config files.".to_string(),
+            description: "Reports on the local workspace: crates, files and lines per language, large files and stale git branches.".to_string(),
             schema: serde_json::json!({
                 "path": "string (optional; defaults to current dir)",
-                "depth": "number (optional)"
+                "roots": "array (optional; several paths in one report)",
+                "depth": "number (optional)",
+                "exclude": "array (optional; .gitignore-style rules)",
+                "respect_gitignore": "boolean (optional; default true)",
+                "large_file_bytes": "number (optional; default 1048576)",
+                "stale_branch_days": "number (optional; default 90)"
             }),
         };
         store.insert(
@@ -160,7 +170,7 @@ pub fn initialize_core_skills(store: &Arc<KnowledgeStore>) -> Result<bool, sled:
     }
 
     // --- write_sandbox_file ---
-    let key = "skills/write_sandbox_file";
+    let key = &keys::skill_key("write_sandbox_file");
     if store.get(skills_slot, key)?.is_none() {
         let record = SkillRecord {
             slug: "write_sandbox_file".to_string(),
@@ -180,7 +190,7 @@ pub fn initialize_core_skills(store: &Arc<KnowledgeStore>) -> Result<bool, sled:
     }
 
     // --- recall_past_actions ---
-    let key = "skills/recall_past_actions";
+    let key = &keys::skill_key("recall_past_actions");
     if store.get(skills_slot, key)?.is_none() {
         let record = SkillRecord {
             slug: "recall_past_actions".to_string(),
@@ -198,7 +208,7 @@ pub fn initialize_core_skills(store: &Arc<KnowledgeStore>) -> Result<bool, sled:
     }
 
     // --- check_alignment ---
-    let key = "skills/check_alignment";
+    let key = &keys::skill_key("check_alignment");
     if store.get(skills_slot, key)?.is_none() {
         let record = SkillRecord {
             slug: "check_alignment".to_string(),
@@ -217,7 +227,7 @@ pub fn initialize_core_skills(store: &Arc<KnowledgeStore>) -> Result<bool, sled:
     }
 
     // --- analyze_sentiment ---
-    let key = "skills/analyze_sentiment";
+    let key = &keys::skill_key("analyze_sentiment");
     if store.get(skills_slot, key)?.is_none() {
         let record = SkillRecord {
             slug: "analyze_sentiment".to_string(),
@@ -235,6 +245,46 @@ pub fn initialize_core_skills(store: &Arc<KnowledgeStore>) -> Result<bool, sled:
         inserted_any = true;
     }
 
+    // --- ParseInboundMessage ---
+    let key = &keys::skill_key("ParseInboundMessage");
+    if store.get(skills_slot, key)?.is_none() {
+        let record = SkillRecord {
+            slug: "ParseInboundMessage".to_string(),
+            description: "Extracts intent category, urgency, requested service, and mentioned dates from free-form lead/email text. With lead_id, enriches the stored lead before the response chain.".to_string(),
+            schema: serde_json::json!({
+                "text": "string (optional if lead_id has message text)",
+                "lead_id": "string (optional; lead to read and enrich)"
+            }),
+        };
+        store.insert(
+            skills_slot,
+            key,
+            serde_json::to_vec(&record).unwrap_or_default().as_slice(),
+        )?;
+        inserted_any = true;
+    }
+
+    // --- KnowledgeSearch ---
+    let key = &keys::skill_key("KnowledgeSearch");
+    if store.get(skills_slot, key)?.is_none() {
+        let record = SkillRecord {
+            slug: "KnowledgeSearch".to_string(),
+            description: "Full-text search over KB record content, ranked by relevance. Use when the exact key is unknown (KnowledgeQuery needs it).".to_string(),
+            schema: serde_json::json!({
+                "query": "string (required)",
+                "slots": "array of numbers 1-8 (optional; default all)",
+                "slot_id": "number 1-8 (optional; single-slot shorthand)",
+                "limit": "number (optional; default 10, max 100)"
+            }),
+        };
+        store.insert(
+            skills_slot,
+            key,
+            serde_json::to_vec(&record).unwrap_or_default().as_slice(),
+        )?;
+        inserted_any = true;
+    }
+
     Ok(inserted_any)
 }
 
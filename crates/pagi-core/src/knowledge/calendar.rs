@@ -0,0 +1,40 @@
+//! Calendar bookings in KB_SOMA (`calendar_booking/{tenant_id}/{timestamp_ms}_{id}`): one record
+//! per event CalendarSkill created in the configured calendar.
+
+use serde::{Deserialize, Serialize};
+
+/// Largest page [`KnowledgeStore::list_calendar_bookings`](crate::KnowledgeStore::list_calendar_bookings) returns.
+pub const CALENDAR_BOOKING_MAX_LIMIT: usize = 200;
+
+/// One event CalendarSkill booked.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct CalendarBooking {
+    pub id: String,
+    pub tenant_id: String,
+    /// `google` or `caldav`.
+    pub provider: String,
+    /// Id of the event in the calendar (Google event id, CalDAV UID).
+    pub event_id: String,
+    pub summary: String,
+    /// RFC 3339, UTC.
+    pub start: String,
+    pub end: String,
+    #[serde(default)]
+    pub attendees: Vec<String>,
+    #[serde(default)]
+    pub lead_id: Option<String>,
+    /// Link to the event, when the provider gives one.
+    #[serde(default)]
+    pub link: Option<String>,
+    pub created_at_ms: i64,
+}
+
+impl CalendarBooking {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        serde_json::to_vec(self).unwrap_or_default()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        serde_json::from_slice(bytes).ok()
+    }
+}
@@ -0,0 +1,93 @@
+//! Change feed: one event per KB write, so the dashboard and skills can react to knowledge
+//! changes without polling.
+//!
+//! `KnowledgeStore` publishes on broadcast channels ([`KnowledgeStore::subscribe`] for one slot,
+//! [`KnowledgeStore::subscribe_all`] for every slot) after each write is applied, so a subscriber
+//! reading the store on an event sees the change. Events are dropped when nobody is listening,
+//! and a slow subscriber lags (`RecvError::Lagged`, see [`KbChange::seq`]) rather than slowing
+//! writes down. Events carry keys only, never values.
+//!
+//! [`KnowledgeStore::subscribe`]: super::KnowledgeStore::subscribe
+//! [`KnowledgeStore::subscribe_all`]: super::KnowledgeStore::subscribe_all
+
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+use tokio::sync::broadcast;
+
+/// Buffered events per subscriber before it starts lagging.
+pub const CHANGE_CHANNEL_CAPACITY: usize = 1024;
+
+/// What happened to a key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KbChangeKind {
+    Insert,
+    Update,
+    Remove,
+    /// Moved to the archive tier (still readable through the archive).
+    Archived,
+    /// The whole slot was replaced (snapshot restore); `key` is empty. Reload the slot.
+    Reset,
+}
+
+/// One KB write.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KbChange {
+    /// Store-wide sequence number, increasing by one per event; a gap after a lag tells how
+    /// many events were missed.
+    pub seq: u64,
+    pub slot_id: u8,
+    pub key: String,
+    pub kind: KbChangeKind,
+    pub timestamp_ms: i64,
+}
+
+/// Broadcast channels of one store: every slot, and each of slots 1–9.
+pub(crate) struct ChangeFeed {
+    all: broadcast::Sender<KbChange>,
+    slots: [broadcast::Sender<KbChange>; 9],
+    seq: AtomicU64,
+}
+
+impl Default for ChangeFeed {
+    fn default() -> Self {
+        Self {
+            all: broadcast::channel(CHANGE_CHANNEL_CAPACITY).0,
+            slots: std::array::from_fn(|_| broadcast::channel(CHANGE_CHANNEL_CAPACITY).0),
+            seq: AtomicU64::new(0),
+        }
+    }
+}
+
+impl ChangeFeed {
+    pub fn publish(&self, slot_id: u8, key: &str, kind: KbChangeKind, timestamp_ms: i64) {
+        let change = KbChange {
+            seq: self.seq.fetch_add(1, Ordering::Relaxed) + 1,
+            slot_id,
+            key: key.to_string(),
+            kind,
+            timestamp_ms,
+        };
+        // Err only means there are no subscribers.
+        if let Some(sender) = self.slot(slot_id) {
+            let _ = sender.send(change.clone());
+        }
+        let _ = self.all.send(change);
+    }
+
+    fn slot(&self, slot_id: u8) -> Option<&broadcast::Sender<KbChange>> {
+        self.slots.get((slot_id as usize).checked_sub(1)?)
+    }
+
+    /// Receiver for `slot_id`; for a slot outside 1–9 it is already closed.
+    pub fn subscribe(&self, slot_id: u8) -> broadcast::Receiver<KbChange> {
+        match self.slot(slot_id) {
+            Some(sender) => sender.subscribe(),
+            None => broadcast::channel(1).1,
+        }
+    }
+
+    pub fn subscribe_all(&self) -> broadcast::Receiver<KbChange> {
+        self.all.subscribe()
+    }
+}
@@ -0,0 +1,145 @@
+//! Record-level compression: large KB values (scraped HTML, long conversation records) are stored
+//! lz4-compressed behind a header.
+//!
+//! `KnowledgeStore::insert` compresses values of slots 1–8 at or above the threshold
+//! ([`DEFAULT_COMPRESSION_THRESHOLD`], see `KnowledgeStore::with_compression_threshold`) when
+//! that makes them smaller, and every read decompresses them, so callers always see the original
+//! bytes. The header is [`COMPRESSION_MAGIC`], a format version byte and a codec byte:
+//! - [`CODEC_LZ4`]: an lz4 block led by the original length
+//! - [`CODEC_STORED`]: the original bytes unchanged, for values that begin with the magic
+//!   themselves
+//!
+//! so any byte string reads back as written, and values without the header (including entries
+//! written before compression existed) are returned as stored. Shadow (slot 9) values are
+//! encrypted and stored as they are. This module holds the codec and the stats type; the store
+//! methods live in `store.rs`.
+
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+
+/// First bytes of a value stored with the compression header.
+pub const COMPRESSION_MAGIC: [u8; 4] = *b"\xffPKZ";
+/// Header format version, after the magic.
+pub const COMPRESSION_VERSION: u8 = 1;
+/// Codec byte: the body is the original value.
+pub const CODEC_STORED: u8 = 0;
+/// Codec byte: the body is an lz4 block prefixed with the original length.
+pub const CODEC_LZ4: u8 = 1;
+
+const HEADER_LEN: usize = COMPRESSION_MAGIC.len() + 2;
+
+/// Values this size (bytes) and larger are compressed.
+pub const DEFAULT_COMPRESSION_THRESHOLD: usize = 4 * 1024;
+
+fn with_header(codec: u8, body: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(HEADER_LEN + body.len());
+    out.extend_from_slice(&COMPRESSION_MAGIC);
+    out.extend_from_slice(&[COMPRESSION_VERSION, codec]);
+    out.extend_from_slice(body);
+    out
+}
+
+/// Codec and body of a value stored with the header.
+fn split_header(stored: &[u8]) -> Option<(u8, &[u8])> {
+    let rest = stored.strip_prefix(&COMPRESSION_MAGIC)?;
+    match rest {
+        [COMPRESSION_VERSION, codec, body @ ..] => Some((*codec, body)),
+        _ => None,
+    }
+}
+
+/// Stored form of `value`: compressed when it is at or over `threshold` and shrinks, wrapped as
+/// is when it happens to start with the magic, and unchanged otherwise.
+pub(crate) fn encode(value: &[u8], threshold: usize) -> Cow<'_, [u8]> {
+    if value.len() >= threshold {
+        let compressed = lz4_flex::compress_prepend_size(value);
+        if HEADER_LEN + compressed.len() < value.len() {
+            return Cow::Owned(with_header(CODEC_LZ4, &compressed));
+        }
+    }
+    if value.starts_with(&COMPRESSION_MAGIC) {
+        return Cow::Owned(escape(value));
+    }
+    Cow::Borrowed(value)
+}
+
+/// `value` behind the header with [`CODEC_STORED`], so it reads back unchanged whatever it
+/// starts with (e.g. plaintext that looks like a sealed value).
+pub(crate) fn escape(value: &[u8]) -> Vec<u8> {
+    with_header(CODEC_STORED, value)
+}
+
+/// The original bytes of a stored value. Values without the header, and ones whose version,
+/// codec or lz4 block does not check out, are returned as stored.
+pub(crate) fn decompress(stored: &[u8]) -> Cow<'_, [u8]> {
+    match split_header(stored) {
+        Some((CODEC_STORED, body)) => Cow::Borrowed(body),
+        Some((CODEC_LZ4, body)) => match lz4_flex::decompress_size_prepended(body) {
+            Ok(out) => Cow::Owned(out),
+            Err(_) => Cow::Borrowed(stored),
+        },
+        _ => Cow::Borrowed(stored),
+    }
+}
+
+/// Whether `stored` is compressed (carries the header with the lz4 codec).
+pub(crate) fn is_compressed(stored: &[u8]) -> bool {
+    matches!(split_header(stored), Some((CODEC_LZ4, _)))
+}
+
+/// On-disk versus original size of one slot's values (keys not counted).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SlotStorageStats {
+    pub slot_id: u8,
+    pub entries: usize,
+    pub compressed_entries: usize,
+    /// Bytes as stored in sled.
+    pub stored_bytes: u64,
+    /// Bytes after decompression.
+    pub raw_bytes: u64,
+}
+
+impl SlotStorageStats {
+    /// `stored_bytes / raw_bytes` (1.0 for an empty slot).
+    pub fn ratio(&self) -> f64 {
+        if self.raw_bytes == 0 {
+            1.0
+        } else {
+            self.stored_bytes as f64 / self.raw_bytes as f64
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn large_values_round_trip_and_small_or_legacy_ones_pass_through() {
+        let large = "<p>roofing supplier price list</p>".repeat(200).into_bytes();
+        let stored = encode(&large, DEFAULT_COMPRESSION_THRESHOLD);
+        assert!(is_compressed(&stored));
+        assert!(stored.len() < large.len() / 4);
+        assert_eq!(decompress(&stored).as_ref(), large.as_slice());
+
+        let small = b"{\"content\":\"short\"}";
+        assert!(matches!(encode(small, DEFAULT_COMPRESSION_THRESHOLD), Cow::Borrowed(_)));
+        let legacy = br#"{"content":"written before compression"}"#;
+        assert!(matches!(decompress(legacy), Cow::Borrowed(_)));
+        // Binary values pass through, even NUL-led ones.
+        assert_eq!(decompress(&[0, 1, 2, 3]).as_ref(), &[0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn values_that_look_like_a_header_read_back_unchanged() {
+        let mut lookalike = COMPRESSION_MAGIC.to_vec();
+        lookalike.extend_from_slice(&[COMPRESSION_VERSION, CODEC_LZ4, 9, 9]);
+        let stored = encode(&lookalike, DEFAULT_COMPRESSION_THRESHOLD);
+        assert!(!is_compressed(&stored));
+        assert_eq!(decompress(&stored).as_ref(), lookalike.as_slice());
+        // A header with an unknown version is not ours: returned as stored.
+        let mut unknown = COMPRESSION_MAGIC.to_vec();
+        unknown.extend_from_slice(&[7, CODEC_LZ4, 1]);
+        assert_eq!(decompress(&unknown).as_ref(), unknown.as_slice());
+    }
+}
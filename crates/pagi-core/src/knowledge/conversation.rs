@@ -0,0 +1,607 @@
+//! Conversation sessions: ordered chat turns per `(tenant, agent, session_id)` in **KB_CHRONOS**,
+//! and the context window assembled from them for the next reply.
+//!
+//! A session's metadata lives under `session/{tenant_id}/{agent_id}/{session_id}` and each turn
+//! (one user message and the reply) under `session_turn/{tenant_id}/{agent_id}/{session_id}/{turn:06}`,
+//! so key order is turn order. Turns are `KbRecord`s, which puts them in the full-text index.
+//! [`ConversationManager::context`] fills a budget of turns with the session's most recent turns
+//! and the older turns of the agent (any session) most relevant to the new message, split by the
+//! orchestrator's short-term / long-term memory weights.
+//!
+//! Memory consolidation keeps long histories inside the window: [`ConversationManager::consolidate`]
+//! (run by the Heartbeat) summarizes each run of [`DIGEST_BATCH_TURNS`] turns that has fallen
+//! behind a session's latest [`DIGEST_KEEP_RECENT_TURNS`] into a digest under
+//! `session_digest/{tenant_id}/{agent_id}/{session_id}/{last_turn:06}`, linking the turns it
+//! covers (which stay in place). The context prefers digests for older material: the session's
+//! latest digest always comes along, and a digested turn is replaced by its digest. This module
+//! holds the manager and its types; the transaction, scan and search primitives live in `store.rs`.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Arc;
+
+use super::prompts::{render_prompt, CONVERSATION_DIGEST_PROMPT};
+use super::store::{KbRecord, KbType, KnowledgeStore, SCAN_MAX_LIMIT};
+use super::transaction::abort_transaction;
+use crate::keys;
+
+/// Turns in the context window when the manager is not configured otherwise.
+pub const DEFAULT_CONTEXT_TURNS: usize = 10;
+/// Longest message text a turn contributes to the context window.
+const CONTEXT_MAX_MESSAGE_CHARS: usize = 1000;
+/// Turns summarized into one digest.
+pub const DIGEST_BATCH_TURNS: u32 = 10;
+/// A session's latest turns that are never digested (they are the recent context).
+pub const DIGEST_KEEP_RECENT_TURNS: u32 = 20;
+
+/// A session id is one segment of ASCII letters, digits, `-`, `_` and `.`, at most 64 long.
+pub fn is_valid_session_id(session_id: &str) -> bool {
+    keys::is_valid_tenant_id(session_id)
+}
+
+/// Metadata of one conversation session.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ConversationSession {
+    pub tenant_id: String,
+    pub agent_id: String,
+    pub session_id: String,
+    /// Turns recorded so far (the number of the latest turn).
+    pub turns: u32,
+    /// Turns `1..=digested_through` are summarized in digests.
+    #[serde(default)]
+    pub digested_through: u32,
+    pub created_at_ms: i64,
+    pub updated_at_ms: i64,
+}
+
+impl ConversationSession {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        serde_json::to_vec(self).unwrap_or_default()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        serde_json::from_slice(bytes).ok()
+    }
+}
+
+/// One exchange of a session: the user's message and the agent's reply.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ConversationTurn {
+    pub session_id: String,
+    /// 1-based position in the session.
+    pub turn: u32,
+    pub user: String,
+    pub assistant: String,
+    pub timestamp_ms: i64,
+}
+
+impl ConversationTurn {
+    /// The turn as stored: `content` is the exchange as text (what the full-text index sees).
+    fn to_record(&self, tenant_id: &str, agent_id: &str) -> KbRecord {
+        KbRecord::with_metadata(
+            format!("User: {}\n\nAssistant: {}", self.user, self.assistant),
+            serde_json::json!({
+                "type": "conversation_turn",
+                "tenant_id": tenant_id,
+                "agent_id": agent_id,
+                "session_id": self.session_id,
+                "turn": self.turn,
+                "user": self.user,
+                "assistant": self.assistant,
+            }),
+            self.timestamp_ms,
+        )
+    }
+
+    fn from_record(record: &KbRecord) -> Option<Self> {
+        Self::from_metadata(&record.metadata, record.timestamp)
+    }
+
+    fn from_metadata(meta: &serde_json::Value, timestamp_ms: i64) -> Option<Self> {
+        if meta.get("type").and_then(|v| v.as_str()) != Some("conversation_turn") {
+            return None;
+        }
+        Some(Self {
+            session_id: meta.get("session_id")?.as_str()?.to_string(),
+            turn: meta.get("turn")?.as_u64()? as u32,
+            user: meta.get("user")?.as_str()?.to_string(),
+            assistant: meta.get("assistant")?.as_str()?.to_string(),
+            timestamp_ms,
+        })
+    }
+}
+
+/// Summary of a run of a session's turns (memory consolidation).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ConversationDigest {
+    pub session_id: String,
+    pub first_turn: u32,
+    pub last_turn: u32,
+    pub summary: String,
+    /// Keys of the summarized turns in KB_CHRONOS.
+    pub turn_keys: Vec<String>,
+    pub created_at_ms: i64,
+}
+
+impl ConversationDigest {
+    /// The digest as stored: `content` is the summary (what the full-text index sees).
+    fn to_record(&self, tenant_id: &str, agent_id: &str) -> KbRecord {
+        KbRecord::with_metadata(
+            self.summary.clone(),
+            serde_json::json!({
+                "type": "conversation_digest",
+                "tenant_id": tenant_id,
+                "agent_id": agent_id,
+                "session_id": self.session_id,
+                "first_turn": self.first_turn,
+                "last_turn": self.last_turn,
+                "turn_keys": self.turn_keys,
+            }),
+            self.created_at_ms,
+        )
+    }
+
+    fn from_metadata(summary: &str, meta: &serde_json::Value, created_at_ms: i64) -> Option<Self> {
+        if meta.get("type").and_then(|v| v.as_str()) != Some("conversation_digest") {
+            return None;
+        }
+        Some(Self {
+            session_id: meta.get("session_id")?.as_str()?.to_string(),
+            first_turn: meta.get("first_turn")?.as_u64()? as u32,
+            last_turn: meta.get("last_turn")?.as_u64()? as u32,
+            summary: summary.to_string(),
+            turn_keys: serde_json::from_value(meta.get("turn_keys")?.clone()).ok()?,
+            created_at_ms,
+        })
+    }
+
+    fn from_record(record: &KbRecord) -> Option<Self> {
+        Self::from_metadata(&record.content, &record.metadata, record.timestamp)
+    }
+}
+
+/// Turns of a session that are due to be summarized into one digest.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DigestBatch {
+    pub session: ConversationSession,
+    /// Oldest first; the turns right after `session.digested_through`.
+    pub turns: Vec<ConversationTurn>,
+}
+
+impl DigestBatch {
+    /// The turns as a plain transcript (the `transcript` of the digest prompt).
+    pub fn transcript(&self) -> String {
+        self.turns
+            .iter()
+            .map(|t| format!("User: {}\nAssistant: {}", t.user, t.assistant))
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
+}
+
+/// Turns chosen for the next reply.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ConversationContext {
+    /// The session's latest turns, oldest first.
+    pub recent: Vec<ConversationTurn>,
+    /// Older turns relevant to the new message, most relevant first.
+    pub relevant: Vec<ConversationTurn>,
+    /// Digests standing in for older turns: the session's latest first, then relevant ones.
+    #[serde(default)]
+    pub digests: Vec<ConversationDigest>,
+}
+
+fn clip(text: &str) -> String {
+    match text.char_indices().nth(CONTEXT_MAX_MESSAGE_CHARS) {
+        Some((end, _)) => format!("{}…", &text[..end]),
+        None => text.to_string(),
+    }
+}
+
+impl ConversationContext {
+    pub fn is_empty(&self) -> bool {
+        self.recent.is_empty() && self.relevant.is_empty() && self.digests.is_empty()
+    }
+
+    /// The context as a system-prompt section; empty when there is no history.
+    pub fn to_prompt_section(&self) -> String {
+        let exchange = |t: &ConversationTurn| format!("User: {}\nAssistant: {}", clip(&t.user), clip(&t.assistant));
+        let mut parts = Vec::new();
+        if !self.digests.is_empty() {
+            let digests: Vec<String> = self
+                .digests
+                .iter()
+                .map(|d| format!("(turns {}-{}) {}", d.first_turn, d.last_turn, d.summary.trim()))
+                .collect();
+            parts.push(format!("Summaries of earlier conversation:\n{}", digests.join("\n\n")));
+        }
+        if !self.relevant.is_empty() {
+            let turns: Vec<String> = self.relevant.iter().map(exchange).collect();
+            parts.push(format!("Relevant earlier conversation:\n{}", turns.join("\n\n")));
+        }
+        if !self.recent.is_empty() {
+            let turns: Vec<String> = self.recent.iter().map(exchange).collect();
+            parts.push(format!("Conversation so far:\n{}", turns.join("\n\n")));
+        }
+        parts.join("\n\n")
+    }
+}
+
+/// Records chat turns per session and assembles the context window for the next reply.
+pub struct ConversationManager {
+    store: Arc<KnowledgeStore>,
+    context_turns: usize,
+    /// (short-term, long-term) weights: the share of the context for recent vs relevant turns.
+    memory_weights: (f32, f32),
+    digest_batch_turns: u32,
+    keep_recent_turns: u32,
+}
+
+impl ConversationManager {
+    pub fn new(store: Arc<KnowledgeStore>) -> Self {
+        Self {
+            store,
+            context_turns: DEFAULT_CONTEXT_TURNS,
+            memory_weights: (0.7, 0.3),
+            digest_batch_turns: DIGEST_BATCH_TURNS,
+            keep_recent_turns: DIGEST_KEEP_RECENT_TURNS,
+        }
+    }
+
+    /// Turns in the context window (recent plus relevant).
+    pub fn with_context_turns(mut self, turns: usize) -> Self {
+        self.context_turns = turns;
+        self
+    }
+
+    /// Splits the context window by the orchestrator's `(short_term, long_term)` memory weights.
+    pub fn with_memory_weights(mut self, weights: (f32, f32)) -> Self {
+        self.memory_weights = weights;
+        self
+    }
+
+    /// Digests cover `batch_turns` turns each, once they are older than a session's latest
+    /// `keep_recent_turns`.
+    pub fn with_digest_policy(mut self, batch_turns: u32, keep_recent_turns: u32) -> Self {
+        self.digest_batch_turns = batch_turns.max(1);
+        self.keep_recent_turns = keep_recent_turns;
+        self
+    }
+
+    /// `(recent, relevant)` turn budget for the context window.
+    pub fn context_budget(&self) -> (usize, usize) {
+        let (short, long) = (self.memory_weights.0.max(0.0), self.memory_weights.1.max(0.0));
+        let short_share = if short + long > 0.0 { short / (short + long) } else { 1.0 };
+        let recent = ((self.context_turns as f32) * short_share).round() as usize;
+        let recent = recent.min(self.context_turns);
+        (recent, self.context_turns - recent)
+    }
+
+    /// Appends a turn to the session (created on its first turn). Returns the stored turn, with
+    /// PII masked when the store has a redactor.
+    pub fn record_turn(
+        &self,
+        tenant_id: &str,
+        agent_id: &str,
+        session_id: &str,
+        user: &str,
+        assistant: &str,
+    ) -> Result<ConversationTurn, sled::Error> {
+        if !is_valid_session_id(session_id) {
+            return Err(sled::Error::Unsupported(format!("invalid session id {:?}", session_id)));
+        }
+        let (user, assistant) = match self.store.redactor() {
+            Some(redactor) => {
+                let (user, mut report) = redactor.redact_text(user);
+                let (assistant, found) = redactor.redact_text(assistant);
+                report.merge(&found);
+                self.store.record_redaction(tenant_id, agent_id, None, "conversation_turn", &report);
+                (user, assistant)
+            }
+            None => (user.to_string(), assistant.to_string()),
+        };
+        let now = self.store.now_ms();
+        let session_key = keys::session_key(tenant_id, agent_id, session_id);
+        self.store.transaction(KbType::Chronos.slot_id(), |tx| {
+            let mut session = tx
+                .get(&session_key)?
+                .and_then(|b| ConversationSession::from_bytes(&b))
+                .unwrap_or_else(|| ConversationSession {
+                    tenant_id: tenant_id.to_string(),
+                    agent_id: agent_id.to_string(),
+                    session_id: session_id.to_string(),
+                    turns: 0,
+                    digested_through: 0,
+                    created_at_ms: now,
+                    updated_at_ms: now,
+                });
+            session.turns = session
+                .turns
+                .checked_add(1)
+                .ok_or_else(|| abort_transaction(format!("session {} is full", session_id)))?;
+            session.updated_at_ms = now;
+            let turn = ConversationTurn {
+                session_id: session_id.to_string(),
+                turn: session.turns,
+                user: user.clone(),
+                assistant: assistant.clone(),
+                timestamp_ms: now,
+            };
+            let turn_key = keys::session_turn_key(tenant_id, agent_id, session_id, turn.turn);
+            tx.insert(&turn_key, &turn.to_record(tenant_id, agent_id).to_bytes())?;
+            tx.insert(&session_key, &session.to_bytes())?;
+            Ok(turn)
+        })
+    }
+
+    /// The session's metadata, if it has any turns.
+    pub fn session(&self, tenant_id: &str, agent_id: &str, session_id: &str) -> Option<ConversationSession> {
+        self.store
+            .get(KbType::Chronos.slot_id(), &keys::session_key(tenant_id, agent_id, session_id))
+            .ok()
+            .flatten()
+            .and_then(|b| ConversationSession::from_bytes(&b))
+    }
+
+    /// The session's latest `limit` turns (at most `SCAN_MAX_LIMIT`), oldest first.
+    pub fn history(
+        &self,
+        tenant_id: &str,
+        agent_id: &str,
+        session_id: &str,
+        limit: usize,
+    ) -> Result<Vec<ConversationTurn>, sled::Error> {
+        if limit == 0 {
+            return Ok(Vec::new());
+        }
+        let prefix = keys::session_turns_prefix(tenant_id, agent_id, session_id);
+        let page = self
+            .store
+            .scan_prefix_rev(KbType::Chronos.slot_id(), &prefix, None, limit.min(SCAN_MAX_LIMIT))?;
+        let mut turns: Vec<ConversationTurn> = page
+            .entries
+            .iter()
+            .filter_map(|(_, b)| KbRecord::from_bytes(b))
+            .filter_map(|r| ConversationTurn::from_record(&r))
+            .collect();
+        turns.reverse();
+        Ok(turns)
+    }
+
+    /// The session's digests, oldest first.
+    pub fn digests(&self, tenant_id: &str, agent_id: &str, session_id: &str) -> Result<Vec<ConversationDigest>, sled::Error> {
+        let prefix = keys::session_digests_prefix(tenant_id, agent_id, session_id);
+        let mut digests = Vec::new();
+        let mut cursor = None;
+        loop {
+            let page = self.store.scan_prefix(KbType::Chronos.slot_id(), &prefix, cursor.as_deref(), SCAN_MAX_LIMIT)?;
+            digests.extend(
+                page.entries
+                    .iter()
+                    .filter_map(|(_, b)| KbRecord::from_bytes(b))
+                    .filter_map(|r| ConversationDigest::from_record(&r)),
+            );
+            match page.next_cursor {
+                Some(next) => cursor = Some(next),
+                None => break,
+            }
+        }
+        Ok(digests)
+    }
+
+    /// Up to `limit` batches of turns due for a digest (at most one per session): the
+    /// `batch_turns` turns after a session's last digest, once all of them are older than its
+    /// latest `keep_recent_turns`.
+    pub fn pending_digests(&self, limit: usize) -> Result<Vec<DigestBatch>, sled::Error> {
+        let slot = KbType::Chronos.slot_id();
+        let batch = self.digest_batch_turns;
+        let mut batches = Vec::new();
+        let mut cursor = None;
+        while batches.len() < limit {
+            let page = self.store.scan_prefix(slot, keys::SESSION_PREFIX, cursor.as_deref(), SCAN_MAX_LIMIT)?;
+            for (_, bytes) in &page.entries {
+                let Some(session) = ConversationSession::from_bytes(bytes) else {
+                    continue;
+                };
+                let due = session.turns.saturating_sub(session.digested_through);
+                if due < batch.saturating_add(self.keep_recent_turns) {
+                    continue;
+                }
+                let (tenant, agent, id) = (&session.tenant_id, &session.agent_id, &session.session_id);
+                let first = session.digested_through + 1;
+                let start = keys::session_turn_key(tenant, agent, id, first);
+                let end = keys::session_turn_key(tenant, agent, id, first + batch);
+                let turns: Vec<ConversationTurn> = self
+                    .store
+                    .scan_range(slot, &start, Some(&end), None, batch as usize)?
+                    .entries
+                    .iter()
+                    .filter_map(|(_, b)| KbRecord::from_bytes(b))
+                    .filter_map(|r| ConversationTurn::from_record(&r))
+                    .collect();
+                if !turns.is_empty() {
+                    batches.push(DigestBatch { session, turns });
+                }
+                if batches.len() >= limit {
+                    break;
+                }
+            }
+            match page.next_cursor {
+                Some(next) => cursor = Some(next),
+                None => break,
+            }
+        }
+        Ok(batches)
+    }
+
+    /// Stores `summary` as the digest of `batch` and advances the session's `digested_through`.
+    /// Fails when the session was consolidated past the batch in the meantime.
+    pub fn store_digest(&self, batch: &DigestBatch, summary: &str) -> Result<ConversationDigest, sled::Error> {
+        let (Some(first), Some(last)) = (batch.turns.first(), batch.turns.last()) else {
+            return Err(sled::Error::Unsupported("digest batch has no turns".to_string()));
+        };
+        let (tenant, agent, id) = (&batch.session.tenant_id, &batch.session.agent_id, &batch.session.session_id);
+        let digest = ConversationDigest {
+            session_id: id.clone(),
+            first_turn: first.turn,
+            last_turn: last.turn,
+            summary: summary.trim().to_string(),
+            turn_keys: batch
+                .turns
+                .iter()
+                .map(|t| keys::session_turn_key(tenant, agent, id, t.turn))
+                .collect(),
+            created_at_ms: self.store.now_ms(),
+        };
+        let session_key = keys::session_key(tenant, agent, id);
+        let digest_key = keys::session_digest_key(tenant, agent, id, digest.last_turn);
+        let record = digest.to_record(tenant, agent).to_bytes();
+        self.store.transaction(KbType::Chronos.slot_id(), |tx| {
+            let mut session = tx
+                .get(&session_key)?
+                .and_then(|b| ConversationSession::from_bytes(&b))
+                .ok_or_else(|| abort_transaction(format!("session {} not found", id)))?;
+            if session.digested_through + 1 != digest.first_turn {
+                return Err(abort_transaction(format!(
+                    "session {} is already digested through turn {}",
+                    id, session.digested_through
+                )));
+            }
+            session.digested_through = digest.last_turn;
+            tx.insert(&digest_key, &record)?;
+            tx.insert(&session_key, &session.to_bytes())?;
+            Ok(())
+        })?;
+        Ok(digest)
+    }
+
+    /// Summarizes up to `max_batches` pending batches: each transcript goes through the
+    /// `conversation_digest` prompt template and `summarize` (the session is passed along so the
+    /// call can be metered to its tenant). A failed batch is logged and retried on a later run.
+    /// Returns the digests stored.
+    pub async fn consolidate<F, Fut>(&self, max_batches: usize, summarize: F) -> Result<Vec<ConversationDigest>, sled::Error>
+    where
+        F: Fn(ConversationSession, String) -> Fut,
+        Fut: Future<Output = Result<String, Box<dyn std::error::Error + Send + Sync>>>,
+    {
+        let mut stored = Vec::new();
+        for batch in self.pending_digests(max_batches)? {
+            let transcript = batch.transcript();
+            let prompt = render_prompt(Some(&self.store), CONVERSATION_DIGEST_PROMPT, &[("transcript", &transcript)])
+                .unwrap_or(transcript);
+            let summary = match summarize(batch.session.clone(), prompt).await {
+                Ok(summary) if !summary.trim().is_empty() => summary,
+                Ok(_) => {
+                    tracing::warn!(target: "pagi::conversation", session_id = %batch.session.session_id, "Empty conversation digest; will retry");
+                    continue;
+                }
+                Err(e) => {
+                    tracing::warn!(target: "pagi::conversation", session_id = %batch.session.session_id, error = %e, "Conversation digest failed; will retry");
+                    continue;
+                }
+            };
+            match self.store_digest(&batch, &summary) {
+                Ok(digest) => stored.push(digest),
+                Err(e) => {
+                    tracing::warn!(target: "pagi::conversation", session_id = %batch.session.session_id, error = %e, "Conversation digest not stored")
+                }
+            }
+        }
+        Ok(stored)
+    }
+
+    /// Context window for a reply to `message` in the session: its most recent turns, its latest
+    /// digest, and the agent's older turns and digests (any session) that best match `message`,
+    /// per [`context_budget`](Self::context_budget). Digested turns are replaced by their digest.
+    pub fn context(
+        &self,
+        tenant_id: &str,
+        agent_id: &str,
+        session_id: &str,
+        message: &str,
+    ) -> Result<ConversationContext, sled::Error> {
+        let (recent_budget, relevant_budget) = self.context_budget();
+        let recent = self.history(tenant_id, agent_id, session_id, recent_budget)?;
+        let mut context = ConversationContext {
+            recent,
+            ..Default::default()
+        };
+        if relevant_budget == 0 {
+            return Ok(context);
+        }
+        // The session's own older material: its latest digest.
+        let digest_prefix = keys::session_digests_prefix(tenant_id, agent_id, session_id);
+        if let Some((_, bytes)) = self
+            .store
+            .scan_prefix_rev(KbType::Chronos.slot_id(), &digest_prefix, None, 1)?
+            .entries
+            .first()
+        {
+            context.digests.extend(KbRecord::from_bytes(bytes).and_then(|r| ConversationDigest::from_record(&r)));
+        }
+        if message.trim().is_empty() {
+            return Ok(context);
+        }
+
+        let turn_prefix = keys::agent_session_turns_prefix(tenant_id, agent_id);
+        let agent_digest_prefix = keys::agent_session_digests_prefix(tenant_id, agent_id);
+        let mut skip: Vec<String> = context
+            .recent
+            .iter()
+            .map(|t| keys::session_turn_key(tenant_id, agent_id, &t.session_id, t.turn))
+            .collect();
+        skip.extend(
+            context
+                .digests
+                .iter()
+                .map(|d| keys::session_digest_key(tenant_id, agent_id, &d.session_id, d.last_turn)),
+        );
+        // Hits beyond the budget make up for digested turns that are dropped below.
+        let hits = self.store.text_search_where(
+            &[KbType::Chronos.slot_id()],
+            message,
+            relevant_budget * 4,
+            |key| (key.starts_with(&turn_prefix) || key.starts_with(&agent_digest_prefix)) && !skip.iter().any(|k| k == key),
+        )?;
+        let mut digested_through: HashMap<String, u32> = HashMap::new();
+        for hit in hits {
+            if context.relevant.len() + context.digests.len() >= relevant_budget {
+                break;
+            }
+            if let Some(digest) = ConversationDigest::from_metadata(&hit.content, &hit.metadata, hit.timestamp) {
+                context.digests.push(digest);
+                continue;
+            }
+            let Some(turn) = ConversationTurn::from_metadata(&hit.metadata, hit.timestamp) else {
+                continue;
+            };
+            let through = *digested_through.entry(turn.session_id.clone()).or_insert_with(|| {
+                self.session(tenant_id, agent_id, &turn.session_id)
+                    .map_or(0, |s| s.digested_through)
+            });
+            if turn.turn > through {
+                context.relevant.push(turn);
+            }
+        }
+        Ok(context)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn context_budget_follows_memory_weights() {
+        let store = Arc::new(KnowledgeStore::open_temporary().unwrap());
+        let manager = ConversationManager::new(Arc::clone(&store));
+        assert_eq!(manager.context_budget(), (7, 3));
+        let manager = ConversationManager::new(Arc::clone(&store))
+            .with_context_turns(4)
+            .with_memory_weights((0.0, 1.0));
+        assert_eq!(manager.context_budget(), (0, 4));
+        let manager = ConversationManager::new(store).with_memory_weights((0.0, 0.0));
+        assert_eq!(manager.context_budget(), (DEFAULT_CONTEXT_TURNS, 0));
+    }
+}
@@ -0,0 +1,56 @@
+//! Email delivery log in KB_SOMA (`email_log/{tenant_id}/{timestamp_ms}_{id}`): one record per
+//! message SendEmail tried to send, whether it went out, failed at the provider or was stopped by
+//! the Ethos policy. Bodies are not kept, only their length.
+
+use serde::{Deserialize, Serialize};
+
+/// Largest page [`KnowledgeStore::list_email_deliveries`](crate::KnowledgeStore::list_email_deliveries) returns.
+pub const EMAIL_LOG_MAX_LIMIT: usize = 200;
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum EmailDeliveryStatus {
+    /// Accepted by the provider.
+    #[default]
+    Sent,
+    /// The provider refused it or could not be reached.
+    Failed,
+    /// The Ethos policy did not allow the rendered message.
+    Blocked,
+}
+
+/// One message SendEmail handled.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct EmailDelivery {
+    pub id: String,
+    pub tenant_id: String,
+    pub status: EmailDeliveryStatus,
+    /// `smtp` or `sendgrid`.
+    pub provider: String,
+    pub from: String,
+    pub to: Vec<String>,
+    pub subject: String,
+    #[serde(default)]
+    pub body_bytes: usize,
+    #[serde(default)]
+    pub template: Option<String>,
+    #[serde(default)]
+    pub lead_id: Option<String>,
+    /// Id the provider gave the message (`Message-ID` for SMTP, `X-Message-Id` for SendGrid).
+    #[serde(default)]
+    pub message_id: Option<String>,
+    /// Why it failed or was blocked.
+    #[serde(default)]
+    pub error: Option<String>,
+    pub created_at_ms: i64,
+}
+
+impl EmailDelivery {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        serde_json::to_vec(self).unwrap_or_default()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        serde_json::from_slice(bytes).ok()
+    }
+}
@@ -0,0 +1,301 @@
+//! Evaluation suites and their stored runs in KB_LOGOS: `eval_runs/{suite}/{timestamp_ms}_{run_id}`
+//! holds each run with its per-case results, `eval_suites/{suite}` the summary of the suite's
+//! latest run.
+//!
+//! A suite is JSON: cases whose `goal` is dispatched through the live orchestrator (current skills,
+//! blueprint and prompt templates), each with assertions on the output. `path` selects part of the
+//! output with a `$`-rooted selector (default `$`, the whole output; strings compare as-is, other
+//! values as JSON text):
+//!
+//! ```json
+//! { "name": "respond_to_lead",
+//!   "cases": [{ "name": "pricing question",
+//!               "goal": { "AutonomousGoal": { "intent": "respond to lead", "context": { "lead_id": "l1" } } },
+//!               "assertions": [
+//!                 { "type": "contains", "path": "$.generated", "value": "pricing", "ignore_case": true },
+//!                 { "type": "regex", "path": "$.generated", "pattern": "(?i)thank" },
+//!                 { "type": "llm_judge", "path": "$.generated", "criteria": "Polite and answers the question", "min_score": 0.7 }] }] }
+//! ```
+//!
+//! Running a suite is `Orchestrator::run_eval`; this module holds the definitions and records.
+
+use crate::keys::is_valid_tenant_id;
+use crate::shared::Goal;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Largest number of cases in one suite.
+pub const EVAL_MAX_CASES: usize = 100;
+/// Largest page [`KnowledgeStore::eval_runs`](crate::KnowledgeStore::eval_runs) returns.
+pub const EVAL_RUNS_MAX_LIMIT: usize = 200;
+/// Skill that grades `llm_judge` assertions when the suite names none.
+pub const EVAL_DEFAULT_JUDGE_SKILL: &str = "ModelRouter";
+
+fn whole_output() -> String {
+    "$".to_string()
+}
+
+fn default_min_score() -> f64 {
+    0.7
+}
+
+/// A named set of eval cases.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EvalSuite {
+    /// Runs are stored under this name: ASCII letters, digits, `-`, `_` and `.`.
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Skill that grades `llm_judge` assertions (default [`EVAL_DEFAULT_JUDGE_SKILL`]).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub judge_skill: Option<String>,
+    pub cases: Vec<EvalCase>,
+}
+
+/// One goal and what its output must satisfy.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EvalCase {
+    pub name: String,
+    pub goal: Goal,
+    /// Agent the goal runs as (default: the caller's).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub agent_id: Option<String>,
+    pub assertions: Vec<EvalAssertion>,
+}
+
+/// A check on a case's output. Each scores 0–1: `llm_judge` its judged score, the others 0 or 1.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum EvalAssertion {
+    /// The selected text contains `value`.
+    Contains {
+        #[serde(default = "whole_output")]
+        path: String,
+        value: String,
+        #[serde(default)]
+        ignore_case: bool,
+    },
+    /// The selected text does not contain `value`.
+    NotContains {
+        #[serde(default = "whole_output")]
+        path: String,
+        value: String,
+        #[serde(default)]
+        ignore_case: bool,
+    },
+    /// The selected text matches `pattern` (Rust regex syntax, unanchored).
+    Regex {
+        #[serde(default = "whole_output")]
+        path: String,
+        pattern: String,
+    },
+    /// The selected value equals `value`.
+    Equals {
+        #[serde(default = "whole_output")]
+        path: String,
+        value: serde_json::Value,
+    },
+    /// The judge skill scores the selected text against `criteria`; passes at `min_score` or above.
+    LlmJudge {
+        #[serde(default = "whole_output")]
+        path: String,
+        criteria: String,
+        #[serde(default = "default_min_score")]
+        min_score: f64,
+    },
+}
+
+impl EvalAssertion {
+    pub fn path(&self) -> &str {
+        match self {
+            EvalAssertion::Contains { path, .. }
+            | EvalAssertion::NotContains { path, .. }
+            | EvalAssertion::Regex { path, .. }
+            | EvalAssertion::Equals { path, .. }
+            | EvalAssertion::LlmJudge { path, .. } => path,
+        }
+    }
+}
+
+/// A suite [`EvalSuite::validate`] rejects.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidEvalSuite(pub String);
+
+impl fmt::Display for InvalidEvalSuite {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid eval suite: {}", self.0)
+    }
+}
+
+impl std::error::Error for InvalidEvalSuite {}
+
+impl EvalSuite {
+    /// Checks the name, case count, that every case has a name and assertions, and each
+    /// assertion's selector, pattern and `min_score`.
+    pub fn validate(&self) -> Result<(), InvalidEvalSuite> {
+        let invalid = |reason: String| Err(InvalidEvalSuite(reason));
+        if !is_valid_tenant_id(&self.name) {
+            return invalid(format!(
+                "name {:?} must be ASCII letters, digits, '-', '_' or '.' (at most 64)",
+                self.name
+            ));
+        }
+        if self.cases.is_empty() || self.cases.len() > EVAL_MAX_CASES {
+            return invalid(format!("expected 1 to {} cases, got {}", EVAL_MAX_CASES, self.cases.len()));
+        }
+        for case in &self.cases {
+            if case.name.trim().is_empty() {
+                return invalid("every case needs a name".to_string());
+            }
+            if case.assertions.is_empty() {
+                return invalid(format!("case {:?} has no assertions", case.name));
+            }
+            for assertion in &case.assertions {
+                if !assertion.path().starts_with('$') {
+                    return invalid(format!("case {:?}: path {:?} must start with '$'", case.name, assertion.path()));
+                }
+                match assertion {
+                    EvalAssertion::Regex { pattern, .. } => {
+                        if let Err(e) = regex::Regex::new(pattern) {
+                            return invalid(format!("case {:?}: bad pattern: {}", case.name, e));
+                        }
+                    }
+                    EvalAssertion::LlmJudge { criteria, min_score, .. } => {
+                        if criteria.trim().is_empty() {
+                            return invalid(format!("case {:?}: llm_judge needs criteria", case.name));
+                        }
+                        if !(0.0..=1.0).contains(min_score) {
+                            return invalid(format!("case {:?}: min_score must be between 0 and 1", case.name));
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// How one assertion fared.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AssertionResult {
+    pub assertion: EvalAssertion,
+    pub passed: bool,
+    pub score: f64,
+    /// Why it failed, or the judge's reply.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+/// How one case fared: it passes when every assertion does and scores their mean.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct EvalCaseResult {
+    pub name: String,
+    pub passed: bool,
+    pub score: f64,
+    pub duration_ms: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output: Option<serde_json::Value>,
+    /// The goal failed; every assertion fails with it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub assertions: Vec<AssertionResult>,
+}
+
+/// One run of a suite: `score` is the mean of the case scores.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct EvalRun {
+    pub run_id: String,
+    pub suite: String,
+    /// What changed since the last run (e.g. `prompt v3`), as the caller named it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    pub started_at_ms: i64,
+    pub duration_ms: u64,
+    pub score: f64,
+    /// Cases that passed, out of `total`.
+    pub passed: usize,
+    pub total: usize,
+    pub cases: Vec<EvalCaseResult>,
+}
+
+/// An [`EvalRun`] without its cases: one point of a suite's score trend.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct EvalRunSummary {
+    pub run_id: String,
+    pub suite: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    pub started_at_ms: i64,
+    pub duration_ms: u64,
+    pub score: f64,
+    pub passed: usize,
+    pub total: usize,
+}
+
+impl EvalRun {
+    pub fn summary(&self) -> EvalRunSummary {
+        EvalRunSummary {
+            run_id: self.run_id.clone(),
+            suite: self.suite.clone(),
+            label: self.label.clone(),
+            started_at_ms: self.started_at_ms,
+            duration_ms: self.duration_ms,
+            score: self.score,
+            passed: self.passed,
+            total: self.total,
+        }
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        serde_json::to_vec(self).unwrap_or_default()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        serde_json::from_slice(bytes).ok()
+    }
+}
+
+impl EvalRunSummary {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        serde_json::to_vec(self).unwrap_or_default()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        serde_json::from_slice(bytes).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn suites_parse_and_validate() {
+        let suite: EvalSuite = serde_json::from_value(serde_json::json!({
+            "name": "respond_to_lead",
+            "cases": [{
+                "name": "pricing",
+                "goal": { "ExecuteSkill": { "name": "DraftResponse", "payload": null } },
+                "assertions": [
+                    { "type": "contains", "value": "price" },
+                    { "type": "llm_judge", "path": "$.generated", "criteria": "polite" }
+                ]
+            }]
+        }))
+        .unwrap();
+        assert!(suite.validate().is_ok());
+        assert_eq!(suite.cases[0].assertions[0].path(), "$");
+        assert!(matches!(suite.cases[0].assertions[1], EvalAssertion::LlmJudge { min_score, .. } if min_score == 0.7));
+
+        let mut bad = suite.clone();
+        bad.cases[0].assertions = vec![EvalAssertion::Regex { path: "$".into(), pattern: "(".into() }];
+        assert!(bad.validate().is_err());
+        bad.name = "a/b".into();
+        assert!(bad.validate().unwrap_err().0.contains("name"));
+        let unknown = serde_json::json!({ "type": "contains", "value": "x", "typo": 1 });
+        assert!(serde_json::from_value::<EvalAssertion>(unknown).is_err());
+    }
+}
@@ -0,0 +1,351 @@
+//! Knowledge graph over KB-3 (Logos): entities and `subject → predicate → object` relations, so
+//! the planner and DraftResponse can pull connected facts ("acme → interested_in → backups")
+//! instead of flat keys.
+//!
+//! The graph lives in each tenant's keyspace (see [`crate::keys`]):
+//!
+//! | Key | Value |
+//! |-----|-------|
+//! | `graph/entity/{entity_id}` | [`GraphEntity`] |
+//! | `graph/out/{subject}/{predicate}/{object}` | [`GraphRelation`] |
+//! | `graph/in/{object}/{predicate}/{subject}` | the same relation, for walking edges backwards |
+//!
+//! Both copies of a relation are written in one transaction, and upserting a relation creates
+//! missing endpoints as bare entities (named after their id), so every edge leads somewhere.
+
+use super::store::{KbType, SCAN_MAX_LIMIT};
+use super::tenant::TenantHandle;
+use super::transaction::{abort_transaction, KbTransaction, KbTxResult};
+use crate::keys;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+
+/// Hops [`TenantHandle::graph_neighbors`] walks at most.
+pub const GRAPH_MAX_DEPTH: usize = 3;
+/// Entities one neighborhood returns at most (the root included).
+pub const GRAPH_MAX_NODES: usize = 200;
+const ENTITY_ID_MAX_LEN: usize = 128;
+
+/// An entity id is one key segment: no `/` or control characters, at most 128 bytes.
+pub fn is_valid_entity_id(id: &str) -> bool {
+    keys::is_segment(id) && id.len() <= ENTITY_ID_MAX_LEN && id.trim() == id && !id.chars().any(char::is_control)
+}
+
+/// A predicate is one segment of ASCII letters, digits, `-`, `_` and `.`, at most 64 long.
+pub fn is_valid_predicate(predicate: &str) -> bool {
+    keys::is_valid_tenant_id(predicate)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct GraphEntity {
+    pub id: String,
+    /// Free-form type (`customer`, `service`, ...).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kind: Option<String>,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "serde_json::Map::is_empty")]
+    pub properties: serde_json::Map<String, serde_json::Value>,
+    pub updated_at_ms: i64,
+}
+
+impl GraphEntity {
+    fn bare(id: &str, now_ms: i64) -> Self {
+        Self {
+            id: id.to_string(),
+            kind: None,
+            name: id.to_string(),
+            properties: Default::default(),
+            updated_at_ms: now_ms,
+        }
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        serde_json::to_vec(self).unwrap_or_default()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        serde_json::from_slice(bytes).ok()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct GraphRelation {
+    pub subject: String,
+    pub predicate: String,
+    pub object: String,
+    #[serde(default, skip_serializing_if = "serde_json::Map::is_empty")]
+    pub properties: serde_json::Map<String, serde_json::Value>,
+    pub updated_at_ms: i64,
+}
+
+impl GraphRelation {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        serde_json::to_vec(self).unwrap_or_default()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        serde_json::from_slice(bytes).ok()
+    }
+
+    fn out_key(&self) -> String {
+        keys::graph_out_key(&self.subject, &self.predicate, &self.object)
+    }
+}
+
+/// An entity reached from the root, `depth` hops away.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct GraphNeighbor {
+    #[serde(flatten)]
+    pub entity: GraphEntity,
+    pub depth: usize,
+}
+
+/// Entities within some hops of a root entity and the relations between them, in both
+/// directions.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct GraphNeighborhood {
+    pub root: String,
+    /// Root first (depth 0), then by depth.
+    pub entities: Vec<GraphNeighbor>,
+    pub relations: Vec<GraphRelation>,
+    /// [`GRAPH_MAX_NODES`] was reached before the walk finished.
+    pub truncated: bool,
+}
+
+impl GraphNeighborhood {
+    /// One line per relation, with entity names: `Acme Corp → interested_in → Managed backups`.
+    pub fn facts(&self) -> Vec<String> {
+        let names: HashMap<&str, &str> =
+            self.entities.iter().map(|n| (n.entity.id.as_str(), n.entity.name.as_str())).collect();
+        let name = |id: &str| names.get(id).copied().unwrap_or(id).to_string();
+        self.relations
+            .iter()
+            .map(|r| format!("{} → {} → {}", name(&r.subject), r.predicate, name(&r.object)))
+            .collect()
+    }
+}
+
+fn invalid(message: String) -> sled::Error {
+    sled::Error::Unsupported(message)
+}
+
+fn check_entity_id(id: &str) -> Result<(), sled::Error> {
+    if is_valid_entity_id(id) {
+        Ok(())
+    } else {
+        Err(invalid(format!("invalid graph entity id {:?}", id)))
+    }
+}
+
+fn check_relation(subject: &str, predicate: &str, object: &str) -> Result<(), sled::Error> {
+    check_entity_id(subject)?;
+    check_entity_id(object)?;
+    if is_valid_predicate(predicate) {
+        Ok(())
+    } else {
+        Err(invalid(format!("invalid graph predicate {:?}", predicate)))
+    }
+}
+
+fn tx_entity(tx: &KbTransaction<'_>, key: &str) -> KbTxResult<Option<GraphEntity>> {
+    match tx.get(key)? {
+        Some(bytes) => GraphEntity::from_bytes(&bytes)
+            .map(Some)
+            .ok_or_else(|| abort_transaction(format!("{} is not a graph entity", key))),
+        None => Ok(None),
+    }
+}
+
+impl TenantHandle<'_> {
+    /// Creates or updates entity `id`: `kind` and `name` replace the stored ones when given, and
+    /// `properties` are merged into the stored properties (a `null` value removes one).
+    pub fn upsert_entity(
+        &self,
+        id: &str,
+        kind: Option<&str>,
+        name: Option<&str>,
+        properties: &serde_json::Map<String, serde_json::Value>,
+    ) -> Result<GraphEntity, sled::Error> {
+        check_entity_id(id)?;
+        let key = self.stored_key(&keys::graph_entity_key(id))?;
+        let now_ms = self.store().now_ms();
+        self.store().transaction(KbType::Logos.slot_id(), |tx| {
+            let mut entity = tx_entity(tx, &key)?.unwrap_or_else(|| GraphEntity::bare(id, now_ms));
+            if let Some(kind) = kind {
+                entity.kind = Some(kind.to_string()).filter(|k| !k.is_empty());
+            }
+            if let Some(name) = name.filter(|n| !n.trim().is_empty()) {
+                entity.name = name.trim().to_string();
+            }
+            for (field, value) in properties {
+                if value.is_null() {
+                    entity.properties.remove(field);
+                } else {
+                    entity.properties.insert(field.clone(), value.clone());
+                }
+            }
+            entity.updated_at_ms = now_ms;
+            tx.insert(&key, &entity.to_bytes())?;
+            Ok(entity)
+        })
+    }
+
+    pub fn graph_entity(&self, id: &str) -> Result<Option<GraphEntity>, sled::Error> {
+        check_entity_id(id)?;
+        Ok(self
+            .get(KbType::Logos.slot_id(), &keys::graph_entity_key(id))?
+            .and_then(|b| GraphEntity::from_bytes(&b)))
+    }
+
+    /// Stores `subject → predicate → object` (replacing its properties) with its reverse copy,
+    /// creating missing endpoint entities.
+    pub fn upsert_relation(
+        &self,
+        subject: &str,
+        predicate: &str,
+        object: &str,
+        properties: &serde_json::Map<String, serde_json::Value>,
+    ) -> Result<GraphRelation, sled::Error> {
+        check_relation(subject, predicate, object)?;
+        let relation = GraphRelation {
+            subject: subject.to_string(),
+            predicate: predicate.to_string(),
+            object: object.to_string(),
+            properties: properties.clone(),
+            updated_at_ms: self.store().now_ms(),
+        };
+        let out_key = self.stored_key(&relation.out_key())?;
+        let in_key = self.stored_key(&keys::graph_in_key(object, predicate, subject))?;
+        let endpoints = [
+            (subject, self.stored_key(&keys::graph_entity_key(subject))?),
+            (object, self.stored_key(&keys::graph_entity_key(object))?),
+        ];
+        self.store().transaction(KbType::Logos.slot_id(), |tx| {
+            for (id, key) in &endpoints {
+                if tx_entity(tx, key)?.is_none() {
+                    tx.insert(key, &GraphEntity::bare(id, relation.updated_at_ms).to_bytes())?;
+                }
+            }
+            let bytes = relation.to_bytes();
+            tx.insert(&out_key, &bytes)?;
+            tx.insert(&in_key, &bytes)?;
+            Ok(())
+        })?;
+        Ok(relation)
+    }
+
+    /// Removes `subject → predicate → object`; the entities stay. False when there was none.
+    pub fn remove_relation(&self, subject: &str, predicate: &str, object: &str) -> Result<bool, sled::Error> {
+        check_relation(subject, predicate, object)?;
+        let out_key = self.stored_key(&keys::graph_out_key(subject, predicate, object))?;
+        let in_key = self.stored_key(&keys::graph_in_key(object, predicate, subject))?;
+        self.store().transaction(KbType::Logos.slot_id(), |tx| {
+            let removed = tx.remove(&out_key)?.is_some();
+            tx.remove(&in_key)?;
+            Ok(removed)
+        })
+    }
+
+    /// Relations `id` takes part in, as subject or object.
+    pub fn graph_relations(&self, id: &str) -> Result<Vec<GraphRelation>, sled::Error> {
+        check_entity_id(id)?;
+        let mut relations = Vec::new();
+        for prefix in [keys::graph_out_prefix(id), keys::graph_in_prefix(id)] {
+            let mut cursor: Option<String> = None;
+            loop {
+                let page = self.scan_prefix(KbType::Logos.slot_id(), &prefix, cursor.as_deref(), SCAN_MAX_LIMIT)?;
+                relations.extend(page.entries.iter().filter_map(|(_, b)| GraphRelation::from_bytes(b)));
+                match page.next_cursor {
+                    Some(next) => cursor = Some(next),
+                    None => break,
+                }
+            }
+        }
+        Ok(relations)
+    }
+
+    /// Entities within `depth` hops of `id` (1 to [`GRAPH_MAX_DEPTH`]), following relations in
+    /// both directions, and the relations walked. None when `id` is not in the graph.
+    pub fn graph_neighbors(&self, id: &str, depth: usize) -> Result<Option<GraphNeighborhood>, sled::Error> {
+        let Some(root) = self.graph_entity(id)? else {
+            return Ok(None);
+        };
+        let depth = depth.clamp(1, GRAPH_MAX_DEPTH);
+        let mut neighborhood = GraphNeighborhood {
+            root: root.id.clone(),
+            entities: vec![GraphNeighbor { entity: root, depth: 0 }],
+            relations: Vec::new(),
+            truncated: false,
+        };
+        let mut seen: HashSet<String> = HashSet::from([id.to_string()]);
+        let mut walked: HashSet<String> = HashSet::new();
+        let mut queue: VecDeque<(String, usize)> = VecDeque::from([(id.to_string(), 0)]);
+        while let Some((current, hops)) = queue.pop_front() {
+            if hops == depth {
+                continue;
+            }
+            for relation in self.graph_relations(&current)? {
+                let other = if relation.subject == current { &relation.object } else { &relation.subject };
+                if !seen.contains(other) {
+                    if seen.len() >= GRAPH_MAX_NODES {
+                        neighborhood.truncated = true;
+                        continue;
+                    }
+                    seen.insert(other.clone());
+                    let entity = self.graph_entity(other)?.unwrap_or_else(|| GraphEntity::bare(other, relation.updated_at_ms));
+                    neighborhood.entities.push(GraphNeighbor { entity, depth: hops + 1 });
+                    queue.push_back((other.clone(), hops + 1));
+                }
+                if walked.insert(relation.out_key()) {
+                    neighborhood.relations.push(relation);
+                }
+            }
+        }
+        Ok(Some(neighborhood))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::KnowledgeStore;
+
+    fn props(value: serde_json::Value) -> serde_json::Map<String, serde_json::Value> {
+        value.as_object().cloned().unwrap_or_default()
+    }
+
+    #[test]
+    fn neighbors_walk_relations_both_ways_up_to_depth() {
+        let store = KnowledgeStore::open_temporary().unwrap();
+        let kb = store.tenant("acme").unwrap();
+        kb.upsert_entity("customer-x", Some("customer"), Some("Customer X"), &props(serde_json::json!({ "tier": "gold" })))
+            .unwrap();
+        kb.upsert_relation("customer-x", "interested_in", "service-y", &Default::default()).unwrap();
+        kb.upsert_relation("service-y", "provided_by", "team-z", &Default::default()).unwrap();
+        kb.upsert_relation("lead-q", "referred_by", "customer-x", &Default::default()).unwrap();
+
+        let one = kb.graph_neighbors("customer-x", 1).unwrap().unwrap();
+        let ids: Vec<(&str, usize)> = one.entities.iter().map(|n| (n.entity.id.as_str(), n.depth)).collect();
+        assert_eq!(ids, vec![("customer-x", 0), ("service-y", 1), ("lead-q", 1)]);
+        assert_eq!(one.facts(), vec!["Customer X → interested_in → service-y", "lead-q → referred_by → Customer X"]);
+
+        let two = kb.graph_neighbors("customer-x", 2).unwrap().unwrap();
+        assert!(two.entities.iter().any(|n| n.entity.id == "team-z" && n.depth == 2));
+        assert_eq!(two.relations.len(), 3);
+
+        // Properties merge; `null` removes one.
+        let updated = kb
+            .upsert_entity("customer-x", None, None, &props(serde_json::json!({ "tier": null, "region": "eu" })))
+            .unwrap();
+        assert_eq!((updated.name.as_str(), updated.properties.len()), ("Customer X", 1));
+
+        assert!(kb.remove_relation("customer-x", "interested_in", "service-y").unwrap());
+        assert!(!kb.remove_relation("customer-x", "interested_in", "service-y").unwrap());
+        let after = kb.graph_neighbors("service-y", 1).unwrap().unwrap();
+        assert_eq!(after.facts(), vec!["service-y → provided_by → team-z"]);
+
+        // Another tenant sees none of it.
+        assert!(store.tenant("beta").unwrap().graph_neighbors("customer-x", 2).unwrap().is_none());
+        assert!(kb.upsert_relation("a/b", "knows", "c", &Default::default()).is_err());
+        assert!(kb.upsert_relation("a", "knows about", "c", &Default::default()).is_err());
+    }
+}
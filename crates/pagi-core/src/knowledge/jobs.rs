@@ -0,0 +1,113 @@
+//! Background goal jobs in KB_SOMA (`job/{job_id}`): goals submitted with `/v1/execute?async=true`.
+//!
+//! The gateway answers with the job id right away and runs the goal in a background task. The job
+//! record follows it: `queued`, then `running` with the plan's top-level step progress
+//! ([`GoalJob::apply_progress`]), then a final status with the result or error. Finished jobs are
+//! kept for [`JOB_RETENTION_MS`] ([`KnowledgeStore::prune_jobs`](crate::KnowledgeStore::prune_jobs)).
+
+use crate::orchestrator::{StepProgress, StepStatus};
+use serde::{Deserialize, Serialize};
+
+/// How long a finished job stays readable.
+pub const JOB_RETENTION_MS: i64 = 7 * 24 * 60 * 60 * 1000;
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    #[default]
+    Queued,
+    Running,
+    Succeeded,
+    Failed,
+    Cancelled,
+    TimedOut,
+}
+
+impl JobStatus {
+    pub fn is_finished(self) -> bool {
+        !matches!(self, JobStatus::Queued | JobStatus::Running)
+    }
+}
+
+/// A goal running (or run) in the background.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct GoalJob {
+    pub job_id: String,
+    pub tenant_id: String,
+    pub agent_id: String,
+    /// Correlation id of the dispatch: cancel with `/v1/execute/:correlation_id/cancel`, follow
+    /// live step events by it.
+    pub correlation_id: String,
+    /// [`Goal::kind`](crate::Goal::kind), e.g. `AutonomousGoal`.
+    pub goal: String,
+    #[serde(default)]
+    pub status: JobStatus,
+    /// Top-level plan steps that completed or were skipped.
+    #[serde(default)]
+    pub steps_completed: usize,
+    /// Top-level plan length, once the plan started.
+    #[serde(default)]
+    pub steps_total: Option<usize>,
+    /// Label of the step running now.
+    #[serde(default)]
+    pub current_step: Option<String>,
+    #[serde(default)]
+    pub result: Option<serde_json::Value>,
+    #[serde(default)]
+    pub error: Option<String>,
+    pub created_at_ms: i64,
+    #[serde(default)]
+    pub started_at_ms: Option<i64>,
+    #[serde(default)]
+    pub finished_at_ms: Option<i64>,
+}
+
+impl GoalJob {
+    /// A queued job; `created_at_ms` is stamped when it is stored.
+    pub fn new(job_id: &str, tenant_id: &str, agent_id: &str, correlation_id: &str, goal: &str) -> Self {
+        Self {
+            job_id: job_id.to_string(),
+            tenant_id: tenant_id.to_string(),
+            agent_id: agent_id.to_string(),
+            correlation_id: correlation_id.to_string(),
+            goal: goal.to_string(),
+            ..Self::default()
+        }
+    }
+
+    /// Folds a step event of the job's plan in (sub-plan steps only move `current_step`).
+    pub fn apply_progress(&mut self, progress: &StepProgress) {
+        if progress.depth > 1 {
+            if progress.status == StepStatus::Started {
+                self.current_step = Some(progress.step.clone());
+            }
+            return;
+        }
+        self.steps_total = Some(progress.total);
+        match progress.status {
+            StepStatus::Started => self.current_step = Some(progress.step.clone()),
+            StepStatus::Completed | StepStatus::Skipped => {
+                self.steps_completed = self.steps_completed.max(progress.index + 1);
+                self.current_step = None;
+            }
+            StepStatus::Failed | StepStatus::Cancelled | StepStatus::Blocked => {}
+        }
+    }
+
+    /// Moves the job into final `status`.
+    pub fn finish(&mut self, status: JobStatus, result: Option<serde_json::Value>, error: Option<String>, now_ms: i64) {
+        self.status = status;
+        self.result = result;
+        self.error = error;
+        self.current_step = None;
+        self.finished_at_ms = Some(now_ms);
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        serde_json::to_vec(self).unwrap_or_default()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        serde_json::from_slice(bytes).ok()
+    }
+}
@@ -17,7 +17,20 @@
 //! | 8    | Soma   | Execution: physical interface, buffer                | Standard (Sled)|
 //! | 9    | Shadow | The Vault: trauma, anchors, private journaling      | **AES-256-GCM**|
 
+mod agents;
+mod approvals;
+mod archive;
+mod audit;
+mod auth;
 mod bootstrap;
+mod calendar;
+mod changes;
+mod compression;
+mod conversation;
+mod email_log;
+mod evals;
+mod graph;
+mod jobs;
 mod kb1;
 mod kb2;
 mod kb3;
@@ -26,10 +39,43 @@ mod kb5;
 mod kb6;
 mod kb7;
 mod kb8;
+mod persona;
+mod policy_rules;
+mod prompts;
+mod redaction;
+mod reembed;
+mod snapshot;
 mod store;
+mod tenant;
+mod text_index;
+mod topics;
+mod transaction;
+mod vector_index;
 pub mod vault;
 
+pub use agents::{
+    is_valid_agent_id, ActiveHours, AgentHealth, AgentRecord, AgentScheduleUpdate, AgentStatus, AgentTickTracker,
+    InvalidAgentSchedule,
+};
+pub use approvals::{ApprovalCheckpoint, ApprovalRecord, ApprovalStatus};
+pub use auth::{hash_api_key, ApiKeyRecord, OidcConfig, OidcPrincipal, Permission, Role, API_KEY_SECRET_PREFIX};
+pub use archive::{ArchiveReport, ArchiveSegment, ARCHIVE_DIR_NAME};
+pub use audit::{
+    AuditOutcome, AuditPage, AuditQuery, AuditRecord, AUDIT_DEFAULT_LIMIT, AUDIT_MAX_LIMIT, AUDIT_TREE, REDACTION_GOAL_TYPE,
+};
+pub use redaction::{PiiKind, RedactionConfig, RedactionReport, Redactor};
+pub use compression::{SlotStorageStats, COMPRESSION_MAGIC, DEFAULT_COMPRESSION_THRESHOLD};
+pub use conversation::{
+    is_valid_session_id, ConversationContext, ConversationDigest, ConversationManager, ConversationSession, ConversationTurn, DigestBatch,
+    DEFAULT_CONTEXT_TURNS, DIGEST_BATCH_TURNS, DIGEST_KEEP_RECENT_TURNS,
+};
+pub use graph::{
+    is_valid_entity_id, is_valid_predicate, GraphEntity, GraphNeighbor, GraphNeighborhood, GraphRelation, GRAPH_MAX_DEPTH,
+    GRAPH_MAX_NODES,
+};
+pub use changes::{KbChange, KbChangeKind, CHANGE_CHANNEL_CAPACITY};
 pub use bootstrap::{initialize_core_identity, initialize_core_skills, initialize_ethos_policy, verify_identity, IdentityStatus};
+pub use reembed::{ReembedCursor, ReembedJob, ReembedStatus, EMBEDDING_MODEL_METADATA_KEY, REEMBED_JOBS_TREE};
 pub use kb1::Kb1;
 pub use kb2::Kb2;
 pub use kb3::Kb3;
@@ -39,8 +85,38 @@ pub use kb6::Kb6;
 pub use kb7::Kb7;
 pub use kb8::Kb8;
 pub use store::{pagi_kb_slot_label, AgentMessage, AlignmentResult, EventRecord, KbRecord, KbStatus, KbType, KnowledgeStore, PolicyRecord, RelationRecord, SovereignState, ETHOS_DEFAULT_POLICY_KEY, SLOT_LABELS, kardia_relation_key};
+pub use crate::keys::{agent_persona_key, agent_persona_profile_key, auto_reply_template_key, DEFAULT_TENANT_ID};
+pub use store::{AutoReplyPrompt, AUTO_REPLY_THREAD_LIMIT, DEFAULT_AUTO_REPLY_TEMPLATE};
+pub use store::{InboxRetry, INBOX_MAX_ATTEMPTS, INBOX_RETRY_BASE_MS, INBOX_RETRY_MAX_MS};
 pub use store::SkillRecord;
-pub use vault::{EmotionalAnchor, SecretVault, VaultError};
+pub use policy_rules::{
+    ActiveEthosPolicy, ConditionTest, InvalidPolicyRules, PolicyDecision, PolicyRule, PolicyRules, RuleAction, RuleCondition,
+    POLICY_MAX_RULES,
+};
+pub use store::{AlertTarget, QuerySelector, StandingQuery, StandingQueryChange, STANDING_QUERY_PREFIX};
+pub use store::{IdempotencyClaim, IdempotencyRecord, IDEMPOTENCY_CLAIM_TIMEOUT_MS};
+pub use store::{usage_month, AgentTokenUsage, TokenBudgetExceeded, TokenUsageRecord};
+pub use store::LlmCacheEntry;
+pub use store::{CasConflict, ScanPage, CAS_MAX_RETRIES, DEFAULT_MAX_VALUE_BYTES, SCAN_MAX_LIMIT};
+pub use persona::{InvalidPersona, PersonaProfile, PERSONA_MAX_BOUNDARIES, PERSONA_MAX_LEN};
+pub use prompts::{
+    is_valid_prompt_name, render_prompt, PromptError, PromptTemplate, HEARTBEAT_BACKGROUND_TASK_PROMPT, PROMPT_MAX_LEN,
+    CONVERSATION_DIGEST_PROMPT, EVAL_JUDGE_PROMPT, SYSTEM_PERSONA_PROMPT, THALAMUS_CLASSIFICATION_PROMPT,
+};
+pub use snapshot::{RestoreReport, SnapshotInfo, SnapshotManager, SnapshotReason, SnapshotRetention, SnapshotTree, SNAPSHOT_DIR_NAME};
+pub use tenant::TenantHandle;
+pub use transaction::{abort_transaction, KbMultiTransaction, KbTransaction, KbTxResult};
+pub use topics::{is_valid_topic_name, TopicSubscription, TOPIC_FANOUT_BATCH};
+pub use jobs::{GoalJob, JobStatus, JOB_RETENTION_MS};
+pub use email_log::{EmailDelivery, EmailDeliveryStatus, EMAIL_LOG_MAX_LIMIT};
+pub use evals::{
+    AssertionResult, EvalAssertion, EvalCase, EvalCaseResult, EvalRun, EvalRunSummary, EvalSuite, InvalidEvalSuite,
+    EVAL_DEFAULT_JUDGE_SKILL, EVAL_MAX_CASES, EVAL_RUNS_MAX_LIMIT,
+};
+pub use calendar::{CalendarBooking, CALENDAR_BOOKING_MAX_LIMIT};
+pub use text_index::{TextSearchHit, TEXT_INDEX_TREE, TEXT_SEARCH_DEFAULT_LIMIT};
+pub use vector_index::{SemanticHit, VECTOR_INDEX_TREE};
+pub use vault::{EmotionalAnchor, EncryptionConfig, SecretVault, VaultError, ENCRYPTED_MARKER};
 
 /// Common trait for all knowledge base slots.
 pub trait KnowledgeSource: Send + Sync {
@@ -0,0 +1,152 @@
+//! Per-agent persona profiles in KB_PNEUMA (`pneuma/{agent_id}/persona_profile`).
+//!
+//! A [`PersonaProfile`] is the structured form of an agent's persona: its system prompt, tone and
+//! boundaries go into the agent's Mission Directive
+//! ([`build_system_directive`](crate::KnowledgeStore::build_system_directive)), which every chat
+//! and Heartbeat generation for the agent starts from, and its preferred model and temperature are
+//! the defaults for those generations ([`PersonaProfile::apply_generation_defaults`]). An agent without a profile keeps
+//! the plain-text `pneuma/{agent_id}/persona` setting, or the shared `core_persona`.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Longest system prompt, tone or boundary (in characters).
+pub const PERSONA_MAX_LEN: usize = 8_000;
+/// Most boundaries a profile may list.
+pub const PERSONA_MAX_BOUNDARIES: usize = 32;
+
+/// Why a persona profile was rejected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidPersona(pub String);
+
+impl fmt::Display for InvalidPersona {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid persona profile: {}", self.0)
+    }
+}
+
+impl std::error::Error for InvalidPersona {}
+
+/// How one agent speaks and which model it speaks with.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct PersonaProfile {
+    /// Set from the key on save.
+    #[serde(default)]
+    pub agent_id: String,
+    /// Who the agent is; replaces `core_persona` in its Mission Directive.
+    pub system_prompt: String,
+    /// e.g. "warm, concise, no jargon".
+    #[serde(default)]
+    pub tone: Option<String>,
+    /// Things the agent must not do or talk about.
+    #[serde(default)]
+    pub boundaries: Vec<String>,
+    /// ModelRouter model used when a request does not name one.
+    #[serde(default)]
+    pub preferred_model: Option<String>,
+    /// Sampling temperature used when a request does not set one (0.0–2.0).
+    #[serde(default)]
+    pub temperature: Option<f32>,
+    #[serde(default)]
+    pub updated_at_ms: i64,
+}
+
+impl PersonaProfile {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        serde_json::to_vec(self).unwrap_or_default()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        serde_json::from_slice(bytes).ok()
+    }
+
+    pub fn validate(&self) -> Result<(), InvalidPersona> {
+        let invalid = |reason: String| Err(InvalidPersona(reason));
+        if self.system_prompt.trim().is_empty() {
+            return invalid("system_prompt must not be empty".to_string());
+        }
+        let texts = std::iter::once(("system_prompt", &self.system_prompt))
+            .chain(self.tone.iter().map(|t| ("tone", t)))
+            .chain(self.boundaries.iter().map(|b| ("boundary", b)));
+        for (field, text) in texts {
+            if text.chars().count() > PERSONA_MAX_LEN {
+                return invalid(format!("{} is longer than {} characters", field, PERSONA_MAX_LEN));
+            }
+        }
+        if self.boundaries.len() > PERSONA_MAX_BOUNDARIES {
+            return invalid(format!("at most {} boundaries", PERSONA_MAX_BOUNDARIES));
+        }
+        if self.boundaries.iter().any(|b| b.trim().is_empty()) {
+            return invalid("boundaries must not be empty".to_string());
+        }
+        if self.preferred_model.as_deref().is_some_and(|m| m.trim().is_empty()) {
+            return invalid("preferred_model must not be empty".to_string());
+        }
+        if let Some(t) = self.temperature {
+            if !(0.0..=2.0).contains(&t) {
+                return invalid(format!("temperature must be between 0.0 and 2.0 (got {})", t));
+            }
+        }
+        Ok(())
+    }
+
+    /// The persona's part of the Mission Directive: system prompt, tone and boundaries.
+    pub fn to_directive(&self) -> String {
+        let mut out = format!("Persona: {}", self.system_prompt.trim());
+        if let Some(tone) = self.tone.as_deref().map(str::trim).filter(|t| !t.is_empty()) {
+            out.push_str(&format!("\nTone: {}", tone));
+        }
+        if !self.boundaries.is_empty() {
+            out.push_str("\nBoundaries (never cross these):");
+            for boundary in &self.boundaries {
+                out.push_str(&format!("\n- {}", boundary.trim()));
+            }
+        }
+        out
+    }
+
+    /// Fills `model` and `temperature` of a ModelRouter payload from the profile where the payload
+    /// leaves them unset (absent or null); a request's own choice wins.
+    pub fn apply_generation_defaults(&self, payload: &mut serde_json::Value) {
+        let Some(fields) = payload.as_object_mut() else {
+            return;
+        };
+        let unset = |fields: &serde_json::Map<String, serde_json::Value>, key: &str| {
+            fields.get(key).is_none_or(serde_json::Value::is_null)
+        };
+        if let Some(model) = &self.preferred_model {
+            if unset(fields, "model") {
+                fields.insert("model".to_string(), serde_json::json!(model));
+            }
+        }
+        if let Some(temperature) = self.temperature {
+            if unset(fields, "temperature") {
+                fields.insert("temperature".to_string(), serde_json::json!(temperature));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn request_settings_win_over_persona_defaults() {
+        let persona = PersonaProfile {
+            system_prompt: "You book plumbing jobs.".to_string(),
+            preferred_model: Some("fast-model".to_string()),
+            temperature: Some(0.2),
+            ..Default::default()
+        };
+        let mut payload = serde_json::json!({ "prompt": "hi", "model": null, "temperature": 0.9 });
+        persona.apply_generation_defaults(&mut payload);
+        assert_eq!(payload["model"], "fast-model");
+        assert!((payload["temperature"].as_f64().unwrap() - 0.9).abs() < 1e-6);
+
+        let too_hot = PersonaProfile { temperature: Some(3.0), ..persona.clone() };
+        assert!(too_hot.validate().is_err());
+        assert!(PersonaProfile::default().validate().is_err());
+        assert!(persona.validate().is_ok());
+    }
+}
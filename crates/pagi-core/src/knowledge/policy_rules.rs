@@ -0,0 +1,327 @@
+//! Ethos policy rules (v2) in KB_ETHOS: scoped rules with payload conditions instead of keyword
+//! substring matching.
+//!
+//! A rule applies to some skills (`skills`, exact names or `Prefix*`; empty means every skill) and
+//! matches when all of its `conditions` hold. A condition reads one payload field by selector
+//! (`$`, `$.content`, `$.files[0].path`) and tests it with a regex, a substring, an equality, an
+//! allowlist (`one_of`) or presence, optionally negated. The matching rule with the highest
+//! `priority` decides `allow`, `deny` or `require_approval`; on a tie the most restrictive action
+//! wins, and with no match the rule set's `default_action` applies. Allow rules above a deny are
+//! how exceptions and allowlists are expressed.
+//!
+//! The current rule set lives under `policy/rules` and every saved version under
+//! `policy_versions/{version:06}`. Without a rule set the legacy keyword
+//! [`PolicyRecord`](super::PolicyRecord) (`policy/default`) stays in force ([`ActiveEthosPolicy`]).
+
+use super::{AlignmentResult, PolicyRecord};
+use crate::orchestrator::mapping::select;
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::fmt;
+
+/// Most rules one rule set may hold.
+pub const POLICY_MAX_RULES: usize = 256;
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum RuleAction {
+    #[default]
+    Allow,
+    Deny,
+    RequireApproval,
+}
+
+impl RuleAction {
+    /// Tie-break order between rules of equal priority: deny over approval over allow.
+    fn restrictiveness(self) -> u8 {
+        match self {
+            RuleAction::Allow => 0,
+            RuleAction::RequireApproval => 1,
+            RuleAction::Deny => 2,
+        }
+    }
+}
+
+/// What a condition checks on the selected value.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ConditionTest {
+    /// The value (strings as-is, anything else as JSON) matches the regex.
+    Regex(String),
+    /// The value contains the text, case-insensitively.
+    Contains(String),
+    /// The value equals this JSON value.
+    Equals(serde_json::Value),
+    /// The value equals one of these JSON values (an allowlist; negate it for a denylist).
+    OneOf(Vec<serde_json::Value>),
+    /// The field is present (`true`) or absent (`false`).
+    Exists(bool),
+}
+
+/// One test against one payload field, e.g. `{"path": "$.content", "regex": "(?i)api[_-]?key"}`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RuleCondition {
+    /// `$`-rooted selector into the skill payload.
+    #[serde(default = "default_path")]
+    pub path: String,
+    #[serde(flatten)]
+    pub test: ConditionTest,
+    /// Inverts the test.
+    #[serde(default)]
+    pub negate: bool,
+}
+
+fn default_path() -> String {
+    "$".to_string()
+}
+
+impl RuleCondition {
+    fn matches(&self, payload: &serde_json::Value) -> bool {
+        let value = select(&self.path, payload);
+        let hit = match (&self.test, value) {
+            (ConditionTest::Exists(expected), value) => value.is_some() == *expected,
+            (_, None) => false,
+            (ConditionTest::Regex(pattern), Some(value)) => regex::Regex::new(pattern)
+                .map(|re| re.is_match(&value_text(value)))
+                .unwrap_or(false),
+            (ConditionTest::Contains(text), Some(value)) => {
+                value_text(value).to_lowercase().contains(&text.to_lowercase())
+            }
+            (ConditionTest::Equals(expected), Some(value)) => value == expected,
+            (ConditionTest::OneOf(allowed), Some(value)) => allowed.contains(value),
+        };
+        hit != self.negate
+    }
+}
+
+fn value_text(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// A scoped Ethos rule.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PolicyRule {
+    /// Unique within the rule set; reported with every decision it makes.
+    pub id: String,
+    #[serde(default)]
+    pub description: String,
+    /// Skills the rule applies to: exact names (case-insensitive) or `Prefix*`. Empty: all skills.
+    #[serde(default)]
+    pub skills: Vec<String>,
+    /// All must match (empty: the rule matches every call of its skills).
+    #[serde(default)]
+    pub conditions: Vec<RuleCondition>,
+    pub action: RuleAction,
+    /// Higher runs first.
+    #[serde(default)]
+    pub priority: i32,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl PolicyRule {
+    fn applies_to(&self, skill: &str) -> bool {
+        self.skills.is_empty()
+            || self.skills.iter().any(|scope| match scope.strip_suffix('*') {
+                Some(prefix) => skill.to_lowercase().starts_with(&prefix.to_lowercase()),
+                None => scope.eq_ignore_ascii_case(skill),
+            })
+    }
+
+    fn matches(&self, skill: &str, payload: &serde_json::Value) -> bool {
+        self.enabled && self.applies_to(skill) && self.conditions.iter().all(|c| c.matches(payload))
+    }
+}
+
+/// A rule set rejected by [`PolicyRules::validate`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidPolicyRules(pub String);
+
+impl fmt::Display for InvalidPolicyRules {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid policy rules: {}", self.0)
+    }
+}
+
+impl std::error::Error for InvalidPolicyRules {}
+
+/// A versioned Ethos rule set.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct PolicyRules {
+    /// Stamped by `put_policy_rules`: 1 for the first saved set, then one up per save.
+    #[serde(default)]
+    pub version: u32,
+    #[serde(default)]
+    pub rules: Vec<PolicyRule>,
+    /// Decision when no rule matches.
+    #[serde(default)]
+    pub default_action: RuleAction,
+    /// Operator note on what changed in this version.
+    #[serde(default)]
+    pub note: Option<String>,
+    #[serde(default)]
+    pub updated_at_ms: i64,
+}
+
+impl PolicyRules {
+    pub fn new(rules: Vec<PolicyRule>, default_action: RuleAction) -> Self {
+        Self {
+            rules,
+            default_action,
+            ..Self::default()
+        }
+    }
+
+    /// Checks ids (unique, one key segment), skill scopes, selectors and regexes.
+    pub fn validate(&self) -> Result<(), InvalidPolicyRules> {
+        if self.rules.len() > POLICY_MAX_RULES {
+            return Err(InvalidPolicyRules(format!("at most {} rules", POLICY_MAX_RULES)));
+        }
+        let mut ids = std::collections::HashSet::new();
+        for rule in &self.rules {
+            if !crate::keys::is_segment(&rule.id) {
+                return Err(InvalidPolicyRules(format!("rule id {:?} must be a non-empty segment without '/'", rule.id)));
+            }
+            if !ids.insert(rule.id.as_str()) {
+                return Err(InvalidPolicyRules(format!("duplicate rule id {:?}", rule.id)));
+            }
+            if rule.skills.iter().any(|s| s.trim().is_empty() || s.trim() == "*") {
+                return Err(InvalidPolicyRules(format!(
+                    "rule {:?}: skill scopes must be names or prefixes (leave skills empty for all)",
+                    rule.id
+                )));
+            }
+            for condition in &rule.conditions {
+                if !condition.path.trim().starts_with('$') {
+                    return Err(InvalidPolicyRules(format!(
+                        "rule {:?}: path {:?} must be a $-rooted selector",
+                        rule.id, condition.path
+                    )));
+                }
+                if let ConditionTest::Regex(pattern) = &condition.test {
+                    regex::Regex::new(pattern)
+                        .map_err(|e| InvalidPolicyRules(format!("rule {:?}: bad regex: {}", rule.id, e)))?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Decides a call of `skill` with `payload` (a missing payload is `null`).
+    pub fn evaluate(&self, skill: &str, payload: Option<&serde_json::Value>) -> PolicyDecision {
+        let payload = payload.unwrap_or(&serde_json::Value::Null);
+        let winner = self
+            .rules
+            .iter()
+            .filter(|rule| rule.matches(skill, payload))
+            // min_by_key keeps the first of equals, so among identical ranks the first listed wins.
+            .min_by_key(|rule| (Reverse(rule.priority), Reverse(rule.action.restrictiveness())));
+        match winner {
+            Some(rule) => PolicyDecision {
+                action: rule.action,
+                rule_id: Some(rule.id.clone()),
+                reason: match (rule.action, rule.description.is_empty()) {
+                    (RuleAction::Allow, _) => None,
+                    (_, true) => Some(format!("Ethos rule '{}' matched skill '{}'", rule.id, skill)),
+                    (_, false) => Some(format!("Ethos rule '{}': {}", rule.id, rule.description)),
+                },
+                version: Some(self.version),
+            },
+            None => PolicyDecision {
+                action: self.default_action,
+                rule_id: None,
+                reason: (self.default_action != RuleAction::Allow)
+                    .then(|| format!("no Ethos rule allows skill '{}' (default: {:?})", skill, self.default_action)),
+                version: Some(self.version),
+            },
+        }
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        serde_json::to_vec(self).unwrap_or_default()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        serde_json::from_slice(bytes).ok()
+    }
+}
+
+/// Outcome of an Ethos check.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PolicyDecision {
+    pub action: RuleAction,
+    /// Rule that decided (None: the default action or the legacy policy).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rule_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    /// Rule set version that decided (None: the legacy policy).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<u32>,
+}
+
+impl PolicyDecision {
+    pub fn allowed() -> Self {
+        Self {
+            action: RuleAction::Allow,
+            rule_id: None,
+            reason: None,
+            version: None,
+        }
+    }
+
+    pub fn is_allowed(&self) -> bool {
+        self.action == RuleAction::Allow
+    }
+
+    /// The decision as a pass/fail alignment result (approval counts as a fail).
+    pub fn alignment(&self) -> AlignmentResult {
+        match self.action {
+            RuleAction::Allow => AlignmentResult::Pass,
+            RuleAction::Deny | RuleAction::RequireApproval => AlignmentResult::Fail {
+                reason: self.reason.clone().unwrap_or_else(|| "blocked by Ethos policy".to_string()),
+            },
+        }
+    }
+}
+
+/// The Ethos policy in force: the v2 rule set when one is stored, else the legacy keyword policy.
+#[derive(Debug, Clone)]
+pub enum ActiveEthosPolicy {
+    Rules(PolicyRules),
+    Legacy(PolicyRecord),
+}
+
+impl ActiveEthosPolicy {
+    /// Decides a call of `skill` with `payload`. The legacy policy scans the payload's `content`
+    /// field, else the whole payload, and denies on a forbidden skill or sensitive keyword.
+    pub fn evaluate(&self, skill: &str, payload: Option<&serde_json::Value>) -> PolicyDecision {
+        match self {
+            ActiveEthosPolicy::Rules(rules) => rules.evaluate(skill, payload),
+            ActiveEthosPolicy::Legacy(policy) => {
+                let content = payload
+                    .map(|p| match p.get("content").and_then(|v| v.as_str()) {
+                        Some(content) => content.to_string(),
+                        None => p.to_string(),
+                    })
+                    .unwrap_or_default();
+                match policy.allows(skill, &content) {
+                    AlignmentResult::Pass => PolicyDecision::allowed(),
+                    AlignmentResult::Fail { reason } => PolicyDecision {
+                        action: RuleAction::Deny,
+                        rule_id: None,
+                        reason: Some(reason),
+                        version: None,
+                    },
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,323 @@
+//! Prompt templates: named prompts with `{{variable}}` placeholders that operators can tune
+//! without recompiling.
+//!
+//! The current version of a template lives in **KB_PNEUMA** under `prompts/{name}` and every
+//! saved version under `prompt_versions/{name}/{version:06}`. A name with nothing stored falls
+//! back to its built-in default (version 0), so call sites always get a prompt. A stored override
+//! of a built-in may only use the variables the built-in's call site provides. This module holds
+//! the template type, rendering and the built-ins; the store methods (`get_prompt`, `put_prompt`,
+//! `list_prompts`, `prompt_versions`) live in `store.rs`.
+
+use super::KnowledgeStore;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Opening line of the Mission Directive (`KnowledgeStore::build_system_directive`).
+pub const SYSTEM_PERSONA_PROMPT: &str = "system_persona";
+/// Heartbeat status update for an agent's `background_task`. Variables: `agent_id`, `task`.
+pub const HEARTBEAT_BACKGROUND_TASK_PROMPT: &str = "heartbeat_background_task";
+/// Thalamus routing of a piece of information to one KB. Variables: `input`, `context`.
+pub const THALAMUS_CLASSIFICATION_PROMPT: &str = "thalamus_classification";
+/// Memory digest of a run of conversation turns. Variables: `transcript`.
+pub const CONVERSATION_DIGEST_PROMPT: &str = "conversation_digest";
+/// Grading of an eval case's output by an `llm_judge` assertion. Variables: `criteria`, `output`.
+pub const EVAL_JUDGE_PROMPT: &str = "eval_judge";
+
+/// Longest template accepted by `put_prompt`.
+pub const PROMPT_MAX_LEN: usize = 32 * 1024;
+
+/// `(name, description, template)` of every built-in prompt.
+const BUILTIN_PROMPTS: &[(&str, &str, &str)] = &[
+    (
+        SYSTEM_PERSONA_PROMPT,
+        "Opening line of the chat system prompt (Mission Directive).",
+        "You are PAGI, a Sovereign AGI. You are an authentic, adaptive collaborator with a touch of wit. \
+         Do not list your skills or JSON schemas unless the user explicitly asks. Balance empathy with candor. \
+         Use the user's Soma and Ethos context (when provided below) to give grounded, peer-like support. \
+         When the user asks for the date or time, state it (e.g. 'It's February 6, 2026'); you may suggest checking Soma levels if they have been at the screen for a long time.",
+    ),
+    (
+        HEARTBEAT_BACKGROUND_TASK_PROMPT,
+        "Heartbeat status update for an agent's background task.",
+        "You are agent_id={{agent_id}}. Background task: {{task}}\n\nProvide a short status update.",
+    ),
+    (
+        THALAMUS_CLASSIFICATION_PROMPT,
+        "Thalamus routing of a piece of information to one knowledge base.",
+        r#"You are a cognitive router. Given a piece of information, choose exactly ONE knowledge base where it belongs. Reply with only that word, nothing else.
+
+Knowledge bases (reply with exactly one word):
+- Logos: pure distilled information, research, papers, findings, code snippets, "Internal Wikipedia"
+- Soma: physical interface, execution, side effects on hardware, file writes, buffer/staging
+- Pneuma: identity, mission, goals, "why", evolving playbook, vision
+- Kardia: user preferences, "who", vibe, personal notes
+- Chronos: conversation history, temporal thread, short/long-term memory
+- Techne: skills registry, blueprints, how-to, specialized functions
+- Oikos: workspace context, "where", system logs, crate layout, project structure
+- Ethos: guardrails, security, audit, "should", constraints
+
+Information to classify:
+"{{input}}"
+
+Context: {{context}}
+Reply with exactly one word from the list above."#,
+    ),
+    (
+        CONVERSATION_DIGEST_PROMPT,
+        "Memory digest of older conversation turns (conversation consolidation).",
+        "Summarize the conversation below into a compact memory digest of at most 5 short bullet points. \
+         Keep facts, decisions, preferences and open questions; drop greetings and filler. \
+         Reply with the bullet points only.\n\n{{transcript}}",
+    ),
+    (
+        EVAL_JUDGE_PROMPT,
+        "Grading of an eval case's output against a criterion (llm_judge assertions).",
+        "You are grading the output of an AI agent against one criterion.\n\nCriterion: {{criteria}}\n\n\
+         Output:\n{{output}}\n\n\
+         Reply with only a score from 0 (does not meet the criterion at all) to 10 (fully meets it).",
+    ),
+];
+
+/// A named prompt template, as stored in KB_PNEUMA.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PromptTemplate {
+    pub name: String,
+    pub template: String,
+    #[serde(default)]
+    pub description: String,
+    /// 0 for a built-in default; each save bumps it by one.
+    pub version: u32,
+    /// When this version was saved (Unix ms; 0 for a built-in).
+    #[serde(default)]
+    pub updated_at_ms: i64,
+}
+
+/// Why a template could not be saved or rendered.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PromptError {
+    InvalidName(String),
+    NotFound(String),
+    /// Empty or longer than [`PROMPT_MAX_LEN`].
+    InvalidTemplate { name: String, reason: String },
+    /// An override of a built-in uses variables its call site does not provide.
+    UnknownVariables { name: String, variables: Vec<String> },
+    /// Rendering without a value for these variables.
+    MissingVariables { name: String, variables: Vec<String> },
+}
+
+impl fmt::Display for PromptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PromptError::InvalidName(name) => write!(f, "invalid prompt name {:?}", name),
+            PromptError::NotFound(name) => write!(f, "prompt {:?} not found", name),
+            PromptError::InvalidTemplate { name, reason } => write!(f, "prompt {:?}: {}", name, reason),
+            PromptError::UnknownVariables { name, variables } => write!(
+                f,
+                "prompt {:?} uses variables its caller does not provide: {}",
+                name,
+                variables.join(", ")
+            ),
+            PromptError::MissingVariables { name, variables } => {
+                write!(f, "prompt {:?} needs values for: {}", name, variables.join(", "))
+            }
+        }
+    }
+}
+
+impl std::error::Error for PromptError {}
+
+/// A prompt name is one segment of ASCII letters, digits, `-`, `_` and `.`, at most 64 long.
+pub fn is_valid_prompt_name(name: &str) -> bool {
+    crate::keys::is_valid_tenant_id(name)
+}
+
+/// `(start, end, variable)` of each `{{variable}}` in `template` (byte offsets of the braces).
+/// Anything between the braces that is not a plain identifier is left as text.
+fn placeholders(template: &str) -> Vec<(usize, usize, &str)> {
+    let mut out = Vec::new();
+    let mut from = 0;
+    while let Some(open) = template[from..].find("{{").map(|i| from + i) {
+        let Some(close) = template[open + 2..].find("}}").map(|i| open + 2 + i) else {
+            break;
+        };
+        let name = template[open + 2..close].trim();
+        if !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            out.push((open, close + 2, name));
+            from = close + 2;
+        } else {
+            from = open + 2;
+        }
+    }
+    out
+}
+
+impl PromptTemplate {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        serde_json::to_vec(self).unwrap_or_default()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        serde_json::from_slice(bytes).ok()
+    }
+
+    /// The built-in default named `name`.
+    pub fn builtin(name: &str) -> Option<Self> {
+        BUILTIN_PROMPTS
+            .iter()
+            .find(|(n, _, _)| *n == name)
+            .map(|(name, description, template)| Self {
+                name: name.to_string(),
+                template: template.to_string(),
+                description: description.to_string(),
+                version: 0,
+                updated_at_ms: 0,
+            })
+    }
+
+    /// Every built-in default, in declaration order.
+    pub fn builtins() -> Vec<Self> {
+        BUILTIN_PROMPTS.iter().filter_map(|(name, _, _)| Self::builtin(name)).collect()
+    }
+
+    /// Variables the template uses, each once, in order of first use.
+    pub fn variables(&self) -> Vec<String> {
+        let mut out: Vec<String> = Vec::new();
+        for (_, _, name) in placeholders(&self.template) {
+            if !out.iter().any(|v| v == name) {
+                out.push(name.to_string());
+            }
+        }
+        out
+    }
+
+    /// Replaces every `{{variable}}` in one pass (so placeholder-like text inside values is kept).
+    /// Fails when a variable has no value.
+    pub fn render(&self, vars: &[(&str, &str)]) -> Result<String, PromptError> {
+        let mut out = String::with_capacity(self.template.len());
+        let mut missing: Vec<String> = Vec::new();
+        let mut last = 0;
+        for (start, end, name) in placeholders(&self.template) {
+            out.push_str(&self.template[last..start]);
+            match vars.iter().find(|(k, _)| *k == name) {
+                Some((_, value)) => out.push_str(value),
+                None if !missing.iter().any(|m| m == name) => missing.push(name.to_string()),
+                None => {}
+            }
+            last = end;
+        }
+        out.push_str(&self.template[last..]);
+        if !missing.is_empty() {
+            return Err(PromptError::MissingVariables {
+                name: self.name.clone(),
+                variables: missing,
+            });
+        }
+        Ok(out)
+    }
+
+    /// Checks `template` before it is saved as `name`.
+    pub fn validate(name: &str, template: &str) -> Result<(), PromptError> {
+        if !is_valid_prompt_name(name) {
+            return Err(PromptError::InvalidName(name.to_string()));
+        }
+        let invalid = |reason: &str| PromptError::InvalidTemplate {
+            name: name.to_string(),
+            reason: reason.to_string(),
+        };
+        if template.trim().is_empty() {
+            return Err(invalid("template must not be empty"));
+        }
+        if template.len() > PROMPT_MAX_LEN {
+            return Err(invalid(&format!("template is longer than {} bytes", PROMPT_MAX_LEN)));
+        }
+        let Some(builtin) = Self::builtin(name) else {
+            return Ok(());
+        };
+        let provided = builtin.variables();
+        let candidate = Self {
+            name: name.to_string(),
+            template: template.to_string(),
+            description: String::new(),
+            version: 0,
+            updated_at_ms: 0,
+        };
+        let unknown: Vec<String> = candidate
+            .variables()
+            .into_iter()
+            .filter(|v| !provided.contains(v))
+            .collect();
+        if unknown.is_empty() {
+            Ok(())
+        } else {
+            Err(PromptError::UnknownVariables {
+                name: name.to_string(),
+                variables: unknown,
+            })
+        }
+    }
+}
+
+/// Renders the prompt `name`: the version stored in `store` when there is one, else the built-in.
+/// A stored version that cannot be rendered is logged and the built-in is used instead.
+pub fn render_prompt(store: Option<&KnowledgeStore>, name: &str, vars: &[(&str, &str)]) -> Result<String, PromptError> {
+    let builtin = PromptTemplate::builtin(name);
+    let stored = store.and_then(|s| s.get_prompt(name)).filter(|p| p.version > 0);
+    match (stored, builtin) {
+        (Some(stored), Some(builtin)) => stored.render(vars).or_else(|e| {
+            tracing::warn!(target: "pagi::prompts", prompt = name, error = %e, "Stored prompt failed to render; using the built-in");
+            builtin.render(vars)
+        }),
+        (Some(template), None) | (None, Some(template)) => template.render(vars),
+        (None, None) => Err(PromptError::NotFound(name.to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn template(text: &str) -> PromptTemplate {
+        PromptTemplate {
+            name: "t".to_string(),
+            template: text.to_string(),
+            description: String::new(),
+            version: 1,
+            updated_at_ms: 0,
+        }
+    }
+
+    #[test]
+    fn render_fills_variables_once_and_reports_missing_ones() {
+        let t = template("Hi {{ name }}, {{name}}! {{ not a var }} {{x}}");
+        assert_eq!(t.variables(), vec!["name", "x"]);
+        assert_eq!(
+            t.render(&[("name", "{{x}}"), ("x", "1")]).unwrap(),
+            "Hi {{x}}, {{x}}! {{ not a var }} 1"
+        );
+        let err = t.render(&[]).unwrap_err();
+        assert_eq!(
+            err,
+            PromptError::MissingVariables {
+                name: "t".to_string(),
+                variables: vec!["name".to_string(), "x".to_string()],
+            }
+        );
+        assert_eq!(template("open {{ only").render(&[]).unwrap(), "open {{ only");
+    }
+
+    #[test]
+    fn builtin_overrides_may_only_use_provided_variables() {
+        let name = HEARTBEAT_BACKGROUND_TASK_PROMPT;
+        assert!(PromptTemplate::validate(name, "Task {{task}} for {{agent_id}}").is_ok());
+        assert!(matches!(
+            PromptTemplate::validate(name, "{{task}} {{mood}}"),
+            Err(PromptError::UnknownVariables { variables, .. }) if variables == vec!["mood".to_string()]
+        ));
+        assert!(PromptTemplate::validate("custom", "{{anything}}").is_ok());
+        assert!(matches!(PromptTemplate::validate("a/b", "x"), Err(PromptError::InvalidName(_))));
+        assert!(matches!(PromptTemplate::validate("custom", " "), Err(PromptError::InvalidTemplate { .. })));
+        for builtin in PromptTemplate::builtins() {
+            assert!(PromptTemplate::validate(&builtin.name, &builtin.template).is_ok(), "{}", builtin.name);
+        }
+    }
+}
@@ -0,0 +1,236 @@
+//! PII redaction: masks emails, phone numbers, SSNs and API-key-like strings before text is
+//! stored in KB_CHRONOS (episodic events, conversation turns) or sent to a live LLM provider.
+//!
+//! A [`Redactor`] is built from `[redaction]` ([`RedactionConfig`], off by default) and attached
+//! to the knowledge store (`KnowledgeStore::with_redaction`) and to the ModelRouter. Each match is
+//! replaced by `[REDACTED:<kind>]`. A [`RedactionReport`] counts what was masked, and every
+//! non-empty report is written to the audit log as a `Redaction` record
+//! (`KnowledgeStore::record_redaction`); the masked values themselves are never kept.
+//!
+//! The patterns only catch well-formed values. For names, postal addresses and the like, the
+//! ModelRouter can also ask a classifier model (`classifier_model`) to list PII in a prompt;
+//! those terms are masked as `classified`. The classifier runs after the patterns, but still sees
+//! everything they missed, so point it at a local provider (Ollama, llama.cpp).
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// A kind of personal or secret data the redactor masks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PiiKind {
+    Email,
+    Phone,
+    /// US social security number (`123-45-6789`).
+    Ssn,
+    /// Provider keys (`sk-…`, `AKIA…`, `ghp_…`, `xox…-`), bearer tokens, `api_key=…` assignments.
+    ApiKey,
+    /// A term the LLM classifier flagged.
+    Classified,
+}
+
+impl PiiKind {
+    /// The kinds matched by patterns (everything but `Classified`).
+    pub const PATTERN_KINDS: [PiiKind; 4] = [PiiKind::Email, PiiKind::Phone, PiiKind::Ssn, PiiKind::ApiKey];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            PiiKind::Email => "email",
+            PiiKind::Phone => "phone",
+            PiiKind::Ssn => "ssn",
+            PiiKind::ApiKey => "api_key",
+            PiiKind::Classified => "classified",
+        }
+    }
+
+    /// Text that replaces a masked value.
+    pub fn mask(self) -> String {
+        format!("[REDACTED:{}]", self.as_str())
+    }
+
+    /// Pattern for the kind (None for `Classified`).
+    fn pattern(self) -> Option<&'static str> {
+        match self {
+            PiiKind::Email => Some(r"(?i)\b[A-Z0-9._%+-]+@[A-Z0-9.-]+\.[A-Z]{2,}\b"),
+            // Separated groups (`555-123-4567`, `(555) 123 4567`, `+1 555.123.4567`) or E.164;
+            // a bare run of ten digits is more often an id or a timestamp than a phone number.
+            PiiKind::Phone => Some(r"(?:\+\d{1,3}[\s.-]?)?(?:\(\d{3}\)\s?|\b\d{3}[\s.-])\d{3}[\s.-]\d{4}\b|\+\d{8,15}\b"),
+            PiiKind::Ssn => Some(r"\b\d{3}-\d{2}-\d{4}\b"),
+            PiiKind::ApiKey => Some(concat!(
+                r"(?i)\b(?:api[_-]?key|secret|token|password|passwd)\s*[:=]\s*[\x22']?[^\s\x22',;]{6,}",
+                r"|\bBearer\s+[A-Za-z0-9._~+/-]{16,}=*",
+                r"|\b(?:sk|pk|rk)[-_](?:live[-_]|test[-_]|proj[-_])?[A-Za-z0-9]{16,}\b",
+                r"|\bAKIA[0-9A-Z]{16}\b",
+                r"|\bgh[pousr]_[A-Za-z0-9]{36,}\b",
+                r"|\bxox[abprs]-[A-Za-z0-9-]{10,}",
+            )),
+            PiiKind::Classified => None,
+        }
+    }
+}
+
+/// `[redaction]`: PII masking before KB_CHRONOS storage and live LLM calls.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RedactionConfig {
+    /// Env `PAGI__REDACTION__ENABLED`.
+    pub enabled: bool,
+    /// Kinds to mask (default: every pattern kind).
+    pub kinds: Vec<PiiKind>,
+    /// Model (alias or name) the ModelRouter asks to list PII the patterns miss. It sees what the
+    /// patterns left unmasked, so use a local provider. None = patterns only.
+    pub classifier_model: Option<String>,
+}
+
+impl Default for RedactionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            kinds: PiiKind::PATTERN_KINDS.to_vec(),
+            classifier_model: None,
+        }
+    }
+}
+
+/// What one redaction masked: matches per kind.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct RedactionReport(pub BTreeMap<PiiKind, usize>);
+
+impl RedactionReport {
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Total masked values.
+    pub fn total(&self) -> usize {
+        self.0.values().sum()
+    }
+
+    pub fn add(&mut self, kind: PiiKind, count: usize) {
+        if count > 0 {
+            *self.0.entry(kind).or_default() += count;
+        }
+    }
+
+    pub fn merge(&mut self, other: &RedactionReport) {
+        for (kind, count) in &other.0 {
+            self.add(*kind, *count);
+        }
+    }
+
+    /// `email×2, phone×1`, for logs and audit summaries.
+    pub fn summary(&self) -> String {
+        self.0
+            .iter()
+            .map(|(kind, count)| format!("{}×{}", kind.as_str(), count))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// Compiled patterns for the configured kinds.
+#[derive(Debug, Clone)]
+pub struct Redactor {
+    patterns: Vec<(PiiKind, Regex)>,
+    classifier_model: Option<String>,
+}
+
+impl Redactor {
+    /// Every pattern kind, no classifier.
+    pub fn new() -> Self {
+        Self::with_kinds(&PiiKind::PATTERN_KINDS)
+    }
+
+    pub fn with_kinds(kinds: &[PiiKind]) -> Self {
+        // SSNs first, so their digits are not taken for a phone number.
+        let mut kinds = kinds.to_vec();
+        kinds.sort_by_key(|k| (*k != PiiKind::Ssn, *k));
+        kinds.dedup();
+        let patterns = kinds
+            .into_iter()
+            .filter_map(|kind| Some((kind, Regex::new(kind.pattern()?).expect("built-in PII pattern"))))
+            .collect();
+        Self {
+            patterns,
+            classifier_model: None,
+        }
+    }
+
+    /// The redactor `config` asks for; None when redaction is off.
+    pub fn from_config(config: &RedactionConfig) -> Option<Self> {
+        if !config.enabled {
+            return None;
+        }
+        Some(Self {
+            classifier_model: config.classifier_model.clone().filter(|m| !m.trim().is_empty()),
+            ..Self::with_kinds(&config.kinds)
+        })
+    }
+
+    /// Classifier model for free-form PII, if configured.
+    pub fn classifier_model(&self) -> Option<&str> {
+        self.classifier_model.as_deref()
+    }
+
+    /// `text` with every match masked.
+    pub fn redact_text(&self, text: &str) -> (String, RedactionReport) {
+        let mut report = RedactionReport::default();
+        let mut out = text.to_string();
+        for (kind, pattern) in &self.patterns {
+            let count = pattern.find_iter(&out).count();
+            if count > 0 {
+                out = pattern.replace_all(&out, kind.mask().as_str()).into_owned();
+                report.add(*kind, count);
+            }
+        }
+        (out, report)
+    }
+
+    /// Masks the strings of `value` in place (object keys are left alone).
+    pub fn redact_value(&self, value: &mut serde_json::Value) -> RedactionReport {
+        let mut report = RedactionReport::default();
+        self.redact_value_into(value, &mut report);
+        report
+    }
+
+    fn redact_value_into(&self, value: &mut serde_json::Value, report: &mut RedactionReport) {
+        match value {
+            serde_json::Value::String(s) => {
+                let (masked, found) = self.redact_text(s);
+                if !found.is_empty() {
+                    *s = masked;
+                    report.merge(&found);
+                }
+            }
+            serde_json::Value::Array(items) => items.iter_mut().for_each(|v| self.redact_value_into(v, report)),
+            serde_json::Value::Object(map) => map.values_mut().for_each(|v| self.redact_value_into(v, report)),
+            _ => {}
+        }
+    }
+
+    /// Masks each of `terms` (e.g. from the classifier) as `classified`. Terms shorter than three
+    /// characters are ignored, so a stray letter does not blank the text.
+    pub fn mask_terms(&self, text: &str, terms: &[String]) -> (String, RedactionReport) {
+        let mut report = RedactionReport::default();
+        let mut out = text.to_string();
+        let mut terms: Vec<&str> = terms.iter().map(|t| t.trim()).filter(|t| t.chars().count() >= 3).collect();
+        // Longest first, so a term inside a longer one does not split it.
+        terms.sort_by_key(|t| std::cmp::Reverse(t.len()));
+        for term in terms {
+            let count = out.matches(term).count();
+            if count > 0 {
+                out = out.replace(term, &PiiKind::Classified.mask());
+                report.add(PiiKind::Classified, count);
+            }
+        }
+        (out, report)
+    }
+}
+
+impl Default for Redactor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
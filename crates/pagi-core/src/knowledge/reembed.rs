@@ -0,0 +1,142 @@
+//! Re-embedding jobs: regenerate the stored vectors of KB records after the embedding model
+//! changes, since vectors from different models cannot be compared.
+//!
+//! A job walks its slots in key order and saves its cursor (slot + last key done) after every
+//! batch in a dedicated sled tree, so an interrupted job resumes where it stopped. The runner
+//! lives in pagi-skills (`Reembedder`), which owns the embedding provider; this module holds the
+//! job record and the store methods live in `store.rs`.
+
+use serde::{Deserialize, Serialize};
+
+/// Sled tree holding re-embedding jobs (key = job id).
+pub const REEMBED_JOBS_TREE: &str = "reembed_jobs";
+/// Metadata key naming the model that produced a record's `embedding`.
+pub const EMBEDDING_MODEL_METADATA_KEY: &str = "embedding_model";
+
+/// Lifecycle of a re-embedding job.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReembedStatus {
+    Running,
+    /// The process stopped while the job was running; it can be resumed.
+    Interrupted,
+    Completed,
+    /// Stopped on a store error; it can be resumed.
+    Failed,
+}
+
+/// Position of a job: records of `slot_id` up to and including `key` are done.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReembedCursor {
+    pub slot_id: u8,
+    pub key: String,
+}
+
+/// A re-embedding job and its progress.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReembedJob {
+    pub id: String,
+    /// Slots to walk, in order.
+    pub slots: Vec<u8>,
+    /// Model override passed to the provider (None = its configured default).
+    #[serde(default)]
+    pub model: Option<String>,
+    /// Model name recorded in the metadata of re-embedded records.
+    pub embedding_model: String,
+    /// Embedding requests in flight at once.
+    pub concurrency: usize,
+    /// Re-embed records already produced by `embedding_model` too.
+    #[serde(default)]
+    pub force: bool,
+    pub status: ReembedStatus,
+    pub created_at_ms: i64,
+    pub updated_at_ms: i64,
+    /// Records with an embedding in the job's slots when it started.
+    pub total: usize,
+    /// Records looked at so far (`updated + skipped + failed`).
+    pub processed: usize,
+    pub updated: usize,
+    /// Already embedded by `embedding_model`.
+    pub skipped: usize,
+    /// Records whose embedding request failed (left unchanged).
+    pub failed: usize,
+    #[serde(default)]
+    pub cursor: Option<ReembedCursor>,
+    /// Last error seen (embedding failure or the store error that stopped the job).
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+impl ReembedJob {
+    pub fn new(
+        slots: Vec<u8>,
+        model: Option<String>,
+        embedding_model: String,
+        concurrency: usize,
+        force: bool,
+        now_ms: i64,
+    ) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().simple().to_string(),
+            slots,
+            model,
+            embedding_model,
+            concurrency: concurrency.max(1),
+            force,
+            status: ReembedStatus::Running,
+            created_at_ms: now_ms,
+            updated_at_ms: now_ms,
+            total: 0,
+            processed: 0,
+            updated: 0,
+            skipped: 0,
+            failed: 0,
+            cursor: None,
+            error: None,
+        }
+    }
+
+    /// True once the job can no longer be resumed.
+    pub fn is_finished(&self) -> bool {
+        self.status == ReembedStatus::Completed
+    }
+
+    /// Key after which `slot_id` continues: the cursor key when the cursor is in that slot,
+    /// otherwise None (start of the slot).
+    pub fn resume_after(&self, slot_id: u8) -> Option<&str> {
+        self.cursor
+            .as_ref()
+            .filter(|c| c.slot_id == slot_id)
+            .map(|c| c.key.as_str())
+    }
+
+    /// Slots still to walk: the cursor's slot and those after it.
+    pub fn remaining_slots(&self) -> Vec<u8> {
+        let start = self
+            .cursor
+            .as_ref()
+            .and_then(|c| self.slots.iter().position(|s| *s == c.slot_id))
+            .unwrap_or(0);
+        self.slots[start..].to_vec()
+    }
+
+    /// Share of `total` processed, 0.0–1.0 (an empty job is 1.0 once completed).
+    pub fn progress(&self) -> f64 {
+        if self.total == 0 {
+            return if self.is_finished() { 1.0 } else { 0.0 };
+        }
+        (self.processed as f64 / self.total as f64).min(1.0)
+    }
+
+    pub fn touch(&mut self, now_ms: i64) {
+        self.updated_at_ms = now_ms;
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        serde_json::to_vec(self).unwrap_or_default()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        serde_json::from_slice(bytes).ok()
+    }
+}
@@ -0,0 +1,357 @@
+//! Point-in-time snapshots of the knowledge store, for rolling back after a bad autonomous run.
+//!
+//! A snapshot is a timestamped directory under `{storage_path}/snapshots/{id}/` holding one
+//! gzip-compressed file per sled tree (every tree: the 9 slots, indexes, audit log, jobs) and a
+//! `manifest.json`; `snapshots/LATEST` holds the newest snapshot's creation time, so taking one does
+//! not list the directory. [`SnapshotManager`] takes snapshots on demand or on a schedule, prunes them by
+//! a [`SnapshotRetention`] policy, and restores either one slot or the whole store. Every restore
+//! first snapshots the current state (reason `pre_restore`), so a rollback can itself be undone. Archive
+//! segment files are not copied. This module holds the manager and file I/O; the tree export and
+//! import live in `store.rs`.
+
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::Arc;
+
+use super::store::KnowledgeStore;
+
+/// Directory (next to the sled DB) that holds snapshots.
+pub const SNAPSHOT_DIR_NAME: &str = "snapshots";
+const MANIFEST_FILE: &str = "manifest.json";
+/// File in the snapshot directory holding the newest snapshot's `created_at_ms`.
+const LATEST_FILE: &str = "LATEST";
+
+type SnapshotError = Box<dyn std::error::Error + Send + Sync>;
+/// Key/value pairs of one tree.
+type TreeEntries = Vec<(Vec<u8>, Vec<u8>)>;
+
+/// Why a snapshot was taken.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SnapshotReason {
+    Scheduled,
+    Manual,
+    /// Taken automatically right before a restore.
+    PreRestore,
+}
+
+/// One tree inside a snapshot.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotTree {
+    pub name: String,
+    /// File of the tree relative to the snapshot directory.
+    pub file: String,
+    pub entries: usize,
+}
+
+/// Manifest of one snapshot (`manifest.json`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotInfo {
+    pub id: String,
+    pub created_at_ms: i64,
+    pub reason: SnapshotReason,
+    pub trees: Vec<SnapshotTree>,
+    /// Total size of the snapshot's files.
+    pub bytes: u64,
+}
+
+impl SnapshotInfo {
+    /// Records across the snapshot's trees.
+    pub fn entries(&self) -> usize {
+        self.trees.iter().map(|t| t.entries).sum()
+    }
+}
+
+/// Result of a restore.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RestoreReport {
+    pub snapshot_id: String,
+    /// Slot restored, or None for the whole store.
+    pub slot_id: Option<u8>,
+    /// Snapshot of the state just before the restore.
+    pub pre_restore_snapshot_id: String,
+    pub trees: usize,
+    pub entries: usize,
+}
+
+/// Which snapshots to keep. The newest `keep_last` are always kept; older ones go once they pass
+/// `max_age_days` (or immediately when it is None).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotRetention {
+    pub keep_last: usize,
+    pub max_age_days: Option<u64>,
+}
+
+impl Default for SnapshotRetention {
+    fn default() -> Self {
+        Self { keep_last: 7, max_age_days: None }
+    }
+}
+
+/// Writes `entries` as a gzip file of length-prefixed key/value pairs and fsyncs it.
+pub(crate) fn write_tree_file(
+    path: &Path,
+    entries: impl Iterator<Item = Result<(Vec<u8>, Vec<u8>), sled::Error>>,
+) -> Result<usize, SnapshotError> {
+    let file = std::fs::File::create(path)?;
+    let mut encoder = GzEncoder::new(BufWriter::new(file), Compression::fast());
+    let mut count = 0;
+    for item in entries {
+        let (k, v) = item?;
+        for part in [&k, &v] {
+            encoder.write_all(&(part.len() as u32).to_le_bytes())?;
+            encoder.write_all(part)?;
+        }
+        count += 1;
+    }
+    encoder.finish()?.into_inner().map_err(|e| e.into_error())?.sync_all()?;
+    Ok(count)
+}
+
+/// Reads every key/value pair of a tree file.
+pub(crate) fn read_tree_file(path: &Path) -> Result<TreeEntries, SnapshotError> {
+    let mut reader = BufReader::new(GzDecoder::new(std::fs::File::open(path)?));
+    let read_part = |reader: &mut BufReader<GzDecoder<std::fs::File>>| -> std::io::Result<Option<Vec<u8>>> {
+        let mut len = [0u8; 4];
+        match reader.read_exact(&mut len) {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+            Err(e) => return Err(e),
+        }
+        let mut buf = vec![0u8; u32::from_le_bytes(len) as usize];
+        reader.read_exact(&mut buf)?;
+        Ok(Some(buf))
+    };
+    let mut out = Vec::new();
+    while let Some(key) = read_part(&mut reader)? {
+        let value = read_part(&mut reader)?.ok_or("truncated snapshot file")?;
+        out.push((key, value));
+    }
+    Ok(out)
+}
+
+/// Manifest of the snapshot directory `dir`, if it is one.
+pub(crate) fn read_manifest(dir: &Path) -> Option<SnapshotInfo> {
+    std::fs::read(dir.join(MANIFEST_FILE)).ok().and_then(|b| serde_json::from_slice(&b).ok())
+}
+
+/// Snapshots under `dir`, newest first. Directories without a readable manifest (e.g. a
+/// snapshot interrupted mid-write) are skipped.
+pub(crate) fn list_snapshots(dir: &Path) -> Result<Vec<SnapshotInfo>, SnapshotError> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+    let mut out = Vec::new();
+    for entry in entries {
+        if let Some(info) = read_manifest(&entry?.path()) {
+            out.push(info);
+        }
+    }
+    out.sort_by(|a, b| b.id.cmp(&a.id));
+    Ok(out)
+}
+
+/// Takes, lists, prunes and restores snapshots of one [`KnowledgeStore`].
+pub struct SnapshotManager {
+    store: Arc<KnowledgeStore>,
+    dir: PathBuf,
+    retention: SnapshotRetention,
+    /// Scheduled snapshot interval; None disables [`run_due`](Self::run_due).
+    interval_ms: Option<i64>,
+    /// Creation time of the newest snapshot (0 = not yet read from [`LATEST_FILE`]).
+    last_snapshot_ms: AtomicI64,
+}
+
+impl SnapshotManager {
+    pub fn new(store: Arc<KnowledgeStore>, dir: impl Into<PathBuf>) -> Self {
+        Self {
+            store,
+            dir: dir.into(),
+            retention: SnapshotRetention::default(),
+            interval_ms: None,
+            last_snapshot_ms: AtomicI64::new(0),
+        }
+    }
+
+    pub fn with_retention(mut self, retention: SnapshotRetention) -> Self {
+        self.retention = retention;
+        self
+    }
+
+    /// Takes a scheduled snapshot whenever the newest one is older than `interval`.
+    pub fn with_interval(mut self, interval: std::time::Duration) -> Self {
+        self.interval_ms = Some(interval.as_millis() as i64);
+        self
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn retention(&self) -> SnapshotRetention {
+        self.retention
+    }
+
+    /// Snapshots every tree into a new directory, then applies the retention policy.
+    pub fn create(&self, reason: SnapshotReason) -> Result<SnapshotInfo, SnapshotError> {
+        // Ids sort by creation time, so two snapshots in the same millisecond get distinct ones.
+        self.load_latest();
+        let now = self.store.now_ms();
+        let newest_ms = self
+            .last_snapshot_ms
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |last| Some(now.max(last + 1)))
+            .unwrap_or(now);
+        let created_at_ms = now.max(newest_ms + 1);
+        let id = format!("{:013}-{}", created_at_ms, &uuid::Uuid::new_v4().simple().to_string()[..8]);
+        let dir = self.dir.join(&id);
+        std::fs::create_dir_all(&dir)?;
+        let trees = match self.store.export_trees(&dir) {
+            Ok(trees) => trees,
+            Err(e) => {
+                let _ = std::fs::remove_dir_all(&dir);
+                return Err(e);
+            }
+        };
+        let bytes = trees
+            .iter()
+            .filter_map(|t| std::fs::metadata(dir.join(&t.file)).ok())
+            .map(|m| m.len())
+            .sum();
+        let info = SnapshotInfo { id, created_at_ms, reason, trees, bytes };
+        let manifest = dir.join(MANIFEST_FILE);
+        std::fs::write(&manifest, serde_json::to_vec_pretty(&info)?)?;
+        std::fs::File::open(&manifest)?.sync_all()?;
+        std::fs::write(self.dir.join(LATEST_FILE), created_at_ms.to_string())?;
+        tracing::info!(
+            target: "pagi::knowledge",
+            snapshot = %info.id,
+            reason = ?reason,
+            trees = info.trees.len(),
+            entries = info.entries(),
+            bytes = info.bytes,
+            "Knowledge snapshot taken"
+        );
+        if let Err(e) = self.prune() {
+            tracing::warn!(target: "pagi::knowledge", error = %e, "Snapshot pruning failed");
+        }
+        Ok(info)
+    }
+
+    /// Folds the newest `created_at_ms` recorded in [`LATEST_FILE`] (possibly by another
+    /// process) into `last_snapshot_ms`.
+    fn load_latest(&self) {
+        let latest = std::fs::read_to_string(self.dir.join(LATEST_FILE))
+            .ok()
+            .and_then(|s| s.trim().parse::<i64>().ok())
+            .unwrap_or(-1);
+        self.last_snapshot_ms.fetch_max(latest, Ordering::SeqCst);
+    }
+
+    /// All snapshots, newest first. Directories without a readable manifest (e.g. a snapshot
+    /// interrupted mid-write) are skipped.
+    pub fn list(&self) -> Result<Vec<SnapshotInfo>, SnapshotError> {
+        list_snapshots(&self.dir)
+    }
+
+    pub fn get(&self, id: &str) -> Result<Option<SnapshotInfo>, SnapshotError> {
+        Ok(self.list()?.into_iter().find(|s| s.id == id))
+    }
+
+    /// Deletes a snapshot. Returns false when it does not exist.
+    pub fn delete(&self, id: &str) -> Result<bool, SnapshotError> {
+        if self.get(id)?.is_none() {
+            return Ok(false);
+        }
+        std::fs::remove_dir_all(self.dir.join(id))?;
+        Ok(true)
+    }
+
+    /// Deletes snapshots the retention policy no longer keeps. Returns their ids.
+    pub fn prune(&self) -> Result<Vec<String>, SnapshotError> {
+        let cutoff = self
+            .retention
+            .max_age_days
+            .map(|days| self.store.now_ms().saturating_sub((days as i64).saturating_mul(24 * 60 * 60 * 1000)));
+        let mut pruned = Vec::new();
+        for info in self.list()?.into_iter().skip(self.retention.keep_last) {
+            if cutoff.is_none_or(|cutoff| info.created_at_ms < cutoff) {
+                std::fs::remove_dir_all(self.dir.join(&info.id))?;
+                pruned.push(info.id);
+            }
+        }
+        if !pruned.is_empty() {
+            tracing::info!(target: "pagi::knowledge", pruned = pruned.len(), "Old knowledge snapshots pruned");
+        }
+        Ok(pruned)
+    }
+
+    /// Takes a scheduled snapshot when one is due. Returns it, or None when not due (or no
+    /// interval is set).
+    pub fn run_due(&self) -> Result<Option<SnapshotInfo>, SnapshotError> {
+        let Some(interval_ms) = self.interval_ms else {
+            return Ok(None);
+        };
+        if self.last_snapshot_ms.load(Ordering::SeqCst) == 0 {
+            self.load_latest();
+        }
+        if self.store.now_ms() - self.last_snapshot_ms.load(Ordering::SeqCst) < interval_ms {
+            return Ok(None);
+        }
+        self.create(SnapshotReason::Scheduled).map(Some)
+    }
+
+    /// Rolls `slot_id` (1–9), or the whole store when None, back to snapshot `id`. The current
+    /// state is snapshotted first. A whole-store restore replaces every tree, including indexes
+    /// and job/audit trees; a slot restore rebuilds that slot's search indexes.
+    pub fn restore(&self, id: &str, slot_id: Option<u8>) -> Result<RestoreReport, SnapshotError> {
+        let info = self.get(id)?.ok_or_else(|| format!("no snapshot {}", id))?;
+        if let Some(slot) = slot_id {
+            if !(1..=9).contains(&slot) {
+                return Err("slot_id must be 1–9".into());
+            }
+        }
+        let pre = self.create(SnapshotReason::PreRestore)?;
+        let (trees, entries) = self.store.import_trees(&self.dir.join(&info.id), &info, slot_id)?;
+        tracing::warn!(
+            target: "pagi::knowledge",
+            snapshot = %info.id,
+            slot_id = ?slot_id,
+            pre_restore = %pre.id,
+            trees,
+            entries,
+            "Knowledge restored from snapshot"
+        );
+        Ok(RestoreReport {
+            snapshot_id: info.id,
+            slot_id,
+            pre_restore_snapshot_id: pre.id,
+            trees,
+            entries,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tree_file_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("t.kv.gz");
+        let entries = vec![
+            Ok((b"a".to_vec(), b"{\"x\":1}".to_vec())),
+            Ok((Vec::new(), vec![0u8, 255])),
+        ];
+        assert_eq!(write_tree_file(&path, entries.into_iter()).unwrap(), 2);
+        let read = read_tree_file(&path).unwrap();
+        assert_eq!(read, vec![(b"a".to_vec(), b"{\"x\":1}".to_vec()), (Vec::new(), vec![0, 255])]);
+    }
+}
@@ -15,14 +15,40 @@
 //! | 8    | Soma   | Execution: Physical interface, side effects, buffer  | Standard (Sled)|
 //! | 9    | Shadow | The Vault: Trauma, anchors, private journaling      | **AES-256-GCM**|
 
+use crate::keys::{self, agent_persona_key, agent_persona_profile_key, auto_reply_template_key, DEFAULT_TENANT_ID};
 use crate::shared::{
-    BiometricState, EthosPolicy, GovernedTask, MentalState, PersonRecord, SomaState,
-    KARDIA_PEOPLE_PREFIX, MENTAL_STATE_KEY,
+    BiometricState, EthosPolicy, GovernedTask, MentalState, PersonRecord, SomaState, MENTAL_STATE_KEY,
 };
-use super::vault::{EmotionalAnchor, SecretVault, VaultError};
+use super::archive::{self, ArchiveReport, ArchiveSegment, ArchivedEntry, ARCHIVE_DIR_NAME};
+use super::compression::{self, SlotStorageStats, DEFAULT_COMPRESSION_THRESHOLD};
+use super::tenant::TenantHandle;
+use super::transaction::{abort_transaction, KbMultiTransaction, KbTransaction, KbTxResult, TxWrites};
+use super::changes::{ChangeFeed, KbChange, KbChangeKind};
+use super::agents::{is_valid_agent_id, AgentHealth, AgentRecord, AgentScheduleUpdate, AgentStatus, AgentTickTracker};
+use super::persona::PersonaProfile;
+use super::topics::{is_valid_topic_name, TopicSubscription, TOPIC_FANOUT_BATCH};
+use super::calendar::{CalendarBooking, CALENDAR_BOOKING_MAX_LIMIT};
+use super::email_log::{EmailDelivery, EMAIL_LOG_MAX_LIMIT};
+use super::evals::{EvalRun, EvalRunSummary, EVAL_RUNS_MAX_LIMIT};
+use super::jobs::{GoalJob, JOB_RETENTION_MS};
+use super::prompts::{render_prompt, PromptTemplate, SYSTEM_PERSONA_PROMPT};
+use super::policy_rules::{ActiveEthosPolicy, PolicyRules};
+use super::approvals::{ApprovalCheckpoint, ApprovalRecord, ApprovalStatus};
+use super::auth::{hash_api_key, ApiKeyRecord, Role, API_KEY_SECRET_PREFIX};
+use super::audit::{audit_key, AuditOutcome, AuditPage, AuditQuery, AuditRecord, AUDIT_TREE, REDACTION_GOAL_TYPE};
+use super::redaction::{RedactionReport, Redactor};
+use super::text_index::{self, IndexedDoc, SlotStats, TextSearchHit, TEXT_INDEX_TREE};
+use super::vector_index::{self, Hnsw, SemanticHit, VECTOR_INDEX_TREE};
+use super::snapshot::{self, SnapshotInfo, SnapshotTree, SNAPSHOT_DIR_NAME};
+use super::reembed::{ReembedJob, ReembedStatus, EMBEDDING_MODEL_METADATA_KEY, REEMBED_JOBS_TREE};
+use super::vault::{self, EmotionalAnchor, SecretVault, VaultError};
 use serde::{Deserialize, Serialize};
-use sled::Db;
-use std::path::Path;
+use crate::storage::{
+    prefix_upper_bound, KvBackend, KvBatch, ReadOnlyBackend, SledBackend, SqliteBackend, StorageBackend, SQLITE_FILE_NAME,
+};
+use crate::clock::{Clock, SystemClock};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use uuid::Uuid;
 
 const DEFAULT_PATH: &str = "./data/pagi_knowledge";
@@ -40,6 +66,27 @@ const TREE_NAMES: [&str; 9] = [
     "kb9_shadow",
 ];
 
+/// Largest page [`KnowledgeStore::scan_prefix`] and friends return, whatever `limit` asks for.
+pub const SCAN_MAX_LIMIT: usize = 10_000;
+
+/// Largest value [`KnowledgeStore::insert`] accepts by default (4 MiB); see
+/// [`KnowledgeStore::with_max_value_bytes`].
+pub const DEFAULT_MAX_VALUE_BYTES: usize = 4 * 1024 * 1024;
+
+/// Refuses a `len`-byte value for `key` over `limit`, with a message naming both.
+pub(crate) fn check_value_size(slot_id: u8, key: &str, len: usize, limit: usize) -> Result<(), String> {
+    if len > limit {
+        return Err(format!(
+            "KB value too large: KB-{} key '{}' is {} bytes, the limit is {} bytes",
+            slot_id, key, len, limit
+        ));
+    }
+    Ok(())
+}
+
+/// Sled tree holding the archive index: `idx/{slot}/{key}` → segment id, `seg/{id}` → [`ArchiveSegment`].
+const ARCHIVE_INDEX_TREE: &str = "archive_index";
+
 /// Human-readable names for the 9 knowledge base slots (Holistic Ontology + Shadow Vault).
 pub const SLOT_LABELS: [&str; 9] = [
     "Pneuma (Vision)",      // KB-1: Identity, mission, evolving playbook
@@ -185,6 +232,43 @@ pub struct SkillRecord {
     pub schema: serde_json::Value,
 }
 
+impl SkillRecord {
+    /// `schema` as JSON Schema. Records may already hold one (`"type": "object"`); otherwise each
+    /// field maps to a hint like `"string (required; ...)"` whose first word is the type.
+    pub fn input_schema(&self) -> serde_json::Value {
+        if self.schema.get("type").and_then(|t| t.as_str()) == Some("object") {
+            return self.schema.clone();
+        }
+        let Some(fields) = self.schema.as_object() else {
+            return serde_json::json!({ "type": "object" });
+        };
+        let mut properties = serde_json::Map::new();
+        let mut required = Vec::new();
+        for (field, hint) in fields {
+            let property = match hint.as_str() {
+                Some(hint) => {
+                    let kind = hint.split_whitespace().next().unwrap_or_default();
+                    let kind = match kind {
+                        "string" | "number" | "integer" | "boolean" | "array" | "object" => kind,
+                        _ => "string",
+                    };
+                    if hint.contains("(required") {
+                        required.push(serde_json::json!(field));
+                    }
+                    serde_json::json!({ "type": kind, "description": hint })
+                }
+                None => hint.clone(),
+            };
+            properties.insert(field.clone(), property);
+        }
+        let mut out = serde_json::json!({ "type": "object", "properties": properties });
+        if !required.is_empty() {
+            out["required"] = serde_json::Value::Array(required);
+        }
+        out
+    }
+}
+
 /// Episodic memory event for **KB_CHRONOS** (the Historian).
 ///
 /// Every successful skill execution or significant update can create a timestamped
@@ -206,12 +290,9 @@ pub struct EventRecord {
 }
 
 impl EventRecord {
-    /// Creates an event with the current timestamp.
-    pub fn now(source_kb: impl Into<String>, reflection: impl Into<String>) -> Self {
-        let timestamp_ms = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .map(|d| d.as_millis() as i64)
-            .unwrap_or(0);
+    /// Creates an event at `timestamp_ms`; [`KnowledgeStore::event`] stamps it with the store's
+    /// clock.
+    pub fn at(timestamp_ms: i64, source_kb: impl Into<String>, reflection: impl Into<String>) -> Self {
         Self {
             timestamp_ms,
             source_kb: source_kb.into(),
@@ -330,15 +411,11 @@ pub enum AlignmentResult {
 /// Key for relation records in **KB_KARDIA**. Full key: `relation/{owner_agent_id}/{target_id}`.
 /// In multi-agent mode, each agent has its own view of relations (to users and other agents).
 pub fn kardia_relation_key(owner_agent_id: &str, target_id: &str) -> String {
-    let owner = if owner_agent_id.is_empty() {
-        "default"
-    } else {
-        owner_agent_id
-    };
-    format!("relation/{}/{}", owner, target_id)
+    keys::relation_key(owner_agent_id, target_id)
 }
 
-/// Inter-agent message stored in **KB_SOMA** inbox (`inbox/{target_agent_id}/{key}`).
+/// Inter-agent message stored in **KB_SOMA** inbox (`inbox/{target_agent_id}/{key}`), or in a
+/// topic's log (`topic/{topic}/{key}`, with an empty `target_agent_id`).
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AgentMessage {
     pub id: String,
@@ -352,15 +429,431 @@ pub struct AgentMessage {
     /// Defaults to false for backwards compatibility with older records.
     #[serde(default)]
     pub is_processed: bool,
+    /// Topic the message was published to (absent for direct messages).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub topic: Option<String>,
+    /// Failed Heartbeat attempts at handling the message (see [`KnowledgeStore::record_inbox_failure`]).
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub attempts: u32,
+    /// The Heartbeat leaves the message alone until then (retry backoff).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_attempt_ms: Option<i64>,
+    /// Why the latest attempt failed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+}
+
+fn is_zero(n: &u32) -> bool {
+    *n == 0
 }
 
 impl AgentMessage {
+    /// A new, unprocessed message from `from_agent_id` to `target_agent_id`, stamped
+    /// `timestamp_ms`; [`KnowledgeStore::message`] stamps it with the store's clock.
+    pub fn new(from_agent_id: &str, target_agent_id: &str, payload: &serde_json::Value, timestamp_ms: i64) -> Self {
+        Self {
+            id: Uuid::new_v4().simple().to_string(),
+            from_agent_id: from_agent_id.to_string(),
+            target_agent_id: target_agent_id.to_string(),
+            payload: payload.clone(),
+            timestamp_ms,
+            is_processed: false,
+            topic: None,
+            attempts: 0,
+            next_attempt_ms: None,
+            last_error: None,
+        }
+    }
+
+    /// Whether the Heartbeat should handle the message at `now_ms`: unprocessed and not waiting
+    /// out a retry backoff.
+    pub fn is_ready(&self, now_ms: i64) -> bool {
+        !self.is_processed && self.next_attempt_ms.is_none_or(|t| t <= now_ms)
+    }
+
+    /// Key of the message in the target's **KB_SOMA** inbox.
+    pub fn inbox_key(&self) -> String {
+        keys::inbox_key(&self.target_agent_id, self.timestamp_ms, &self.id)
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        serde_json::to_vec(self).unwrap_or_default()
+    }
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        serde_json::from_slice(bytes).ok()
+    }
+
+    /// Human-readable body: a string payload, or the `text` / `message` / `content` field of an
+    /// object payload; anything else is rendered as compact JSON.
+    pub fn text(&self) -> String {
+        match &self.payload {
+            serde_json::Value::String(s) => s.clone(),
+            serde_json::Value::Object(o) => ["text", "message", "content"]
+                .iter()
+                .find_map(|f| o.get(*f).and_then(|v| v.as_str()))
+                .map(str::to_string)
+                .unwrap_or_else(|| self.payload.to_string()),
+            other => other.to_string(),
+        }
+    }
+}
+
+/// Default frame for Heartbeat auto-replies, used when an agent has no
+/// `pneuma/{agent_id}/auto_reply_template` of its own.
+///
+/// Placeholders: `{agent_id}`, `{sender}`, `{message}` and `{thread}` (recent exchange with the sender).
+pub const DEFAULT_AUTO_REPLY_TEMPLATE: &str = "You are agent {agent_id}, replying to a message from agent {sender}.\n\n\
+Recent conversation with {sender}:\n{thread}\n\n\
+New message from {sender}:\n{message}\n\n\
+Reply in your own voice, staying consistent with your identity and your relationship with {sender}.";
+
+/// How many earlier messages between two agents are rendered into `{thread}`.
+pub const AUTO_REPLY_THREAD_LIMIT: usize = 10;
+
+/// Failed attempts after which [`KnowledgeStore::record_inbox_failure`] dead-letters a message.
+pub const INBOX_MAX_ATTEMPTS: u32 = 5;
+
+/// Backoff after the first failed attempt; it doubles with each further one.
+pub const INBOX_RETRY_BASE_MS: i64 = 30_000;
+
+/// Longest backoff between two attempts.
+pub const INBOX_RETRY_MAX_MS: i64 = 3_600_000;
+
+/// What [`KnowledgeStore::record_inbox_failure`] did with the message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InboxRetry {
+    /// Left in the inbox for another attempt at `next_attempt_ms`.
+    Scheduled { attempts: u32, next_attempt_ms: i64 },
+    /// Moved to `inbox_dlq/{agent_id}/...` after [`INBOX_MAX_ATTEMPTS`] failures.
+    DeadLettered { attempts: u32 },
+}
+
+/// Latest inbox messages [`KnowledgeStore::agent_health`] counts pending ones among (the batch the
+/// Heartbeat looks at).
+const AGENT_HEALTH_INBOX_WINDOW: usize = 25;
+
+/// Single-pass `{name}` substitution, so placeholder-like text inside values is left alone.
+/// Unknown placeholders are kept verbatim.
+fn render_template(template: &str, vars: &[(&str, &str)]) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        out.push_str(&rest[..open]);
+        let tail = &rest[open..];
+        let value = tail.find('}').and_then(|close| {
+            let name = &tail[1..close];
+            vars.iter().find(|(k, _)| *k == name).map(|(_, v)| (*v, close))
+        });
+        match value {
+            Some((v, close)) => {
+                out.push_str(v);
+                rest = &tail[close + 1..];
+            }
+            None => {
+                out.push('{');
+                rest = &tail[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Prompt pair for a Heartbeat auto-reply: the same Mission Directive chat uses, plus the
+/// rendered per-agent template.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AutoReplyPrompt {
+    pub system_prompt: String,
+    pub prompt: String,
+}
+
+/// Key prefix for standing queries in **KB_SOMA** (`standing_query/{id}`).
+pub const STANDING_QUERY_PREFIX: &str = "standing_query/";
+
+/// What a [`StandingQuery`] watches within its slot.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum QuerySelector {
+    /// A single key (e.g. `current_pulse`).
+    Key { key: String },
+    /// Every key starting with the prefix (e.g. `research/competitorX`).
+    Prefix { prefix: String },
+}
+
+/// Where change alerts for a [`StandingQuery`] are delivered.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AlertTarget {
+    /// Inter-agent message in the agent's KB_SOMA inbox.
+    Inbox { agent_id: String },
+    /// JSON POST to an external URL.
+    Webhook { url: String },
+}
+
+/// Saved KnowledgeQuery subscription stored in **KB_SOMA** under `standing_query/{id}`.
+///
+/// The Heartbeat re-evaluates enabled queries and emits an alert when the result
+/// fingerprint changes. The first evaluation only records a baseline.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StandingQuery {
+    pub id: String,
+    pub name: String,
+    /// Slot to watch (1–8; the encrypted Shadow slot cannot be watched).
+    pub slot_id: u8,
+    pub selector: QuerySelector,
+    pub notify: AlertTarget,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    pub created_at_ms: i64,
+    /// Fingerprint of the last evaluated result; `None` until the baseline is taken.
+    #[serde(default)]
+    pub last_fingerprint: Option<String>,
+    #[serde(default)]
+    pub last_checked_ms: Option<i64>,
+    #[serde(default)]
+    pub last_changed_ms: Option<i64>,
+}
+
+impl StandingQuery {
+    pub fn new(
+        name: impl Into<String>,
+        slot_id: u8,
+        selector: QuerySelector,
+        notify: AlertTarget,
+        created_at_ms: i64,
+    ) -> Self {
+        Self {
+            id: Uuid::new_v4().simple().to_string(),
+            name: name.into(),
+            slot_id,
+            selector,
+            notify,
+            enabled: true,
+            created_at_ms,
+            last_fingerprint: None,
+            last_checked_ms: None,
+            last_changed_ms: None,
+        }
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        serde_json::to_vec(self).unwrap_or_default()
+    }
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        serde_json::from_slice(bytes).ok()
+    }
+}
+
+/// A standing query whose results changed since the previous evaluation.
+#[derive(Debug, Clone, Serialize)]
+pub struct StandingQueryChange {
+    /// The query after this evaluation (new fingerprint already applied).
+    pub query: StandingQuery,
+    pub previous_fingerprint: Option<String>,
+    /// Current matching entries (`key -> value`), capped at [`STANDING_QUERY_SNAPSHOT_LIMIT`].
+    pub snapshot: serde_json::Value,
+    pub match_count: usize,
+}
+
+/// Entries matched by a standing query (`key`, raw value), sorted by key.
+pub type StandingQueryMatches = Vec<(String, Vec<u8>)>;
+
+/// Archived key/value pairs of a slot (`key`, raw value).
+pub type ArchivedKv = Vec<(String, Vec<u8>)>;
+
+/// Max entries included in a change snapshot (the fingerprint always covers all matches).
+pub const STANDING_QUERY_SNAPSHOT_LIMIT: usize = 50;
+
+/// FNV-1a: small, dependency-free and stable across builds (fingerprints are persisted).
+fn fnv1a_64(chunks: &[(String, Vec<u8>)]) -> String {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for (k, v) in chunks {
+        for b in k.as_bytes().iter().chain([0u8].iter()).chain(v.iter()).chain([0xffu8].iter()) {
+            hash ^= *b as u64;
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+    }
+    format!("{:016x}", hash)
+}
+
+/// How long an in-progress idempotency claim blocks retries before it is presumed abandoned
+/// (the process handling it died); the key can then be claimed again.
+pub const IDEMPOTENCY_CLAIM_TIMEOUT_MS: i64 = 15 * 60 * 1000;
+
+/// One page of a [`KnowledgeStore::scan_prefix`], [`KnowledgeStore::scan_prefix_rev`] or
+/// [`KnowledgeStore::scan_range`] scan, in scan order.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ScanPage {
+    pub entries: Vec<(String, Vec<u8>)>,
+    /// Pass as `cursor` to continue after the last entry; None when the scan is complete.
+    pub next_cursor: Option<String>,
+}
+
+/// A [`KnowledgeStore::compare_and_swap`] lost: the key no longer held the expected value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CasConflict {
+    pub slot_id: u8,
+    pub key: String,
+    /// What the key holds now (None: absent).
+    pub current: Option<Vec<u8>>,
+}
+
+impl std::fmt::Display for CasConflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "KB-{} key {:?} changed since it was read", self.slot_id, self.key)
+    }
+}
+
+impl std::error::Error for CasConflict {}
+
+/// Attempts [`KnowledgeStore::update`] makes before giving up on a contended key.
+pub const CAS_MAX_RETRIES: usize = 32;
+
+/// A request outcome cached under a client idempotency key in **KB_SOMA**
+/// (`idempotency/{tenant_id}/{key}`), so a retried request returns it instead of running again.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct IdempotencyRecord {
+    pub tenant_id: String,
+    pub key: String,
+    /// [`IdempotencyRecord::fingerprint`] of the request that claimed the key.
+    pub request_fingerprint: String,
+    /// The cached response; `None` while the first request is still running.
+    #[serde(default)]
+    pub response: Option<serde_json::Value>,
+    pub created_at_ms: i64,
+    pub expires_at_ms: i64,
+}
+
+impl IdempotencyRecord {
+    /// Stable fingerprint of a request body; reusing a key for a different request is rejected.
+    pub fn fingerprint(request: &serde_json::Value) -> String {
+        fnv1a_64(&[(String::new(), request.to_string().into_bytes())])
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        serde_json::to_vec(self).unwrap_or_default()
+    }
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        serde_json::from_slice(bytes).ok()
+    }
+
+    fn is_expired(&self, now_ms: i64) -> bool {
+        now_ms >= self.expires_at_ms
+            || (self.response.is_none() && now_ms - self.created_at_ms >= IDEMPOTENCY_CLAIM_TIMEOUT_MS)
+    }
+}
+
+/// Outcome of [`KnowledgeStore::claim_idempotency_key`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum IdempotencyClaim {
+    /// The key was free: run the request, then `complete_idempotency_key` (or release it).
+    Claimed,
+    /// Another request with this key is still running.
+    InProgress,
+    /// The request already ran; this is its response.
+    Completed(serde_json::Value),
+    /// The key was used for a different request.
+    Mismatch,
+}
+
+/// `YYYY-MM` (UTC) of `timestamp_ms`: the month token usage is counted and budgeted in.
+pub fn usage_month(timestamp_ms: i64) -> String {
+    let (year, month, _) = crate::orchestrator::civil_from_days(timestamp_ms.div_euclid(86_400_000));
+    format!("{:04}-{:02}", year, month)
+}
+
+/// LLM token counts of one agent within a [`TokenUsageRecord`].
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct AgentTokenUsage {
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub total_tokens: u64,
+    pub requests: u64,
+}
+
+/// LLM token usage of one tenant in one month, in **KB_SOMA** (`usage/{tenant_id}/{YYYY-MM}`).
+/// Written by the ModelRouter after each live generation and checked against the tenant's
+/// monthly budget.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TokenUsageRecord {
+    pub tenant_id: String,
+    /// `YYYY-MM` (UTC).
+    pub month: String,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub total_tokens: u64,
+    pub requests: u64,
+    /// Part of `total_tokens` estimated from text length (providers that report no usage).
+    #[serde(default)]
+    pub estimated_tokens: u64,
+    /// The same counts per agent id.
+    #[serde(default)]
+    pub by_agent: std::collections::BTreeMap<String, AgentTokenUsage>,
+    pub updated_at_ms: i64,
+}
+
+impl TokenUsageRecord {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        serde_json::to_vec(self).unwrap_or_default()
+    }
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        serde_json::from_slice(bytes).ok()
+    }
+}
+
+/// A live generation refused because the tenant has used its monthly token budget.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenBudgetExceeded {
+    pub tenant_id: String,
+    /// `YYYY-MM` (UTC); the budget resets when the month changes.
+    pub month: String,
+    pub used_tokens: u64,
+    pub budget_tokens: u64,
+}
+
+impl std::fmt::Display for TokenBudgetExceeded {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "monthly token budget exceeded for tenant {:?}: {} of {} tokens used in {}",
+            self.tenant_id, self.used_tokens, self.budget_tokens, self.month
+        )
+    }
+}
+
+impl std::error::Error for TokenBudgetExceeded {}
+
+/// A cached LLM reply in **KB_SOMA** (`llm_cache/{hash}`), so an identical generation within the
+/// TTL is answered without calling the provider.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct LlmCacheEntry {
+    pub model: String,
+    pub response: String,
+    pub created_at_ms: i64,
+    pub expires_at_ms: i64,
+}
+
+impl LlmCacheEntry {
+    /// Cache hash of a generation: the model plus every input that shapes the reply (prompts,
+    /// sampling parameters), in order.
+    pub fn cache_hash(model: &str, parts: &[&str]) -> String {
+        let chunks: Vec<(String, Vec<u8>)> = std::iter::once(model)
+            .chain(parts.iter().copied())
+            .map(|part| (String::new(), part.as_bytes().to_vec()))
+            .collect();
+        fnv1a_64(&chunks)
+    }
+
     pub fn to_bytes(&self) -> Vec<u8> {
         serde_json::to_vec(self).unwrap_or_default()
     }
     pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
         serde_json::from_slice(bytes).ok()
     }
+
+    fn is_expired(&self, now_ms: i64) -> bool {
+        now_ms >= self.expires_at_ms
+    }
 }
 
 /// Relationship/social record for **KB_KARDIA** (the Heart).
@@ -390,14 +883,9 @@ fn default_trust() -> f32 {
 }
 
 impl RelationRecord {
-    pub fn new(user_id: impl Into<String>) -> Self {
-        let user_id = user_id.into();
-        let last_updated_ms = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .map(|d| d.as_millis() as i64)
-            .unwrap_or(0);
+    pub fn new(user_id: impl Into<String>, last_updated_ms: i64) -> Self {
         Self {
-            user_id: user_id.clone(),
+            user_id: user_id.into(),
             trust_score: 0.5,
             communication_style: String::new(),
             last_sentiment: String::new(),
@@ -417,10 +905,6 @@ impl RelationRecord {
 
     pub fn with_sentiment(mut self, sentiment: impl Into<String>) -> Self {
         self.last_sentiment = sentiment.into();
-        self.last_updated_ms = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .map(|d| d.as_millis() as i64)
-            .unwrap_or(0);
         self
     }
 
@@ -449,31 +933,26 @@ impl RelationRecord {
 }
 
 impl KbRecord {
-    /// Creates a new KbRecord with the given content.
-    pub fn new(content: impl Into<String>) -> Self {
+    /// Creates a new KbRecord with the given content, stamped `timestamp_ms` (usually
+    /// [`KnowledgeStore::now_ms`]).
+    pub fn new(content: impl Into<String>, timestamp_ms: i64) -> Self {
         Self {
             id: Uuid::new_v4(),
             content: content.into(),
             metadata: serde_json::json!({}),
             embedding: None,
-            timestamp: std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .map(|d| d.as_millis() as i64)
-                .unwrap_or(0),
+            timestamp: timestamp_ms,
         }
     }
 
     /// Creates a new KbRecord with content and metadata.
-    pub fn with_metadata(content: impl Into<String>, metadata: serde_json::Value) -> Self {
+    pub fn with_metadata(content: impl Into<String>, metadata: serde_json::Value, timestamp_ms: i64) -> Self {
         Self {
             id: Uuid::new_v4(),
             content: content.into(),
             metadata,
             embedding: None,
-            timestamp: std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .map(|d| d.as_millis() as i64)
-                .unwrap_or(0),
+            timestamp: timestamp_ms,
         }
     }
 
@@ -482,16 +961,14 @@ impl KbRecord {
         content: impl Into<String>,
         metadata: serde_json::Value,
         embedding: Vec<f32>,
+        timestamp_ms: i64,
     ) -> Self {
         Self {
             id: Uuid::new_v4(),
             content: content.into(),
             metadata,
             embedding: Some(embedding),
-            timestamp: std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .map(|d| d.as_millis() as i64)
-                .unwrap_or(0),
+            timestamp: timestamp_ms,
         }
     }
 
@@ -523,9 +1000,29 @@ pub fn pagi_kb_slot_label(slot_id: u8) -> &'static str {
 /// via AES-256-GCM using the `SecretVault`. If no master key is provided, Slot 9
 /// remains locked and all operations on it return errors.
 pub struct KnowledgeStore {
-    db: Db,
+    /// Storage engine (see `storage/`); sled unless the config picks another.
+    db: Box<dyn KvBackend>,
     /// The Secret Vault for Slot 9 (Shadow_KB). Initialized from `PAGI_SHADOW_KEY` env var.
     vault: SecretVault,
+    /// Segment directory for the archive tier (defaults to `archive/` next to the DB directory).
+    archive_dir: PathBuf,
+    /// Serializes full-text and vector index updates.
+    index_lock: std::sync::Mutex<()>,
+    /// Serializes topic publishes (see [`KnowledgeStore::next_topic_timestamp_ms`]).
+    publish_lock: std::sync::Mutex<()>,
+    /// Insert/remove events for subscribers (see `changes.rs`).
+    changes: ChangeFeed,
+    /// Values of slots 1–8 this size and larger are stored compressed (see `compression.rs`).
+    compression_threshold: usize,
+    /// Writes of larger values are refused (before compression or encryption).
+    max_value_bytes: usize,
+    /// Slots 1–8 whose values are sealed with the vault key (see `vault.rs`).
+    encrypted_slots: Vec<u8>,
+    /// Masks PII in KB_CHRONOS events and conversation turns before they are written (see
+    /// `redaction.rs`); None = stored as given.
+    redactor: Option<Redactor>,
+    /// Time source for event and record timestamps (see `clock.rs`).
+    clock: Arc<dyn Clock>,
 }
 
 impl KnowledgeStore {
@@ -538,17 +1035,233 @@ impl KnowledgeStore {
     /// Opens or creates the knowledge DB at the given path.
     /// The Shadow Vault is initialized from the `PAGI_SHADOW_KEY` environment variable.
     pub fn open_path<P: AsRef<Path>>(path: P) -> Result<Self, sled::Error> {
-        let db = sled::open(path)?;
-        let vault = SecretVault::from_env();
-        Ok(Self { db, vault })
+        Self::open_with_backend(path, StorageBackend::Sled)
+    }
+
+    /// Opens or creates the knowledge DB at the given path on `backend` (`CoreConfig::storage_backend`).
+    /// The Shadow Vault is initialized from the `PAGI_SHADOW_KEY` environment variable.
+    pub fn open_with_backend<P: AsRef<Path>>(path: P, backend: StorageBackend) -> Result<Self, sled::Error> {
+        let db = backend.open(&path)?;
+        Ok(Self::from_backend(db, SecretVault::from_env(), Self::default_archive_dir(path.as_ref())))
     }
 
     /// Opens or creates the knowledge DB with an explicit master key for the Shadow Vault.
     /// Pass `None` to create a store with a locked vault.
     pub fn open_with_key<P: AsRef<Path>>(path: P, master_key: Option<&[u8; 32]>) -> Result<Self, sled::Error> {
-        let db = sled::open(path)?;
-        let vault = SecretVault::new(master_key);
-        Ok(Self { db, vault })
+        let db = StorageBackend::Sled.open(&path)?;
+        Ok(Self::from_backend(db, SecretVault::new(master_key), Self::default_archive_dir(path.as_ref())))
+    }
+
+    /// Opens a temporary in-memory store (removed when dropped) with a locked Shadow Vault.
+    /// Used by blueprint scenarios; archiving writes to a fresh directory under the OS temp dir.
+    pub fn open_temporary() -> Result<Self, sled::Error> {
+        let archive_dir = std::env::temp_dir().join(format!("pagi_temp_{}", Uuid::new_v4())).join(ARCHIVE_DIR_NAME);
+        Ok(Self::from_backend(Box::new(SledBackend::temporary()?), SecretVault::new(None), archive_dir))
+    }
+
+    /// Opens the knowledge DB at `path` for reading while another process (e.g. the gateway)
+    /// holds it for writing. On SQLite (`pagi.sqlite3` in `path`) this is a read-only
+    /// connection to the live file. sled allows one process per directory, so a sled store is
+    /// read from its newest snapshot in `{storage_path}/snapshots`, loaded into memory; `path`
+    /// may also be a snapshot directory itself. Every write returns an error. On SQLite, a slot
+    /// whose full-text index the writer has not built yet has no text search hits.
+    /// The Shadow Vault is initialized from the `PAGI_SHADOW_KEY` environment variable.
+    pub fn open_read_only<P: AsRef<Path>>(path: P) -> Result<Self, sled::Error> {
+        let path = path.as_ref();
+        let db: Box<dyn KvBackend> = if path.join(SQLITE_FILE_NAME).exists() {
+            Box::new(SqliteBackend::open_read_only(path)?)
+        } else {
+            let db = Self::load_latest_snapshot(path)?;
+            // Search indexes are built on first use; build the missing ones while the copy is writable.
+            let staging = Self::from_backend(Box::new(db.clone()), SecretVault::new(None), Self::default_archive_dir(path));
+            staging.build_missing_indexes()?;
+            Box::new(db)
+        };
+        Ok(Self::from_backend(
+            Box::new(ReadOnlyBackend::new(db)),
+            SecretVault::from_env(),
+            Self::default_archive_dir(path),
+        ))
+    }
+
+    /// Builds the full-text and vector indexes of the slots that have none yet.
+    fn build_missing_indexes(&self) -> Result<(), sled::Error> {
+        let text = self.db.open_tree(TEXT_INDEX_TREE)?;
+        let vectors = self.db.open_tree(VECTOR_INDEX_TREE)?;
+        for slot_id in 1..=8 {
+            if !text.contains_key(text_index::built_key(slot_id).as_bytes())? {
+                self.rebuild_text_index(slot_id)?;
+            }
+            if !vectors.contains_key(Hnsw::new(&*vectors, slot_id).built_key().as_bytes())? {
+                self.rebuild_vector_index(slot_id)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// In-memory copy of the snapshot at `path`, or of the newest snapshot of the DB at `path`.
+    fn load_latest_snapshot(path: &Path) -> Result<SledBackend, sled::Error> {
+        let snapshot_err = |e: Box<dyn std::error::Error + Send + Sync>| {
+            sled::Error::Unsupported(format!("cannot read snapshot of {}: {}", path.display(), e))
+        };
+        let (dir, info) = match snapshot::read_manifest(path) {
+            Some(info) => (path.to_path_buf(), info),
+            None => {
+                let snapshots = path.parent().unwrap_or_else(|| Path::new(".")).join(SNAPSHOT_DIR_NAME);
+                let newest = snapshot::list_snapshots(&snapshots).map_err(snapshot_err)?.into_iter().next();
+                let info = newest.ok_or_else(|| {
+                    sled::Error::Unsupported(format!(
+                        "no snapshot of {} in {}; take one (POST /api/v1/knowledge/snapshots) or use storage_backend = \"sqlite\"",
+                        path.display(),
+                        snapshots.display()
+                    ))
+                })?;
+                (snapshots.join(&info.id), info)
+            }
+        };
+        let db = SledBackend::temporary()?;
+        for saved in &info.trees {
+            let mut batch = KvBatch::default();
+            for (k, v) in snapshot::read_tree_file(&dir.join(&saved.file)).map_err(snapshot_err)? {
+                batch.insert(k, v);
+            }
+            db.open_tree(&saved.name)?.apply_batch(batch)?;
+        }
+        tracing::info!(
+            target: "pagi::knowledge",
+            path = %path.display(),
+            snapshot = %info.id,
+            entries = info.entries(),
+            "Knowledge store opened read-only from snapshot"
+        );
+        Ok(db)
+    }
+
+    fn from_backend(db: Box<dyn KvBackend>, vault: SecretVault, archive_dir: PathBuf) -> Self {
+        Self {
+            db,
+            vault,
+            archive_dir,
+            index_lock: Default::default(),
+            publish_lock: Default::default(),
+            changes: Default::default(),
+            compression_threshold: DEFAULT_COMPRESSION_THRESHOLD,
+            max_value_bytes: DEFAULT_MAX_VALUE_BYTES,
+            encrypted_slots: Vec::new(),
+            redactor: None,
+            clock: Arc::new(SystemClock),
+        }
+    }
+
+    /// Name of the storage engine: `"sled"` or `"sqlite"`.
+    pub fn backend_name(&self) -> &'static str {
+        self.db.name()
+    }
+
+    /// Whether the store was opened with [`open_read_only`](Self::open_read_only).
+    pub fn is_read_only(&self) -> bool {
+        self.db.is_read_only()
+    }
+
+    /// `{storage_path}/archive` for a DB at `{storage_path}/pagi_knowledge`.
+    fn default_archive_dir(db_path: &Path) -> PathBuf {
+        db_path
+            .parent()
+            .unwrap_or_else(|| Path::new("."))
+            .join(ARCHIVE_DIR_NAME)
+    }
+
+    /// Overrides the size (bytes) from which values are stored compressed; `usize::MAX` turns
+    /// compression off. Existing entries keep their encoding until rewritten.
+    pub fn with_compression_threshold(mut self, bytes: usize) -> Self {
+        self.compression_threshold = bytes;
+        self
+    }
+
+    /// Overrides the largest value (bytes) a write may store ([`DEFAULT_MAX_VALUE_BYTES`]).
+    pub fn with_max_value_bytes(mut self, bytes: usize) -> Self {
+        self.max_value_bytes = bytes;
+        self
+    }
+
+    pub fn max_value_bytes(&self) -> usize {
+        self.max_value_bytes
+    }
+
+    /// Encrypts the values of `slots` (1–8; others are ignored) at rest with the vault key, as
+    /// Slot 9 is. Plaintext values already stored are sealed as they are read or rewritten; until
+    /// then they read back as they are. Writes fail while the vault is locked.
+    pub fn with_encrypted_slots(mut self, slots: &[u8]) -> Self {
+        for &slot_id in slots {
+            if !(1..=8).contains(&slot_id) {
+                tracing::warn!(target: "pagi::vault", kb_slot = slot_id, "Ignoring [encryption] slot outside 1–8");
+            } else if !self.encrypted_slots.contains(&slot_id) {
+                self.encrypted_slots.push(slot_id);
+            }
+        }
+        if !self.encrypted_slots.is_empty() && !self.vault.is_unlocked() {
+            tracing::warn!(
+                target: "pagi::vault",
+                slots = ?self.encrypted_slots,
+                "Encrypted slots cannot be written: the vault is locked (no valid PAGI_SHADOW_KEY)"
+            );
+        }
+        self
+    }
+
+    /// Whether values of `slot_id` are encrypted at rest (Slot 9, and slots named in
+    /// [`with_encrypted_slots`](Self::with_encrypted_slots)).
+    pub fn is_slot_encrypted(&self, slot_id: u8) -> bool {
+        slot_id == SHADOW_SLOT_ID || self.encrypted_slots.contains(&slot_id)
+    }
+
+    /// Masks PII with `redactor` in everything later written to KB_CHRONOS through
+    /// [`append_chronos_event`](Self::append_chronos_event) and conversation turns.
+    pub fn with_redaction(mut self, redactor: Redactor) -> Self {
+        self.redactor = Some(redactor);
+        self
+    }
+
+    /// The redactor attached with [`with_redaction`](Self::with_redaction), if any.
+    pub fn redactor(&self) -> Option<&Redactor> {
+        self.redactor.as_ref()
+    }
+
+    /// Reads the time from `clock` (e.g. a [`FrozenClock`](crate::FrozenClock) in simulation mode)
+    /// for the timestamps the store writes.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    pub fn clock(&self) -> &Arc<dyn Clock> {
+        &self.clock
+    }
+
+    /// Milliseconds since the Unix epoch by the store's clock.
+    pub fn now_ms(&self) -> i64 {
+        self.clock.now_ms()
+    }
+
+    /// An event stamped with the store's clock (see [`EventRecord::at`]).
+    pub fn event(&self, source_kb: impl Into<String>, reflection: impl Into<String>) -> EventRecord {
+        EventRecord::at(self.now_ms(), source_kb, reflection)
+    }
+
+    /// A message stamped with the store's clock (see [`AgentMessage::new`]).
+    pub fn message(&self, from_agent_id: &str, target_agent_id: &str, payload: &serde_json::Value) -> AgentMessage {
+        AgentMessage::new(from_agent_id, target_agent_id, payload, self.now_ms())
+    }
+
+    /// Overrides the archive segment directory.
+    pub fn with_archive_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.archive_dir = dir.into();
+        self
+    }
+
+    /// Directory holding archive segment files.
+    pub fn archive_dir(&self) -> &Path {
+        &self.archive_dir
     }
 
     /// Returns a reference to the Shadow Vault for direct vault operations.
@@ -573,10 +1286,24 @@ impl KnowledgeStore {
     ///
     /// **Slot 9 (Shadow):** Returns the raw encrypted bytes. Use `get_shadow_anchor()`
     /// or `get_shadow_decrypted()` for automatic decryption.
+    ///
+    /// **Encrypted slots 1–8:** A plaintext value (written before encryption was turned on) is
+    /// sealed in place as it is read.
     pub fn get(&self, slot_id: u8, key: &str) -> Result<Option<Vec<u8>>, sled::Error> {
         let tree = self.db.open_tree(Self::tree_name(slot_id))?;
-        let v = tree.get(key.as_bytes())?;
-        Ok(v.map(|iv| iv.to_vec()))
+        let Some(stored) = tree.get(key.as_bytes())? else {
+            return Ok(None);
+        };
+        let value = self.decode_value(slot_id, &stored)?;
+        if self.encrypted_slots.contains(&slot_id) && !vault::is_sealed(&stored) && self.vault.is_unlocked() && !self.is_read_only() {
+            // Only swapped if nobody wrote the key meanwhile; the content is unchanged, so no
+            // change event.
+            let sealed = self.encode_value(slot_id, &value)?;
+            if tree.compare_and_swap(key.as_bytes(), Some(&stored), Some(sealed.as_ref()))?.is_ok() {
+                tracing::debug!(target: "pagi::vault", kb_slot = slot_id, key = key, "Plaintext value encrypted on read");
+            }
+        }
+        Ok(Some(value))
     }
 
     /// Inserts `value` at `key` in the tree for `slot_id` (1–9).
@@ -585,6 +1312,12 @@ impl KnowledgeStore {
     /// If the Shadow Vault is locked, returns an error. Use `insert_shadow_anchor()` for
     /// typed anchor storage.
     ///
+    /// **Slots 1–8:** Values at or above the compression threshold are stored compressed; reads
+    /// return the original bytes. Values of encrypted slots are then sealed with the vault key
+    /// (refused while it is locked).
+    ///
+    /// Values over [`max_value_bytes`](Self::max_value_bytes) are refused.
+    ///
     /// Logs the write operation to the tracing system.
     pub fn insert(
         &self,
@@ -592,6 +1325,15 @@ impl KnowledgeStore {
         key: &str,
         value: &[u8],
     ) -> Result<Option<Vec<u8>>, sled::Error> {
+        // Structured keyspaces (inbox/, event/, relation/, ...) must match their format in their slot.
+        if let Err(e) = keys::validate_key(slot_id, key) {
+            tracing::warn!(target: "pagi::knowledge", kb_slot = slot_id, key = key, "KB write REJECTED — {}", e);
+            return Err(sled::Error::Unsupported(e.to_string()));
+        }
+        if let Err(e) = check_value_size(slot_id, key, value.len(), self.max_value_bytes) {
+            tracing::warn!(target: "pagi::knowledge", kb_slot = slot_id, key = key, bytes = value.len(), "KB write REJECTED — {}", e);
+            return Err(sled::Error::Unsupported(e));
+        }
         // Slot 9 (Shadow): auto-encrypt before writing
         let effective_value: std::borrow::Cow<'_, [u8]> = if slot_id == SHADOW_SLOT_ID {
             match self.vault.encrypt_blob(value) {
@@ -617,12 +1359,21 @@ impl KnowledgeStore {
                 }
             }
         } else {
-            std::borrow::Cow::Borrowed(value)
+            match self.encode_value(slot_id, value) {
+                Ok(encoded) => encoded,
+                Err(e) => {
+                    tracing::warn!(target: "pagi::vault", kb_slot = slot_id, key = key, "KB write REJECTED — {}", e);
+                    return Err(e);
+                }
+            }
         };
 
         let tree_name = Self::tree_name(slot_id);
         let tree = self.db.open_tree(tree_name)?;
         let prev = tree.insert(key.as_bytes(), effective_value.as_ref())?;
+        self.update_indexes(slot_id, key, Some(value));
+        let kind = if prev.is_some() { KbChangeKind::Update } else { KbChangeKind::Insert };
+        self.changes.publish(slot_id, key, kind, self.now_ms());
         
         // Log KB write for observability (never log Shadow content)
         let kb_label = pagi_kb_slot_label(slot_id);
@@ -657,7 +1408,7 @@ impl KnowledgeStore {
             );
         }
         
-        Ok(prev.map(|iv| iv.to_vec()))
+        prev.map(|iv| self.decode_value(slot_id, &iv)).transpose()
     }
 
     /// Inserts a KbRecord at the specified key in the tree for `slot_id` (1–8).
@@ -680,10 +1431,15 @@ impl KnowledgeStore {
     /// Removes the key in the tree for `slot_id` (1–8). Returns the previous value if present.
     /// Logs the removal operation to the tracing system.
     pub fn remove(&self, slot_id: u8, key: &str) -> Result<Option<Vec<u8>>, sled::Error> {
+        if self.encrypted_slots.contains(&slot_id) && !self.vault.is_unlocked() {
+            return Err(Self::vault_error(slot_id, VaultError::Locked));
+        }
         let tree = self.db.open_tree(Self::tree_name(slot_id))?;
         let prev = tree.remove(key.as_bytes())?;
         
         if prev.is_some() {
+            self.update_indexes(slot_id, key, None);
+            self.changes.publish(slot_id, key, KbChangeKind::Remove, self.now_ms());
             let kb_label = pagi_kb_slot_label(slot_id);
             tracing::info!(
                 target: "pagi::knowledge",
@@ -698,22 +1454,177 @@ impl KnowledgeStore {
             );
         }
         
-        Ok(prev.map(|iv| iv.to_vec()))
+        prev.map(|iv| self.decode_value(slot_id, &iv)).transpose()
     }
 
-    /// Returns all keys in the tree for `slot_id` (1–8). Order is not guaranteed.
-    pub fn scan_keys(&self, slot_id: u8) -> Result<Vec<String>, sled::Error> {
-        let tree = self.db.open_tree(Self::tree_name(slot_id))?;
-        let keys: Vec<String> = tree
-            .iter()
-            .keys()
-            .filter_map(|k| k.ok())
-            .filter_map(|k| String::from_utf8(k.to_vec()).ok())
-            .collect();
-        Ok(keys)
+    /// `tenant_id`'s view of the store (see `tenant.rs`); an empty id is the default tenant.
+    /// Errors when the id is not a valid tenant id.
+    pub fn tenant<'a>(&'a self, tenant_id: &'a str) -> Result<TenantHandle<'a>, sled::Error> {
+        TenantHandle::new(self, tenant_id)
     }
 
-    /// Returns all key/value pairs in the tree for `slot_id` (1–8).
+    /// Change events for `slot_id`, published after each insert, removal, archive move and
+    /// snapshot restore in that slot. The receiver is closed for a slot outside 1–9.
+    pub fn subscribe(&self, slot_id: u8) -> tokio::sync::broadcast::Receiver<KbChange> {
+        self.changes.subscribe(slot_id)
+    }
+
+    /// Change events for every slot.
+    pub fn subscribe_all(&self) -> tokio::sync::broadcast::Receiver<KbChange> {
+        self.changes.subscribe_all()
+    }
+
+    /// Writes `new` at `key` in `slot_id` (1–8) only if the key still holds `expected` (None:
+    /// absent); `new` = None removes it. Values compare as read through [`get`](Self::get), so
+    /// compression does not matter. On a lost race the caller gets a [`CasConflict`] with the
+    /// current value to re-apply its change to; see [`update`](Self::update) for the retry loop.
+    /// `new` is held to the same size limit as [`insert`](Self::insert).
+    pub fn compare_and_swap(
+        &self,
+        slot_id: u8,
+        key: &str,
+        expected: Option<&[u8]>,
+        new: Option<&[u8]>,
+    ) -> Result<Result<(), CasConflict>, sled::Error> {
+        if !(1..=8).contains(&slot_id) {
+            return Err(sled::Error::Unsupported(format!("compare_and_swap is not supported on KB-{}", slot_id)));
+        }
+        if let Err(e) = keys::validate_key(slot_id, key) {
+            return Err(sled::Error::Unsupported(e.to_string()));
+        }
+        if let Some(value) = new {
+            if let Err(e) = check_value_size(slot_id, key, value.len(), self.max_value_bytes) {
+                tracing::warn!(target: "pagi::knowledge", kb_slot = slot_id, key = key, bytes = value.len(), "KB write REJECTED — {}", e);
+                return Err(sled::Error::Unsupported(e));
+            }
+        }
+        let tree = self.db.open_tree(Self::tree_name(slot_id))?;
+        let stored = tree.get(key.as_bytes())?;
+        let current = stored.as_ref().map(|v| self.decode_value(slot_id, v)).transpose()?;
+        let conflict = |current: Option<Vec<u8>>| CasConflict { slot_id, key: key.to_string(), current };
+        if current.as_deref() != expected {
+            return Ok(Err(conflict(current)));
+        }
+        let encoded = new.map(|v| self.encode_value(slot_id, v)).transpose()?;
+        if let Err(actual) = tree.compare_and_swap(key.as_bytes(), stored.as_deref(), encoded.as_deref())? {
+            return Ok(Err(conflict(actual.map(|v| self.decode_value(slot_id, &v)).transpose()?)));
+        }
+        if current.is_none() && new.is_none() {
+            return Ok(Ok(()));
+        }
+        self.update_indexes(slot_id, key, new);
+        let kind = match (current.is_some(), new.is_some()) {
+            (_, false) => KbChangeKind::Remove,
+            (true, true) => KbChangeKind::Update,
+            (false, true) => KbChangeKind::Insert,
+        };
+        self.changes.publish(slot_id, key, kind, self.now_ms());
+        tracing::debug!(target: "pagi::knowledge", kb_slot = slot_id, key = key, action = ?kind, "KB-{} compare-and-swap on key '{}'", slot_id, key);
+        Ok(Ok(()))
+    }
+
+    /// Read-modify-write of `key` in `slot_id` (1–8) without lost updates: `f` gets the current
+    /// value (None: absent) and returns the new one (None: remove). When another writer got there
+    /// first, `f` runs again on its value, up to [`CAS_MAX_RETRIES`] times. Returns the value
+    /// written.
+    pub fn update<F>(&self, slot_id: u8, key: &str, mut f: F) -> Result<Option<Vec<u8>>, sled::Error>
+    where
+        F: FnMut(Option<&[u8]>) -> Option<Vec<u8>>,
+    {
+        let mut current = self.get(slot_id, key)?;
+        for _ in 0..CAS_MAX_RETRIES {
+            let new = f(current.as_deref());
+            match self.compare_and_swap(slot_id, key, current.as_deref(), new.as_deref())? {
+                Ok(()) => return Ok(new),
+                Err(conflict) => current = conflict.current,
+            }
+        }
+        Err(sled::Error::Unsupported(format!(
+            "KB-{} key {:?} kept changing; gave up after {} attempts",
+            slot_id, key, CAS_MAX_RETRIES
+        )))
+    }
+
+    /// [`update`](Self::update) for a [`KbRecord`]: `f` edits the current record (or a
+    /// `KbRecord::new("", now)` when the key is absent or not a record) and the timestamp is
+    /// refreshed. Returns the record written.
+    pub fn update_record<F>(&self, slot_id: u8, key: &str, mut f: F) -> Result<KbRecord, sled::Error>
+    where
+        F: FnMut(&mut KbRecord),
+    {
+        let mut written = None;
+        self.update(slot_id, key, |current| {
+            let now = self.now_ms();
+            let mut record = current.and_then(KbRecord::from_bytes).unwrap_or_else(|| KbRecord::new("", now));
+            f(&mut record);
+            record.timestamp = now;
+            let bytes = record.to_bytes();
+            written = Some(record);
+            Some(bytes)
+        })?;
+        Ok(written.unwrap_or_else(|| KbRecord::new("", self.now_ms())))
+    }
+
+    /// Runs `f` as one sled transaction over `slot_id` (1–8): all of its writes land or none do
+    /// (see `transaction.rs`). `f` may run more than once, so it must not have side effects of
+    /// its own. Returns `f`'s value, or the error it aborted with.
+    pub fn transaction<F, R>(&self, slot_id: u8, f: F) -> Result<R, sled::Error>
+    where
+        F: Fn(&KbTransaction<'_>) -> KbTxResult<R>,
+    {
+        self.transaction_multi(&[slot_id], |tx| f(tx.slot(slot_id)?))
+    }
+
+    /// [`transaction`](Self::transaction) across several slots (1–8): `tx.slot(id)` is the view
+    /// of each slot in `slot_ids`.
+    pub fn transaction_multi<F, R>(&self, slot_ids: &[u8], f: F) -> Result<R, sled::Error>
+    where
+        F: Fn(&KbMultiTransaction<'_>) -> KbTxResult<R>,
+    {
+        if let Some(slot_id) = slot_ids.iter().find(|s| !(1..=8).contains(*s)) {
+            return Err(sled::Error::Unsupported(format!("transactions are not supported on KB-{}", slot_id)));
+        }
+        let trees: Vec<&str> = slot_ids.iter().map(|&slot_id| Self::tree_name(slot_id)).collect();
+        let writes = TxWrites::default();
+        let value = std::cell::RefCell::new(None);
+        self.db.transaction(&trees, &|views| {
+            writes.borrow_mut().clear();
+            let slots = slot_ids
+                .iter()
+                .zip(views)
+                .map(|(&slot_id, &view)| KbTransaction::new(slot_id, view, &writes, self))
+                .collect();
+            *value.borrow_mut() = Some(f(&KbMultiTransaction::new(slots))?);
+            Ok(())
+        })?;
+        let value = value
+            .into_inner()
+            .ok_or_else(|| sled::Error::ReportableBug("transaction committed without a result".into()))?;
+        for write in writes.into_inner() {
+            self.update_indexes(write.slot_id, &write.key, write.value.as_deref());
+            let kind = match (write.existed, write.value.is_some()) {
+                (_, false) => KbChangeKind::Remove,
+                (true, true) => KbChangeKind::Update,
+                (false, true) => KbChangeKind::Insert,
+            };
+            self.changes.publish(write.slot_id, &write.key, kind, self.now_ms());
+        }
+        tracing::debug!(target: "pagi::knowledge", slots = ?slot_ids, "KB transaction committed");
+        Ok(value)
+    }
+
+    /// Returns all keys in the tree for `slot_id` (1–8). Order is not guaranteed.
+    pub fn scan_keys(&self, slot_id: u8) -> Result<Vec<String>, sled::Error> {
+        let tree = self.db.open_tree(Self::tree_name(slot_id))?;
+        let keys: Vec<String> = tree
+            .iter()
+            .filter_map(|item| item.ok())
+            .filter_map(|(k, _)| String::from_utf8(k).ok())
+            .collect();
+        Ok(keys)
+    }
+
+    /// Returns all key/value pairs in the tree for `slot_id` (1–8).
     ///
     /// This is useful for implementing higher-level search (including semantic search)
     /// without exposing the underlying sled `Tree`.
@@ -723,11 +1634,97 @@ impl KnowledgeStore {
         for item in tree.iter() {
             let (k, v) = item?;
             let key = String::from_utf8(k.to_vec()).unwrap_or_default();
-            out.push((key, v.to_vec()));
+            out.push((key, self.decode_value(slot_id, &v)?));
         }
         Ok(out)
     }
 
+    /// Key/value pairs of `slot_id` whose key starts with `prefix`, in key order, at most `limit`
+    /// per page (capped at [`SCAN_MAX_LIMIT`]). Only the prefix's key range is read, unlike
+    /// [`scan_kv`](Self::scan_kv). Pass the page's `next_cursor` back as `cursor` to continue.
+    pub fn scan_prefix(
+        &self,
+        slot_id: u8,
+        prefix: &str,
+        cursor: Option<&str>,
+        limit: usize,
+    ) -> Result<ScanPage, sled::Error> {
+        let upper = prefix_upper_bound(prefix.as_bytes());
+        self.scan_page(slot_id, prefix.as_bytes(), upper, cursor, limit, false)
+    }
+
+    /// [`scan_prefix`](Self::scan_prefix) in descending key order. `event/` and `inbox/` keys
+    /// order by timestamp, so this reads an agent's stream newest first.
+    pub fn scan_prefix_rev(
+        &self,
+        slot_id: u8,
+        prefix: &str,
+        cursor: Option<&str>,
+        limit: usize,
+    ) -> Result<ScanPage, sled::Error> {
+        let upper = prefix_upper_bound(prefix.as_bytes());
+        self.scan_page(slot_id, prefix.as_bytes(), upper, cursor, limit, true)
+    }
+
+    /// Key/value pairs of `slot_id` with `start <= key < end` (no `end`: to the last key), in key
+    /// order, paged like [`scan_prefix`](Self::scan_prefix).
+    pub fn scan_range(
+        &self,
+        slot_id: u8,
+        start: &str,
+        end: Option<&str>,
+        cursor: Option<&str>,
+        limit: usize,
+    ) -> Result<ScanPage, sled::Error> {
+        let upper = end.map(|e| e.as_bytes().to_vec());
+        self.scan_page(slot_id, start.as_bytes(), upper, cursor, limit, false)
+    }
+
+    /// Reads one page of `lower <= key < upper`, resuming after `cursor` (before it when `reverse`).
+    fn scan_page(
+        &self,
+        slot_id: u8,
+        lower: &[u8],
+        upper: Option<Vec<u8>>,
+        cursor: Option<&str>,
+        limit: usize,
+        reverse: bool,
+    ) -> Result<ScanPage, sled::Error> {
+        use std::ops::Bound;
+
+        let mut from = Bound::Included(lower.to_vec());
+        let mut to = upper.map_or(Bound::Unbounded, Bound::Excluded);
+        match cursor.map(str::as_bytes) {
+            Some(c) if reverse && !matches!(&to, Bound::Excluded(u) if c >= u.as_slice()) => {
+                to = Bound::Excluded(c.to_vec());
+            }
+            Some(_) if reverse => {}
+            Some(c) if c >= lower => from = Bound::Excluded(c.to_vec()),
+            _ => {}
+        }
+        let empty = match (&from, &to) {
+            (Bound::Included(l), Bound::Excluded(u)) => u <= l,
+            (Bound::Excluded(l), Bound::Excluded(u)) => u <= l,
+            _ => false,
+        };
+        let mut page = ScanPage::default();
+        if empty {
+            return Ok(page);
+        }
+        let tree = self.db.open_tree(Self::tree_name(slot_id))?;
+        let items = tree.range(from, to, reverse);
+        let limit = limit.min(SCAN_MAX_LIMIT);
+        for item in items {
+            let (k, v) = item?;
+            if page.entries.len() == limit {
+                page.next_cursor = page.entries.last().map(|(key, _)| key.clone());
+                break;
+            }
+            page.entries.push((String::from_utf8_lossy(&k).into_owned(), self.decode_value(slot_id, &v)?));
+        }
+        Ok(page)
+    }
+
     /// Returns all successfully-deserialized [`KbRecord`](crates/pagi-core/src/knowledge/store.rs:119)
     /// values from the given slot.
     pub fn scan_records(&self, slot_id: u8) -> Result<Vec<(String, KbRecord)>, sled::Error> {
@@ -743,400 +1740,2757 @@ impl KnowledgeStore {
 
     /// Returns the number of entries in the tree for `slot_id` (1–8).
     pub fn count(&self, slot_id: u8) -> Result<usize, sled::Error> {
-        let tree = self.db.open_tree(Self::tree_name(slot_id))?;
-        Ok(tree.len())
+        self.db.open_tree(Self::tree_name(slot_id))?.len()
     }
 
-    /// Returns status information for all 9 KB slots (including Shadow Vault).
-    pub fn get_all_status(&self) -> Vec<KbStatus> {
-        KbType::all_with_shadow()
-            .iter()
-            .map(|kb_type| {
-                let slot_id = kb_type.slot_id();
-                let tree_result = self.db.open_tree(kb_type.tree_name());
-                match tree_result {
-                    Ok(tree) => {
-                        let mut status = KbStatus {
-                            slot_id,
-                            name: kb_type.label().to_string(),
-                            tree_name: kb_type.tree_name().to_string(),
-                            connected: true,
-                            entry_count: tree.len(),
-                            error: None,
-                        };
-                        // Shadow slot: indicate lock status
-                        if kb_type.is_encrypted() && !self.vault.is_unlocked() {
-                            status.error = Some("LOCKED (no master key)".to_string());
-                        }
-                        status
-                    },
-                    Err(e) => KbStatus {
-                        slot_id,
-                        name: kb_type.label().to_string(),
-                        tree_name: kb_type.tree_name().to_string(),
-                        connected: false,
-                        entry_count: 0,
-                        error: Some(e.to_string()),
-                    },
-                }
-            })
-            .collect()
+    // -------------------------------------------------------------------------
+    // Full-text index over KbRecord content (see text_index.rs)
+    // -------------------------------------------------------------------------
+
+    /// Records in `slots` (empty: 1–8) whose content matches `query`, best BM25 score first, at
+    /// most `limit`. A record matches on any query term; more and rarer terms rank higher.
+    /// Slots searched for the first time are indexed first.
+    pub fn text_search(&self, slots: &[u8], query: &str, limit: usize) -> Result<Vec<TextSearchHit>, sled::Error> {
+        self.text_search_where(slots, query, limit, |_| true)
     }
 
-    /// Initializes the 8 Sled trees by inserting a `metadata` key in each tree describing its purpose.
-    /// Safe to call multiple times (overwrites existing metadata). Call after opening the store (e.g. at startup).
-    pub fn pagi_init_kb_metadata(&self) -> Result<(), sled::Error> {
-        tracing::info!(target: "pagi::knowledge", "Initializing 8 Knowledge Base trees (L2 Memory)...");
-        
-        for kb_type in KbType::all() {
-            let slot_id = kb_type.slot_id();
-            let label = kb_type.label();
-            let tree_name = kb_type.tree_name();
-            
-            let metadata = serde_json::json!({
-                "slot_id": slot_id,
-                "name": label,
-                "tree_name": tree_name,
-                "purpose": label,
-                "kb_type": format!("{:?}", kb_type),
-                "initialized_at": std::time::SystemTime::now()
-                    .duration_since(std::time::UNIX_EPOCH)
-                    .map(|d| d.as_millis() as i64)
-                    .unwrap_or(0),
-                "vector_metadata": {
-                    "embedding_model": null,
-                    "vector_dims": null,
-                    "semantic_search_enabled": false
+    /// [`text_search`](Self::text_search) over the records whose key passes `keep`.
+    pub(crate) fn text_search_where(
+        &self,
+        slots: &[u8],
+        query: &str,
+        limit: usize,
+        keep: impl Fn(&str) -> bool,
+    ) -> Result<Vec<TextSearchHit>, sled::Error> {
+        let terms: std::collections::BTreeSet<String> = text_index::tokenize(query).into_iter().collect();
+        if terms.is_empty() || limit == 0 {
+            return Ok(Vec::new());
+        }
+        let slots: Vec<u8> = if slots.is_empty() {
+            (1..=8).collect()
+        } else {
+            slots.iter().copied().filter(|s| (1..=8).contains(s)).collect()
+        };
+        let tree = self.db.open_tree(TEXT_INDEX_TREE)?;
+        let mut scores: std::collections::HashMap<(u8, String), f32> = std::collections::HashMap::new();
+        for slot_id in slots {
+            if !tree.contains_key(text_index::built_key(slot_id).as_bytes())? {
+                // A read-only store cannot build the index; the slot has no hits until its writer does.
+                if self.is_read_only() {
+                    continue;
                 }
-            });
-            let bytes = metadata.to_string().into_bytes();
-            
-            // Use direct tree insert to avoid double-logging during init
-            let tree = self.db.open_tree(tree_name)?;
-            tree.insert("__kb_metadata__", bytes.as_slice())?;
-            
-            tracing::info!(
-                target: "pagi::knowledge",
-                kb_slot = slot_id,
-                kb_name = label,
-                tree = tree_name,
-                "KB-{} [{}] initialized (tree: {})",
+                self.rebuild_text_index(slot_id)?;
+            }
+            let stats: SlotStats = tree
+                .get(text_index::stats_key(slot_id).as_bytes())?
+                .and_then(|b| serde_json::from_slice(&b).ok())
+                .unwrap_or_default();
+            for term in &terms {
+                let prefix = text_index::posting_prefix(slot_id, term);
+                let mut postings = Vec::new();
+                for item in tree.scan_prefix(prefix.as_bytes()) {
+                    let (k, v) = item?;
+                    let key = String::from_utf8_lossy(&k[prefix.len()..]).into_owned();
+                    if let Some((tf, len)) = text_index::decode_posting(&v) {
+                        postings.push((key, tf, len));
+                    }
+                }
+                let df = postings.len();
+                for (key, tf, len) in postings.into_iter().filter(|(key, _, _)| keep(key)) {
+                    *scores.entry((slot_id, key)).or_default() += text_index::bm25(tf, len, df, &stats);
+                }
+            }
+        }
+        let mut ranked: Vec<((u8, String), f32)> = scores.into_iter().collect();
+        ranked.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        let mut hits = Vec::new();
+        for ((slot_id, key), score) in ranked {
+            if hits.len() == limit {
+                break;
+            }
+            // A posting can outlive its record for a moment (concurrent remove); skip it.
+            let Some(record) = self.get_record(slot_id, &key)? else {
+                continue;
+            };
+            hits.push(TextSearchHit {
                 slot_id,
-                label,
-                tree_name
-            );
+                key,
+                score,
+                content: record.content,
+                metadata: record.metadata,
+                timestamp: record.timestamp,
+            });
         }
-        
-        tracing::info!(target: "pagi::knowledge", "✓ All 8 Knowledge Bases initialized successfully");
-        Ok(())
+        Ok(hits)
     }
 
-    /// Appends an episodic memory event to **KB_CHRONOS** (the Historian).
-    ///
-    /// Key format: `event/{agent_id}/{timestamp_ms}_{uuid}` so each agent has its own memory stream.
-    /// Use `agent_id` = `"default"` for single-agent mode.
-    pub fn append_chronos_event(
+    /// Drops and rebuilds the full-text index of `slot_id` from its live records. Returns the
+    /// number of records indexed.
+    pub fn rebuild_text_index(&self, slot_id: u8) -> Result<usize, sled::Error> {
+        if !(1..=8).contains(&slot_id) {
+            return Ok(0);
+        }
+        let _guard = self.index_lock.lock().unwrap_or_else(|e| e.into_inner());
+        let tree = self.db.open_tree(TEXT_INDEX_TREE)?;
+        let mut batch = KvBatch::default();
+        for prefix in [text_index::slot_posting_prefix(slot_id), text_index::slot_doc_prefix(slot_id)] {
+            for item in tree.scan_prefix(prefix.as_bytes()) {
+                batch.remove(item?.0);
+            }
+        }
+        batch.remove(text_index::stats_key(slot_id).as_bytes());
+        tree.apply_batch(batch)?;
+        let mut indexed = 0;
+        for (key, bytes) in self.scan_kv(slot_id)? {
+            if self.reindex_text(slot_id, &key, Some(&bytes))? {
+                indexed += 1;
+            }
+        }
+        tree.insert(text_index::built_key(slot_id).as_bytes(), &b"1"[..])?;
+        tracing::info!(target: "pagi::knowledge", kb_slot = slot_id, records = indexed, "Full-text index rebuilt");
+        Ok(indexed)
+    }
+
+    /// Brings the full-text and vector indexes in line with a write of `value` (None: removal) at
+    /// `key`. Index failures are logged, not returned, so they never fail the write itself.
+    fn update_indexes(&self, slot_id: u8, key: &str, value: Option<&[u8]>) {
+        if !(1..=8).contains(&slot_id) {
+            return;
+        }
+        let _guard = self.index_lock.lock().unwrap_or_else(|e| e.into_inner());
+        if let Err(e) = self.reindex_text(slot_id, key, value) {
+            tracing::warn!(target: "pagi::knowledge", kb_slot = slot_id, key = key, error = %e, "Full-text index update failed");
+        }
+        if let Err(e) = self.reindex_vector(slot_id, key, value) {
+            tracing::warn!(target: "pagi::knowledge", kb_slot = slot_id, key = key, error = %e, "Vector index update failed");
+        }
+    }
+
+    /// Replaces the postings of `key` with those of `value` (a [`KbRecord`]; None or any other
+    /// value just removes them). Returns whether the record is now indexed. Encrypted slots are
+    /// not indexed, since postings would keep their words in plaintext.
+    fn reindex_text(&self, slot_id: u8, key: &str, value: Option<&[u8]>) -> Result<bool, sled::Error> {
+        if !(1..=8).contains(&slot_id) {
+            return Ok(false);
+        }
+        let value = value.filter(|_| !self.encrypted_slots.contains(&slot_id));
+        let tree = self.db.open_tree(TEXT_INDEX_TREE)?;
+        let doc_key = text_index::doc_key(slot_id, key);
+        let old: Option<IndexedDoc> = tree
+            .get(doc_key.as_bytes())?
+            .and_then(|b| serde_json::from_slice(&b).ok());
+        let new = value
+            .and_then(KbRecord::from_bytes)
+            .map(|record| text_index::term_frequencies(&record.content))
+            .filter(|(tf, _)| !tf.is_empty());
+        if old.is_none() && new.is_none() {
+            return Ok(false);
+        }
+        let mut batch = KvBatch::default();
+        let (mut docs, mut total_len) = (0i64, 0i64);
+        if let Some(old) = &old {
+            for term in &old.terms {
+                batch.remove(format!("{}{}", text_index::posting_prefix(slot_id, term), key).as_bytes());
+            }
+            batch.remove(doc_key.as_bytes());
+            docs -= 1;
+            total_len -= old.len as i64;
+        }
+        let indexed = new.is_some();
+        if let Some((tf, len)) = new {
+            for (term, count) in &tf {
+                let posting = text_index::encode_posting(*count, len);
+                batch.insert(format!("{}{}", text_index::posting_prefix(slot_id, term), key).as_bytes(), &posting[..]);
+            }
+            let doc = IndexedDoc { len, terms: tf.into_keys().collect() };
+            batch.insert(doc_key.as_bytes(), serde_json::to_vec(&doc).unwrap_or_default());
+            docs += 1;
+            total_len += len as i64;
+        }
+        tree.apply_batch(batch)?;
+        tree.update_and_fetch(text_index::stats_key(slot_id).as_bytes(), &mut |old| {
+            let mut stats: SlotStats = old.and_then(|b| serde_json::from_slice(b).ok()).unwrap_or_default();
+            stats.docs = (stats.docs as i64 + docs).max(0) as u64;
+            stats.total_len = (stats.total_len as i64 + total_len).max(0) as u64;
+            Some(serde_json::to_vec(&stats).unwrap_or_default())
+        })?;
+        Ok(indexed)
+    }
+
+    // -------------------------------------------------------------------------
+    // Vector index over KbRecord embeddings (see vector_index.rs)
+    // -------------------------------------------------------------------------
+
+    /// The `k` records of `slot_id` whose embeddings are most similar (cosine) to `embedding`,
+    /// most similar first, through the slot's HNSW index (built on the first search). Queries of a
+    /// size the index does not hold are answered by scanning the slot. Archived records are not
+    /// searched.
+    pub fn semantic_search(&self, slot_id: u8, embedding: &[f32], k: usize) -> Result<Vec<SemanticHit>, sled::Error> {
+        self.semantic_search_where(slot_id, embedding, k, |_| true)
+    }
+
+    /// [`semantic_search`](Self::semantic_search) over the records whose key passes `keep`. The
+    /// index is asked for more neighbours until `k` of them pass or it has no more.
+    pub(crate) fn semantic_search_where(
         &self,
-        agent_id: &str,
-        event: &EventRecord,
-    ) -> Result<(), sled::Error> {
-        let slot_id = KbType::Chronos.slot_id();
-        let agent_prefix = if agent_id.is_empty() { "default" } else { agent_id };
-        let key = format!(
-            "event/{}/{}_{}",
-            agent_prefix,
-            event.timestamp_ms,
-            Uuid::new_v4().simple()
-        );
-        self.insert(slot_id, &key, &event.to_bytes())?;
-        tracing::debug!(
-            target: "pagi::chronos",
-            agent_id = %agent_prefix,
-            key = %key,
-            source = %event.source_kb,
-            "Chronos: episodic event recorded"
-        );
-        Ok(())
+        slot_id: u8,
+        embedding: &[f32],
+        k: usize,
+        keep: impl Fn(&str) -> bool,
+    ) -> Result<Vec<SemanticHit>, sled::Error> {
+        let Some(query) = vector_index::normalize(embedding) else {
+            return Ok(Vec::new());
+        };
+        if !(1..=8).contains(&slot_id) || k == 0 {
+            return Ok(Vec::new());
+        }
+        let tree = self.db.open_tree(VECTOR_INDEX_TREE)?;
+        let graph = Hnsw::new(&*tree, slot_id);
+        if !tree.contains_key(graph.built_key().as_bytes())? && !self.is_read_only() {
+            self.rebuild_vector_index(slot_id)?;
+        }
+        if graph.dims()? == Some(query.len()) {
+            let mut fetch = k;
+            loop {
+                let neighbours = graph.search(&query, fetch)?;
+                let exhausted = neighbours.len() < fetch;
+                let mut hits = Vec::new();
+                for (key, score) in neighbours {
+                    if !keep(&key) {
+                        continue;
+                    }
+                    // A node can outlive its record for a moment (concurrent remove); skip it.
+                    if let Some(record) = self.get_record(slot_id, &key)? {
+                        hits.push(Self::semantic_hit(slot_id, key, score, record));
+                    }
+                }
+                if hits.len() >= k || exhausted {
+                    hits.truncate(k);
+                    return Ok(hits);
+                }
+                fetch *= 4;
+            }
+        }
+        let mut hits = Vec::new();
+        for (key, bytes) in self.scan_kv(slot_id)? {
+            if !keep(&key) {
+                continue;
+            }
+            let Some(record) = KbRecord::from_bytes(&bytes) else {
+                continue;
+            };
+            let Some(vector) = record.embedding.as_deref().filter(|v| v.len() == query.len()) else {
+                continue;
+            };
+            let Some(vector) = vector_index::normalize(vector) else {
+                continue;
+            };
+            let score = vector.iter().zip(&query).map(|(a, b)| a * b).sum();
+            hits.push(Self::semantic_hit(slot_id, key, score, record));
+        }
+        hits.sort_by(|a, b| b.score.total_cmp(&a.score).then_with(|| a.key.cmp(&b.key)));
+        hits.truncate(k);
+        Ok(hits)
     }
 
-    /// Returns the most recent episodic events from **KB_CHRONOS** for the given agent, newest first.
-    ///
-    /// Used by the "recall_past_actions" skill so the Agent can answer "What did you do recently?"
-    pub fn get_recent_chronos_events(
+    fn semantic_hit(slot_id: u8, key: String, score: f32, record: KbRecord) -> SemanticHit {
+        SemanticHit {
+            slot_id,
+            key,
+            score,
+            content: record.content,
+            metadata: record.metadata,
+            timestamp: record.timestamp,
+        }
+    }
+
+    /// Drops and rebuilds the vector index of `slot_id` from its live records. The index takes the
+    /// slot's most common embedding size; records of other sizes are left out. Returns the number
+    /// of records indexed.
+    pub fn rebuild_vector_index(&self, slot_id: u8) -> Result<usize, sled::Error> {
+        if !(1..=8).contains(&slot_id) {
+            return Ok(0);
+        }
+        let _guard = self.index_lock.lock().unwrap_or_else(|e| e.into_inner());
+        let tree = self.db.open_tree(VECTOR_INDEX_TREE)?;
+        let graph = Hnsw::new(&*tree, slot_id);
+        graph.clear()?;
+        let embedded: Vec<(String, Vec<f32>)> = self
+            .scan_kv(slot_id)?
+            .into_iter()
+            .filter_map(|(key, bytes)| Some((key, KbRecord::from_bytes(&bytes)?.embedding?)))
+            .collect();
+        let mut sizes: std::collections::HashMap<usize, usize> = std::collections::HashMap::new();
+        for (_, vector) in &embedded {
+            *sizes.entry(vector.len()).or_default() += 1;
+        }
+        let dims = sizes.into_iter().max_by_key(|(dims, count)| (*count, *dims)).map(|(dims, _)| dims);
+        let mut indexed = 0;
+        for (key, vector) in embedded.iter().filter(|(_, v)| Some(v.len()) == dims) {
+            if graph.insert(key, vector)? {
+                indexed += 1;
+            }
+        }
+        tree.insert(graph.built_key().as_bytes(), &b"1"[..])?;
+        tracing::info!(target: "pagi::knowledge", kb_slot = slot_id, records = indexed, "Vector index rebuilt");
+        Ok(indexed)
+    }
+
+    /// Replaces the graph node of `key` with the embedding of `value` (a [`KbRecord`]; None, a
+    /// record without embedding, or any other value just removes it). Returns whether the record
+    /// is now indexed.
+    fn reindex_vector(&self, slot_id: u8, key: &str, value: Option<&[u8]>) -> Result<bool, sled::Error> {
+        let tree = self.db.open_tree(VECTOR_INDEX_TREE)?;
+        let graph = Hnsw::new(&*tree, slot_id);
+        match value.and_then(KbRecord::from_bytes).and_then(|r| r.embedding) {
+            Some(vector) => graph.insert(key, &vector),
+            None => graph.remove(key).map(|_| false),
+        }
+    }
+
+    // -------------------------------------------------------------------------
+    // Archive tier (cold records → compressed segment files)
+    // -------------------------------------------------------------------------
+
+    fn archive_index_key(slot_id: u8, key: &str) -> String {
+        format!("idx/{}/{}", slot_id, key)
+    }
+
+    fn archive_segment_key(segment_id: &str) -> String {
+        format!("seg/{}", segment_id)
+    }
+
+    /// Moves records in `slot_id` whose timestamp is older than `cutoff_ms` into a new compressed
+    /// segment file and removes them from sled. Intended for Chronos and Logos; encrypted slots
+    /// (Shadow, and any in `[encryption]`) cannot be archived, since segments are plaintext.
+    /// Records without a timestamp are left in place.
+    pub fn archive_older_than(
         &self,
-        agent_id: &str,
-        limit: usize,
-    ) -> Result<Vec<EventRecord>, sled::Error> {
-        let slot_id = KbType::Chronos.slot_id();
-        let agent_prefix = if agent_id.is_empty() { "default" } else { agent_id };
-        let prefix = format!("event/{}", agent_prefix);
-        let mut events: Vec<(i64, EventRecord)> = self
+        slot_id: u8,
+        cutoff_ms: i64,
+    ) -> Result<ArchiveReport, Box<dyn std::error::Error + Send + Sync>> {
+        if !(1..=8).contains(&slot_id) {
+            return Err(format!("slot {} cannot be archived", slot_id).into());
+        }
+        if self.is_slot_encrypted(slot_id) {
+            return Err(format!("slot {} is encrypted and cannot be archived", slot_id).into());
+        }
+        let mut entries: Vec<ArchivedEntry> = self
             .scan_kv(slot_id)?
             .into_iter()
-            .filter(|(k, _)| k.starts_with(&prefix))
-            .filter_map(|(_, bytes)| EventRecord::from_bytes(&bytes).map(|e| (e.timestamp_ms, e)))
+            .filter(|(key, _)| !key.starts_with("__"))
+            .filter_map(|(key, bytes)| {
+                let timestamp_ms = archive::record_timestamp_ms(&bytes)?;
+                let value = String::from_utf8(bytes).ok()?;
+                (timestamp_ms < cutoff_ms).then_some(ArchivedEntry { key, value, timestamp_ms })
+            })
             .collect();
-        events.sort_by(|a, b| b.0.cmp(&a.0));
-        Ok(events.into_iter().take(limit).map(|(_, e)| e).collect())
+        if entries.is_empty() {
+            return Ok(ArchiveReport { slot_id, segment_id: None, archived: 0 });
+        }
+        entries.sort_by_key(|e| e.timestamp_ms);
+
+        let now_ms = self.now_ms();
+        let segment_id = format!("{:013}_{}", now_ms, Uuid::new_v4().simple());
+        let file = format!("{}/{}.jsonl.gz", Self::tree_name(slot_id), segment_id);
+        archive::write_segment(&self.archive_dir.join(&file), &entries)?;
+
+        let segment = ArchiveSegment {
+            id: segment_id.clone(),
+            slot_id,
+            file,
+            count: entries.len(),
+            live: entries.len(),
+            min_timestamp_ms: entries.first().map(|e| e.timestamp_ms).unwrap_or(0),
+            max_timestamp_ms: entries.last().map(|e| e.timestamp_ms).unwrap_or(0),
+            created_at_ms: now_ms,
+        };
+        let index = self.db.open_tree(ARCHIVE_INDEX_TREE)?;
+        let mut batch = KvBatch::default();
+        for entry in &entries {
+            batch.insert(Self::archive_index_key(slot_id, &entry.key).as_bytes(), segment_id.as_bytes());
+        }
+        batch.insert(Self::archive_segment_key(&segment_id).as_bytes(), segment.to_bytes());
+        index.apply_batch(batch)?;
+
+        let tree = self.db.open_tree(Self::tree_name(slot_id))?;
+        let mut batch = KvBatch::default();
+        for entry in &entries {
+            batch.remove(entry.key.as_bytes());
+        }
+        tree.apply_batch(batch)?;
+        for entry in &entries {
+            self.update_indexes(slot_id, &entry.key, None);
+            self.changes.publish(slot_id, &entry.key, KbChangeKind::Archived, self.now_ms());
+        }
+        self.db.flush()?;
+
+        tracing::info!(
+            target: "pagi::knowledge",
+            kb_slot = slot_id,
+            segment = %segment_id,
+            archived = entries.len(),
+            "KB-{} archived {} cold records",
+            slot_id,
+            entries.len()
+        );
+        Ok(ArchiveReport { slot_id, segment_id: Some(segment_id), archived: entries.len() })
+    }
+
+    /// Lists archive segments, optionally for one slot.
+    pub fn list_archive_segments(&self, slot_id: Option<u8>) -> Result<Vec<ArchiveSegment>, sled::Error> {
+        let index = self.db.open_tree(ARCHIVE_INDEX_TREE)?;
+        let mut out = Vec::new();
+        for item in index.scan_prefix(b"seg/") {
+            let (_, v) = item?;
+            if let Some(segment) = ArchiveSegment::from_bytes(&v) {
+                if slot_id.is_none_or(|s| s == segment.slot_id) {
+                    out.push(segment);
+                }
+            }
+        }
+        Ok(out)
+    }
+
+    fn archive_segment(&self, segment_id: &str) -> Result<Option<ArchiveSegment>, sled::Error> {
+        let index = self.db.open_tree(ARCHIVE_INDEX_TREE)?;
+        Ok(index
+            .get(Self::archive_segment_key(segment_id).as_bytes())?
+            .and_then(|v| ArchiveSegment::from_bytes(&v)))
+    }
+
+    /// Reads an archived value (slow path: decompresses the segment). None if not archived.
+    pub fn get_archived(
+        &self,
+        slot_id: u8,
+        key: &str,
+    ) -> Result<Option<Vec<u8>>, Box<dyn std::error::Error + Send + Sync>> {
+        let index = self.db.open_tree(ARCHIVE_INDEX_TREE)?;
+        let Some(segment_id) = index.get(Self::archive_index_key(slot_id, key).as_bytes())? else {
+            return Ok(None);
+        };
+        let segment_id = String::from_utf8_lossy(&segment_id).to_string();
+        let Some(segment) = self.archive_segment(&segment_id)? else {
+            return Ok(None);
+        };
+        let entries = archive::read_segment(&self.archive_dir.join(&segment.file))?;
+        Ok(entries
+            .into_iter()
+            .find(|e| e.key == key)
+            .map(|e| e.value.into_bytes()))
+    }
+
+    /// Returns the live value, falling back to the archive tier.
+    pub fn get_or_archived(
+        &self,
+        slot_id: u8,
+        key: &str,
+    ) -> Result<Option<Vec<u8>>, Box<dyn std::error::Error + Send + Sync>> {
+        match self.get(slot_id, key)? {
+            Some(v) => Ok(Some(v)),
+            None => self.get_archived(slot_id, key),
+        }
+    }
+
+    /// All archived (not restored) key/value pairs of a slot. Reads every segment of the slot.
+    pub fn scan_archived_kv(
+        &self,
+        slot_id: u8,
+    ) -> Result<ArchivedKv, Box<dyn std::error::Error + Send + Sync>> {
+        let index = self.db.open_tree(ARCHIVE_INDEX_TREE)?;
+        let mut out = Vec::new();
+        for segment in self.list_archive_segments(Some(slot_id))? {
+            for entry in archive::read_segment(&self.archive_dir.join(&segment.file))? {
+                let indexed = index.get(Self::archive_index_key(slot_id, &entry.key).as_bytes())?;
+                if indexed.is_some_and(|id| id == segment.id.as_bytes()) {
+                    out.push((entry.key, entry.value.into_bytes()));
+                }
+            }
+        }
+        Ok(out)
+    }
+
+    /// Live key/value pairs plus archived ones. Archive read failures are logged and skipped so
+    /// recall/search keep working on the live data.
+    pub fn scan_kv_with_archive(&self, slot_id: u8) -> Result<Vec<(String, Vec<u8>)>, sled::Error> {
+        let mut out = self.scan_kv(slot_id)?;
+        match self.scan_archived_kv(slot_id) {
+            Ok(archived) => out.extend(archived),
+            Err(e) => tracing::warn!(target: "pagi::knowledge", kb_slot = slot_id, error = %e, "Archive read failed"),
+        }
+        Ok(out)
+    }
+
+    /// Restores one archived record into its slot. Returns false if the key was not archived.
+    pub fn restore_archived(
+        &self,
+        slot_id: u8,
+        key: &str,
+    ) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        let Some(value) = self.get_archived(slot_id, key)? else {
+            return Ok(false);
+        };
+        let index = self.db.open_tree(ARCHIVE_INDEX_TREE)?;
+        let Some(segment_id) = index.get(Self::archive_index_key(slot_id, key).as_bytes())? else {
+            return Ok(false);
+        };
+        let segment_id = String::from_utf8_lossy(&segment_id).to_string();
+        self.db.open_tree(Self::tree_name(slot_id))?.insert(key.as_bytes(), self.encode_value(slot_id, &value)?.as_ref())?;
+        self.update_indexes(slot_id, key, Some(&value));
+        self.changes.publish(slot_id, key, KbChangeKind::Insert, self.now_ms());
+        index.remove(Self::archive_index_key(slot_id, key).as_bytes())?;
+        self.release_archive_entries(&segment_id, 1)?;
+        Ok(true)
+    }
+
+    /// Restores every still-archived record of a segment. Returns the number restored.
+    pub fn restore_archive_segment(
+        &self,
+        segment_id: &str,
+    ) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
+        let Some(segment) = self.archive_segment(segment_id)? else {
+            return Ok(0);
+        };
+        let index = self.db.open_tree(ARCHIVE_INDEX_TREE)?;
+        let tree = self.db.open_tree(Self::tree_name(segment.slot_id))?;
+        let mut restored = 0;
+        for entry in archive::read_segment(&self.archive_dir.join(&segment.file))? {
+            let index_key = Self::archive_index_key(segment.slot_id, &entry.key);
+            if index.get(index_key.as_bytes())?.is_some_and(|id| id == segment.id.as_bytes()) {
+                tree.insert(entry.key.as_bytes(), self.encode_value(segment.slot_id, entry.value.as_bytes())?.as_ref())?;
+                self.update_indexes(segment.slot_id, &entry.key, Some(entry.value.as_bytes()));
+                self.changes.publish(segment.slot_id, &entry.key, KbChangeKind::Insert, self.now_ms());
+                index.remove(index_key.as_bytes())?;
+                restored += 1;
+            }
+        }
+        self.release_archive_entries(segment_id, restored)?;
+        Ok(restored)
+    }
+
+    /// Decrements a segment's live count; deletes the segment file and metadata at zero.
+    fn release_archive_entries(
+        &self,
+        segment_id: &str,
+        released: usize,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let Some(mut segment) = self.archive_segment(segment_id)? else {
+            return Ok(());
+        };
+        let index = self.db.open_tree(ARCHIVE_INDEX_TREE)?;
+        segment.live = segment.live.saturating_sub(released);
+        if segment.live == 0 {
+            index.remove(Self::archive_segment_key(segment_id).as_bytes())?;
+            if let Err(e) = std::fs::remove_file(self.archive_dir.join(&segment.file)) {
+                tracing::warn!(target: "pagi::knowledge", segment = %segment_id, error = %e, "Failed to delete empty archive segment");
+            }
+        } else {
+            index.insert(Self::archive_segment_key(segment_id).as_bytes(), &segment.to_bytes())?;
+        }
+        Ok(())
+    }
+
+    // -------------------------------------------------------------------------
+    // Snapshots (tree export/import; see snapshot.rs)
+    // -------------------------------------------------------------------------
+
+    /// Writes every sled tree into `dir`, one file per tree. Returns the trees written.
+    pub(crate) fn export_trees(&self, dir: &Path) -> Result<Vec<SnapshotTree>, Box<dyn std::error::Error + Send + Sync>> {
+        self.db.flush()?;
+        let mut names = self.db.tree_names()?;
+        names.sort();
+        let mut trees = Vec::with_capacity(names.len());
+        for (i, name) in names.into_iter().enumerate() {
+            let tree = self.db.open_tree(&name)?;
+            let file = format!("{:03}.kv.gz", i);
+            let entries = snapshot::write_tree_file(&dir.join(&file), tree.iter())?;
+            trees.push(SnapshotTree {
+                name,
+                file,
+                entries,
+            });
+        }
+        Ok(trees)
+    }
+
+    /// Replaces trees with their contents in the snapshot at `dir`: the tree of `slot_id`, or
+    /// every tree when None (trees created after the snapshot are emptied). Returns the trees
+    /// and entries restored.
+    pub(crate) fn import_trees(
+        &self,
+        dir: &Path,
+        info: &SnapshotInfo,
+        slot_id: Option<u8>,
+    ) -> Result<(usize, usize), Box<dyn std::error::Error + Send + Sync>> {
+        let targets: Vec<String> = match slot_id {
+            Some(slot) => vec![Self::tree_name(slot).to_string()],
+            None => {
+                let mut names: Vec<String> = info.trees.iter().map(|t| t.name.clone()).collect();
+                for name in self.db.tree_names()? {
+                    if !names.contains(&name) {
+                        names.push(name);
+                    }
+                }
+                names
+            }
+        };
+        let mut entries = 0;
+        for name in &targets {
+            let saved = match info.trees.iter().find(|t| &t.name == name) {
+                Some(t) => snapshot::read_tree_file(&dir.join(&t.file))?,
+                None => Vec::new(),
+            };
+            let tree = self.db.open_tree(name)?;
+            tree.clear()?;
+            for chunk in saved.chunks(1_000) {
+                let mut batch = KvBatch::default();
+                for (k, v) in chunk {
+                    batch.insert(k.as_slice(), v.as_slice());
+                }
+                tree.apply_batch(batch)?;
+            }
+            entries += saved.len();
+        }
+        if let Some(slot) = slot_id.filter(|s| (1..=8).contains(s)) {
+            self.rebuild_text_index(slot)?;
+            self.rebuild_vector_index(slot)?;
+        }
+        self.db.flush()?;
+        for slot in slot_id.map_or(1..=9, |s| s..=s) {
+            self.changes.publish(slot, "", KbChangeKind::Reset, self.now_ms());
+        }
+        Ok((targets.len(), entries))
+    }
+
+    /// Stored form of a slot 1–8 value: compressed when it is over the threshold and shrinks,
+    /// then sealed when the slot is encrypted. Plaintext that starts like a sealed value is
+    /// escaped, so it is never mistaken for one.
+    pub(crate) fn encode_value<'a>(&self, slot_id: u8, value: &'a [u8]) -> Result<std::borrow::Cow<'a, [u8]>, sled::Error> {
+        let encoded = compression::encode(value, self.compression_threshold);
+        if !self.encrypted_slots.contains(&slot_id) {
+            if vault::looks_sealed(&encoded) {
+                return Ok(std::borrow::Cow::Owned(compression::escape(&encoded)));
+            }
+            return Ok(encoded);
+        }
+        self.vault
+            .seal(&encoded)
+            .map(std::borrow::Cow::Owned)
+            .map_err(|e| Self::vault_error(slot_id, e))
+    }
+
+    /// Original bytes of a stored value; Shadow values are returned as stored (encrypted).
+    /// Sealed values are decrypted whether or not the slot is still encrypted, so turning
+    /// encryption off leaves them readable.
+    pub(crate) fn decode_value(&self, slot_id: u8, stored: &[u8]) -> Result<Vec<u8>, sled::Error> {
+        if slot_id == SHADOW_SLOT_ID {
+            return Ok(stored.to_vec());
+        }
+        if vault::is_sealed(stored) {
+            let opened = self.vault.unseal(stored).map_err(|e| Self::vault_error(slot_id, e))?;
+            return Ok(compression::decompress(&opened).into_owned());
+        }
+        Ok(compression::decompress(stored).into_owned())
+    }
+
+    fn vault_error(slot_id: u8, e: VaultError) -> sled::Error {
+        sled::Error::Unsupported(format!("KB-{} is encrypted: {}", slot_id, e))
+    }
+
+    /// Stored versus original value sizes for each of the 9 slots. Reads every value.
+    pub fn storage_stats(&self) -> Result<Vec<SlotStorageStats>, sled::Error> {
+        let mut out = Vec::with_capacity(9);
+        for kb_type in KbType::all_with_shadow() {
+            let slot_id = kb_type.slot_id();
+            let mut stats = SlotStorageStats { slot_id, ..Default::default() };
+            for item in self.db.open_tree(kb_type.tree_name())?.iter() {
+                let (_, v) = item?;
+                stats.entries += 1;
+                stats.stored_bytes += v.len() as u64;
+                // Sealed values are measured inside (as stored if the vault is locked).
+                let opened = (slot_id != SHADOW_SLOT_ID && vault::is_sealed(&v)).then(|| self.vault.unseal(&v).ok()).flatten();
+                let v = opened.as_deref().unwrap_or(&v);
+                if slot_id == SHADOW_SLOT_ID {
+                    stats.raw_bytes += v.len() as u64;
+                    continue;
+                }
+                if compression::is_compressed(v) {
+                    stats.compressed_entries += 1;
+                }
+                stats.raw_bytes += compression::decompress(v).len() as u64;
+            }
+            out.push(stats);
+        }
+        Ok(out)
+    }
+
+    /// Returns status information for all 9 KB slots (including Shadow Vault).
+    pub fn get_all_status(&self) -> Vec<KbStatus> {
+        KbType::all_with_shadow()
+            .iter()
+            .map(|kb_type| {
+                let slot_id = kb_type.slot_id();
+                let count_result = self.db.open_tree(kb_type.tree_name()).and_then(|tree| tree.len());
+                match count_result {
+                    Ok(entry_count) => {
+                        let mut status = KbStatus {
+                            slot_id,
+                            name: kb_type.label().to_string(),
+                            tree_name: kb_type.tree_name().to_string(),
+                            connected: true,
+                            entry_count,
+                            encrypted: self.is_slot_encrypted(slot_id),
+                            error: None,
+                        };
+                        // Shadow slot: indicate lock status
+                        if status.encrypted && !self.vault.is_unlocked() {
+                            status.error = Some("LOCKED (no master key)".to_string());
+                        }
+                        status
+                    },
+                    Err(e) => KbStatus {
+                        slot_id,
+                        name: kb_type.label().to_string(),
+                        tree_name: kb_type.tree_name().to_string(),
+                        connected: false,
+                        entry_count: 0,
+                        encrypted: self.is_slot_encrypted(slot_id),
+                        error: Some(e.to_string()),
+                    },
+                }
+            })
+            .collect()
+    }
+
+    /// Opens every tree (slots 1–9) and reads one key from it: a cheap check that the store
+    /// answers, without counting entries like [`get_all_status`](Self::get_all_status).
+    /// Returns the slots that failed, with the error.
+    pub fn probe_trees(&self) -> Vec<(u8, String)> {
+        KbType::all_with_shadow()
+            .iter()
+            .filter_map(|kb_type| {
+                self.db
+                    .open_tree(kb_type.tree_name())
+                    .and_then(|tree| tree.get(b"__readiness_probe__"))
+                    .err()
+                    .map(|e| (kb_type.slot_id(), e.to_string()))
+            })
+            .collect()
+    }
+
+    /// Initializes the 8 Sled trees by inserting a `metadata` key in each tree describing its purpose.
+    /// Safe to call multiple times (overwrites existing metadata). Call after opening the store (e.g. at startup).
+    pub fn pagi_init_kb_metadata(&self) -> Result<(), sled::Error> {
+        tracing::info!(target: "pagi::knowledge", "Initializing 8 Knowledge Base trees (L2 Memory)...");
+        
+        for kb_type in KbType::all() {
+            let slot_id = kb_type.slot_id();
+            let label = kb_type.label();
+            let tree_name = kb_type.tree_name();
+            
+            let metadata = serde_json::json!({
+                "slot_id": slot_id,
+                "name": label,
+                "tree_name": tree_name,
+                "purpose": label,
+                "kb_type": format!("{:?}", kb_type),
+                "initialized_at": self.now_ms(),
+                "vector_metadata": {
+                    "embedding_model": null,
+                    "vector_dims": null,
+                    "semantic_search_enabled": false
+                }
+            });
+            let bytes = metadata.to_string().into_bytes();
+            
+            // Use direct tree insert to avoid double-logging during init
+            let tree = self.db.open_tree(tree_name)?;
+            tree.insert(b"__kb_metadata__", bytes.as_slice())?;
+            
+            tracing::info!(
+                target: "pagi::knowledge",
+                kb_slot = slot_id,
+                kb_name = label,
+                tree = tree_name,
+                "KB-{} [{}] initialized (tree: {})",
+                slot_id,
+                label,
+                tree_name
+            );
+        }
+        
+        tracing::info!(target: "pagi::knowledge", "✓ All 8 Knowledge Bases initialized successfully");
+        Ok(())
+    }
+
+    /// Appends an episodic memory event to **KB_CHRONOS** (the Historian).
+    ///
+    /// Key format: `event/{agent_id}/{timestamp_ms}_{uuid}` so each agent has its own memory stream.
+    /// Use `agent_id` = `"default"` for single-agent mode.
+    pub fn append_chronos_event(
+        &self,
+        agent_id: &str,
+        event: &EventRecord,
+    ) -> Result<(), sled::Error> {
+        let slot_id = KbType::Chronos.slot_id();
+        let key = keys::event_key(agent_id, event.timestamp_ms, &Uuid::new_v4().simple().to_string());
+        let bytes = match self.redactor() {
+            Some(redactor) => {
+                let mut report = RedactionReport::default();
+                let mut masked = event.clone();
+                for text in std::iter::once(&mut masked.reflection).chain(masked.outcome.as_mut()) {
+                    let (redacted, found) = redactor.redact_text(text);
+                    *text = redacted;
+                    report.merge(&found);
+                }
+                self.record_redaction(DEFAULT_TENANT_ID, agent_id, None, "chronos_event", &report);
+                masked.to_bytes()
+            }
+            None => event.to_bytes(),
+        };
+        self.insert(slot_id, &key, &bytes)?;
+        tracing::debug!(
+            target: "pagi::chronos",
+            agent_id = %agent_id,
+            key = %key,
+            source = %event.source_kb,
+            "Chronos: episodic event recorded"
+        );
+        Ok(())
+    }
+
+    /// Returns the most recent episodic events from **KB_CHRONOS** for the given agent, newest first.
+    /// When fewer than `limit` live events exist, archived events fill the remainder (slow path).
+    ///
+    /// Used by the "recall_past_actions" skill so the Agent can answer "What did you do recently?"
+    pub fn get_recent_chronos_events(
+        &self,
+        agent_id: &str,
+        limit: usize,
+    ) -> Result<Vec<EventRecord>, sled::Error> {
+        let slot_id = KbType::Chronos.slot_id();
+        let prefix = keys::event_prefix(agent_id);
+        let live: Vec<EventRecord> = self
+            .scan_prefix_rev(slot_id, &prefix, None, limit)?
+            .entries
+            .into_iter()
+            .filter_map(|(_, bytes)| EventRecord::from_bytes(&bytes))
+            .collect();
+        if live.len() >= limit {
+            return Ok(live);
+        }
+        let kv = self.scan_kv_with_archive(slot_id)?;
+        let mut events: Vec<(i64, EventRecord)> = kv
+            .into_iter()
+            .filter(|(k, _)| k.starts_with(&prefix))
+            .filter_map(|(_, bytes)| EventRecord::from_bytes(&bytes).map(|e| (e.timestamp_ms, e)))
+            .collect();
+        events.sort_by(|a, b| b.0.cmp(&a.0));
+        Ok(events.into_iter().take(limit).map(|(_, e)| e).collect())
+    }
+
+    /// Returns the active safety policy from **KB_ETHOS**, if present.
+    pub fn get_ethos_policy(&self) -> Option<PolicyRecord> {
+        let slot_id = KbType::Ethos.slot_id();
+        self.get(slot_id, ETHOS_DEFAULT_POLICY_KEY)
+            .ok()
+            .flatten()
+            .and_then(|b| PolicyRecord::from_bytes(&b))
+    }
+
+    /// Writes the active safety policy to **KB_ETHOS**.
+    pub fn set_ethos_policy(&self, policy: &PolicyRecord) -> Result<(), sled::Error> {
+        let slot_id = KbType::Ethos.slot_id();
+        self.insert(slot_id, ETHOS_DEFAULT_POLICY_KEY, &policy.to_bytes())?;
+        Ok(())
+    }
+
+    /// The current v2 Ethos rule set (`policy/rules`), if one was saved.
+    pub fn get_policy_rules(&self) -> Option<PolicyRules> {
+        self.get(KbType::Ethos.slot_id(), keys::POLICY_RULES_KEY)
+            .ok()
+            .flatten()
+            .and_then(|b| PolicyRules::from_bytes(&b))
+    }
+
+    /// Saves `rules` as the next version of the Ethos rule set (the current key and its history
+    /// entry are written together). Fails when [`PolicyRules::validate`] rejects it.
+    pub fn put_policy_rules(&self, rules: &PolicyRules) -> Result<PolicyRules, sled::Error> {
+        rules.validate().map_err(|e| sled::Error::Unsupported(e.to_string()))?;
+        let updated_at_ms = self.now_ms();
+        // The history outlives a cleared rule set, so numbering continues from its newest entry.
+        let archived = self
+            .scan_prefix_rev(KbType::Ethos.slot_id(), keys::POLICY_VERSION_PREFIX, None, 1)?
+            .entries
+            .first()
+            .and_then(|(_, b)| PolicyRules::from_bytes(b))
+            .map_or(0, |p| p.version);
+        self.transaction(KbType::Ethos.slot_id(), |tx| {
+            let current = tx
+                .get(keys::POLICY_RULES_KEY)?
+                .and_then(|b| PolicyRules::from_bytes(&b))
+                .map_or(0, |p| p.version);
+            let saved = PolicyRules {
+                version: current.max(archived) + 1,
+                updated_at_ms,
+                ..rules.clone()
+            };
+            let bytes = saved.to_bytes();
+            tx.insert(keys::POLICY_RULES_KEY, &bytes)?;
+            tx.insert(&keys::policy_version_key(saved.version), &bytes)?;
+            Ok(saved)
+        })
+    }
+
+    /// Drops the current rule set so the legacy keyword policy applies again; its versions stay in
+    /// the history. Returns whether a rule set was in force.
+    pub fn clear_policy_rules(&self) -> Result<bool, sled::Error> {
+        Ok(self.remove(KbType::Ethos.slot_id(), keys::POLICY_RULES_KEY)?.is_some())
+    }
+
+    /// Every saved version of the Ethos rule set, oldest first.
+    pub fn policy_rule_versions(&self) -> Result<Vec<PolicyRules>, sled::Error> {
+        let mut versions = Vec::new();
+        let mut cursor = None;
+        loop {
+            let page = self.scan_prefix(KbType::Ethos.slot_id(), keys::POLICY_VERSION_PREFIX, cursor.as_deref(), SCAN_MAX_LIMIT)?;
+            versions.extend(page.entries.iter().filter_map(|(_, b)| PolicyRules::from_bytes(b)));
+            match page.next_cursor {
+                Some(next) => cursor = Some(next),
+                None => break,
+            }
+        }
+        Ok(versions)
+    }
+
+    /// One saved version of the Ethos rule set.
+    pub fn get_policy_rules_version(&self, version: u32) -> Option<PolicyRules> {
+        self.get(KbType::Ethos.slot_id(), &keys::policy_version_key(version))
+            .ok()
+            .flatten()
+            .and_then(|b| PolicyRules::from_bytes(&b))
+    }
+
+    /// The Ethos policy in force: the v2 rule set, else the legacy keyword policy, else None.
+    pub fn active_ethos_policy(&self) -> Option<ActiveEthosPolicy> {
+        self.get_policy_rules()
+            .map(ActiveEthosPolicy::Rules)
+            .or_else(|| self.get_ethos_policy().map(ActiveEthosPolicy::Legacy))
+    }
+
+    // ─────────────────────────────────────────────────────────────────────────
+    // Approvals (Ethos) — skill calls waiting for a human decision
+    // ─────────────────────────────────────────────────────────────────────────
+
+    /// Checks whether the call in `request` may run. An approved request for the same call grants
+    /// it (and is marked `executed` when `consume` is set); a pending or denied one is returned as
+    /// is; otherwise `request` is stored as a new pending approval.
+    pub fn approval_checkpoint(&self, request: &ApprovalRecord, consume: bool) -> Result<ApprovalCheckpoint, sled::Error> {
+        let slot_id = KbType::Ethos.slot_id();
+        let mut existing: Vec<ApprovalRecord> = self
+            .scan_approvals()?
+            .into_iter()
+            .filter(|a| a.same_call(request) && a.status != ApprovalStatus::Executed)
+            .collect();
+        existing.sort_by_key(|a| match a.status {
+            ApprovalStatus::Approved => 0,
+            ApprovalStatus::Denied => 1,
+            _ => 2,
+        });
+        if let Some(found) = existing.into_iter().next() {
+            return Ok(match found.status {
+                ApprovalStatus::Approved if consume => {
+                    let executed_at_ms = self.now_ms();
+                    let key = keys::approval_key(&found.approval_id);
+                    let consumed = self.transaction(slot_id, |tx| {
+                        match tx.get(&key)?.and_then(|b| ApprovalRecord::from_bytes(&b)) {
+                            Some(mut approval) if approval.status == ApprovalStatus::Approved => {
+                                approval.status = ApprovalStatus::Executed;
+                                approval.executed_at_ms = Some(executed_at_ms);
+                                tx.insert(&key, &approval.to_bytes())?;
+                                Ok(Some(approval))
+                            }
+                            _ => Ok(None),
+                        }
+                    })?;
+                    match consumed {
+                        Some(approval) => ApprovalCheckpoint::Granted(approval),
+                        // Consumed concurrently: this run needs its own approval.
+                        None => return self.approval_checkpoint(request, consume),
+                    }
+                }
+                ApprovalStatus::Approved => ApprovalCheckpoint::Granted(found),
+                ApprovalStatus::Denied => ApprovalCheckpoint::Denied(found),
+                _ => ApprovalCheckpoint::Pending(found),
+            });
+        }
+        let approval = ApprovalRecord {
+            approval_id: Uuid::new_v4().simple().to_string(),
+            status: ApprovalStatus::Pending,
+            created_at_ms: self.now_ms(),
+            ..request.clone()
+        };
+        self.insert(slot_id, &keys::approval_key(&approval.approval_id), &approval.to_bytes())?;
+        Ok(ApprovalCheckpoint::Pending(approval))
+    }
+
+    pub fn get_approval(&self, approval_id: &str) -> Option<ApprovalRecord> {
+        self.get(KbType::Ethos.slot_id(), &keys::approval_key(approval_id))
+            .ok()
+            .flatten()
+            .and_then(|b| ApprovalRecord::from_bytes(&b))
+    }
+
+    /// Approvals, newest first, optionally only those in `status`.
+    pub fn list_approvals(&self, status: Option<ApprovalStatus>, limit: usize) -> Result<Vec<ApprovalRecord>, sled::Error> {
+        let mut approvals: Vec<ApprovalRecord> = self
+            .scan_approvals()?
+            .into_iter()
+            .filter(|a| status.is_none_or(|s| a.status == s))
+            .collect();
+        approvals.sort_by_key(|a| std::cmp::Reverse(a.created_at_ms));
+        approvals.truncate(limit);
+        Ok(approvals)
+    }
+
+    fn scan_approvals(&self) -> Result<Vec<ApprovalRecord>, sled::Error> {
+        let mut approvals = Vec::new();
+        let mut cursor = None;
+        loop {
+            let page = self.scan_prefix(KbType::Ethos.slot_id(), keys::APPROVAL_PREFIX, cursor.as_deref(), SCAN_MAX_LIMIT)?;
+            approvals.extend(page.entries.iter().filter_map(|(_, b)| ApprovalRecord::from_bytes(b)));
+            match page.next_cursor {
+                Some(next) => cursor = Some(next),
+                None => break,
+            }
+        }
+        Ok(approvals)
+    }
+
+    /// Records an operator's decision on a pending approval. Returns None when there is no such
+    /// approval; fails when it was already decided.
+    pub fn decide_approval(
+        &self,
+        approval_id: &str,
+        approve: bool,
+        decided_by: &str,
+        note: Option<&str>,
+    ) -> Result<Option<ApprovalRecord>, sled::Error> {
+        let key = keys::approval_key(approval_id);
+        let decided_at_ms = self.now_ms();
+        self.transaction(KbType::Ethos.slot_id(), |tx| {
+            let Some(mut approval) = tx.get(&key)?.and_then(|b| ApprovalRecord::from_bytes(&b)) else {
+                return Ok(None);
+            };
+            if approval.status != ApprovalStatus::Pending {
+                return Err(abort_transaction(format!(
+                    "approval {} is already {}",
+                    approval_id,
+                    approval.status.as_str()
+                )));
+            }
+            approval.status = if approve { ApprovalStatus::Approved } else { ApprovalStatus::Denied };
+            approval.decided_at_ms = Some(decided_at_ms);
+            approval.decided_by = Some(decided_by.to_string());
+            approval.note = note.map(str::to_string);
+            tx.insert(&key, &approval.to_bytes())?;
+            Ok(Some(approval))
+        })
+    }
+
+    /// Applies `update` to an approval (e.g. to attach the goal to resume or its job id).
+    pub fn update_approval(
+        &self,
+        approval_id: &str,
+        update: impl Fn(&mut ApprovalRecord),
+    ) -> Result<Option<ApprovalRecord>, sled::Error> {
+        let key = keys::approval_key(approval_id);
+        self.transaction(KbType::Ethos.slot_id(), |tx| {
+            let Some(mut approval) = tx.get(&key)?.and_then(|b| ApprovalRecord::from_bytes(&b)) else {
+                return Ok(None);
+            };
+            update(&mut approval);
+            tx.insert(&key, &approval.to_bytes())?;
+            Ok(Some(approval))
+        })
+    }
+
+    // ─────────────────────────────────────────────────────────────────────────
+    // API keys (Ethos) — gateway credentials with a role
+    // ─────────────────────────────────────────────────────────────────────────
+
+    /// Issues an API key. Returns its record and the secret, which is not stored: only its hash
+    /// is, so the secret cannot be shown again. A `tenant` key needs `tenant_id`; other roles take
+    /// none.
+    pub fn create_api_key(
+        &self,
+        name: &str,
+        role: Role,
+        tenant_id: Option<&str>,
+        created_by: Option<&str>,
+    ) -> Result<(ApiKeyRecord, String), sled::Error> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(sled::Error::Unsupported("API key name must not be empty".to_string()));
+        }
+        let tenant_id = tenant_id.map(str::trim).filter(|t| !t.is_empty());
+        role.check_tenant_binding(tenant_id).map_err(sled::Error::Unsupported)?;
+        let secret = format!("{}{}{}", API_KEY_SECRET_PREFIX, Uuid::new_v4().simple(), Uuid::new_v4().simple());
+        let record = ApiKeyRecord {
+            key_id: Uuid::new_v4().simple().to_string(),
+            name: name.to_string(),
+            role,
+            tenant_id: tenant_id.map(str::to_string),
+            key_prefix: secret.chars().take(API_KEY_SECRET_PREFIX.len() + 6).collect(),
+            created_at_ms: self.now_ms(),
+            created_by: created_by.map(str::to_string),
+            revoked_at_ms: None,
+        };
+        let record_key = keys::api_key_key(&record.key_id);
+        let hash_key = keys::api_key_hash_key(&hash_api_key(&secret));
+        self.transaction(KbType::Ethos.slot_id(), |tx| {
+            tx.insert(&record_key, &record.to_bytes())?;
+            tx.insert(&hash_key, record.key_id.as_bytes())?;
+            Ok(())
+        })?;
+        Ok((record, secret))
+    }
+
+    /// The active key whose secret is `secret`, if any.
+    pub fn authenticate_api_key(&self, secret: &str) -> Option<ApiKeyRecord> {
+        let slot_id = KbType::Ethos.slot_id();
+        let key_id = self.get(slot_id, &keys::api_key_hash_key(&hash_api_key(secret))).ok().flatten()?;
+        self.get_api_key(std::str::from_utf8(&key_id).ok()?).filter(ApiKeyRecord::is_active)
+    }
+
+    /// Whether any key can authenticate (the gateway enforces roles once one exists).
+    pub fn has_api_keys(&self) -> bool {
+        self.scan_prefix(KbType::Ethos.slot_id(), keys::API_KEY_HASH_PREFIX, None, 1)
+            .is_ok_and(|page| !page.entries.is_empty())
+    }
+
+    pub fn get_api_key(&self, key_id: &str) -> Option<ApiKeyRecord> {
+        self.get(KbType::Ethos.slot_id(), &keys::api_key_key(key_id))
+            .ok()
+            .flatten()
+            .and_then(|b| ApiKeyRecord::from_bytes(&b))
+    }
+
+    /// Every key, revoked ones included, newest first.
+    pub fn list_api_keys(&self) -> Result<Vec<ApiKeyRecord>, sled::Error> {
+        let mut records = Vec::new();
+        let mut cursor = None;
+        loop {
+            let page = self.scan_prefix(KbType::Ethos.slot_id(), keys::API_KEY_PREFIX, cursor.as_deref(), SCAN_MAX_LIMIT)?;
+            records.extend(page.entries.iter().filter_map(|(_, b)| ApiKeyRecord::from_bytes(b)));
+            match page.next_cursor {
+                Some(next) => cursor = Some(next),
+                None => break,
+            }
+        }
+        records.sort_by_key(|r| std::cmp::Reverse(r.created_at_ms));
+        Ok(records)
+    }
+
+    /// Revokes a key: its hash is dropped, so it stops authenticating at once. Returns None when
+    /// there is no such key; revoking twice keeps the first revocation time.
+    pub fn revoke_api_key(&self, key_id: &str) -> Result<Option<ApiKeyRecord>, sled::Error> {
+        let slot_id = KbType::Ethos.slot_id();
+        let mut hash_keys = Vec::new();
+        let mut cursor = None;
+        loop {
+            let page = self.scan_prefix(slot_id, keys::API_KEY_HASH_PREFIX, cursor.as_deref(), SCAN_MAX_LIMIT)?;
+            hash_keys.extend(page.entries.into_iter().filter(|(_, v)| v == key_id.as_bytes()).map(|(k, _)| k));
+            match page.next_cursor {
+                Some(next) => cursor = Some(next),
+                None => break,
+            }
+        }
+        let record_key = keys::api_key_key(key_id);
+        let revoked_at_ms = self.now_ms();
+        self.transaction(slot_id, |tx| {
+            let Some(mut record) = tx.get(&record_key)?.and_then(|b| ApiKeyRecord::from_bytes(&b)) else {
+                return Ok(None);
+            };
+            for hash_key in &hash_keys {
+                tx.remove(hash_key)?;
+            }
+            if record.revoked_at_ms.is_none() {
+                record.revoked_at_ms = Some(revoked_at_ms);
+                tx.insert(&record_key, &record.to_bytes())?;
+            }
+            Ok(Some(record))
+        })
+    }
+
+    /// Returns the active philosophical policy from **KB_ETHOS**, if present.
+    /// Stored under key [`crate::ETHOS_POLICY_KEY`] (`ethos/current`).
+    pub fn get_ethos_philosophical_policy(&self) -> Option<crate::EthosPolicy> {
+        let slot_id = KbType::Ethos.slot_id();
+        self.get(slot_id, crate::ETHOS_POLICY_KEY)
+            .ok()
+            .flatten()
+            .and_then(|b| crate::EthosPolicy::from_bytes(&b))
+    }
+
+    /// Writes the philosophical policy to **KB_ETHOS** under `ethos/current`.
+    pub fn set_ethos_philosophical_policy(
+        &self,
+        policy: &crate::EthosPolicy,
+    ) -> Result<(), sled::Error> {
+        let slot_id = KbType::Ethos.slot_id();
+        self.insert(slot_id, crate::ETHOS_POLICY_KEY, &policy.to_bytes())?;
+        Ok(())
+    }
+
+    /// Returns the relation record from **KB_KARDIA** for (owner_agent_id, target_id).
+    /// Use owner_agent_id = "default" for single-agent mode.
+    pub fn get_kardia_relation(
+        &self,
+        owner_agent_id: &str,
+        target_id: &str,
+    ) -> Option<RelationRecord> {
+        let slot_id = KbType::Kardia.slot_id();
+        let key = kardia_relation_key(owner_agent_id, target_id);
+        self.get(slot_id, &key).ok().flatten().and_then(|b| RelationRecord::from_bytes(&b))
+    }
+
+    /// Writes the relation record to **KB_KARDIA** under (owner_agent_id, record.user_id).
+    pub fn set_kardia_relation(
+        &self,
+        owner_agent_id: &str,
+        record: &RelationRecord,
+    ) -> Result<(), sled::Error> {
+        let slot_id = KbType::Kardia.slot_id();
+        let key = kardia_relation_key(owner_agent_id, &record.user_id);
+        self.insert(slot_id, &key, &record.to_bytes())?;
+        Ok(())
+    }
+
+    /// Read-modify-write of the relation (owner_agent_id → target_id) in **KB_KARDIA** that does not
+    /// lose concurrent updates (see [`update`](Self::update)). A missing relation starts from
+    /// `RelationRecord::new(target_id, now)`; `last_updated_ms` is refreshed. Returns the record
+    /// written.
+    pub fn update_kardia_relation<F>(
+        &self,
+        owner_agent_id: &str,
+        target_id: &str,
+        mut f: F,
+    ) -> Result<RelationRecord, sled::Error>
+    where
+        F: FnMut(&mut RelationRecord),
+    {
+        let key = kardia_relation_key(owner_agent_id, target_id);
+        let mut written = None;
+        self.update(KbType::Kardia.slot_id(), &key, |current| {
+            let now = self.now_ms();
+            let mut record = current
+                .and_then(RelationRecord::from_bytes)
+                .unwrap_or_else(|| RelationRecord::new(target_id, now));
+            f(&mut record);
+            record.last_updated_ms = now;
+            let bytes = record.to_bytes();
+            written = Some(record);
+            Some(bytes)
+        })?;
+        Ok(written.unwrap_or_else(|| RelationRecord::new(target_id, self.now_ms())))
+    }
+
+    /// Key for a person in the Relational Map: `people/{name_slug}`.
+    pub fn kardia_person_key(name_slug: &str) -> String {
+        keys::person_key(name_slug)
+    }
+
+    /// Returns a **PersonRecord** from the Relational Map (KB_KARDIA) by name slug.
+    pub fn get_person(&self, name_slug: &str) -> Option<PersonRecord> {
+        let slot_id = KbType::Kardia.slot_id();
+        let key = Self::kardia_person_key(name_slug);
+        self.get(slot_id, &key)
+            .ok()
+            .flatten()
+            .and_then(|b| serde_json::from_slice(&b).ok())
+    }
+
+    /// Writes a **PersonRecord** to the Relational Map (KB_KARDIA) under `people/{name_slug}`.
+    pub fn set_person(&self, record: &PersonRecord) -> Result<(), sled::Error> {
+        let slot_id = KbType::Kardia.slot_id();
+        let slug = PersonRecord::name_slug(&record.name);
+        let key = Self::kardia_person_key(&slug);
+        let bytes = serde_json::to_vec(record).unwrap_or_default();
+        self.insert(slot_id, &key, &bytes)?;
+        Ok(())
+    }
+
+    /// Returns all **PersonRecord**s in the Relational Map (KB_KARDIA) with key prefix `people/`.
+    pub fn list_people(&self) -> Result<Vec<PersonRecord>, sled::Error> {
+        let slot_id = KbType::Kardia.slot_id();
+        let kv = self.scan_kv(slot_id)?;
+        let prefix = keys::PEOPLE_PREFIX;
+        let mut out: Vec<PersonRecord> = kv
+            .into_iter()
+            .filter(|(k, _)| k.starts_with(prefix))
+            .filter_map(|(_, bytes)| serde_json::from_slice(&bytes).ok())
+            .collect();
+        out.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(out)
+    }
+
+    /// Returns the **MentalState** (Emotional Context Layer) from **KB_KARDIA**.
+    /// Stored under a global key so the Cognitive Governor can modulate tone and demand.
+    pub fn get_mental_state(&self, _owner_agent_id: &str) -> MentalState {
+        let slot_id = KbType::Kardia.slot_id();
+        match self.get(slot_id, MENTAL_STATE_KEY) {
+            Ok(Some(bytes)) => serde_json::from_slice(&bytes).unwrap_or_default(),
+            _ => MentalState::default(),
+        }
+    }
+
+    /// Writes the **MentalState** to **KB_KARDIA**. Used by JournalSkill and gateway.
+    pub fn set_mental_state(&self, _owner_agent_id: &str, state: &MentalState) -> Result<(), sled::Error> {
+        let slot_id = KbType::Kardia.slot_id();
+        let bytes = serde_json::to_vec(state).unwrap_or_default();
+        self.insert(slot_id, MENTAL_STATE_KEY, &bytes)?;
+        Ok(())
+    }
+
+    /// Key in **KB_SOMA** (Slot 8) where the current BiometricState is stored (BioGate).
+    pub const BIOMETRIC_STATE_KEY: &str = "biometric/current";
+
+    /// Returns the **BiometricState** (Physical Load) from **KB_SOMA** (Slot 8).
+    pub fn get_biometric_state(&self) -> BiometricState {
+        let slot_id = KbType::Soma.slot_id();
+        match self.get(slot_id, Self::BIOMETRIC_STATE_KEY) {
+            Ok(Some(bytes)) => serde_json::from_slice(&bytes).unwrap_or_default(),
+            _ => BiometricState::default(),
+        }
+    }
+
+    /// Writes the **BiometricState** to **KB_SOMA** (Slot 8). Used by BioGateSync skill.
+    pub fn set_biometric_state(&self, state: &BiometricState) -> Result<(), sled::Error> {
+        let slot_id = KbType::Soma.slot_id();
+        let bytes = serde_json::to_vec(state).unwrap_or_default();
+        self.insert(slot_id, Self::BIOMETRIC_STATE_KEY, &bytes)?;
+        Ok(())
+    }
+
+    /// Key in **KB_SOMA** (Slot 8) where the current SomaState is stored (BioGate v2).
+    pub const SOMA_STATE_KEY: &str = "soma/current";
+
+    /// Returns the **SomaState** (BioGate health metrics) from **KB_SOMA** (Slot 8).
+    pub fn get_soma_state(&self) -> SomaState {
+        let slot_id = KbType::Soma.slot_id();
+        match self.get(slot_id, Self::SOMA_STATE_KEY) {
+            Ok(Some(bytes)) => serde_json::from_slice(&bytes).unwrap_or_default(),
+            _ => SomaState::default(),
+        }
+    }
+
+    /// Writes the **SomaState** to **KB_SOMA** (Slot 8). Used by BioGateSync skill.
+    pub fn set_soma_state(&self, state: &SomaState) -> Result<(), sled::Error> {
+        let slot_id = KbType::Soma.slot_id();
+        let bytes = serde_json::to_vec(state).unwrap_or_default();
+        self.insert(slot_id, Self::SOMA_STATE_KEY, &bytes)?;
+        Ok(())
+    }
+
+    /// Returns the **effective** MentalState for the Cognitive Governor: Kardia baseline
+    /// merged with Soma (BioGate) physical load.
+    ///
+    /// **Cross-layer reaction (BioGate v2 — SomaState):**
+    /// If `readiness_score < 50` **OR** `sleep_hours < 6.0`:
+    /// - `burnout_risk` is incremented by **+0.15**
+    /// - `grace_multiplier` is set to **1.6**
+    ///
+    /// **Legacy fallback (BiometricState):**
+    /// If `sleep_score < 60`, burnout_risk is increased by 0.2 and grace_multiplier set to 1.5.
+    pub fn get_effective_mental_state(&self, owner_agent_id: &str) -> MentalState {
+        let mut mental = self.get_mental_state(owner_agent_id);
+
+        // BioGate v2: SomaState cross-layer reaction (takes priority)
+        let soma = self.get_soma_state();
+        if soma.needs_biogate_adjustment() {
+            mental.burnout_risk = (mental.burnout_risk + SomaState::BURNOUT_RISK_INCREMENT).min(1.0);
+            mental.grace_multiplier = SomaState::GRACE_MULTIPLIER_OVERRIDE;
+        } else {
+            // Legacy fallback: BiometricState
+            let bio = self.get_biometric_state();
+            if bio.poor_sleep() {
+                mental.burnout_risk = (mental.burnout_risk + 0.2).min(1.0);
+                mental.grace_multiplier = 1.5;
+            }
+        }
+
+        mental.clamp();
+        mental
+    }
+
+    /// Pushes an inter-agent message to **KB_SOMA** (inbox for target agent).
+    /// Key: `inbox/{target_agent_id}/{timestamp_ms}_{uuid}`. Returns the message id.
+    pub fn push_agent_message(
+        &self,
+        from_agent_id: &str,
+        target_agent_id: &str,
+        payload: &serde_json::Value,
+    ) -> Result<String, sled::Error> {
+        let msg = self.message(from_agent_id, target_agent_id, payload);
+        self.insert(KbType::Soma.slot_id(), &msg.inbox_key(), &msg.to_bytes())?;
+        Ok(msg.id)
+    }
+
+    /// Returns the most recent messages for an agent from **KB_SOMA** inbox, newest first,
+    /// including the underlying inbox key.
+    ///
+    /// This is primarily used by the Heartbeat so it can mark messages as processed
+    /// without deleting them (preserving KB_SOMA history).
+    pub fn get_agent_messages_with_keys(
+        &self,
+        target_agent_id: &str,
+        limit: usize,
+    ) -> Result<Vec<(String, AgentMessage)>, sled::Error> {
+        let slot_id = KbType::Soma.slot_id();
+        let prefix = keys::inbox_prefix(target_agent_id);
+        Ok(self
+            .scan_prefix_rev(slot_id, &prefix, None, limit)?
+            .entries
+            .into_iter()
+            .filter_map(|(k, bytes)| AgentMessage::from_bytes(&bytes).map(|m| (k, m)))
+            .collect())
+    }
+
+    /// Returns the most recent messages for an agent from **KB_SOMA** inbox, newest first.
+    pub fn get_agent_messages(
+        &self,
+        target_agent_id: &str,
+        limit: usize,
+    ) -> Result<Vec<AgentMessage>, sled::Error> {
+        Ok(self
+            .get_agent_messages_with_keys(target_agent_id, limit)?
+            .into_iter()
+            .map(|(_, m)| m)
+            .collect())
+    }
+
+    /// Records a failed attempt at handling the inbox message at `inbox_key`: it is retried with
+    /// exponential backoff ([`INBOX_RETRY_BASE_MS`] doubling, at most [`INBOX_RETRY_MAX_MS`]) and
+    /// moved to the agent's dead letters (`inbox_dlq/{agent_id}/...`) after [`INBOX_MAX_ATTEMPTS`]
+    /// failures. Returns `None` when the message is gone or already processed.
+    pub fn record_inbox_failure(&self, inbox_key: &str, error: &str) -> Result<Option<InboxRetry>, sled::Error> {
+        let now = self.now_ms();
+        self.transaction(KbType::Soma.slot_id(), |tx| {
+            let Some(mut msg) = tx.get(inbox_key)?.and_then(|b| AgentMessage::from_bytes(&b)) else {
+                return Ok(None);
+            };
+            if msg.is_processed {
+                return Ok(None);
+            }
+            msg.attempts += 1;
+            msg.last_error = Some(error.to_string());
+            if msg.attempts >= INBOX_MAX_ATTEMPTS {
+                msg.next_attempt_ms = None;
+                tx.remove(inbox_key)?;
+                tx.insert(
+                    &keys::inbox_dlq_key(&msg.target_agent_id, msg.timestamp_ms, &msg.id),
+                    &msg.to_bytes(),
+                )?;
+                return Ok(Some(InboxRetry::DeadLettered { attempts: msg.attempts }));
+            }
+            let backoff = INBOX_RETRY_BASE_MS
+                .saturating_mul(1 << (msg.attempts - 1).min(20))
+                .min(INBOX_RETRY_MAX_MS);
+            let next_attempt_ms = now + backoff;
+            msg.next_attempt_ms = Some(next_attempt_ms);
+            tx.insert(inbox_key, &msg.to_bytes())?;
+            Ok(Some(InboxRetry::Scheduled {
+                attempts: msg.attempts,
+                next_attempt_ms,
+            }))
+        })
+    }
+
+    /// The agent's dead-lettered messages (**KB_SOMA** `inbox_dlq/{agent_id}/...`), newest first.
+    pub fn get_dead_letters(&self, agent_id: &str, limit: usize) -> Result<Vec<AgentMessage>, sled::Error> {
+        Ok(self
+            .scan_prefix_rev(KbType::Soma.slot_id(), &keys::inbox_dlq_prefix(agent_id), None, limit)?
+            .entries
+            .into_iter()
+            .filter_map(|(_, bytes)| AgentMessage::from_bytes(&bytes))
+            .collect())
+    }
+
+    /// Moves dead-lettered message `message_id` back into the agent's inbox with its attempts
+    /// reset (`last_error` is kept for reference). Returns the requeued message, or `None` when
+    /// the agent has no such dead letter.
+    pub fn requeue_dead_letter(&self, agent_id: &str, message_id: &str) -> Result<Option<AgentMessage>, sled::Error> {
+        let slot_id = KbType::Soma.slot_id();
+        let prefix = keys::inbox_dlq_prefix(agent_id);
+        let mut cursor = None;
+        let dlq_key = loop {
+            let page = self.scan_prefix(slot_id, &prefix, cursor.as_deref(), SCAN_MAX_LIMIT)?;
+            if let Some((key, _)) = page
+                .entries
+                .iter()
+                .find(|(k, _)| keys::parse_inbox_dlq_key(k).is_some_and(|k| k.id == message_id))
+            {
+                break key.clone();
+            }
+            match page.next_cursor {
+                Some(next) => cursor = Some(next),
+                None => return Ok(None),
+            }
+        };
+        self.transaction(slot_id, |tx| {
+            let Some(mut msg) = tx.get(&dlq_key)?.and_then(|b| AgentMessage::from_bytes(&b)) else {
+                return Ok(None);
+            };
+            msg.attempts = 0;
+            msg.next_attempt_ms = None;
+            tx.remove(&dlq_key)?;
+            tx.insert(&msg.inbox_key(), &msg.to_bytes())?;
+            Ok(Some(msg))
+        })
+    }
+
+    /// Agents with at least one **KB_SOMA** inbox message, in key order. Seeks from one agent's
+    /// inbox to the next, so it reads one key per agent rather than every message.
+    pub fn inbox_agent_ids(&self) -> Result<Vec<String>, sled::Error> {
+        let slot_id = KbType::Soma.slot_id();
+        let mut agents = Vec::new();
+        let mut cursor: Option<String> = None;
+        loop {
+            let page = self.scan_prefix(slot_id, keys::INBOX_PREFIX, cursor.as_deref(), 1)?;
+            let Some((key, _)) = page.entries.first() else {
+                return Ok(agents);
+            };
+            let agent_id = match keys::parse_inbox_key(key) {
+                Some(inbox) => inbox.agent_id,
+                // Not a message key: step past it one key at a time.
+                None => {
+                    cursor = Some(key.clone());
+                    continue;
+                }
+            };
+            // `inbox/{agent_id}0` sorts after every `inbox/{agent_id}/...` key ('0' follows '/').
+            cursor = Some(format!("{}{}0", keys::INBOX_PREFIX, agent_id));
+            agents.push(agent_id);
+        }
+    }
+
+    /// Publishes `payload` from `from_agent_id` to `topic` (**KB_SOMA** `topic/{topic}/...`); the
+    /// Heartbeat copies it to every subscriber's inbox. Returns the message id.
+    pub fn publish_topic_message(
+        &self,
+        from_agent_id: &str,
+        topic: &str,
+        payload: &serde_json::Value,
+    ) -> Result<String, sled::Error> {
+        if !is_valid_topic_name(topic) {
+            return Err(sled::Error::Unsupported(format!("invalid topic name {:?}", topic)));
+        }
+        let _guard = self.publish_lock.lock().unwrap_or_else(|e| e.into_inner());
+        let mut msg = self.message(from_agent_id, "", payload);
+        msg.topic = Some(topic.to_string());
+        msg.timestamp_ms = self.next_topic_timestamp_ms(topic)?;
+        let key = keys::topic_key(topic, msg.timestamp_ms, &msg.id);
+        self.insert(KbType::Soma.slot_id(), &key, &msg.to_bytes())?;
+        Ok(msg.id)
+    }
+
+    /// Now, but strictly after the newest message persisted for `topic`: a subscription's fan-out
+    /// cursor is the last key it delivered, so a later message must never sort before it (two
+    /// messages in the same millisecond would otherwise order by their random ids). Callers hold
+    /// `publish_lock`.
+    fn next_topic_timestamp_ms(&self, topic: &str) -> Result<i64, sled::Error> {
+        let newest = self
+            .scan_prefix_rev(KbType::Soma.slot_id(), &keys::topic_prefix(topic), None, 1)?
+            .entries
+            .first()
+            .and_then(|(key, _)| keys::parse_topic_key(key))
+            .map(|parsed| parsed.timestamp_ms);
+        let now = self.now_ms();
+        Ok(newest.map_or(now, |newest| now.max(newest + 1)))
+    }
+
+    /// Subscribes `agent_id` to `topic` from now on (messages already published are not
+    /// delivered). Subscribing again keeps the existing subscription.
+    pub fn subscribe_agent(&self, agent_id: &str, topic: &str) -> Result<TopicSubscription, sled::Error> {
+        if !is_valid_topic_name(topic) || !keys::is_segment(agent_id) {
+            return Err(sled::Error::Unsupported(format!("invalid subscription {:?} -> {:?}", agent_id, topic)));
+        }
+        let slot_id = KbType::Soma.slot_id();
+        let latest = self
+            .scan_prefix_rev(slot_id, &keys::topic_prefix(topic), None, 1)?
+            .entries
+            .into_iter()
+            .next()
+            .map(|(key, _)| key);
+        let key = keys::subscription_key(agent_id, topic);
+        let subscription = TopicSubscription {
+            agent_id: agent_id.to_string(),
+            topic: topic.to_string(),
+            created_at_ms: self.now_ms(),
+            delivered_through: latest,
+        };
+        self.transaction(slot_id, |tx| {
+            if let Some(existing) = tx.get(&key)?.and_then(|b| TopicSubscription::from_bytes(&b)) {
+                return Ok(existing);
+            }
+            tx.insert(&key, &subscription.to_bytes())?;
+            Ok(subscription.clone())
+        })
+    }
+
+    /// Ends `agent_id`'s subscription to `topic`; returns whether it existed.
+    pub fn unsubscribe_agent(&self, agent_id: &str, topic: &str) -> Result<bool, sled::Error> {
+        Ok(self
+            .remove(KbType::Soma.slot_id(), &keys::subscription_key(agent_id, topic))?
+            .is_some())
+    }
+
+    /// `agent_id`'s topic subscriptions, by topic.
+    pub fn agent_subscriptions(&self, agent_id: &str) -> Result<Vec<TopicSubscription>, sled::Error> {
+        self.scan_subscriptions(&keys::subscriptions_prefix(agent_id))
+    }
+
+    /// Agents subscribed to `topic`.
+    pub fn topic_subscribers(&self, topic: &str) -> Result<Vec<String>, sled::Error> {
+        Ok(self
+            .scan_subscriptions(keys::SUBSCRIPTION_PREFIX)?
+            .into_iter()
+            .filter(|s| s.topic == topic)
+            .map(|s| s.agent_id)
+            .collect())
+    }
+
+    fn scan_subscriptions(&self, prefix: &str) -> Result<Vec<TopicSubscription>, sled::Error> {
+        let mut subscriptions = Vec::new();
+        let mut cursor = None;
+        loop {
+            let page = self.scan_prefix(KbType::Soma.slot_id(), prefix, cursor.as_deref(), SCAN_MAX_LIMIT)?;
+            subscriptions.extend(page.entries.iter().filter_map(|(_, b)| TopicSubscription::from_bytes(b)));
+            match page.next_cursor {
+                Some(next) => cursor = Some(next),
+                None => break,
+            }
+        }
+        Ok(subscriptions)
+    }
+
+    /// Copies new topic messages into their subscribers' inboxes (up to [`TOPIC_FANOUT_BATCH`] per
+    /// subscription and pass), skipping the subscriber's own messages. Each subscription's
+    /// deliveries and its cursor are written in one transaction, and a copy keeps the original's
+    /// key suffix, so nothing is delivered twice. Returns the number of messages delivered.
+    pub fn fan_out_topics(&self) -> Result<usize, sled::Error> {
+        let slot_id = KbType::Soma.slot_id();
+        let mut delivered = 0;
+        for subscription in self.scan_subscriptions(keys::SUBSCRIPTION_PREFIX)? {
+            let page = self.scan_prefix(
+                slot_id,
+                &keys::topic_prefix(&subscription.topic),
+                subscription.delivered_through.as_deref(),
+                TOPIC_FANOUT_BATCH,
+            )?;
+            let Some((last_key, _)) = page.entries.last() else {
+                continue;
+            };
+            let copies: Vec<AgentMessage> = page
+                .entries
+                .iter()
+                .filter_map(|(_, b)| AgentMessage::from_bytes(b))
+                .filter(|m| m.from_agent_id != subscription.agent_id)
+                .map(|m| AgentMessage {
+                    target_agent_id: subscription.agent_id.clone(),
+                    is_processed: false,
+                    topic: Some(subscription.topic.clone()),
+                    ..m
+                })
+                .collect();
+            let key = keys::subscription_key(&subscription.agent_id, &subscription.topic);
+            let written = self.transaction(slot_id, |tx| {
+                // Skip when the subscription was removed or advanced since it was read.
+                let Some(mut current) = tx.get(&key)?.and_then(|b| TopicSubscription::from_bytes(&b)) else {
+                    return Ok(0);
+                };
+                if current.delivered_through != subscription.delivered_through {
+                    return Ok(0);
+                }
+                for copy in &copies {
+                    tx.insert(&copy.inbox_key(), &copy.to_bytes())?;
+                }
+                current.delivered_through = Some(last_key.clone());
+                tx.insert(&key, &current.to_bytes())?;
+                Ok(copies.len())
+            })?;
+            delivered += written;
+        }
+        Ok(delivered)
+    }
+
+    /// The Heartbeat's topic step, shared by the gateway and the daemon loops:
+    /// [`fan_out_topics`](Self::fan_out_topics) with the outcome logged. A failure is logged, not
+    /// returned, so the rest of the tick still runs. Returns the number of messages delivered.
+    pub fn heartbeat_fan_out_topics(&self) -> usize {
+        match self.fan_out_topics() {
+            Ok(delivered) => {
+                if delivered > 0 {
+                    tracing::debug!(target: "pagi::knowledge", delivered, "Fanned out topic messages");
+                }
+                delivered
+            }
+            Err(e) => {
+                tracing::warn!(target: "pagi::knowledge", error = %e, "Topic fan-out failed");
+                0
+            }
+        }
+    }
+
+    /// Returns the exchange between `agent_id` and `peer_id` (both inboxes), oldest first,
+    /// keeping only the most recent `limit` messages.
+    pub fn get_agent_thread(
+        &self,
+        agent_id: &str,
+        peer_id: &str,
+        limit: usize,
+    ) -> Result<Vec<AgentMessage>, sled::Error> {
+        let slot_id = KbType::Soma.slot_id();
+        let mine = keys::inbox_prefix(agent_id);
+        let theirs = keys::inbox_prefix(peer_id);
+        let mut thread: Vec<AgentMessage> = self
+            .scan_kv(slot_id)?
+            .into_iter()
+            .filter_map(|(k, bytes)| {
+                let msg = AgentMessage::from_bytes(&bytes)?;
+                let relevant = (k.starts_with(&mine) && msg.from_agent_id == peer_id)
+                    || (k.starts_with(&theirs) && msg.from_agent_id == agent_id);
+                relevant.then_some(msg)
+            })
+            .collect();
+        thread.sort_by_key(|m| m.timestamp_ms);
+        let skip = thread.len().saturating_sub(limit);
+        Ok(thread.into_iter().skip(skip).collect())
+    }
+
+    /// Text stored under an agent-scoped Pneuma key: a [`KbRecord`]'s content or a raw UTF-8 string.
+    fn get_agent_pneuma_text(&self, key: &str) -> Option<String> {
+        let bytes = self.get(KbType::Pneuma.slot_id(), key).ok()??;
+        let text = match KbRecord::from_bytes(&bytes) {
+            Some(record) => record.content,
+            None => String::from_utf8(bytes).ok()?,
+        };
+        let text = text.trim();
+        (!text.is_empty()).then(|| text.to_string())
+    }
+
+    /// Auto-reply template for `agent_id` (see [`DEFAULT_AUTO_REPLY_TEMPLATE`] for placeholders).
+    pub fn get_auto_reply_template(&self, agent_id: &str) -> String {
+        self.get_agent_pneuma_text(&auto_reply_template_key(agent_id))
+            .unwrap_or_else(|| DEFAULT_AUTO_REPLY_TEMPLATE.to_string())
+    }
+
+    /// Stores a per-agent auto-reply template in **KB_PNEUMA**.
+    pub fn set_auto_reply_template(&self, agent_id: &str, template: &str) -> Result<(), sled::Error> {
+        self.insert(
+            KbType::Pneuma.slot_id(),
+            &auto_reply_template_key(agent_id),
+            template.as_bytes(),
+        )?;
+        Ok(())
+    }
+
+    /// Stores a per-agent persona in **KB_PNEUMA**; it replaces `core_persona` in that agent's Mission Directive.
+    pub fn set_agent_persona(&self, agent_id: &str, persona: &str) -> Result<(), sled::Error> {
+        self.insert(KbType::Pneuma.slot_id(), &agent_persona_key(agent_id), persona.as_bytes())?;
+        Ok(())
+    }
+
+    /// The agent's [`PersonaProfile`] from **KB_PNEUMA**, if one was saved.
+    pub fn get_persona_profile(&self, agent_id: &str) -> Option<PersonaProfile> {
+        self.get(KbType::Pneuma.slot_id(), &agent_persona_profile_key(agent_id))
+            .ok()
+            .flatten()
+            .and_then(|b| PersonaProfile::from_bytes(&b))
+    }
+
+    /// Validates and stores `profile` as the agent's persona (stamping `agent_id` and
+    /// `updated_at_ms`); it replaces the plain-text persona in the agent's Mission Directive.
+    pub fn put_persona_profile(&self, agent_id: &str, profile: &PersonaProfile) -> Result<PersonaProfile, sled::Error> {
+        if !keys::is_segment(agent_id) {
+            return Err(sled::Error::Unsupported(format!("invalid agent id {:?}", agent_id)));
+        }
+        profile.validate().map_err(|e| sled::Error::Unsupported(e.to_string()))?;
+        let mut saved = profile.clone();
+        saved.agent_id = agent_id.to_string();
+        saved.updated_at_ms = self.now_ms();
+        self.insert(KbType::Pneuma.slot_id(), &agent_persona_profile_key(agent_id), &saved.to_bytes())?;
+        Ok(saved)
+    }
+
+    /// Removes the agent's [`PersonaProfile`]; returns whether one existed.
+    pub fn remove_persona_profile(&self, agent_id: &str) -> Result<bool, sled::Error> {
+        Ok(self
+            .remove(KbType::Pneuma.slot_id(), &agent_persona_profile_key(agent_id))?
+            .is_some())
+    }
+
+    /// Builds the Heartbeat auto-reply for `msg` (received by `agent_id`) through the same context
+    /// assembly as chat: the Mission Directive toward the sender (identity, Kardia relationship,
+    /// mental state) as system prompt, and the agent's template filled with the thread history.
+    pub fn build_auto_reply_prompt(&self, agent_id: &str, msg: &AgentMessage) -> AutoReplyPrompt {
+        let sender = msg.from_agent_id.as_str();
+        let thread: Vec<String> = self
+            .get_agent_thread(agent_id, sender, AUTO_REPLY_THREAD_LIMIT + 1)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|m| m.id != msg.id)
+            .map(|m| format!("{}: {}", m.from_agent_id, m.text()))
+            .collect();
+        let skip = thread.len().saturating_sub(AUTO_REPLY_THREAD_LIMIT);
+        let thread = if thread.is_empty() {
+            "(no earlier messages)".to_string()
+        } else {
+            thread[skip..].join("\n")
+        };
+        let prompt = render_template(
+            &self.get_auto_reply_template(agent_id),
+            &[
+                ("agent_id", agent_id),
+                ("sender", sender),
+                ("thread", &thread),
+                ("message", &msg.text()),
+            ],
+        );
+        AutoReplyPrompt {
+            system_prompt: self.build_system_directive(agent_id, sender),
+            prompt,
+        }
+    }
+
+    // ─────────────────────────────────────────────────────────────────────────
+    // Standing Queries (Soma) — saved KnowledgeQuery subscriptions with change alerts
+    // ─────────────────────────────────────────────────────────────────────────
+
+    /// Stores a [`StandingQuery`] in **KB_SOMA** under `standing_query/{id}`.
+    pub fn set_standing_query(&self, query: &StandingQuery) -> Result<(), sled::Error> {
+        if !(1..=8).contains(&query.slot_id) {
+            return Err(sled::Error::Unsupported(format!(
+                "standing queries can watch slots 1-8 (got {})",
+                query.slot_id
+            )));
+        }
+        let key = format!("{}{}", STANDING_QUERY_PREFIX, query.id);
+        self.insert(KbType::Soma.slot_id(), &key, &query.to_bytes())?;
+        Ok(())
+    }
+
+    /// Retrieves a [`StandingQuery`] by id.
+    pub fn get_standing_query(&self, id: &str) -> Option<StandingQuery> {
+        let key = format!("{}{}", STANDING_QUERY_PREFIX, id);
+        self.get(KbType::Soma.slot_id(), &key)
+            .ok()
+            .flatten()
+            .and_then(|b| StandingQuery::from_bytes(&b))
+    }
+
+    /// Returns all standing queries, oldest first.
+    pub fn list_standing_queries(&self) -> Result<Vec<StandingQuery>, sled::Error> {
+        let mut queries: Vec<StandingQuery> = self
+            .scan_kv(KbType::Soma.slot_id())?
+            .into_iter()
+            .filter(|(k, _)| k.starts_with(STANDING_QUERY_PREFIX))
+            .filter_map(|(_, bytes)| StandingQuery::from_bytes(&bytes))
+            .collect();
+        queries.sort_by_key(|q| q.created_at_ms);
+        Ok(queries)
+    }
+
+    /// Removes a standing query by id. Returns whether it existed.
+    pub fn remove_standing_query(&self, id: &str) -> Result<bool, sled::Error> {
+        let key = format!("{}{}", STANDING_QUERY_PREFIX, id);
+        Ok(self.remove(KbType::Soma.slot_id(), &key)?.is_some())
+    }
+
+    /// Evaluates a standing query against its slot. Returns `(fingerprint, matching entries)`
+    /// with entries sorted by key. Standing-query records themselves are never matched.
+    pub fn evaluate_standing_query(
+        &self,
+        query: &StandingQuery,
+    ) -> Result<(String, StandingQueryMatches), sled::Error> {
+        let mut entries: Vec<(String, Vec<u8>)> = match &query.selector {
+            QuerySelector::Key { key } => self
+                .get(query.slot_id, key)?
+                .map(|v| vec![(key.clone(), v)])
+                .unwrap_or_default(),
+            QuerySelector::Prefix { prefix } => self
+                .scan_kv(query.slot_id)?
+                .into_iter()
+                .filter(|(k, _)| k.starts_with(prefix.as_str()))
+                .collect(),
+        };
+        if query.slot_id == KbType::Soma.slot_id() {
+            entries.retain(|(k, _)| !k.starts_with(STANDING_QUERY_PREFIX));
+        }
+        entries.sort_by(|a, b| a.0.cmp(&b.0));
+        Ok((fnv1a_64(&entries), entries))
+    }
+
+    /// Re-evaluates every enabled standing query, persists the new fingerprints, and returns
+    /// the queries whose results changed. A query's first evaluation records a baseline only.
+    pub fn check_standing_queries(&self) -> Result<Vec<StandingQueryChange>, sled::Error> {
+        let now = self.now_ms();
+        let mut changes = Vec::new();
+        for mut query in self.list_standing_queries()? {
+            if !query.enabled {
+                continue;
+            }
+            let (fingerprint, entries) = self.evaluate_standing_query(&query)?;
+            let previous = query.last_fingerprint.clone();
+            let changed = previous.as_deref().is_some_and(|p| p != fingerprint);
+            query.last_checked_ms = Some(now);
+            if previous.as_deref() == Some(fingerprint.as_str()) {
+                // Unchanged: skip the write so quiet queries don't churn KB_SOMA every tick.
+                continue;
+            }
+            query.last_fingerprint = Some(fingerprint);
+            if changed {
+                query.last_changed_ms = Some(now);
+            }
+            self.set_standing_query(&query)?;
+            if changed {
+                let snapshot: serde_json::Map<String, serde_json::Value> = entries
+                    .iter()
+                    .take(STANDING_QUERY_SNAPSHOT_LIMIT)
+                    .map(|(k, v)| {
+                        let value = serde_json::from_slice(v)
+                            .unwrap_or_else(|_| serde_json::Value::String(String::from_utf8_lossy(v).into_owned()));
+                        (k.clone(), value)
+                    })
+                    .collect();
+                tracing::info!(
+                    target: "pagi::knowledge",
+                    query_id = %query.id,
+                    name = %query.name,
+                    matches = entries.len(),
+                    "Standing query results changed"
+                );
+                changes.push(StandingQueryChange {
+                    query,
+                    previous_fingerprint: previous,
+                    snapshot: serde_json::Value::Object(snapshot),
+                    match_count: entries.len(),
+                });
+            }
+        }
+        Ok(changes)
+    }
+
+    // ─────────────────────────────────────────────────────────────────────────
+    // Idempotency keys (Soma) — cached request outcomes for safe client retries
+    // ─────────────────────────────────────────────────────────────────────────
+
+    /// Claims `key` for a request with `fingerprint`. The claim is atomic, so of two concurrent
+    /// requests with the same key only one gets [`IdempotencyClaim::Claimed`]. Expired entries
+    /// (past `ttl_ms`, or claims abandoned for [`IDEMPOTENCY_CLAIM_TIMEOUT_MS`]) count as free.
+    pub fn claim_idempotency_key(
+        &self,
+        tenant_id: &str,
+        key: &str,
+        fingerprint: &str,
+        ttl_ms: i64,
+    ) -> Result<IdempotencyClaim, sled::Error> {
+        let db_key = keys::idempotency_key(tenant_id, key);
+        keys::validate_key(KbType::Soma.slot_id(), &db_key).map_err(|e| sled::Error::Unsupported(e.to_string()))?;
+        let tree = self.db.open_tree(Self::tree_name(KbType::Soma.slot_id()))?;
+        loop {
+            let now = self.now_ms();
+            let current = tree.get(db_key.as_bytes())?;
+            let decoded = current.as_ref().map(|v| self.decode_value(KbType::Soma.slot_id(), v)).transpose()?;
+            if let Some(existing) = decoded.as_ref().and_then(|v| IdempotencyRecord::from_bytes(v)) {
+                if !existing.is_expired(now) {
+                    return Ok(if existing.request_fingerprint != fingerprint {
+                        IdempotencyClaim::Mismatch
+                    } else {
+                        match existing.response {
+                            Some(response) => IdempotencyClaim::Completed(response),
+                            None => IdempotencyClaim::InProgress,
+                        }
+                    });
+                }
+            }
+            let claim = IdempotencyRecord {
+                tenant_id: tenant_id.to_string(),
+                key: key.to_string(),
+                request_fingerprint: fingerprint.to_string(),
+                response: None,
+                created_at_ms: now,
+                expires_at_ms: now + ttl_ms.max(0),
+            };
+            let encoded = self.encode_value(KbType::Soma.slot_id(), &claim.to_bytes())?.into_owned();
+            if tree.compare_and_swap(db_key.as_bytes(), current.as_deref(), Some(&encoded))?.is_ok() {
+                tracing::debug!(target: "pagi::knowledge", key = %db_key, "KB-8 [Soma] idempotency key claimed");
+                return Ok(IdempotencyClaim::Claimed);
+            }
+            // Lost a race with another writer; re-read.
+        }
+    }
+
+    /// Stores the response of a claimed key; retries with the key get it until the TTL expires.
+    pub fn complete_idempotency_key(
+        &self,
+        tenant_id: &str,
+        key: &str,
+        response: &serde_json::Value,
+    ) -> Result<(), sled::Error> {
+        let db_key = keys::idempotency_key(tenant_id, key);
+        let slot = KbType::Soma.slot_id();
+        let Some(mut record) = self.get(slot, &db_key)?.and_then(|b| IdempotencyRecord::from_bytes(&b)) else {
+            return Err(sled::Error::Unsupported(format!("idempotency key {:?} was not claimed", key)));
+        };
+        record.response = Some(response.clone());
+        self.insert(slot, &db_key, &record.to_bytes())?;
+        Ok(())
+    }
+
+    /// Drops a claim without a response (the request did not run), so the key can be retried.
+    pub fn release_idempotency_key(&self, tenant_id: &str, key: &str) -> Result<(), sled::Error> {
+        self.remove(KbType::Soma.slot_id(), &keys::idempotency_key(tenant_id, key))?;
+        Ok(())
+    }
+
+    /// The cached record for `key`, expired or not.
+    pub fn get_idempotency_record(&self, tenant_id: &str, key: &str) -> Option<IdempotencyRecord> {
+        self.get(KbType::Soma.slot_id(), &keys::idempotency_key(tenant_id, key))
+            .ok()
+            .flatten()
+            .and_then(|b| IdempotencyRecord::from_bytes(&b))
+    }
+
+    /// Removes expired idempotency records. Returns how many were removed.
+    pub fn prune_idempotency_keys(&self) -> Result<usize, sled::Error> {
+        let now = self.now_ms();
+        let mut removed = 0;
+        for (key, bytes) in self.scan_kv(KbType::Soma.slot_id())? {
+            if !key.starts_with(keys::IDEMPOTENCY_PREFIX) {
+                continue;
+            }
+            if IdempotencyRecord::from_bytes(&bytes).is_none_or(|r| r.is_expired(now)) {
+                self.remove(KbType::Soma.slot_id(), &key)?;
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+
+    // ─────────────────────────────────────────────────────────────────────────
+    // Background goal jobs (Soma) — `/v1/execute?async=true` status and results
+    // ─────────────────────────────────────────────────────────────────────────
+
+    /// Stores a new job (stamping `created_at_ms`). Returns `None` when the id is taken.
+    pub fn create_job(&self, job: &GoalJob) -> Result<Option<GoalJob>, sled::Error> {
+        if !keys::is_segment(&job.job_id) {
+            return Err(sled::Error::Unsupported(format!("invalid job id {:?}", job.job_id)));
+        }
+        let key = keys::job_key(&job.job_id);
+        let mut created = job.clone();
+        created.created_at_ms = self.now_ms();
+        self.transaction(KbType::Soma.slot_id(), |tx| {
+            if tx.get(&key)?.is_some() {
+                return Ok(None);
+            }
+            tx.insert(&key, &created.to_bytes())?;
+            Ok(Some(created.clone()))
+        })
+    }
+
+    pub fn get_job(&self, job_id: &str) -> Option<GoalJob> {
+        self.get(KbType::Soma.slot_id(), &keys::job_key(job_id))
+            .ok()
+            .flatten()
+            .and_then(|b| GoalJob::from_bytes(&b))
+    }
+
+    /// Applies `update` to the job and stores it, unless the job already finished. Returns the
+    /// stored job, or `None` when there is no such job.
+    pub fn update_job(&self, job_id: &str, update: impl Fn(&mut GoalJob)) -> Result<Option<GoalJob>, sled::Error> {
+        let key = keys::job_key(job_id);
+        self.transaction(KbType::Soma.slot_id(), |tx| {
+            let Some(mut job) = tx.get(&key)?.and_then(|b| GoalJob::from_bytes(&b)) else {
+                return Ok(None);
+            };
+            if !job.status.is_finished() {
+                update(&mut job);
+                tx.insert(&key, &job.to_bytes())?;
+            }
+            Ok(Some(job))
+        })
+    }
+
+    /// Removes jobs that finished more than [`JOB_RETENTION_MS`] ago. Returns how many were removed.
+    pub fn prune_jobs(&self) -> Result<usize, sled::Error> {
+        let cutoff = self.now_ms() - JOB_RETENTION_MS;
+        let slot_id = KbType::Soma.slot_id();
+        let mut expired = Vec::new();
+        let mut cursor = None;
+        loop {
+            let page = self.scan_prefix(slot_id, keys::JOB_PREFIX, cursor.as_deref(), SCAN_MAX_LIMIT)?;
+            expired.extend(page.entries.iter().filter_map(|(key, bytes)| {
+                let job = GoalJob::from_bytes(bytes)?;
+                job.finished_at_ms.is_some_and(|t| t < cutoff).then(|| key.clone())
+            }));
+            match page.next_cursor {
+                Some(next) => cursor = Some(next),
+                None => break,
+            }
+        }
+        for key in &expired {
+            self.remove(slot_id, key)?;
+        }
+        Ok(expired.len())
+    }
+
+    // ─────────────────────────────────────────────────────────────────────────
+    // Token usage (Soma) — monthly LLM token counts per tenant and agent
+    // ─────────────────────────────────────────────────────────────────────────
+
+    /// Adds one generation's tokens to the tenant's record for the current month (created on first
+    /// use). `estimated` marks counts derived from text length rather than reported by the
+    /// provider. Returns the updated record.
+    pub fn record_token_usage(
+        &self,
+        tenant_id: &str,
+        agent_id: &str,
+        prompt_tokens: u64,
+        completion_tokens: u64,
+        estimated: bool,
+    ) -> Result<TokenUsageRecord, sled::Error> {
+        let now = self.now_ms();
+        let month = usage_month(now);
+        let db_key = keys::usage_key(tenant_id, &month);
+        keys::validate_key(KbType::Soma.slot_id(), &db_key).map_err(|e| sled::Error::Unsupported(e.to_string()))?;
+        let total = prompt_tokens + completion_tokens;
+        let mut written = None;
+        self.update(KbType::Soma.slot_id(), &db_key, |current| {
+            let mut record = current.and_then(TokenUsageRecord::from_bytes).unwrap_or_else(|| TokenUsageRecord {
+                tenant_id: tenant_id.to_string(),
+                month: month.clone(),
+                ..Default::default()
+            });
+            record.prompt_tokens += prompt_tokens;
+            record.completion_tokens += completion_tokens;
+            record.total_tokens += total;
+            record.requests += 1;
+            if estimated {
+                record.estimated_tokens += total;
+            }
+            let agent = record.by_agent.entry(agent_id.to_string()).or_default();
+            agent.prompt_tokens += prompt_tokens;
+            agent.completion_tokens += completion_tokens;
+            agent.total_tokens += total;
+            agent.requests += 1;
+            record.updated_at_ms = now;
+            let bytes = record.to_bytes();
+            written = Some(record);
+            Some(bytes)
+        })?;
+        Ok(written.unwrap_or_default())
+    }
+
+    /// The tenant's usage in `month` (`YYYY-MM`), if it used any tokens then.
+    pub fn get_token_usage(&self, tenant_id: &str, month: &str) -> Option<TokenUsageRecord> {
+        self.get(KbType::Soma.slot_id(), &keys::usage_key(tenant_id, month))
+            .ok()
+            .flatten()
+            .and_then(|b| TokenUsageRecord::from_bytes(&b))
+    }
+
+    /// Every monthly usage record of the tenant, oldest month first.
+    pub fn list_token_usage(&self, tenant_id: &str) -> Result<Vec<TokenUsageRecord>, sled::Error> {
+        let prefix = keys::usage_prefix(tenant_id);
+        let mut records = Vec::new();
+        let mut cursor = None;
+        loop {
+            let page = self.scan_prefix(KbType::Soma.slot_id(), &prefix, cursor.as_deref(), SCAN_MAX_LIMIT)?;
+            records.extend(page.entries.iter().filter_map(|(_, bytes)| TokenUsageRecord::from_bytes(bytes)));
+            match page.next_cursor {
+                Some(next) => cursor = Some(next),
+                None => return Ok(records),
+            }
+        }
+    }
+
+    // ─────────────────────────────────────────────────────────────────────────
+    // Email delivery log (Soma) — what SendEmail sent, failed to send or held back
+    // ─────────────────────────────────────────────────────────────────────────
+
+    /// Appends `delivery` to its tenant's log (stamping `created_at_ms` when unset). Returns the key.
+    pub fn record_email_delivery(&self, delivery: &EmailDelivery) -> Result<String, sled::Error> {
+        let mut record = delivery.clone();
+        if record.created_at_ms == 0 {
+            record.created_at_ms = self.now_ms();
+        }
+        let key = keys::email_log_key(&record.tenant_id, record.created_at_ms, &record.id);
+        self.insert(KbType::Soma.slot_id(), &key, &record.to_bytes())?;
+        Ok(key)
+    }
+
+    /// The tenant's latest email deliveries, newest first (at most [`EMAIL_LOG_MAX_LIMIT`]).
+    pub fn list_email_deliveries(&self, tenant_id: &str, limit: usize) -> Result<Vec<EmailDelivery>, sled::Error> {
+        let prefix = keys::email_log_prefix(tenant_id);
+        let page = self.scan_prefix_rev(KbType::Soma.slot_id(), &prefix, None, limit.clamp(1, EMAIL_LOG_MAX_LIMIT))?;
+        Ok(page.entries.iter().filter_map(|(_, bytes)| EmailDelivery::from_bytes(bytes)).collect())
+    }
+
+    // ─────────────────────────────────────────────────────────────────────────
+    // Calendar bookings (Soma) — events CalendarSkill created
+    // ─────────────────────────────────────────────────────────────────────────
+
+    /// Records `booking` under its tenant (stamping `created_at_ms` when unset). Returns the key.
+    pub fn record_calendar_booking(&self, booking: &CalendarBooking) -> Result<String, sled::Error> {
+        let mut record = booking.clone();
+        if record.created_at_ms == 0 {
+            record.created_at_ms = self.now_ms();
+        }
+        let key = keys::calendar_booking_key(&record.tenant_id, record.created_at_ms, &record.id);
+        self.insert(KbType::Soma.slot_id(), &key, &record.to_bytes())?;
+        Ok(key)
+    }
+
+    /// The tenant's latest bookings, newest first (at most [`CALENDAR_BOOKING_MAX_LIMIT`]).
+    pub fn list_calendar_bookings(&self, tenant_id: &str, limit: usize) -> Result<Vec<CalendarBooking>, sled::Error> {
+        let prefix = keys::calendar_booking_prefix(tenant_id);
+        let page = self.scan_prefix_rev(KbType::Soma.slot_id(), &prefix, None, limit.clamp(1, CALENDAR_BOOKING_MAX_LIMIT))?;
+        Ok(page.entries.iter().filter_map(|(_, bytes)| CalendarBooking::from_bytes(bytes)).collect())
+    }
+
+    // ─────────────────────────────────────────────────────────────────────────
+    // Eval runs (Logos) — scored suite runs and each suite's trend
+    // ─────────────────────────────────────────────────────────────────────────
+
+    /// Stores `run` and makes it its suite's latest (both keys are written together).
+    pub fn put_eval_run(&self, run: &EvalRun) -> Result<(), sled::Error> {
+        let run_key = keys::eval_run_key(&run.suite, run.started_at_ms, &run.run_id);
+        let suite_key = keys::eval_suite_key(&run.suite);
+        let (run_bytes, summary_bytes) = (run.to_bytes(), run.summary().to_bytes());
+        self.transaction(KbType::Logos.slot_id(), |tx| {
+            tx.insert(&run_key, &run_bytes)?;
+            tx.insert(&suite_key, &summary_bytes)?;
+            Ok(())
+        })
+    }
+
+    /// The suite's latest runs without their cases, newest first (at most [`EVAL_RUNS_MAX_LIMIT`]).
+    pub fn eval_runs(&self, suite: &str, limit: usize) -> Result<Vec<EvalRunSummary>, sled::Error> {
+        let prefix = keys::eval_runs_prefix(suite);
+        let page = self.scan_prefix_rev(KbType::Logos.slot_id(), &prefix, None, limit.clamp(1, EVAL_RUNS_MAX_LIMIT))?;
+        Ok(page.entries.iter().filter_map(|(_, bytes)| EvalRun::from_bytes(bytes)).map(|run| run.summary()).collect())
+    }
+
+    /// One run of `suite` with its per-case results.
+    pub fn get_eval_run(&self, suite: &str, run_id: &str) -> Result<Option<EvalRun>, sled::Error> {
+        let prefix = keys::eval_runs_prefix(suite);
+        let mut cursor = None;
+        loop {
+            let page = self.scan_prefix(KbType::Logos.slot_id(), &prefix, cursor.as_deref(), SCAN_MAX_LIMIT)?;
+            let found = page.entries.iter().find(|(key, _)| {
+                keys::parse_eval_run_key(key).is_some_and(|parsed| parsed.id == run_id)
+            });
+            if let Some((_, bytes)) = found {
+                return Ok(EvalRun::from_bytes(bytes));
+            }
+            match page.next_cursor {
+                Some(next) => cursor = Some(next),
+                None => return Ok(None),
+            }
+        }
+    }
+
+    /// The latest run of every suite that has one, sorted by suite name.
+    pub fn eval_suites(&self) -> Result<Vec<EvalRunSummary>, sled::Error> {
+        let mut suites = Vec::new();
+        let mut cursor = None;
+        loop {
+            let page = self.scan_prefix(KbType::Logos.slot_id(), keys::EVAL_SUITE_PREFIX, cursor.as_deref(), SCAN_MAX_LIMIT)?;
+            suites.extend(page.entries.iter().filter_map(|(_, bytes)| EvalRunSummary::from_bytes(bytes)));
+            match page.next_cursor {
+                Some(next) => cursor = Some(next),
+                None => return Ok(suites),
+            }
+        }
+    }
+
+    // ─────────────────────────────────────────────────────────────────────────
+    // LLM response cache (Soma) — replies reused for identical generations
+    // ─────────────────────────────────────────────────────────────────────────
+
+    /// The cached reply under `hash`, unless it expired.
+    pub fn get_llm_cache(&self, hash: &str) -> Option<LlmCacheEntry> {
+        self.get(KbType::Soma.slot_id(), &keys::llm_cache_key(hash))
+            .ok()
+            .flatten()
+            .and_then(|b| LlmCacheEntry::from_bytes(&b))
+            .filter(|entry| !entry.is_expired(self.now_ms()))
+    }
+
+    /// Caches `entry` under `hash`, then keeps at most `max_entries` entries: expired ones go
+    /// first, then the oldest. Returns how many were evicted.
+    pub fn put_llm_cache(&self, hash: &str, entry: &LlmCacheEntry, max_entries: usize) -> Result<usize, sled::Error> {
+        let slot = KbType::Soma.slot_id();
+        let db_key = keys::llm_cache_key(hash);
+        keys::validate_key(slot, &db_key).map_err(|e| sled::Error::Unsupported(e.to_string()))?;
+        self.insert(slot, &db_key, &entry.to_bytes())?;
+
+        let now = self.now_ms();
+        let mut entries: Vec<(String, i64, bool)> = Vec::new();
+        let mut cursor = None;
+        loop {
+            let page = self.scan_prefix(slot, keys::LLM_CACHE_PREFIX, cursor.as_deref(), SCAN_MAX_LIMIT)?;
+            for (key, bytes) in &page.entries {
+                match LlmCacheEntry::from_bytes(bytes) {
+                    Some(cached) => entries.push((key.clone(), cached.created_at_ms, cached.is_expired(now))),
+                    None => entries.push((key.clone(), i64::MIN, true)),
+                }
+            }
+            match page.next_cursor {
+                Some(next) => cursor = Some(next),
+                None => break,
+            }
+        }
+        // Expired first, then oldest first; the entry just written is never evicted.
+        entries.retain(|(key, _, _)| *key != db_key);
+        entries.sort_by_key(|&(_, created_at_ms, expired)| (!expired, created_at_ms));
+        let keep = max_entries.saturating_sub(1);
+        let mut evicted = 0;
+        for (key, _, expired) in &entries {
+            if !expired && entries.len() - evicted <= keep {
+                break;
+            }
+            self.remove(slot, key)?;
+            evicted += 1;
+        }
+        Ok(evicted)
+    }
+
+    /// Removes expired cache entries. Returns how many were removed.
+    pub fn prune_llm_cache(&self) -> Result<usize, sled::Error> {
+        let now = self.now_ms();
+        let mut removed = 0;
+        for (key, bytes) in self.scan_kv(KbType::Soma.slot_id())? {
+            if !key.starts_with(keys::LLM_CACHE_PREFIX) {
+                continue;
+            }
+            if LlmCacheEntry::from_bytes(&bytes).is_none_or(|e| e.is_expired(now)) {
+                self.remove(KbType::Soma.slot_id(), &key)?;
+                removed += 1;
+            }
+        }
+        Ok(removed)
     }
 
-    /// Returns the active safety policy from **KB_ETHOS**, if present.
-    pub fn get_ethos_policy(&self) -> Option<PolicyRecord> {
-        let slot_id = KbType::Ethos.slot_id();
-        self.get(slot_id, ETHOS_DEFAULT_POLICY_KEY)
+    // ─────────────────────────────────────────────────────────────────────────
+    // Agent registry (Pneuma) — the agents this instance runs
+    // ─────────────────────────────────────────────────────────────────────────
+
+    /// The registered agent `agent_id` (active or archived).
+    pub fn get_agent(&self, agent_id: &str) -> Option<AgentRecord> {
+        self.get(KbType::Pneuma.slot_id(), &keys::agent_key(agent_id))
             .ok()
             .flatten()
-            .and_then(|b| PolicyRecord::from_bytes(&b))
+            .and_then(|b| AgentRecord::from_bytes(&b))
     }
 
-    /// Writes the active safety policy to **KB_ETHOS**.
-    pub fn set_ethos_policy(&self, policy: &PolicyRecord) -> Result<(), sled::Error> {
-        let slot_id = KbType::Ethos.slot_id();
-        self.insert(slot_id, ETHOS_DEFAULT_POLICY_KEY, &policy.to_bytes())?;
-        Ok(())
+    /// Registers `agent` (stamping `created_at_ms`). Returns `None` when the id is already
+    /// registered, archived or not.
+    pub fn create_agent(&self, agent: &AgentRecord) -> Result<Option<AgentRecord>, sled::Error> {
+        if !is_valid_agent_id(&agent.agent_id) {
+            return Err(sled::Error::Unsupported(format!("invalid agent id {:?}", agent.agent_id)));
+        }
+        let key = keys::agent_key(&agent.agent_id);
+        let mut created = agent.clone();
+        created.status = AgentStatus::Active;
+        created.created_at_ms = self.now_ms();
+        created.archived_at_ms = None;
+        self.transaction(KbType::Pneuma.slot_id(), |tx| {
+            if tx.get(&key)?.is_some() {
+                return Ok(None);
+            }
+            tx.insert(&key, &created.to_bytes())?;
+            Ok(Some(created.clone()))
+        })
     }
 
-    /// Returns the active philosophical policy from **KB_ETHOS**, if present.
-    /// Stored under key [`crate::ETHOS_POLICY_KEY`] (`ethos/current`).
-    pub fn get_ethos_philosophical_policy(&self) -> Option<crate::EthosPolicy> {
-        let slot_id = KbType::Ethos.slot_id();
-        self.get(slot_id, crate::ETHOS_POLICY_KEY)
-            .ok()
-            .flatten()
-            .and_then(|b| crate::EthosPolicy::from_bytes(&b))
+    /// Every registered agent, archived ones included, by id.
+    pub fn list_agents(&self) -> Result<Vec<AgentRecord>, sled::Error> {
+        let mut agents = Vec::new();
+        let mut cursor = None;
+        loop {
+            let page = self.scan_prefix(KbType::Pneuma.slot_id(), keys::AGENT_PREFIX, cursor.as_deref(), SCAN_MAX_LIMIT)?;
+            agents.extend(page.entries.iter().filter_map(|(_, b)| AgentRecord::from_bytes(b)));
+            match page.next_cursor {
+                Some(next) => cursor = Some(next),
+                None => break,
+            }
+        }
+        Ok(agents)
     }
 
-    /// Writes the philosophical policy to **KB_ETHOS** under `ethos/current`.
-    pub fn set_ethos_philosophical_policy(
-        &self,
-        policy: &crate::EthosPolicy,
-    ) -> Result<(), sled::Error> {
-        let slot_id = KbType::Ethos.slot_id();
-        self.insert(slot_id, crate::ETHOS_POLICY_KEY, &policy.to_bytes())?;
-        Ok(())
+    /// Ids of the registered agents that are not archived: the agents the Heartbeat serves.
+    pub fn active_agent_ids(&self) -> Result<Vec<String>, sled::Error> {
+        Ok(self
+            .list_agents()?
+            .into_iter()
+            .filter(AgentRecord::is_active)
+            .map(|a| a.agent_id)
+            .collect())
     }
 
-    /// Returns the relation record from **KB_KARDIA** for (owner_agent_id, target_id).
-    /// Use owner_agent_id = "default" for single-agent mode.
-    pub fn get_kardia_relation(
-        &self,
-        owner_agent_id: &str,
-        target_id: &str,
-    ) -> Option<RelationRecord> {
-        let slot_id = KbType::Kardia.slot_id();
-        let key = kardia_relation_key(owner_agent_id, target_id);
-        self.get(slot_id, &key).ok().flatten().and_then(|b| RelationRecord::from_bytes(&b))
+    /// Ids of the agents the Heartbeat should serve this tick: active ones that are not paused,
+    /// inside their active hours and due per their tick interval (recorded in `ticks`).
+    pub fn due_agent_ids(&self, ticks: &mut AgentTickTracker) -> Result<Vec<String>, sled::Error> {
+        Ok(ticks.due(&self.list_agents()?, self.now_ms()))
     }
 
-    /// Writes the relation record to **KB_KARDIA** under (owner_agent_id, record.user_id).
-    pub fn set_kardia_relation(
-        &self,
-        owner_agent_id: &str,
-        record: &RelationRecord,
-    ) -> Result<(), sled::Error> {
-        let slot_id = KbType::Kardia.slot_id();
-        let key = kardia_relation_key(owner_agent_id, &record.user_id);
-        self.insert(slot_id, &key, &record.to_bytes())?;
-        Ok(())
+    /// Applies `update` to the agent's schedule (pause, tick interval, active hours). Returns the
+    /// updated record, or `None` when no such agent is registered. Fails when
+    /// [`AgentScheduleUpdate::validate`] rejects it.
+    pub fn update_agent_schedule(&self, agent_id: &str, update: &AgentScheduleUpdate) -> Result<Option<AgentRecord>, sled::Error> {
+        update.validate().map_err(|e| sled::Error::Unsupported(e.to_string()))?;
+        let key = keys::agent_key(agent_id);
+        self.transaction(KbType::Pneuma.slot_id(), |tx| {
+            let Some(mut agent) = tx.get(&key)?.and_then(|b| AgentRecord::from_bytes(&b)) else {
+                return Ok(None);
+            };
+            agent.apply_schedule(update);
+            tx.insert(&key, &agent.to_bytes())?;
+            Ok(Some(agent))
+        })
     }
 
-    /// Key for a person in the Relational Map: `people/{name_slug}`.
-    pub fn kardia_person_key(name_slug: &str) -> String {
-        format!("{}{}", KARDIA_PEOPLE_PREFIX, name_slug)
+    /// Marks the agent archived (its inbox, settings and history stay). Returns the updated
+    /// record, or `None` when no such agent is registered.
+    pub fn archive_agent(&self, agent_id: &str) -> Result<Option<AgentRecord>, sled::Error> {
+        let key = keys::agent_key(agent_id);
+        let archived_at_ms = self.now_ms();
+        self.transaction(KbType::Pneuma.slot_id(), |tx| {
+            let Some(mut agent) = tx.get(&key)?.and_then(|b| AgentRecord::from_bytes(&b)) else {
+                return Ok(None);
+            };
+            if agent.is_active() {
+                agent.status = AgentStatus::Archived;
+                agent.archived_at_ms = Some(archived_at_ms);
+                tx.insert(&key, &agent.to_bytes())?;
+            }
+            Ok(Some(agent))
+        })
     }
 
-    /// Returns a **PersonRecord** from the Relational Map (KB_KARDIA) by name slug.
-    pub fn get_person(&self, name_slug: &str) -> Option<PersonRecord> {
-        let slot_id = KbType::Kardia.slot_id();
-        let key = Self::kardia_person_key(name_slug);
-        self.get(slot_id, &key)
+    /// Registers every agent that has inbox messages but no registry entry (agents from before
+    /// the registry existed). Returns how many were added.
+    pub fn register_inbox_agents(&self) -> Result<usize, sled::Error> {
+        let mut added = 0;
+        for agent_id in self.inbox_agent_ids()? {
+            if is_valid_agent_id(&agent_id) && self.create_agent(&AgentRecord::new(&agent_id, None, Vec::new()))?.is_some() {
+                added += 1;
+            }
+        }
+        Ok(added)
+    }
+
+    /// Pending inbox messages and latest Chronos activity of `agent_id`.
+    pub fn agent_health(&self, agent_id: &str) -> Result<AgentHealth, sled::Error> {
+        let pending_messages = self
+            .get_agent_messages(agent_id, AGENT_HEALTH_INBOX_WINDOW)?
+            .iter()
+            .filter(|m| !m.is_processed)
+            .count();
+        let last_activity_ms = self
+            .scan_prefix_rev(KbType::Chronos.slot_id(), &keys::event_prefix(agent_id), None, 1)?
+            .entries
+            .first()
+            .and_then(|(key, _)| keys::parse_event_key(key))
+            .map(|k| k.timestamp_ms);
+        Ok(AgentHealth {
+            pending_messages,
+            last_activity_ms,
+        })
+    }
+
+    // ─────────────────────────────────────────────────────────────────────────
+    // Prompt templates (Pneuma) — operator-tunable prompts with version history
+    // ─────────────────────────────────────────────────────────────────────────
+
+    /// The current version of prompt `name`: the stored one, else its built-in default.
+    pub fn get_prompt(&self, name: &str) -> Option<PromptTemplate> {
+        self.get(KbType::Pneuma.slot_id(), &keys::prompt_key(name))
             .ok()
             .flatten()
-            .and_then(|b| serde_json::from_slice(&b).ok())
+            .and_then(|b| PromptTemplate::from_bytes(&b))
+            .or_else(|| PromptTemplate::builtin(name))
     }
 
-    /// Writes a **PersonRecord** to the Relational Map (KB_KARDIA) under `people/{name_slug}`.
-    pub fn set_person(&self, record: &PersonRecord) -> Result<(), sled::Error> {
-        let slot_id = KbType::Kardia.slot_id();
-        let slug = PersonRecord::name_slug(&record.name);
-        let key = Self::kardia_person_key(&slug);
-        let bytes = serde_json::to_vec(record).unwrap_or_default();
-        self.insert(slot_id, &key, &bytes)?;
-        Ok(())
+    /// Saves `template` as the next version of prompt `name` (the current key and its history
+    /// entry are written together). Fails when [`PromptTemplate::validate`] rejects it.
+    pub fn put_prompt(&self, name: &str, template: &str, description: Option<&str>) -> Result<PromptTemplate, sled::Error> {
+        PromptTemplate::validate(name, template).map_err(|e| sled::Error::Unsupported(e.to_string()))?;
+        let updated_at_ms = self.now_ms();
+        let current_key = keys::prompt_key(name);
+        self.transaction(KbType::Pneuma.slot_id(), |tx| {
+            let previous = tx.get(&current_key)?.and_then(|b| PromptTemplate::from_bytes(&b));
+            let description = description
+                .map(str::to_string)
+                .or_else(|| previous.as_ref().map(|p| p.description.clone()))
+                .or_else(|| PromptTemplate::builtin(name).map(|p| p.description))
+                .unwrap_or_default();
+            let prompt = PromptTemplate {
+                name: name.to_string(),
+                template: template.to_string(),
+                description,
+                version: previous.map_or(0, |p| p.version) + 1,
+                updated_at_ms,
+            };
+            let bytes = prompt.to_bytes();
+            tx.insert(&current_key, &bytes)?;
+            tx.insert(&keys::prompt_version_key(name, prompt.version), &bytes)?;
+            Ok(prompt)
+        })
     }
 
-    /// Returns all **PersonRecord**s in the Relational Map (KB_KARDIA) with key prefix `people/`.
-    pub fn list_people(&self) -> Result<Vec<PersonRecord>, sled::Error> {
-        let slot_id = KbType::Kardia.slot_id();
-        let kv = self.scan_kv(slot_id)?;
-        let prefix = KARDIA_PEOPLE_PREFIX;
-        let mut out: Vec<PersonRecord> = kv
-            .into_iter()
-            .filter(|(k, _)| k.starts_with(prefix))
-            .filter_map(|(_, bytes)| serde_json::from_slice(&bytes).ok())
-            .collect();
-        out.sort_by(|a, b| a.name.cmp(&b.name));
-        Ok(out)
+    /// Every prompt: the current version of each stored one plus the built-ins nothing overrides,
+    /// sorted by name.
+    pub fn list_prompts(&self) -> Result<Vec<PromptTemplate>, sled::Error> {
+        let mut prompts: Vec<PromptTemplate> = Vec::new();
+        let mut cursor = None;
+        loop {
+            let page = self.scan_prefix(KbType::Pneuma.slot_id(), keys::PROMPT_PREFIX, cursor.as_deref(), SCAN_MAX_LIMIT)?;
+            prompts.extend(page.entries.iter().filter_map(|(_, b)| PromptTemplate::from_bytes(b)));
+            match page.next_cursor {
+                Some(next) => cursor = Some(next),
+                None => break,
+            }
+        }
+        for builtin in PromptTemplate::builtins() {
+            if !prompts.iter().any(|p| p.name == builtin.name) {
+                prompts.push(builtin);
+            }
+        }
+        prompts.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(prompts)
     }
 
-    /// Returns the **MentalState** (Emotional Context Layer) from **KB_KARDIA**.
-    /// Stored under a global key so the Cognitive Governor can modulate tone and demand.
-    pub fn get_mental_state(&self, _owner_agent_id: &str) -> MentalState {
-        let slot_id = KbType::Kardia.slot_id();
-        match self.get(slot_id, MENTAL_STATE_KEY) {
-            Ok(Some(bytes)) => serde_json::from_slice(&bytes).unwrap_or_default(),
-            _ => MentalState::default(),
+    /// Every saved version of prompt `name`, oldest first (empty for a built-in never overridden).
+    pub fn prompt_versions(&self, name: &str) -> Result<Vec<PromptTemplate>, sled::Error> {
+        let prefix = keys::prompt_versions_prefix(name);
+        let mut versions = Vec::new();
+        let mut cursor = None;
+        loop {
+            let page = self.scan_prefix(KbType::Pneuma.slot_id(), &prefix, cursor.as_deref(), SCAN_MAX_LIMIT)?;
+            versions.extend(page.entries.iter().filter_map(|(_, b)| PromptTemplate::from_bytes(b)));
+            match page.next_cursor {
+                Some(next) => cursor = Some(next),
+                None => break,
+            }
         }
+        Ok(versions)
     }
 
-    /// Writes the **MentalState** to **KB_KARDIA**. Used by JournalSkill and gateway.
-    pub fn set_mental_state(&self, _owner_agent_id: &str, state: &MentalState) -> Result<(), sled::Error> {
-        let slot_id = KbType::Kardia.slot_id();
-        let bytes = serde_json::to_vec(state).unwrap_or_default();
-        self.insert(slot_id, MENTAL_STATE_KEY, &bytes)?;
+    // ─────────────────────────────────────────────────────────────────────────
+    // Audit log (dedicated tree) — one record per top-level dispatch
+    // ─────────────────────────────────────────────────────────────────────────
+
+    /// Appends a dispatch record to the audit log.
+    pub fn append_audit_record(&self, record: &AuditRecord) -> Result<(), sled::Error> {
+        let tree = self.db.open_tree(AUDIT_TREE)?;
+        let key = audit_key(record.timestamp_ms, self.db.generate_id()?);
+        tree.insert(key.as_bytes(), &record.to_bytes())?;
         Ok(())
     }
 
-    /// Key in **KB_SOMA** (Slot 8) where the current BiometricState is stored (BioGate).
-    pub const BIOMETRIC_STATE_KEY: &str = "biometric/current";
-
-    /// Returns the **BiometricState** (Physical Load) from **KB_SOMA** (Slot 8).
-    pub fn get_biometric_state(&self) -> BiometricState {
-        let slot_id = KbType::Soma.slot_id();
-        match self.get(slot_id, Self::BIOMETRIC_STATE_KEY) {
-            Ok(Some(bytes)) => serde_json::from_slice(&bytes).unwrap_or_default(),
-            _ => BiometricState::default(),
+    /// Notes a redaction in the audit log (`goal_type: "Redaction"`, `skill`: where it happened,
+    /// `redactions`: what was masked). Empty reports are not recorded; failures are only logged,
+    /// since the masked write goes ahead either way.
+    pub fn record_redaction(
+        &self,
+        tenant_id: &str,
+        agent_id: &str,
+        correlation_id: Option<&str>,
+        site: &str,
+        report: &RedactionReport,
+    ) {
+        if report.is_empty() {
+            return;
+        }
+        tracing::info!(target: "pagi::redaction", site, masked = %report.summary(), "PII redacted");
+        let record = AuditRecord {
+            id: Uuid::new_v4().simple().to_string(),
+            timestamp_ms: self.now_ms(),
+            tenant_id: tenant_id.to_string(),
+            agent_id: agent_id.to_string(),
+            correlation_id: correlation_id.map(str::to_string),
+            goal_type: REDACTION_GOAL_TYPE.to_string(),
+            skill: Some(site.to_string()),
+            intent: None,
+            duration_ms: 0,
+            outcome: AuditOutcome::Ok,
+            error: None,
+            redactions: report.clone(),
+        };
+        if let Err(e) = self.append_audit_record(&record) {
+            tracing::warn!(target: "pagi::redaction", error = %e, "Failed to write redaction audit record");
         }
     }
 
-    /// Writes the **BiometricState** to **KB_SOMA** (Slot 8). Used by BioGateSync skill.
-    pub fn set_biometric_state(&self, state: &BiometricState) -> Result<(), sled::Error> {
-        let slot_id = KbType::Soma.slot_id();
-        let bytes = serde_json::to_vec(state).unwrap_or_default();
-        self.insert(slot_id, Self::BIOMETRIC_STATE_KEY, &bytes)?;
-        Ok(())
+    /// Audit records matching `query`, newest first, one page at a time.
+    pub fn query_audit(&self, query: &AuditQuery) -> Result<AuditPage, sled::Error> {
+        let tree = self.db.open_tree(AUDIT_TREE)?;
+        let lower = format!("{:013}", query.from_ms.unwrap_or(0).max(0));
+        let mut upper = query
+            .to_ms
+            .map(|t| format!("{:013}", t.max(0)))
+            .unwrap_or_else(|| "~".to_string());
+        if let Some(cursor) = query.cursor.as_deref().filter(|c| *c < upper.as_str()) {
+            upper = cursor.to_string();
+        }
+        let limit = query.page_size();
+        let mut records = Vec::with_capacity(limit);
+        let mut next_cursor = None;
+        let mut last_key = None;
+        if lower < upper {
+            for item in tree.range(std::ops::Bound::Included(lower.into_bytes()), std::ops::Bound::Excluded(upper.into_bytes()), true) {
+                let (k, v) = item?;
+                let Some(record) = AuditRecord::from_bytes(&v) else {
+                    continue;
+                };
+                if !query.matches(&record) {
+                    continue;
+                }
+                if records.len() == limit {
+                    next_cursor = last_key;
+                    break;
+                }
+                last_key = Some(String::from_utf8_lossy(&k).into_owned());
+                records.push(record);
+            }
+        }
+        Ok(AuditPage { records, next_cursor })
     }
 
-    /// Key in **KB_SOMA** (Slot 8) where the current SomaState is stored (BioGate v2).
-    pub const SOMA_STATE_KEY: &str = "soma/current";
-
-    /// Returns the **SomaState** (BioGate health metrics) from **KB_SOMA** (Slot 8).
-    pub fn get_soma_state(&self) -> SomaState {
-        let slot_id = KbType::Soma.slot_id();
-        match self.get(slot_id, Self::SOMA_STATE_KEY) {
-            Ok(Some(bytes)) => serde_json::from_slice(&bytes).unwrap_or_default(),
-            _ => SomaState::default(),
+    /// Removes audit records older than `before_ms`. Returns how many were removed.
+    pub fn prune_audit_records(&self, before_ms: i64) -> Result<usize, sled::Error> {
+        let tree = self.db.open_tree(AUDIT_TREE)?;
+        let upper = format!("{:013}", before_ms.max(0));
+        let mut batch = KvBatch::default();
+        let mut removed = 0;
+        for item in tree.range(std::ops::Bound::Unbounded, std::ops::Bound::Excluded(upper.into_bytes()), false) {
+            let (k, _) = item?;
+            batch.remove(k);
+            removed += 1;
         }
+        tree.apply_batch(batch)?;
+        Ok(removed)
     }
 
-    /// Writes the **SomaState** to **KB_SOMA** (Slot 8). Used by BioGateSync skill.
-    pub fn set_soma_state(&self, state: &SomaState) -> Result<(), sled::Error> {
-        let slot_id = KbType::Soma.slot_id();
-        let bytes = serde_json::to_vec(state).unwrap_or_default();
-        self.insert(slot_id, Self::SOMA_STATE_KEY, &bytes)?;
+    // ─────────────────────────────────────────────────────────────────────────
+    // Re-embedding jobs (dedicated tree) and in-place embedding updates
+    // ─────────────────────────────────────────────────────────────────────────
+
+    /// Saves (creates or updates) a re-embedding job.
+    pub fn save_reembed_job(&self, job: &ReembedJob) -> Result<(), sled::Error> {
+        let tree = self.db.open_tree(REEMBED_JOBS_TREE)?;
+        tree.insert(job.id.as_bytes(), &job.to_bytes())?;
         Ok(())
     }
 
-    /// Returns the **effective** MentalState for the Cognitive Governor: Kardia baseline
-    /// merged with Soma (BioGate) physical load.
-    ///
-    /// **Cross-layer reaction (BioGate v2 — SomaState):**
-    /// If `readiness_score < 50` **OR** `sleep_hours < 6.0`:
-    /// - `burnout_risk` is incremented by **+0.15**
-    /// - `grace_multiplier` is set to **1.6**
-    ///
-    /// **Legacy fallback (BiometricState):**
-    /// If `sleep_score < 60`, burnout_risk is increased by 0.2 and grace_multiplier set to 1.5.
-    pub fn get_effective_mental_state(&self, owner_agent_id: &str) -> MentalState {
-        let mut mental = self.get_mental_state(owner_agent_id);
+    pub fn get_reembed_job(&self, id: &str) -> Result<Option<ReembedJob>, sled::Error> {
+        let tree = self.db.open_tree(REEMBED_JOBS_TREE)?;
+        Ok(tree.get(id.as_bytes())?.and_then(|v| ReembedJob::from_bytes(&v)))
+    }
 
-        // BioGate v2: SomaState cross-layer reaction (takes priority)
-        let soma = self.get_soma_state();
-        if soma.needs_biogate_adjustment() {
-            mental.burnout_risk = (mental.burnout_risk + SomaState::BURNOUT_RISK_INCREMENT).min(1.0);
-            mental.grace_multiplier = SomaState::GRACE_MULTIPLIER_OVERRIDE;
-        } else {
-            // Legacy fallback: BiometricState
-            let bio = self.get_biometric_state();
-            if bio.poor_sleep() {
-                mental.burnout_risk = (mental.burnout_risk + 0.2).min(1.0);
-                mental.grace_multiplier = 1.5;
+    /// All re-embedding jobs, newest first.
+    pub fn list_reembed_jobs(&self) -> Result<Vec<ReembedJob>, sled::Error> {
+        let tree = self.db.open_tree(REEMBED_JOBS_TREE)?;
+        let mut jobs = Vec::new();
+        for item in tree.iter() {
+            let (_, v) = item?;
+            if let Some(job) = ReembedJob::from_bytes(&v) {
+                jobs.push(job);
             }
         }
+        jobs.sort_by_key(|j| std::cmp::Reverse(j.created_at_ms));
+        Ok(jobs)
+    }
 
-        mental.clamp();
-        mental
+    /// Marks jobs left `Running` by a previous process as `Interrupted` (call at startup, before
+    /// starting jobs). Returns how many were marked.
+    pub fn interrupt_running_reembed_jobs(&self) -> Result<usize, sled::Error> {
+        let mut marked = 0;
+        for mut job in self.list_reembed_jobs()? {
+            if job.status == ReembedStatus::Running {
+                job.status = ReembedStatus::Interrupted;
+                job.touch(self.now_ms());
+                self.save_reembed_job(&job)?;
+                marked += 1;
+            }
+        }
+        Ok(marked)
     }
 
-    /// Pushes an inter-agent message to **KB_SOMA** (inbox for target agent).
-    /// Key: `inbox/{target_agent_id}/{timestamp_ms}_{uuid}`. Returns the message id.
-    pub fn push_agent_message(
-        &self,
-        from_agent_id: &str,
-        target_agent_id: &str,
-        payload: &serde_json::Value,
-    ) -> Result<String, sled::Error> {
-        let slot_id = KbType::Soma.slot_id();
-        let ts = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .map(|d| d.as_millis() as i64)
-            .unwrap_or(0);
-        let id = Uuid::new_v4().simple().to_string();
-        let key = format!("inbox/{}/{}_{}", target_agent_id, ts, id);
-        let msg = AgentMessage {
-            id: id.clone(),
-            from_agent_id: from_agent_id.to_string(),
-            target_agent_id: target_agent_id.to_string(),
-            payload: payload.clone(),
-            timestamp_ms: ts,
-            is_processed: false,
-        };
-        self.insert(slot_id, &key, &msg.to_bytes())?;
-        Ok(id)
+    /// Number of records in `slot_id` that carry an embedding.
+    pub fn count_embedded_records(&self, slot_id: u8) -> Result<usize, sled::Error> {
+        let tree = self.db.open_tree(Self::tree_name(slot_id))?;
+        let mut count = 0;
+        for item in tree.iter() {
+            let (_, v) = item?;
+            if KbRecord::from_bytes(&self.decode_value(slot_id, &v)?).is_some_and(|r| r.embedding.is_some()) {
+                count += 1;
+            }
+        }
+        Ok(count)
     }
 
-    /// Returns the most recent messages for an agent from **KB_SOMA** inbox, newest first,
-    /// including the underlying inbox key.
-    ///
-    /// This is primarily used by the Heartbeat so it can mark messages as processed
-    /// without deleting them (preserving KB_SOMA history).
-    pub fn get_agent_messages_with_keys(
+    /// Up to `limit` records of `slot_id` that carry an embedding, in key order, starting after
+    /// `after` (exclusive) so a walk can resume from its last key.
+    pub fn scan_embedded_records(
         &self,
-        target_agent_id: &str,
+        slot_id: u8,
+        after: Option<&str>,
         limit: usize,
-    ) -> Result<Vec<(String, AgentMessage)>, sled::Error> {
-        let slot_id = KbType::Soma.slot_id();
-        let prefix = format!("inbox/{}/", target_agent_id);
-        let mut messages: Vec<(i64, String, AgentMessage)> = self
-            .scan_kv(slot_id)?
-            .into_iter()
-            .filter(|(k, _)| k.starts_with(&prefix))
-            .filter_map(|(k, bytes)| AgentMessage::from_bytes(&bytes).map(|m| (m.timestamp_ms, k, m)))
-            .collect();
-        messages.sort_by(|a, b| b.0.cmp(&a.0));
-        Ok(messages
-            .into_iter()
-            .take(limit)
-            .map(|(_ts, k, m)| (k, m))
-            .collect())
+    ) -> Result<Vec<(String, KbRecord)>, sled::Error> {
+        let tree = self.db.open_tree(Self::tree_name(slot_id))?;
+        let iter = match after {
+            Some(key) => tree.range(std::ops::Bound::Excluded(key.as_bytes().to_vec()), std::ops::Bound::Unbounded, false),
+            None => tree.iter(),
+        };
+        let mut out = Vec::new();
+        for item in iter {
+            if out.len() == limit {
+                break;
+            }
+            let (k, v) = item?;
+            if let Some(record) = KbRecord::from_bytes(&self.decode_value(slot_id, &v)?).filter(|r| r.embedding.is_some()) {
+                out.push((String::from_utf8_lossy(&k).into_owned(), record));
+            }
+        }
+        Ok(out)
     }
 
-    /// Returns the most recent messages for an agent from **KB_SOMA** inbox, newest first.
-    pub fn get_agent_messages(
+    /// Replaces the embedding of the record at `key` in place (content, id and timestamp are
+    /// kept) and records `model` and the vector size in its metadata. Returns false when the
+    /// record no longer exists.
+    pub fn set_record_embedding(
         &self,
-        target_agent_id: &str,
-        limit: usize,
-    ) -> Result<Vec<AgentMessage>, sled::Error> {
-        let slot_id = KbType::Soma.slot_id();
-        let prefix = format!("inbox/{}", target_agent_id);
-        let mut messages: Vec<(i64, AgentMessage)> = self
-            .scan_kv(slot_id)?
-            .into_iter()
-            .filter(|(k, _)| k.starts_with(&prefix))
-            .filter_map(|(_, bytes)| AgentMessage::from_bytes(&bytes).map(|m| (m.timestamp_ms, m)))
-            .collect();
-        messages.sort_by(|a, b| b.0.cmp(&a.0));
-        Ok(messages.into_iter().take(limit).map(|(_, m)| m).collect())
+        slot_id: u8,
+        key: &str,
+        embedding: Vec<f32>,
+        model: &str,
+    ) -> Result<bool, sled::Error> {
+        let Some(mut record) = self.get_record(slot_id, key)? else {
+            return Ok(false);
+        };
+        if !record.metadata.is_object() {
+            record.metadata = serde_json::json!({});
+        }
+        record.metadata[EMBEDDING_MODEL_METADATA_KEY] = serde_json::json!(model);
+        record.metadata["vector_dims"] = serde_json::json!(embedding.len());
+        record.embedding = Some(embedding);
+        self.insert_record(slot_id, key, &record)?;
+        Ok(true)
     }
 
     /// Returns all skill manifests stored in KB-5 (Techne / Skills & Blueprints).
@@ -1162,10 +4516,12 @@ impl KnowledgeStore {
                 Ok(s) => s,
                 Err(_) => continue,
             };
-            if !key.starts_with("skills/") {
+            if !key.starts_with(keys::SKILL_PREFIX) {
                 continue;
             }
-            let bytes = v.to_vec();
+            let Ok(bytes) = self.decode_value(slot_id, &v) else {
+                continue;
+            };
             if let Ok(rec) = serde_json::from_slice::<SkillRecord>(&bytes) {
                 out.push(rec);
             }
@@ -1251,7 +4607,7 @@ impl KnowledgeStore {
                 Ok(s) => s,
                 Err(_) => continue,
             };
-            if !key.starts_with("anchor/") {
+            if !key.starts_with(keys::ANCHOR_PREFIX) {
                 continue;
             }
             let encrypted = v.to_vec();
@@ -1271,15 +4627,37 @@ impl KnowledgeStore {
     /// Stores a [`GovernedTask`] in **KB_OIKOS** (Slot 2) under `oikos/tasks/{task_id}`.
     pub fn set_governed_task(&self, task: &crate::GovernedTask) -> Result<(), sled::Error> {
         let slot_id = KbType::Oikos.slot_id();
-        let key = format!("{}{}", crate::OIKOS_TASK_PREFIX, task.task_id);
+        let key = keys::task_key(&task.task_id);
         self.insert(slot_id, &key, &task.to_bytes())?;
         Ok(())
     }
 
+    /// Read-modify-write of a governed task that does not lose concurrent updates (see
+    /// [`update`](Self::update)). Returns the task written, or None when it does not exist (it is
+    /// not created).
+    pub fn update_governed_task<F>(&self, task_id: &str, mut f: F) -> Result<Option<crate::GovernedTask>, sled::Error>
+    where
+        F: FnMut(&mut crate::GovernedTask),
+    {
+        let key = keys::task_key(task_id);
+        let mut written = None;
+        self.update(KbType::Oikos.slot_id(), &key, |current| {
+            let bytes = current?;
+            let Some(mut task) = crate::GovernedTask::from_bytes(bytes) else {
+                return Some(bytes.to_vec());
+            };
+            f(&mut task);
+            let bytes = task.to_bytes();
+            written = Some(task);
+            Some(bytes)
+        })?;
+        Ok(written)
+    }
+
     /// Retrieves a [`GovernedTask`] from **KB_OIKOS** (Slot 2) by task_id.
     pub fn get_governed_task(&self, task_id: &str) -> Option<crate::GovernedTask> {
         let slot_id = KbType::Oikos.slot_id();
-        let key = format!("{}{}", crate::OIKOS_TASK_PREFIX, task_id);
+        let key = keys::task_key(task_id);
         self.get(slot_id, &key)
             .ok()
             .flatten()
@@ -1290,7 +4668,7 @@ impl KnowledgeStore {
     pub fn list_governed_tasks(&self) -> Result<Vec<crate::GovernedTask>, sled::Error> {
         let slot_id = KbType::Oikos.slot_id();
         let kv = self.scan_kv(slot_id)?;
-        let prefix = crate::OIKOS_TASK_PREFIX;
+        let prefix = keys::TASK_PREFIX;
         let mut tasks: Vec<crate::GovernedTask> = kv
             .into_iter()
             .filter(|(k, _)| k.starts_with(prefix))
@@ -1307,7 +4685,7 @@ impl KnowledgeStore {
     /// Removes a governed task from **KB_OIKOS** (Slot 2) by task_id.
     pub fn remove_governed_task(&self, task_id: &str) -> Result<bool, sled::Error> {
         let slot_id = KbType::Oikos.slot_id();
-        let key = format!("{}{}", crate::OIKOS_TASK_PREFIX, task_id);
+        let key = keys::task_key(task_id);
         let prev = self.remove(slot_id, &key)?;
         Ok(prev.is_some())
     }
@@ -1330,11 +4708,20 @@ impl KnowledgeStore {
     pub fn evaluate_and_persist_tasks(&self, agent_id: &str) -> Result<Vec<crate::GovernedTask>, sled::Error> {
         let governor = self.create_task_governor(agent_id);
         let tasks = self.list_governed_tasks()?;
-        let evaluated = governor.evaluate_batch(&tasks);
-
-        // Persist each evaluated task back to Oikos
-        for task in &evaluated {
-            self.set_governed_task(task)?;
+        let mut evaluated = governor.evaluate_batch(&tasks, self.now_ms());
+
+        // Persist each evaluation onto the task as stored now, so fields another writer changed
+        // since the list was read are kept (and re-evaluated).
+        for task in &mut evaluated {
+            let stored = self.update_governed_task(&task.task_id, |current| {
+                let (action, effective_priority) = governor.evaluate(current);
+                current.action = action;
+                current.effective_priority = effective_priority;
+                current.last_evaluated_ms = task.last_evaluated_ms;
+            })?;
+            if let Some(stored) = stored {
+                *task = stored;
+            }
         }
 
         // Persist governance summary
@@ -1432,13 +4819,9 @@ impl KnowledgeStore {
         let pneuma_slot = KbType::Pneuma.slot_id();
         let mut parts: Vec<String> = Vec::new();
 
-        // 0) Sovereign persona (always first) — Gemini-style: authentic, witty, no skill dumps
-        parts.push(
-            "You are PAGI, a Sovereign AGI. You are an authentic, adaptive collaborator with a touch of wit. \
-             Do not list your skills or JSON schemas unless the user explicitly asks. Balance empathy with candor. \
-             Use the user's Soma and Ethos context (when provided below) to give grounded, peer-like support. \
-             When the user asks for the date or time, state it (e.g. 'It's February 6, 2026'); you may suggest checking Soma levels if they have been at the screen for a long time.".to_string(),
-        );
+        // 0) Sovereign persona (always first) — Gemini-style: authentic, witty, no skill dumps.
+        //    Operators can retune it as the `system_persona` prompt template.
+        parts.push(render_prompt(Some(self), SYSTEM_PERSONA_PROMPT, &[]).unwrap_or_default());
 
         // 1) Identity (Slot 1 / Pneuma)
         if let Ok(Some(mission)) = self.get_record(pneuma_slot, "core_mission") {
@@ -1447,7 +4830,11 @@ impl KnowledgeStore {
                 mission.content
             ));
         }
-        if let Ok(Some(persona)) = self.get_record(pneuma_slot, "core_persona") {
+        if let Some(profile) = self.get_persona_profile(agent_id) {
+            parts.push(profile.to_directive());
+        } else if let Some(persona) = self.get_agent_pneuma_text(&agent_persona_key(agent_id)) {
+            parts.push(format!("Persona: {}", persona));
+        } else if let Ok(Some(persona)) = self.get_record(pneuma_slot, "core_persona") {
             parts.push(format!("Persona: {}", persona.content));
         }
 
@@ -1548,5 +4935,9 @@ pub struct KbStatus {
     pub tree_name: String,
     pub connected: bool,
     pub entry_count: usize,
+    /// Values are encrypted at rest (Slot 9, and slots in `[encryption]`). Values written
+    /// before encryption was turned on stay plaintext until read or rewritten.
+    #[serde(default)]
+    pub encrypted: bool,
     pub error: Option<String>,
 }
@@ -0,0 +1,169 @@
+//! Tenant namespacing: a [`TenantHandle`] reads and writes only its tenant's records.
+//!
+//! Records of tenant `acme` are stored as `tenant/acme/{key}` in the slot trees; callers of the
+//! handle see plain keys. The `default` tenant owns the un-namespaced keys, so records written
+//! before tenants had their own keyspace become the default tenant's without being rewritten,
+//! and everything that reads the store directly (bootstrap, Chronos, the inbox) keeps its keys.
+//! The default tenant never sees `tenant/` keys, and no handle accepts a key that starts with
+//! `tenant/`, so one tenant cannot name another's records. Skills that take a
+//! [`TenantContext`](crate::TenantContext) go through `KnowledgeStore::tenant(&ctx.tenant_id)`.
+//! This module holds the handle; the store methods it calls live in `store.rs`, and the graph
+//! methods in `graph.rs`.
+
+use super::store::{KbRecord, KnowledgeStore, ScanPage, SCAN_MAX_LIMIT};
+use super::text_index::TextSearchHit;
+use super::vector_index::SemanticHit;
+use crate::keys::{self, DEFAULT_TENANT_ID, TENANT_PREFIX};
+
+/// One tenant's view of the knowledge store. Keys passed in and returned are the tenant's own
+/// (without the `tenant/{id}/` prefix).
+#[derive(Clone, Copy)]
+pub struct TenantHandle<'a> {
+    store: &'a KnowledgeStore,
+    tenant_id: &'a str,
+}
+
+fn unsupported(message: String) -> sled::Error {
+    sled::Error::Unsupported(message)
+}
+
+impl<'a> TenantHandle<'a> {
+    pub(crate) fn new(store: &'a KnowledgeStore, tenant_id: &'a str) -> Result<Self, sled::Error> {
+        let tenant_id = if tenant_id.is_empty() { DEFAULT_TENANT_ID } else { tenant_id };
+        if !keys::is_valid_tenant_id(tenant_id) {
+            return Err(unsupported(format!("invalid tenant id {:?}", tenant_id)));
+        }
+        Ok(Self { store, tenant_id })
+    }
+
+    pub fn tenant_id(&self) -> &str {
+        self.tenant_id
+    }
+
+    pub fn is_default(&self) -> bool {
+        self.tenant_id == DEFAULT_TENANT_ID
+    }
+
+    pub(super) fn store(&self) -> &'a KnowledgeStore {
+        self.store
+    }
+
+    /// Stored key of the tenant's `key`.
+    pub(super) fn stored_key(&self, key: &str) -> Result<String, sled::Error> {
+        if key.starts_with(TENANT_PREFIX) {
+            return Err(unsupported(format!("key {:?} is reserved for tenant namespaces", key)));
+        }
+        Ok(keys::tenant_key(self.tenant_id, key))
+    }
+
+    /// The tenant's key for a stored key, or None when the stored key belongs to another tenant.
+    fn own_key(&self, stored: &str) -> Option<String> {
+        let (tenant_id, key) = keys::parse_tenant_key(stored)?;
+        (tenant_id == self.tenant_id).then_some(key)
+    }
+
+    pub fn get(&self, slot_id: u8, key: &str) -> Result<Option<Vec<u8>>, sled::Error> {
+        self.store.get(slot_id, &self.stored_key(key)?)
+    }
+
+    pub fn get_record(&self, slot_id: u8, key: &str) -> Result<Option<KbRecord>, sled::Error> {
+        self.store.get_record(slot_id, &self.stored_key(key)?)
+    }
+
+    pub fn insert(&self, slot_id: u8, key: &str, value: &[u8]) -> Result<Option<Vec<u8>>, sled::Error> {
+        self.store.insert(slot_id, &self.stored_key(key)?, value)
+    }
+
+    pub fn insert_record(&self, slot_id: u8, key: &str, record: &KbRecord) -> Result<Option<Vec<u8>>, sled::Error> {
+        self.store.insert_record(slot_id, &self.stored_key(key)?, record)
+    }
+
+    pub fn remove(&self, slot_id: u8, key: &str) -> Result<Option<Vec<u8>>, sled::Error> {
+        self.store.remove(slot_id, &self.stored_key(key)?)
+    }
+
+    /// The tenant's key/value pairs in `slot_id` whose key starts with `prefix`, paged like
+    /// [`KnowledgeStore::scan_prefix`]. Cursors are the tenant's keys.
+    pub fn scan_prefix(
+        &self,
+        slot_id: u8,
+        prefix: &str,
+        cursor: Option<&str>,
+        limit: usize,
+    ) -> Result<ScanPage, sled::Error> {
+        let stored_prefix = self.stored_key(prefix)?;
+        let stored_cursor = cursor.map(|c| self.stored_key(c)).transpose()?;
+        let mut page = ScanPage::default();
+        let limit = limit.min(SCAN_MAX_LIMIT);
+        let mut after = stored_cursor;
+        // The default tenant's range can hold other tenants' keys; skip them and keep reading.
+        loop {
+            let raw = self.store.scan_prefix(slot_id, &stored_prefix, after.as_deref(), limit)?;
+            for (stored, value) in raw.entries {
+                if page.entries.len() == limit {
+                    page.next_cursor = page.entries.last().map(|(key, _)| key.clone());
+                    return Ok(page);
+                }
+                if let Some(key) = self.own_key(&stored) {
+                    page.entries.push((key, value));
+                }
+            }
+            match raw.next_cursor {
+                Some(next) => after = Some(next),
+                None => return Ok(page),
+            }
+        }
+    }
+
+    /// All of the tenant's key/value pairs in `slot_id`, in key order.
+    pub fn scan_kv(&self, slot_id: u8) -> Result<Vec<(String, Vec<u8>)>, sled::Error> {
+        let mut out = Vec::new();
+        let mut cursor: Option<String> = None;
+        loop {
+            let page = self.scan_prefix(slot_id, "", cursor.as_deref(), SCAN_MAX_LIMIT)?;
+            out.extend(page.entries);
+            match page.next_cursor {
+                Some(next) => cursor = Some(next),
+                None => return Ok(out),
+            }
+        }
+    }
+
+    /// The tenant's records in `slot_id` that deserialize as [`KbRecord`].
+    pub fn scan_records(&self, slot_id: u8) -> Result<Vec<(String, KbRecord)>, sled::Error> {
+        Ok(self
+            .scan_kv(slot_id)?
+            .into_iter()
+            .filter_map(|(key, bytes)| KbRecord::from_bytes(&bytes).map(|r| (key, r)))
+            .collect())
+    }
+
+    /// Number of the tenant's entries in `slot_id`.
+    pub fn count(&self, slot_id: u8) -> Result<usize, sled::Error> {
+        Ok(self.scan_kv(slot_id)?.len())
+    }
+
+    /// [`KnowledgeStore::text_search`] over the tenant's records only.
+    pub fn text_search(&self, slots: &[u8], query: &str, limit: usize) -> Result<Vec<TextSearchHit>, sled::Error> {
+        let mut hits = self.store.text_search_where(slots, query, limit, |stored| self.own_key(stored).is_some())?;
+        for hit in &mut hits {
+            if let Some(key) = self.own_key(&hit.key) {
+                hit.key = key;
+            }
+        }
+        Ok(hits)
+    }
+
+    /// [`KnowledgeStore::semantic_search`] over the tenant's records only.
+    pub fn semantic_search(&self, slot_id: u8, embedding: &[f32], k: usize) -> Result<Vec<SemanticHit>, sled::Error> {
+        let mut hits = self
+            .store
+            .semantic_search_where(slot_id, embedding, k, |stored| self.own_key(stored).is_some())?;
+        for hit in &mut hits {
+            if let Some(key) = self.own_key(&hit.key) {
+                hit.key = key;
+            }
+        }
+        Ok(hits)
+    }
+}
@@ -0,0 +1,155 @@
+//! Full-text index over [`KbRecord`](super::KbRecord) content in slots 1–8 (Shadow is encrypted
+//! and never indexed).
+//!
+//! Postings live in a dedicated sled tree: `p/{slot}/{term}/{key}` → term frequency and document
+//! length, `d/{slot}/{key}` → the terms a record was indexed under (so rewrites and removals can
+//! drop its postings), and `s/{slot}` → document count and total length for BM25. `built/{slot}`
+//! marks a slot whose existing records have been indexed; the first search of an unmarked slot
+//! indexes it. Writes through `KnowledgeStore::insert`/`remove` keep the index current. This
+//! module holds the tokenizer, keys and types; the store methods (`text_search`,
+//! `rebuild_text_index`) live in `store.rs`.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Sled tree holding the full-text index.
+pub const TEXT_INDEX_TREE: &str = "text_index";
+/// Results returned when a search does not set a limit.
+pub const TEXT_SEARCH_DEFAULT_LIMIT: usize = 10;
+
+/// BM25 term-frequency saturation.
+const BM25_K1: f32 = 1.2;
+/// BM25 document-length normalization.
+const BM25_B: f32 = 0.75;
+
+/// Words too common to be worth a posting list.
+const STOPWORDS: &[&str] = &[
+    "a", "an", "and", "are", "as", "at", "be", "but", "by", "for", "from", "has", "have", "in", "is", "it", "its",
+    "of", "on", "or", "that", "the", "this", "to", "was", "were", "will", "with",
+];
+
+/// One search result.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TextSearchHit {
+    pub slot_id: u8,
+    pub key: String,
+    /// BM25 relevance; only comparable within one search.
+    pub score: f32,
+    pub content: String,
+    pub metadata: serde_json::Value,
+    pub timestamp: i64,
+}
+
+/// Terms a record was indexed under, kept so its postings can be removed.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub(crate) struct IndexedDoc {
+    pub len: u32,
+    pub terms: Vec<String>,
+}
+
+/// Per-slot totals for BM25.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub(crate) struct SlotStats {
+    pub docs: u64,
+    pub total_len: u64,
+}
+
+impl SlotStats {
+    pub fn avg_len(&self) -> f32 {
+        if self.docs == 0 {
+            1.0
+        } else {
+            (self.total_len as f32 / self.docs as f32).max(1.0)
+        }
+    }
+}
+
+/// Lowercased alphanumeric tokens of `text`, without stopwords and one-character tokens.
+pub fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|t| t.chars().count() > 1)
+        .map(str::to_lowercase)
+        .filter(|t| !STOPWORDS.contains(&t.as_str()))
+        .collect()
+}
+
+/// Term frequencies of `text` and its token count.
+pub(crate) fn term_frequencies(text: &str) -> (BTreeMap<String, u32>, u32) {
+    let tokens = tokenize(text);
+    let len = tokens.len() as u32;
+    let mut tf = BTreeMap::new();
+    for token in tokens {
+        *tf.entry(token).or_insert(0) += 1;
+    }
+    (tf, len)
+}
+
+/// BM25 weight of one term in one document.
+pub(crate) fn bm25(tf: u32, doc_len: u32, df: usize, stats: &SlotStats) -> f32 {
+    let n = stats.docs.max(df as u64) as f32;
+    let df = df as f32;
+    let idf = (1.0 + (n - df + 0.5) / (df + 0.5)).ln();
+    let tf = tf as f32;
+    let norm = 1.0 - BM25_B + BM25_B * doc_len as f32 / stats.avg_len();
+    idf * tf * (BM25_K1 + 1.0) / (tf + BM25_K1 * norm)
+}
+
+/// `p/{slot}/{term}/` – postings of one term.
+pub(crate) fn posting_prefix(slot_id: u8, term: &str) -> String {
+    format!("p/{}/{}/", slot_id, term)
+}
+
+/// `p/{slot}/` – every posting of a slot.
+pub(crate) fn slot_posting_prefix(slot_id: u8) -> String {
+    format!("p/{}/", slot_id)
+}
+
+pub(crate) fn doc_key(slot_id: u8, key: &str) -> String {
+    format!("d/{}/{}", slot_id, key)
+}
+
+/// `d/{slot}/` – every indexed document of a slot.
+pub(crate) fn slot_doc_prefix(slot_id: u8) -> String {
+    format!("d/{}/", slot_id)
+}
+
+pub(crate) fn stats_key(slot_id: u8) -> String {
+    format!("s/{}", slot_id)
+}
+
+pub(crate) fn built_key(slot_id: u8) -> String {
+    format!("built/{}", slot_id)
+}
+
+/// Posting value: term frequency and document length, little-endian.
+pub(crate) fn encode_posting(tf: u32, doc_len: u32) -> [u8; 8] {
+    let mut out = [0u8; 8];
+    out[..4].copy_from_slice(&tf.to_le_bytes());
+    out[4..].copy_from_slice(&doc_len.to_le_bytes());
+    out
+}
+
+pub(crate) fn decode_posting(bytes: &[u8]) -> Option<(u32, u32)> {
+    let tf = u32::from_le_bytes(bytes.get(..4)?.try_into().ok()?);
+    let len = u32::from_le_bytes(bytes.get(4..8)?.try_into().ok()?);
+    Some((tf, len))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tokenizer_lowercases_and_drops_noise() {
+        assert_eq!(tokenize("The Roof-Repair quote, for 2 houses!"), ["roof", "repair", "quote", "houses"]);
+        assert_eq!(term_frequencies("leak leak roof").0.get("leak"), Some(&2));
+    }
+
+    #[test]
+    fn bm25_prefers_rare_terms_and_short_documents() {
+        let stats = SlotStats { docs: 100, total_len: 1_000 };
+        assert!(bm25(1, 10, 2, &stats) > bm25(1, 10, 50, &stats));
+        assert!(bm25(1, 5, 2, &stats) > bm25(1, 50, 2, &stats));
+        assert_eq!(decode_posting(&encode_posting(3, 40)), Some((3, 40)));
+    }
+}
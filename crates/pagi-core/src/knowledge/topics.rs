@@ -0,0 +1,40 @@
+//! Pub/sub topics between agents in KB_SOMA.
+//!
+//! A message published to a topic lands in the topic's log (`topic/{topic}/{timestamp_ms}_{id}`);
+//! each agent subscribed to it (`subscription/{agent_id}/{topic}`) gets a copy in its inbox when
+//! the Heartbeat fans the log out
+//! ([`KnowledgeStore::heartbeat_fan_out_topics`](crate::KnowledgeStore::heartbeat_fan_out_topics)).
+//! A subscription remembers the last topic message it delivered, so fan-out resumes where it
+//! stopped and a new subscriber only sees what is published after it subscribed. Copies keep the
+//! message's id and timestamp and carry [`AgentMessage::topic`](super::AgentMessage::topic).
+
+use serde::{Deserialize, Serialize};
+
+/// Topic messages delivered to one subscriber per fan-out pass.
+pub const TOPIC_FANOUT_BATCH: usize = 100;
+
+/// A topic name is one segment of ASCII letters, digits, `-`, `_` and `.`, at most 64 long.
+pub fn is_valid_topic_name(topic: &str) -> bool {
+    crate::keys::is_valid_tenant_id(topic)
+}
+
+/// An agent's subscription to a topic.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TopicSubscription {
+    pub agent_id: String,
+    pub topic: String,
+    pub created_at_ms: i64,
+    /// Key of the last topic message delivered to the agent's inbox.
+    #[serde(default)]
+    pub delivered_through: Option<String>,
+}
+
+impl TopicSubscription {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        serde_json::to_vec(self).unwrap_or_default()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        serde_json::from_slice(bytes).ok()
+    }
+}
@@ -0,0 +1,126 @@
+//! Transactional multi-key writes: several keys of one slot, or of several slots, are written
+//! all-or-nothing.
+//!
+//! `KnowledgeStore::transaction(slot, |tx| ...)` runs the closure in a backend transaction over
+//! the slot's tree; `KnowledgeStore::transaction_multi(&[slots], |tx| ...)` does the same across
+//! trees, with `tx.slot(id)` giving the per-slot view. Either every write of the closure lands or
+//! none does, so a crash between "mark message processed" and "push reply" cannot leave one
+//! without the other. sled re-runs the closure when another writer touched a key it read (SQLite
+//! holds the write lock instead), so the closure must not have side effects of its own (generate
+//! ids and timestamps before it) and must not call the store outside `tx`. Index updates and
+//! change events follow the commit, once per write. Slots 1–8 only: Shadow writes need the vault.
+//! Values of encrypted slots are sealed and opened as in `KnowledgeStore::insert` and `get`.
+//! This module holds the transaction views; the store methods live in `store.rs`.
+
+use super::store::{check_value_size, KbRecord, KnowledgeStore};
+use crate::keys;
+use crate::storage::{KvTxResult, KvTxTree};
+use sled::transaction::ConflictableTransactionError;
+use std::cell::RefCell;
+
+/// Result of an operation inside a transaction closure. Return `Err(abort_transaction(..))` to
+/// roll back; the store method then returns that error.
+pub type KbTxResult<T> = KvTxResult<T>;
+
+/// Error that aborts the transaction with `message`.
+pub fn abort_transaction(message: impl Into<String>) -> ConflictableTransactionError<sled::Error> {
+    ConflictableTransactionError::Abort(sled::Error::Unsupported(message.into()))
+}
+
+/// One write staged by a transaction, applied to the indexes and change feed after the commit.
+pub(crate) struct TxWrite {
+    pub slot_id: u8,
+    pub key: String,
+    /// Original (uncompressed) bytes; None for a removal.
+    pub value: Option<Vec<u8>>,
+    /// Whether the key held a value before the write.
+    pub existed: bool,
+}
+
+/// Writes of the current attempt; cleared when the closure is re-run.
+pub(crate) type TxWrites = RefCell<Vec<TxWrite>>;
+
+/// One slot's view inside a transaction. Values read and written are the original bytes;
+/// compression and encryption are applied as in `KnowledgeStore::insert`.
+pub struct KbTransaction<'a> {
+    slot_id: u8,
+    tree: &'a dyn KvTxTree,
+    writes: &'a TxWrites,
+    /// Encodes values (size limit, compression, encryption) as the store does.
+    store: &'a KnowledgeStore,
+}
+
+impl<'a> KbTransaction<'a> {
+    pub(crate) fn new(slot_id: u8, tree: &'a dyn KvTxTree, writes: &'a TxWrites, store: &'a KnowledgeStore) -> Self {
+        Self { slot_id, tree, writes, store }
+    }
+
+    fn decode(&self, stored: Option<Vec<u8>>) -> KbTxResult<Option<Vec<u8>>> {
+        stored
+            .map(|v| self.store.decode_value(self.slot_id, &v))
+            .transpose()
+            .map_err(|e| abort_transaction(e.to_string()))
+    }
+
+    pub fn slot_id(&self) -> u8 {
+        self.slot_id
+    }
+
+    pub fn get(&self, key: &str) -> KbTxResult<Option<Vec<u8>>> {
+        self.decode(self.tree.get(key.as_bytes())?)
+    }
+
+    pub fn get_record(&self, key: &str) -> KbTxResult<Option<KbRecord>> {
+        Ok(self.get(key)?.and_then(|b| KbRecord::from_bytes(&b)))
+    }
+
+    /// Stages `value` at `key`; returns the previous value. A key that breaks its keyspace's
+    /// format, or a value over the store's size limit, aborts the transaction.
+    pub fn insert(&self, key: &str, value: &[u8]) -> KbTxResult<Option<Vec<u8>>> {
+        if let Err(e) = keys::validate_key(self.slot_id, key) {
+            return Err(abort_transaction(e.to_string()));
+        }
+        check_value_size(self.slot_id, key, value.len(), self.store.max_value_bytes()).map_err(abort_transaction)?;
+        let stored = self.store.encode_value(self.slot_id, value).map_err(|e| abort_transaction(e.to_string()))?;
+        let prev = self.tree.insert(key.as_bytes(), &stored)?;
+        self.writes.borrow_mut().push(TxWrite {
+            slot_id: self.slot_id,
+            key: key.to_string(),
+            value: Some(value.to_vec()),
+            existed: prev.is_some(),
+        });
+        self.decode(prev)
+    }
+
+    pub fn insert_record(&self, key: &str, record: &KbRecord) -> KbTxResult<Option<Vec<u8>>> {
+        self.insert(key, &record.to_bytes())
+    }
+
+    /// Stages the removal of `key`; returns the previous value.
+    pub fn remove(&self, key: &str) -> KbTxResult<Option<Vec<u8>>> {
+        let prev = self.tree.remove(key.as_bytes())?;
+        if prev.is_some() {
+            self.writes.borrow_mut().push(TxWrite { slot_id: self.slot_id, key: key.to_string(), value: None, existed: true });
+        }
+        self.decode(prev)
+    }
+}
+
+/// View of a transaction spanning several slots.
+pub struct KbMultiTransaction<'a> {
+    slots: Vec<KbTransaction<'a>>,
+}
+
+impl<'a> KbMultiTransaction<'a> {
+    pub(crate) fn new(slots: Vec<KbTransaction<'a>>) -> Self {
+        Self { slots }
+    }
+
+    /// The view of `slot_id`; aborts when the slot was not named when the transaction started.
+    pub fn slot(&self, slot_id: u8) -> KbTxResult<&KbTransaction<'a>> {
+        self.slots
+            .iter()
+            .find(|tx| tx.slot_id == slot_id)
+            .ok_or_else(|| abort_transaction(format!("KB-{} is not part of this transaction", slot_id)))
+    }
+}
@@ -18,6 +18,14 @@
 //!
 //! Decrypted blobs are returned in a **memory-locked** buffer (`LockedVec`) so
 //! the OS cannot swap them to disk (mlock/VirtualLock).
+//!
+//! ## Encrypted Slots 1–8
+//!
+//! Other slots can be encrypted with the same key (`[encryption] slots`, e.g. Chronos and
+//! Kardia). Their values are sealed as `[ENCRYPTED_MARKER][SEALED_VERSION][nonce][ciphertext+tag]`;
+//! the header tells them apart from plaintext values written before encryption was turned on.
+//! Plaintext that happens to begin with the marker is escaped on write (wrapped in the
+//! compression header's stored codec), so a value is only ever read as sealed if it was sealed.
 
 use crate::secure_memory::LockedVec;
 use aes_gcm::{
@@ -32,6 +40,41 @@ const NONCE_LEN: usize = 12;
 /// Environment variable holding the 64-hex-char master key.
 const ENV_SHADOW_KEY: &str = "PAGI_SHADOW_KEY";
 
+/// First bytes of a sealed slot 1–8 value.
+pub const ENCRYPTED_MARKER: [u8; 4] = *b"\xffPKV";
+/// Sealed format version, after the marker.
+pub const SEALED_VERSION: u8 = 1;
+
+const SEALED_HEADER_LEN: usize = ENCRYPTED_MARKER.len() + 1;
+
+/// The encrypted blob of a sealed slot 1–8 value.
+fn sealed_blob(stored: &[u8]) -> Option<&[u8]> {
+    match stored.strip_prefix(&ENCRYPTED_MARKER)? {
+        [SEALED_VERSION, blob @ ..] => Some(blob),
+        _ => None,
+    }
+}
+
+/// Whether `stored` is a sealed slot 1–8 value.
+pub(crate) fn is_sealed(stored: &[u8]) -> bool {
+    sealed_blob(stored).is_some()
+}
+
+/// Whether a plaintext value must be escaped before it is stored, because it would otherwise
+/// read back as sealed.
+pub(crate) fn looks_sealed(value: &[u8]) -> bool {
+    value.starts_with(&ENCRYPTED_MARKER)
+}
+
+/// `[encryption]`: slots 1–8 encrypted at rest with the vault key (Slot 9 always is).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EncryptionConfig {
+    /// Slot ids, e.g. `[4, 7]` for Chronos and Kardia. Needs `PAGI_SHADOW_KEY`: without it the
+    /// slots cannot be written.
+    pub slots: Vec<u8>,
+}
+
 /// Errors specific to the Shadow Vault.
 #[derive(Debug, Clone)]
 pub enum VaultError {
@@ -89,10 +132,7 @@ fn default_true() -> bool {
 impl EmotionalAnchor {
     /// Creates a new active anchor with the current timestamp.
     pub fn new(anchor_type: impl Into<String>, intensity: f32) -> Self {
-        let ts = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .map(|d| d.as_millis() as i64)
-            .unwrap_or(0);
+        let ts = crate::clock::system_now_ms();
         Self {
             anchor_type: anchor_type.into(),
             intensity: intensity.clamp(0.0, 1.0),
@@ -139,10 +179,10 @@ impl SecretVault {
         Self { cipher }
     }
 
-    /// Attempts to create a vault from the `PAGI_SHADOW_KEY` environment variable.
-    /// Returns a locked vault if the env var is missing or malformed.
+    /// Attempts to create a vault from the `PAGI_SHADOW_KEY` secret (see [`crate::secret`]).
+    /// Returns a locked vault if it is missing or malformed.
     pub fn from_env() -> Self {
-        let key_bytes = std::env::var(ENV_SHADOW_KEY).ok().and_then(|hex| {
+        let key_bytes = crate::secrets::secret(ENV_SHADOW_KEY).and_then(|hex| {
             let hex = hex.trim().replace([' ', '\n'], "");
             if hex.len() != 64 {
                 tracing::warn!(
@@ -225,6 +265,25 @@ impl SecretVault {
             .map_err(|e| VaultError::DecryptionFailed(e.to_string()))
     }
 
+    /// Encrypts a slot 1–8 value behind [`ENCRYPTED_MARKER`] and [`SEALED_VERSION`].
+    pub(crate) fn seal(&self, data: &[u8]) -> Result<Vec<u8>, VaultError> {
+        let blob = self.encrypt_blob(data)?;
+        let mut out = Vec::with_capacity(SEALED_HEADER_LEN + blob.len());
+        out.extend_from_slice(&ENCRYPTED_MARKER);
+        out.push(SEALED_VERSION);
+        out.extend_from_slice(&blob);
+        Ok(out)
+    }
+
+    /// Decrypts a value produced by [`seal`](Self::seal); values without the header are
+    /// returned as stored.
+    pub(crate) fn unseal(&self, stored: &[u8]) -> Result<Vec<u8>, VaultError> {
+        match sealed_blob(stored) {
+            Some(blob) => Ok(self.decrypt_blob(blob)?.as_slice().to_vec()),
+            None => Ok(stored.to_vec()),
+        }
+    }
+
     /// Encrypts an `EmotionalAnchor` for storage in Slot 9.
     pub fn encrypt_anchor(&self, anchor: &EmotionalAnchor) -> Result<Vec<u8>, VaultError> {
         self.encrypt_blob(&anchor.to_bytes())
@@ -320,6 +379,18 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn sealed_values_carry_the_marker_and_plaintext_passes_through() {
+        let vault = SecretVault::new(Some(&test_key()));
+        let sealed = vault.seal(b"{\"content\":\"hi\"}").unwrap();
+        assert!(is_sealed(&sealed));
+        assert_eq!(vault.unseal(&sealed).unwrap(), b"{\"content\":\"hi\"}");
+        assert_eq!(vault.unseal(b"{}").unwrap(), b"{}");
+        assert!(!is_sealed(b"\x01hello"));
+        assert!(!is_sealed(b"\xffPKV\x07unknown version"));
+        assert!(matches!(SecretVault::new(None).unseal(&sealed), Err(VaultError::Locked)));
+    }
+
     #[test]
     fn corrupt_blob_detected() {
         let key = test_key();
@@ -0,0 +1,440 @@
+//! Approximate-nearest-neighbor index (HNSW) over [`KbRecord`](super::KbRecord) embeddings in
+//! slots 1–8, so semantic search does not have to score every record.
+//!
+//! Each slot has its own graph in a dedicated sled tree: `v/{slot}/{key}` → the node's level and
+//! unit-normalized vector (little-endian f32s), `g/{slot}/{level}/{key}` → its neighbor keys on
+//! that level, `e/{slot}` → the entry point and the slot's vector size. `built/{slot}` marks a slot
+//! whose existing records have been indexed; the first search of an unmarked slot indexes it.
+//! Scores are cosine similarity. A slot indexes one vector size; records of another size are left
+//! out (the store falls back to a scan for queries of that size). This module holds the graph;
+//! the store methods (`semantic_search`, `rebuild_vector_index`) live in `store.rs`.
+
+use crate::storage::{KvBatch, KvTree};
+use serde::{Deserialize, Serialize};
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+
+/// Sled tree holding the vector index.
+pub const VECTOR_INDEX_TREE: &str = "vector_index";
+
+/// Neighbors per node on levels above 0.
+const M: usize = 16;
+/// Neighbors per node on level 0.
+const M0: usize = 2 * M;
+/// Candidate list size while inserting.
+const EF_CONSTRUCTION: usize = 100;
+/// Minimum candidate list size while searching.
+const EF_SEARCH: usize = 64;
+const MAX_LEVEL: u8 = 16;
+
+/// One semantic search result.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SemanticHit {
+    pub slot_id: u8,
+    pub key: String,
+    /// Cosine similarity to the query (-1.0–1.0).
+    pub score: f32,
+    pub content: String,
+    pub metadata: serde_json::Value,
+    pub timestamp: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct EntryPoint {
+    key: String,
+    level: u8,
+    dims: usize,
+}
+
+/// Similarity-ordered candidate.
+#[derive(Debug, Clone, PartialEq)]
+struct Scored(f32, String);
+
+impl Eq for Scored {}
+
+impl PartialOrd for Scored {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Scored {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.total_cmp(&other.0).then_with(|| other.1.cmp(&self.1))
+    }
+}
+
+/// `vector` scaled to unit length; None for empty, zero or non-finite vectors.
+pub(crate) fn normalize(vector: &[f32]) -> Option<Vec<f32>> {
+    let norm = vector.iter().map(|x| x * x).sum::<f32>().sqrt();
+    (norm > 0.0 && norm.is_finite()).then(|| vector.iter().map(|x| x / norm).collect())
+}
+
+fn dot(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+/// Node level drawn from the key's hash, so a rebuild lays out the same graph.
+fn level_for(key: &str) -> u8 {
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    key.hash(&mut hasher);
+    let unit = ((hasher.finish() >> 11) as f64 + 1.0) / (1u64 << 53) as f64;
+    let level = (-unit.ln() / (M as f64).ln()).floor();
+    (level as u8).min(MAX_LEVEL)
+}
+
+fn encode_node(level: u8, vector: &[f32]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(1 + vector.len() * 4);
+    out.push(level);
+    for x in vector {
+        out.extend_from_slice(&x.to_le_bytes());
+    }
+    out
+}
+
+fn decode_node(bytes: &[u8]) -> Option<(u8, Vec<f32>)> {
+    let (&level, rest) = bytes.split_first()?;
+    let vector = rest
+        .chunks_exact(4)
+        .map(|c| f32::from_le_bytes([c[0], c[1], c[2], c[3]]))
+        .collect();
+    Some((level, vector))
+}
+
+/// HNSW graph of one slot, read and written directly in the index tree. Writers must be
+/// serialized by the caller.
+pub(crate) struct Hnsw<'a> {
+    tree: &'a dyn KvTree,
+    slot_id: u8,
+}
+
+impl<'a> Hnsw<'a> {
+    pub fn new(tree: &'a dyn KvTree, slot_id: u8) -> Self {
+        Self { tree, slot_id }
+    }
+
+    fn node_key(&self, key: &str) -> String {
+        format!("v/{}/{}", self.slot_id, key)
+    }
+
+    fn node_prefix(&self) -> String {
+        format!("v/{}/", self.slot_id)
+    }
+
+    fn neighbors_key(&self, level: u8, key: &str) -> String {
+        format!("g/{}/{}/{}", self.slot_id, level, key)
+    }
+
+    fn entry_key(&self) -> String {
+        format!("e/{}", self.slot_id)
+    }
+
+    pub fn built_key(&self) -> String {
+        format!("built/{}", self.slot_id)
+    }
+
+    fn node(&self, key: &str) -> Result<Option<(u8, Vec<f32>)>, sled::Error> {
+        Ok(self.tree.get(self.node_key(key).as_bytes())?.and_then(|b| decode_node(&b)))
+    }
+
+    fn neighbors(&self, level: u8, key: &str) -> Result<Vec<String>, sled::Error> {
+        Ok(self
+            .tree
+            .get(self.neighbors_key(level, key).as_bytes())?
+            .and_then(|b| serde_json::from_slice(&b).ok())
+            .unwrap_or_default())
+    }
+
+    fn set_neighbors(&self, level: u8, key: &str, neighbors: &[String]) -> Result<(), sled::Error> {
+        self.tree.insert(
+            self.neighbors_key(level, key).as_bytes(),
+            &serde_json::to_vec(neighbors).unwrap_or_default(),
+        )?;
+        Ok(())
+    }
+
+    fn entry(&self) -> Result<Option<EntryPoint>, sled::Error> {
+        Ok(self
+            .tree
+            .get(self.entry_key().as_bytes())?
+            .and_then(|b| serde_json::from_slice(&b).ok()))
+    }
+
+    fn set_entry(&self, entry: &EntryPoint) -> Result<(), sled::Error> {
+        self.tree.insert(self.entry_key().as_bytes(), &serde_json::to_vec(entry).unwrap_or_default())?;
+        Ok(())
+    }
+
+    /// Vector size of the slot's indexed records; None while the graph is empty.
+    pub fn dims(&self) -> Result<Option<usize>, sled::Error> {
+        Ok(self.entry()?.map(|e| e.dims))
+    }
+
+    /// Similarity of `query` to `key`, with a per-call vector cache. None for a removed node.
+    fn similarity(
+        &self,
+        query: &[f32],
+        key: &str,
+        cache: &mut HashMap<String, Vec<f32>>,
+    ) -> Result<Option<f32>, sled::Error> {
+        if let Some(vector) = cache.get(key) {
+            return Ok(Some(dot(query, vector)));
+        }
+        let Some((_, vector)) = self.node(key)? else {
+            return Ok(None);
+        };
+        let sim = dot(query, &vector);
+        cache.insert(key.to_string(), vector);
+        Ok(Some(sim))
+    }
+
+    /// Best-first search of one level from `entry`; up to `ef` nodes, most similar first.
+    fn search_level(
+        &self,
+        query: &[f32],
+        entry: Vec<Scored>,
+        ef: usize,
+        level: u8,
+        cache: &mut HashMap<String, Vec<f32>>,
+    ) -> Result<Vec<Scored>, sled::Error> {
+        let mut visited: HashSet<String> = entry.iter().map(|s| s.1.clone()).collect();
+        let mut candidates: BinaryHeap<Scored> = entry.iter().cloned().collect();
+        let mut results: BinaryHeap<Reverse<Scored>> = entry.into_iter().map(Reverse).collect();
+        while let Some(current) = candidates.pop() {
+            if results.len() >= ef && results.peek().is_some_and(|w| current.0 < w.0 .0) {
+                break;
+            }
+            for neighbor in self.neighbors(level, &current.1)? {
+                if !visited.insert(neighbor.clone()) {
+                    continue;
+                }
+                let Some(sim) = self.similarity(query, &neighbor, cache)? else {
+                    continue;
+                };
+                if results.len() < ef || results.peek().is_some_and(|w| sim > w.0 .0) {
+                    candidates.push(Scored(sim, neighbor.clone()));
+                    results.push(Reverse(Scored(sim, neighbor)));
+                    if results.len() > ef {
+                        results.pop();
+                    }
+                }
+            }
+        }
+        let mut out: Vec<Scored> = results.into_iter().map(|r| r.0).collect();
+        out.sort_by(|a, b| b.cmp(a));
+        Ok(out)
+    }
+
+    /// Greedy descent from the entry point to `level`, returning the closest node found.
+    fn descend(
+        &self,
+        query: &[f32],
+        entry: &EntryPoint,
+        level: u8,
+        cache: &mut HashMap<String, Vec<f32>>,
+    ) -> Result<Vec<Scored>, sled::Error> {
+        let Some(sim) = self.similarity(query, &entry.key, cache)? else {
+            return Ok(Vec::new());
+        };
+        let mut nearest = vec![Scored(sim, entry.key.clone())];
+        for l in (level + 1..=entry.level).rev() {
+            nearest = self.search_level(query, nearest, 1, l, cache)?;
+        }
+        Ok(nearest)
+    }
+
+    /// The `k` indexed keys most similar to `query` (already normalized), most similar first.
+    pub fn search(&self, query: &[f32], k: usize) -> Result<Vec<(String, f32)>, sled::Error> {
+        let Some(entry) = self.entry()? else {
+            return Ok(Vec::new());
+        };
+        if entry.dims != query.len() || k == 0 {
+            return Ok(Vec::new());
+        }
+        let mut cache = HashMap::new();
+        let nearest = self.descend(query, &entry, 0, &mut cache)?;
+        let mut found = self.search_level(query, nearest, EF_SEARCH.max(k), 0, &mut cache)?;
+        found.truncate(k);
+        Ok(found.into_iter().map(|s| (s.1, s.0)).collect())
+    }
+
+    /// Adds (or replaces) `key` with `vector`. Returns false when the vector cannot be indexed
+    /// (zero length, or a size other than the slot's).
+    pub fn insert(&self, key: &str, vector: &[f32]) -> Result<bool, sled::Error> {
+        self.remove(key)?;
+        let Some(vector) = normalize(vector) else {
+            return Ok(false);
+        };
+        let entry = self.entry()?;
+        if entry.as_ref().is_some_and(|e| e.dims != vector.len()) {
+            return Ok(false);
+        }
+        let level = level_for(key);
+        self.tree.insert(self.node_key(key).as_bytes(), &encode_node(level, &vector))?;
+        let Some(entry) = entry else {
+            self.set_entry(&EntryPoint { key: key.to_string(), level, dims: vector.len() })?;
+            return Ok(true);
+        };
+
+        let mut cache = HashMap::new();
+        cache.insert(key.to_string(), vector.clone());
+        let mut nearest = self.descend(&vector, &entry, level, &mut cache)?;
+        for l in (0..=level.min(entry.level)).rev() {
+            let candidates = self.search_level(&vector, nearest, EF_CONSTRUCTION, l, &mut cache)?;
+            let max = if l == 0 { M0 } else { M };
+            let neighbors: Vec<String> = candidates
+                .iter()
+                .filter(|s| s.1 != key)
+                .take(M)
+                .map(|s| s.1.clone())
+                .collect();
+            self.set_neighbors(l, key, &neighbors)?;
+            for neighbor in &neighbors {
+                let mut links = self.neighbors(l, neighbor)?;
+                links.push(key.to_string());
+                self.set_pruned(l, neighbor, links, max, &mut cache)?;
+            }
+            nearest = candidates;
+        }
+        if level > entry.level {
+            self.set_entry(&EntryPoint { key: key.to_string(), level, dims: entry.dims })?;
+        }
+        Ok(true)
+    }
+
+    /// Stores the links of `key`, keeping the `max` most similar to it when there are more.
+    fn set_pruned(
+        &self,
+        level: u8,
+        key: &str,
+        links: Vec<String>,
+        max: usize,
+        cache: &mut HashMap<String, Vec<f32>>,
+    ) -> Result<(), sled::Error> {
+        let mut unique: Vec<String> = links.into_iter().filter(|l| l != key).collect();
+        unique.sort();
+        unique.dedup();
+        if unique.len() <= max {
+            return self.set_neighbors(level, key, &unique);
+        }
+        let Some((_, base)) = self.node(key)? else {
+            return Ok(());
+        };
+        let mut scored = Vec::with_capacity(unique.len());
+        for link in unique {
+            if let Some(sim) = self.similarity(&base, &link, cache)? {
+                scored.push(Scored(sim, link));
+            }
+        }
+        scored.sort_by(|a, b| b.cmp(a));
+        scored.truncate(max);
+        let kept: Vec<String> = scored.into_iter().map(|s| s.1).collect();
+        self.set_neighbors(level, key, &kept)
+    }
+
+    /// Drops `key` from the graph, reconnecting its neighbors to each other. Returns whether it
+    /// was indexed.
+    pub fn remove(&self, key: &str) -> Result<bool, sled::Error> {
+        let Some((level, _)) = self.node(key)? else {
+            return Ok(false);
+        };
+        self.tree.remove(self.node_key(key).as_bytes())?;
+        let mut cache = HashMap::new();
+        for l in 0..=level {
+            let orphaned = self.neighbors(l, key)?;
+            let max = if l == 0 { M0 } else { M };
+            for neighbor in &orphaned {
+                let mut links: Vec<String> = self.neighbors(l, neighbor)?.into_iter().filter(|n| n != key).collect();
+                links.extend(orphaned.iter().filter(|o| *o != neighbor).cloned());
+                self.set_pruned(l, neighbor, links, max, &mut cache)?;
+            }
+            self.tree.remove(self.neighbors_key(l, key).as_bytes())?;
+        }
+        if self.entry()?.is_some_and(|e| e.key == key) {
+            self.reelect_entry()?;
+        }
+        Ok(true)
+    }
+
+    /// Makes the highest-level remaining node the entry point (or clears it when none is left).
+    fn reelect_entry(&self) -> Result<(), sled::Error> {
+        let dims = self.dims()?.unwrap_or(0);
+        let prefix = self.node_prefix();
+        let mut best: Option<(u8, String)> = None;
+        for item in self.tree.scan_prefix(prefix.as_bytes()) {
+            let (k, v) = item?;
+            let level = v.first().copied().unwrap_or(0);
+            if best.as_ref().is_none_or(|(l, _)| level > *l) {
+                best = Some((level, String::from_utf8_lossy(&k[prefix.len()..]).into_owned()));
+            }
+        }
+        match best {
+            Some((level, key)) => self.set_entry(&EntryPoint { key, level, dims }),
+            None => {
+                self.tree.remove(self.entry_key().as_bytes())?;
+                Ok(())
+            }
+        }
+    }
+
+    /// Removes the whole graph of the slot, including its built marker.
+    pub fn clear(&self) -> Result<(), sled::Error> {
+        let mut batch = KvBatch::default();
+        for prefix in [self.node_prefix(), format!("g/{}/", self.slot_id)] {
+            for item in self.tree.scan_prefix(prefix.as_bytes()) {
+                batch.remove(item?.0);
+            }
+        }
+        batch.remove(self.entry_key().as_bytes());
+        batch.remove(self.built_key().as_bytes());
+        self.tree.apply_batch(batch)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::{KvBackend, SledBackend};
+
+    fn unit(angle: f32) -> Vec<f32> {
+        vec![angle.cos(), angle.sin(), 0.1]
+    }
+
+    #[test]
+    fn graph_finds_nearest_and_survives_removals() {
+        let db = SledBackend::temporary().unwrap();
+        let tree = db.open_tree(VECTOR_INDEX_TREE).unwrap();
+        let graph = Hnsw::new(&*tree, 3);
+        for i in 0..300 {
+            assert!(graph.insert(&format!("k{:03}", i), &unit(i as f32 * 0.02)).unwrap());
+        }
+        assert!(!graph.insert("wrong-size", &[1.0, 0.0]).unwrap());
+        assert!(!graph.insert("zero", &[0.0, 0.0, 0.0]).unwrap());
+
+        let query = normalize(&unit(150.0 * 0.02)).unwrap();
+        let found = graph.search(&query, 3).unwrap();
+        assert_eq!(found[0].0, "k150");
+        assert!(found.iter().all(|(k, _)| ["k149", "k150", "k151"].contains(&k.as_str())));
+
+        for i in 140..=160 {
+            assert!(graph.remove(&format!("k{:03}", i)).unwrap());
+        }
+        let found = graph.search(&query, 2).unwrap();
+        let mut keys: Vec<&str> = found.iter().map(|(k, _)| k.as_str()).collect();
+        keys.sort();
+        assert_eq!(keys, ["k139", "k161"]);
+
+        graph.clear().unwrap();
+        assert!(graph.search(&query, 3).unwrap().is_empty());
+        assert_eq!(graph.dims().unwrap(), None);
+    }
+
+    #[test]
+    fn levels_are_deterministic_and_mostly_zero() {
+        assert_eq!(level_for("a"), level_for("a"));
+        let zero = (0..1000).filter(|i| level_for(&i.to_string()) == 0).count();
+        assert!(zero > 850, "{zero}");
+        assert_eq!(decode_node(&encode_node(2, &[0.5, -1.0])), Some((2, vec![0.5, -1.0])));
+    }
+}
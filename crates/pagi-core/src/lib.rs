@@ -3,36 +3,115 @@
 //! Re-exports the former pagi-shared, pagi-orchestrator, pagi-memory, and pagi-knowledge
 //! so add-ons and the gateway keep a consistent public API.
 
+mod clock;
+mod config_sources;
+mod db_lock;
+pub mod keys;
 mod knowledge;
 mod memory;
 mod orchestrator;
+mod sandbox;
+mod secrets;
 mod secure_memory;
 mod shadow_store;
 mod shared;
+mod storage;
 
 // Shared (former pagi-shared) + Emotional Context Layer + Task Governance
 pub use shared::{
-    BiometricState, CoreConfig, EthosPolicy, Goal, MentalState, MENTAL_STATE_KEY, PersonRecord,
+    BiometricState, CoreConfig, EthosPolicy, GenerationAdjustment, GenerationModulation, Goal, LlmProviderConfig, LlmProviderKind,
+    MentalState, MENTAL_STATE_KEY, LimitsConfig, SkillLimits, ModelTarget, PersonRecord, RagConfig, RerankerKind, ScraperConfig, IngestAdapter, IngestConfig, IngestSourceConfig, EmailConfig, EmailProvider, EmailTemplate, SmtpConfig, SmtpTls, ChatChannelConfig, ChatConfig, ChatKind, SlackCommandConfig, ChannelRoute, ChannelsConfig, TelegramConfig, TelegramMode, CalDavConfig, CalendarConfig, CalendarProvider, GoogleCalendarConfig, LogFormat, LoggingConfig, WebhooksConfig, SimulationConfig, SIMULATION_DEFAULT_START_MS, TlsConfig, TokenBudgetConfig,
     SomaState, TenantContext, KARDIA_PEOPLE_PREFIX, DEFAULT_AGENT_ID, ETHOS_POLICY_KEY,
     // Dynamic Task Governance (Oikos)
     GovernanceAction, GovernedTask, TaskDifficulty, TaskGovernor,
     OIKOS_TASK_PREFIX, OIKOS_GOVERNANCE_SUMMARY_KEY,
 };
+// Wall clock behind a trait (frozen in simulation mode)
+pub use clock::{system_now_ms, Clock, FrozenClock, SystemClock};
+// Layered config sources (file, environment file, PAGI__* env, CLI overrides) and checks
+pub use config_sources::{ConfigSources, DEFAULT_CONFIG_PATH, LLM_MODES};
+// Keys and provider credentials from env, files, Vault or AWS Secrets Manager
+pub use secrets::{
+    install as install_secrets, init_from_config as init_secrets, secret, AwsSecretsManagerSecrets, EnvSecrets, FileSecrets,
+    Secrets, SecretsConfig, SecretsProvider, SecretsProviderConfig, VaultSecrets, SECRET_API_KEY, SECRET_LLM_API_KEY,
+    SECRET_SHADOW_KEY,
+};
 pub use shadow_store::{DecryptedEntry, PersonalHistoryEntry, ShadowStore, ShadowStoreHandle};
 
+// Symlink-aware path resolution inside a sandbox root (skill limits, PathJail)
+pub use sandbox::{resolve_in_sandbox, SandboxError};
+
+// Sled lock ownership and stale-lock recovery
+pub use db_lock::{open_db, DbLocked, DbOwner, DB_OWNER_FILE};
+
+// Storage backends beneath the knowledge store and memory
+pub use storage::{
+    KvBackend, KvBatch, KvIter, KvResult, KvTree, KvTxResult, KvTxTree, KvUpdateFn, ReadOnlyBackend, SledBackend, SqliteBackend,
+    StorageBackend, SQLITE_FILE_NAME,
+};
+
 // Memory (former pagi-memory)
-pub use memory::MemoryManager;
+pub use memory::{
+    LeadFilter, LeadPage, LeadRecord, LeadStatus, LeadTransition, MemoryManager, LEAD_DEFAULT_LIMIT, LEAD_HISTORY_PREFIX, LEAD_MAX_LIMIT,
+};
 
 // Knowledge (former pagi-knowledge) - L2 Memory System + Shadow Vault
 pub use knowledge::{
     initialize_core_identity, initialize_core_skills, initialize_ethos_policy, pagi_kb_slot_label, verify_identity, IdentityStatus, AgentMessage, AlignmentResult, EventRecord, Kb1, Kb2, Kb3,
     Kb4, Kb5, Kb6, Kb7, Kb8, KbRecord, KbStatus, KbType, KnowledgeSource, KnowledgeStore,
     PolicyRecord, RelationRecord, SovereignState, ETHOS_DEFAULT_POLICY_KEY, SkillRecord, SLOT_LABELS, kardia_relation_key,
-    EmotionalAnchor, SecretVault, VaultError,
+    ActiveEthosPolicy, ConditionTest, InvalidPolicyRules, PolicyDecision, PolicyRule, PolicyRules, RuleAction, RuleCondition,
+    POLICY_MAX_RULES, ApprovalCheckpoint, ApprovalRecord, ApprovalStatus,
+    hash_api_key, ApiKeyRecord, OidcConfig, OidcPrincipal, Permission, Role, API_KEY_SECRET_PREFIX,
+    EmotionalAnchor, EncryptionConfig, SecretVault, VaultError, ENCRYPTED_MARKER,
+    AlertTarget, QuerySelector, StandingQuery, StandingQueryChange, STANDING_QUERY_PREFIX,
+    IdempotencyClaim, IdempotencyRecord, IDEMPOTENCY_CLAIM_TIMEOUT_MS,
+    usage_month, AgentTokenUsage, TokenBudgetExceeded, TokenUsageRecord, LlmCacheEntry,
+    InvalidPersona, PersonaProfile, PERSONA_MAX_BOUNDARIES, PERSONA_MAX_LEN,
+    is_valid_prompt_name, render_prompt, PromptError, PromptTemplate, HEARTBEAT_BACKGROUND_TASK_PROMPT, PROMPT_MAX_LEN,
+    CONVERSATION_DIGEST_PROMPT, EVAL_JUDGE_PROMPT, SYSTEM_PERSONA_PROMPT, THALAMUS_CLASSIFICATION_PROMPT,
+    CasConflict, ScanPage, CAS_MAX_RETRIES, DEFAULT_MAX_VALUE_BYTES, SCAN_MAX_LIMIT,
+    RestoreReport, SnapshotInfo, SnapshotManager, SnapshotReason, SnapshotRetention, SnapshotTree, SNAPSHOT_DIR_NAME,
+    SemanticHit, TextSearchHit, TEXT_INDEX_TREE, TEXT_SEARCH_DEFAULT_LIMIT, VECTOR_INDEX_TREE,
+    KbChange, KbChangeKind, CHANGE_CHANNEL_CAPACITY,
+    is_valid_session_id, ConversationContext, ConversationDigest, ConversationManager, ConversationSession, ConversationTurn, DigestBatch,
+    DEFAULT_CONTEXT_TURNS, DIGEST_BATCH_TURNS, DIGEST_KEEP_RECENT_TURNS,
+    SlotStorageStats, COMPRESSION_MAGIC, DEFAULT_COMPRESSION_THRESHOLD,
+    TenantHandle, DEFAULT_TENANT_ID,
+    is_valid_entity_id, is_valid_predicate, GraphEntity, GraphNeighbor, GraphNeighborhood, GraphRelation, GRAPH_MAX_DEPTH, GRAPH_MAX_NODES,
+    abort_transaction, KbMultiTransaction, KbTransaction, KbTxResult,
+    ArchiveReport, ArchiveSegment, ARCHIVE_DIR_NAME,
+    is_valid_agent_id, ActiveHours, AgentHealth, AgentRecord, AgentScheduleUpdate, AgentStatus, AgentTickTracker, InvalidAgentSchedule,
+    is_valid_topic_name, TopicSubscription, TOPIC_FANOUT_BATCH,
+    GoalJob, JobStatus, JOB_RETENTION_MS, EmailDelivery, EmailDeliveryStatus, EMAIL_LOG_MAX_LIMIT, CalendarBooking, CALENDAR_BOOKING_MAX_LIMIT,
+    AssertionResult, EvalAssertion, EvalCase, EvalCaseResult, EvalRun, EvalRunSummary, EvalSuite, InvalidEvalSuite,
+    EVAL_DEFAULT_JUDGE_SKILL, EVAL_MAX_CASES, EVAL_RUNS_MAX_LIMIT,
+    AuditOutcome, AuditPage, AuditQuery, AuditRecord, AUDIT_DEFAULT_LIMIT, AUDIT_MAX_LIMIT, AUDIT_TREE, REDACTION_GOAL_TYPE,
+    PiiKind, RedactionConfig, RedactionReport, Redactor,
+    ReembedCursor, ReembedJob, ReembedStatus, EMBEDDING_MODEL_METADATA_KEY, REEMBED_JOBS_TREE,
+    agent_persona_key, agent_persona_profile_key, auto_reply_template_key, AutoReplyPrompt, AUTO_REPLY_THREAD_LIMIT, DEFAULT_AUTO_REPLY_TEMPLATE,
+    InboxRetry, INBOX_MAX_ATTEMPTS, INBOX_RETRY_BASE_MS, INBOX_RETRY_MAX_MS,
 };
 
 // Orchestrator (former pagi-orchestrator)
 pub use orchestrator::{
-    AgentSkill, BlueprintRegistry, ControlPanelMessage, ControlPanelReceiver, Orchestrator, Plan,
-    SkillRegistry,
+    AgentSkill, BlueprintRegistry, ControlPanelMessage, ControlPanelReceiver, IntentSpec, OnBlocked, Orchestrator,
+    apply_map, PayloadMap, Plan, PlanStep, SkillRegistry, StepCondition, StepPolicy, BLUEPRINT_CANDIDATE_PREFIX, BLUEPRINT_KB_PREFIX,
+    translate_error, UserFacingError, SkillNotEnabled, TenantSkillPolicy, TenantSkillRegistry,
+    EthosBlocked, EthosCheck, PlanPreview, PreviewStep, CancellationToken, DeadlineExceeded, GoalCancelled,
+    InvalidTrace, ReplayDiff, ReplayMode, ReplayStep, StepOutcome, TraceReplay, ValueChange,
+    DispatchPermit, DispatchQueue, DispatchQueueConfig, QueueStats, RateLimit, RateLimiter, Throttled,
+    SkillError, SkillStats, SkillStatsRegistry, SKILL_STATS_PREFIX,
+    BlueprintError, BlueprintIssue, parse_blueprint, validate_blueprint, MAX_SUB_PLAN_DEPTH,
+    check_capabilities, missing_capabilities, CapabilityContractError, MissingCapability,
+    civil_from_days, CronExpr, InvalidSchedule, Schedule, ScheduleRun, ScheduledGoal, SchedulerRegistry, SCHEDULE_PREFIX,
+    InvalidSource, ScrapeSource, SourceRefresh, SourceRegistry, SOURCE_PREFIX,
+    check_webhook_url, sign_webhook, webhook_client, PublicResolver, DeliveryAttempt, DeliveryStatus, InvalidWebhook, Webhook,
+    WebhookDelivery, WebhookEvent, WebhookRegistry, WEBHOOK_DELIVERY_PREFIX, WEBHOOK_FAILED_RETENTION_MS, WEBHOOK_MAX_ATTEMPTS, WEBHOOK_PREFIX, WEBHOOK_RETRY_BASE_MS, WEBHOOK_RETRY_MAX_MS,
+    MockSkill, Scenario, ScenarioGiven, ScenarioKbEntry, ScenarioOutcome, ScenarioRunner, ScenarioThen, ScenarioWhen,
+    compare_versions, DomainPack, InstalledPack, PackDowngrade, PackInstall, PackInstallStatus, PackKbKey, PackKbSeed,
+    PackRegistry, PackTemplates, PACK_KB_PREFIX,
+    StepProgress, StepStatus, PROGRESS_CHANNEL_CAPACITY,
+    is_confirmation, IntentAction, IntentMatch, IntentRouter, IntentRoutingConfig, IntentSource,
+    INTENT_CONFIRMATION_TTL, DEFAULT_MAX_TOOL_ITERATIONS, MAX_TOOL_ITERATIONS,
 };
@@ -1,63 +1,424 @@
-//! Multi-layer memory: short-term cache (DashMap) and long-term Sled DB.
+//! Multi-layer memory: short-term cache (DashMap) and long-term storage (sled or SQLite, see
+//! `storage/`).
+//!
+//! Besides raw paths, the vault holds typed lead records (LeadCapture's inquiries) under
+//! `lead_history/{tenant}/{lead_id}`: the captured fields at the top level of one JSON object,
+//! plus `id`, `status`, `created_at_ms` and `updated_at_ms`.
 
+use crate::clock::{Clock, SystemClock};
 use crate::shared::TenantContext;
+use crate::storage::{prefix_upper_bound, KvBackend, SledBackend, StorageBackend, DEFAULT_TREE};
 use dashmap::DashMap;
-use sled::Db;
+use serde::{Deserialize, Serialize};
+use std::ops::Bound;
 use std::path::Path;
 use std::sync::Arc;
 
 const DEFAULT_VAULT_PATH: &str = "./data/pagi_vault";
 
+/// Path prefix of lead records: `lead_history/{tenant}/{lead_id}`.
+pub const LEAD_HISTORY_PREFIX: &str = "lead_history";
+/// Default page size of [`MemoryManager::list_leads`].
+pub const LEAD_DEFAULT_LIMIT: usize = 50;
+/// Largest page of [`MemoryManager::list_leads`].
+pub const LEAD_MAX_LIMIT: usize = 500;
+/// Longest lead id.
+const LEAD_MAX_ID_LEN: usize = 128;
+/// Fields of the stored object owned by [`LeadRecord`] rather than the captured inquiry.
+const LEAD_RECORD_FIELDS: [&str; 5] = ["id", "status", "status_changed_at_ms", "created_at_ms", "updated_at_ms"];
+
+/// Where a lead is in the sales pipeline.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LeadStatus {
+    #[default]
+    New,
+    Contacted,
+    Qualified,
+    Closed,
+}
+
+impl LeadStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LeadStatus::New => "new",
+            LeadStatus::Contacted => "contacted",
+            LeadStatus::Qualified => "qualified",
+            LeadStatus::Closed => "closed",
+        }
+    }
+
+    /// Pipeline moves: forward from `new`, back to `contacted` from `qualified`, `contacted` again
+    /// (another touch, which restarts its clock), and `closed` back to `new` (reopened).
+    pub fn can_transition_to(self, to: LeadStatus) -> bool {
+        use LeadStatus::*;
+        matches!(
+            (self, to),
+            (New, Contacted | Qualified | Closed)
+                | (Contacted, Contacted | Qualified | Closed)
+                | (Qualified, Contacted | Closed)
+                | (Closed, New)
+        )
+    }
+}
+
+impl std::fmt::Display for LeadStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Outcome of [`MemoryManager::update_lead_status`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum LeadTransition {
+    Moved { from: LeadStatus, lead: LeadRecord },
+    /// The pipeline does not allow `from` → `to`; the lead is unchanged.
+    Invalid { from: LeadStatus, to: LeadStatus },
+    NotFound,
+}
+
+/// A captured inquiry. `fields` are the submitted fields (name, email, message, ...) and what
+/// skills add to them (e.g. ParseInboundMessage's `parsed`); they are stored at the top level.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LeadRecord {
+    /// Empty on [`MemoryManager::put_lead`]: a UUID is assigned.
+    #[serde(default)]
+    pub id: String,
+    #[serde(default)]
+    pub status: LeadStatus,
+    /// When the lead entered its current status.
+    #[serde(default)]
+    pub status_changed_at_ms: i64,
+    #[serde(default)]
+    pub created_at_ms: i64,
+    #[serde(default)]
+    pub updated_at_ms: i64,
+    #[serde(flatten)]
+    pub fields: serde_json::Map<String, serde_json::Value>,
+}
+
+impl LeadRecord {
+    /// A new lead from the submitted fields (e.g. LeadCapture's payload).
+    pub fn new(fields: serde_json::Map<String, serde_json::Value>) -> Self {
+        Self { fields, ..Self::default() }
+    }
+
+    /// Reads a stored lead. Blobs written before leads were typed have only the inquiry fields;
+    /// they load as `new` with their id from the path. A `status` that is not a [`LeadStatus`]
+    /// also reads as `new`.
+    fn from_stored(id: &str, bytes: &[u8]) -> Option<Self> {
+        let serde_json::Value::Object(mut fields) = serde_json::from_slice(bytes).ok()? else {
+            return None;
+        };
+        let mut take = |field: &str| fields.remove(field);
+        let status = take("status").and_then(|s| serde_json::from_value(s).ok()).unwrap_or_default();
+        let created_at_ms = take("created_at_ms").and_then(|t| t.as_i64()).unwrap_or(0);
+        let updated_at_ms = take("updated_at_ms").and_then(|t| t.as_i64()).unwrap_or(created_at_ms);
+        let status_changed_at_ms = take("status_changed_at_ms").and_then(|t| t.as_i64()).unwrap_or(created_at_ms);
+        take("id");
+        Some(Self { id: id.to_string(), status, status_changed_at_ms, created_at_ms, updated_at_ms, fields })
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut object = self.fields.clone();
+        object.retain(|k, _| !LEAD_RECORD_FIELDS.contains(&k.as_str()));
+        object.insert("id".into(), self.id.clone().into());
+        object.insert("status".into(), serde_json::json!(self.status));
+        object.insert("status_changed_at_ms".into(), self.status_changed_at_ms.into());
+        object.insert("created_at_ms".into(), self.created_at_ms.into());
+        object.insert("updated_at_ms".into(), self.updated_at_ms.into());
+        serde_json::to_vec(&object).unwrap_or_default()
+    }
+}
+
+/// Filter of [`MemoryManager::list_leads`]. All filters are optional and combine with AND.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LeadFilter {
+    #[serde(default)]
+    pub status: Option<LeadStatus>,
+    /// Leads created at or after this time.
+    #[serde(default)]
+    pub since_ms: Option<i64>,
+    /// Page size (default [`LEAD_DEFAULT_LIMIT`], at most [`LEAD_MAX_LIMIT`]).
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+impl LeadFilter {
+    pub fn matches(&self, lead: &LeadRecord) -> bool {
+        self.status.is_none_or(|s| s == lead.status) && self.since_ms.is_none_or(|t| lead.created_at_ms >= t)
+    }
+
+    pub fn page_size(&self) -> usize {
+        self.limit.unwrap_or(LEAD_DEFAULT_LIMIT).clamp(1, LEAD_MAX_LIMIT)
+    }
+}
+
+/// One page of leads, in id order.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LeadPage {
+    pub leads: Vec<LeadRecord>,
+    /// Pass as `cursor` to get the next page; None on the last page.
+    pub next_cursor: Option<String>,
+}
+
+fn lead_path(ctx: &TenantContext, id: &str) -> String {
+    format!("{}/{}/{}", LEAD_HISTORY_PREFIX, ctx.tenant_id, id)
+}
+
+fn check_lead_id(id: &str) -> Result<(), sled::Error> {
+    if id.is_empty() || id.len() > LEAD_MAX_ID_LEN || id.contains('/') || id.chars().any(char::is_control) {
+        return Err(sled::Error::Unsupported(format!("invalid lead id {:?}", id)));
+    }
+    Ok(())
+}
+
 fn cache_key(ctx: &TenantContext, path: &str) -> String {
     format!("{}:{}", ctx.tenant_id, path)
 }
 
-/// Manages short-term (in-memory) cache and long-term Sled storage.
+/// Manages short-term (in-memory) cache and long-term storage.
 pub struct MemoryManager {
-    db: Db,
-    /// Hot cache: tenant-scoped path -> value. Checked before Sled.
+    db: Box<dyn KvBackend>,
+    /// Hot cache: tenant-scoped path -> value. Checked before the backend.
     cache: Arc<DashMap<String, Vec<u8>>>,
+    /// Stamps lead records ([`SystemClock`] unless [`with_clock`](Self::with_clock) swaps it).
+    clock: Arc<dyn Clock>,
 }
 
 impl MemoryManager {
-    /// Opens or creates a Sled database at `./data/pagi_vault` with an in-memory cache.
+    /// Opens or creates a sled database at `./data/pagi_vault` with an in-memory cache.
     pub fn new() -> Result<Self, sled::Error> {
         Self::open_path(DEFAULT_VAULT_PATH)
     }
 
-    /// Opens or creates a Sled database at the given path with an in-memory cache.
+    /// Opens or creates a sled database at the given path with an in-memory cache.
     pub fn open_path<P: AsRef<Path>>(path: P) -> Result<Self, sled::Error> {
-        let db = sled::open(path)?;
+        Self::open_with_backend(path, StorageBackend::Sled)
+    }
+
+    /// Opens or creates the vault at the given path on `backend` (`CoreConfig::storage_backend`).
+    pub fn open_with_backend<P: AsRef<Path>>(path: P, backend: StorageBackend) -> Result<Self, sled::Error> {
+        let db = backend.open(path)?;
         Ok(Self {
             db,
             cache: Arc::new(DashMap::new()),
+            clock: Arc::new(SystemClock),
         })
     }
 
-    /// Persists a value at the given path. Writes to both the hot cache and Sled (long-term).
+    /// Opens an in-memory vault removed when dropped (e.g. for a process that cannot open the
+    /// shared one).
+    pub fn open_temporary() -> Result<Self, sled::Error> {
+        Ok(Self {
+            db: Box::new(SledBackend::temporary()?),
+            cache: Arc::new(DashMap::new()),
+            clock: Arc::new(SystemClock),
+        })
+    }
+
+    /// Reads the time from `clock` (e.g. the store's [`FrozenClock`](crate::FrozenClock) in
+    /// simulation mode) for the lead timestamps the vault writes.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Milliseconds since the Unix epoch by the vault's clock.
+    pub fn now_ms(&self) -> i64 {
+        self.clock.now_ms()
+    }
+
+    /// Persists a value at the given path. Writes to both the hot cache and the backend (long-term).
     pub fn save_path(
         &self,
         ctx: &TenantContext,
         path: &str,
         value: &[u8],
     ) -> Result<(), sled::Error> {
-        let key = path.as_bytes();
-        self.db.insert(key, value)?;
+        self.db.open_tree(DEFAULT_TREE)?.insert(path.as_bytes(), value)?;
         self.cache.insert(cache_key(ctx, path), value.to_vec());
         Ok(())
     }
 
-    /// Retrieves a value at the given path. Checks the hot cache first, then Sled.
+    /// Retrieves a value at the given path. Checks the hot cache first, then the backend.
     pub fn get_path(&self, ctx: &TenantContext, path: &str) -> Result<Option<Vec<u8>>, sled::Error> {
         let ck = cache_key(ctx, path);
         if let Some(v) = self.cache.get(&ck) {
             return Ok(Some(v.clone()));
         }
-        let v = self.db.get(path.as_bytes())?;
-        let out = v.map(|iv| iv.to_vec());
+        let out = self.db.open_tree(DEFAULT_TREE)?.get(path.as_bytes())?;
         if let Some(ref vec) = out {
             self.cache.insert(ck, vec.clone());
         }
         Ok(out)
     }
+
+    /// Removes the value at the given path from the backend and the hot cache. Returns whether
+    /// it existed.
+    pub fn remove_path(&self, ctx: &TenantContext, path: &str) -> Result<bool, sled::Error> {
+        self.cache.remove(&cache_key(ctx, path));
+        Ok(self.db.open_tree(DEFAULT_TREE)?.remove(path.as_bytes())?.is_some())
+    }
+
+    /// Stores `lead` for the tenant and returns it as stored: an empty id gets a UUID,
+    /// `created_at_ms` and `status_changed_at_ms` are set on first write unless given,
+    /// `status_changed_at_ms` again when the status differs from the stored one, and
+    /// `updated_at_ms` on every write. Does not check the pipeline; see
+    /// [`update_lead_status`](Self::update_lead_status).
+    pub fn put_lead(&self, ctx: &TenantContext, mut lead: LeadRecord) -> Result<LeadRecord, sled::Error> {
+        if lead.id.is_empty() {
+            lead.id = uuid::Uuid::new_v4().to_string();
+        }
+        check_lead_id(&lead.id)?;
+        let now = self.now_ms();
+        let stored = self.get_lead(ctx, &lead.id)?;
+        if lead.created_at_ms == 0 {
+            lead.created_at_ms = stored.as_ref().map_or(now, |stored| stored.created_at_ms);
+        }
+        match stored {
+            Some(stored) if stored.status != lead.status => lead.status_changed_at_ms = now,
+            Some(stored) if lead.status_changed_at_ms == 0 => lead.status_changed_at_ms = stored.status_changed_at_ms,
+            None if lead.status_changed_at_ms == 0 => lead.status_changed_at_ms = now,
+            _ => {}
+        }
+        lead.updated_at_ms = now;
+        self.save_path(ctx, &lead_path(ctx, &lead.id), &lead.to_bytes())?;
+        Ok(lead)
+    }
+
+    /// The tenant's lead `id`, if stored.
+    pub fn get_lead(&self, ctx: &TenantContext, id: &str) -> Result<Option<LeadRecord>, sled::Error> {
+        check_lead_id(id)?;
+        Ok(self
+            .get_path(ctx, &lead_path(ctx, id))?
+            .and_then(|bytes| LeadRecord::from_stored(id, &bytes)))
+    }
+
+    /// Removes the tenant's lead `id`. Returns whether it existed.
+    pub fn delete_lead(&self, ctx: &TenantContext, id: &str) -> Result<bool, sled::Error> {
+        check_lead_id(id)?;
+        self.remove_path(ctx, &lead_path(ctx, id))
+    }
+
+    /// One page of the tenant's leads matching `filter`, in id order, starting after `cursor`
+    /// (the previous page's `next_cursor`).
+    pub fn list_leads(&self, ctx: &TenantContext, filter: &LeadFilter, cursor: Option<&str>) -> Result<LeadPage, sled::Error> {
+        let prefix = lead_path(ctx, "");
+        let lower = match cursor {
+            Some(cursor) => Bound::Excluded(lead_path(ctx, cursor).into_bytes()),
+            None => Bound::Included(prefix.clone().into_bytes()),
+        };
+        let upper = prefix_upper_bound(prefix.as_bytes()).map_or(Bound::Unbounded, Bound::Excluded);
+        let limit = filter.page_size();
+        let mut page = LeadPage::default();
+        for item in self.db.open_tree(DEFAULT_TREE)?.range(lower, upper, false) {
+            let (key, bytes) = item?;
+            let Some(id) = std::str::from_utf8(&key).ok().and_then(|k| k.strip_prefix(&prefix)) else {
+                continue;
+            };
+            let Some(lead) = LeadRecord::from_stored(id, &bytes).filter(|lead| filter.matches(lead)) else {
+                continue;
+            };
+            if page.leads.len() == limit {
+                page.next_cursor = page.leads.last().map(|last| last.id.clone());
+                break;
+            }
+            page.leads.push(lead);
+        }
+        Ok(page)
+    }
+
+    /// Moves the tenant's lead `id` to `status` when the pipeline allows it
+    /// ([`LeadStatus::can_transition_to`]).
+    pub fn update_lead_status(&self, ctx: &TenantContext, id: &str, status: LeadStatus) -> Result<LeadTransition, sled::Error> {
+        let Some(mut lead) = self.get_lead(ctx, id)? else {
+            return Ok(LeadTransition::NotFound);
+        };
+        let from = lead.status;
+        if !from.can_transition_to(status) {
+            return Ok(LeadTransition::Invalid { from, to: status });
+        }
+        lead.status = status;
+        // Moving to the same status (another touch) restarts its clock.
+        lead.status_changed_at_ms = self.now_ms();
+        Ok(LeadTransition::Moved { from, lead: self.put_lead(ctx, lead)? })
+    }
+
+    /// Tenants with at least one lead, in order. Skips over each tenant's leads rather than
+    /// reading them.
+    pub fn lead_tenants(&self) -> Result<Vec<String>, sled::Error> {
+        let tree = self.db.open_tree(DEFAULT_TREE)?;
+        let prefix = format!("{}/", LEAD_HISTORY_PREFIX);
+        let upper = prefix_upper_bound(prefix.as_bytes()).map_or(Bound::Unbounded, Bound::Excluded);
+        let mut lower = Bound::Included(prefix.clone().into_bytes());
+        let mut tenants = Vec::new();
+        while let Some(item) = tree.range(lower, upper.clone(), false).next() {
+            let (key, _) = item?;
+            let key = String::from_utf8_lossy(&key).into_owned();
+            let Some(tenant) = key.strip_prefix(&prefix).and_then(|rest| rest.split_once('/')).map(|(t, _)| t.to_string()) else {
+                break;
+            };
+            let tenant_prefix = format!("{}{}/", prefix, tenant);
+            match prefix_upper_bound(tenant_prefix.as_bytes()) {
+                Some(next) => lower = Bound::Included(next),
+                None => lower = Bound::Excluded(key.into_bytes()),
+            }
+            tenants.push(tenant);
+        }
+        Ok(tenants)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ctx(tenant_id: &str) -> TenantContext {
+        TenantContext::new(tenant_id)
+    }
+
+    #[test]
+    fn leads_are_listed_filtered_and_updated_per_tenant() {
+        let memory = MemoryManager::open_temporary().unwrap();
+        let acme = ctx("acme");
+        let fields = |email: &str| serde_json::json!({ "email": email, "message": "Need a quote" });
+        let mut ids = Vec::new();
+        for email in ["a@x.io", "b@x.io", "c@x.io"] {
+            let serde_json::Value::Object(fields) = fields(email) else { unreachable!() };
+            ids.push(memory.put_lead(&acme, LeadRecord::new(fields)).unwrap().id);
+        }
+        // A blob written before leads were typed.
+        memory
+            .save_path(&acme, "lead_history/acme/legacy", br#"{"email":"d@x.io","status":"urgent"}"#)
+            .unwrap();
+        memory.put_lead(&ctx("beta"), LeadRecord::default()).unwrap();
+
+        let legacy = memory.get_lead(&acme, "legacy").unwrap().unwrap();
+        assert_eq!((legacy.status, legacy.fields["email"].as_str()), (LeadStatus::New, Some("d@x.io")));
+
+        let LeadTransition::Moved { from, lead: updated } = memory.update_lead_status(&acme, &ids[1], LeadStatus::Qualified).unwrap()
+        else {
+            panic!("new → qualified is allowed");
+        };
+        assert_eq!((from, updated.fields["message"].as_str()), (LeadStatus::New, Some("Need a quote")));
+        assert!(updated.status_changed_at_ms >= updated.created_at_ms);
+        assert_eq!(
+            memory.update_lead_status(&acme, &ids[1], LeadStatus::New).unwrap(),
+            LeadTransition::Invalid { from: LeadStatus::Qualified, to: LeadStatus::New }
+        );
+        assert_eq!(memory.update_lead_status(&acme, "missing", LeadStatus::Closed).unwrap(), LeadTransition::NotFound);
+
+        let filter = LeadFilter { limit: Some(3), ..LeadFilter::default() };
+        let first = memory.list_leads(&acme, &filter, None).unwrap();
+        assert_eq!(first.leads.len(), 3);
+        let rest = memory.list_leads(&acme, &filter, first.next_cursor.as_deref()).unwrap();
+        assert_eq!((rest.leads.len(), rest.next_cursor), (1, None));
+
+        let qualified = LeadFilter { status: Some(LeadStatus::Qualified), ..LeadFilter::default() };
+        let page = memory.list_leads(&acme, &qualified, None).unwrap();
+        assert_eq!(page.leads.iter().map(|l| l.id.as_str()).collect::<Vec<_>>(), vec![ids[1].as_str()]);
+        assert!(memory.get_lead(&acme, "../beta").is_err());
+        assert_eq!(memory.lead_tenants().unwrap(), vec!["acme".to_string(), "beta".to_string()]);
+    }
 }
@@ -0,0 +1,95 @@
+//! Dispatch audit: every top-level dispatch is written to the KnowledgeStore audit log
+//! ([`AuditRecord`]) once it ends, when a store is attached.
+
+use super::cancel::{DeadlineExceeded, GoalCancelled};
+use super::queue::Throttled;
+use crate::knowledge::{AuditOutcome, AuditRecord, KnowledgeStore};
+use crate::shared::{Goal, TenantContext};
+use std::time::Instant;
+
+/// A dispatch being timed for its audit record.
+pub(super) struct DispatchAudit {
+    started: Instant,
+    timestamp_ms: i64,
+    goal_type: &'static str,
+    skill: Option<String>,
+    intent: Option<String>,
+}
+
+impl DispatchAudit {
+    /// A dispatch of `goal` starting at `now_ms` (the orchestrator's clock).
+    pub(super) fn start(goal: &Goal, now_ms: i64) -> Self {
+        let intent = match goal {
+            Goal::AutonomousGoal { intent, .. } => Some(intent.clone()),
+            _ => None,
+        };
+        Self::new(goal.kind(), goal.skill_name().map(str::to_string), intent, now_ms)
+    }
+
+    /// A streamed `ExecuteSkill` of `skill` starting at `now_ms`.
+    pub(super) fn start_skill(skill: &str, now_ms: i64) -> Self {
+        Self::new("ExecuteSkill", Some(skill.to_string()), None, now_ms)
+    }
+
+    fn new(goal_type: &'static str, skill: Option<String>, intent: Option<String>, timestamp_ms: i64) -> Self {
+        Self {
+            started: Instant::now(),
+            timestamp_ms,
+            goal_type,
+            skill,
+            intent,
+        }
+    }
+
+    /// Records the dispatch with the outcome of `error` (None = ok).
+    pub(super) fn finish(
+        self,
+        knowledge: Option<&KnowledgeStore>,
+        ctx: &TenantContext,
+        error: Option<&(dyn std::error::Error + Send + Sync + 'static)>,
+    ) {
+        let outcome = error.map_or(AuditOutcome::Ok, outcome_of);
+        self.finish_with(knowledge, ctx, outcome, error.map(|e| e.to_string()));
+    }
+
+    pub(super) fn finish_with(
+        self,
+        knowledge: Option<&KnowledgeStore>,
+        ctx: &TenantContext,
+        outcome: AuditOutcome,
+        error: Option<String>,
+    ) {
+        let Some(knowledge) = knowledge else {
+            return;
+        };
+        let record = AuditRecord {
+            id: uuid::Uuid::new_v4().simple().to_string(),
+            timestamp_ms: self.timestamp_ms,
+            tenant_id: ctx.tenant_id.clone(),
+            agent_id: ctx.resolved_agent_id().to_string(),
+            correlation_id: ctx.correlation_id.clone(),
+            goal_type: self.goal_type.to_string(),
+            skill: self.skill,
+            intent: self.intent,
+            duration_ms: self.started.elapsed().as_millis() as u64,
+            outcome,
+            error: error.map(|e| AuditRecord::truncate_error(&e)),
+            redactions: Default::default(),
+        };
+        if let Err(e) = knowledge.append_audit_record(&record) {
+            tracing::warn!(target: "pagi::orchestrator", error = %e, "Failed to write audit record");
+        }
+    }
+}
+
+fn outcome_of(err: &(dyn std::error::Error + Send + Sync + 'static)) -> AuditOutcome {
+    if err.is::<Throttled>() {
+        AuditOutcome::Throttled
+    } else if err.is::<DeadlineExceeded>() {
+        AuditOutcome::Timeout
+    } else if err.is::<GoalCancelled>() {
+        AuditOutcome::Cancelled
+    } else {
+        AuditOutcome::Error
+    }
+}
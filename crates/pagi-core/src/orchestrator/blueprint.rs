@@ -1,86 +1,937 @@
 //! Blueprint: intent → skill chain. Loaded from JSON/TOML for use-case-agnostic orchestration.
 
-use serde::Deserialize;
+use super::mapping::PayloadMap;
+use crate::knowledge::{KbType, KnowledgeStore};
+use serde::de::{self, MapAccess, SeqAccess, Visitor};
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::path::Path;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, PoisonError, RwLock};
 
-/// A plan is an ordered sequence of skill names to execute.
+/// Execution policy for a single plan step: retries, backoff, timeout, and what a block by the
+/// Ethos policy does. The default is one attempt with no timeout (the pre-policy behaviour).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StepPolicy {
+    /// Additional attempts after the first failure (0 = no retry).
+    pub max_retries: u32,
+    /// Delay before the first retry in milliseconds; doubled after each failed attempt.
+    pub backoff_ms: u64,
+    /// Per-attempt timeout in milliseconds (`tokio::time::timeout`). None = no timeout.
+    pub timeout_ms: Option<u64>,
+    /// When true, a step that exhausts its attempts does not abort the chain; the failure is
+    /// recorded in the trace and the next step receives the previous payload.
+    pub continue_on_failure: bool,
+    /// What the chain does when an Ethos rule stops the step's input (default `halt`).
+    pub on_blocked: OnBlocked,
+}
+
+/// How a plan reacts to a step the Ethos policy blocks (`deny`, or `require_approval` without a
+/// grant). The block is recorded in the trace with the decision either way.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OnBlocked {
+    /// Stop the chain with [`EthosBlocked`](super::EthosBlocked).
+    #[default]
+    Halt,
+    /// Skip the step; the next step receives the previous payload.
+    Skip,
+    /// Try the step's fallback skills (each checked in turn); halt when none is allowed.
+    Fallback,
+}
+
+impl Default for StepPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 0,
+            backoff_ms: 250,
+            timeout_ms: None,
+            continue_on_failure: false,
+            on_blocked: OnBlocked::Halt,
+        }
+    }
+}
+
+impl StepPolicy {
+    /// Total number of attempts allowed (first try + retries).
+    #[inline]
+    pub fn max_attempts(&self) -> u32 {
+        self.max_retries.saturating_add(1)
+    }
+
+    /// Backoff delay before retry number `retry` (1-based): `backoff_ms * 2^(retry-1)`.
+    pub fn backoff_for(&self, retry: u32) -> std::time::Duration {
+        let factor = 1u64 << retry.saturating_sub(1).min(16);
+        std::time::Duration::from_millis(self.backoff_ms.saturating_mul(factor))
+    }
+}
+
+/// Guard for a conditional step: `{ "path": "/sentiment", "equals": "positive" }`.
+///
+/// `path` is a JSON pointer (a leading `/` is optional) into the output of the previous executed
+/// step, or the intent context for the first step. The step runs only when the value found there
+/// equals `equals`; a missing value never matches.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct StepCondition {
+    pub path: String,
+    pub equals: serde_json::Value,
+}
+
+impl StepCondition {
+    pub fn new(path: impl Into<String>, equals: serde_json::Value) -> Self {
+        Self {
+            path: path.into(),
+            equals,
+        }
+    }
+
+    /// Value at `path` in `input`, if present.
+    pub fn lookup<'a>(&self, input: &'a serde_json::Value) -> Option<&'a serde_json::Value> {
+        if self.path.is_empty() || self.path.starts_with('/') {
+            input.pointer(&self.path)
+        } else {
+            input.pointer(&format!("/{}", self.path))
+        }
+    }
+
+    /// Whether the step guarded by this condition should run for `input`.
+    pub fn matches(&self, input: &serde_json::Value) -> bool {
+        self.lookup(input) == Some(&self.equals)
+    }
+}
+
+/// Maximum nesting of sub-plan steps below the top-level plan.
+pub const MAX_SUB_PLAN_DEPTH: usize = 4;
+
+/// One step of a plan: the skill to run (or the intent whose plan runs as a nested sub-plan),
+/// its execution policy, an optional `when` guard, and an optional input `map` (see [`super::mapping`]).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlanStep {
+    /// Skill to run; empty for sub-plan steps.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub skill: String,
+    /// Intent whose plan runs as this step. The sub-plan receives the step input and its final
+    /// output becomes the step output. Only `continue_on_failure` of the policy applies; retries
+    /// and timeouts belong to the skills inside.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub intent: Option<String>,
+    #[serde(default)]
+    pub policy: StepPolicy,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub when: Option<StepCondition>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub map: Option<PayloadMap>,
+    /// Alternative skills for this step, tried in order when the primary fails. Skills the
+    /// orchestrator's stats mark unhealthy are tried last.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fallbacks: Vec<String>,
+}
+
+impl PlanStep {
+    /// Unconditional step with the default policy.
+    pub fn new(skill: impl Into<String>) -> Self {
+        Self {
+            skill: skill.into(),
+            intent: None,
+            policy: StepPolicy::default(),
+            when: None,
+            map: None,
+            fallbacks: Vec::new(),
+        }
+    }
+
+    /// Step that runs another intent's plan (a nested sub-plan).
+    pub fn sub_plan(intent: impl Into<String>) -> Self {
+        Self {
+            intent: Some(normalize_intent(&intent.into())),
+            ..Self::new("")
+        }
+    }
+
+    /// Skill name, or `intent:{name}` for sub-plan steps (for traces and responses).
+    pub fn label(&self) -> String {
+        match &self.intent {
+            Some(intent) => format!("intent:{}", intent),
+            None => self.skill.clone(),
+        }
+    }
+
+    /// Runs this step only when `condition` matches the previous step's output.
+    pub fn with_condition(mut self, condition: StepCondition) -> Self {
+        self.when = Some(condition);
+        self
+    }
+
+    /// Builds this step's input from the previous output with `map` instead of the built-in pairs.
+    pub fn with_map(mut self, map: PayloadMap) -> Self {
+        self.map = Some(map);
+        self
+    }
+
+    /// Alternative skills to try when the primary fails (or is currently unhealthy).
+    pub fn with_fallbacks<I, S>(mut self, fallbacks: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.fallbacks = fallbacks.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// The primary skill followed by its fallbacks (none for sub-plan steps).
+    pub fn candidates(&self) -> Vec<&str> {
+        if self.intent.is_some() {
+            return Vec::new();
+        }
+        std::iter::once(self.skill.as_str())
+            .chain(self.fallbacks.iter().map(String::as_str))
+            .collect()
+    }
+}
+
+impl PartialEq<&str> for PlanStep {
+    fn eq(&self, other: &&str) -> bool {
+        self.skill == *other
+    }
+}
+
+/// A plan is an ordered sequence of steps (skill + policy) to execute.
 #[derive(Debug, Clone)]
 pub struct Plan {
-    pub steps: Vec<String>,
+    pub steps: Vec<PlanStep>,
+}
+
+impl Plan {
+    /// Step labels in execution order (for traces and responses); see [`PlanStep::label`].
+    pub fn skill_names(&self) -> Vec<String> {
+        self.steps.iter().map(PlanStep::label).collect()
+    }
+}
+
+/// JSON shape for one step: a bare skill name or
+/// `{ "skill": "...", "policy": { ... }, "when": { "path": "...", "equals": ... }, "map": { "prompt": "$.draft" },
+/// "fallbacks": ["OtherSkill"] }`, or a sub-plan `{ "intent": "summarize news", ... }` (no `fallbacks`).
+/// Unknown fields and empty skill or intent names are rejected.
+#[derive(Debug)]
+pub enum StepSpec {
+    Name(String),
+    Detailed {
+        skill: String,
+        policy: Option<StepPolicy>,
+        when: Option<StepCondition>,
+        map: Option<PayloadMap>,
+        fallbacks: Vec<String>,
+    },
+    SubPlan {
+        intent: String,
+        policy: Option<StepPolicy>,
+        when: Option<StepCondition>,
+        map: Option<PayloadMap>,
+    },
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct StepObject {
+    #[serde(default)]
+    skill: Option<String>,
+    #[serde(default)]
+    intent: Option<String>,
+    #[serde(default)]
+    policy: Option<StepPolicy>,
+    #[serde(default)]
+    when: Option<StepCondition>,
+    #[serde(default)]
+    map: Option<PayloadMap>,
+    #[serde(default)]
+    fallbacks: Vec<String>,
+}
+
+// Hand-written instead of `#[serde(untagged)]`: untagged enums buffer their input, so errors
+// inside a step lose the line/column and read only "did not match any variant".
+impl<'de> Deserialize<'de> for StepSpec {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct StepVisitor;
+
+        impl<'de> Visitor<'de> for StepVisitor {
+            type Value = StepSpec;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a skill name or a step object { \"skill\": ... } / { \"intent\": ... }")
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<StepSpec, E> {
+                if v.trim().is_empty() {
+                    return Err(E::custom("skill name must not be empty"));
+                }
+                Ok(StepSpec::Name(v.to_string()))
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<StepSpec, A::Error> {
+                let step = StepObject::deserialize(de::value::MapAccessDeserializer::new(map))?;
+                match (step.skill, step.intent) {
+                    (Some(_), Some(_)) => Err(de::Error::custom("step must set either \"skill\" or \"intent\", not both")),
+                    (None, None) => Err(de::Error::custom("step must set \"skill\" or \"intent\"")),
+                    (None, Some(intent)) => {
+                        if normalize_intent(&intent).is_empty() {
+                            return Err(de::Error::custom("intent name must not be empty"));
+                        }
+                        if !step.fallbacks.is_empty() {
+                            return Err(de::Error::custom("sub-plan steps cannot have fallbacks"));
+                        }
+                        Ok(StepSpec::SubPlan {
+                            intent,
+                            policy: step.policy,
+                            when: step.when,
+                            map: step.map,
+                        })
+                    }
+                    (Some(skill), None) => {
+                        if skill.trim().is_empty() || step.fallbacks.iter().any(|f| f.trim().is_empty()) {
+                            return Err(de::Error::custom("skill name must not be empty"));
+                        }
+                        Ok(StepSpec::Detailed {
+                            skill,
+                            policy: step.policy,
+                            when: step.when,
+                            map: step.map,
+                            fallbacks: step.fallbacks,
+                        })
+                    }
+                }
+            }
+        }
+
+        deserializer.deserialize_any(StepVisitor)
+    }
+}
+
+/// JSON shape for one intent: a list of steps, or `{ "steps": [...], "policy": { ... } }`
+/// where `policy` is the default for steps that do not set their own. At least one step is required.
+#[derive(Debug)]
+pub enum IntentSpec {
+    Steps(Vec<StepSpec>),
+    Detailed {
+        steps: Vec<StepSpec>,
+        policy: Option<StepPolicy>,
+    },
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct IntentObject {
+    steps: Vec<StepSpec>,
+    #[serde(default)]
+    policy: Option<StepPolicy>,
+}
+
+impl<'de> Deserialize<'de> for IntentSpec {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct IntentVisitor;
+
+        impl<'de> Visitor<'de> for IntentVisitor {
+            type Value = IntentSpec;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a list of steps or an object { \"steps\": [...], \"policy\": {...} }")
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, seq: A) -> Result<IntentSpec, A::Error> {
+                let steps = Vec::<StepSpec>::deserialize(de::value::SeqAccessDeserializer::new(seq))?;
+                non_empty(&steps)?;
+                Ok(IntentSpec::Steps(steps))
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<IntentSpec, A::Error> {
+                let intent = IntentObject::deserialize(de::value::MapAccessDeserializer::new(map))?;
+                non_empty(&intent.steps)?;
+                Ok(IntentSpec::Detailed {
+                    steps: intent.steps,
+                    policy: intent.policy,
+                })
+            }
+        }
+
+        deserializer.deserialize_any(IntentVisitor)
+    }
+}
+
+fn non_empty<E: de::Error>(steps: &[StepSpec]) -> Result<(), E> {
+    if steps.is_empty() {
+        return Err(E::custom("intent must have at least one step"));
+    }
+    Ok(())
+}
+
+impl IntentSpec {
+    /// Resolves the spec into steps, applying the intent-level default policy.
+    pub fn into_steps(self) -> Vec<PlanStep> {
+        let (steps, default_policy) = match self {
+            IntentSpec::Steps(steps) => (steps, None),
+            IntentSpec::Detailed { steps, policy } => (steps, policy),
+        };
+        let default_policy = default_policy.unwrap_or_default();
+        steps
+            .into_iter()
+            .map(|spec| match spec {
+                StepSpec::Name(skill) => PlanStep {
+                    policy: default_policy.clone(),
+                    ..PlanStep::new(skill)
+                },
+                StepSpec::Detailed {
+                    skill,
+                    policy,
+                    when,
+                    map,
+                    fallbacks,
+                } => PlanStep {
+                    skill,
+                    intent: None,
+                    policy: policy.unwrap_or_else(|| default_policy.clone()),
+                    when,
+                    map,
+                    fallbacks,
+                },
+                StepSpec::SubPlan { intent, policy, when, map } => PlanStep {
+                    policy: policy.unwrap_or_else(|| default_policy.clone()),
+                    when,
+                    map,
+                    ..PlanStep::sub_plan(intent)
+                },
+            })
+            .collect()
+    }
 }
 
 /// JSON shape for blueprint file: { "intents": { "intent name": ["SkillA", "SkillB"], ... } }
 #[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct BlueprintFile {
-    pub intents: HashMap<String, Vec<String>>,
+    #[serde(deserialize_with = "unique_intents")]
+    pub intents: HashMap<String, IntentSpec>,
+}
+
+/// Intent map that rejects empty names and names that collide after normalization
+/// (`"Respond To Lead"` and `"respond to lead"`), which would otherwise silently overwrite.
+fn unique_intents<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<HashMap<String, IntentSpec>, D::Error> {
+    struct IntentsVisitor;
+
+    impl<'de> Visitor<'de> for IntentsVisitor {
+        type Value = HashMap<String, IntentSpec>;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("an object mapping intent names to steps")
+        }
+
+        fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+            let mut intents = HashMap::new();
+            let mut seen = HashMap::new();
+            while let Some(name) = map.next_key::<String>()? {
+                let normalized = normalize_intent(&name);
+                if normalized.is_empty() {
+                    return Err(de::Error::custom("intent name must not be empty"));
+                }
+                if let Some(previous) = seen.insert(normalized, name.clone()) {
+                    return Err(de::Error::custom(format!(
+                        "duplicate intent \"{}\" (same as \"{}\"; intent names are case-insensitive)",
+                        name, previous
+                    )));
+                }
+                let spec = map.next_value::<IntentSpec>()?;
+                intents.insert(name, spec);
+            }
+            Ok(intents)
+        }
+    }
+
+    deserializer.deserialize_map(IntentsVisitor)
+}
+
+/// One problem found in a blueprint file. `line`/`column` are 1-based and None when the
+/// location is unknown.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BlueprintIssue {
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+    pub message: String,
+}
+
+impl fmt::Display for BlueprintIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.line, self.column) {
+            (Some(line), Some(column)) => write!(f, "line {} column {}: {}", line, column, self.message),
+            _ => f.write_str(&self.message),
+        }
+    }
+}
+
+/// A blueprint file failed validation.
+#[derive(Debug)]
+pub struct BlueprintError {
+    pub path: PathBuf,
+    pub issues: Vec<BlueprintIssue>,
+}
+
+impl fmt::Display for BlueprintError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid blueprint {}", self.path.display())?;
+        for issue in &self.issues {
+            write!(f, "\n  {}", issue)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for BlueprintError {}
+
+/// Parses blueprint JSON strictly. Syntax errors, unknown fields, empty step lists and duplicate
+/// intents are reported with their line and column.
+pub fn parse_blueprint(text: &str) -> Result<HashMap<String, Vec<PlanStep>>, BlueprintIssue> {
+    let file: BlueprintFile = serde_json::from_str(text).map_err(|e| BlueprintIssue {
+        line: Some(e.line()).filter(|l| *l > 0),
+        column: Some(e.column()).filter(|_| e.line() > 0),
+        message: e.to_string().split(" at line ").next().unwrap_or_default().to_string(),
+    })?;
+    Ok(file
+        .intents
+        .into_iter()
+        .map(|(k, v)| (normalize_intent(&k), v.into_steps()))
+        .collect())
+}
+
+/// A problem with a sub-plan reference: `owner` has a step running `target`.
+#[derive(Debug)]
+struct SubPlanIssue {
+    owner: String,
+    target: String,
+    /// Intents from the walk's root down to `owner`.
+    chain: Vec<String>,
+    message: String,
+}
+
+/// Sub-plan references to unknown intents, cycles, and nesting deeper than [`MAX_SUB_PLAN_DEPTH`].
+fn sub_plan_issues(intents: &HashMap<String, Vec<PlanStep>>) -> Vec<SubPlanIssue> {
+    fn walk(
+        intents: &HashMap<String, Vec<PlanStep>>,
+        chain: &mut Vec<String>,
+        issues: &mut Vec<SubPlanIssue>,
+    ) {
+        let owner = chain.last().cloned().unwrap_or_default();
+        for target in intents[&owner].iter().filter_map(|s| s.intent.as_deref()) {
+            let message = if !intents.contains_key(target) {
+                format!("intent \"{}\": unknown sub-plan intent \"{}\"", owner, target)
+            } else if chain.iter().any(|c| c == target) {
+                format!(
+                    "intent \"{}\": sub-plan \"{}\" forms a cycle ({} -> {})",
+                    owner,
+                    target,
+                    chain.join(" -> "),
+                    target
+                )
+            } else if chain.len() > MAX_SUB_PLAN_DEPTH {
+                format!(
+                    "intent \"{}\": sub-plan \"{}\" nests deeper than {} levels ({} -> {})",
+                    owner,
+                    target,
+                    MAX_SUB_PLAN_DEPTH,
+                    chain.join(" -> "),
+                    target
+                )
+            } else {
+                chain.push(target.to_string());
+                walk(intents, chain, issues);
+                chain.pop();
+                continue;
+            };
+            issues.push(SubPlanIssue {
+                owner: owner.clone(),
+                target: target.to_string(),
+                chain: chain.clone(),
+                message,
+            });
+        }
+    }
+
+    let mut roots: Vec<&String> = intents.keys().collect();
+    roots.sort();
+    let mut issues = Vec::new();
+    for root in roots {
+        walk(intents, &mut vec![root.clone()], &mut issues);
+    }
+    // The same reference is reached from every root above it; keep the first report.
+    let mut seen = std::collections::HashSet::new();
+    issues.retain(|i: &SubPlanIssue| seen.insert((i.owner.clone(), i.target.clone())));
+    issues
+}
+
+/// Validates blueprint JSON: [`parse_blueprint`], then every sub-plan step must name a defined
+/// intent without cycles or nesting deeper than [`MAX_SUB_PLAN_DEPTH`], and (when `skills` is
+/// given) every step and fallback skill must be one of `skills`. Returns all issues found; empty
+/// means valid.
+pub fn validate_blueprint(text: &str, skills: Option<&[String]>) -> Vec<BlueprintIssue> {
+    let intents = match parse_blueprint(text) {
+        Ok(intents) => intents,
+        Err(issue) => return vec![issue],
+    };
+    let mut issues: Vec<BlueprintIssue> = sub_plan_issues(&intents)
+        .into_iter()
+        .map(|i| {
+            let (line, column) = locate_skill(text, &i.owner, &i.target).unzip();
+            BlueprintIssue {
+                line,
+                column,
+                message: i.message,
+            }
+        })
+        .collect();
+    let Some(skills) = skills else {
+        issues.sort_by_key(|i| (i.line, i.column));
+        return issues;
+    };
+    let mut names: Vec<&String> = intents.keys().collect();
+    names.sort();
+    for intent in names {
+        for step in &intents[intent] {
+            for skill in step.candidates() {
+                if skills.iter().any(|s| s == skill) {
+                    continue;
+                }
+                let (line, column) = locate_skill(text, intent, skill).unzip();
+                issues.push(BlueprintIssue {
+                    line,
+                    column,
+                    message: format!("intent \"{}\": unknown skill \"{}\" (not registered)", intent, skill),
+                });
+            }
+        }
+    }
+    issues.sort_by_key(|i| (i.line, i.column));
+    issues.dedup();
+    issues
+}
+
+/// Best-effort position of `"skill"` (or sub-plan intent) within the (case-insensitive) intent's entry.
+fn locate_skill(text: &str, intent: &str, skill: &str) -> Option<(usize, usize)> {
+    let lower = text.to_lowercase();
+    let key = serde_json::to_string(intent).ok()?;
+    let intent_at = lower.find(&key.to_lowercase())?;
+    let needle = serde_json::to_string(skill).ok()?;
+    let offset = intent_at + text[intent_at..].find(&needle)?;
+    let before = &text[..offset];
+    let line = before.matches('\n').count() + 1;
+    let column = before.len() - before.rfind('\n').map_or(0, |i| i + 1) + 1;
+    Some((line, column))
+}
+
+/// KB-5 (Techne) key prefix for runtime blueprint entries: `blueprints/{intent}`.
+/// Entries overlay the JSON file; a `{ "deleted": true }` tombstone hides a file intent.
+pub const BLUEPRINT_KB_PREFIX: &str = "blueprints/";
+
+/// Stored shape of a runtime blueprint entry in KB-5.
+#[derive(Debug, Serialize, Deserialize)]
+struct StoredIntent {
+    intent: String,
+    #[serde(default)]
+    steps: Vec<PlanStep>,
+    #[serde(default)]
+    deleted: bool,
 }
 
 /// Registry that maps intent names to plans. Load from file or use default.
-#[derive(Debug, Clone)]
+///
+/// Intents can be edited at runtime with [`upsert_intent`](Self::upsert_intent) /
+/// [`remove_intent`](Self::remove_intent). When a store is attached
+/// ([`with_store`](Self::with_store)) edits persist to KB-5 and survive [`reload`](Self::reload),
+/// which re-reads the JSON file and re-applies the KB-5 overlay.
 pub struct BlueprintRegistry {
-    intents: HashMap<String, Vec<String>>,
+    intents: RwLock<HashMap<String, Vec<PlanStep>>>,
+    /// JSON file this registry was loaded from (re-read on reload).
+    source: Option<PathBuf>,
+    store: Option<Arc<KnowledgeStore>>,
+}
+
+impl std::fmt::Debug for BlueprintRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BlueprintRegistry")
+            .field("intents", &self.intents)
+            .field("source", &self.source)
+            .field("store", &self.store.is_some())
+            .finish()
+    }
+}
+
+pub(crate) fn normalize_intent(intent: &str) -> String {
+    intent.trim().to_lowercase()
 }
 
 impl BlueprintRegistry {
-    /// Empty registry (no intents).
-    pub fn empty() -> Self {
+    fn from_map(intents: HashMap<String, Vec<PlanStep>>) -> Self {
         Self {
-            intents: HashMap::new(),
+            intents: RwLock::new(intents),
+            source: None,
+            store: None,
         }
     }
 
+    /// Empty registry (no intents).
+    pub fn empty() -> Self {
+        Self::from_map(HashMap::new())
+    }
+
     /// Default blueprint with common intents (e.g. "respond to lead").
     pub fn default_blueprint() -> Self {
+        Self::from_map(Self::default_intents())
+    }
+
+    fn default_intents() -> HashMap<String, Vec<PlanStep>> {
         let mut intents = HashMap::new();
         intents.insert(
             "respond to lead".to_string(),
             vec![
-                "DraftResponse".to_string(),
-                "SalesCloser".to_string(),
-                "ModelRouter".to_string(),
+                PlanStep::new("DraftResponse"),
+                PlanStep::new("SalesCloser"),
+                PlanStep::new("ModelRouter"),
             ],
         );
-        Self { intents }
+        intents
     }
 
-    /// Load from a JSON file. Returns default on error or missing file.
+    /// Load from a JSON file. Returns default on error or missing file; validation errors are
+    /// logged with their line and column (use [`try_load_json_path`](Self::try_load_json_path)
+    /// to fail instead). The path is remembered so [`reload`](Self::reload) can re-read it.
     pub fn load_json_path<P: AsRef<Path>>(path: P) -> Self {
         let path = path.as_ref();
-        let s = match std::fs::read_to_string(path) {
-            Ok(s) => s,
-            Err(_) => return Self::default_blueprint(),
+        let intents = match Self::read_json(path) {
+            Ok(intents) => intents,
+            Err(e) => {
+                if path.exists() {
+                    tracing::error!(target: "pagi::orchestrator", error = %e, "Blueprint rejected; using default intents");
+                }
+                Self::default_intents()
+            }
+        };
+        Self {
+            source: Some(path.to_path_buf()),
+            ..Self::from_map(intents)
+        }
+    }
+
+    /// Like [`load_json_path`](Self::load_json_path) but an existing file that fails validation
+    /// is an error. A missing file still yields the default intents.
+    pub fn try_load_json_path<P: AsRef<Path>>(path: P) -> Result<Self, BlueprintError> {
+        let path = path.as_ref();
+        let intents = if path.exists() {
+            Self::read_json(path)?
+        } else {
+            Self::default_intents()
         };
-        let file: BlueprintFile = match serde_json::from_str(&s) {
-            Ok(f) => f,
-            Err(_) => return Self::default_blueprint(),
+        Ok(Self {
+            source: Some(path.to_path_buf()),
+            ..Self::from_map(intents)
+        })
+    }
+
+    fn read_json(path: &Path) -> Result<HashMap<String, Vec<PlanStep>>, BlueprintError> {
+        let error = |issue| BlueprintError {
+            path: path.to_path_buf(),
+            issues: vec![issue],
         };
-        let intents = file
-            .intents
-            .into_iter()
-            .map(|(k, v)| (k.trim().to_lowercase(), v))
-            .collect();
-        Self { intents }
+        let text = std::fs::read_to_string(path).map_err(|e| {
+            error(BlueprintIssue {
+                line: None,
+                column: None,
+                message: e.to_string(),
+            })
+        })?;
+        parse_blueprint(&text).map_err(error)
     }
 
-    /// Build from in-memory intents (e.g. for tests).
+    /// Build from in-memory intents (e.g. for tests). Every step uses the default policy.
     pub fn from_intents(intents: HashMap<String, Vec<String>>) -> Self {
         let intents = intents
             .into_iter()
-            .map(|(k, v)| (k.trim().to_lowercase(), v))
+            .map(|(k, v)| (normalize_intent(&k), v.into_iter().map(PlanStep::new).collect()))
             .collect();
-        Self { intents }
+        Self::from_map(intents)
+    }
+
+    /// Build from in-memory plans with explicit per-step policies.
+    pub fn from_plans(plans: HashMap<String, Plan>) -> Self {
+        let intents = plans
+            .into_iter()
+            .map(|(k, p)| (normalize_intent(&k), p.steps))
+            .collect();
+        Self::from_map(intents)
+    }
+
+    /// Backs runtime edits with KB-5 and applies any stored entries on top of the current intents.
+    pub fn with_store(mut self, store: Arc<KnowledgeStore>) -> Self {
+        self.store = Some(store);
+        if let Ok(mut intents) = self.intents.write() {
+            if let Err(e) = self.apply_store_overlay(&mut intents) {
+                tracing::warn!(target: "pagi::orchestrator", error = %e, "Failed to read KB-5 blueprints");
+            }
+        }
+        self
+    }
+
+    fn apply_store_overlay(
+        &self,
+        intents: &mut HashMap<String, Vec<PlanStep>>,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let Some(store) = &self.store else {
+            return Ok(());
+        };
+        for (key, bytes) in store.scan_kv(KbType::Techne.slot_id())? {
+            if !key.starts_with(BLUEPRINT_KB_PREFIX) {
+                continue;
+            }
+            let Ok(entry) = serde_json::from_slice::<StoredIntent>(&bytes) else {
+                continue;
+            };
+            let name = normalize_intent(&entry.intent);
+            if entry.deleted {
+                intents.remove(&name);
+            } else {
+                intents.insert(name, entry.steps);
+            }
+        }
+        Ok(())
+    }
+
+    fn write_store(&self, entry: &StoredIntent) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        if let Some(store) = &self.store {
+            let key = format!("{}{}", BLUEPRINT_KB_PREFIX, normalize_intent(&entry.intent));
+            store.insert(KbType::Techne.slot_id(), &key, &serde_json::to_vec(entry)?)?;
+        }
+        Ok(())
+    }
+
+    /// Re-reads the JSON source (if any) and re-applies the KB-5 overlay. On a file read or parse
+    /// error the current intents are kept and the error is returned. Returns the intent count.
+    pub fn reload(&self) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
+        let mut fresh = match &self.source {
+            Some(path) => Self::read_json(path).map_err(|e| format!("blueprint reload failed: {}", e))?,
+            None => self.intents.read().map(|g| g.clone()).unwrap_or_default(),
+        };
+        self.apply_store_overlay(&mut fresh)?;
+        let count = fresh.len();
+        *self.intents.write().unwrap_or_else(PoisonError::into_inner) = fresh;
+        tracing::info!(target: "pagi::orchestrator", intents = count, "Blueprint reloaded");
+        Ok(count)
+    }
+
+    /// Adds or replaces an intent at runtime (persisted to KB-5 when a store is attached).
+    pub fn upsert_intent(
+        &self,
+        intent: &str,
+        steps: Vec<PlanStep>,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.upsert_intents(vec![(intent.to_string(), steps)])
+    }
+
+    /// Adds or replaces several intents at once. Sub-plan references are checked against the
+    /// whole batch, so intents may call each other in any order; nothing is written on error.
+    pub fn upsert_intents(
+        &self,
+        entries: Vec<(String, Vec<PlanStep>)>,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut names = Vec::with_capacity(entries.len());
+        for (intent, steps) in &entries {
+            let name = normalize_intent(intent);
+            if name.is_empty() {
+                return Err("intent name must not be empty".into());
+            }
+            if steps.is_empty() {
+                return Err(format!("intent \"{}\" must have at least one step", name).into());
+            }
+            names.push(name);
+        }
+        let mut candidate = self.intents.read().map(|g| g.clone()).unwrap_or_default();
+        for (name, (_, steps)) in names.iter().zip(&entries) {
+            candidate.insert(name.clone(), steps.clone());
+        }
+        if let Some(issue) = sub_plan_issues(&candidate)
+            .into_iter()
+            .find(|i| i.chain.iter().any(|c| names.contains(c)))
+        {
+            return Err(issue.message.into());
+        }
+        for (name, (_, steps)) in names.iter().zip(&entries) {
+            self.write_store(&StoredIntent {
+                intent: name.clone(),
+                steps: steps.clone(),
+                deleted: false,
+            })?;
+        }
+        let mut intents = self.intents.write().unwrap_or_else(PoisonError::into_inner);
+        for (name, (_, steps)) in names.into_iter().zip(entries) {
+            intents.insert(name, steps);
+        }
+        Ok(())
+    }
+
+    /// Drops the runtime entry for an intent: the file-defined plan comes back if there is one,
+    /// otherwise the intent disappears. Unlike [`remove_intent`](Self::remove_intent) no tombstone
+    /// is written. Returns whether the intent still exists afterwards.
+    pub fn revert_intent(&self, intent: &str) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        let name = normalize_intent(intent);
+        if let Some(store) = &self.store {
+            store.remove(KbType::Techne.slot_id(), &format!("{}{}", BLUEPRINT_KB_PREFIX, name))?;
+        }
+        let base = match &self.source {
+            Some(path) => Self::read_json(path).ok().and_then(|mut file| file.remove(&name)),
+            None => None,
+        };
+        let mut intents = self.intents.write().unwrap_or_else(PoisonError::into_inner);
+        Ok(match base {
+            Some(steps) => {
+                intents.insert(name, steps);
+                true
+            }
+            None => {
+                intents.remove(&name);
+                false
+            }
+        })
+    }
+
+    /// Removes an intent at runtime. With a store attached a KB-5 tombstone keeps file-defined
+    /// intents hidden across reloads. Returns whether the intent existed.
+    pub fn remove_intent(&self, intent: &str) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        let name = normalize_intent(intent);
+        self.write_store(&StoredIntent {
+            intent: name.clone(),
+            steps: Vec::new(),
+            deleted: true,
+        })?;
+        Ok(self
+            .intents
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(&name)
+            .is_some())
     }
 
     /// Returns a plan for the given intent, or None if unknown.
     pub fn plan_for_intent(&self, intent: &str) -> Option<Plan> {
-        let key = intent.trim().to_lowercase();
-        self.intents.get(&key).cloned().map(|steps| Plan { steps })
+        let key = normalize_intent(intent);
+        self.intents
+            .read()
+            .ok()?
+            .get(&key)
+            .cloned()
+            .map(|steps| Plan { steps })
     }
 
     /// List registered intent names.
     pub fn intent_names(&self) -> Vec<String> {
-        self.intents.keys().cloned().collect()
+        self.intents
+            .read()
+            .map(|g| g.keys().cloned().collect())
+            .unwrap_or_default()
     }
 }
 
@@ -101,6 +952,92 @@ mod tests {
         assert_eq!(plan.steps, ["DraftResponse", "SalesCloser", "ModelRouter"]);
     }
 
+    #[test]
+    fn json_steps_accept_names_and_policies() {
+        let json = r#"{
+            "intents": {
+                "Respond To Lead": {
+                    "policy": { "max_retries": 1 },
+                    "steps": [
+                        "DraftResponse",
+                        { "skill": "ModelRouter", "policy": { "max_retries": 3, "backoff_ms": 100, "timeout_ms": 5000 } }
+                    ]
+                },
+                "summarize news": ["CommunityScraper", "ModelRouter"]
+            }
+        }"#;
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("blueprint.json");
+        std::fs::write(&path, json).unwrap();
+        let reg = BlueprintRegistry::load_json_path(&path);
+
+        let plan = reg.plan_for_intent("respond to lead").unwrap();
+        assert_eq!(plan.steps, ["DraftResponse", "ModelRouter"]);
+        assert_eq!(plan.steps[0].policy.max_retries, 1);
+        assert_eq!(plan.steps[1].policy.max_retries, 3);
+        assert_eq!(plan.steps[1].policy.timeout_ms, Some(5000));
+
+        let plan = reg.plan_for_intent("summarize news").unwrap();
+        assert_eq!(plan.steps[0].policy, StepPolicy::default());
+    }
+
+    #[test]
+    fn json_steps_accept_when_conditions() {
+        let json = r#"{
+            "intents": {
+                "triage lead": [
+                    "DraftResponse",
+                    { "skill": "SalesCloser", "when": { "path": "/sentiment", "equals": "positive" } },
+                    { "skill": "ModelRouter", "when": { "path": "score/value", "equals": 3 } }
+                ]
+            }
+        }"#;
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("blueprint.json");
+        std::fs::write(&path, json).unwrap();
+        let plan = BlueprintRegistry::load_json_path(&path).plan_for_intent("triage lead").unwrap();
+        assert!(plan.steps[0].when.is_none());
+
+        let when = plan.steps[1].when.as_ref().unwrap();
+        assert!(when.matches(&serde_json::json!({ "sentiment": "positive" })));
+        assert!(!when.matches(&serde_json::json!({ "sentiment": "negative" })));
+        assert!(!when.matches(&serde_json::json!({})));
+
+        let when = plan.steps[2].when.as_ref().unwrap();
+        assert!(when.matches(&serde_json::json!({ "score": { "value": 3 } })));
+    }
+
+    #[test]
+    fn json_steps_accept_payload_maps() {
+        let json = r#"{
+            "intents": {
+                "summarize page": [
+                    "GenericWebFetcher",
+                    { "skill": "ModelRouter", "map": { "prompt": "$.body", "temperature": 0.1 } }
+                ]
+            }
+        }"#;
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("blueprint.json");
+        std::fs::write(&path, json).unwrap();
+        let plan = BlueprintRegistry::load_json_path(&path).plan_for_intent("summarize page").unwrap();
+        assert!(plan.steps[0].map.is_none());
+        let map = plan.steps[1].map.as_ref().unwrap();
+        assert_eq!(map["prompt"], "$.body");
+        assert_eq!(map["temperature"], 0.1);
+    }
+
+    #[test]
+    fn backoff_doubles_per_retry() {
+        let policy = StepPolicy {
+            backoff_ms: 100,
+            ..StepPolicy::default()
+        };
+        assert_eq!(policy.backoff_for(1).as_millis(), 100);
+        assert_eq!(policy.backoff_for(2).as_millis(), 200);
+        assert_eq!(policy.backoff_for(3).as_millis(), 400);
+    }
+
     #[test]
     fn from_intents_custom_plan() {
         let mut intents = HashMap::new();
@@ -113,4 +1050,130 @@ mod tests {
         assert_eq!(plan.steps, ["GenericWebFetcher", "Summarize"]);
         assert!(reg.plan_for_intent("respond to lead").is_none());
     }
+
+    #[test]
+    fn runtime_edits_persist_in_kb5_across_reload() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("blueprint.json");
+        std::fs::write(&path, r#"{"intents": {"summarize news": ["CommunityScraper", "ModelRouter"]}}"#).unwrap();
+        let store = Arc::new(KnowledgeStore::open_path(dir.path().join("kb")).unwrap());
+
+        let reg = BlueprintRegistry::load_json_path(&path).with_store(Arc::clone(&store));
+        reg.upsert_intent("Greet Visitor", vec![PlanStep::new("DraftResponse")]).unwrap();
+        assert!(reg.remove_intent("summarize news").unwrap());
+
+        // Edit the file: the new intent appears on reload, the tombstoned one stays hidden.
+        std::fs::write(
+            &path,
+            r#"{"intents": {"summarize news": ["ModelRouter"], "triage": ["LeadCapture"]}}"#,
+        )
+        .unwrap();
+        assert_eq!(reg.reload().unwrap(), 2);
+        assert!(reg.plan_for_intent("summarize news").is_none());
+        assert_eq!(reg.plan_for_intent("triage").unwrap().steps, ["LeadCapture"]);
+        assert_eq!(reg.plan_for_intent("greet visitor").unwrap().steps, ["DraftResponse"]);
+
+        // A fresh registry over the same store sees the runtime edits.
+        let reopened = BlueprintRegistry::load_json_path(&path).with_store(store);
+        assert!(reopened.plan_for_intent("greet visitor").is_some());
+        assert!(reopened.plan_for_intent("summarize news").is_none());
+    }
+
+    #[test]
+    fn strict_parse_reports_position() {
+        let typo = "{\n  \"intents\": {\n    \"triage\": [\n      { \"skill\": \"DraftResponse\", \"polcy\": {} }\n    ]\n  }\n}";
+        let issue = parse_blueprint(typo).unwrap_err();
+        assert_eq!(issue.line, Some(4));
+        assert!(issue.message.starts_with("unknown field `polcy`"), "{}", issue.message);
+
+        let issue = parse_blueprint("{\"intents\": {\"triage\": []}}").unwrap_err();
+        assert!(issue.message.contains("at least one step"), "{}", issue.message);
+        let issue = parse_blueprint("{\"intents\": {\"a\": [\"X\"], \"A\": [\"Y\"]}}").unwrap_err();
+        assert!(issue.message.starts_with("duplicate intent \"A\""), "{}", issue.message);
+        let issue = parse_blueprint("{\"intent\": {}}").unwrap_err();
+        assert!(issue.message.starts_with("unknown field `intent`"), "{}", issue.message);
+        let issue = parse_blueprint("{\"intents\": {\"a\": [{\"skill\": \"X\", \"policy\": {\"retries\": 2}}]}}").unwrap_err();
+        assert!(issue.message.starts_with("unknown field `retries`"), "{}", issue.message);
+        assert_eq!(parse_blueprint("{ not json").unwrap_err().line, Some(1));
+    }
+
+    #[test]
+    fn validate_flags_unregistered_skills_with_location() {
+        let json = "{\"intents\": {\n  \"triage\": [\"DraftResponse\",\n    {\"skill\": \"ModelRouter\", \"fallbacks\": [\"Missing\"]}]\n}}";
+        let skills = vec!["DraftResponse".to_string(), "ModelRouter".to_string()];
+        let issues = validate_blueprint(json, Some(&skills));
+        assert_eq!(issues.len(), 1);
+        assert_eq!((issues[0].line, issues[0].column), (Some(3), Some(44)));
+        assert_eq!(issues[0].message, "intent \"triage\": unknown skill \"Missing\" (not registered)");
+        assert!(validate_blueprint(json, None).is_empty());
+    }
+
+    #[test]
+    fn sub_plan_steps_parse_and_validate_references() {
+        let json = r#"{"intents": {"Digest": ["Fetch", {"intent": "Summarize News", "map": {"text": "$.body"}}]}}"#;
+        let intents = parse_blueprint(json).unwrap();
+        let step = &intents["digest"][1];
+        assert_eq!(step.intent.as_deref(), Some("summarize news"));
+        assert_eq!(step.label(), "intent:summarize news");
+        assert!(step.candidates().is_empty());
+
+        let issue = parse_blueprint(r#"{"intents": {"a": [{"intent": "b", "skill": "X"}]}}"#).unwrap_err();
+        assert!(issue.message.contains("not both"), "{}", issue.message);
+        let issue = parse_blueprint(r#"{"intents": {"a": [{"intent": "b", "fallbacks": ["X"]}]}}"#).unwrap_err();
+        assert!(issue.message.contains("cannot have fallbacks"), "{}", issue.message);
+
+        let json = "{\"intents\": {\n  \"a\": [{\"intent\": \"b\"}],\n  \"b\": [\"X\", {\"intent\": \"a\"}],\n  \"c\": [{\"intent\": \"missing\"}]\n}}";
+        let messages: Vec<String> = validate_blueprint(json, None).into_iter().map(|i| i.message).collect();
+        assert_eq!(
+            messages,
+            [
+                "intent \"a\": sub-plan \"b\" forms a cycle (b -> a -> b)",
+                "intent \"b\": sub-plan \"a\" forms a cycle (a -> b -> a)",
+                "intent \"c\": unknown sub-plan intent \"missing\"",
+            ]
+        );
+        assert_eq!(validate_blueprint(json, None)[2].line, Some(4));
+    }
+
+    #[test]
+    fn sub_plan_depth_limit_and_upsert_checks() {
+        let depth = MAX_SUB_PLAN_DEPTH + 1;
+        let mut plans: HashMap<String, Vec<PlanStep>> = (0..depth)
+            .map(|i| (format!("l{}", i), vec![PlanStep::sub_plan(format!("l{}", i + 1))]))
+            .collect();
+        plans.insert(format!("l{}", depth), vec![PlanStep::new("X")]);
+        let issues = sub_plan_issues(&plans);
+        assert_eq!(issues.len(), 1);
+        assert!(issues[0].message.contains("nests deeper than"), "{}", issues[0].message);
+
+        let reg = BlueprintRegistry::from_intents(HashMap::from([("a".to_string(), vec!["X".to_string()])]));
+        reg.upsert_intent("b", vec![PlanStep::sub_plan("A")]).unwrap();
+        let err = reg.upsert_intent("a", vec![PlanStep::sub_plan("b")]).unwrap_err();
+        assert!(err.to_string().contains("forms a cycle"), "{}", err);
+        let err = reg.upsert_intent("c", vec![PlanStep::sub_plan("nope")]).unwrap_err();
+        assert!(err.to_string().contains("unknown sub-plan intent"), "{}", err);
+    }
+
+    #[test]
+    fn try_load_rejects_invalid_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("blueprint.json");
+        assert!(BlueprintRegistry::try_load_json_path(&path).unwrap().plan_for_intent("respond to lead").is_some());
+        std::fs::write(&path, r#"{"intents": {"a": {"steps": ["X"], "polciy": {}}}}"#).unwrap();
+        let err = BlueprintRegistry::try_load_json_path(&path).unwrap_err();
+        assert!(err.to_string().contains("line 1 column"), "{}", err);
+        // The lenient loader still falls back to the defaults.
+        assert!(BlueprintRegistry::load_json_path(&path).plan_for_intent("respond to lead").is_some());
+    }
+
+    #[test]
+    fn reload_keeps_current_intents_on_parse_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("blueprint.json");
+        std::fs::write(&path, r#"{"intents": {"a": ["X"]}}"#).unwrap();
+        let reg = BlueprintRegistry::load_json_path(&path);
+        std::fs::write(&path, "{ not json").unwrap();
+        assert!(reg.reload().is_err());
+        assert!(reg.plan_for_intent("a").is_some());
+    }
 }
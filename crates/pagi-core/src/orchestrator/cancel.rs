@@ -0,0 +1,215 @@
+//! Cancellation for in-flight goals.
+//!
+//! Every dispatch runs under a [`CancellationToken`]. When the request has a correlation id the
+//! token is registered in [`InFlight`] so [`super::Orchestrator::cancel`] can stop it: plan steps
+//! check the token between steps, a running skill future is dropped when it fires (which aborts
+//! HTTP requests in flight), and streams end with a `cancelled` chunk.
+//!
+//! A request deadline ([`TenantContext::deadline_ms`]) fires the same token, and the resulting
+//! cancellation is reported as [`DeadlineExceeded`].
+
+use crate::shared::TenantContext;
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
+use std::sync::{Arc, Mutex, PoisonError};
+
+pub use tokio_util::sync::CancellationToken;
+
+/// A goal was cancelled before it finished.
+#[derive(Debug)]
+pub struct GoalCancelled {
+    /// Plan steps (or sub-goals) that completed before cancellation.
+    pub completed_steps: usize,
+}
+
+impl fmt::Display for GoalCancelled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "goal cancelled after {} completed step(s)", self.completed_steps)
+    }
+}
+
+impl std::error::Error for GoalCancelled {}
+
+/// The request's deadline ([`TenantContext::deadline_ms`]) passed before the goal finished.
+#[derive(Debug)]
+pub struct DeadlineExceeded {
+    pub deadline_ms: i64,
+    /// Plan steps (or sub-goals) that completed before the deadline.
+    pub completed_steps: usize,
+}
+
+impl fmt::Display for DeadlineExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "deadline exceeded after {} completed step(s)", self.completed_steps)
+    }
+}
+
+impl std::error::Error for DeadlineExceeded {}
+
+/// Cancels the dispatch token when the deadline passes; dropping it stops the timer.
+pub(crate) struct DeadlineTimer(tokio::task::JoinHandle<()>);
+
+impl Drop for DeadlineTimer {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+/// Starts a [`DeadlineTimer`] for `ctx`'s deadline (None without one). An already passed
+/// deadline cancels `token` at once.
+pub(crate) fn cancel_at_deadline(ctx: &TenantContext, token: &CancellationToken) -> Option<DeadlineTimer> {
+    let remaining = ctx.remaining()?;
+    if remaining.is_zero() {
+        token.cancel();
+    }
+    let token = token.clone();
+    Some(DeadlineTimer(tokio::spawn(async move {
+        tokio::time::sleep(remaining).await;
+        token.cancel();
+    })))
+}
+
+/// Reports a cancellation caused by `ctx`'s deadline (rather than a cancel request) as
+/// [`DeadlineExceeded`]; other errors pass through.
+pub(crate) fn deadline_error(
+    ctx: &TenantContext,
+    err: Box<dyn std::error::Error + Send + Sync>,
+) -> Box<dyn std::error::Error + Send + Sync> {
+    match (err.downcast_ref::<GoalCancelled>(), ctx.deadline_ms) {
+        (Some(cancelled), Some(deadline_ms)) if ctx.deadline_exceeded() => DeadlineExceeded {
+            deadline_ms,
+            completed_steps: cancelled.completed_steps,
+        }
+        .into(),
+        _ => err,
+    }
+}
+
+/// Tokens of running dispatches, keyed by correlation id.
+#[derive(Default)]
+pub(crate) struct InFlight {
+    tokens: Mutex<HashMap<String, CancellationToken>>,
+}
+
+impl InFlight {
+    /// Registers `token` under `correlation_id` until the returned guard drops. A second dispatch
+    /// with the same id is chained to the first, so one cancel stops both.
+    pub(crate) fn register(self: &Arc<Self>, correlation_id: &str, token: &CancellationToken) -> InFlightGuard {
+        let mut tokens = self.tokens.lock().unwrap_or_else(PoisonError::into_inner);
+        let owner = match tokens.get(correlation_id) {
+            Some(existing) => {
+                let existing = existing.clone();
+                let token = token.clone();
+                tokio::spawn(async move {
+                    tokio::select! {
+                        _ = existing.cancelled() => token.cancel(),
+                        _ = token.cancelled() => {}
+                    }
+                });
+                false
+            }
+            None => {
+                tokens.insert(correlation_id.to_string(), token.clone());
+                true
+            }
+        };
+        InFlightGuard {
+            in_flight: Arc::clone(self),
+            correlation_id: correlation_id.to_string(),
+            owner,
+        }
+    }
+
+    /// Cancels the dispatch registered under `correlation_id`. Returns false if none is running.
+    pub(crate) fn cancel(&self, correlation_id: &str) -> bool {
+        let tokens = self.tokens.lock().unwrap_or_else(PoisonError::into_inner);
+        match tokens.get(correlation_id) {
+            Some(token) => {
+                token.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub(crate) fn ids(&self) -> Vec<String> {
+        let mut ids: Vec<String> = self
+            .tokens
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .keys()
+            .cloned()
+            .collect();
+        ids.sort();
+        ids
+    }
+}
+
+/// Removes the registration when the dispatch (or its stream) ends.
+pub(crate) struct InFlightGuard {
+    in_flight: Arc<InFlight>,
+    correlation_id: String,
+    owner: bool,
+}
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        if self.owner {
+            self.in_flight
+                .tokens
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .remove(&self.correlation_id);
+        }
+    }
+}
+
+/// Runs `fut` unless `token` fires first; the future is dropped on cancellation.
+pub(crate) async fn run_cancellable<T>(
+    token: &CancellationToken,
+    completed_steps: usize,
+    fut: impl Future<Output = Result<T, Box<dyn std::error::Error + Send + Sync>>>,
+) -> Result<T, Box<dyn std::error::Error + Send + Sync>> {
+    tokio::select! {
+        biased;
+        _ = token.cancelled() => Err(GoalCancelled { completed_steps }.into()),
+        out = fut => out,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn registry_cancels_and_cleans_up() {
+        let in_flight = Arc::new(InFlight::default());
+        let token = CancellationToken::new();
+        let guard = in_flight.register("c-1", &token);
+        assert_eq!(in_flight.ids(), ["c-1"]);
+
+        let second = CancellationToken::new();
+        let second_guard = in_flight.register("c-1", &second);
+        assert!(in_flight.cancel("c-1"));
+        assert!(token.is_cancelled());
+        tokio::time::timeout(std::time::Duration::from_secs(1), second.cancelled())
+            .await
+            .expect("chained dispatch cancelled too");
+
+        drop(second_guard);
+        assert_eq!(in_flight.ids(), ["c-1"]);
+        drop(guard);
+        assert!(in_flight.ids().is_empty());
+        assert!(!in_flight.cancel("c-1"));
+    }
+
+    #[tokio::test]
+    async fn run_cancellable_drops_the_future() {
+        let token = CancellationToken::new();
+        token.cancel();
+        let out: Result<(), _> = run_cancellable(&token, 2, std::future::pending()).await;
+        let err = out.unwrap_err();
+        assert_eq!(err.downcast_ref::<GoalCancelled>().map(|c| c.completed_steps), Some(2));
+    }
+}
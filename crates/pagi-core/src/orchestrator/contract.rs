@@ -0,0 +1,82 @@
+//! Capabilities contract between blueprints and the skill registry: every skill an intent names
+//! (step skills and their fallbacks) must be registered. A renamed or unregistered skill would
+//! otherwise only surface when the intent is dispatched; the gateway checks its blueprint against
+//! its standard skill wiring at boot, and its tests check the shipped blueprints.
+
+use super::BlueprintRegistry;
+use std::fmt;
+
+/// A skill an intent needs that the registry does not provide.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct MissingCapability {
+    pub intent: String,
+    pub skill: String,
+}
+
+/// The blueprint names skills that are not registered; lists every one of them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CapabilityContractError {
+    /// Where the blueprint came from (file path, or e.g. `default blueprint`).
+    pub blueprint: String,
+    /// Sorted by intent, then skill.
+    pub missing: Vec<MissingCapability>,
+}
+
+impl fmt::Display for CapabilityContractError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {} missing capabilit{} (intent skills that are not registered):",
+            self.blueprint,
+            self.missing.len(),
+            if self.missing.len() == 1 { "y" } else { "ies" }
+        )?;
+        for m in &self.missing {
+            write!(f, "\n  - intent \"{}\" needs skill \"{}\"", m.intent, m.skill)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for CapabilityContractError {}
+
+/// Skills named by `blueprint`'s intents that are not in `skills`. Sub-plan steps are checked by
+/// [`validate_blueprint`](super::validate_blueprint), not here.
+pub fn missing_capabilities(blueprint: &BlueprintRegistry, skills: &[String]) -> Vec<MissingCapability> {
+    let mut missing = Vec::new();
+    for intent in blueprint.intent_names() {
+        let Some(plan) = blueprint.plan_for_intent(&intent) else {
+            continue;
+        };
+        for step in &plan.steps {
+            for skill in step.candidates() {
+                if !skills.iter().any(|s| s == skill) {
+                    missing.push(MissingCapability {
+                        intent: intent.clone(),
+                        skill: skill.to_string(),
+                    });
+                }
+            }
+        }
+    }
+    missing.sort();
+    missing.dedup();
+    missing
+}
+
+/// Fails with every missing capability when `blueprint` names skills outside `skills`.
+/// `source` labels the blueprint in the error (e.g. its file path).
+pub fn check_capabilities(
+    source: &str,
+    blueprint: &BlueprintRegistry,
+    skills: &[String],
+) -> Result<(), CapabilityContractError> {
+    let missing = missing_capabilities(blueprint, skills);
+    if missing.is_empty() {
+        return Ok(());
+    }
+    Err(CapabilityContractError {
+        blueprint: source.to_string(),
+        missing,
+    })
+}
@@ -0,0 +1,215 @@
+//! Running eval suites (`knowledge::evals`): every case's goal is dispatched as a caller's would be
+//! (current skills, blueprint and prompt templates, Ethos gate included) and its output checked
+//! against the case's assertions.
+//!
+//! `llm_judge` assertions render the `eval_judge` prompt template and run it through the suite's
+//! judge skill; the first number in the reply (`generated`, or the whole output) is read as a score
+//! out of 10. With a knowledge store attached the run is stored in KB-3, so a suite's scores can be
+//! compared run over run.
+
+use super::mapping::select;
+use super::Orchestrator;
+use crate::knowledge::{
+    render_prompt, AssertionResult, EvalAssertion, EvalCaseResult, EvalRun, EvalSuite, InvalidEvalSuite,
+    EVAL_DEFAULT_JUDGE_SKILL, EVAL_JUDGE_PROMPT,
+};
+use crate::shared::{Goal, TenantContext};
+use std::time::Instant;
+use uuid::Uuid;
+
+impl Orchestrator {
+    /// Runs every case of `suite` in order and scores it (see the module docs). Fails only when the
+    /// suite does not validate; a failing goal fails its case.
+    pub async fn run_eval(
+        &self,
+        ctx: &TenantContext,
+        suite: &EvalSuite,
+        label: Option<String>,
+    ) -> Result<EvalRun, InvalidEvalSuite> {
+        suite.validate()?;
+        let started_at_ms = self.now_ms();
+        let started = Instant::now();
+        let judge_skill = suite.judge_skill.as_deref().unwrap_or(EVAL_DEFAULT_JUDGE_SKILL);
+        let mut cases = Vec::with_capacity(suite.cases.len());
+        for case in &suite.cases {
+            let case_ctx = TenantContext {
+                agent_id: case.agent_id.clone().or_else(|| ctx.agent_id.clone()),
+                ..ctx.clone()
+            };
+            let case_started = Instant::now();
+            let (output, error) = match self.dispatch(&case_ctx, case.goal.clone()).await {
+                Ok(output) => (Some(output), None),
+                Err(e) => (None, Some(e.to_string())),
+            };
+            let mut assertions = Vec::with_capacity(case.assertions.len());
+            for assertion in &case.assertions {
+                assertions.push(match &output {
+                    Some(output) => self.check_assertion(&case_ctx, judge_skill, assertion, output).await,
+                    None => failed(assertion, "the goal failed".to_string()),
+                });
+            }
+            cases.push(EvalCaseResult {
+                name: case.name.clone(),
+                passed: assertions.iter().all(|a| a.passed),
+                score: mean(assertions.iter().map(|a| a.score)),
+                duration_ms: case_started.elapsed().as_millis() as u64,
+                output,
+                error,
+                assertions,
+            });
+        }
+        let run = EvalRun {
+            run_id: Uuid::new_v4().simple().to_string(),
+            suite: suite.name.clone(),
+            label,
+            started_at_ms,
+            duration_ms: started.elapsed().as_millis() as u64,
+            score: mean(cases.iter().map(|c| c.score)),
+            passed: cases.iter().filter(|c| c.passed).count(),
+            total: cases.len(),
+            cases,
+        };
+        if let Some(knowledge) = self.knowledge.as_ref() {
+            if let Err(e) = knowledge.put_eval_run(&run) {
+                tracing::warn!(target: "pagi::eval", suite = %run.suite, error = %e, "Failed to store eval run");
+            }
+        }
+        tracing::info!(
+            target: "pagi::eval",
+            suite = %run.suite,
+            run_id = %run.run_id,
+            score = run.score,
+            passed = run.passed,
+            total = run.total,
+            "Eval run finished"
+        );
+        Ok(run)
+    }
+
+    async fn check_assertion(
+        &self,
+        ctx: &TenantContext,
+        judge_skill: &str,
+        assertion: &EvalAssertion,
+        output: &serde_json::Value,
+    ) -> AssertionResult {
+        let Some(selected) = select(assertion.path(), output) else {
+            return failed(assertion, format!("nothing at {}", assertion.path()));
+        };
+        let text = match selected {
+            serde_json::Value::String(s) => s.clone(),
+            other => other.to_string(),
+        };
+        let contains = |value: &str, ignore_case: bool| {
+            if ignore_case {
+                text.to_lowercase().contains(&value.to_lowercase())
+            } else {
+                text.contains(value)
+            }
+        };
+        let passed = match assertion {
+            EvalAssertion::Contains { value, ignore_case, .. } => contains(value, *ignore_case),
+            EvalAssertion::NotContains { value, ignore_case, .. } => !contains(value, *ignore_case),
+            EvalAssertion::Regex { pattern, .. } => match regex::Regex::new(pattern) {
+                Ok(re) => re.is_match(&text),
+                Err(e) => return failed(assertion, e.to_string()),
+            },
+            EvalAssertion::Equals { value, .. } => selected == value,
+            EvalAssertion::LlmJudge { criteria, min_score, .. } => {
+                return self.judge(ctx, judge_skill, assertion, criteria, *min_score, &text).await;
+            }
+        };
+        AssertionResult {
+            assertion: assertion.clone(),
+            passed,
+            score: if passed { 1.0 } else { 0.0 },
+            detail: (!passed).then(|| format!("{} was {}", assertion.path(), truncate(&text))),
+        }
+    }
+
+    async fn judge(
+        &self,
+        ctx: &TenantContext,
+        judge_skill: &str,
+        assertion: &EvalAssertion,
+        criteria: &str,
+        min_score: f64,
+        text: &str,
+    ) -> AssertionResult {
+        let prompt = match render_prompt(
+            self.knowledge.as_deref(),
+            EVAL_JUDGE_PROMPT,
+            &[("criteria", criteria), ("output", text)],
+        ) {
+            Ok(prompt) => prompt,
+            Err(e) => return failed(assertion, e.to_string()),
+        };
+        let goal = Goal::ExecuteSkill {
+            name: judge_skill.to_string(),
+            payload: Some(serde_json::json!({ "prompt": prompt })),
+        };
+        let reply = match self.dispatch(ctx, goal).await {
+            Ok(out) => match out.get("generated").and_then(|v| v.as_str()) {
+                Some(generated) => generated.to_string(),
+                None => out.to_string(),
+            },
+            Err(e) => return failed(assertion, format!("judge {} failed: {}", judge_skill, e)),
+        };
+        match parse_judge_score(&reply) {
+            Some(score) => AssertionResult {
+                assertion: assertion.clone(),
+                passed: score >= min_score,
+                score,
+                detail: Some(truncate(&reply)),
+            },
+            None => failed(assertion, format!("judge reply has no score: {}", truncate(&reply))),
+        }
+    }
+}
+
+/// The first number in `reply` as a 0–1 score (the judge answers out of 10); None when there is
+/// none or it is above 10.
+fn parse_judge_score(reply: &str) -> Option<f64> {
+    let re = regex::Regex::new(r"\d+(?:\.\d+)?").ok()?;
+    let score: f64 = re.find(reply)?.as_str().parse().ok()?;
+    (score <= 10.0).then_some(score / 10.0)
+}
+
+fn failed(assertion: &EvalAssertion, detail: String) -> AssertionResult {
+    AssertionResult {
+        assertion: assertion.clone(),
+        passed: false,
+        score: 0.0,
+        detail: Some(detail),
+    }
+}
+
+fn mean(scores: impl Iterator<Item = f64>) -> f64 {
+    let (sum, count) = scores.fold((0.0, 0usize), |(sum, count), s| (sum + s, count + 1));
+    if count == 0 {
+        0.0
+    } else {
+        sum / count as f64
+    }
+}
+
+fn truncate(text: &str) -> String {
+    const MAX: usize = 200;
+    match text.char_indices().nth(MAX) {
+        Some((end, _)) => format!("{}…", &text[..end]),
+        None => text.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_judge_score;
+
+    #[test]
+    fn judge_scores_are_read_out_of_ten() {
+        assert_eq!(parse_judge_score("8"), Some(0.8));
+        assert_eq!(parse_judge_score("Score: 7.5/10 – polite but vague"), Some(0.75));
+        assert_eq!(parse_judge_score("no idea"), None);
+        assert_eq!(parse_judge_score("42"), None);
+    }
+}
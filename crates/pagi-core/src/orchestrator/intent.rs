@@ -0,0 +1,352 @@
+//! Chat intent routing: maps free-form chat messages to blueprint intents so "go refresh the
+//! local events" runs a plan instead of only being answered.
+//!
+//! Classification is weighted term matching: every intent is described by its name plus optional
+//! example phrases, and terms are weighted by how specific they are across intents (a term only
+//! one intent uses counts more). The confidence is how much of the best-matching description the
+//! message covers, blended with how much of the message the description explains. When that is
+//! inconclusive, the orchestrator can ask the ModelRouter instead (`llm_fallback`), weighting the
+//! model's own confidence by `llm_weight`.
+//!
+//! Above `run_threshold` the intent runs at once; above `confirm_threshold` the user is asked
+//! first and the intent runs when the next message from the same agent/tenant confirms it.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+/// How long a confirmation question stays answerable.
+pub const INTENT_CONFIRMATION_TTL: Duration = Duration::from_secs(300);
+
+/// A runner-up within this margin of the best intent makes the match ambiguous (confirm, never run).
+const AMBIGUITY_MARGIN: f32 = 0.1;
+
+const STOPWORDS: &[&str] = &[
+    "a", "an", "and", "are", "as", "at", "be", "can", "could", "do", "for", "from", "go", "hey", "i", "in", "is",
+    "it", "just", "let", "me", "my", "now", "of", "on", "or", "our", "please", "should", "some", "that", "the",
+    "this", "to", "up", "us", "we", "what", "whats", "will", "with", "would", "you", "your",
+];
+
+const CONFIRMATIONS: &[&str] = &[
+    "yes", "y", "yeah", "yep", "sure", "ok", "okay", "confirm", "go ahead", "do it", "please do", "run it",
+];
+
+/// Thresholds and switches for chat intent routing.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IntentRoutingConfig {
+    pub enabled: bool,
+    /// Confidence at which the intent runs without asking.
+    pub run_threshold: f32,
+    /// Confidence at which the user is asked to confirm; below it the message is plain chat.
+    pub confirm_threshold: f32,
+    /// Ask the ModelRouter when term matching stays below `confirm_threshold`.
+    pub llm_fallback: bool,
+    /// Multiplier on the model's self-reported confidence.
+    pub llm_weight: f32,
+}
+
+impl Default for IntentRoutingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            run_threshold: 0.75,
+            confirm_threshold: 0.45,
+            llm_fallback: false,
+            llm_weight: 0.8,
+        }
+    }
+}
+
+impl IntentRoutingConfig {
+    /// Reads `PAGI_INTENT_ROUTING` (`off` disables), `PAGI_INTENT_RUN_THRESHOLD`,
+    /// `PAGI_INTENT_CONFIRM_THRESHOLD`, `PAGI_INTENT_LLM_FALLBACK` (`1`/`true`) and
+    /// `PAGI_INTENT_LLM_WEIGHT`.
+    pub fn from_env() -> Self {
+        let mut config = Self::default();
+        let flag = |key: &str| std::env::var(key).ok().map(|v| v.trim().to_ascii_lowercase());
+        let ratio = |key: &str| {
+            std::env::var(key)
+                .ok()
+                .and_then(|v| v.trim().parse::<f32>().ok())
+                .map(|v| v.clamp(0.0, 1.0))
+        };
+        if let Some(v) = flag("PAGI_INTENT_ROUTING") {
+            config.enabled = !matches!(v.as_str(), "0" | "false" | "off" | "no");
+        }
+        if let Some(v) = ratio("PAGI_INTENT_RUN_THRESHOLD") {
+            config.run_threshold = v;
+        }
+        if let Some(v) = ratio("PAGI_INTENT_CONFIRM_THRESHOLD") {
+            config.confirm_threshold = v;
+        }
+        if let Some(v) = flag("PAGI_INTENT_LLM_FALLBACK") {
+            config.llm_fallback = matches!(v.as_str(), "1" | "true" | "on" | "yes");
+        }
+        if let Some(v) = ratio("PAGI_INTENT_LLM_WEIGHT") {
+            config.llm_weight = v;
+        }
+        config
+    }
+}
+
+/// What to do with a classified message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IntentAction {
+    Run,
+    Confirm,
+}
+
+/// How a match was found.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IntentSource {
+    Terms,
+    Llm,
+    /// The user confirmed an earlier `Confirm` match.
+    Confirmation,
+}
+
+/// A chat message mapped to a blueprint intent.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct IntentMatch {
+    pub intent: String,
+    pub confidence: f32,
+    pub action: IntentAction,
+    pub source: IntentSource,
+    /// The message that was classified (for a confirmation, the original request).
+    pub message: String,
+}
+
+impl IntentMatch {
+    /// Question shown to the user for a `Confirm` match.
+    pub fn confirmation_prompt(&self) -> String {
+        format!(
+            "It sounds like you want me to run \"{}\" ({:.0}% sure). Reply \"yes\" to run it, or keep chatting.",
+            self.intent,
+            self.confidence * 100.0
+        )
+    }
+}
+
+struct Pending {
+    intent: IntentMatch,
+    asked_at: Instant,
+}
+
+/// Classifies chat messages against blueprint intents and tracks open confirmation questions.
+pub struct IntentRouter {
+    config: IntentRoutingConfig,
+    /// Example phrases by normalized intent name.
+    examples: HashMap<String, Vec<String>>,
+    /// Open confirmation questions by `{agent_id}/{tenant_id}`.
+    pending: Mutex<HashMap<String, Pending>>,
+}
+
+impl IntentRouter {
+    pub fn new(config: IntentRoutingConfig) -> Self {
+        Self {
+            config,
+            examples: HashMap::new(),
+            pending: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Adds example phrases per intent (names are normalized like blueprint intents).
+    pub fn with_examples(mut self, examples: HashMap<String, Vec<String>>) -> Self {
+        for (intent, phrases) in examples {
+            self.examples
+                .entry(super::blueprint::normalize_intent(&intent))
+                .or_default()
+                .extend(phrases);
+        }
+        self
+    }
+
+    /// Reads `{ "intent name": ["example phrase", ...] }` from a JSON file.
+    pub fn load_examples<P: AsRef<Path>>(
+        path: P,
+    ) -> Result<HashMap<String, Vec<String>>, Box<dyn std::error::Error + Send + Sync>> {
+        let text = std::fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&text)?)
+    }
+
+    pub fn config(&self) -> &IntentRoutingConfig {
+        &self.config
+    }
+
+    /// Weighted term match of `message` against `intents`. None when nothing reaches
+    /// `confirm_threshold`; a close runner-up turns `Run` into `Confirm`.
+    pub fn classify(&self, message: &str, intents: &[String]) -> Option<IntentMatch> {
+        let words = terms(message);
+        if words.is_empty() || intents.is_empty() {
+            return None;
+        }
+        let descriptions: Vec<(&String, Vec<HashSet<String>>)> = intents
+            .iter()
+            .map(|intent| {
+                let mut docs = vec![terms(intent)];
+                if let Some(examples) = self.examples.get(&super::blueprint::normalize_intent(intent)) {
+                    docs.extend(examples.iter().map(|e| terms(e)));
+                }
+                docs.retain(|d| !d.is_empty());
+                (intent, docs)
+            })
+            .collect();
+
+        // Specificity: 1 + ln(intents / intents using the term).
+        let mut usage: HashMap<&str, usize> = HashMap::new();
+        for (_, docs) in &descriptions {
+            let vocabulary: HashSet<&str> = docs.iter().flatten().map(String::as_str).collect();
+            for term in vocabulary {
+                *usage.entry(term).or_default() += 1;
+            }
+        }
+        let total = intents.len() as f32;
+        let weight = |term: &str| match usage.get(term) {
+            Some(&n) => 1.0 + (total / n as f32).ln(),
+            None => 1.0,
+        };
+        let message_weight: f32 = words.iter().map(|t| weight(t)).sum();
+
+        let mut scored: Vec<(f32, &String)> = descriptions
+            .iter()
+            .map(|(intent, docs)| {
+                let best = docs
+                    .iter()
+                    .map(|doc| {
+                        let doc_weight: f32 = doc.iter().map(|t| weight(t)).sum();
+                        let matched: f32 = doc.intersection(&words).map(|t| weight(t)).sum();
+                        let coverage = matched / doc_weight;
+                        let precision = matched / message_weight;
+                        0.8 * coverage + 0.2 * precision
+                    })
+                    .fold(0.0_f32, f32::max);
+                (best, *intent)
+            })
+            .collect();
+        scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+        let (confidence, intent) = scored[0];
+        let runner_up = scored.get(1).map_or(0.0, |s| s.0);
+        let action = self.action_for(confidence)?;
+        let action = match action {
+            IntentAction::Run if confidence - runner_up < AMBIGUITY_MARGIN => IntentAction::Confirm,
+            action => action,
+        };
+        Some(IntentMatch {
+            intent: intent.clone(),
+            confidence,
+            action,
+            source: IntentSource::Terms,
+            message: message.to_string(),
+        })
+    }
+
+    /// Run / confirm / nothing for a confidence.
+    pub fn action_for(&self, confidence: f32) -> Option<IntentAction> {
+        if confidence >= self.config.run_threshold {
+            Some(IntentAction::Run)
+        } else if confidence >= self.config.confirm_threshold {
+            Some(IntentAction::Confirm)
+        } else {
+            None
+        }
+    }
+
+    /// Remembers a `Confirm` match until the next message of `key` (or the TTL).
+    pub fn ask(&self, key: &str, intent: IntentMatch) {
+        self.pending.lock().unwrap_or_else(PoisonError::into_inner).insert(
+            key.to_string(),
+            Pending {
+                intent,
+                asked_at: Instant::now(),
+            },
+        );
+    }
+
+    /// Consumes the open question of `key`: the intent to run when `reply` confirms it. Any other
+    /// reply drops the question.
+    pub fn answer(&self, key: &str, reply: &str) -> Option<IntentMatch> {
+        let pending = self.pending.lock().unwrap_or_else(PoisonError::into_inner).remove(key)?;
+        if pending.asked_at.elapsed() > INTENT_CONFIRMATION_TTL || !is_confirmation(reply) {
+            return None;
+        }
+        Some(IntentMatch {
+            action: IntentAction::Run,
+            source: IntentSource::Confirmation,
+            ..pending.intent
+        })
+    }
+}
+
+/// "yes", "ok, go ahead", "Do it!" ...
+pub fn is_confirmation(reply: &str) -> bool {
+    let reply: String = reply
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_alphanumeric() || c == ' ' { c } else { ' ' })
+        .collect();
+    let reply = reply.split_whitespace().collect::<Vec<_>>().join(" ");
+    !reply.is_empty()
+        && reply.split_whitespace().count() <= 4
+        && CONFIRMATIONS
+            .iter()
+            .any(|c| reply == *c || reply.starts_with(&format!("{} ", c)) || reply.ends_with(&format!(" {}", c)))
+}
+
+/// Lowercased content words with plural / -ing / -ed endings stripped.
+fn terms(text: &str) -> HashSet<String> {
+    text.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty() && !STOPWORDS.contains(w))
+        .map(stem)
+        .collect()
+}
+
+fn stem(word: &str) -> String {
+    for suffix in ["ing", "ed", "es", "s"] {
+        if word.len() > suffix.len() + 3 {
+            if let Some(stem) = word.strip_suffix(suffix) {
+                return stem.to_string();
+            }
+        }
+    }
+    word.to_string()
+}
+
+/// Prompt asking the ModelRouter to pick one of `intents` for `message`.
+pub fn build_classification_prompt(message: &str, intents: &[String]) -> String {
+    let mut prompt = String::new();
+    prompt.push_str("You route chat messages to automation intents.\n");
+    prompt.push_str("Reply with ONLY a JSON object: {\"intent\": \"<one of the intents, or none>\", \"confidence\": <0.0-1.0>}.\n");
+    prompt.push_str("Pick an intent only when the user asks for that task to be done.\n\nIntents:\n");
+    for intent in intents {
+        prompt.push_str(&format!("- {}\n", intent));
+    }
+    prompt.push_str(&format!("\nMessage: {}\n", message));
+    prompt
+}
+
+#[derive(Deserialize)]
+struct Classification {
+    intent: Option<String>,
+    #[serde(default)]
+    confidence: f32,
+}
+
+/// The `(intent, confidence)` from the model's reply: the first JSON object in the text, with the
+/// intent mapped to the registered spelling. None for "none", unknown intents or unparsable text.
+pub fn parse_classification_response(text: &str, intents: &[String]) -> Option<(String, f32)> {
+    let start = text.find('{')?;
+    let reply: Classification = serde_json::Deserializer::from_str(&text[start..])
+        .into_iter::<Classification>()
+        .next()?
+        .ok()?;
+    let name = super::blueprint::normalize_intent(reply.intent.as_deref()?);
+    let intent = intents
+        .iter()
+        .find(|i| super::blueprint::normalize_intent(i) == name)?;
+    Some((intent.clone(), reply.confidence.clamp(0.0, 1.0)))
+}
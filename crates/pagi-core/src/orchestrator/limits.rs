@@ -0,0 +1,287 @@
+//! Per-skill execution limits, enforced around every skill call the orchestrator makes.
+//!
+//! A skill's limits come from `[limits.skills.<name>]`, then from the skill itself
+//! ([`AgentSkill::limits`], e.g. the fs_tools sandbox roots), then from the `[limits]` defaults:
+//! - `timeout_ms`: the call is dropped when it runs longer (a plan step's own `timeout_ms` can
+//!   only shorten it)
+//! - `max_payload_bytes` / `max_result_bytes`: serialized size of the payload and the result
+//! - `sandbox_root`: the payload fields that carry paths (`path`, a string, and `roots`, a list of
+//!   strings) must resolve inside it through [`resolve_in_sandbox`]: relative paths are taken from
+//!   the root, `..` segments are rejected, and symlinks, dangling ones included, cannot lead out.
+//!   Skills that take paths under other field names are not covered and must check them
+//!   themselves
+//!
+//! A violation fails the call with a [`SkillError`], counts as a failure in the skill's stats and
+//! is logged to KB_CHRONOS when a knowledge store is attached.
+
+use super::{AgentSkill, Orchestrator};
+use crate::sandbox::resolve_in_sandbox;
+use crate::shared::{LimitsConfig, SkillLimits, TenantContext};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Payload fields checked against `sandbox_root`, each a path or a list of paths.
+const SANDBOXED_FIELDS: [&str; 2] = ["path", "roots"];
+
+/// A skill call stopped by its execution limits.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SkillError {
+    Timeout { skill: String, timeout_ms: u64 },
+    PayloadTooLarge { skill: String, bytes: usize, limit: usize },
+    ResultTooLarge { skill: String, bytes: usize, limit: usize },
+    OutsideSandbox { skill: String, path: String, root: PathBuf },
+}
+
+impl SkillError {
+    pub fn skill(&self) -> &str {
+        match self {
+            Self::Timeout { skill, .. }
+            | Self::PayloadTooLarge { skill, .. }
+            | Self::ResultTooLarge { skill, .. }
+            | Self::OutsideSandbox { skill, .. } => skill,
+        }
+    }
+
+    /// Stable name of the violated limit (`timeout`, `payload_too_large`, `result_too_large`,
+    /// `outside_sandbox`).
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Timeout { .. } => "timeout",
+            Self::PayloadTooLarge { .. } => "payload_too_large",
+            Self::ResultTooLarge { .. } => "result_too_large",
+            Self::OutsideSandbox { .. } => "outside_sandbox",
+        }
+    }
+}
+
+impl fmt::Display for SkillError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Timeout { skill, timeout_ms } => write!(f, "skill {} timed out after {} ms", skill, timeout_ms),
+            Self::PayloadTooLarge { skill, bytes, limit } => {
+                write!(f, "payload for skill {} is {} bytes (limit {})", skill, bytes, limit)
+            }
+            Self::ResultTooLarge { skill, bytes, limit } => {
+                write!(f, "result of skill {} is {} bytes (limit {})", skill, bytes, limit)
+            }
+            Self::OutsideSandbox { skill, path, root } => {
+                write!(f, "skill {} may not access {} (outside {})", skill, path, root.display())
+            }
+        }
+    }
+}
+
+impl std::error::Error for SkillError {}
+
+/// `limits` for `skill`, falling back to `declared` and then to the `[limits]` defaults. Zero
+/// defaults mean no limit.
+fn resolve(limits: &LimitsConfig, skill: &str, declared: SkillLimits) -> SkillLimits {
+    let configured = limits.skills.get(skill).cloned().unwrap_or_default();
+    let nonzero_u64 = |n: u64| (n > 0).then_some(n);
+    let nonzero = |n: usize| (n > 0).then_some(n);
+    SkillLimits {
+        timeout_ms: configured.timeout_ms.or(declared.timeout_ms).or(nonzero_u64(limits.skill_timeout_ms)),
+        max_payload_bytes: configured
+            .max_payload_bytes
+            .or(declared.max_payload_bytes)
+            .or(nonzero(limits.skill_max_payload_bytes)),
+        max_result_bytes: configured
+            .max_result_bytes
+            .or(declared.max_result_bytes)
+            .or(nonzero(limits.skill_max_result_bytes)),
+        sandbox_root: configured.sandbox_root.or(declared.sandbox_root),
+    }
+}
+
+fn serialized_len(value: &serde_json::Value) -> usize {
+    serde_json::to_vec(value).map(|v| v.len()).unwrap_or(usize::MAX)
+}
+
+/// Whether `path` (relative paths taken from `root`) stays inside `root`.
+fn within_sandbox(root: &Path, path: &str) -> bool {
+    resolve_in_sandbox(root, Path::new(path)).is_ok()
+}
+
+/// The paths in the payload's [`SANDBOXED_FIELDS`].
+fn payload_paths(payload: &serde_json::Value) -> impl Iterator<Item = &str> {
+    SANDBOXED_FIELDS.iter().filter_map(|field| payload.get(field)).flat_map(|value| match value {
+        serde_json::Value::Array(items) => items.iter().filter_map(|v| v.as_str()).collect(),
+        value => value.as_str().into_iter().collect::<Vec<_>>(),
+    })
+}
+
+fn check_payload(skill: &str, limits: &SkillLimits, payload: Option<&serde_json::Value>) -> Result<(), SkillError> {
+    let Some(payload) = payload else { return Ok(()) };
+    if let Some(limit) = limits.max_payload_bytes {
+        let bytes = serialized_len(payload);
+        if bytes > limit {
+            return Err(SkillError::PayloadTooLarge { skill: skill.to_string(), bytes, limit });
+        }
+    }
+    let Some(root) = &limits.sandbox_root else { return Ok(()) };
+    for path in payload_paths(payload) {
+        if !within_sandbox(root, path) {
+            return Err(SkillError::OutsideSandbox {
+                skill: skill.to_string(),
+                path: path.to_string(),
+                root: root.clone(),
+            });
+        }
+    }
+    Ok(())
+}
+
+impl Orchestrator {
+    /// Replaces the skill execution limits (default: [`LimitsConfig::default`]).
+    pub fn with_skill_limits(mut self, limits: LimitsConfig) -> Self {
+        self.skill_limits = limits;
+        self
+    }
+
+    /// The limits `skill` runs under (see the module docs for the order they are taken in).
+    pub fn skill_limits(&self, skill: &dyn AgentSkill) -> SkillLimits {
+        resolve(&self.skill_limits, skill.name(), skill.limits())
+    }
+
+    /// Runs `skill` within its limits; `step_timeout_ms` (a plan step's policy) can only shorten
+    /// the skill's own timeout. Violations are logged to KB_CHRONOS.
+    pub(super) async fn execute_limited(
+        &self,
+        skill: &dyn AgentSkill,
+        ctx: &TenantContext,
+        payload: Option<serde_json::Value>,
+        step_timeout_ms: Option<u64>,
+    ) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>> {
+        let limits = self.skill_limits(skill);
+        let outcome = self.run_within(skill, ctx, payload, &limits, step_timeout_ms).await;
+        if let Some(violation) = outcome.as_ref().err().and_then(|e| e.downcast_ref::<SkillError>()) {
+            self.log_violation(ctx, violation);
+        }
+        outcome
+    }
+
+    /// The payload size and sandbox checks alone, for streamed calls (whose chunks go straight to
+    /// the caller, so their timeout and result size are not enforced here).
+    pub(super) fn check_stream_payload(
+        &self,
+        ctx: &TenantContext,
+        skill: &dyn AgentSkill,
+        payload: Option<&serde_json::Value>,
+    ) -> Result<(), SkillError> {
+        check_payload(skill.name(), &self.skill_limits(skill), payload).inspect_err(|violation| {
+            self.log_violation(ctx, violation);
+        })
+    }
+
+    async fn run_within(
+        &self,
+        skill: &dyn AgentSkill,
+        ctx: &TenantContext,
+        payload: Option<serde_json::Value>,
+        limits: &SkillLimits,
+        step_timeout_ms: Option<u64>,
+    ) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>> {
+        check_payload(skill.name(), limits, payload.as_ref())?;
+        let timeout_ms = match (limits.timeout_ms, step_timeout_ms) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+        let result = match timeout_ms {
+            Some(ms) => tokio::time::timeout(std::time::Duration::from_millis(ms), skill.execute(ctx, payload))
+                .await
+                .map_err(|_| SkillError::Timeout {
+                    skill: skill.name().to_string(),
+                    timeout_ms: ms,
+                })??,
+            None => skill.execute(ctx, payload).await?,
+        };
+        if let Some(limit) = limits.max_result_bytes {
+            let bytes = serialized_len(&result);
+            if bytes > limit {
+                return Err(SkillError::ResultTooLarge {
+                    skill: skill.name().to_string(),
+                    bytes,
+                    limit,
+                }
+                .into());
+            }
+        }
+        Ok(result)
+    }
+
+    fn log_violation(&self, ctx: &TenantContext, violation: &SkillError) {
+        tracing::warn!(
+            target: "pagi::orchestrator",
+            skill = violation.skill(),
+            violation = violation.kind(),
+            error = %violation,
+            "Skill call stopped by its execution limits"
+        );
+        if let Some(knowledge) = &self.knowledge {
+            let event = knowledge
+                .event("Chronos", violation.to_string())
+                .with_skill(violation.skill())
+                .with_outcome(violation.kind());
+            if let Err(e) = knowledge.append_chronos_event(ctx.resolved_agent_id(), &event) {
+                tracing::warn!(target: "pagi::orchestrator", error = %e, "Failed to log skill limit violation to Chronos");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn configured_limits_win_over_declared_and_defaults() {
+        let mut config = LimitsConfig {
+            skill_max_result_bytes: 0,
+            ..LimitsConfig::default()
+        };
+        config.skills.insert("Slow".into(), SkillLimits { timeout_ms: Some(50), ..Default::default() });
+        let declared = SkillLimits {
+            timeout_ms: Some(10_000),
+            max_payload_bytes: Some(64),
+            ..Default::default()
+        };
+        let limits = resolve(&config, "Slow", declared.clone());
+        assert_eq!(limits.timeout_ms, Some(50));
+        assert_eq!(limits.max_payload_bytes, Some(64));
+        assert_eq!(limits.max_result_bytes, None);
+        assert_eq!(resolve(&config, "Other", declared).timeout_ms, Some(10_000));
+    }
+
+    #[test]
+    fn sandbox_paths_are_canonicalized() {
+        let root = std::env::temp_dir().join(format!("pagi_limits_{}", uuid::Uuid::new_v4().simple()));
+        std::fs::create_dir_all(root.join("inner")).unwrap();
+        assert!(within_sandbox(&root, "inner/report.md"));
+        assert!(within_sandbox(&root, "not/yet/created.md"));
+        assert!(within_sandbox(&root, &root.join("inner").to_string_lossy()));
+        assert!(!within_sandbox(&root, "inner/../../escape.md"));
+        assert!(!within_sandbox(&root, "/etc/passwd"));
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(std::env::temp_dir(), root.join("link")).unwrap();
+            assert!(!within_sandbox(&root, "link/escape.md"));
+            std::os::unix::fs::symlink(std::env::temp_dir().join("pagi_limits_missing"), root.join("dangling")).unwrap();
+            assert!(!within_sandbox(&root, "dangling"));
+            assert!(!within_sandbox(&root, "dangling/escape.md"));
+        }
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn every_path_field_is_checked() {
+        let root = std::env::temp_dir().join(format!("pagi_limits_{}", uuid::Uuid::new_v4().simple()));
+        std::fs::create_dir_all(&root).unwrap();
+        let limits = SkillLimits { sandbox_root: Some(root.clone()), ..Default::default() };
+        let check = |payload: serde_json::Value| check_payload("Fs", &limits, Some(&payload));
+        assert!(check(serde_json::json!({ "path": "a", "roots": ["b", "c/d"] })).is_ok());
+        let err = check(serde_json::json!({ "path": "a", "roots": ["b", "/etc"] })).unwrap_err();
+        assert_eq!(err, SkillError::OutsideSandbox { skill: "Fs".into(), path: "/etc".into(), root: root.clone() });
+        assert!(check(serde_json::json!({ "roots": "../up" })).is_err());
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
@@ -0,0 +1,139 @@
+//! Declarative payload mapping between chained plan steps.
+//!
+//! A step's `map` builds its input object from the previous step's output (or the intent
+//! context for the first step): string values starting with `$` are JSONPath-style selectors
+//! (`$`, `$.draft`, `$.items[0].title`), every other value is copied as a literal. A selector
+//! that matches nothing yields `null`.
+//!
+//! Steps without a `map` fall back to the built-in mappings for the original skill pairs
+//! (e.g. `DraftResponse → ModelRouter` maps `prompt: $.draft`), then to passing the previous
+//! output through unchanged.
+
+use std::collections::BTreeMap;
+
+/// Output field → selector or literal.
+pub type PayloadMap = BTreeMap<String, serde_json::Value>;
+
+/// Resolves a `$`-rooted selector against `value`. Returns None for malformed selectors and
+/// for paths that do not exist.
+pub fn select<'a>(selector: &str, value: &'a serde_json::Value) -> Option<&'a serde_json::Value> {
+    let mut rest = selector.trim().strip_prefix('$')?;
+    let mut current = value;
+    while !rest.is_empty() {
+        if let Some(after_dot) = rest.strip_prefix('.') {
+            let end = after_dot.find(['.', '[']).unwrap_or(after_dot.len());
+            let field = &after_dot[..end];
+            if field.is_empty() {
+                return None;
+            }
+            current = current.get(field)?;
+            rest = &after_dot[end..];
+        } else if let Some(after_bracket) = rest.strip_prefix('[') {
+            let end = after_bracket.find(']')?;
+            let token = after_bracket[..end].trim();
+            current = match token.parse::<usize>() {
+                Ok(index) => current.get(index)?,
+                Err(_) => current.get(token.trim_matches(|c| c == '\'' || c == '"'))?,
+            };
+            rest = &after_bracket[end + 1..];
+        } else {
+            return None;
+        }
+    }
+    Some(current)
+}
+
+/// Builds a step input object from `map`, resolving selectors against `input`.
+pub fn apply_map(map: &PayloadMap, input: &serde_json::Value) -> serde_json::Value {
+    let object = map
+        .iter()
+        .map(|(field, spec)| {
+            let value = match spec.as_str() {
+                Some(selector) if selector.starts_with('$') => {
+                    select(selector, input).cloned().unwrap_or(serde_json::Value::Null)
+                }
+                _ => spec.clone(),
+            };
+            (field.clone(), value)
+        })
+        .collect();
+    serde_json::Value::Object(object)
+}
+
+/// Symbolic version of [`apply_map`] for previews, where the input is not known yet: selectors
+/// become `"<{source}{path}>"` placeholders (e.g. `<DraftResponse.draft>`), literals are kept.
+pub fn describe_map(map: &PayloadMap, source: &str) -> serde_json::Value {
+    let object = map
+        .iter()
+        .map(|(field, spec)| {
+            let value = match spec.as_str() {
+                Some(selector) if selector.starts_with('$') => {
+                    serde_json::Value::String(placeholder(source, selector))
+                }
+                _ => spec.clone(),
+            };
+            (field.clone(), value)
+        })
+        .collect();
+    serde_json::Value::Object(object)
+}
+
+/// Placeholder for the value a selector will read from `source`'s output.
+pub fn placeholder(source: &str, selector: &str) -> String {
+    format!("<{}{}>", source, selector.trim().trim_start_matches('$'))
+}
+
+/// Built-in mappings for the skill pairs that were chained before blueprints could declare a
+/// `map`. Kept so existing blueprints behave the same.
+pub fn builtin_map(previous_skill: &str, next_skill: &str) -> Option<PayloadMap> {
+    let (field, selector) = match (previous_skill, next_skill) {
+        ("DraftResponse", "SalesCloser") => ("draft", "$.draft"),
+        ("SalesCloser", "ModelRouter") | ("DraftResponse", "ModelRouter") => ("prompt", "$.draft"),
+        ("CommunityScraper", "ModelRouter") => ("prompt", "$.event"),
+        _ => return None,
+    };
+    Some(PayloadMap::from([(field.to_string(), serde_json::json!(selector))]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn select_handles_fields_indices_and_root() {
+        let v = serde_json::json!({ "draft": "hi", "items": [{ "title": "a" }, { "title": "b" }] });
+        assert_eq!(select("$", &v), Some(&v));
+        assert_eq!(select("$.draft", &v).unwrap(), "hi");
+        assert_eq!(select("$.items[1].title", &v).unwrap(), "b");
+        assert_eq!(select("$['draft']", &v).unwrap(), "hi");
+        assert!(select("$.missing", &v).is_none());
+        assert!(select("draft", &v).is_none());
+    }
+
+    #[test]
+    fn apply_map_mixes_selectors_and_literals() {
+        let map: PayloadMap = serde_json::from_value(serde_json::json!({
+            "prompt": "$.draft",
+            "temperature": 0.2,
+            "missing": "$.nope"
+        }))
+        .unwrap();
+        let out = apply_map(&map, &serde_json::json!({ "draft": "hello" }));
+        assert_eq!(out, serde_json::json!({ "prompt": "hello", "temperature": 0.2, "missing": null }));
+    }
+
+    #[test]
+    fn describe_map_uses_placeholders() {
+        let map = builtin_map("DraftResponse", "ModelRouter").unwrap();
+        assert_eq!(describe_map(&map, "DraftResponse"), serde_json::json!({ "prompt": "<DraftResponse.draft>" }));
+        assert_eq!(placeholder("Echo", "$"), "<Echo>");
+    }
+
+    #[test]
+    fn builtin_pairs_are_preserved() {
+        let map = builtin_map("CommunityScraper", "ModelRouter").unwrap();
+        let out = apply_map(&map, &serde_json::json!({ "event": "scraped" }));
+        assert_eq!(out["prompt"], "scraped");
+        assert!(builtin_map("ModelRouter", "SalesCloser").is_none());
+    }
+}
@@ -0,0 +1,256 @@
+//! `Goal::MemoryOp`: reads, writes and deletes addressed by path, within the caller's tenant.
+//!
+//! Paths:
+//! - `vault/{tree}/{key}`: the vault path `{tree}/{tenant}/{key}` (e.g. `vault/lead_history/{id}`)
+//! - `knowledge/{slot}/{key}`: key `key` of KB slot 1–8
+//! - `leads`: list lead records; `value` is an optional [`LeadFilter`] plus `cursor`
+//! - `leads/{id}`: a lead record; a write stores `value`'s fields
+//! - `leads/{id}/status`: move the lead to status `value` (e.g. `"qualified"`) when the pipeline
+//!   allows it; otherwise the result is `invalid_transition`
+//!
+//! No `value` reads; a `value` writes (JSON text, or a string's bytes as is); `delete` removes.
+//! Writes and deletes pass the Ethos gate as skill `MemoryOp` with payload `{ path, value, delete }`.
+//! Every operation is logged to KB_CHRONOS when a knowledge store is attached (lead transitions
+//! as "Lead {id} moved from {from} to {to}").
+
+use super::{EthosScope, Orchestrator};
+use crate::memory::{LeadFilter, LeadRecord, LeadStatus, LeadTransition, MemoryManager};
+use crate::shared::TenantContext;
+
+type MemoryOpResult = Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>>;
+
+/// Skill name Ethos rules and Chronos events see for memory operations.
+const MEMORY_OP_SKILL: &str = "MemoryOp";
+
+/// What a `MemoryOp` path addresses.
+#[derive(Debug, PartialEq)]
+enum MemoryTarget<'a> {
+    Vault { tree: &'a str, key: &'a str },
+    Knowledge { slot_id: u8, key: &'a str },
+    Leads,
+    Lead { id: &'a str },
+    LeadStatus { id: &'a str },
+}
+
+impl<'a> MemoryTarget<'a> {
+    fn parse(path: &'a str) -> Result<Self, String> {
+        let path = path.trim_matches('/');
+        let unsupported = || {
+            format!(
+                "MemoryOp path {:?} is not supported (vault/{{tree}}/{{key}}, knowledge/{{slot}}/{{key}}, leads, leads/{{id}}, leads/{{id}}/status)",
+                path
+            )
+        };
+        let (store, rest) = path.split_once('/').unwrap_or((path, ""));
+        let (first, key) = rest.split_once('/').unwrap_or((rest, ""));
+        match (store, first, key) {
+            ("leads", "", _) => Ok(Self::Leads),
+            ("leads", id, "") => Ok(Self::Lead { id }),
+            ("leads", id, "status") => Ok(Self::LeadStatus { id }),
+            ("vault", tree, key) if !tree.is_empty() && !key.is_empty() => Ok(Self::Vault { tree, key }),
+            ("knowledge", slot, key) if !key.is_empty() => match slot.parse::<u8>() {
+                Ok(slot_id @ 1..=8) => Ok(Self::Knowledge { slot_id, key }),
+                _ => Err(format!("MemoryOp knowledge slot {:?} must be 1–8", slot)),
+            },
+            _ => Err(unsupported()),
+        }
+    }
+}
+
+/// Stored bytes as JSON (a string when they are not JSON text).
+fn decode(bytes: Vec<u8>) -> serde_json::Value {
+    serde_json::from_slice(&bytes).unwrap_or_else(|_| String::from_utf8_lossy(&bytes).into_owned().into())
+}
+
+fn encode(value: &serde_json::Value) -> Vec<u8> {
+    match value {
+        serde_json::Value::String(s) => s.clone().into_bytes(),
+        value => value.to_string().into_bytes(),
+    }
+}
+
+impl Orchestrator {
+    /// Runs a `Goal::MemoryOp` and logs it to Chronos.
+    pub(super) fn run_memory_op(
+        &self,
+        ctx: &TenantContext,
+        path: &str,
+        value: Option<serde_json::Value>,
+        delete: bool,
+    ) -> MemoryOpResult {
+        let operation = match (&value, delete) {
+            (Some(_), true) => return Err("MemoryOp takes a value or delete, not both".into()),
+            // The `leads` value is a filter.
+            (Some(_), false) if path.trim_matches('/') != "leads" => "write",
+            (None, true) => "delete",
+            _ => "read",
+        };
+        let result = self.memory_op(ctx, path, value, delete);
+        if let Some(knowledge) = self.knowledge.as_ref() {
+            let outcome = match &result {
+                Ok(out) => out.get("status").and_then(|s| s.as_str()).unwrap_or("ok").to_string(),
+                Err(e) => format!("failed: {}", e),
+            };
+            let reflection = match &result {
+                Ok(out) if out["status"] == "updated" && out.get("from").is_some() => format!(
+                    "Lead {} moved from {} to {}",
+                    out["lead"]["id"].as_str().unwrap_or_default(),
+                    out["from"].as_str().unwrap_or_default(),
+                    out["to"].as_str().unwrap_or_default()
+                ),
+                _ => format!("Memory {} on path: {}", operation, path),
+            };
+            let event = knowledge.event("Chronos", reflection)
+                .with_skill(MEMORY_OP_SKILL)
+                .with_outcome(outcome);
+            if let Err(e) = knowledge.append_chronos_event(ctx.resolved_agent_id(), &event) {
+                tracing::warn!(target: "pagi::orchestrator", error = %e, "Failed to log memory operation to Chronos");
+            }
+        }
+        result
+    }
+
+    fn memory_op(&self, ctx: &TenantContext, path: &str, value: Option<serde_json::Value>, delete: bool) -> MemoryOpResult {
+        let target = MemoryTarget::parse(path)?;
+        let writes = value.is_some() || delete;
+        if writes && target != MemoryTarget::Leads {
+            let payload = serde_json::json!({ "path": path, "value": value, "delete": delete });
+            // Coerced, not `?`-converted, so callers can downcast to EthosBlocked.
+            self.ethos_gate(ctx, MEMORY_OP_SKILL, Some(&payload), EthosScope::Direct)
+                .map_err(|blocked| blocked as Box<dyn std::error::Error + Send + Sync>)?;
+        }
+        match target {
+            MemoryTarget::Knowledge { slot_id, key } => {
+                if !self.pagi_kb_active(slot_id) {
+                    return Ok(serde_json::json!({
+                        "status": "kb_disabled",
+                        "message": format!("KB-{} is disabled by the control panel.", slot_id),
+                        "path": path,
+                    }));
+                }
+                let knowledge = self.knowledge.as_ref().ok_or("MemoryOp on knowledge/ requires a knowledge store")?;
+                let kb = knowledge.tenant(&ctx.tenant_id)?;
+                Ok(match (value, delete) {
+                    (Some(value), _) => {
+                        kb.insert(slot_id, key, &encode(&value))?;
+                        serde_json::json!({ "status": "saved", "path": path })
+                    }
+                    (None, true) => serde_json::json!({ "status": removed(kb.remove(slot_id, key)?.is_some()), "path": path }),
+                    (None, false) => found(path, kb.get(slot_id, key)?.map(decode)),
+                })
+            }
+            target => {
+                let memory = self.memory.as_ref().ok_or("MemoryOp requires a vault (Orchestrator::with_memory)")?;
+                vault_op(memory, ctx, path, target, value, delete)
+            }
+        }
+    }
+}
+
+fn removed(existed: bool) -> &'static str {
+    if existed {
+        "deleted"
+    } else {
+        "not_found"
+    }
+}
+
+fn found(path: &str, value: Option<serde_json::Value>) -> serde_json::Value {
+    match value {
+        Some(value) => serde_json::json!({ "status": "ok", "path": path, "value": value }),
+        None => serde_json::json!({ "status": "not_found", "path": path }),
+    }
+}
+
+fn vault_op(
+    memory: &MemoryManager,
+    ctx: &TenantContext,
+    path: &str,
+    target: MemoryTarget<'_>,
+    value: Option<serde_json::Value>,
+    delete: bool,
+) -> MemoryOpResult {
+    match (target, value) {
+        (MemoryTarget::Vault { tree, key }, value) => {
+            let vault_path = format!("{}/{}/{}", tree, ctx.tenant_id, key);
+            Ok(match value {
+                Some(value) => {
+                    memory.save_path(ctx, &vault_path, &encode(&value))?;
+                    serde_json::json!({ "status": "saved", "path": path })
+                }
+                None if delete => serde_json::json!({ "status": removed(memory.remove_path(ctx, &vault_path)?), "path": path }),
+                None => found(path, memory.get_path(ctx, &vault_path)?.map(decode)),
+            })
+        }
+        (MemoryTarget::Leads, value) => {
+            if delete {
+                return Err("MemoryOp cannot delete leads; delete leads/{id}".into());
+            }
+            let value = value.unwrap_or(serde_json::Value::Null);
+            let cursor = value.get("cursor").and_then(|c| c.as_str()).map(str::to_string);
+            let filter: LeadFilter = match value {
+                serde_json::Value::Null => LeadFilter::default(),
+                value => serde_json::from_value(value).map_err(|e| format!("MemoryOp leads filter: {}", e))?,
+            };
+            let page = memory.list_leads(ctx, &filter, cursor.as_deref())?;
+            Ok(serde_json::json!({
+                "status": "ok",
+                "path": path,
+                "leads": page.leads,
+                "next_cursor": page.next_cursor,
+            }))
+        }
+        (MemoryTarget::Lead { id }, None) if delete => {
+            Ok(serde_json::json!({ "status": removed(memory.delete_lead(ctx, id)?), "path": path }))
+        }
+        (MemoryTarget::Lead { id }, None) => Ok(match memory.get_lead(ctx, id)? {
+            Some(lead) => serde_json::json!({ "status": "ok", "path": path, "lead": lead }),
+            None => serde_json::json!({ "status": "not_found", "path": path }),
+        }),
+        (MemoryTarget::Lead { id }, Some(serde_json::Value::Object(fields))) => {
+            let mut lead = LeadRecord::new(fields);
+            if let Some(stored) = memory.get_lead(ctx, id)? {
+                lead.status = stored.status;
+                lead.created_at_ms = stored.created_at_ms;
+            }
+            lead.id = id.to_string();
+            let lead = memory.put_lead(ctx, lead)?;
+            Ok(serde_json::json!({ "status": "saved", "path": path, "lead": lead }))
+        }
+        (MemoryTarget::Lead { .. }, Some(_)) => Err("MemoryOp lead value must be an object of lead fields".into()),
+        (MemoryTarget::LeadStatus { id }, Some(status)) => {
+            let status: LeadStatus =
+                serde_json::from_value(status).map_err(|e| format!("MemoryOp lead status: {}", e))?;
+            Ok(match memory.update_lead_status(ctx, id, status)? {
+                LeadTransition::Moved { from, lead } => {
+                    serde_json::json!({ "status": "updated", "path": path, "from": from, "to": status, "lead": lead })
+                }
+                LeadTransition::Invalid { from, to } => {
+                    serde_json::json!({ "status": "invalid_transition", "path": path, "from": from, "to": to })
+                }
+                LeadTransition::NotFound => serde_json::json!({ "status": "not_found", "path": path }),
+            })
+        }
+        (MemoryTarget::LeadStatus { .. }, None) => Err("MemoryOp leads/{id}/status takes a status value".into()),
+        (MemoryTarget::Knowledge { .. }, _) => unreachable!("knowledge paths are handled by the orchestrator"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn paths_parse_to_targets() {
+        assert_eq!(
+            MemoryTarget::parse("vault/notes/2024/q1"),
+            Ok(MemoryTarget::Vault { tree: "notes", key: "2024/q1" })
+        );
+        assert_eq!(MemoryTarget::parse("/knowledge/3/graph/entity/x"), Ok(MemoryTarget::Knowledge { slot_id: 3, key: "graph/entity/x" }));
+        assert_eq!(MemoryTarget::parse("leads"), Ok(MemoryTarget::Leads));
+        assert_eq!(MemoryTarget::parse("leads/abc/status"), Ok(MemoryTarget::LeadStatus { id: "abc" }));
+        assert!(MemoryTarget::parse("knowledge/9/key").is_err());
+        assert!(MemoryTarget::parse("vault/notes").is_err());
+        assert!(MemoryTarget::parse("cache/x/y").is_err());
+    }
+}
@@ -1,17 +1,81 @@
 //! Master Brain: task delegation and reasoning.
 
+mod audit;
 mod blueprint;
+mod cancel;
+mod contract;
 mod control;
+mod eval;
+mod intent;
+mod limits;
+pub(crate) mod mapping;
+mod memory_op;
+mod pack;
 mod planner;
+mod preview;
+mod progress;
+mod queue;
+mod replay;
+mod scenario;
+mod schedule;
+mod sources;
+mod span;
+mod stats;
+mod tenant;
+mod tools;
+mod user_error;
+mod webhooks;
 
-pub use blueprint::{BlueprintRegistry, Plan};
+pub use blueprint::{
+    parse_blueprint, validate_blueprint, BlueprintError, BlueprintIssue, BlueprintRegistry, IntentSpec, OnBlocked, Plan,
+    PlanStep, StepCondition, StepPolicy, BLUEPRINT_KB_PREFIX, MAX_SUB_PLAN_DEPTH,
+};
+pub use cancel::{CancellationToken, DeadlineExceeded, GoalCancelled};
+pub use contract::{check_capabilities, missing_capabilities, CapabilityContractError, MissingCapability};
 pub use control::ControlPanelMessage;
+pub use intent::{
+    is_confirmation, IntentAction, IntentMatch, IntentRouter, IntentRoutingConfig, IntentSource,
+    INTENT_CONFIRMATION_TTL,
+};
+pub use limits::SkillError;
+pub use mapping::{apply_map, PayloadMap};
+pub use pack::{
+    compare_versions, DomainPack, InstalledPack, PackDowngrade, PackInstall, PackInstallStatus, PackKbKey, PackKbSeed,
+    PackRegistry, PackTemplates, PACK_KB_PREFIX,
+};
+pub use planner::BLUEPRINT_CANDIDATE_PREFIX;
+pub use preview::{EthosCheck, PlanPreview, PreviewStep};
+pub use progress::{StepProgress, StepStatus, PROGRESS_CHANNEL_CAPACITY};
+pub use queue::{DispatchPermit, DispatchQueue, DispatchQueueConfig, QueueStats, RateLimit, RateLimiter, Throttled};
+pub use replay::{InvalidTrace, ReplayDiff, ReplayMode, ReplayStep, StepOutcome, TraceReplay, ValueChange};
+pub use scenario::{
+    MockSkill, Scenario, ScenarioGiven, ScenarioKbEntry, ScenarioOutcome, ScenarioRunner, ScenarioThen, ScenarioWhen,
+};
+pub use schedule::{
+    civil_from_days, CronExpr, InvalidSchedule, Schedule, ScheduleRun, ScheduledGoal, SchedulerRegistry, SCHEDULE_PREFIX,
+};
+pub use sources::{InvalidSource, ScrapeSource, SourceRefresh, SourceRegistry, SOURCE_PREFIX};
+pub use stats::{SkillStats, SkillStatsRegistry, SKILL_STATS_PREFIX};
+pub use tenant::{SkillNotEnabled, TenantSkillPolicy, TenantSkillRegistry};
+pub use tools::{DEFAULT_MAX_TOOL_ITERATIONS, MAX_TOOL_ITERATIONS};
+pub use user_error::{translate_error, UserFacingError};
+pub use webhooks::{
+    check_webhook_url, sign as sign_webhook, webhook_client, PublicResolver, DeliveryAttempt, DeliveryStatus, InvalidWebhook, Webhook,
+    WebhookDelivery, WebhookEvent, WebhookRegistry, WEBHOOK_DELIVERY_PREFIX, WEBHOOK_FAILED_RETENTION_MS,
+    WEBHOOK_MAX_ATTEMPTS, WEBHOOK_PREFIX, WEBHOOK_RETRY_BASE_MS, WEBHOOK_RETRY_MAX_MS,
+};
 
-use crate::shared::{Goal, TenantContext};
+use audit::DispatchAudit;
+use cancel::{cancel_at_deadline, deadline_error, run_cancellable, InFlight};
+use crate::knowledge::{ActiveEthosPolicy, ApprovalCheckpoint, ApprovalRecord, AuditOutcome, KnowledgeStore, PolicyDecision, RuleAction};
+use crate::memory::MemoryManager;
+use crate::shared::{Goal, LimitsConfig, SkillLimits, TenantContext};
+use span::{dispatch_span, record_error, step_span};
 use std::fmt;
 use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
-use std::sync::{Arc, RwLock};
-use tokio::sync::mpsc;
+use std::sync::{Arc, PoisonError, RwLock};
+use tokio::sync::{broadcast, mpsc};
+use tracing::Instrument;
 
 #[derive(Debug)]
 struct UnknownSkill(String);
@@ -24,6 +88,75 @@ impl fmt::Display for UnknownSkill {
 
 impl std::error::Error for UnknownSkill {}
 
+/// A skill call stopped by an Ethos rule (`deny` or `require_approval`) before it ran.
+#[derive(Debug, Clone)]
+pub struct EthosBlocked {
+    pub skill: String,
+    pub decision: PolicyDecision,
+    /// Pending approval for the call (`require_approval` under a correlation id).
+    pub approval_id: Option<String>,
+    /// Trace of the plan the block halted, up to and including the blocked step (empty for a
+    /// direct skill call).
+    pub steps: Vec<serde_json::Value>,
+}
+
+impl EthosBlocked {
+    /// Whether the rule asks for approval rather than denying outright.
+    pub fn requires_approval(&self) -> bool {
+        self.decision.action == RuleAction::RequireApproval
+    }
+}
+
+impl fmt::Display for EthosBlocked {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let verdict = if self.requires_approval() { "requires approval" } else { "is blocked" };
+        write!(f, "skill {} {} by Ethos policy", self.skill, verdict)?;
+        if let Some(reason) = &self.decision.reason {
+            write!(f, ": {}", reason)?;
+        }
+        match &self.approval_id {
+            Some(id) => write!(f, " (approval {})", id),
+            None => Ok(()),
+        }
+    }
+}
+
+impl std::error::Error for EthosBlocked {}
+
+/// A sub-plan step that cannot be expanded.
+#[derive(Debug)]
+struct InvalidSubPlan {
+    intent: String,
+    reason: String,
+}
+
+impl fmt::Display for InvalidSubPlan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "sub-plan intent {}: {}", self.intent, self.reason)
+    }
+}
+
+impl std::error::Error for InvalidSubPlan {}
+
+/// Which Ethos policy a skill call is checked against.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum EthosScope {
+    /// An `ExecuteSkill` goal: the rule set (v2), else the legacy keyword policy.
+    Direct,
+    /// A call the orchestrator makes itself (plan steps, tool calls, built-in goals): the rule
+    /// set only. The legacy policy scans the whole payload, so it would stop any chain passing
+    /// model output on (`token_usage`).
+    Internal,
+}
+
+/// Outcome of running one plan level: the last step output, the skill that produced it (for
+/// built-in payload mapping into the next step), and the per-step trace.
+struct PlanRun {
+    output: serde_json::Value,
+    last_skill: Option<String>,
+    steps: Vec<serde_json::Value>,
+}
+
 /// Trait implemented by all agent capabilities (skills).
 #[async_trait::async_trait]
 pub trait AgentSkill: Send + Sync {
@@ -36,6 +169,33 @@ pub trait AgentSkill: Send + Sync {
         ctx: &TenantContext,
         payload: Option<serde_json::Value>,
     ) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Streaming variant of [`execute`](Self::execute): progress chunks are sent as they are
+    /// produced (e.g. `{ "delta": "..." }` for generated text) and the **last chunk before the
+    /// channel closes is the final result**. The default runs `execute` and sends its result
+    /// as a single chunk, so every skill can be streamed.
+    async fn execute_stream(
+        &self,
+        ctx: &TenantContext,
+        payload: Option<serde_json::Value>,
+    ) -> Result<mpsc::Receiver<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>> {
+        let result = self.execute(ctx, payload).await?;
+        Ok(single_chunk(result))
+    }
+
+    /// Limits the skill asks for (e.g. a sandbox root for file access); `[limits.skills.<name>]`
+    /// overrides them and `[limits]` fills in the rest.
+    fn limits(&self) -> SkillLimits {
+        SkillLimits::default()
+    }
+}
+
+/// Wraps a complete result as a one-chunk stream.
+fn single_chunk(value: serde_json::Value) -> mpsc::Receiver<serde_json::Value> {
+    let (tx, rx) = mpsc::channel(1);
+    // Capacity 1 and a fresh channel: this cannot fail or block.
+    let _ = tx.try_send(value);
+    rx
 }
 
 /// Registry of agent skills that can be dispatched by name.
@@ -84,6 +244,28 @@ pub struct Orchestrator {
     skills_enabled: AtomicBool,
     /// (short_term, long_term) weights for memory retrieval scoring.
     memory_weights: RwLock<(f32, f32)>,
+    /// Optional store for the dynamic planner (KB-5 manifests and blueprint candidates).
+    knowledge: Option<Arc<KnowledgeStore>>,
+    /// Optional vault for `Goal::MemoryOp` (`vault/` and `leads` paths).
+    memory: Option<Arc<MemoryManager>>,
+    /// Optional per-tenant view (skill allow/deny lists and tenant-specific instances).
+    tenants: Option<Arc<TenantSkillRegistry>>,
+    /// Cancellation tokens of running dispatches, by correlation id.
+    in_flight: Arc<InFlight>,
+    /// Optional admission control (concurrency cap and per-tenant rate limits).
+    queue: Option<Arc<DispatchQueue>>,
+    /// Rolling per-skill outcomes and latency (persisted to KB-5 once a store is attached).
+    stats: Arc<SkillStatsRegistry>,
+    /// Plan step transitions, for live progress views.
+    progress: broadcast::Sender<StepProgress>,
+    /// Control state after each applied control-panel message (a `FullState` snapshot).
+    control_updates: broadcast::Sender<ControlPanelMessage>,
+    /// Optional chat intent routing (free-form chat to `AutonomousGoal`).
+    intent_router: Option<Arc<IntentRouter>>,
+    /// Events also posted to chat through the `NotifyChat` skill.
+    chat_events: RwLock<Vec<WebhookEvent>>,
+    /// Timeout, payload/result size and sandbox limits for skill calls.
+    skill_limits: LimitsConfig,
 }
 
 impl Orchestrator {
@@ -94,6 +276,17 @@ impl Orchestrator {
             active_kbs: AtomicU8::new(0xFF),
             skills_enabled: AtomicBool::new(true),
             memory_weights: RwLock::new((0.7, 0.3)),
+            knowledge: None,
+            memory: None,
+            tenants: None,
+            in_flight: Arc::new(InFlight::default()),
+            queue: None,
+            stats: Arc::new(SkillStatsRegistry::default()),
+            progress: broadcast::channel(PROGRESS_CHANNEL_CAPACITY).0,
+            control_updates: broadcast::channel(16).0,
+            intent_router: None,
+            chat_events: RwLock::new(Vec::new()),
+            skill_limits: LimitsConfig::default(),
         }
     }
 
@@ -104,9 +297,304 @@ impl Orchestrator {
             active_kbs: AtomicU8::new(0xFF),
             skills_enabled: AtomicBool::new(true),
             memory_weights: RwLock::new((0.7, 0.3)),
+            knowledge: None,
+            memory: None,
+            tenants: None,
+            in_flight: Arc::new(InFlight::default()),
+            queue: None,
+            stats: Arc::new(SkillStatsRegistry::default()),
+            progress: broadcast::channel(PROGRESS_CHANNEL_CAPACITY).0,
+            control_updates: broadcast::channel(16).0,
+            intent_router: None,
+            chat_events: RwLock::new(Vec::new()),
+            skill_limits: LimitsConfig::default(),
         }
     }
 
+    /// Attaches the KnowledgeStore so the dynamic planner can read KB-5 skill manifests and
+    /// store synthesized plans as blueprint candidates. Skill stats are loaded from and persisted
+    /// to the same store.
+    pub fn with_knowledge(mut self, knowledge: Arc<KnowledgeStore>) -> Self {
+        self.stats = Arc::new(SkillStatsRegistry::with_store(Arc::clone(&knowledge)));
+        self.knowledge = Some(knowledge);
+        self
+    }
+
+    /// Attaches the vault that `Goal::MemoryOp` reads and writes (`vault/` and `leads` paths).
+    pub fn with_memory(mut self, memory: Arc<MemoryManager>) -> Self {
+        self.memory = Some(memory);
+        self
+    }
+
+    /// Resolves skills through a per-tenant view: `ctx.tenant_id` selects the tenant's policy and
+    /// skill overrides.
+    pub fn with_tenants(mut self, tenants: Arc<TenantSkillRegistry>) -> Self {
+        self.tenants = Some(tenants);
+        self
+    }
+
+    /// Admits every top-level dispatch through `queue`; rejected requests fail with [`Throttled`].
+    pub fn with_dispatch_queue(mut self, queue: Arc<DispatchQueue>) -> Self {
+        self.queue = Some(queue);
+        self
+    }
+
+    /// Posts `events` to chat as well as to webhooks, through the registered `NotifyChat` skill.
+    pub fn with_chat_notifications(self, events: Vec<WebhookEvent>) -> Self {
+        self.set_chat_notifications(events);
+        self
+    }
+
+    /// Replaces the events posted to chat (config reload).
+    pub fn set_chat_notifications(&self, events: Vec<WebhookEvent>) {
+        *self.chat_events.write().unwrap_or_else(PoisonError::into_inner) = events;
+    }
+
+    /// Lets chat messages trigger blueprint intents ([`Orchestrator::route_chat`]).
+    pub fn with_intent_router(mut self, router: Arc<IntentRouter>) -> Self {
+        self.intent_router = Some(router);
+        self
+    }
+
+    /// Chat intent router, when attached.
+    pub fn intent_router(&self) -> Option<&Arc<IntentRouter>> {
+        self.intent_router.as_ref()
+    }
+
+    /// Dispatch queue, when attached.
+    pub fn dispatch_queue(&self) -> Option<&Arc<DispatchQueue>> {
+        self.queue.as_ref()
+    }
+
+    /// Per-skill execution history (success rate, latency, last error).
+    pub fn skill_stats(&self) -> &Arc<SkillStatsRegistry> {
+        &self.stats
+    }
+
+    /// Step-by-step progress of running `AutonomousGoal` plans, for every dispatch (filter by
+    /// [`StepProgress::correlation_id`]).
+    pub fn subscribe_progress(&self) -> broadcast::Receiver<StepProgress> {
+        self.progress.subscribe()
+    }
+
+    /// Milliseconds since the Unix epoch by the attached store's clock (the system clock without
+    /// a store).
+    pub(crate) fn now_ms(&self) -> i64 {
+        self.knowledge.as_ref().map_or_else(crate::clock::system_now_ms, |k| k.now_ms())
+    }
+
+    fn report_progress(&self, mut event: StepProgress) {
+        event.timestamp_ms = self.now_ms();
+        // Err only means there are no subscribers.
+        let _ = self.progress.send(event);
+    }
+
+    /// Control state as a `FullState` message after every applied control-panel message.
+    pub fn subscribe_control(&self) -> broadcast::Receiver<ControlPanelMessage> {
+        self.control_updates.subscribe()
+    }
+
+    /// Current control state as a `FullState` message.
+    pub fn control_state(&self) -> ControlPanelMessage {
+        let mask = self.active_kbs.load(Ordering::Acquire);
+        let (short_term, long_term) = self.pagi_memory_weights();
+        ControlPanelMessage::FullState {
+            kb_states: std::array::from_fn(|i| mask & (1u8 << i) != 0),
+            skills_enabled: self.pagi_skills_enabled(),
+            short_term_memory_weight: short_term,
+            long_term_memory_weight: long_term,
+        }
+    }
+
+    /// Sends the current control state to control subscribers, e.g. after a config reload.
+    pub fn broadcast_control_state(&self) {
+        // Err only means there are no subscribers.
+        let _ = self.control_updates.send(self.control_state());
+    }
+
+    async fn admit(&self, ctx: &TenantContext) -> Result<Option<DispatchPermit>, Throttled> {
+        match &self.queue {
+            Some(queue) => queue.admit(&ctx.tenant_id).await.map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Per-tenant skill registry, when attached.
+    pub fn tenants(&self) -> Option<&Arc<TenantSkillRegistry>> {
+        self.tenants.as_ref()
+    }
+
+    /// Blueprint registry used for `AutonomousGoal` plans (supports runtime edits and reload).
+    pub fn blueprint(&self) -> &Arc<BlueprintRegistry> {
+        &self.blueprint
+    }
+
+    /// Names of all registered skills.
+    pub fn skill_names(&self) -> Vec<String> {
+        self.registry.skill_names()
+    }
+
+    /// Names of the skills a tenant can run.
+    pub fn skill_names_for(&self, tenant_id: &str) -> Vec<String> {
+        match &self.tenants {
+            Some(tenants) => tenants.skill_names(&self.registry, tenant_id),
+            None => self.registry.skill_names(),
+        }
+    }
+
+    /// Cancels the running dispatch (or stream) with this correlation id. Returns false when no
+    /// such request is in flight.
+    pub fn cancel(&self, correlation_id: &str) -> bool {
+        let found = self.in_flight.cancel(correlation_id);
+        if found {
+            tracing::info!(target: "pagi::orchestrator", correlation_id, "Cancellation requested");
+        }
+        found
+    }
+
+    /// Correlation ids of the dispatches currently running.
+    pub fn in_flight(&self) -> Vec<String> {
+        self.in_flight.ids()
+    }
+
+    /// Looks up a skill for the request's tenant (global registry when no tenant view is attached).
+    fn skill(
+        &self,
+        ctx: &TenantContext,
+        name: &str,
+    ) -> Result<Arc<dyn AgentSkill>, Box<dyn std::error::Error + Send + Sync>> {
+        let found = match &self.tenants {
+            Some(tenants) => tenants.resolve(&self.registry, &ctx.tenant_id, name)?,
+            None => self.registry.get(name),
+        };
+        found.ok_or_else(|| UnknownSkill(name.to_string()).into())
+    }
+
+    /// Skills to try for a plan step: the primary plus its fallbacks, healthy ones first.
+    /// Candidates that do not resolve for the tenant are dropped; if none resolve, the primary's
+    /// lookup error is returned.
+    fn step_candidates(
+        &self,
+        ctx: &TenantContext,
+        step: &PlanStep,
+    ) -> Result<Vec<Arc<dyn AgentSkill>>, Box<dyn std::error::Error + Send + Sync>> {
+        let candidates: Vec<Arc<dyn AgentSkill>> = self
+            .stats
+            .prefer_healthy(&step.candidates())
+            .into_iter()
+            .filter_map(|name| self.skill(ctx, name).ok())
+            .collect();
+        if candidates.is_empty() {
+            return Err(self
+                .skill(ctx, &step.skill)
+                .err()
+                .unwrap_or_else(|| UnknownSkill(step.skill.clone()).into()));
+        }
+        Ok(candidates)
+    }
+
+    /// Checks a skill call against the Ethos policy in KB_ETHOS (see [`EthosScope`] for which one).
+    /// A `require_approval` call under a correlation id runs once an operator approved it, and
+    /// otherwise becomes (or stays) a pending approval.
+    fn ethos_gate(
+        &self,
+        ctx: &TenantContext,
+        skill: &str,
+        payload: Option<&serde_json::Value>,
+        scope: EthosScope,
+    ) -> Result<(), Box<EthosBlocked>> {
+        let Some(knowledge) = self.knowledge.as_ref() else {
+            return Ok(());
+        };
+        let policy = match scope {
+            EthosScope::Direct => knowledge.active_ethos_policy(),
+            EthosScope::Internal => knowledge.get_policy_rules().map(ActiveEthosPolicy::Rules),
+        };
+        let Some(policy) = policy else {
+            return Ok(());
+        };
+        let mut decision = policy.evaluate(skill, payload);
+        let mut approval_id = None;
+        match (decision.action, ctx.correlation_id.as_deref()) {
+            (RuleAction::Allow, _) => return Ok(()),
+            (RuleAction::RequireApproval, Some(correlation_id)) => {
+                let request = ApprovalRecord {
+                    rule_id: decision.rule_id.clone(),
+                    reason: decision.reason.clone(),
+                    ..ApprovalRecord::new(&ctx.tenant_id, ctx.resolved_agent_id(), correlation_id, skill, payload.cloned())
+                };
+                match knowledge.approval_checkpoint(&request, true) {
+                    Ok(ApprovalCheckpoint::Granted(_)) => return Ok(()),
+                    Ok(ApprovalCheckpoint::Pending(approval)) => approval_id = Some(approval.approval_id),
+                    Ok(ApprovalCheckpoint::Denied(approval)) => {
+                        decision.action = RuleAction::Deny;
+                        decision.reason = Some(approval.denial_reason());
+                        approval_id = Some(approval.approval_id);
+                    }
+                    Err(e) => {
+                        tracing::warn!(target: "pagi::orchestrator", skill, error = %e, "Failed to record approval request");
+                    }
+                }
+            }
+            _ => {}
+        }
+        tracing::warn!(
+            target: "pagi::orchestrator",
+            skill,
+            rule = decision.rule_id.as_deref().unwrap_or("default"),
+            action = ?decision.action,
+            "Skill call blocked by Ethos policy"
+        );
+        if decision.action == RuleAction::Deny {
+            let data = serde_json::json!({
+                "skill": skill,
+                "agent_id": ctx.resolved_agent_id(),
+                "correlation_id": ctx.correlation_id,
+                "rule_id": decision.rule_id,
+                "reason": decision.reason,
+            });
+            self.emit_webhook(ctx, WebhookEvent::PolicyViolated, data);
+        }
+        Err(Box::new(EthosBlocked {
+            skill: skill.to_string(),
+            decision,
+            approval_id,
+            steps: Vec::new(),
+        }))
+    }
+
+    /// Runs a single skill call after the Ethos gate and within the skill's limits, recording its
+    /// outcome and latency.
+    async fn execute_skill(
+        &self,
+        skill: &dyn AgentSkill,
+        ctx: &TenantContext,
+        payload: Option<serde_json::Value>,
+        scope: EthosScope,
+    ) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>> {
+        // Coerced rather than `?`-converted, which would box the box and hide it from `downcast_ref`.
+        self.ethos_gate(ctx, skill.name(), payload.as_ref(), scope)
+            .map_err(|blocked| blocked as Box<dyn std::error::Error + Send + Sync>)?;
+        let started = std::time::Instant::now();
+        let outcome = self.execute_limited(skill, ctx, payload, None).await;
+        self.stats.record(
+            skill.name(),
+            outcome.is_ok(),
+            started.elapsed().as_millis() as u64,
+            outcome.as_ref().err().map(|e| e.to_string()),
+        );
+        if let (Ok(result), Some(event)) = (&outcome, WebhookEvent::for_skill(skill.name())) {
+            let data = serde_json::json!({
+                "skill": skill.name(),
+                "agent_id": ctx.resolved_agent_id(),
+                "correlation_id": ctx.correlation_id,
+                "result": result,
+            });
+            self.emit_webhook(ctx, event, data);
+        }
+        outcome
+    }
+
     /// Applies a control-panel message to the orchestrator state (lock-free where possible).
     pub fn pagi_apply_control_signal(&self, msg: ControlPanelMessage) {
         use ControlPanelMessage::*;
@@ -147,6 +635,7 @@ impl Orchestrator {
                 }
             }
         }
+        self.broadcast_control_state();
     }
 
     /// Returns whether the given KB slot (1..=8) is active.
@@ -181,13 +670,138 @@ impl Orchestrator {
         });
     }
 
+    /// Streaming counterpart of [`dispatch`](Self::dispatch). `ExecuteSkill` forwards the chunks
+    /// of [`AgentSkill::execute_stream`]; every other goal is dispatched normally and its result
+    /// delivered as a single final chunk.
+    pub async fn dispatch_stream(
+        &self,
+        ctx: &TenantContext,
+        goal: Goal,
+    ) -> Result<mpsc::Receiver<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>> {
+        self.dispatch_stream_with_token(ctx, goal, CancellationToken::new()).await
+    }
+
+    /// [`dispatch_stream`](Self::dispatch_stream) under a caller-owned token. On cancellation the
+    /// stream ends with `{ "done": true, "status": "cancelled" }` and the skill's receiver is
+    /// dropped, which stops the producer (e.g. ModelRouter aborts its HTTP stream).
+    pub async fn dispatch_stream_with_token(
+        &self,
+        ctx: &TenantContext,
+        goal: Goal,
+        token: CancellationToken,
+    ) -> Result<mpsc::Receiver<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>> {
+        match goal {
+            Goal::ExecuteSkill { name, payload } if self.skills_enabled.load(Ordering::Acquire) => {
+                let audit = DispatchAudit::start_skill(&name, self.now_ms());
+                let span = dispatch_span(ctx, "ExecuteSkill");
+                let timer = cancel_at_deadline(ctx, &token);
+                let opened = async {
+                    let permit = run_cancellable(&token, 0, async { Ok(self.admit(ctx).await?) }).await?;
+                    let guard = ctx.correlation_id.as_deref().map(|id| self.in_flight.register(id, &token));
+                    let skill = self.skill(ctx, &name)?;
+                    self.check_stream_payload(ctx, skill.as_ref(), payload.as_ref())?;
+                    let inner = run_cancellable(&token, 0, skill.execute_stream(ctx, payload)).await?;
+                    Ok::<_, Box<dyn std::error::Error + Send + Sync>>((permit, guard, inner))
+                };
+                let (permit, guard, mut inner) = match opened.instrument(span.clone()).await {
+                    Ok(opened) => opened,
+                    Err(e) => {
+                        let e = deadline_error(ctx, e);
+                        record_error(&span, e.as_ref());
+                        audit.finish(self.knowledge.as_deref(), ctx, Some(e.as_ref()));
+                        return Err(e);
+                    }
+                };
+                let stream_ctx = ctx.clone();
+                let knowledge = self.knowledge.clone();
+                let (tx, rx) = mpsc::channel(32);
+                let stream_span = span.clone();
+                tokio::spawn(
+                    async move {
+                        let _guard = guard;
+                        let _permit = permit;
+                        let _timer = timer;
+                        let outcome = loop {
+                            tokio::select! {
+                                _ = token.cancelled() => {
+                                    let status = if stream_ctx.deadline_exceeded() { "deadline_exceeded" } else { "cancelled" };
+                                    tracing::info!(target: "pagi::orchestrator", skill = %name, status, "Stream cancelled");
+                                    let _ = tx
+                                        .send(serde_json::json!({ "done": true, "status": status, "cancelled": true }))
+                                        .await;
+                                    break if stream_ctx.deadline_exceeded() { AuditOutcome::Timeout } else { AuditOutcome::Cancelled };
+                                }
+                                _ = tx.closed() => break AuditOutcome::Cancelled,
+                                chunk = inner.recv() => match chunk {
+                                    Some(chunk) => {
+                                        if tx.send(chunk).await.is_err() {
+                                            break AuditOutcome::Cancelled;
+                                        }
+                                    }
+                                    None => break AuditOutcome::Ok,
+                                },
+                            }
+                        };
+                        if outcome != AuditOutcome::Ok {
+                            stream_span.record("otel.status_code", "ERROR");
+                        }
+                        audit.finish_with(knowledge.as_deref(), &stream_ctx, outcome, None);
+                    }
+                    .instrument(span),
+                );
+                Ok(rx)
+            }
+            goal => Ok(single_chunk(self.dispatch_with_token(ctx, goal, token).await?)),
+        }
+    }
+
     /// Dispatches a goal; ExecuteSkill is routed to the registered skill and executed.
-    /// Respects control-panel state: skills disabled and inactive KBs are gated.
+    /// Respects control-panel state: skills disabled and inactive KBs are gated. With a store
+    /// attached, every dispatch (streams included) is written to the audit log when it ends.
     pub async fn dispatch(
         &self,
         ctx: &TenantContext,
         goal: Goal,
     ) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>> {
+        self.dispatch_with_token(ctx, goal, CancellationToken::new()).await
+    }
+
+    /// [`dispatch`](Self::dispatch) under a caller-owned token. The token is also registered under
+    /// `ctx.correlation_id` so [`cancel`](Self::cancel) can reach it. Cancellation is checked
+    /// between plan steps and sub-goals, and a running skill call is dropped when the token fires;
+    /// the dispatch then fails with [`GoalCancelled`]. `ctx.deadline_ms` fires the token too (also
+    /// while waiting in the dispatch queue), failing the dispatch with [`DeadlineExceeded`].
+    pub async fn dispatch_with_token(
+        &self,
+        ctx: &TenantContext,
+        goal: Goal,
+        token: CancellationToken,
+    ) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>> {
+        let audit = DispatchAudit::start(&goal, self.now_ms());
+        let span = dispatch_span(ctx, goal.kind());
+        let _timer = cancel_at_deadline(ctx, &token);
+        let run = async {
+            let _permit = run_cancellable(&token, 0, async { Ok(self.admit(ctx).await?) }).await?;
+            let _guard = ctx.correlation_id.as_deref().map(|id| self.in_flight.register(id, &token));
+            self.dispatch_inner(ctx, goal, &token).await
+        };
+        let result = run.instrument(span.clone()).await.map_err(|e| deadline_error(ctx, e));
+        if let Err(e) = &result {
+            record_error(&span, e.as_ref());
+        }
+        audit.finish(self.knowledge.as_deref(), ctx, result.as_ref().err().map(|e| e.as_ref()));
+        result
+    }
+
+    async fn dispatch_inner(
+        &self,
+        ctx: &TenantContext,
+        goal: Goal,
+        token: &CancellationToken,
+    ) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>> {
+        if token.is_cancelled() {
+            return Err(GoalCancelled { completed_steps: 0 }.into());
+        }
         if !self.skills_enabled.load(Ordering::Acquire) {
             return Ok(serde_json::json!({
                 "status": "skills_disabled",
@@ -198,11 +812,8 @@ impl Orchestrator {
 
         match goal {
             Goal::ExecuteSkill { name, payload } => {
-                let skill = self
-                    .registry
-                    .get(&name)
-                    .ok_or_else(|| UnknownSkill(name.clone()))?;
-                skill.execute(ctx, payload).await
+                let skill = self.skill(ctx, &name)?;
+                run_cancellable(token, 0, self.execute_skill(skill.as_ref(), ctx, payload, EthosScope::Direct)).await
             }
             Goal::QueryKnowledge { slot_id, query } => {
                 if !self.pagi_kb_active(slot_id) {
@@ -214,45 +825,32 @@ impl Orchestrator {
                     }));
                 }
                 let payload = serde_json::json!({ "slot_id": slot_id, "query_key": query });
-                let skill = self
-                    .registry
-                    .get("KnowledgeQuery")
-                    .ok_or_else(|| UnknownSkill("KnowledgeQuery".into()))?;
-                skill.execute(ctx, Some(payload)).await
+                let skill = self.skill(ctx, "KnowledgeQuery")?;
+                run_cancellable(token, 0, self.execute_skill(skill.as_ref(), ctx, Some(payload), EthosScope::Internal)).await
             }
             Goal::IngestData { payload } => {
-                let skill = self
-                    .registry
-                    .get("LeadCapture")
-                    .ok_or_else(|| UnknownSkill("LeadCapture".into()))?;
-                skill.execute(ctx, payload).await
+                let skill = self.skill(ctx, "LeadCapture")?;
+                run_cancellable(token, 0, self.execute_skill(skill.as_ref(), ctx, payload, EthosScope::Internal)).await
             }
             Goal::AssembleContext { context_id } => {
                 let payload = serde_json::json!({ "lead_id": context_id });
-                let skill = self
-                    .registry
-                    .get("DraftResponse")
-                    .ok_or_else(|| UnknownSkill("DraftResponse".into()))?;
-                skill.execute(ctx, Some(payload)).await
+                let skill = self.skill(ctx, "DraftResponse")?;
+                run_cancellable(token, 0, self.execute_skill(skill.as_ref(), ctx, Some(payload), EthosScope::Internal)).await
             }
             Goal::GenerateFinalResponse { context_id } => {
-                let draft_skill = self
-                    .registry
-                    .get("DraftResponse")
-                    .ok_or_else(|| UnknownSkill("DraftResponse".into()))?;
+                let draft_skill = self.skill(ctx, "DraftResponse")?;
                 let draft_payload = serde_json::json!({ "lead_id": context_id });
-                let draft_result = draft_skill.execute(ctx, Some(draft_payload)).await?;
+                let draft = self.execute_skill(draft_skill.as_ref(), ctx, Some(draft_payload), EthosScope::Internal);
+                let draft_result = run_cancellable(token, 0, draft).await?;
                 let prompt = draft_result
                     .get("draft")
                     .and_then(|v| v.as_str())
                     .unwrap_or("")
                     .to_string();
-                let router_skill = self
-                    .registry
-                    .get("ModelRouter")
-                    .ok_or_else(|| UnknownSkill("ModelRouter".into()))?;
+                let router_skill = self.skill(ctx, "ModelRouter")?;
                 let router_payload = serde_json::json!({ "prompt": prompt });
-                let router_result = router_skill.execute(ctx, Some(router_payload)).await?;
+                let route = self.execute_skill(router_skill.as_ref(), ctx, Some(router_payload), EthosScope::Internal);
+                let router_result = run_cancellable(token, 1, route).await?;
                 let mut map = match router_result {
                     serde_json::Value::Object(m) => m,
                     _ => {
@@ -268,48 +866,48 @@ impl Orchestrator {
                 Ok(serde_json::Value::Object(map))
             }
             Goal::AutonomousGoal { intent, context } => {
-                let plan = self.blueprint.plan_for_intent(&intent).ok_or_else(|| {
-                    std::io::Error::new(
-                        std::io::ErrorKind::InvalidInput,
-                        format!("unknown intent: {}", intent),
-                    )
-                })?;
+                let (plan, plan_source) = match self.blueprint.plan_for_intent(&intent) {
+                    Some(plan) => (plan, "blueprint"),
+                    None => (
+                        run_cancellable(token, 0, self.synthesize_plan(ctx, &intent, context.as_ref())).await?,
+                        "llm",
+                    ),
+                };
                 let initial_context = context.clone().unwrap_or(serde_json::json!({}));
-                let mut payload = initial_context.clone();
-                let mut previous_result = serde_json::Value::Null;
-                let mut previous_skill: Option<String> = None;
-                let mut steps_trace: Vec<serde_json::Value> = Vec::new();
-
-                for skill_name in &plan.steps {
-                    let skill = self
-                        .registry
-                        .get(skill_name)
-                        .ok_or_else(|| UnknownSkill(skill_name.clone()))?;
-                    let step_input = chain_payload(previous_skill.as_deref(), skill_name, &previous_result, payload.clone());
-                    previous_result = skill.execute(ctx, step_input.clone()).await?;
-                    previous_skill = Some(skill_name.clone());
-                    payload = previous_result.clone();
-
-                    steps_trace.push(serde_json::json!({
-                        "skill": skill_name,
-                        "input": step_input,
-                        "output": previous_result
-                    }));
-                }
-
-                let final_result = previous_result.clone();
+                let plan_steps = plan.skill_names();
+                let mut completed_steps = 0;
+                let run = self
+                    .run_plan(
+                        ctx,
+                        &plan,
+                        initial_context.clone(),
+                        token,
+                        &mut vec![blueprint::normalize_intent(&intent)],
+                        &mut completed_steps,
+                    )
+                    .await?;
+                let steps_trace = run.steps;
+                let final_result = run.output;
                 let thought_log = serde_json::json!({
                     "intent": intent,
                     "context": initial_context,
-                    "plan_steps": plan.steps,
+                    "plan_steps": plan_steps,
+                    "plan_source": plan_source,
                     "steps": steps_trace,
                     "final_result": final_result
                 });
 
-                if let Some(audit_skill) = self.registry.get("ResearchAudit") {
+                if let Ok(audit_skill) = self.skill(ctx, "ResearchAudit") {
                     let audit_payload = serde_json::json!({ "trace": thought_log });
                     if let Ok(audit_result) = audit_skill.execute(ctx, Some(audit_payload)).await {
                         if let Some(trace_id) = audit_result.get("trace_id").and_then(|v| v.as_str()) {
+                            let completed = serde_json::json!({
+                                "intent": intent,
+                                "plan_steps": plan_steps,
+                                "plan_source": plan_source,
+                                "trace_id": trace_id,
+                            });
+                            self.emit_webhook(ctx, WebhookEvent::GoalCompleted, completed);
                             let mut out = match final_result {
                                 serde_json::Value::Object(m) => m,
                                 _ => {
@@ -320,20 +918,24 @@ impl Orchestrator {
                             };
                             out.insert("goal".to_string(), serde_json::json!("AutonomousGoal"));
                             out.insert("intent".to_string(), serde_json::json!(intent));
-                            out.insert("plan_steps".to_string(), serde_json::json!(plan.steps));
+                            out.insert("plan_steps".to_string(), serde_json::json!(plan_steps));
+                            out.insert("plan_source".to_string(), serde_json::json!(plan_source));
                             out.insert("trace_id".to_string(), serde_json::json!(trace_id));
                             return Ok(serde_json::Value::Object(out));
                         }
                     }
                 }
 
+                let completed = serde_json::json!({ "intent": intent, "plan_steps": plan_steps, "plan_source": plan_source });
+                self.emit_webhook(ctx, WebhookEvent::GoalCompleted, completed);
                 let mut out = match final_result {
                     serde_json::Value::Object(m) => m,
                     _ => return Ok(final_result),
                 };
                 out.insert("goal".to_string(), serde_json::json!("AutonomousGoal"));
                 out.insert("intent".to_string(), serde_json::json!(intent));
-                out.insert("plan_steps".to_string(), serde_json::json!(plan.steps));
+                out.insert("plan_steps".to_string(), serde_json::json!(plan_steps));
+                out.insert("plan_source".to_string(), serde_json::json!(plan_source));
                 Ok(serde_json::Value::Object(out))
             }
             Goal::UpdateKnowledgeSlot {
@@ -355,61 +957,622 @@ impl Orchestrator {
                 if let Some(html) = source_html {
                     payload["html"] = serde_json::Value::String(html);
                 }
-                let skill = self
-                    .registry
-                    .get("CommunityScraper")
-                    .ok_or_else(|| UnknownSkill("CommunityScraper".into()))?;
-                skill.execute(ctx, Some(payload)).await
-            }
-            Goal::MemoryOp { path, value } => {
-                Ok(serde_json::json!({ "path": path, "value": value, "status": "dispatched" }))
+                let skill = self.skill(ctx, "CommunityScraper")?;
+                run_cancellable(token, 0, self.execute_skill(skill.as_ref(), ctx, Some(payload), EthosScope::Internal)).await
             }
+            Goal::ToolChat {
+                prompt,
+                system_prompt,
+                max_iterations,
+            } => self.run_tool_chat(ctx, prompt, system_prompt, max_iterations, token).await,
+            Goal::MemoryOp { path, value, delete } => self.run_memory_op(ctx, &path, value, delete),
             Goal::Custom(s) => Ok(serde_json::json!({ "custom": s, "status": "dispatched" })),
+            Goal::Sequence(goals) => {
+                let mut results = Vec::with_capacity(goals.len());
+                let mut stopped = false;
+                for (index, goal) in goals.into_iter().enumerate() {
+                    let kind = goal.kind();
+                    if !stopped && token.is_cancelled() {
+                        return Err(GoalCancelled { completed_steps: index }.into());
+                    }
+                    if stopped {
+                        results.push(serde_json::json!({ "index": index, "goal": kind, "status": "skipped" }));
+                        continue;
+                    }
+                    let outcome = Box::pin(self.dispatch_inner(ctx, goal, token)).await;
+                    if let Err(e) = &outcome {
+                        if e.is::<GoalCancelled>() {
+                            return Err(GoalCancelled { completed_steps: index }.into());
+                        }
+                        tracing::warn!(
+                            target: "pagi::orchestrator",
+                            index,
+                            goal = kind,
+                            error = %e,
+                            "Sequence sub-goal failed; skipping remaining goals"
+                        );
+                        stopped = true;
+                    }
+                    results.push(sub_goal_entry(index, kind, outcome));
+                }
+                Ok(composite_result("Sequence", results))
+            }
+            Goal::Parallel(goals) => {
+                let kinds: Vec<&'static str> = goals.iter().map(Goal::kind).collect();
+                let outcomes = futures_util::future::join_all(
+                    goals.into_iter().map(|goal| Box::pin(self.dispatch_inner(ctx, goal, token))),
+                )
+                .await;
+                if token.is_cancelled() {
+                    let completed_steps = outcomes.iter().filter(|o| o.is_ok()).count();
+                    return Err(GoalCancelled { completed_steps }.into());
+                }
+                let results = outcomes
+                    .into_iter()
+                    .zip(kinds)
+                    .enumerate()
+                    .map(|(index, (outcome, kind))| sub_goal_entry(index, kind, outcome))
+                    .collect();
+                Ok(composite_result("Parallel", results))
+            }
         }
     }
-}
 
-/// Derives the next skill's payload from the previous skill's result (output chaining).
-fn chain_payload(
-    previous_skill: Option<&str>,
-    next_skill: &str,
-    previous_result: &serde_json::Value,
-    fallback: serde_json::Value,
-) -> Option<serde_json::Value> {
-    match (previous_skill, next_skill) {
-        (Some("DraftResponse"), "SalesCloser") => {
-            let draft = previous_result
-                .get("draft")
-                .and_then(|v| v.as_str())
-                .unwrap_or("")
-                .to_string();
-            Some(serde_json::json!({ "draft": draft }))
+    /// Runs `plan` from `input`, expanding sub-plan steps recursively. `chain` holds the intents
+    /// being expanded (outermost first); `completed_steps` counts successful steps across all
+    /// levels (reported on cancellation).
+    async fn run_plan(
+        &self,
+        ctx: &TenantContext,
+        plan: &Plan,
+        input: serde_json::Value,
+        token: &CancellationToken,
+        chain: &mut Vec<String>,
+        completed_steps: &mut usize,
+    ) -> Result<PlanRun, Box<dyn std::error::Error + Send + Sync>> {
+        let intent = chain.last().cloned().unwrap_or_default();
+        let mut payload = input;
+        let mut previous_result = serde_json::Value::Null;
+        let mut previous_skill: Option<String> = None;
+        let mut steps_trace: Vec<serde_json::Value> = Vec::new();
+        let depth = chain.len();
+
+        for (index, step) in plan.steps.iter().enumerate() {
+            let skill_name = &step.skill;
+            let label = step.label();
+            let progress = StepProgress::started(ctx, &intent, depth, index, plan.steps.len(), label.clone());
+            if token.is_cancelled() {
+                tracing::warn!(
+                    target: "pagi::orchestrator",
+                    intent = %intent,
+                    completed_steps = *completed_steps,
+                    next_skill = %label,
+                    "AutonomousGoal cancelled between plan steps"
+                );
+                return Err(GoalCancelled { completed_steps: *completed_steps }.into());
+            }
+            let candidates = match step.intent {
+                Some(_) => Vec::new(),
+                None => self.step_candidates(ctx, step)?,
+            };
+            let branch = step.when.as_ref().map(|cond| {
+                let actual = cond.lookup(&payload).cloned();
+                let taken = actual.as_ref() == Some(&cond.equals);
+                tracing::info!(
+                    target: "pagi::orchestrator",
+                    skill = %label,
+                    path = %cond.path,
+                    taken,
+                    "Plan step condition evaluated"
+                );
+                serde_json::json!({
+                    "path": cond.path,
+                    "equals": cond.equals,
+                    "actual": actual,
+                    "taken": taken
+                })
+            });
+            let mut entry = match &step.intent {
+                Some(sub_intent) => serde_json::json!({ "intent": sub_intent }),
+                None => serde_json::json!({ "skill": skill_name }),
+            };
+            if let Some(branch) = &branch {
+                entry["branch"] = branch.clone();
+            }
+            if branch.as_ref().is_some_and(|b| b["taken"] == false) {
+                entry["status"] = serde_json::json!("skipped");
+                steps_trace.push(entry);
+                self.report_progress(progress.with_status(StepStatus::Skipped));
+                continue;
+            }
+            let step_input = chain_payload(previous_skill.as_deref(), step, &payload);
+            entry["input"] = serde_json::json!(step_input);
+            self.report_progress(progress.clone());
+            let span = step_span(&intent, depth, index, &label);
+
+            if let Some(sub_intent) = &step.intent {
+                let sub_input = step_input.unwrap_or(serde_json::Value::Null);
+                let outcome = self
+                    .run_sub_plan(ctx, sub_intent, sub_input, token, chain, completed_steps)
+                    .instrument(span.clone())
+                    .await;
+                if let Err(e) = &outcome {
+                    record_error(&span, e.as_ref());
+                }
+                self.report_progress(match &outcome {
+                    Ok(_) => progress.with_status(StepStatus::Completed),
+                    Err(e) if e.is::<GoalCancelled>() => progress.with_status(StepStatus::Cancelled),
+                    Err(e) if e.is::<EthosBlocked>() => progress.with_status(StepStatus::Blocked).with_error(e),
+                    Err(e) => progress.with_status(StepStatus::Failed).with_error(e),
+                });
+                match outcome {
+                    Err(e) if e.is::<GoalCancelled>() => return Err(e),
+                    Ok(run) => {
+                        previous_result = run.output;
+                        payload = previous_result.clone();
+                        previous_skill = run.last_skill;
+                        entry["output"] = previous_result.clone();
+                        entry["depth"] = serde_json::json!(chain.len());
+                        entry["steps"] = serde_json::json!(run.steps);
+                        steps_trace.push(entry);
+                    }
+                    // The sub-plan's own step policy already chose to halt on the block.
+                    Err(e) if e.is::<EthosBlocked>() => {
+                        let blocked = e.downcast_ref::<EthosBlocked>();
+                        entry["status"] = serde_json::json!("blocked");
+                        entry["error"] = serde_json::json!(e.to_string());
+                        entry["ethos"] = blocked.map(ethos_trace).unwrap_or_default();
+                        entry["steps"] = serde_json::json!(blocked.map(|b| b.steps.clone()).unwrap_or_default());
+                        steps_trace.push(entry);
+                        return Err(with_blocked_trace(e, steps_trace));
+                    }
+                    Err(e) if step.policy.continue_on_failure => {
+                        tracing::warn!(
+                            target: "pagi::orchestrator",
+                            intent = %sub_intent,
+                            error = %e,
+                            "Sub-plan failed; continuing per step policy"
+                        );
+                        entry["status"] = serde_json::json!("failed");
+                        entry["error"] = serde_json::json!(e.to_string());
+                        steps_trace.push(entry);
+                    }
+                    Err(e) => return Err(e),
+                }
+                continue;
+            }
+
+            let mut fallback_errors = Vec::new();
+            let mut candidates = candidates.iter().peekable();
+            let (executed, outcome, attempts) = loop {
+                let skill = candidates.next().expect("step has at least one candidate");
+                let (outcome, attempts) = match self.ethos_gate(ctx, skill.name(), step_input.as_ref(), EthosScope::Internal) {
+                    Err(blocked) => (Err(blocked as Box<dyn std::error::Error + Send + Sync>), Vec::new()),
+                    Ok(()) => {
+                        execute_with_policy(
+                            skill.as_ref(),
+                            ctx,
+                            step_input.clone(),
+                            &step.policy,
+                            token,
+                            *completed_steps,
+                            self,
+                        )
+                        .instrument(span.clone())
+                        .await
+                    }
+                };
+                let blocked = outcome.as_ref().err().and_then(|e| e.downcast_ref::<EthosBlocked>()).cloned();
+                match outcome {
+                    // A blocked step only moves on to its fallbacks when its policy says so.
+                    Err(e) if blocked.is_some() && step.policy.on_blocked != OnBlocked::Fallback => {
+                        break (skill.name().to_string(), Err(e), attempts)
+                    }
+                    Err(e) if !e.is::<GoalCancelled>() && candidates.peek().is_some() => {
+                        tracing::warn!(
+                            target: "pagi::orchestrator",
+                            skill = skill.name(),
+                            error = %e,
+                            "Plan step failed; trying fallback skill"
+                        );
+                        let mut failed = serde_json::json!({
+                            "skill": skill.name(),
+                            "error": e.to_string(),
+                            "attempts": attempts
+                        });
+                        if let Some(blocked) = &blocked {
+                            failed["ethos"] = ethos_trace(blocked);
+                        }
+                        fallback_errors.push(failed);
+                    }
+                    outcome => break (skill.name().to_string(), outcome, attempts),
+                }
+            };
+            entry["attempts"] = serde_json::json!(attempts);
+            span.record("executed_skill", executed.as_str());
+            if let Err(e) = &outcome {
+                record_error(&span, e.as_ref());
+            }
+            if !fallback_errors.is_empty() {
+                entry["fallback_attempts"] = serde_json::json!(fallback_errors);
+            }
+            let mut finished = match &outcome {
+                Ok(_) => progress.with_status(StepStatus::Completed),
+                Err(e) if e.is::<GoalCancelled>() => progress.with_status(StepStatus::Cancelled),
+                Err(e) if e.is::<EthosBlocked>() => progress.with_status(StepStatus::Blocked).with_error(e),
+                Err(e) => progress.with_status(StepStatus::Failed).with_error(e),
+            };
+            if executed != *skill_name {
+                finished = finished.with_executed_skill(executed.as_str());
+            }
+            self.report_progress(finished);
+            match outcome {
+                Err(e) if e.is::<GoalCancelled>() => {
+                    tracing::warn!(
+                        target: "pagi::orchestrator",
+                        intent = %intent,
+                        completed_steps = *completed_steps,
+                        skill = %skill_name,
+                        "AutonomousGoal cancelled during plan step"
+                    );
+                    return Err(e);
+                }
+                Ok(result) => {
+                    *completed_steps += 1;
+                    previous_result = result;
+                    payload = previous_result.clone();
+                    entry["output"] = previous_result.clone();
+                    if executed != *skill_name {
+                        entry["executed_skill"] = serde_json::json!(executed);
+                    }
+                    previous_skill = Some(executed);
+                    steps_trace.push(entry);
+                }
+                Err(e) if e.is::<EthosBlocked>() => {
+                    entry["status"] = serde_json::json!("blocked");
+                    entry["error"] = serde_json::json!(e.to_string());
+                    entry["ethos"] = e.downcast_ref::<EthosBlocked>().map(ethos_trace).unwrap_or_default();
+                    if executed != *skill_name {
+                        entry["executed_skill"] = serde_json::json!(executed);
+                    }
+                    steps_trace.push(entry);
+                    if step.policy.on_blocked != OnBlocked::Skip {
+                        return Err(with_blocked_trace(e, steps_trace));
+                    }
+                    tracing::warn!(
+                        target: "pagi::orchestrator",
+                        skill = %skill_name,
+                        error = %e,
+                        "Plan step blocked by Ethos policy; skipping per step policy"
+                    );
+                }
+                Err(e) if step.policy.continue_on_failure => {
+                    tracing::warn!(
+                        target: "pagi::orchestrator",
+                        skill = %skill_name,
+                        error = %e,
+                        "Plan step failed; continuing per step policy"
+                    );
+                    entry["status"] = serde_json::json!("failed");
+                    entry["error"] = serde_json::json!(e.to_string());
+                    steps_trace.push(entry);
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        Ok(PlanRun {
+            output: previous_result,
+            last_skill: previous_skill,
+            steps: steps_trace,
+        })
+    }
+
+    /// Runs the blueprint plan of `intent` as a nested sub-plan. Unknown intents, cycles, and
+    /// nesting deeper than [`MAX_SUB_PLAN_DEPTH`] fail with [`InvalidSubPlan`].
+    async fn run_sub_plan(
+        &self,
+        ctx: &TenantContext,
+        intent: &str,
+        input: serde_json::Value,
+        token: &CancellationToken,
+        chain: &mut Vec<String>,
+        completed_steps: &mut usize,
+    ) -> Result<PlanRun, Box<dyn std::error::Error + Send + Sync>> {
+        let name = blueprint::normalize_intent(intent);
+        let invalid = |reason: String| InvalidSubPlan {
+            intent: name.clone(),
+            reason,
+        };
+        if chain.contains(&name) {
+            return Err(invalid(format!("cycle ({} -> {})", chain.join(" -> "), name)).into());
+        }
+        if chain.len() > MAX_SUB_PLAN_DEPTH {
+            return Err(invalid(format!("nesting deeper than {} levels", MAX_SUB_PLAN_DEPTH)).into());
+        }
+        let plan = self
+            .blueprint
+            .plan_for_intent(&name)
+            .ok_or_else(|| invalid("no blueprint entry".to_string()))?;
+        tracing::info!(
+            target: "pagi::orchestrator",
+            parent = %chain.join(" -> "),
+            intent = %name,
+            steps = ?plan.skill_names(),
+            "Expanding sub-plan"
+        );
+        chain.push(name);
+        let run = Box::pin(self.run_plan(ctx, &plan, input, token, chain, completed_steps)).await;
+        chain.pop();
+        run
+    }
+
+    /// Decides whether a chat message should run a blueprint intent. `Run` means dispatch
+    /// `AutonomousGoal { intent }` now; `Confirm` means ask the user first (the question stays open
+    /// for the agent/tenant, and a confirming next message comes back as `Run`). None (or no
+    /// router attached / routing disabled) means answer the message as plain chat.
+    pub async fn route_chat(&self, ctx: &TenantContext, message: &str) -> Option<IntentMatch> {
+        let router = self.intent_router.as_ref().filter(|r| r.config().enabled)?;
+        let key = format!("{}/{}", ctx.resolved_agent_id(), ctx.tenant_id);
+        if let Some(confirmed) = router.answer(&key, message) {
+            return Some(confirmed);
+        }
+        let intents = self.blueprint.intent_names();
+        let found = match router.classify(message, &intents) {
+            Some(found) => Some(found),
+            None if router.config().llm_fallback => self.classify_with_model(ctx, router, message, &intents).await,
+            None => None,
+        }?;
+        tracing::info!(
+            target: "pagi::orchestrator",
+            intent = %found.intent,
+            confidence = found.confidence,
+            action = ?found.action,
+            source = ?found.source,
+            "Chat message matched intent"
+        );
+        if found.action == IntentAction::Confirm {
+            router.ask(&key, found.clone());
+        }
+        Some(found)
+    }
+
+    /// LLM fallback for [`Orchestrator::route_chat`]; failures just mean "no intent".
+    async fn classify_with_model(
+        &self,
+        ctx: &TenantContext,
+        router: &IntentRouter,
+        message: &str,
+        intents: &[String],
+    ) -> Option<IntentMatch> {
+        let model = self.skill(ctx, "ModelRouter").ok()?;
+        let prompt = intent::build_classification_prompt(message, intents);
+        let reply = match model
+            .execute(ctx, Some(serde_json::json!({ "prompt": prompt, "temperature": 0.0 })))
+            .await
+        {
+            Ok(reply) => reply,
+            Err(e) => {
+                tracing::warn!(target: "pagi::orchestrator", error = %e, "Intent classification via ModelRouter failed");
+                return None;
+            }
+        };
+        let text = reply.get("generated").and_then(|v| v.as_str()).unwrap_or("");
+        let (intent, confidence) = intent::parse_classification_response(text, intents)?;
+        let confidence = confidence * router.config().llm_weight;
+        Some(IntentMatch {
+            intent,
+            confidence,
+            action: router.action_for(confidence)?,
+            source: IntentSource::Llm,
+            message: message.to_string(),
+        })
+    }
+
+    /// Dynamic planner fallback for intents without a blueprint entry: asks the ModelRouter for a
+    /// skill chain (given registered skills and KB-5 manifests), validates it against the registry,
+    /// and records it in KB-5 as a blueprint candidate.
+    async fn synthesize_plan(
+        &self,
+        ctx: &TenantContext,
+        intent: &str,
+        context: Option<&serde_json::Value>,
+    ) -> Result<Plan, Box<dyn std::error::Error + Send + Sync>> {
+        let unknown = |reason: String| -> Box<dyn std::error::Error + Send + Sync> {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("unknown intent: {} ({})", intent, reason),
+            )
+            .into()
+        };
+        let router = self
+            .skill(ctx, "ModelRouter")
+            .map_err(|_| unknown("no blueprint entry and ModelRouter unavailable for planning".into()))?;
+        let skill_names = self.skill_names_for(&ctx.tenant_id);
+        let manifests = self
+            .knowledge
+            .as_ref()
+            .map(|k| k.get_skills())
+            .unwrap_or_default();
+        let prompt = planner::build_planning_prompt(intent, context, &skill_names, &manifests, &self.stats.all());
+        let reply = router
+            .execute(ctx, Some(serde_json::json!({ "prompt": prompt, "temperature": 0.0 })))
+            .await?;
+        let text = reply.get("generated").and_then(|v| v.as_str()).unwrap_or("");
+        let proposed = planner::parse_plan_response(text)
+            .ok_or_else(|| unknown("planner reply did not contain a skill list".into()))?;
+        let plan = planner::validate_plan(&proposed, &skill_names).map_err(|e| unknown(e.to_string()))?;
+
+        tracing::info!(
+            target: "pagi::orchestrator",
+            intent = %intent,
+            steps = ?plan.skill_names(),
+            "Dynamic planner synthesized plan for unknown intent"
+        );
+        if let Some(knowledge) = &self.knowledge {
+            if let Err(e) = planner::store_blueprint_candidate(knowledge, intent, &plan) {
+                tracing::warn!(target: "pagi::orchestrator", error = %e, "Failed to store blueprint candidate");
+            }
         }
-        (Some("SalesCloser"), "ModelRouter") => {
-            let prompt = previous_result
-                .get("draft")
-                .and_then(|v| v.as_str())
-                .unwrap_or("")
-                .to_string();
-            Some(serde_json::json!({ "prompt": prompt }))
+        Ok(plan)
+    }
+}
+
+/// Trace entry for one sub-goal of a `Sequence` / `Parallel` goal.
+fn sub_goal_entry(
+    index: usize,
+    kind: &str,
+    outcome: Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>>,
+) -> serde_json::Value {
+    match outcome {
+        Ok(output) => serde_json::json!({ "index": index, "goal": kind, "status": "ok", "output": output }),
+        Err(e) => serde_json::json!({ "index": index, "goal": kind, "status": "failed", "error": e.to_string() }),
+    }
+}
+
+/// Combined result of a composite goal. `status` is `ok` when every sub-goal succeeded,
+/// `failed` when none did, and `partial_failure` otherwise.
+fn composite_result(goal: &str, results: Vec<serde_json::Value>) -> serde_json::Value {
+    let count = |status: &str| results.iter().filter(|r| r["status"] == status).count();
+    let (succeeded, failed, skipped) = (count("ok"), count("failed"), count("skipped"));
+    let status = if failed == 0 && skipped == 0 {
+        "ok"
+    } else if succeeded == 0 {
+        "failed"
+    } else {
+        "partial_failure"
+    };
+    serde_json::json!({
+        "goal": goal,
+        "status": status,
+        "succeeded": succeeded,
+        "failed": failed,
+        "skipped": skipped,
+        "results": results
+    })
+}
+
+/// Trace entry for an Ethos block: the decision plus the pending approval, if any.
+fn ethos_trace(blocked: &EthosBlocked) -> serde_json::Value {
+    let mut trace = serde_json::json!(blocked.decision);
+    trace["approval_id"] = serde_json::json!(blocked.approval_id);
+    trace
+}
+
+/// Attaches the trace of the plan level an Ethos block halted to the [`EthosBlocked`] error.
+fn with_blocked_trace(
+    e: Box<dyn std::error::Error + Send + Sync>,
+    steps: Vec<serde_json::Value>,
+) -> Box<dyn std::error::Error + Send + Sync> {
+    match e.downcast::<EthosBlocked>() {
+        Ok(mut blocked) => {
+            blocked.steps = steps;
+            blocked
         }
-        (Some("DraftResponse"), "ModelRouter") => {
-            let prompt = previous_result
-                .get("draft")
-                .and_then(|v| v.as_str())
-                .unwrap_or("")
-                .to_string();
-            Some(serde_json::json!({ "prompt": prompt }))
+        Err(e) => e,
+    }
+}
+
+/// Runs one plan step under its [`StepPolicy`]: each attempt is bounded by `timeout_ms` (and the
+/// skill's own limits) and failed attempts are retried with exponential backoff. Returns the final
+/// outcome plus a per-attempt trace (`attempt`, `status`, `elapsed_ms`, `error`). Cancellation
+/// interrupts the attempt or backoff in progress and is never retried, nor are size and sandbox
+/// violations. Every completed attempt (timeouts included) is recorded in the skill stats.
+async fn execute_with_policy(
+    skill: &dyn AgentSkill,
+    ctx: &TenantContext,
+    input: Option<serde_json::Value>,
+    policy: &StepPolicy,
+    token: &CancellationToken,
+    completed_steps: usize,
+    orchestrator: &Orchestrator,
+) -> (
+    Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>>,
+    Vec<serde_json::Value>,
+) {
+    let mut attempts = Vec::new();
+    let max_attempts = policy.max_attempts();
+    let mut attempt = 0u32;
+    loop {
+        attempt += 1;
+        let started = std::time::Instant::now();
+        let call = orchestrator.execute_limited(skill, ctx, input.clone(), policy.timeout_ms);
+        let outcome = run_cancellable(token, completed_steps, call).await;
+        let elapsed_ms = started.elapsed().as_millis() as u64;
+        if !outcome.as_ref().is_err_and(|e| e.is::<GoalCancelled>()) {
+            orchestrator.stats.record(skill.name(), outcome.is_ok(), elapsed_ms, outcome.as_ref().err().map(|e| e.to_string()));
         }
-        (Some("CommunityScraper"), "ModelRouter") => {
-            let prompt = previous_result
-                .get("event")
-                .and_then(|v| v.as_str())
-                .unwrap_or("")
-                .to_string();
-            Some(serde_json::json!({ "prompt": prompt }))
+        match outcome {
+            Ok(value) => {
+                attempts.push(serde_json::json!({
+                    "attempt": attempt,
+                    "status": "ok",
+                    "elapsed_ms": elapsed_ms
+                }));
+                return (Ok(value), attempts);
+            }
+            Err(e) => {
+                if e.is::<GoalCancelled>() {
+                    attempts.push(serde_json::json!({
+                        "attempt": attempt,
+                        "status": "cancelled",
+                        "elapsed_ms": elapsed_ms
+                    }));
+                    return (Err(e), attempts);
+                }
+                let violation = e.downcast_ref::<SkillError>().map(SkillError::kind);
+                let status = match violation {
+                    Some("timeout") => "timeout",
+                    Some(_) => "limit_exceeded",
+                    None => "error",
+                };
+                attempts.push(serde_json::json!({
+                    "attempt": attempt,
+                    "status": status,
+                    "elapsed_ms": elapsed_ms,
+                    "error": e.to_string()
+                }));
+                if attempt >= max_attempts || status == "limit_exceeded" {
+                    return (Err(e), attempts);
+                }
+                tracing::warn!(
+                    target: "pagi::orchestrator",
+                    skill = skill.name(),
+                    attempt,
+                    max_attempts,
+                    error = %e,
+                    "Plan step attempt failed; retrying"
+                );
+                let backoff = async {
+                    tokio::time::sleep(policy.backoff_for(attempt)).await;
+                    Ok(())
+                };
+                if let Err(e) = run_cancellable(token, completed_steps, backoff).await {
+                    return (Err(e), attempts);
+                }
+            }
         }
-        _ if previous_result.is_null() => Some(fallback),
-        _ => Some(fallback),
     }
 }
+
+/// Derives the next step's payload from the previous step's output (or the intent context for
+/// the first step): the step's declarative `map` if set, else a built-in mapping for known skill
+/// pairs, else the input unchanged.
+fn chain_payload(
+    previous_skill: Option<&str>,
+    step: &PlanStep,
+    input: &serde_json::Value,
+) -> Option<serde_json::Value> {
+    let builtin;
+    let map = match &step.map {
+        Some(map) => map,
+        None => match previous_skill.and_then(|prev| mapping::builtin_map(prev, &step.skill)) {
+            Some(map) => {
+                builtin = map;
+                &builtin
+            }
+            None => return Some(input.clone()),
+        },
+    };
+    Some(mapping::apply_map(map, input))
+}
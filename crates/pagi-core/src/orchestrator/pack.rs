@@ -0,0 +1,543 @@
+//! Domain packs: installable bundles of blueprints, prompt templates, skill manifests, KB seed
+//! records and a default Ethos policy for one line of work (e.g. local-services sales, personal
+//! wellbeing), so a new agent can be cloned from a template instead of configured by hand.
+//!
+//! A pack is a single TOML (or `.json`) file, or a directory holding `pack.toml` / `pack.json`
+//! plus optionally `blueprint.json` (the `PAGI_BLUEPRINT_PATH` format) and
+//! `templates/persona.md` / `templates/auto_reply.md`:
+//!
+//! ```toml
+//! name = "local-services-sales"
+//! version = "1.2.0"
+//! description = "Lead intake and follow-up for a home-services business"
+//! requires = ["ModelRouter"]
+//!
+//! [blueprints]
+//! "qualify lead" = ["ParseInboundMessage", "ModelRouter"]
+//!
+//! [templates]
+//! persona = "You book jobs for a local plumbing company."
+//!
+//! [[skills]]
+//! slug = "quote_estimate"
+//! description = "Rough price range for a described job"
+//! schema = { job = "string" }
+//!
+//! [[kb]]
+//! slot = 3
+//! key = "pricing/{agent_id}"
+//! value = { callout_fee = 80 }
+//!
+//! [policy]
+//! forbidden_actions = ["write_sandbox_file"]
+//! ```
+//!
+//! [`PackRegistry::install`] applies a pack for one agent and records what it wrote in KB-5
+//! (`packs/{agent_id}/{name}`). Intents are global; templates are the agent's own, and seed keys
+//! may contain `{agent_id}`. Installing a newer version upgrades in place and reverts intents and
+//! KB keys the new version no longer ships; the same version is a no-op and an older one is
+//! refused ([`PackDowngrade`]) unless forced. Reverting a key restores what it held before the
+//! pack first wrote it, and an intent falls back to the blueprint file. Entries another installed
+//! pack also owns are left alone.
+
+use super::blueprint::normalize_intent;
+use super::{parse_blueprint, BlueprintRegistry, IntentSpec, PlanStep};
+use crate::keys;
+use crate::knowledge::{
+    agent_persona_key, auto_reply_template_key, KbType, KnowledgeStore, PolicyRecord, SkillRecord,
+    ETHOS_DEFAULT_POLICY_KEY,
+};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+use std::sync::Arc;
+
+/// Key prefix for installed packs in **KB_TECHNE** (`packs/{agent_id}/{name}`).
+pub const PACK_KB_PREFIX: &str = "packs/";
+
+/// A domain pack manifest (see the module docs for the file layout).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DomainPack {
+    pub name: String,
+    /// Dotted numeric version (`1`, `1.2`, `1.2.3`); compared to decide upgrades.
+    pub version: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Skills the gateway must register, beyond those the blueprints name.
+    #[serde(default)]
+    pub requires: Vec<String>,
+    /// Intent name → steps, in the `blueprint.json` intent format.
+    #[serde(default)]
+    pub blueprints: BTreeMap<String, serde_json::Value>,
+    #[serde(default)]
+    pub templates: PackTemplates,
+    /// Skill manifests written to KB-5 (`skills/{slug}`) for the planner and MCP clients.
+    #[serde(default)]
+    pub skills: Vec<SkillRecord>,
+    #[serde(default)]
+    pub kb: Vec<PackKbSeed>,
+    /// Replaces the KB_ETHOS default policy while the pack is installed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub policy: Option<PolicyRecord>,
+}
+
+/// Per-agent prompt templates (KB_PNEUMA).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PackTemplates {
+    /// Replaces `core_persona` in the agent's Mission Directive.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub persona: Option<String>,
+    /// Heartbeat auto-reply template (see `DEFAULT_AUTO_REPLY_TEMPLATE` for placeholders).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auto_reply: Option<String>,
+}
+
+/// A KB record seeded on install. String values are stored as UTF-8 text, anything else as JSON.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PackKbSeed {
+    pub slot: u8,
+    /// `{agent_id}` is replaced with the target agent.
+    pub key: String,
+    pub value: serde_json::Value,
+}
+
+/// A KB key written by an installed pack, with the value it replaced.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PackKbKey {
+    pub slot: u8,
+    pub key: String,
+    /// Value before the pack first wrote the key (restored when the pack lets go of it).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub previous: Option<Vec<u8>>,
+}
+
+/// What a pack installed for one agent (stored in KB-5).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InstalledPack {
+    pub name: String,
+    pub version: String,
+    pub agent_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub installed_at_ms: i64,
+    pub updated_at_ms: i64,
+    /// Normalized intent names the pack owns.
+    pub intents: Vec<String>,
+    pub kb: Vec<PackKbKey>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PackInstallStatus {
+    Installed,
+    Upgraded,
+    /// Same version installed again with `force`.
+    Reinstalled,
+    /// Older version installed with `force`.
+    Downgraded,
+    /// Same version already installed; nothing was written.
+    Unchanged,
+}
+
+/// Result of [`PackRegistry::install`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PackInstall {
+    pub name: String,
+    pub agent_id: String,
+    pub version: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub previous_version: Option<String>,
+    pub status: PackInstallStatus,
+    pub intents: Vec<String>,
+    /// Intents the previous version owned and this one does not (reverted).
+    pub removed_intents: Vec<String>,
+    pub kb_keys: usize,
+    pub removed_kb_keys: usize,
+}
+
+/// Refused install: an older version than the one installed, without `force`.
+#[derive(Debug)]
+pub struct PackDowngrade {
+    pub name: String,
+    pub installed: String,
+    pub requested: String,
+}
+
+impl fmt::Display for PackDowngrade {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "pack {} {} is installed; refusing to downgrade to {} without force",
+            self.name, self.installed, self.requested
+        )
+    }
+}
+
+impl std::error::Error for PackDowngrade {}
+
+/// Orders dotted numeric versions (`1.2` == `1.2.0`). None if either does not parse.
+pub fn compare_versions(a: &str, b: &str) -> Option<Ordering> {
+    let (a, b) = (parse_version(a)?, parse_version(b)?);
+    let len = a.len().max(b.len());
+    let part = |v: &[u64], i: usize| v.get(i).copied().unwrap_or(0);
+    Some((0..len).map(|i| part(&a, i).cmp(&part(&b, i))).find(|o| o.is_ne()).unwrap_or(Ordering::Equal))
+}
+
+fn parse_version(v: &str) -> Option<Vec<u64>> {
+    v.trim().split('.').map(|p| p.parse().ok()).collect()
+}
+
+impl DomainPack {
+    /// Loads a pack file (`.json` as JSON, anything else as TOML) or a pack directory.
+    pub fn load_path<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let path = path.as_ref();
+        if !path.is_dir() {
+            return Self::read_manifest(path);
+        }
+        let manifest = ["pack.toml", "pack.json"]
+            .iter()
+            .map(|f| path.join(f))
+            .find(|p| p.exists())
+            .ok_or_else(|| format!("{}: no pack.toml or pack.json", path.display()))?;
+        let mut pack = Self::read_manifest(&manifest)?;
+
+        let blueprint = path.join("blueprint.json");
+        if blueprint.exists() {
+            let text = std::fs::read_to_string(&blueprint).map_err(|e| format!("{}: {}", blueprint.display(), e))?;
+            parse_blueprint(&text).map_err(|e| format!("{}: {}", blueprint.display(), e))?;
+            let file: serde_json::Value = serde_json::from_str(&text)?;
+            for (intent, spec) in file["intents"].as_object().into_iter().flatten() {
+                let name = normalize_intent(intent);
+                if pack.blueprints.keys().any(|k| normalize_intent(k) == name) {
+                    return Err(format!("{}: intent \"{}\" is also defined in the manifest", blueprint.display(), name).into());
+                }
+                pack.blueprints.insert(intent.clone(), spec.clone());
+            }
+        }
+
+        let templates = path.join("templates");
+        for (file, template) in [
+            ("persona.md", &mut pack.templates.persona),
+            ("auto_reply.md", &mut pack.templates.auto_reply),
+        ] {
+            let file = templates.join(file);
+            if !file.exists() {
+                continue;
+            }
+            if template.is_some() {
+                return Err(format!("{}: template is also set in the manifest", file.display()).into());
+            }
+            *template = Some(std::fs::read_to_string(&file).map_err(|e| format!("{}: {}", file.display(), e))?);
+        }
+        Ok(pack)
+    }
+
+    fn read_manifest(path: &Path) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let text = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        let parsed = if path.extension().is_some_and(|ext| ext == "json") {
+            serde_json::from_str(&text).map_err(|e| e.to_string())
+        } else {
+            toml::from_str(&text).map_err(|e| e.to_string())
+        };
+        Ok(parsed.map_err(|e| format!("{}: {}", path.display(), e))?)
+    }
+
+    /// The pack's intents as (normalized name, steps).
+    pub fn plans(&self) -> Result<Vec<(String, Vec<PlanStep>)>, String> {
+        let mut plans: Vec<(String, Vec<PlanStep>)> = Vec::with_capacity(self.blueprints.len());
+        for (intent, spec) in &self.blueprints {
+            let name = normalize_intent(intent);
+            if name.is_empty() {
+                return Err("intent name must not be empty".to_string());
+            }
+            if plans.iter().any(|(n, _)| *n == name) {
+                return Err(format!("intent \"{}\" is defined twice", name));
+            }
+            let spec: IntentSpec =
+                serde_json::from_value(spec.clone()).map_err(|e| format!("intent \"{}\": {}", name, e))?;
+            plans.push((name, spec.into_steps()));
+        }
+        Ok(plans)
+    }
+
+    /// Everything that would stop an install. With `skills`, blueprint steps and `requires` must
+    /// name registered skills.
+    pub fn validate(&self, skills: Option<&[String]>) -> Vec<String> {
+        let mut issues = Vec::new();
+        if self.name.trim().is_empty() || self.name.contains('/') {
+            issues.push(format!("invalid pack name \"{}\"", self.name));
+        }
+        if parse_version(&self.version).is_none() {
+            issues.push(format!("invalid version \"{}\" (expected e.g. 1.2.0)", self.version));
+        }
+        let plans = self.plans().unwrap_or_else(|e| {
+            issues.push(e);
+            Vec::new()
+        });
+        if let Some(skills) = skills {
+            let mut unknown: Vec<&str> = self
+                .requires
+                .iter()
+                .map(String::as_str)
+                .chain(plans.iter().flat_map(|(_, steps)| steps.iter().flat_map(|s| s.candidates())))
+                .filter(|s| !skills.iter().any(|r| r == s))
+                .collect();
+            unknown.sort_unstable();
+            unknown.dedup();
+            if !unknown.is_empty() {
+                issues.push(format!("unregistered skills: {}", unknown.join(", ")));
+            }
+        }
+        for seed in &self.kb {
+            if !(1..=8).contains(&seed.slot) {
+                issues.push(format!("kb \"{}\": slot {} is not a KB slot (1-8)", seed.key, seed.slot));
+            }
+            if seed.key.trim().is_empty() {
+                issues.push("kb seed key must not be empty".to_string());
+            }
+        }
+        for skill in &self.skills {
+            if skill.slug.trim().is_empty() {
+                issues.push("skill manifest slug must not be empty".to_string());
+            }
+        }
+        issues
+    }
+
+    /// The KB records this pack writes for `agent_id`: templates, skill manifests, policy, seeds.
+    fn kb_writes(&self, agent_id: &str) -> Result<Vec<(u8, String, Vec<u8>)>, serde_json::Error> {
+        let pneuma = KbType::Pneuma.slot_id();
+        let mut writes = Vec::new();
+        if let Some(persona) = &self.templates.persona {
+            writes.push((pneuma, agent_persona_key(agent_id), persona.clone().into_bytes()));
+        }
+        if let Some(template) = &self.templates.auto_reply {
+            writes.push((pneuma, auto_reply_template_key(agent_id), template.clone().into_bytes()));
+        }
+        for skill in &self.skills {
+            writes.push((KbType::Techne.slot_id(), keys::skill_key(&skill.slug), serde_json::to_vec(skill)?));
+        }
+        if let Some(policy) = &self.policy {
+            writes.push((KbType::Ethos.slot_id(), ETHOS_DEFAULT_POLICY_KEY.to_string(), policy.to_bytes()));
+        }
+        for seed in &self.kb {
+            let bytes = match &seed.value {
+                serde_json::Value::String(s) => s.clone().into_bytes(),
+                other => serde_json::to_vec(other)?,
+            };
+            writes.push((seed.slot, seed.key.replace("{agent_id}", agent_id), bytes));
+        }
+        Ok(writes)
+    }
+}
+
+fn record_key(agent_id: &str, name: &str) -> String {
+    format!("{}{}/{}", PACK_KB_PREFIX, agent_id, name)
+}
+
+/// Installed domain packs, persisted in **KB_TECHNE**.
+#[derive(Clone)]
+pub struct PackRegistry {
+    store: Arc<KnowledgeStore>,
+}
+
+impl PackRegistry {
+    pub fn new(store: Arc<KnowledgeStore>) -> Self {
+        Self { store }
+    }
+
+    pub fn get(&self, agent_id: &str, name: &str) -> Option<InstalledPack> {
+        self.store
+            .get(KbType::Techne.slot_id(), &record_key(agent_id, name))
+            .ok()
+            .flatten()
+            .and_then(|b| serde_json::from_slice(&b).ok())
+    }
+
+    /// All installed packs, by agent then name.
+    pub fn list(&self) -> Result<Vec<InstalledPack>, sled::Error> {
+        let mut packs: Vec<InstalledPack> = self
+            .store
+            .scan_kv(KbType::Techne.slot_id())?
+            .into_iter()
+            .filter(|(k, _)| k.starts_with(PACK_KB_PREFIX))
+            .filter_map(|(_, bytes)| serde_json::from_slice(&bytes).ok())
+            .collect();
+        packs.sort_by(|a, b| (&a.agent_id, &a.name).cmp(&(&b.agent_id, &b.name)));
+        Ok(packs)
+    }
+
+    /// Installs or upgrades `pack` for `agent_id`. With `skills`, every skill the pack needs must
+    /// be registered. `force` reinstalls the same version or allows a downgrade.
+    pub fn install(
+        &self,
+        pack: &DomainPack,
+        agent_id: &str,
+        blueprint: &BlueprintRegistry,
+        skills: Option<&[String]>,
+        force: bool,
+    ) -> Result<PackInstall, Box<dyn std::error::Error + Send + Sync>> {
+        let issues = pack.validate(skills);
+        if !issues.is_empty() {
+            return Err(format!("pack {}: {}", pack.name, issues.join("; ")).into());
+        }
+        let existing = self.get(agent_id, &pack.name);
+        let status = match &existing {
+            None => PackInstallStatus::Installed,
+            Some(installed) => match compare_versions(&pack.version, &installed.version) {
+                Some(Ordering::Greater) => PackInstallStatus::Upgraded,
+                Some(Ordering::Equal) if force => PackInstallStatus::Reinstalled,
+                Some(Ordering::Less) | None if force => PackInstallStatus::Downgraded,
+                Some(Ordering::Equal) => {
+                    return Ok(PackInstall {
+                        name: installed.name.clone(),
+                        agent_id: agent_id.to_string(),
+                        version: installed.version.clone(),
+                        previous_version: Some(installed.version.clone()),
+                        status: PackInstallStatus::Unchanged,
+                        intents: installed.intents.clone(),
+                        removed_intents: Vec::new(),
+                        kb_keys: installed.kb.len(),
+                        removed_kb_keys: 0,
+                    });
+                }
+                _ => {
+                    return Err(Box::new(PackDowngrade {
+                        name: pack.name.clone(),
+                        installed: installed.version.clone(),
+                        requested: pack.version.clone(),
+                    }));
+                }
+            },
+        };
+
+        let plans = pack.plans()?;
+        let intents: Vec<String> = plans.iter().map(|(name, _)| name.clone()).collect();
+        blueprint.upsert_intents(plans)?;
+
+        // A key this install (or another pack) already owns keeps the value from before any pack.
+        let others = self.others(agent_id, &pack.name)?;
+        let mut kb = Vec::new();
+        for (slot, key, bytes) in pack.kb_writes(agent_id)? {
+            let owned = existing
+                .iter()
+                .chain(&others)
+                .flat_map(|p| &p.kb)
+                .find(|k| k.slot == slot && k.key == key);
+            let previous = match owned {
+                Some(owned) => owned.previous.clone(),
+                None => self.store.get(slot, &key)?,
+            };
+            self.store.insert(slot, &key, &bytes)?;
+            kb.push(PackKbKey { slot, key, previous });
+        }
+
+        let (mut removed_intents, mut removed_kb_keys) = (Vec::new(), 0);
+        if let Some(old) = &existing {
+            let stale_intents: Vec<String> = old.intents.iter().filter(|i| !intents.contains(i)).cloned().collect();
+            let stale_keys: Vec<PackKbKey> = old
+                .kb
+                .iter()
+                .filter(|o| !kb.iter().any(|k| k.slot == o.slot && k.key == o.key))
+                .cloned()
+                .collect();
+            removed_kb_keys = stale_keys.len();
+            self.release(agent_id, &pack.name, &stale_intents, &stale_keys, blueprint)?;
+            removed_intents = stale_intents;
+        }
+
+        let now = self.store.now_ms();
+        let record = InstalledPack {
+            name: pack.name.clone(),
+            version: pack.version.clone(),
+            agent_id: agent_id.to_string(),
+            description: pack.description.clone(),
+            installed_at_ms: existing.as_ref().map_or(now, |e| e.installed_at_ms),
+            updated_at_ms: now,
+            intents: intents.clone(),
+            kb,
+        };
+        self.store.insert(
+            KbType::Techne.slot_id(),
+            &record_key(agent_id, &pack.name),
+            &serde_json::to_vec(&record)?,
+        )?;
+        tracing::info!(
+            target: "pagi::orchestrator",
+            pack = %pack.name,
+            version = %pack.version,
+            agent_id = %agent_id,
+            status = ?status,
+            "Domain pack installed"
+        );
+        Ok(PackInstall {
+            name: record.name,
+            agent_id: record.agent_id,
+            version: record.version,
+            previous_version: existing.map(|e| e.version),
+            status,
+            intents,
+            removed_intents,
+            kb_keys: record.kb.len(),
+            removed_kb_keys,
+        })
+    }
+
+    /// Reverts everything the pack installed for `agent_id`. Returns the removed record, or None
+    /// if it was not installed.
+    pub fn uninstall(
+        &self,
+        agent_id: &str,
+        name: &str,
+        blueprint: &BlueprintRegistry,
+    ) -> Result<Option<InstalledPack>, Box<dyn std::error::Error + Send + Sync>> {
+        let Some(installed) = self.get(agent_id, name) else {
+            return Ok(None);
+        };
+        self.release(agent_id, name, &installed.intents, &installed.kb, blueprint)?;
+        self.store.remove(KbType::Techne.slot_id(), &record_key(agent_id, name))?;
+        tracing::info!(target: "pagi::orchestrator", pack = %name, agent_id = %agent_id, "Domain pack uninstalled");
+        Ok(Some(installed))
+    }
+
+    fn others(&self, agent_id: &str, name: &str) -> Result<Vec<InstalledPack>, sled::Error> {
+        Ok(self
+            .list()?
+            .into_iter()
+            .filter(|p| !(p.agent_id == agent_id && p.name == name))
+            .collect())
+    }
+
+    /// Reverts intents and KB keys no longer owned by (agent_id, name), skipping anything another
+    /// installed pack still owns.
+    fn release(
+        &self,
+        agent_id: &str,
+        name: &str,
+        intents: &[String],
+        keys: &[PackKbKey],
+        blueprint: &BlueprintRegistry,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let others = self.others(agent_id, name)?;
+        for intent in intents {
+            if !others.iter().any(|p| p.intents.contains(intent)) {
+                blueprint.revert_intent(intent)?;
+            }
+        }
+        for owned in keys {
+            if others.iter().flat_map(|p| &p.kb).any(|k| k.slot == owned.slot && k.key == owned.key) {
+                continue;
+            }
+            match &owned.previous {
+                Some(bytes) => self.store.insert(owned.slot, &owned.key, bytes)?,
+                None => self.store.remove(owned.slot, &owned.key)?,
+            };
+        }
+        Ok(())
+    }
+}
@@ -1,6 +1,20 @@
 //! Dynamic skill planning: delegates to BlueprintRegistry (default or loaded from config).
+//!
+//! When an intent has no blueprint entry, the orchestrator falls back to an LLM-synthesized plan:
+//! the ModelRouter is prompted with the registered skills and the KB-5 (Techne) skill manifests,
+//! the proposed skill names are validated against the registry, and the accepted plan is stored
+//! back in Techne under `blueprint_candidates/{intent}` for later review/promotion.
 
-use super::blueprint::{BlueprintRegistry, Plan};
+use super::blueprint::{BlueprintRegistry, Plan, PlanStep};
+use super::stats::SkillStats;
+use crate::knowledge::{KbType, KnowledgeStore, SkillRecord};
+use std::fmt;
+
+/// KB-5 key prefix for LLM-generated plans awaiting review.
+pub const BLUEPRINT_CANDIDATE_PREFIX: &str = "blueprint_candidates/";
+
+/// Upper bound on synthesized plan length (guards against runaway LLM output).
+pub const MAX_SYNTHESIZED_STEPS: usize = 8;
 
 /// Returns a plan for the given intent using the default blueprint, or None if unknown.
 #[allow(dead_code)]
@@ -8,6 +22,137 @@ pub fn plan_for_intent(intent: &str) -> Option<Plan> {
     BlueprintRegistry::default_blueprint().plan_for_intent(intent)
 }
 
+/// Why a synthesized plan was rejected.
+#[derive(Debug)]
+pub struct InvalidPlan(pub String);
+
+impl fmt::Display for InvalidPlan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid synthesized plan: {}", self.0)
+    }
+}
+
+impl std::error::Error for InvalidPlan {}
+
+/// Builds the planning prompt sent to the ModelRouter for an unknown intent. Recent execution
+/// stats of the available skills are included so the planner can avoid failing ones.
+pub fn build_planning_prompt(
+    intent: &str,
+    context: Option<&serde_json::Value>,
+    skill_names: &[String],
+    manifests: &[SkillRecord],
+    stats: &[SkillStats],
+) -> String {
+    let mut prompt = String::new();
+    prompt.push_str("You are the PAGI planner. Compose an ordered chain of skills that fulfils the intent.\n");
+    prompt.push_str("Reply with ONLY a JSON array of skill names, e.g. [\"SkillA\", \"SkillB\"].\n");
+    prompt.push_str(&format!("Use at most {} steps and only the skills listed below.\n\n", MAX_SYNTHESIZED_STEPS));
+    prompt.push_str(&format!("Intent: {}\n", intent));
+    if let Some(ctx) = context.filter(|c| !c.is_null()) {
+        prompt.push_str(&format!("Context: {}\n", ctx));
+    }
+    prompt.push_str("\nAvailable skills:\n");
+    for name in skill_names {
+        prompt.push_str(&format!("- {}\n", name));
+    }
+    if !manifests.is_empty() {
+        prompt.push_str("\nSkill manifests (KB-5):\n");
+        for m in manifests {
+            prompt.push_str(&format!("- {}: {} (args: {})\n", m.slug, m.description, m.schema));
+        }
+    }
+    let health: Vec<String> = stats
+        .iter()
+        .filter(|s| skill_names.contains(&s.skill))
+        .filter_map(|s| {
+            let rate = s.success_rate()? * 100.0;
+            let latency = s.avg_latency_ms().unwrap_or(0);
+            Some(match s.is_unhealthy() {
+                true => format!("- {}: UNHEALTHY ({:.0}% success recently), avoid unless no alternative\n", s.skill, rate),
+                false => format!("- {}: {:.0}% success, avg {} ms\n", s.skill, rate, latency),
+            })
+        })
+        .collect();
+    if !health.is_empty() {
+        prompt.push_str("\nSkill health (recent calls):\n");
+        prompt.push_str(&health.concat());
+    }
+    prompt
+}
+
+/// Extracts the proposed skill names from the LLM reply: the first JSON array in the text,
+/// or an object with a `steps` array. Markdown fences and surrounding prose are tolerated.
+pub fn parse_plan_response(text: &str) -> Option<Vec<String>> {
+    let start = text.find(['[', '{'])?;
+    let slice = &text[start..];
+    let value = serde_json::Deserializer::from_str(slice)
+        .into_iter::<serde_json::Value>()
+        .next()?
+        .ok()?;
+    let steps = match &value {
+        serde_json::Value::Array(a) => a,
+        serde_json::Value::Object(o) => o.get("steps")?.as_array()?,
+        _ => return None,
+    };
+    let names: Vec<String> = steps
+        .iter()
+        .filter_map(|s| match s {
+            serde_json::Value::String(name) => Some(name.trim().to_string()),
+            serde_json::Value::Object(o) => o.get("skill").and_then(|v| v.as_str()).map(|n| n.trim().to_string()),
+            _ => None,
+        })
+        .filter(|n| !n.is_empty())
+        .collect();
+    Some(names)
+}
+
+/// Validates proposed names against the registered skills (case-insensitive, mapped to the
+/// registered spelling). Every step must resolve; empty or oversized plans are rejected.
+pub fn validate_plan(proposed: &[String], skill_names: &[String]) -> Result<Plan, InvalidPlan> {
+    if proposed.is_empty() {
+        return Err(InvalidPlan("no steps proposed".into()));
+    }
+    if proposed.len() > MAX_SYNTHESIZED_STEPS {
+        return Err(InvalidPlan(format!(
+            "{} steps exceeds the limit of {}",
+            proposed.len(),
+            MAX_SYNTHESIZED_STEPS
+        )));
+    }
+    let mut steps = Vec::with_capacity(proposed.len());
+    for name in proposed {
+        let registered = skill_names
+            .iter()
+            .find(|s| s.eq_ignore_ascii_case(name))
+            .ok_or_else(|| InvalidPlan(format!("unregistered skill: {}", name)))?;
+        steps.push(PlanStep::new(registered.clone()));
+    }
+    Ok(Plan { steps })
+}
+
+/// Stores a synthesized plan in KB-5 (Techne) as a blueprint candidate. Returns the key.
+pub fn store_blueprint_candidate(
+    store: &KnowledgeStore,
+    intent: &str,
+    plan: &Plan,
+) -> Result<String, sled::Error> {
+    let key = format!("{}{}", BLUEPRINT_CANDIDATE_PREFIX, intent.trim().to_lowercase());
+    let created_at_ms = store.now_ms();
+    let record = serde_json::json!({
+        "intent": intent,
+        "steps": plan.skill_names(),
+        "source": "llm",
+        "status": "candidate",
+        "created_at_ms": created_at_ms,
+    });
+    store.insert(
+        KbType::Techne.slot_id(),
+        &key,
+        serde_json::to_vec(&record).unwrap_or_default().as_slice(),
+    )?;
+    Ok(key)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -25,4 +170,40 @@ mod tests {
     fn plan_unknown_intent() {
         assert!(plan_for_intent("unknown intent").is_none());
     }
+
+    #[test]
+    fn parse_plan_response_tolerates_prose_and_objects() {
+        let text = "Sure! Here is the plan:\n```json\n[\"DraftResponse\", \"ModelRouter\"]\n```";
+        assert_eq!(
+            parse_plan_response(text).unwrap(),
+            vec!["DraftResponse".to_string(), "ModelRouter".to_string()]
+        );
+        let text = r#"{"steps": [{"skill": "CommunityScraper"}, "ModelRouter"]}"#;
+        assert_eq!(parse_plan_response(text).unwrap().len(), 2);
+        assert!(parse_plan_response("no plan here").is_none());
+    }
+
+    #[test]
+    fn validate_plan_maps_case_and_rejects_unknown() {
+        let registered = vec!["DraftResponse".to_string(), "ModelRouter".to_string()];
+        let plan = validate_plan(&["draftresponse".into(), "ModelRouter".into()], &registered).unwrap();
+        assert_eq!(plan.steps, ["DraftResponse", "ModelRouter"]);
+        assert!(validate_plan(&["RmRf".into()], &registered).is_err());
+        assert!(validate_plan(&[], &registered).is_err());
+    }
+
+    #[test]
+    fn planning_prompt_flags_unhealthy_skills() {
+        let registry = super::super::SkillStatsRegistry::default();
+        for _ in 0..5 {
+            registry.record("CommunityScraper", false, 40, Some("timeout".into()));
+            registry.record("ModelRouter", true, 120, None);
+        }
+        registry.record("Unlisted", true, 1, None);
+        let names = vec!["CommunityScraper".to_string(), "ModelRouter".to_string()];
+        let prompt = build_planning_prompt("research", None, &names, &[], &registry.all());
+        assert!(prompt.contains("- CommunityScraper: UNHEALTHY (0% success recently)"));
+        assert!(prompt.contains("- ModelRouter: 100% success, avg 120 ms"));
+        assert!(!prompt.contains("Unlisted"));
+    }
 }
@@ -0,0 +1,380 @@
+//! Dry-run plan previews: what `dispatch` would do for a goal, without calling any skill.
+//!
+//! [`Orchestrator::plan_preview`] resolves the blueprint, checks that every step's skill exists
+//! (and is enabled for the tenant), evaluates `when` guards that only read the intent context,
+//! runs the KB-6 Ethos policy (rule set or legacy keywords) against each intended payload, and estimates chained payloads.
+//! Inputs that depend on an earlier step's output are shown with placeholders such as
+//! `<DraftResponse.draft>`. Sub-plan steps are previewed as nested `AutonomousGoal` previews.
+
+use super::blueprint::{normalize_intent, MAX_SUB_PLAN_DEPTH};
+use super::{chain_payload, mapping, Orchestrator, Plan, PlanStep, DEFAULT_MAX_TOOL_ITERATIONS, MAX_TOOL_ITERATIONS};
+use crate::knowledge::{ActiveEthosPolicy, RuleAction};
+use crate::shared::{Goal, TenantContext};
+use serde::Serialize;
+use std::sync::atomic::Ordering;
+
+/// Would-be execution of one goal.
+#[derive(Debug, Clone, Serialize)]
+pub struct PlanPreview {
+    pub goal: &'static str,
+    /// True when no issue was found here or in any sub-goal.
+    pub valid: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub intent: Option<String>,
+    /// `blueprint` or `llm` (AutonomousGoal only).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub plan_source: Option<&'static str>,
+    pub steps: Vec<PreviewStep>,
+    /// Previews of `Sequence` / `Parallel` sub-goals and of sub-plan steps.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub sub_goals: Vec<PlanPreview>,
+    /// Problems that would make dispatch fail or be blocked.
+    pub issues: Vec<String>,
+    /// Informational remarks (e.g. the plan would be synthesized at dispatch time).
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub notes: Vec<String>,
+}
+
+/// One step the goal would run.
+#[derive(Debug, Clone, Serialize)]
+pub struct PreviewStep {
+    pub skill: String,
+    /// `would_run`, `would_skip`, or `depends` (the guard reads an earlier step's output).
+    pub status: &'static str,
+    /// The skill resolves for the tenant.
+    pub available: bool,
+    /// Input the skill would receive (None for skipped steps).
+    pub input: Option<serde_json::Value>,
+    /// True when `input` contains placeholders for earlier step outputs.
+    pub input_estimated: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub branch: Option<serde_json::Value>,
+    /// KB-6 policy result (None when no policy is stored).
+    pub ethos: Option<EthosCheck>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EthosCheck {
+    pub pass: bool,
+    pub action: RuleAction,
+    /// Rule that decided (rule sets only).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rule_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+impl PlanPreview {
+    fn new(goal: &Goal) -> Self {
+        Self {
+            goal: goal.kind(),
+            valid: true,
+            intent: None,
+            plan_source: None,
+            steps: Vec::new(),
+            sub_goals: Vec::new(),
+            issues: Vec::new(),
+            notes: Vec::new(),
+        }
+    }
+
+    /// Nested preview for a sub-plan step.
+    fn sub_plan(intent: &str) -> Self {
+        Self {
+            goal: "AutonomousGoal",
+            valid: true,
+            intent: Some(intent.to_string()),
+            plan_source: Some("blueprint"),
+            steps: Vec::new(),
+            sub_goals: Vec::new(),
+            issues: Vec::new(),
+            notes: Vec::new(),
+        }
+    }
+}
+
+/// The Ethos decision for one intended call (same evaluation as the orchestrator's gate for a direct call).
+fn ethos_check(policy: &ActiveEthosPolicy, skill: &str, input: Option<&serde_json::Value>) -> EthosCheck {
+    let decision = policy.evaluate(skill, input);
+    EthosCheck {
+        pass: decision.is_allowed(),
+        action: decision.action,
+        rule_id: decision.rule_id,
+        reason: decision.reason,
+    }
+}
+
+/// Estimated input for a step that follows `previous`, whose output is unknown before dispatch.
+fn estimated_input(previous: &str, step: &PlanStep) -> serde_json::Value {
+    match step.map.clone().or_else(|| mapping::builtin_map(previous, &step.skill)) {
+        Some(map) => mapping::describe_map(&map, previous),
+        None => serde_json::Value::String(mapping::placeholder(previous, "$")),
+    }
+}
+
+impl Orchestrator {
+    /// Previews `goal` without executing it: the resolved plan, skill availability, Ethos results,
+    /// and estimated payloads. Unknown intents report `plan_source: "llm"` without calling the
+    /// planner.
+    pub fn plan_preview(&self, ctx: &TenantContext, goal: &Goal) -> PlanPreview {
+        let policy = self.knowledge.as_ref().and_then(|k| k.active_ethos_policy());
+        let mut preview = self.preview_goal(ctx, goal, policy.as_ref());
+        if !self.skills_enabled.load(Ordering::Acquire) {
+            preview
+                .issues
+                .push("skills execution is disabled by the control panel".to_string());
+            preview.valid = false;
+        }
+        preview
+    }
+
+    fn preview_goal(&self, ctx: &TenantContext, goal: &Goal, policy: Option<&ActiveEthosPolicy>) -> PlanPreview {
+        let mut preview = PlanPreview::new(goal);
+        let exact = |preview: &mut PlanPreview, skill: &str, input: Option<serde_json::Value>| {
+            self.preview_step(ctx, preview, policy, skill, "would_run", input, false, None);
+        };
+        match goal {
+            Goal::ExecuteSkill { name, payload } => exact(&mut preview, name, payload.clone()),
+            Goal::QueryKnowledge { slot_id, query } => {
+                if self.pagi_kb_active(*slot_id) {
+                    let payload = serde_json::json!({ "slot_id": slot_id, "query_key": query });
+                    exact(&mut preview, "KnowledgeQuery", Some(payload));
+                } else {
+                    preview.notes.push(format!("KB-{} is disabled by the control panel", slot_id));
+                }
+            }
+            Goal::IngestData { payload } => exact(&mut preview, "LeadCapture", payload.clone()),
+            Goal::AssembleContext { context_id } => {
+                exact(&mut preview, "DraftResponse", Some(serde_json::json!({ "lead_id": context_id })));
+            }
+            Goal::GenerateFinalResponse { context_id } => {
+                exact(&mut preview, "DraftResponse", Some(serde_json::json!({ "lead_id": context_id })));
+                let prompt = mapping::placeholder("DraftResponse", "$.draft");
+                let input = Some(serde_json::json!({ "prompt": prompt }));
+                self.preview_step(ctx, &mut preview, policy, "ModelRouter", "would_run", input, true, None);
+            }
+            Goal::AutonomousGoal { intent, context } => {
+                self.preview_autonomous(ctx, &mut preview, policy, intent, context.as_ref());
+            }
+            Goal::UpdateKnowledgeSlot {
+                slot_id,
+                source_url,
+                source_html,
+            } => {
+                if self.pagi_kb_active(*slot_id) {
+                    let mut payload = serde_json::json!({ "slot_id": slot_id });
+                    if let Some(url) = source_url {
+                        payload["url"] = serde_json::json!(url);
+                    }
+                    if let Some(html) = source_html {
+                        payload["html"] = serde_json::json!(html);
+                    }
+                    exact(&mut preview, "CommunityScraper", Some(payload));
+                } else {
+                    preview.notes.push(format!("KB-{} is disabled by the control panel", slot_id));
+                }
+            }
+            Goal::ToolChat { max_iterations, .. } => {
+                let iterations = max_iterations
+                    .unwrap_or(DEFAULT_MAX_TOOL_ITERATIONS)
+                    .clamp(1, MAX_TOOL_ITERATIONS);
+                exact(&mut preview, "ModelRouter", None);
+                preview.notes.push(format!(
+                    "the model may call the tenant's KB-5 skills as tools for up to {} turns",
+                    iterations
+                ));
+            }
+            Goal::MemoryOp { .. } | Goal::Custom(_) => {}
+            Goal::Sequence(goals) | Goal::Parallel(goals) => {
+                preview.sub_goals = goals.iter().map(|g| self.preview_goal(ctx, g, policy)).collect();
+            }
+        }
+        preview.valid = preview.issues.is_empty() && preview.sub_goals.iter().all(|g| g.valid);
+        preview
+    }
+
+    fn preview_autonomous(
+        &self,
+        ctx: &TenantContext,
+        preview: &mut PlanPreview,
+        policy: Option<&ActiveEthosPolicy>,
+        intent: &str,
+        context: Option<&serde_json::Value>,
+    ) {
+        preview.intent = Some(intent.to_string());
+        let Some(plan) = self.blueprint.plan_for_intent(intent) else {
+            preview.plan_source = Some("llm");
+            match self.skill(ctx, "ModelRouter") {
+                Ok(_) => preview
+                    .notes
+                    .push("no blueprint entry; the dynamic planner would synthesize a plan at dispatch".to_string()),
+                Err(_) => preview
+                    .issues
+                    .push(format!("unknown intent: {} (no blueprint entry and ModelRouter unavailable)", intent)),
+            }
+            return;
+        };
+        preview.plan_source = Some("blueprint");
+        let known = Some(context.cloned().unwrap_or(serde_json::json!({})));
+        self.preview_plan(ctx, preview, policy, &plan, known, &mut vec![normalize_intent(intent)]);
+    }
+
+    /// Previews `plan`'s steps into `preview`; `known` is the plan input when it can be computed
+    /// before dispatch. Sub-plan steps recurse into nested previews (`chain` detects cycles).
+    fn preview_plan(
+        &self,
+        ctx: &TenantContext,
+        preview: &mut PlanPreview,
+        policy: Option<&ActiveEthosPolicy>,
+        plan: &Plan,
+        mut known: Option<serde_json::Value>,
+        chain: &mut Vec<String>,
+    ) {
+        // The payload is known until the first step that may run; after that it is that step's output.
+        let mut previous: Option<String> = None;
+        for step in &plan.steps {
+            let (status, branch) = match (&step.when, &known) {
+                (None, _) => ("would_run", None),
+                (Some(cond), Some(payload)) => {
+                    let actual = cond.lookup(payload).cloned();
+                    let taken = actual.as_ref() == Some(&cond.equals);
+                    let status = if taken { "would_run" } else { "would_skip" };
+                    let branch = serde_json::json!({
+                        "path": cond.path,
+                        "equals": cond.equals,
+                        "actual": actual,
+                        "taken": taken
+                    });
+                    (status, Some(branch))
+                }
+                (Some(cond), None) => {
+                    let branch = serde_json::json!({ "path": cond.path, "equals": cond.equals, "taken": null });
+                    ("depends", Some(branch))
+                }
+            };
+            let (input, estimated) = match (status, &known, previous.as_deref()) {
+                ("would_skip", _, _) => (None, false),
+                (_, Some(payload), prev) => (chain_payload(prev, step, payload), false),
+                (_, None, Some(prev)) => (Some(estimated_input(prev, step)), true),
+                (_, None, None) => (None, true),
+            };
+            match &step.intent {
+                Some(sub_intent) => {
+                    let sub_input = (!estimated).then(|| input.clone().unwrap_or_default());
+                    let available = self.preview_sub_plan(ctx, preview, policy, sub_intent, sub_input, chain);
+                    preview.steps.push(PreviewStep {
+                        skill: step.label(),
+                        status,
+                        available,
+                        input,
+                        input_estimated: estimated,
+                        branch,
+                        ethos: None,
+                    });
+                }
+                None => self.preview_step(ctx, preview, policy, &step.skill, status, input, estimated, branch),
+            }
+            if status != "would_skip" {
+                known = None;
+                previous = Some(step.label());
+            }
+        }
+    }
+
+    /// Adds the nested preview of a sub-plan step to `preview.sub_goals`. Returns false (with an
+    /// issue on `preview`) when the sub-plan cannot be expanded.
+    fn preview_sub_plan(
+        &self,
+        ctx: &TenantContext,
+        preview: &mut PlanPreview,
+        policy: Option<&ActiveEthosPolicy>,
+        intent: &str,
+        input: Option<serde_json::Value>,
+        chain: &mut Vec<String>,
+    ) -> bool {
+        let name = normalize_intent(intent);
+        let problem = if chain.contains(&name) {
+            Some(format!("cycle ({} -> {})", chain.join(" -> "), name))
+        } else if chain.len() > MAX_SUB_PLAN_DEPTH {
+            Some(format!("nesting deeper than {} levels", MAX_SUB_PLAN_DEPTH))
+        } else {
+            None
+        };
+        let plan = match (problem, self.blueprint.plan_for_intent(&name)) {
+            (None, Some(plan)) => plan,
+            (problem, _) => {
+                let reason = problem.unwrap_or_else(|| "no blueprint entry".to_string());
+                preview.issues.push(format!("sub-plan intent {}: {}", name, reason));
+                return false;
+            }
+        };
+        let mut nested = PlanPreview::sub_plan(&name);
+        chain.push(name);
+        self.preview_plan(ctx, &mut nested, policy, &plan, input, chain);
+        chain.pop();
+        nested.valid = nested.issues.is_empty() && nested.sub_goals.iter().all(|g| g.valid);
+        preview.sub_goals.push(nested);
+        true
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn preview_step(
+        &self,
+        ctx: &TenantContext,
+        preview: &mut PlanPreview,
+        policy: Option<&ActiveEthosPolicy>,
+        skill: &str,
+        status: &'static str,
+        input: Option<serde_json::Value>,
+        input_estimated: bool,
+        branch: Option<serde_json::Value>,
+    ) {
+        let available = match self.skill(ctx, skill) {
+            Ok(_) => true,
+            Err(e) => {
+                preview.issues.push(format!("{}: {}", skill, e));
+                false
+            }
+        };
+        let ethos = policy.map(|p| ethos_check(p, skill, input.as_ref()));
+        if let Some(EthosCheck { pass: false, reason, .. }) = &ethos {
+            if status != "would_skip" {
+                let reason = reason.as_deref().unwrap_or("policy violation");
+                preview.issues.push(format!("{}: blocked by Ethos: {}", skill, reason));
+            }
+        }
+        preview.steps.push(PreviewStep {
+            skill: skill.to_string(),
+            status,
+            available,
+            input,
+            input_estimated,
+            branch,
+            ethos,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn estimated_input_uses_map_builtin_or_passthrough() {
+        let step = PlanStep::new("ModelRouter");
+        assert_eq!(
+            estimated_input("DraftResponse", &step),
+            serde_json::json!({ "prompt": "<DraftResponse.draft>" })
+        );
+        assert_eq!(estimated_input("Echo", &step), serde_json::json!("<Echo>"));
+    }
+
+    #[test]
+    fn ethos_scans_content_field_first() {
+        let policy = ActiveEthosPolicy::Legacy(crate::knowledge::PolicyRecord::default());
+        let input = serde_json::json!({ "content": "hello", "note": "password" });
+        assert!(ethos_check(&policy, "Echo", Some(&input)).pass);
+        let input = serde_json::json!({ "note": "password" });
+        assert!(!ethos_check(&policy, "Echo", Some(&input)).pass);
+    }
+}
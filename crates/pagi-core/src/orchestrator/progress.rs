@@ -0,0 +1,89 @@
+//! Live plan progress: one event per plan step as an `AutonomousGoal` runs.
+//!
+//! The orchestrator publishes on a broadcast channel ([`Orchestrator::subscribe_progress`]);
+//! subscribers filter by `correlation_id`. Events are dropped when nobody is listening, and a
+//! slow subscriber lags rather than slowing the plan down.
+//!
+//! [`Orchestrator::subscribe_progress`]: super::Orchestrator::subscribe_progress
+
+use crate::shared::TenantContext;
+use serde::Serialize;
+
+/// Buffered events per subscriber before it starts lagging.
+pub const PROGRESS_CHANNEL_CAPACITY: usize = 256;
+
+/// State a plan step moved into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StepStatus {
+    Started,
+    Completed,
+    Failed,
+    /// The step's `when` condition did not match.
+    Skipped,
+    Cancelled,
+    /// The Ethos policy stopped the step's input (see [`OnBlocked`](super::OnBlocked)).
+    Blocked,
+}
+
+/// A plan step changing state.
+#[derive(Debug, Clone, Serialize)]
+pub struct StepProgress {
+    /// Correlation id of the dispatch (absent for requests submitted without one).
+    pub correlation_id: Option<String>,
+    pub agent_id: String,
+    /// Intent whose plan holds the step (the sub-plan's intent for nested steps).
+    pub intent: String,
+    /// 1 for the top-level plan, +1 per sub-plan level.
+    pub depth: usize,
+    /// Position of the step within its plan (0-based) and the plan's length.
+    pub index: usize,
+    pub total: usize,
+    /// Step label: the skill name, or `intent:<name>` for a sub-plan step.
+    pub step: String,
+    pub status: StepStatus,
+    /// Skill that actually ran, when a fallback replaced the step's primary skill.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub executed_skill: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// When the orchestrator reported the event (by the store's clock when one is attached).
+    pub timestamp_ms: i64,
+}
+
+impl StepProgress {
+    /// A `Started` event for step `index` of `total` in `intent`'s plan.
+    pub(super) fn started(ctx: &TenantContext, intent: &str, depth: usize, index: usize, total: usize, step: String) -> Self {
+        Self {
+            correlation_id: ctx.correlation_id.clone(),
+            agent_id: ctx.resolved_agent_id().to_string(),
+            intent: intent.to_string(),
+            depth,
+            index,
+            total,
+            step,
+            status: StepStatus::Started,
+            executed_skill: None,
+            error: None,
+            timestamp_ms: 0,
+        }
+    }
+
+    /// The same step moved into `status`.
+    pub(super) fn with_status(&self, status: StepStatus) -> Self {
+        Self {
+            status,
+            ..self.clone()
+        }
+    }
+
+    pub(super) fn with_executed_skill(mut self, skill: impl Into<String>) -> Self {
+        self.executed_skill = Some(skill.into());
+        self
+    }
+
+    pub(super) fn with_error(mut self, error: impl ToString) -> Self {
+        self.error = Some(error.to_string());
+        self
+    }
+}
@@ -0,0 +1,330 @@
+//! Dispatch admission: bounded concurrency, a bounded wait queue, and per-tenant rate limits.
+//!
+//! When a [`DispatchQueue`] is attached, every top-level dispatch first takes a token from the
+//! tenant's bucket (refilled at `per_minute`, holding up to `burst`), then waits for one of
+//! `max_concurrent` execution slots. Requests that exceed the tenant's budget, or arrive while
+//! `max_queued` requests are already waiting, fail fast with [`Throttled`] and a retry-after hint.
+
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+/// Token bucket limits for one tenant.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct RateLimit {
+    /// Sustained requests per minute.
+    pub per_minute: u32,
+    /// Requests allowed in a burst (bucket capacity).
+    pub burst: u32,
+}
+
+impl RateLimit {
+    /// Parses `per_minute` or `per_minute/burst` (burst defaults to `per_minute`, at least 1).
+    pub fn parse(s: &str) -> Option<Self> {
+        let (rate, burst) = match s.trim().split_once('/') {
+            Some((rate, burst)) => (rate.trim().parse().ok()?, Some(burst.trim().parse().ok()?)),
+            None => (s.trim().parse().ok()?, None),
+        };
+        if rate == 0 {
+            return None;
+        }
+        Some(Self {
+            per_minute: rate,
+            burst: burst.unwrap_or(rate).max(1),
+        })
+    }
+}
+
+/// Queue configuration.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DispatchQueueConfig {
+    /// Goals executing at once; further admitted requests wait in the queue.
+    pub max_concurrent: usize,
+    /// Requests allowed to wait for a slot before new ones are rejected.
+    pub max_queued: usize,
+    /// Limit for tenants without an entry in `tenant_limits` (None = unlimited).
+    pub default_limit: Option<RateLimit>,
+    pub tenant_limits: HashMap<String, RateLimit>,
+}
+
+impl Default for DispatchQueueConfig {
+    fn default() -> Self {
+        Self {
+            max_concurrent: 16,
+            max_queued: 64,
+            default_limit: None,
+            tenant_limits: HashMap::new(),
+        }
+    }
+}
+
+impl DispatchQueueConfig {
+    /// Reads `PAGI_MAX_CONCURRENT_GOALS`, `PAGI_MAX_QUEUED_GOALS`, `PAGI_TENANT_RATE_LIMIT`
+    /// (`per_minute[/burst]`) and `PAGI_TENANT_RATE_LIMITS` (`tenant=per_minute[/burst],...`).
+    pub fn from_env() -> Self {
+        let mut config = Self::default();
+        let parse = |key: &str| std::env::var(key).ok().and_then(|v| v.trim().parse::<usize>().ok());
+        if let Some(n) = parse("PAGI_MAX_CONCURRENT_GOALS") {
+            config.max_concurrent = n.max(1);
+        }
+        if let Some(n) = parse("PAGI_MAX_QUEUED_GOALS") {
+            config.max_queued = n;
+        }
+        if let Ok(v) = std::env::var("PAGI_TENANT_RATE_LIMIT") {
+            config.default_limit = RateLimit::parse(&v);
+        }
+        if let Ok(v) = std::env::var("PAGI_TENANT_RATE_LIMITS") {
+            config.tenant_limits = parse_tenant_limits(&v);
+        }
+        config
+    }
+
+    fn limit_for(&self, tenant_id: &str) -> Option<RateLimit> {
+        self.tenant_limits.get(tenant_id).copied().or(self.default_limit)
+    }
+}
+
+/// `acme=120/20,beta=30` → per-tenant limits (malformed entries are skipped).
+fn parse_tenant_limits(s: &str) -> HashMap<String, RateLimit> {
+    s.split(',')
+        .filter_map(|entry| {
+            let (tenant, limit) = entry.split_once('=')?;
+            Some((tenant.trim().to_string(), RateLimit::parse(limit)?))
+        })
+        .filter(|(tenant, _)| !tenant.is_empty())
+        .collect()
+}
+
+/// A request was rejected by admission control.
+#[derive(Debug)]
+pub struct Throttled {
+    /// `rate_limited` (tenant over budget) or `queue_full`.
+    pub reason: &'static str,
+    pub tenant_id: String,
+    /// When a retry can be expected to succeed.
+    pub retry_after: Duration,
+}
+
+impl Throttled {
+    /// `retry_after` rounded up to whole seconds (for the `Retry-After` header).
+    pub fn retry_after_secs(&self) -> u64 {
+        self.retry_after.as_secs() + u64::from(self.retry_after.subsec_nanos() > 0)
+    }
+}
+
+impl fmt::Display for Throttled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.reason {
+            "queue_full" => write!(f, "dispatch queue is full; retry after {}s", self.retry_after_secs()),
+            _ => write!(
+                f,
+                "tenant {} exceeded its rate limit; retry after {}s",
+                self.tenant_id,
+                self.retry_after_secs()
+            ),
+        }
+    }
+}
+
+impl std::error::Error for Throttled {}
+
+/// Point-in-time queue metrics.
+#[derive(Debug, Clone, Serialize)]
+pub struct QueueStats {
+    pub running: usize,
+    /// Requests waiting for an execution slot (queue depth).
+    pub queued: usize,
+    pub max_concurrent: usize,
+    pub max_queued: usize,
+    pub admitted_total: u64,
+    pub rate_limited_total: u64,
+    pub queue_full_total: u64,
+}
+
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+impl Bucket {
+    /// Takes one token, or returns how long until one is available.
+    fn take(&mut self, limit: RateLimit, now: Instant) -> Result<(), Duration> {
+        let per_sec = f64::from(limit.per_minute) / 60.0;
+        let capacity = f64::from(limit.burst);
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * per_sec).min(capacity);
+        self.updated = now;
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - self.tokens) / per_sec))
+        }
+    }
+}
+
+/// Token buckets by key (a tenant, a chat, ...), each charged against the limit it is called with.
+#[derive(Default)]
+pub struct RateLimiter {
+    buckets: Mutex<HashMap<String, Bucket>>,
+}
+
+impl RateLimiter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Takes one token from `key`'s bucket (full on first use), or returns how long until one is
+    /// available.
+    pub fn check(&self, key: &str, limit: RateLimit) -> Result<(), Duration> {
+        let now = Instant::now();
+        let mut buckets = self.buckets.lock().unwrap_or_else(PoisonError::into_inner);
+        let bucket = buckets.entry(key.to_string()).or_insert(Bucket {
+            tokens: f64::from(limit.burst),
+            updated: now,
+        });
+        bucket.take(limit, now)
+    }
+}
+
+/// Admission control for [`super::Orchestrator`] dispatches.
+pub struct DispatchQueue {
+    config: DispatchQueueConfig,
+    slots: Arc<Semaphore>,
+    buckets: RateLimiter,
+    queued: AtomicUsize,
+    admitted: AtomicU64,
+    rate_limited: AtomicU64,
+    queue_full: AtomicU64,
+}
+
+impl fmt::Debug for DispatchQueue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DispatchQueue").field("stats", &self.stats()).finish()
+    }
+}
+
+/// Held while a goal executes; releases its slot on drop.
+pub struct DispatchPermit {
+    _slot: OwnedSemaphorePermit,
+}
+
+/// Decrements the queue depth when the waiter is admitted or dropped.
+struct Waiting<'a>(&'a AtomicUsize);
+
+impl Drop for Waiting<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+impl DispatchQueue {
+    pub fn new(config: DispatchQueueConfig) -> Self {
+        let slots = Arc::new(Semaphore::new(config.max_concurrent.max(1)));
+        Self {
+            config,
+            slots,
+            buckets: RateLimiter::new(),
+            queued: AtomicUsize::new(0),
+            admitted: AtomicU64::new(0),
+            rate_limited: AtomicU64::new(0),
+            queue_full: AtomicU64::new(0),
+        }
+    }
+
+    pub fn config(&self) -> &DispatchQueueConfig {
+        &self.config
+    }
+
+    /// Charges the tenant's rate limit, then waits for an execution slot.
+    pub async fn admit(&self, tenant_id: &str) -> Result<DispatchPermit, Throttled> {
+        self.charge(tenant_id)?;
+        if let Ok(slot) = Arc::clone(&self.slots).try_acquire_owned() {
+            self.admitted.fetch_add(1, Ordering::Relaxed);
+            return Ok(DispatchPermit { _slot: slot });
+        }
+        let depth = self.queued.fetch_add(1, Ordering::AcqRel);
+        let waiting = Waiting(&self.queued);
+        if depth >= self.config.max_queued {
+            drop(waiting);
+            self.queue_full.fetch_add(1, Ordering::Relaxed);
+            tracing::warn!(target: "pagi::orchestrator", tenant_id, depth, "Dispatch queue full; rejecting goal");
+            return Err(Throttled {
+                reason: "queue_full",
+                tenant_id: tenant_id.to_string(),
+                retry_after: Duration::from_secs(1),
+            });
+        }
+        let slot = Arc::clone(&self.slots)
+            .acquire_owned()
+            .await
+            .expect("dispatch semaphore is never closed");
+        drop(waiting);
+        self.admitted.fetch_add(1, Ordering::Relaxed);
+        Ok(DispatchPermit { _slot: slot })
+    }
+
+    fn charge(&self, tenant_id: &str) -> Result<(), Throttled> {
+        let Some(limit) = self.config.limit_for(tenant_id) else {
+            return Ok(());
+        };
+        self.buckets.check(tenant_id, limit).map_err(|retry_after| {
+            self.rate_limited.fetch_add(1, Ordering::Relaxed);
+            tracing::warn!(
+                target: "pagi::orchestrator",
+                tenant_id,
+                retry_after_ms = retry_after.as_millis() as u64,
+                "Tenant rate limit exceeded"
+            );
+            Throttled {
+                reason: "rate_limited",
+                tenant_id: tenant_id.to_string(),
+                retry_after,
+            }
+        })
+    }
+
+    pub fn stats(&self) -> QueueStats {
+        let max_concurrent = self.config.max_concurrent.max(1);
+        QueueStats {
+            running: max_concurrent - self.slots.available_permits(),
+            queued: self.queued.load(Ordering::Acquire),
+            max_concurrent,
+            max_queued: self.config.max_queued,
+            admitted_total: self.admitted.load(Ordering::Relaxed),
+            rate_limited_total: self.rate_limited.load(Ordering::Relaxed),
+            queue_full_total: self.queue_full.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_limits() {
+        assert_eq!(RateLimit::parse("60"), Some(RateLimit { per_minute: 60, burst: 60 }));
+        assert_eq!(RateLimit::parse(" 120 / 10 "), Some(RateLimit { per_minute: 120, burst: 10 }));
+        assert_eq!(RateLimit::parse("0"), None);
+        let limits = parse_tenant_limits("acme=30/5, bad, beta=x, gamma=10");
+        assert_eq!(limits.len(), 2);
+        assert_eq!(limits["acme"].burst, 5);
+    }
+
+    #[test]
+    fn bucket_refills_over_time() {
+        let limit = RateLimit { per_minute: 60, burst: 2 };
+        let start = Instant::now();
+        let mut bucket = Bucket { tokens: 2.0, updated: start };
+        assert!(bucket.take(limit, start).is_ok());
+        assert!(bucket.take(limit, start).is_ok());
+        let wait = bucket.take(limit, start).unwrap_err();
+        assert!(wait <= Duration::from_secs(1) && wait > Duration::from_millis(900));
+        assert!(bucket.take(limit, start + Duration::from_secs(1)).is_ok());
+    }
+}
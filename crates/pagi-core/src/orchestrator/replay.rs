@@ -0,0 +1,430 @@
+//! Replays of recorded `AutonomousGoal` traces (the thought logs ResearchAudit keeps in KB-8)
+//! against the current skills and blueprint, with a per-step diff of the outputs.
+//!
+//! [`ReplayMode::Recorded`] calls every recorded skill step again with its recorded input, so a bug
+//! reproduces deterministically whatever the earlier steps return now. Steps that were skipped
+//! stay skipped, and sub-plan steps are replayed through their recorded inner steps.
+//! [`ReplayMode::Chained`] runs the plan again from the recorded context, chaining outputs as
+//! dispatch does: the intent's current blueprint plan, or the recorded `plan_steps` when the
+//! blueprint no longer has the intent (e.g. a plan the LLM synthesized).
+//!
+//! Steps are matched by position; `2.0` is the first step of the sub-plan at step 2. Replays call
+//! skills for real (Ethos gate included) but record no new trace.
+
+use super::blueprint::normalize_intent;
+use super::cancel::run_cancellable;
+use super::span::dispatch_span;
+use super::{EthosBlocked, EthosScope, GoalCancelled, Orchestrator, Plan, PlanStep};
+use super::CancellationToken;
+use crate::shared::TenantContext;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::atomic::Ordering;
+use tracing::Instrument;
+
+/// How a trace is replayed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReplayMode {
+    /// Each skill step with its recorded input.
+    #[default]
+    Recorded,
+    /// The plan from the recorded context, each step fed by the one before.
+    Chained,
+}
+
+/// A recorded trace that cannot be replayed (not an `AutonomousGoal` thought log).
+#[derive(Debug)]
+pub struct InvalidTrace(pub String);
+
+impl fmt::Display for InvalidTrace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "trace cannot be replayed: {}", self.0)
+    }
+}
+
+impl std::error::Error for InvalidTrace {}
+
+/// How a replayed step compares with its recording.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReplayDiff {
+    Same,
+    Changed,
+    /// Only in the replay (the current plan has more steps).
+    Added,
+    /// Only in the recording.
+    Removed,
+}
+
+/// Result of one step, recorded or replayed.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StepOutcome {
+    /// `ok`, `failed`, `blocked` or `skipped`.
+    pub status: String,
+    /// Fallback skill that ran instead of the step's own.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub executed_skill: Option<String>,
+    pub output: serde_json::Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl StepOutcome {
+    fn of(entry: &serde_json::Value) -> Self {
+        let text = |key: &str| entry.get(key).and_then(|v| v.as_str()).map(str::to_string);
+        Self {
+            status: text("status").unwrap_or_else(|| "ok".to_string()),
+            executed_skill: text("executed_skill"),
+            output: entry.get("output").cloned().unwrap_or_default(),
+            error: text("error"),
+        }
+    }
+}
+
+/// One value that differs between the recorded and the replayed output.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ValueChange {
+    /// JSON pointer into the output (`""` is the whole output).
+    pub path: String,
+    /// Left out when the value is missing on that side.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recorded: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub replayed: Option<serde_json::Value>,
+}
+
+/// One step of the replay next to its recording.
+#[derive(Debug, Clone, Serialize)]
+pub struct ReplayStep {
+    /// Position in the plan: `1`, or `1.0` inside the sub-plan at step 1.
+    pub path: String,
+    /// Skill name, or `intent:{name}` for sub-plan steps (the replayed one when they differ).
+    pub step: String,
+    pub diff: ReplayDiff,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recorded: Option<StepOutcome>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub replayed: Option<StepOutcome>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub changes: Vec<ValueChange>,
+}
+
+/// A replayed trace, step by step against its recording.
+#[derive(Debug, Clone, Serialize)]
+pub struct TraceReplay {
+    pub mode: ReplayMode,
+    pub intent: String,
+    /// `recorded`, or `blueprint` when a chained replay ran the intent's current plan.
+    pub plan_source: &'static str,
+    pub plan_steps: Vec<String>,
+    /// Steps that are not `same`.
+    pub changed_steps: usize,
+    pub steps: Vec<ReplayStep>,
+    /// Output of a chained replay.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub final_result: Option<serde_json::Value>,
+    /// How `final_result` differs from the recorded one.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub final_changes: Vec<ValueChange>,
+    /// Why a chained replay stopped early; the steps it did not reach show as `removed`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// `intent:{name}` for sub-plan entries, else the skill name.
+fn step_label(entry: &serde_json::Value) -> String {
+    match entry.get("intent").and_then(|v| v.as_str()) {
+        Some(intent) => format!("intent:{}", intent),
+        None => entry.get("skill").and_then(|v| v.as_str()).unwrap_or_default().to_string(),
+    }
+}
+
+/// Trace entries in execution order, sub-plan steps after their sub-plan, keyed by position.
+fn flatten<'a>(steps: &'a [serde_json::Value], prefix: &str, out: &mut Vec<(String, &'a serde_json::Value)>) {
+    for (index, entry) in steps.iter().enumerate() {
+        let path = if prefix.is_empty() { index.to_string() } else { format!("{}.{}", prefix, index) };
+        out.push((path.clone(), entry));
+        if let Some(inner) = entry.get("steps").and_then(|v| v.as_array()) {
+            flatten(inner, &path, out);
+        }
+    }
+}
+
+/// Appends the values that differ between `recorded` and `replayed` under `path`. Objects and
+/// equally long arrays are compared member by member.
+fn value_changes(
+    path: &str,
+    recorded: Option<&serde_json::Value>,
+    replayed: Option<&serde_json::Value>,
+    out: &mut Vec<ValueChange>,
+) {
+    if recorded == replayed {
+        return;
+    }
+    match (recorded, replayed) {
+        (Some(serde_json::Value::Object(a)), Some(serde_json::Value::Object(b))) => {
+            let mut keys: Vec<&String> = a.keys().chain(b.keys()).collect();
+            keys.sort();
+            keys.dedup();
+            for key in keys {
+                let child = format!("{}/{}", path, key.replace('~', "~0").replace('/', "~1"));
+                value_changes(&child, a.get(key), b.get(key), out);
+            }
+        }
+        (Some(serde_json::Value::Array(a)), Some(serde_json::Value::Array(b))) if a.len() == b.len() => {
+            for (index, (x, y)) in a.iter().zip(b).enumerate() {
+                value_changes(&format!("{}/{}", path, index), Some(x), Some(y), out);
+            }
+        }
+        _ => out.push(ValueChange {
+            path: path.to_string(),
+            recorded: recorded.cloned(),
+            replayed: replayed.cloned(),
+        }),
+    }
+}
+
+/// Pairs recorded and replayed steps by position and diffs each pair.
+fn diff_steps(recorded: &[serde_json::Value], replayed: &[serde_json::Value]) -> Vec<ReplayStep> {
+    let (mut old, mut new) = (Vec::new(), Vec::new());
+    flatten(recorded, "", &mut old);
+    flatten(replayed, "", &mut new);
+    let mut new_by_path: HashMap<&str, &serde_json::Value> = new.iter().map(|(p, e)| (p.as_str(), *e)).collect();
+    let mut steps = Vec::with_capacity(old.len());
+    for (path, entry) in &old {
+        let recorded = StepOutcome::of(entry);
+        let Some(replayed_entry) = new_by_path.remove(path.as_str()) else {
+            steps.push(ReplayStep {
+                path: path.clone(),
+                step: step_label(entry),
+                diff: ReplayDiff::Removed,
+                recorded: Some(recorded),
+                replayed: None,
+                changes: Vec::new(),
+            });
+            continue;
+        };
+        let replayed = StepOutcome::of(replayed_entry);
+        let mut changes = Vec::new();
+        value_changes("", Some(&recorded.output), Some(&replayed.output), &mut changes);
+        let step = step_label(replayed_entry);
+        let same = step == step_label(entry)
+            && recorded.status == replayed.status
+            && recorded.executed_skill == replayed.executed_skill
+            && changes.is_empty();
+        steps.push(ReplayStep {
+            path: path.clone(),
+            step,
+            diff: if same { ReplayDiff::Same } else { ReplayDiff::Changed },
+            recorded: Some(recorded),
+            replayed: Some(replayed),
+            changes,
+        });
+    }
+    for (path, entry) in &new {
+        if new_by_path.contains_key(path.as_str()) {
+            steps.push(ReplayStep {
+                path: path.clone(),
+                step: step_label(entry),
+                diff: ReplayDiff::Added,
+                recorded: None,
+                replayed: Some(StepOutcome::of(entry)),
+                changes: Vec::new(),
+            });
+        }
+    }
+    steps
+}
+
+impl Orchestrator {
+    /// Replays `trace` (the `trace` object of a KB-8 thought log) in `mode` and diffs every step
+    /// against the recording. Waits for a dispatch slot like [`dispatch`](Self::dispatch);
+    /// cancelling `token` stops it with [`GoalCancelled`].
+    pub async fn replay_trace(
+        &self,
+        ctx: &TenantContext,
+        trace: &serde_json::Value,
+        mode: ReplayMode,
+        token: &CancellationToken,
+    ) -> Result<TraceReplay, Box<dyn std::error::Error + Send + Sync>> {
+        let intent = trace
+            .get("intent")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| InvalidTrace("no intent (only AutonomousGoal traces can be replayed)".to_string()))?;
+        let recorded_steps = trace
+            .get("steps")
+            .and_then(|v| v.as_array())
+            .ok_or_else(|| InvalidTrace("no steps".to_string()))?;
+        if !self.skills_enabled.load(Ordering::Acquire) {
+            return Err("Skills execution is disabled by the control panel.".into());
+        }
+        let run = async {
+            let _permit = run_cancellable(token, 0, async { Ok(self.admit(ctx).await?) }).await?;
+            let mut completed_steps = 0;
+            let mut replay = match mode {
+                ReplayMode::Recorded => {
+                    let steps = self.replay_recorded(ctx, recorded_steps, token, &mut completed_steps).await?;
+                    TraceReplay {
+                        mode,
+                        intent: intent.to_string(),
+                        plan_source: "recorded",
+                        plan_steps: recorded_steps.iter().map(step_label).collect(),
+                        changed_steps: 0,
+                        steps: diff_steps(recorded_steps, &steps),
+                        final_result: None,
+                        final_changes: Vec::new(),
+                        error: None,
+                    }
+                }
+                ReplayMode::Chained => {
+                    let (plan, plan_source) = match self.blueprint.plan_for_intent(intent) {
+                        Some(plan) => (plan, "blueprint"),
+                        None => (recorded_plan(trace)?, "recorded"),
+                    };
+                    let context = trace.get("context").cloned().unwrap_or(serde_json::json!({}));
+                    let mut chain = vec![normalize_intent(intent)];
+                    let outcome = self.run_plan(ctx, &plan, context, token, &mut chain, &mut completed_steps).await;
+                    let (steps, final_result, error) = match outcome {
+                        Ok(run) => (run.steps, Some(run.output), None),
+                        Err(e) if e.is::<GoalCancelled>() => return Err(e),
+                        Err(e) => {
+                            let steps = e.downcast_ref::<EthosBlocked>().map(|b| b.steps.clone()).unwrap_or_default();
+                            (steps, None, Some(e.to_string()))
+                        }
+                    };
+                    let mut final_changes = Vec::new();
+                    if let Some(result) = &final_result {
+                        value_changes("", trace.get("final_result"), Some(result), &mut final_changes);
+                    }
+                    TraceReplay {
+                        mode,
+                        intent: intent.to_string(),
+                        plan_source,
+                        plan_steps: plan.skill_names(),
+                        changed_steps: 0,
+                        steps: diff_steps(recorded_steps, &steps),
+                        final_result,
+                        final_changes,
+                        error,
+                    }
+                }
+            };
+            replay.changed_steps = replay.steps.iter().filter(|s| s.diff != ReplayDiff::Same).count();
+            tracing::info!(
+                target: "pagi::orchestrator",
+                intent = %intent,
+                mode = ?mode,
+                changed_steps = replay.changed_steps,
+                "Trace replayed"
+            );
+            Ok(replay)
+        };
+        run.instrument(dispatch_span(ctx, "TraceReplay")).await
+    }
+
+    /// Runs every recorded skill step again with its recorded input, returning trace entries
+    /// shaped like the recording's.
+    async fn replay_recorded(
+        &self,
+        ctx: &TenantContext,
+        steps: &[serde_json::Value],
+        token: &CancellationToken,
+        completed_steps: &mut usize,
+    ) -> Result<Vec<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>> {
+        let mut replayed = Vec::with_capacity(steps.len());
+        for entry in steps {
+            if token.is_cancelled() {
+                return Err(GoalCancelled { completed_steps: *completed_steps }.into());
+            }
+            let mut out = serde_json::json!({});
+            for key in ["skill", "intent", "executed_skill"] {
+                if let Some(value) = entry.get(key) {
+                    out[key] = value.clone();
+                }
+            }
+            let recorded = StepOutcome::of(entry);
+            if recorded.status == "skipped" {
+                out["status"] = serde_json::json!("skipped");
+            } else if entry.get("intent").is_some() {
+                match entry.get("steps").and_then(|v| v.as_array()) {
+                    Some(inner) => {
+                        let inner = Box::pin(self.replay_recorded(ctx, inner, token, completed_steps)).await?;
+                        // Like a sub-plan run: the output of its last step that produced one.
+                        out["output"] = inner
+                            .iter()
+                            .rev()
+                            .find_map(|s| s.get("output").cloned())
+                            .unwrap_or_default();
+                        out["steps"] = serde_json::json!(inner);
+                    }
+                    None => {
+                        out["status"] = serde_json::json!("skipped");
+                        out["error"] = serde_json::json!("no recorded sub-plan steps to replay");
+                    }
+                }
+            } else {
+                let name = recorded.executed_skill.clone().unwrap_or_else(|| step_label(entry));
+                let input = entry.get("input").filter(|v| !v.is_null()).cloned();
+                let outcome = match self.skill(ctx, &name) {
+                    Ok(skill) => {
+                        let call = self.execute_skill(skill.as_ref(), ctx, input, EthosScope::Internal);
+                        run_cancellable(token, *completed_steps, call).await
+                    }
+                    Err(e) => Err(e),
+                };
+                match outcome {
+                    Ok(output) => {
+                        *completed_steps += 1;
+                        out["output"] = output;
+                    }
+                    Err(e) if e.is::<GoalCancelled>() => return Err(e),
+                    Err(e) => {
+                        out["status"] = serde_json::json!(if e.is::<EthosBlocked>() { "blocked" } else { "failed" });
+                        out["error"] = serde_json::json!(e.to_string());
+                    }
+                }
+            }
+            replayed.push(out);
+        }
+        Ok(replayed)
+    }
+}
+
+/// The plan a trace recorded in `plan_steps` (policies, guards and maps are not recorded).
+fn recorded_plan(trace: &serde_json::Value) -> Result<Plan, InvalidTrace> {
+    let labels = trace
+        .get("plan_steps")
+        .and_then(|v| v.as_array())
+        .filter(|steps| !steps.is_empty())
+        .ok_or_else(|| InvalidTrace("no plan_steps, and the intent has no blueprint plan".to_string()))?;
+    let steps = labels
+        .iter()
+        .map(|label| {
+            let label = label.as_str().unwrap_or_default();
+            match label.strip_prefix("intent:") {
+                Some(intent) => PlanStep::sub_plan(intent),
+                None => PlanStep::new(label),
+            }
+        })
+        .collect();
+    Ok(Plan { steps })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn value_changes_point_at_the_differing_members() {
+        let recorded = serde_json::json!({ "draft": "Hi", "meta": { "tokens": 3, "a/b": 1 }, "tags": ["x", "y"] });
+        let replayed = serde_json::json!({ "draft": "Hi", "meta": { "tokens": 5 }, "tags": ["x", "z"], "new": true });
+        let mut changes = Vec::new();
+        value_changes("", Some(&recorded), Some(&replayed), &mut changes);
+        let paths: Vec<&str> = changes.iter().map(|c| c.path.as_str()).collect();
+        assert_eq!(paths, ["/meta/a~1b", "/meta/tokens", "/new", "/tags/1"]);
+        assert_eq!(changes[0].replayed, None);
+        assert_eq!(changes[2].recorded, None);
+    }
+}
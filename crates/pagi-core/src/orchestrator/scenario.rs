@@ -0,0 +1,400 @@
+//! Declarative blueprint scenarios (given / when / then).
+//!
+//! A scenario file (YAML; TOML and JSON are also read) seeds a temporary in-memory knowledge
+//! store, replaces skills with mocks, dispatches one autonomous goal through the blueprint, and
+//! asserts on the final payload, the thought log, the skills called and the inputs they received.
+//! Blueprint changes can then be covered without writing Rust:
+//!
+//! ```yaml
+//! name: lead reply goes through the closer
+//!
+//! given:
+//!   kb:
+//!     - slot: 1
+//!       key: core_persona
+//!       value: Friendly sales assistant
+//!   skills:
+//!     DraftResponse:
+//!       output: { draft: Thanks for reaching out }
+//!     ModelRouter:
+//!       error: rate limited
+//!       fail_times: 1
+//!       output: { generated: Hello! }
+//!
+//! when:
+//!   intent: respond to lead
+//!   context: { lead_id: lead-1 }
+//!
+//! then:
+//!   called: [ParseInboundMessage, DraftResponse, SalesCloser, ModelRouter, ModelRouter]
+//!   output: { "$.generated": Hello! }
+//!   trace: { "$.steps[0].skill": ParseInboundMessage }
+//!   inputs: { ModelRouter: { "$.prompt": Thanks for reaching out } }
+//! ```
+//!
+//! Selectors use the payload mapping syntax (`$.field`, `$.items[0]`). Skills without a mock echo
+//! their input; `ResearchAudit` is always replaced by a recorder that captures the thought log.
+//! KB-5 blueprint entries seeded in `given.kb` overlay the blueprint as they do at runtime.
+
+use super::mapping::select;
+use super::{AgentSkill, BlueprintRegistry, Orchestrator, Plan, SkillRegistry};
+use crate::knowledge::KnowledgeStore;
+use crate::shared::{Goal, TenantContext};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use std::sync::{Arc, Mutex, PoisonError};
+
+/// One given / when / then scenario.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Scenario {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub given: ScenarioGiven,
+    pub when: ScenarioWhen,
+    #[serde(default)]
+    pub then: ScenarioThen,
+}
+
+/// Initial state: KB entries, skill mocks, and optionally an inline blueprint.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ScenarioGiven {
+    #[serde(default)]
+    pub kb: Vec<ScenarioKbEntry>,
+    #[serde(default)]
+    pub skills: BTreeMap<String, MockSkill>,
+    /// Blueprint in the `blueprint.json` format (`{ "intents": { ... } }`). When absent the runner's
+    /// blueprint is used.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blueprint: Option<serde_json::Value>,
+}
+
+/// A KB record written before dispatch. String values are stored as UTF-8 text, anything else as JSON.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ScenarioKbEntry {
+    pub slot: u8,
+    pub key: String,
+    pub value: serde_json::Value,
+}
+
+/// Canned behaviour for one skill. With only `error` every call fails; with `fail_times` the first
+/// calls fail and later ones return `output`. Without `output` the skill echoes its input.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MockSkill {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output: Option<serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fail_times: Option<u32>,
+}
+
+/// The goal to dispatch.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ScenarioWhen {
+    pub intent: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context: Option<serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub agent_id: Option<String>,
+}
+
+/// Expectations. Every field is optional; an empty `then` only checks that dispatch succeeds.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ScenarioThen {
+    /// Dispatch must fail with an error containing this text.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Skill calls in order (retries included, `ResearchAudit` excluded).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub called: Option<Vec<String>>,
+    /// Selector → expected value on the final payload.
+    #[serde(default)]
+    pub output: BTreeMap<String, serde_json::Value>,
+    /// Selector → expected value on the thought log (`intent`, `plan_steps`, `steps`, ...).
+    #[serde(default)]
+    pub trace: BTreeMap<String, serde_json::Value>,
+    /// Skill → selector → expected value on the input of that skill's first call.
+    #[serde(default)]
+    pub inputs: BTreeMap<String, BTreeMap<String, serde_json::Value>>,
+}
+
+impl Scenario {
+    /// Loads a scenario file; `.toml` files are parsed as TOML, `.json` as JSON, everything else
+    /// as YAML.
+    pub fn load_path<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        let parsed = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => toml::from_str(&text).map_err(|e| e.to_string()),
+            Some("json") => serde_json::from_str(&text).map_err(|e| e.to_string()),
+            _ => serde_yaml::from_str(&text).map_err(|e| e.to_string()),
+        };
+        Ok(parsed.map_err(|e| format!("{}: {}", path.display(), e))?)
+    }
+
+    /// Every `.yaml` / `.yml` / `.toml` / `.json` scenario in `dir`, sorted by file name.
+    pub fn load_dir<P: AsRef<Path>>(dir: P) -> Result<Vec<Self>, Box<dyn std::error::Error + Send + Sync>> {
+        let mut paths: Vec<_> = std::fs::read_dir(dir.as_ref())?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|p| p.extension().is_some_and(|ext| ["yaml", "yml", "toml", "json"].iter().any(|e| ext == *e)))
+            .collect();
+        paths.sort();
+        paths.iter().map(Self::load_path).collect()
+    }
+}
+
+/// Result of running one scenario. `failures` is empty when every expectation held.
+#[derive(Debug, Clone, Serialize)]
+pub struct ScenarioOutcome {
+    pub name: String,
+    pub failures: Vec<String>,
+    pub called: Vec<String>,
+    pub output: Option<serde_json::Value>,
+    pub trace: Option<serde_json::Value>,
+}
+
+impl ScenarioOutcome {
+    pub fn passed(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+/// Call log shared by the mocks of one run: (skill, input) in call order.
+type CallLog = Arc<Mutex<Vec<(String, serde_json::Value)>>>;
+
+struct ScenarioSkill {
+    name: String,
+    mock: MockSkill,
+    calls: CallLog,
+}
+
+#[async_trait::async_trait]
+impl AgentSkill for ScenarioSkill {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn execute(
+        &self,
+        _ctx: &TenantContext,
+        payload: Option<serde_json::Value>,
+    ) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>> {
+        let input = payload.unwrap_or(serde_json::Value::Null);
+        let call = {
+            let mut calls = self.calls.lock().unwrap_or_else(PoisonError::into_inner);
+            calls.push((self.name.clone(), input.clone()));
+            calls.iter().filter(|(name, _)| *name == self.name).count() as u32
+        };
+        if let Some(error) = &self.mock.error {
+            if self.mock.fail_times.is_none_or(|n| call <= n) {
+                return Err(error.clone().into());
+            }
+        }
+        Ok(self.mock.output.clone().unwrap_or(input))
+    }
+}
+
+/// Stands in for `ResearchAudit`: keeps the thought log.
+struct TraceRecorder(Arc<Mutex<Option<serde_json::Value>>>);
+
+#[async_trait::async_trait]
+impl AgentSkill for TraceRecorder {
+    fn name(&self) -> &str {
+        "ResearchAudit"
+    }
+
+    async fn execute(
+        &self,
+        _ctx: &TenantContext,
+        payload: Option<serde_json::Value>,
+    ) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>> {
+        *self.0.lock().unwrap_or_else(PoisonError::into_inner) = payload.and_then(|p| p.get("trace").cloned());
+        Ok(serde_json::json!({ "trace_id": "scenario" }))
+    }
+}
+
+/// Runs scenarios against a blueprint (`blueprint.json` text) unless a scenario brings its own.
+#[derive(Debug, Clone, Default)]
+pub struct ScenarioRunner {
+    blueprint: Option<String>,
+}
+
+impl ScenarioRunner {
+    /// Runner with no shared blueprint: scenarios must define `given.blueprint`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_blueprint(mut self, blueprint_json: impl Into<String>) -> Self {
+        self.blueprint = Some(blueprint_json.into());
+        self
+    }
+
+    pub fn with_blueprint_path<P: AsRef<Path>>(self, path: P) -> std::io::Result<Self> {
+        Ok(self.with_blueprint(std::fs::read_to_string(path)?))
+    }
+
+    /// Runs one scenario. Setup problems (invalid blueprint, KB write failure) are errors;
+    /// unmet expectations are reported in [`ScenarioOutcome::failures`].
+    pub async fn run(&self, scenario: &Scenario) -> Result<ScenarioOutcome, Box<dyn std::error::Error + Send + Sync>> {
+        let blueprint_text = match (&scenario.given.blueprint, &self.blueprint) {
+            (Some(inline), _) => inline.to_string(),
+            (None, Some(text)) => text.clone(),
+            (None, None) => return Err(format!("scenario {}: no blueprint given", scenario.name).into()),
+        };
+        let intents = super::parse_blueprint(&blueprint_text)
+            .map_err(|issue| format!("scenario {}: blueprint {}", scenario.name, issue))?;
+
+        let store = Arc::new(KnowledgeStore::open_temporary()?);
+        for entry in &scenario.given.kb {
+            let bytes = match &entry.value {
+                serde_json::Value::String(s) => s.clone().into_bytes(),
+                other => serde_json::to_vec(other)?,
+            };
+            store.insert(entry.slot, &entry.key, &bytes)?;
+        }
+
+        // KB-5 entries in `given.kb` overlay the blueprint, as they do in the gateway.
+        let plans: HashMap<String, Plan> = intents
+            .into_iter()
+            .map(|(intent, steps)| (intent, Plan { steps }))
+            .collect();
+        let blueprint = BlueprintRegistry::from_plans(plans).with_store(Arc::clone(&store));
+
+        let calls: CallLog = Arc::default();
+        let trace = Arc::new(Mutex::new(None));
+        let mut registry = SkillRegistry::new();
+        let mut names: Vec<String> = blueprint
+            .intent_names()
+            .iter()
+            .filter_map(|intent| blueprint.plan_for_intent(intent))
+            .flat_map(|plan| plan.steps.iter().flat_map(|s| s.candidates()).map(str::to_string).collect::<Vec<_>>())
+            .chain(scenario.given.skills.keys().cloned())
+            .collect();
+        names.sort();
+        names.dedup();
+        for name in names.into_iter().filter(|n| n != "ResearchAudit") {
+            registry.register(Arc::new(ScenarioSkill {
+                mock: scenario.given.skills.get(&name).cloned().unwrap_or_default(),
+                name,
+                calls: Arc::clone(&calls),
+            }));
+        }
+        registry.register(Arc::new(TraceRecorder(Arc::clone(&trace))));
+
+        let orchestrator =
+            Orchestrator::with_blueprint(Arc::new(registry), Arc::new(blueprint)).with_knowledge(Arc::clone(&store));
+        let ctx = TenantContext {
+            tenant_id: "scenario".to_string(),
+            correlation_id: None,
+            agent_id: scenario.when.agent_id.clone(),
+            ..Default::default()
+        };
+        let goal = Goal::AutonomousGoal {
+            intent: scenario.when.intent.clone(),
+            context: scenario.when.context.clone(),
+        };
+        let result = orchestrator.dispatch(&ctx, goal).await;
+
+        let calls = std::mem::take(&mut *calls.lock().unwrap_or_else(PoisonError::into_inner));
+        let trace = trace.lock().unwrap_or_else(PoisonError::into_inner).take();
+        let then = &scenario.then;
+        let mut failures = Vec::new();
+
+        let output = match (result, &then.error) {
+            (Ok(out), None) => Some(out),
+            (Ok(out), Some(expected)) => {
+                failures.push(format!("expected dispatch to fail with \"{}\", but it succeeded", expected));
+                Some(out)
+            }
+            (Err(e), Some(expected)) => {
+                if !e.to_string().contains(expected.as_str()) {
+                    failures.push(format!("expected error containing \"{}\", got \"{}\"", expected, e));
+                }
+                None
+            }
+            (Err(e), None) => {
+                failures.push(format!("dispatch failed: {}", e));
+                None
+            }
+        };
+
+        let called: Vec<String> = calls.iter().map(|(name, _)| name.clone()).collect();
+        if let Some(expected) = &then.called {
+            if *expected != called {
+                failures.push(format!("called {:?}, expected {:?}", called, expected));
+            }
+        }
+        check_values("output", output.as_ref(), &then.output, &mut failures);
+        check_values("trace", trace.as_ref(), &then.trace, &mut failures);
+        for (skill, expected) in &then.inputs {
+            let input = calls.iter().find(|(name, _)| name == skill).map(|(_, input)| input);
+            if input.is_none() {
+                failures.push(format!("inputs.{}: skill was not called", skill));
+                continue;
+            }
+            check_values(&format!("inputs.{}", skill), input, expected, &mut failures);
+        }
+
+        Ok(ScenarioOutcome {
+            name: scenario.name.clone(),
+            failures,
+            called,
+            output,
+            trace,
+        })
+    }
+}
+
+/// Compares each selector's value in `actual` with the expected one.
+fn check_values(
+    label: &str,
+    actual: Option<&serde_json::Value>,
+    expected: &BTreeMap<String, serde_json::Value>,
+    failures: &mut Vec<String>,
+) {
+    for (selector, want) in expected {
+        match actual.and_then(|value| select(selector, value)) {
+            Some(got) if got == want => {}
+            Some(got) => failures.push(format!("{} {}: expected {}, got {}", label, selector, want, got)),
+            None => failures.push(format!("{} {}: expected {}, but nothing matched", label, selector, want)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn yaml_scenario_parses_and_rejects_unknown_fields() {
+        let scenario: Scenario = serde_yaml::from_str(
+            r#"
+name: s
+given:
+  skills:
+    A:
+      output: { x: 1 }
+when:
+  intent: go
+then:
+  output: { "$.x": 1 }
+"#,
+        )
+        .unwrap();
+        assert_eq!(scenario.given.skills["A"].output, Some(serde_json::json!({ "x": 1 })));
+        assert_eq!(scenario.then.output["$.x"], serde_json::json!(1));
+
+        let err = serde_yaml::from_str::<Scenario>("name: s\nwhen:\n  intent: go\nthen:\n  outputs: {}\n");
+        assert!(err.unwrap_err().to_string().contains("unknown field `outputs`"));
+    }
+}
@@ -0,0 +1,465 @@
+//! Delayed and recurring goals, evaluated by the Heartbeat.
+//!
+//! A [`ScheduledGoal`] pairs a [`Goal`] with the [`TenantContext`] it runs under and either a
+//! recurring schedule or a one-shot `run_at_ms`. Entries live in **KB_OIKOS** under
+//! `schedule/{id}`. Each tick the Heartbeat calls [`SchedulerRegistry::run_due`], which dispatches
+//! every entry whose `next_run_ms` has passed and logs the run to the agent's Chronos history.
+//!
+//! Schedules (all times UTC):
+//! - five-field cron: `minute hour day-of-month month day-of-week`, each `*`, `n`, `a-b`, `*/n`,
+//!   `a-b/n` or a comma list (e.g. `0 7 * * *` = every morning at 07:00, `*/15 9-17 * * 1-5`);
+//! - `@hourly`, `@daily` (`@midnight`), `@weekly`, `@monthly`, `@yearly` (`@annually`);
+//! - `@every <n><s|m|h|d>` (e.g. `@every 30m`), counted from the previous run.
+//!
+//! Missed runs are not replayed: an overdue entry runs once and is rescheduled from now.
+
+use super::Orchestrator;
+use crate::knowledge::{KbType, KnowledgeStore};
+use crate::shared::{Goal, TenantContext};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Key prefix for scheduled goals in **KB_OIKOS** (`schedule/{id}`).
+pub const SCHEDULE_PREFIX: &str = "schedule/";
+
+const MINUTE_MS: i64 = 60_000;
+const DAY_MS: i64 = 24 * 60 * MINUTE_MS;
+/// Cron expressions that match nothing within this many days are rejected.
+const MAX_LOOKAHEAD_DAYS: i64 = 4 * 366;
+
+/// A schedule string could not be parsed (or never fires).
+#[derive(Debug, Clone, PartialEq)]
+pub struct InvalidSchedule(pub String);
+
+impl fmt::Display for InvalidSchedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid schedule: {}", self.0)
+    }
+}
+
+impl std::error::Error for InvalidSchedule {}
+
+/// Parsed form of a schedule string.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Schedule {
+    Cron(CronExpr),
+    /// Fixed interval in milliseconds.
+    Every(i64),
+}
+
+impl Schedule {
+    pub fn parse(s: &str) -> Result<Self, InvalidSchedule> {
+        let s = s.trim();
+        let expr = match s {
+            "@hourly" => "0 * * * *",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            "@yearly" | "@annually" => "0 0 1 1 *",
+            _ => match s.strip_prefix("@every") {
+                Some(interval) => return parse_interval(interval.trim()).map(Schedule::Every),
+                None => s,
+            },
+        };
+        let cron = CronExpr::parse(expr)?;
+        if cron.next_after(0).is_none() {
+            return Err(InvalidSchedule(format!("'{}' never fires", s)));
+        }
+        Ok(Schedule::Cron(cron))
+    }
+
+    /// First run strictly after `after_ms`.
+    pub fn next_after(&self, after_ms: i64) -> Option<i64> {
+        match self {
+            Schedule::Cron(cron) => cron.next_after(after_ms),
+            Schedule::Every(interval) => Some(after_ms + interval),
+        }
+    }
+}
+
+/// `30s`, `15m`, `2h`, `1d` → milliseconds.
+pub(crate) fn parse_interval(s: &str) -> Result<i64, InvalidSchedule> {
+    let invalid = || InvalidSchedule(format!("'@every {}' (expected e.g. 30m, 2h, 1d)", s));
+    let unit_at = s.find(|c: char| !c.is_ascii_digit()).ok_or_else(invalid)?;
+    let n: i64 = s[..unit_at].parse().map_err(|_| invalid())?;
+    let unit_ms = match &s[unit_at..] {
+        "s" => 1_000,
+        "m" => MINUTE_MS,
+        "h" => 60 * MINUTE_MS,
+        "d" => DAY_MS,
+        _ => return Err(invalid()),
+    };
+    match n.checked_mul(unit_ms) {
+        Some(ms) if ms >= 1_000 => Ok(ms),
+        _ => Err(invalid()),
+    }
+}
+
+/// Five-field cron expression (UTC). Day-of-month and day-of-week follow cron's rule: when both
+/// are restricted, a day matches if either does.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CronExpr {
+    minutes: u64,
+    hours: u32,
+    days: u32,
+    months: u16,
+    weekdays: u8,
+    any_day: bool,
+    any_weekday: bool,
+}
+
+impl CronExpr {
+    pub fn parse(s: &str) -> Result<Self, InvalidSchedule> {
+        let fields: Vec<&str> = s.split_whitespace().collect();
+        let [minute, hour, day, month, weekday] = fields[..] else {
+            return Err(InvalidSchedule(format!("'{}' (expected 5 cron fields)", s)));
+        };
+        // Day-of-week 7 is Sunday, like 0.
+        let weekdays = parse_field(weekday, 0, 7)?;
+        Ok(Self {
+            minutes: parse_field(minute, 0, 59)?,
+            hours: parse_field(hour, 0, 23)? as u32,
+            days: parse_field(day, 1, 31)? as u32,
+            months: parse_field(month, 1, 12)? as u16,
+            weekdays: ((weekdays | (weekdays >> 7)) & 0x7f) as u8,
+            any_day: day == "*",
+            any_weekday: weekday == "*",
+        })
+    }
+
+    fn day_matches(&self, day: u32, weekday: u32) -> bool {
+        let by_day = self.days & (1 << day) != 0;
+        let by_weekday = self.weekdays & (1 << weekday) != 0;
+        match (self.any_day, self.any_weekday) {
+            (false, false) => by_day || by_weekday,
+            _ => by_day && by_weekday,
+        }
+    }
+
+    /// First matching minute strictly after `after_ms`.
+    pub fn next_after(&self, after_ms: i64) -> Option<i64> {
+        let mut t = (after_ms.div_euclid(MINUTE_MS) + 1) * MINUTE_MS;
+        let limit = t + MAX_LOOKAHEAD_DAYS * DAY_MS;
+        while t < limit {
+            let days = t.div_euclid(DAY_MS);
+            let (_, month, day) = civil_from_days(days);
+            let weekday = (days + 4).rem_euclid(7) as u32; // 1970-01-01 was a Thursday
+            if self.months & (1 << month) == 0 || !self.day_matches(day, weekday) {
+                t = (days + 1) * DAY_MS;
+                continue;
+            }
+            let minute_of_day = t.rem_euclid(DAY_MS) / MINUTE_MS;
+            let (hour, minute) = (minute_of_day / 60, minute_of_day % 60);
+            if self.hours & (1 << hour) == 0 {
+                t = days * DAY_MS + (hour + 1) * 60 * MINUTE_MS;
+                continue;
+            }
+            if self.minutes & (1 << minute) == 0 {
+                t += MINUTE_MS;
+                continue;
+            }
+            return Some(t);
+        }
+        None
+    }
+}
+
+/// Bitmask of the values selected by one cron field.
+fn parse_field(field: &str, min: u32, max: u32) -> Result<u64, InvalidSchedule> {
+    let invalid = || InvalidSchedule(format!("cron field '{}' (allowed {}-{})", field, min, max));
+    let mut mask = 0u64;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (range, step.parse::<u32>().ok().filter(|s| *s > 0).ok_or_else(invalid)?),
+            None => (part, 1),
+        };
+        let (lo, hi) = match range {
+            "*" => (min, max),
+            _ => match range.split_once('-') {
+                Some((lo, hi)) => (lo.parse().map_err(|_| invalid())?, hi.parse().map_err(|_| invalid())?),
+                None => {
+                    let n = range.parse().map_err(|_| invalid())?;
+                    // `n/step` runs from n to the end of the range.
+                    (n, if part.contains('/') { max } else { n })
+                }
+            },
+        };
+        if lo < min || hi > max || lo > hi {
+            return Err(invalid());
+        }
+        for v in (lo..=hi).step_by(step as usize) {
+            mask |= 1 << v;
+        }
+    }
+    Ok(mask)
+}
+
+/// Days since 1970-01-01 → (year, month 1-12, day 1-31) in the proleptic Gregorian calendar.
+pub fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+/// A goal to run later (once) or on a recurring schedule, stored in **KB_OIKOS**.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduledGoal {
+    pub id: String,
+    pub name: String,
+    /// Recurring schedule (cron or `@every`); None for a one-shot delayed goal.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schedule: Option<String>,
+    pub goal: Goal,
+    /// Context the goal is dispatched with (tenant, agent).
+    pub ctx: TenantContext,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    pub created_at_ms: i64,
+    /// When the goal runs next; None once a one-shot goal has run.
+    #[serde(default)]
+    pub next_run_ms: Option<i64>,
+    #[serde(default)]
+    pub last_run_ms: Option<i64>,
+    /// `ok` or `failed`.
+    #[serde(default)]
+    pub last_status: Option<String>,
+    #[serde(default)]
+    pub last_error: Option<String>,
+    #[serde(default)]
+    pub run_count: u64,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl ScheduledGoal {
+    /// Recurring goal created at `now_ms` (`KnowledgeStore::now_ms`); the first run is the
+    /// schedule's next occurrence after it.
+    pub fn recurring(
+        name: impl Into<String>,
+        schedule: &str,
+        goal: Goal,
+        ctx: TenantContext,
+        now_ms: i64,
+    ) -> Result<Self, InvalidSchedule> {
+        let next = Schedule::parse(schedule)?.next_after(now_ms);
+        let mut entry = Self::new(name.into(), goal, ctx, next, now_ms);
+        entry.schedule = Some(schedule.trim().to_string());
+        Ok(entry)
+    }
+
+    /// One-shot goal created at `now_ms` that runs once at `run_at_ms` (or on the next tick if
+    /// that has passed).
+    pub fn delayed(name: impl Into<String>, run_at_ms: i64, goal: Goal, ctx: TenantContext, now_ms: i64) -> Self {
+        Self::new(name.into(), goal, ctx, Some(run_at_ms), now_ms)
+    }
+
+    fn new(name: String, goal: Goal, ctx: TenantContext, next_run_ms: Option<i64>, now_ms: i64) -> Self {
+        Self {
+            id: Uuid::new_v4().simple().to_string(),
+            name,
+            schedule: None,
+            goal,
+            ctx,
+            enabled: true,
+            created_at_ms: now_ms,
+            next_run_ms,
+            last_run_ms: None,
+            last_status: None,
+            last_error: None,
+            run_count: 0,
+        }
+    }
+
+    pub fn is_due(&self, now_ms: i64) -> bool {
+        self.enabled && self.next_run_ms.is_some_and(|t| t <= now_ms)
+    }
+}
+
+/// Outcome of one scheduled run.
+#[derive(Debug, Clone, Serialize)]
+pub struct ScheduleRun {
+    pub id: String,
+    pub name: String,
+    pub ran_at_ms: i64,
+    pub ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub next_run_ms: Option<i64>,
+}
+
+/// Scheduled goals persisted in **KB_OIKOS**.
+#[derive(Clone)]
+pub struct SchedulerRegistry {
+    store: Arc<KnowledgeStore>,
+}
+
+impl SchedulerRegistry {
+    pub fn new(store: Arc<KnowledgeStore>) -> Self {
+        Self { store }
+    }
+
+    /// Stores (or replaces) a scheduled goal. Recurring schedules are validated first.
+    pub fn set(&self, entry: &ScheduledGoal) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        if let Some(schedule) = &entry.schedule {
+            Schedule::parse(schedule)?;
+        }
+        let bytes = serde_json::to_vec(entry)?;
+        self.store.insert(KbType::Oikos.slot_id(), &key(&entry.id), &bytes)?;
+        Ok(())
+    }
+
+    pub fn get(&self, id: &str) -> Option<ScheduledGoal> {
+        self.store
+            .get(KbType::Oikos.slot_id(), &key(id))
+            .ok()
+            .flatten()
+            .and_then(|b| serde_json::from_slice(&b).ok())
+    }
+
+    /// All scheduled goals, oldest first.
+    pub fn list(&self) -> Result<Vec<ScheduledGoal>, sled::Error> {
+        let mut entries: Vec<ScheduledGoal> = self
+            .store
+            .scan_kv(KbType::Oikos.slot_id())?
+            .into_iter()
+            .filter(|(k, _)| k.starts_with(SCHEDULE_PREFIX))
+            .filter_map(|(_, bytes)| serde_json::from_slice(&bytes).ok())
+            .collect();
+        entries.sort_by_key(|e| e.created_at_ms);
+        Ok(entries)
+    }
+
+    /// Removes a scheduled goal. Returns whether it existed.
+    pub fn remove(&self, id: &str) -> Result<bool, sled::Error> {
+        Ok(self.store.remove(KbType::Oikos.slot_id(), &key(id))?.is_some())
+    }
+
+    /// Dispatches every due goal (see [`Self::run_due_at`]).
+    pub async fn run_due(
+        &self,
+        orchestrator: &Orchestrator,
+    ) -> Result<Vec<ScheduleRun>, Box<dyn std::error::Error + Send + Sync>> {
+        self.run_due_at(orchestrator, self.store.now_ms()).await
+    }
+
+    /// Dispatches every goal due at `now`, one at a time. Each entry is rescheduled (or, for
+    /// one-shot goals, retired) before it runs, so a failing goal is not retried every tick.
+    /// Runs are logged to the agent's Chronos history.
+    pub async fn run_due_at(
+        &self,
+        orchestrator: &Orchestrator,
+        now: i64,
+    ) -> Result<Vec<ScheduleRun>, Box<dyn std::error::Error + Send + Sync>> {
+        let mut runs = Vec::new();
+        for mut entry in self.list()? {
+            if !entry.is_due(now) {
+                continue;
+            }
+            entry.next_run_ms = match entry.schedule.as_deref().map(Schedule::parse) {
+                Some(Ok(schedule)) => schedule.next_after(now),
+                Some(Err(e)) => {
+                    tracing::warn!(target: "pagi::scheduler", id = %entry.id, error = %e, "Disabling scheduled goal");
+                    entry.enabled = false;
+                    None
+                }
+                None => None,
+            };
+            if entry.next_run_ms.is_none() {
+                entry.enabled = false;
+            }
+            entry.last_run_ms = Some(now);
+            entry.run_count += 1;
+            self.set(&entry)?;
+
+            let mut ctx = entry.ctx.clone();
+            if ctx.correlation_id.is_none() {
+                ctx.correlation_id = Some(format!("schedule-{}-{}", entry.id, entry.run_count));
+            }
+            let outcome = orchestrator.dispatch(&ctx, entry.goal.clone()).await;
+            let error = outcome.as_ref().err().map(|e| e.to_string());
+            entry.last_status = Some(if error.is_none() { "ok" } else { "failed" }.to_string());
+            entry.last_error = error.clone();
+            self.set(&entry)?;
+
+            tracing::info!(
+                target: "pagi::scheduler",
+                id = %entry.id,
+                name = %entry.name,
+                goal = entry.goal.kind(),
+                ok = error.is_none(),
+                next_run_ms = entry.next_run_ms,
+                "Scheduled goal ran"
+            );
+            let event = self.store.event(
+                "Chronos",
+                format!("Scheduled goal '{}' ran ({})", entry.name, entry.goal.kind()),
+            )
+            .with_skill("scheduler")
+            .with_outcome(match &error {
+                None => "ok".to_string(),
+                Some(e) => format!("failed: {}", e),
+            });
+            if let Err(e) = self.store.append_chronos_event(ctx.resolved_agent_id(), &event) {
+                tracing::warn!(target: "pagi::scheduler", error = %e, "Failed to log scheduled run to Chronos");
+            }
+            runs.push(ScheduleRun {
+                id: entry.id,
+                name: entry.name,
+                ran_at_ms: now,
+                ok: error.is_none(),
+                error,
+                next_run_ms: entry.next_run_ms,
+            });
+        }
+        Ok(runs)
+    }
+}
+
+fn key(id: &str) -> String {
+    format!("{}{}", SCHEDULE_PREFIX, id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 2024-03-15 (a Friday) 06:30 UTC.
+    const FRI_0630: i64 = 1_710_484_200_000;
+
+    #[test]
+    fn cron_next_occurrences() {
+        let every_morning = Schedule::parse("0 7 * * *").unwrap();
+        assert_eq!(every_morning.next_after(FRI_0630), Some(FRI_0630 + 30 * MINUTE_MS));
+        let after = every_morning.next_after(FRI_0630 + 30 * MINUTE_MS).unwrap();
+        assert_eq!(after, FRI_0630 + 30 * MINUTE_MS + DAY_MS);
+
+        // Weekdays only: Friday 07:00 → Monday 07:00.
+        let weekdays = Schedule::parse("0 7 * * 1-5").unwrap();
+        let fri_0700 = FRI_0630 + 30 * MINUTE_MS;
+        assert_eq!(weekdays.next_after(fri_0700), Some(fri_0700 + 3 * DAY_MS));
+
+        let quarter_hours = Schedule::parse("*/15 * * * *").unwrap();
+        assert_eq!(quarter_hours.next_after(FRI_0630), Some(FRI_0630 + 15 * MINUTE_MS));
+
+        assert_eq!(civil_from_days(FRI_0630 / DAY_MS), (2024, 3, 15));
+        assert_eq!(Schedule::parse("@every 90s").unwrap(), Schedule::Every(90_000));
+    }
+
+    #[test]
+    fn rejects_bad_schedules() {
+        for bad in ["", "0 7 * *", "60 * * * *", "0 0 30 2 *", "*/0 * * * *", "@every 5x", "@every 0m"] {
+            assert!(Schedule::parse(bad).is_err(), "{:?} should be rejected", bad);
+        }
+    }
+}
@@ -0,0 +1,262 @@
+//! Scrape sources: pages the Heartbeat keeps fresh in a knowledge slot.
+//!
+//! A [`ScrapeSource`] says "fetch `url` into slot N every `every`" (e.g. `6h`). Entries live in
+//! **KB_OIKOS** under `source/{id}`. Each tick the Heartbeat calls [`SourceRegistry::run_due`],
+//! which dispatches [`Goal::UpdateKnowledgeSlot`] for every stale source and records the outcome
+//! (`last_success_ms`, `last_error`) on the entry. A failing source is retried after its
+//! interval, not every tick.
+
+use super::schedule::parse_interval;
+use super::Orchestrator;
+use crate::knowledge::{KbType, KnowledgeStore};
+use crate::shared::{Goal, TenantContext};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Key prefix for scrape sources in **KB_OIKOS** (`source/{id}`).
+pub const SOURCE_PREFIX: &str = "source/";
+
+/// A page refreshed into a knowledge slot on an interval, stored in **KB_OIKOS**.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScrapeSource {
+    pub id: String,
+    pub name: String,
+    pub url: String,
+    /// Slot (1–8) the scraped events are written to.
+    pub slot_id: u8,
+    /// Refresh interval as written (`30m`, `6h`, `1d`).
+    pub every: String,
+    pub interval_ms: i64,
+    /// Context the refresh is dispatched with (tenant, agent).
+    pub ctx: TenantContext,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    pub created_at_ms: i64,
+    #[serde(default)]
+    pub last_attempt_ms: Option<i64>,
+    #[serde(default)]
+    pub last_success_ms: Option<i64>,
+    #[serde(default)]
+    pub last_error: Option<String>,
+    #[serde(default)]
+    pub last_error_ms: Option<i64>,
+    #[serde(default)]
+    pub success_count: u64,
+    #[serde(default)]
+    pub failure_count: u64,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// A source could not be registered.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InvalidSource(pub String);
+
+impl std::fmt::Display for InvalidSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid source: {}", self.0)
+    }
+}
+
+impl std::error::Error for InvalidSource {}
+
+impl ScrapeSource {
+    /// A source created at `now_ms` (`KnowledgeStore::now_ms`), due on the next tick. `every` is
+    /// an interval like `6h` (or `@every 6h`).
+    pub fn new(
+        name: impl Into<String>,
+        url: impl Into<String>,
+        slot_id: u8,
+        every: &str,
+        ctx: TenantContext,
+        now_ms: i64,
+    ) -> Result<Self, InvalidSource> {
+        let url = url.into().trim().to_string();
+        if !(url.starts_with("http://") || url.starts_with("https://")) {
+            return Err(InvalidSource(format!("url '{}' must be http(s)", url)));
+        }
+        if !(1..=8).contains(&slot_id) {
+            return Err(InvalidSource(format!("slot_id {} (expected 1–8)", slot_id)));
+        }
+        let every = every.trim();
+        let interval = every.strip_prefix("@every").unwrap_or(every).trim();
+        let interval_ms = parse_interval(interval).map_err(|e| InvalidSource(e.0))?;
+        Ok(Self {
+            id: Uuid::new_v4().simple().to_string(),
+            name: name.into(),
+            url,
+            slot_id,
+            every: interval.to_string(),
+            interval_ms,
+            ctx,
+            enabled: true,
+            created_at_ms: now_ms,
+            last_attempt_ms: None,
+            last_success_ms: None,
+            last_error: None,
+            last_error_ms: None,
+            success_count: 0,
+            failure_count: 0,
+        })
+    }
+
+    /// Whether the slot holds a scrape of this source younger than its interval.
+    pub fn is_fresh(&self, now_ms: i64) -> bool {
+        self.last_success_ms.is_some_and(|t| now_ms - t < self.interval_ms)
+    }
+
+    /// Stale and not attempted within the last interval.
+    pub fn is_due(&self, now_ms: i64) -> bool {
+        self.enabled
+            && !self.is_fresh(now_ms)
+            && self.last_attempt_ms.is_none_or(|t| now_ms - t >= self.interval_ms)
+    }
+
+    /// The entry as listed by the API: the stored fields plus `fresh` and `next_refresh_ms`.
+    pub fn status(&self, now_ms: i64) -> serde_json::Value {
+        let mut value = serde_json::to_value(self).unwrap_or_default();
+        let next = self.last_attempt_ms.map_or(now_ms, |t| (t + self.interval_ms).max(now_ms));
+        value["fresh"] = serde_json::json!(self.is_fresh(now_ms));
+        value["next_refresh_ms"] = serde_json::json!(self.enabled.then_some(next));
+        value
+    }
+}
+
+/// Outcome of one source refresh.
+#[derive(Debug, Clone, Serialize)]
+pub struct SourceRefresh {
+    pub id: String,
+    pub name: String,
+    pub ran_at_ms: i64,
+    pub ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Scrape sources persisted in **KB_OIKOS**.
+#[derive(Clone)]
+pub struct SourceRegistry {
+    store: Arc<KnowledgeStore>,
+}
+
+impl SourceRegistry {
+    pub fn new(store: Arc<KnowledgeStore>) -> Self {
+        Self { store }
+    }
+
+    /// Stores (or replaces) a source.
+    pub fn set(&self, source: &ScrapeSource) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let bytes = serde_json::to_vec(source)?;
+        self.store.insert(KbType::Oikos.slot_id(), &key(&source.id), &bytes)?;
+        Ok(())
+    }
+
+    pub fn get(&self, id: &str) -> Option<ScrapeSource> {
+        self.store
+            .get(KbType::Oikos.slot_id(), &key(id))
+            .ok()
+            .flatten()
+            .and_then(|b| serde_json::from_slice(&b).ok())
+    }
+
+    /// All sources, oldest first.
+    pub fn list(&self) -> Result<Vec<ScrapeSource>, sled::Error> {
+        let mut sources: Vec<ScrapeSource> = self
+            .store
+            .scan_kv(KbType::Oikos.slot_id())?
+            .into_iter()
+            .filter(|(k, _)| k.starts_with(SOURCE_PREFIX))
+            .filter_map(|(_, bytes)| serde_json::from_slice(&bytes).ok())
+            .collect();
+        sources.sort_by_key(|s| s.created_at_ms);
+        Ok(sources)
+    }
+
+    /// Removes a source. Returns whether it existed.
+    pub fn remove(&self, id: &str) -> Result<bool, sled::Error> {
+        Ok(self.store.remove(KbType::Oikos.slot_id(), &key(id))?.is_some())
+    }
+
+    /// Refreshes every stale source (see [`Self::run_due_at`]).
+    pub async fn run_due(
+        &self,
+        orchestrator: &Orchestrator,
+    ) -> Result<Vec<SourceRefresh>, Box<dyn std::error::Error + Send + Sync>> {
+        self.run_due_at(orchestrator, self.store.now_ms()).await
+    }
+
+    /// Dispatches `UpdateKnowledgeSlot` for every source due at `now`, one at a time, and records
+    /// the outcome on the source. A slot disabled by the control panel counts as a failure.
+    pub async fn run_due_at(
+        &self,
+        orchestrator: &Orchestrator,
+        now: i64,
+    ) -> Result<Vec<SourceRefresh>, Box<dyn std::error::Error + Send + Sync>> {
+        let mut refreshes = Vec::new();
+        for mut source in self.list()? {
+            if !source.is_due(now) {
+                continue;
+            }
+            source.last_attempt_ms = Some(now);
+            self.set(&source)?;
+
+            let mut ctx = source.ctx.clone();
+            if ctx.correlation_id.is_none() {
+                ctx.correlation_id = Some(format!("source-{}-{}", source.id, now));
+            }
+            let goal = Goal::UpdateKnowledgeSlot {
+                slot_id: source.slot_id,
+                source_url: Some(source.url.clone()),
+                source_html: None,
+            };
+            let error = match orchestrator.dispatch(&ctx, goal).await {
+                Ok(result) if result.get("status").and_then(|s| s.as_str()) == Some("kb_disabled") => {
+                    Some(result["message"].as_str().unwrap_or("slot is disabled").to_string())
+                }
+                Ok(_) => None,
+                Err(e) => Some(e.to_string()),
+            };
+            // Removed while the page was fetched: nothing to record.
+            let Some(mut source) = self.get(&source.id) else {
+                continue;
+            };
+            match &error {
+                None => {
+                    source.last_success_ms = Some(now);
+                    source.success_count += 1;
+                }
+                Some(e) => {
+                    source.last_error = Some(e.clone());
+                    source.last_error_ms = Some(now);
+                    source.failure_count += 1;
+                }
+            }
+            self.set(&source)?;
+
+            tracing::info!(
+                target: "pagi::sources",
+                id = %source.id,
+                name = %source.name,
+                slot_id = source.slot_id,
+                ok = error.is_none(),
+                error = error.as_deref().unwrap_or(""),
+                "Scrape source refreshed"
+            );
+            refreshes.push(SourceRefresh {
+                id: source.id,
+                name: source.name,
+                ran_at_ms: now,
+                ok: error.is_none(),
+                error,
+            });
+        }
+        Ok(refreshes)
+    }
+}
+
+fn key(id: &str) -> String {
+    format!("{}{}", SOURCE_PREFIX, id)
+}
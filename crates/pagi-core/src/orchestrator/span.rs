@@ -0,0 +1,45 @@
+//! Tracing spans for dispatch chains: each dispatch opens a `dispatch` span (the root of the goal's
+//! trace), and each plan step a `plan_step` span under it, so sub-plans and skill calls nest below
+//! the step that ran them. The gateway exports these through OpenTelemetry when OTLP is configured;
+//! without a subscriber they cost nothing.
+
+use crate::shared::TenantContext;
+use tracing::field::Empty;
+use tracing::Span;
+
+/// Root span of one dispatch, tagged with the request's correlation id.
+pub(super) fn dispatch_span(ctx: &TenantContext, goal: &str) -> Span {
+    tracing::info_span!(
+        target: "pagi::orchestrator",
+        "dispatch",
+        otel.name = %format!("dispatch {}", goal),
+        otel.status_code = Empty,
+        goal,
+        tenant_id = %ctx.tenant_id,
+        agent_id = %ctx.resolved_agent_id(),
+        correlation_id = ctx.correlation_id.as_deref().unwrap_or(""),
+        error = Empty,
+    )
+}
+
+/// Span of plan step `index` of `intent` (`depth` 1 is the top-level plan).
+pub(super) fn step_span(intent: &str, depth: usize, index: usize, label: &str) -> Span {
+    tracing::info_span!(
+        target: "pagi::orchestrator",
+        "plan_step",
+        otel.name = %format!("step {}", label),
+        otel.status_code = Empty,
+        intent,
+        depth,
+        index,
+        skill = label,
+        executed_skill = Empty,
+        error = Empty,
+    )
+}
+
+/// Marks `span` as failed with `error` (OpenTelemetry status ERROR).
+pub(super) fn record_error(span: &Span, error: &(dyn std::error::Error + Send + Sync)) {
+    span.record("otel.status_code", "ERROR");
+    span.record("error", tracing::field::display(error));
+}
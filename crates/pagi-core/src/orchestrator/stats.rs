@@ -0,0 +1,215 @@
+//! Rolling per-skill execution statistics.
+//!
+//! Every skill attempt made by the orchestrator is recorded (outcome and latency). Totals plus a
+//! window of the last [`WINDOW`] outcomes are kept per skill and persisted in KB-5 (Techne) under
+//! `skill_stats/{skill}` when a store is attached. The dynamic planner sees the resulting health
+//! in its prompt, and plan steps with `fallbacks` try healthy skills first.
+
+use crate::knowledge::{KbType, KnowledgeStore};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, PoisonError, RwLock};
+
+/// KB-5 key prefix for persisted stats.
+pub const SKILL_STATS_PREFIX: &str = "skill_stats/";
+
+/// Outcomes kept for the rolling success rate and latency.
+const WINDOW: usize = 50;
+/// Recent outcomes needed before a skill can be judged unhealthy.
+const MIN_SAMPLES: usize = 5;
+/// Rolling success rate below which a skill is unhealthy.
+const UNHEALTHY_BELOW: f64 = 0.5;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Sample {
+    ok: bool,
+    latency_ms: u64,
+}
+
+/// Execution history of one skill.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SkillStats {
+    pub skill: String,
+    pub total_calls: u64,
+    pub total_failures: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+    pub last_updated_ms: i64,
+    #[serde(default)]
+    recent: VecDeque<Sample>,
+}
+
+impl SkillStats {
+    fn new(skill: &str) -> Self {
+        Self {
+            skill: skill.to_string(),
+            total_calls: 0,
+            total_failures: 0,
+            last_error: None,
+            last_updated_ms: 0,
+            recent: VecDeque::new(),
+        }
+    }
+
+    fn record(&mut self, ok: bool, latency_ms: u64, error: Option<String>, now_ms: i64) {
+        self.total_calls += 1;
+        if !ok {
+            self.total_failures += 1;
+            self.last_error = error;
+        }
+        self.last_updated_ms = now_ms;
+        if self.recent.len() == WINDOW {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(Sample { ok, latency_ms });
+    }
+
+    /// Success rate over the recent window (None before the first call).
+    pub fn success_rate(&self) -> Option<f64> {
+        if self.recent.is_empty() {
+            return None;
+        }
+        let ok = self.recent.iter().filter(|s| s.ok).count();
+        Some(ok as f64 / self.recent.len() as f64)
+    }
+
+    /// Mean latency over the recent window.
+    pub fn avg_latency_ms(&self) -> Option<u64> {
+        if self.recent.is_empty() {
+            return None;
+        }
+        Some(self.recent.iter().map(|s| s.latency_ms).sum::<u64>() / self.recent.len() as u64)
+    }
+
+    /// True when enough recent calls failed to treat the skill as currently failing.
+    pub fn is_unhealthy(&self) -> bool {
+        self.recent.len() >= MIN_SAMPLES && self.success_rate().is_some_and(|r| r < UNHEALTHY_BELOW)
+    }
+
+    /// API shape: totals plus the rolling success rate, latency, and health.
+    pub fn summary(&self) -> serde_json::Value {
+        serde_json::json!({
+            "skill": self.skill,
+            "total_calls": self.total_calls,
+            "total_failures": self.total_failures,
+            "recent_calls": self.recent.len(),
+            "success_rate": self.success_rate(),
+            "avg_latency_ms": self.avg_latency_ms(),
+            "healthy": !self.is_unhealthy(),
+            "last_error": self.last_error,
+            "last_updated_ms": self.last_updated_ms,
+        })
+    }
+}
+
+/// Stats for all skills, optionally persisted to KB-5.
+#[derive(Default)]
+pub struct SkillStatsRegistry {
+    stats: RwLock<HashMap<String, SkillStats>>,
+    store: Option<Arc<KnowledgeStore>>,
+}
+
+impl SkillStatsRegistry {
+    /// Loads persisted stats from KB-5 and writes every update back.
+    pub fn with_store(store: Arc<KnowledgeStore>) -> Self {
+        let slot = KbType::Techne.slot_id();
+        let stats = store
+            .scan_kv(slot)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|(key, _)| key.starts_with(SKILL_STATS_PREFIX))
+            .filter_map(|(_, bytes)| serde_json::from_slice::<SkillStats>(&bytes).ok())
+            .map(|s| (s.skill.clone(), s))
+            .collect();
+        Self {
+            stats: RwLock::new(stats),
+            store: Some(store),
+        }
+    }
+
+    /// Records one attempt of `skill`.
+    pub fn record(&self, skill: &str, ok: bool, latency_ms: u64, error: Option<String>) {
+        let now_ms = self.store.as_ref().map_or_else(crate::clock::system_now_ms, |s| s.now_ms());
+        let snapshot = {
+            let mut stats = self.stats.write().unwrap_or_else(PoisonError::into_inner);
+            let entry = stats.entry(skill.to_string()).or_insert_with(|| SkillStats::new(skill));
+            entry.record(ok, latency_ms, error, now_ms);
+            entry.clone()
+        };
+        if let Some(store) = &self.store {
+            let key = format!("{}{}", SKILL_STATS_PREFIX, skill);
+            let persisted = serde_json::to_vec(&snapshot)
+                .map_err(|e| e.to_string())
+                .and_then(|bytes| store.insert(KbType::Techne.slot_id(), &key, &bytes).map_err(|e| e.to_string()));
+            if let Err(e) = persisted {
+                tracing::warn!(target: "pagi::orchestrator", skill, error = %e, "Failed to persist skill stats");
+            }
+        }
+    }
+
+    pub fn get(&self, skill: &str) -> Option<SkillStats> {
+        self.stats.read().ok()?.get(skill).cloned()
+    }
+
+    /// All stats, sorted by skill name.
+    pub fn all(&self) -> Vec<SkillStats> {
+        let mut all: Vec<SkillStats> = self
+            .stats
+            .read()
+            .map(|s| s.values().cloned().collect())
+            .unwrap_or_default();
+        all.sort_by(|a, b| a.skill.cmp(&b.skill));
+        all
+    }
+
+    pub fn is_unhealthy(&self, skill: &str) -> bool {
+        self.get(skill).is_some_and(|s| s.is_unhealthy())
+    }
+
+    /// Orders candidate skills healthy-first, keeping the declared order within each group.
+    pub fn prefer_healthy<'a>(&self, candidates: &[&'a str]) -> Vec<&'a str> {
+        let (healthy, unhealthy): (Vec<&str>, Vec<&str>) =
+            candidates.iter().partition(|skill| !self.is_unhealthy(skill));
+        healthy.into_iter().chain(unhealthy).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rolling_window_and_health() {
+        let registry = SkillStatsRegistry::default();
+        for _ in 0..4 {
+            registry.record("Flaky", false, 10, Some("boom".into()));
+        }
+        assert!(!registry.is_unhealthy("Flaky"), "too few samples");
+        registry.record("Flaky", false, 30, Some("boom".into()));
+        assert!(registry.is_unhealthy("Flaky"));
+        assert_eq!(registry.get("Flaky").unwrap().avg_latency_ms(), Some(14));
+
+        for _ in 0..WINDOW {
+            registry.record("Flaky", true, 10, None);
+        }
+        let stats = registry.get("Flaky").unwrap();
+        assert_eq!(stats.success_rate(), Some(1.0));
+        assert_eq!((stats.total_calls, stats.total_failures), (55, 5));
+        assert_eq!(registry.prefer_healthy(&["A", "Flaky"]), ["A", "Flaky"]);
+    }
+
+    #[test]
+    fn persists_to_kb5() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = Arc::new(KnowledgeStore::open_path(dir.path()).unwrap());
+        let registry = SkillStatsRegistry::with_store(Arc::clone(&store));
+        registry.record("Echo", true, 5, None);
+        registry.record("Echo", false, 7, Some("bad input".into()));
+
+        let reloaded = SkillStatsRegistry::with_store(store);
+        let stats = reloaded.get("Echo").unwrap();
+        assert_eq!(stats.total_calls, 2);
+        assert_eq!(stats.success_rate(), Some(0.5));
+        assert_eq!(stats.last_error.as_deref(), Some("bad input"));
+    }
+}
@@ -0,0 +1,245 @@
+//! Per-tenant view over the global SkillRegistry.
+//!
+//! Each tenant (`TenantContext::tenant_id`) can restrict which skills it may run (allowlist and/or
+//! denylist), carry tenant-scoped skill config (e.g. ModelRouter API settings), and register its
+//! own skill instances that shadow the global ones. The Orchestrator resolves every skill through
+//! [`TenantSkillRegistry::resolve`] when one is attached; tenants without a policy see the global
+//! registry unchanged.
+//!
+//! JSON shape (`config/tenants.json`):
+//! `{ "tenants": { "acme": { "allowed_skills": [...], "disabled_skills": [...], "skill_config": { "ModelRouter": { ... } } } } }`
+
+use super::{AgentSkill, SkillRegistry};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+use std::sync::{Arc, PoisonError, RwLock};
+
+/// Skill access and config for one tenant.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TenantSkillPolicy {
+    /// When set, only these skills are available to the tenant.
+    pub allowed_skills: Option<Vec<String>>,
+    /// Skills the tenant may never run (applied after `allowed_skills`).
+    pub disabled_skills: Vec<String>,
+    /// Tenant-scoped config per skill name (read by whoever builds the tenant's skill instances).
+    pub skill_config: HashMap<String, serde_json::Value>,
+}
+
+impl TenantSkillPolicy {
+    /// Whether `skill` is enabled under this policy.
+    pub fn allows(&self, skill: &str) -> bool {
+        let allowed = self
+            .allowed_skills
+            .as_ref()
+            .is_none_or(|list| list.iter().any(|s| s == skill));
+        allowed && !self.disabled_skills.iter().any(|s| s == skill)
+    }
+}
+
+/// A skill exists but the tenant's policy does not allow it.
+#[derive(Debug)]
+pub struct SkillNotEnabled {
+    pub tenant_id: String,
+    pub skill: String,
+}
+
+impl fmt::Display for SkillNotEnabled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "skill {} is not enabled for tenant {}", self.skill, self.tenant_id)
+    }
+}
+
+impl std::error::Error for SkillNotEnabled {}
+
+#[derive(Debug, Deserialize)]
+struct TenantsFile {
+    tenants: HashMap<String, TenantSkillPolicy>,
+}
+
+/// Skill name → tenant-specific instance.
+type TenantSkills = HashMap<String, Arc<dyn AgentSkill>>;
+
+/// Tenant policies plus tenant-specific skill instances.
+#[derive(Default)]
+pub struct TenantSkillRegistry {
+    policies: RwLock<HashMap<String, TenantSkillPolicy>>,
+    /// tenant_id → tenant-specific instances.
+    overrides: RwLock<HashMap<String, TenantSkills>>,
+}
+
+impl fmt::Debug for TenantSkillRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TenantSkillRegistry")
+            .field("tenants", &self.tenant_ids())
+            .finish()
+    }
+}
+
+impl TenantSkillRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Loads policies from a JSON file (`{ "tenants": { ... } }`).
+    pub fn load_json_path<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let s = std::fs::read_to_string(path)?;
+        let file: TenantsFile = serde_json::from_str(&s)?;
+        let registry = Self::new();
+        for (tenant_id, policy) in file.tenants {
+            registry.set_policy(tenant_id, policy);
+        }
+        Ok(registry)
+    }
+
+    /// Sets (replaces) a tenant's policy.
+    pub fn set_policy(&self, tenant_id: impl Into<String>, policy: TenantSkillPolicy) {
+        self.policies
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(tenant_id.into(), policy);
+    }
+
+    /// The tenant's policy, if one is configured.
+    pub fn policy(&self, tenant_id: &str) -> Option<TenantSkillPolicy> {
+        self.policies.read().ok()?.get(tenant_id).cloned()
+    }
+
+    /// Tenant-scoped config for one skill.
+    pub fn skill_config(&self, tenant_id: &str, skill: &str) -> Option<serde_json::Value> {
+        self.policy(tenant_id)?.skill_config.get(skill).cloned()
+    }
+
+    /// Registers a tenant-specific instance that shadows the global skill of the same name.
+    pub fn register_for_tenant(&self, tenant_id: impl Into<String>, skill: Arc<dyn AgentSkill>) {
+        self.overrides
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .entry(tenant_id.into())
+            .or_default()
+            .insert(skill.name().to_string(), skill);
+    }
+
+    /// Tenants with a policy or skill overrides.
+    pub fn tenant_ids(&self) -> Vec<String> {
+        let mut ids: Vec<String> = self
+            .policies
+            .read()
+            .map(|p| p.keys().cloned().collect())
+            .unwrap_or_default();
+        if let Ok(o) = self.overrides.read() {
+            ids.extend(o.keys().cloned());
+        }
+        ids.sort();
+        ids.dedup();
+        ids
+    }
+
+    /// Whether the tenant may run `skill` (true when the tenant has no policy).
+    pub fn is_enabled(&self, tenant_id: &str, skill: &str) -> bool {
+        self.policy(tenant_id).is_none_or(|p| p.allows(skill))
+    }
+
+    /// Resolves a skill for a tenant: policy check, then the tenant's override, then the global
+    /// registry. `Ok(None)` means no such skill exists.
+    pub fn resolve(
+        &self,
+        base: &SkillRegistry,
+        tenant_id: &str,
+        skill: &str,
+    ) -> Result<Option<Arc<dyn AgentSkill>>, SkillNotEnabled> {
+        if !self.is_enabled(tenant_id, skill) {
+            return Err(SkillNotEnabled {
+                tenant_id: tenant_id.to_string(),
+                skill: skill.to_string(),
+            });
+        }
+        let tenant_skill = self
+            .overrides
+            .read()
+            .ok()
+            .and_then(|o| o.get(tenant_id).and_then(|m| m.get(skill)).cloned());
+        Ok(tenant_skill.or_else(|| base.get(skill)))
+    }
+
+    /// Skill names visible to a tenant (global + overrides, filtered by policy).
+    pub fn skill_names(&self, base: &SkillRegistry, tenant_id: &str) -> Vec<String> {
+        let mut names = base.skill_names();
+        if let Some(o) = self.overrides.read().ok().and_then(|o| o.get(tenant_id).cloned()) {
+            names.extend(o.into_keys());
+        }
+        names.sort();
+        names.dedup();
+        names.retain(|n| self.is_enabled(tenant_id, n));
+        names
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shared::TenantContext;
+
+    struct Named(&'static str, &'static str);
+
+    #[async_trait::async_trait]
+    impl AgentSkill for Named {
+        fn name(&self) -> &str {
+            self.0
+        }
+
+        async fn execute(
+            &self,
+            _ctx: &TenantContext,
+            _payload: Option<serde_json::Value>,
+        ) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>> {
+            Ok(serde_json::json!({ "instance": self.1 }))
+        }
+    }
+
+    #[test]
+    fn policies_filter_and_overrides_shadow() {
+        let mut base = SkillRegistry::new();
+        base.register(Arc::new(Named("ModelRouter", "global")));
+        base.register(Arc::new(Named("FsWrite", "global")));
+        base.register(Arc::new(Named("Search", "global")));
+
+        let tenants = TenantSkillRegistry::new();
+        tenants.set_policy(
+            "acme",
+            TenantSkillPolicy {
+                allowed_skills: Some(vec!["ModelRouter".into(), "Search".into()]),
+                disabled_skills: vec!["Search".into()],
+                ..Default::default()
+            },
+        );
+        tenants.register_for_tenant("acme", Arc::new(Named("ModelRouter", "acme")));
+
+        assert_eq!(tenants.skill_names(&base, "acme"), ["ModelRouter"]);
+        assert!(tenants.resolve(&base, "acme", "FsWrite").is_err());
+        assert!(tenants.resolve(&base, "acme", "Search").is_err());
+        assert!(tenants.resolve(&base, "acme", "Missing").is_err());
+        assert!(tenants.resolve(&base, "acme", "ModelRouter").unwrap().is_some());
+
+        // Tenants without a policy see the global registry.
+        assert_eq!(tenants.skill_names(&base, "other").len(), 3);
+        assert!(tenants.resolve(&base, "other", "Missing").unwrap().is_none());
+    }
+
+    #[test]
+    fn load_json_policies() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tenants.json");
+        std::fs::write(
+            &path,
+            r#"{"tenants": {"acme": {"disabled_skills": ["FsWrite"], "skill_config": {"ModelRouter": {"model": "m"}}}}}"#,
+        )
+        .unwrap();
+        let tenants = TenantSkillRegistry::load_json_path(&path).unwrap();
+        assert!(!tenants.is_enabled("acme", "FsWrite"));
+        assert!(tenants.is_enabled("acme", "ModelRouter"));
+        assert_eq!(tenants.skill_config("acme", "ModelRouter").unwrap()["model"], "m");
+    }
+}
@@ -0,0 +1,221 @@
+//! Tool-calling chat (`Goal::ToolChat`): the ModelRouter is offered the tenant's KB-5 (Techne)
+//! skill manifests as tools. When the model asks for a tool, the orchestrator runs that skill and
+//! sends the result back, turn after turn, until the model answers without tool calls or the
+//! iteration cap is reached. Every model turn and tool call is recorded in the result's `trace`.
+//!
+//! The ModelRouter payload and reply are provider-neutral; the router converts them to the
+//! provider's function-calling format:
+//! - payload: `{ "messages": [{ "role", "content", "tool_calls"?, "tool_call_id"? }], "tools":
+//!   [{ "name", "description", "parameters" }], "system_prompt"? }`
+//! - reply: `{ "generated": "...", "tool_calls": [{ "id", "name", "arguments" }] }`
+
+use super::{run_cancellable, CancellationToken, EthosScope, GoalCancelled, Orchestrator};
+use crate::knowledge::SkillRecord;
+use crate::shared::TenantContext;
+
+/// Model turns allowed when the goal does not set `max_iterations`.
+pub const DEFAULT_MAX_TOOL_ITERATIONS: u32 = 5;
+
+/// Upper bound on `max_iterations` (guards against runaway tool loops).
+pub const MAX_TOOL_ITERATIONS: u32 = 20;
+
+/// Tool results longer than this are truncated before they go back to the model.
+const MAX_TOOL_RESULT_CHARS: usize = 4000;
+
+/// A tool call requested by the model.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ToolCall {
+    pub id: String,
+    pub name: String,
+    pub arguments: serde_json::Value,
+}
+
+/// Tool definitions for the manifests whose slug is one of `skill_names` (the ModelRouter itself
+/// is never offered).
+pub fn tool_specs(manifests: &[SkillRecord], skill_names: &[String]) -> Vec<serde_json::Value> {
+    manifests
+        .iter()
+        .filter(|m| m.slug != "ModelRouter" && skill_names.contains(&m.slug))
+        .map(|m| {
+            serde_json::json!({
+                "name": m.slug,
+                "description": m.description,
+                "parameters": m.input_schema(),
+            })
+        })
+        .collect()
+}
+
+/// Tool calls in a ModelRouter reply (none when the model answered).
+pub fn parse_tool_calls(reply: &serde_json::Value) -> Vec<ToolCall> {
+    let Some(calls) = reply.get("tool_calls").and_then(|v| v.as_array()) else {
+        return Vec::new();
+    };
+    calls
+        .iter()
+        .enumerate()
+        .filter_map(|(i, call)| {
+            let name = call.get("name").and_then(|v| v.as_str())?.to_string();
+            let id = match call.get("id").and_then(|v| v.as_str()) {
+                Some(id) if !id.is_empty() => id.to_string(),
+                _ => format!("call_{}", i),
+            };
+            let arguments = match call.get("arguments") {
+                Some(serde_json::Value::String(s)) => serde_json::from_str(s).unwrap_or_else(|_| serde_json::json!({})),
+                Some(args) if !args.is_null() => args.clone(),
+                _ => serde_json::json!({}),
+            };
+            Some(ToolCall { id, name, arguments })
+        })
+        .collect()
+}
+
+fn truncate(text: String) -> String {
+    match text.char_indices().nth(MAX_TOOL_RESULT_CHARS) {
+        Some((end, _)) => format!("{}… [truncated]", &text[..end]),
+        None => text,
+    }
+}
+
+impl Orchestrator {
+    /// Runs the tool-calling loop for `prompt`. See the module docs.
+    pub(super) async fn run_tool_chat(
+        &self,
+        ctx: &TenantContext,
+        prompt: String,
+        system_prompt: Option<String>,
+        max_iterations: Option<u32>,
+        token: &CancellationToken,
+    ) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>> {
+        let max_iterations = max_iterations
+            .unwrap_or(DEFAULT_MAX_TOOL_ITERATIONS)
+            .clamp(1, MAX_TOOL_ITERATIONS);
+        let router = self.skill(ctx, "ModelRouter")?;
+        let manifests = self.knowledge.as_ref().map(|k| k.get_skills()).unwrap_or_default();
+        let tools = tool_specs(&manifests, &self.skill_names_for(&ctx.tenant_id));
+        let offered: Vec<&str> = tools.iter().filter_map(|t| t["name"].as_str()).collect();
+
+        let mut messages = vec![serde_json::json!({ "role": "user", "content": prompt })];
+        let mut trace = Vec::new();
+        let mut calls_made = 0;
+        let mut answer = String::new();
+        for iteration in 1..=max_iterations {
+            let mut payload = serde_json::json!({ "messages": messages, "tools": tools });
+            if let Some(system_prompt) = &system_prompt {
+                payload["system_prompt"] = serde_json::json!(system_prompt);
+            }
+            let call = self.execute_skill(router.as_ref(), ctx, Some(payload), EthosScope::Internal);
+            let reply = run_cancellable(token, calls_made, call).await?;
+            answer = reply.get("generated").and_then(|v| v.as_str()).unwrap_or("").to_string();
+            let calls = parse_tool_calls(&reply);
+            trace.push(serde_json::json!({
+                "iteration": iteration,
+                "kind": "model",
+                "generated": answer,
+                "tool_calls": calls.iter().map(|c| c.name.as_str()).collect::<Vec<_>>(),
+                "token_usage": reply.get("token_usage"),
+            }));
+            if calls.is_empty() {
+                return Ok(serde_json::json!({
+                    "goal": "ToolChat",
+                    "status": "ok",
+                    "answer": answer,
+                    "iterations": iteration,
+                    "tool_calls": calls_made,
+                    "trace": trace,
+                }));
+            }
+
+            messages.push(serde_json::json!({
+                "role": "assistant",
+                "content": answer,
+                "tool_calls": calls
+                    .iter()
+                    .map(|c| serde_json::json!({ "id": c.id, "name": c.name, "arguments": c.arguments }))
+                    .collect::<Vec<_>>(),
+            }));
+            for call in calls {
+                let started = std::time::Instant::now();
+                let outcome = match offered.contains(&call.name.as_str()) {
+                    true => match self.skill(ctx, &call.name) {
+                        Ok(skill) => {
+                            let run = self.execute_skill(skill.as_ref(), ctx, Some(call.arguments.clone()), EthosScope::Internal);
+                            run_cancellable(token, calls_made, run).await
+                        }
+                        Err(e) => Err(e),
+                    },
+                    false => Err(format!("unknown tool: {}", call.name).into()),
+                };
+                calls_made += 1;
+                let mut entry = serde_json::json!({
+                    "iteration": iteration,
+                    "kind": "tool",
+                    "tool": call.name,
+                    "call_id": call.id,
+                    "arguments": call.arguments,
+                    "elapsed_ms": started.elapsed().as_millis() as u64,
+                });
+                let content = match outcome {
+                    Ok(output) => {
+                        entry["status"] = serde_json::json!("ok");
+                        let content = output.to_string();
+                        entry["output"] = output;
+                        content
+                    }
+                    Err(e) if e.is::<GoalCancelled>() => return Err(e),
+                    Err(e) => {
+                        tracing::warn!(
+                            target: "pagi::orchestrator",
+                            tool = %call.name,
+                            error = %e,
+                            "Tool call failed; reporting the error to the model"
+                        );
+                        entry["status"] = serde_json::json!("error");
+                        entry["error"] = serde_json::json!(e.to_string());
+                        serde_json::json!({ "error": e.to_string() }).to_string()
+                    }
+                };
+                trace.push(entry);
+                messages.push(serde_json::json!({
+                    "role": "tool",
+                    "tool_call_id": call.id,
+                    "name": call.name,
+                    "content": truncate(content),
+                }));
+            }
+        }
+        tracing::warn!(
+            target: "pagi::orchestrator",
+            max_iterations,
+            tool_calls = calls_made,
+            "Tool chat stopped at the iteration cap"
+        );
+        Ok(serde_json::json!({
+            "goal": "ToolChat",
+            "status": "max_iterations_reached",
+            "answer": answer,
+            "iterations": max_iterations,
+            "tool_calls": calls_made,
+            "trace": trace,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tool_calls_accept_string_or_object_arguments() {
+        let reply = serde_json::json!({ "tool_calls": [
+            { "id": "a", "name": "one", "arguments": "{\"limit\": 2}" },
+            { "name": "two", "arguments": { "x": true } },
+            { "id": "c" },
+        ]});
+        let calls = parse_tool_calls(&reply);
+        assert_eq!(calls.len(), 2);
+        assert_eq!(calls[0].arguments, serde_json::json!({ "limit": 2 }));
+        assert_eq!((calls[1].id.as_str(), calls[1].arguments["x"].as_bool()), ("call_1", Some(true)));
+        assert!(parse_tool_calls(&serde_json::json!({ "generated": "hi" })).is_empty());
+    }
+}
@@ -0,0 +1,255 @@
+//! User-facing error translation: maps internal dispatch errors to friendly, actionable messages.
+//!
+//! Full error details stay in logs (and Chronos, at the call site); chat users get a short message,
+//! a stable `code`, and the request's correlation id to quote to support.
+
+use super::{
+    planner::InvalidPlan, DeadlineExceeded, EthosBlocked, GoalCancelled, SkillError, SkillNotEnabled, Throttled, UnknownSkill,
+};
+use crate::knowledge::TokenBudgetExceeded;
+use serde::Serialize;
+
+/// Friendly rendering of an internal error.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct UserFacingError {
+    /// Stable machine-readable class (e.g. `skill_unavailable`, `timeout`).
+    pub code: &'static str,
+    /// Message safe to show to end users.
+    pub message: String,
+    /// Whether retrying the same request later may succeed.
+    pub retryable: bool,
+    /// Correlation id for support, when known.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub correlation_id: Option<String>,
+}
+
+impl UserFacingError {
+    fn new(code: &'static str, message: &str, retryable: bool) -> Self {
+        Self {
+            code,
+            message: message.to_string(),
+            retryable,
+            correlation_id: None,
+        }
+    }
+
+    pub fn with_correlation_id(mut self, id: impl Into<String>) -> Self {
+        self.correlation_id = Some(id.into());
+        self
+    }
+
+    /// Message with the support reference appended (for plain-text channels such as streaming chat).
+    pub fn display_message(&self) -> String {
+        match &self.correlation_id {
+            Some(id) => format!("{} (reference: {})", self.message, id),
+            None => self.message.clone(),
+        }
+    }
+}
+
+/// Translates an internal error into a [`UserFacingError`]. Typed orchestrator errors are matched
+/// by downcast; errors from skills and providers (which arrive as strings) by message pattern.
+pub fn translate_error(err: &(dyn std::error::Error + 'static)) -> UserFacingError {
+    if err.is::<UnknownSkill>() {
+        return UserFacingError::new(
+            "skill_unavailable",
+            "That capability isn't available right now. An administrator may need to enable the skill.",
+            false,
+        );
+    }
+    if err.is::<SkillNotEnabled>() {
+        return UserFacingError::new(
+            "skill_not_enabled",
+            "That capability isn't enabled for your account. Contact your administrator to turn it on.",
+            false,
+        );
+    }
+    if let Some(blocked) = err.downcast_ref::<EthosBlocked>() {
+        return match blocked.requires_approval() {
+            true => UserFacingError::new(
+                "approval_required",
+                "That action needs an administrator's approval under the workspace's safety policy.",
+                false,
+            ),
+            false => UserFacingError::new(
+                "policy_violation",
+                "That action isn't allowed by the workspace's safety policy.",
+                false,
+            ),
+        };
+    }
+    if let Some(violation) = err.downcast_ref::<SkillError>() {
+        return match violation {
+            SkillError::Timeout { .. } => UserFacingError::new(
+                "timeout",
+                "This is taking longer than expected. Please try again in a moment.",
+                true,
+            ),
+            SkillError::PayloadTooLarge { .. } | SkillError::ResultTooLarge { .. } => UserFacingError::new(
+                "too_large",
+                "That request is too large to process. Try sending less at once.",
+                false,
+            ),
+            SkillError::OutsideSandbox { .. } => UserFacingError::new(
+                "policy_violation",
+                "That action tried to access files outside its allowed folder.",
+                false,
+            ),
+        };
+    }
+    if let Some(throttled) = err.downcast_ref::<Throttled>() {
+        let message = match throttled.reason {
+            "queue_full" => format!(
+                "The service is busy right now. Please try again in {}s.",
+                throttled.retry_after_secs()
+            ),
+            _ => format!(
+                "You're sending requests faster than your workspace allows. Please wait {}s and try again.",
+                throttled.retry_after_secs()
+            ),
+        };
+        return UserFacingError::new(throttled.reason, &message, true);
+    }
+    if err.is::<DeadlineExceeded>() {
+        return UserFacingError::new(
+            "deadline_exceeded",
+            "That took longer than the time allowed for the request. Please try again, or allow more time.",
+            true,
+        );
+    }
+    if let Some(exceeded) = err.downcast_ref::<TokenBudgetExceeded>() {
+        return UserFacingError::new(
+            "budget_exceeded",
+            &format!(
+                "Your workspace has used its language model budget for {}. Contact your administrator to raise it.",
+                exceeded.month
+            ),
+            false,
+        );
+    }
+    if err.is::<GoalCancelled>() {
+        return UserFacingError::new("cancelled", "The request was cancelled before it finished.", false);
+    }
+    if err.is::<InvalidPlan>() {
+        return UserFacingError::new(
+            "unknown_intent",
+            "I couldn't work out how to handle that request. Try rephrasing it or breaking it into smaller steps.",
+            false,
+        );
+    }
+    if err.is::<sled::Error>() {
+        return UserFacingError::new(
+            "storage_unavailable",
+            "My memory store is temporarily unavailable. Please try again shortly.",
+            true,
+        );
+    }
+
+    let text = err.to_string();
+    let lower = text.to_lowercase();
+    if lower.starts_with("unknown intent") {
+        UserFacingError::new(
+            "unknown_intent",
+            "I don't know how to handle that request yet. Try rephrasing it or breaking it into smaller steps.",
+            false,
+        )
+    } else if lower.contains("environment variable not found") || lower.contains("pagi_llm_api_key") {
+        UserFacingError::new(
+            "llm_not_configured",
+            "The language model isn't configured on this server. An administrator needs to set the LLM API key.",
+            false,
+        )
+    } else if lower.contains("(429") || lower.contains("rate limit") {
+        UserFacingError::new(
+            "rate_limited",
+            "The language model provider is busy right now. Please wait a few seconds and try again.",
+            true,
+        )
+    } else if lower.contains("openrouter api error") || lower.contains("error sending request") {
+        UserFacingError::new(
+            "llm_unavailable",
+            "I couldn't reach the language model provider. Please try again in a moment.",
+            true,
+        )
+    } else if lower.contains("requires payload") {
+        UserFacingError::new(
+            "invalid_request",
+            "Your message was missing something I need. Please check it and try again.",
+            false,
+        )
+    } else if lower.contains("ethos") || lower.contains("blocked") {
+        UserFacingError::new(
+            "policy_blocked",
+            "I can't help with that request because it conflicts with the active safety policy.",
+            false,
+        )
+    } else {
+        UserFacingError::new(
+            "internal",
+            "Something went wrong while handling your message. Please try again.",
+            true,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn translate(err: Box<dyn std::error::Error + Send + Sync>) -> UserFacingError {
+        translate_error(err.as_ref())
+    }
+
+    #[test]
+    fn typed_errors_map_to_codes() {
+        assert_eq!(translate(Box::new(UnknownSkill("ModelRouter".into()))).code, "skill_unavailable");
+        let timeout = translate(Box::new(SkillError::Timeout {
+            skill: "ModelRouter".into(),
+            timeout_ms: 10,
+        }));
+        assert_eq!(timeout.code, "timeout");
+        assert!(timeout.retryable);
+        let too_large = translate(Box::new(SkillError::PayloadTooLarge {
+            skill: "DocumentIngest".into(),
+            bytes: 2048,
+            limit: 1024,
+        }));
+        assert_eq!(too_large.code, "too_large");
+        assert!(!too_large.retryable);
+        let throttled = translate(Box::new(Throttled {
+            reason: "rate_limited",
+            tenant_id: "acme".into(),
+            retry_after: std::time::Duration::from_millis(1500),
+        }));
+        assert_eq!(throttled.code, "rate_limited");
+        assert!(throttled.message.contains("wait 2s"));
+        let cancelled = translate(Box::new(GoalCancelled { completed_steps: 1 }));
+        assert_eq!(cancelled.code, "cancelled");
+        assert!(!cancelled.retryable);
+        let budget = translate(Box::new(TokenBudgetExceeded {
+            tenant_id: "acme".into(),
+            month: "2024-03".into(),
+            used_tokens: 1200,
+            budget_tokens: 1000,
+        }));
+        assert_eq!(budget.code, "budget_exceeded");
+        assert!(budget.message.contains("2024-03") && !budget.retryable);
+    }
+
+    #[test]
+    fn string_errors_map_by_pattern_and_never_leak_details() {
+        let e = translate("OpenRouter API error (429 Too Many Requests): slow down".into());
+        assert_eq!(e.code, "rate_limited");
+        let e = translate("unknown intent: frobnicate".into());
+        assert_eq!(e.code, "unknown_intent");
+        let e = translate("segfault in module xyz at 0xdeadbeef".into());
+        assert_eq!(e.code, "internal");
+        assert!(!e.message.contains("0xdeadbeef"));
+    }
+
+    #[test]
+    fn display_message_includes_reference() {
+        let e = translate("boom".into()).with_correlation_id("abc-123");
+        assert!(e.display_message().ends_with("(reference: abc-123)"));
+    }
+}
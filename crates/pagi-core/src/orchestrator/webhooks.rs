@@ -0,0 +1,766 @@
+//! Outbound webhooks: tell external services about captured leads, generated drafts, policy
+//! violations, opened maintenance tasks and completed autonomous goals.
+//!
+//! A [`Webhook`] (URL, event filter, HMAC secret, optional tenant) lives in **KB_OIKOS** under
+//! `webhook/{id}`. [`WebhookRegistry::enqueue`] queues one [`WebhookDelivery`] per matching hook
+//! under `webhook_delivery/{id}`; each tick the Heartbeat calls [`WebhookRegistry::deliver_due`],
+//! which POSTs the due deliveries. A failed attempt is retried with exponential backoff
+//! ([`WEBHOOK_RETRY_BASE_MS`] doubling, at most [`WEBHOOK_RETRY_MAX_MS`]); after
+//! [`WEBHOOK_MAX_ATTEMPTS`] the delivery is kept as `failed` for [`WEBHOOK_FAILED_RETENTION_MS`].
+//! Delivered ones are removed.
+//!
+//! Webhook URLs may not point at loopback, link-local, private or cloud metadata addresses
+//! ([`check_webhook_url`]): [`WebhookRegistry::register`] checks them, and every delivery checks
+//! again (DNS may have changed since). Requests go through [`webhook_client`], which resolves
+//! hosts with the same rules at connect time ([`PublicResolver`]) and does not follow redirects.
+//!
+//! The orchestrator queues `lead.captured` (LeadCapture), `draft.generated` (DraftResponse),
+//! `policy.violated` (an Ethos deny) and `goal.completed` (an `AutonomousGoal` plan finished); the
+//! gateway's Oikos guardian queues `maintenance.opened`. Events listed in `[chat] notify` are also
+//! posted to chat by the `NotifyChat` skill.
+//! Each request carries `X-Pagi-Event`, `X-Pagi-Delivery`, `X-Pagi-Timestamp` and
+//! `X-Pagi-Signature: sha256={hex}`, see [`sign`].
+
+use super::Orchestrator;
+use crate::knowledge::{KbType, KnowledgeStore};
+use crate::secrets::{hex, hmac_sha256};
+use crate::shared::TenantContext;
+use serde::{Deserialize, Serialize};
+use std::net::IpAddr;
+use std::sync::{Arc, OnceLock, PoisonError};
+use uuid::Uuid;
+
+/// Key prefix for webhooks in **KB_OIKOS** (`webhook/{id}`).
+pub const WEBHOOK_PREFIX: &str = "webhook/";
+
+/// Key prefix for queued deliveries in **KB_OIKOS** (`webhook_delivery/{id}`).
+pub const WEBHOOK_DELIVERY_PREFIX: &str = "webhook_delivery/";
+
+/// Attempts after which a delivery is given up on (kept as `failed`).
+pub const WEBHOOK_MAX_ATTEMPTS: u32 = 6;
+
+/// Backoff after the first failed attempt; it doubles with each further one.
+pub const WEBHOOK_RETRY_BASE_MS: i64 = 30_000;
+
+/// Longest backoff between two attempts.
+pub const WEBHOOK_RETRY_MAX_MS: i64 = 3_600_000;
+
+/// How long a delivery given up on is kept (from its creation) before it is removed.
+pub const WEBHOOK_FAILED_RETENTION_MS: i64 = 7 * 24 * 60 * 60 * 1000;
+
+/// Deliveries attempted per [`WebhookRegistry::deliver_due`] call.
+const DELIVERIES_PER_RUN: usize = 20;
+
+/// Skill that posts [chat events](Orchestrator::with_chat_notifications).
+const CHAT_SKILL: &str = "NotifyChat";
+
+const DELIVERY_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// Cloud instance metadata hosts webhooks may never reach.
+const METADATA_HOSTS: &[&str] = &["metadata", "metadata.google.internal", "metadata.azure.com"];
+
+/// What happened.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum WebhookEvent {
+    #[serde(rename = "lead.captured")]
+    LeadCaptured,
+    #[serde(rename = "draft.generated")]
+    DraftGenerated,
+    #[serde(rename = "policy.violated")]
+    PolicyViolated,
+    #[serde(rename = "maintenance.opened")]
+    MaintenanceOpened,
+    #[serde(rename = "goal.completed")]
+    GoalCompleted,
+}
+
+impl WebhookEvent {
+    pub const ALL: [WebhookEvent; 5] = [
+        WebhookEvent::LeadCaptured,
+        WebhookEvent::DraftGenerated,
+        WebhookEvent::PolicyViolated,
+        WebhookEvent::MaintenanceOpened,
+        WebhookEvent::GoalCompleted,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WebhookEvent::LeadCaptured => "lead.captured",
+            WebhookEvent::DraftGenerated => "draft.generated",
+            WebhookEvent::PolicyViolated => "policy.violated",
+            WebhookEvent::MaintenanceOpened => "maintenance.opened",
+            WebhookEvent::GoalCompleted => "goal.completed",
+        }
+    }
+
+    /// The event a successful call of `skill` raises.
+    pub fn for_skill(skill: &str) -> Option<Self> {
+        match skill {
+            "LeadCapture" => Some(WebhookEvent::LeadCaptured),
+            "DraftResponse" => Some(WebhookEvent::DraftGenerated),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for WebhookEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// An endpoint notified of events, stored in **KB_OIKOS**.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Webhook {
+    pub id: String,
+    pub name: String,
+    pub url: String,
+    /// Events delivered; empty for all of them.
+    #[serde(default)]
+    pub events: Vec<WebhookEvent>,
+    /// Only this tenant's events; None for every tenant.
+    #[serde(default)]
+    pub tenant_id: Option<String>,
+    /// HMAC-SHA256 key for `X-Pagi-Signature`.
+    pub secret: String,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    pub created_at_ms: i64,
+    #[serde(default)]
+    pub last_delivery_ms: Option<i64>,
+    #[serde(default)]
+    pub last_error: Option<String>,
+    #[serde(default)]
+    pub last_error_ms: Option<i64>,
+    #[serde(default)]
+    pub delivered_count: u64,
+    #[serde(default)]
+    pub failed_count: u64,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// A webhook could not be registered.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InvalidWebhook(pub String);
+
+impl std::fmt::Display for InvalidWebhook {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid webhook: {}", self.0)
+    }
+}
+
+impl std::error::Error for InvalidWebhook {}
+
+impl Webhook {
+    /// An enabled webhook created at `now_ms` (`KnowledgeStore::now_ms`). Without a `secret`, one
+    /// is generated (`whsec_` and 64 hex digits).
+    pub fn new(
+        name: impl Into<String>,
+        url: impl Into<String>,
+        events: Vec<WebhookEvent>,
+        tenant_id: Option<String>,
+        secret: Option<String>,
+        now_ms: i64,
+    ) -> Result<Self, InvalidWebhook> {
+        let url = url.into().trim().to_string();
+        if !(url.starts_with("http://") || url.starts_with("https://")) {
+            return Err(InvalidWebhook(format!("url '{}' must be http(s)", url)));
+        }
+        let secret = match secret.map(|s| s.trim().to_string()) {
+            Some(secret) if secret.len() < 16 => {
+                return Err(InvalidWebhook("secret must be at least 16 characters".to_string()))
+            }
+            Some(secret) => secret,
+            None => format!("whsec_{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple()),
+        };
+        let mut events = events;
+        events.sort_by_key(|e| e.as_str());
+        events.dedup();
+        Ok(Self {
+            id: Uuid::new_v4().simple().to_string(),
+            name: name.into(),
+            url,
+            events,
+            tenant_id: tenant_id.filter(|t| !t.trim().is_empty()),
+            secret,
+            enabled: true,
+            created_at_ms: now_ms,
+            last_delivery_ms: None,
+            last_error: None,
+            last_error_ms: None,
+            delivered_count: 0,
+            failed_count: 0,
+        })
+    }
+
+    /// Whether `event` for `tenant_id` goes to this hook.
+    pub fn matches(&self, event: WebhookEvent, tenant_id: &str) -> bool {
+        self.enabled
+            && (self.events.is_empty() || self.events.contains(&event))
+            && self.tenant_id.as_deref().is_none_or(|t| t == tenant_id)
+    }
+
+    /// The entry as listed by the API: the stored fields without the secret.
+    pub fn status(&self) -> serde_json::Value {
+        let mut value = serde_json::to_value(self).unwrap_or_default();
+        if let Some(fields) = value.as_object_mut() {
+            fields.remove("secret");
+        }
+        value
+    }
+}
+
+/// `pending` until delivered (then removed) or given up on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeliveryStatus {
+    Pending,
+    Failed,
+}
+
+/// One event queued for one webhook, stored in **KB_OIKOS**.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookDelivery {
+    /// Creation time and a random suffix, so the queue scans oldest first.
+    pub id: String,
+    pub webhook_id: String,
+    pub event: WebhookEvent,
+    pub tenant_id: String,
+    pub data: serde_json::Value,
+    pub created_at_ms: i64,
+    pub status: DeliveryStatus,
+    #[serde(default)]
+    pub attempts: u32,
+    pub next_attempt_ms: i64,
+    #[serde(default)]
+    pub last_error: Option<String>,
+}
+
+impl WebhookDelivery {
+    /// The request body: `{ id, event, tenant_id, created_at_ms, data }`.
+    pub fn body(&self) -> serde_json::Value {
+        serde_json::json!({
+            "id": self.id,
+            "event": self.event,
+            "tenant_id": self.tenant_id,
+            "created_at_ms": self.created_at_ms,
+            "data": self.data,
+        })
+    }
+}
+
+/// Outcome of one delivery attempt.
+#[derive(Debug, Clone, Serialize)]
+pub struct DeliveryAttempt {
+    pub delivery_id: String,
+    pub webhook_id: String,
+    pub event: WebhookEvent,
+    pub ok: bool,
+    pub attempts: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// `X-Pagi-Signature` value: `sha256=` and the hex HMAC-SHA256 of `{timestamp_ms}.{body}` under
+/// `secret`. Receivers recompute it over the raw body and the `X-Pagi-Timestamp` header.
+pub fn sign(secret: &str, timestamp_ms: i64, body: &[u8]) -> String {
+    let mut message = format!("{}.", timestamp_ms).into_bytes();
+    message.extend_from_slice(body);
+    format!("sha256={}", hex(&hmac_sha256(secret.as_bytes(), &message)))
+}
+
+/// Whether a webhook may be sent to `ip`: no loopback, unspecified, link-local (which holds the
+/// 169.254.169.254 metadata service), private, shared, multicast or broadcast addresses.
+fn is_blocked_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(v4) => {
+            let [a, b, ..] = v4.octets();
+            v4.is_loopback()
+                || v4.is_unspecified()
+                || v4.is_link_local()
+                || v4.is_private()
+                || v4.is_multicast()
+                || v4.is_broadcast()
+                // 100.64.0.0/10 (carrier-grade NAT; 100.100.100.200 is a metadata service).
+                || (a == 100 && (64..128).contains(&b))
+        }
+        IpAddr::V6(v6) => match v6.to_ipv4_mapped() {
+            Some(v4) => is_blocked_ip(IpAddr::V4(v4)),
+            None => {
+                let first = v6.segments()[0];
+                v6.is_loopback()
+                    || v6.is_unspecified()
+                    || v6.is_multicast()
+                    // fe80::/10 link-local, fc00::/7 unique local.
+                    || (first & 0xffc0) == 0xfe80
+                    || (first & 0xfe00) == 0xfc00
+            }
+        },
+    }
+}
+
+/// Checks that `url` is an http(s) URL whose host (and every address it resolves to) may receive
+/// webhooks. Returns the reason it may not.
+///
+/// Passing the check does not pin the addresses: connect through [`webhook_client`] (or another
+/// client using [`PublicResolver`]) so a host that resolves differently on the next lookup is
+/// refused again.
+pub async fn check_webhook_url(url: &str) -> Result<(), String> {
+    let parsed = reqwest::Url::parse(url).map_err(|e| format!("invalid webhook url: {}", e))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err("webhook url must be http(s)".to_string());
+    }
+    let host = match parsed.host_str() {
+        Some(host) if !host.is_empty() => host,
+        _ => return Err("webhook url has no host".to_string()),
+    };
+    resolve_public(host, parsed.port_or_known_default().unwrap_or(80)).await?;
+    Ok(())
+}
+
+/// Addresses of `host`, refused when the name is local or a metadata service, or when any of
+/// them is blocked ([`is_blocked_ip`]).
+async fn resolve_public(host: &str, port: u16) -> Result<Vec<std::net::SocketAddr>, String> {
+    let host = host.trim_start_matches('[').trim_end_matches(']').trim_end_matches('.').to_lowercase();
+    if host == "localhost" || host.ends_with(".localhost") || METADATA_HOSTS.contains(&host.as_str()) {
+        return Err(format!("webhook host {} is not allowed", host));
+    }
+    let addrs: Vec<_> = tokio::net::lookup_host((host.as_str(), port))
+        .await
+        .map_err(|e| format!("webhook host {} does not resolve: {}", host, e))?
+        .collect();
+    if let Some(addr) = addrs.iter().find(|addr| is_blocked_ip(addr.ip())) {
+        return Err(format!("webhook host {} resolves to blocked address {}", host, addr.ip()));
+    }
+    Ok(addrs)
+}
+
+/// DNS resolver for outbound clients that may only reach public addresses: a host is resolved
+/// once, at connect time, and refused like [`check_webhook_url`] would refuse it, so a name that
+/// passed an earlier check cannot be rebound to an internal address. IP literals never reach a
+/// resolver; check them with [`check_webhook_url`] first.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PublicResolver;
+
+impl reqwest::dns::Resolve for PublicResolver {
+    fn resolve(&self, name: reqwest::dns::Name) -> reqwest::dns::Resolving {
+        Box::pin(async move {
+            let addrs = resolve_public(name.as_str(), 0).await?;
+            Ok(Box::new(addrs.into_iter()) as reqwest::dns::Addrs)
+        })
+    }
+}
+
+/// Client shared by every webhook request (deliveries and standing query alerts). Hosts are
+/// resolved through [`PublicResolver`], and redirects are not followed, so a permitted host cannot
+/// rebind or bounce the request to a blocked one.
+pub fn webhook_client() -> &'static reqwest::Client {
+    static CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+    CLIENT.get_or_init(|| {
+        reqwest::Client::builder()
+            .timeout(DELIVERY_TIMEOUT)
+            .redirect(reqwest::redirect::Policy::none())
+            .dns_resolver(Arc::new(PublicResolver))
+            .build()
+            .unwrap_or_default()
+    })
+}
+
+/// [`webhook_client`] without the resolver, for `[webhooks] allow_private_hosts`.
+fn private_webhook_client() -> &'static reqwest::Client {
+    static CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+    CLIENT.get_or_init(|| {
+        reqwest::Client::builder()
+            .timeout(DELIVERY_TIMEOUT)
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .unwrap_or_default()
+    })
+}
+
+/// Webhooks and their delivery queue, persisted in **KB_OIKOS**.
+#[derive(Clone)]
+pub struct WebhookRegistry {
+    store: Arc<KnowledgeStore>,
+    allow_private_hosts: bool,
+}
+
+impl WebhookRegistry {
+    pub fn new(store: Arc<KnowledgeStore>) -> Self {
+        Self {
+            store,
+            allow_private_hosts: false,
+        }
+    }
+
+    /// Lets webhooks reach loopback, link-local and private addresses (`[webhooks]
+    /// allow_private_hosts`, for local development only). Off by default.
+    pub fn with_private_hosts(mut self, allowed: bool) -> Self {
+        self.allow_private_hosts = allowed;
+        self
+    }
+
+    /// [`check_webhook_url`], unless private hosts are allowed.
+    async fn check_url(&self, url: &str) -> Result<(), String> {
+        if self.allow_private_hosts {
+            return Ok(());
+        }
+        check_webhook_url(url).await
+    }
+
+    /// Stores a new webhook once its URL passes [`check_webhook_url`] (an [`InvalidWebhook`]
+    /// otherwise).
+    pub async fn register(&self, webhook: &Webhook) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.check_url(&webhook.url).await.map_err(InvalidWebhook)?;
+        self.set(webhook)
+    }
+
+    /// Stores (or replaces) a webhook.
+    pub fn set(&self, webhook: &Webhook) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let bytes = serde_json::to_vec(webhook)?;
+        self.store.insert(KbType::Oikos.slot_id(), &key(&webhook.id), &bytes)?;
+        Ok(())
+    }
+
+    pub fn get(&self, id: &str) -> Option<Webhook> {
+        self.store
+            .get(KbType::Oikos.slot_id(), &key(id))
+            .ok()
+            .flatten()
+            .and_then(|b| serde_json::from_slice(&b).ok())
+    }
+
+    /// All webhooks, oldest first.
+    pub fn list(&self) -> Result<Vec<Webhook>, sled::Error> {
+        let mut webhooks: Vec<Webhook> = self
+            .scan(WEBHOOK_PREFIX)?
+            .into_iter()
+            .filter_map(|(_, bytes)| serde_json::from_slice(&bytes).ok())
+            .collect();
+        webhooks.sort_by_key(|w| w.created_at_ms);
+        Ok(webhooks)
+    }
+
+    /// Removes a webhook and its queued deliveries. Returns whether it existed.
+    pub fn remove(&self, id: &str) -> Result<bool, sled::Error> {
+        for delivery in self.deliveries(id)? {
+            self.store.remove(KbType::Oikos.slot_id(), &delivery_key(&delivery.id))?;
+        }
+        Ok(self.store.remove(KbType::Oikos.slot_id(), &key(id))?.is_some())
+    }
+
+    /// Queues `event` for every enabled webhook that wants it. Returns how many were queued.
+    pub fn enqueue(
+        &self,
+        event: WebhookEvent,
+        tenant_id: &str,
+        data: serde_json::Value,
+    ) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
+        let now = self.store.now_ms();
+        let mut queued = 0;
+        for webhook in self.list()?.into_iter().filter(|w| w.matches(event, tenant_id)) {
+            let delivery = WebhookDelivery {
+                id: format!("{:013}-{}", now, Uuid::new_v4().simple()),
+                webhook_id: webhook.id,
+                event,
+                tenant_id: tenant_id.to_string(),
+                data: data.clone(),
+                created_at_ms: now,
+                status: DeliveryStatus::Pending,
+                attempts: 0,
+                next_attempt_ms: now,
+                last_error: None,
+            };
+            self.set_delivery(&delivery)?;
+            queued += 1;
+        }
+        Ok(queued)
+    }
+
+    /// The webhook's queued and failed deliveries, oldest first.
+    pub fn deliveries(&self, webhook_id: &str) -> Result<Vec<WebhookDelivery>, sled::Error> {
+        Ok(self
+            .all_deliveries()?
+            .into_iter()
+            .filter(|d| d.webhook_id == webhook_id)
+            .collect())
+    }
+
+    /// Delivers what is due now through [`webhook_client`] (see [`Self::deliver_due_at`]).
+    pub async fn deliver_due(&self) -> Result<Vec<DeliveryAttempt>, Box<dyn std::error::Error + Send + Sync>> {
+        let http = if self.allow_private_hosts { private_webhook_client() } else { webhook_client() };
+        self.deliver_due_at(http, self.store.now_ms()).await
+    }
+
+    /// POSTs up to 20 pending deliveries due at `now`, oldest first, and records each outcome on
+    /// the delivery and its webhook. A 2xx answer delivers; a URL that no longer passes
+    /// [`check_webhook_url`] fails the attempt without a request; deliveries of removed or
+    /// disabled webhooks are dropped, and failed ones older than [`WEBHOOK_FAILED_RETENTION_MS`]
+    /// removed.
+    pub async fn deliver_due_at(
+        &self,
+        http: &reqwest::Client,
+        now: i64,
+    ) -> Result<Vec<DeliveryAttempt>, Box<dyn std::error::Error + Send + Sync>> {
+        let mut due = Vec::new();
+        for delivery in self.all_deliveries()? {
+            match delivery.status {
+                DeliveryStatus::Failed if now - delivery.created_at_ms >= WEBHOOK_FAILED_RETENTION_MS => {
+                    self.store.remove(KbType::Oikos.slot_id(), &delivery_key(&delivery.id))?;
+                }
+                DeliveryStatus::Pending if delivery.next_attempt_ms <= now && due.len() < DELIVERIES_PER_RUN => {
+                    due.push(delivery);
+                }
+                _ => {}
+            }
+        }
+        let mut attempts = Vec::new();
+        for mut delivery in due {
+            let Some(mut webhook) = self.get(&delivery.webhook_id).filter(|w| w.enabled) else {
+                self.store.remove(KbType::Oikos.slot_id(), &delivery_key(&delivery.id))?;
+                continue;
+            };
+            let error = match self.check_url(&webhook.url).await {
+                Ok(()) => {
+                    let body = serde_json::to_vec(&delivery.body())?;
+                    let response = http
+                        .post(&webhook.url)
+                        .timeout(DELIVERY_TIMEOUT)
+                        .header("content-type", "application/json")
+                        .header("X-Pagi-Event", delivery.event.as_str())
+                        .header("X-Pagi-Delivery", delivery.id.as_str())
+                        .header("X-Pagi-Timestamp", now.to_string())
+                        .header("X-Pagi-Signature", sign(&webhook.secret, now, &body))
+                        .body(body)
+                        .send()
+                        .await;
+                    match response {
+                        Ok(r) if r.status().is_success() => None,
+                        Ok(r) => Some(format!("HTTP {}", r.status())),
+                        Err(e) => Some(e.to_string()),
+                    }
+                }
+                Err(e) => Some(e),
+            };
+            delivery.attempts += 1;
+            match &error {
+                None => {
+                    self.store.remove(KbType::Oikos.slot_id(), &delivery_key(&delivery.id))?;
+                    webhook.last_delivery_ms = Some(now);
+                    webhook.delivered_count += 1;
+                }
+                Some(e) => {
+                    delivery.last_error = Some(e.clone());
+                    if delivery.attempts >= WEBHOOK_MAX_ATTEMPTS {
+                        delivery.status = DeliveryStatus::Failed;
+                        webhook.failed_count += 1;
+                    } else {
+                        delivery.next_attempt_ms = now
+                            + WEBHOOK_RETRY_BASE_MS
+                                .saturating_mul(1 << (delivery.attempts - 1).min(20))
+                                .min(WEBHOOK_RETRY_MAX_MS);
+                    }
+                    self.set_delivery(&delivery)?;
+                    webhook.last_error = Some(e.clone());
+                    webhook.last_error_ms = Some(now);
+                }
+            }
+            // Keep counters of a webhook removed while the request was in flight out of the store.
+            if self.get(&webhook.id).is_some() {
+                self.set(&webhook)?;
+            }
+            tracing::info!(
+                target: "pagi::webhooks",
+                webhook_id = %webhook.id,
+                delivery_id = %delivery.id,
+                event = %delivery.event,
+                attempts = delivery.attempts,
+                ok = error.is_none(),
+                error = error.as_deref().unwrap_or(""),
+                "Webhook delivery attempted"
+            );
+            attempts.push(DeliveryAttempt {
+                delivery_id: delivery.id,
+                webhook_id: webhook.id,
+                event: delivery.event,
+                ok: error.is_none(),
+                attempts: delivery.attempts,
+                error,
+            });
+        }
+        Ok(attempts)
+    }
+
+    fn set_delivery(&self, delivery: &WebhookDelivery) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let bytes = serde_json::to_vec(delivery)?;
+        self.store.insert(KbType::Oikos.slot_id(), &delivery_key(&delivery.id), &bytes)?;
+        Ok(())
+    }
+
+    fn all_deliveries(&self) -> Result<Vec<WebhookDelivery>, sled::Error> {
+        Ok(self
+            .scan(WEBHOOK_DELIVERY_PREFIX)?
+            .into_iter()
+            .filter_map(|(_, bytes)| serde_json::from_slice(&bytes).ok())
+            .collect())
+    }
+
+    fn scan(&self, prefix: &str) -> Result<Vec<(String, Vec<u8>)>, sled::Error> {
+        let mut entries = Vec::new();
+        let mut cursor = None;
+        loop {
+            let page = self.store.scan_prefix(KbType::Oikos.slot_id(), prefix, cursor.as_deref(), 500)?;
+            entries.extend(page.entries);
+            match page.next_cursor {
+                Some(next) => cursor = Some(next),
+                None => return Ok(entries),
+            }
+        }
+    }
+}
+
+impl Orchestrator {
+    /// Queues `event` for the tenant's webhooks when a knowledge store is attached, and posts it
+    /// to chat when it is one of the [chat events](Orchestrator::with_chat_notifications). Failures
+    /// are logged, never returned: a notification must not fail the call that raised it.
+    ///
+    /// The chat post runs in the background and calls `NotifyChat` directly, past the Ethos gate,
+    /// so a denied notification cannot raise another `policy.violated`.
+    pub fn emit_webhook(&self, ctx: &TenantContext, event: WebhookEvent, data: serde_json::Value) {
+        if self.chat_events.read().unwrap_or_else(PoisonError::into_inner).contains(&event) {
+            match (self.skill(ctx, CHAT_SKILL), tokio::runtime::Handle::try_current()) {
+                (Ok(skill), Ok(runtime)) => {
+                    let ctx = ctx.clone();
+                    let payload = serde_json::json!({ "event": event, "data": data });
+                    runtime.spawn(async move {
+                        if let Err(e) = skill.execute(&ctx, Some(payload)).await {
+                            tracing::warn!(target: "pagi::webhooks", event = %event, error = %e, "Failed to post event to chat");
+                        }
+                    });
+                }
+                (Err(_), _) => {
+                    tracing::debug!(target: "pagi::webhooks", event = %event, "No {} skill registered; chat notification skipped", CHAT_SKILL)
+                }
+                (_, Err(_)) => {}
+            }
+        }
+        let Some(knowledge) = self.knowledge.as_ref() else {
+            return;
+        };
+        if let Err(e) = WebhookRegistry::new(Arc::clone(knowledge)).enqueue(event, &ctx.tenant_id, data) {
+            tracing::warn!(target: "pagi::webhooks", event = %event, error = %e, "Failed to queue webhook deliveries");
+        }
+    }
+}
+
+fn key(id: &str) -> String {
+    format!("{}{}", WEBHOOK_PREFIX, id)
+}
+
+fn delivery_key(id: &str) -> String {
+    format!("{}{}", WEBHOOK_DELIVERY_PREFIX, id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn failed_deliveries_back_off_and_give_up() {
+        let store = Arc::new(KnowledgeStore::open_temporary().unwrap());
+        let registry = WebhookRegistry::new(Arc::clone(&store));
+        // Stored with a loopback URL (as after a DNS change): every attempt fails before a request.
+        let events = vec![WebhookEvent::LeadCaptured];
+        let hook = Webhook {
+            url: "http://127.0.0.1:9/hook".into(),
+            ..Webhook::new("crm", "https://hooks.example.com/", events, Some("acme".into()), None, store.now_ms()).unwrap()
+        };
+        registry.set(&hook).unwrap();
+        assert!(hook.secret.starts_with("whsec_") && hook.status().get("secret").is_none());
+
+        assert_eq!(registry.enqueue(WebhookEvent::DraftGenerated, "acme", serde_json::json!({})).unwrap(), 0);
+        assert_eq!(registry.enqueue(WebhookEvent::LeadCaptured, "beta", serde_json::json!({})).unwrap(), 0);
+        assert_eq!(registry.enqueue(WebhookEvent::LeadCaptured, "acme", serde_json::json!({ "lead_id": "l1" })).unwrap(), 1);
+
+        let http = reqwest::Client::new();
+        let mut now = store.now_ms();
+        for attempt in 1..=WEBHOOK_MAX_ATTEMPTS {
+            let attempts = registry.deliver_due_at(&http, now).await.unwrap();
+            assert_eq!((attempts.len(), attempts[0].ok, attempts[0].attempts), (1, false, attempt));
+            assert!(attempts[0].error.as_deref().unwrap().contains("blocked address"));
+            // Not retried before its backoff is over.
+            assert!(registry.deliver_due_at(&http, now).await.unwrap().is_empty());
+            now = registry.deliveries(&hook.id).unwrap()[0].next_attempt_ms;
+        }
+        let deliveries = registry.deliveries(&hook.id).unwrap();
+        assert_eq!(deliveries[0].status, DeliveryStatus::Failed);
+        assert_eq!(deliveries[0].data["lead_id"], "l1");
+        assert_eq!(registry.get(&hook.id).unwrap().failed_count, 1);
+
+        // Kept for the retention window, then removed.
+        let created_at_ms = deliveries[0].created_at_ms;
+        registry.deliver_due_at(&http, created_at_ms + WEBHOOK_FAILED_RETENTION_MS - 1).await.unwrap();
+        assert_eq!(registry.deliveries(&hook.id).unwrap().len(), 1);
+        registry.deliver_due_at(&http, created_at_ms + WEBHOOK_FAILED_RETENTION_MS).await.unwrap();
+        assert!(registry.deliveries(&hook.id).unwrap().is_empty());
+
+        registry.enqueue(WebhookEvent::LeadCaptured, "acme", serde_json::json!({})).unwrap();
+        assert!(registry.remove(&hook.id).unwrap());
+        assert!(registry.deliveries(&hook.id).unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn webhook_urls_to_internal_addresses_are_rejected() {
+        for url in [
+            "ftp://example.com/hook",
+            "http://localhost:8080/hook",
+            "http://127.0.0.1/hook",
+            "http://169.254.169.254/latest/meta-data",
+            "http://metadata.google.internal/computeMetadata/v1",
+            "http://10.0.0.5/hook",
+            "http://100.100.100.200/hook",
+            "http://[::1]/hook",
+            "http://[fe80::1]/hook",
+            "http://[::ffff:127.0.0.1]/hook",
+        ] {
+            assert!(check_webhook_url(url).await.is_err(), "{} should be rejected", url);
+        }
+        assert!(check_webhook_url("https://93.184.215.14/hook").await.is_ok());
+
+        let store = Arc::new(KnowledgeStore::open_temporary().unwrap());
+        let registry = WebhookRegistry::new(Arc::clone(&store));
+        for url in ["http://127.0.0.1:8080/hook", "http://169.254.169.254/latest/meta-data"] {
+            let hook = Webhook::new("internal", url, Vec::new(), None, None, store.now_ms()).unwrap();
+            let err = registry.register(&hook).await.unwrap_err();
+            assert!(err.downcast_ref::<InvalidWebhook>().is_some(), "{}", err);
+            assert!(registry.get(&hook.id).is_none());
+        }
+        let local = Webhook::new("dev", "http://127.0.0.1:8080/hook", Vec::new(), None, None, store.now_ms()).unwrap();
+        registry.clone().with_private_hosts(true).register(&local).await.unwrap();
+        assert!(registry.get(&local.id).is_some());
+    }
+
+    #[tokio::test]
+    async fn webhook_client_resolves_names_to_public_addresses_only() {
+        // A name that (now) resolves to loopback is refused at connect time, even though a server
+        // is listening there.
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://localhost:{}/hook", listener.local_addr().unwrap().port());
+        let err = webhook_client().post(&url).send().await.unwrap_err();
+        assert!(format!("{:?}", err).contains("is not allowed"), "{:?}", err);
+
+        use reqwest::dns::Resolve;
+        let err = PublicResolver.resolve("metadata.google.internal".parse().unwrap()).await.err().unwrap();
+        assert!(err.to_string().contains("is not allowed"));
+    }
+
+    #[test]
+    fn signature_covers_timestamp_and_body() {
+        let signature = sign("whsec_test_secret_123", 1_700_000_000_000, br#"{"event":"lead.captured"}"#);
+        assert!(signature.starts_with("sha256=") && signature.len() == 7 + 64);
+        assert_ne!(signature, sign("whsec_test_secret_123", 1_700_000_000_001, br#"{"event":"lead.captured"}"#));
+    }
+}
@@ -0,0 +1,132 @@
+//! Resolving paths inside a sandbox directory: the orchestrator's `sandbox_root` limit and
+//! pagi-skills' `PathJail` both go through [`resolve_in_sandbox`].
+//!
+//! Existing components are inspected without following links. A symlink must resolve inside the
+//! root; a dangling one (which `exists()` reports as missing) is refused, since writing through it
+//! would create its target wherever it points. Components that do not exist yet are taken as is.
+
+use std::fmt;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+/// Why [`resolve_in_sandbox`] refused a path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SandboxError {
+    /// The path has a `..` segment.
+    Traversal,
+    /// The path, or a symlink along it, leads outside the root.
+    Outside,
+    /// A symlink along the path points at nothing.
+    DanglingSymlink,
+    /// The root or a component could not be inspected.
+    Io(String),
+}
+
+impl fmt::Display for SandboxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Traversal => write!(f, "path traversal is forbidden"),
+            Self::Outside => write!(f, "path is outside the sandbox"),
+            Self::DanglingSymlink => write!(f, "path goes through a dangling symlink"),
+            Self::Io(e) => write!(f, "failed to inspect path: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for SandboxError {}
+
+/// Absolute form of `path` with its nearest existing ancestor canonicalized (the rest, which does
+/// not exist yet, appended as is).
+fn canonicalize_existing(path: &Path) -> std::io::Result<PathBuf> {
+    let path = std::path::absolute(path)?;
+    let mut existing = path.as_path();
+    let mut tail = Vec::new();
+    while !existing.exists() {
+        match (existing.parent(), existing.file_name()) {
+            (Some(parent), Some(name)) => {
+                tail.push(name.to_os_string());
+                existing = parent;
+            }
+            _ => break,
+        }
+    }
+    let mut resolved = existing.canonicalize()?;
+    resolved.extend(tail.iter().rev());
+    Ok(resolved)
+}
+
+/// Absolute path of `path` inside `root`: relative paths are taken from `root`, absolute ones
+/// must lie under it. Neither `root` nor the target need exist, but every existing component must
+/// resolve inside the root.
+pub fn resolve_in_sandbox(root: &Path, path: &Path) -> Result<PathBuf, SandboxError> {
+    if path.components().any(|c| c == Component::ParentDir) {
+        return Err(SandboxError::Traversal);
+    }
+    let io = |e: std::io::Error| SandboxError::Io(e.to_string());
+    let absolute_root = std::path::absolute(root).map_err(io)?;
+    let root = canonicalize_existing(&absolute_root).map_err(io)?;
+    let rel = match path.is_absolute() {
+        true => path
+            .strip_prefix(&absolute_root)
+            .or_else(|_| path.strip_prefix(&root))
+            .map_err(|_| SandboxError::Outside)?,
+        false => path,
+    };
+    let mut existing = root.clone();
+    for component in rel.components() {
+        let name = match component {
+            Component::Normal(name) => name,
+            Component::CurDir => continue,
+            _ => return Err(SandboxError::Outside),
+        };
+        let next = existing.join(name);
+        match fs::symlink_metadata(&next) {
+            Ok(meta) if meta.file_type().is_symlink() => {
+                let resolved = next.canonicalize().map_err(|_| SandboxError::DanglingSymlink)?;
+                if !resolved.starts_with(&root) {
+                    return Err(SandboxError::Outside);
+                }
+                existing = resolved;
+            }
+            Ok(_) => existing = next,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => break,
+            Err(e) => return Err(io(e)),
+        }
+    }
+    // `existing` only fails to canonicalize when the root itself does not exist yet.
+    if let Ok(existing) = existing.canonicalize() {
+        if !existing.starts_with(&root) {
+            return Err(SandboxError::Outside);
+        }
+    }
+    Ok(root.join(rel))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn paths_resolve_inside_the_root_only() {
+        let root = std::env::temp_dir().join(format!("pagi_sandbox_{}", uuid::Uuid::new_v4().simple()));
+        std::fs::create_dir_all(root.join("inner")).unwrap();
+        let canonical = root.canonicalize().unwrap();
+        assert_eq!(resolve_in_sandbox(&root, Path::new("inner/report.md")), Ok(canonical.join("inner/report.md")));
+        assert!(resolve_in_sandbox(&root, Path::new("not/yet/created.md")).is_ok());
+        assert!(resolve_in_sandbox(&root, &root.join("inner")).is_ok());
+        assert!(resolve_in_sandbox(&root.join("missing_root"), Path::new("a.md")).is_ok());
+        assert_eq!(resolve_in_sandbox(&root, Path::new("inner/../../escape.md")), Err(SandboxError::Traversal));
+        assert_eq!(resolve_in_sandbox(&root, Path::new("/etc/passwd")), Err(SandboxError::Outside));
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(std::env::temp_dir(), root.join("link")).unwrap();
+            std::os::unix::fs::symlink(root.join("inner"), root.join("inner_link")).unwrap();
+            std::os::unix::fs::symlink(root.join("missing"), root.join("dangling")).unwrap();
+            assert_eq!(resolve_in_sandbox(&root, Path::new("link/escape.md")), Err(SandboxError::Outside));
+            assert!(resolve_in_sandbox(&root, Path::new("inner_link/report.md")).is_ok());
+            assert_eq!(resolve_in_sandbox(&root, Path::new("dangling")), Err(SandboxError::DanglingSymlink));
+            assert_eq!(resolve_in_sandbox(&root, Path::new("dangling/report.md")), Err(SandboxError::DanglingSymlink));
+        }
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
@@ -0,0 +1,609 @@
+//! Secrets: the gateway API key, the shadow key and LLM provider keys, fetched from a chain of
+//! providers instead of only the environment (`[secrets]`).
+//!
+//! Providers are asked in order and the first that holds a name wins:
+//!
+//! | Kind | Source |
+//! |------|--------|
+//! | `env` | process environment (and `.env`) |
+//! | `file` | one file per secret in `dir`, named after it (Docker / Kubernetes secret mounts) |
+//! | `vault` | HashiCorp Vault KV (v2 or v1) document at `path`, one field per secret |
+//! | `aws_secrets_manager` | AWS Secrets Manager secret holding a JSON object, one field per secret |
+//!
+//! The names in use are fetched at startup ([`Secrets::load`]) and refetched every
+//! `refresh_secs`, so a rotated key is picked up without a restart; a failed refresh keeps the
+//! last values. Code reads them through [`secret`], which uses the process-wide cache
+//! ([`install`]) and falls back to the environment for names never loaded (CLI subcommands,
+//! tests).
+
+use async_trait::async_trait;
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock, PoisonError, RwLock};
+use std::time::Duration;
+
+use crate::clock::{Clock, SystemClock};
+use crate::shared::{CalendarProvider, CoreConfig, EmailProvider, TelegramMode};
+
+/// Gateway admin key (`X-API-Key`).
+pub const SECRET_API_KEY: &str = "PAGI_API_KEY";
+/// Vault / ShadowStore master key (64 hex chars).
+pub const SECRET_SHADOW_KEY: &str = "PAGI_SHADOW_KEY";
+/// Default ModelRouter key.
+pub const SECRET_LLM_API_KEY: &str = "PAGI_LLM_API_KEY";
+
+/// Names that hold the name of another secret (the fallback provider's key).
+const INDIRECT_NAMES: [&str; 1] = ["PAGI_LLM_FALLBACK_API_KEY_ENV"];
+
+/// HTTP timeout of the Vault and AWS providers.
+const FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// `[secrets]`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SecretsConfig {
+    /// Providers in lookup order (default: env only).
+    pub providers: Vec<SecretsProviderConfig>,
+    /// How often loaded names are refetched; 0 = only at startup.
+    pub refresh_secs: u64,
+    /// More names to load at startup, next to the ones PAGI reads itself (see
+    /// [`CoreConfig::secret_names`]).
+    pub names: Vec<String>,
+}
+
+impl Default for SecretsConfig {
+    fn default() -> Self {
+        Self {
+            providers: vec![SecretsProviderConfig::Env],
+            refresh_secs: 300,
+            names: Vec::new(),
+        }
+    }
+}
+
+/// One `[[secrets.providers]]` entry.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SecretsProviderConfig {
+    Env,
+    File {
+        /// Directory holding one file per secret (e.g. `/run/secrets`).
+        dir: String,
+    },
+    Vault {
+        /// Server address (`https://vault.internal:8200`).
+        addr: String,
+        /// API path of the document, e.g. `secret/data/pagi` for KV v2.
+        path: String,
+        /// Environment variable holding the Vault token.
+        #[serde(default = "default_vault_token_env")]
+        token_env: String,
+        /// File holding the token instead (e.g. written by the Vault agent).
+        #[serde(default)]
+        token_file: Option<String>,
+        /// Vault Enterprise namespace.
+        #[serde(default)]
+        namespace: Option<String>,
+    },
+    AwsSecretsManager {
+        /// Secret name or ARN; its value must be a JSON object.
+        secret_id: String,
+        /// Default: `AWS_REGION` / `AWS_DEFAULT_REGION`.
+        #[serde(default)]
+        region: Option<String>,
+        /// Endpoint override (VPC endpoints, LocalStack).
+        #[serde(default)]
+        endpoint: Option<String>,
+    },
+}
+
+fn default_vault_token_env() -> String {
+    "VAULT_TOKEN".to_string()
+}
+
+impl CoreConfig {
+    /// Secrets PAGI reads: the gateway, shadow and LLM keys, each LLM provider's key, each
+    /// ingest source's signing key, the `[email]` provider's credential, the `[chat]` webhook URLs
+    /// and Slack signing secret, the Telegram bot's token and webhook secret, the `[calendar]`
+    /// provider's credentials, and `[secrets] names`.
+    pub fn secret_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = [SECRET_API_KEY, SECRET_SHADOW_KEY, SECRET_LLM_API_KEY].map(String::from).to_vec();
+        names.extend(
+            self.llm_providers
+                .values()
+                .filter_map(|p| p.api_key_env.as_deref().or(p.kind.default_api_key_env()))
+                .map(String::from),
+        );
+        names.extend(self.ingest.sources.values().filter_map(|s| s.secret_env.clone()));
+        match self.email.provider {
+            EmailProvider::Smtp if self.email.smtp.username.is_some() => names.push(self.email.smtp_password_env().to_string()),
+            EmailProvider::Sendgrid => names.push(self.email.sendgrid_api_key_env().to_string()),
+            _ => {}
+        }
+        names.extend(self.chat.channels.values().map(|c| c.webhook_env.clone()).filter(|n| !n.is_empty()));
+        if self.chat.slack_commands.enabled {
+            names.push(self.chat.slack_commands.signing_secret_env().to_string());
+        }
+        let telegram = &self.channels.telegram;
+        if telegram.enabled {
+            names.push(telegram.bot_token_env().to_string());
+            if telegram.mode == TelegramMode::Webhook {
+                names.push(telegram.webhook_secret_env().to_string());
+            }
+        }
+        match self.calendar.provider {
+            CalendarProvider::Google => {
+                names.push(self.calendar.google_client_secret_env().to_string());
+                names.push(self.calendar.google_refresh_token_env().to_string());
+            }
+            CalendarProvider::Caldav if self.calendar.caldav.username.is_some() => {
+                names.push(self.calendar.caldav_password_env().to_string())
+            }
+            _ => {}
+        }
+        names.extend(INDIRECT_NAMES.iter().filter_map(|n| std::env::var(n).ok()).filter(|n| !n.trim().is_empty()));
+        names.extend(self.secrets.names.iter().cloned());
+        names.sort();
+        names.dedup();
+        names
+    }
+}
+
+/// A source of secrets.
+#[async_trait]
+pub trait SecretsProvider: Send + Sync {
+    /// Short name for logs (`env`, `vault`, ...).
+    fn name(&self) -> &'static str;
+
+    /// The values of those of `names` this provider holds.
+    async fn fetch(&self, names: &[String]) -> Result<HashMap<String, String>, String>;
+}
+
+/// Process environment.
+pub struct EnvSecrets;
+
+#[async_trait]
+impl SecretsProvider for EnvSecrets {
+    fn name(&self) -> &'static str {
+        "env"
+    }
+
+    async fn fetch(&self, names: &[String]) -> Result<HashMap<String, String>, String> {
+        Ok(names
+            .iter()
+            .filter_map(|n| std::env::var(n).ok().filter(|v| !v.is_empty()).map(|v| (n.clone(), v)))
+            .collect())
+    }
+}
+
+/// One file per secret in a directory; trailing newlines are dropped.
+pub struct FileSecrets {
+    dir: std::path::PathBuf,
+}
+
+impl FileSecrets {
+    pub fn new(dir: impl Into<std::path::PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+}
+
+#[async_trait]
+impl SecretsProvider for FileSecrets {
+    fn name(&self) -> &'static str {
+        "file"
+    }
+
+    async fn fetch(&self, names: &[String]) -> Result<HashMap<String, String>, String> {
+        let mut out = HashMap::new();
+        for name in names.iter().filter(|n| is_plain_name(n)) {
+            match tokio::fs::read_to_string(self.dir.join(name)).await {
+                Ok(value) => {
+                    let value = value.trim_end_matches(['\r', '\n']);
+                    if !value.is_empty() {
+                        out.insert(name.clone(), value.to_string());
+                    }
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => return Err(format!("{}: {}", self.dir.join(name).display(), e)),
+            }
+        }
+        Ok(out)
+    }
+}
+
+/// Secret names double as file names, so they may not leave the directory.
+fn is_plain_name(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.')) && !name.starts_with('.')
+}
+
+/// Fields of a HashiCorp Vault KV document.
+pub struct VaultSecrets {
+    client: reqwest::Client,
+    url: String,
+    token_env: String,
+    token_file: Option<String>,
+    namespace: Option<String>,
+}
+
+impl VaultSecrets {
+    pub fn new(addr: &str, path: &str, token_env: &str, token_file: Option<String>, namespace: Option<String>) -> Result<Self, String> {
+        Ok(Self {
+            client: http_client()?,
+            url: format!("{}/v1/{}", addr.trim_end_matches('/'), path.trim_start_matches('/')),
+            token_env: token_env.to_string(),
+            token_file,
+            namespace,
+        })
+    }
+
+    async fn token(&self) -> Result<String, String> {
+        let token = match &self.token_file {
+            Some(file) => tokio::fs::read_to_string(file).await.map_err(|e| format!("Vault token file {}: {}", file, e))?,
+            None => std::env::var(&self.token_env).map_err(|_| format!("Vault token: {} is not set", self.token_env))?,
+        };
+        Ok(token.trim().to_string())
+    }
+}
+
+#[async_trait]
+impl SecretsProvider for VaultSecrets {
+    fn name(&self) -> &'static str {
+        "vault"
+    }
+
+    async fn fetch(&self, names: &[String]) -> Result<HashMap<String, String>, String> {
+        let mut req = self.client.get(&self.url).header("X-Vault-Token", self.token().await?);
+        if let Some(namespace) = &self.namespace {
+            req = req.header("X-Vault-Namespace", namespace);
+        }
+        let resp = req.send().await.map_err(|e| format!("Vault {}: {}", self.url, e))?;
+        let status = resp.status();
+        if status == reqwest::StatusCode::NOT_FOUND {
+            return Ok(HashMap::new());
+        }
+        if !status.is_success() {
+            return Err(format!("Vault {}: HTTP {}", self.url, status));
+        }
+        let body: serde_json::Value = resp.json().await.map_err(|e| format!("Vault {}: {}", self.url, e))?;
+        // KV v2 nests the fields under data.data; KV v1 has them under data.
+        let data = match body.pointer("/data/data") {
+            Some(inner @ serde_json::Value::Object(_)) => inner,
+            _ => body.get("data").ok_or_else(|| format!("Vault {}: response has no data", self.url))?,
+        };
+        Ok(pick_fields(data, names))
+    }
+}
+
+/// Fields of a JSON secret in AWS Secrets Manager (`GetSecretValue`, SigV4-signed with the
+/// standard `AWS_ACCESS_KEY_ID` / `AWS_SECRET_ACCESS_KEY` / `AWS_SESSION_TOKEN` variables).
+pub struct AwsSecretsManagerSecrets {
+    client: reqwest::Client,
+    secret_id: String,
+    region: String,
+    endpoint: String,
+}
+
+impl AwsSecretsManagerSecrets {
+    pub fn new(secret_id: &str, region: Option<String>, endpoint: Option<String>) -> Result<Self, String> {
+        let region = region
+            .or_else(|| std::env::var("AWS_REGION").ok())
+            .or_else(|| std::env::var("AWS_DEFAULT_REGION").ok())
+            .ok_or("aws_secrets_manager needs a region (or AWS_REGION)")?;
+        let endpoint = endpoint.unwrap_or_else(|| format!("https://secretsmanager.{}.amazonaws.com", region));
+        Ok(Self {
+            client: http_client()?,
+            secret_id: secret_id.to_string(),
+            region,
+            endpoint,
+        })
+    }
+}
+
+#[async_trait]
+impl SecretsProvider for AwsSecretsManagerSecrets {
+    fn name(&self) -> &'static str {
+        "aws_secrets_manager"
+    }
+
+    async fn fetch(&self, names: &[String]) -> Result<HashMap<String, String>, String> {
+        let env = |n: &str| std::env::var(n).ok().filter(|v| !v.is_empty());
+        let (Some(access_key), Some(secret_key)) = (env("AWS_ACCESS_KEY_ID"), env("AWS_SECRET_ACCESS_KEY")) else {
+            return Err("AWS Secrets Manager: AWS_ACCESS_KEY_ID / AWS_SECRET_ACCESS_KEY are not set".to_string());
+        };
+        let url = reqwest::Url::parse(&self.endpoint).map_err(|e| format!("AWS endpoint {}: {}", self.endpoint, e))?;
+        let host = match (url.host_str(), url.port()) {
+            (Some(host), Some(port)) => format!("{}:{}", host, port),
+            (Some(host), None) => host.to_string(),
+            (None, _) => return Err(format!("AWS endpoint {} has no host", self.endpoint)),
+        };
+        let body = serde_json::json!({ "SecretId": self.secret_id }).to_string();
+        let session_token = env("AWS_SESSION_TOKEN");
+        let request = SigV4Request {
+            host: &host,
+            target: "secretsmanager.GetSecretValue",
+            body: &body,
+            session_token: session_token.as_deref(),
+        };
+        let headers = request.sign(&access_key, &secret_key, &self.region, "secretsmanager", SystemClock.now_secs());
+        let mut req = self.client.post(url).body(body.clone());
+        for (name, value) in headers {
+            req = req.header(name, value);
+        }
+        let resp = req.send().await.map_err(|e| format!("AWS Secrets Manager: {}", e))?;
+        let status = resp.status();
+        let reply: serde_json::Value = resp.json().await.unwrap_or_default();
+        if !status.is_success() {
+            let kind = reply.get("__type").and_then(|t| t.as_str()).unwrap_or("");
+            if kind.ends_with("ResourceNotFoundException") {
+                return Ok(HashMap::new());
+            }
+            return Err(format!("AWS Secrets Manager {}: HTTP {} {}", self.secret_id, status, kind));
+        }
+        let secret_string = reply
+            .get("SecretString")
+            .and_then(|s| s.as_str())
+            .ok_or_else(|| format!("AWS Secrets Manager {}: no SecretString", self.secret_id))?;
+        let fields: serde_json::Value = serde_json::from_str(secret_string)
+            .map_err(|_| format!("AWS Secrets Manager {}: SecretString is not a JSON object", self.secret_id))?;
+        Ok(pick_fields(&fields, names))
+    }
+}
+
+/// The `names` fields of a JSON object, as strings.
+fn pick_fields(object: &serde_json::Value, names: &[String]) -> HashMap<String, String> {
+    names
+        .iter()
+        .filter_map(|name| {
+            let value = match object.get(name)? {
+                serde_json::Value::String(s) => s.clone(),
+                serde_json::Value::Null => return None,
+                other => other.to_string(),
+            };
+            Some((name.clone(), value))
+        })
+        .collect()
+}
+
+fn http_client() -> Result<reqwest::Client, String> {
+    reqwest::Client::builder().timeout(FETCH_TIMEOUT).build().map_err(|e| e.to_string())
+}
+
+/// An AWS JSON-protocol call (`POST /` with `X-Amz-Target`) to sign.
+struct SigV4Request<'a> {
+    host: &'a str,
+    target: &'a str,
+    body: &'a str,
+    session_token: Option<&'a str>,
+}
+
+impl SigV4Request<'_> {
+    /// Headers to send, `authorization` included (AWS Signature Version 4).
+    fn sign(&self, access_key: &str, secret_key: &str, region: &str, service: &str, now_secs: u64) -> Vec<(&'static str, String)> {
+        let (date, amz_date) = amz_dates(now_secs);
+        let mut headers = vec![
+            ("content-type", "application/x-amz-json-1.1".to_string()),
+            ("host", self.host.to_string()),
+            ("x-amz-date", amz_date.clone()),
+        ];
+        if let Some(token) = self.session_token {
+            headers.push(("x-amz-security-token", token.to_string()));
+        }
+        headers.push(("x-amz-target", self.target.to_string()));
+        let signed_headers = headers.iter().map(|(n, _)| *n).collect::<Vec<_>>().join(";");
+        let canonical_headers: String = headers.iter().map(|(n, v)| format!("{}:{}\n", n, v.trim())).collect();
+        let canonical_request = format!(
+            "POST\n/\n\n{}\n{}\n{}",
+            canonical_headers,
+            signed_headers,
+            hex(&Sha256::digest(self.body.as_bytes()))
+        );
+        let scope = format!("{}/{}/{}/aws4_request", date, region, service);
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+            amz_date,
+            scope,
+            hex(&Sha256::digest(canonical_request.as_bytes()))
+        );
+        let key = signing_key(secret_key, &date, region, service);
+        let signature = hex(&hmac_sha256(&key, string_to_sign.as_bytes()));
+        headers.retain(|(n, _)| *n != "host");
+        headers.push((
+            "authorization",
+            format!("AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}", access_key, scope, signed_headers, signature),
+        ));
+        headers
+    }
+}
+
+pub(crate) fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC takes keys of any length");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+fn signing_key(secret_key: &str, date: &str, region: &str, service: &str) -> Vec<u8> {
+    [date, region, service, "aws4_request"]
+        .iter()
+        .fold(format!("AWS4{}", secret_key).into_bytes(), |key, part| hmac_sha256(&key, part.as_bytes()))
+}
+
+pub(crate) fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// `YYYYMMDD` and `YYYYMMDDTHHMMSSZ` (UTC) of a Unix time.
+fn amz_dates(secs: u64) -> (String, String) {
+    let days = (secs / 86_400) as i64;
+    let rem = secs % 86_400;
+    // Civil date from days since 1970-01-01 (Howard Hinnant's algorithm).
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    let date = format!("{:04}{:02}{:02}", year, month, day);
+    let time = format!("{:02}{:02}{:02}", rem / 3_600, rem % 3_600 / 60, rem % 60);
+    (date.clone(), format!("{}T{}Z", date, time))
+}
+
+/// Provider chain with a cache of the names loaded so far.
+pub struct Secrets {
+    providers: Vec<Box<dyn SecretsProvider>>,
+    refresh: Duration,
+    /// Loaded names → value (None: no provider holds it).
+    cache: RwLock<HashMap<String, Option<String>>>,
+}
+
+impl Secrets {
+    /// `refresh` = zero turns periodic refetching off.
+    pub fn new(providers: Vec<Box<dyn SecretsProvider>>, refresh: Duration) -> Self {
+        Self {
+            providers,
+            refresh,
+            cache: RwLock::new(HashMap::new()),
+        }
+    }
+
+    pub fn from_config(config: &SecretsConfig) -> Result<Self, String> {
+        let providers = config
+            .providers
+            .iter()
+            .map(|p| -> Result<Box<dyn SecretsProvider>, String> {
+                Ok(match p {
+                    SecretsProviderConfig::Env => Box::new(EnvSecrets),
+                    SecretsProviderConfig::File { dir } => Box::new(FileSecrets::new(dir)),
+                    SecretsProviderConfig::Vault { addr, path, token_env, token_file, namespace } => {
+                        Box::new(VaultSecrets::new(addr, path, token_env, token_file.clone(), namespace.clone())?)
+                    }
+                    SecretsProviderConfig::AwsSecretsManager { secret_id, region, endpoint } => {
+                        Box::new(AwsSecretsManagerSecrets::new(secret_id, region.clone(), endpoint.clone())?)
+                    }
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self::new(providers, Duration::from_secs(config.refresh_secs)))
+    }
+
+    /// Provider names, in lookup order.
+    pub fn provider_names(&self) -> Vec<&'static str> {
+        self.providers.iter().map(|p| p.name()).collect()
+    }
+
+    /// Fetches `names` and keeps them loaded (refetched by [`refresh`](Self::refresh)).
+    pub async fn load(&self, names: &[String]) -> Result<(), String> {
+        let fetched = self.fetch(names).await?;
+        self.cache.write().unwrap_or_else(PoisonError::into_inner).extend(fetched);
+        Ok(())
+    }
+
+    /// Refetches every loaded name. Returns how many values changed; on error the cache is
+    /// left as it was.
+    pub async fn refresh(&self) -> Result<usize, String> {
+        let names: Vec<String> = self.cache.read().unwrap_or_else(PoisonError::into_inner).keys().cloned().collect();
+        let fetched = self.fetch(&names).await?;
+        let mut cache = self.cache.write().unwrap_or_else(PoisonError::into_inner);
+        let changed = fetched.iter().filter(|(name, value)| cache.get(*name) != Some(*value)).count();
+        cache.extend(fetched);
+        Ok(changed)
+    }
+
+    /// The cached value of `name`; None when no provider holds it or it was never loaded.
+    pub fn get(&self, name: &str) -> Option<String> {
+        self.cache.read().unwrap_or_else(PoisonError::into_inner).get(name).cloned().flatten()
+    }
+
+    /// Asks each provider, in order, for the names the ones before it lacked.
+    async fn fetch(&self, names: &[String]) -> Result<HashMap<String, Option<String>>, String> {
+        let mut found: HashMap<String, Option<String>> = HashMap::new();
+        for provider in &self.providers {
+            let missing: Vec<String> = names.iter().filter(|n| !found.contains_key(*n)).cloned().collect();
+            if missing.is_empty() {
+                break;
+            }
+            let values = provider.fetch(&missing).await.map_err(|e| format!("secrets provider {}: {}", provider.name(), e))?;
+            found.extend(values.into_iter().filter(|(n, _)| missing.contains(n)).map(|(n, v)| (n, Some(v))));
+        }
+        for name in names {
+            found.entry(name.clone()).or_insert(None);
+        }
+        Ok(found)
+    }
+
+    /// Refetches loaded names every `refresh_secs` until the runtime shuts down (no task when
+    /// refreshing is off).
+    pub fn spawn_refresh(self: &Arc<Self>) -> Option<tokio::task::JoinHandle<()>> {
+        if self.refresh.is_zero() {
+            return None;
+        }
+        let secrets = Arc::clone(self);
+        Some(tokio::spawn(async move {
+            let mut interval = tokio::time::interval(secrets.refresh);
+            interval.tick().await;
+            loop {
+                interval.tick().await;
+                match secrets.refresh().await {
+                    Ok(0) => {}
+                    Ok(changed) => tracing::info!(target: "pagi::secrets", changed, "Secrets refreshed"),
+                    Err(e) => tracing::warn!(target: "pagi::secrets", error = %e, "Secrets refresh failed; keeping the last values"),
+                }
+            }
+        }))
+    }
+}
+
+static INSTALLED: OnceLock<Arc<Secrets>> = OnceLock::new();
+
+/// Makes `secrets` the process-wide source of [`secret`]. Returns false if one was already
+/// installed.
+pub fn install(secrets: Arc<Secrets>) -> bool {
+    INSTALLED.set(secrets).is_ok()
+}
+
+/// Builds `[secrets]`, loads [`CoreConfig::secret_names`], installs the result and starts its
+/// refresh task. Call once at startup, before opening stores that read the shadow key.
+pub async fn init_from_config(config: &CoreConfig) -> Result<Arc<Secrets>, String> {
+    let secrets = Arc::new(Secrets::from_config(&config.secrets)?);
+    secrets.load(&config.secret_names()).await?;
+    if !install(Arc::clone(&secrets)) {
+        tracing::warn!(target: "pagi::secrets", "Secrets were already installed; keeping the first");
+    }
+    secrets.spawn_refresh();
+    tracing::info!(target: "pagi::secrets", providers = ?secrets.provider_names(), "Secrets loaded");
+    Ok(secrets)
+}
+
+/// The value of secret `name`: from the installed [`Secrets`], else the environment.
+pub fn secret(name: &str) -> Option<String> {
+    INSTALLED
+        .get()
+        .and_then(|s| s.get(name))
+        .or_else(|| std::env::var(name).ok())
+        .filter(|v| !v.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sigv4_matches_the_aws_reference_values() {
+        // Signing key example from the AWS SigV4 documentation.
+        let key = signing_key("wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY", "20150830", "us-east-1", "iam");
+        assert_eq!(hex(&key), "c4afb1cc5771d871763a393e44b703571b55cc28424d1a5e86da6ed3c154a4b9");
+        assert_eq!(amz_dates(1_440_938_160), ("20150830".to_string(), "20150830T123600Z".to_string()));
+        assert_eq!(amz_dates(951_782_400).0, "20000229");
+
+        let request = SigV4Request { host: "secretsmanager.us-east-1.amazonaws.com", target: "secretsmanager.GetSecretValue", body: "{}", session_token: Some("tok") };
+        let headers = request.sign("AKID", "secret", "us-east-1", "secretsmanager", 1_440_938_160);
+        let auth = &headers.iter().find(|(n, _)| *n == "authorization").unwrap().1;
+        assert!(auth.starts_with("AWS4-HMAC-SHA256 Credential=AKID/20150830/us-east-1/secretsmanager/aws4_request, "));
+        assert!(auth.contains("SignedHeaders=content-type;host;x-amz-date;x-amz-security-token;x-amz-target, Signature="));
+        assert!(!headers.iter().any(|(n, _)| *n == "host"));
+    }
+}
@@ -41,10 +41,10 @@ pub struct ShadowStore {
 }
 
 impl ShadowStore {
-    /// Opens the shadow DB at `path` (e.g. `./data/pagi_shadow`). Uses `PAGI_SHADOW_KEY` (64 hex chars = 32 bytes).
+    /// Opens the shadow DB at `path` (e.g. `./data/pagi_shadow`). Uses the `PAGI_SHADOW_KEY` secret (64 hex chars = 32 bytes).
     pub fn open_path(path: &Path) -> Result<Self, String> {
-        let db = sled::open(path).map_err(|e| format!("shadow store open: {}", e))?;
-        let key_bytes = std::env::var(ENV_SHADOW_KEY).ok().and_then(|hex| {
+        let db = crate::db_lock::open_db(path).map_err(|e| format!("shadow store open: {}", e))?;
+        let key_bytes = crate::secrets::secret(ENV_SHADOW_KEY).and_then(|hex| {
             let hex = hex.trim().replace([' ', '\n'], "");
             if hex.len() != 64 {
                 return None;
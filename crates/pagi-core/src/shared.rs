@@ -1,8 +1,12 @@
 //! Shared types used across all UAC crates.
 
+use crate::knowledge::{EncryptionConfig, OidcConfig, RedactionConfig, DEFAULT_MAX_VALUE_BYTES};
+use crate::orchestrator::{PayloadMap, RateLimit, WebhookEvent};
+use crate::clock::system_now_ms;
+use crate::secrets::SecretsConfig;
+use crate::storage::StorageBackend;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::path::Path;
 
 // -----------------------------------------------------------------------------
 // Emotional Context Layer — Cognitive Governor state (Kardia/Soma as state-modifiers for Logos)
@@ -67,12 +71,142 @@ impl MentalState {
 /// Key in KB_KARDIA (slot 7) where the serialized MentalState is stored.
 pub const MENTAL_STATE_KEY: &str = "mental_state";
 
+/// Cognitive Governor → generation parameters: how the effective [`MentalState`] adjusts
+/// temperature, max_tokens, and the response length target of a ModelRouter request.
+///
+/// Adjustments only apply when a threshold is crossed (or grace > 1.0); a calm state leaves the
+/// request untouched. Override fields with `PAGI_GENERATION_MODULATION` (JSON), or set it to
+/// `off` to disable.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GenerationModulation {
+    pub enabled: bool,
+    /// Temperature assumed when the request does not set one.
+    pub base_temperature: f32,
+    /// max_tokens assumed when the request does not set one.
+    pub base_max_tokens: u32,
+    /// Response length target (words) before scaling.
+    pub base_response_words: u32,
+    /// `relational_stress` above this shifts temperature by `stress_temperature_delta`.
+    pub stress_threshold: f32,
+    pub stress_temperature_delta: f32,
+    /// `burnout_risk` above this scales max_tokens and the length target by `burnout_length_factor`.
+    pub burnout_threshold: f32,
+    pub burnout_length_factor: f32,
+    pub min_temperature: f32,
+    pub max_temperature: f32,
+}
+
+impl Default for GenerationModulation {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            base_temperature: 0.7,
+            base_max_tokens: 1024,
+            base_response_words: 300,
+            stress_threshold: 0.7,
+            stress_temperature_delta: -0.3,
+            burnout_threshold: 0.6,
+            burnout_length_factor: 0.6,
+            min_temperature: 0.1,
+            max_temperature: 1.2,
+        }
+    }
+}
+
+/// Generation parameters after [`GenerationModulation::apply`], with the reasons for each change.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GenerationAdjustment {
+    pub temperature: Option<f32>,
+    pub max_tokens: Option<u32>,
+    /// Soft length target for the reply (words); None when no length pressure applies.
+    pub response_words: Option<u32>,
+    pub reasons: Vec<String>,
+}
+
+impl GenerationAdjustment {
+    /// True when the mental state changed anything.
+    pub fn is_adjusted(&self) -> bool {
+        !self.reasons.is_empty()
+    }
+
+    /// System-prompt line carrying the length target.
+    pub fn length_instruction(&self) -> Option<String> {
+        self.response_words
+            .map(|w| format!("Keep the reply under about {} words.", w))
+    }
+}
+
+impl GenerationModulation {
+    /// Defaults overridden by `PAGI_GENERATION_MODULATION` (JSON object or `off`).
+    pub fn from_env() -> Self {
+        match std::env::var("PAGI_GENERATION_MODULATION") {
+            Ok(v) if matches!(v.trim(), "off" | "false" | "0") => Self {
+                enabled: false,
+                ..Self::default()
+            },
+            Ok(v) if !v.trim().is_empty() => serde_json::from_str(&v).unwrap_or_else(|e| {
+                tracing::warn!(target: "pagi::orchestrator", error = %e, "Invalid PAGI_GENERATION_MODULATION; using defaults");
+                Self::default()
+            }),
+            _ => Self::default(),
+        }
+    }
+
+    /// Maps the effective mental state onto the requested parameters.
+    pub fn apply(
+        &self,
+        state: &MentalState,
+        temperature: Option<f32>,
+        max_tokens: Option<u32>,
+    ) -> GenerationAdjustment {
+        let mut out = GenerationAdjustment {
+            temperature,
+            max_tokens,
+            ..GenerationAdjustment::default()
+        };
+        if !self.enabled {
+            return out;
+        }
+        if state.relational_stress > self.stress_threshold {
+            let t = temperature.unwrap_or(self.base_temperature) + self.stress_temperature_delta;
+            out.temperature = Some(t.clamp(self.min_temperature, self.max_temperature));
+            out.reasons.push(format!(
+                "relational_stress {:.2} > {:.2}: temperature {:+.2}",
+                state.relational_stress, self.stress_threshold, self.stress_temperature_delta
+            ));
+        }
+        let mut length_factor = 1.0f32;
+        if state.burnout_risk > self.burnout_threshold {
+            length_factor *= self.burnout_length_factor;
+            out.reasons.push(format!(
+                "burnout_risk {:.2} > {:.2}: length x{:.2}",
+                state.burnout_risk, self.burnout_threshold, self.burnout_length_factor
+            ));
+        }
+        if state.grace_multiplier > 1.0 {
+            length_factor /= state.grace_multiplier;
+            out.reasons.push(format!(
+                "grace_multiplier {:.2}: length x{:.2}",
+                state.grace_multiplier,
+                1.0 / state.grace_multiplier
+            ));
+        }
+        if length_factor < 1.0 {
+            let tokens = max_tokens.unwrap_or(self.base_max_tokens) as f32 * length_factor;
+            out.max_tokens = Some((tokens.round() as u32).max(64));
+            out.response_words = Some(((self.base_response_words as f32 * length_factor).round() as u32).max(30));
+        }
+        out
+    }
+}
+
 // -----------------------------------------------------------------------------
 // Relational Map (Kardia) — Person records in Slot 7
 // -----------------------------------------------------------------------------
 
 /// Prefix in **KB_KARDIA** (Slot 7) for person records. Full key: `people/{name_slug}`.
-pub const KARDIA_PEOPLE_PREFIX: &str = "people/";
+pub const KARDIA_PEOPLE_PREFIX: &str = crate::keys::PEOPLE_PREFIX;
 
 /// One person in the Relational Map (Kardia). Tracks relationship, trust, attachment style, and triggers
 /// so the AGI can give context-aware advice when the user mentions them (e.g. in journal or conflict).
@@ -403,7 +537,10 @@ impl EthosPolicy {
 pub const DEFAULT_AGENT_ID: &str = "default";
 
 /// Tenant context for multi-tenant and multi-agent isolation across the UAC system.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+///
+/// Build it with [`new`](Self::new) (or `..Default::default()` when setting several fields) so
+/// adding a field does not break every literal.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct TenantContext {
     /// Unique tenant identifier.
     pub tenant_id: String,
@@ -413,9 +550,28 @@ pub struct TenantContext {
     /// When None or empty, [`DEFAULT_AGENT_ID`] is used.
     #[serde(default)]
     pub agent_id: Option<String>,
+    /// Absolute deadline (Unix ms) of the request. The orchestrator stops the dispatch with
+    /// `DeadlineExceeded` when it passes, and skills bound their own timeouts by it
+    /// ([`bounded_timeout`](Self::bounded_timeout)). None means no deadline.
+    #[serde(default)]
+    pub deadline_ms: Option<i64>,
 }
 
 impl TenantContext {
+    /// Context for `tenant_id` with no correlation id, agent or deadline.
+    pub fn new(tenant_id: impl Into<String>) -> Self {
+        Self {
+            tenant_id: tenant_id.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Sets the absolute deadline (Unix ms), keeping an earlier one if already set.
+    pub fn with_deadline(mut self, deadline_ms: i64) -> Self {
+        self.deadline_ms = Some(self.deadline_ms.map_or(deadline_ms, |d| d.min(deadline_ms)));
+        self
+    }
+
     /// Resolved agent ID (never empty).
     pub fn resolved_agent_id(&self) -> &str {
         self.agent_id
@@ -423,6 +579,26 @@ impl TenantContext {
             .filter(|s| !s.is_empty())
             .unwrap_or(DEFAULT_AGENT_ID)
     }
+
+    /// Sets the deadline `timeout` from now, keeping an earlier deadline if one is already set.
+    pub fn with_timeout(self, timeout: std::time::Duration) -> Self {
+        self.with_deadline(system_now_ms().saturating_add(timeout.as_millis().min(i64::MAX as u128) as i64))
+    }
+
+    /// Time left before the deadline (zero once it has passed); None without a deadline.
+    pub fn remaining(&self) -> Option<std::time::Duration> {
+        self.deadline_ms
+            .map(|d| std::time::Duration::from_millis(d.saturating_sub(system_now_ms()).max(0) as u64))
+    }
+
+    pub fn deadline_exceeded(&self) -> bool {
+        self.remaining().is_some_and(|r| r.is_zero())
+    }
+
+    /// `timeout` shortened to the time left before the deadline.
+    pub fn bounded_timeout(&self, timeout: std::time::Duration) -> std::time::Duration {
+        self.remaining().map_or(timeout, |r| r.min(timeout))
+    }
 }
 
 /// High-level goal types the orchestrator can delegate.
@@ -433,8 +609,16 @@ pub enum Goal {
     ExecuteSkill { name: String, payload: Option<serde_json::Value> },
     /// Query the knowledge base by slot index (1–8).
     QueryKnowledge { slot_id: u8, query: String },
-    /// Read or write memory at a path.
-    MemoryOp { path: String, value: Option<serde_json::Value> },
+    /// Read (no `value`), write (`value`) or `delete` memory at a path within the caller's tenant:
+    /// `vault/{tree}/{key}`, `knowledge/{slot}/{key}`, or the typed lead records (`leads` lists
+    /// them with `value` as a `LeadFilter` plus `cursor`, `leads/{id}`, `leads/{id}/status`).
+    /// Writes and deletes are checked against the Ethos policy as skill `MemoryOp`.
+    MemoryOp {
+        path: String,
+        value: Option<serde_json::Value>,
+        #[serde(default)]
+        delete: bool,
+    },
     /// Generic data ingestion (e.g. lead capture, form submit). Payload is use-case specific.
     IngestData { payload: Option<serde_json::Value> },
     /// Assemble context from memory and knowledge slots for a given context id (e.g. lead_id).
@@ -449,8 +633,51 @@ pub enum Goal {
         source_url: Option<String>,
         source_html: Option<String>,
     },
+    /// Chat in which the model may call the tenant's skills (KB-5 manifests) as tools; the
+    /// orchestrator runs each call and feeds the result back for up to `max_iterations` model turns.
+    ToolChat {
+        prompt: String,
+        system_prompt: Option<String>,
+        max_iterations: Option<u32>,
+    },
     /// Custom goal for extension.
     Custom(String),
+    /// Run sub-goals in order; the first failure stops the sequence (remaining goals are skipped).
+    Sequence(Vec<Goal>),
+    /// Run sub-goals concurrently; each succeeds or fails independently.
+    Parallel(Vec<Goal>),
+}
+
+impl Goal {
+    /// Variant name (for traces and composite results).
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Goal::ExecuteSkill { .. } => "ExecuteSkill",
+            Goal::QueryKnowledge { .. } => "QueryKnowledge",
+            Goal::MemoryOp { .. } => "MemoryOp",
+            Goal::IngestData { .. } => "IngestData",
+            Goal::AssembleContext { .. } => "AssembleContext",
+            Goal::GenerateFinalResponse { .. } => "GenerateFinalResponse",
+            Goal::AutonomousGoal { .. } => "AutonomousGoal",
+            Goal::UpdateKnowledgeSlot { .. } => "UpdateKnowledgeSlot",
+            Goal::ToolChat { .. } => "ToolChat",
+            Goal::Custom(_) => "Custom",
+            Goal::Sequence(_) => "Sequence",
+            Goal::Parallel(_) => "Parallel",
+        }
+    }
+
+    /// Skill the orchestrator runs for this goal directly; None for plans, composites and goals
+    /// handled without a skill.
+    pub fn skill_name(&self) -> Option<&str> {
+        match self {
+            Goal::ExecuteSkill { name, .. } => Some(name),
+            Goal::QueryKnowledge { .. } => Some("KnowledgeQuery"),
+            Goal::IngestData { .. } => Some("LeadCapture"),
+            Goal::AssembleContext { .. } | Goal::GenerateFinalResponse { .. } => Some("DraftResponse"),
+            _ => None,
+        }
+    }
 }
 
 /// Global application configuration (Gateway + identity). Load from TOML or env.
@@ -460,7 +687,22 @@ pub struct CoreConfig {
     pub app_name: String,
     /// HTTP port for the gateway.
     pub port: u16,
-    /// Base directory for Sled DBs (memory vault and knowledge store paths are derived from this).
+    /// Addresses the gateway listens on with `port`, or `address:port` for its own port. Empty
+    /// means `127.0.0.1`; `0.0.0.0` / `::` listen on every interface (containers). Env
+    /// `PAGI__BIND_ADDRESSES` takes a comma-separated list.
+    #[serde(default, deserialize_with = "comma_list")]
+    pub bind_addresses: Vec<String>,
+    /// HTTPS for every bind address (`[tls]`); plain HTTP when unset.
+    #[serde(default)]
+    pub tls: TlsConfig,
+    /// Log format and per-request logging (`[logging]`).
+    #[serde(default)]
+    pub logging: LoggingConfig,
+    /// Deterministic mode for CI and local dev: frozen clock, seeded mock LLM, throwaway stores
+    /// (`[simulation]`, off by default).
+    #[serde(default)]
+    pub simulation: SimulationConfig,
+    /// Base directory for the DBs (memory vault and knowledge store paths are derived from this).
     pub storage_path: String,
     /// LLM mode (e.g. "mock", "openai", "local").
     pub llm_mode: String,
@@ -471,9 +713,822 @@ pub struct CoreConfig {
     /// Human-readable labels for knowledge slots 1–8. Keys in file are string numerals "1".."8".
     #[serde(default)]
     pub slot_labels: HashMap<String, String>,
+    /// Seconds between Heartbeat ticks. Unset falls back to env `PAGI_TICK_RATE_SECS`, else 5.
+    #[serde(default)]
+    pub tick_rate_secs: Option<u64>,
+    /// Backend of the memory vault and knowledge store: `"sled"` (default) or `"sqlite"`, which
+    /// lets several processes open them at once. Env `PAGI__STORAGE_BACKEND`.
+    #[serde(default)]
+    pub storage_backend: StorageBackend,
+    /// LLM providers by name (`[llm_providers.<name>]`), referenced from `model_aliases`.
+    #[serde(default)]
+    pub llm_providers: HashMap<String, LlmProviderConfig>,
+    /// Model aliases (`[[model_aliases.<alias>]]`): a generation for `<alias>` tries its targets in
+    /// order and moves to the next one when a provider answers 5xx or times out.
+    #[serde(default)]
+    pub model_aliases: HashMap<String, Vec<ModelTarget>>,
+    /// Monthly LLM token budgets per tenant (`[token_budgets]`), enforced by the ModelRouter.
+    #[serde(default)]
+    pub token_budgets: TokenBudgetConfig,
+    /// PII masking before KB_CHRONOS storage and live LLM calls (`[redaction]`, off by default).
+    #[serde(default)]
+    pub redaction: RedactionConfig,
+    /// JWTs from an OIDC provider, accepted by the gateway next to API keys (`[oidc]`, off by
+    /// default).
+    #[serde(default)]
+    pub oidc: OidcConfig,
+    /// Request body and KB value size limits (`[limits]`).
+    #[serde(default)]
+    pub limits: LimitsConfig,
+    /// Slots 1–8 encrypted at rest with the vault key (`[encryption]`, none by default).
+    #[serde(default)]
+    pub encryption: EncryptionConfig,
+    /// Where keys and provider credentials come from (`[secrets]`, environment by default).
+    #[serde(default)]
+    pub secrets: SecretsConfig,
+    /// How CommunityScraper fetches pages (`[scraper]`).
+    #[serde(default)]
+    pub scraper: ScraperConfig,
+    /// Retrieval for RAG chats: candidate pool, reranker and score thresholds (`[rag]`).
+    #[serde(default)]
+    pub rag: RagConfig,
+    /// Third-party webhooks turned into leads at `POST /api/v1/ingest/:source` (`[ingest]`).
+    #[serde(default)]
+    pub ingest: IngestConfig,
+    /// How SendEmail delivers messages: provider, senders and templates (`[email]`, off by
+    /// default).
+    #[serde(default)]
+    pub email: EmailConfig,
+    /// Slack and Discord channels for NotifyChat, and the Slack slash-command bridge (`[chat]`).
+    #[serde(default)]
+    pub chat: ChatConfig,
+    /// Messaging services end users chat with an agent over (`[channels]`).
+    #[serde(default)]
+    pub channels: ChannelsConfig,
+    /// Where CalendarSkill reads availability and books events (`[calendar]`).
+    #[serde(default)]
+    pub calendar: CalendarConfig,
+    /// Outbound webhooks (`[webhooks]`).
+    #[serde(default)]
+    pub webhooks: WebhooksConfig,
+}
+
+/// A list given as a TOML array or as one comma-separated string (from an environment variable).
+fn comma_list<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<Vec<String>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum List {
+        One(String),
+        Many(Vec<String>),
+    }
+    Ok(match List::deserialize(deserializer)? {
+        List::One(text) => text.split(',').map(str::trim).filter(|s| !s.is_empty()).map(str::to_string).collect(),
+        List::Many(items) => items,
+    })
+}
+
+/// Certificate and key the gateway serves HTTPS with (`[tls]`). Both set = HTTPS, neither = HTTP.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TlsConfig {
+    /// PEM certificate chain, leaf first.
+    pub cert_path: Option<String>,
+    /// PEM private key (PKCS#8, PKCS#1 or SEC1).
+    pub key_path: Option<String>,
+}
+
+impl TlsConfig {
+    pub fn enabled(&self) -> bool {
+        self.cert_path.is_some() && self.key_path.is_some()
+    }
+}
+
+/// How gateway log events are written to stdout.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LogFormat {
+    /// Human-readable lines.
+    #[default]
+    Text,
+    /// One JSON object per event: `ts_ms`, `level`, `target`, `message`, `fields`,
+    /// `correlation_id` and `tenant` (from the event or its dispatch span).
+    Json,
+}
+
+/// Log output (`[logging]`). Env `PAGI__LOGGING__FORMAT=json`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LoggingConfig {
+    pub format: LogFormat,
+    /// Log method, path, status, latency and body sizes of every HTTP request (target
+    /// `pagi::http`; health and readiness probes at debug level).
+    pub requests: bool,
+}
+
+impl Default for LoggingConfig {
+    fn default() -> Self {
+        Self { format: LogFormat::Text, requests: true }
+    }
+}
+
+/// Outbound webhooks (`[webhooks]`). Env `PAGI__WEBHOOKS__ALLOW_PRIVATE_HOSTS=true`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WebhooksConfig {
+    /// Let webhooks reach loopback, link-local and private addresses (local development only;
+    /// cloud metadata hosts stay reachable too, so never turn this on in production).
+    pub allow_private_hosts: bool,
+}
+
+/// Fixed start of the simulated clock: 2024-01-01T00:00:00Z.
+pub const SIMULATION_DEFAULT_START_MS: i64 = 1_704_067_200_000;
+
+/// Deterministic simulation mode (`[simulation]`). Env `PAGI__SIMULATION__ENABLED=true`.
+///
+/// The gateway then opens its stores in a fresh temporary directory, runs on a
+/// [`FrozenClock`](crate::FrozenClock) that starts at `start_ms` and moves one tick interval per
+/// Heartbeat tick, and answers every generation with the mock LLM, from `fixtures_path` when set
+/// (see `MockFixtures` in pagi-skills).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SimulationConfig {
+    pub enabled: bool,
+    pub start_ms: i64,
+    /// JSON fixtures file of mock replies; the built-in mock text when unset.
+    pub fixtures_path: Option<String>,
+    /// Replaces the fixtures file's `seed`.
+    pub seed: Option<u64>,
+}
+
+impl Default for SimulationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            start_ms: SIMULATION_DEFAULT_START_MS,
+            fixtures_path: None,
+            seed: None,
+        }
+    }
+}
+
+/// API spoken by an LLM provider.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LlmProviderKind {
+    /// OpenAI, or any OpenAI-compatible API (OpenRouter).
+    #[default]
+    Openai,
+    /// Anthropic Messages API.
+    Anthropic,
+    /// Local Ollama server (OpenAI-compatible endpoint, no key).
+    Ollama,
+    /// Local llama.cpp server (OpenAI-compatible endpoint, no key).
+    LlamaCpp,
+    /// Azure OpenAI; a target's `model` is the deployment name.
+    Azure,
+}
+
+impl LlmProviderKind {
+    /// Secret holding the key when `api_key_env` is unset (local kinds need none).
+    pub fn default_api_key_env(self) -> Option<&'static str> {
+        match self {
+            Self::Openai => Some("OPENAI_API_KEY"),
+            Self::Anthropic => Some("ANTHROPIC_API_KEY"),
+            Self::Azure => Some("AZURE_OPENAI_API_KEY"),
+            Self::Ollama | Self::LlamaCpp => None,
+        }
+    }
+}
+
+/// One provider under `[llm_providers.<name>]`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LlmProviderConfig {
+    pub kind: LlmProviderKind,
+    /// Chat endpoint; each kind has a default. For Azure, the resource endpoint
+    /// (`https://<resource>.openai.azure.com`).
+    pub api_url: Option<String>,
+    /// Secret holding the key, from `[secrets]` (default per kind; local kinds need none).
+    pub api_key_env: Option<String>,
+    /// Azure `api-version` query parameter.
+    pub api_version: Option<String>,
+}
+
+/// A model served by one provider, as listed under a model alias.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModelTarget {
+    /// Key in `llm_providers`.
+    pub provider: String,
+    pub model: String,
+}
+
+/// `[token_budgets]`: tokens a tenant may use per calendar month (UTC). Tenants without a limit
+/// are unmetered; usage is still recorded.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TokenBudgetConfig {
+    /// Limit for tenants without an entry in `tenants` (None = unlimited).
+    /// Env `PAGI__TOKEN_BUDGETS__MONTHLY_TOKENS`.
+    pub monthly_tokens: Option<u64>,
+    /// Per-tenant limits (`[token_budgets.tenants]`, `acme = 2000000`).
+    pub tenants: HashMap<String, u64>,
+}
+
+/// `[limits]`: how large request bodies and stored KB values may be, and how long and how much
+/// data a skill call may take (see [`SkillLimits`]).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LimitsConfig {
+    /// Largest request body the gateway reads (bytes); larger ones get 413.
+    /// Env `PAGI__LIMITS__MAX_BODY_BYTES`.
+    pub max_body_bytes: usize,
+    /// Per-route overrides of `max_body_bytes`, by route pattern
+    /// (`"/api/v1/sandbox/files/*path" = 4194304`).
+    pub route_body_bytes: HashMap<String, usize>,
+    /// Largest value a knowledge store write may store (bytes).
+    /// Env `PAGI__LIMITS__MAX_KB_VALUE_BYTES`.
+    pub max_kb_value_bytes: usize,
+    /// Longest a skill call may run (ms, 0 = no limit); a plan step's `timeout_ms` can only
+    /// shorten it. Env `PAGI__LIMITS__SKILL_TIMEOUT_MS`.
+    pub skill_timeout_ms: u64,
+    /// Largest serialized skill payload (bytes, 0 = no limit).
+    /// Env `PAGI__LIMITS__SKILL_MAX_PAYLOAD_BYTES`.
+    pub skill_max_payload_bytes: usize,
+    /// Largest serialized skill result (bytes, 0 = no limit).
+    /// Env `PAGI__LIMITS__SKILL_MAX_RESULT_BYTES`.
+    pub skill_max_result_bytes: usize,
+    /// Per-skill limits (`[limits.skills.CommunityScraper]`, `timeout_ms = 60000`).
+    pub skills: HashMap<String, SkillLimits>,
+}
+
+impl Default for LimitsConfig {
+    fn default() -> Self {
+        Self {
+            max_body_bytes: 2 * 1024 * 1024,
+            route_body_bytes: HashMap::new(),
+            max_kb_value_bytes: DEFAULT_MAX_VALUE_BYTES,
+            skill_timeout_ms: 5 * 60 * 1000,
+            skill_max_payload_bytes: 8 * 1024 * 1024,
+            skill_max_result_bytes: 16 * 1024 * 1024,
+            skills: HashMap::new(),
+        }
+    }
+}
+
+/// Execution limits of one skill: from `[limits.skills.<name>]`, or declared by the skill itself
+/// (`AgentSkill::limits`). Unset fields fall back to the skill's own, then to `[limits]`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SkillLimits {
+    pub timeout_ms: Option<u64>,
+    pub max_payload_bytes: Option<usize>,
+    pub max_result_bytes: Option<usize>,
+    /// Directory the payload's `path` and `roots` must stay inside (relative paths are taken
+    /// from it).
+    pub sandbox_root: Option<std::path::PathBuf>,
+}
+
+/// `[scraper]`: how CommunityScraper and FeedIngest fetch `url`s.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScraperConfig {
+    /// Fetch pages over HTTP; when false only inline `html` is accepted.
+    /// Env `PAGI__SCRAPER__FETCH`.
+    pub fetch: bool,
+    /// Sent as `User-Agent` and matched against robots.txt groups.
+    pub user_agent: String,
+    /// Per-request timeout (seconds), shortened by the request deadline.
+    pub timeout_secs: u64,
+    /// Skip URLs the site's robots.txt disallows for `user_agent`.
+    pub respect_robots: bool,
+    /// Minimum gap between requests to one host (ms); a larger robots.txt `Crawl-delay` wins.
+    pub min_interval_ms: u64,
+    /// Let fetches (and their redirects) reach loopback, link-local and private addresses, like
+    /// `[webhooks] allow_private_hosts` (local development only).
+    pub allow_private_hosts: bool,
+}
+
+impl Default for ScraperConfig {
+    fn default() -> Self {
+        Self {
+            fetch: true,
+            user_agent: "UAC-CommunityScraper/1.0".to_string(),
+            timeout_secs: 30,
+            respect_robots: true,
+            min_interval_ms: 1_000,
+            allow_private_hosts: false,
+        }
+    }
+}
+
+/// How RAG candidates are reordered after the semantic search.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RerankerKind {
+    /// Keep the similarity order.
+    #[default]
+    None,
+    /// Local term-overlap scoring (IDF-weighted coverage of the question), blended with the
+    /// similarity; no model call.
+    Lexical,
+    /// The LLM scores each candidate against the question (cross-encoder style); falls back to
+    /// `lexical` when the model cannot answer.
+    Llm,
+}
+
+/// `[rag]`: how a RAG chat picks the sources it puts into the prompt.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RagConfig {
+    /// Env `PAGI__RAG__RERANKER`.
+    pub reranker: RerankerKind,
+    /// Candidates retrieved per slot for the reranker; at least the request's `rag_top_k`.
+    pub candidate_pool: usize,
+    /// Candidates below this similarity (cosine, -1..1) are dropped before reranking.
+    pub min_similarity: f32,
+    /// Candidates the reranker scores below this (0..1) are not cited.
+    pub min_rerank_score: f32,
+}
+
+impl Default for RagConfig {
+    fn default() -> Self {
+        Self {
+            reranker: RerankerKind::None,
+            candidate_pool: 20,
+            min_similarity: -1.0,
+            min_rerank_score: 0.0,
+        }
+    }
+}
+
+/// `[ingest]`: form and scheduling providers that may post leads to `/api/v1/ingest/:source`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct IngestConfig {
+    /// Sources by the `:source` they post to (`[ingest.sources.<name>]`); others get 404.
+    pub sources: HashMap<String, IngestSourceConfig>,
+}
+
+/// How an ingest source's payloads are shaped and signed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IngestAdapter {
+    /// Any JSON object, signed like PAGI's own outbound webhooks (`X-Pagi-Signature`).
+    #[default]
+    Generic,
+    /// Typeform `form_response` webhooks (`Typeform-Signature`).
+    Typeform,
+    /// Calendly `invitee.created` webhooks (`Calendly-Webhook-Signature`).
+    Calendly,
+}
+
+/// One source under `[ingest.sources.<name>]`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct IngestSourceConfig {
+    /// Payload shape; defaults to the source name when it names an adapter, else `generic`.
+    pub adapter: Option<IngestAdapter>,
+    /// Secret holding the provider's signing key, from `[secrets]`. Required unless
+    /// `allow_unsigned`.
+    pub secret_env: Option<String>,
+    /// Accept payloads without a valid signature (local testing only).
+    pub allow_unsigned: bool,
+    /// Tenant the leads are captured for (the default tenant when unset).
+    pub tenant_id: Option<String>,
+    /// Lead fields built from the adapter's normalized payload with `$` selectors; the adapter's
+    /// default template when empty.
+    pub map: PayloadMap,
+}
+
+impl IngestSourceConfig {
+    /// The configured adapter, or the one the source `name` names.
+    pub fn adapter_for(&self, name: &str) -> IngestAdapter {
+        self.adapter.unwrap_or(match name {
+            "typeform" => IngestAdapter::Typeform,
+            "calendly" => IngestAdapter::Calendly,
+            _ => IngestAdapter::Generic,
+        })
+    }
+}
+
+/// Service SendEmail hands messages to.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EmailProvider {
+    /// Sending is off; SendEmail fails without contacting anyone.
+    #[default]
+    None,
+    /// An SMTP submission server (`[email.smtp]`).
+    Smtp,
+    /// The SendGrid v3 Mail Send API.
+    Sendgrid,
+}
+
+/// How the SMTP connection is secured.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SmtpTls {
+    /// Plain connection upgraded with `STARTTLS` (port 587).
+    #[default]
+    Starttls,
+    /// TLS from the first byte (port 465).
+    Implicit,
+    /// No TLS (local relays and tests only; credentials are sent in the clear).
+    None,
+}
+
+/// `[email.smtp]`: the submission server.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SmtpConfig {
+    pub host: String,
+    pub port: u16,
+    pub tls: SmtpTls,
+    /// `AUTH PLAIN` user; no authentication when unset.
+    pub username: Option<String>,
+    /// Secret holding the password, from `[secrets]` (default `SMTP_PASSWORD`).
+    pub password_env: Option<String>,
+}
+
+impl Default for SmtpConfig {
+    fn default() -> Self {
+        Self {
+            host: "localhost".to_string(),
+            port: 587,
+            tls: SmtpTls::Starttls,
+            username: None,
+            password_env: None,
+        }
+    }
+}
+
+/// A named email under `[email.templates.<name>]`: `{{variable}}` placeholders filled from the
+/// lead and the call's `vars`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EmailTemplate {
+    pub subject: String,
+    pub body: String,
+}
+
+/// `[email]`: how SendEmail delivers generated responses.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EmailConfig {
+    /// Env `PAGI__EMAIL__PROVIDER`.
+    pub provider: EmailProvider,
+    /// Sender (`"Acme Sales <sales@acme.example>"`) for tenants without one in `tenant_from`.
+    pub from: Option<String>,
+    /// Per-tenant senders (`[email.tenant_from]`, `acme = "Acme <hello@acme.example>"`).
+    pub tenant_from: HashMap<String, String>,
+    pub smtp: SmtpConfig,
+    /// Secret holding the SendGrid API key, from `[secrets]` (default `SENDGRID_API_KEY`).
+    pub sendgrid_api_key_env: Option<String>,
+    /// Mail Send endpoint; the SendGrid API by default.
+    pub sendgrid_api_url: Option<String>,
+    /// Templates by name (`[email.templates.<name>]`).
+    pub templates: HashMap<String, EmailTemplate>,
+    /// Per-message timeout (seconds) for the SMTP session or API call.
+    pub timeout_secs: u64,
+}
+
+impl Default for EmailConfig {
+    fn default() -> Self {
+        Self {
+            provider: EmailProvider::None,
+            from: None,
+            tenant_from: HashMap::new(),
+            smtp: SmtpConfig::default(),
+            sendgrid_api_key_env: None,
+            sendgrid_api_url: None,
+            templates: HashMap::new(),
+            timeout_secs: 30,
+        }
+    }
+}
+
+impl EmailConfig {
+    /// The sender for `tenant_id`: its own, else `from`.
+    pub fn from_for(&self, tenant_id: &str) -> Option<&str> {
+        self.tenant_from.get(tenant_id).or(self.from.as_ref()).map(String::as_str)
+    }
+
+    /// Secret holding the SMTP password.
+    pub fn smtp_password_env(&self) -> &str {
+        self.smtp.password_env.as_deref().unwrap_or("SMTP_PASSWORD")
+    }
+
+    /// Secret holding the SendGrid API key.
+    pub fn sendgrid_api_key_env(&self) -> &str {
+        self.sendgrid_api_key_env.as_deref().unwrap_or("SENDGRID_API_KEY")
+    }
+}
+
+/// Chat service a channel posts to.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChatKind {
+    /// Slack incoming webhook (`{ text }`).
+    #[default]
+    Slack,
+    /// Discord channel webhook (`{ content }`).
+    Discord,
+}
+
+/// One channel under `[chat.channels.<name>]`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ChatChannelConfig {
+    pub kind: ChatKind,
+    /// Secret holding the channel's webhook URL, from `[secrets]` (the URL is the credential).
+    pub webhook_env: String,
+}
+
+/// `[chat.slack_commands]`: Slack slash commands dispatched as goals at
+/// `POST /api/v1/chat/slack/commands`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SlackCommandConfig {
+    pub enabled: bool,
+    /// Secret holding the Slack app's signing secret (default `SLACK_SIGNING_SECRET`).
+    pub signing_secret_env: Option<String>,
+    /// Tenant the goals run for (the default tenant when unset).
+    pub tenant_id: Option<String>,
+    /// Agent the goals run as.
+    pub agent_id: Option<String>,
+}
+
+impl SlackCommandConfig {
+    pub fn signing_secret_env(&self) -> &str {
+        self.signing_secret_env.as_deref().unwrap_or("SLACK_SIGNING_SECRET")
+    }
+}
+
+/// `[chat]`: where NotifyChat posts, and which events it posts.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ChatConfig {
+    /// Channels by name (`[chat.channels.<name>]`).
+    pub channels: HashMap<String, ChatChannelConfig>,
+    /// Channel per agent (`[chat.agents]`, `SAGE_BOT = "ops"`).
+    pub agents: HashMap<String, String>,
+    /// Channel for agents without one in `agents`.
+    pub default_channel: Option<String>,
+    /// Events posted to the acting agent's channel as they happen (`policy.violated`,
+    /// `maintenance.opened`, `goal.completed`, ...); none by default.
+    pub notify: Vec<WebhookEvent>,
+    pub slack_commands: SlackCommandConfig,
+}
+
+impl ChatConfig {
+    /// The channel `agent_id` posts to: its own, else `default_channel`.
+    pub fn channel_for(&self, agent_id: &str) -> Option<(&str, &ChatChannelConfig)> {
+        let name = self.agents.get(agent_id).or(self.default_channel.as_ref())?;
+        self.channels.get(name).map(|channel| (name.as_str(), channel))
+    }
+}
+
+/// How the Telegram bot receives its updates.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TelegramMode {
+    /// The gateway long-polls `getUpdates`; no public URL needed.
+    #[default]
+    Polling,
+    /// Telegram POSTs updates to `/api/v1/channels/telegram/webhook` (registered with `setWebhook`).
+    Webhook,
+}
+
+/// Tenant and agent one chat talks to; unset fields fall back to the channel's.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ChannelRoute {
+    pub tenant_id: Option<String>,
+    pub agent_id: Option<String>,
+}
+
+/// `[channels.telegram]`: a Telegram bot whose chats run through the chat pipeline.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TelegramConfig {
+    pub enabled: bool,
+    pub mode: TelegramMode,
+    /// Secret holding the bot token from @BotFather (default `TELEGRAM_BOT_TOKEN`).
+    pub bot_token_env: Option<String>,
+    /// Secret compared with `X-Telegram-Bot-Api-Secret-Token` in webhook mode (default
+    /// `TELEGRAM_WEBHOOK_SECRET`).
+    pub webhook_secret_env: Option<String>,
+    /// Bot API base URL (default `https://api.telegram.org`).
+    pub api_url: Option<String>,
+    /// Tenant and agent of chats without an entry in `chats` (default tenant and agent).
+    pub tenant_id: Option<String>,
+    pub agent_id: Option<String>,
+    /// Per-chat routes by Telegram chat id (`[channels.telegram.chats."123456789"]`).
+    pub chats: HashMap<String, ChannelRoute>,
+    /// Answer only the chats listed in `chats`.
+    pub listed_chats_only: bool,
+    /// Messages each chat may send, as `per_minute[/burst]` (default `20/5`).
+    pub rate_limit: Option<String>,
+}
+
+impl TelegramConfig {
+    pub fn bot_token_env(&self) -> &str {
+        self.bot_token_env.as_deref().unwrap_or("TELEGRAM_BOT_TOKEN")
+    }
+
+    pub fn webhook_secret_env(&self) -> &str {
+        self.webhook_secret_env.as_deref().unwrap_or("TELEGRAM_WEBHOOK_SECRET")
+    }
+
+    pub fn api_url(&self) -> &str {
+        self.api_url.as_deref().unwrap_or("https://api.telegram.org").trim_end_matches('/')
+    }
+
+    /// Tenant and agent of `chat_id`, or None when the chat is not listed and only listed
+    /// chats are answered.
+    pub fn route_for(&self, chat_id: &str) -> Option<(String, String)> {
+        let route = self.chats.get(chat_id);
+        if route.is_none() && self.listed_chats_only {
+            return None;
+        }
+        let pick = |own: Option<&Option<String>>, channel: &Option<String>, default: &str| {
+            own.and_then(|o| o.clone()).or_else(|| channel.clone()).unwrap_or_else(|| default.to_string())
+        };
+        Some((
+            pick(route.map(|r| &r.tenant_id), &self.tenant_id, crate::DEFAULT_TENANT_ID),
+            pick(route.map(|r| &r.agent_id), &self.agent_id, DEFAULT_AGENT_ID),
+        ))
+    }
+
+    /// The per-chat message limit; an unparsable `rate_limit` falls back to the default.
+    pub fn rate_limit(&self) -> RateLimit {
+        self.rate_limit
+            .as_deref()
+            .and_then(RateLimit::parse)
+            .unwrap_or(RateLimit { per_minute: 20, burst: 5 })
+    }
+}
+
+/// `[channels]`: conversational channel adapters.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ChannelsConfig {
+    pub telegram: TelegramConfig,
+}
+
+/// Calendar service CalendarSkill talks to.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CalendarProvider {
+    /// No calendar configured; CalendarSkill fails with a configuration error.
+    #[default]
+    None,
+    /// Google Calendar API v3, with an OAuth refresh token.
+    Google,
+    /// A CalDAV calendar collection (Nextcloud, iCloud, Fastmail, Radicale, ...).
+    Caldav,
+}
+
+/// `[calendar.google]`: OAuth client and calendar.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GoogleCalendarConfig {
+    /// Calendar id (default `primary`).
+    pub calendar_id: Option<String>,
+    pub client_id: String,
+    /// Secret holding the OAuth client secret (default `GOOGLE_CALENDAR_CLIENT_SECRET`).
+    pub client_secret_env: Option<String>,
+    /// Secret holding the refresh token (default `GOOGLE_CALENDAR_REFRESH_TOKEN`).
+    pub refresh_token_env: Option<String>,
+    /// API and token endpoints; Google's by default.
+    pub api_url: Option<String>,
+    pub token_url: Option<String>,
+}
+
+/// `[calendar.caldav]`: the calendar collection.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CalDavConfig {
+    /// Collection URL (`https://dav.example.com/calendars/sales/consultations/`).
+    pub url: String,
+    /// Basic auth user; no authentication when unset.
+    pub username: Option<String>,
+    /// Secret holding the password (default `CALDAV_PASSWORD`).
+    pub password_env: Option<String>,
+}
+
+/// `[calendar]`: where CalendarSkill finds free slots and books consultations.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CalendarConfig {
+    pub provider: CalendarProvider,
+    pub google: GoogleCalendarConfig,
+    pub caldav: CalDavConfig,
+    /// Bookable hours each work day, local time (`"09:00-17:00"`).
+    pub work_hours: String,
+    /// Bookable weekdays, 1 = Monday .. 7 = Sunday.
+    pub work_days: Vec<u8>,
+    /// Local time as minutes east of UTC (no daylight saving adjustment).
+    pub utc_offset_minutes: i32,
+    /// Default length of a consultation.
+    pub slot_minutes: u32,
+    /// How far ahead free slots are searched.
+    pub lookahead_days: u32,
+    /// Free slots offered per call.
+    pub max_slots: usize,
+    /// Per-request timeout (seconds).
+    pub timeout_secs: u64,
+}
+
+impl Default for CalendarConfig {
+    fn default() -> Self {
+        Self {
+            provider: CalendarProvider::None,
+            google: GoogleCalendarConfig::default(),
+            caldav: CalDavConfig::default(),
+            work_hours: "09:00-17:00".to_string(),
+            work_days: vec![1, 2, 3, 4, 5],
+            utc_offset_minutes: 0,
+            slot_minutes: 30,
+            lookahead_days: 7,
+            max_slots: 5,
+            timeout_secs: 30,
+        }
+    }
+}
+
+impl CalendarConfig {
+    /// `work_hours` as minutes after local midnight, or None when malformed or empty.
+    pub fn work_minutes(&self) -> Option<(u32, u32)> {
+        let minutes = |hm: &str| {
+            let (h, m) = hm.trim().split_once(':')?;
+            let (h, m): (u32, u32) = (h.parse().ok()?, m.parse().ok()?);
+            (h <= 24 && m < 60).then_some(h * 60 + m).filter(|&t| t <= 24 * 60)
+        };
+        let (start, end) = self.work_hours.split_once('-')?;
+        let (start, end) = (minutes(start)?, minutes(end)?);
+        (start < end).then_some((start, end))
+    }
+
+    pub fn google_calendar_id(&self) -> &str {
+        self.google.calendar_id.as_deref().unwrap_or("primary")
+    }
+
+    pub fn google_client_secret_env(&self) -> &str {
+        self.google.client_secret_env.as_deref().unwrap_or("GOOGLE_CALENDAR_CLIENT_SECRET")
+    }
+
+    pub fn google_refresh_token_env(&self) -> &str {
+        self.google.refresh_token_env.as_deref().unwrap_or("GOOGLE_CALENDAR_REFRESH_TOKEN")
+    }
+
+    pub fn caldav_password_env(&self) -> &str {
+        self.caldav.password_env.as_deref().unwrap_or("CALDAV_PASSWORD")
+    }
+}
+
+impl LimitsConfig {
+    /// Body limit of the route `pattern` (as matched, e.g. `/v1/jobs/:job_id`).
+    pub fn body_limit(&self, pattern: &str) -> usize {
+        self.route_body_bytes.get(pattern).copied().unwrap_or(self.max_body_bytes)
+    }
+}
+
+impl TokenBudgetConfig {
+    /// Monthly token limit of `tenant_id`, if it has one.
+    pub fn monthly_limit(&self, tenant_id: &str) -> Option<u64> {
+        self.tenants.get(tenant_id).copied().or(self.monthly_tokens)
+    }
 }
 
 impl CoreConfig {
+    /// Time between Heartbeat ticks: `tick_rate_secs`, else env `PAGI_TICK_RATE_SECS`, else 5 s (at least 1 s).
+    pub fn tick_rate(&self) -> std::time::Duration {
+        let secs = self
+            .tick_rate_secs
+            .or_else(|| std::env::var("PAGI_TICK_RATE_SECS").ok().and_then(|s| s.parse().ok()))
+            .unwrap_or(5);
+        std::time::Duration::from_secs(secs.max(1))
+    }
+
+    /// Socket addresses of `bind_addresses` (`127.0.0.1` when empty), each with `port` unless
+    /// it names its own.
+    pub fn listen_addrs(&self) -> Result<Vec<std::net::SocketAddr>, String> {
+        if self.bind_addresses.is_empty() {
+            return Ok(vec![std::net::SocketAddr::from(([127, 0, 0, 1], self.port))]);
+        }
+        self.bind_addresses
+            .iter()
+            .map(|entry| {
+                let entry = entry.trim();
+                entry
+                    .parse::<std::net::SocketAddr>()
+                    .or_else(|_| entry.parse::<std::net::IpAddr>().map(|ip| std::net::SocketAddr::new(ip, self.port)))
+                    .map_err(|_| format!("bind_addresses: {:?} is not an IP address or address:port", entry))
+            })
+            .collect()
+    }
+
     /// Slot labels as `u8` -> label. Keys that are not 1–8 are skipped.
     pub fn slot_labels_map(&self) -> HashMap<u8, String> {
         self.slot_labels
@@ -482,28 +1537,11 @@ impl CoreConfig {
             .collect()
     }
 
-    /// Load config from file and environment. Precedence: env `PAGI_CONFIG` path > `config/gateway.toml` > defaults.
+    /// Loads and checks the config from the process's [`ConfigSources`](crate::ConfigSources):
+    /// defaults < config file (`PAGI_CONFIG`, default `config/gateway`) < environment file
+    /// (`PAGI_ENV`) < `PAGI__*` variables < command-line overrides. See [`Self::load_from`].
     pub fn load() -> Result<Self, config::ConfigError> {
-        let config_path = std::env::var("PAGI_CONFIG").unwrap_or_else(|_| "config/gateway".to_string());
-        let builder = config::Config::builder()
-            .set_default("app_name", "UAC Gateway")?
-            .set_default("port", 8001_i64)?
-            .set_default("storage_path", "./data")?
-            .set_default("llm_mode", "mock")?
-            .set_default("frontend_enabled", false)?;
-
-        let path = Path::new(&config_path);
-        let builder = if path.exists() {
-            builder.add_source(config::File::from(path))
-        } else {
-            builder
-        };
-
-        let built = builder
-            .add_source(config::Environment::with_prefix("PAGI").separator("__"))
-            .build()?;
-
-        built.try_deserialize()
+        Self::load_from(&crate::ConfigSources::current())
     }
 }
 
@@ -513,7 +1551,7 @@ impl CoreConfig {
 
 /// Key prefix in **KB_OIKOS** (Slot 2) where governed tasks are stored.
 /// Full key: `oikos/tasks/{task_id}`.
-pub const OIKOS_TASK_PREFIX: &str = "oikos/tasks/";
+pub const OIKOS_TASK_PREFIX: &str = crate::keys::TASK_PREFIX;
 
 /// Key in **KB_OIKOS** (Slot 2) where the governance summary is stored.
 pub const OIKOS_GOVERNANCE_SUMMARY_KEY: &str = "oikos/governance_summary";
@@ -642,12 +1680,8 @@ impl Default for GovernedTask {
 }
 
 impl GovernedTask {
-    /// Creates a new task with the given ID, title, and difficulty.
-    pub fn new(task_id: impl Into<String>, title: impl Into<String>, difficulty: TaskDifficulty) -> Self {
-        let now_ms = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .map(|d| d.as_millis() as i64)
-            .unwrap_or(0);
+    /// Creates a new task with the given ID, title, and difficulty, created at `now_ms`.
+    pub fn new(task_id: impl Into<String>, title: impl Into<String>, difficulty: TaskDifficulty, now_ms: i64) -> Self {
         Self {
             task_id: task_id.into(),
             title: title.into(),
@@ -897,14 +1931,18 @@ impl TaskGovernor {
         }
     }
 
-    /// Evaluates a batch of tasks and returns them sorted by effective priority (highest first).
-    /// Each task is updated with its governance action and effective priority.
-    pub fn evaluate_batch(&self, tasks: &[GovernedTask]) -> Vec<GovernedTask> {
-        let now_ms = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .map(|d| d.as_millis() as i64)
-            .unwrap_or(0);
+    /// Evaluates a batch of tasks at `now_ms` and returns them sorted by effective priority
+    /// (highest first). Each task is updated with its governance action and effective priority.
+    pub fn evaluate_batch(&self, tasks: &[GovernedTask], now_ms: i64) -> Vec<GovernedTask> {
+        let mut evaluated = self.rank(tasks);
+        for task in &mut evaluated {
+            task.last_evaluated_ms = now_ms;
+        }
+        evaluated
+    }
 
+    /// [`evaluate_batch`](Self::evaluate_batch) without touching `last_evaluated_ms`.
+    fn rank(&self, tasks: &[GovernedTask]) -> Vec<GovernedTask> {
         let mut evaluated: Vec<GovernedTask> = tasks
             .iter()
             .map(|task| {
@@ -912,7 +1950,6 @@ impl TaskGovernor {
                 let mut t = task.clone();
                 t.action = action;
                 t.effective_priority = effective_priority;
-                t.last_evaluated_ms = now_ms;
                 t
             })
             .collect();
@@ -931,7 +1968,7 @@ impl TaskGovernor {
 
     /// Generates a human-readable governance summary for the current state.
     pub fn governance_summary(&self, tasks: &[GovernedTask]) -> String {
-        let evaluated = self.evaluate_batch(tasks);
+        let evaluated = self.rank(tasks);
         let proceed_count = evaluated.iter().filter(|t| t.action.is_proceed()).count();
         let postpone_count = evaluated.iter().filter(|t| t.action.is_postpone()).count();
         let other_count = evaluated.len() - proceed_count - postpone_count;
@@ -0,0 +1,212 @@
+//! Key-value backends beneath [`KnowledgeStore`](crate::KnowledgeStore) and
+//! [`MemoryManager`](crate::MemoryManager).
+//!
+//! Both stores talk to named trees of ordered byte keys through [`KvBackend`] / [`KvTree`]:
+//! - [`SledBackend`] (default) — the embedded sled DB. One process at a time: sled holds an OS
+//!   lock on the directory (see `db_lock.rs`).
+//! - [`SqliteBackend`] — one SQLite file (`pagi.sqlite3`) in the same directory, in WAL mode, so
+//!   the gateway, daemon and Studio UI can open the same store at once.
+//!
+//! The backend is chosen with `storage_backend` in [`CoreConfig`](crate::CoreConfig)
+//! (`"sled"` or `"sqlite"`, env `PAGI__STORAGE_BACKEND`). The two keep separate files, so
+//! switching starts from an empty store; carry data over with a knowledge snapshot (export on the
+//! old backend, restore on the new one). Errors are reported as `sled::Error` whatever the
+//! backend, so the stores' signatures do not depend on it. Change events and index locks are
+//! per process: with SQLite, another process's writes are visible on read but not pushed to this
+//! process's subscribers. [`ReadOnlyBackend`] wraps either one for processes that only read.
+
+mod read_only;
+mod sled_backend;
+mod sqlite_backend;
+
+use serde::{Deserialize, Serialize};
+use sled::transaction::ConflictableTransactionError;
+use std::ops::Bound;
+use std::path::Path;
+
+pub use read_only::ReadOnlyBackend;
+pub use sled_backend::SledBackend;
+pub use sqlite_backend::{SqliteBackend, SQLITE_FILE_NAME};
+
+/// Result of a backend operation.
+pub type KvResult<T> = Result<T, sled::Error>;
+
+/// Result of an operation inside a backend transaction (see [`KvBackend::transaction`]).
+pub type KvTxResult<T> = Result<T, ConflictableTransactionError<sled::Error>>;
+
+/// Key/value pairs in key order (or reverse key order).
+pub type KvIter = Box<dyn Iterator<Item = KvResult<(Vec<u8>, Vec<u8>)>> + Send>;
+
+/// New value of a key from its current one (None: absent / remove), for
+/// [`KvTree::update_and_fetch`].
+pub type KvUpdateFn<'a> = dyn FnMut(Option<&[u8]>) -> Option<Vec<u8>> + 'a;
+
+/// Tree that is always present (sled's default tree); [`MemoryManager`](crate::MemoryManager)
+/// keeps its paths there.
+pub const DEFAULT_TREE: &str = "__sled__default";
+
+/// Which backend a store opens.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StorageBackend {
+    #[default]
+    Sled,
+    Sqlite,
+}
+
+impl StorageBackend {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            StorageBackend::Sled => "sled",
+            StorageBackend::Sqlite => "sqlite",
+        }
+    }
+
+    /// Opens (or creates) the store directory at `path` with this backend.
+    pub fn open(self, path: impl AsRef<Path>) -> KvResult<Box<dyn KvBackend>> {
+        Ok(match self {
+            StorageBackend::Sled => Box::new(SledBackend::open(path)?),
+            StorageBackend::Sqlite => Box::new(SqliteBackend::open(path)?),
+        })
+    }
+}
+
+impl std::fmt::Display for StorageBackend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Writes applied together by [`KvTree::apply_batch`].
+#[derive(Debug, Default, Clone)]
+pub struct KvBatch {
+    ops: Vec<(Vec<u8>, Option<Vec<u8>>)>,
+}
+
+impl KvBatch {
+    pub fn insert(&mut self, key: impl AsRef<[u8]>, value: impl AsRef<[u8]>) {
+        self.ops.push((key.as_ref().to_vec(), Some(value.as_ref().to_vec())));
+    }
+
+    pub fn remove(&mut self, key: impl AsRef<[u8]>) {
+        self.ops.push((key.as_ref().to_vec(), None));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ops.is_empty()
+    }
+
+    /// The writes in order; None is a removal.
+    pub fn into_ops(self) -> Vec<(Vec<u8>, Option<Vec<u8>>)> {
+        self.ops
+    }
+}
+
+/// A storage engine holding named trees.
+pub trait KvBackend: Send + Sync {
+    /// `"sled"` or `"sqlite"`.
+    fn name(&self) -> &'static str;
+
+    /// Whether writes are rejected (see [`ReadOnlyBackend`]).
+    fn is_read_only(&self) -> bool {
+        false
+    }
+
+    /// The tree called `name`, created empty when missing.
+    fn open_tree(&self, name: &str) -> KvResult<Box<dyn KvTree>>;
+
+    /// Names of the trees that exist.
+    fn tree_names(&self) -> KvResult<Vec<String>>;
+
+    /// A new id, unique and increasing for the life of the store.
+    fn generate_id(&self) -> KvResult<u64>;
+
+    /// Makes every write so far durable.
+    fn flush(&self) -> KvResult<()>;
+
+    /// Runs `f` over `trees` (by name, in that order) so that all of its writes land or none do.
+    /// `f` may run again when a concurrent writer conflicts; an `Abort` error is returned as is.
+    fn transaction(&self, trees: &[&str], f: &dyn Fn(&[&dyn KvTxTree]) -> KvTxResult<()>) -> KvResult<()>;
+}
+
+/// Ordered byte keys to byte values.
+pub trait KvTree: Send + Sync {
+    fn get(&self, key: &[u8]) -> KvResult<Option<Vec<u8>>>;
+
+    /// Writes `value` at `key`; returns the previous value.
+    fn insert(&self, key: &[u8], value: &[u8]) -> KvResult<Option<Vec<u8>>>;
+
+    /// Removes `key`; returns the previous value.
+    fn remove(&self, key: &[u8]) -> KvResult<Option<Vec<u8>>>;
+
+    /// Writes `new` (None: remove) only if `key` holds `expected` (None: absent); otherwise
+    /// returns the current value in `Err`.
+    fn compare_and_swap(
+        &self,
+        key: &[u8],
+        expected: Option<&[u8]>,
+        new: Option<&[u8]>,
+    ) -> KvResult<Result<(), Option<Vec<u8>>>>;
+
+    /// Entries between `lower` and `upper`, in key order or, with `reverse`, the opposite.
+    fn range(&self, lower: Bound<Vec<u8>>, upper: Bound<Vec<u8>>, reverse: bool) -> KvIter;
+
+    /// Applies every write of `batch` atomically.
+    fn apply_batch(&self, batch: KvBatch) -> KvResult<()>;
+
+    /// Removes every entry.
+    fn clear(&self) -> KvResult<()>;
+
+    fn len(&self) -> KvResult<usize>;
+
+    fn is_empty(&self) -> KvResult<bool> {
+        Ok(self.len()? == 0)
+    }
+
+    fn contains_key(&self, key: &[u8]) -> KvResult<bool> {
+        Ok(self.get(key)?.is_some())
+    }
+
+    /// Every entry in key order.
+    fn iter(&self) -> KvIter {
+        self.range(Bound::Unbounded, Bound::Unbounded, false)
+    }
+
+    /// Entries whose key starts with `prefix`, in key order.
+    fn scan_prefix(&self, prefix: &[u8]) -> KvIter {
+        let upper = prefix_upper_bound(prefix).map_or(Bound::Unbounded, Bound::Excluded);
+        self.range(Bound::Included(prefix.to_vec()), upper, false)
+    }
+
+    /// Read-modify-write of `key` through [`compare_and_swap`](Self::compare_and_swap), re-running
+    /// `f` when another writer got there first. Returns the value written.
+    fn update_and_fetch(&self, key: &[u8], f: &mut KvUpdateFn<'_>) -> KvResult<Option<Vec<u8>>> {
+        let mut current = self.get(key)?;
+        loop {
+            let new = f(current.as_deref());
+            match self.compare_and_swap(key, current.as_deref(), new.as_deref())? {
+                Ok(()) => return Ok(new),
+                Err(actual) => current = actual,
+            }
+        }
+    }
+}
+
+/// One tree inside a [`KvBackend::transaction`].
+pub trait KvTxTree {
+    fn get(&self, key: &[u8]) -> KvTxResult<Option<Vec<u8>>>;
+    fn insert(&self, key: &[u8], value: &[u8]) -> KvTxResult<Option<Vec<u8>>>;
+    fn remove(&self, key: &[u8]) -> KvTxResult<Option<Vec<u8>>>;
+}
+
+/// Smallest key above every key starting with `prefix` (None: no upper bound).
+pub(crate) fn prefix_upper_bound(prefix: &[u8]) -> Option<Vec<u8>> {
+    let mut upper = prefix.to_vec();
+    while let Some(last) = upper.pop() {
+        if last < u8::MAX {
+            upper.push(last + 1);
+            return Some(upper);
+        }
+    }
+    None
+}
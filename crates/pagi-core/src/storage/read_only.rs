@@ -0,0 +1,91 @@
+//! [`KvBackend`] wrapper that serves reads from the wrapped backend and rejects every write.
+
+use super::{KvBackend, KvBatch, KvIter, KvResult, KvTree, KvTxResult, KvTxTree};
+use std::ops::Bound;
+
+fn read_only_error() -> sled::Error {
+    sled::Error::Unsupported("the store is open read-only".to_string())
+}
+
+/// Read-only view of a backend (see `KnowledgeStore::open_read_only`).
+pub struct ReadOnlyBackend {
+    inner: Box<dyn KvBackend>,
+}
+
+impl ReadOnlyBackend {
+    pub fn new(inner: Box<dyn KvBackend>) -> Self {
+        Self { inner }
+    }
+}
+
+impl KvBackend for ReadOnlyBackend {
+    fn name(&self) -> &'static str {
+        self.inner.name()
+    }
+
+    fn is_read_only(&self) -> bool {
+        true
+    }
+
+    fn open_tree(&self, name: &str) -> KvResult<Box<dyn KvTree>> {
+        Ok(Box::new(ReadOnlyTree(self.inner.open_tree(name)?)))
+    }
+
+    fn tree_names(&self) -> KvResult<Vec<String>> {
+        self.inner.tree_names()
+    }
+
+    fn generate_id(&self) -> KvResult<u64> {
+        Err(read_only_error())
+    }
+
+    /// Nothing is written, so there is nothing to flush.
+    fn flush(&self) -> KvResult<()> {
+        Ok(())
+    }
+
+    fn transaction(&self, _trees: &[&str], _f: &dyn Fn(&[&dyn KvTxTree]) -> KvTxResult<()>) -> KvResult<()> {
+        Err(read_only_error())
+    }
+}
+
+struct ReadOnlyTree(Box<dyn KvTree>);
+
+impl KvTree for ReadOnlyTree {
+    fn get(&self, key: &[u8]) -> KvResult<Option<Vec<u8>>> {
+        self.0.get(key)
+    }
+
+    fn insert(&self, _key: &[u8], _value: &[u8]) -> KvResult<Option<Vec<u8>>> {
+        Err(read_only_error())
+    }
+
+    fn remove(&self, _key: &[u8]) -> KvResult<Option<Vec<u8>>> {
+        Err(read_only_error())
+    }
+
+    fn compare_and_swap(
+        &self,
+        _key: &[u8],
+        _expected: Option<&[u8]>,
+        _new: Option<&[u8]>,
+    ) -> KvResult<Result<(), Option<Vec<u8>>>> {
+        Err(read_only_error())
+    }
+
+    fn range(&self, lower: Bound<Vec<u8>>, upper: Bound<Vec<u8>>, reverse: bool) -> KvIter {
+        self.0.range(lower, upper, reverse)
+    }
+
+    fn apply_batch(&self, _batch: KvBatch) -> KvResult<()> {
+        Err(read_only_error())
+    }
+
+    fn clear(&self) -> KvResult<()> {
+        Err(read_only_error())
+    }
+
+    fn len(&self) -> KvResult<usize> {
+        self.0.len()
+    }
+}
@@ -0,0 +1,140 @@
+//! [`KvBackend`] over sled: one sled tree per named tree.
+
+use super::{KvBackend, KvBatch, KvIter, KvResult, KvTree, KvTxResult, KvTxTree};
+use sled::transaction::{TransactionError, Transactional, TransactionalTree};
+use std::ops::Bound;
+use std::path::Path;
+
+/// The embedded sled DB (default backend). Clones share the DB.
+#[derive(Clone)]
+pub struct SledBackend {
+    db: sled::Db,
+}
+
+impl SledBackend {
+    /// Opens the sled directory at `path`, recording this process as its owner (see
+    /// [`open_db`](crate::open_db)).
+    pub fn open(path: impl AsRef<Path>) -> KvResult<Self> {
+        Ok(Self { db: crate::db_lock::open_db(path)? })
+    }
+
+    /// An in-memory DB removed when dropped.
+    pub fn temporary() -> KvResult<Self> {
+        Ok(Self { db: sled::Config::new().temporary(true).open()? })
+    }
+}
+
+impl KvBackend for SledBackend {
+    fn name(&self) -> &'static str {
+        "sled"
+    }
+
+    fn open_tree(&self, name: &str) -> KvResult<Box<dyn KvTree>> {
+        Ok(Box::new(SledTree(self.db.open_tree(name)?)))
+    }
+
+    fn tree_names(&self) -> KvResult<Vec<String>> {
+        Ok(self
+            .db
+            .tree_names()
+            .iter()
+            .map(|name| String::from_utf8_lossy(name).into_owned())
+            .collect())
+    }
+
+    fn generate_id(&self) -> KvResult<u64> {
+        self.db.generate_id()
+    }
+
+    fn flush(&self) -> KvResult<()> {
+        self.db.flush()?;
+        Ok(())
+    }
+
+    fn transaction(&self, trees: &[&str], f: &dyn Fn(&[&dyn KvTxTree]) -> KvTxResult<()>) -> KvResult<()> {
+        let trees = trees.iter().map(|name| self.db.open_tree(name)).collect::<Result<Vec<_>, _>>()?;
+        trees
+            .as_slice()
+            .transaction(|views| {
+                let views: Vec<SledTxTree> = views.iter().map(SledTxTree).collect();
+                let views: Vec<&dyn KvTxTree> = views.iter().map(|v| v as &dyn KvTxTree).collect();
+                f(&views)
+            })
+            .map_err(|e| match e {
+                TransactionError::Abort(e) | TransactionError::Storage(e) => e,
+            })
+    }
+}
+
+struct SledTree(sled::Tree);
+
+impl KvTree for SledTree {
+    fn get(&self, key: &[u8]) -> KvResult<Option<Vec<u8>>> {
+        Ok(self.0.get(key)?.map(|v| v.to_vec()))
+    }
+
+    fn insert(&self, key: &[u8], value: &[u8]) -> KvResult<Option<Vec<u8>>> {
+        Ok(self.0.insert(key, value)?.map(|v| v.to_vec()))
+    }
+
+    fn remove(&self, key: &[u8]) -> KvResult<Option<Vec<u8>>> {
+        Ok(self.0.remove(key)?.map(|v| v.to_vec()))
+    }
+
+    fn compare_and_swap(
+        &self,
+        key: &[u8],
+        expected: Option<&[u8]>,
+        new: Option<&[u8]>,
+    ) -> KvResult<Result<(), Option<Vec<u8>>>> {
+        Ok(self
+            .0
+            .compare_and_swap(key, expected, new)?
+            .map_err(|e| e.current.map(|v| v.to_vec())))
+    }
+
+    fn range(&self, lower: Bound<Vec<u8>>, upper: Bound<Vec<u8>>, reverse: bool) -> KvIter {
+        let range = self.0.range::<Vec<u8>, _>((lower, upper));
+        let pairs = |item: sled::Result<(sled::IVec, sled::IVec)>| item.map(|(k, v)| (k.to_vec(), v.to_vec()));
+        if reverse {
+            Box::new(range.rev().map(pairs))
+        } else {
+            Box::new(range.map(pairs))
+        }
+    }
+
+    fn apply_batch(&self, batch: KvBatch) -> KvResult<()> {
+        let mut sled_batch = sled::Batch::default();
+        for (key, value) in batch.into_ops() {
+            match value {
+                Some(value) => sled_batch.insert(key, value),
+                None => sled_batch.remove(key),
+            }
+        }
+        self.0.apply_batch(sled_batch)
+    }
+
+    fn clear(&self) -> KvResult<()> {
+        self.0.clear()
+    }
+
+    fn len(&self) -> KvResult<usize> {
+        Ok(self.0.len())
+    }
+}
+
+struct SledTxTree<'a>(&'a TransactionalTree);
+
+impl KvTxTree for SledTxTree<'_> {
+    fn get(&self, key: &[u8]) -> KvTxResult<Option<Vec<u8>>> {
+        Ok(self.0.get(key)?.map(|v| v.to_vec()))
+    }
+
+    fn insert(&self, key: &[u8], value: &[u8]) -> KvTxResult<Option<Vec<u8>>> {
+        Ok(self.0.insert(key, value)?.map(|v| v.to_vec()))
+    }
+
+    fn remove(&self, key: &[u8]) -> KvTxResult<Option<Vec<u8>>> {
+        Ok(self.0.remove(key)?.map(|v| v.to_vec()))
+    }
+}
@@ -0,0 +1,370 @@
+//! [`KvBackend`] over SQLite: every tree lives in one `kv (tree, key, value)` table, keyed by
+//! `(tree, key)`. SQLite compares BLOB keys bytewise, so ranges come back in the same order as
+//! sled's. Writes run in `BEGIN IMMEDIATE` transactions and the file is in WAL mode with a busy
+//! timeout, so several processes can read and write the same store.
+
+use super::{KvBackend, KvBatch, KvIter, KvResult, KvTree, KvTxResult, KvTxTree};
+use rusqlite::types::Value;
+use rusqlite::{params, params_from_iter, Connection, OpenFlags, OptionalExtension};
+use sled::transaction::ConflictableTransactionError;
+use std::collections::{HashSet, VecDeque};
+use std::ops::Bound;
+use std::path::Path;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+
+/// SQLite file inside the store directory.
+pub const SQLITE_FILE_NAME: &str = "pagi.sqlite3";
+
+/// How long a write waits for another process's write to finish.
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+/// Rows fetched per query while iterating a range.
+const RANGE_PAGE: usize = 256;
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS kv_trees (name TEXT PRIMARY KEY NOT NULL);
+    CREATE TABLE IF NOT EXISTS kv (
+        tree TEXT NOT NULL,
+        key BLOB NOT NULL,
+        value BLOB NOT NULL,
+        PRIMARY KEY (tree, key)
+    ) WITHOUT ROWID;
+    CREATE TABLE IF NOT EXISTS kv_meta (name TEXT PRIMARY KEY NOT NULL, value INTEGER NOT NULL);
+";
+
+fn sqlite_err(e: rusqlite::Error) -> sled::Error {
+    sled::Error::Io(std::io::Error::other(e))
+}
+
+fn lock(conn: &Mutex<Connection>) -> MutexGuard<'_, Connection> {
+    conn.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Runs `f` in a write transaction; rolls back when it (or the commit) fails.
+fn write<T>(conn: &Connection, f: impl FnOnce(&Connection) -> rusqlite::Result<T>) -> KvResult<T> {
+    conn.execute_batch("BEGIN IMMEDIATE").map_err(sqlite_err)?;
+    match f(conn).and_then(|v| conn.execute_batch("COMMIT").map(|()| v)) {
+        Ok(v) => Ok(v),
+        Err(e) => {
+            let _ = conn.execute_batch("ROLLBACK");
+            Err(sqlite_err(e))
+        }
+    }
+}
+
+fn get_in(conn: &Connection, tree: &str, key: &[u8]) -> rusqlite::Result<Option<Vec<u8>>> {
+    conn.prepare_cached("SELECT value FROM kv WHERE tree = ?1 AND key = ?2")?
+        .query_row(params![tree, key], |row| row.get(0))
+        .optional()
+}
+
+fn put_in(conn: &Connection, tree: &str, key: &[u8], value: &[u8]) -> rusqlite::Result<()> {
+    conn.prepare_cached(
+        "INSERT INTO kv (tree, key, value) VALUES (?1, ?2, ?3)
+         ON CONFLICT (tree, key) DO UPDATE SET value = excluded.value",
+    )?
+    .execute(params![tree, key, value])?;
+    Ok(())
+}
+
+fn delete_in(conn: &Connection, tree: &str, key: &[u8]) -> rusqlite::Result<()> {
+    conn.prepare_cached("DELETE FROM kv WHERE tree = ?1 AND key = ?2")?
+        .execute(params![tree, key])?;
+    Ok(())
+}
+
+/// A SQLite-file store.
+pub struct SqliteBackend {
+    conn: Arc<Mutex<Connection>>,
+    /// Trees already recorded in `kv_trees` by this process.
+    known_trees: Mutex<HashSet<String>>,
+    /// Opened with [`open_read_only`](Self::open_read_only): trees are not registered.
+    read_only: bool,
+}
+
+impl SqliteBackend {
+    /// Opens (or creates) `{path}/pagi.sqlite3`.
+    pub fn open(path: impl AsRef<Path>) -> KvResult<Self> {
+        let path = path.as_ref();
+        std::fs::create_dir_all(path)?;
+        let conn = Connection::open(path.join(SQLITE_FILE_NAME)).map_err(sqlite_err)?;
+        conn.busy_timeout(BUSY_TIMEOUT).map_err(sqlite_err)?;
+        conn.query_row("PRAGMA journal_mode = WAL", [], |_| Ok(())).map_err(sqlite_err)?;
+        conn.execute_batch("PRAGMA synchronous = NORMAL;").map_err(sqlite_err)?;
+        Self::with_connection(conn)
+    }
+
+    /// Opens an existing `{path}/pagi.sqlite3` with a read-only connection. Commits of other
+    /// processes (e.g. a running gateway) are visible; writes fail.
+    pub fn open_read_only(path: impl AsRef<Path>) -> KvResult<Self> {
+        let flags = OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_URI | OpenFlags::SQLITE_OPEN_NO_MUTEX;
+        let conn = Connection::open_with_flags(path.as_ref().join(SQLITE_FILE_NAME), flags).map_err(sqlite_err)?;
+        conn.busy_timeout(BUSY_TIMEOUT).map_err(sqlite_err)?;
+        Ok(Self { conn: Arc::new(Mutex::new(conn)), known_trees: Default::default(), read_only: true })
+    }
+
+    /// An in-memory database removed when dropped.
+    pub fn temporary() -> KvResult<Self> {
+        Self::with_connection(Connection::open_in_memory().map_err(sqlite_err)?)
+    }
+
+    fn with_connection(conn: Connection) -> KvResult<Self> {
+        conn.execute_batch(SCHEMA).map_err(sqlite_err)?;
+        Ok(Self { conn: Arc::new(Mutex::new(conn)), known_trees: Default::default(), read_only: false })
+    }
+
+    fn register_tree(&self, name: &str) -> KvResult<()> {
+        if self.read_only {
+            return Ok(());
+        }
+        let mut known = self.known_trees.lock().unwrap_or_else(|e| e.into_inner());
+        if !known.contains(name) {
+            lock(&self.conn)
+                .execute("INSERT OR IGNORE INTO kv_trees (name) VALUES (?1)", [name])
+                .map_err(sqlite_err)?;
+            known.insert(name.to_string());
+        }
+        Ok(())
+    }
+}
+
+impl KvBackend for SqliteBackend {
+    fn name(&self) -> &'static str {
+        "sqlite"
+    }
+
+    fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    fn open_tree(&self, name: &str) -> KvResult<Box<dyn KvTree>> {
+        self.register_tree(name)?;
+        Ok(Box::new(SqliteTree { conn: Arc::clone(&self.conn), name: name.to_string() }))
+    }
+
+    fn tree_names(&self) -> KvResult<Vec<String>> {
+        let conn = lock(&self.conn);
+        let mut stmt = conn.prepare("SELECT name FROM kv_trees ORDER BY name").map_err(sqlite_err)?;
+        let names = stmt
+            .query_map([], |row| row.get(0))
+            .and_then(|rows| rows.collect::<rusqlite::Result<Vec<String>>>())
+            .map_err(sqlite_err)?;
+        Ok(names)
+    }
+
+    fn generate_id(&self) -> KvResult<u64> {
+        let id: i64 = write(&lock(&self.conn), |conn| {
+            conn.query_row(
+                "INSERT INTO kv_meta (name, value) VALUES ('next_id', 0)
+                 ON CONFLICT (name) DO UPDATE SET value = value + 1 RETURNING value",
+                [],
+                |row| row.get(0),
+            )
+        })?;
+        Ok(id as u64)
+    }
+
+    fn flush(&self) -> KvResult<()> {
+        lock(&self.conn)
+            .query_row("PRAGMA wal_checkpoint(PASSIVE)", [], |_| Ok(()))
+            .map_err(sqlite_err)
+    }
+
+    fn transaction(&self, trees: &[&str], f: &dyn Fn(&[&dyn KvTxTree]) -> KvTxResult<()>) -> KvResult<()> {
+        for name in trees {
+            self.register_tree(name)?;
+        }
+        let conn = lock(&self.conn);
+        loop {
+            conn.execute_batch("BEGIN IMMEDIATE").map_err(sqlite_err)?;
+            let views: Vec<SqliteTxTree> = trees.iter().map(|name| SqliteTxTree { conn: &conn, name }).collect();
+            let views: Vec<&dyn KvTxTree> = views.iter().map(|v| v as &dyn KvTxTree).collect();
+            let outcome = f(&views);
+            let err = match outcome {
+                Ok(()) => match conn.execute_batch("COMMIT") {
+                    Ok(()) => return Ok(()),
+                    Err(e) => sqlite_err(e),
+                },
+                Err(ConflictableTransactionError::Conflict) => {
+                    let _ = conn.execute_batch("ROLLBACK");
+                    continue;
+                }
+                Err(ConflictableTransactionError::Abort(e)) | Err(ConflictableTransactionError::Storage(e)) => e,
+            };
+            let _ = conn.execute_batch("ROLLBACK");
+            return Err(err);
+        }
+    }
+}
+
+struct SqliteTree {
+    conn: Arc<Mutex<Connection>>,
+    name: String,
+}
+
+impl KvTree for SqliteTree {
+    fn get(&self, key: &[u8]) -> KvResult<Option<Vec<u8>>> {
+        get_in(&lock(&self.conn), &self.name, key).map_err(sqlite_err)
+    }
+
+    fn insert(&self, key: &[u8], value: &[u8]) -> KvResult<Option<Vec<u8>>> {
+        write(&lock(&self.conn), |conn| {
+            let prev = get_in(conn, &self.name, key)?;
+            put_in(conn, &self.name, key, value)?;
+            Ok(prev)
+        })
+    }
+
+    fn remove(&self, key: &[u8]) -> KvResult<Option<Vec<u8>>> {
+        write(&lock(&self.conn), |conn| {
+            let prev = get_in(conn, &self.name, key)?;
+            if prev.is_some() {
+                delete_in(conn, &self.name, key)?;
+            }
+            Ok(prev)
+        })
+    }
+
+    fn compare_and_swap(
+        &self,
+        key: &[u8],
+        expected: Option<&[u8]>,
+        new: Option<&[u8]>,
+    ) -> KvResult<Result<(), Option<Vec<u8>>>> {
+        write(&lock(&self.conn), |conn| {
+            let current = get_in(conn, &self.name, key)?;
+            if current.as_deref() != expected {
+                return Ok(Err(current));
+            }
+            match new {
+                Some(value) => put_in(conn, &self.name, key, value)?,
+                None => delete_in(conn, &self.name, key)?,
+            }
+            Ok(Ok(()))
+        })
+    }
+
+    fn range(&self, lower: Bound<Vec<u8>>, upper: Bound<Vec<u8>>, reverse: bool) -> KvIter {
+        Box::new(SqliteRange {
+            conn: Arc::clone(&self.conn),
+            tree: self.name.clone(),
+            lower,
+            upper,
+            reverse,
+            buffer: VecDeque::new(),
+            done: false,
+        })
+    }
+
+    fn apply_batch(&self, batch: KvBatch) -> KvResult<()> {
+        write(&lock(&self.conn), |conn| {
+            for (key, value) in batch.into_ops() {
+                match value {
+                    Some(value) => put_in(conn, &self.name, &key, &value)?,
+                    None => delete_in(conn, &self.name, &key)?,
+                }
+            }
+            Ok(())
+        })
+    }
+
+    fn clear(&self) -> KvResult<()> {
+        write(&lock(&self.conn), |conn| {
+            conn.execute("DELETE FROM kv WHERE tree = ?1", [&self.name]).map(|_| ())
+        })
+    }
+
+    fn len(&self) -> KvResult<usize> {
+        let count: i64 = lock(&self.conn)
+            .query_row("SELECT COUNT(*) FROM kv WHERE tree = ?1", [&self.name], |row| row.get(0))
+            .map_err(sqlite_err)?;
+        Ok(count as usize)
+    }
+}
+
+/// Range iterator that reads [`RANGE_PAGE`] rows per query, holding the connection only while
+/// it does.
+struct SqliteRange {
+    conn: Arc<Mutex<Connection>>,
+    tree: String,
+    lower: Bound<Vec<u8>>,
+    upper: Bound<Vec<u8>>,
+    reverse: bool,
+    buffer: VecDeque<(Vec<u8>, Vec<u8>)>,
+    done: bool,
+}
+
+impl SqliteRange {
+    fn fetch(&mut self) -> rusqlite::Result<()> {
+        let mut sql = String::from("SELECT key, value FROM kv WHERE tree = ?1");
+        let mut values = vec![Value::Text(self.tree.clone())];
+        for (bound, included, excluded) in [(&self.lower, ">=", ">"), (&self.upper, "<=", "<")] {
+            let (op, key) = match bound {
+                Bound::Included(key) => (included, key),
+                Bound::Excluded(key) => (excluded, key),
+                Bound::Unbounded => continue,
+            };
+            values.push(Value::Blob(key.clone()));
+            sql.push_str(&format!(" AND key {} ?{}", op, values.len()));
+        }
+        sql.push_str(if self.reverse { " ORDER BY key DESC" } else { " ORDER BY key ASC" });
+        sql.push_str(&format!(" LIMIT {}", RANGE_PAGE));
+
+        let conn = lock(&self.conn);
+        let mut stmt = conn.prepare_cached(&sql)?;
+        let rows = stmt
+            .query_map(params_from_iter(values.iter()), |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<rusqlite::Result<Vec<(Vec<u8>, Vec<u8>)>>>()?;
+        self.done = rows.len() < RANGE_PAGE;
+        // Continue after the last key read.
+        if let Some((last, _)) = rows.last() {
+            let next = Bound::Excluded(last.clone());
+            if self.reverse {
+                self.upper = next;
+            } else {
+                self.lower = next;
+            }
+        }
+        self.buffer.extend(rows);
+        Ok(())
+    }
+}
+
+impl Iterator for SqliteRange {
+    type Item = KvResult<(Vec<u8>, Vec<u8>)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.buffer.is_empty() && !self.done {
+            if let Err(e) = self.fetch() {
+                self.done = true;
+                return Some(Err(sqlite_err(e)));
+            }
+        }
+        self.buffer.pop_front().map(Ok)
+    }
+}
+
+struct SqliteTxTree<'a> {
+    conn: &'a Connection,
+    name: &'a str,
+}
+
+impl KvTxTree for SqliteTxTree<'_> {
+    fn get(&self, key: &[u8]) -> KvTxResult<Option<Vec<u8>>> {
+        get_in(self.conn, self.name, key).map_err(|e| ConflictableTransactionError::Storage(sqlite_err(e)))
+    }
+
+    fn insert(&self, key: &[u8], value: &[u8]) -> KvTxResult<Option<Vec<u8>>> {
+        let prev = self.get(key)?;
+        put_in(self.conn, self.name, key, value).map_err(|e| ConflictableTransactionError::Storage(sqlite_err(e)))?;
+        Ok(prev)
+    }
+
+    fn remove(&self, key: &[u8]) -> KvTxResult<Option<Vec<u8>>> {
+        let prev = self.get(key)?;
+        if prev.is_some() {
+            delete_in(self.conn, self.name, key).map_err(|e| ConflictableTransactionError::Storage(sqlite_err(e)))?;
+        }
+        Ok(prev)
+    }
+}
@@ -0,0 +1,95 @@
+//! Agent registry in KB_PNEUMA: create, list, archive, health, schedules and the inbox migration.
+
+use pagi_core::{ActiveHours, AgentRecord, AgentScheduleUpdate, AgentStatus, AgentTickTracker, KnowledgeStore};
+
+#[test]
+fn agents_are_created_once_and_archived_out_of_the_active_set() {
+    let store = KnowledgeStore::open_temporary().unwrap();
+    let sales = AgentRecord::new("sales", Some("Sales desk"), vec!["ModelRouter".to_string()]);
+    let created = store.create_agent(&sales).unwrap().unwrap();
+    assert!(created.created_at_ms > 0);
+    assert_eq!(created.display_name, "Sales desk");
+    assert_eq!(store.create_agent(&sales).unwrap(), None);
+    store.create_agent(&AgentRecord::new("support", None, Vec::new())).unwrap();
+    assert!(store.create_agent(&AgentRecord::new("a/b", None, Vec::new())).is_err());
+    assert_eq!(store.active_agent_ids().unwrap(), ["sales", "support"]);
+
+    let archived = store.archive_agent("sales").unwrap().unwrap();
+    assert_eq!(archived.status, AgentStatus::Archived);
+    assert!(archived.archived_at_ms.is_some());
+    assert_eq!(store.active_agent_ids().unwrap(), ["support"]);
+    assert_eq!(store.list_agents().unwrap().len(), 2);
+    assert_eq!(store.archive_agent("missing").unwrap(), None);
+    // An archived id stays taken.
+    assert_eq!(store.create_agent(&sales).unwrap(), None);
+}
+
+#[test]
+fn inbox_agents_are_registered_and_report_health() {
+    let store = KnowledgeStore::open_temporary().unwrap();
+    store.push_agent_message("ops", "sales", &serde_json::json!({ "text": "lead" })).unwrap();
+    store.push_agent_message("sales", "ops", &serde_json::json!({ "text": "thanks" })).unwrap();
+    store.append_chronos_event("sales", &store.event("Chronos", "replied")).unwrap();
+
+    assert_eq!(store.register_inbox_agents().unwrap(), 2);
+    assert_eq!(store.register_inbox_agents().unwrap(), 0);
+    assert_eq!(store.active_agent_ids().unwrap(), ["ops", "sales"]);
+
+    let health = store.agent_health("sales").unwrap();
+    assert_eq!(health.pending_messages, 1);
+    assert!(health.last_activity_ms.is_some());
+    assert_eq!(store.agent_health("ops").unwrap().last_activity_ms, None);
+}
+
+#[test]
+fn agent_schedules_gate_heartbeat_ticks() {
+    const HOUR_MS: i64 = 3_600_000;
+    let office = ActiveHours { start: "09:00".to_string(), end: "18:00".to_string(), utc_offset_minutes: 60 };
+    assert!(office.contains(8 * HOUR_MS)); // 09:00 at UTC+1
+    assert!(!office.contains(17 * HOUR_MS)); // 18:00 at UTC+1
+    let night = ActiveHours { start: "22:00".to_string(), end: "06:00".to_string(), utc_offset_minutes: 0 };
+    assert!(night.contains(23 * HOUR_MS) && night.contains(24 * HOUR_MS + 5 * HOUR_MS));
+    assert!(!night.contains(12 * HOUR_MS));
+
+    let mut sage = AgentRecord::new("sage", None, Vec::new());
+    sage.tick_interval_secs = Some(60);
+    sage.active_hours = Some(office);
+    let idle = AgentRecord::new("idle", None, Vec::new());
+    let mut ticks = AgentTickTracker::default();
+    let agents = [sage.clone(), idle];
+    let nine = 8 * HOUR_MS;
+    assert_eq!(ticks.due(&agents, nine), ["sage", "idle"]);
+    assert_eq!(ticks.due(&agents, nine + 30_000), ["idle"]);
+    assert_eq!(ticks.due(&agents, nine + 60_000), ["sage", "idle"]);
+    assert_eq!(ticks.due(&agents, 20 * HOUR_MS), ["idle"]);
+
+    sage.paused = true;
+    assert!(!sage.is_due(nine + 10 * 60_000, None));
+}
+
+#[test]
+fn agent_schedules_are_updated_and_validated() {
+    let store = KnowledgeStore::open_temporary().unwrap();
+    store.create_agent(&AgentRecord::new("noisy", None, Vec::new())).unwrap();
+    let pause: AgentScheduleUpdate =
+        serde_json::from_value(serde_json::json!({ "paused": true, "tick_interval_secs": 300 })).unwrap();
+    let agent = store.update_agent_schedule("noisy", &pause).unwrap().unwrap();
+    assert!(agent.paused && agent.is_active());
+    assert_eq!(agent.tick_interval_secs, Some(300));
+    assert!(store.due_agent_ids(&mut AgentTickTracker::default()).unwrap().is_empty());
+
+    // Absent fields stay; `null` clears.
+    let resume: AgentScheduleUpdate =
+        serde_json::from_value(serde_json::json!({ "paused": false, "tick_interval_secs": null })).unwrap();
+    let agent = store.update_agent_schedule("noisy", &resume).unwrap().unwrap();
+    assert!(!agent.paused);
+    assert_eq!(agent.tick_interval_secs, None);
+    assert_eq!(store.get_agent("noisy").unwrap(), agent);
+    assert_eq!(store.due_agent_ids(&mut AgentTickTracker::default()).unwrap(), ["noisy"]);
+
+    let bad: AgentScheduleUpdate =
+        serde_json::from_value(serde_json::json!({ "active_hours": { "start": "9am", "end": "18:00" } })).unwrap();
+    assert!(bad.validate().is_err());
+    assert!(store.update_agent_schedule("noisy", &bad).is_err());
+    assert_eq!(store.update_agent_schedule("missing", &resume).unwrap(), None);
+}
@@ -0,0 +1,78 @@
+//! Gateway credentials: API keys in KB_ETHOS (issuing, hashing, roles, revocation) and the
+//! mapping of OIDC token claims to a role and a tenant.
+
+use pagi_core::{hash_api_key, KnowledgeStore, OidcConfig, Permission, Role, API_KEY_SECRET_PREFIX};
+use serde_json::json;
+
+#[test]
+fn keys_authenticate_by_hash_until_revoked() {
+    let store = KnowledgeStore::open_temporary().unwrap();
+    assert!(!store.has_api_keys());
+
+    let (admin, admin_secret) = store.create_api_key("root", Role::Admin, None, None).unwrap();
+    let (tenant, tenant_secret) = store.create_api_key("acme-backend", Role::Tenant, Some("acme"), Some("root")).unwrap();
+    assert!(store.has_api_keys());
+    assert!(admin_secret.starts_with(API_KEY_SECRET_PREFIX));
+    assert!(admin_secret.starts_with(&admin.key_prefix) && admin.key_prefix.len() < admin_secret.len());
+    assert_eq!(tenant.created_by.as_deref(), Some("root"));
+
+    // Only the hash is stored.
+    assert_eq!(store.authenticate_api_key(&admin_secret).unwrap().key_id, admin.key_id);
+    assert_eq!(store.authenticate_api_key(&format!(" {} ", tenant_secret)).unwrap().name, "acme-backend");
+    assert!(store.authenticate_api_key("pagi_unknown").is_none());
+    assert_ne!(hash_api_key(&admin_secret), admin_secret);
+    let listed = store.list_api_keys().unwrap();
+    assert_eq!(listed.iter().map(|k| k.name.as_str()).collect::<Vec<_>>(), vec!["acme-backend", "root"]);
+
+    let revoked = store.revoke_api_key(&tenant.key_id).unwrap().unwrap();
+    assert!(!revoked.is_active());
+    assert!(store.authenticate_api_key(&tenant_secret).is_none());
+    assert!(!store.get_api_key(&tenant.key_id).unwrap().is_active());
+    assert!(store.revoke_api_key("missing").unwrap().is_none());
+
+    // Binding rules: tenant keys need a tenant, other roles take none.
+    assert!(store.create_api_key("x", Role::Tenant, None, None).is_err());
+    assert!(store.create_api_key("x", Role::Operator, Some("acme"), None).is_err());
+}
+
+#[test]
+fn roles_grant_their_permissions_and_tenant_keys_their_tenant() {
+    assert!(Role::Admin.allows(Permission::KeyAdmin) && Role::Admin.allows(Permission::VaultRead));
+    assert!(Role::Operator.allows(Permission::KbAdmin) && !Role::Operator.allows(Permission::VaultRead));
+    assert!(Role::Readonly.allows(Permission::Read) && !Role::Readonly.allows(Permission::Execute));
+    assert!(Role::Tenant.allows(Permission::Execute) && !Role::Tenant.allows(Permission::KbAdmin));
+
+    let store = KnowledgeStore::open_temporary().unwrap();
+    let (tenant, _) = store.create_api_key("acme", Role::Tenant, Some("acme"), None).unwrap();
+    assert!(tenant.allows(Permission::Execute, Some("acme")));
+    assert!(!tenant.allows(Permission::Execute, Some("globex")));
+    assert!(!tenant.allows(Permission::Read, None));
+    let (operator, _) = store.create_api_key("ops", Role::Operator, None, None).unwrap();
+    assert!(operator.allows(Permission::Read, Some("globex")) && operator.allows(Permission::KbAdmin, None));
+}
+
+#[test]
+fn oidc_claims_map_to_the_most_permissive_role_and_a_tenant() {
+    let config = OidcConfig {
+        issuer: Some("https://sso.example.com".into()),
+        audiences: vec!["pagi".into()],
+        role_map: [("pagi-admins".to_string(), Role::Admin)].into_iter().collect(),
+        ..OidcConfig::default()
+    };
+    assert!(config.is_enabled() && !OidcConfig::default().is_enabled());
+
+    let principal = config
+        .principal(&json!({ "sub": "u1", "email": "ops@example.com", "roles": ["readonly", "Operator", "unknown"] }))
+        .unwrap();
+    assert_eq!((principal.name.as_str(), principal.role, principal.tenant_id), ("ops@example.com", Role::Operator, None));
+    // Mapped values, space-separated strings and a tenant claim.
+    let principal = config.principal(&json!({ "sub": "u2", "roles": "tenant pagi-admins", "tenant_id": "acme" })).unwrap();
+    assert_eq!((principal.name.as_str(), principal.role), ("u2", Role::Admin));
+    assert_eq!(principal.tenant_id.as_deref(), Some("acme"));
+
+    assert_eq!(config.principal(&json!({ "sub": "u3", "roles": ["viewer"] })).unwrap_err(), "token grants no role (roles claim)");
+    assert!(config.principal(&json!({ "sub": "u4", "roles": ["tenant"] })).unwrap_err().contains("tenant_id claim"));
+    assert!(config.principal(&json!({ "roles": ["admin"] })).is_err());
+    let fallback = OidcConfig { default_role: Some(Role::Readonly), ..config };
+    assert_eq!(fallback.principal(&json!({ "sub": "u5" })).unwrap().role, Role::Readonly);
+}
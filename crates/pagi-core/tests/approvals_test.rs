@@ -0,0 +1,132 @@
+//! Human-in-the-loop approvals: pending records, decisions, and one-shot grants at the Ethos gate.
+
+use pagi_core::{
+    AgentSkill, ApprovalCheckpoint, ApprovalRecord, ApprovalStatus, EthosBlocked, Goal, KnowledgeStore, Orchestrator,
+    PolicyRule, PolicyRules, RuleAction, SkillRegistry, TenantContext,
+};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+
+struct Counted(Arc<AtomicU32>);
+
+#[async_trait::async_trait]
+impl AgentSkill for Counted {
+    fn name(&self) -> &str {
+        "PaymentSend"
+    }
+
+    async fn execute(
+        &self,
+        _ctx: &TenantContext,
+        _payload: Option<serde_json::Value>,
+    ) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>> {
+        self.0.fetch_add(1, Ordering::SeqCst);
+        Ok(serde_json::json!({ "status": "ok" }))
+    }
+}
+
+fn ctx(correlation_id: &str) -> TenantContext {
+    TenantContext {
+        tenant_id: "acme".to_string(),
+        correlation_id: Some(correlation_id.to_string()),
+        agent_id: Some("billing".to_string()),
+        ..Default::default()
+    }
+}
+
+fn payments_need_approval(knowledge: &KnowledgeStore) {
+    let rule: PolicyRule = serde_json::from_value(serde_json::json!({
+        "id": "payments",
+        "skills": ["Pay*"],
+        "action": "require_approval"
+    }))
+    .unwrap();
+    knowledge.put_policy_rules(&PolicyRules::new(vec![rule], RuleAction::Allow)).unwrap();
+}
+
+#[test]
+fn checkpoint_requests_once_and_grants_once() {
+    let knowledge = KnowledgeStore::open_temporary().unwrap();
+    let payload = Some(serde_json::json!({ "amount": 5 }));
+    let request = ApprovalRecord::new("acme", "billing", "c-1", "PaymentSend", payload.clone());
+
+    let ApprovalCheckpoint::Pending(pending) = knowledge.approval_checkpoint(&request, true).unwrap() else {
+        panic!("expected a pending approval");
+    };
+    assert_eq!(pending.status, ApprovalStatus::Pending);
+    assert!(pending.created_at_ms > 0);
+    // Asking again returns the same pending approval instead of a second one.
+    let ApprovalCheckpoint::Pending(again) = knowledge.approval_checkpoint(&request, true).unwrap() else {
+        panic!("expected a pending approval");
+    };
+    assert_eq!(again.approval_id, pending.approval_id);
+    assert_eq!(knowledge.list_approvals(Some(ApprovalStatus::Pending), 10).unwrap().len(), 1);
+
+    let approved = knowledge
+        .decide_approval(&pending.approval_id, true, "alice", Some("ok for today"))
+        .unwrap()
+        .unwrap();
+    assert_eq!(approved.status, ApprovalStatus::Approved);
+    assert_eq!(approved.decided_by.as_deref(), Some("alice"));
+    assert!(knowledge.decide_approval(&pending.approval_id, false, "bob", None).is_err());
+    assert!(knowledge.decide_approval("missing", true, "bob", None).unwrap().is_none());
+
+    // A different payload or correlation id is a different call.
+    let other = ApprovalRecord::new("acme", "billing", "c-2", "PaymentSend", payload.clone());
+    assert!(matches!(knowledge.approval_checkpoint(&other, false).unwrap(), ApprovalCheckpoint::Pending(_)));
+
+    // Peeking leaves the grant in place; consuming uses it up.
+    assert!(matches!(knowledge.approval_checkpoint(&request, false).unwrap(), ApprovalCheckpoint::Granted(_)));
+    let ApprovalCheckpoint::Granted(executed) = knowledge.approval_checkpoint(&request, true).unwrap() else {
+        panic!("expected a grant");
+    };
+    assert_eq!(executed.status, ApprovalStatus::Executed);
+    assert!(executed.executed_at_ms.is_some());
+    let ApprovalCheckpoint::Pending(fresh) = knowledge.approval_checkpoint(&request, true).unwrap() else {
+        panic!("a used grant must not be reused");
+    };
+    assert_ne!(fresh.approval_id, pending.approval_id);
+
+    knowledge.decide_approval(&fresh.approval_id, false, "bob", None).unwrap();
+    let ApprovalCheckpoint::Denied(denied) = knowledge.approval_checkpoint(&request, true).unwrap() else {
+        panic!("expected the denial");
+    };
+    assert!(denied.denial_reason().contains("bob"));
+    let all = knowledge.list_approvals(None, 10).unwrap();
+    assert_eq!(all.len(), 3);
+    assert!(all.windows(2).all(|w| w[0].created_at_ms >= w[1].created_at_ms));
+}
+
+#[tokio::test]
+async fn orchestrator_suspends_until_approved() {
+    let knowledge = Arc::new(KnowledgeStore::open_temporary().unwrap());
+    payments_need_approval(&knowledge);
+    let calls = Arc::new(AtomicU32::new(0));
+    let mut registry = SkillRegistry::new();
+    registry.register(Arc::new(Counted(Arc::clone(&calls))));
+    let orch = Orchestrator::new(Arc::new(registry)).with_knowledge(Arc::clone(&knowledge));
+    let goal = Goal::ExecuteSkill {
+        name: "PaymentSend".into(),
+        payload: Some(serde_json::json!({ "amount": 5 })),
+    };
+
+    let err = orch.dispatch(&ctx("c-1"), goal.clone()).await.unwrap_err();
+    let blocked = err.downcast_ref::<EthosBlocked>().expect("EthosBlocked");
+    assert!(blocked.requires_approval());
+    let approval_id = blocked.approval_id.clone().expect("pending approval");
+    assert_eq!(calls.load(Ordering::SeqCst), 0);
+
+    // Without a correlation id there is nothing to resume, so no approval is recorded.
+    let anonymous = TenantContext { correlation_id: None, ..ctx("") };
+    let err = orch.dispatch(&anonymous, goal.clone()).await.unwrap_err();
+    assert!(err.downcast_ref::<EthosBlocked>().unwrap().approval_id.is_none());
+
+    knowledge.decide_approval(&approval_id, true, "alice", None).unwrap();
+    orch.dispatch(&ctx("c-1"), goal.clone()).await.unwrap();
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+    assert_eq!(knowledge.get_approval(&approval_id).unwrap().status, ApprovalStatus::Executed);
+
+    // The grant was for one run.
+    assert!(orch.dispatch(&ctx("c-1"), goal).await.is_err());
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+}
@@ -0,0 +1,65 @@
+//! Archive tier: cold records move to compressed segments, stay readable, and can be restored.
+
+use pagi_core::{EventRecord, KbRecord, KbType, KnowledgeStore};
+
+fn event_at(timestamp_ms: i64, reflection: &str) -> EventRecord {
+    EventRecord::at(timestamp_ms, "Chronos", reflection)
+}
+
+#[test]
+fn archived_chronos_events_remain_recallable_and_restorable() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = KnowledgeStore::open_path(dir.path().join("pagi_knowledge")).unwrap();
+    assert_eq!(store.archive_dir(), dir.path().join("archive"));
+    let chronos = KbType::Chronos.slot_id();
+
+    store.append_chronos_event("agent", &event_at(1_000, "old one")).unwrap();
+    store.append_chronos_event("agent", &event_at(2_000, "old two")).unwrap();
+    store.append_chronos_event("agent", &store.event("Chronos", "fresh")).unwrap();
+
+    let report = store.archive_older_than(chronos, 10_000).unwrap();
+    assert_eq!(report.archived, 2);
+    assert_eq!(store.count(chronos).unwrap(), 1);
+    let segments = store.list_archive_segments(Some(chronos)).unwrap();
+    assert_eq!(segments.len(), 1);
+    assert_eq!(segments[0].min_timestamp_ms, 1_000);
+    let segment_path = store.archive_dir().join(&segments[0].file);
+    assert!(segment_path.exists());
+
+    // Recall transparently reaches into the archive when live events are not enough.
+    let recent = store.get_recent_chronos_events("agent", 10).unwrap();
+    let reflections: Vec<&str> = recent.iter().map(|e| e.reflection.as_str()).collect();
+    assert_eq!(reflections, ["fresh", "old two", "old one"]);
+    assert_eq!(store.get_recent_chronos_events("agent", 1).unwrap()[0].reflection, "fresh");
+
+    // Point restore, then segment restore deletes the emptied segment file.
+    let archived = store.scan_archived_kv(chronos).unwrap();
+    assert_eq!(archived.len(), 2);
+    let key = &archived[0].0;
+    assert!(store.get(chronos, key).unwrap().is_none());
+    assert!(store.get_or_archived(chronos, key).unwrap().is_some());
+    assert!(store.restore_archived(chronos, key).unwrap());
+    assert!(store.get(chronos, key).unwrap().is_some());
+    assert_eq!(store.scan_archived_kv(chronos).unwrap().len(), 1);
+
+    assert_eq!(store.restore_archive_segment(&segments[0].id).unwrap(), 1);
+    assert_eq!(store.count(chronos).unwrap(), 3);
+    assert!(store.list_archive_segments(None).unwrap().is_empty());
+    assert!(!segment_path.exists());
+}
+
+#[test]
+fn archive_skips_undated_records_and_rejects_shadow() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = KnowledgeStore::open_path(dir.path().join("pagi_knowledge")).unwrap();
+    let logos = KbType::Logos.slot_id();
+    let old = KbRecord::new("old research", 5);
+    store.insert_record(logos, "research/old", &old).unwrap();
+    store.insert(logos, "research/undated", b"{\"content\":\"x\"}").unwrap();
+
+    let report = store.archive_older_than(logos, 100).unwrap();
+    assert_eq!(report.archived, 1);
+    assert!(store.get(logos, "research/undated").unwrap().is_some());
+    assert_eq!(store.scan_kv_with_archive(logos).unwrap().len(), 2);
+    assert!(store.archive_older_than(KbType::Shadow.slot_id(), 100).is_err());
+}
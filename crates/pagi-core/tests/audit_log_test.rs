@@ -0,0 +1,186 @@
+//! Dispatch audit log: one record per top-level dispatch, filterable and paged newest first.
+
+mod common;
+
+use common::orchestrator_with_plans;
+use pagi_core::{
+    AgentSkill, AuditOutcome, AuditQuery, AuditRecord, Goal, KnowledgeStore, Orchestrator,
+    PlanStep, SkillRegistry, TenantContext,
+};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Succeeds, or fails when the payload has `"fail": true`.
+struct Echo(&'static str);
+
+#[async_trait::async_trait]
+impl AgentSkill for Echo {
+    fn name(&self) -> &str {
+        self.0
+    }
+
+    async fn execute(
+        &self,
+        _ctx: &TenantContext,
+        payload: Option<serde_json::Value>,
+    ) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>> {
+        if payload.as_ref().and_then(|p| p.get("fail")).is_some() {
+            return Err("echo failed".into());
+        }
+        Ok(serde_json::json!({ "skill": self.0 }))
+    }
+}
+
+fn ctx(tenant: &str) -> TenantContext {
+    TenantContext {
+        tenant_id: tenant.to_string(),
+        correlation_id: None,
+        agent_id: Some("auditor".to_string()),
+        ..Default::default()
+    }
+}
+
+fn skill(name: &str, fail: bool) -> Goal {
+    Goal::ExecuteSkill {
+        name: name.to_string(),
+        payload: fail.then(|| serde_json::json!({ "fail": true })),
+    }
+}
+
+fn orchestrator(knowledge: &Arc<KnowledgeStore>) -> Orchestrator {
+    let mut registry = SkillRegistry::new();
+    registry.register(Arc::new(Echo("Alpha")));
+    registry.register(Arc::new(Echo("Beta")));
+    orchestrator_with_plans(registry, [("both", vec![PlanStep::new("Alpha"), PlanStep::new("Beta")])])
+        .with_knowledge(Arc::clone(knowledge))
+}
+
+#[tokio::test]
+async fn every_dispatch_is_recorded_with_outcome() {
+    let knowledge = Arc::new(KnowledgeStore::open_temporary().unwrap());
+    let orch = orchestrator(&knowledge);
+
+    orch.dispatch(&ctx("acme"), skill("Alpha", false)).await.unwrap();
+    orch.dispatch(&ctx("acme"), skill("Beta", true)).await.unwrap_err();
+    let plan = Goal::AutonomousGoal {
+        intent: "both".to_string(),
+        context: None,
+    };
+    orch.dispatch(&ctx("globex"), plan).await.unwrap();
+
+    let page = knowledge.query_audit(&AuditQuery::default()).unwrap();
+    assert_eq!(page.records.len(), 3, "plan steps are not audited separately");
+    assert!(page.next_cursor.is_none());
+
+    let plan = &page.records[0];
+    assert_eq!(plan.goal_type, "AutonomousGoal");
+    assert_eq!(plan.intent.as_deref(), Some("both"));
+    assert!(plan.skill.is_none());
+    assert_eq!(plan.tenant_id, "globex");
+
+    let failed = &page.records[1];
+    assert_eq!(failed.skill.as_deref(), Some("Beta"));
+    assert_eq!(failed.outcome, AuditOutcome::Error);
+    assert_eq!(failed.error.as_deref(), Some("echo failed"));
+
+    let ok = &page.records[2];
+    assert_eq!(ok.outcome, AuditOutcome::Ok);
+    assert_eq!(ok.agent_id, "auditor");
+    assert!(ok.error.is_none());
+}
+
+#[tokio::test]
+async fn streams_and_cancellations_are_recorded() {
+    let knowledge = Arc::new(KnowledgeStore::open_temporary().unwrap());
+    let orch = orchestrator(&knowledge);
+
+    let mut rx = orch.dispatch_stream(&ctx("acme"), skill("Alpha", false)).await.unwrap();
+    while rx.recv().await.is_some() {}
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    let expired = ctx("acme").with_timeout(Duration::ZERO);
+    orch.dispatch(&expired, skill("Alpha", false)).await.unwrap_err();
+
+    let records = knowledge.query_audit(&AuditQuery::default()).unwrap().records;
+    let outcomes: Vec<AuditOutcome> = records.iter().map(|r| r.outcome).collect();
+    assert!(outcomes.contains(&AuditOutcome::Ok), "{:?}", outcomes);
+    assert!(outcomes.contains(&AuditOutcome::Timeout), "{:?}", outcomes);
+}
+
+fn record(id: &str, timestamp_ms: i64, tenant: &str, skill: &str, outcome: AuditOutcome) -> AuditRecord {
+    AuditRecord {
+        id: id.to_string(),
+        timestamp_ms,
+        tenant_id: tenant.to_string(),
+        agent_id: "default".to_string(),
+        correlation_id: None,
+        goal_type: "ExecuteSkill".to_string(),
+        skill: Some(skill.to_string()),
+        intent: None,
+        duration_ms: 5,
+        outcome,
+        error: None,
+        redactions: Default::default(),
+    }
+}
+
+#[test]
+fn queries_filter_page_and_prune() {
+    let knowledge = KnowledgeStore::open_temporary().unwrap();
+    for i in 0..10 {
+        let tenant = if i % 2 == 0 { "acme" } else { "globex" };
+        let skill = if i < 5 { "Alpha" } else { "Beta" };
+        let outcome = if i == 7 { AuditOutcome::Error } else { AuditOutcome::Ok };
+        knowledge
+            .append_audit_record(&record(&format!("r{}", i), 1_000 + i as i64 * 100, tenant, skill, outcome))
+            .unwrap();
+    }
+
+    let acme = AuditQuery {
+        tenant_id: Some("acme".to_string()),
+        ..Default::default()
+    };
+    let ids = |q: &AuditQuery| -> Vec<String> {
+        knowledge.query_audit(q).unwrap().records.into_iter().map(|r| r.id).collect()
+    };
+    assert_eq!(ids(&acme), vec!["r8", "r6", "r4", "r2", "r0"]);
+
+    let beta_acme = AuditQuery {
+        skill: Some("beta".to_string()),
+        ..acme.clone()
+    };
+    assert_eq!(ids(&beta_acme), vec!["r8", "r6"]);
+
+    let errors = AuditQuery {
+        outcome: Some(AuditOutcome::Error),
+        ..Default::default()
+    };
+    assert_eq!(ids(&errors), vec!["r7"]);
+
+    // Time range: from inclusive, to exclusive.
+    let window = AuditQuery {
+        from_ms: Some(1_200),
+        to_ms: Some(1_500),
+        ..Default::default()
+    };
+    assert_eq!(ids(&window), vec!["r4", "r3", "r2"]);
+
+    // Pages of 4 cover everything once, newest first.
+    let mut query = AuditQuery {
+        limit: Some(4),
+        ..Default::default()
+    };
+    let mut seen = Vec::new();
+    loop {
+        let page = knowledge.query_audit(&query).unwrap();
+        seen.extend(page.records.into_iter().map(|r| r.id));
+        match page.next_cursor {
+            Some(cursor) => query.cursor = Some(cursor),
+            None => break,
+        }
+    }
+    assert_eq!(seen, (0..10).rev().map(|i| format!("r{}", i)).collect::<Vec<_>>());
+
+    assert_eq!(knowledge.prune_audit_records(1_500).unwrap(), 5);
+    assert_eq!(ids(&AuditQuery::default()).len(), 5);
+}
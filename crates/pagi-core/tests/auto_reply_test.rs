@@ -0,0 +1,64 @@
+//! Integration test: Heartbeat auto-replies share chat's context assembly.
+//!
+//! Verifies that:
+//! 1. The system prompt is the Mission Directive toward the sender (per-agent persona, Kardia relation).
+//! 2. The prompt renders the default template with the thread history between the two agents.
+//! 3. A per-agent template in Pneuma replaces the default frame.
+
+use pagi_core::{KnowledgeStore, RelationRecord, DEFAULT_AUTO_REPLY_TEMPLATE};
+
+fn push(store: &KnowledgeStore, from: &str, to: &str, text: &str) {
+    store
+        .push_agent_message(from, to, &serde_json::json!({ "text": text }))
+        .unwrap();
+    std::thread::sleep(std::time::Duration::from_millis(2));
+}
+
+#[test]
+fn auto_reply_uses_persona_relationship_and_thread() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = KnowledgeStore::open_path(dir.path()).unwrap();
+
+    store.set_agent_persona("scout", "A terse field scout who speaks in short reports.").unwrap();
+    store
+        .set_kardia_relation(
+            "scout",
+            &RelationRecord::new("planner", store.now_ms()).with_communication_style("formal"),
+        )
+        .unwrap();
+
+    push(&store, "planner", "scout", "Status of the north ridge?");
+    push(&store, "scout", "planner", "Clear. Two paths.");
+    push(&store, "other", "scout", "Unrelated chatter");
+    push(&store, "planner", "scout", "Which path is faster?");
+
+    let msg = store.get_agent_messages("scout", 1).unwrap().remove(0);
+    let reply = store.build_auto_reply_prompt("scout", &msg);
+
+    assert!(reply.system_prompt.contains("A terse field scout"));
+    assert!(reply.system_prompt.contains("Communication style: formal"));
+
+    assert!(reply.prompt.contains("planner: Status of the north ridge?\nscout: Clear. Two paths."));
+    assert!(reply.prompt.contains("New message from planner:\nWhich path is faster?"));
+    assert!(!reply.prompt.contains("Unrelated chatter"));
+    assert!(!reply.prompt.contains("{thread}"));
+}
+
+#[test]
+fn per_agent_template_overrides_default() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = KnowledgeStore::open_path(dir.path()).unwrap();
+
+    assert_eq!(store.get_auto_reply_template("scout"), DEFAULT_AUTO_REPLY_TEMPLATE);
+    store
+        .set_auto_reply_template("scout", "[{agent_id} -> {sender}] {message} | {unknown}")
+        .unwrap();
+
+    push(&store, "planner", "scout", "Say {thread} literally");
+    let msg = store.get_agent_messages("scout", 1).unwrap().remove(0);
+    let reply = store.build_auto_reply_prompt("scout", &msg);
+
+    assert_eq!(reply.prompt, "[scout -> planner] Say {thread} literally | {unknown}");
+    // Agents without their own persona keep the shared one.
+    assert!(!reply.system_prompt.contains("terse"));
+}
@@ -0,0 +1,57 @@
+//! Runs the declarative blueprint scenarios in `config/scenarios/` against `config/blueprint.json`.
+//!
+//! Add a `.yaml` file there to cover a blueprint change; this test picks it up.
+
+use pagi_core::{Scenario, ScenarioRunner};
+use std::path::PathBuf;
+
+fn config_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../config")
+}
+
+#[tokio::test]
+async fn config_scenarios_pass() {
+    let runner = ScenarioRunner::new()
+        .with_blueprint_path(config_dir().join("blueprint.json"))
+        .unwrap();
+    let scenarios = Scenario::load_dir(config_dir().join("scenarios")).unwrap();
+    assert!(!scenarios.is_empty());
+
+    let mut failed = Vec::new();
+    for scenario in &scenarios {
+        let outcome = runner.run(scenario).await.unwrap();
+        if !outcome.passed() {
+            failed.push(format!("{}:\n  {}", outcome.name, outcome.failures.join("\n  ")));
+        }
+    }
+    assert!(failed.is_empty(), "failing scenarios:\n{}", failed.join("\n"));
+}
+
+#[tokio::test]
+async fn failed_expectations_are_reported_and_kb_state_is_seeded() {
+    let scenario: Scenario = serde_json::from_value(serde_json::json!({
+        "name": "inline",
+        "given": {
+            "kb": [{ "slot": 5, "key": "blueprints/echo twice", "value": {
+                "intent": "echo twice", "steps": [{ "skill": "Echo" }, { "skill": "Echo" }]
+            } }],
+            "blueprint": { "intents": { "echo once": ["Echo"] } }
+        },
+        "when": { "intent": "echo twice", "context": { "n": 1 } },
+        "then": {
+            "called": ["Echo"],
+            "output": { "$.n": 2, "$.missing": true },
+            "inputs": { "Other": { "$": {} } }
+        }
+    }))
+    .unwrap();
+    let outcome = ScenarioRunner::new().run(&scenario).await.unwrap();
+
+    assert_eq!(outcome.called, vec!["Echo", "Echo"]);
+    assert_eq!(outcome.failures.len(), 4, "{:?}", outcome.failures);
+    assert!(outcome.failures[0].starts_with("called [\"Echo\", \"Echo\"], expected [\"Echo\"]"));
+    assert_eq!(outcome.failures[1], "output $.missing: expected true, but nothing matched");
+    assert_eq!(outcome.failures[2], "output $.n: expected 2, got 1");
+    assert_eq!(outcome.failures[3], "inputs.Other: skill was not called");
+    assert_eq!(outcome.trace.unwrap()["plan_steps"], serde_json::json!(["Echo", "Echo"]));
+}
@@ -0,0 +1,143 @@
+//! Cancellation: in-flight goals stop between plan steps (or mid-skill) and streams end early.
+
+mod common;
+
+use common::orchestrator_with_plans;
+use pagi_core::{
+    AgentSkill, CancellationToken, Goal, GoalCancelled, Orchestrator, PlanStep,
+    SkillRegistry, TenantContext,
+};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+/// Sleeps, then counts the call as completed.
+struct Slow(&'static str, u64, Arc<AtomicU32>);
+
+#[async_trait::async_trait]
+impl AgentSkill for Slow {
+    fn name(&self) -> &str {
+        self.0
+    }
+
+    async fn execute(
+        &self,
+        _ctx: &TenantContext,
+        _payload: Option<serde_json::Value>,
+    ) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>> {
+        tokio::time::sleep(Duration::from_millis(self.1)).await;
+        self.2.fetch_add(1, Ordering::SeqCst);
+        Ok(serde_json::json!({ "skill": self.0 }))
+    }
+
+    async fn execute_stream(
+        &self,
+        _ctx: &TenantContext,
+        _payload: Option<serde_json::Value>,
+    ) -> Result<mpsc::Receiver<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>> {
+        let (tx, rx) = mpsc::channel(1);
+        let delay = self.1;
+        tokio::spawn(async move {
+            for i in 0.. {
+                tokio::time::sleep(Duration::from_millis(delay)).await;
+                if tx.send(serde_json::json!({ "delta": i.to_string() })).await.is_err() {
+                    return;
+                }
+            }
+        });
+        Ok(rx)
+    }
+}
+
+fn ctx(correlation_id: &str) -> TenantContext {
+    TenantContext {
+        tenant_id: "test".to_string(),
+        correlation_id: Some(correlation_id.to_string()),
+        agent_id: None,
+        ..Default::default()
+    }
+}
+
+fn orchestrator(calls: &Arc<AtomicU32>) -> Arc<Orchestrator> {
+    let mut registry = SkillRegistry::new();
+    registry.register(Arc::new(Slow("Fast", 10, Arc::clone(calls))));
+    registry.register(Arc::new(Slow("Slow", 5_000, Arc::clone(calls))));
+    let steps = vec![PlanStep::new("Fast"), PlanStep::new("Slow"), PlanStep::new("Fast")];
+    Arc::new(orchestrator_with_plans(registry, [("long", steps)]))
+}
+
+fn autonomous() -> Goal {
+    Goal::AutonomousGoal {
+        intent: "long".to_string(),
+        context: None,
+    }
+}
+
+#[tokio::test]
+async fn cancel_by_correlation_id_stops_autonomous_goal() {
+    let calls = Arc::new(AtomicU32::new(0));
+    let orchestrator = orchestrator(&calls);
+
+    let running = {
+        let orchestrator = Arc::clone(&orchestrator);
+        tokio::spawn(async move { orchestrator.dispatch(&ctx("corr-1"), autonomous()).await })
+    };
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert_eq!(orchestrator.in_flight(), ["corr-1"]);
+    assert!(orchestrator.cancel("corr-1"));
+
+    let err = tokio::time::timeout(Duration::from_secs(1), running)
+        .await
+        .expect("cancelled promptly")
+        .unwrap()
+        .unwrap_err();
+    let cancelled = err.downcast_ref::<GoalCancelled>().expect("GoalCancelled");
+    assert_eq!(cancelled.completed_steps, 1);
+    // Only the first step finished; the slow step was dropped and the last never started.
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+    assert!(orchestrator.in_flight().is_empty());
+    assert!(!orchestrator.cancel("corr-1"));
+}
+
+#[tokio::test]
+async fn cancelled_token_stops_before_the_first_step() {
+    let calls = Arc::new(AtomicU32::new(0));
+    let orchestrator = orchestrator(&calls);
+    let token = CancellationToken::new();
+    token.cancel();
+
+    let err = orchestrator
+        .dispatch_with_token(&ctx("corr-2"), autonomous(), token)
+        .await
+        .unwrap_err();
+    assert!(err.is::<GoalCancelled>());
+    assert_eq!(calls.load(Ordering::SeqCst), 0);
+}
+
+#[tokio::test]
+async fn cancel_ends_stream_with_cancelled_chunk() {
+    let calls = Arc::new(AtomicU32::new(0));
+    let orchestrator = orchestrator(&calls);
+    let goal = Goal::ExecuteSkill {
+        name: "Fast".to_string(),
+        payload: None,
+    };
+    let mut rx = orchestrator.dispatch_stream(&ctx("corr-3"), goal).await.unwrap();
+    assert!(rx.recv().await.unwrap().get("delta").is_some());
+
+    assert!(orchestrator.cancel("corr-3"));
+    let last = tokio::time::timeout(Duration::from_secs(1), async {
+        let mut last = None;
+        while let Some(chunk) = rx.recv().await {
+            last = Some(chunk);
+        }
+        last
+    })
+    .await
+    .expect("stream closed after cancel")
+    .unwrap();
+    assert_eq!(last["status"], "cancelled");
+    assert_eq!(last["done"], true);
+    assert!(orchestrator.in_flight().is_empty());
+}
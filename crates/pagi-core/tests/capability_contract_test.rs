@@ -0,0 +1,62 @@
+//! Capabilities contract: every skill a blueprint intent names must be registered.
+
+use pagi_core::{check_capabilities, missing_capabilities, BlueprintRegistry, MissingCapability, Plan, PlanStep};
+use std::collections::HashMap;
+
+fn skills(names: &[&str]) -> Vec<String> {
+    names.iter().map(|s| s.to_string()).collect()
+}
+
+#[test]
+fn default_blueprint_lists_its_skills_when_none_are_registered() {
+    let blueprint = BlueprintRegistry::default_blueprint();
+    let err = check_capabilities("default blueprint", &blueprint, &[]).unwrap_err();
+    let missing: Vec<&str> = err.missing.iter().map(|m| m.skill.as_str()).collect();
+    assert_eq!(missing, vec!["DraftResponse", "ModelRouter", "SalesCloser"]);
+    assert!(err.to_string().starts_with("default blueprint: 3 missing capabilities"));
+    assert!(err
+        .to_string()
+        .contains("intent \"respond to lead\" needs skill \"SalesCloser\""));
+
+    let all = skills(&["DraftResponse", "SalesCloser", "ModelRouter"]);
+    assert!(check_capabilities("default blueprint", &blueprint, &all).is_ok());
+}
+
+#[test]
+fn renamed_skills_and_fallbacks_are_reported_per_intent() {
+    let plans = HashMap::from([
+        (
+            "triage".to_string(),
+            Plan {
+                steps: vec![
+                    PlanStep::new("ParseInboundMessage"),
+                    PlanStep::new("ModelRouter").with_fallbacks(["LocalModel"]),
+                ],
+            },
+        ),
+        (
+            "digest".to_string(),
+            Plan {
+                steps: vec![PlanStep::new("ScrapeCommunity"), PlanStep::sub_plan("triage")],
+            },
+        ),
+    ]);
+    let blueprint = BlueprintRegistry::from_plans(plans);
+    let registered = skills(&["ParseInboundMessage", "ModelRouter", "CommunityScraper"]);
+
+    assert_eq!(
+        missing_capabilities(&blueprint, &registered),
+        vec![
+            MissingCapability {
+                intent: "digest".to_string(),
+                skill: "ScrapeCommunity".to_string(),
+            },
+            MissingCapability {
+                intent: "triage".to_string(),
+                skill: "LocalModel".to_string(),
+            },
+        ]
+    );
+    let err = check_capabilities("config/blueprint.json", &blueprint, &registered).unwrap_err();
+    assert!(err.to_string().starts_with("config/blueprint.json: 2 missing capabilities"));
+}
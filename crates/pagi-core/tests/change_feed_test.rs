@@ -0,0 +1,78 @@
+//! Change feed: per-slot and store-wide events for KB writes, archive moves and restores.
+
+use pagi_core::{KbChange, KbChangeKind, KbRecord, KbType, KnowledgeStore, SnapshotManager, SnapshotReason};
+use std::sync::Arc;
+use tokio::sync::broadcast::{error::TryRecvError, Receiver};
+
+fn drain(rx: &mut Receiver<KbChange>) -> Vec<(u8, String, KbChangeKind)> {
+    std::iter::from_fn(|| rx.try_recv().ok())
+        .map(|c| (c.slot_id, c.key, c.kind))
+        .collect()
+}
+
+#[test]
+fn writes_reach_slot_and_store_wide_subscribers() {
+    let store = KnowledgeStore::open_temporary().unwrap();
+    let logos = KbType::Logos.slot_id();
+    let oikos = KbType::Oikos.slot_id();
+    // Nothing is subscribed yet: publishing must not fail the write.
+    store.insert_record(logos, "early", &KbRecord::new("unseen", store.now_ms())).unwrap();
+
+    let mut slot_rx = store.subscribe(logos);
+    let mut all_rx = store.subscribe_all();
+    store.insert_record(logos, "fact", &KbRecord::new("first", store.now_ms())).unwrap();
+    store.insert_record(logos, "fact", &KbRecord::new("second", store.now_ms())).unwrap();
+    store.insert_record(oikos, "task", &KbRecord::new("elsewhere", store.now_ms())).unwrap();
+    store.remove(logos, "fact").unwrap();
+    // Removing a missing key changes nothing and publishes nothing.
+    store.remove(logos, "fact").unwrap();
+
+    assert_eq!(
+        drain(&mut slot_rx),
+        [
+            (logos, "fact".to_string(), KbChangeKind::Insert),
+            (logos, "fact".to_string(), KbChangeKind::Update),
+            (logos, "fact".to_string(), KbChangeKind::Remove),
+        ]
+    );
+    let all = drain(&mut all_rx);
+    assert_eq!(all.len(), 4);
+    assert_eq!(all[2], (oikos, "task".to_string(), KbChangeKind::Insert));
+
+    let mut closed = store.subscribe(12);
+    assert_eq!(closed.try_recv(), Err(TryRecvError::Closed));
+}
+
+#[test]
+fn sequence_numbers_increase_and_archive_moves_are_reported() {
+    let store = KnowledgeStore::open_temporary().unwrap();
+    let chronos = KbType::Chronos.slot_id();
+    let mut rx = store.subscribe(chronos);
+    let old = KbRecord::new("cold", 1_000);
+    store.insert_record(chronos, "old", &old).unwrap();
+    assert_eq!(store.archive_older_than(chronos, 10_000).unwrap().archived, 1);
+    assert!(store.restore_archived(chronos, "old").unwrap());
+
+    let changes: Vec<KbChange> = std::iter::from_fn(|| rx.try_recv().ok()).collect();
+    let kinds: Vec<KbChangeKind> = changes.iter().map(|c| c.kind).collect();
+    assert_eq!(kinds, [KbChangeKind::Insert, KbChangeKind::Archived, KbChangeKind::Insert]);
+    assert!(changes.windows(2).all(|w| w[1].seq == w[0].seq + 1));
+}
+
+#[test]
+fn snapshot_restore_resets_the_restored_slots() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = Arc::new(KnowledgeStore::open_path(dir.path().join("pagi_knowledge")).unwrap());
+    let manager = SnapshotManager::new(Arc::clone(&store), dir.path().join("snapshots"));
+    let logos = KbType::Logos.slot_id();
+    let snapshot = manager.create(SnapshotReason::Manual).unwrap();
+
+    let mut rx = store.subscribe_all();
+    manager.restore(&snapshot.id, Some(logos)).unwrap();
+    assert_eq!(drain(&mut rx), [(logos, String::new(), KbChangeKind::Reset)]);
+
+    manager.restore(&snapshot.id, None).unwrap();
+    let resets = drain(&mut rx);
+    assert_eq!(resets.len(), 9);
+    assert!(resets.iter().all(|(_, key, kind)| key.is_empty() && *kind == KbChangeKind::Reset));
+}
@@ -0,0 +1,38 @@
+//! Injected clocks: events, messages and leads are stamped by the store's clock, not the wall.
+
+use pagi_core::{FrozenClock, KnowledgeStore, LeadRecord, MemoryManager, TenantContext};
+use std::sync::Arc;
+
+const FROZEN_MS: i64 = 1_000_000;
+
+#[test]
+fn frozen_clock_stamps_events_and_messages() {
+    let clock = Arc::new(FrozenClock::new(FROZEN_MS));
+    let store = KnowledgeStore::open_temporary().unwrap().with_clock(clock.clone());
+
+    store.append_chronos_event("agent", &store.event("Chronos", "first")).unwrap();
+    clock.advance(500);
+    store.append_chronos_event("agent", &store.event("Chronos", "second")).unwrap();
+
+    let events = store.get_recent_chronos_events("agent", 10).unwrap();
+    let stamps: Vec<(&str, i64)> = events.iter().map(|e| (e.reflection.as_str(), e.timestamp_ms)).collect();
+    assert_eq!(stamps, [("second", FROZEN_MS + 500), ("first", FROZEN_MS)]);
+
+    let message = store.message("a", "b", &serde_json::json!({"hi": true}));
+    assert_eq!(message.timestamp_ms, FROZEN_MS + 500);
+}
+
+#[test]
+fn frozen_clock_stamps_leads() {
+    let clock = Arc::new(FrozenClock::new(FROZEN_MS));
+    let memory = MemoryManager::open_temporary().unwrap().with_clock(clock.clone());
+    let ctx = TenantContext::new("acme");
+
+    let lead = memory.put_lead(&ctx, LeadRecord::default()).unwrap();
+    assert_eq!((lead.created_at_ms, lead.updated_at_ms), (FROZEN_MS, FROZEN_MS));
+
+    clock.advance(60_000);
+    let lead = memory.put_lead(&ctx, lead).unwrap();
+    assert_eq!(lead.created_at_ms, FROZEN_MS);
+    assert_eq!(lead.updated_at_ms, FROZEN_MS + 60_000);
+}
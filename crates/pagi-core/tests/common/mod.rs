@@ -0,0 +1,41 @@
+//! Helpers shared by the integration tests (`mod common;`). Each test binary uses a subset.
+#![allow(dead_code)]
+
+use pagi_core::{AgentSkill, BlueprintRegistry, Orchestrator, Plan, PlanStep, SkillRegistry, TenantContext};
+use std::sync::{Arc, Mutex};
+
+/// Context for the `test` tenant.
+pub fn ctx() -> TenantContext {
+    TenantContext::new("test")
+}
+
+/// Orchestrator over `registry` whose blueprint runs the given steps for each intent.
+pub fn orchestrator_with_plans<'a>(
+    registry: SkillRegistry,
+    plans: impl IntoIterator<Item = (&'a str, Vec<PlanStep>)>,
+) -> Orchestrator {
+    let plans = plans
+        .into_iter()
+        .map(|(intent, steps)| (intent.to_string(), Plan { steps }))
+        .collect();
+    Orchestrator::with_blueprint(Arc::new(registry), Arc::new(BlueprintRegistry::from_plans(plans)))
+}
+
+/// Stands in for ResearchAudit and keeps the trace the orchestrator asked it to save.
+pub struct CaptureAudit(pub Arc<Mutex<Option<serde_json::Value>>>);
+
+#[async_trait::async_trait]
+impl AgentSkill for CaptureAudit {
+    fn name(&self) -> &str {
+        "ResearchAudit"
+    }
+
+    async fn execute(
+        &self,
+        _ctx: &TenantContext,
+        payload: Option<serde_json::Value>,
+    ) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>> {
+        *self.0.lock().unwrap() = payload.and_then(|p| p.get("trace").cloned());
+        Ok(serde_json::json!({ "trace_id": "t-1" }))
+    }
+}
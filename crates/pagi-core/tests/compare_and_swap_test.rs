@@ -0,0 +1,95 @@
+//! Optimistic concurrency: compare-and-swap and retrying updates do not lose concurrent writes.
+
+use pagi_core::{GovernedTask, KbType, KnowledgeStore, RelationRecord, TaskDifficulty};
+use std::sync::Arc;
+
+#[test]
+fn compare_and_swap_writes_only_over_the_expected_value() {
+    let store = KnowledgeStore::open_temporary().unwrap();
+    let logos = KbType::Logos.slot_id();
+
+    assert_eq!(store.compare_and_swap(logos, "k", None, Some(b"v1")).unwrap(), Ok(()));
+    let conflict = store.compare_and_swap(logos, "k", None, Some(b"v2")).unwrap().unwrap_err();
+    assert_eq!(conflict.current.as_deref(), Some(&b"v1"[..]));
+    assert_eq!(store.compare_and_swap(logos, "k", Some(b"v1"), Some(b"v2")).unwrap(), Ok(()));
+    assert_eq!(store.get(logos, "k").unwrap().as_deref(), Some(&b"v2"[..]));
+    assert_eq!(store.compare_and_swap(logos, "k", Some(b"v2"), None).unwrap(), Ok(()));
+    assert!(store.get(logos, "k").unwrap().is_none());
+
+    // Compressed values compare by their content.
+    let large = "quarterly roofing report ".repeat(400).into_bytes();
+    store.insert(logos, "big", &large).unwrap();
+    assert_eq!(store.compare_and_swap(logos, "big", Some(&large), Some(b"small")).unwrap(), Ok(()));
+
+    assert!(store.compare_and_swap(KbType::Shadow.slot_id(), "anchor/x", None, Some(b"v")).is_err());
+}
+
+#[test]
+fn concurrent_updates_are_not_lost() {
+    let store = Arc::new(KnowledgeStore::open_temporary().unwrap());
+    let oikos = KbType::Oikos.slot_id();
+    let threads: Vec<_> = (0..8)
+        .map(|_| {
+            let store = Arc::clone(&store);
+            std::thread::spawn(move || {
+                for _ in 0..25 {
+                    store
+                        .update_record(oikos, "counter", |record| {
+                            let n: u32 = record.content.parse().unwrap_or(0);
+                            record.content = (n + 1).to_string();
+                        })
+                        .unwrap();
+                }
+            })
+        })
+        .collect();
+    for t in threads {
+        t.join().unwrap();
+    }
+    assert_eq!(store.get_record(oikos, "counter").unwrap().unwrap().content, "200");
+
+    let trust: Vec<_> = (0..4)
+        .map(|_| {
+            let store = Arc::clone(&store);
+            std::thread::spawn(move || {
+                for _ in 0..10 {
+                    store.update_kardia_relation("SAGE_BOT", "DEV_BOT", |r| r.trust_score += 0.01).unwrap();
+                }
+            })
+        })
+        .collect();
+    for t in trust {
+        t.join().unwrap();
+    }
+    let rel = store.get_kardia_relation("SAGE_BOT", "DEV_BOT").unwrap();
+    assert!((rel.trust_score - (RelationRecord::new("DEV_BOT", store.now_ms()).trust_score + 0.4)).abs() < 0.001);
+}
+
+#[test]
+fn task_evaluation_keeps_fields_written_since_the_tasks_were_listed() {
+    let store = KnowledgeStore::open_temporary().unwrap();
+    store.set_governed_task(&GovernedTask::new("t1", "Fix gutter", TaskDifficulty::Low, store.now_ms())).unwrap();
+    assert!(store.update_governed_task("missing", |_| {}).unwrap().is_none());
+
+    let updated = store.update_governed_task("t1", |task| task.title = "Fix gutter and downspout".into()).unwrap().unwrap();
+    assert_eq!(updated.title, "Fix gutter and downspout");
+    let evaluated = store.evaluate_and_persist_tasks("default").unwrap();
+    assert_eq!(evaluated[0].title, "Fix gutter and downspout");
+    assert!(store.get_governed_task("t1").unwrap().last_evaluated_ms > 0);
+}
+
+#[test]
+fn compare_and_swap_and_update_refuse_values_over_the_size_limit() {
+    let store = KnowledgeStore::open_temporary().unwrap().with_max_value_bytes(16);
+    let logos = KbType::Logos.slot_id();
+    store.insert(logos, "k", b"small").unwrap();
+
+    let err = store.compare_and_swap(logos, "k", Some(b"small"), Some(&[b'a'; 17])).unwrap_err();
+    assert!(err.to_string().contains("KB-3 key 'k' is 17 bytes, the limit is 16 bytes"));
+    assert!(store.update(logos, "k", |_| Some(vec![b'a'; 17])).is_err());
+    assert!(store.update(logos, "new", |_| Some(vec![b'a'; 17])).is_err());
+
+    assert_eq!(store.get(logos, "k").unwrap().as_deref(), Some(&b"small"[..]));
+    assert!(store.get(logos, "new").unwrap().is_none());
+    assert_eq!(store.compare_and_swap(logos, "k", Some(b"small"), Some(&[b'a'; 16])).unwrap(), Ok(()));
+}
@@ -0,0 +1,104 @@
+//! Composite goals: Goal::Sequence and Goal::Parallel with partial-failure semantics.
+
+mod common;
+
+use common::ctx;
+use pagi_core::{AgentSkill, Goal, Orchestrator, SkillRegistry, TenantContext};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+
+/// Echoes its payload; fails when `payload.fail` is true. Counts calls.
+struct Echo(Arc<AtomicU32>);
+
+#[async_trait::async_trait]
+impl AgentSkill for Echo {
+    fn name(&self) -> &str {
+        "Echo"
+    }
+
+    async fn execute(
+        &self,
+        _ctx: &TenantContext,
+        payload: Option<serde_json::Value>,
+    ) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>> {
+        self.0.fetch_add(1, Ordering::SeqCst);
+        let payload = payload.unwrap_or(serde_json::Value::Null);
+        if payload.get("fail").and_then(|v| v.as_bool()).unwrap_or(false) {
+            return Err("echo asked to fail".into());
+        }
+        Ok(serde_json::json!({ "echo": payload }))
+    }
+}
+
+fn orchestrator() -> (Orchestrator, Arc<AtomicU32>) {
+    let calls = Arc::new(AtomicU32::new(0));
+    let mut registry = SkillRegistry::new();
+    registry.register(Arc::new(Echo(Arc::clone(&calls))));
+    (Orchestrator::new(Arc::new(registry)), calls)
+}
+
+fn echo(payload: serde_json::Value) -> Goal {
+    Goal::ExecuteSkill {
+        name: "Echo".into(),
+        payload: Some(payload),
+    }
+}
+
+#[tokio::test]
+async fn sequence_runs_in_order_and_stops_at_first_failure() {
+    let (orch, calls) = orchestrator();
+    let out = orch
+        .dispatch(
+            &ctx(),
+            Goal::Sequence(vec![
+                echo(serde_json::json!({ "step": 1 })),
+                echo(serde_json::json!({ "fail": true })),
+                echo(serde_json::json!({ "step": 3 })),
+            ]),
+        )
+        .await
+        .unwrap();
+    assert_eq!(out["goal"], "Sequence");
+    assert_eq!(out["status"], "partial_failure");
+    assert_eq!(out["succeeded"], 1);
+    assert_eq!(out["failed"], 1);
+    assert_eq!(out["skipped"], 1);
+    assert_eq!(out["results"][0]["output"]["echo"]["step"], 1);
+    assert_eq!(out["results"][1]["error"], "echo asked to fail");
+    assert_eq!(out["results"][2]["status"], "skipped");
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn parallel_runs_every_goal_and_reports_each_outcome() {
+    let (orch, calls) = orchestrator();
+    let out = orch
+        .dispatch(
+            &ctx(),
+            Goal::Parallel(vec![
+                echo(serde_json::json!({ "fail": true })),
+                echo(serde_json::json!({ "step": "b" })),
+                Goal::Sequence(vec![echo(serde_json::json!({ "step": "c" }))]),
+            ]),
+        )
+        .await
+        .unwrap();
+    assert_eq!(out["status"], "partial_failure");
+    assert_eq!(out["results"][0]["status"], "failed");
+    assert_eq!(out["results"][1]["output"]["echo"]["step"], "b");
+    assert_eq!(out["results"][2]["goal"], "Sequence");
+    assert_eq!(out["results"][2]["output"]["status"], "ok");
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn composite_goals_round_trip_through_json() {
+    let json = r#"{"Sequence": [{"IngestData": {"payload": null}}, {"Parallel": [{"Custom": "notify"}]}]}"#;
+    let goal: Goal = serde_json::from_str(json).unwrap();
+    assert_eq!(goal.kind(), "Sequence");
+    let (orch, _) = orchestrator();
+    let out = orch.dispatch(&ctx(), goal).await.unwrap();
+    // IngestData needs LeadCapture (not registered) so the sequence fails before the Parallel step.
+    assert_eq!(out["status"], "failed");
+    assert_eq!(out["results"][1]["status"], "skipped");
+}
@@ -0,0 +1,57 @@
+//! Record-level compression: large values are stored compressed and read back unchanged.
+
+use pagi_core::{KbRecord, KbType, KnowledgeStore, COMPRESSION_MAGIC};
+
+fn page(n: usize) -> String {
+    "<div class=\"listing\">Roof repair quote, 30 year shingles, labour included.</div>\n".repeat(n)
+}
+
+#[test]
+fn large_values_are_stored_compressed_and_read_back_unchanged() {
+    let store = KnowledgeStore::open_temporary().unwrap();
+    let logos = KbType::Logos.slot_id();
+    let large = KbRecord::new(page(200), store.now_ms());
+    store.insert_record(logos, "scrape/quotes", &large).unwrap();
+    store.insert_record(logos, "note", &KbRecord::new("short note", store.now_ms())).unwrap();
+
+    assert_eq!(store.get_record(logos, "scrape/quotes").unwrap().unwrap().content, large.content);
+    let scanned = store.scan_kv(logos).unwrap();
+    assert!(scanned.iter().all(|(_, v)| !v.starts_with(&COMPRESSION_MAGIC)));
+    let paged = store.scan_prefix(logos, "scrape/", None, 10).unwrap();
+    assert_eq!(KbRecord::from_bytes(&paged.entries[0].1).unwrap().content, large.content);
+    // Indexes see the original content.
+    assert_eq!(store.text_search(&[logos], "shingles", 5).unwrap()[0].key, "scrape/quotes");
+
+    let stats = store.storage_stats().unwrap();
+    assert_eq!(stats.len(), 9);
+    let slot = &stats[logos as usize - 1];
+    assert_eq!((slot.entries, slot.compressed_entries), (2, 1));
+    assert!(slot.stored_bytes * 5 < slot.raw_bytes);
+    assert!(slot.ratio() < 0.2);
+
+    // Overwrites and removals return the original previous value.
+    let prev = store.insert_record(logos, "scrape/quotes", &KbRecord::new("replaced", store.now_ms())).unwrap().unwrap();
+    assert_eq!(KbRecord::from_bytes(&prev).unwrap().content, large.content);
+    assert_eq!(store.storage_stats().unwrap()[logos as usize - 1].compressed_entries, 0);
+}
+
+#[test]
+fn threshold_is_configurable_and_archive_round_trips_keep_content() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = KnowledgeStore::open_path(dir.path().join("pagi_knowledge"))
+        .unwrap()
+        .with_compression_threshold(usize::MAX);
+    let chronos = KbType::Chronos.slot_id();
+    store.insert_record(chronos, "raw", &KbRecord::new(page(200), store.now_ms())).unwrap();
+    assert_eq!(store.storage_stats().unwrap()[chronos as usize - 1].compressed_entries, 0);
+
+    let store = store.with_compression_threshold(64);
+    let old = KbRecord::new(page(20), 1_000);
+    store.insert_record(chronos, "old", &old).unwrap();
+    assert_eq!(store.storage_stats().unwrap()[chronos as usize - 1].compressed_entries, 1);
+    assert_eq!(store.archive_older_than(chronos, 10_000).unwrap().archived, 1);
+    assert_eq!(store.get_archived(chronos, "old").unwrap().and_then(|b| KbRecord::from_bytes(&b)).unwrap().content, old.content);
+    assert!(store.restore_archived(chronos, "old").unwrap());
+    assert_eq!(store.get_record(chronos, "old").unwrap().unwrap().content, old.content);
+    assert_eq!(store.storage_stats().unwrap()[chronos as usize - 1].compressed_entries, 1);
+}
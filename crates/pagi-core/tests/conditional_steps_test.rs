@@ -0,0 +1,106 @@
+//! Conditional plan steps (`when { path, equals }`) evaluated against the previous step's output.
+
+mod common;
+
+use common::{ctx, orchestrator_with_plans, CaptureAudit};
+use pagi_core::{
+    AgentSkill, Goal, Orchestrator, PlanStep, SkillRegistry, StepCondition, TenantContext,
+};
+use std::sync::{Arc, Mutex};
+
+/// Classifies `payload.text` as positive or negative.
+struct Sentiment;
+
+#[async_trait::async_trait]
+impl AgentSkill for Sentiment {
+    fn name(&self) -> &str {
+        "Sentiment"
+    }
+
+    async fn execute(
+        &self,
+        _ctx: &TenantContext,
+        payload: Option<serde_json::Value>,
+    ) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>> {
+        let text = payload
+            .as_ref()
+            .and_then(|p| p.get("text"))
+            .and_then(|v| v.as_str())
+            .unwrap_or("");
+        let sentiment = if text.contains("great") { "positive" } else { "negative" };
+        Ok(serde_json::json!({ "sentiment": sentiment }))
+    }
+}
+
+/// Returns `{ "handled_by": <name> }`.
+struct Handler(&'static str);
+
+#[async_trait::async_trait]
+impl AgentSkill for Handler {
+    fn name(&self) -> &str {
+        self.0
+    }
+
+    async fn execute(
+        &self,
+        _ctx: &TenantContext,
+        _payload: Option<serde_json::Value>,
+    ) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(serde_json::json!({ "handled_by": self.0 }))
+    }
+}
+
+fn orchestrator(trace: Arc<Mutex<Option<serde_json::Value>>>) -> Orchestrator {
+    let mut registry = SkillRegistry::new();
+    registry.register(Arc::new(Sentiment));
+    registry.register(Arc::new(Handler("Closer")));
+    registry.register(Arc::new(Handler("Escalate")));
+    registry.register(Arc::new(CaptureAudit(trace)));
+    let steps = vec![
+        PlanStep::new("Sentiment"),
+        PlanStep::new("Closer").with_condition(StepCondition::new("/sentiment", serde_json::json!("positive"))),
+        PlanStep::new("Escalate").with_condition(StepCondition::new("/sentiment", serde_json::json!("negative"))),
+    ];
+    orchestrator_with_plans(registry, [("triage", steps)])
+}
+
+async fn run(text: &str) -> (serde_json::Value, serde_json::Value) {
+    let trace = Arc::new(Mutex::new(None));
+    let orch = orchestrator(Arc::clone(&trace));
+    let out = orch
+        .dispatch(
+            &ctx(),
+            Goal::AutonomousGoal {
+                intent: "triage".into(),
+                context: Some(serde_json::json!({ "text": text })),
+            },
+        )
+        .await
+        .expect("conditional chain should succeed");
+    let trace = trace.lock().unwrap().clone().expect("thought log recorded");
+    (out, trace)
+}
+
+#[tokio::test]
+async fn positive_branch_runs_closer_and_skips_escalate() {
+    let (out, trace) = run("this is great").await;
+    assert_eq!(out["handled_by"], "Closer");
+
+    let steps = trace["steps"].as_array().unwrap();
+    assert_eq!(steps.len(), 3);
+    assert_eq!(steps[1]["branch"]["taken"], true);
+    assert_eq!(steps[1]["branch"]["actual"], "positive");
+    assert_eq!(steps[2]["status"], "skipped");
+    assert_eq!(steps[2]["branch"]["taken"], false);
+    assert_eq!(steps[2]["branch"]["equals"], "negative");
+}
+
+#[tokio::test]
+async fn negative_branch_evaluates_against_last_executed_output() {
+    // Closer is skipped, so Escalate's condition still sees Sentiment's output.
+    let (out, trace) = run("meh").await;
+    assert_eq!(out["handled_by"], "Escalate");
+    let steps = trace["steps"].as_array().unwrap();
+    assert_eq!(steps[1]["status"], "skipped");
+    assert_eq!(steps[2]["branch"]["taken"], true);
+}
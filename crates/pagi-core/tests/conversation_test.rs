@@ -0,0 +1,125 @@
+//! Conversation sessions in KB_CHRONOS: ordered turns per (tenant, agent, session) and the
+//! context window of recent plus relevant older turns, and memory digests of older turns.
+
+use pagi_core::{ConversationManager, KnowledgeStore, CONVERSATION_DIGEST_PROMPT};
+use std::sync::Arc;
+
+#[test]
+fn turns_are_numbered_per_session_and_read_back_in_order() {
+    let manager = ConversationManager::new(Arc::new(KnowledgeStore::open_temporary().unwrap()));
+    for i in 1..=3 {
+        let turn = manager.record_turn("acme", "sales", "s1", &format!("question {}", i), "answer").unwrap();
+        assert_eq!(turn.turn, i);
+    }
+    manager.record_turn("acme", "support", "s1", "other agent", "answer").unwrap();
+    manager.record_turn("globex", "sales", "s1", "other tenant", "answer").unwrap();
+
+    let history = manager.history("acme", "sales", "s1", 2).unwrap();
+    assert_eq!(history.iter().map(|t| t.user.as_str()).collect::<Vec<_>>(), vec!["question 2", "question 3"]);
+    let session = manager.session("acme", "sales", "s1").unwrap();
+    assert_eq!(session.turns, 3);
+    assert!(session.created_at_ms <= session.updated_at_ms);
+    assert_eq!(manager.session("acme", "support", "s1").unwrap().turns, 1);
+    assert!(manager.session("acme", "sales", "missing").is_none());
+    assert!(manager.record_turn("acme", "sales", "bad/id", "x", "y").is_err());
+}
+
+#[test]
+fn context_mixes_recent_turns_with_relevant_older_ones() {
+    let store = Arc::new(KnowledgeStore::open_temporary().unwrap());
+    let manager = ConversationManager::new(Arc::clone(&store))
+        .with_context_turns(3)
+        .with_memory_weights((2.0, 1.0));
+    manager.record_turn("acme", "sales", "old", "Our warehouse is in Rotterdam", "Got it").unwrap();
+    for i in 1..=4 {
+        manager.record_turn("acme", "sales", "now", &format!("step {}", i), "ok").unwrap();
+    }
+    manager.record_turn("acme", "support", "x", "Rotterdam office hours", "9 to 5").unwrap();
+
+    let context = manager.context("acme", "sales", "now", "Ship it from Rotterdam?").unwrap();
+    assert_eq!(context.recent.iter().map(|t| t.turn).collect::<Vec<_>>(), vec![3, 4]);
+    assert_eq!(context.relevant.len(), 1);
+    assert_eq!(context.relevant[0].session_id, "old");
+    let section = context.to_prompt_section();
+    assert!(section.starts_with("Relevant earlier conversation:\nUser: Our warehouse is in Rotterdam"));
+    assert!(section.ends_with("User: step 4\nAssistant: ok"));
+
+    // Long-term weight 0: only the recent window.
+    let recent_only = ConversationManager::new(store).with_memory_weights((1.0, 0.0));
+    let context = recent_only.context("acme", "sales", "fresh", "Rotterdam").unwrap();
+    assert!(context.is_empty());
+    assert_eq!(context.to_prompt_section(), "");
+}
+
+#[tokio::test]
+async fn consolidation_digests_old_turns_and_context_prefers_the_digest() {
+    let store = Arc::new(KnowledgeStore::open_temporary().unwrap());
+    let manager = ConversationManager::new(Arc::clone(&store))
+        .with_context_turns(4)
+        .with_memory_weights((1.0, 1.0))
+        .with_digest_policy(3, 2);
+    manager.record_turn("acme", "sales", "s1", "Our warehouse is in Rotterdam", "Got it").unwrap();
+    for i in 2..=6 {
+        manager.record_turn("acme", "sales", "s1", &format!("step {}", i), "ok").unwrap();
+    }
+    manager.record_turn("acme", "sales", "short", "hello", "hi").unwrap();
+
+    let pending = manager.pending_digests(10).unwrap();
+    assert_eq!(pending.len(), 1);
+    assert_eq!(pending[0].turns.iter().map(|t| t.turn).collect::<Vec<_>>(), vec![1, 2, 3]);
+
+    let digests = manager
+        .consolidate(10, |session, prompt| async move {
+            assert_eq!(session.session_id, "s1");
+            assert!(prompt.contains("User: Our warehouse is in Rotterdam\nAssistant: Got it"));
+            Ok::<_, Box<dyn std::error::Error + Send + Sync>>("- Warehouse: Rotterdam".to_string())
+        })
+        .await
+        .unwrap();
+    assert_eq!(digests.len(), 1);
+    assert_eq!((digests[0].first_turn, digests[0].last_turn), (1, 3));
+    assert_eq!(digests[0].turn_keys.len(), 3);
+    assert_eq!(manager.session("acme", "sales", "s1").unwrap().digested_through, 3);
+    // The next batch (4..=6) would reach into the kept-recent turns.
+    assert!(manager.pending_digests(10).unwrap().is_empty());
+    // A stale batch is not stored twice.
+    assert!(manager.store_digest(&pending[0], "again").is_err());
+    assert_eq!(manager.digests("acme", "sales", "s1").unwrap(), digests);
+
+    // The digested turn 1 is replaced by its digest; the originals stay readable.
+    let context = manager.context("acme", "sales", "s1", "Where is the warehouse in Rotterdam?").unwrap();
+    assert_eq!(context.recent.iter().map(|t| t.turn).collect::<Vec<_>>(), vec![5, 6]);
+    assert!(context.relevant.is_empty());
+    assert_eq!(context.digests, digests);
+    assert!(context
+        .to_prompt_section()
+        .starts_with("Summaries of earlier conversation:\n(turns 1-3) - Warehouse: Rotterdam"));
+    assert_eq!(manager.history("acme", "sales", "s1", 10).unwrap().len(), 6);
+}
+
+#[tokio::test]
+async fn failed_summaries_are_retried_later() {
+    let store = Arc::new(KnowledgeStore::open_temporary().unwrap());
+    store
+        .put_prompt(CONVERSATION_DIGEST_PROMPT, "Digest:\n{{transcript}}", None)
+        .unwrap();
+    let manager = ConversationManager::new(store).with_digest_policy(2, 0);
+    for i in 1..=2 {
+        manager.record_turn("acme", "sales", "s1", &format!("q{}", i), "a").unwrap();
+    }
+    let failed = manager
+        .consolidate(10, |_, _| async { Err::<String, _>("model unavailable".into()) })
+        .await
+        .unwrap();
+    assert!(failed.is_empty());
+    assert_eq!(manager.session("acme", "sales", "s1").unwrap().digested_through, 0);
+
+    let digests = manager
+        .consolidate(10, |_, prompt| async move {
+            assert!(prompt.starts_with("Digest:\nUser: q1"));
+            Ok::<_, Box<dyn std::error::Error + Send + Sync>>("summary".to_string())
+        })
+        .await
+        .unwrap();
+    assert_eq!(digests.len(), 1);
+}
@@ -0,0 +1,164 @@
+//! Deadlines: a request's time budget reaches skills through the context and stops the goal
+//! (queued, between steps or mid-skill) with `DeadlineExceeded` once it passes.
+
+mod common;
+
+use common::orchestrator_with_plans;
+use pagi_core::{
+    translate_error, AgentSkill, DeadlineExceeded, DispatchQueue, DispatchQueueConfig, Goal,
+    Orchestrator, PlanStep, SkillRegistry, TenantContext,
+};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+/// Sleeps, then reports the time it had left.
+struct Slow(&'static str, u64);
+
+#[async_trait::async_trait]
+impl AgentSkill for Slow {
+    fn name(&self) -> &str {
+        self.0
+    }
+
+    async fn execute(
+        &self,
+        ctx: &TenantContext,
+        _payload: Option<serde_json::Value>,
+    ) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>> {
+        let remaining_ms = ctx.remaining().map(|r| r.as_millis() as u64);
+        tokio::time::sleep(Duration::from_millis(self.1)).await;
+        Ok(serde_json::json!({ "skill": self.0, "remaining_ms": remaining_ms }))
+    }
+
+    async fn execute_stream(
+        &self,
+        _ctx: &TenantContext,
+        _payload: Option<serde_json::Value>,
+    ) -> Result<mpsc::Receiver<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>> {
+        let (tx, rx) = mpsc::channel(1);
+        let delay = self.1;
+        tokio::spawn(async move {
+            for i in 0.. {
+                tokio::time::sleep(Duration::from_millis(delay)).await;
+                if tx.send(serde_json::json!({ "delta": i.to_string() })).await.is_err() {
+                    return;
+                }
+            }
+        });
+        Ok(rx)
+    }
+}
+
+fn ctx(timeout: Option<Duration>) -> TenantContext {
+    let ctx = TenantContext::new("test");
+    match timeout {
+        Some(t) => ctx.with_timeout(t),
+        None => ctx,
+    }
+}
+
+fn orchestrator() -> Orchestrator {
+    let mut registry = SkillRegistry::new();
+    registry.register(Arc::new(Slow("Fast", 10)));
+    registry.register(Arc::new(Slow("Slow", 5_000)));
+    let steps = vec![PlanStep::new("Fast"), PlanStep::new("Slow"), PlanStep::new("Fast")];
+    orchestrator_with_plans(registry, [("long", steps)])
+}
+
+fn skill(name: &str) -> Goal {
+    Goal::ExecuteSkill {
+        name: name.to_string(),
+        payload: None,
+    }
+}
+
+#[test]
+fn context_helpers_keep_the_earliest_deadline() {
+    let none = ctx(None);
+    assert!(none.remaining().is_none());
+    assert!(!none.deadline_exceeded());
+    assert_eq!(none.bounded_timeout(Duration::from_secs(30)), Duration::from_secs(30));
+
+    let short = ctx(Some(Duration::from_secs(2)));
+    assert!(short.bounded_timeout(Duration::from_secs(30)) <= Duration::from_secs(2));
+    assert_eq!(short.bounded_timeout(Duration::from_millis(5)), Duration::from_millis(5));
+    // A later budget does not extend the deadline.
+    assert_eq!(short.clone().with_timeout(Duration::from_secs(60)).deadline_ms, short.deadline_ms);
+
+    let passed = ctx(Some(Duration::ZERO));
+    assert!(passed.deadline_exceeded());
+    assert_eq!(passed.remaining(), Some(Duration::ZERO));
+}
+
+#[tokio::test]
+async fn skills_see_the_remaining_budget() {
+    let result = orchestrator()
+        .dispatch(&ctx(Some(Duration::from_secs(10))), skill("Fast"))
+        .await
+        .unwrap();
+    let remaining = result["remaining_ms"].as_u64().unwrap();
+    assert!(remaining > 0 && remaining <= 10_000, "remaining {}", remaining);
+}
+
+#[tokio::test]
+async fn deadline_stops_a_plan_mid_step() {
+    let goal = Goal::AutonomousGoal {
+        intent: "long".to_string(),
+        context: None,
+    };
+    let started = std::time::Instant::now();
+    let err = orchestrator()
+        .dispatch(&ctx(Some(Duration::from_millis(200))), goal)
+        .await
+        .unwrap_err();
+    assert!(started.elapsed() < Duration::from_secs(2));
+    let exceeded = err.downcast_ref::<DeadlineExceeded>().expect("DeadlineExceeded");
+    assert_eq!(exceeded.completed_steps, 1);
+    let user_error = translate_error(err.as_ref());
+    assert_eq!(user_error.code, "deadline_exceeded");
+    assert!(user_error.retryable);
+}
+
+#[tokio::test]
+async fn deadline_ends_a_stream() {
+    let mut rx = orchestrator()
+        .dispatch_stream(&ctx(Some(Duration::from_millis(150))), skill("Fast"))
+        .await
+        .unwrap();
+    let mut last = serde_json::Value::Null;
+    while let Some(chunk) = tokio::time::timeout(Duration::from_secs(2), rx.recv()).await.unwrap() {
+        last = chunk;
+    }
+    assert_eq!(last["status"], "deadline_exceeded");
+}
+
+#[tokio::test]
+async fn deadline_applies_while_waiting_in_the_queue() {
+    let orchestrator = Arc::new(orchestrator().with_dispatch_queue(Arc::new(DispatchQueue::new(
+        DispatchQueueConfig {
+            max_concurrent: 1,
+            ..Default::default()
+        },
+    ))));
+    let busy = {
+        let orchestrator = Arc::clone(&orchestrator);
+        tokio::spawn(async move { orchestrator.dispatch(&ctx(None), skill("Slow")).await })
+    };
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    let started = std::time::Instant::now();
+    let err = orchestrator
+        .dispatch(&ctx(Some(Duration::from_millis(100))), skill("Fast"))
+        .await
+        .unwrap_err();
+    assert!(started.elapsed() < Duration::from_secs(2));
+    assert_eq!(err.downcast_ref::<DeadlineExceeded>().unwrap().completed_steps, 0);
+    busy.abort();
+}
+
+#[tokio::test]
+async fn no_deadline_runs_to_completion() {
+    let result = orchestrator().dispatch(&ctx(None), skill("Fast")).await.unwrap();
+    assert!(result["remaining_ms"].is_null());
+}
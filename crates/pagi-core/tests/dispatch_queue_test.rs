@@ -0,0 +1,111 @@
+//! Dispatch queue: concurrency cap, bounded queue, and per-tenant rate limits.
+
+use pagi_core::{
+    AgentSkill, DispatchQueue, DispatchQueueConfig, Goal, Orchestrator, RateLimit, SkillRegistry, TenantContext,
+    Throttled,
+};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Tracks how many executions overlap.
+struct Tracked {
+    active: Arc<AtomicUsize>,
+    peak: Arc<AtomicUsize>,
+}
+
+#[async_trait::async_trait]
+impl AgentSkill for Tracked {
+    fn name(&self) -> &str {
+        "Work"
+    }
+
+    async fn execute(
+        &self,
+        _ctx: &TenantContext,
+        _payload: Option<serde_json::Value>,
+    ) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>> {
+        let now = self.active.fetch_add(1, Ordering::SeqCst) + 1;
+        self.peak.fetch_max(now, Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        self.active.fetch_sub(1, Ordering::SeqCst);
+        Ok(serde_json::json!({ "ok": true }))
+    }
+}
+
+fn work() -> Goal {
+    Goal::ExecuteSkill {
+        name: "Work".to_string(),
+        payload: None,
+    }
+}
+
+fn orchestrator(config: DispatchQueueConfig) -> (Arc<Orchestrator>, Arc<AtomicUsize>) {
+    let peak = Arc::new(AtomicUsize::new(0));
+    let mut registry = SkillRegistry::new();
+    registry.register(Arc::new(Tracked {
+        active: Arc::new(AtomicUsize::new(0)),
+        peak: Arc::clone(&peak),
+    }));
+    let orchestrator =
+        Orchestrator::new(Arc::new(registry)).with_dispatch_queue(Arc::new(DispatchQueue::new(config)));
+    (Arc::new(orchestrator), peak)
+}
+
+#[tokio::test]
+async fn concurrency_is_capped_and_waiters_queue() {
+    let (orchestrator, peak) = orchestrator(DispatchQueueConfig {
+        max_concurrent: 2,
+        max_queued: 16,
+        ..Default::default()
+    });
+    let runs = (0..6).map(|_| {
+        let orchestrator = Arc::clone(&orchestrator);
+        tokio::spawn(async move { orchestrator.dispatch(&TenantContext::new("acme"), work()).await })
+    });
+    for run in futures_util::future::join_all(runs).await {
+        assert!(run.unwrap().is_ok());
+    }
+    assert_eq!(peak.load(Ordering::SeqCst), 2);
+    let stats = orchestrator.dispatch_queue().unwrap().stats();
+    assert_eq!((stats.running, stats.queued, stats.admitted_total), (0, 0, 6));
+}
+
+#[tokio::test]
+async fn full_queue_rejects() {
+    let (orchestrator, _) = orchestrator(DispatchQueueConfig {
+        max_concurrent: 1,
+        max_queued: 0,
+        ..Default::default()
+    });
+    let first = {
+        let orchestrator = Arc::clone(&orchestrator);
+        tokio::spawn(async move { orchestrator.dispatch(&TenantContext::new("acme"), work()).await })
+    };
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    let err = orchestrator.dispatch(&TenantContext::new("beta"), work()).await.unwrap_err();
+    assert_eq!(err.downcast_ref::<Throttled>().unwrap().reason, "queue_full");
+    assert!(first.await.unwrap().is_ok());
+}
+
+#[tokio::test]
+async fn tenant_over_budget_is_rate_limited() {
+    let mut tenant_limits = HashMap::new();
+    tenant_limits.insert("acme".to_string(), RateLimit { per_minute: 60, burst: 2 });
+    let (orchestrator, _) = orchestrator(DispatchQueueConfig {
+        tenant_limits,
+        ..Default::default()
+    });
+
+    assert!(orchestrator.dispatch(&TenantContext::new("acme"), work()).await.is_ok());
+    assert!(orchestrator.dispatch(&TenantContext::new("acme"), work()).await.is_ok());
+    let err = orchestrator.dispatch(&TenantContext::new("acme"), work()).await.unwrap_err();
+    let throttled = err.downcast_ref::<Throttled>().expect("Throttled");
+    assert_eq!(throttled.reason, "rate_limited");
+    assert_eq!(throttled.retry_after_secs(), 1);
+
+    // Other tenants have no limit configured.
+    assert!(orchestrator.dispatch(&TenantContext::new("beta"), work()).await.is_ok());
+    assert_eq!(orchestrator.dispatch_queue().unwrap().stats().rate_limited_total, 1);
+}
@@ -0,0 +1,119 @@
+//! Streaming dispatch: `AgentSkill::execute_stream` and `Orchestrator::dispatch_stream`.
+
+mod common;
+
+use common::ctx;
+use pagi_core::{AgentSkill, Goal, Orchestrator, SkillRegistry, TenantContext};
+use std::sync::Arc;
+use tokio::sync::mpsc;
+
+/// Non-streaming skill: relies on the default single-chunk `execute_stream`.
+struct Echo;
+
+#[async_trait::async_trait]
+impl AgentSkill for Echo {
+    fn name(&self) -> &str {
+        "Echo"
+    }
+
+    async fn execute(
+        &self,
+        _ctx: &TenantContext,
+        payload: Option<serde_json::Value>,
+    ) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(serde_json::json!({ "status": "ok", "echo": payload }))
+    }
+}
+
+/// Streams three progress chunks before its final result.
+struct Counter;
+
+#[async_trait::async_trait]
+impl AgentSkill for Counter {
+    fn name(&self) -> &str {
+        "Counter"
+    }
+
+    async fn execute(
+        &self,
+        _ctx: &TenantContext,
+        _payload: Option<serde_json::Value>,
+    ) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(serde_json::json!({ "status": "ok", "count": 3 }))
+    }
+
+    async fn execute_stream(
+        &self,
+        _ctx: &TenantContext,
+        _payload: Option<serde_json::Value>,
+    ) -> Result<mpsc::Receiver<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>> {
+        let (tx, rx) = mpsc::channel(8);
+        tokio::spawn(async move {
+            for i in 1..=3 {
+                let _ = tx.send(serde_json::json!({ "progress": i })).await;
+            }
+            let _ = tx.send(serde_json::json!({ "status": "ok", "count": 3, "done": true })).await;
+        });
+        Ok(rx)
+    }
+}
+
+fn orchestrator() -> Orchestrator {
+    let mut registry = SkillRegistry::new();
+    registry.register(Arc::new(Echo));
+    registry.register(Arc::new(Counter));
+    Orchestrator::new(Arc::new(registry))
+}
+
+async fn collect(mut rx: mpsc::Receiver<serde_json::Value>) -> Vec<serde_json::Value> {
+    let mut out = Vec::new();
+    while let Some(chunk) = rx.recv().await {
+        out.push(chunk);
+    }
+    out
+}
+
+#[tokio::test]
+async fn default_execute_stream_yields_single_final_chunk() {
+    let rx = orchestrator()
+        .dispatch_stream(
+            &ctx(),
+            Goal::ExecuteSkill { name: "Echo".into(), payload: Some(serde_json::json!({ "x": 1 })) },
+        )
+        .await
+        .unwrap();
+    let chunks = collect(rx).await;
+    assert_eq!(chunks.len(), 1);
+    assert_eq!(chunks[0]["echo"]["x"], 1);
+}
+
+#[tokio::test]
+async fn dispatch_stream_forwards_skill_chunks() {
+    let rx = orchestrator()
+        .dispatch_stream(&ctx(), Goal::ExecuteSkill { name: "Counter".into(), payload: None })
+        .await
+        .unwrap();
+    let chunks = collect(rx).await;
+    assert_eq!(chunks.len(), 4);
+    assert_eq!(chunks[0]["progress"], 1);
+    assert_eq!(chunks[3]["done"], true);
+}
+
+#[tokio::test]
+async fn dispatch_stream_respects_disabled_skills_and_unknown_names() {
+    let orch = orchestrator();
+    assert!(orch
+        .dispatch_stream(&ctx(), Goal::ExecuteSkill { name: "Nope".into(), payload: None })
+        .await
+        .is_err());
+
+    orch.pagi_apply_control_signal(pagi_core::ControlPanelMessage::SkillsEnabled(false));
+    let chunks = collect(
+        orch.dispatch_stream(&ctx(), Goal::ExecuteSkill { name: "Counter".into(), payload: None })
+            .await
+            .unwrap(),
+    )
+    .await;
+    assert_eq!(chunks.len(), 1);
+    assert_eq!(chunks[0]["status"], "skills_disabled");
+}
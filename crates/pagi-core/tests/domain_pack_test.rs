@@ -0,0 +1,133 @@
+//! Domain packs: the shipped packs in `config/packs/` load and validate, and installs upgrade,
+//! refuse downgrades and revert cleanly.
+
+use pagi_core::{
+    BlueprintRegistry, DomainPack, KbType, KnowledgeStore, PackDowngrade, PackInstallStatus, PackRegistry,
+};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+fn config_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../config")
+}
+
+fn gateway_skills() -> Vec<String> {
+    [
+        "ModelRouter",
+        "BioGateSync",
+        "EthosSync",
+        "OikosTaskGovernor",
+        "ReflectShadow",
+        "ParseInboundMessage",
+    ]
+    .iter()
+    .map(|s| s.to_string())
+    .collect()
+}
+
+fn pack(version: &str, blueprints: serde_json::Value, kb: serde_json::Value) -> DomainPack {
+    serde_json::from_value(serde_json::json!({
+        "name": "sales",
+        "version": version,
+        "blueprints": blueprints,
+        "templates": { "persona": format!("Sales persona v{}", version) },
+        "kb": kb,
+    }))
+    .unwrap()
+}
+
+#[test]
+fn shipped_packs_load_and_validate() {
+    let dir = DomainPack::load_path(config_dir().join("packs/local-services-sales")).unwrap();
+    assert_eq!(dir.name, "local-services-sales");
+    assert!(dir.blueprints.contains_key("qualify lead"), "blueprint.json intents are merged");
+    assert!(dir.templates.persona.is_some(), "templates/persona.md is read");
+
+    let file = DomainPack::load_path(config_dir().join("packs/personal-wellbeing.toml")).unwrap();
+    assert!(file.templates.auto_reply.is_some());
+
+    let skills = gateway_skills();
+    for pack in [dir, file] {
+        assert_eq!(pack.validate(Some(&skills)), Vec::<String>::new(), "{}", pack.name);
+    }
+}
+
+#[test]
+fn install_upgrade_downgrade_and_uninstall() {
+    let store = Arc::new(KnowledgeStore::open_temporary().unwrap());
+    store.insert(KbType::Logos.slot_id(), "pricing", b"original").unwrap();
+    let file = std::env::temp_dir().join(format!("pagi_pack_blueprint_{}.json", std::process::id()));
+    std::fs::write(&file, r#"{ "intents": { "respond to lead": ["ModelRouter"] } }"#).unwrap();
+    let blueprint = BlueprintRegistry::try_load_json_path(&file)
+        .unwrap()
+        .with_store(Arc::clone(&store));
+    let packs = PackRegistry::new(Arc::clone(&store));
+    let skills = gateway_skills();
+
+    let v1 = pack(
+        "1.0",
+        serde_json::json!({
+            "qualify lead": ["ParseInboundMessage", { "intent": "follow up" }],
+            "follow up": ["ModelRouter"],
+        }),
+        serde_json::json!([
+            { "slot": 3, "key": "pricing", "value": { "callout_fee": 80 } },
+            { "slot": 3, "key": "areas/{agent_id}", "value": "north side" },
+        ]),
+    );
+    let report = packs.install(&v1, "agent-a", &blueprint, Some(&skills), false).unwrap();
+    assert_eq!(report.status, PackInstallStatus::Installed);
+    assert_eq!(report.kb_keys, 3, "persona + two seeds");
+    assert!(blueprint.plan_for_intent("qualify lead").is_some(), "sub-plans may reference each other in any order");
+    assert_eq!(store.get(3, "areas/agent-a").unwrap().unwrap(), b"north side");
+    assert_eq!(
+        store.get(KbType::Pneuma.slot_id(), &pagi_core::agent_persona_key("agent-a")).unwrap().unwrap(),
+        b"Sales persona v1.0"
+    );
+
+    let again = packs.install(&v1, "agent-a", &blueprint, Some(&skills), false).unwrap();
+    assert_eq!(again.status, PackInstallStatus::Unchanged);
+
+    let v2 = pack(
+        "1.1.0",
+        serde_json::json!({ "qualify lead": ["ParseInboundMessage", "ModelRouter"] }),
+        serde_json::json!([{ "slot": 3, "key": "pricing", "value": { "callout_fee": 95 } }]),
+    );
+    let report = packs.install(&v2, "agent-a", &blueprint, Some(&skills), false).unwrap();
+    assert_eq!(report.status, PackInstallStatus::Upgraded);
+    assert_eq!(report.previous_version.as_deref(), Some("1.0"));
+    assert_eq!(report.removed_intents, ["follow up"]);
+    assert_eq!(report.removed_kb_keys, 1);
+    assert!(blueprint.plan_for_intent("follow up").is_none());
+    assert!(store.get(3, "areas/agent-a").unwrap().is_none());
+
+    let err = packs.install(&v1, "agent-a", &blueprint, Some(&skills), false).unwrap_err();
+    assert!(err.downcast_ref::<PackDowngrade>().is_some(), "{}", err);
+
+    let unknown = pack("2.0", serde_json::json!({ "x": ["NoSuchSkill"] }), serde_json::json!([]));
+    let err = packs.install(&unknown, "agent-a", &blueprint, Some(&skills), false).unwrap_err();
+    assert!(err.to_string().contains("unregistered skills: NoSuchSkill"), "{}", err);
+
+    // A pack intent shadowing a file intent falls back to the file on uninstall; seeds restore
+    // the value they replaced.
+    let shadowing = pack(
+        "1.1.0",
+        serde_json::json!({ "respond to lead": ["ParseInboundMessage", "ModelRouter"] }),
+        serde_json::json!([{ "slot": 3, "key": "pricing", "value": "override" }]),
+    );
+    packs.install(&shadowing, "agent-b", &blueprint, Some(&skills), false).unwrap();
+    assert_eq!(blueprint.plan_for_intent("respond to lead").unwrap().steps.len(), 2);
+
+    packs.uninstall("agent-a", "sales", &blueprint).unwrap().unwrap();
+    assert_eq!(
+        store.get(3, "pricing").unwrap().unwrap(),
+        b"override",
+        "key still owned by agent-b's install is left alone"
+    );
+    packs.uninstall("agent-b", "sales", &blueprint).unwrap().unwrap();
+    assert_eq!(store.get(3, "pricing").unwrap().unwrap(), b"original");
+    assert!(blueprint.plan_for_intent("qualify lead").is_none());
+    assert_eq!(blueprint.plan_for_intent("respond to lead").unwrap().steps.len(), 1);
+    assert!(packs.list().unwrap().is_empty());
+    let _ = std::fs::remove_file(file);
+}
@@ -0,0 +1,105 @@
+//! Dynamic planner fallback: unknown intents get an LLM-synthesized, registry-validated plan
+//! that is stored in KB-5 as a blueprint candidate.
+
+mod common;
+
+use common::ctx;
+use pagi_core::{
+    AgentSkill, BlueprintRegistry, Goal, KbType, KnowledgeStore, Orchestrator, SkillRegistry, TenantContext,
+    BLUEPRINT_CANDIDATE_PREFIX,
+};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Stands in for ModelRouter: replies to planning prompts with a fixed text.
+struct ScriptedRouter(&'static str);
+
+#[async_trait::async_trait]
+impl AgentSkill for ScriptedRouter {
+    fn name(&self) -> &str {
+        "ModelRouter"
+    }
+
+    async fn execute(
+        &self,
+        _ctx: &TenantContext,
+        payload: Option<serde_json::Value>,
+    ) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>> {
+        let prompt = payload
+            .as_ref()
+            .and_then(|p| p.get("prompt"))
+            .and_then(|v| v.as_str())
+            .unwrap_or("");
+        if prompt.starts_with("You are the PAGI planner") {
+            return Ok(serde_json::json!({ "status": "ok", "generated": self.0 }));
+        }
+        Ok(serde_json::json!({ "status": "ok", "generated": format!("answer to: {}", prompt) }))
+    }
+}
+
+struct Greeter;
+
+#[async_trait::async_trait]
+impl AgentSkill for Greeter {
+    fn name(&self) -> &str {
+        "Greeter"
+    }
+
+    async fn execute(
+        &self,
+        _ctx: &TenantContext,
+        _payload: Option<serde_json::Value>,
+    ) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(serde_json::json!({ "status": "ok", "greeting": "hello" }))
+    }
+}
+
+fn orchestrator(reply: &'static str, knowledge: Arc<KnowledgeStore>) -> Orchestrator {
+    let mut registry = SkillRegistry::new();
+    registry.register(Arc::new(ScriptedRouter(reply)));
+    registry.register(Arc::new(Greeter));
+    Orchestrator::with_blueprint(
+        Arc::new(registry),
+        Arc::new(BlueprintRegistry::from_intents(HashMap::new())),
+    )
+    .with_knowledge(knowledge)
+}
+
+#[tokio::test]
+async fn unknown_intent_is_planned_executed_and_stored_as_candidate() {
+    let dir = tempfile::tempdir().unwrap();
+    let knowledge = Arc::new(KnowledgeStore::open_path(dir.path()).unwrap());
+    let orch = orchestrator(r#"Plan: ["greeter", "ModelRouter"]"#, Arc::clone(&knowledge));
+
+    let out = orch
+        .dispatch(&ctx(), Goal::AutonomousGoal { intent: "Say Hello".into(), context: None })
+        .await
+        .unwrap();
+    assert_eq!(out["plan_source"], "llm");
+    assert_eq!(out["plan_steps"], serde_json::json!(["Greeter", "ModelRouter"]));
+
+    let key = format!("{}say hello", BLUEPRINT_CANDIDATE_PREFIX);
+    let stored = knowledge.get(KbType::Techne.slot_id(), &key).unwrap().expect("candidate stored");
+    let candidate: serde_json::Value = serde_json::from_slice(&stored).unwrap();
+    assert_eq!(candidate["status"], "candidate");
+    assert_eq!(candidate["steps"], serde_json::json!(["Greeter", "ModelRouter"]));
+}
+
+#[tokio::test]
+async fn plans_with_unregistered_skills_are_rejected() {
+    let dir = tempfile::tempdir().unwrap();
+    let knowledge = Arc::new(KnowledgeStore::open_path(dir.path()).unwrap());
+    let orch = orchestrator(r#"["Greeter", "DeleteEverything"]"#, Arc::clone(&knowledge));
+
+    let err = orch
+        .dispatch(&ctx(), Goal::AutonomousGoal { intent: "wipe".into(), context: None })
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("unknown intent: wipe"));
+    assert!(err.to_string().contains("DeleteEverything"));
+    assert!(knowledge
+        .scan_keys(KbType::Techne.slot_id())
+        .unwrap()
+        .iter()
+        .all(|k| !k.starts_with(BLUEPRINT_CANDIDATE_PREFIX)));
+}
@@ -0,0 +1,257 @@
+//! Ethos policy rules (v2): scoped conditions, priorities, versioning, and the orchestrator gate.
+
+mod common;
+
+use common::ctx;
+use pagi_core::{
+    ActiveEthosPolicy, AgentSkill, BlueprintRegistry, EthosBlocked, Goal, KnowledgeStore, OnBlocked, Orchestrator, Plan,
+    PlanStep, PolicyRecord, PolicyRule, PolicyRules, RuleAction, SkillRegistry, StepPolicy, TenantContext,
+};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+
+struct Counted(&'static str, Arc<AtomicU32>);
+
+#[async_trait::async_trait]
+impl AgentSkill for Counted {
+    fn name(&self) -> &str {
+        self.0
+    }
+
+    async fn execute(
+        &self,
+        _ctx: &TenantContext,
+        _payload: Option<serde_json::Value>,
+    ) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>> {
+        self.1.fetch_add(1, Ordering::SeqCst);
+        Ok(serde_json::json!({ "status": "ok" }))
+    }
+}
+
+fn rule(json: serde_json::Value) -> PolicyRule {
+    serde_json::from_value(json).unwrap()
+}
+
+fn rules() -> PolicyRules {
+    PolicyRules::new(
+        vec![
+            rule(serde_json::json!({
+                "id": "no-secrets",
+                "description": "credentials in content",
+                "conditions": [{ "path": "$.content", "regex": "(?i)api[_-]?key\\s*=\\s*\\S+" }],
+                "action": "deny",
+                "priority": 10
+            })),
+            rule(serde_json::json!({
+                "id": "sandbox-allowlist",
+                "skills": ["write_sandbox_file"],
+                "conditions": [{ "path": "$.path", "one_of": ["notes.txt", "todo.txt"], "negate": true }],
+                "action": "deny"
+            })),
+            rule(serde_json::json!({
+                "id": "payments",
+                "skills": ["Pay*"],
+                "action": "require_approval",
+                "priority": 5
+            })),
+            rule(serde_json::json!({
+                "id": "trusted-payments",
+                "skills": ["PayInternal"],
+                "conditions": [{ "path": "$.amount", "exists": true }],
+                "action": "allow",
+                "priority": 5
+            })),
+        ],
+        RuleAction::Allow,
+    )
+}
+
+#[test]
+fn rules_match_by_scope_condition_and_priority() {
+    let rules = rules();
+    rules.validate().unwrap();
+    let decide = |skill: &str, payload: serde_json::Value| rules.evaluate(skill, Some(&payload));
+
+    // Plain text mentioning a "token" is no longer over-blocked; an actual credential is.
+    assert!(decide("Echo", serde_json::json!({ "content": "rotate the token tomorrow" })).is_allowed());
+    let denied = decide("Echo", serde_json::json!({ "content": "API_KEY = sk-123" }));
+    assert_eq!(denied.action, RuleAction::Deny);
+    assert_eq!(denied.rule_id.as_deref(), Some("no-secrets"));
+    assert!(denied.reason.unwrap().contains("credentials in content"));
+
+    // Allowlist: only listed sandbox paths pass.
+    assert!(decide("write_sandbox_file", serde_json::json!({ "path": "notes.txt" })).is_allowed());
+    let outside = decide("Write_Sandbox_File", serde_json::json!({ "path": "secrets.env" }));
+    assert_eq!(outside.rule_id.as_deref(), Some("sandbox-allowlist"));
+
+    // Prefix scope; on equal priority the more restrictive action wins.
+    assert_eq!(decide("PaymentSend", serde_json::json!({})).action, RuleAction::RequireApproval);
+    assert_eq!(decide("PayInternal", serde_json::json!({ "amount": 5 })).action, RuleAction::RequireApproval);
+    // Higher priority beats the scope rules.
+    let both = decide("PaymentSend", serde_json::json!({ "content": "apikey=abc" }));
+    assert_eq!(both.rule_id.as_deref(), Some("no-secrets"));
+
+    // Default action applies when nothing matches; disabled rules never match.
+    let mut strict = rules.clone();
+    strict.default_action = RuleAction::Deny;
+    strict.rules[0].enabled = false;
+    assert_eq!(strict.evaluate("Echo", None).action, RuleAction::Deny);
+    assert!(strict.evaluate("Echo", None).rule_id.is_none());
+}
+
+#[test]
+fn validation_rejects_bad_rules() {
+    let mut bad = rules();
+    bad.rules[1].id = "no-secrets".to_string();
+    assert!(bad.validate().unwrap_err().to_string().contains("duplicate"));
+
+    let bad = PolicyRules::new(
+        vec![rule(serde_json::json!({ "id": "r", "conditions": [{ "regex": "(" }], "action": "deny" }))],
+        RuleAction::Allow,
+    );
+    assert!(bad.validate().unwrap_err().to_string().contains("bad regex"));
+
+    let bad = PolicyRules::new(
+        vec![rule(serde_json::json!({ "id": "r", "conditions": [{ "path": "content", "exists": true }], "action": "deny" }))],
+        RuleAction::Allow,
+    );
+    assert!(bad.validate().is_err());
+    let bad = PolicyRules::new(vec![rule(serde_json::json!({ "id": "a/b", "action": "deny" }))], RuleAction::Allow);
+    assert!(bad.validate().is_err());
+}
+
+#[test]
+fn rule_sets_are_versioned_and_fall_back_to_the_legacy_policy() {
+    let dir = tempfile::tempdir().unwrap();
+    let knowledge = KnowledgeStore::open_path(dir.path().join("kb")).unwrap();
+    knowledge.set_ethos_policy(&PolicyRecord::default()).unwrap();
+    let payload = serde_json::json!({ "content": "rotate the token tomorrow" });
+    let policy = knowledge.active_ethos_policy().unwrap();
+    assert!(matches!(policy, ActiveEthosPolicy::Legacy(_)));
+    assert!(!policy.evaluate("Echo", Some(&payload)).is_allowed());
+
+    let first = knowledge.put_policy_rules(&rules()).unwrap();
+    assert_eq!(first.version, 1);
+    assert!(first.updated_at_ms > 0);
+    let second = knowledge
+        .put_policy_rules(&PolicyRules {
+            note: Some("drop payments".to_string()),
+            ..PolicyRules::new(rules().rules[..2].to_vec(), RuleAction::Allow)
+        })
+        .unwrap();
+    assert_eq!(second.version, 2);
+    assert_eq!(knowledge.get_policy_rules().unwrap(), second);
+    assert_eq!(knowledge.get_policy_rules_version(1).unwrap().rules.len(), 4);
+    assert!(knowledge.active_ethos_policy().unwrap().evaluate("Echo", Some(&payload)).is_allowed());
+
+    let invalid = PolicyRules::new(vec![rule(serde_json::json!({ "id": "", "action": "deny" }))], RuleAction::Allow);
+    assert!(knowledge.put_policy_rules(&invalid).is_err());
+
+    // Clearing restores the legacy policy; the history stays and numbering continues.
+    assert!(knowledge.clear_policy_rules().unwrap());
+    assert!(matches!(knowledge.active_ethos_policy().unwrap(), ActiveEthosPolicy::Legacy(_)));
+    assert_eq!(knowledge.put_policy_rules(&rules()).unwrap().version, 3);
+    let versions: Vec<u32> = knowledge.policy_rule_versions().unwrap().iter().map(|v| v.version).collect();
+    assert_eq!(versions, [1, 2, 3]);
+}
+
+#[tokio::test]
+async fn orchestrator_gates_skill_calls_and_plan_steps() {
+    let dir = tempfile::tempdir().unwrap();
+    let knowledge = Arc::new(KnowledgeStore::open_path(dir.path().join("kb")).unwrap());
+    let calls = Arc::new(AtomicU32::new(0));
+    let mut registry = SkillRegistry::new();
+    registry.register(Arc::new(Counted("Echo", Arc::clone(&calls))));
+    registry.register(Arc::new(Counted("PaymentSend", Arc::clone(&calls))));
+    let mut plans = HashMap::new();
+    plans.insert("pay".to_string(), Plan { steps: vec![PlanStep::new("Echo"), PlanStep::new("PaymentSend")] });
+    plans.insert("echo".to_string(), Plan { steps: vec![PlanStep::new("Echo")] });
+    let orch = Orchestrator::with_blueprint(Arc::new(registry), Arc::new(BlueprintRegistry::from_plans(plans)))
+        .with_knowledge(Arc::clone(&knowledge));
+
+    // Without a rule set the legacy keyword policy covers direct calls only: plan steps would trip
+    // over model output such as `token_usage`.
+    knowledge.set_ethos_policy(&PolicyRecord::default()).unwrap();
+    let secret = Goal::ExecuteSkill {
+        name: "Echo".into(),
+        payload: Some(serde_json::json!({ "content": "api_key=sk-1" })),
+    };
+    let err = orch.dispatch(&ctx(), secret.clone()).await.unwrap_err();
+    assert!(err.downcast_ref::<EthosBlocked>().unwrap().decision.rule_id.is_none());
+    let echo = Goal::AutonomousGoal {
+        intent: "echo".into(),
+        context: Some(serde_json::json!({ "token_usage": 12 })),
+    };
+    orch.dispatch(&ctx(), echo).await.unwrap();
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+    knowledge.put_policy_rules(&rules()).unwrap();
+    let err = orch.dispatch(&ctx(), secret).await.unwrap_err();
+    let blocked = err.downcast_ref::<EthosBlocked>().expect("EthosBlocked");
+    assert_eq!(blocked.decision.rule_id.as_deref(), Some("no-secrets"));
+    assert!(!blocked.requires_approval());
+    assert!(blocked.steps.is_empty());
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+    let goal = Goal::AutonomousGoal { intent: "pay".into(), context: None };
+    let err = orch.dispatch(&ctx(), goal).await.unwrap_err();
+    let blocked = err.downcast_ref::<EthosBlocked>().expect("EthosBlocked");
+    assert_eq!(blocked.skill, "PaymentSend");
+    assert!(blocked.requires_approval());
+    // Echo ran; the payment step never did, and the halted trace says why.
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+    assert_eq!(blocked.steps.len(), 2);
+    assert_eq!(blocked.steps[1]["status"], "blocked");
+    assert_eq!(blocked.steps[1]["ethos"]["rule_id"], "payments");
+}
+
+#[tokio::test]
+async fn blocked_plan_steps_halt_skip_or_fall_back() {
+    let knowledge = Arc::new(KnowledgeStore::open_temporary().unwrap());
+    knowledge.put_policy_rules(&rules()).unwrap();
+    let calls = Arc::new(AtomicU32::new(0));
+    let mut registry = SkillRegistry::new();
+    for name in ["Echo", "PaymentSend", "Ledger"] {
+        registry.register(Arc::new(Counted(name, Arc::clone(&calls))));
+    }
+    let step = |on_blocked: OnBlocked| PlanStep {
+        fallbacks: vec!["Ledger".to_string()],
+        policy: StepPolicy { on_blocked, ..StepPolicy::default() },
+        ..PlanStep::new("PaymentSend")
+    };
+    let mut plans = HashMap::new();
+    for (intent, on_blocked) in [("halt", OnBlocked::Halt), ("skip", OnBlocked::Skip), ("fallback", OnBlocked::Fallback)] {
+        plans.insert(intent.to_string(), Plan { steps: vec![step(on_blocked), PlanStep::new("Echo")] });
+    }
+    plans.insert("nested".to_string(), Plan { steps: vec![PlanStep::new("Echo"), PlanStep::sub_plan("halt")] });
+    let orch = Orchestrator::with_blueprint(Arc::new(registry), Arc::new(BlueprintRegistry::from_plans(plans)))
+        .with_knowledge(Arc::clone(&knowledge));
+    let ctx = ctx();
+    let run = |intent: &str| orch.dispatch(&ctx, Goal::AutonomousGoal { intent: intent.into(), context: None });
+
+    // Halt: no fallback, and the next step never runs.
+    let err = run("halt").await.unwrap_err();
+    let blocked = err.downcast_ref::<EthosBlocked>().expect("EthosBlocked");
+    assert_eq!(blocked.steps.len(), 1);
+    assert!(blocked.steps[0].get("fallback_attempts").is_none());
+    assert_eq!(calls.load(Ordering::SeqCst), 0);
+
+    // Skip: the block is traced and the chain goes on.
+    let out = run("skip").await.unwrap();
+    assert_eq!(out["status"], "ok");
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+    // Fallback: the blocked candidate is traced and the allowed fallback runs instead.
+    run("fallback").await.unwrap();
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+    // A sub-plan that halts halts its parent, with the inner trace nested in the outer one.
+    let err = run("nested").await.unwrap_err();
+    let blocked = err.downcast_ref::<EthosBlocked>().expect("EthosBlocked");
+    assert_eq!(blocked.skill, "PaymentSend");
+    assert_eq!(blocked.steps.len(), 2);
+    assert_eq!(blocked.steps[1]["status"], "blocked");
+    assert_eq!(blocked.steps[1]["steps"][0]["status"], "blocked");
+    assert_eq!(calls.load(Ordering::SeqCst), 4);
+}
@@ -0,0 +1,139 @@
+//! Eval suites: scoring goal outputs against assertions and keeping each suite's runs in KB-3.
+
+mod common;
+
+use common::ctx;
+use pagi_core::{
+    AgentSkill, EvalSuite, InvalidEvalSuite, KnowledgeStore, Orchestrator, SkillRegistry, TenantContext,
+    EVAL_JUDGE_PROMPT,
+};
+use std::sync::{Arc, Mutex};
+
+/// Drafts a canned reply to the payload's `question`.
+struct Draft;
+
+#[async_trait::async_trait]
+impl AgentSkill for Draft {
+    fn name(&self) -> &str {
+        "Draft"
+    }
+
+    async fn execute(
+        &self,
+        _ctx: &TenantContext,
+        payload: Option<serde_json::Value>,
+    ) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>> {
+        let question = payload.as_ref().and_then(|p| p.get("question")).and_then(|v| v.as_str()).unwrap_or_default();
+        if question.is_empty() {
+            return Err("no question".into());
+        }
+        Ok(serde_json::json!({ "generated": format!("Thanks for asking about {}. Our pricing starts at $10.", question) }))
+    }
+}
+
+/// Answers with a fixed score and keeps the prompts it was asked to grade.
+struct Judge(&'static str, Arc<Mutex<Vec<String>>>);
+
+#[async_trait::async_trait]
+impl AgentSkill for Judge {
+    fn name(&self) -> &str {
+        "Judge"
+    }
+
+    async fn execute(
+        &self,
+        _ctx: &TenantContext,
+        payload: Option<serde_json::Value>,
+    ) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>> {
+        let prompt = payload.and_then(|p| p.get("prompt").and_then(|v| v.as_str()).map(str::to_string));
+        self.1.lock().unwrap().push(prompt.unwrap_or_default());
+        Ok(serde_json::json!({ "generated": self.0 }))
+    }
+}
+
+fn suite() -> EvalSuite {
+    serde_json::from_value(serde_json::json!({
+        "name": "respond_to_lead",
+        "judge_skill": "Judge",
+        "cases": [
+            {
+                "name": "pricing",
+                "goal": { "ExecuteSkill": { "name": "Draft", "payload": { "question": "backups" } } },
+                "assertions": [
+                    { "type": "contains", "path": "$.generated", "value": "PRICING", "ignore_case": true },
+                    { "type": "regex", "path": "$.generated", "pattern": "\\$\\d+" },
+                    { "type": "llm_judge", "path": "$.generated", "criteria": "Polite", "min_score": 0.7 }
+                ]
+            },
+            {
+                "name": "no question",
+                "goal": { "ExecuteSkill": { "name": "Draft", "payload": {} } },
+                "assertions": [{ "type": "not_contains", "value": "error" }]
+            }
+        ]
+    }))
+    .unwrap()
+}
+
+fn orchestrator(judge_reply: &'static str, store: &Arc<KnowledgeStore>) -> (Orchestrator, Arc<Mutex<Vec<String>>>) {
+    let prompts = Arc::new(Mutex::new(Vec::new()));
+    let mut registry = SkillRegistry::new();
+    registry.register(Arc::new(Draft));
+    registry.register(Arc::new(Judge(judge_reply, Arc::clone(&prompts))));
+    (Orchestrator::new(Arc::new(registry)).with_knowledge(Arc::clone(store)), prompts)
+}
+
+#[tokio::test]
+async fn runs_are_scored_and_stored_per_suite() {
+    let store = Arc::new(KnowledgeStore::open_temporary().unwrap());
+    let (orch, prompts) = orchestrator("8", &store);
+
+    let run = orch.run_eval(&ctx(), &suite(), Some("baseline".into())).await.unwrap();
+    assert_eq!((run.passed, run.total), (1, 2));
+    let pricing = &run.cases[0];
+    assert!(pricing.passed, "{:?}", pricing.assertions);
+    assert!((pricing.score - (1.0 + 1.0 + 0.8) / 3.0).abs() < 1e-9);
+    let failed = &run.cases[1];
+    assert!(!failed.passed && failed.error.as_deref() == Some("no question"));
+    assert_eq!(failed.score, 0.0);
+    assert!((run.score - pricing.score / 2.0).abs() < 1e-9);
+    assert!(prompts.lock().unwrap()[0].contains("Criterion: Polite"));
+
+    // A stricter judge (or prompt) shows up as a lower score in the suite's trend.
+    store.put_prompt(EVAL_JUDGE_PROMPT, "Grade {{output}} for: {{criteria}}. Answer 0-10.", None).unwrap();
+    let (strict, prompts) = orchestrator("Score: 3/10", &store);
+    let second = strict.run_eval(&ctx(), &suite(), None).await.unwrap();
+    assert!(!second.cases[0].passed);
+    assert!(second.score < run.score);
+    assert!(prompts.lock().unwrap()[0].starts_with("Grade Thanks for asking"));
+
+    let trend = store.eval_runs("respond_to_lead", 10).unwrap();
+    assert_eq!(trend.iter().map(|r| r.run_id.as_str()).collect::<Vec<_>>(), [second.run_id.as_str(), run.run_id.as_str()]);
+    assert_eq!(trend[1].label.as_deref(), Some("baseline"));
+    let latest = store.eval_suites().unwrap();
+    assert_eq!(latest.len(), 1);
+    assert_eq!(latest[0].run_id, second.run_id);
+    assert_eq!(store.get_eval_run("respond_to_lead", &run.run_id).unwrap(), Some(run));
+    assert_eq!(store.get_eval_run("respond_to_lead", "missing").unwrap(), None);
+}
+
+#[tokio::test]
+async fn unparseable_judge_replies_fail_the_assertion() {
+    let store = Arc::new(KnowledgeStore::open_temporary().unwrap());
+    let (orch, _) = orchestrator("Looks good to me", &store);
+    let run = orch.run_eval(&ctx(), &suite(), None).await.unwrap();
+    let judged = &run.cases[0].assertions[2];
+    assert!(!judged.passed);
+    assert!(judged.detail.as_deref().unwrap().contains("no score"), "{:?}", judged.detail);
+}
+
+#[tokio::test]
+async fn invalid_suites_are_rejected_before_running() {
+    let store = Arc::new(KnowledgeStore::open_temporary().unwrap());
+    let (orch, _) = orchestrator("8", &store);
+    let mut suite = suite();
+    suite.cases[1].assertions.clear();
+    let err: InvalidEvalSuite = orch.run_eval(&ctx(), &suite, None).await.unwrap_err();
+    assert!(err.0.contains("no assertions"), "{}", err);
+    assert!(store.eval_suites().unwrap().is_empty());
+}
@@ -0,0 +1,61 @@
+//! Background goal jobs in KB_SOMA: step progress, final status and retention.
+
+use pagi_core::{GoalJob, JobStatus, KnowledgeStore, StepProgress, StepStatus};
+
+fn step(depth: usize, index: usize, status: StepStatus) -> StepProgress {
+    StepProgress {
+        correlation_id: Some("job-1".to_string()),
+        agent_id: "default".to_string(),
+        intent: "digest".to_string(),
+        depth,
+        index,
+        total: 3,
+        step: format!("Step{}", index),
+        status,
+        executed_skill: None,
+        error: None,
+        timestamp_ms: 0,
+    }
+}
+
+#[test]
+fn jobs_track_top_level_steps_and_freeze_once_finished() {
+    let store = KnowledgeStore::open_temporary().unwrap();
+    let job = GoalJob::new("job-1", "acme", "default", "job-1", "AutonomousGoal");
+    let created = store.create_job(&job).unwrap().unwrap();
+    assert!(created.created_at_ms > 0);
+    assert_eq!(created.status, JobStatus::Queued);
+    assert_eq!(store.create_job(&job).unwrap(), None);
+    assert!(store.create_job(&GoalJob::new("a/b", "acme", "default", "x", "ExecuteSkill")).is_err());
+
+    store.update_job("job-1", |j| j.status = JobStatus::Running).unwrap();
+    for event in [
+        step(1, 0, StepStatus::Started),
+        step(1, 0, StepStatus::Completed),
+        step(1, 1, StepStatus::Skipped),
+        step(1, 2, StepStatus::Started),
+        step(2, 0, StepStatus::Completed),
+    ] {
+        store.update_job("job-1", |j| j.apply_progress(&event)).unwrap();
+    }
+    let running = store.get_job("job-1").unwrap();
+    assert_eq!((running.steps_completed, running.steps_total), (2, Some(3)));
+    assert_eq!(running.current_step.as_deref(), Some("Step2"));
+
+    let done = serde_json::json!({ "status": "ok" });
+    store
+        .update_job("job-1", |j| j.finish(JobStatus::Succeeded, Some(done.clone()), None, 1))
+        .unwrap();
+    // A finished job no longer changes.
+    let finished = store.update_job("job-1", |j| j.status = JobStatus::Failed).unwrap().unwrap();
+    assert_eq!(finished.status, JobStatus::Succeeded);
+    assert_eq!(finished.result, Some(done));
+    assert_eq!(finished.current_step, None);
+    assert_eq!(store.update_job("missing", |j| j.status = JobStatus::Running).unwrap(), None);
+
+    // Finished long ago (timestamp 1): pruned; the queued job stays.
+    store.create_job(&GoalJob::new("job-2", "acme", "default", "job-2", "ExecuteSkill")).unwrap();
+    assert_eq!(store.prune_jobs().unwrap(), 1);
+    assert!(store.get_job("job-1").is_none());
+    assert!(store.get_job("job-2").is_some());
+}
@@ -0,0 +1,94 @@
+//! Idempotency keys in KB_SOMA: claim, replay, mismatch, expiry and concurrent claims.
+
+use pagi_core::{IdempotencyClaim, IdempotencyRecord, KnowledgeStore};
+use std::sync::Arc;
+
+const HOUR_MS: i64 = 60 * 60 * 1000;
+
+fn fingerprint(goal: &str) -> String {
+    IdempotencyRecord::fingerprint(&serde_json::json!({ "goal": goal }))
+}
+
+#[test]
+fn claim_complete_and_replay() {
+    let store = KnowledgeStore::open_temporary().unwrap();
+    let fp = fingerprint("lead");
+    assert_eq!(store.claim_idempotency_key("acme", "k1", &fp, HOUR_MS).unwrap(), IdempotencyClaim::Claimed);
+    assert_eq!(store.claim_idempotency_key("acme", "k1", &fp, HOUR_MS).unwrap(), IdempotencyClaim::InProgress);
+
+    let response = serde_json::json!({ "status": "saved", "lead_id": "L1" });
+    store.complete_idempotency_key("acme", "k1", &response).unwrap();
+    assert_eq!(
+        store.claim_idempotency_key("acme", "k1", &fp, HOUR_MS).unwrap(),
+        IdempotencyClaim::Completed(response)
+    );
+    assert_eq!(
+        store.claim_idempotency_key("acme", "k1", &fingerprint("other"), HOUR_MS).unwrap(),
+        IdempotencyClaim::Mismatch
+    );
+    // Keys are per tenant.
+    assert_eq!(store.claim_idempotency_key("globex", "k1", &fp, HOUR_MS).unwrap(), IdempotencyClaim::Claimed);
+    assert!(store.complete_idempotency_key("acme", "never-claimed", &serde_json::json!({})).is_err());
+}
+
+#[test]
+fn released_and_expired_keys_can_be_claimed_again() {
+    let store = KnowledgeStore::open_temporary().unwrap();
+    let fp = fingerprint("lead");
+    store.claim_idempotency_key("acme", "throttled", &fp, HOUR_MS).unwrap();
+    store.release_idempotency_key("acme", "throttled").unwrap();
+    assert_eq!(store.claim_idempotency_key("acme", "throttled", &fp, HOUR_MS).unwrap(), IdempotencyClaim::Claimed);
+
+    // A zero TTL expires at once, even for a different request.
+    store.claim_idempotency_key("acme", "short", &fp, 0).unwrap();
+    store.complete_idempotency_key("acme", "short", &serde_json::json!({ "ok": true })).unwrap();
+    assert_eq!(
+        store.claim_idempotency_key("acme", "short", &fingerprint("other"), HOUR_MS).unwrap(),
+        IdempotencyClaim::Claimed
+    );
+
+    // A claim whose request never finished stops blocking after IDEMPOTENCY_CLAIM_TIMEOUT_MS.
+    let abandoned = IdempotencyRecord {
+        tenant_id: "acme".to_string(),
+        key: "crashed".to_string(),
+        request_fingerprint: fp.clone(),
+        response: None,
+        created_at_ms: 0,
+        expires_at_ms: i64::MAX,
+    };
+    store
+        .insert(8, &pagi_core::keys::idempotency_key("acme", "crashed"), &abandoned.to_bytes())
+        .unwrap();
+    assert_eq!(store.claim_idempotency_key("acme", "crashed", &fp, HOUR_MS).unwrap(), IdempotencyClaim::Claimed);
+}
+
+#[test]
+fn prune_removes_only_expired_records() {
+    let store = KnowledgeStore::open_temporary().unwrap();
+    let fp = fingerprint("lead");
+    store.claim_idempotency_key("acme", "old", &fp, 0).unwrap();
+    store.claim_idempotency_key("acme", "fresh", &fp, HOUR_MS).unwrap();
+    store.insert(8, "unrelated", b"kept").unwrap();
+
+    assert_eq!(store.prune_idempotency_keys().unwrap(), 1);
+    assert!(store.get_idempotency_record("acme", "old").is_none());
+    assert!(store.get_idempotency_record("acme", "fresh").is_some());
+    assert!(store.get(8, "unrelated").unwrap().is_some());
+}
+
+#[test]
+fn concurrent_claims_admit_one_request() {
+    let store = Arc::new(KnowledgeStore::open_temporary().unwrap());
+    let fp = fingerprint("lead");
+    let handles: Vec<_> = (0..8)
+        .map(|_| {
+            let (store, fp) = (Arc::clone(&store), fp.clone());
+            std::thread::spawn(move || store.claim_idempotency_key("acme", "race", &fp, HOUR_MS).unwrap())
+        })
+        .collect();
+    let claims: Vec<IdempotencyClaim> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+    assert_eq!(claims.iter().filter(|c| **c == IdempotencyClaim::Claimed).count(), 1);
+    assert!(claims
+        .iter()
+        .all(|c| matches!(c, IdempotencyClaim::Claimed | IdempotencyClaim::InProgress)));
+}
@@ -0,0 +1,43 @@
+//! Heartbeat inbox work items: retry backoff on failed attempts, dead-lettering and requeueing.
+
+use pagi_core::{InboxRetry, KnowledgeStore, INBOX_MAX_ATTEMPTS, INBOX_RETRY_BASE_MS};
+
+#[test]
+fn failed_messages_back_off_then_dead_letter_and_requeue() {
+    let store = KnowledgeStore::open_temporary().unwrap();
+    let id = store.push_agent_message("sales", "support", &serde_json::json!({ "text": "help" })).unwrap();
+    let (key, msg) = store.get_agent_messages_with_keys("support", 1).unwrap().remove(0);
+    assert!(msg.is_ready(msg.timestamp_ms));
+
+    let Some(InboxRetry::Scheduled { attempts: 1, next_attempt_ms }) = store.record_inbox_failure(&key, "timeout").unwrap() else {
+        panic!("first failure should schedule a retry");
+    };
+    let msg = store.get_agent_messages("support", 1).unwrap().remove(0);
+    assert_eq!(msg.last_error.as_deref(), Some("timeout"));
+    assert!(!msg.is_ready(next_attempt_ms - 1) && msg.is_ready(next_attempt_ms));
+    let Some(InboxRetry::Scheduled { next_attempt_ms: second, .. }) = store.record_inbox_failure(&key, "timeout").unwrap() else {
+        panic!("second failure should schedule a retry");
+    };
+    assert!(second - next_attempt_ms >= INBOX_RETRY_BASE_MS);
+
+    for _ in 2..INBOX_MAX_ATTEMPTS - 1 {
+        store.record_inbox_failure(&key, "timeout").unwrap();
+    }
+    assert_eq!(
+        store.record_inbox_failure(&key, "still down").unwrap(),
+        Some(InboxRetry::DeadLettered { attempts: INBOX_MAX_ATTEMPTS })
+    );
+    assert!(store.get_agent_messages("support", 10).unwrap().is_empty());
+    assert_eq!(store.record_inbox_failure(&key, "gone").unwrap(), None);
+    let dead = store.get_dead_letters("support", 10).unwrap();
+    assert_eq!(dead.len(), 1);
+    assert_eq!(dead[0].last_error.as_deref(), Some("still down"));
+
+    assert_eq!(store.requeue_dead_letter("support", "missing").unwrap().map(|m| m.id), None);
+    let requeued = store.requeue_dead_letter("support", &id).unwrap().unwrap();
+    assert_eq!(requeued.attempts, 0);
+    assert!(store.get_dead_letters("support", 10).unwrap().is_empty());
+    let (back_key, back) = store.get_agent_messages_with_keys("support", 1).unwrap().remove(0);
+    assert_eq!((back_key, back.id.as_str()), (key, id.as_str()));
+    assert!(back.is_ready(back.timestamp_ms));
+}
@@ -0,0 +1,145 @@
+//! Chat intent routing: weighted term matching, confirmation flow and ModelRouter fallback.
+
+use pagi_core::{
+    is_confirmation, AgentSkill, BlueprintRegistry, IntentAction, IntentRouter, IntentRoutingConfig, IntentSource,
+    Orchestrator, Plan, PlanStep, SkillRegistry, TenantContext,
+};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// ModelRouter stand-in replying with a fixed text.
+struct Router(&'static str);
+
+#[async_trait::async_trait]
+impl AgentSkill for Router {
+    fn name(&self) -> &str {
+        "ModelRouter"
+    }
+
+    async fn execute(
+        &self,
+        _ctx: &TenantContext,
+        _payload: Option<serde_json::Value>,
+    ) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(serde_json::json!({ "generated": self.0 }))
+    }
+}
+
+fn intents() -> Vec<String> {
+    vec!["summarize news".to_string(), "respond to lead".to_string()]
+}
+
+fn examples() -> HashMap<String, Vec<String>> {
+    HashMap::from([(
+        "Summarize News".to_string(),
+        vec!["refresh the local events".to_string(), "scrape the community calendar".to_string()],
+    )])
+}
+
+fn orchestrator(config: IntentRoutingConfig, model_reply: &'static str) -> Orchestrator {
+    let mut registry = SkillRegistry::new();
+    registry.register(Arc::new(Router(model_reply)));
+    let plans = intents()
+        .into_iter()
+        .map(|i| (i, Plan { steps: vec![PlanStep::new("ModelRouter")] }))
+        .collect();
+    Orchestrator::with_blueprint(Arc::new(registry), Arc::new(BlueprintRegistry::from_plans(plans)))
+        .with_intent_router(Arc::new(IntentRouter::new(config).with_examples(examples())))
+}
+
+#[test]
+fn weighted_terms_pick_run_confirm_or_nothing() {
+    let router = IntentRouter::new(IntentRoutingConfig::default()).with_examples(examples());
+
+    let found = router.classify("Go refresh the local events", &intents()).unwrap();
+    assert_eq!(found.intent, "summarize news");
+    assert_eq!(found.action, IntentAction::Run);
+    assert_eq!(found.source, IntentSource::Terms);
+    assert!(found.confidence >= 0.75, "confidence {}", found.confidence);
+
+    let partial = router.classify("any local events this weekend?", &intents()).unwrap();
+    assert_eq!(partial.intent, "summarize news");
+    assert_eq!(partial.action, IntentAction::Confirm, "confidence {}", partial.confidence);
+
+    assert!(router.classify("tell me a joke about cats", &intents()).is_none());
+    assert!(router.classify("", &intents()).is_none());
+}
+
+#[test]
+fn close_runner_up_only_asks() {
+    let router = IntentRouter::new(IntentRoutingConfig::default());
+    let intents = vec!["send report".to_string(), "print report".to_string()];
+    let found = router.classify("report", &intents).unwrap();
+    assert_eq!(found.action, IntentAction::Confirm);
+}
+
+#[test]
+fn confirmations_are_short_affirmatives() {
+    for reply in ["yes", "Yes!", "ok, go ahead", "sure", "do it please"] {
+        assert!(is_confirmation(reply), "{:?}", reply);
+    }
+    for reply in ["no", "yesterday was fun", "", "yes but first tell me what the weather is like"] {
+        assert!(!is_confirmation(reply), "{:?}", reply);
+    }
+}
+
+#[tokio::test]
+async fn confirm_then_yes_runs_the_intent() {
+    let orch = orchestrator(IntentRoutingConfig::default(), "");
+    let asked = orch.route_chat(&TenantContext::new("alice"), "any local events this weekend?").await.unwrap();
+    assert_eq!(asked.action, IntentAction::Confirm);
+    assert!(asked.confirmation_prompt().contains("summarize news"));
+
+    // Another tenant's "yes" does not answer alice's question.
+    assert!(orch.route_chat(&TenantContext::new("bob"), "yes").await.is_none());
+
+    let confirmed = orch.route_chat(&TenantContext::new("alice"), "yes").await.unwrap();
+    assert_eq!(confirmed.action, IntentAction::Run);
+    assert_eq!(confirmed.source, IntentSource::Confirmation);
+    assert_eq!(confirmed.message, "any local events this weekend?");
+    // Answered once only.
+    assert!(orch.route_chat(&TenantContext::new("alice"), "yes").await.is_none());
+
+    // Anything but a confirmation drops the question.
+    orch.route_chat(&TenantContext::new("alice"), "any local events this weekend?").await.unwrap();
+    assert!(orch.route_chat(&TenantContext::new("alice"), "never mind").await.is_none());
+    assert!(orch.route_chat(&TenantContext::new("alice"), "yes").await.is_none());
+}
+
+#[tokio::test]
+async fn model_fallback_is_weighted_and_optional() {
+    let reply = r#"Sure: {"intent": "Respond to Lead", "confidence": 1.0}"#;
+    let message = "that customer emailed again, handle it";
+
+    let off = orchestrator(IntentRoutingConfig::default(), reply);
+    assert!(off.route_chat(&TenantContext::new("t"), message).await.is_none());
+
+    let config = IntentRoutingConfig {
+        llm_fallback: true,
+        ..IntentRoutingConfig::default()
+    };
+    let found = orchestrator(config.clone(), reply).route_chat(&TenantContext::new("t"), message).await.unwrap();
+    assert_eq!(found.intent, "respond to lead");
+    assert_eq!(found.source, IntentSource::Llm);
+    assert!((found.confidence - 0.8).abs() < 1e-6);
+    assert_eq!(found.action, IntentAction::Run);
+
+    let unsure = orchestrator(config.clone(), r#"{"intent": "respond to lead", "confidence": 0.4}"#);
+    assert!(unsure.route_chat(&TenantContext::new("t"), message).await.is_none());
+    let none = orchestrator(config, r#"{"intent": "none", "confidence": 0.9}"#);
+    assert!(none.route_chat(&TenantContext::new("t"), message).await.is_none());
+}
+
+#[tokio::test]
+async fn disabled_or_detached_router_never_routes() {
+    let disabled = orchestrator(
+        IntentRoutingConfig {
+            enabled: false,
+            ..IntentRoutingConfig::default()
+        },
+        "",
+    );
+    assert!(disabled.route_chat(&TenantContext::new("t"), "refresh the local events").await.is_none());
+    let plain = Orchestrator::new(Arc::new(SkillRegistry::new()));
+    assert!(plain.route_chat(&TenantContext::new("t"), "summarize news").await.is_none());
+}
@@ -0,0 +1,48 @@
+//! LLM response cache in KB_SOMA: hashing, expiry and the size bound.
+
+use pagi_core::{KnowledgeStore, LlmCacheEntry};
+
+fn entry(response: &str, created_at_ms: i64, expires_at_ms: i64) -> LlmCacheEntry {
+    LlmCacheEntry {
+        model: "m".to_string(),
+        response: response.to_string(),
+        created_at_ms,
+        expires_at_ms,
+    }
+}
+
+#[test]
+fn hash_covers_model_and_every_part() {
+    let base = LlmCacheEntry::cache_hash("m", &["system", "prompt"]);
+    assert_eq!(base, LlmCacheEntry::cache_hash("m", &["system", "prompt"]));
+    assert_ne!(base, LlmCacheEntry::cache_hash("other", &["system", "prompt"]));
+    assert_ne!(base, LlmCacheEntry::cache_hash("m", &["systemprompt", ""]));
+    assert_ne!(base, LlmCacheEntry::cache_hash("m", &["prompt", "system"]));
+}
+
+#[test]
+fn expired_entries_are_misses_and_pruned() {
+    let store = KnowledgeStore::open_temporary().unwrap();
+    store.put_llm_cache("live", &entry("hello", 0, i64::MAX), 10).unwrap();
+    store.put_llm_cache("stale", &entry("old", 0, 1), 10).unwrap();
+    assert_eq!(store.get_llm_cache("live").unwrap().response, "hello");
+    assert!(store.get_llm_cache("stale").is_none());
+    assert!(store.get_llm_cache("missing").is_none());
+    assert_eq!(store.prune_llm_cache().unwrap(), 1);
+}
+
+#[test]
+fn size_bound_evicts_expired_then_oldest() {
+    let store = KnowledgeStore::open_temporary().unwrap();
+    store.put_llm_cache("a", &entry("a", 1, i64::MAX), 3).unwrap();
+    store.put_llm_cache("b", &entry("b", 2, i64::MAX), 3).unwrap();
+    store.put_llm_cache("c", &entry("c", 3, 1), 3).unwrap();
+    // Full: the expired entry goes before the oldest live one.
+    assert_eq!(store.put_llm_cache("d", &entry("d", 4, i64::MAX), 3).unwrap(), 1);
+    assert!(store.get_llm_cache("a").is_some());
+    assert_eq!(store.put_llm_cache("e", &entry("e", 5, i64::MAX), 3).unwrap(), 1);
+    assert!(store.get_llm_cache("a").is_none());
+    for hash in ["b", "d", "e"] {
+        assert!(store.get_llm_cache(hash).is_some(), "{}", hash);
+    }
+}
@@ -0,0 +1,70 @@
+//! `Goal::MemoryOp`: path-addressed reads, writes and deletes in the vault and knowledge store.
+
+use pagi_core::{
+    EthosBlocked, Goal, KnowledgeStore, MemoryManager, Orchestrator, PolicyRule, PolicyRules, RuleAction, SkillRegistry,
+    TenantContext,
+};
+use std::sync::Arc;
+
+fn op(path: &str, value: Option<serde_json::Value>, delete: bool) -> Goal {
+    Goal::MemoryOp { path: path.to_string(), value, delete }
+}
+
+#[tokio::test]
+async fn memory_ops_read_write_and_delete_within_the_tenant() {
+    let knowledge = Arc::new(KnowledgeStore::open_temporary().unwrap());
+    let memory = Arc::new(MemoryManager::open_temporary().unwrap());
+    let orch = Orchestrator::new(Arc::new(SkillRegistry::new()))
+        .with_knowledge(Arc::clone(&knowledge))
+        .with_memory(Arc::clone(&memory));
+    let acme = TenantContext::new("acme");
+
+    let saved = orch.dispatch(&acme, op("vault/notes/today", Some(serde_json::json!({ "todo": 3 })), false)).await.unwrap();
+    assert_eq!(saved["status"], "saved");
+    assert_eq!(memory.get_path(&acme, "notes/acme/today").unwrap().unwrap(), br#"{"todo":3}"#);
+    let read = orch.dispatch(&acme, op("vault/notes/today", None, false)).await.unwrap();
+    assert_eq!(read["value"]["todo"], 3);
+    let other = orch.dispatch(&TenantContext::new("beta"), op("vault/notes/today", None, false)).await.unwrap();
+    assert_eq!(other["status"], "not_found");
+    assert_eq!(orch.dispatch(&acme, op("vault/notes/today", None, true)).await.unwrap()["status"], "deleted");
+    assert_eq!(orch.dispatch(&acme, op("vault/notes/today", None, false)).await.unwrap()["status"], "not_found");
+
+    orch.dispatch(&acme, op("knowledge/3/notes/plan", Some(serde_json::json!("ship it")), false)).await.unwrap();
+    assert_eq!(knowledge.tenant("acme").unwrap().get(3, "notes/plan").unwrap().unwrap(), b"ship it");
+    let read = orch.dispatch(&acme, op("knowledge/3/notes/plan", None, false)).await.unwrap();
+    assert_eq!(read["value"], "ship it");
+    assert_eq!(orch.dispatch(&TenantContext::new("beta"), op("knowledge/3/notes/plan", None, false)).await.unwrap()["status"], "not_found");
+
+    assert!(orch.dispatch(&acme, op("knowledge/9/key", None, false)).await.is_err());
+    assert!(orch.dispatch(&acme, op("vault/notes/x", Some(serde_json::json!(1)), true)).await.is_err());
+
+    let events = knowledge.get_recent_chronos_events("default", 20).unwrap();
+    let logged: Vec<_> = events.iter().filter(|e| e.skill_name.as_deref() == Some("MemoryOp")).collect();
+    assert!(logged.iter().any(|e| e.reflection == "Memory delete on path: vault/notes/today"
+        && e.outcome.as_deref() == Some("deleted")));
+    assert!(logged.iter().any(|e| e.outcome.as_deref().is_some_and(|o| o.starts_with("failed:"))));
+}
+
+#[tokio::test]
+async fn ethos_rules_gate_memory_writes_but_not_reads() {
+    let knowledge = Arc::new(KnowledgeStore::open_temporary().unwrap());
+    let orch = Orchestrator::new(Arc::new(SkillRegistry::new()))
+        .with_knowledge(Arc::clone(&knowledge))
+        .with_memory(Arc::new(MemoryManager::open_temporary().unwrap()));
+    let rule: PolicyRule = serde_json::from_value(serde_json::json!({
+        "id": "identity-is-read-only",
+        "skills": ["MemoryOp"],
+        "conditions": [{ "path": "$.path", "regex": "^knowledge/1/" }],
+        "action": "deny"
+    }))
+    .unwrap();
+    knowledge.put_policy_rules(&PolicyRules::new(vec![rule], RuleAction::Allow)).unwrap();
+    let acme = TenantContext::new("acme");
+
+    let err = orch.dispatch(&acme, op("knowledge/1/mission", Some(serde_json::json!("new")), false)).await.unwrap_err();
+    let blocked = err.downcast_ref::<EthosBlocked>().expect("EthosBlocked");
+    assert_eq!(blocked.decision.rule_id.as_deref(), Some("identity-is-read-only"));
+    assert!(orch.dispatch(&acme, op("knowledge/1/mission", None, true)).await.is_err());
+    assert_eq!(orch.dispatch(&acme, op("knowledge/1/mission", None, false)).await.unwrap()["status"], "not_found");
+    assert_eq!(orch.dispatch(&acme, op("knowledge/2/mission", Some(serde_json::json!("ok")), false)).await.unwrap()["status"], "saved");
+}
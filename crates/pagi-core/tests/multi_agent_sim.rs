@@ -11,7 +11,7 @@
 //! `KnowledgeStore::{push_agent_message,get_agent_messages}` primitives directly
 //! (avoids a circular dev-dependency on `pagi-skills`).
 
-use pagi_core::{AlignmentResult, EventRecord, KnowledgeStore, PolicyRecord};
+use pagi_core::{AlignmentResult, KnowledgeStore, PolicyRecord};
 use tempfile::tempdir;
 
 #[test]
@@ -52,7 +52,7 @@ pub fn demo() -> &'static str {
     std::fs::write(&file_path, vulnerable).expect("write vulnerable code");
 
     // DEV marks its own state as pending review.
-    let dev_pending = EventRecord::now(
+    let dev_pending = store.event(
         "Soma",
         format!("Task Pending: awaiting audit of research_sandbox/{rel_file}"),
     )
@@ -108,7 +108,7 @@ pub fn demo() -> &'static str {
     let violation_reason = violation_reason.unwrap();
 
     // SAGE logs the violation to its own CHRONOS.
-    let sage_event = EventRecord::now(
+    let sage_event = store.event(
         "Ethos",
         format!("Policy Violation: {violation_reason}"),
     )
@@ -78,6 +78,9 @@ fn project_manager_person() -> PersonRecord {
     }
 }
 
+/// Fixed evaluation time so task timestamps are deterministic.
+const NOW_MS: i64 = 1_700_000_000_000;
+
 /// The three governed tasks for the audit scenario.
 fn audit_tasks() -> Vec<GovernedTask> {
     vec![
@@ -86,6 +89,7 @@ fn audit_tasks() -> Vec<GovernedTask> {
             "quarterly_report",
             "Finish Quarterly Report",
             TaskDifficulty::High,
+            NOW_MS,
         )
         .with_priority(0.8)
         .with_tags(vec![
@@ -102,6 +106,7 @@ fn audit_tasks() -> Vec<GovernedTask> {
             "grocery_run",
             "Weekly Grocery Run",
             TaskDifficulty::Low,
+            NOW_MS,
         )
         .with_priority(0.4)
         .with_tags(vec!["personal".to_string(), "routine".to_string()])
@@ -111,6 +116,7 @@ fn audit_tasks() -> Vec<GovernedTask> {
             "server_patch",
             "Emergency Server Patch",
             TaskDifficulty::Critical,
+            NOW_MS,
         )
         .with_priority(0.95)
         .with_tags(vec![
@@ -449,6 +455,7 @@ fn oikos_audit_ethos_shapes_governance_reason() {
         "quarterly_report",
         "Finish Quarterly Report",
         TaskDifficulty::High,
+        store.now_ms(),
     )
     .with_priority(0.8)
     .with_tags(vec![
@@ -526,6 +533,7 @@ fn oikos_audit_critical_immunity_under_maximum_stress() {
         "server_patch",
         "Emergency Server Patch",
         TaskDifficulty::Critical,
+        store.now_ms(),
     )
     .with_priority(0.95);
     store.set_governed_task(&critical).unwrap();
@@ -740,6 +748,7 @@ fn oikos_audit_ethos_lens_differentiation() {
             "quarterly_report",
             "Finish Quarterly Report",
             TaskDifficulty::High,
+            store.now_ms(),
         )
         .with_priority(0.8)
         .with_tags(vec!["work".to_string(), "conflict".to_string()]);
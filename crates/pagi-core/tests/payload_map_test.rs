@@ -0,0 +1,82 @@
+//! Declarative `map` between chained steps, and the built-in pair mappings it replaces.
+
+mod common;
+
+use common::ctx;
+use pagi_core::{AgentSkill, BlueprintRegistry, Goal, Orchestrator, PayloadMap, Plan, PlanStep, SkillRegistry, TenantContext};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Returns a fixed value regardless of input.
+struct Fixed(&'static str, serde_json::Value);
+
+#[async_trait::async_trait]
+impl AgentSkill for Fixed {
+    fn name(&self) -> &str {
+        self.0
+    }
+
+    async fn execute(
+        &self,
+        _ctx: &TenantContext,
+        _payload: Option<serde_json::Value>,
+    ) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(self.1.clone())
+    }
+}
+
+/// Returns the payload it received.
+struct Echo(&'static str);
+
+#[async_trait::async_trait]
+impl AgentSkill for Echo {
+    fn name(&self) -> &str {
+        self.0
+    }
+
+    async fn execute(
+        &self,
+        _ctx: &TenantContext,
+        payload: Option<serde_json::Value>,
+    ) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(serde_json::json!({ "received": payload }))
+    }
+}
+
+async fn run(steps: Vec<PlanStep>) -> serde_json::Value {
+    let mut registry = SkillRegistry::new();
+    registry.register(Arc::new(Fixed(
+        "Fetcher",
+        serde_json::json!({ "page": { "title": "Pricing", "body": "Pro is $20" } }),
+    )));
+    registry.register(Arc::new(Fixed("DraftResponse", serde_json::json!({ "draft": "Hello lead" }))));
+    registry.register(Arc::new(Echo("Summarizer")));
+    registry.register(Arc::new(Echo("ModelRouter")));
+    let mut plans = HashMap::new();
+    plans.insert("run".to_string(), Plan { steps });
+    let orch = Orchestrator::with_blueprint(Arc::new(registry), Arc::new(BlueprintRegistry::from_plans(plans)));
+    orch.dispatch(&ctx(), Goal::AutonomousGoal { intent: "run".into(), context: None })
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn declarative_map_shapes_next_input_without_orchestrator_changes() {
+    let map: PayloadMap = serde_json::from_value(serde_json::json!({
+        "text": "$.page.body",
+        "heading": "$.page.title",
+        "style": "bullet"
+    }))
+    .unwrap();
+    let out = run(vec![PlanStep::new("Fetcher"), PlanStep::new("Summarizer").with_map(map)]).await;
+    assert_eq!(
+        out["received"],
+        serde_json::json!({ "text": "Pro is $20", "heading": "Pricing", "style": "bullet" })
+    );
+}
+
+#[tokio::test]
+async fn builtin_pairs_still_apply_without_a_map() {
+    let out = run(vec![PlanStep::new("DraftResponse"), PlanStep::new("ModelRouter")]).await;
+    assert_eq!(out["received"], serde_json::json!({ "prompt": "Hello lead" }));
+}
@@ -0,0 +1,127 @@
+//! Live plan progress (`subscribe_progress`) and control-state updates (`subscribe_control`).
+
+mod common;
+
+use common::orchestrator_with_plans;
+use pagi_core::{
+    AgentSkill, ControlPanelMessage, Goal, Orchestrator, PlanStep, SkillRegistry,
+    StepCondition, StepStatus, TenantContext,
+};
+use std::sync::Arc;
+
+/// Returns `{ "by": <name> }`, or fails when named "Broken".
+struct Step(&'static str);
+
+#[async_trait::async_trait]
+impl AgentSkill for Step {
+    fn name(&self) -> &str {
+        self.0
+    }
+
+    async fn execute(
+        &self,
+        _ctx: &TenantContext,
+        _payload: Option<serde_json::Value>,
+    ) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>> {
+        if self.0 == "Broken" {
+            return Err("broken on purpose".into());
+        }
+        Ok(serde_json::json!({ "by": self.0 }))
+    }
+}
+
+fn orchestrator() -> Orchestrator {
+    let mut registry = SkillRegistry::new();
+    for name in ["Fetch", "Broken", "Backup", "Summarize", "Never"] {
+        registry.register(Arc::new(Step(name)));
+    }
+    let digest = vec![
+        PlanStep::new("Broken").with_fallbacks(["Backup"]),
+        PlanStep::new("Never").with_condition(StepCondition::new("by", serde_json::json!("nobody"))),
+        PlanStep::new("Summarize"),
+    ];
+    let morning = vec![PlanStep::new("Fetch"), PlanStep::sub_plan("digest")];
+    orchestrator_with_plans(registry, [("digest", digest), ("morning", morning)])
+}
+
+#[tokio::test]
+async fn plan_steps_report_progress_in_order() {
+    let orch = orchestrator();
+    let mut rx = orch.subscribe_progress();
+    let ctx = TenantContext {
+        tenant_id: "test".to_string(),
+        correlation_id: Some("run-1".to_string()),
+        agent_id: Some("sales".to_string()),
+        ..Default::default()
+    };
+    orch.dispatch(&ctx, Goal::AutonomousGoal { intent: "morning".to_string(), context: None })
+        .await
+        .unwrap();
+
+    let mut events = Vec::new();
+    while let Ok(event) = rx.try_recv() {
+        assert_eq!(event.correlation_id.as_deref(), Some("run-1"));
+        assert_eq!(event.agent_id, "sales");
+        events.push(event);
+    }
+    let seen: Vec<(String, usize, &str, StepStatus)> = events
+        .iter()
+        .map(|e| (e.intent.clone(), e.depth, e.step.as_str(), e.status))
+        .collect();
+    assert_eq!(
+        seen,
+        vec![
+            ("morning".to_string(), 1, "Fetch", StepStatus::Started),
+            ("morning".to_string(), 1, "Fetch", StepStatus::Completed),
+            ("morning".to_string(), 1, "intent:digest", StepStatus::Started),
+            ("digest".to_string(), 2, "Broken", StepStatus::Started),
+            ("digest".to_string(), 2, "Broken", StepStatus::Completed),
+            ("digest".to_string(), 2, "Never", StepStatus::Skipped),
+            ("digest".to_string(), 2, "Summarize", StepStatus::Started),
+            ("digest".to_string(), 2, "Summarize", StepStatus::Completed),
+            ("morning".to_string(), 1, "intent:digest", StepStatus::Completed),
+        ]
+    );
+    assert_eq!(events[4].executed_skill.as_deref(), Some("Backup"), "fallback is reported");
+    assert_eq!((events[6].index, events[6].total), (2, 3));
+}
+
+#[tokio::test]
+async fn failed_step_reports_error() {
+    let orch = orchestrator();
+    orch.blueprint().upsert_intent("fragile", vec![PlanStep::new("Broken")]).unwrap();
+    let mut rx = orch.subscribe_progress();
+    let ctx = TenantContext::new("test");
+    let err = orch
+        .dispatch(&ctx, Goal::AutonomousGoal { intent: "fragile".to_string(), context: None })
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("broken on purpose"));
+
+    assert_eq!(rx.try_recv().unwrap().status, StepStatus::Started);
+    let failed = rx.try_recv().unwrap();
+    assert_eq!(failed.status, StepStatus::Failed);
+    assert_eq!(failed.error.as_deref(), Some("broken on purpose"));
+    assert_eq!(failed.agent_id, pagi_core::DEFAULT_AGENT_ID);
+}
+
+#[test]
+fn control_signals_publish_full_state() {
+    let orch = orchestrator();
+    let mut rx = orch.subscribe_control();
+    orch.pagi_apply_control_signal(ControlPanelMessage::KbState { index: 2, active: false });
+    orch.pagi_apply_control_signal(ControlPanelMessage::SkillsEnabled(false));
+
+    match rx.try_recv().unwrap() {
+        ControlPanelMessage::FullState { kb_states, skills_enabled, .. } => {
+            assert_eq!(kb_states, [true, true, false, true, true, true, true, true]);
+            assert!(skills_enabled);
+        }
+        other => panic!("expected FullState, got {:?}", other),
+    }
+    match rx.try_recv().unwrap() {
+        ControlPanelMessage::FullState { skills_enabled, .. } => assert!(!skills_enabled),
+        other => panic!("expected FullState, got {:?}", other),
+    }
+    assert!(!orch.pagi_kb_active(3));
+}
@@ -0,0 +1,70 @@
+//! Prefix and range scans: ordered, bounded to their keys, and paged by cursor.
+
+use pagi_core::{AgentMessage, EventRecord, KbType, KnowledgeStore};
+
+fn keys(page: &pagi_core::ScanPage) -> Vec<&str> {
+    page.entries.iter().map(|(k, _)| k.as_str()).collect()
+}
+
+#[test]
+fn prefix_scans_page_forward_and_backward_within_the_prefix() {
+    let store = KnowledgeStore::open_temporary().unwrap();
+    let logos = KbType::Logos.slot_id();
+    for key in ["doc/a", "doc/b", "doc/c", "doc/d", "doc/e", "docs", "doc", "note/a"] {
+        store.insert(logos, key, key.as_bytes()).unwrap();
+    }
+
+    let first = store.scan_prefix(logos, "doc/", None, 2).unwrap();
+    assert_eq!(keys(&first), ["doc/a", "doc/b"]);
+    assert_eq!(first.entries[0].1, b"doc/a");
+    assert_eq!(first.next_cursor.as_deref(), Some("doc/b"));
+    let second = store.scan_prefix(logos, "doc/", first.next_cursor.as_deref(), 2).unwrap();
+    assert_eq!(keys(&second), ["doc/c", "doc/d"]);
+    let last = store.scan_prefix(logos, "doc/", second.next_cursor.as_deref(), 2).unwrap();
+    assert_eq!(keys(&last), ["doc/e"]);
+    assert_eq!(last.next_cursor, None);
+
+    let newest = store.scan_prefix_rev(logos, "doc/", None, 3).unwrap();
+    assert_eq!(keys(&newest), ["doc/e", "doc/d", "doc/c"]);
+    let older = store.scan_prefix_rev(logos, "doc/", newest.next_cursor.as_deref(), 3).unwrap();
+    assert_eq!(keys(&older), ["doc/b", "doc/a"]);
+    assert_eq!(older.next_cursor, None);
+
+    assert!(store.scan_prefix(logos, "missing/", None, 10).unwrap().entries.is_empty());
+    assert!(store.scan_prefix(logos, "doc/", None, 0).unwrap().entries.is_empty());
+}
+
+#[test]
+fn range_scan_is_half_open_and_resumes_after_the_cursor() {
+    let store = KnowledgeStore::open_temporary().unwrap();
+    let logos = KbType::Logos.slot_id();
+    for key in ["a", "b", "c", "d"] {
+        store.insert(logos, key, b"v").unwrap();
+    }
+    assert_eq!(keys(&store.scan_range(logos, "b", Some("d"), None, 10).unwrap()), ["b", "c"]);
+    assert_eq!(keys(&store.scan_range(logos, "b", None, Some("b"), 10).unwrap()), ["c", "d"]);
+    assert!(store.scan_range(logos, "c", Some("b"), None, 10).unwrap().entries.is_empty());
+}
+
+#[test]
+fn streams_read_newest_first_and_inbox_agents_are_discovered() {
+    let store = KnowledgeStore::open_temporary().unwrap();
+    for (ts, reflection) in [(999, "oldest"), (1_000, "middle"), (1_700_000_000_000, "newest")] {
+        let event = EventRecord::at(ts, "Chronos", reflection);
+        store.append_chronos_event("scout", &event).unwrap();
+    }
+    store.append_chronos_event("scout2", &store.event("Chronos", "other agent")).unwrap();
+    let recent = store.get_recent_chronos_events("scout", 2).unwrap();
+    let reflections: Vec<&str> = recent.iter().map(|e| e.reflection.as_str()).collect();
+    assert_eq!(reflections, ["newest", "middle"]);
+
+    for (agent, text) in [("sales", "first"), ("sales", "second"), ("sales-eu", "hi"), ("ops", "ping")] {
+        store.push_agent_message("lead", agent, &serde_json::json!({ "text": text })).unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(2));
+    }
+    let inbox: Vec<AgentMessage> = store.get_agent_messages("sales", 10).unwrap();
+    let texts: Vec<&str> = inbox.iter().filter_map(|m| m.payload["text"].as_str()).collect();
+    assert_eq!(texts, ["second", "first"]);
+    // Key order: `-` sorts before `/`.
+    assert_eq!(store.inbox_agent_ids().unwrap(), ["ops", "sales-eu", "sales"]);
+}
@@ -0,0 +1,52 @@
+//! Prompt templates in KB_PNEUMA: versioned saves, history and fallback to the built-ins.
+
+use pagi_core::{render_prompt, KnowledgeStore, PromptTemplate, HEARTBEAT_BACKGROUND_TASK_PROMPT, SYSTEM_PERSONA_PROMPT};
+
+#[test]
+fn saves_bump_the_version_and_keep_history() {
+    let store = KnowledgeStore::open_temporary().unwrap();
+    assert_eq!(store.get_prompt("greeting"), None);
+    assert!(store.prompt_versions("greeting").unwrap().is_empty());
+
+    let first = store.put_prompt("greeting", "Hello {{name}}", Some("Greets a user")).unwrap();
+    let second = store.put_prompt("greeting", "Hi {{name}}!", None).unwrap();
+    assert_eq!((first.version, second.version), (1, 2));
+    assert_eq!(second.description, "Greets a user");
+
+    let current = store.get_prompt("greeting").unwrap();
+    assert_eq!(current, second);
+    assert_eq!(current.render(&[("name", "Ada")]).unwrap(), "Hi Ada!");
+    let history = store.prompt_versions("greeting").unwrap();
+    assert_eq!(history.iter().map(|p| p.version).collect::<Vec<_>>(), vec![1, 2]);
+    assert_eq!(history[0].template, "Hello {{name}}");
+
+    assert!(store.put_prompt("bad/name", "x", None).is_err());
+    assert!(store.put_prompt(HEARTBEAT_BACKGROUND_TASK_PROMPT, "{{unknown}}", None).is_err());
+}
+
+#[test]
+fn builtins_are_served_until_overridden() {
+    let store = KnowledgeStore::open_temporary().unwrap();
+    let vars = [("agent_id", "sales"), ("task", "check leads")];
+    let builtin = render_prompt(Some(&store), HEARTBEAT_BACKGROUND_TASK_PROMPT, &vars).unwrap();
+    assert_eq!(builtin, render_prompt(None, HEARTBEAT_BACKGROUND_TASK_PROMPT, &vars).unwrap());
+    assert!(builtin.contains("agent_id=sales") && builtin.contains("check leads"));
+    assert_eq!(store.get_prompt(HEARTBEAT_BACKGROUND_TASK_PROMPT).unwrap().version, 0);
+
+    store
+        .put_prompt(HEARTBEAT_BACKGROUND_TASK_PROMPT, "[{{agent_id}}] {{task}}: one line, please.", None)
+        .unwrap();
+    assert_eq!(
+        render_prompt(Some(&store), HEARTBEAT_BACKGROUND_TASK_PROMPT, &vars).unwrap(),
+        "[sales] check leads: one line, please."
+    );
+
+    let names: Vec<String> = store.list_prompts().unwrap().into_iter().map(|p| p.name).collect();
+    assert!(names.contains(&SYSTEM_PERSONA_PROMPT.to_string()));
+    assert_eq!(names.iter().filter(|n| *n == HEARTBEAT_BACKGROUND_TASK_PROMPT).count(), 1);
+    assert!(render_prompt(Some(&store), "missing", &[]).is_err());
+
+    store.put_prompt(SYSTEM_PERSONA_PROMPT, "You are Ada, a terse assistant.", None).unwrap();
+    assert!(store.build_system_directive("default", "user").starts_with("You are Ada, a terse assistant."));
+    assert!(PromptTemplate::builtin(SYSTEM_PERSONA_PROMPT).unwrap().template.starts_with("You are PAGI"));
+}
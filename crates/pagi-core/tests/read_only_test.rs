@@ -0,0 +1,51 @@
+//! Read-only stores: a second process reads the knowledge store a writer holds open — live on
+//! SQLite, from the newest snapshot on sled — and every write is rejected.
+
+use pagi_core::{KbRecord, KbType, KnowledgeStore, SnapshotManager, SnapshotReason, StorageBackend};
+use std::sync::Arc;
+
+#[test]
+fn sled_store_is_read_from_its_newest_snapshot_while_the_writer_holds_it() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("pagi_knowledge");
+    let writer = Arc::new(KnowledgeStore::open_path(&path).unwrap());
+    let logos = KbType::Logos.slot_id();
+    let err = KnowledgeStore::open_read_only(&path).err().unwrap();
+    assert!(err.to_string().contains("no snapshot"), "{}", err);
+
+    writer.insert_record(logos, "fact", &KbRecord::new("the roof warranty is ten years", writer.now_ms())).unwrap();
+    let snapshot = SnapshotManager::new(Arc::clone(&writer), dir.path().join("snapshots"))
+        .create(SnapshotReason::Manual)
+        .unwrap();
+    writer.insert_record(logos, "later", &KbRecord::new("written after the snapshot", writer.now_ms())).unwrap();
+
+    let reader = KnowledgeStore::open_read_only(&path).unwrap();
+    assert!(reader.is_read_only());
+    assert!(!writer.is_read_only());
+    assert_eq!(reader.get_record(logos, "fact").unwrap().unwrap().content, "the roof warranty is ten years");
+    assert_eq!(reader.get(logos, "later").unwrap(), None);
+    assert_eq!(reader.text_search(&[logos], "warranty", 5).unwrap().len(), 1);
+    assert!(reader.insert_record(logos, "fact", &KbRecord::new("overwritten", reader.now_ms())).is_err());
+    assert!(reader.remove(logos, "fact").is_err());
+
+    // A snapshot directory can be opened directly.
+    let pinned = KnowledgeStore::open_read_only(dir.path().join("snapshots").join(&snapshot.id)).unwrap();
+    assert_eq!(pinned.scan_keys(logos).unwrap(), ["fact"]);
+}
+
+#[test]
+fn sqlite_store_is_read_live() {
+    let dir = tempfile::tempdir().unwrap();
+    let writer = KnowledgeStore::open_with_backend(dir.path(), StorageBackend::Sqlite).unwrap();
+    let reader = KnowledgeStore::open_read_only(dir.path()).unwrap();
+    assert_eq!(reader.backend_name(), "sqlite");
+    assert!(reader.is_read_only());
+    let oikos = KbType::Oikos.slot_id();
+
+    writer.insert_record(oikos, "task", &KbRecord::new("call the supplier", writer.now_ms())).unwrap();
+    assert_eq!(reader.get_record(oikos, "task").unwrap().unwrap().content, "call the supplier");
+    assert!(reader.insert_record(oikos, "task", &KbRecord::new("cancelled", reader.now_ms())).is_err());
+    assert!(reader.compare_and_swap(oikos, "new", None, Some(b"x")).is_err());
+    assert!(reader.transaction(oikos, |tx| tx.remove("task").map(|_| ())).is_err());
+    assert_eq!(writer.get_record(oikos, "task").unwrap().unwrap().content, "call the supplier");
+}
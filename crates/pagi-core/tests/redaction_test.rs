@@ -0,0 +1,70 @@
+//! PII redaction: pattern masking, and masked Chronos writes with a `Redaction` audit record.
+
+use pagi_core::{
+    AuditQuery, ConversationManager, KnowledgeStore, PiiKind, RedactionConfig, Redactor,
+    REDACTION_GOAL_TYPE,
+};
+use std::sync::Arc;
+
+#[test]
+fn patterns_mask_each_kind_and_count_it() {
+    let redactor = Redactor::new();
+    let (masked, report) = redactor.redact_text(
+        "Mail jane.doe@example.com or call (555) 123-4567; SSN 123-45-6789, api_key=abcd1234efgh and sk-proj-ABCDEFGHIJKLMNOP1234",
+    );
+    assert_eq!(
+        masked,
+        "Mail [REDACTED:email] or call [REDACTED:phone]; SSN [REDACTED:ssn], [REDACTED:api_key] and [REDACTED:api_key]"
+    );
+    assert_eq!(report.0.get(&PiiKind::ApiKey), Some(&2));
+    assert_eq!(report.total(), 5);
+
+    // Ids and timestamps are not phone numbers.
+    let (kept, report) = redactor.redact_text("order 1234567890 at 1700000000000");
+    assert_eq!(kept, "order 1234567890 at 1700000000000");
+    assert!(report.is_empty());
+
+    // Only the configured kinds are masked; strings nested in JSON are masked, keys are not.
+    let emails_only = Redactor::with_kinds(&[PiiKind::Email]);
+    let mut value = serde_json::json!({ "contact": ["a@b.io", "555-123-4567"], "a@b.io": 1 });
+    assert_eq!(emails_only.redact_value(&mut value).total(), 1);
+    assert_eq!(value, serde_json::json!({ "contact": ["[REDACTED:email]", "555-123-4567"], "a@b.io": 1 }));
+
+    let (masked, report) = redactor.mask_terms("Jane Doe lives at 1 Main St, Jane", &["Jane Doe".into(), "Jane".into(), "J".into()]);
+    assert_eq!(masked, "[REDACTED:classified] lives at 1 Main St, [REDACTED:classified]");
+    assert_eq!(report.0.get(&PiiKind::Classified), Some(&2));
+
+    assert!(Redactor::from_config(&RedactionConfig::default()).is_none());
+}
+
+#[test]
+fn chronos_writes_are_masked_and_audited() {
+    let store = Arc::new(KnowledgeStore::open_temporary().unwrap().with_redaction(Redactor::new()));
+    let event = store.event("Chronos", "User jane@example.com asked for a callback").with_outcome("called 555-123-4567");
+    store.append_chronos_event("sales", &event).unwrap();
+    let stored = store.get_recent_chronos_events("sales", 1).unwrap();
+    assert_eq!(stored[0].reflection, "User [REDACTED:email] asked for a callback");
+    assert_eq!(stored[0].outcome.as_deref(), Some("called [REDACTED:phone]"));
+
+    let manager = ConversationManager::new(Arc::clone(&store));
+    let turn = manager.record_turn("acme", "sales", "s1", "my SSN is 123-45-6789", "Thanks").unwrap();
+    assert_eq!(turn.user, "my SSN is [REDACTED:ssn]");
+    assert_eq!(manager.history("acme", "sales", "s1", 1).unwrap()[0].user, "my SSN is [REDACTED:ssn]");
+    // Nothing to mask, nothing audited.
+    manager.record_turn("acme", "sales", "s1", "hello", "hi").unwrap();
+
+    let audit = store
+        .query_audit(&AuditQuery {
+            goal_type: Some(REDACTION_GOAL_TYPE.to_string()),
+            ..AuditQuery::default()
+        })
+        .unwrap();
+    let sites: Vec<_> = audit.records.iter().filter_map(|r| r.skill.as_deref()).collect();
+    assert_eq!(sites, vec!["conversation_turn", "chronos_event"]);
+    assert_eq!(audit.records[0].tenant_id, "acme");
+    assert_eq!(audit.records[0].redactions.0.get(&PiiKind::Ssn), Some(&1));
+    assert_eq!(audit.records[1].redactions.total(), 2);
+    // The audit log keeps counts, never the masked values.
+    let raw = serde_json::to_string(&audit.records).unwrap();
+    assert!(!raw.contains("jane@example.com") && !raw.contains("123-45-6789"));
+}
@@ -0,0 +1,110 @@
+//! Scheduled goals: recurring and delayed goals dispatched by the Heartbeat and logged to Chronos.
+
+use pagi_core::{
+    AgentSkill, Goal, KnowledgeStore, Orchestrator, ScheduledGoal, SchedulerRegistry, SkillRegistry, TenantContext,
+};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+
+const MINUTE_MS: i64 = 60_000;
+
+/// Counts calls and records the tenant it ran for.
+struct Refresh(Arc<AtomicU32>);
+
+#[async_trait::async_trait]
+impl AgentSkill for Refresh {
+    fn name(&self) -> &str {
+        "Refresh"
+    }
+
+    async fn execute(
+        &self,
+        ctx: &TenantContext,
+        _payload: Option<serde_json::Value>,
+    ) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>> {
+        self.0.fetch_add(1, Ordering::SeqCst);
+        Ok(serde_json::json!({ "tenant": ctx.tenant_id }))
+    }
+}
+
+fn ctx() -> TenantContext {
+    TenantContext {
+        tenant_id: "acme".to_string(),
+        correlation_id: None,
+        agent_id: Some("ops".to_string()),
+        ..Default::default()
+    }
+}
+
+fn refresh() -> Goal {
+    Goal::ExecuteSkill {
+        name: "Refresh".to_string(),
+        payload: None,
+    }
+}
+
+#[tokio::test]
+async fn recurring_and_delayed_goals_run_when_due() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = Arc::new(KnowledgeStore::open_path(dir.path()).unwrap());
+    let calls = Arc::new(AtomicU32::new(0));
+    let mut registry = SkillRegistry::new();
+    registry.register(Arc::new(Refresh(Arc::clone(&calls))));
+    let orchestrator = Orchestrator::new(Arc::new(registry));
+    let scheduler = SchedulerRegistry::new(Arc::clone(&store));
+
+    let now_ms = store.now_ms();
+    let recurring = ScheduledGoal::recurring("refresh", "@every 5m", refresh(), ctx(), now_ms).unwrap();
+    let delayed = ScheduledGoal::delayed("once", now_ms + MINUTE_MS, refresh(), ctx(), now_ms);
+    scheduler.set(&recurring).unwrap();
+    scheduler.set(&delayed).unwrap();
+
+    // Nothing is due yet.
+    assert!(scheduler.run_due(&orchestrator).await.unwrap().is_empty());
+
+    let t1 = now_ms + 2 * MINUTE_MS;
+    let runs = scheduler.run_due_at(&orchestrator, t1).await.unwrap();
+    assert_eq!(runs.len(), 1);
+    assert_eq!(runs[0].name, "once");
+    assert!(runs[0].ok && runs[0].next_run_ms.is_none());
+    let once = scheduler.get(&delayed.id).unwrap();
+    assert!(!once.enabled);
+    assert_eq!(once.last_status.as_deref(), Some("ok"));
+
+    let t2 = now_ms + 6 * MINUTE_MS;
+    let runs = scheduler.run_due_at(&orchestrator, t2).await.unwrap();
+    assert_eq!(runs.len(), 1);
+    assert_eq!(runs[0].next_run_ms, Some(t2 + 5 * MINUTE_MS));
+    // Already rescheduled: running again at the same instant does nothing.
+    assert!(scheduler.run_due_at(&orchestrator, t2).await.unwrap().is_empty());
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+    let events = store.get_recent_chronos_events("ops", 10).unwrap();
+    assert_eq!(events.len(), 2);
+    assert!(events.iter().all(|e| e.skill_name.as_deref() == Some("scheduler")));
+}
+
+#[tokio::test]
+async fn failed_runs_are_recorded_and_entries_removable() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = Arc::new(KnowledgeStore::open_path(dir.path()).unwrap());
+    let orchestrator = Orchestrator::new(Arc::new(SkillRegistry::new()));
+    let now_ms = store.now_ms();
+    let scheduler = SchedulerRegistry::new(store);
+
+    let entry = ScheduledGoal::recurring("missing skill", "0 7 * * *", refresh(), ctx(), now_ms).unwrap();
+    scheduler.set(&entry).unwrap();
+    let runs = scheduler
+        .run_due_at(&orchestrator, entry.next_run_ms.unwrap())
+        .await
+        .unwrap();
+    assert!(!runs[0].ok);
+    let stored = scheduler.get(&entry.id).unwrap();
+    assert_eq!(stored.last_status.as_deref(), Some("failed"));
+    assert_eq!(stored.next_run_ms, Some(entry.next_run_ms.unwrap() + 24 * 60 * MINUTE_MS));
+    assert!(stored.enabled);
+
+    assert!(ScheduledGoal::recurring("bad", "every morning", refresh(), ctx(), now_ms).is_err());
+    assert!(scheduler.remove(&entry.id).unwrap());
+    assert!(scheduler.list().unwrap().is_empty());
+}
@@ -0,0 +1,157 @@
+//! Secrets providers (`[secrets]`): lookup order across providers, the file and Vault providers,
+//! and refreshing the cache without losing values when a provider fails.
+
+use async_trait::async_trait;
+use pagi_core::{CoreConfig, EnvSecrets, FileSecrets, Secrets, SecretsConfig, SecretsProvider, SecretsProviderConfig};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+/// Provider backed by a map the test can change (or make fail).
+#[derive(Clone, Default)]
+struct Fixed(Arc<Mutex<Option<HashMap<String, String>>>>);
+
+impl Fixed {
+    fn set(&self, values: &[(&str, &str)]) {
+        *self.0.lock().unwrap() = Some(values.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect());
+    }
+
+    fn fail(&self) {
+        *self.0.lock().unwrap() = None;
+    }
+}
+
+#[async_trait]
+impl SecretsProvider for Fixed {
+    fn name(&self) -> &'static str {
+        "fixed"
+    }
+
+    async fn fetch(&self, names: &[String]) -> Result<HashMap<String, String>, String> {
+        let values = self.0.lock().unwrap().clone().ok_or("unreachable")?;
+        Ok(values.into_iter().filter(|(k, _)| names.contains(k)).collect())
+    }
+}
+
+fn names(list: &[&str]) -> Vec<String> {
+    list.iter().map(|n| n.to_string()).collect()
+}
+
+#[tokio::test]
+async fn first_provider_holding_a_name_wins_and_refresh_keeps_values_on_failure() {
+    let (first, second) = (Fixed::default(), Fixed::default());
+    first.set(&[("A", "from-first")]);
+    second.set(&[("A", "shadowed"), ("B", "from-second")]);
+    let secrets = Secrets::new(vec![Box::new(first.clone()), Box::new(second.clone())], Duration::ZERO);
+    secrets.load(&names(&["A", "B", "C"])).await.unwrap();
+    assert_eq!(secrets.get("A").as_deref(), Some("from-first"));
+    assert_eq!(secrets.get("B").as_deref(), Some("from-second"));
+    assert_eq!(secrets.get("C"), None);
+    assert_eq!(secrets.get("never-loaded"), None);
+
+    // Rotation is picked up on refresh; a name can move between providers.
+    first.set(&[("A", "rotated"), ("C", "new")]);
+    assert_eq!(secrets.refresh().await.unwrap(), 2);
+    assert_eq!(secrets.get("A").as_deref(), Some("rotated"));
+    assert_eq!(secrets.get("C").as_deref(), Some("new"));
+    assert_eq!(secrets.refresh().await.unwrap(), 0);
+
+    second.fail();
+    first.set(&[("A", "ignored")]);
+    assert!(secrets.refresh().await.unwrap_err().contains("fixed"));
+    assert_eq!(secrets.get("A").as_deref(), Some("rotated"));
+    assert_eq!(secrets.get("B").as_deref(), Some("from-second"));
+    assert!(secrets.load(&names(&["D"])).await.is_err());
+}
+
+#[tokio::test]
+async fn file_provider_reads_one_file_per_name() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("PAGI_API_KEY"), "file-key\n").unwrap();
+    std::fs::write(dir.path().join("EMPTY"), "\n").unwrap();
+    let provider = FileSecrets::new(dir.path());
+    let found = provider.fetch(&names(&["PAGI_API_KEY", "EMPTY", "MISSING", "../PAGI_API_KEY"])).await.unwrap();
+    assert_eq!(found.len(), 1);
+    assert_eq!(found["PAGI_API_KEY"], "file-key");
+
+    std::env::set_var("PAGI_SECRETS_TEST_ENV", "env-value");
+    let found = EnvSecrets.fetch(&names(&["PAGI_SECRETS_TEST_ENV", "PAGI_SECRETS_TEST_UNSET"])).await.unwrap();
+    assert_eq!(found.get("PAGI_SECRETS_TEST_ENV").map(String::as_str), Some("env-value"));
+    assert_eq!(found.len(), 1);
+}
+
+#[tokio::test]
+async fn vault_provider_reads_kv_v2_fields_with_the_token() {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let requests = Arc::new(Mutex::new(Vec::<String>::new()));
+    let seen = Arc::clone(&requests);
+    tokio::spawn(async move {
+        while let Ok((mut socket, _)) = listener.accept().await {
+            let mut buf = vec![0u8; 4096];
+            let n = socket.read(&mut buf).await.unwrap_or(0);
+            seen.lock().unwrap().push(String::from_utf8_lossy(&buf[..n]).to_lowercase());
+            let body = r#"{"data":{"data":{"PAGI_SHADOW_KEY":"vault-shadow","PORT":8200},"metadata":{"version":3}}}"#;
+            let reply = format!("HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}", body.len(), body);
+            let _ = socket.write_all(reply.as_bytes()).await;
+        }
+    });
+
+    std::env::set_var("PAGI_SECRETS_TEST_VAULT_TOKEN", "s.test-token");
+    let config = SecretsConfig {
+        providers: vec![SecretsProviderConfig::Vault {
+            addr: format!("http://{}/", addr),
+            path: "secret/data/pagi".into(),
+            token_env: "PAGI_SECRETS_TEST_VAULT_TOKEN".into(),
+            token_file: None,
+            namespace: Some("team".into()),
+        }],
+        refresh_secs: 0,
+        names: Vec::new(),
+    };
+    let secrets = Secrets::from_config(&config).unwrap();
+    assert_eq!(secrets.provider_names(), vec!["vault"]);
+    secrets.load(&names(&["PAGI_SHADOW_KEY", "PORT", "ABSENT"])).await.unwrap();
+    assert_eq!(secrets.get("PAGI_SHADOW_KEY").as_deref(), Some("vault-shadow"));
+    assert_eq!(secrets.get("PORT").as_deref(), Some("8200"));
+    assert_eq!(secrets.get("ABSENT"), None);
+
+    let request = requests.lock().unwrap()[0].clone();
+    assert!(request.starts_with("get /v1/secret/data/pagi "));
+    assert!(request.contains("x-vault-token: s.test-token"));
+    assert!(request.contains("x-vault-namespace: team"));
+}
+
+#[test]
+fn config_lists_the_names_pagi_reads() {
+    let config: CoreConfig = toml::from_str(
+        r#"
+        app_name = "test"
+        port = 8001
+        storage_path = "./data"
+        llm_mode = "mock"
+
+        [llm_providers.claude]
+        kind = "anthropic"
+        [llm_providers.local]
+        kind = "ollama"
+        [llm_providers.router]
+        api_key_env = "OPENROUTER_KEY"
+
+        [secrets]
+        names = ["EXTRA"]
+        [[secrets.providers]]
+        kind = "file"
+        dir = "/run/secrets"
+        "#,
+    )
+    .unwrap();
+    let names = config.secret_names();
+    for name in ["PAGI_API_KEY", "PAGI_SHADOW_KEY", "PAGI_LLM_API_KEY", "ANTHROPIC_API_KEY", "OPENROUTER_KEY", "EXTRA"] {
+        assert!(names.iter().any(|n| n == name), "{} missing from {:?}", name, names);
+    }
+    assert_eq!(config.secrets.providers, vec![SecretsProviderConfig::File { dir: "/run/secrets".into() }]);
+    assert_eq!(config.secrets.refresh_secs, 300);
+    assert_eq!(SecretsConfig::default().providers, vec![SecretsProviderConfig::Env]);
+}
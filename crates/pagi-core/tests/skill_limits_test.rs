@@ -0,0 +1,168 @@
+//! Per-skill execution limits: timeouts, payload/result sizes and sandbox roots.
+
+mod common;
+
+use common::ctx;
+use pagi_core::{
+    AgentSkill, BlueprintRegistry, Goal, KnowledgeStore, LimitsConfig, Orchestrator, Plan, PlanStep, SkillError,
+    SkillLimits, SkillRegistry, StepPolicy, TenantContext,
+};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+
+/// Sleeps for the payload's `sleep_ms`, then returns `size` bytes of text.
+struct Worker;
+
+#[async_trait::async_trait]
+impl AgentSkill for Worker {
+    fn name(&self) -> &str {
+        "Worker"
+    }
+
+    async fn execute(
+        &self,
+        _ctx: &TenantContext,
+        payload: Option<serde_json::Value>,
+    ) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>> {
+        let payload = payload.unwrap_or_default();
+        let sleep_ms = payload.get("sleep_ms").and_then(|v| v.as_u64()).unwrap_or(0);
+        tokio::time::sleep(std::time::Duration::from_millis(sleep_ms)).await;
+        let size = payload.get("size").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+        Ok(serde_json::json!({ "text": "x".repeat(size) }))
+    }
+}
+
+/// Counts its calls; confined to `root`.
+struct FileSkill {
+    root: PathBuf,
+    calls: AtomicU32,
+}
+
+#[async_trait::async_trait]
+impl AgentSkill for FileSkill {
+    fn name(&self) -> &str {
+        "FileSkill"
+    }
+
+    fn limits(&self) -> SkillLimits {
+        SkillLimits {
+            sandbox_root: Some(self.root.clone()),
+            ..Default::default()
+        }
+    }
+
+    async fn execute(
+        &self,
+        _ctx: &TenantContext,
+        _payload: Option<serde_json::Value>,
+    ) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>> {
+        let calls = self.calls.fetch_add(1, Ordering::SeqCst) + 1;
+        Ok(serde_json::json!({ "status": "ok", "calls": calls }))
+    }
+}
+
+fn execute(name: &str, payload: serde_json::Value) -> Goal {
+    Goal::ExecuteSkill {
+        name: name.into(),
+        payload: Some(payload),
+    }
+}
+
+fn setup(limits: LimitsConfig) -> (Orchestrator, Arc<KnowledgeStore>, Arc<FileSkill>, PathBuf) {
+    let root = std::env::temp_dir().join(format!("pagi_skill_limits_{}", uuid::Uuid::new_v4().simple()));
+    std::fs::create_dir_all(&root).unwrap();
+    let file_skill = Arc::new(FileSkill {
+        root: root.clone(),
+        calls: AtomicU32::new(0),
+    });
+    let mut registry = SkillRegistry::new();
+    registry.register(Arc::new(Worker));
+    registry.register(Arc::clone(&file_skill) as Arc<dyn AgentSkill>);
+    let mut plans = HashMap::new();
+    let policy = StepPolicy {
+        max_retries: 2,
+        backoff_ms: 1,
+        ..StepPolicy::default()
+    };
+    plans.insert(
+        "write".to_string(),
+        Plan {
+            steps: vec![PlanStep {
+                policy,
+                ..PlanStep::new("FileSkill")
+            }],
+        },
+    );
+    let store = Arc::new(KnowledgeStore::open_temporary().unwrap());
+    let orch = Orchestrator::with_blueprint(Arc::new(registry), Arc::new(BlueprintRegistry::from_plans(plans)))
+        .with_knowledge(Arc::clone(&store))
+        .with_skill_limits(limits);
+    (orch, store, file_skill, root)
+}
+
+fn violation<'a>(err: &'a (dyn std::error::Error + Send + Sync + 'static)) -> &'a SkillError {
+    err.downcast_ref::<SkillError>().unwrap_or_else(|| panic!("not a SkillError: {}", err))
+}
+
+#[tokio::test]
+async fn slow_skills_time_out_and_are_logged_to_chronos() {
+    let mut limits = LimitsConfig::default();
+    limits.skills.insert("Worker".into(), SkillLimits { timeout_ms: Some(20), ..Default::default() });
+    let (orch, store, _, _) = setup(limits);
+
+    let err = orch.dispatch(&ctx(), execute("Worker", serde_json::json!({ "sleep_ms": 5000 }))).await.unwrap_err();
+    assert_eq!(violation(err.as_ref()), &SkillError::Timeout { skill: "Worker".into(), timeout_ms: 20 });
+    assert!(orch.dispatch(&ctx(), execute("Worker", serde_json::json!({ "sleep_ms": 1 }))).await.is_ok());
+
+    let events = store.get_recent_chronos_events("default", 10).unwrap();
+    let logged = events.iter().find(|e| e.skill_name.as_deref() == Some("Worker")).expect("violation event");
+    assert_eq!(logged.outcome.as_deref(), Some("timeout"));
+    assert!(logged.reflection.contains("timed out after 20 ms"));
+    assert_eq!(orch.skill_stats().get("Worker").unwrap().total_failures, 1);
+}
+
+#[tokio::test]
+async fn oversized_payloads_and_results_are_rejected() {
+    let limits = LimitsConfig {
+        skill_max_payload_bytes: 64,
+        skill_max_result_bytes: 128,
+        ..LimitsConfig::default()
+    };
+    let (orch, _, _, _) = setup(limits);
+
+    let big_payload = serde_json::json!({ "padding": "y".repeat(100) });
+    let err = orch.dispatch(&ctx(), execute("Worker", big_payload)).await.unwrap_err();
+    assert!(matches!(violation(err.as_ref()), SkillError::PayloadTooLarge { limit: 64, .. }));
+
+    let err = orch.dispatch(&ctx(), execute("Worker", serde_json::json!({ "size": 500 }))).await.unwrap_err();
+    assert!(matches!(violation(err.as_ref()), SkillError::ResultTooLarge { limit: 128, .. }));
+    assert!(orch.dispatch(&ctx(), execute("Worker", serde_json::json!({ "size": 10 }))).await.is_ok());
+}
+
+#[tokio::test]
+async fn paths_outside_the_sandbox_never_reach_the_skill() {
+    let (orch, store, file_skill, root) = setup(LimitsConfig::default());
+
+    let out = orch.dispatch(&ctx(), execute("FileSkill", serde_json::json!({ "path": "notes/today.md" }))).await.unwrap();
+    assert_eq!(out["status"], "ok");
+    let err = orch
+        .dispatch(&ctx(), execute("FileSkill", serde_json::json!({ "path": "../outside.md" })))
+        .await
+        .unwrap_err();
+    assert_eq!(violation(err.as_ref()).kind(), "outside_sandbox");
+
+    // Plan steps are not retried on a sandbox violation.
+    let goal = Goal::AutonomousGoal {
+        intent: "write".into(),
+        context: Some(serde_json::json!({ "path": "/etc/passwd" })),
+    };
+    let err = orch.dispatch(&ctx(), goal).await.unwrap_err();
+    assert_eq!(violation(err.as_ref()).skill(), "FileSkill");
+    assert_eq!(file_skill.calls.load(Ordering::SeqCst), 1);
+
+    let events = store.get_recent_chronos_events("default", 10).unwrap();
+    assert_eq!(events.iter().filter(|e| e.outcome.as_deref() == Some("outside_sandbox")).count(), 2);
+    let _ = std::fs::remove_dir_all(&root);
+}
@@ -0,0 +1,107 @@
+//! Per-skill execution stats: recorded on every call and used to pick healthy fallbacks.
+
+mod common;
+
+use common::ctx;
+use pagi_core::{
+    AgentSkill, BlueprintRegistry, Goal, KnowledgeStore, Orchestrator, Plan, PlanStep, SkillRegistry, TenantContext,
+};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+
+/// Always fails or always succeeds, counting calls.
+struct Fixed {
+    name: &'static str,
+    ok: bool,
+    calls: AtomicU32,
+}
+
+#[async_trait::async_trait]
+impl AgentSkill for Fixed {
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    async fn execute(
+        &self,
+        _ctx: &TenantContext,
+        _payload: Option<serde_json::Value>,
+    ) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        if self.ok {
+            Ok(serde_json::json!({ "by": self.name }))
+        } else {
+            Err(format!("{} is down", self.name).into())
+        }
+    }
+}
+
+fn goal() -> Goal {
+    Goal::AutonomousGoal {
+        intent: "fetch".to_string(),
+        context: None,
+    }
+}
+
+fn setup(store: Arc<KnowledgeStore>) -> (Orchestrator, Arc<Fixed>, Arc<Fixed>) {
+    let primary = Arc::new(Fixed { name: "Primary", ok: false, calls: AtomicU32::new(0) });
+    let backup = Arc::new(Fixed { name: "Backup", ok: true, calls: AtomicU32::new(0) });
+    let mut registry = SkillRegistry::new();
+    registry.register(Arc::clone(&primary) as Arc<dyn AgentSkill>);
+    registry.register(Arc::clone(&backup) as Arc<dyn AgentSkill>);
+    let mut plans = HashMap::new();
+    plans.insert(
+        "fetch".to_string(),
+        Plan {
+            steps: vec![PlanStep::new("Primary").with_fallbacks(["Backup"])],
+        },
+    );
+    let orchestrator =
+        Orchestrator::with_blueprint(Arc::new(registry), Arc::new(BlueprintRegistry::from_plans(plans)))
+            .with_knowledge(store);
+    (orchestrator, primary, backup)
+}
+
+#[tokio::test]
+async fn failing_step_falls_back_and_unhealthy_primary_is_skipped() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = Arc::new(KnowledgeStore::open_path(dir.path()).unwrap());
+    let (orchestrator, primary, backup) = setup(Arc::clone(&store));
+
+    let result = orchestrator.dispatch(&ctx(), goal()).await.unwrap();
+    assert_eq!(result["by"], "Backup");
+    assert_eq!((primary.calls.load(Ordering::SeqCst), backup.calls.load(Ordering::SeqCst)), (1, 1));
+
+    for _ in 0..4 {
+        orchestrator.dispatch(&ctx(), goal()).await.unwrap();
+    }
+    let stats = orchestrator.skill_stats();
+    assert!(stats.is_unhealthy("Primary"));
+    assert_eq!(stats.get("Backup").unwrap().success_rate(), Some(1.0));
+
+    // With five straight failures recorded, the primary is now tried last (so not at all).
+    orchestrator.dispatch(&ctx(), goal()).await.unwrap();
+    assert_eq!(primary.calls.load(Ordering::SeqCst), 5);
+    assert_eq!(backup.calls.load(Ordering::SeqCst), 6);
+    drop(orchestrator);
+
+    // Stats survive a restart via KB-5.
+    let (reopened, _, _) = setup(store);
+    assert_eq!(reopened.skill_stats().get("Primary").unwrap().total_failures, 5);
+}
+
+#[tokio::test]
+async fn direct_skill_calls_are_recorded() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = Arc::new(KnowledgeStore::open_path(dir.path()).unwrap());
+    let (orchestrator, _, _) = setup(store);
+    let goal = Goal::ExecuteSkill {
+        name: "Primary".to_string(),
+        payload: None,
+    };
+    assert!(orchestrator.dispatch(&ctx(), goal).await.is_err());
+    let stats = orchestrator.skill_stats().get("Primary").unwrap();
+    assert_eq!((stats.total_calls, stats.total_failures), (1, 1));
+    assert_eq!(stats.last_error.as_deref(), Some("Primary is down"));
+}
@@ -0,0 +1,119 @@
+//! Encryption at rest for slots 1–8 (`[encryption] slots`): values are sealed with the vault key,
+//! plaintext written before encryption was turned on is sealed as it is read, and a locked vault
+//! can neither read nor write them.
+
+use pagi_core::{KbRecord, KbType, KnowledgeStore};
+use std::path::Path;
+
+/// Deterministic test key (32 bytes). NOT for production.
+fn test_key() -> [u8; 32] {
+    let mut key = [0u8; 32];
+    for (i, b) in key.iter_mut().enumerate() {
+        *b = (i as u8).wrapping_mul(7).wrapping_add(42);
+    }
+    key
+}
+
+/// Opens the store at `path`, waiting for the previous handle's sled lock to be released (its
+/// flusher thread can hold it for a moment after the drop).
+fn open(path: &Path, key: Option<&[u8; 32]>) -> KnowledgeStore {
+    for _ in 0..100 {
+        if let Ok(store) = KnowledgeStore::open_with_key(path, key) {
+            return store;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(20));
+    }
+    KnowledgeStore::open_with_key(path, key).unwrap()
+}
+
+/// Whether any file under `dir` contains `needle`.
+fn on_disk(dir: &Path, needle: &str) -> bool {
+    std::fs::read_dir(dir).unwrap().flatten().any(|entry| {
+        let path = entry.path();
+        if path.is_dir() {
+            on_disk(&path, needle)
+        } else {
+            let bytes = std::fs::read(&path).unwrap_or_default();
+            bytes.windows(needle.len()).any(|w| w == needle.as_bytes())
+        }
+    })
+}
+
+#[test]
+fn encrypted_slots_seal_values_and_migrate_plaintext_on_read() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("pagi_knowledge");
+    let chronos = KbType::Chronos.slot_id();
+    let kardia = KbType::Kardia.slot_id();
+    let logos = KbType::Logos.slot_id();
+
+    {
+        let store = open(&path, Some(&test_key()));
+        store.insert_record(chronos, "old", &KbRecord::new("said before encryption", store.now_ms())).unwrap();
+        assert!(!store.get_all_status()[chronos as usize - 1].encrypted);
+    }
+    {
+        let store = open(&path, Some(&test_key())).with_encrypted_slots(&[chronos, kardia, 0, 9]);
+        let encrypted: Vec<u8> = store.get_all_status().iter().filter(|s| s.encrypted).map(|s| s.slot_id).collect();
+        assert_eq!(encrypted, vec![chronos, kardia, 9]);
+
+        store.insert_record(chronos, "new", &KbRecord::new("whispered secret", store.now_ms())).unwrap();
+        let long = "prefers morning calls, dislikes small talk. ".repeat(200);
+        store.insert_record(kardia, "prefs", &KbRecord::new(long.clone(), store.now_ms())).unwrap();
+        store.insert_record(logos, "note", &KbRecord::new("public notes", store.now_ms())).unwrap();
+        store
+            .transaction(chronos, |tx| {
+                tx.insert_record("staged", &KbRecord::new("written in a transaction", store.now_ms()))?;
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(store.get_record(chronos, "new").unwrap().unwrap().content, "whispered secret");
+        assert_eq!(store.get_record(kardia, "prefs").unwrap().unwrap().content, long);
+        assert_eq!(store.scan_kv(chronos).unwrap().len(), 3);
+        assert_eq!(store.transaction(chronos, |tx| tx.get_record("staged")).unwrap().unwrap().content, "written in a transaction");
+
+        // Encrypted slots are neither full-text indexed nor archived to plaintext segments.
+        assert!(store.text_search(&[chronos], "whispered", 5).unwrap().is_empty());
+        assert_eq!(store.text_search(&[logos], "public", 5).unwrap().len(), 1);
+        assert!(store.archive_older_than(chronos, i64::MAX).is_err());
+    }
+    {
+        // Without the key, sealed values cannot be read; "old" has not been read since, so it is
+        // still plaintext.
+        let locked = open(&path, None);
+        assert!(locked.get(chronos, "new").unwrap_err().to_string().contains("KB-4 is encrypted"));
+        assert!(locked.get(chronos, "old").unwrap().is_some());
+    }
+    {
+        let store = open(&path, Some(&test_key())).with_encrypted_slots(&[chronos]);
+        assert_eq!(store.get_record(chronos, "old").unwrap().unwrap().content, "said before encryption");
+    }
+    {
+        let locked = open(&path, None).with_encrypted_slots(&[chronos]);
+        assert!(locked.get(chronos, "old").is_err());
+        assert!(locked.insert_record(chronos, "x", &KbRecord::new("refused", locked.now_ms())).is_err());
+        assert!(locked.remove(chronos, "old").is_err());
+        let status = &locked.get_all_status()[chronos as usize - 1];
+        assert!(status.encrypted && status.error.is_some());
+    }
+    {
+        // Turning encryption off leaves sealed values readable.
+        let store = open(&path, Some(&test_key()));
+        assert_eq!(store.get_record(chronos, "new").unwrap().unwrap().content, "whispered secret");
+        assert!(!store.get_all_status()[chronos as usize - 1].encrypted);
+    }
+    assert!(!on_disk(dir.path(), "whispered secret"));
+    assert!(!on_disk(dir.path(), "written in a transaction"));
+}
+
+#[test]
+fn plaintext_that_looks_sealed_round_trips_in_unencrypted_slots() {
+    let store = KnowledgeStore::open_temporary().unwrap();
+    let values: [&[u8]; 3] = [b"\x01hello", b"\xffPKV\x01not a sealed value", b"\xffPKZ\x01not compressed either"];
+    for (i, value) in values.into_iter().enumerate() {
+        let key = format!("k{}", i);
+        store.insert(2, &key, value).unwrap();
+        assert_eq!(store.get(2, &key).unwrap().as_deref(), Some(value));
+    }
+    assert_eq!(store.scan_kv(2).unwrap().len(), 3);
+}
@@ -0,0 +1,73 @@
+//! Snapshots: taken to timestamped directories, pruned by retention, restored per slot or whole.
+
+use pagi_core::{KbRecord, KbType, KnowledgeStore, SnapshotManager, SnapshotReason, SnapshotRetention};
+use std::sync::Arc;
+
+fn content(store: &KnowledgeStore, slot_id: u8, key: &str) -> Option<String> {
+    store.get_record(slot_id, key).unwrap().map(|r| r.content)
+}
+
+#[test]
+fn slot_and_whole_store_roll_back_to_a_snapshot() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = Arc::new(KnowledgeStore::open_path(dir.path().join("pagi_knowledge")).unwrap());
+    let manager = SnapshotManager::new(Arc::clone(&store), dir.path().join("snapshots"));
+    let logos = KbType::Logos.slot_id();
+    let oikos = KbType::Oikos.slot_id();
+    store.insert_record(logos, "fact", &KbRecord::new("the roof warranty is ten years", store.now_ms())).unwrap();
+    store.insert_record(oikos, "task", &KbRecord::new("call the supplier", store.now_ms())).unwrap();
+
+    let good = manager.create(SnapshotReason::Manual).unwrap();
+    assert!(dir.path().join("snapshots").join(&good.id).join("manifest.json").exists());
+    assert!(good.trees.iter().any(|t| t.name == KbType::Logos.tree_name() && t.entries == 1));
+
+    // A bad run rewrites and deletes knowledge in two slots.
+    store.insert_record(logos, "fact", &KbRecord::new("the roof warranty is void", store.now_ms())).unwrap();
+    store.insert_record(logos, "junk", &KbRecord::new("hallucinated supplier discount", store.now_ms())).unwrap();
+    store.remove(oikos, "task").unwrap();
+
+    let report = manager.restore(&good.id, Some(logos)).unwrap();
+    assert_eq!(report.slot_id, Some(logos));
+    assert_eq!(content(&store, logos, "fact").as_deref(), Some("the roof warranty is ten years"));
+    assert_eq!(content(&store, logos, "junk"), None);
+    // Search indexes follow the restored slot.
+    let hits = store.text_search(&[logos], "warranty", 10).unwrap();
+    assert_eq!(hits.len(), 1);
+    assert!(store.text_search(&[logos], "hallucinated", 10).unwrap().is_empty());
+    // Other slots are untouched by a slot restore.
+    assert_eq!(content(&store, oikos, "task"), None);
+
+    manager.restore(&good.id, None).unwrap();
+    assert_eq!(content(&store, oikos, "task").as_deref(), Some("call the supplier"));
+
+    // Every restore snapshots the state it replaced, so it can be undone.
+    let pre = manager.get(&report.pre_restore_snapshot_id).unwrap().unwrap();
+    assert_eq!(pre.reason, SnapshotReason::PreRestore);
+    manager.restore(&pre.id, Some(logos)).unwrap();
+    assert_eq!(content(&store, logos, "junk").as_deref(), Some("hallucinated supplier discount"));
+
+    assert!(manager.restore("missing", None).is_err());
+    assert!(manager.restore(&good.id, Some(12)).is_err());
+}
+
+#[test]
+fn retention_keeps_the_newest_and_schedule_waits_for_the_interval() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = Arc::new(KnowledgeStore::open_path(dir.path().join("pagi_knowledge")).unwrap());
+    let manager = SnapshotManager::new(Arc::clone(&store), dir.path().join("snapshots"))
+        .with_retention(SnapshotRetention { keep_last: 2, max_age_days: None })
+        .with_interval(std::time::Duration::from_secs(3600));
+
+    let first = manager.run_due().unwrap().expect("no snapshot yet, so one is due");
+    assert_eq!(first.reason, SnapshotReason::Scheduled);
+    assert!(manager.run_due().unwrap().is_none());
+
+    let second = manager.create(SnapshotReason::Manual).unwrap();
+    let third = manager.create(SnapshotReason::Manual).unwrap();
+    let ids: Vec<String> = manager.list().unwrap().into_iter().map(|s| s.id).collect();
+    assert_eq!(ids, [third.id.clone(), second.id]);
+    assert!(!dir.path().join("snapshots").join(&first.id).exists());
+
+    assert!(manager.delete(&third.id).unwrap());
+    assert!(!manager.delete(&third.id).unwrap());
+}
@@ -0,0 +1,89 @@
+//! Scrape sources: stale sources are refreshed by the Heartbeat through `UpdateKnowledgeSlot`,
+//! with the last success and error tracked per source.
+
+mod common;
+
+use common::ctx;
+use pagi_core::{AgentSkill, KnowledgeStore, Orchestrator, ScrapeSource, SkillRegistry, SourceRegistry, TenantContext};
+use std::sync::{Arc, Mutex};
+
+const HOUR_MS: i64 = 60 * 60 * 1000;
+
+/// Stands in for CommunityScraper: records each url and fails for urls containing "down".
+struct Scraper(Arc<Mutex<Vec<(String, u64)>>>);
+
+#[async_trait::async_trait]
+impl AgentSkill for Scraper {
+    fn name(&self) -> &str {
+        "CommunityScraper"
+    }
+
+    async fn execute(
+        &self,
+        _ctx: &TenantContext,
+        payload: Option<serde_json::Value>,
+    ) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>> {
+        let payload = payload.unwrap_or_default();
+        let url = payload["url"].as_str().unwrap_or_default().to_string();
+        self.0.lock().unwrap().push((url.clone(), payload["slot_id"].as_u64().unwrap_or(0)));
+        if url.contains("down") {
+            return Err("HTTP 503".into());
+        }
+        Ok(serde_json::json!({ "status": "ok" }))
+    }
+}
+
+#[tokio::test]
+async fn stale_sources_are_refreshed_and_outcomes_tracked() {
+    let store = Arc::new(KnowledgeStore::open_temporary().unwrap());
+    let fetched = Arc::new(Mutex::new(Vec::new()));
+    let mut registry = SkillRegistry::new();
+    registry.register(Arc::new(Scraper(Arc::clone(&fetched))));
+    let orchestrator = Orchestrator::new(Arc::new(registry));
+    let sources = SourceRegistry::new(Arc::clone(&store));
+
+    let t0 = store.now_ms();
+    let news = ScrapeSource::new("news", "https://example.com/news", 5, "6h", ctx(), t0).unwrap();
+    let down = ScrapeSource::new("down", "https://down.example.com/", 3, "@every 1h", ctx(), t0).unwrap();
+    assert_eq!((news.every.as_str(), news.interval_ms), ("6h", 6 * HOUR_MS));
+    sources.set(&news).unwrap();
+    sources.set(&down).unwrap();
+
+    // New sources are stale: both run on the first tick.
+    let mut runs = sources.run_due_at(&orchestrator, t0).await.unwrap();
+    runs.sort_by(|a, b| a.name.cmp(&b.name));
+    assert_eq!(runs.iter().map(|r| (r.name.as_str(), r.ok)).collect::<Vec<_>>(), vec![("down", false), ("news", true)]);
+    let mut urls = fetched.lock().unwrap().clone();
+    urls.sort();
+    assert_eq!(urls, vec![("https://down.example.com/".to_string(), 3), ("https://example.com/news".to_string(), 5)]);
+    let stored = sources.get(&news.id).unwrap();
+    assert_eq!((stored.last_success_ms, stored.success_count), (Some(t0), 1));
+    assert!(stored.is_fresh(t0 + HOUR_MS));
+    let failed = sources.get(&down.id).unwrap();
+    assert_eq!(failed.last_error.as_deref(), Some("HTTP 503"));
+    assert_eq!((failed.last_error_ms, failed.failure_count, failed.last_success_ms), (Some(t0), 1, None));
+    assert_eq!(failed.status(t0)["fresh"], false);
+
+    // Neither runs again before its interval, failing or not.
+    assert!(sources.run_due_at(&orchestrator, t0 + HOUR_MS / 2).await.unwrap().is_empty());
+    let runs = sources.run_due_at(&orchestrator, t0 + HOUR_MS).await.unwrap();
+    assert_eq!(runs.iter().map(|r| r.name.as_str()).collect::<Vec<_>>(), vec!["down"]);
+    let runs = sources.run_due_at(&orchestrator, t0 + 6 * HOUR_MS).await.unwrap();
+    assert_eq!(runs.len(), 2);
+    assert_eq!(sources.get(&news.id).unwrap().success_count, 2);
+
+    let mut paused = sources.get(&news.id).unwrap();
+    paused.enabled = false;
+    sources.set(&paused).unwrap();
+    assert!(sources.run_due_at(&orchestrator, t0 + 24 * HOUR_MS).await.unwrap().iter().all(|r| r.name == "down"));
+    assert!(sources.remove(&down.id).unwrap());
+    assert_eq!(sources.list().unwrap().len(), 1);
+}
+
+#[test]
+fn sources_are_validated() {
+    assert!(ScrapeSource::new("x", "ftp://example.com", 5, "6h", ctx(), 0).is_err());
+    assert!(ScrapeSource::new("x", "https://example.com", 9, "6h", ctx(), 0).is_err());
+    assert!(ScrapeSource::new("x", "https://example.com", 5, "often", ctx(), 0).is_err());
+    assert!(ScrapeSource::new("x", "https://example.com", 5, "1d", ctx(), 0).is_ok());
+}
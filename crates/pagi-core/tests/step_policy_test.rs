@@ -0,0 +1,120 @@
+//! Per-step execution policy (retries, backoff, timeout) for AutonomousGoal chains.
+
+mod common;
+
+use common::{ctx, orchestrator_with_plans};
+use pagi_core::{AgentSkill, Goal, Orchestrator, PlanStep, SkillRegistry, StepPolicy, TenantContext};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+
+/// Fails the first `fail_times` calls, then succeeds.
+struct FlakySkill {
+    calls: AtomicU32,
+    fail_times: u32,
+}
+
+#[async_trait::async_trait]
+impl AgentSkill for FlakySkill {
+    fn name(&self) -> &str {
+        "Flaky"
+    }
+
+    async fn execute(
+        &self,
+        _ctx: &TenantContext,
+        _payload: Option<serde_json::Value>,
+    ) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>> {
+        let n = self.calls.fetch_add(1, Ordering::SeqCst) + 1;
+        if n <= self.fail_times {
+            return Err(format!("transient failure #{}", n).into());
+        }
+        Ok(serde_json::json!({ "status": "ok", "calls": n }))
+    }
+}
+
+/// Sleeps longer than any reasonable step timeout.
+struct SlowSkill;
+
+#[async_trait::async_trait]
+impl AgentSkill for SlowSkill {
+    fn name(&self) -> &str {
+        "Slow"
+    }
+
+    async fn execute(
+        &self,
+        _ctx: &TenantContext,
+        _payload: Option<serde_json::Value>,
+    ) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>> {
+        tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+        Ok(serde_json::json!({ "status": "ok" }))
+    }
+}
+
+fn orchestrator(intent: &str, steps: Vec<PlanStep>, fail_times: u32) -> Orchestrator {
+    let mut registry = SkillRegistry::new();
+    registry.register(Arc::new(FlakySkill {
+        calls: AtomicU32::new(0),
+        fail_times,
+    }));
+    registry.register(Arc::new(SlowSkill));
+    orchestrator_with_plans(registry, [(intent, steps)])
+}
+
+fn step(skill: &str, policy: StepPolicy) -> PlanStep {
+    PlanStep {
+        policy,
+        ..PlanStep::new(skill)
+    }
+}
+
+#[tokio::test]
+async fn retries_recover_transient_failures() {
+    let policy = StepPolicy {
+        max_retries: 2,
+        backoff_ms: 1,
+        ..StepPolicy::default()
+    };
+    let orch = orchestrator("flaky", vec![step("Flaky", policy)], 2);
+    let out = orch
+        .dispatch(&ctx(), Goal::AutonomousGoal { intent: "flaky".into(), context: None })
+        .await
+        .expect("chain should recover after retries");
+    assert_eq!(out["calls"], 3);
+}
+
+#[tokio::test]
+async fn exhausted_retries_fail_the_chain() {
+    let policy = StepPolicy {
+        max_retries: 1,
+        backoff_ms: 1,
+        ..StepPolicy::default()
+    };
+    let orch = orchestrator("flaky", vec![step("Flaky", policy)], 5);
+    let err = orch
+        .dispatch(&ctx(), Goal::AutonomousGoal { intent: "flaky".into(), context: None })
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("transient failure #2"));
+}
+
+#[tokio::test]
+async fn timeout_with_continue_on_failure_degrades_gracefully() {
+    let slow = StepPolicy {
+        timeout_ms: Some(20),
+        backoff_ms: 1,
+        continue_on_failure: true,
+        ..StepPolicy::default()
+    };
+    let orch = orchestrator(
+        "slow then flaky",
+        vec![step("Slow", slow), step("Flaky", StepPolicy::default())],
+        0,
+    );
+    let out = orch
+        .dispatch(&ctx(), Goal::AutonomousGoal { intent: "slow then flaky".into(), context: None })
+        .await
+        .expect("chain continues past the timed-out step");
+    assert_eq!(out["status"], "ok");
+    assert_eq!(out["plan_steps"], serde_json::json!(["Slow", "Flaky"]));
+}
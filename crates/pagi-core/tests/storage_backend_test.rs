@@ -0,0 +1,79 @@
+//! Storage backends: the knowledge store and vault behave the same on SQLite as on sled, SQLite
+//! stores can be opened by several handles at once, and snapshots carry data between backends.
+
+mod common;
+
+use common::ctx;
+use pagi_core::{
+    KbRecord, KbType, KnowledgeStore, MemoryManager, SnapshotManager, SnapshotReason, StorageBackend,
+};
+use std::sync::Arc;
+
+#[test]
+fn knowledge_store_runs_on_sqlite() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = KnowledgeStore::open_with_backend(dir.path(), StorageBackend::Sqlite).unwrap();
+    assert_eq!(store.backend_name(), "sqlite");
+    assert!(dir.path().join(pagi_core::SQLITE_FILE_NAME).exists());
+    let logos = KbType::Logos.slot_id();
+    let oikos = KbType::Oikos.slot_id();
+
+    for (key, text) in [("doc/a", "roof warranty"), ("doc/b", "gutter cleaning"), ("note/c", "supplier call")] {
+        store.insert_record(logos, key, &KbRecord::new(text, store.now_ms())).unwrap();
+    }
+    let page = store.scan_prefix(logos, "doc/", None, 10).unwrap();
+    let keys: Vec<&str> = page.entries.iter().map(|(k, _)| k.as_str()).collect();
+    assert_eq!(keys, ["doc/a", "doc/b"]);
+    let newest = store.scan_prefix_rev(logos, "doc/", None, 1).unwrap();
+    assert_eq!(newest.entries[0].0, "doc/b");
+    assert_eq!(newest.next_cursor.as_deref(), Some("doc/b"));
+    assert_eq!(store.text_search(&[logos], "warranty", 5).unwrap().len(), 1);
+
+    // Compare-and-swap and transactions keep their semantics.
+    assert!(store.compare_and_swap(oikos, "counter", None, Some(b"1")).unwrap().is_ok());
+    let conflict = store.compare_and_swap(oikos, "counter", None, Some(b"2")).unwrap().unwrap_err();
+    assert_eq!(conflict.current.as_deref(), Some(&b"1"[..]));
+    store
+        .transaction_multi(&[logos, oikos], |tx| {
+            tx.slot(logos)?.remove("note/c")?;
+            tx.slot(oikos)?.insert("counter", b"2")?;
+            Ok(())
+        })
+        .unwrap();
+    assert_eq!(store.get(logos, "note/c").unwrap(), None);
+    assert_eq!(store.get(oikos, "counter").unwrap().as_deref(), Some(&b"2"[..]));
+}
+
+#[test]
+fn sqlite_store_is_shared_between_handles() {
+    let dir = tempfile::tempdir().unwrap();
+    let gateway = KnowledgeStore::open_with_backend(dir.path().join("kb"), StorageBackend::Sqlite).unwrap();
+    // A second open of the same directory (as the daemon or Studio UI would) is not locked out.
+    let studio = KnowledgeStore::open_with_backend(dir.path().join("kb"), StorageBackend::Sqlite).unwrap();
+    let logos = KbType::Logos.slot_id();
+
+    gateway.insert_record(logos, "fact", &KbRecord::new("written by the gateway", gateway.now_ms())).unwrap();
+    assert_eq!(studio.get_record(logos, "fact").unwrap().unwrap().content, "written by the gateway");
+
+    let vault_a = MemoryManager::open_with_backend(dir.path().join("vault"), StorageBackend::Sqlite).unwrap();
+    let vault_b = MemoryManager::open_with_backend(dir.path().join("vault"), StorageBackend::Sqlite).unwrap();
+    vault_a.save_path(&ctx(), "leads/1", b"jane").unwrap();
+    assert_eq!(vault_b.get_path(&ctx(), "leads/1").unwrap().as_deref(), Some(&b"jane"[..]));
+}
+
+#[test]
+fn snapshot_moves_knowledge_from_sled_to_sqlite() {
+    let dir = tempfile::tempdir().unwrap();
+    let snapshots = dir.path().join("snapshots");
+    let logos = KbType::Logos.slot_id();
+    let id = {
+        let sled = Arc::new(KnowledgeStore::open_with_backend(dir.path().join("sled"), StorageBackend::Sled).unwrap());
+        sled.insert_record(logos, "fact", &KbRecord::new("the roof warranty is ten years", sled.now_ms())).unwrap();
+        SnapshotManager::new(sled, &snapshots).create(SnapshotReason::Manual).unwrap().id
+    };
+
+    let sqlite = Arc::new(KnowledgeStore::open_with_backend(dir.path().join("sqlite"), StorageBackend::Sqlite).unwrap());
+    SnapshotManager::new(Arc::clone(&sqlite), &snapshots).restore(&id, None).unwrap();
+    assert_eq!(sqlite.get_record(logos, "fact").unwrap().unwrap().content, "the roof warranty is ten years");
+    assert_eq!(sqlite.text_search(&[logos], "warranty", 5).unwrap().len(), 1);
+}
@@ -0,0 +1,123 @@
+//! Sub-plan steps (`{ "intent": "..." }`): nested blueprint plans expanded at dispatch time.
+
+mod common;
+
+use common::{ctx, orchestrator_with_plans, CaptureAudit};
+use pagi_core::{
+    AgentSkill, Goal, Orchestrator, PayloadMap, PlanStep, SkillRegistry, TenantContext,
+    MAX_SUB_PLAN_DEPTH,
+};
+use std::sync::{Arc, Mutex};
+
+/// Appends its name to `payload.trail`.
+struct Trail(&'static str);
+
+#[async_trait::async_trait]
+impl AgentSkill for Trail {
+    fn name(&self) -> &str {
+        self.0
+    }
+
+    async fn execute(
+        &self,
+        _ctx: &TenantContext,
+        payload: Option<serde_json::Value>,
+    ) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>> {
+        let mut trail = payload
+            .as_ref()
+            .and_then(|p| p.get("trail"))
+            .and_then(|v| v.as_array())
+            .cloned()
+            .unwrap_or_default();
+        trail.push(serde_json::json!(self.0));
+        Ok(serde_json::json!({ "trail": trail }))
+    }
+}
+
+fn orchestrator(plans: Vec<(&str, Vec<PlanStep>)>, trace: Arc<Mutex<Option<serde_json::Value>>>) -> Orchestrator {
+    let mut registry = SkillRegistry::new();
+    for name in ["Fetch", "Summarize", "Translate", "Publish"] {
+        registry.register(Arc::new(Trail(name)));
+    }
+    registry.register(Arc::new(CaptureAudit(trace)));
+    orchestrator_with_plans(registry, plans)
+}
+
+fn goal(intent: &str) -> Goal {
+    Goal::AutonomousGoal {
+        intent: intent.into(),
+        context: Some(serde_json::json!({ "trail": [] })),
+    }
+}
+
+#[tokio::test]
+async fn nested_plans_run_in_order_and_trace_their_structure() {
+    let trace = Arc::new(Mutex::new(None));
+    let orch = orchestrator(
+        vec![
+            (
+                "daily digest",
+                vec![PlanStep::new("Fetch"), PlanStep::sub_plan("summarize news"), PlanStep::new("Publish")],
+            ),
+            ("summarize news", vec![PlanStep::new("Summarize"), PlanStep::sub_plan("translate")]),
+            ("translate", vec![PlanStep::new("Translate")]),
+        ],
+        Arc::clone(&trace),
+    );
+    let out = orch.dispatch(&ctx(), goal("daily digest")).await.expect("nested plan should succeed");
+    assert_eq!(out["trail"], serde_json::json!(["Fetch", "Summarize", "Translate", "Publish"]));
+    assert_eq!(out["plan_steps"], serde_json::json!(["Fetch", "intent:summarize news", "Publish"]));
+
+    let trace = trace.lock().unwrap().clone().expect("thought log recorded");
+    let sub = &trace["steps"][1];
+    assert_eq!(sub["intent"], "summarize news");
+    assert_eq!(sub["depth"], 1);
+    assert_eq!(sub["input"]["trail"], serde_json::json!(["Fetch"]));
+    assert_eq!(sub["steps"][0]["skill"], "Summarize");
+    assert_eq!(sub["steps"][1]["intent"], "translate");
+    assert_eq!(sub["steps"][1]["depth"], 2);
+    assert_eq!(sub["steps"][1]["steps"][0]["skill"], "Translate");
+    assert_eq!(sub["output"]["trail"], serde_json::json!(["Fetch", "Summarize", "Translate"]));
+}
+
+#[tokio::test]
+async fn sub_plan_input_honours_map() {
+    let trace = Arc::new(Mutex::new(None));
+    let map: PayloadMap = serde_json::from_value(serde_json::json!({ "trail": "$.missing" })).unwrap();
+    let orch = orchestrator(
+        vec![
+            ("outer", vec![PlanStep::new("Fetch"), PlanStep::sub_plan("inner").with_map(map)]),
+            ("inner", vec![PlanStep::new("Summarize")]),
+        ],
+        trace,
+    );
+    let out = orch.dispatch(&ctx(), goal("outer")).await.unwrap();
+    assert_eq!(out["trail"], serde_json::json!(["Summarize"]));
+}
+
+#[tokio::test]
+async fn cycles_and_excess_depth_fail_at_dispatch() {
+    let trace = Arc::new(Mutex::new(None));
+    let orch = orchestrator(
+        vec![
+            ("ping", vec![PlanStep::new("Fetch"), PlanStep::sub_plan("pong")]),
+            ("pong", vec![PlanStep::sub_plan("ping")]),
+        ],
+        Arc::clone(&trace),
+    );
+    let err = orch.dispatch(&ctx(), goal("ping")).await.unwrap_err();
+    assert!(err.to_string().contains("cycle (ping -> pong -> ping)"), "{}", err);
+
+    let levels = MAX_SUB_PLAN_DEPTH + 1;
+    let names: Vec<String> = (0..=levels).map(|i| format!("level {}", i)).collect();
+    let mut plans: Vec<(&str, Vec<PlanStep>)> = names
+        .windows(2)
+        .map(|w| (w[0].as_str(), vec![PlanStep::sub_plan(w[1].as_str())]))
+        .collect();
+    plans.push((names[levels].as_str(), vec![PlanStep::new("Fetch")]));
+    let orch = orchestrator(plans, trace);
+    let err = orch.dispatch(&ctx(), goal("level 0")).await.unwrap_err();
+    assert!(err.to_string().contains("nesting deeper than"), "{}", err);
+    assert!(err.to_string().starts_with("sub-plan intent"), "{}", err);
+    assert!(orch.dispatch(&ctx(), goal("level 1")).await.is_ok());
+}
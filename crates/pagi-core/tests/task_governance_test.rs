@@ -18,25 +18,28 @@ use pagi_core::{
 };
 use std::sync::Arc;
 
+/// Fixed evaluation time so task timestamps are deterministic.
+const NOW_MS: i64 = 1_700_000_000_000;
+
 // ---------------------------------------------------------------------------
 // Helper: build a standard task set for testing
 // ---------------------------------------------------------------------------
 
 fn sample_tasks() -> Vec<GovernedTask> {
     vec![
-        GovernedTask::new("email_replies", "Reply to routine emails", TaskDifficulty::Low)
+        GovernedTask::new("email_replies", "Reply to routine emails", TaskDifficulty::Low, NOW_MS)
             .with_priority(0.4)
             .with_tags(vec!["work".to_string()]),
-        GovernedTask::new("code_review", "Review PR #42", TaskDifficulty::Medium)
+        GovernedTask::new("code_review", "Review PR #42", TaskDifficulty::Medium, NOW_MS)
             .with_priority(0.6)
             .with_tags(vec!["work".to_string(), "code".to_string()]),
-        GovernedTask::new("architecture_decision", "Design new microservice boundary", TaskDifficulty::High)
+        GovernedTask::new("architecture_decision", "Design new microservice boundary", TaskDifficulty::High, NOW_MS)
             .with_priority(0.8)
             .with_tags(vec!["work".to_string(), "architecture".to_string()]),
-        GovernedTask::new("conflict_resolution", "Address PM feedback on missed deadline", TaskDifficulty::High)
+        GovernedTask::new("conflict_resolution", "Address PM feedback on missed deadline", TaskDifficulty::High, NOW_MS)
             .with_priority(0.7)
             .with_tags(vec!["work".to_string(), "conflict".to_string()]),
-        GovernedTask::new("deploy_hotfix", "Deploy critical security patch", TaskDifficulty::Critical)
+        GovernedTask::new("deploy_hotfix", "Deploy critical security patch", TaskDifficulty::Critical, NOW_MS)
             .with_priority(0.95)
             .with_tags(vec!["work".to_string(), "urgent".to_string()]),
     ]
@@ -84,7 +87,7 @@ fn stressed_mental() -> MentalState {
 fn healthy_state_all_tasks_proceed() {
     let governor = TaskGovernor::new(healthy_soma(), healthy_mental(), None);
     let tasks = sample_tasks();
-    let evaluated = governor.evaluate_batch(&tasks);
+    let evaluated = governor.evaluate_batch(&tasks, NOW_MS);
 
     for task in &evaluated {
         assert!(
@@ -112,7 +115,7 @@ fn sleep_deprived_stoic_postpones_high_difficulty() {
     let stoic = EthosPolicy::preset("Stoic").unwrap();
     let governor = TaskGovernor::new(sleep_deprived_soma(), healthy_mental(), Some(stoic));
     let tasks = sample_tasks();
-    let evaluated = governor.evaluate_batch(&tasks);
+    let evaluated = governor.evaluate_batch(&tasks, NOW_MS);
 
     // Architecture decision (High) should be postponed
     let arch = evaluated.iter().find(|t| t.task_id == "architecture_decision").unwrap();
@@ -155,7 +158,7 @@ fn high_emotional_stress_postpones_conflict_tasks() {
     let compassionate = EthosPolicy::preset("Compassionate-Witness").unwrap();
     let governor = TaskGovernor::new(healthy_soma(), stressed_mental(), Some(compassionate));
     let tasks = sample_tasks();
-    let evaluated = governor.evaluate_batch(&tasks);
+    let evaluated = governor.evaluate_batch(&tasks, NOW_MS);
 
     // Conflict resolution should be postponed due to high relational stress
     let conflict = evaluated.iter().find(|t| t.task_id == "conflict_resolution").unwrap();
@@ -195,7 +198,7 @@ fn critical_tasks_always_proceed() {
     };
     let governor = TaskGovernor::new(soma, mental, None);
 
-    let critical_task = GovernedTask::new("emergency", "Server on fire", TaskDifficulty::Critical)
+    let critical_task = GovernedTask::new("emergency", "Server on fire", TaskDifficulty::Critical, NOW_MS)
         .with_priority(1.0);
 
     let (action, priority) = governor.evaluate(&critical_task);
@@ -220,7 +223,7 @@ fn growth_mindset_produces_different_reason_text() {
     let growth = EthosPolicy::preset("Growth-Mindset").unwrap();
     let governor = TaskGovernor::new(sleep_deprived_soma(), healthy_mental(), Some(growth));
 
-    let hard_task = GovernedTask::new("deep_research", "Write research paper", TaskDifficulty::High)
+    let hard_task = GovernedTask::new("deep_research", "Write research paper", TaskDifficulty::High, NOW_MS)
         .with_priority(0.8);
 
     let (action, _) = governor.evaluate(&hard_task);
@@ -253,7 +256,7 @@ fn taoist_lens_produces_wu_wei_reasoning() {
     let taoist = EthosPolicy::preset("Taoist").unwrap();
     let governor = TaskGovernor::new(sleep_deprived_soma(), healthy_mental(), Some(taoist));
 
-    let hard_task = GovernedTask::new("planning", "Strategic planning session", TaskDifficulty::High)
+    let hard_task = GovernedTask::new("planning", "Strategic planning session", TaskDifficulty::High, NOW_MS)
         .with_priority(0.7);
 
     let (action, _) = governor.evaluate(&hard_task);
@@ -275,7 +278,7 @@ fn existentialist_lens_produces_freedom_reasoning() {
     let existentialist = EthosPolicy::preset("Existentialist").unwrap();
     let governor = TaskGovernor::new(sleep_deprived_soma(), healthy_mental(), Some(existentialist));
 
-    let hard_task = GovernedTask::new("decision", "Major career decision", TaskDifficulty::High)
+    let hard_task = GovernedTask::new("decision", "Major career decision", TaskDifficulty::High, NOW_MS)
         .with_priority(0.9);
 
     let (action, _) = governor.evaluate(&hard_task);
@@ -362,7 +365,7 @@ fn emotional_penalty_scales_with_stress() {
 fn effective_priority_reduced_under_load() {
     let governor = TaskGovernor::new(sleep_deprived_soma(), stressed_mental(), None);
 
-    let task = GovernedTask::new("medium_task", "Regular work", TaskDifficulty::Medium)
+    let task = GovernedTask::new("medium_task", "Regular work", TaskDifficulty::Medium, NOW_MS)
         .with_priority(0.8);
 
     let (_, effective_priority) = governor.evaluate(&task);
@@ -382,7 +385,7 @@ fn effective_priority_reduced_under_load() {
 fn batch_evaluation_sorts_correctly() {
     let governor = TaskGovernor::new(sleep_deprived_soma(), healthy_mental(), None);
     let tasks = sample_tasks();
-    let evaluated = governor.evaluate_batch(&tasks);
+    let evaluated = governor.evaluate_batch(&tasks, NOW_MS);
 
     // Proceed tasks should come before postponed tasks
     let mut seen_non_proceed = false;
@@ -404,7 +407,7 @@ fn batch_evaluation_sorts_correctly() {
 
 #[test]
 fn governed_task_serialization_roundtrip() {
-    let task = GovernedTask::new("test_task", "Test Task", TaskDifficulty::High)
+    let task = GovernedTask::new("test_task", "Test Task", TaskDifficulty::High, NOW_MS)
         .with_priority(0.75)
         .with_tags(vec!["test".to_string(), "important".to_string()])
         .with_description("A test task for serialization");
@@ -444,8 +447,8 @@ fn kb_integration_store_and_retrieve_tasks() {
     let store = Arc::new(KnowledgeStore::open_path(kb_dir.path()).unwrap());
 
     // Store tasks
-    let task1 = GovernedTask::new("task_a", "Task A", TaskDifficulty::Low).with_priority(0.3);
-    let task2 = GovernedTask::new("task_b", "Task B", TaskDifficulty::High).with_priority(0.9);
+    let task1 = GovernedTask::new("task_a", "Task A", TaskDifficulty::Low, store.now_ms()).with_priority(0.3);
+    let task2 = GovernedTask::new("task_b", "Task B", TaskDifficulty::High, store.now_ms()).with_priority(0.9);
 
     store.set_governed_task(&task1).unwrap();
     store.set_governed_task(&task2).unwrap();
@@ -575,7 +578,7 @@ fn governance_summary_is_readable() {
 fn no_ethos_policy_produces_generic_reasons() {
     let governor = TaskGovernor::new(sleep_deprived_soma(), healthy_mental(), None);
 
-    let hard_task = GovernedTask::new("hard", "Hard task", TaskDifficulty::High)
+    let hard_task = GovernedTask::new("hard", "Hard task", TaskDifficulty::High, NOW_MS)
         .with_priority(0.8);
 
     let (action, _) = governor.evaluate(&hard_task);
@@ -606,7 +609,7 @@ fn low_tone_weight_reduces_philosophical_influence() {
 
     let governor = TaskGovernor::new(sleep_deprived_soma(), healthy_mental(), Some(stoic));
 
-    let hard_task = GovernedTask::new("hard", "Hard task", TaskDifficulty::High)
+    let hard_task = GovernedTask::new("hard", "Hard task", TaskDifficulty::High, NOW_MS)
         .with_priority(0.8);
 
     let (action, _) = governor.evaluate(&hard_task);
@@ -640,7 +643,7 @@ fn medium_difficulty_moderate_load_deprioritize() {
     };
     let governor = TaskGovernor::new(soma, mental, None);
 
-    let medium_task = GovernedTask::new("review", "Code review", TaskDifficulty::Medium)
+    let medium_task = GovernedTask::new("review", "Code review", TaskDifficulty::Medium, NOW_MS)
         .with_priority(0.6);
 
     let (action, _) = governor.evaluate(&medium_task);
@@ -0,0 +1,74 @@
+//! Per-tenant skill registries: disabled skills are refused, tenant instances shadow global ones.
+
+use pagi_core::{
+    AgentSkill, Goal, Orchestrator, SkillNotEnabled, SkillRegistry, TenantContext, TenantSkillPolicy,
+    TenantSkillRegistry,
+};
+use std::sync::Arc;
+
+/// Reports which instance ran.
+struct Tagged(&'static str, &'static str);
+
+#[async_trait::async_trait]
+impl AgentSkill for Tagged {
+    fn name(&self) -> &str {
+        self.0
+    }
+
+    async fn execute(
+        &self,
+        _ctx: &TenantContext,
+        _payload: Option<serde_json::Value>,
+    ) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(serde_json::json!({ "instance": self.1 }))
+    }
+}
+
+fn ctx(tenant_id: &str) -> TenantContext {
+    TenantContext::new(tenant_id.to_string())
+}
+
+fn run(name: &str) -> Goal {
+    Goal::ExecuteSkill {
+        name: name.into(),
+        payload: None,
+    }
+}
+
+fn orchestrator() -> Orchestrator {
+    let mut registry = SkillRegistry::new();
+    registry.register(Arc::new(Tagged("ModelRouter", "global")));
+    registry.register(Arc::new(Tagged("KnowledgePruner", "global")));
+
+    let tenants = TenantSkillRegistry::new();
+    tenants.set_policy(
+        "acme",
+        TenantSkillPolicy {
+            disabled_skills: vec!["KnowledgePruner".into()],
+            ..Default::default()
+        },
+    );
+    tenants.register_for_tenant("acme", Arc::new(Tagged("ModelRouter", "acme")));
+    Orchestrator::new(Arc::new(registry)).with_tenants(Arc::new(tenants))
+}
+
+#[tokio::test]
+async fn tenant_view_filters_and_shadows_skills() {
+    let orchestrator = orchestrator();
+
+    let err = orchestrator
+        .dispatch(&ctx("acme"), run("KnowledgePruner"))
+        .await
+        .unwrap_err();
+    let not_enabled = err.downcast_ref::<SkillNotEnabled>().expect("SkillNotEnabled");
+    assert_eq!(not_enabled.tenant_id, "acme");
+
+    let out = orchestrator.dispatch(&ctx("acme"), run("ModelRouter")).await.unwrap();
+    assert_eq!(out["instance"], "acme");
+    assert_eq!(orchestrator.skill_names_for("acme"), ["ModelRouter"]);
+
+    // Other tenants keep the global registry.
+    let out = orchestrator.dispatch(&ctx("other"), run("ModelRouter")).await.unwrap();
+    assert_eq!(out["instance"], "global");
+    assert!(orchestrator.dispatch(&ctx("other"), run("KnowledgePruner")).await.is_ok());
+}
@@ -0,0 +1,69 @@
+//! Tenant namespacing: each tenant's handle reaches only its own records.
+
+use pagi_core::{KbRecord, KbType, KnowledgeStore, DEFAULT_TENANT_ID};
+
+#[test]
+fn tenants_cannot_read_each_others_records() {
+    let store = KnowledgeStore::open_temporary().unwrap();
+    let logos = KbType::Logos.slot_id();
+    // Written before tenants had keyspaces: the default tenant's.
+    store.insert_record(logos, "roof", &KbRecord::new("shared roofing notes", store.now_ms())).unwrap();
+
+    let acme = store.tenant("acme").unwrap();
+    let beta = store.tenant("beta").unwrap();
+    acme.insert_record(logos, "roof", &KbRecord::new("acme roofing quote", store.now_ms())).unwrap();
+    beta.insert_record(logos, "roof", &KbRecord::new("beta roofing quote", store.now_ms())).unwrap();
+
+    assert_eq!(acme.get_record(logos, "roof").unwrap().unwrap().content, "acme roofing quote");
+    assert_eq!(beta.get_record(logos, "roof").unwrap().unwrap().content, "beta roofing quote");
+    let default = store.tenant(DEFAULT_TENANT_ID).unwrap();
+    assert_eq!(default.get_record(logos, "roof").unwrap().unwrap().content, "shared roofing notes");
+    assert_eq!(store.tenant("").unwrap().tenant_id(), DEFAULT_TENANT_ID);
+    assert_eq!(store.get_record(logos, "tenant/acme/roof").unwrap().unwrap().content, "acme roofing quote");
+
+    // No handle can name another tenant's key.
+    assert!(default.get(logos, "tenant/acme/roof").is_err());
+    assert!(beta.insert(logos, "tenant/acme/roof", b"overwrite").is_err());
+    assert!(store.tenant("acme/roof").is_err());
+    assert!(store.tenant("../x").is_err());
+
+    let keys = |kv: Vec<(String, Vec<u8>)>| kv.into_iter().map(|(k, _)| k).collect::<Vec<_>>();
+    assert_eq!(keys(acme.scan_kv(logos).unwrap()), ["roof"]);
+    assert_eq!(keys(default.scan_kv(logos).unwrap()), ["roof"]);
+    assert_eq!(beta.count(logos).unwrap(), 1);
+
+    let hits = acme.text_search(&[logos], "roofing", 10).unwrap();
+    assert_eq!(hits.len(), 1);
+    assert_eq!((hits[0].key.as_str(), hits[0].content.as_str()), ("roof", "acme roofing quote"));
+    assert_eq!(default.text_search(&[logos], "roofing", 10).unwrap()[0].content, "shared roofing notes");
+
+    acme.remove(logos, "roof").unwrap();
+    assert!(acme.get(logos, "roof").unwrap().is_none());
+    assert!(beta.get(logos, "roof").unwrap().is_some());
+}
+
+#[test]
+fn pages_skip_other_tenants_and_resume_from_the_tenants_own_cursor() {
+    let store = KnowledgeStore::open_temporary().unwrap();
+    let logos = KbType::Logos.slot_id();
+    let acme = store.tenant("acme").unwrap();
+    for i in 0..5 {
+        store.insert(logos, &format!("note/{}", i), b"{}").unwrap();
+        acme.insert(logos, &format!("note/{}", i), b"{}").unwrap();
+        store.tenant("beta").unwrap().insert(logos, &format!("note/{}", i), b"{}").unwrap();
+    }
+    // `tenant/...` sorts between `note/...` and nothing else, so the default range crosses it.
+    store.insert(logos, "zeta", b"{}").unwrap();
+
+    let default = store.tenant(DEFAULT_TENANT_ID).unwrap();
+    let first = default.scan_prefix(logos, "", None, 4).unwrap();
+    assert_eq!(first.entries.len(), 4);
+    let second = default.scan_prefix(logos, "", first.next_cursor.as_deref(), 4).unwrap();
+    let keys: Vec<&str> = second.entries.iter().map(|(k, _)| k.as_str()).collect();
+    assert_eq!(keys, ["note/4", "zeta"]);
+    assert!(second.next_cursor.is_none());
+
+    let page = acme.scan_prefix(logos, "note/", Some("note/2"), 10).unwrap();
+    let keys: Vec<&str> = page.entries.iter().map(|(k, _)| k.as_str()).collect();
+    assert_eq!(keys, ["note/3", "note/4"]);
+}
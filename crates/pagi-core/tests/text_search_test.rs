@@ -0,0 +1,80 @@
+//! Full-text search: BM25-ranked hits across slots, kept current by writes and the archive tier.
+
+use pagi_core::{KbRecord, KbType, KnowledgeStore};
+
+fn record_at(timestamp: i64, content: &str) -> KbRecord {
+    KbRecord::new(content, timestamp)
+}
+
+fn keys(store: &KnowledgeStore, slots: &[u8], query: &str) -> Vec<String> {
+    store.text_search(slots, query, 10).unwrap().into_iter().map(|h| h.key).collect()
+}
+
+#[test]
+fn ranks_by_relevance_across_slots() {
+    let store = KnowledgeStore::open_temporary().unwrap();
+    let logos = KbType::Logos.slot_id();
+    let oikos = KbType::Oikos.slot_id();
+    store.insert_record(logos, "roof-guide", &KbRecord::new("Roof repair: patching a roof leak after a storm", store.now_ms())).unwrap();
+    store.insert_record(logos, "gutters", &KbRecord::new("Cleaning gutters prevents a leak at the fascia", store.now_ms())).unwrap();
+    store.insert_record(logos, "siding", &KbRecord::new("Vinyl siding colors and warranty terms", store.now_ms())).unwrap();
+    store
+        .insert_record(oikos, "job-42", &KbRecord::with_metadata("Roof leak reported by the Hansen family", serde_json::json!({ "lead": "42" }), store.now_ms()))
+        .unwrap();
+    // Non-record values are skipped, not indexed as noise.
+    store.insert(logos, "raw", b"roof roof roof").unwrap();
+
+    let hits = store.text_search(&[], "roof leak", 10).unwrap();
+    let found: Vec<(u8, &str)> = hits.iter().map(|h| (h.slot_id, h.key.as_str())).collect();
+    assert_eq!(found.len(), 3);
+    assert!(found[..2].contains(&(logos, "roof-guide")) && found[..2].contains(&(oikos, "job-42")));
+    assert_eq!(found[2], (logos, "gutters"));
+    assert!(hits.windows(2).all(|w| w[0].score >= w[1].score));
+    let job = hits.iter().find(|h| h.key == "job-42").unwrap();
+    assert_eq!(job.metadata["lead"], "42");
+
+    assert_eq!(keys(&store, &[logos], "ROOF"), ["roof-guide"]);
+    assert_eq!(keys(&store, &[oikos], "warranty"), Vec::<String>::new());
+    assert!(keys(&store, &[], "the and of").is_empty());
+    assert_eq!(store.text_search(&[], "leak", 1).unwrap().len(), 1);
+}
+
+#[test]
+fn rewrites_and_removals_update_the_index() {
+    let store = KnowledgeStore::open_temporary().unwrap();
+    let logos = KbType::Logos.slot_id();
+    store.insert_record(logos, "note", &KbRecord::new("quarterly invoice reminder", store.now_ms())).unwrap();
+    assert_eq!(keys(&store, &[logos], "invoice"), ["note"]);
+
+    store.insert_record(logos, "note", &KbRecord::new("annual budget review", store.now_ms())).unwrap();
+    assert!(keys(&store, &[logos], "invoice").is_empty());
+    assert_eq!(keys(&store, &[logos], "budget"), ["note"]);
+
+    store.remove(logos, "note").unwrap();
+    assert!(keys(&store, &[logos], "budget").is_empty());
+}
+
+#[test]
+fn index_rebuilds_from_live_records() {
+    let store = KnowledgeStore::open_temporary().unwrap();
+    let logos = KbType::Logos.slot_id();
+    store.insert_record(logos, "legacy", &KbRecord::new("shingle supplier contract", store.now_ms())).unwrap();
+    assert_eq!(keys(&store, &[logos], "supplier"), ["legacy"]);
+    assert_eq!(store.rebuild_text_index(logos).unwrap(), 1);
+    assert_eq!(keys(&store, &[logos], "contract"), ["legacy"]);
+}
+
+#[test]
+fn archived_records_leave_the_index_until_restored() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = KnowledgeStore::open_path(dir.path().join("pagi_knowledge")).unwrap();
+    let logos = KbType::Logos.slot_id();
+    store.insert_record(logos, "old", &record_at(1_000, "gravel driveway estimate")).unwrap();
+    store.insert_record(logos, "new", &KbRecord::new("paved driveway estimate", store.now_ms())).unwrap();
+
+    assert_eq!(store.archive_older_than(logos, 10_000).unwrap().archived, 1);
+    assert_eq!(keys(&store, &[logos], "driveway"), ["new"]);
+
+    assert!(store.restore_archived(logos, "old").unwrap());
+    assert_eq!(keys(&store, &[logos], "gravel"), ["old"]);
+}
@@ -0,0 +1,63 @@
+//! Monthly LLM token usage in KB_SOMA: accumulation per tenant and agent, concurrent writers and
+//! month keys.
+
+use pagi_core::{usage_month, KnowledgeStore, TokenBudgetConfig};
+use std::sync::Arc;
+
+#[test]
+fn usage_accumulates_per_tenant_and_agent() {
+    let store = KnowledgeStore::open_temporary().unwrap();
+    store.record_token_usage("acme", "sales", 100, 20, false).unwrap();
+    store.record_token_usage("acme", "support", 10, 5, true).unwrap();
+    let record = store.record_token_usage("acme", "sales", 1, 1, false).unwrap();
+    assert_eq!((record.prompt_tokens, record.completion_tokens, record.total_tokens), (111, 26, 137));
+    assert_eq!((record.requests, record.estimated_tokens), (3, 15));
+    assert_eq!(record.by_agent["sales"].total_tokens, 122);
+    assert_eq!(record.by_agent["support"].requests, 1);
+
+    let month = usage_month(record.updated_at_ms);
+    assert_eq!(store.get_token_usage("acme", &month), Some(record));
+    assert!(store.get_token_usage("globex", &month).is_none());
+    assert_eq!(store.list_token_usage("acme").unwrap().len(), 1);
+    assert!(store.list_token_usage("acm").unwrap().is_empty());
+}
+
+#[test]
+fn concurrent_generations_lose_no_tokens() {
+    let store = Arc::new(KnowledgeStore::open_temporary().unwrap());
+    let handles: Vec<_> = (0..8)
+        .map(|i| {
+            let store = Arc::clone(&store);
+            std::thread::spawn(move || {
+                for _ in 0..10 {
+                    store.record_token_usage("acme", &format!("agent-{}", i % 2), 3, 2, false).unwrap();
+                }
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+    let record = &store.list_token_usage("acme").unwrap()[0];
+    assert_eq!((record.total_tokens, record.requests), (400, 80));
+    assert_eq!(record.by_agent["agent-0"].total_tokens, 200);
+}
+
+#[test]
+fn months_are_utc_calendar_months() {
+    assert_eq!(usage_month(0), "1970-01");
+    // 2024-02-29T23:59:59.999Z and one millisecond later.
+    assert_eq!(usage_month(1_709_251_199_999), "2024-02");
+    assert_eq!(usage_month(1_709_251_200_000), "2024-03");
+}
+
+#[test]
+fn tenant_budgets_override_the_default() {
+    let budgets = TokenBudgetConfig {
+        monthly_tokens: Some(1_000),
+        tenants: [("acme".to_string(), 50_000)].into_iter().collect(),
+    };
+    assert_eq!(budgets.monthly_limit("acme"), Some(50_000));
+    assert_eq!(budgets.monthly_limit("globex"), Some(1_000));
+    assert_eq!(TokenBudgetConfig::default().monthly_limit("acme"), None);
+}
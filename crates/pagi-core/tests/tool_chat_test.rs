@@ -0,0 +1,149 @@
+//! Goal::ToolChat: KB-5 manifests offered as tools, skills run for the model's tool calls and
+//! their results fed back until a final answer or the iteration cap.
+
+mod common;
+
+use common::ctx;
+use pagi_core::{
+    keys, AgentSkill, Goal, KbType, KnowledgeStore, Orchestrator, SkillRecord, SkillRegistry, TenantContext,
+};
+use std::sync::{Arc, Mutex};
+
+/// Stands in for ModelRouter in tool mode. Calls `tool` until a tool result is the last message
+/// (or always, when `always_call`), then answers with that result. Records every payload.
+struct ToolRouter {
+    tool: &'static str,
+    always_call: bool,
+    payloads: Arc<Mutex<Vec<serde_json::Value>>>,
+}
+
+#[async_trait::async_trait]
+impl AgentSkill for ToolRouter {
+    fn name(&self) -> &str {
+        "ModelRouter"
+    }
+
+    async fn execute(
+        &self,
+        _ctx: &TenantContext,
+        payload: Option<serde_json::Value>,
+    ) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>> {
+        let payload = payload.unwrap_or_default();
+        self.payloads.lock().unwrap().push(payload.clone());
+        let last = payload["messages"].as_array().and_then(|m| m.last()).cloned().unwrap_or_default();
+        if last["role"] == "tool" && !self.always_call {
+            let answer = format!("the time is {}", last["content"].as_str().unwrap_or(""));
+            return Ok(serde_json::json!({ "status": "ok", "generated": answer, "tool_calls": [] }));
+        }
+        Ok(serde_json::json!({
+            "status": "ok",
+            "generated": "",
+            "tool_calls": [{ "id": "call_1", "name": self.tool, "arguments": "{\"zone\": \"UTC\"}" }]
+        }))
+    }
+}
+
+/// Returns a fixed time for the requested zone.
+struct Clock;
+
+#[async_trait::async_trait]
+impl AgentSkill for Clock {
+    fn name(&self) -> &str {
+        "clock"
+    }
+
+    async fn execute(
+        &self,
+        _ctx: &TenantContext,
+        payload: Option<serde_json::Value>,
+    ) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>> {
+        let zone = payload.as_ref().and_then(|p| p.get("zone")).and_then(|v| v.as_str()).unwrap_or("?");
+        Ok(serde_json::json!({ "time": "12:00", "zone": zone }))
+    }
+}
+
+fn orchestrator(
+    tool: &'static str,
+    always_call: bool,
+) -> (Orchestrator, Arc<Mutex<Vec<serde_json::Value>>>) {
+    let knowledge = KnowledgeStore::open_temporary().unwrap();
+    let record = SkillRecord {
+        slug: "clock".to_string(),
+        description: "Current time in a time zone".to_string(),
+        schema: serde_json::json!({ "zone": "string (required; IANA name)" }),
+    };
+    knowledge
+        .insert(
+            KbType::Techne.slot_id(),
+            &keys::skill_key("clock"),
+            &serde_json::to_vec(&record).unwrap(),
+        )
+        .unwrap();
+    let payloads = Arc::new(Mutex::new(Vec::new()));
+    let mut registry = SkillRegistry::new();
+    registry.register(Arc::new(ToolRouter {
+        tool,
+        always_call,
+        payloads: Arc::clone(&payloads),
+    }));
+    registry.register(Arc::new(Clock));
+    let orch = Orchestrator::new(Arc::new(registry)).with_knowledge(Arc::new(knowledge));
+    (orch, payloads)
+}
+
+fn tool_chat(max_iterations: Option<u32>) -> Goal {
+    Goal::ToolChat {
+        prompt: "What time is it?".to_string(),
+        system_prompt: Some("Be brief.".to_string()),
+        max_iterations,
+    }
+}
+
+#[tokio::test]
+async fn tool_calls_run_skills_and_feed_results_back() {
+    let (orch, payloads) = orchestrator("clock", false);
+    let out = orch.dispatch(&ctx(), tool_chat(None)).await.unwrap();
+    assert_eq!(out["goal"], "ToolChat");
+    assert_eq!(out["status"], "ok");
+    assert_eq!((out["iterations"].as_u64(), out["tool_calls"].as_u64()), (Some(2), Some(1)));
+    assert!(out["answer"].as_str().unwrap().contains("12:00"));
+
+    let trace = out["trace"].as_array().unwrap();
+    assert_eq!(trace.len(), 3);
+    assert_eq!(trace[1]["kind"], "tool");
+    assert_eq!(trace[1]["status"], "ok");
+    assert_eq!(trace[1]["arguments"]["zone"], "UTC");
+    assert_eq!(trace[1]["output"]["zone"], "UTC");
+
+    let payloads = payloads.lock().unwrap();
+    let first = &payloads[0];
+    assert_eq!(first["system_prompt"], "Be brief.");
+    assert_eq!(first["tools"][0]["name"], "clock");
+    assert_eq!(first["tools"][0]["parameters"]["required"], serde_json::json!(["zone"]));
+    let messages = payloads[1]["messages"].as_array().unwrap();
+    assert_eq!(messages.len(), 3);
+    assert_eq!(messages[1]["tool_calls"][0]["name"], "clock");
+    assert_eq!(messages[2]["role"], "tool");
+    assert_eq!(messages[2]["tool_call_id"], "call_1");
+}
+
+#[tokio::test]
+async fn loop_stops_at_the_iteration_cap() {
+    let (orch, payloads) = orchestrator("clock", true);
+    let out = orch.dispatch(&ctx(), tool_chat(Some(3))).await.unwrap();
+    assert_eq!(out["status"], "max_iterations_reached");
+    assert_eq!((out["iterations"].as_u64(), out["tool_calls"].as_u64()), (Some(3), Some(3)));
+    assert_eq!(out["trace"].as_array().unwrap().len(), 6);
+    assert_eq!(payloads.lock().unwrap().len(), 3);
+}
+
+#[tokio::test]
+async fn unknown_tools_are_reported_to_the_model() {
+    let (orch, payloads) = orchestrator("shutdown", false);
+    let out = orch.dispatch(&ctx(), tool_chat(None)).await.unwrap();
+    assert_eq!(out["status"], "ok");
+    assert_eq!(out["trace"][1]["status"], "error");
+    assert_eq!(out["trace"][1]["error"], "unknown tool: shutdown");
+    let messages = payloads.lock().unwrap()[1]["messages"].clone();
+    assert!(messages[2]["content"].as_str().unwrap().contains("unknown tool"));
+}
@@ -0,0 +1,43 @@
+//! Pub/sub topics in KB_SOMA: publish, subscribe and Heartbeat fan-out into inboxes.
+
+use pagi_core::KnowledgeStore;
+
+#[test]
+fn broadcasts_reach_each_subscriber_once_from_subscription_on() {
+    let store = KnowledgeStore::open_temporary().unwrap();
+    store
+        .publish_topic_message("SAGE_BOT", "maintenance", &serde_json::json!({ "text": "before anyone listened" }))
+        .unwrap();
+    for agent in ["dev-1", "dev-2", "SAGE_BOT"] {
+        store.subscribe_agent(agent, "maintenance").unwrap();
+    }
+    assert_eq!(store.topic_subscribers("maintenance").unwrap(), ["SAGE_BOT", "dev-1", "dev-2"]);
+    assert_eq!(store.fan_out_topics().unwrap(), 0);
+
+    store
+        .publish_topic_message("SAGE_BOT", "maintenance", &serde_json::json!({ "text": "Restarting at 02:00" }))
+        .unwrap();
+    // Both dev agents get a copy; the publisher does not get its own broadcast.
+    assert_eq!(store.fan_out_topics().unwrap(), 2);
+    assert_eq!(store.fan_out_topics().unwrap(), 0);
+    let inbox = store.get_agent_messages("dev-1", 10).unwrap();
+    assert_eq!(inbox.len(), 1);
+    assert_eq!(inbox[0].text(), "Restarting at 02:00");
+    assert_eq!(inbox[0].from_agent_id, "SAGE_BOT");
+    assert_eq!(inbox[0].target_agent_id, "dev-1");
+    assert_eq!(inbox[0].topic.as_deref(), Some("maintenance"));
+    assert!(store.get_agent_messages("SAGE_BOT", 10).unwrap().is_empty());
+
+    assert!(store.unsubscribe_agent("dev-2", "maintenance").unwrap());
+    assert!(!store.unsubscribe_agent("dev-2", "maintenance").unwrap());
+    store.publish_topic_message("ops", "maintenance", &serde_json::json!("Done")).unwrap();
+    assert_eq!(store.fan_out_topics().unwrap(), 2);
+    assert_eq!(store.get_agent_messages("dev-2", 10).unwrap().len(), 1);
+    assert_eq!(store.get_agent_messages("SAGE_BOT", 10).unwrap().len(), 1);
+
+    let subscriptions = store.agent_subscriptions("dev-1").unwrap();
+    assert_eq!(subscriptions.len(), 1);
+    assert!(subscriptions[0].delivered_through.is_some());
+    assert!(store.publish_topic_message("a", "bad/topic", &serde_json::json!("x")).is_err());
+    assert!(store.subscribe_agent("dev-1", "").is_err());
+}
@@ -0,0 +1,134 @@
+//! Trace replay: re-running a recorded AutonomousGoal trace against changed skills or blueprints.
+
+mod common;
+
+use common::{ctx, CaptureAudit};
+use pagi_core::{
+    AgentSkill, BlueprintRegistry, CancellationToken, Goal, InvalidTrace, Orchestrator, Plan, PlanStep, ReplayDiff,
+    ReplayMode, SkillRegistry, TenantContext,
+};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// Appends `{name}@v{version}` to `payload.trail`; bumping the version stands in for a skill change.
+struct Versioned(&'static str, Arc<AtomicU64>);
+
+#[async_trait::async_trait]
+impl AgentSkill for Versioned {
+    fn name(&self) -> &str {
+        self.0
+    }
+
+    async fn execute(
+        &self,
+        _ctx: &TenantContext,
+        payload: Option<serde_json::Value>,
+    ) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>> {
+        let mut trail = payload
+            .as_ref()
+            .and_then(|p| p.get("trail"))
+            .and_then(|v| v.as_array())
+            .cloned()
+            .unwrap_or_default();
+        trail.push(serde_json::json!(format!("{}@v{}", self.0, self.1.load(Ordering::SeqCst))));
+        Ok(serde_json::json!({ "trail": trail }))
+    }
+}
+
+struct Setup {
+    orch: Orchestrator,
+    summarize_version: Arc<AtomicU64>,
+    trace: Arc<Mutex<Option<serde_json::Value>>>,
+}
+
+fn setup(plans: Vec<(&str, Vec<PlanStep>)>) -> Setup {
+    let summarize_version = Arc::new(AtomicU64::new(1));
+    let trace = Arc::new(Mutex::new(None));
+    let mut registry = SkillRegistry::new();
+    for name in ["Fetch", "Publish"] {
+        registry.register(Arc::new(Versioned(name, Arc::new(AtomicU64::new(1)))));
+    }
+    registry.register(Arc::new(Versioned("Summarize", Arc::clone(&summarize_version))));
+    registry.register(Arc::new(CaptureAudit(Arc::clone(&trace))));
+    let plans: HashMap<String, Plan> = plans
+        .into_iter()
+        .map(|(intent, steps)| (intent.to_string(), Plan { steps }))
+        .collect();
+    let orch = Orchestrator::with_blueprint(Arc::new(registry), Arc::new(BlueprintRegistry::from_plans(plans)));
+    Setup { orch, summarize_version, trace }
+}
+
+async fn record(setup: &Setup, intent: &str) -> serde_json::Value {
+    let goal = Goal::AutonomousGoal {
+        intent: intent.into(),
+        context: Some(serde_json::json!({ "trail": [] })),
+    };
+    setup.orch.dispatch(&ctx(), goal).await.expect("recorded run should succeed");
+    setup.trace.lock().unwrap().clone().expect("thought log recorded")
+}
+
+#[tokio::test]
+async fn recorded_replay_reruns_each_step_with_its_recorded_input() {
+    let setup = setup(vec![
+        ("digest", vec![PlanStep::new("Fetch"), PlanStep::sub_plan("summary")]),
+        ("summary", vec![PlanStep::new("Summarize")]),
+    ]);
+    let trace = record(&setup, "digest").await;
+    let token = CancellationToken::new();
+
+    let unchanged = setup.orch.replay_trace(&ctx(), &trace, ReplayMode::Recorded, &token).await.unwrap();
+    assert_eq!(unchanged.changed_steps, 0, "{:?}", unchanged.steps);
+    let paths: Vec<&str> = unchanged.steps.iter().map(|s| s.path.as_str()).collect();
+    assert_eq!(paths, ["0", "1", "1.0"]);
+
+    setup.summarize_version.store(2, Ordering::SeqCst);
+    let replay = setup.orch.replay_trace(&ctx(), &trace, ReplayMode::Recorded, &token).await.unwrap();
+    assert_eq!(replay.plan_source, "recorded");
+    assert_eq!(replay.steps[0].diff, ReplayDiff::Same);
+    let summarize = &replay.steps[2];
+    assert_eq!((summarize.step.as_str(), summarize.diff), ("Summarize", ReplayDiff::Changed));
+    assert_eq!(summarize.changes.len(), 1);
+    assert_eq!(summarize.changes[0].path, "/trail/1");
+    assert_eq!(summarize.changes[0].recorded, Some(serde_json::json!("Summarize@v1")));
+    assert_eq!(summarize.changes[0].replayed, Some(serde_json::json!("Summarize@v2")));
+    assert_eq!(replay.steps[1].diff, ReplayDiff::Changed, "the sub-plan output follows its last step");
+    assert_eq!(replay.changed_steps, 2);
+}
+
+#[tokio::test]
+async fn chained_replay_runs_the_current_blueprint_plan() {
+    let recorder = setup(vec![("digest", vec![PlanStep::new("Fetch"), PlanStep::new("Summarize")])]);
+    let trace = record(&recorder, "digest").await;
+    let token = CancellationToken::new();
+
+    let current = setup(vec![(
+        "digest",
+        vec![PlanStep::new("Fetch"), PlanStep::new("Summarize"), PlanStep::new("Publish")],
+    )]);
+    let replay = current.orch.replay_trace(&ctx(), &trace, ReplayMode::Chained, &token).await.unwrap();
+    assert_eq!(replay.plan_source, "blueprint");
+    assert_eq!(replay.plan_steps, ["Fetch", "Summarize", "Publish"]);
+    let diffs: Vec<ReplayDiff> = replay.steps.iter().map(|s| s.diff).collect();
+    assert_eq!(diffs, [ReplayDiff::Same, ReplayDiff::Same, ReplayDiff::Added]);
+    assert_eq!(
+        replay.final_result,
+        Some(serde_json::json!({ "trail": ["Fetch@v1", "Summarize@v1", "Publish@v1"] }))
+    );
+    assert_eq!(replay.final_changes.len(), 1, "{:?}", replay.final_changes);
+
+    // Without a blueprint entry the recorded plan_steps run again.
+    let bare = setup(Vec::new());
+    let replay = bare.orch.replay_trace(&ctx(), &trace, ReplayMode::Chained, &token).await.unwrap();
+    assert_eq!((replay.plan_source, replay.changed_steps), ("recorded", 0));
+    assert!(replay.final_changes.is_empty());
+}
+
+#[tokio::test]
+async fn traces_without_plan_steps_are_rejected() {
+    let setup = setup(Vec::new());
+    let token = CancellationToken::new();
+    let trace = serde_json::json!({ "trace": "not a thought log" });
+    let err = setup.orch.replay_trace(&ctx(), &trace, ReplayMode::Recorded, &token).await.unwrap_err();
+    assert!(err.is::<InvalidTrace>(), "{}", err);
+}
@@ -0,0 +1,99 @@
+//! Transactions: multi-key writes within a slot and across slots commit together or not at all.
+
+use pagi_core::{abort_transaction, AgentMessage, KbChangeKind, KbRecord, KbType, KnowledgeStore};
+
+#[test]
+fn writes_across_slots_commit_together() {
+    let store = KnowledgeStore::open_temporary().unwrap();
+    let soma = KbType::Soma.slot_id();
+    let chronos = KbType::Chronos.slot_id();
+    let msg = AgentMessage::new("planner", "scout", &serde_json::json!({ "text": "ping" }), store.now_ms());
+    store.insert(soma, &msg.inbox_key(), &msg.to_bytes()).unwrap();
+    let mut rx = store.subscribe_all();
+
+    let reply = AgentMessage::new("scout", "planner", &serde_json::json!({ "text": "pong" }), store.now_ms());
+    let mut processed = msg.clone();
+    processed.is_processed = true;
+    let event_key = pagi_core::keys::event_key("scout", 1_000, "e1");
+    let reply_id = store
+        .transaction_multi(&[soma, chronos], |tx| {
+            let inbox = tx.slot(soma)?;
+            inbox.insert(&reply.inbox_key(), &reply.to_bytes())?;
+            inbox.insert(&msg.inbox_key(), &processed.to_bytes())?;
+            tx.slot(chronos)?.insert_record(&event_key, &KbRecord::new("replied to planner", store.now_ms()))?;
+            Ok(reply.id.clone())
+        })
+        .unwrap();
+
+    assert_eq!(reply_id, reply.id);
+    assert!(store.get_agent_messages("scout", 1).unwrap()[0].is_processed);
+    assert_eq!(store.get_agent_messages("planner", 1).unwrap()[0].text(), "pong");
+    assert_eq!(store.get_record(chronos, &event_key).unwrap().unwrap().content, "replied to planner");
+    // Index updates and change events follow the commit.
+    assert_eq!(store.text_search(&[chronos], "replied", 5).unwrap().len(), 1);
+    let kinds: Vec<KbChangeKind> = std::iter::from_fn(|| rx.try_recv().ok()).map(|c| c.kind).collect();
+    assert_eq!(kinds, [KbChangeKind::Insert, KbChangeKind::Update, KbChangeKind::Insert]);
+}
+
+#[test]
+fn an_abort_or_bad_key_leaves_nothing_behind() {
+    let store = KnowledgeStore::open_temporary().unwrap();
+    let logos = KbType::Logos.slot_id();
+    store.insert_record(logos, "kept", &KbRecord::new("original", store.now_ms())).unwrap();
+    let mut rx = store.subscribe(logos);
+
+    let err = store
+        .transaction(logos, |tx| {
+            tx.insert_record("kept", &KbRecord::new("overwritten", store.now_ms()))?;
+            tx.insert_record("new", &KbRecord::new("staged", store.now_ms()))?;
+            Err::<(), _>(abort_transaction("changed my mind"))
+        })
+        .unwrap_err();
+    assert!(err.to_string().contains("changed my mind"));
+    assert_eq!(store.get_record(logos, "kept").unwrap().unwrap().content, "original");
+    assert!(store.get(logos, "new").unwrap().is_none());
+
+    // A structured key in the wrong format aborts the whole transaction too.
+    let soma = KbType::Soma.slot_id();
+    assert!(store
+        .transaction(soma, |tx| {
+            tx.insert("note", b"fine")?;
+            tx.insert("inbox/not-a-message-key", b"{}")?;
+            Ok(())
+        })
+        .is_err());
+    assert!(store.get(soma, "note").unwrap().is_none());
+    assert!(rx.try_recv().is_err());
+
+    // Reads inside see the transaction's own writes; Shadow and unnamed slots are refused.
+    let seen = store
+        .transaction(logos, |tx| {
+            tx.remove("kept")?;
+            tx.get("kept")
+        })
+        .unwrap();
+    assert!(seen.is_none());
+    assert!(store.transaction(9, |_| Ok(())).is_err());
+    assert!(store.transaction_multi(&[logos], |tx| tx.slot(soma).map(|_| ())).is_err());
+}
+
+#[test]
+fn values_over_the_size_limit_are_refused() {
+    let store = KnowledgeStore::open_temporary().unwrap().with_max_value_bytes(1024);
+    assert_eq!(store.max_value_bytes(), 1024);
+    let logos = KbType::Logos.slot_id();
+    store.insert(logos, "fits", &[b'a'; 1024]).unwrap();
+    // Highly compressible, but the limit applies to the value as written.
+    assert!(store.insert(logos, "big", &[b'a'; 1025]).is_err());
+    assert!(store.get(logos, "big").unwrap().is_none());
+
+    let err = store
+        .transaction(logos, |tx| {
+            tx.insert("small", b"ok")?;
+            tx.insert("big", &[b'a'; 4096])?;
+            Ok(())
+        })
+        .unwrap_err();
+    assert!(err.to_string().contains("KB-3 key 'big' is 4096 bytes, the limit is 1024 bytes"));
+    assert!(store.get(logos, "small").unwrap().is_none());
+}
@@ -0,0 +1,73 @@
+//! Vector index: semantic search through the HNSW graph, kept current by writes.
+
+use pagi_core::{KbRecord, KbType, KnowledgeStore};
+
+/// Unit-ish vector pointing at `angle` in the first two dimensions.
+fn at(angle: f32) -> Vec<f32> {
+    vec![angle.cos(), angle.sin(), 0.05, 0.05]
+}
+
+fn embedded(timestamp: i64, content: &str, vector: Vec<f32>) -> KbRecord {
+    KbRecord::with_embedding(content, serde_json::json!({}), vector, timestamp)
+}
+
+fn keys(store: &KnowledgeStore, slot_id: u8, query: &[f32], k: usize) -> Vec<String> {
+    store.semantic_search(slot_id, query, k).unwrap().into_iter().map(|h| h.key).collect()
+}
+
+#[test]
+fn nearest_records_come_back_most_similar_first() {
+    let store = KnowledgeStore::open_temporary().unwrap();
+    let logos = KbType::Logos.slot_id();
+    for i in 0..200 {
+        store.insert_record(logos, &format!("doc/{:03}", i), &embedded(store.now_ms(), &format!("finding {}", i), at(i as f32 * 0.025))).unwrap();
+    }
+    store.insert_record(logos, "plain", &KbRecord::new("no embedding", store.now_ms())).unwrap();
+
+    let hits = store.semantic_search(logos, &at(2.5), 3).unwrap();
+    assert_eq!(hits[0].key, "doc/100");
+    assert_eq!(hits[0].content, "finding 100");
+    assert!(hits[0].score > 0.99);
+    let mut near: Vec<&str> = hits.iter().map(|h| h.key.as_str()).collect();
+    near.sort();
+    assert_eq!(near, ["doc/099", "doc/100", "doc/101"]);
+    assert!(store.semantic_search(logos, &[0.0; 4], 3).unwrap().is_empty());
+    assert!(store.semantic_search(KbType::Oikos.slot_id(), &at(2.5), 3).unwrap().is_empty());
+}
+
+#[test]
+fn rewrites_removals_and_archiving_update_the_index() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = KnowledgeStore::open_path(dir.path().join("pagi_knowledge")).unwrap();
+    let logos = KbType::Logos.slot_id();
+    store.insert_record(logos, "a", &embedded(store.now_ms(), "a", at(0.0))).unwrap();
+    store.insert_record(logos, "b", &embedded(store.now_ms(), "b", at(1.0))).unwrap();
+    store.insert_record(logos, "c", &embedded(store.now_ms(), "c", at(2.0))).unwrap();
+    assert_eq!(keys(&store, logos, &at(0.1), 1), ["a"]);
+
+    // Re-embedding moves the record; removal drops it.
+    assert!(store.set_record_embedding(logos, "a", at(3.0), "test-model").unwrap());
+    assert_eq!(keys(&store, logos, &at(0.1), 1), ["b"]);
+    store.remove(logos, "b").unwrap();
+    assert_eq!(keys(&store, logos, &at(0.1), 3), ["c", "a"]);
+
+    let old = embedded(1_000, "old", at(0.1));
+    store.insert_record(logos, "old", &old).unwrap();
+    assert_eq!(keys(&store, logos, &at(0.1), 1), ["old"]);
+    assert_eq!(store.archive_older_than(logos, 10_000).unwrap().archived, 1);
+    assert_eq!(keys(&store, logos, &at(0.1), 1), ["c"]);
+}
+
+#[test]
+fn rebuild_takes_the_common_size_and_other_sizes_are_scanned() {
+    let store = KnowledgeStore::open_temporary().unwrap();
+    let logos = KbType::Logos.slot_id();
+    store.insert_record(logos, "small", &embedded(store.now_ms(), "small", vec![1.0, 0.0])).unwrap();
+    for i in 0..5 {
+        store.insert_record(logos, &format!("wide/{}", i), &embedded(store.now_ms(), "wide", at(i as f32))).unwrap();
+    }
+    assert_eq!(store.rebuild_vector_index(logos).unwrap(), 5);
+    assert_eq!(keys(&store, logos, &at(3.0), 1), ["wide/3"]);
+    // Not in the graph, still found by a query of its own size.
+    assert_eq!(keys(&store, logos, &[0.9, 0.1], 5), ["small"]);
+}
@@ -3,7 +3,7 @@
 //! Takes the last N user messages, infers sentiment and communication style,
 //! and stores/updates a RelationRecord so the agent can adapt its voice (Pneuma) to the user (Kardia).
 
-use pagi_core::{AgentSkill, KnowledgeStore, RelationRecord, TenantContext};
+use pagi_core::{AgentSkill, KnowledgeStore, TenantContext};
 use serde::Deserialize;
 use std::sync::Arc;
 
@@ -85,12 +85,10 @@ impl AgentSkill for AnalyzeSentiment {
         let style = infer_communication_style(&messages);
         let owner_agent_id = ctx.resolved_agent_id();
 
-        let mut record = self
-            .store
-            .get_kardia_relation(owner_agent_id, &args.user_id)
-            .unwrap_or_else(|| RelationRecord::new(&args.user_id));
-        record = record.with_sentiment(&sentiment).with_communication_style(&style);
-        self.store.set_kardia_relation(owner_agent_id, &record)?;
+        // Compare-and-swap, so a trust bump landing at the same time is kept.
+        let record = self.store.update_kardia_relation(owner_agent_id, &args.user_id, |rel| {
+            *rel = rel.clone().with_sentiment(&sentiment).with_communication_style(&style);
+        })?;
 
         Ok(serde_json::json!({
             "status": "ok",
@@ -0,0 +1,640 @@
+//! Calendar skill: offers free consultation slots and books them in Google Calendar or a CalDAV
+//! calendar (`[calendar]`).
+//!
+//! Payload:
+//! - `{ action: "availability", from?, to?, duration_minutes?, limit? }` – free slots inside
+//!   `work_hours` on `work_days`, between `from` (default now) and `to` (default `lookahead_days`
+//!   later), avoiding the calendar's busy times
+//! - `{ action: "book", start, duration_minutes?, summary?, description?, attendees?, lead_id? }` –
+//!   creates the event if the slot is still free; otherwise answers `status: "unavailable"` with
+//!   the next free `slots`
+//!
+//! Without `action`, a payload with `start` books and any other checks availability. Times are
+//! RFC 3339; the output is the input object with `slots` or `booking` added, so the skill can
+//! sit between blueprint steps. Attendees default to the lead's `email`. Each booking is recorded
+//! in KB_SOMA (`calendar_booking/{tenant_id}/...`, see [`CalendarBooking`]) and, with a `lead_id`,
+//! on the lead as `booking { event_id, start, end }`.
+
+use crate::feed_ingest::{days_from_civil, parse_rfc3339};
+use pagi_core::{
+    civil_from_days, AgentSkill, CalendarBooking, CalendarConfig, CalendarProvider, KnowledgeStore, MemoryManager, TenantContext,
+};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+const SKILL_NAME: &str = "CalendarSkill";
+const GOOGLE_API_URL: &str = "https://www.googleapis.com/calendar/v3";
+const GOOGLE_TOKEN_URL: &str = "https://oauth2.googleapis.com/token";
+const MINUTE_MS: i64 = 60_000;
+const DAY_MS: i64 = 86_400_000;
+/// Slots offered at most per call, whatever `limit` says.
+const MAX_SLOTS: usize = 50;
+/// Longest consultation that can be booked.
+const MAX_DURATION_MINUTES: i64 = 8 * 60;
+
+type SkillError = Box<dyn std::error::Error + Send + Sync>;
+
+/// A busy or free interval, ms since the epoch.
+type Span = (i64, i64);
+
+/// `ms` as RFC 3339 in UTC (`2030-01-07T09:00:00Z`).
+fn format_rfc3339(ms: i64) -> String {
+    let (year, month, day) = civil_from_days(ms.div_euclid(DAY_MS));
+    let secs = ms.rem_euclid(DAY_MS) / 1000;
+    format!("{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z", year, month, day, secs / 3600, secs / 60 % 60, secs % 60)
+}
+
+/// `ms` in iCalendar UTC form (`20300107T090000Z`).
+fn format_ics(ms: i64) -> String {
+    format_rfc3339(ms).replace(['-', ':'], "")
+}
+
+/// An iCalendar date-time value: `20300107T090000Z` (UTC), `20300107T090000` (local, read with
+/// `offset_minutes`) or `20300107` (a date, local midnight).
+fn parse_ics_time(value: &str, offset_minutes: i64) -> Option<i64> {
+    let num = |r: std::ops::Range<usize>| value.get(r)?.parse::<i64>().ok();
+    let days = days_from_civil(num(0..4)?, num(4..6)?, num(6..8)?)?;
+    let (local_ms, utc) = match value.get(8..9) {
+        None => (days * DAY_MS, false),
+        Some("T") => {
+            let ms = days * DAY_MS + ((num(9..11)? * 60 + num(11..13)?) * 60 + num(13..15)?) * 1000;
+            (ms, value.ends_with('Z'))
+        }
+        Some(_) => return None,
+    };
+    Some(if utc { local_ms } else { local_ms - offset_minutes * MINUTE_MS })
+}
+
+/// An iCalendar duration (`PT30M`, `P1D`, `P1W`, `PT1H30M`) in ms.
+fn parse_ics_duration(value: &str) -> Option<i64> {
+    let rest = value.trim_start_matches(['+', '-']).strip_prefix('P')?;
+    let (mut total, mut number) = (0i64, String::new());
+    for c in rest.chars() {
+        match c {
+            '0'..='9' => number.push(c),
+            'T' => {}
+            unit => {
+                let n: i64 = std::mem::take(&mut number).parse().ok()?;
+                total += n * match unit {
+                    'W' => 7 * DAY_MS,
+                    'D' => DAY_MS,
+                    'H' => 60 * MINUTE_MS,
+                    'M' => MINUTE_MS,
+                    'S' => 1000,
+                    _ => return None,
+                };
+            }
+        }
+    }
+    Some(total)
+}
+
+/// Busy spans of the VEVENTs in an iCalendar document; cancelled and transparent events are free.
+fn ics_busy(ics: &str, offset_minutes: i64) -> Vec<Span> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in ics.lines() {
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(continued), Some(last)) => last.push_str(continued),
+            _ => lines.push(line.trim_end_matches('\r').to_string()),
+        }
+    }
+    let mut busy = Vec::new();
+    let mut event: Option<Vec<(String, String)>> = None;
+    for line in lines {
+        if line == "BEGIN:VEVENT" {
+            event = Some(Vec::new());
+        } else if line == "END:VEVENT" {
+            let Some(props) = event.take() else { continue };
+            let prop = |name: &str| props.iter().find(|(n, _)| n == name).map(|(_, v)| v.as_str());
+            if prop("STATUS") == Some("CANCELLED") || prop("TRANSP") == Some("TRANSPARENT") {
+                continue;
+            }
+            let Some(start) = prop("DTSTART").and_then(|v| parse_ics_time(v, offset_minutes)) else { continue };
+            let all_day = prop("DTSTART").is_some_and(|v| v.len() == 8);
+            let end = prop("DTEND")
+                .and_then(|v| parse_ics_time(v, offset_minutes))
+                .or_else(|| prop("DURATION").and_then(parse_ics_duration).map(|d| start + d))
+                .unwrap_or(if all_day { start + DAY_MS } else { start });
+            if end > start {
+                busy.push((start, end));
+            }
+        } else if let Some(props) = event.as_mut() {
+            if let Some((name, value)) = line.split_once(':') {
+                // Parameters (`DTSTART;TZID=Europe/Berlin`) are dropped; see `parse_ics_time`.
+                let name = name.split(';').next().unwrap_or(name).to_ascii_uppercase();
+                props.push((name, value.trim().to_string()));
+            }
+        }
+    }
+    busy
+}
+
+/// iCalendar text: commas, semicolons, backslashes and newlines escaped.
+fn ics_text(text: &str) -> String {
+    text.replace('\\', "\\\\").replace(';', "\\;").replace(',', "\\,").replace('\n', "\\n").replace('\r', "")
+}
+
+/// `value` percent-encoded for one URL path segment.
+fn path_segment(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'@' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+/// Free slots of `duration` between `from` and `to`, starting on the `slot_minutes` grid inside
+/// the work hours of each work day, that overlap nothing in `busy`.
+fn free_slots(config: &CalendarConfig, busy: &[Span], from: i64, to: i64, duration: i64, limit: usize) -> Vec<Span> {
+    let Some((work_start, work_end)) = config.work_minutes() else {
+        return Vec::new();
+    };
+    let offset = i64::from(config.utc_offset_minutes) * MINUTE_MS;
+    let step = i64::from(config.slot_minutes.max(5)) * MINUTE_MS;
+    let mut slots = Vec::new();
+    let mut day = (from + offset).div_euclid(DAY_MS);
+    while slots.len() < limit && day * DAY_MS - offset < to {
+        // 1970-01-01 was a Thursday; 1 = Monday.
+        let weekday = ((day + 3).rem_euclid(7) + 1) as u8;
+        if config.work_days.contains(&weekday) {
+            let window_end = day * DAY_MS + i64::from(work_end) * MINUTE_MS - offset;
+            let mut start = day * DAY_MS + i64::from(work_start) * MINUTE_MS - offset;
+            while slots.len() < limit && start + duration <= window_end.min(to) {
+                let end = start + duration;
+                if start >= from && !busy.iter().any(|&(b_start, b_end)| b_start < end && start < b_end) {
+                    slots.push((start, end));
+                }
+                start += step;
+            }
+        }
+        day += 1;
+    }
+    slots
+}
+
+/// Offers and books consultation slots.
+pub struct CalendarSkill {
+    knowledge: Arc<KnowledgeStore>,
+    memory: Arc<MemoryManager>,
+    config: CalendarConfig,
+    http: reqwest::Client,
+    /// Google access token and when it expires.
+    google_token: Mutex<Option<(String, Instant)>>,
+}
+
+impl CalendarSkill {
+    pub fn new(knowledge: Arc<KnowledgeStore>, memory: Arc<MemoryManager>) -> Self {
+        Self {
+            knowledge,
+            memory,
+            config: CalendarConfig::default(),
+            http: reqwest::Client::new(),
+            google_token: Mutex::new(None),
+        }
+    }
+
+    pub fn with_config(mut self, config: CalendarConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    fn timeout(&self) -> Duration {
+        Duration::from_secs(self.config.timeout_secs.max(1))
+    }
+
+    fn google_url(&self, path: &str) -> String {
+        let base = self.config.google.api_url.as_deref().unwrap_or(GOOGLE_API_URL).trim_end_matches('/');
+        format!("{}/{}", base, path)
+    }
+
+    /// A Google access token, refreshed with the refresh token a minute before it expires.
+    async fn google_access_token(&self) -> Result<String, SkillError> {
+        if let Some((token, expires)) = self.google_token.lock().unwrap_or_else(PoisonError::into_inner).clone() {
+            if Instant::now() < expires {
+                return Ok(token);
+            }
+        }
+        let secret = |name: &str| pagi_core::secret(name).ok_or_else(|| format!("calendar secret {} is not set", name));
+        let form = [
+            ("client_id", self.config.google.client_id.clone()),
+            ("client_secret", secret(self.config.google_client_secret_env())?),
+            ("refresh_token", secret(self.config.google_refresh_token_env())?),
+            ("grant_type", "refresh_token".to_string()),
+        ];
+        let url = self.config.google.token_url.as_deref().unwrap_or(GOOGLE_TOKEN_URL);
+        let res = self.http.post(url).timeout(self.timeout()).form(&form).send().await?;
+        let status = res.status();
+        let body: serde_json::Value = res.json().await.unwrap_or_default();
+        let token = body["access_token"]
+            .as_str()
+            .filter(|_| status.is_success())
+            .ok_or_else(|| format!("Google token refresh answered {}: {}", status, body["error_description"].as_str().unwrap_or("no access_token")))?
+            .to_string();
+        let lifetime = body["expires_in"].as_u64().unwrap_or(3600).saturating_sub(60);
+        *self.google_token.lock().unwrap_or_else(PoisonError::into_inner) =
+            Some((token.clone(), Instant::now() + Duration::from_secs(lifetime)));
+        Ok(token)
+    }
+
+    /// The calendar's busy spans overlapping `from..to`.
+    async fn busy(&self, from: i64, to: i64) -> Result<Vec<Span>, SkillError> {
+        match self.config.provider {
+            CalendarProvider::None => Err("CalendarSkill: no [calendar] provider is configured".into()),
+            CalendarProvider::Google => {
+                let calendar_id = self.config.google_calendar_id();
+                let body = serde_json::json!({
+                    "timeMin": format_rfc3339(from),
+                    "timeMax": format_rfc3339(to),
+                    "items": [{ "id": calendar_id }],
+                });
+                let token = self.google_access_token().await?;
+                let res = self.http.post(self.google_url("freeBusy")).timeout(self.timeout()).bearer_auth(token).json(&body).send().await?;
+                let status = res.status();
+                let answer: serde_json::Value = res.json().await.unwrap_or_default();
+                let calendar = &answer["calendars"][calendar_id];
+                if !status.is_success() || calendar["errors"].as_array().is_some_and(|e| !e.is_empty()) {
+                    return Err(format!("Google freeBusy answered {}: {}", status, answer).into());
+                }
+                Ok(calendar["busy"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|b| Some((parse_rfc3339(b["start"].as_str()?)?, parse_rfc3339(b["end"].as_str()?)?)))
+                    .collect())
+            }
+            CalendarProvider::Caldav => {
+                let (start, end) = (format_ics(from), format_ics(to));
+                let query = format!(
+                    r#"<?xml version="1.0" encoding="utf-8"?>
+<c:calendar-query xmlns:d="DAV:" xmlns:c="urn:ietf:params:xml:ns:caldav">
+  <d:prop><c:calendar-data><c:expand start="{start}" end="{end}"/></c:calendar-data></d:prop>
+  <c:filter><c:comp-filter name="VCALENDAR"><c:comp-filter name="VEVENT"><c:time-range start="{start}" end="{end}"/></c:comp-filter></c:comp-filter></c:filter>
+</c:calendar-query>"#
+                );
+                let report = reqwest::Method::from_bytes(b"REPORT").expect("REPORT is a valid method");
+                let res = self
+                    .caldav(report, &self.config.caldav.url)?
+                    .header("Depth", "1")
+                    .header("Content-Type", "application/xml; charset=utf-8")
+                    .body(query)
+                    .send()
+                    .await?;
+                let status = res.status();
+                let xml = res.text().await?;
+                if !status.is_success() {
+                    return Err(format!("CalDAV REPORT answered {}", status).into());
+                }
+                let doc = roxmltree::Document::parse(&xml).map_err(|e| format!("CalDAV REPORT answered malformed XML: {}", e))?;
+                let offset = i64::from(self.config.utc_offset_minutes);
+                Ok(doc
+                    .descendants()
+                    .filter(|n| n.tag_name().name() == "calendar-data")
+                    .filter_map(|n| n.text())
+                    .flat_map(|ics| ics_busy(ics, offset))
+                    .collect())
+            }
+        }
+    }
+
+    /// A CalDAV request with the configured credentials.
+    fn caldav(&self, method: reqwest::Method, url: &str) -> Result<reqwest::RequestBuilder, SkillError> {
+        if url.is_empty() {
+            return Err("CalendarSkill: [calendar.caldav] url is not set".into());
+        }
+        let request = self.http.request(method, url).timeout(self.timeout());
+        Ok(match &self.config.caldav.username {
+            Some(user) => {
+                let password = pagi_core::secret(self.config.caldav_password_env())
+                    .ok_or_else(|| format!("calendar secret {} is not set", self.config.caldav_password_env()))?;
+                request.basic_auth(user, Some(password))
+            }
+            None => request,
+        })
+    }
+
+    /// Creates the event; its id and link.
+    async fn create_event(&self, span: Span, summary: &str, description: Option<&str>, attendees: &[String]) -> Result<(String, Option<String>), SkillError> {
+        match self.config.provider {
+            CalendarProvider::None => Err("CalendarSkill: no [calendar] provider is configured".into()),
+            CalendarProvider::Google => {
+                let body = serde_json::json!({
+                    "summary": summary,
+                    "description": description,
+                    "start": { "dateTime": format_rfc3339(span.0) },
+                    "end": { "dateTime": format_rfc3339(span.1) },
+                    "attendees": attendees.iter().map(|a| serde_json::json!({ "email": a })).collect::<Vec<_>>(),
+                });
+                let path = format!("calendars/{}/events?sendUpdates=all", path_segment(self.config.google_calendar_id()));
+                let token = self.google_access_token().await?;
+                let res = self.http.post(self.google_url(&path)).timeout(self.timeout()).bearer_auth(token).json(&body).send().await?;
+                let status = res.status();
+                let event: serde_json::Value = res.json().await.unwrap_or_default();
+                let id = event["id"]
+                    .as_str()
+                    .filter(|_| status.is_success())
+                    .ok_or_else(|| format!("Google Calendar answered {}: {}", status, event["error"]["message"].as_str().unwrap_or("no event id")))?;
+                Ok((id.to_string(), event["htmlLink"].as_str().map(String::from)))
+            }
+            CalendarProvider::Caldav => {
+                let uid = format!("{}@pagi", uuid::Uuid::new_v4().simple());
+                let mut ics = vec![
+                    "BEGIN:VCALENDAR".to_string(),
+                    "VERSION:2.0".to_string(),
+                    "PRODID:-//PAGI//CalendarSkill//EN".to_string(),
+                    "BEGIN:VEVENT".to_string(),
+                    format!("UID:{}", uid),
+                    format!("DTSTAMP:{}", format_ics(self.knowledge.now_ms())),
+                    format!("DTSTART:{}", format_ics(span.0)),
+                    format!("DTEND:{}", format_ics(span.1)),
+                    format!("SUMMARY:{}", ics_text(summary)),
+                ];
+                if let Some(description) = description {
+                    ics.push(format!("DESCRIPTION:{}", ics_text(description)));
+                }
+                ics.extend(attendees.iter().map(|a| format!("ATTENDEE;RSVP=TRUE:mailto:{}", a)));
+                ics.extend(["END:VEVENT".to_string(), "END:VCALENDAR".to_string(), String::new()]);
+                let url = format!("{}/{}.ics", self.config.caldav.url.trim_end_matches('/'), path_segment(&uid));
+                let res = self
+                    .caldav(reqwest::Method::PUT, &url)?
+                    .header("If-None-Match", "*")
+                    .header("Content-Type", "text/calendar; charset=utf-8")
+                    .body(ics.join("\r\n"))
+                    .send()
+                    .await?;
+                if !res.status().is_success() {
+                    return Err(format!("CalDAV PUT answered {}", res.status()).into());
+                }
+                Ok((uid, Some(url)))
+            }
+        }
+    }
+
+    fn provider_name(&self) -> &'static str {
+        match self.config.provider {
+            CalendarProvider::Google => "google",
+            CalendarProvider::Caldav => "caldav",
+            CalendarProvider::None => "none",
+        }
+    }
+}
+
+fn slots_json(slots: &[Span]) -> serde_json::Value {
+    slots
+        .iter()
+        .map(|&(start, end)| serde_json::json!({ "start": format_rfc3339(start), "end": format_rfc3339(end) }))
+        .collect()
+}
+
+#[async_trait::async_trait]
+impl AgentSkill for CalendarSkill {
+    fn name(&self) -> &str {
+        SKILL_NAME
+    }
+
+    async fn execute(&self, ctx: &TenantContext, payload: Option<serde_json::Value>) -> Result<serde_json::Value, SkillError> {
+        let payload = payload.unwrap_or(serde_json::Value::Null);
+        let text = |field: &str| payload.get(field).and_then(|v| v.as_str()).map(str::trim).filter(|s| !s.is_empty());
+        let time = |field: &str| -> Result<Option<i64>, SkillError> {
+            text(field)
+                .map(|t| parse_rfc3339(t).ok_or_else(|| format!("CalendarSkill: `{}` is not an RFC 3339 time: {:?}", field, t).into()))
+                .transpose()
+        };
+        let duration_minutes = payload["duration_minutes"].as_i64().unwrap_or(i64::from(self.config.slot_minutes));
+        if !(5..=MAX_DURATION_MINUTES).contains(&duration_minutes) {
+            return Err(format!("CalendarSkill: duration_minutes must be 5..={}", MAX_DURATION_MINUTES).into());
+        }
+        let duration = duration_minutes * MINUTE_MS;
+        let limit = payload["limit"].as_u64().map_or(self.config.max_slots, |l| l as usize).clamp(1, MAX_SLOTS);
+        let mut out = match &payload {
+            serde_json::Value::Object(map) => map.clone(),
+            _ => serde_json::Map::new(),
+        };
+        out.insert("skill".to_string(), serde_json::json!(SKILL_NAME));
+        let lookahead = i64::from(self.config.lookahead_days.max(1)) * DAY_MS;
+        let booking = match text("action") {
+            Some("book") => true,
+            Some("availability") => false,
+            Some(other) => return Err(format!("CalendarSkill: unknown action {:?} (availability or book)", other).into()),
+            None => text("start").is_some(),
+        };
+
+        if !booking {
+            let from = time("from")?.unwrap_or_else(|| self.knowledge.now_ms());
+            let to = time("to")?.unwrap_or(from + lookahead);
+            if to <= from {
+                return Err("CalendarSkill: `to` must be after `from`".into());
+            }
+            let busy = self.busy(from, to).await?;
+            let slots = free_slots(&self.config, &busy, from, to, duration, limit);
+            out.insert("status".to_string(), serde_json::json!("available"));
+            out.insert("slots".to_string(), slots_json(&slots));
+            return Ok(serde_json::Value::Object(out));
+        }
+
+        let start = time("start")?.ok_or("CalendarSkill: booking requires `start`")?;
+        let span = (start, start + duration);
+        let busy = self.busy(span.0, span.1).await?;
+        if busy.iter().any(|&(b_start, b_end)| b_start < span.1 && span.0 < b_end) {
+            let alternatives = self.busy(span.0, span.0 + lookahead).await?;
+            let slots = free_slots(&self.config, &alternatives, span.0, span.0 + lookahead, duration, limit);
+            out.insert("status".to_string(), serde_json::json!("unavailable"));
+            out.insert("slots".to_string(), slots_json(&slots));
+            return Ok(serde_json::Value::Object(out));
+        }
+
+        let lead_id = text("lead_id");
+        let lead = match lead_id {
+            Some(id) => Some(self.memory.get_lead(ctx, id)?.ok_or_else(|| format!("CalendarSkill: unknown lead {}", id))?),
+            None => None,
+        };
+        let lead_field = |name: &str| lead.as_ref().and_then(|l| l.fields.get(name)).and_then(|v| v.as_str()).map(str::trim);
+        let attendees: Vec<String> = match payload.get("attendees") {
+            Some(serde_json::Value::String(a)) => vec![a.clone()],
+            Some(serde_json::Value::Array(a)) => a.iter().filter_map(|a| a.as_str()).map(String::from).collect(),
+            _ => lead_field("email").filter(|e| !e.is_empty()).map(String::from).into_iter().collect(),
+        };
+        if attendees.iter().any(|a| !a.contains('@') || a.contains(|c: char| c.is_whitespace() || c == ':')) {
+            return Err("CalendarSkill: attendees must be email addresses".into());
+        }
+        let summary = match (text("summary"), lead_field("name").filter(|n| !n.is_empty())) {
+            (Some(summary), _) => summary.to_string(),
+            (None, Some(name)) => format!("Consultation with {}", name),
+            (None, None) => "Consultation".to_string(),
+        };
+        let (event_id, link) = self.create_event(span, &summary, text("description"), &attendees).await?;
+
+        let record = CalendarBooking {
+            id: uuid::Uuid::new_v4().simple().to_string(),
+            tenant_id: ctx.tenant_id.clone(),
+            provider: self.provider_name().to_string(),
+            event_id,
+            summary,
+            start: format_rfc3339(span.0),
+            end: format_rfc3339(span.1),
+            attendees,
+            lead_id: lead_id.map(String::from),
+            link,
+            created_at_ms: 0,
+        };
+        if let Err(e) = self.knowledge.record_calendar_booking(&record) {
+            tracing::warn!(target: "pagi::skills", booking_id = %record.id, error = %e, "CalendarSkill: failed to record booking");
+        }
+        if let Some(mut lead) = lead {
+            lead.fields.insert(
+                "booking".to_string(),
+                serde_json::json!({ "event_id": record.event_id, "start": record.start, "end": record.end }),
+            );
+            if let Err(e) = self.memory.put_lead(ctx, lead) {
+                tracing::warn!(target: "pagi::skills", error = %e, "CalendarSkill: failed to note the booking on the lead");
+            }
+        }
+        tracing::info!(target: "pagi::skills", booking_id = %record.id, provider = %record.provider, start = %record.start, "CalendarSkill: event booked");
+        out.insert("status".to_string(), serde_json::json!("booked"));
+        out.insert("booking".to_string(), serde_json::to_value(&record)?);
+        Ok(serde_json::Value::Object(out))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pagi_core::{CalDavConfig, LeadRecord};
+
+    /// Monday 2030-01-07 00:00 UTC.
+    const MONDAY: i64 = 21_921 * DAY_MS;
+
+    #[test]
+    fn free_slots_skip_busy_times_and_weekends() {
+        let config = CalendarConfig::default();
+        let busy = [(MONDAY + 10 * 60 * MINUTE_MS, MONDAY + 11 * 60 * MINUTE_MS)];
+        let slots = free_slots(&config, &busy, MONDAY + 9 * 60 * MINUTE_MS, MONDAY + 7 * DAY_MS, 60 * MINUTE_MS, 4);
+        let starts: Vec<String> = slots.iter().map(|s| format_rfc3339(s.0)).collect();
+        assert_eq!(starts, ["2030-01-07T09:00:00Z", "2030-01-07T11:00:00Z", "2030-01-07T11:30:00Z", "2030-01-07T12:00:00Z"]);
+        let saturday = MONDAY + 5 * DAY_MS;
+        let weekend = free_slots(&config, &[], saturday, saturday + 3 * DAY_MS, 30 * MINUTE_MS, 1);
+        assert_eq!(format_rfc3339(weekend[0].0), "2030-01-14T09:00:00Z");
+    }
+
+    #[test]
+    fn reads_busy_events_from_icalendar() {
+        let ics = "BEGIN:VCALENDAR\r\nBEGIN:VEVENT\r\nUID:a\r\nDTSTART:20300107T100000Z\r\nDTEND:20300107T110000Z\r\nEND:VEVENT\r\n\
+                   BEGIN:VEVENT\r\nUID:b\r\nDTSTART;TZID=Europe/Berlin:20300107T140000\r\nDURATION:PT1H30M\r\nEND:VEVENT\r\n\
+                   BEGIN:VEVENT\r\nUID:c\r\nDTSTART;VALUE=DATE:20300108\r\nTRANSP:TRANSPARENT\r\nEND:VEVENT\r\nEND:VCALENDAR\r\n";
+        let busy = ics_busy(ics, 60);
+        assert_eq!(
+            busy,
+            [
+                (MONDAY + 10 * 60 * MINUTE_MS, MONDAY + 11 * 60 * MINUTE_MS),
+                (MONDAY + 13 * 60 * MINUTE_MS, MONDAY + 14 * 60 * MINUTE_MS + 30 * MINUTE_MS),
+            ]
+        );
+    }
+
+    /// A CalDAV server with one event, Monday 10:00–11:00 UTC; returns the collection url and the
+    /// requests made so far (method and body).
+    async fn serve_caldav() -> (String, Arc<Mutex<Vec<(String, String)>>>) {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/cal/", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let seen = Arc::clone(&requests);
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let mut request = Vec::new();
+                let mut buf = vec![0u8; 8192];
+                // Read the head, then as much body as content-length announces.
+                let (head, body) = loop {
+                    let n = socket.read(&mut buf).await.unwrap_or(0);
+                    request.extend_from_slice(&buf[..n]);
+                    let text = String::from_utf8_lossy(&request).to_string();
+                    if let Some((head, body)) = text.split_once("\r\n\r\n") {
+                        let length = head
+                            .lines()
+                            .find_map(|l| l.to_ascii_lowercase().strip_prefix("content-length:").map(|v| v.trim().parse().unwrap_or(0)))
+                            .unwrap_or(0usize);
+                        if body.len() >= length || n == 0 {
+                            break (head.to_string(), body.to_string());
+                        }
+                    } else if n == 0 {
+                        break (text, String::new());
+                    }
+                };
+                let method = head.split_whitespace().next().unwrap_or("").to_string();
+                seen.lock().unwrap().push((method.clone(), body));
+                let (status, body) = match method.as_str() {
+                    "PUT" => ("201 Created", String::new()),
+                    _ => (
+                        "207 Multi-Status",
+                        "<?xml version=\"1.0\"?><d:multistatus xmlns:d=\"DAV:\" xmlns:c=\"urn:ietf:params:xml:ns:caldav\">\
+                         <d:response><d:href>/cal/a.ics</d:href><d:propstat><d:prop><c:calendar-data>BEGIN:VCALENDAR\r\n\
+                         BEGIN:VEVENT\r\nUID:a\r\nDTSTART:20300107T100000Z\r\nDTEND:20300107T110000Z\r\nEND:VEVENT\r\n\
+                         END:VCALENDAR\r\n</c:calendar-data></d:prop></d:propstat></d:response></d:multistatus>"
+                            .to_string(),
+                    ),
+                };
+                let head = format!(
+                    "HTTP/1.1 {}\r\ncontent-type: application/xml\r\ncontent-length: {}\r\nconnection: close\r\n\r\n",
+                    status,
+                    body.len()
+                );
+                let _ = socket.write_all(head.as_bytes()).await;
+                let _ = socket.write_all(body.as_bytes()).await;
+            }
+        });
+        (url, requests)
+    }
+
+    #[tokio::test]
+    async fn offers_and_books_slots_in_a_caldav_calendar() {
+        let (url, requests) = serve_caldav().await;
+
+        let knowledge = Arc::new(KnowledgeStore::open_temporary().unwrap());
+        let memory = Arc::new(MemoryManager::open_temporary().unwrap());
+        let ctx = TenantContext::new("acme");
+        let mut fields = serde_json::Map::new();
+        fields.insert("name".to_string(), serde_json::json!("Ada"));
+        fields.insert("email".to_string(), serde_json::json!("ada@example.com"));
+        let lead = memory.put_lead(&ctx, LeadRecord::new(fields)).unwrap();
+        let skill = CalendarSkill::new(Arc::clone(&knowledge), Arc::clone(&memory)).with_config(CalendarConfig {
+            provider: CalendarProvider::Caldav,
+            caldav: CalDavConfig { url, ..Default::default() },
+            ..Default::default()
+        });
+
+        let from = format_rfc3339(MONDAY + 9 * 60 * MINUTE_MS);
+        let offer = skill
+            .execute(&ctx, Some(serde_json::json!({ "lead_id": lead.id, "from": from, "duration_minutes": 60, "limit": 2 })))
+            .await
+            .unwrap();
+        assert_eq!(offer["status"], "available");
+        assert_eq!(offer["lead_id"], lead.id.as_str());
+        assert_eq!(offer["slots"][0]["start"], "2030-01-07T09:00:00Z");
+        assert_eq!(offer["slots"][1]["start"], "2030-01-07T11:00:00Z");
+
+        let taken = skill
+            .execute(&ctx, Some(serde_json::json!({ "lead_id": lead.id, "start": "2030-01-07T10:30:00Z" })))
+            .await
+            .unwrap();
+        assert_eq!(taken["status"], "unavailable");
+        assert_eq!(taken["slots"][0]["start"], "2030-01-07T11:00:00Z");
+
+        let booked = skill
+            .execute(&ctx, Some(serde_json::json!({ "lead_id": lead.id, "start": "2030-01-07T11:00:00Z" })))
+            .await
+            .unwrap();
+        assert_eq!(booked["status"], "booked");
+        assert_eq!(booked["booking"]["end"], "2030-01-07T11:30:00Z");
+        let put = requests.lock().unwrap().iter().find(|(m, _)| m == "PUT").cloned().expect("event PUT to the calendar");
+        assert!(put.1.contains("DTSTART:20300107T110000Z"));
+        assert!(put.1.contains("SUMMARY:Consultation with Ada"));
+        assert!(put.1.contains("ATTENDEE;RSVP=TRUE:mailto:ada@example.com"));
+
+        let log = knowledge.list_calendar_bookings("acme", 10).unwrap();
+        assert_eq!(log.len(), 1);
+        assert_eq!(log[0].lead_id.as_deref(), Some(lead.id.as_str()));
+        let lead = memory.get_lead(&ctx, &lead.id).unwrap().unwrap();
+        assert_eq!(lead.fields["booking"]["start"], "2030-01-07T11:00:00Z");
+    }
+}
@@ -1,9 +1,10 @@
 //! Check Alignment skill: consults **KB_ETHOS** to return pass/fail for an intended action.
 //!
 //! Allows the Agent or external systems to ask "Is running skill X with this payload
-//! aligned with current safety protocols?" without executing the skill.
+//! aligned with current safety protocols?" without executing the skill. Evaluates the Ethos rule
+//! set when one is stored, else the legacy keyword policy.
 
-use pagi_core::{AgentSkill, AlignmentResult, KnowledgeStore, TenantContext};
+use pagi_core::{AgentSkill, KnowledgeStore, PolicyDecision, TenantContext};
 use serde::Deserialize;
 use std::sync::Arc;
 
@@ -16,6 +17,9 @@ struct CheckAlignmentArgs {
     /// Content to scan for sensitive keywords (e.g. payload content or summary).
     #[serde(default)]
     content: String,
+    /// Full intended payload, for rules that test payload fields (default: `{ "content": content }`).
+    #[serde(default)]
+    payload: Option<serde_json::Value>,
 }
 
 /// Consults KB_ETHOS and returns whether the intended action is allowed.
@@ -42,25 +46,20 @@ impl AgentSkill for CheckAlignment {
     ) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>> {
         let payload = payload.ok_or("check_alignment requires payload: { skill_name, content? }")?;
         let args: CheckAlignmentArgs = serde_json::from_value(payload)?;
-        let content = if args.content.is_empty() {
-            "".to_string()
-        } else {
-            args.content
-        };
-        let policy = self.store.get_ethos_policy();
-        let result = match policy {
-            None => AlignmentResult::Pass,
-            Some(p) => p.allows(&args.skill_name, &content),
-        };
-        let (pass, reason) = match &result {
-            AlignmentResult::Pass => (true, serde_json::Value::Null),
-            AlignmentResult::Fail { reason } => (false, serde_json::Value::String(reason.clone())),
+        let intended = args
+            .payload
+            .unwrap_or_else(|| serde_json::json!({ "content": args.content }));
+        let decision = match self.store.active_ethos_policy() {
+            None => PolicyDecision::allowed(),
+            Some(policy) => policy.evaluate(&args.skill_name, Some(&intended)),
         };
         Ok(serde_json::json!({
             "status": "ok",
             "skill": SKILL_NAME,
-            "pass": pass,
-            "reason": reason,
+            "pass": decision.is_allowed(),
+            "action": decision.action,
+            "rule_id": decision.rule_id,
+            "reason": decision.reason,
         }))
     }
 }
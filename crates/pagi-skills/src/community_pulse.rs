@@ -46,10 +46,7 @@ impl AgentSkill for CommunityPulse {
             .unwrap_or("")
             .to_string();
 
-        let updated_at = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
+        let updated_at = self.knowledge.clock().now_secs();
         let pulse = serde_json::json!({
             "location": location,
             "trend": trend,
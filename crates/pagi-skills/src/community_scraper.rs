@@ -1,7 +1,16 @@
 //! Community Scraper skill: fetches a URL (or uses provided HTML), extracts headlines/events, and updates KB-5 (Community Pulse).
+//!
+//! Fetching follows `[scraper]` (see [`crate::web_fetch`]): robots.txt is honoured, one host is
+//! contacted at most every `min_interval_ms`, only HTML is accepted, and the page is decoded with
+//! its declared charset. With `fetch = false` only inline `html` is accepted.
+//!
+//! Only plain text is stored: text inside scripts, styles and the like is skipped, and markup that
+//! survives as text (entity-encoded tags) is stripped. Pages and inline HTML over
+//! [`MAX_HTML_BYTES`] are refused, and the stored event is capped at [`MAX_EVENT_CHARS`].
 
-use pagi_core::{AgentSkill, KnowledgeStore, TenantContext};
-use scraper::{Html, Selector};
+use crate::web_fetch::{PoliteFetcher, HTML_TYPES};
+use pagi_core::{AgentSkill, KnowledgeStore, ScraperConfig, TenantContext};
+use scraper::{ElementRef, Html, Selector};
 use std::sync::Arc;
 
 const SKILL_NAME: &str = "CommunityScraper";
@@ -9,15 +18,34 @@ const KB_SLOT_COMMUNITY: u8 = 5;
 const CURRENT_PULSE_KEY: &str = "current_pulse";
 const DEFAULT_LOCATION: &str = "Stockdale";
 const DEFAULT_TREND: &str = "Scraped";
+/// Largest page or inline `html` the skill parses.
+pub const MAX_HTML_BYTES: usize = 2 * 1024 * 1024;
+/// Longest event text stored; longer extracts are cut.
+pub const MAX_EVENT_CHARS: usize = 8_000;
+/// Elements whose text is never page content.
+const SKIPPED_ELEMENTS: [&str; 6] = ["script", "style", "noscript", "template", "svg", "iframe"];
 
 /// Fetches a page (or uses provided HTML), extracts headings/article text, and writes to KB-5.
 pub struct CommunityScraper {
     knowledge: Arc<KnowledgeStore>,
+    config: ScraperConfig,
+    /// Built on first fetch, so that constructing the skill never fails.
+    fetcher: tokio::sync::OnceCell<PoliteFetcher>,
 }
 
 impl CommunityScraper {
     pub fn new(knowledge: Arc<KnowledgeStore>) -> Self {
-        Self { knowledge }
+        Self {
+            knowledge,
+            config: ScraperConfig::default(),
+            fetcher: tokio::sync::OnceCell::new(),
+        }
+    }
+
+    /// Fetch settings (`[scraper]`).
+    pub fn with_config(mut self, config: ScraperConfig) -> Self {
+        self.config = config;
+        self
     }
 }
 
@@ -41,7 +69,7 @@ fn extract_headlines_and_events(html: &str) -> String {
     for selector_str in selectors {
         if let Ok(sel) = Selector::parse(selector_str) {
             for el in document.select(&sel) {
-                let text = el.text().collect::<Vec<_>>().join(" ").trim().to_string();
+                let text = visible_text(el);
                 if !text.is_empty() && !parts.contains(&text) {
                     parts.push(text);
                 }
@@ -52,10 +80,52 @@ fn extract_headlines_and_events(html: &str) -> String {
     if parts.is_empty() {
         "(no events extracted)".to_string()
     } else {
-        parts.join(". ")
+        let events = parts.join(". ");
+        match events.char_indices().nth(MAX_EVENT_CHARS) {
+            Some((cut, _)) => events[..cut].to_string(),
+            None => events,
+        }
     }
 }
 
+/// Sanitized text of `el`, without the text of skipped elements.
+pub(crate) fn visible_text(el: ElementRef<'_>) -> String {
+    let texts: Vec<&str> = el
+        .descendants()
+        .filter(|node| {
+            !node
+                .ancestors()
+                .any(|a| a.value().as_element().is_some_and(|e| SKIPPED_ELEMENTS.contains(&e.name())))
+        })
+        .filter_map(|node| node.value().as_text().map(|t| &**t))
+        .collect();
+    sanitize_text(&texts.join(" "))
+}
+
+/// Plain text safe to store and render: tag-like markup (e.g. a `<script>` that arrived
+/// entity-encoded) and control characters are removed, and whitespace is collapsed.
+fn sanitize_text(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '<' && chars.peek().is_some_and(|n| n.is_ascii_alphabetic() || matches!(n, '/' | '!' | '?')) {
+            for skipped in chars.by_ref() {
+                if skipped == '>' {
+                    break;
+                }
+            }
+            out.push(' ');
+        } else {
+            out.push(if c.is_control() { ' ' } else { c });
+        }
+    }
+    out.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn too_large(what: &str, bytes: impl std::fmt::Display) -> Box<dyn std::error::Error + Send + Sync> {
+    format!("CommunityScraper: {} is {} bytes, the limit is {} bytes", what, bytes, MAX_HTML_BYTES).into()
+}
+
 #[async_trait::async_trait]
 impl AgentSkill for CommunityScraper {
     fn name(&self) -> &str {
@@ -64,7 +134,7 @@ impl AgentSkill for CommunityScraper {
 
     async fn execute(
         &self,
-        _ctx: &TenantContext,
+        ctx: &TenantContext,
         payload: Option<serde_json::Value>,
     ) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>> {
         let payload = payload.ok_or("CommunityScraper requires payload: { url: string } or { slot_id?: 1..8, url?, html? }")?;
@@ -88,21 +158,25 @@ impl AgentSkill for CommunityScraper {
             .to_string();
 
         let html = if let Some(html) = html_override {
+            if html.len() > MAX_HTML_BYTES {
+                return Err(too_large("inline html", html.len()));
+            }
             html
         } else {
             let url = url.ok_or("CommunityScraper requires 'url' when 'html' is not provided")?;
-            let client = reqwest::Client::builder()
-                .user_agent("UAC-CommunityScraper/1.0")
-                .build()?;
-            let resp = client.get(&url).send().await?;
-            resp.text().await?
+            if !self.config.fetch {
+                return Err("CommunityScraper: fetching is disabled ([scraper] fetch = false); provide 'html'".into());
+            }
+            let fetcher = self
+                .fetcher
+                .get_or_try_init(|| async { PoliteFetcher::new(self.config.clone()) })
+                .await?;
+            let timeout = ctx.bounded_timeout(std::time::Duration::from_secs(self.config.timeout_secs));
+            fetcher.fetch_text(&url, timeout, MAX_HTML_BYTES, HTML_TYPES).await?
         };
 
         let event = extract_headlines_and_events(&html);
-        let updated_at = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
+        let updated_at = self.knowledge.clock().now_secs();
         let pulse = serde_json::json!({
             "location": location,
             "trend": DEFAULT_TREND,
@@ -124,3 +198,113 @@ impl AgentSkill for CommunityScraper {
         }))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extracted_events_are_plain_text() {
+        let html = "<html><body>
+            <h1>Fair <b>opens</b>\u{7}<script>alert('x')</script><style>h1{}</style></h1>
+            <h2>&lt;img src=x onerror=alert(1)&gt;Road   closed</h2>
+            <h3>2 &lt; 3</h3>
+        </body></html>";
+        assert_eq!(extract_headlines_and_events(html), "Fair opens. Road closed. 2 < 3");
+
+        let long = format!("<h1>{}</h1>", "a".repeat(MAX_EVENT_CHARS + 10));
+        assert_eq!(extract_headlines_and_events(&long).chars().count(), MAX_EVENT_CHARS);
+    }
+
+    fn ctx() -> TenantContext {
+        TenantContext::new("t")
+    }
+
+    /// Serves robots.txt (disallowing `/private`), a Latin-1 page and a JSON document; returns
+    /// the base url and the paths requested so far.
+    async fn serve_site() -> (String, Arc<std::sync::Mutex<Vec<String>>>) {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        let paths = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let seen = Arc::clone(&paths);
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let mut buf = vec![0u8; 4096];
+                let n = socket.read(&mut buf).await.unwrap_or(0);
+                let request = String::from_utf8_lossy(&buf[..n]).to_string();
+                let path = request.split_whitespace().nth(1).unwrap_or("").to_string();
+                seen.lock().unwrap().push(path.clone());
+                let (content_type, body): (&str, &[u8]) = match path.as_str() {
+                    "/robots.txt" => ("text/plain", b"User-agent: *\nDisallow: /private\n"),
+                    "/news" | "/private" => ("text/html; charset=iso-8859-1", b"<html><h1>Caf\xe9 opens Friday</h1></html>"),
+                    "/moved" => ("text/html", b""),
+                    _ => ("application/json", b"{\"h1\": 1}"),
+                };
+                let status = if path == "/moved" { "301 Moved Permanently\r\nlocation: /private" } else { "200 OK" };
+                let head = format!(
+                    "HTTP/1.1 {}\r\ncontent-type: {}\r\ncontent-length: {}\r\nconnection: close\r\n\r\n",
+                    status,
+                    content_type,
+                    body.len()
+                );
+                let _ = socket.write_all(head.as_bytes()).await;
+                let _ = socket.write_all(body).await;
+            }
+        });
+        (base, paths)
+    }
+
+    #[tokio::test]
+    async fn fetches_politely_and_decodes_the_declared_charset() {
+        let (base, paths) = serve_site().await;
+        let knowledge = Arc::new(KnowledgeStore::open_temporary().unwrap());
+        let config = ScraperConfig {
+            min_interval_ms: 150,
+            allow_private_hosts: true,
+            ..ScraperConfig::default()
+        };
+        let skill = CommunityScraper::new(Arc::clone(&knowledge)).with_config(config);
+
+        let started = std::time::Instant::now();
+        let out = skill.execute(&ctx(), Some(serde_json::json!({ "url": format!("{}/news", base) }))).await.unwrap();
+        assert_eq!(out["event"], "Café opens Friday");
+        skill.execute(&ctx(), Some(serde_json::json!({ "url": format!("{}/news", base) }))).await.unwrap();
+        // robots.txt, then two pages, each at least min_interval_ms after the previous request.
+        assert!(started.elapsed() >= std::time::Duration::from_millis(300));
+
+        let err = skill.execute(&ctx(), Some(serde_json::json!({ "url": format!("{}/private", base) }))).await.unwrap_err();
+        assert!(err.to_string().contains("disallowed by robots.txt"));
+        // Each redirect hop is matched against robots.txt too.
+        let err = skill.execute(&ctx(), Some(serde_json::json!({ "url": format!("{}/moved", base) }))).await.unwrap_err();
+        assert!(err.to_string().contains("/private is disallowed by robots.txt"));
+        let err = skill.execute(&ctx(), Some(serde_json::json!({ "url": format!("{}/data.json", base) }))).await.unwrap_err();
+        assert!(err.to_string().contains("application/json, expected text/html"));
+        assert_eq!(*paths.lock().unwrap(), vec!["/robots.txt", "/news", "/news", "/moved", "/data.json"]);
+    }
+
+    #[tokio::test]
+    async fn disabled_fetching_only_accepts_inline_html() {
+        let knowledge = Arc::new(KnowledgeStore::open_temporary().unwrap());
+        let config = ScraperConfig { fetch: false, ..ScraperConfig::default() };
+        let skill = CommunityScraper::new(Arc::clone(&knowledge)).with_config(config);
+        let err = skill.execute(&ctx(), Some(serde_json::json!({ "url": "http://127.0.0.1:9/" }))).await.unwrap_err();
+        assert!(err.to_string().contains("fetching is disabled"));
+        let out = skill
+            .execute(&ctx(), Some(serde_json::json!({ "url": "http://127.0.0.1:9/", "html": "<h1>Inline</h1>" })))
+            .await
+            .unwrap();
+        assert_eq!(out["event"], "Inline");
+    }
+
+    #[tokio::test]
+    async fn oversized_inline_html_is_refused() {
+        let knowledge = Arc::new(KnowledgeStore::open_temporary().unwrap());
+        let skill = CommunityScraper::new(Arc::clone(&knowledge));
+        let ctx = ctx();
+        let html = format!("<h1>{}</h1>", "x".repeat(MAX_HTML_BYTES));
+        let err = skill.execute(&ctx, Some(serde_json::json!({ "html": html }))).await.unwrap_err();
+        assert!(err.to_string().contains("inline html is 2097161 bytes, the limit is 2097152 bytes"));
+        assert!(knowledge.get(KB_SLOT_COMMUNITY, CURRENT_PULSE_KEY).unwrap().is_none());
+    }
+}
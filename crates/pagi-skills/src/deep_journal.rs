@@ -18,7 +18,7 @@
 //!    `MentalState` (relational_stress, burnout_risk, grace_multiplier).
 
 use pagi_core::{
-    AgentSkill, EmotionalAnchor, KnowledgeStore, PersonalHistoryEntry, ShadowStoreHandle,
+    keys, AgentSkill, EmotionalAnchor, KnowledgeStore, PersonalHistoryEntry, ShadowStoreHandle,
     TenantContext,
 };
 use crate::journal_skill::{apply_anchors_to_state, extract_anchors};
@@ -93,10 +93,7 @@ impl AgentSkill for DeepJournalSkill {
         let raw_entry = args.raw_entry;
         let agent_id = ctx.resolved_agent_id();
 
-        let timestamp_ms = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .map(|d| d.as_millis() as i64)
-            .unwrap_or(0);
+        let timestamp_ms = self.store.now_ms();
 
         // ── Step 1: Determine anchor label & intensity ──────────────────────
         // Use explicit values if provided; otherwise auto-extract from text.
@@ -120,7 +117,7 @@ impl AgentSkill for DeepJournalSkill {
             .clamp(0.0, 1.0);
 
         let record_id = format!("journal/{}", timestamp_ms);
-        let anchor_key = format!("anchor/{}", label);
+        let anchor_key = keys::anchor_key(&label);
 
         // ── Step 2: Insert EmotionalAnchor into Slot 9 (Shadow_KB) ─────────
         // This is what `check_mental_load()` reads for Compassionate Routing.
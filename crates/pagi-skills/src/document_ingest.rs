@@ -0,0 +1,629 @@
+//! Document ingestion: turns an uploaded PDF, DOCX, Markdown or text file into embedded chunks
+//! in a knowledge slot, for retrieval over whole documents (`POST /api/v1/kb/:slot/documents`).
+//!
+//! The text is extracted, split into chunks of about `chunk_chars` characters that overlap by
+//! `overlap_chars` (cut at paragraph, sentence or word ends where possible), and each chunk is
+//! embedded with the [`ModelRouter`]'s embedding provider. A document is stored within its tenant
+//! as one header record `doc/{id}` (filename, format, chunk count) and its chunks
+//! `doc/{id}/{index}`, each a [`KbRecord`] with the vector and `document_id` metadata. The id is
+//! a hash of the file, so uploading the same file again replaces its chunks.
+
+use futures_util::stream::{self, StreamExt};
+use pagi_core::{KbRecord, KnowledgeStore, TenantHandle, EMBEDDING_MODEL_METADATA_KEY, SCAN_MAX_LIMIT};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::io::Read;
+use std::sync::Arc;
+
+use crate::model_router::ModelRouter;
+
+/// Key prefix of document records (`doc/{id}`, chunks `doc/{id}/{index}`).
+pub const DOCUMENT_KEY_PREFIX: &str = "doc/";
+pub const DEFAULT_CHUNK_CHARS: usize = 1_200;
+pub const DEFAULT_OVERLAP_CHARS: usize = 200;
+const MIN_CHUNK_CHARS: usize = 200;
+const MAX_CHUNK_CHARS: usize = 8_000;
+/// Chunks per document; larger documents are refused.
+pub const MAX_DOCUMENT_CHUNKS: usize = 5_000;
+/// Chunks embedded at once.
+const EMBED_CONCURRENCY: usize = 4;
+/// Largest `word/document.xml` read from a DOCX (guards against zip bombs).
+const MAX_DOCX_XML_BYTES: u64 = 64 * 1024 * 1024;
+
+/// File formats text can be extracted from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DocumentFormat {
+    Pdf,
+    Docx,
+    Markdown,
+    Text,
+}
+
+impl DocumentFormat {
+    /// Format of an upload, by content type or else by file extension.
+    pub fn detect(filename: &str, content_type: Option<&str>) -> Option<Self> {
+        let mime = content_type
+            .and_then(|c| c.split(';').next())
+            .map(|c| c.trim().to_ascii_lowercase());
+        let by_mime = match mime.as_deref() {
+            Some("application/pdf") => Some(Self::Pdf),
+            Some("application/vnd.openxmlformats-officedocument.wordprocessingml.document") => Some(Self::Docx),
+            Some("text/markdown") | Some("text/x-markdown") => Some(Self::Markdown),
+            Some("text/plain") => Some(Self::Text),
+            _ => None,
+        };
+        let extension = filename.rsplit_once('.').map(|(_, ext)| ext.to_ascii_lowercase());
+        let by_extension = match extension.as_deref() {
+            Some("pdf") => Some(Self::Pdf),
+            Some("docx") => Some(Self::Docx),
+            Some("md") | Some("markdown") => Some(Self::Markdown),
+            Some("txt") | Some("text") => Some(Self::Text),
+            _ => None,
+        };
+        // Browsers send text/plain (or octet-stream) for .md files: the extension is more specific.
+        match (by_mime, by_extension) {
+            (Some(Self::Text), Some(ext)) => Some(ext),
+            (Some(mime), _) => Some(mime),
+            (None, ext) => ext,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Pdf => "pdf",
+            Self::Docx => "docx",
+            Self::Markdown => "markdown",
+            Self::Text => "text",
+        }
+    }
+}
+
+/// Why a document was not ingested.
+#[derive(Debug)]
+pub enum DocumentError {
+    /// Not a PDF, DOCX, Markdown or text file.
+    Unsupported(String),
+    /// The file could not be read as its format.
+    Extract(String),
+    /// The file holds no text (e.g. a scanned PDF), or is too long.
+    Rejected(String),
+    /// The embedding provider failed.
+    Embedding(String),
+    Store(String),
+}
+
+impl std::fmt::Display for DocumentError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Unsupported(m) | Self::Rejected(m) | Self::Store(m) => write!(f, "{}", m),
+            Self::Extract(m) => write!(f, "cannot extract text: {}", m),
+            Self::Embedding(m) => write!(f, "embedding failed: {}", m),
+        }
+    }
+}
+
+impl std::error::Error for DocumentError {}
+
+fn store_error(e: impl std::fmt::Display) -> DocumentError {
+    DocumentError::Store(e.to_string())
+}
+
+/// Chunking and embedding settings of one upload.
+#[derive(Debug, Clone)]
+pub struct IngestOptions {
+    pub chunk_chars: usize,
+    pub overlap_chars: usize,
+    /// Overrides the provider's embedding model.
+    pub embedding_model: Option<String>,
+}
+
+impl Default for IngestOptions {
+    fn default() -> Self {
+        Self {
+            chunk_chars: DEFAULT_CHUNK_CHARS,
+            overlap_chars: DEFAULT_OVERLAP_CHARS,
+            embedding_model: None,
+        }
+    }
+}
+
+/// A stored document (its header record), as listed by the API.
+#[derive(Debug, Clone, Serialize)]
+pub struct IngestedDocument {
+    pub document_id: String,
+    pub slot_id: u8,
+    pub filename: String,
+    pub format: DocumentFormat,
+    pub bytes: usize,
+    pub chars: usize,
+    pub chunks: usize,
+    pub embedding_model: String,
+    pub vector_dims: usize,
+    pub ingested_at_ms: i64,
+    /// Whether an earlier upload of the same file was replaced.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub replaced: bool,
+}
+
+/// A piece of a document's text and its byte offset in the extracted text.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Chunk {
+    pub text: String,
+    pub offset: usize,
+}
+
+/// Extracts, chunks, embeds and stores documents.
+pub struct DocumentIngest {
+    store: Arc<KnowledgeStore>,
+    router: ModelRouter,
+}
+
+impl DocumentIngest {
+    pub fn new(store: Arc<KnowledgeStore>) -> Self {
+        Self::with_router(store, ModelRouter::new())
+    }
+
+    pub fn with_router(store: Arc<KnowledgeStore>, router: ModelRouter) -> Self {
+        Self { store, router }
+    }
+
+    /// Ingests one file into `slot_id` (1–8) for `tenant_id`. Chunks are only written once every
+    /// chunk is embedded, so a failed upload leaves the slot as it was.
+    pub async fn ingest(
+        &self,
+        tenant_id: &str,
+        slot_id: u8,
+        filename: &str,
+        content_type: Option<&str>,
+        bytes: Vec<u8>,
+        options: &IngestOptions,
+    ) -> Result<IngestedDocument, DocumentError> {
+        if !(1..=8).contains(&slot_id) {
+            return Err(DocumentError::Rejected(format!("slot {} cannot hold documents (slots 1-8 only)", slot_id)));
+        }
+        let format = DocumentFormat::detect(filename, content_type).ok_or_else(|| {
+            DocumentError::Unsupported(format!(
+                "{} ({}) is not a PDF, DOCX, Markdown or text file",
+                filename,
+                content_type.unwrap_or("no content type")
+            ))
+        })?;
+        let document_id = document_id(&bytes);
+        let size = bytes.len();
+        let text = tokio::task::spawn_blocking(move || extract_text(format, &bytes))
+            .await
+            .map_err(|e| DocumentError::Extract(e.to_string()))??;
+        let chunks = chunk_text(&text, options.chunk_chars, options.overlap_chars);
+        if chunks.is_empty() {
+            return Err(DocumentError::Rejected(format!("{} contains no text", filename)));
+        }
+        if chunks.len() > MAX_DOCUMENT_CHUNKS {
+            return Err(DocumentError::Rejected(format!(
+                "{} makes {} chunks, the limit is {}",
+                filename,
+                chunks.len(),
+                MAX_DOCUMENT_CHUNKS
+            )));
+        }
+
+        let model = options.embedding_model.as_deref();
+        let texts: Vec<String> = chunks.iter().map(|chunk| chunk.text.clone()).collect();
+        let embeddings: Vec<_> = stream::iter(texts)
+            .map(|text| self.embed(text, options.embedding_model.clone()))
+            .buffered(EMBED_CONCURRENCY)
+            .collect()
+            .await;
+        let embeddings = embeddings.into_iter().collect::<Result<Vec<_>, _>>()?;
+
+        let embedding_model = self.router.embedding_model_name(model);
+        let vector_dims = embeddings.first().map_or(0, Vec::len);
+        let replaced = self.remove(tenant_id, slot_id, &document_id)? > 0;
+        let kb = self.store.tenant(tenant_id).map_err(store_error)?;
+        let count = chunks.len();
+        for (index, (chunk, embedding)) in chunks.into_iter().zip(embeddings).enumerate() {
+            let metadata = serde_json::json!({
+                "type": "document_chunk",
+                "tags": ["document"],
+                "document_id": document_id,
+                "filename": filename,
+                "chunk_index": index,
+                "chunk_count": count,
+                "offset": chunk.offset,
+                EMBEDDING_MODEL_METADATA_KEY: embedding_model,
+                "vector_dims": embedding.len(),
+            });
+            let record = KbRecord::with_embedding(chunk.text, metadata, embedding, self.store.now_ms());
+            kb.insert_record(slot_id, &chunk_key(&document_id, index), &record)
+                .map_err(store_error)?;
+        }
+        let document = IngestedDocument {
+            document_id,
+            slot_id,
+            filename: filename.to_string(),
+            format,
+            bytes: size,
+            chars: text.chars().count(),
+            chunks: count,
+            embedding_model,
+            vector_dims,
+            ingested_at_ms: self.store.now_ms(),
+            replaced,
+        };
+        let mut metadata = serde_json::to_value(&document).unwrap_or_default();
+        metadata["type"] = serde_json::json!("document");
+        metadata["tags"] = serde_json::json!(["document"]);
+        if let Some(m) = metadata.as_object_mut() {
+            m.remove("replaced");
+        }
+        kb.insert_record(
+            slot_id,
+            &header_key(&document.document_id),
+            &KbRecord::with_metadata(document.filename.clone(), metadata, document.ingested_at_ms),
+        )
+        .map_err(store_error)?;
+        tracing::info!(
+            target: "pagi::knowledge",
+            tenant_id,
+            slot_id,
+            document_id = %document.document_id,
+            filename,
+            format = format.as_str(),
+            chunks = count,
+            replaced,
+            "Document ingested"
+        );
+        Ok(document)
+    }
+
+    async fn embed(&self, text: String, model: Option<String>) -> Result<Vec<f32>, DocumentError> {
+        self.router
+            .embedding(&text, model.as_deref())
+            .await
+            .map_err(|e| DocumentError::Embedding(e.to_string()))
+    }
+
+    /// The documents stored in `slot_id` for `tenant_id`, in id order.
+    pub fn list(&self, tenant_id: &str, slot_id: u8) -> Result<Vec<IngestedDocument>, DocumentError> {
+        let kb = self.store.tenant(tenant_id).map_err(store_error)?;
+        Ok(scan_keys(&kb, slot_id, DOCUMENT_KEY_PREFIX)?
+            .into_iter()
+            .filter(|key| !key[DOCUMENT_KEY_PREFIX.len()..].contains('/'))
+            .filter_map(|key| kb.get_record(slot_id, &key).ok().flatten())
+            .filter_map(|record| {
+                let mut metadata = record.metadata;
+                metadata["slot_id"] = serde_json::json!(slot_id);
+                header_from_metadata(metadata)
+            })
+            .collect())
+    }
+
+    /// Removes a document and its chunks. Returns the number of records removed (0 when there
+    /// was no such document).
+    pub fn remove(&self, tenant_id: &str, slot_id: u8, document_id: &str) -> Result<usize, DocumentError> {
+        let kb = self.store.tenant(tenant_id).map_err(store_error)?;
+        let mut removed = 0;
+        for key in scan_keys(&kb, slot_id, &format!("{}/", header_key(document_id)))? {
+            removed += usize::from(kb.remove(slot_id, &key).map_err(store_error)?.is_some());
+        }
+        removed += usize::from(kb.remove(slot_id, &header_key(document_id)).map_err(store_error)?.is_some());
+        Ok(removed)
+    }
+}
+
+fn header_from_metadata(metadata: serde_json::Value) -> Option<IngestedDocument> {
+    let format = match metadata["format"].as_str()? {
+        "pdf" => DocumentFormat::Pdf,
+        "docx" => DocumentFormat::Docx,
+        "markdown" => DocumentFormat::Markdown,
+        _ => DocumentFormat::Text,
+    };
+    let count = |field: &str| metadata[field].as_u64().unwrap_or(0) as usize;
+    Some(IngestedDocument {
+        document_id: metadata["document_id"].as_str()?.to_string(),
+        slot_id: metadata["slot_id"].as_u64()? as u8,
+        filename: metadata["filename"].as_str().unwrap_or_default().to_string(),
+        format,
+        bytes: count("bytes"),
+        chars: count("chars"),
+        chunks: count("chunks"),
+        embedding_model: metadata[EMBEDDING_MODEL_METADATA_KEY].as_str().unwrap_or_default().to_string(),
+        vector_dims: count("vector_dims"),
+        ingested_at_ms: metadata["ingested_at_ms"].as_i64().unwrap_or(0),
+        replaced: false,
+    })
+}
+
+fn scan_keys(kb: &TenantHandle<'_>, slot_id: u8, prefix: &str) -> Result<Vec<String>, DocumentError> {
+    let mut keys = Vec::new();
+    let mut cursor: Option<String> = None;
+    loop {
+        let page = kb.scan_prefix(slot_id, prefix, cursor.as_deref(), SCAN_MAX_LIMIT).map_err(store_error)?;
+        keys.extend(page.entries.into_iter().map(|(key, _)| key));
+        match page.next_cursor {
+            Some(next) => cursor = Some(next),
+            None => return Ok(keys),
+        }
+    }
+}
+
+fn document_id(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)[..12].iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn header_key(document_id: &str) -> String {
+    format!("{}{}", DOCUMENT_KEY_PREFIX, document_id)
+}
+
+fn chunk_key(document_id: &str, index: usize) -> String {
+    format!("{}{}/{:05}", DOCUMENT_KEY_PREFIX, document_id, index)
+}
+
+/// Plain text of a file in `format`, paragraphs separated by blank lines.
+pub fn extract_text(format: DocumentFormat, bytes: &[u8]) -> Result<String, DocumentError> {
+    match format {
+        DocumentFormat::Pdf => extract_pdf(bytes),
+        DocumentFormat::Docx => extract_docx(bytes),
+        DocumentFormat::Markdown => Ok(extract_markdown(&String::from_utf8_lossy(bytes))),
+        DocumentFormat::Text => Ok(String::from_utf8_lossy(bytes).into_owned()),
+    }
+}
+
+fn extract_pdf(bytes: &[u8]) -> Result<String, DocumentError> {
+    // pdf-extract panics on some malformed files rather than returning an error.
+    match std::panic::catch_unwind(|| pdf_extract::extract_text_from_mem(bytes)) {
+        Ok(Ok(text)) => Ok(text),
+        Ok(Err(e)) => Err(DocumentError::Extract(e.to_string())),
+        Err(_) => Err(DocumentError::Extract("malformed PDF".to_string())),
+    }
+}
+
+fn extract_docx(bytes: &[u8]) -> Result<String, DocumentError> {
+    let mut archive =
+        zip::ZipArchive::new(std::io::Cursor::new(bytes)).map_err(|e| DocumentError::Extract(e.to_string()))?;
+    let mut xml = String::new();
+    archive
+        .by_name("word/document.xml")
+        .map_err(|e| DocumentError::Extract(format!("not a Word document: {}", e)))?
+        .take(MAX_DOCX_XML_BYTES)
+        .read_to_string(&mut xml)
+        .map_err(|e| DocumentError::Extract(e.to_string()))?;
+    let doc = roxmltree::Document::parse(&xml).map_err(|e| DocumentError::Extract(e.to_string()))?;
+    let mut paragraphs = Vec::new();
+    for paragraph in doc.descendants().filter(|n| n.tag_name().name() == "p") {
+        let mut text = String::new();
+        for node in paragraph.descendants() {
+            match node.tag_name().name() {
+                "t" => text.push_str(node.text().unwrap_or_default()),
+                "tab" => text.push('\t'),
+                "br" | "cr" => text.push('\n'),
+                _ => {}
+            }
+        }
+        if !text.trim().is_empty() {
+            paragraphs.push(text);
+        }
+    }
+    Ok(paragraphs.join("\n\n"))
+}
+
+fn extract_markdown(markdown: &str) -> String {
+    use pulldown_cmark::{Event, Parser, TagEnd};
+    let mut text = String::new();
+    for event in Parser::new(markdown) {
+        match event {
+            Event::Text(t) | Event::Code(t) => text.push_str(&t),
+            Event::SoftBreak => text.push(' '),
+            Event::HardBreak => text.push('\n'),
+            Event::End(TagEnd::Paragraph | TagEnd::Heading(_) | TagEnd::Item | TagEnd::CodeBlock | TagEnd::TableRow) => {
+                text.push_str("\n\n")
+            }
+            Event::End(TagEnd::TableCell) => text.push('\t'),
+            _ => {}
+        }
+    }
+    text
+}
+
+/// Splits `text` into chunks of at most `chunk_chars` characters, each starting `overlap_chars`
+/// before the previous one ended. Chunks end at a paragraph break, else a sentence end, else a
+/// space, when one falls in their second half.
+pub fn chunk_text(text: &str, chunk_chars: usize, overlap_chars: usize) -> Vec<Chunk> {
+    let size = chunk_chars.clamp(MIN_CHUNK_CHARS, MAX_CHUNK_CHARS);
+    let overlap = overlap_chars.min(size / 2);
+    let mut chunks = Vec::new();
+    let mut start = text.len() - text.trim_start().len();
+    while start < text.len() {
+        let rest = &text[start..];
+        let end = match rest.char_indices().nth(size) {
+            None => text.len(),
+            Some((limit, _)) => start + break_point(&rest[..limit]),
+        };
+        let piece = text[start..end].trim();
+        if !piece.is_empty() {
+            let offset = start + (text[start..end].len() - text[start..end].trim_start().len());
+            chunks.push(Chunk {
+                text: piece.to_string(),
+                offset,
+            });
+        }
+        if end >= text.len() {
+            break;
+        }
+        // Back up `overlap` characters, to the start of a word, but always move forward.
+        let mut next = end;
+        if overlap > 0 {
+            if let Some((back, _)) = text[..end].char_indices().rev().nth(overlap - 1) {
+                next = text[back..end]
+                    .find(char::is_whitespace)
+                    .map_or(back, |ws| back + ws);
+            }
+        }
+        if next <= start {
+            next = end;
+        }
+        start = next + (text[next..].len() - text[next..].trim_start().len());
+    }
+    chunks
+}
+
+/// Where to end a chunk taken from `window`: the last paragraph break, sentence end or space in
+/// its second half, else its end.
+fn break_point(window: &str) -> usize {
+    let half = window.len() / 2;
+    let after = |found: Option<usize>, len: usize| found.map(|i| i + len).filter(|&i| i > half);
+    let sentence_end = window
+        .char_indices()
+        .rev()
+        .find(|&(i, c)| matches!(c, '.' | '?' | '!') && window[i + 1..].starts_with(char::is_whitespace))
+        .map(|(i, _)| i);
+    after(window.rfind("\n\n"), 2)
+        .or_else(|| after(sentence_end, 1))
+        .or_else(|| after(window.rfind(char::is_whitespace), 1))
+        .unwrap_or(window.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model_router::LlmMode;
+    use std::io::Write;
+
+    fn docx(paragraphs: &[&str]) -> Vec<u8> {
+        let body: String = paragraphs
+            .iter()
+            .map(|p| format!("<w:p><w:r><w:t xml:space=\"preserve\">{}</w:t></w:r></w:p>", p))
+            .collect();
+        let xml = format!(
+            "<w:document xmlns:w=\"http://schemas.openxmlformats.org/wordprocessingml/2006/main\"><w:body>{}</w:body></w:document>",
+            body
+        );
+        let mut zip = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+        zip.start_file("word/document.xml", zip::write::SimpleFileOptions::default()).unwrap();
+        zip.write_all(xml.as_bytes()).unwrap();
+        zip.finish().unwrap().into_inner()
+    }
+
+    fn pdf(line: &str) -> Vec<u8> {
+        use lopdf::content::{Content, Operation};
+        use lopdf::{dictionary, Document, Object, Stream};
+        let mut doc = Document::with_version("1.5");
+        let pages_id = doc.new_object_id();
+        let font_id = doc.add_object(dictionary! {
+            "Type" => "Font", "Subtype" => "Type1", "BaseFont" => "Courier",
+        });
+        let resources_id = doc.add_object(dictionary! { "Font" => dictionary! { "F1" => font_id } });
+        let content = Content {
+            operations: vec![
+                Operation::new("BT", vec![]),
+                Operation::new("Tf", vec!["F1".into(), 12.into()]),
+                Operation::new("Td", vec![72.into(), 700.into()]),
+                Operation::new("Tj", vec![Object::string_literal(line)]),
+                Operation::new("ET", vec![]),
+            ],
+        };
+        let content_id = doc.add_object(Stream::new(dictionary! {}, content.encode().unwrap()));
+        let page_id = doc.add_object(dictionary! {
+            "Type" => "Page", "Parent" => pages_id, "Contents" => content_id,
+        });
+        doc.objects.insert(
+            pages_id,
+            Object::Dictionary(dictionary! {
+                "Type" => "Pages", "Kids" => vec![page_id.into()], "Count" => 1,
+                "Resources" => resources_id, "MediaBox" => vec![0.into(), 0.into(), 595.into(), 842.into()],
+            }),
+        );
+        let catalog_id = doc.add_object(dictionary! { "Type" => "Catalog", "Pages" => pages_id });
+        doc.trailer.set("Root", catalog_id);
+        let mut bytes = Vec::new();
+        doc.save_to(&mut bytes).unwrap();
+        bytes
+    }
+
+    #[test]
+    fn extracts_text_from_each_format() {
+        assert_eq!(DocumentFormat::detect("notes.md", Some("text/plain")), Some(DocumentFormat::Markdown));
+        assert_eq!(DocumentFormat::detect("upload", Some("application/pdf")), Some(DocumentFormat::Pdf));
+        assert_eq!(DocumentFormat::detect("photo.png", Some("image/png")), None);
+
+        let markdown = "# Refund policy\n\nRefunds within **30 days**.\n\n- keep the `receipt`\n";
+        assert_eq!(
+            extract_text(DocumentFormat::Markdown, markdown.as_bytes()).unwrap().trim(),
+            "Refund policy\n\nRefunds within 30 days.\n\nkeep the receipt"
+        );
+        let text = extract_text(DocumentFormat::Docx, &docx(&["Chapter one", "It was a dark night."])).unwrap();
+        assert_eq!(text, "Chapter one\n\nIt was a dark night.");
+        let text = extract_text(DocumentFormat::Pdf, &pdf("Quarterly revenue grew")).unwrap();
+        assert!(text.contains("Quarterly revenue grew"), "{:?}", text);
+
+        assert!(matches!(extract_text(DocumentFormat::Docx, b"not a zip"), Err(DocumentError::Extract(_))));
+        assert!(matches!(extract_text(DocumentFormat::Pdf, b"%PDF-1.4 garbage"), Err(DocumentError::Extract(_))));
+    }
+
+    #[test]
+    fn chunks_overlap_and_end_at_boundaries() {
+        let sentence = "The council met on Tuesday to vote on the budget. ";
+        let text = sentence.repeat(40);
+        let chunks = chunk_text(&text, 300, 60);
+        assert!(chunks.len() > 6);
+        for chunk in &chunks {
+            assert!(chunk.text.chars().count() <= 300);
+            assert!(chunk.text.ends_with("budget."), "{:?}", chunk.text);
+            assert_eq!(&text[chunk.offset..chunk.offset + chunk.text.len()], chunk.text);
+        }
+        // Each chunk starts inside the previous one.
+        for pair in chunks.windows(2) {
+            let prev_end = pair[0].offset + pair[0].text.len();
+            assert!(pair[1].offset < prev_end && pair[1].offset > pair[0].offset);
+        }
+        assert_eq!(chunks.last().unwrap().offset + chunks.last().unwrap().text.len(), text.trim_end().len());
+
+        assert_eq!(chunk_text("  short note ", 1_200, 200), vec![Chunk { text: "short note".into(), offset: 2 }]);
+        assert!(chunk_text(" \n ", 1_200, 200).is_empty());
+        // No spaces at all: hard cuts.
+        assert_eq!(chunk_text(&"x".repeat(500), 200, 0).len(), 3);
+    }
+
+    #[tokio::test]
+    async fn ingests_replaces_and_removes_documents_per_tenant() {
+        let store = Arc::new(KnowledgeStore::open_temporary().unwrap());
+        let ingest = DocumentIngest::with_router(Arc::clone(&store), ModelRouter::with_mode(LlmMode::Mock));
+        let handbook = "Employees get 25 vacation days.\n\n".repeat(60).into_bytes();
+        let options = IngestOptions {
+            chunk_chars: 400,
+            ..Default::default()
+        };
+
+        let doc = ingest
+            .ingest("acme", 3, "handbook.txt", Some("text/plain"), handbook.clone(), &options)
+            .await
+            .unwrap();
+        assert_eq!((doc.format, doc.embedding_model.as_str(), doc.vector_dims), (DocumentFormat::Text, "mock", 64));
+        assert!(doc.chunks > 4 && !doc.replaced);
+        let kb = store.tenant("acme").unwrap();
+        let chunk = kb.get_record(3, &chunk_key(&doc.document_id, 1)).unwrap().unwrap();
+        assert_eq!(chunk.metadata["document_id"], doc.document_id.as_str());
+        assert_eq!(chunk.metadata["chunk_index"], 1);
+        assert_eq!(chunk.embedding.as_ref().map(Vec::len), Some(64));
+        assert!(store.get_record(3, &chunk_key(&doc.document_id, 0)).unwrap().is_none(), "tenant scoped");
+
+        let listed = ingest.list("acme", 3).unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!((listed[0].filename.as_str(), listed[0].chunks), ("handbook.txt", doc.chunks));
+        assert!(ingest.list("other", 3).unwrap().is_empty());
+
+        let again = ingest.ingest("acme", 3, "handbook.txt", None, handbook, &options).await.unwrap();
+        assert!(again.replaced);
+        assert_eq!(kb.count(3).unwrap(), doc.chunks + 1);
+
+        assert_eq!(ingest.remove("acme", 3, &doc.document_id).unwrap(), doc.chunks + 1);
+        assert_eq!(kb.count(3).unwrap(), 0);
+        assert_eq!(ingest.remove("acme", 3, &doc.document_id).unwrap(), 0);
+
+        let err = ingest.ingest("acme", 3, "photo.png", Some("image/png"), vec![1, 2], &options).await.unwrap_err();
+        assert!(matches!(err, DocumentError::Unsupported(_)));
+        let err = ingest.ingest("acme", 3, "blank.txt", None, b"  \n".to_vec(), &options).await.unwrap_err();
+        assert_eq!(err.to_string(), "blank.txt contains no text");
+        assert!(ingest.ingest("acme", 9, "a.txt", None, b"x".to_vec(), &options).await.is_err());
+    }
+}
@@ -1,4 +1,5 @@
 //! Draft Response skill: composite task that combines KB-1 (Brand Voice), KB-5 (Community Pulse), and lead data into a mock draft.
+//! When the lead is an entity of the tenant's KB-3 knowledge graph, its connected facts are added too.
 
 use pagi_core::{AgentSkill, KnowledgeStore, MemoryManager, TenantContext};
 use std::sync::Arc;
@@ -77,15 +78,23 @@ impl AgentSkill for DraftResponse {
             .and_then(|v| String::from_utf8(v).ok())
             .unwrap_or_else(|| "{}".to_string());
 
+        // Connected facts ("lead → interested_in → service") when the lead is in the graph.
+        let connections = match self.knowledge.tenant(&ctx.tenant_id)?.graph_neighbors(&lead_id, 1) {
+            Ok(Some(neighborhood)) => neighborhood.facts(),
+            _ => Vec::new(),
+        };
+        let connections_text = if connections.is_empty() { "(none)".to_string() } else { connections.join("; ") };
+
         let draft = format!(
-            "[Mock Draft – precursor to LLM]\n\nBrand Voice: {}\n\nLocal Context: {}\n\nLead data: {}\n\n---\nDraft: Thank you for reaching out. We will respond shortly.",
-            brand_voice, local_context, lead_data
+            "[Mock Draft – precursor to LLM]\n\nBrand Voice: {}\n\nLocal Context: {}\n\nLead data: {}\n\nKnown connections: {}\n\n---\nDraft: Thank you for reaching out. We will respond shortly.",
+            brand_voice, local_context, lead_data, connections_text
         );
 
         Ok(serde_json::json!({
             "status": "ok",
             "skill": SKILL_NAME,
             "lead_id": lead_id,
+            "connections": connections,
             "draft": draft
         }))
     }
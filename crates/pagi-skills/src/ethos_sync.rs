@@ -20,7 +20,7 @@
 //!
 //! Custom schools are supported by providing `core_maxims` directly.
 
-use pagi_core::{AgentSkill, EthosPolicy, EventRecord, KnowledgeStore, TenantContext};
+use pagi_core::{AgentSkill, EthosPolicy, KnowledgeStore, TenantContext};
 use serde::Deserialize;
 use std::sync::Arc;
 
@@ -99,7 +99,7 @@ impl AgentSkill for EthosSync {
         let agent_id = ctx.resolved_agent_id();
 
         // Chronos: log the school switch.
-        let event = EventRecord::now(
+        let event = self.store.event(
             "Chronos",
             format!(
                 "Ethos philosophical lens switched to '{}' (tone_weight={:.1}, maxims={}).",
@@ -149,6 +149,7 @@ mod tests {
             tenant_id: "test".to_string(),
             correlation_id: None,
             agent_id: Some("default".to_string()),
+            ..Default::default()
         };
         let payload = serde_json::json!({
             "active_school": "Stoic",
@@ -184,6 +185,7 @@ mod tests {
             tenant_id: "test".to_string(),
             correlation_id: None,
             agent_id: Some("default".to_string()),
+            ..Default::default()
         };
         let payload = serde_json::json!({
             "active_school": "Growth-Mindset",
@@ -207,6 +209,7 @@ mod tests {
             tenant_id: "test".to_string(),
             correlation_id: None,
             agent_id: Some("default".to_string()),
+            ..Default::default()
         };
         let payload = serde_json::json!({
             "active_school": "Absurdist",
@@ -235,6 +238,7 @@ mod tests {
             tenant_id: "test".to_string(),
             correlation_id: None,
             agent_id: Some("default".to_string()),
+            ..Default::default()
         };
         let payload = serde_json::json!({
             "active_school": "",
@@ -0,0 +1,477 @@
+//! Extract Entities skill: finds entities, relations and people in ingested text and writes them
+//! into the caller's KB-3 knowledge graph and the Kardia relational map.
+//!
+//! Input, in lookup order: `text` (e.g. a chat turn), a stored lead (`lead_id`, as returned by
+//! LeadCapture or ParseInboundMessage), or a scraped `event` (CommunityScraper). Live mode asks
+//! the ModelRouter in JSON mode; mock mode, LLM errors and malformed replies fall back to rules
+//! (proper-noun runs, "my boss Sarah"-style people, lead and event fields).
+//!
+//! The text's source becomes an entity too (the lead under its `lead_id`, so DraftResponse finds
+//! its facts; the event under `event-{slug}`) and `mentions` everything found. The input object
+//! is passed through with the extraction added, so the skill can sit between blueprint steps
+//! (`CommunityScraper` → `ExtractEntities` → `ModelRouter` still sees `event`).
+
+use pagi_core::{AgentSkill, KnowledgeStore, MemoryManager, PersonRecord, TenantContext};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+use crate::model_router::ModelRouter;
+
+const SKILL_NAME: &str = "ExtractEntities";
+const LEAD_HISTORY_PREFIX: &str = "lead_history";
+/// Fields that may hold the text, in lookup order (payload first, then the stored lead).
+const TEXT_FIELDS: [&str; 4] = ["text", "message", "body", "content"];
+/// Characters of text sent to the LLM.
+const LLM_TEXT_CHARS: usize = 4000;
+/// Entities, relations and people kept from one extraction, each.
+const MAX_EXTRACTED: usize = 50;
+/// Longest entity name kept (characters).
+const MAX_NAME_CHARS: usize = 96;
+/// "my {relationship} {Name}".
+const RELATIONSHIP_CUES: [&str; 18] = [
+    "boss", "manager", "partner", "wife", "husband", "mother", "mom", "father", "dad", "sister", "brother", "friend",
+    "colleague", "coworker", "neighbor", "son", "daughter", "client",
+];
+/// Capitalized words that do not name anything on their own.
+const NOT_NAMES: [&str; 24] = [
+    "I", "I'm", "I'd", "I'll", "Hi", "Hello", "Hey", "Dear", "Thanks", "Thank", "Please", "Regards", "Monday",
+    "Tuesday", "Wednesday", "Thursday", "Friday", "Saturday", "Sunday", "The", "A", "An", "We", "Our",
+];
+const ORGANIZATION_SUFFIXES: [&str; 8] = ["Inc", "LLC", "Ltd", "Corp", "Co", "Company", "GmbH", "Group"];
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExtractedEntity {
+    pub name: String,
+    #[serde(default)]
+    pub kind: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExtractedRelation {
+    /// Entity names.
+    pub subject: String,
+    pub predicate: String,
+    pub object: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExtractedPerson {
+    pub name: String,
+    #[serde(default)]
+    pub relationship: Option<String>,
+}
+
+/// What one text mentions.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Extraction {
+    #[serde(default)]
+    pub entities: Vec<ExtractedEntity>,
+    #[serde(default)]
+    pub relations: Vec<ExtractedRelation>,
+    #[serde(default)]
+    pub people: Vec<ExtractedPerson>,
+}
+
+impl Extraction {
+    /// Rule-based extraction: "my boss Sarah"-style people and runs of capitalized words
+    /// (a single word only when it does not start a sentence).
+    pub fn extract_fallback(text: &str) -> Self {
+        let words: Vec<&str> = text.split_whitespace().collect();
+        let mut extraction = Self::default();
+        let mut i = 0;
+        while i < words.len() {
+            let bare = trim_word(words[i]);
+            let next = words.get(i + 1).map(|w| trim_word(w));
+            if bare.eq_ignore_ascii_case("my") && next.is_some_and(|n| RELATIONSHIP_CUES.contains(&n.to_lowercase().as_str())) {
+                let relationship = next.unwrap_or_default().to_lowercase();
+                let (name, len) = name_run(&words[i + 2..]);
+                if let Some(name) = name {
+                    extraction.people.push(ExtractedPerson { name, relationship: Some(relationship) });
+                    i += 2 + len;
+                    continue;
+                }
+            }
+            let sentence_start = i == 0 || words[i - 1].ends_with(['.', '!', '?', ':']);
+            let (name, len) = name_run(&words[i..]);
+            match name {
+                Some(name) if len > 1 || !sentence_start => {
+                    let kind = ORGANIZATION_SUFFIXES
+                        .iter()
+                        .any(|s| name.ends_with(&format!(" {}", s)))
+                        .then(|| "organization".to_string());
+                    extraction.entities.push(ExtractedEntity { name, kind });
+                    i += len;
+                }
+                _ => i += len.max(1),
+            }
+        }
+        extraction.normalized()
+    }
+
+    /// Drops empty or overlong names and duplicates, makes predicates `snake_case` and caps the
+    /// counts at [`MAX_EXTRACTED`].
+    fn normalized(self) -> Self {
+        let mut out = Self::default();
+        for person in self.people {
+            let Some(name) = clean_name(&person.name) else { continue };
+            if out.people.iter().any(|p| p.name.eq_ignore_ascii_case(&name)) {
+                continue;
+            }
+            let relationship = person.relationship.map(|r| r.trim().to_lowercase()).filter(|r| !r.is_empty());
+            out.people.push(ExtractedPerson { name, relationship });
+        }
+        for entity in self.entities {
+            let Some(name) = clean_name(&entity.name) else { continue };
+            let is_person = out.people.iter().any(|p| p.name.eq_ignore_ascii_case(&name));
+            if is_person || out.entities.iter().any(|e| e.name.eq_ignore_ascii_case(&name)) {
+                continue;
+            }
+            let kind = entity.kind.map(|k| k.trim().to_lowercase()).filter(|k| !k.is_empty());
+            out.entities.push(ExtractedEntity { name, kind });
+        }
+        for relation in self.relations {
+            let (Some(subject), Some(object)) = (clean_name(&relation.subject), clean_name(&relation.object)) else {
+                continue;
+            };
+            let predicate = predicate(&relation.predicate);
+            if predicate.is_empty() || out.relations.iter().any(|r| r.subject == subject && r.predicate == predicate && r.object == object) {
+                continue;
+            }
+            out.relations.push(ExtractedRelation { subject, predicate, object });
+        }
+        out.people.truncate(MAX_EXTRACTED);
+        out.entities.truncate(MAX_EXTRACTED);
+        out.relations.truncate(MAX_EXTRACTED);
+        out
+    }
+}
+
+fn trim_word(word: &str) -> &str {
+    word.trim_matches(|c: char| !c.is_alphanumeric() && c != '\'' && c != '&')
+}
+
+fn is_name_word(word: &str) -> bool {
+    word.chars().next().is_some_and(|c| c.is_uppercase()) && !NOT_NAMES.contains(&word)
+}
+
+/// The run of capitalized words (at most 4) at the start of `words`, and the words it spans.
+/// A run ends at punctuation.
+fn name_run(words: &[&str]) -> (Option<String>, usize) {
+    let mut parts = Vec::new();
+    for word in words.iter().take(4) {
+        let bare = trim_word(word);
+        if !is_name_word(bare) {
+            break;
+        }
+        parts.push(bare);
+        if word.ends_with([',', '.', '!', '?', ';', ':', ')']) {
+            break;
+        }
+    }
+    let len = parts.len();
+    ((!parts.is_empty()).then(|| parts.join(" ")), len)
+}
+
+fn clean_name(name: &str) -> Option<String> {
+    let name = name.split_whitespace().collect::<Vec<_>>().join(" ");
+    (!name.is_empty() && name.chars().count() <= MAX_NAME_CHARS && !name.contains('/')).then_some(name)
+}
+
+/// `interested in` → `interested_in`; ASCII letters, digits and `_` only.
+fn predicate(raw: &str) -> String {
+    let mut out = String::new();
+    for c in raw.trim().to_lowercase().chars() {
+        if c.is_ascii_alphanumeric() {
+            out.push(c);
+        } else if !out.ends_with('_') && !out.is_empty() {
+            out.push('_');
+        }
+    }
+    out.trim_end_matches('_').chars().take(64).collect()
+}
+
+/// Entity id for a name: lowercase letters and digits joined by `-`.
+pub fn entity_id(name: &str) -> String {
+    let mut id = String::new();
+    for c in name.chars().flat_map(char::to_lowercase) {
+        if c.is_alphanumeric() {
+            id.push(c);
+        } else if !id.is_empty() && !id.ends_with('-') {
+            id.push('-');
+        }
+    }
+    id.trim_end_matches('-').chars().take(MAX_NAME_CHARS).collect()
+}
+
+fn build_extraction_prompt(text: &str) -> String {
+    format!(
+        r#"List the named entities in this text, how they relate, and the people in the author's life it mentions. Reply with a single JSON object only:
+{{"entities": [{{"name": string, "kind": "person" | "organization" | "place" | "product" | "service" | "event" | other}}], "relations": [{{"subject": entity name, "predicate": snake_case verb such as "works_at" or "interested_in", "object": entity name}}], "people": [{{"name": string, "relationship": their relationship to the author (e.g. "boss") or null}}]}}
+
+Text:
+"""
+{}
+"""#,
+        text
+    )
+}
+
+/// Where the text came from; becomes an entity that `mentions` everything extracted.
+struct Source {
+    id: String,
+    kind: &'static str,
+    name: String,
+    /// Relations the source's own fields imply (`located_in`, `interested_in`, `works_at`).
+    relations: Vec<(String, String, Option<&'static str>)>,
+}
+
+fn text_field(value: &serde_json::Value) -> Option<String> {
+    TEXT_FIELDS
+        .iter()
+        .find_map(|field| value.get(*field).and_then(|v| v.as_str()))
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+}
+
+fn str_field<'a>(value: &'a serde_json::Value, path: &[&str]) -> Option<&'a str> {
+    path.iter()
+        .try_fold(value, |v, field| v.get(*field))
+        .and_then(|v| v.as_str())
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+}
+
+/// Extracts entities, relations and people from text and stores them in the KB-3 graph and Kardia.
+pub struct ExtractEntities {
+    knowledge: Arc<KnowledgeStore>,
+    memory: Arc<MemoryManager>,
+    router: Arc<ModelRouter>,
+}
+
+impl ExtractEntities {
+    pub fn new(knowledge: Arc<KnowledgeStore>, memory: Arc<MemoryManager>, router: Arc<ModelRouter>) -> Self {
+        Self { knowledge, memory, router }
+    }
+
+    /// LLM JSON extraction with rule-based fallback. Returns the extraction and the extractor used.
+    async fn extract(&self, text: &str) -> (Extraction, &'static str) {
+        let prompt = build_extraction_prompt(&text.chars().take(LLM_TEXT_CHARS).collect::<String>());
+        match self.router.generate_json(&prompt).await {
+            Ok(value) => match serde_json::from_value::<Extraction>(value) {
+                Ok(extraction) => (extraction.normalized(), "llm"),
+                Err(e) => {
+                    tracing::debug!(target: "pagi::skills", error = %e, "ExtractEntities: LLM JSON did not match schema");
+                    (Extraction::extract_fallback(text), "rules")
+                }
+            },
+            Err(e) => {
+                tracing::debug!(target: "pagi::skills", error = %e, "ExtractEntities: using rule-based extraction");
+                (Extraction::extract_fallback(text), "rules")
+            }
+        }
+    }
+
+    /// The text to read and its source, from the payload or the stored lead.
+    fn input(&self, ctx: &TenantContext, payload: &serde_json::Value) -> Result<(String, Option<Source>), Box<dyn std::error::Error + Send + Sync>> {
+        if let Some(lead_id) = str_field(payload, &["lead_id"]) {
+            let path = format!("{}/{}/{}", LEAD_HISTORY_PREFIX, ctx.tenant_id, lead_id);
+            let lead: serde_json::Value = self
+                .memory
+                .get_path(ctx, &path)?
+                .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+                .unwrap_or_default();
+            if let Some(text) = text_field(payload).or_else(|| text_field(&lead)) {
+                let mut relations = Vec::new();
+                if let Some(company) = str_field(&lead, &["company"]) {
+                    relations.push(("works_at".to_string(), company.to_string(), Some("organization")));
+                }
+                if let Some(service) = str_field(&lead, &["parsed", "requested_service"]) {
+                    relations.push(("interested_in".to_string(), service.to_string(), Some("service")));
+                }
+                let name = str_field(&lead, &["name"])
+                    .or_else(|| str_field(&lead, &["email"]))
+                    .map_or_else(|| format!("Lead {}", lead_id), str::to_string);
+                return Ok((text, Some(Source { id: lead_id.to_string(), kind: "lead", name, relations })));
+            }
+        }
+        if let Some(text) = text_field(payload) {
+            return Ok((text, None));
+        }
+        if let Some(event) = str_field(payload, &["event"]) {
+            let relations = str_field(payload, &["location"])
+                .map(|location| vec![("located_in".to_string(), location.to_string(), Some("place"))])
+                .unwrap_or_default();
+            let source = Source { id: format!("event-{}", entity_id(event)), kind: "event", name: event.to_string(), relations };
+            return Ok((event.to_string(), Some(source)));
+        }
+        Err("ExtractEntities requires payload: { text } or { lead_id } of a lead with message text or { event }".into())
+    }
+}
+
+#[async_trait::async_trait]
+impl AgentSkill for ExtractEntities {
+    fn name(&self) -> &str {
+        SKILL_NAME
+    }
+
+    async fn execute(
+        &self,
+        ctx: &TenantContext,
+        payload: Option<serde_json::Value>,
+    ) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>> {
+        let payload = payload.unwrap_or(serde_json::Value::Null);
+        let (text, source) = self.input(ctx, &payload)?;
+        let (extraction, extractor) = self.extract(&text).await;
+
+        let kb = self.knowledge.tenant(&ctx.tenant_id)?;
+        let none = serde_json::Map::new();
+        let mut entities: Vec<serde_json::Value> = Vec::new();
+        let mut upsert = |name: &str, kind: Option<&str>| -> Result<Option<String>, Box<dyn std::error::Error + Send + Sync>> {
+            let id = entity_id(name);
+            if id.is_empty() {
+                return Ok(None);
+            }
+            let entity = kb.upsert_entity(&id, kind, Some(name), &none)?;
+            if !entities.iter().any(|e| e["id"] == entity.id.as_str()) {
+                entities.push(serde_json::json!({ "id": entity.id, "name": entity.name, "kind": entity.kind }));
+            }
+            Ok(Some(id))
+        };
+
+        let mut mentioned = Vec::new();
+        for entity in &extraction.entities {
+            mentioned.extend(upsert(&entity.name, entity.kind.as_deref())?);
+        }
+        for person in &extraction.people {
+            mentioned.extend(upsert(&person.name, Some("person"))?);
+        }
+        let mut relations = Vec::new();
+        for relation in &extraction.relations {
+            if let (Some(subject), Some(object)) = (upsert(&relation.subject, None)?, upsert(&relation.object, None)?) {
+                relations.push((subject, relation.predicate.clone(), object));
+            }
+        }
+        let source_id = match &source {
+            Some(source) => {
+                kb.upsert_entity(&source.id, Some(source.kind), Some(&source.name), &none)?;
+                for (predicate, object, kind) in &source.relations {
+                    if let Some(object) = upsert(object, *kind)? {
+                        relations.push((source.id.clone(), predicate.clone(), object));
+                    }
+                }
+                for id in &mentioned {
+                    relations.push((source.id.clone(), "mentions".to_string(), id.clone()));
+                }
+                Some(source.id.clone())
+            }
+            None => None,
+        };
+        let mut written = Vec::new();
+        for (subject, predicate, object) in relations {
+            if subject != object && !written.contains(&(subject.clone(), predicate.clone(), object.clone())) {
+                kb.upsert_relation(&subject, &predicate, &object, &none)?;
+                written.push((subject, predicate, object));
+            }
+        }
+
+        // People in the user's life also go into Kardia's relational map.
+        let mut people = Vec::new();
+        for person in &extraction.people {
+            let slug = PersonRecord::name_slug(&person.name);
+            let mut record = self.knowledge.get_person(&slug).unwrap_or_else(|| PersonRecord {
+                name: person.name.clone(),
+                ..PersonRecord::default()
+            });
+            if let Some(relationship) = person.relationship.as_deref().filter(|_| record.relationship.is_empty()) {
+                record.relationship = relationship.to_string();
+            }
+            self.knowledge.set_person(&record)?;
+            people.push(serde_json::json!({ "name": record.name, "name_slug": slug, "relationship": record.relationship }));
+        }
+
+        let mut out = match payload {
+            serde_json::Value::Object(map) => map,
+            _ => serde_json::Map::new(),
+        };
+        let relations: Vec<serde_json::Value> = written
+            .into_iter()
+            .map(|(subject, predicate, object)| serde_json::json!({ "subject": subject, "predicate": predicate, "object": object }))
+            .collect();
+        for (field, value) in [
+            ("status", serde_json::json!("ok")),
+            ("skill", serde_json::json!(SKILL_NAME)),
+            ("extractor", serde_json::json!(extractor)),
+            ("source", serde_json::json!(source_id)),
+            ("entities", serde_json::json!(entities)),
+            ("relations", serde_json::json!(relations)),
+            ("people", serde_json::json!(people)),
+        ] {
+            out.insert(field.to_string(), value);
+        }
+        Ok(serde_json::Value::Object(out))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model_router::LlmMode;
+
+    fn ctx() -> TenantContext {
+        TenantContext::new("acme")
+    }
+
+    #[test]
+    fn rules_find_people_and_proper_nouns() {
+        let extraction = Extraction::extract_fallback(
+            "Hi, I spoke with my boss Sarah Lee about the Harbor Festival. Northwind Traders Inc will cater. Thanks!",
+        );
+        assert_eq!(
+            extraction.people,
+            vec![ExtractedPerson { name: "Sarah Lee".to_string(), relationship: Some("boss".to_string()) }]
+        );
+        let names: Vec<(&str, Option<&str>)> =
+            extraction.entities.iter().map(|e| (e.name.as_str(), e.kind.as_deref())).collect();
+        assert_eq!(names, vec![("Harbor Festival", None), ("Northwind Traders Inc", Some("organization"))]);
+        assert_eq!(predicate(" Interested in! "), "interested_in");
+        assert_eq!(entity_id("Northwind Traders Inc."), "northwind-traders-inc");
+    }
+
+    #[tokio::test]
+    async fn lead_text_feeds_the_graph_and_kardia() {
+        let knowledge = Arc::new(KnowledgeStore::open_temporary().unwrap());
+        let memory = Arc::new(MemoryManager::open_temporary().unwrap());
+        let lead = serde_json::json!({
+            "name": "Dana Brooks",
+            "company": "Blue Fjord Ltd",
+            "message": "My partner Alex and I need a quote for solar panels before the Riverside Fair.",
+            "parsed": { "requested_service": "solar panels" },
+        });
+        memory.save_path(&ctx(), "lead_history/acme/lead-7", &serde_json::to_vec(&lead).unwrap()).unwrap();
+        let skill = ExtractEntities::new(Arc::clone(&knowledge), memory, Arc::new(ModelRouter::with_mode(LlmMode::Mock)));
+
+        let out = skill.execute(&ctx(), Some(serde_json::json!({ "status": "saved", "lead_id": "lead-7" }))).await.unwrap();
+        assert_eq!((out["extractor"].as_str(), out["source"].as_str()), (Some("rules"), Some("lead-7")));
+        assert_eq!(out["lead_id"], "lead-7", "input fields are passed through");
+
+        let facts = knowledge.tenant("acme").unwrap().graph_neighbors("lead-7", 1).unwrap().unwrap().facts();
+        for fact in [
+            "Dana Brooks → works_at → Blue Fjord Ltd",
+            "Dana Brooks → interested_in → solar panels",
+            "Dana Brooks → mentions → Riverside Fair",
+            "Dana Brooks → mentions → Alex",
+        ] {
+            assert!(facts.iter().any(|f| f == fact), "{} missing from {:?}", fact, facts);
+        }
+        assert_eq!(knowledge.get_person("alex").map(|p| p.relationship), Some("partner".to_string()));
+
+        let out = skill
+            .execute(&ctx(), Some(serde_json::json!({ "event": "Harbor Festival returns", "location": "Portside" })))
+            .await
+            .unwrap();
+        assert_eq!(out["source"], "event-harbor-festival-returns");
+        assert_eq!(out["event"], "Harbor Festival returns");
+        assert!(out["relations"].as_array().unwrap().iter().any(|r| r["predicate"] == "located_in" && r["object"] == "portside"));
+    }
+}
@@ -0,0 +1,532 @@
+//! Feed Ingest skill: reads an RSS (2.0 / 1.0), Atom or sitemap document and stores each entry
+//! as a [`KbRecord`] in a slot, within the caller's tenant.
+//!
+//! Entries are keyed `feed/{hash of the GUID}` (Atom `id`, RSS `guid`, else the link), so an
+//! entry already ingested is skipped. Each record's content is the title and a plain-text
+//! summary; `title`, `link`, `guid`, `published` (and `published_ms` when the date parses) and
+//! the feed's url and title are kept in its metadata. The result carries a `digest` of the new
+//! entries, ready to pass to ModelRouter as the prompt, and `has_new` so the "summarize feed"
+//! blueprint skips the LLM call when nothing new arrived.
+//!
+//! Documents are fetched like CommunityScraper's pages (`[scraper]`, see [`crate::web_fetch`]);
+//! a `xml` payload field is used instead when given, so the feed url, its robots.txt and every
+//! redirect hop must pass the fetcher's host check. A sitemap index lists its sitemaps under
+//! `sitemaps` without fetching them; children on blocked hosts are left out.
+
+use crate::community_scraper::visible_text;
+use crate::web_fetch::{PoliteFetcher, XML_TYPES};
+use pagi_core::{AgentSkill, KbRecord, KnowledgeStore, ScraperConfig, TenantContext};
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+
+const SKILL_NAME: &str = "FeedIngest";
+const DEFAULT_SLOT: u8 = 5;
+/// Key prefix of ingested entries (`feed/{hash}`).
+pub const FEED_KEY_PREFIX: &str = "feed/";
+/// Largest feed document read.
+pub const MAX_FEED_BYTES: usize = 4 * 1024 * 1024;
+/// Entries stored per call unless `limit` says otherwise.
+const DEFAULT_LIMIT: usize = 50;
+const MAX_LIMIT: usize = 500;
+/// Longest summary stored per entry.
+const MAX_SUMMARY_CHARS: usize = 2_000;
+/// Summary characters per entry in the digest.
+const DIGEST_SUMMARY_CHARS: usize = 300;
+
+/// Fetches RSS/Atom feeds and sitemaps and stores their entries as KB records.
+pub struct FeedIngest {
+    knowledge: Arc<KnowledgeStore>,
+    config: ScraperConfig,
+    fetcher: tokio::sync::OnceCell<PoliteFetcher>,
+}
+
+impl FeedIngest {
+    pub fn new(knowledge: Arc<KnowledgeStore>) -> Self {
+        Self {
+            knowledge,
+            config: ScraperConfig::default(),
+            fetcher: tokio::sync::OnceCell::new(),
+        }
+    }
+
+    /// Fetch settings (`[scraper]`, shared with CommunityScraper).
+    pub fn with_config(mut self, config: ScraperConfig) -> Self {
+        self.config = config;
+        self
+    }
+}
+
+/// One feed entry (or sitemap url), normalized across formats.
+#[derive(Debug, Clone, PartialEq)]
+struct FeedEntry {
+    guid: String,
+    title: String,
+    link: Option<String>,
+    published: Option<String>,
+    summary: String,
+}
+
+/// What a document held.
+#[derive(Debug, Default, PartialEq)]
+struct ParsedFeed {
+    kind: &'static str,
+    title: Option<String>,
+    entries: Vec<FeedEntry>,
+    /// Sitemap index: the sitemaps it lists.
+    sitemaps: Vec<String>,
+}
+
+fn parse_feed(xml: &str) -> Result<ParsedFeed, String> {
+    let doc = roxmltree::Document::parse(xml).map_err(|e| format!("not a feed: {}", e))?;
+    let root = doc.root_element();
+    match root.tag_name().name() {
+        "rss" => {
+            let channel = child(root, "channel").ok_or("RSS feed has no channel")?;
+            Ok(ParsedFeed {
+                kind: "rss",
+                title: child_text(channel, "title"),
+                entries: children(channel, "item").filter_map(rss_item).collect(),
+                sitemaps: Vec::new(),
+            })
+        }
+        // RSS 1.0: items are siblings of the channel.
+        "RDF" => Ok(ParsedFeed {
+            kind: "rss",
+            title: child(root, "channel").and_then(|c| child_text(c, "title")),
+            entries: children(root, "item").filter_map(rss_item).collect(),
+            sitemaps: Vec::new(),
+        }),
+        "feed" => Ok(ParsedFeed {
+            kind: "atom",
+            title: child_text(root, "title"),
+            entries: children(root, "entry").filter_map(atom_entry).collect(),
+            sitemaps: Vec::new(),
+        }),
+        "urlset" => Ok(ParsedFeed {
+            kind: "sitemap",
+            title: None,
+            entries: children(root, "url")
+                .filter_map(|url| {
+                    let loc = child_text(url, "loc")?;
+                    Some(FeedEntry {
+                        guid: loc.clone(),
+                        title: loc.clone(),
+                        link: Some(loc),
+                        published: child_text(url, "lastmod"),
+                        summary: String::new(),
+                    })
+                })
+                .collect(),
+            sitemaps: Vec::new(),
+        }),
+        "sitemapindex" => Ok(ParsedFeed {
+            kind: "sitemap_index",
+            title: None,
+            entries: Vec::new(),
+            sitemaps: children(root, "sitemap").filter_map(|s| child_text(s, "loc")).collect(),
+        }),
+        other => Err(format!("not a feed: unknown root element <{}>", other)),
+    }
+}
+
+fn rss_item(item: roxmltree::Node<'_, '_>) -> Option<FeedEntry> {
+    let link = child_text(item, "link");
+    let guid = child_text(item, "guid").or_else(|| link.clone())?;
+    let summary = child_text(item, "description").or_else(|| child_text(item, "encoded")).unwrap_or_default();
+    Some(FeedEntry {
+        title: child_text(item, "title").unwrap_or_else(|| guid.clone()),
+        guid,
+        link,
+        published: child_text(item, "pubDate").or_else(|| child_text(item, "date")),
+        summary: plain_text(&summary),
+    })
+}
+
+fn atom_entry(entry: roxmltree::Node<'_, '_>) -> Option<FeedEntry> {
+    let links: Vec<_> = children(entry, "link").collect();
+    let link = links
+        .iter()
+        .find(|l| l.attribute("rel").is_none_or(|rel| rel == "alternate"))
+        .or(links.first())
+        .and_then(|l| l.attribute("href"))
+        .map(str::to_string);
+    let guid = child_text(entry, "id").or_else(|| link.clone())?;
+    let summary = child_text(entry, "summary").or_else(|| child_text(entry, "content")).unwrap_or_default();
+    Some(FeedEntry {
+        title: child_text(entry, "title").unwrap_or_else(|| guid.clone()),
+        guid,
+        link,
+        published: child_text(entry, "published").or_else(|| child_text(entry, "updated")),
+        summary: plain_text(&summary),
+    })
+}
+
+fn child<'a, 'i>(node: roxmltree::Node<'a, 'i>, name: &str) -> Option<roxmltree::Node<'a, 'i>> {
+    node.children().find(|c| c.is_element() && c.tag_name().name() == name)
+}
+
+/// Element children named `name`, in any namespace.
+fn children<'a, 'i: 'a>(node: roxmltree::Node<'a, 'i>, name: &'a str) -> impl Iterator<Item = roxmltree::Node<'a, 'i>> + 'a {
+    node.children().filter(move |c| c.is_element() && c.tag_name().name() == name)
+}
+
+fn child_text(node: roxmltree::Node<'_, '_>, name: &str) -> Option<String> {
+    let text: String = child(node, name)?.descendants().filter(|d| d.is_text()).filter_map(|d| d.text()).collect();
+    let text = text.trim();
+    (!text.is_empty()).then(|| text.to_string())
+}
+
+/// Summaries are often HTML (escaped or in CDATA): keep the visible text only.
+fn plain_text(html: &str) -> String {
+    let text = visible_text(scraper::Html::parse_fragment(html).root_element());
+    match text.char_indices().nth(MAX_SUMMARY_CHARS) {
+        Some((cut, _)) => text[..cut].to_string(),
+        None => text,
+    }
+}
+
+/// Key of the entry with `guid`.
+fn entry_key(guid: &str) -> String {
+    let hash = Sha256::digest(guid.trim().as_bytes());
+    let hex: String = hash[..16].iter().map(|b| format!("{:02x}", b)).collect();
+    format!("{}{}", FEED_KEY_PREFIX, hex)
+}
+
+/// RFC 3339 (Atom, sitemaps) or RFC 822/2822 (RSS) date → ms since the epoch.
+fn parse_date_ms(s: &str) -> Option<i64> {
+    let s = s.trim();
+    if s.as_bytes().get(4) == Some(&b'-') {
+        parse_rfc3339(s)
+    } else {
+        parse_rfc2822(s)
+    }
+}
+
+pub(crate) fn parse_rfc3339(s: &str) -> Option<i64> {
+    let num = |r: std::ops::Range<usize>| s.get(r)?.parse::<i64>().ok();
+    let (year, month, day) = (num(0..4)?, num(5..7)?, num(8..10)?);
+    if s.len() == 10 {
+        return Some(days_from_civil(year, month, day)? * 86_400_000);
+    }
+    let (hour, minute, second) = (num(11..13)?, num(14..16)?, num(17..19)?);
+    let mut rest = &s[19..];
+    let mut millis = 0;
+    if let Some(fraction) = rest.strip_prefix('.') {
+        let digits = fraction.find(|c: char| !c.is_ascii_digit()).unwrap_or(fraction.len());
+        millis = format!("{:0<3}", &fraction[..digits.min(3)]).parse().ok()?;
+        rest = &fraction[digits..];
+    }
+    let offset = match rest {
+        "Z" | "z" | "" => 0,
+        _ => parse_offset(rest)?,
+    };
+    let days = days_from_civil(year, month, day)?;
+    Some(((days * 24 + hour) * 60 + minute - offset) * 60_000 + second * 1_000 + millis)
+}
+
+/// `Mon, 02 Jan 2006 15:04:05 +0000` (weekday and seconds optional, zone names allowed).
+fn parse_rfc2822(s: &str) -> Option<i64> {
+    let s = s.split_once(',').map_or(s, |(_, rest)| rest);
+    let parts: Vec<&str> = s.split_whitespace().collect();
+    let [day, month, year, time, zone @ ..] = parts.as_slice() else {
+        return None;
+    };
+    const MONTHS: [&str; 12] = ["jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec"];
+    let month = MONTHS.iter().position(|m| month.to_ascii_lowercase().starts_with(m))? as i64 + 1;
+    let mut year: i64 = year.parse().ok()?;
+    if year < 100 {
+        year += if year < 50 { 2000 } else { 1900 };
+    }
+    let mut hms = time.split(':').map(|p| p.parse::<i64>().ok());
+    let (hour, minute, second) = (hms.next()??, hms.next()??, hms.next().unwrap_or(Some(0))?);
+    let offset = match zone.first().copied().unwrap_or("GMT") {
+        "GMT" | "UT" | "UTC" | "Z" => 0,
+        "EST" => -300,
+        "EDT" | "CST" => -240 - 60 * i64::from(zone[0] == "CST"),
+        "CDT" | "MST" => -300 - 60 * i64::from(zone[0] == "MST"),
+        "MDT" | "PST" => -360 - 60 * i64::from(zone[0] == "PST"),
+        "PDT" => -420,
+        other => parse_offset(other)?,
+    };
+    let days = days_from_civil(year, month, day.parse().ok()?)?;
+    Some(((days * 24 + hour) * 60 + minute - offset) * 60_000 + second * 1_000)
+}
+
+/// `+hh:mm`, `-hhmm` → minutes east of UTC.
+fn parse_offset(s: &str) -> Option<i64> {
+    let sign = match s.as_bytes().first()? {
+        b'+' => 1,
+        b'-' => -1,
+        _ => return None,
+    };
+    let digits: String = s[1..].chars().filter(|c| *c != ':').collect();
+    if digits.len() != 4 {
+        return None;
+    }
+    Some(sign * (digits[..2].parse::<i64>().ok()? * 60 + digits[2..].parse::<i64>().ok()?))
+}
+
+/// Days since 1970-01-01 of a proleptic Gregorian date.
+pub(crate) fn days_from_civil(year: i64, month: i64, day: i64) -> Option<i64> {
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y.rem_euclid(400);
+    let mp = (month + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    Some(era * 146_097 + doe - 719_468)
+}
+
+/// Prompt summarizing `entries` for ModelRouter.
+fn digest(feed_title: Option<&str>, entries: &[&FeedEntry]) -> String {
+    if entries.is_empty() {
+        return String::new();
+    }
+    let mut out = format!(
+        "Summarize these new items from {} in a short briefing:\n",
+        feed_title.unwrap_or("the feed")
+    );
+    for entry in entries {
+        out.push_str("\n- ");
+        out.push_str(&entry.title);
+        if let Some(published) = &entry.published {
+            out.push_str(&format!(" ({})", published));
+        }
+        if !entry.summary.is_empty() {
+            let summary = match entry.summary.char_indices().nth(DIGEST_SUMMARY_CHARS) {
+                Some((cut, _)) => format!("{}…", &entry.summary[..cut]),
+                None => entry.summary.clone(),
+            };
+            out.push_str(": ");
+            out.push_str(&summary);
+        }
+    }
+    out
+}
+
+#[async_trait::async_trait]
+impl AgentSkill for FeedIngest {
+    fn name(&self) -> &str {
+        SKILL_NAME
+    }
+
+    async fn execute(
+        &self,
+        ctx: &TenantContext,
+        payload: Option<serde_json::Value>,
+    ) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>> {
+        let payload = payload.ok_or("FeedIngest requires payload: { url: string } or { xml: string, slot_id?: 1..8, limit? }")?;
+        let slot_id = payload.get("slot_id").and_then(|v| v.as_u64()).map(|n| n as u8).unwrap_or(DEFAULT_SLOT);
+        if !(1..=8).contains(&slot_id) {
+            return Err("slot_id must be 1–8".into());
+        }
+        let limit = payload
+            .get("limit")
+            .and_then(|v| v.as_u64())
+            .map_or(DEFAULT_LIMIT, |n| (n as usize).min(MAX_LIMIT));
+        let url = payload.get("url").and_then(|v| v.as_str()).map(str::to_string);
+
+        let xml = match payload.get("xml").and_then(|v| v.as_str()) {
+            Some(xml) if xml.len() > MAX_FEED_BYTES => {
+                return Err(format!("FeedIngest: inline xml is {} bytes, the limit is {} bytes", xml.len(), MAX_FEED_BYTES).into())
+            }
+            Some(xml) => xml.to_string(),
+            None => {
+                let url = url.as_deref().ok_or("FeedIngest requires 'url' when 'xml' is not provided")?;
+                if !self.config.fetch {
+                    return Err("FeedIngest: fetching is disabled ([scraper] fetch = false); provide 'xml'".into());
+                }
+                let fetcher = self
+                    .fetcher
+                    .get_or_try_init(|| async { PoliteFetcher::new(self.config.clone()) })
+                    .await?;
+                let timeout = ctx.bounded_timeout(std::time::Duration::from_secs(self.config.timeout_secs));
+                fetcher.fetch_text(url, timeout, MAX_FEED_BYTES, XML_TYPES).await?
+            }
+        };
+        let feed = parse_feed(&xml)?;
+
+        let kb = self.knowledge.tenant(&ctx.tenant_id)?;
+        let mut new_entries = Vec::new();
+        let mut stored = Vec::new();
+        let mut duplicates = 0;
+        for entry in feed.entries.iter().take(limit) {
+            let key = entry_key(&entry.guid);
+            if kb.get(slot_id, &key)?.is_some() {
+                duplicates += 1;
+                continue;
+            }
+            let content = if entry.summary.is_empty() {
+                entry.title.clone()
+            } else {
+                format!("{}\n\n{}", entry.title, entry.summary)
+            };
+            let metadata = serde_json::json!({
+                "type": "feed_entry",
+                "tags": ["feed", feed.kind],
+                "title": entry.title,
+                "link": entry.link,
+                "guid": entry.guid,
+                "published": entry.published,
+                "published_ms": entry.published.as_deref().and_then(parse_date_ms),
+                "feed_url": url,
+                "feed_title": feed.title,
+            });
+            let record = KbRecord::with_metadata(content, metadata, self.knowledge.now_ms());
+            kb.insert_record(slot_id, &key, &record)?;
+            stored.push(serde_json::json!({
+                "key": key,
+                "guid": entry.guid,
+                "title": entry.title,
+                "link": entry.link,
+                "published": entry.published,
+            }));
+            new_entries.push(entry);
+        }
+        tracing::info!(
+            target: "pagi::feed_ingest",
+            url = url.as_deref().unwrap_or("(inline)"),
+            kind = feed.kind,
+            ingested = new_entries.len(),
+            duplicates,
+            "Feed ingested"
+        );
+
+        let mut result = serde_json::json!({
+            "status": "ok",
+            "skill": SKILL_NAME,
+            "slot_id": slot_id,
+            "kind": feed.kind,
+            "feed_title": feed.title,
+            "ingested": new_entries.len(),
+            "duplicates": duplicates,
+            "has_new": !new_entries.is_empty(),
+            "entries": stored,
+            "digest": digest(feed.title.as_deref(), &new_entries),
+        });
+        if !feed.sitemaps.is_empty() {
+            let fetcher = self
+                .fetcher
+                .get_or_try_init(|| async { PoliteFetcher::new(self.config.clone()) })
+                .await?;
+            let mut sitemaps = Vec::new();
+            for sitemap in &feed.sitemaps {
+                match fetcher.check_url(sitemap).await {
+                    Ok(()) => sitemaps.push(sitemap),
+                    Err(e) => tracing::warn!(target: "pagi::feed_ingest", error = %e, "Sitemap skipped"),
+                }
+            }
+            result["sitemaps"] = serde_json::json!(sitemaps);
+        }
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RSS: &str = r#"<?xml version="1.0"?>
+        <rss version="2.0" xmlns:content="http://purl.org/rss/1.0/modules/content/"><channel>
+          <title>Stockdale Herald</title>
+          <item>
+            <title>Fair opens Friday</title>
+            <link>https://herald.example/fair</link>
+            <guid isPermaLink="false">herald-101</guid>
+            <pubDate>Fri, 15 Mar 2024 06:30:00 GMT</pubDate>
+            <description>&lt;p&gt;Rides &amp;amp; &lt;b&gt;food&lt;/b&gt;&lt;/p&gt;</description>
+          </item>
+          <item>
+            <title>Road closed</title>
+            <link>https://herald.example/road</link>
+            <pubDate>Fri, 15 Mar 2024 08:00:00 -0400</pubDate>
+            <content:encoded><![CDATA[<div>Main St. <script>x()</script>until noon</div>]]></content:encoded>
+          </item>
+        </channel></rss>"#;
+
+    #[test]
+    fn parses_rss_atom_and_sitemaps() {
+        let feed = parse_feed(RSS).unwrap();
+        assert_eq!((feed.kind, feed.title.as_deref()), ("rss", Some("Stockdale Herald")));
+        assert_eq!(feed.entries[0].guid, "herald-101");
+        assert_eq!(feed.entries[0].summary, "Rides & food");
+        // No guid: the link identifies the entry.
+        assert_eq!(feed.entries[1].guid, "https://herald.example/road");
+        assert_eq!(feed.entries[1].summary, "Main St. until noon");
+
+        let atom = r#"<feed xmlns="http://www.w3.org/2005/Atom"><title>Council</title>
+            <entry><id>tag:council,2024:7</id><title type="html">Budget vote</title>
+              <link rel="self" href="https://council.example/api/7"/><link href="https://council.example/7"/>
+              <updated>2024-03-15T09:00:00+01:00</updated><summary>Passed 5-2</summary></entry></feed>"#;
+        let feed = parse_feed(atom).unwrap();
+        assert_eq!(feed.kind, "atom");
+        let entry = &feed.entries[0];
+        assert_eq!((entry.guid.as_str(), entry.link.as_deref()), ("tag:council,2024:7", Some("https://council.example/7")));
+
+        let sitemap = r#"<urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">
+            <url><loc>https://x.example/a</loc><lastmod>2024-03-15</lastmod></url></urlset>"#;
+        let feed = parse_feed(sitemap).unwrap();
+        assert_eq!((feed.kind, feed.entries[0].link.as_deref()), ("sitemap", Some("https://x.example/a")));
+        let index = r#"<sitemapindex><sitemap><loc>https://x.example/s1.xml</loc></sitemap></sitemapindex>"#;
+        assert_eq!(parse_feed(index).unwrap().sitemaps, vec!["https://x.example/s1.xml"]);
+
+        assert!(parse_feed("<html><body/></html>").unwrap_err().contains("unknown root element <html>"));
+        assert!(parse_feed("not xml").is_err());
+    }
+
+    #[test]
+    fn parses_feed_dates() {
+        // 2024-03-15T06:30:00Z
+        let t = 1_710_484_200_000;
+        assert_eq!(parse_date_ms("Fri, 15 Mar 2024 06:30:00 GMT"), Some(t));
+        assert_eq!(parse_date_ms("15 Mar 2024 08:30:00 +0200"), Some(t));
+        assert_eq!(parse_date_ms("Fri, 15 Mar 2024 02:30:00 EDT"), Some(t));
+        assert_eq!(parse_date_ms("2024-03-15T07:30:00+01:00"), Some(t));
+        assert_eq!(parse_date_ms("2024-03-15T06:30:00.250Z"), Some(t + 250));
+        assert_eq!(parse_date_ms("2024-03-15"), Some(t - (6 * 60 + 30) * 60_000));
+        assert_eq!(parse_date_ms("yesterday"), None);
+    }
+
+    #[tokio::test]
+    async fn ingests_entries_once_per_guid_within_the_tenant() {
+        let knowledge = Arc::new(KnowledgeStore::open_temporary().unwrap());
+        let skill = FeedIngest::new(Arc::clone(&knowledge));
+        let ctx = TenantContext::new("acme");
+        let out = skill.execute(&ctx, Some(serde_json::json!({ "xml": RSS, "slot_id": 3 }))).await.unwrap();
+        assert_eq!((out["ingested"].as_u64(), out["duplicates"].as_u64()), (Some(2), Some(0)));
+        let digest = out["digest"].as_str().unwrap();
+        assert!(digest.starts_with("Summarize these new items from Stockdale Herald"));
+        assert!(digest.contains("- Fair opens Friday (Fri, 15 Mar 2024 06:30:00 GMT): Rides & food"));
+
+        let key = out["entries"][0]["key"].as_str().unwrap();
+        let record = knowledge.tenant("acme").unwrap().get_record(3, key).unwrap().unwrap();
+        assert_eq!(record.content, "Fair opens Friday\n\nRides & food");
+        assert_eq!(record.metadata["link"], "https://herald.example/fair");
+        assert_eq!(record.metadata["published_ms"], 1_710_484_200_000_i64);
+        assert!(knowledge.get_record(3, key).unwrap().is_none());
+
+        let again = skill.execute(&ctx, Some(serde_json::json!({ "xml": RSS, "slot_id": 3 }))).await.unwrap();
+        assert_eq!((again["ingested"].as_u64(), again["duplicates"].as_u64()), (Some(0), Some(2)));
+        assert_eq!(again["digest"], "");
+    }
+
+    #[tokio::test]
+    async fn internal_hosts_are_not_fetched() {
+        let knowledge = Arc::new(KnowledgeStore::open_temporary().unwrap());
+        let skill = FeedIngest::new(Arc::clone(&knowledge));
+        let ctx = TenantContext::new("acme");
+        for url in ["http://127.0.0.1/feed.xml", "http://169.254.169.254/latest/meta-data/", "http://metadata.google.internal/"] {
+            let err = skill.execute(&ctx, Some(serde_json::json!({ "url": url }))).await.unwrap_err();
+            assert!(err.to_string().contains("cannot be fetched"), "{}: {}", url, err);
+        }
+
+        let index = r#"<sitemapindex>
+            <sitemap><loc>http://10.0.0.5/s1.xml</loc></sitemap>
+            <sitemap><loc>https://93.184.215.14/s2.xml</loc></sitemap></sitemapindex>"#;
+        let out = skill.execute(&ctx, Some(serde_json::json!({ "xml": index }))).await.unwrap();
+        assert_eq!(out["sitemaps"], serde_json::json!(["https://93.184.215.14/s2.xml"]));
+    }
+}
@@ -1,15 +1,18 @@
 //! Filesystem tools for bare-metal introspection.
 //!
-//! Primary entrypoint: [`analyze_workspace()`](crates/pagi-skills/src/fs_tools.rs:74)
+//! Primary entrypoint: [`analyze_workspace()`]
 //!
 //! This module implements the `fs_workspace_analyzer` discovery skill, allowing the
-//! orchestrator to scan the local Rust workspace and report crate structure.
-//! When a store is provided, scan results are stored in **KB_OIKOS** (Context / "The World").
-
-use pagi_core::{AgentSkill, KbRecord, KbType, KnowledgeStore, TenantContext};
-use serde::{Deserialize, Serialize};
+//! orchestrator to scan the local workspace and report crate structure, languages, large files
+//! and stale branches (see [`crate::workspace_report`]). When a store is provided, the report is
+//! stored in **KB_OIKOS** (Context / "The World").
+
+use crate::workspace_report::{rel_or_abs, WorkspaceReport, WorkspaceScanOptions, OIKOS_WORKSPACE_SCAN_KEY};
+use pagi_core::{
+    resolve_in_sandbox, AgentSkill, Clock, KbRecord, KbType, KnowledgeStore, SkillLimits, SystemClock, TenantContext,
+};
+use serde::Deserialize;
 use std::sync::Arc;
-use std::collections::VecDeque;
 use std::fs;
 use std::fs::OpenOptions;
 use std::io::Write;
@@ -17,53 +20,80 @@ use std::path::{Path, PathBuf};
 
 const SKILL_NAME: &str = "fs_workspace_analyzer";
 
-const SANDBOX_WRITE_SKILL_NAME: &str = "write_sandbox_file";
+/// `metadata.type` of the workspace report record in KB_OIKOS.
+const WORKSPACE_REPORT_TYPE: &str = "workspace_report";
+
+/// Skill name of [`WriteSandboxFile`]; Ethos policies on sandbox writes refer to it.
+pub const SANDBOX_WRITE_SKILL_NAME: &str = "write_sandbox_file";
 
-/// Arguments accepted by the `fs_workspace_analyzer` skill.
+/// Arguments accepted by the `fs_workspace_analyzer` skill; set fields override the analyzer's
+/// [`WorkspaceScanOptions`].
 #[derive(Debug, Clone, Default, Deserialize)]
 struct FsWorkspaceAnalyzerArgs {
-    /// Path to analyze. If omitted, defaults to the process current directory.
+    /// Path to analyze. If omitted, defaults to the configured roots or the current directory.
     #[serde(default)]
     path: Option<String>,
+    /// Several paths to analyze into one report.
+    #[serde(default)]
+    roots: Vec<String>,
     /// Maximum directory depth to traverse (0 = just the root).
     #[serde(default)]
     depth: Option<usize>,
+    /// `.gitignore`-style rules, applied after the configured ones.
+    #[serde(default)]
+    exclude: Vec<String>,
+    #[serde(default)]
+    respect_gitignore: Option<bool>,
+    #[serde(default)]
+    large_file_bytes: Option<u64>,
+    #[serde(default)]
+    stale_branch_days: Option<u64>,
 }
 
-#[derive(Debug, Clone, Serialize)]
-struct CrateInfo {
-    /// Best-effort crate/package name derived from Cargo.toml.
-    #[serde(skip_serializing_if = "Option::is_none")]
-    name: Option<String>,
-    /// Path to crate directory, relative to the analyzed root (when possible).
-    path: String,
-    /// Path to the manifest, relative to the analyzed root (when possible).
-    manifest: String,
-    /// Whether `src/` exists under the crate directory.
-    has_src: bool,
-    /// Whether this manifest looks like a workspace root.
-    is_workspace: bool,
+impl FsWorkspaceAnalyzerArgs {
+    fn apply(self, mut options: WorkspaceScanOptions) -> WorkspaceScanOptions {
+        let roots: Vec<PathBuf> = self.path.into_iter().chain(self.roots).map(PathBuf::from).collect();
+        if !roots.is_empty() {
+            options.roots = roots;
+        }
+        options.exclude.extend(self.exclude);
+        options.max_depth = self.depth.unwrap_or(options.max_depth);
+        options.respect_gitignore = self.respect_gitignore.unwrap_or(options.respect_gitignore);
+        options.large_file_bytes = self.large_file_bytes.unwrap_or(options.large_file_bytes);
+        options.stale_branch_days = self.stale_branch_days.unwrap_or(options.stale_branch_days);
+        options
+    }
 }
 
-/// Key under KB_OIKOS where the latest workspace scan is stored.
-pub const OIKOS_WORKSPACE_SCAN_KEY: &str = "workspace_scan/latest";
-
-/// Agent skill wrapper that exposes [`analyze_workspace()`](crates/pagi-skills/src/fs_tools.rs:74)
-/// via the orchestrator skill registry. When constructed with `new_with_store`, scan output
-/// is stored in **KB_OIKOS** (Context) for the cognitive map.
+/// Agent skill wrapper that exposes [`WorkspaceReport::scan`] via the orchestrator skill
+/// registry. When constructed with `new_with_store`, the report is stored in **KB_OIKOS**
+/// (Context) for the cognitive map, the guardian and dashboards.
 pub struct FsWorkspaceAnalyzer {
     store: Option<Arc<KnowledgeStore>>,
+    options: WorkspaceScanOptions,
 }
 
 impl FsWorkspaceAnalyzer {
     /// No store: scan results are returned only (no KB write).
     pub fn new() -> Self {
-        Self { store: None }
+        Self {
+            store: None,
+            options: WorkspaceScanOptions::default(),
+        }
     }
 
     /// With store: scan results are also written to KB_OIKOS (workspace_scan/latest).
     pub fn new_with_store(store: Arc<KnowledgeStore>) -> Self {
-        Self { store: Some(store) }
+        Self {
+            store: Some(store),
+            ..Self::new()
+        }
+    }
+
+    /// Default roots, exclusion rules and thresholds for calls that do not set them.
+    pub fn with_options(mut self, options: WorkspaceScanOptions) -> Self {
+        self.options = options;
+        self
     }
 }
 
@@ -73,155 +103,19 @@ impl Default for FsWorkspaceAnalyzer {
     }
 }
 
-/// Recursively scans a Rust workspace root and returns a JSON summary of detected crates.
-///
-/// It identifies:
-/// - `Cargo.toml` files
-/// - `src/` directories under crate roots
-/// - `add-ons/` folder (if present)
-///
-/// The scan intentionally skips common heavy directories (e.g. `target/`, `.git/`, `node_modules/`, `data/`).
+/// Scans a Rust workspace root with the default [`WorkspaceScanOptions`] and returns the
+/// [`WorkspaceReport`] as JSON (plus `status` and `skill`).
 pub fn analyze_workspace(path: &Path) -> serde_json::Value {
-    let depth_limit = 25usize;
-
-    let root = path.to_path_buf();
-
-    let mut add_ons_found = false;
-    let mut add_ons_path: Option<String> = None;
-    let mut cargo_manifests: Vec<CrateInfo> = Vec::new();
-
-    let skip_dir_names = [
-        ".git",
-        "target",
-        "node_modules",
-        // this repo includes large sled/db fixtures under add-ons/pagi-gateway/data
-        "data",
-        "db",
-        "blobs",
-    ];
-
-    let mut q: VecDeque<(PathBuf, usize)> = VecDeque::new();
-    q.push_back((root.clone(), 0));
-
-    while let Some((dir, depth)) = q.pop_front() {
-        // Depth guard
-        if depth > depth_limit {
-            continue;
-        }
-
-        // Detect add-ons folder
-        if dir.file_name().and_then(|s| s.to_str()) == Some("add-ons") {
-            add_ons_found = true;
-            add_ons_path = Some(rel_or_abs(&root, &dir));
-        }
-
-        // Record Cargo.toml manifests at this level.
-        let manifest_path = dir.join("Cargo.toml");
-        if manifest_path.is_file() {
-            let (pkg_name, is_workspace) = read_manifest_metadata(&manifest_path);
-            let has_src = dir.join("src").is_dir();
-            cargo_manifests.push(CrateInfo {
-                name: pkg_name,
-                path: rel_or_abs(&root, &dir),
-                manifest: rel_or_abs(&root, &manifest_path),
-                has_src,
-                is_workspace,
-            });
-        }
-
-        // Walk children directories.
-        let read_dir = match fs::read_dir(&dir) {
-            Ok(rd) => rd,
-            Err(_) => continue,
-        };
-        for entry in read_dir.flatten() {
-            let p = entry.path();
-            if !p.is_dir() {
-                continue;
-            }
-            let Some(name) = p.file_name().and_then(|s| s.to_str()) else {
-                continue;
-            };
-            if skip_dir_names.iter().any(|&s| s.eq_ignore_ascii_case(name)) {
-                continue;
-            }
-            q.push_back((p, depth + 1));
-        }
-    }
-
-    // Sort deterministically by manifest path.
-    cargo_manifests.sort_by(|a, b| a.manifest.cmp(&b.manifest));
-
-    let crate_count = cargo_manifests.len();
-    let workspace_roots: Vec<&CrateInfo> = cargo_manifests.iter().filter(|c| c.is_workspace).collect();
-
-    let summary = format!(
-        "Workspace scan root: {}\nCrates/manifests found: {}\nWorkspace manifests: {}\nadd-ons present: {}",
-        rel_or_abs(&root, &root),
-        crate_count,
-        workspace_roots.len(),
-        add_ons_found
-    );
-
-    serde_json::json!({
-        "status": "ok",
-        "skill": SKILL_NAME,
-        "root": rel_or_abs(&root, &root),
-        "crate_count": crate_count,
-        "crates": cargo_manifests,
-        "workspace_manifest_count": workspace_roots.len(),
-        "add_ons_found": add_ons_found,
-        "add_ons_path": add_ons_path,
-        "summary": summary,
-    })
-}
-
-fn rel_or_abs(root: &Path, p: &Path) -> String {
-    p.strip_prefix(root)
-        .ok()
-        .map(|rp| {
-            let s = rp.to_string_lossy().to_string();
-            if s.is_empty() { ".".to_string() } else { s }
-        })
-        .unwrap_or_else(|| p.to_string_lossy().to_string())
-        .replace('\\', "/")
+    let now_ms = SystemClock.now_ms();
+    let report = WorkspaceReport::scan(path, &[path.to_path_buf()], &WorkspaceScanOptions::default(), now_ms);
+    report_json(&report)
 }
 
-fn read_manifest_metadata(manifest_path: &Path) -> (Option<String>, bool) {
-    let Ok(text) = fs::read_to_string(manifest_path) else {
-        return (None, false);
-    };
-
-    let is_workspace = text.lines().any(|l| l.trim() == "[workspace]");
-    let mut in_package = false;
-    let mut name: Option<String> = None;
-
-    for raw in text.lines() {
-        let line = raw.trim();
-        if line.starts_with('[') && line.ends_with(']') {
-            in_package = line == "[package]";
-            // Stop scanning package name once we hit another section after [package].
-            if name.is_some() && !in_package {
-                break;
-            }
-            continue;
-        }
-        if !in_package {
-            continue;
-        }
-
-        // Very small TOML subset parse: `name = "..."`.
-        let Some(rest) = line.strip_prefix("name") else { continue };
-        let rest = rest.trim_start();
-        let Some(rest) = rest.strip_prefix('=') else { continue };
-        let rest = rest.trim();
-        if let Some(stripped) = rest.strip_prefix('"').and_then(|r| r.strip_suffix('"')) {
-            name = Some(stripped.to_string());
-            break;
-        }
-    }
-
-    (name, is_workspace)
+fn report_json(report: &WorkspaceReport) -> serde_json::Value {
+    let mut out = serde_json::to_value(report).unwrap_or_default();
+    out["status"] = serde_json::json!("ok");
+    out["skill"] = serde_json::json!(SKILL_NAME);
+    out
 }
 
 fn canonicalize_within_base(base: &Path, candidate: &Path) -> Result<PathBuf, String> {
@@ -243,6 +137,14 @@ impl AgentSkill for FsWorkspaceAnalyzer {
         SKILL_NAME
     }
 
+    /// Scans stay within the current working directory.
+    fn limits(&self) -> SkillLimits {
+        SkillLimits {
+            sandbox_root: std::env::current_dir().ok(),
+            ..Default::default()
+        }
+    }
+
     async fn execute(
         &self,
         _ctx: &TenantContext,
@@ -252,43 +154,55 @@ impl AgentSkill for FsWorkspaceAnalyzer {
             Some(v) => serde_json::from_value(v).unwrap_or_default(),
             None => FsWorkspaceAnalyzerArgs::default(),
         };
+        let options = args.apply(self.options.clone());
 
         let base = std::env::current_dir()?;
-        let requested = args
-            .path
-            .as_deref()
-            .map(PathBuf::from)
-            .unwrap_or_else(|| base.clone());
+        let requested = match options.roots.is_empty() {
+            true => vec![base.clone()],
+            false => options.roots.iter().map(|r| base.join(r)).collect(),
+        };
 
         // Safety: restrict scanning to within the current working directory.
-        let root = canonicalize_within_base(&base, &requested).map_err(std::io::Error::other)?;
+        let roots = requested
+            .iter()
+            .map(|r| canonicalize_within_base(&base, r))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(std::io::Error::other)?;
+        let report_base = match roots.as_slice() {
+            [root] => root.clone(),
+            _ => base.canonicalize()?,
+        };
+        let now_ms = match &self.store {
+            Some(store) => store.now_ms(),
+            None => SystemClock.now_ms(),
+        };
+        let report = WorkspaceReport::scan(&report_base, &roots, &options, now_ms);
 
-        let mut out = analyze_workspace(&root);
-        if let Some(d) = args.depth {
-            out["requested_depth"] = serde_json::json!(d);
-        }
-        out["requested_path"] = serde_json::json!(requested.to_string_lossy().to_string());
-        out["canonical_root"] = serde_json::json!(root.to_string_lossy().to_string());
+        let mut out = report_json(&report);
+        out["canonical_roots"] = serde_json::json!(roots.iter().map(|r| r.to_string_lossy()).collect::<Vec<_>>());
 
         // Breadcrumbs: store in KB_OIKOS (Context / "The World") when store is available
         if let Some(ref store) = self.store {
             let slot_id = KbType::Oikos.slot_id();
-            let content = serde_json::to_string(&out).unwrap_or_else(|_| "{}".to_string());
+            let content = serde_json::to_string(&report).unwrap_or_else(|_| "{}".to_string());
             let record = KbRecord::with_metadata(
                 content,
                 serde_json::json!({
-                    "type": "workspace_scan",
+                    "type": WORKSPACE_REPORT_TYPE,
                     "skill": SKILL_NAME,
-                    "crate_count": out.get("crate_count").and_then(|v| v.as_u64()).unwrap_or(0),
+                    "crate_count": report.crate_count,
+                    "large_file_count": report.large_files.len(),
+                    "stale_branch_count": report.stale_branches.len(),
                     "tags": ["oikos", "workspace", "context"]
                 }),
+                now_ms,
             );
             if store.insert_record(slot_id, OIKOS_WORKSPACE_SCAN_KEY, &record).is_ok() {
                 tracing::info!(
                     target: "pagi::fs_tools",
                     slot = slot_id,
                     key = OIKOS_WORKSPACE_SCAN_KEY,
-                    "Workspace scan stored in KB_OIKOS (Context)"
+                    "Workspace report stored in KB_OIKOS (Context)"
                 );
             }
         }
@@ -313,7 +227,7 @@ struct WriteSandboxFileArgs {
 
 /// Agent skill: write a file within `research_sandbox/` only.
 ///
-/// Safety properties:
+/// Safety properties (see [`PathJail`]):
 /// - Rejects absolute paths and any `..` segments
 /// - Enforces a canonicalized prefix check against the canonical sandbox root
 pub struct WriteSandboxFile;
@@ -342,7 +256,9 @@ fn sanitize_sandbox_rel_path(input: &str) -> Result<PathBuf, String> {
         .unwrap_or(raw.as_str());
 
     let p = Path::new(raw);
-    if p.is_absolute() {
+    // Drive-letter paths (`C:/...`) are only absolute on Windows; reject them everywhere.
+    let drive_letter = matches!(raw.as_bytes(), [letter, b':', ..] if letter.is_ascii_alphabetic());
+    if p.is_absolute() || drive_letter {
         return Err("absolute paths are forbidden".to_string());
     }
 
@@ -366,34 +282,63 @@ fn sanitize_sandbox_rel_path(input: &str) -> Result<PathBuf, String> {
     Ok(out)
 }
 
-fn canonical_sandbox_target(base: &Path, rel: &Path) -> Result<(PathBuf, PathBuf), String> {
-    // Ensure sandbox exists, then canonicalize.
-    let sandbox_root = base.join("research_sandbox");
-    fs::create_dir_all(&sandbox_root)
-        .map_err(|e| format!("failed to create research_sandbox directory: {e}"))?;
-    let sandbox_root_canon = sandbox_root
-        .canonicalize()
-        .map_err(|e| format!("failed to canonicalize sandbox root: {e}"))?;
-
-    let target = sandbox_root.join(rel);
-    let parent = target
-        .parent()
-        .ok_or_else(|| "invalid path: missing parent".to_string())?;
-    fs::create_dir_all(parent)
-        .map_err(|e| format!("failed to create parent directories: {e}"))?;
-    let parent_canon = parent
-        .canonicalize()
-        .map_err(|e| format!("failed to canonicalize parent directory: {e}"))?;
+/// Confines file access to one directory (e.g. `research_sandbox/`).
+///
+/// Paths are relative to the root: absolute paths and `..` segments are rejected, and every
+/// resolved path is canonicalized and prefix-checked so symlinks cannot lead outside the root.
+#[derive(Debug, Clone)]
+pub struct PathJail {
+    root: PathBuf,
+}
+
+impl PathJail {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// Jail over `{base}/research_sandbox`.
+    pub fn research_sandbox(base: &Path) -> Self {
+        Self::new(base.join("research_sandbox"))
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
 
-    if !parent_canon.starts_with(&sandbox_root_canon) {
-        return Err("path is outside research_sandbox".to_string());
+    /// Canonical root, creating the directory when missing.
+    pub fn canonical_root(&self) -> Result<PathBuf, String> {
+        fs::create_dir_all(&self.root)
+            .map_err(|e| format!("failed to create sandbox directory: {e}"))?;
+        self.root
+            .canonicalize()
+            .map_err(|e| format!("failed to canonicalize sandbox root: {e}"))
     }
 
-    let file_name = target
-        .file_name()
-        .ok_or_else(|| "invalid path: missing filename".to_string())?;
-    let target_canon = parent_canon.join(file_name);
-    Ok((sandbox_root_canon, target_canon))
+    /// Absolute path of `rel` inside the jail. The target need not exist, but every existing
+    /// component must resolve inside the root; a dangling symlink is refused rather than written
+    /// through (see [`pagi_core::resolve_in_sandbox`]).
+    pub fn resolve(&self, rel: &str) -> Result<PathBuf, String> {
+        let rel = sanitize_sandbox_rel_path(rel)?;
+        let root = self.canonical_root()?;
+        resolve_in_sandbox(&root, &rel).map_err(|e| e.to_string())
+    }
+
+    /// Like [`resolve`](Self::resolve), creating the parent directories of the target.
+    pub fn resolve_for_write(&self, rel: &str) -> Result<PathBuf, String> {
+        let target = self.resolve(rel)?;
+        let parent = target
+            .parent()
+            .ok_or_else(|| "invalid path: missing parent".to_string())?;
+        fs::create_dir_all(parent).map_err(|e| format!("failed to create parent directories: {e}"))?;
+        // Re-check now that the directories exist.
+        self.resolve(rel)
+    }
+
+    /// `path` relative to the root, with `/` separators.
+    pub fn relative(&self, path: &Path) -> String {
+        let root = self.root.canonicalize().unwrap_or_else(|_| self.root.clone());
+        rel_or_abs(&root, path)
+    }
 }
 
 #[async_trait::async_trait]
@@ -402,6 +347,14 @@ impl AgentSkill for WriteSandboxFile {
         SANDBOX_WRITE_SKILL_NAME
     }
 
+    /// Writes stay within `research_sandbox/`.
+    fn limits(&self) -> SkillLimits {
+        SkillLimits {
+            sandbox_root: std::env::current_dir().ok().map(|base| PathJail::research_sandbox(&base).root().to_path_buf()),
+            ..Default::default()
+        }
+    }
+
     async fn execute(
         &self,
         _ctx: &TenantContext,
@@ -418,14 +371,9 @@ impl AgentSkill for WriteSandboxFile {
         };
 
         let base = std::env::current_dir()?;
-        let rel = sanitize_sandbox_rel_path(&args.path).map_err(std::io::Error::other)?;
-        let (sandbox_root_canon, target_canon) =
-            canonical_sandbox_target(&base, &rel).map_err(std::io::Error::other)?;
-
-        // Final guard: ensure the final file path still prefixes the sandbox root.
-        if !target_canon.starts_with(&sandbox_root_canon) {
-            return Err(std::io::Error::other("path is outside research_sandbox"))?;
-        }
+        let target_canon = PathJail::research_sandbox(&base)
+            .resolve_for_write(&args.path)
+            .map_err(std::io::Error::other)?;
 
         let bytes = if args.append {
             let mut f = OpenOptions::new()
@@ -465,6 +413,27 @@ mod tests {
         assert!(v.get("crate_count").and_then(|n| n.as_u64()).unwrap_or(0) >= 1);
     }
 
+    #[tokio::test]
+    async fn analyzer_stores_its_report_in_oikos() {
+        let store = Arc::new(KnowledgeStore::open_temporary().unwrap());
+        let analyzer = FsWorkspaceAnalyzer::new_with_store(Arc::clone(&store)).with_options(WorkspaceScanOptions {
+            large_file_bytes: u64::MAX,
+            ..WorkspaceScanOptions::default()
+        });
+        let ctx = TenantContext::new("test");
+        let out = analyzer
+            .execute(&ctx, Some(serde_json::json!({ "path": "src", "exclude": ["lib.rs"] })))
+            .await
+            .unwrap();
+        assert_eq!(out["status"], "ok");
+        let report = WorkspaceReport::latest(&store).expect("stored report");
+        assert_eq!(report.root, ".");
+        assert!(report.large_files.is_empty());
+        assert!(report.languages.iter().any(|l| l.language == "Rust" && l.lines > 0));
+        assert_eq!(report.excluded, 1);
+        assert!(analyzer.execute(&ctx, Some(serde_json::json!({ "path": "../.." }))).await.is_err());
+    }
+
     #[test]
     fn write_sandbox_file_rejects_traversal() {
         assert!(sanitize_sandbox_rel_path("../evil.md").is_err());
@@ -483,6 +452,45 @@ mod tests {
         assert_eq!(p.to_string_lossy().replace('\\', "/"), "report_01.md");
     }
 
+    #[test]
+    fn path_jail_resolves_inside_root_only() {
+        let tmp_root = std::env::temp_dir().join(format!("pagi_path_jail_test_{}", std::process::id()));
+        let jail = PathJail::new(tmp_root.join("sandbox"));
+
+        let target = jail.resolve_for_write("notes/todo.txt").unwrap();
+        assert!(target.parent().unwrap().is_dir());
+        assert_eq!(jail.relative(&target), "notes/todo.txt");
+        assert!(jail.resolve("../outside.txt").is_err());
+        assert!(jail.resolve("/etc/passwd").is_err());
+
+        #[cfg(unix)]
+        {
+            fs::create_dir_all(tmp_root.join("outside")).unwrap();
+            std::os::unix::fs::symlink(tmp_root.join("outside"), tmp_root.join("sandbox").join("link")).unwrap();
+            assert!(jail.resolve("link/escape.txt").is_err());
+        }
+
+        let _ = fs::remove_dir_all(tmp_root);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn path_jail_refuses_writes_through_dangling_symlinks() {
+        let tmp_root = std::env::temp_dir().join(format!("pagi_path_jail_dangling_{}", std::process::id()));
+        let jail = PathJail::new(tmp_root.join("sandbox"));
+        let outside = tmp_root.join("outside.txt");
+        fs::create_dir_all(jail.root()).unwrap();
+        std::os::unix::fs::symlink(&outside, jail.root().join("report.md")).unwrap();
+        std::os::unix::fs::symlink(tmp_root.join("missing_dir"), jail.root().join("nested")).unwrap();
+
+        assert!(jail.resolve_for_write("report.md").is_err());
+        assert!(jail.resolve_for_write("nested/report.md").is_err());
+        assert!(!outside.exists());
+        assert!(!tmp_root.join("missing_dir").exists());
+
+        let _ = fs::remove_dir_all(tmp_root);
+    }
+
     #[test]
     fn write_sandbox_file_happy_path_writes() {
         use std::time::{SystemTime, UNIX_EPOCH};
@@ -503,11 +511,7 @@ mod tests {
         let rt = tokio::runtime::Runtime::new().unwrap();
         let res = rt
             .block_on(WriteSandboxFile::new().execute(
-                &TenantContext {
-                    tenant_id: "t".to_string(),
-                    correlation_id: None,
-                    agent_id: None,
-                },
+                &TenantContext::new("t"),
                 Some(serde_json::json!({
                     "path": "report.md",
                     "content": "hello",
@@ -57,6 +57,7 @@ impl AgentSkill for GetAgentMessages {
                     "id": m.id,
                     "from_agent_id": m.from_agent_id,
                     "target_agent_id": m.target_agent_id,
+                    "topic": m.topic,
                     "payload": m.payload,
                     "timestamp_ms": m.timestamp_ms,
                 })
@@ -0,0 +1,154 @@
+//! Graph Query skill: reads and writes the KB-3 knowledge graph within the caller's tenant.
+//!
+//! Payload `action` (default `neighbors`):
+//! - `neighbors` `{ entity, depth? }` – entities within `depth` hops (default 1) and the
+//!   relations between them, plus `facts` lines ("Customer X → interested_in → Service Y")
+//! - `entity` `{ entity }` – one entity and its relations
+//! - `upsert_entity` `{ entity, kind?, name?, properties? }`
+//! - `upsert_relation` `{ subject, predicate, object, properties? }`
+//! - `remove_relation` `{ subject, predicate, object }`
+
+use pagi_core::{AgentSkill, KnowledgeStore, TenantContext};
+use std::sync::Arc;
+
+const SKILL_NAME: &str = "GraphQuery";
+
+/// Entities and relations of the KB-3 knowledge graph.
+pub struct GraphQuery {
+    store: Arc<KnowledgeStore>,
+}
+
+impl GraphQuery {
+    pub fn new(store: Arc<KnowledgeStore>) -> Self {
+        Self { store }
+    }
+}
+
+fn field<'a>(payload: &'a serde_json::Value, name: &str) -> Result<&'a str, Box<dyn std::error::Error + Send + Sync>> {
+    payload
+        .get(name)
+        .and_then(|v| v.as_str())
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .ok_or_else(|| format!("GraphQuery {} required", name).into())
+}
+
+fn properties(payload: &serde_json::Value) -> Result<serde_json::Map<String, serde_json::Value>, Box<dyn std::error::Error + Send + Sync>> {
+    match payload.get("properties") {
+        None | Some(serde_json::Value::Null) => Ok(Default::default()),
+        Some(serde_json::Value::Object(map)) => Ok(map.clone()),
+        Some(_) => Err("GraphQuery properties must be an object".into()),
+    }
+}
+
+#[async_trait::async_trait]
+impl AgentSkill for GraphQuery {
+    fn name(&self) -> &str {
+        SKILL_NAME
+    }
+
+    async fn execute(
+        &self,
+        ctx: &TenantContext,
+        payload: Option<serde_json::Value>,
+    ) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>> {
+        let payload = payload.ok_or("GraphQuery requires payload: { action?, entity | subject, predicate, object }")?;
+        let action = payload.get("action").and_then(|a| a.as_str()).unwrap_or("neighbors");
+        let kb = self.store.tenant(&ctx.tenant_id)?;
+        let result = match action {
+            "neighbors" => {
+                let entity = field(&payload, "entity")?;
+                let depth = payload.get("depth").and_then(|d| d.as_u64()).unwrap_or(1) as usize;
+                match kb.graph_neighbors(entity, depth)? {
+                    Some(neighborhood) => serde_json::json!({
+                        "found": true,
+                        "facts": neighborhood.facts(),
+                        "neighborhood": neighborhood,
+                    }),
+                    None => serde_json::json!({ "found": false, "entity": entity, "facts": [] }),
+                }
+            }
+            "entity" => {
+                let entity = field(&payload, "entity")?;
+                match kb.graph_entity(entity)? {
+                    Some(record) => serde_json::json!({
+                        "found": true,
+                        "entity": record,
+                        "relations": kb.graph_relations(entity)?,
+                    }),
+                    None => serde_json::json!({ "found": false, "entity": entity }),
+                }
+            }
+            "upsert_entity" => {
+                let entity = kb.upsert_entity(
+                    field(&payload, "entity")?,
+                    payload.get("kind").and_then(|k| k.as_str()),
+                    payload.get("name").and_then(|n| n.as_str()),
+                    &properties(&payload)?,
+                )?;
+                serde_json::json!({ "entity": entity })
+            }
+            "upsert_relation" => {
+                let relation = kb.upsert_relation(
+                    field(&payload, "subject")?,
+                    field(&payload, "predicate")?,
+                    field(&payload, "object")?,
+                    &properties(&payload)?,
+                )?;
+                serde_json::json!({ "relation": relation })
+            }
+            "remove_relation" => {
+                let removed =
+                    kb.remove_relation(field(&payload, "subject")?, field(&payload, "predicate")?, field(&payload, "object")?)?;
+                serde_json::json!({ "removed": removed })
+            }
+            other => return Err(format!("GraphQuery action {:?} is not supported", other).into()),
+        };
+        let mut response = serde_json::json!({ "status": "ok", "skill": SKILL_NAME, "action": action });
+        if let (Some(response), serde_json::Value::Object(result)) = (response.as_object_mut(), result) {
+            response.extend(result);
+        }
+        Ok(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ctx(tenant_id: &str) -> TenantContext {
+        TenantContext::new(tenant_id)
+    }
+
+    #[tokio::test]
+    async fn relations_written_through_the_skill_come_back_as_facts() {
+        let store = Arc::new(KnowledgeStore::open_temporary().unwrap());
+        let skill = GraphQuery::new(Arc::clone(&store));
+        let acme = ctx("acme");
+        skill
+            .execute(&acme, Some(serde_json::json!({ "action": "upsert_entity", "entity": "customer-x", "name": "Customer X" })))
+            .await
+            .unwrap();
+        skill
+            .execute(
+                &acme,
+                Some(serde_json::json!({
+                    "action": "upsert_relation",
+                    "subject": "customer-x",
+                    "predicate": "interested_in",
+                    "object": "service-y",
+                })),
+            )
+            .await
+            .unwrap();
+
+        let result = skill.execute(&acme, Some(serde_json::json!({ "entity": "service-y" }))).await.unwrap();
+        assert_eq!(result["found"], true);
+        assert_eq!(result["facts"], serde_json::json!(["Customer X → interested_in → service-y"]));
+        assert_eq!(result["neighborhood"]["entities"][1]["depth"], 1);
+
+        let result = skill.execute(&ctx("beta"), Some(serde_json::json!({ "entity": "service-y" }))).await.unwrap();
+        assert_eq!(result["found"], false);
+        assert!(skill.execute(&acme, Some(serde_json::json!({ "action": "drop" }))).await.is_err());
+    }
+}
@@ -1,4 +1,4 @@
-//! Knowledge Insert skill: writes key-value pairs into a KB slot.
+//! Knowledge Insert skill: writes key-value pairs into a KB slot, within the caller's tenant.
 
 use pagi_core::{AgentSkill, KnowledgeStore, TenantContext};
 use std::sync::Arc;
@@ -24,7 +24,7 @@ impl AgentSkill for KnowledgeInsert {
 
     async fn execute(
         &self,
-        _ctx: &TenantContext,
+        ctx: &TenantContext,
         payload: Option<serde_json::Value>,
     ) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>> {
         let payload = payload.ok_or("KnowledgeInsert requires payload: { slot_id: 1..8, key: string, value: string }")?;
@@ -45,7 +45,7 @@ impl AgentSkill for KnowledgeInsert {
         if !(1..=8).contains(&slot_id) {
             return Err("slot_id must be 1–8".into());
         }
-        self.store.insert(slot_id, &key, value.as_bytes())?;
+        self.store.tenant(&ctx.tenant_id)?.insert(slot_id, &key, value.as_bytes())?;
         Ok(serde_json::json!({
             "status": "ok",
             "skill": SKILL_NAME,
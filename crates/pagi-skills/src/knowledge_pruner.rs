@@ -33,10 +33,8 @@ impl AgentSkill for KnowledgePruner {
         _ctx: &TenantContext,
         payload: Option<serde_json::Value>,
     ) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>> {
-        let now_secs = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
+        // The store's clock, so simulation mode prunes by its frozen time.
+        let now_secs = self.knowledge.clock().now_secs();
 
         let p = payload.as_ref();
         let kb5_max_age_days = p
@@ -1,11 +1,12 @@
-//! Knowledge Query skill: retrieves values from a KB slot by key.
+//! Knowledge Query skill: retrieves values from a KB slot by key, within the caller's tenant.
 
 use pagi_core::{AgentSkill, KnowledgeStore, TenantContext};
 use std::sync::Arc;
 
 const SKILL_NAME: &str = "KnowledgeQuery";
 
-/// Retrieves values from the 8-slot knowledge base via slot_id and query_key.
+/// Retrieves values from the 8-slot knowledge base via slot_id and query_key
+/// (`slot` and `key` are accepted as shorthands).
 pub struct KnowledgeQuery {
     store: Arc<KnowledgeStore>,
 }
@@ -24,16 +25,18 @@ impl AgentSkill for KnowledgeQuery {
 
     async fn execute(
         &self,
-        _ctx: &TenantContext,
+        ctx: &TenantContext,
         payload: Option<serde_json::Value>,
     ) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>> {
         let payload = payload.ok_or("KnowledgeQuery requires payload: { slot_id: 1..8, query_key: string }")?;
         let slot_id = payload
             .get("slot_id")
+            .or_else(|| payload.get("slot"))
             .and_then(|s| s.as_u64())
             .ok_or("slot_id required")? as u8;
         let query_key = payload
             .get("query_key")
+            .or_else(|| payload.get("key"))
             .and_then(|q| q.as_str())
             .ok_or("query_key required")?
             .to_string();
@@ -42,6 +45,7 @@ impl AgentSkill for KnowledgeQuery {
         }
         let value = self
             .store
+            .tenant(&ctx.tenant_id)?
             .get(slot_id, &query_key)?
             .and_then(|v| String::from_utf8(v).ok());
         Ok(serde_json::json!({
@@ -0,0 +1,90 @@
+//! Knowledge Search skill: ranked full-text search over KB record content.
+//!
+//! Where KnowledgeQuery needs the exact key, this finds records by the words in them, across one
+//! slot, a list of slots, or all of 1–8 (BM25 relevance via `KnowledgeStore::text_search`). Only
+//! the caller's tenant's records are searched.
+
+use pagi_core::{AgentSkill, KnowledgeStore, TenantContext, TEXT_SEARCH_DEFAULT_LIMIT};
+use serde::Deserialize;
+use std::sync::Arc;
+
+const SKILL_NAME: &str = "KnowledgeSearch";
+/// Largest `limit` a caller may ask for.
+const MAX_LIMIT: usize = 100;
+/// Characters of content returned per hit.
+const PREVIEW_CHARS: usize = 200;
+
+#[derive(Debug, Deserialize)]
+struct SearchArgs {
+    query: String,
+    /// Single slot to search; shorthand for `slots: [slot_id]`.
+    #[serde(default)]
+    slot_id: Option<u8>,
+    /// Slots to search (empty or absent: 1–8).
+    #[serde(default)]
+    slots: Vec<u8>,
+    #[serde(default)]
+    limit: Option<usize>,
+}
+
+/// Searches record content in the 8-slot knowledge base by query text.
+pub struct KnowledgeSearch {
+    store: Arc<KnowledgeStore>,
+}
+
+impl KnowledgeSearch {
+    pub fn new(store: Arc<KnowledgeStore>) -> Self {
+        Self { store }
+    }
+}
+
+#[async_trait::async_trait]
+impl AgentSkill for KnowledgeSearch {
+    fn name(&self) -> &str {
+        SKILL_NAME
+    }
+
+    async fn execute(
+        &self,
+        ctx: &TenantContext,
+        payload: Option<serde_json::Value>,
+    ) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>> {
+        let payload = payload.ok_or("KnowledgeSearch requires payload: { query: string, slots?: [1..8], limit? }")?;
+        let args: SearchArgs = serde_json::from_value(payload)?;
+        if args.query.trim().is_empty() {
+            return Err("query required".into());
+        }
+        let mut slots = args.slots;
+        slots.extend(args.slot_id);
+        if slots.iter().any(|s| !(1..=8).contains(s)) {
+            return Err("slot ids must be 1–8".into());
+        }
+        slots.sort_unstable();
+        slots.dedup();
+        let limit = args.limit.unwrap_or(TEXT_SEARCH_DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+
+        let hits = self.store.tenant(&ctx.tenant_id)?.text_search(&slots, &args.query, limit)?;
+
+        let results: Vec<serde_json::Value> = hits
+            .into_iter()
+            .map(|hit| {
+                serde_json::json!({
+                    "slot_id": hit.slot_id,
+                    "key": hit.key,
+                    "score": hit.score,
+                    "preview": hit.content.chars().take(PREVIEW_CHARS).collect::<String>(),
+                    "metadata": hit.metadata,
+                    "timestamp": hit.timestamp,
+                })
+            })
+            .collect();
+        Ok(serde_json::json!({
+            "status": "ok",
+            "skill": SKILL_NAME,
+            "query": args.query,
+            "slots": if slots.is_empty() { (1..=8).collect() } else { slots },
+            "result_count": results.len(),
+            "results": results,
+        }))
+    }
+}
@@ -1,11 +1,10 @@
-//! Lead Capture skill: persists customer inquiry payloads under the tenant's Lead History path.
+//! Lead Capture skill: persists customer inquiry payloads as `new` lead records under the
+//! tenant's Lead History path.
 
-use pagi_core::{AgentSkill, MemoryManager, TenantContext};
+use pagi_core::{AgentSkill, LeadRecord, MemoryManager, TenantContext, LEAD_HISTORY_PREFIX};
 use std::sync::Arc;
-use uuid::Uuid;
 
 const SKILL_NAME: &str = "LeadCapture";
-const LEAD_HISTORY_PREFIX: &str = "lead_history";
 
 /// Saves customer inquiry payloads to the tenant's Lead History in pagi-memory.
 pub struct LeadCapture {
@@ -29,15 +28,15 @@ impl AgentSkill for LeadCapture {
         ctx: &TenantContext,
         payload: Option<serde_json::Value>,
     ) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>> {
-        let payload = payload.ok_or("LeadCapture requires a JSON payload (customer inquiry)")?;
-        let lead_id = Uuid::new_v4().to_string();
-        let path = format!("{}/{}/{}", LEAD_HISTORY_PREFIX, ctx.tenant_id, lead_id);
-        let bytes = serde_json::to_vec(&payload)?;
-        self.memory.save_path(ctx, &path, &bytes)?;
+        let Some(serde_json::Value::Object(fields)) = payload else {
+            return Err("LeadCapture requires a JSON object payload (customer inquiry)".into());
+        };
+        let lead = self.memory.put_lead(ctx, LeadRecord::new(fields))?;
+        let path = format!("{}/{}/{}", LEAD_HISTORY_PREFIX, ctx.tenant_id, lead.id);
         Ok(serde_json::json!({
             "status": "saved",
             "skill": SKILL_NAME,
-            "lead_id": lead_id,
+            "lead_id": lead.id,
             "path": path
         }))
     }
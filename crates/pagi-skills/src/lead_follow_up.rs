@@ -0,0 +1,186 @@
+//! Lead Follow-Up skill: drafts follow-up messages for the tenant's leads that have sat in
+//! `contacted` for `stale_days` (default 3) without moving on.
+//!
+//! Each draft is stored on the lead as `follow_up { draft, drafted_at_ms, days_in_status }`; the
+//! lead stays `contacted`. A lead gets one draft per stint in `contacted`: marking it contacted
+//! again restarts the clock. The ModelRouter writes the draft; when it fails a short template is
+//! used. The gateway's Heartbeat runs this for every tenant when `PAGI_LEAD_FOLLOW_UP_DAYS` is set.
+
+use pagi_core::{AgentSkill, LeadFilter, LeadRecord, LeadStatus, MemoryManager, TenantContext, LEAD_MAX_LIMIT};
+use std::sync::Arc;
+
+use crate::model_router::ModelRouter;
+
+const SKILL_NAME: &str = "LeadFollowUp";
+pub const DEFAULT_FOLLOW_UP_DAYS: u64 = 3;
+/// Drafts written per run unless the payload asks for fewer.
+const MAX_DRAFTS_PER_RUN: usize = 10;
+const DAY_MS: i64 = 24 * 60 * 60 * 1000;
+
+fn field<'a>(lead: &'a LeadRecord, path: &[&str]) -> Option<&'a str> {
+    let (first, rest) = path.split_first()?;
+    rest.iter()
+        .try_fold(lead.fields.get(*first)?, |v, field| v.get(*field))
+        .and_then(|v| v.as_str())
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+}
+
+/// Whether `lead` is due a follow-up at `now`: contacted for `stale_days` and no draft yet in
+/// this stint.
+fn is_due(lead: &LeadRecord, stale_days: u64, now: i64) -> bool {
+    let drafted_at = lead
+        .fields
+        .get("follow_up")
+        .and_then(|f| f.get("drafted_at_ms"))
+        .and_then(|t| t.as_i64());
+    lead.status == LeadStatus::Contacted
+        && now - lead.status_changed_at_ms >= stale_days as i64 * DAY_MS
+        && drafted_at.is_none_or(|t| t < lead.status_changed_at_ms)
+}
+
+fn build_follow_up_prompt(lead: &LeadRecord, days: i64) -> String {
+    let mut prompt = format!(
+        "Write a short, friendly follow-up message to a prospective customer we contacted {} day(s) ago and have not heard back from. \
+         Refer to their original request, offer to answer questions, and end with one clear next step. Reply with the message only.\n",
+        days
+    );
+    for (label, path) in [
+        ("Name", &["name"][..]),
+        ("Company", &["company"]),
+        ("Requested service", &["parsed", "requested_service"]),
+        ("Original message", &["message"]),
+    ] {
+        if let Some(value) = field(lead, path) {
+            prompt.push_str(&format!("{}: {}\n", label, value));
+        }
+    }
+    prompt
+}
+
+fn template_follow_up(lead: &LeadRecord) -> String {
+    let greeting = field(lead, &["name"]).map_or_else(|| "Hi,".to_string(), |name| format!("Hi {},", name));
+    let topic = field(lead, &["parsed", "requested_service"]).map_or_else(|| "your inquiry".to_string(), |s| format!("your {} inquiry", s));
+    format!(
+        "{}\n\nJust following up on {}. Do you have any questions I can answer? If it helps, I'm happy to set up a quick call this week.",
+        greeting, topic
+    )
+}
+
+/// Drafts follow-ups for leads stuck in `contacted`.
+pub struct LeadFollowUp {
+    memory: Arc<MemoryManager>,
+    router: Arc<ModelRouter>,
+}
+
+impl LeadFollowUp {
+    pub fn new(memory: Arc<MemoryManager>, router: Arc<ModelRouter>) -> Self {
+        Self { memory, router }
+    }
+
+    async fn draft(&self, ctx: &TenantContext, lead: &LeadRecord, days: i64) -> String {
+        let payload = serde_json::json!({ "prompt": build_follow_up_prompt(lead, days) });
+        match self.router.execute(ctx, Some(payload)).await {
+            Ok(out) => match out.get("generated").and_then(|g| g.as_str()).map(str::trim).filter(|g| !g.is_empty()) {
+                Some(generated) => generated.to_string(),
+                None => template_follow_up(lead),
+            },
+            Err(e) => {
+                tracing::debug!(target: "pagi::skills", lead_id = %lead.id, error = %e, "LeadFollowUp: using template draft");
+                template_follow_up(lead)
+            }
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl AgentSkill for LeadFollowUp {
+    fn name(&self) -> &str {
+        SKILL_NAME
+    }
+
+    async fn execute(
+        &self,
+        ctx: &TenantContext,
+        payload: Option<serde_json::Value>,
+    ) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>> {
+        let payload = payload.unwrap_or(serde_json::Value::Null);
+        let stale_days = payload.get("stale_days").and_then(|d| d.as_u64()).unwrap_or(DEFAULT_FOLLOW_UP_DAYS);
+        let limit = payload
+            .get("limit")
+            .and_then(|l| l.as_u64())
+            .map_or(MAX_DRAFTS_PER_RUN, |l| (l as usize).clamp(1, MAX_DRAFTS_PER_RUN));
+        let now = self.memory.now_ms();
+        let filter = LeadFilter { status: Some(LeadStatus::Contacted), limit: Some(LEAD_MAX_LIMIT), ..LeadFilter::default() };
+
+        let mut due = Vec::new();
+        let mut cursor = None;
+        loop {
+            let page = self.memory.list_leads(ctx, &filter, cursor.as_deref())?;
+            due.extend(page.leads.into_iter().filter(|lead| is_due(lead, stale_days, now)));
+            match page.next_cursor {
+                Some(next) if due.len() < limit => cursor = Some(next),
+                _ => break,
+            }
+        }
+        // Longest-waiting first.
+        due.sort_by_key(|lead| lead.status_changed_at_ms);
+        due.truncate(limit);
+
+        let mut drafted = Vec::new();
+        for mut lead in due {
+            let days = (now - lead.status_changed_at_ms) / DAY_MS;
+            let draft = self.draft(ctx, &lead, days).await;
+            lead.fields.insert(
+                "follow_up".to_string(),
+                serde_json::json!({ "draft": draft, "drafted_at_ms": now, "days_in_status": days }),
+            );
+            let lead = self.memory.put_lead(ctx, lead)?;
+            drafted.push(serde_json::json!({ "lead_id": lead.id, "days_in_status": days, "draft": draft }));
+        }
+        Ok(serde_json::json!({
+            "status": "ok",
+            "skill": SKILL_NAME,
+            "stale_days": stale_days,
+            "count": drafted.len(),
+            "drafted": drafted,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model_router::LlmMode;
+
+    #[tokio::test]
+    async fn drafts_once_for_leads_stuck_in_contacted() {
+        let memory = Arc::new(MemoryManager::open_temporary().unwrap());
+        let ctx = TenantContext::new("acme");
+        let lead = |name: &str, status: LeadStatus, days_ago: i64| {
+            let serde_json::Value::Object(fields) = serde_json::json!({ "name": name, "parsed": { "requested_service": "roof repair" } })
+            else {
+                unreachable!()
+            };
+            LeadRecord { status, status_changed_at_ms: memory.now_ms() - days_ago * DAY_MS, ..LeadRecord::new(fields) }
+        };
+        memory.put_lead(&ctx, lead("Stale", LeadStatus::Contacted, 5)).unwrap();
+        let fresh = memory.put_lead(&ctx, lead("Fresh", LeadStatus::Contacted, 1)).unwrap();
+        memory.put_lead(&ctx, lead("Qualified", LeadStatus::Qualified, 9)).unwrap();
+        let skill = LeadFollowUp::new(Arc::clone(&memory), Arc::new(ModelRouter::with_mode(LlmMode::Mock)));
+
+        let out = skill.execute(&ctx, Some(serde_json::json!({ "stale_days": 3 }))).await.unwrap();
+        assert_eq!(out["count"], 1);
+        assert_eq!(out["drafted"][0]["days_in_status"], 5);
+        let stale_id = out["drafted"][0]["lead_id"].as_str().unwrap();
+        let stored = memory.get_lead(&ctx, stale_id).unwrap().unwrap();
+        assert_eq!(stored.status, LeadStatus::Contacted);
+        assert!(stored.fields["follow_up"]["draft"].as_str().is_some_and(|d| !d.is_empty()));
+
+        // Drafted already this stint; the fresh lead is not due.
+        let out = skill.execute(&ctx, None).await.unwrap();
+        assert_eq!(out["count"], 0);
+        assert!(!is_due(&fresh, 3, memory.now_ms()));
+        assert!(template_follow_up(&fresh).contains("roof repair"));
+    }
+}
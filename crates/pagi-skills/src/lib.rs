@@ -4,29 +4,47 @@ pub use pagi_core::{AgentSkill, SkillRegistry};
 
 mod community_pulse;
 mod community_scraper;
+mod document_ingest;
 mod draft_response;
+mod feed_ingest;
+mod graph_query;
 mod knowledge_insert;
 mod knowledge_pruner;
 mod knowledge_query;
+mod knowledge_search;
 mod lead_capture;
+mod lead_follow_up;
+mod llm_cache;
+mod llm_fixtures;
+mod llm_providers;
+mod llm_quality;
 mod fs_tools;
 mod model_router;
+mod notify_chat;
+mod reembed;
+mod rerank;
 mod analyze_sentiment;
+mod calendar;
 mod check_alignment;
 mod recall_past_actions;
 mod research_semantic;
 mod research_audit;
 mod sales_closer;
+mod send_email;
 mod thalamus;
+mod web_fetch;
 mod message_agent;
 mod get_agent_messages;
 mod biogate_sync;
 mod deep_journal;
 mod ethos_sync;
+mod extract_entities;
 mod journal_skill;
 mod kardia_map;
 mod oikos_task_governor;
+mod parse_inbound_message;
 mod reflect_shadow;
+mod workspace_report;
 
 pub use analyze_sentiment::AnalyzeSentiment;
 pub use biogate_sync::BioGateSync;
@@ -34,17 +52,37 @@ pub use kardia_map::KardiaMap;
 pub use check_alignment::CheckAlignment;
 pub use community_pulse::CommunityPulse;
 pub use community_scraper::CommunityScraper;
+pub use document_ingest::{
+    chunk_text, extract_text, Chunk, DocumentError, DocumentFormat, DocumentIngest, IngestOptions, IngestedDocument,
+    DEFAULT_CHUNK_CHARS, DEFAULT_OVERLAP_CHARS, DOCUMENT_KEY_PREFIX, MAX_DOCUMENT_CHUNKS,
+};
 pub use draft_response::DraftResponse;
+pub use extract_entities::{entity_id, ExtractEntities, Extraction, ExtractedEntity, ExtractedPerson, ExtractedRelation};
+pub use feed_ingest::{FeedIngest, FEED_KEY_PREFIX};
+pub use graph_query::GraphQuery;
 pub use knowledge_insert::KnowledgeInsert;
 pub use knowledge_pruner::KnowledgePruner;
 pub use knowledge_query::KnowledgeQuery;
+pub use knowledge_search::KnowledgeSearch;
 pub use lead_capture::LeadCapture;
-pub use fs_tools::{analyze_workspace, FsWorkspaceAnalyzer, WriteSandboxFile};
-pub use model_router::{LlmMode, ModelRouter};
+pub use lead_follow_up::{LeadFollowUp, DEFAULT_FOLLOW_UP_DAYS};
+pub use fs_tools::{analyze_workspace, FsWorkspaceAnalyzer, PathJail, WriteSandboxFile, SANDBOX_WRITE_SKILL_NAME};
+pub use llm_cache::ResponseCacheConfig;
+pub use llm_fixtures::{MockFixture, MockFixtures};
+pub use llm_providers::{LlmProviders, ProviderHealth, DOWN_AFTER_FAILURES, DOWN_COOLDOWN_SECS};
+pub use llm_quality::{
+    GenerationOutcome, ModelQuality, QualityMonitor, QualityThresholds, LLM_QUALITY_ALERT_TYPE, LLM_QUALITY_SENDER,
+};
+pub use model_router::{trace_context_headers, LlmApiConfig, LlmMode, ModelRouter};
+pub use reembed::{Reembedder, DEFAULT_REEMBED_CONCURRENCY, MAX_REEMBED_CONCURRENCY, REEMBED_BATCH_SIZE};
+pub use rerank::{rerank, RerankScores};
 pub use research_semantic::{ResearchEmbedInsert, ResearchSemanticSearch};
 pub use recall_past_actions::RecallPastActions;
 pub use research_audit::ResearchAudit;
+pub use notify_chat::NotifyChat;
+pub use calendar::CalendarSkill;
 pub use sales_closer::SalesCloser;
+pub use send_email::SendEmail;
 pub use thalamus::{route_information, route_to_ontology, RouteMetadata};
 pub use message_agent::MessageAgent;
 pub use get_agent_messages::GetAgentMessages;
@@ -52,4 +90,9 @@ pub use deep_journal::DeepJournalSkill;
 pub use ethos_sync::EthosSync;
 pub use journal_skill::JournalSkill;
 pub use oikos_task_governor::OikosTaskGovernor;
+pub use parse_inbound_message::{ParseInboundMessage, ParsedMessage};
 pub use reflect_shadow::ReflectShadowSkill;
+pub use workspace_report::{
+    CrateInfo, IgnoreRules, LanguageStats, LargeFile, StaleBranch, WorkspaceReport, WorkspaceScanOptions, DEFAULT_EXCLUDES,
+    MAX_LARGE_FILES, OIKOS_WORKSPACE_SCAN_KEY,
+};
@@ -0,0 +1,69 @@
+//! Exact-match response cache for live LLM generations.
+//!
+//! A generation with the same model, prompts and sampling parameters as one answered within the
+//! last `ttl_secs` gets the stored reply instead of a provider call. Entries live in KB-Soma
+//! (`llm_cache/{hash}`, see [`LlmCacheEntry`]) so they survive restarts and are shared by every
+//! router on the store; at most `max_entries` are kept, oldest evicted first. Cached replies are
+//! not metered against token budgets.
+
+use pagi_core::{KnowledgeStore, LlmCacheEntry};
+use serde::{Deserialize, Serialize};
+
+/// TTL and size bound of the cache. Override with `PAGI_LLM_CACHE` (JSON object, e.g.
+/// `{"ttl_secs": 3600, "max_entries": 1000}`); a zero TTL (the default) disables it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ResponseCacheConfig {
+    pub ttl_secs: u64,
+    pub max_entries: usize,
+}
+
+impl Default for ResponseCacheConfig {
+    fn default() -> Self {
+        Self {
+            ttl_secs: 0,
+            max_entries: 1000,
+        }
+    }
+}
+
+impl ResponseCacheConfig {
+    pub fn from_env() -> Self {
+        match std::env::var("PAGI_LLM_CACHE") {
+            Ok(v) if !v.trim().is_empty() => serde_json::from_str(&v).unwrap_or_else(|e| {
+                tracing::warn!(target: "pagi::model_router", error = %e, "Invalid PAGI_LLM_CACHE; cache disabled");
+                Self::default()
+            }),
+            _ => Self::default(),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.ttl_secs > 0 && self.max_entries > 0
+    }
+
+    /// The unexpired reply cached under `hash`.
+    pub(crate) fn lookup(&self, store: &KnowledgeStore, hash: &str) -> Option<LlmCacheEntry> {
+        if !self.is_enabled() {
+            return None;
+        }
+        store.get_llm_cache(hash)
+    }
+
+    /// Caches a reply under `hash` for `ttl_secs`. Failures are logged; the reply is still returned.
+    pub(crate) fn store(&self, store: &KnowledgeStore, hash: &str, model: &str, response: &str) {
+        if !self.is_enabled() || response.trim().is_empty() {
+            return;
+        }
+        let now_ms = store.now_ms();
+        let entry = LlmCacheEntry {
+            model: model.to_string(),
+            response: response.to_string(),
+            created_at_ms: now_ms,
+            expires_at_ms: now_ms.saturating_add(self.ttl_secs.saturating_mul(1000).min(i64::MAX as u64) as i64),
+        };
+        if let Err(e) = store.put_llm_cache(hash, &entry, self.max_entries) {
+            tracing::warn!(target: "pagi::model_router", error = %e, "[ModelRouter] Failed to cache reply");
+        }
+    }
+}
@@ -0,0 +1,125 @@
+//! Seeded mock LLM replies for simulation mode.
+//!
+//! In mock mode a [`ModelRouter`](crate::ModelRouter) with fixtures answers from a JSON file
+//! instead of its built-in canned text. The first fixture whose every `contains` string appears in
+//! the prompt (case-insensitive) answers; `default` answers the rest, and without one the
+//! built-in mock does. Raw generations (Thalamus classification) skip `default`, so unmatched
+//! prompts keep the built-in keyword classifier. A fixture with several `responses` picks one by
+//! hashing the seed with the prompt, so the same prompt gets the same reply on every run:
+//!
+//! ```json
+//! { "seed": 42,
+//!   "fixtures": [{ "contains": ["pricing"], "responses": ["Plans start at $10.", "From $10 a month."] }],
+//!   "default": ["Thanks, we will follow up shortly."] }
+//! ```
+
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// One canned reply set, chosen by what the prompt contains.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MockFixture {
+    /// Every string must occur in the prompt (case-insensitive); empty matches any prompt.
+    #[serde(default)]
+    pub contains: Vec<String>,
+    pub responses: Vec<String>,
+}
+
+/// Mock replies loaded from a fixtures file (see the module docs).
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MockFixtures {
+    #[serde(default)]
+    pub seed: u64,
+    #[serde(default)]
+    pub fixtures: Vec<MockFixture>,
+    #[serde(default)]
+    pub default: Vec<String>,
+}
+
+impl MockFixtures {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        let fixtures: Self = serde_json::from_str(&text).map_err(|e| format!("{}: {}", path.display(), e))?;
+        if let Some(empty) = fixtures.fixtures.iter().find(|f| f.responses.is_empty()) {
+            return Err(format!("{}: fixture {:?} has no responses", path.display(), empty.contains).into());
+        }
+        Ok(fixtures)
+    }
+
+    /// Replaces the file's seed (`[simulation] seed`).
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// The reply for `prompt`, or None when no fixture matches and there is no default.
+    pub fn reply(&self, prompt: &str) -> Option<&str> {
+        self.pick(prompt, self.matching(prompt).unwrap_or(&self.default))
+    }
+
+    /// The reply of the fixture matching `prompt`, ignoring `default`.
+    pub fn fixture_reply(&self, prompt: &str) -> Option<&str> {
+        self.pick(prompt, self.matching(prompt)?)
+    }
+
+    fn matching(&self, prompt: &str) -> Option<&[String]> {
+        let lower = prompt.to_lowercase();
+        self.fixtures
+            .iter()
+            .find(|f| f.contains.iter().all(|c| lower.contains(&c.to_lowercase())))
+            .map(|f| f.responses.as_slice())
+    }
+
+    fn pick<'a>(&self, prompt: &str, responses: &'a [String]) -> Option<&'a str> {
+        if responses.is_empty() {
+            return None;
+        }
+        let pick = fnv1a_64(self.seed, prompt) % responses.len() as u64;
+        Some(&responses[pick as usize])
+    }
+}
+
+/// FNV-1a over the seed and prompt: stable across builds and platforms, unlike `DefaultHasher`.
+fn fnv1a_64(seed: u64, prompt: &str) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for b in seed.to_le_bytes().iter().chain(prompt.as_bytes()) {
+        hash ^= *b as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixtures() -> MockFixtures {
+        serde_json::from_value(serde_json::json!({
+            "seed": 7,
+            "fixtures": [
+                { "contains": ["Pricing", "plan"], "responses": ["a", "b", "c", "d"] },
+                { "contains": ["hello"], "responses": ["hi"] }
+            ],
+            "default": ["fallback"]
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn replies_are_picked_by_prompt_and_seed() {
+        let f = fixtures();
+        assert_eq!(f.reply("say HELLO"), Some("hi"));
+        assert_eq!(f.reply("anything else"), Some("fallback"));
+        assert_eq!(f.fixture_reply("anything else"), None);
+        let first = f.reply("which pricing plan?").unwrap();
+        assert!(["a", "b", "c", "d"].contains(&first));
+        assert_eq!(f.reply("which pricing plan?"), Some(first));
+        // Another seed can pick another reply, but again the same one every time.
+        let reseeded = f.clone().with_seed(8);
+        assert_eq!(reseeded.reply("which pricing plan?"), reseeded.reply("which pricing plan?"));
+        assert_eq!(MockFixtures::default().reply("x"), None);
+    }
+}
@@ -0,0 +1,309 @@
+//! LLM providers behind model aliases (`[llm_providers.*]` and `[[model_aliases.*]]` in
+//! [`CoreConfig`]).
+//!
+//! A generation whose model names an alias tries the alias's targets in order; the
+//! [`ModelRouter`](crate::ModelRouter) moves to the next target when a provider answers 5xx,
+//! times out, or cannot be reached. Each provider's outcomes are tracked here: after
+//! [`DOWN_AFTER_FAILURES`] failures in a row it is marked down for [`DOWN_COOLDOWN_SECS`] and
+//! tried after the healthy targets until then.
+
+use pagi_core::{system_now_ms, CoreConfig, LlmProviderConfig, LlmProviderKind, ModelTarget};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::{Mutex, PoisonError};
+
+/// Consecutive failures after which a provider is marked down.
+pub const DOWN_AFTER_FAILURES: u32 = 3;
+/// How long a provider stays down before it is tried in its configured place again.
+pub const DOWN_COOLDOWN_SECS: u64 = 60;
+const DEFAULT_AZURE_API_VERSION: &str = "2024-06-01";
+
+fn default_api_url(kind: LlmProviderKind) -> Option<&'static str> {
+    match kind {
+        LlmProviderKind::Openai => Some("https://api.openai.com/v1/chat/completions"),
+        LlmProviderKind::Anthropic => Some("https://api.anthropic.com/v1/messages"),
+        LlmProviderKind::Ollama => Some("http://127.0.0.1:11434/v1/chat/completions"),
+        LlmProviderKind::LlamaCpp => Some("http://127.0.0.1:8080/v1/chat/completions"),
+        LlmProviderKind::Azure => None,
+    }
+}
+
+/// One alias target ready to call.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ResolvedTarget {
+    pub provider: String,
+    pub kind: LlmProviderKind,
+    pub url: String,
+    /// Empty for local providers.
+    pub key: String,
+    pub model: String,
+}
+
+/// Health of one provider, as reported in `/v1/status`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ProviderHealth {
+    pub provider: String,
+    pub kind: LlmProviderKind,
+    /// `"up"`, or `"down"` until `down_until_ms`.
+    pub status: &'static str,
+    pub requests: u64,
+    pub failures: u64,
+    pub consecutive_failures: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_success_ms: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_failure_ms: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub down_until_ms: Option<i64>,
+}
+
+#[derive(Default)]
+struct HealthWindow {
+    requests: u64,
+    failures: u64,
+    consecutive_failures: u32,
+    last_error: Option<String>,
+    last_success_ms: Option<i64>,
+    last_failure_ms: Option<i64>,
+    down_until_ms: Option<i64>,
+}
+
+impl HealthWindow {
+    fn is_down(&self, now_ms: i64) -> bool {
+        self.down_until_ms.is_some_and(|until| now_ms < until)
+    }
+}
+
+/// Configured providers and aliases, with per-provider health. Shared by every router (and the
+/// status endpoint) that should see the same health.
+#[derive(Default)]
+pub struct LlmProviders {
+    providers: HashMap<String, LlmProviderConfig>,
+    aliases: HashMap<String, Vec<ModelTarget>>,
+    health: Mutex<HashMap<String, HealthWindow>>,
+}
+
+impl LlmProviders {
+    pub fn new(providers: HashMap<String, LlmProviderConfig>, aliases: HashMap<String, Vec<ModelTarget>>) -> Self {
+        for (alias, targets) in &aliases {
+            for target in targets.iter().filter(|t| !providers.contains_key(&t.provider)) {
+                tracing::warn!(
+                    target: "pagi::model_router",
+                    alias = %alias,
+                    provider = %target.provider,
+                    "Model alias names an unknown LLM provider; target skipped"
+                );
+            }
+        }
+        Self {
+            providers,
+            aliases,
+            health: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn from_config(config: &CoreConfig) -> Self {
+        Self::new(config.llm_providers.clone(), config.model_aliases.clone())
+    }
+
+    pub fn is_alias(&self, model: &str) -> bool {
+        self.aliases.contains_key(model)
+    }
+
+    /// Targets of `alias` that can be called, healthy providers first (configured order within
+    /// each group). Targets whose provider is unknown, has no endpoint, or whose key variable is
+    /// unset are skipped. None when `alias` is not an alias.
+    pub(crate) fn targets(&self, alias: &str) -> Option<Vec<ResolvedTarget>> {
+        self.targets_at(alias, system_now_ms())
+    }
+
+    fn targets_at(&self, alias: &str, now_ms: i64) -> Option<Vec<ResolvedTarget>> {
+        let targets = self.aliases.get(alias)?;
+        let health = self.health.lock().unwrap_or_else(PoisonError::into_inner);
+        let (mut up, mut down) = (Vec::new(), Vec::new());
+        for target in targets {
+            let Some(resolved) = self.resolve(target) else {
+                continue;
+            };
+            if health.get(&target.provider).is_some_and(|h| h.is_down(now_ms)) {
+                down.push(resolved);
+            } else {
+                up.push(resolved);
+            }
+        }
+        up.append(&mut down);
+        Some(up)
+    }
+
+    fn resolve(&self, target: &ModelTarget) -> Option<ResolvedTarget> {
+        let config = self.providers.get(&target.provider)?;
+        let base = config.api_url.as_deref().or(default_api_url(config.kind));
+        let Some(base) = base else {
+            tracing::warn!(target: "pagi::model_router", provider = %target.provider, "LLM provider has no api_url; skipped");
+            return None;
+        };
+        let url = match config.kind {
+            LlmProviderKind::Azure => format!(
+                "{}/openai/deployments/{}/chat/completions?api-version={}",
+                base.trim_end_matches('/'),
+                target.model,
+                config.api_version.as_deref().unwrap_or(DEFAULT_AZURE_API_VERSION)
+            ),
+            _ => base.to_string(),
+        };
+        let key = match config.api_key_env.as_deref().or(config.kind.default_api_key_env()) {
+            Some(env) => match pagi_core::secret(env) {
+                Some(key) => key,
+                None => {
+                    tracing::warn!(target: "pagi::model_router", provider = %target.provider, env = %env, "LLM provider key is not set; skipped");
+                    return None;
+                }
+            },
+            None => String::new(),
+        };
+        Some(ResolvedTarget {
+            provider: target.provider.clone(),
+            kind: config.kind,
+            url,
+            key,
+            model: target.model.clone(),
+        })
+    }
+
+    pub fn record_success(&self, provider: &str) {
+        let mut health = self.health.lock().unwrap_or_else(PoisonError::into_inner);
+        let window = health.entry(provider.to_string()).or_default();
+        window.requests += 1;
+        window.consecutive_failures = 0;
+        window.down_until_ms = None;
+        window.last_success_ms = Some(system_now_ms());
+    }
+
+    pub fn record_failure(&self, provider: &str, error: &str) {
+        self.record_failure_at(provider, error, system_now_ms())
+    }
+
+    fn record_failure_at(&self, provider: &str, error: &str, now_ms: i64) {
+        let mut health = self.health.lock().unwrap_or_else(PoisonError::into_inner);
+        let window = health.entry(provider.to_string()).or_default();
+        window.requests += 1;
+        window.failures += 1;
+        window.consecutive_failures += 1;
+        window.last_error = Some(error.to_string());
+        window.last_failure_ms = Some(now_ms);
+        if window.consecutive_failures >= DOWN_AFTER_FAILURES && !window.is_down(now_ms) {
+            window.down_until_ms = Some(now_ms + (DOWN_COOLDOWN_SECS * 1000) as i64);
+            tracing::warn!(
+                target: "pagi::model_router",
+                provider = %provider,
+                error = %error,
+                "LLM provider marked down after {} failures in a row",
+                window.consecutive_failures
+            );
+        }
+    }
+
+    /// Health of every configured provider, sorted by name.
+    pub fn health(&self) -> Vec<ProviderHealth> {
+        self.health_at(system_now_ms())
+    }
+
+    fn health_at(&self, now_ms: i64) -> Vec<ProviderHealth> {
+        let health = self.health.lock().unwrap_or_else(PoisonError::into_inner);
+        let empty = HealthWindow::default();
+        let mut out: Vec<ProviderHealth> = self
+            .providers
+            .iter()
+            .map(|(name, config)| {
+                let w = health.get(name).unwrap_or(&empty);
+                let down = w.is_down(now_ms);
+                ProviderHealth {
+                    provider: name.clone(),
+                    kind: config.kind,
+                    status: if down { "down" } else { "up" },
+                    requests: w.requests,
+                    failures: w.failures,
+                    consecutive_failures: w.consecutive_failures,
+                    last_error: w.last_error.clone(),
+                    last_success_ms: w.last_success_ms,
+                    last_failure_ms: w.last_failure_ms,
+                    down_until_ms: w.down_until_ms.filter(|_| down),
+                }
+            })
+            .collect();
+        out.sort_by(|a, b| a.provider.cmp(&b.provider));
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn providers() -> LlmProviders {
+        let provider = |kind, api_url: Option<&str>| LlmProviderConfig {
+            kind,
+            api_url: api_url.map(str::to_string),
+            api_key_env: None,
+            api_version: None,
+        };
+        let target = |provider: &str, model: &str| ModelTarget {
+            provider: provider.to_string(),
+            model: model.to_string(),
+        };
+        LlmProviders::new(
+            [
+                ("local".to_string(), provider(LlmProviderKind::Ollama, None)),
+                ("azure".to_string(), provider(LlmProviderKind::Azure, Some("https://res.openai.azure.com/"))),
+                ("nowhere".to_string(), provider(LlmProviderKind::Azure, None)),
+            ]
+            .into_iter()
+            .collect(),
+            [(
+                "smart".to_string(),
+                vec![target("local", "llama3"), target("nowhere", "x"), target("missing", "y"), target("azure", "gpt4o")],
+            )]
+            .into_iter()
+            .collect(),
+        )
+    }
+
+    #[test]
+    fn targets_resolve_endpoints_and_skip_unusable_ones() {
+        std::env::set_var("AZURE_OPENAI_API_KEY", "az-key");
+        let providers = providers();
+        assert!(providers.targets("gpt-4o").is_none());
+        let targets = providers.targets("smart").unwrap();
+        let names: Vec<&str> = targets.iter().map(|t| t.provider.as_str()).collect();
+        assert_eq!(names, ["local", "azure"]);
+        assert_eq!(targets[0].url, "http://127.0.0.1:11434/v1/chat/completions");
+        assert_eq!(targets[0].key, "");
+        assert_eq!(
+            targets[1].url,
+            "https://res.openai.azure.com/openai/deployments/gpt4o/chat/completions?api-version=2024-06-01"
+        );
+        assert_eq!(targets[1].key, "az-key");
+    }
+
+    #[test]
+    fn failing_provider_goes_down_then_last_then_recovers() {
+        std::env::set_var("AZURE_OPENAI_API_KEY", "az-key");
+        let providers = providers();
+        let now = 1_000_000;
+        for _ in 0..DOWN_AFTER_FAILURES {
+            providers.record_failure_at("local", "HTTP 503", now);
+        }
+        let order = |at| -> Vec<String> { providers.targets_at("smart", at).unwrap().into_iter().map(|t| t.provider).collect() };
+        assert_eq!(order(now), ["azure", "local"]);
+        let local = providers.health_at(now).into_iter().find(|h| h.provider == "local").unwrap();
+        assert_eq!((local.status, local.failures, local.last_error.as_deref()), ("down", 3, Some("HTTP 503")));
+
+        let later = now + (DOWN_COOLDOWN_SECS * 1000) as i64;
+        assert_eq!(order(later), ["local", "azure"]);
+        providers.record_success("local");
+        let local = providers.health_at(later).into_iter().find(|h| h.provider == "local").unwrap();
+        assert_eq!((local.status, local.consecutive_failures, local.requests), ("up", 0, 4));
+    }
+}
@@ -0,0 +1,240 @@
+//! Rolling-window quality monitor for live LLM generations.
+//!
+//! Every live generation is recorded per model as ok, failed (transport or API error), empty, or
+//! blocked (rejected by post-processing, e.g. JSON mode replies that do not parse). When a model's
+//! rates over its recent window exceed the [`QualityThresholds`] it is marked degraded for
+//! `cooldown_secs`: the [`ModelRouter`](crate::ModelRouter) sends its traffic to the fallback
+//! provider and operators are alerted. After the cooldown the model is tried again with a fresh window.
+
+use pagi_core::{system_now_ms, AlertTarget};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Mutex, PoisonError};
+
+/// Sender id used for inbox alerts.
+pub const LLM_QUALITY_SENDER: &str = "llm_quality";
+/// `payload.type` of inbox alerts (the Heartbeat does not auto-reply to these).
+pub const LLM_QUALITY_ALERT_TYPE: &str = "llm_quality_alert";
+
+/// Result of one live generation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GenerationOutcome {
+    Ok,
+    Failed,
+    Empty,
+    Blocked,
+}
+
+/// Window size, rate limits, and alert target. Override with `PAGI_LLM_QUALITY` (JSON object).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct QualityThresholds {
+    /// Recent generations kept per model.
+    pub window: usize,
+    /// Generations in the window before rates are judged.
+    pub min_samples: usize,
+    pub max_failure_rate: f64,
+    pub max_empty_rate: f64,
+    pub max_blocked_rate: f64,
+    /// How long a degraded model stays on the fallback provider.
+    pub cooldown_secs: u64,
+    /// Where degradation alerts go in addition to the log (agent inbox or webhook).
+    pub notify: Option<AlertTarget>,
+}
+
+impl Default for QualityThresholds {
+    fn default() -> Self {
+        Self {
+            window: 20,
+            min_samples: 5,
+            max_failure_rate: 0.5,
+            max_empty_rate: 0.3,
+            max_blocked_rate: 0.3,
+            cooldown_secs: 300,
+            notify: None,
+        }
+    }
+}
+
+impl QualityThresholds {
+    /// Defaults overridden by `PAGI_LLM_QUALITY` (JSON object).
+    pub fn from_env() -> Self {
+        match std::env::var("PAGI_LLM_QUALITY") {
+            Ok(v) if !v.trim().is_empty() => serde_json::from_str(&v).unwrap_or_else(|e| {
+                tracing::warn!(target: "pagi::model_router", error = %e, "Invalid PAGI_LLM_QUALITY; using defaults");
+                Self::default()
+            }),
+            _ => Self::default(),
+        }
+    }
+}
+
+/// Rolling quality of one model.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ModelQuality {
+    pub model: String,
+    pub samples: usize,
+    pub failure_rate: f64,
+    pub empty_rate: f64,
+    pub blocked_rate: f64,
+    /// Set while the model is degraded (traffic goes to the fallback provider).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub degraded_until_ms: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub degraded_reason: Option<String>,
+}
+
+#[derive(Default)]
+struct ModelWindow {
+    recent: VecDeque<GenerationOutcome>,
+    degraded_until_ms: Option<i64>,
+    degraded_reason: Option<String>,
+}
+
+impl ModelWindow {
+    fn rate(&self, outcome: GenerationOutcome) -> f64 {
+        if self.recent.is_empty() {
+            return 0.0;
+        }
+        self.recent.iter().filter(|o| **o == outcome).count() as f64 / self.recent.len() as f64
+    }
+
+    /// Ends an expired degradation: the model gets a fresh window.
+    fn expire(&mut self, now_ms: i64) {
+        if self.degraded_until_ms.is_some_and(|until| now_ms >= until) {
+            *self = Self::default();
+        }
+    }
+
+    fn quality(&self, model: &str) -> ModelQuality {
+        ModelQuality {
+            model: model.to_string(),
+            samples: self.recent.len(),
+            failure_rate: self.rate(GenerationOutcome::Failed),
+            empty_rate: self.rate(GenerationOutcome::Empty),
+            blocked_rate: self.rate(GenerationOutcome::Blocked),
+            degraded_until_ms: self.degraded_until_ms,
+            degraded_reason: self.degraded_reason.clone(),
+        }
+    }
+}
+
+/// Per-model rolling windows. Shared by every router that should see the same provider health.
+#[derive(Default)]
+pub struct QualityMonitor {
+    thresholds: QualityThresholds,
+    models: Mutex<HashMap<String, ModelWindow>>,
+}
+
+impl QualityMonitor {
+    pub fn new(thresholds: QualityThresholds) -> Self {
+        Self {
+            thresholds,
+            models: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn thresholds(&self) -> &QualityThresholds {
+        &self.thresholds
+    }
+
+    /// Records a generation. Returns the model's quality when this sample tipped it into the
+    /// degraded state (the caller alerts and switches to the fallback).
+    pub fn record(&self, model: &str, outcome: GenerationOutcome) -> Option<ModelQuality> {
+        self.record_at(model, outcome, system_now_ms())
+    }
+
+    /// [`record`](Self::record) at an explicit time (for tests and replays).
+    pub fn record_at(&self, model: &str, outcome: GenerationOutcome, now_ms: i64) -> Option<ModelQuality> {
+        let t = &self.thresholds;
+        let mut models = self.models.lock().unwrap_or_else(PoisonError::into_inner);
+        let window = models.entry(model.to_string()).or_default();
+        window.expire(now_ms);
+        if window.recent.len() >= t.window.max(1) {
+            window.recent.pop_front();
+        }
+        window.recent.push_back(outcome);
+        if window.degraded_until_ms.is_some() || window.recent.len() < t.min_samples {
+            return None;
+        }
+        let checks = [
+            ("failure", GenerationOutcome::Failed, t.max_failure_rate),
+            ("empty response", GenerationOutcome::Empty, t.max_empty_rate),
+            ("post-processing block", GenerationOutcome::Blocked, t.max_blocked_rate),
+        ];
+        let reason = checks.iter().find_map(|(label, kind, max)| {
+            let rate = window.rate(*kind);
+            (rate > *max).then(|| format!("{} rate {:.0}% exceeds {:.0}%", label, rate * 100.0, max * 100.0))
+        })?;
+        window.degraded_until_ms = Some(now_ms + (t.cooldown_secs * 1000) as i64);
+        window.degraded_reason = Some(reason);
+        Some(window.quality(model))
+    }
+
+    /// True while `model` is within a degradation cooldown.
+    pub fn is_degraded(&self, model: &str) -> bool {
+        self.is_degraded_at(model, system_now_ms())
+    }
+
+    pub fn is_degraded_at(&self, model: &str, now_ms: i64) -> bool {
+        let mut models = self.models.lock().unwrap_or_else(PoisonError::into_inner);
+        models.get_mut(model).is_some_and(|w| {
+            w.expire(now_ms);
+            w.degraded_until_ms.is_some()
+        })
+    }
+
+    /// Quality of every model seen so far, sorted by name.
+    pub fn snapshot(&self) -> Vec<ModelQuality> {
+        let models = self.models.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut out: Vec<ModelQuality> = models.iter().map(|(m, w)| w.quality(m)).collect();
+        out.sort_by(|a, b| a.model.cmp(&b.model));
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn monitor() -> QualityMonitor {
+        QualityMonitor::new(QualityThresholds {
+            window: 10,
+            min_samples: 4,
+            cooldown_secs: 60,
+            ..QualityThresholds::default()
+        })
+    }
+
+    #[test]
+    fn trips_once_rates_exceed_thresholds_and_recovers_after_cooldown() {
+        let m = monitor();
+        for _ in 0..3 {
+            assert!(m.record_at("a", GenerationOutcome::Failed, 0).is_none(), "below min_samples");
+        }
+        let tripped = m.record_at("a", GenerationOutcome::Failed, 0).expect("failure rate 100%");
+        assert_eq!(tripped.degraded_until_ms, Some(60_000));
+        assert!(tripped.degraded_reason.unwrap().starts_with("failure rate"));
+        assert!(m.is_degraded_at("a", 59_999));
+        // Already degraded: no second alert.
+        assert!(m.record_at("a", GenerationOutcome::Failed, 1).is_none());
+
+        assert!(!m.is_degraded_at("a", 60_000));
+        assert_eq!(m.snapshot()[0].samples, 0);
+    }
+
+    #[test]
+    fn empty_and_blocked_rates_are_tracked_per_model() {
+        let m = monitor();
+        for outcome in [GenerationOutcome::Ok, GenerationOutcome::Ok, GenerationOutcome::Empty] {
+            m.record_at("a", outcome, 0);
+            m.record_at("b", GenerationOutcome::Blocked, 0);
+        }
+        let tripped = m.record_at("a", GenerationOutcome::Empty, 0).expect("empty rate 50% > 30%");
+        assert!(tripped.degraded_reason.unwrap().starts_with("empty response rate 50%"));
+        let tripped = m.record_at("b", GenerationOutcome::Blocked, 0).unwrap();
+        assert!(tripped.degraded_reason.unwrap().starts_with("post-processing block"));
+        assert!(!m.is_degraded_at("c", 0));
+    }
+}
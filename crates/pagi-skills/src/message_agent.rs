@@ -1,7 +1,8 @@
-//! Message Agent skill: send a JSON payload to another agent's inbox (KB_SOMA).
+//! Message Agent skill: send a JSON payload to another agent's inbox (KB_SOMA), or publish it
+//! to a topic that the Heartbeat fans out to every subscribed agent.
 //!
 //! Enables inter-agent communication for multi-agent workflows. The sender is
-//! the current agent (from TenantContext); the target is specified in the payload.
+//! the current agent (from TenantContext); the target agent or topic is specified in the payload.
 
 use pagi_core::{AgentSkill, KnowledgeStore, TenantContext};
 use serde::Deserialize;
@@ -12,12 +13,16 @@ const SKILL_NAME: &str = "message_agent";
 #[derive(Debug, Deserialize)]
 struct MessageAgentArgs {
     /// Target agent id (e.g. "auditor", "developer").
-    target_agent_id: String,
+    #[serde(default)]
+    target_agent_id: Option<String>,
+    /// Topic to publish to instead (e.g. "maintenance").
+    #[serde(default)]
+    topic: Option<String>,
     /// JSON payload to deliver (object, string, or array).
     message: serde_json::Value,
 }
 
-/// Sends a message to another agent's inbox in KB_SOMA.
+/// Sends a message to another agent's inbox in KB_SOMA, or publishes it to a topic.
 pub struct MessageAgent {
     store: Arc<KnowledgeStore>,
 }
@@ -41,12 +46,26 @@ impl AgentSkill for MessageAgent {
     ) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>> {
         let args: MessageAgentArgs = payload
             .and_then(|v| serde_json::from_value(v).ok())
-            .ok_or("message_agent requires { target_agent_id, message }")?;
-        let target = args.target_agent_id.trim();
+            .ok_or("message_agent requires { target_agent_id | topic, message }")?;
+        let from_id = ctx.resolved_agent_id();
+        if let Some(topic) = args.topic.as_deref().map(str::trim).filter(|t| !t.is_empty()) {
+            if args.target_agent_id.is_some() {
+                return Err("message_agent takes either target_agent_id or topic, not both".into());
+            }
+            let message_id = self.store.publish_topic_message(from_id, topic, &args.message)?;
+            return Ok(serde_json::json!({
+                "status": "ok",
+                "skill": SKILL_NAME,
+                "message_id": message_id,
+                "from_agent_id": from_id,
+                "topic": topic,
+                "subscribers": self.store.topic_subscribers(topic)?.len(),
+            }));
+        }
+        let target = args.target_agent_id.as_deref().unwrap_or_default().trim();
         if target.is_empty() {
-            return Err("target_agent_id is required".into());
+            return Err("target_agent_id or topic is required".into());
         }
-        let from_id = ctx.resolved_agent_id();
         let message_id = self
             .store
             .push_agent_message(from_id, target, &args.message)?;
@@ -1,22 +1,55 @@
 //! Model Router skill: sends contextual prompt to an LLM (mock or live API) and returns generated text.
-//! Supports both non-streaming (JSON response) and streaming (SSE) modes.
-
-use pagi_core::{AgentSkill, KnowledgeStore, TenantContext};
+//! Supports both non-streaming (JSON response) and streaming (SSE) modes. Models named by a model
+//! alias go through the alias's providers with failover (see [`LlmProviders`]). With a knowledge
+//! store attached, live generations count against the tenant's monthly token budget, and
+//! identical generations can be answered from the response cache (see [`ResponseCacheConfig`]).
+//! With a [`Redactor`] attached, PII in prompts is masked before any live provider sees it.
+
+use crate::llm_cache::ResponseCacheConfig;
+use crate::llm_fixtures::MockFixtures;
+use crate::llm_providers::{LlmProviders, ResolvedTarget};
+use crate::llm_quality::{
+    GenerationOutcome, ModelQuality, QualityMonitor, QualityThresholds, LLM_QUALITY_ALERT_TYPE, LLM_QUALITY_SENDER,
+};
+use pagi_core::{
+    usage_month, AgentSkill, AlertTarget, GenerationAdjustment, GenerationModulation, KnowledgeStore, LlmCacheEntry,
+    LlmProviderKind, RedactionReport, Redactor, TenantContext, TokenBudgetConfig, TokenBudgetExceeded,
+};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use tokio::sync::mpsc;
 
 const SKILL_NAME: &str = "ModelRouter";
+
+/// System prompt of the PII classifier (`[redaction] classifier_model`).
+const PII_CLASSIFIER_PROMPT: &str = "List the personal data in the user's text: names of people, postal addresses, \
+     dates of birth, account or card numbers, and other details that identify a person. Copy each one exactly as it \
+     appears. Answer with a JSON object only: {\"pii\": [\"...\"]}, with an empty list when there is none.";
 const ENV_LLM_MODE: &str = "PAGI_LLM_MODE";
 const ENV_LLM_API_URL: &str = "PAGI_LLM_API_URL";
 const ENV_LLM_API_KEY: &str = "PAGI_LLM_API_KEY";
 const ENV_LLM_MODEL: &str = "PAGI_LLM_MODEL";
+const ENV_LLM_FALLBACK_API_URL: &str = "PAGI_LLM_FALLBACK_API_URL";
+const ENV_LLM_FALLBACK_API_KEY_ENV: &str = "PAGI_LLM_FALLBACK_API_KEY_ENV";
+const ENV_LLM_FALLBACK_MODEL: &str = "PAGI_LLM_FALLBACK_MODEL";
+const ENV_LLM_TIMEOUT_SECS: &str = "PAGI_LLM_TIMEOUT_SECS";
 const ENV_EMBEDDINGS_API_URL: &str = "PAGI_EMBEDDINGS_API_URL";
 const ENV_EMBEDDINGS_MODEL: &str = "PAGI_EMBEDDINGS_MODEL";
 const DEFAULT_API_URL: &str = "https://openrouter.ai/api/v1/chat/completions";
 const DEFAULT_EMBEDDINGS_API_URL: &str = "https://openrouter.ai/api/v1/embeddings";
 const DEFAULT_MODEL: &str = "deepseek/deepseek-v3.2";
+/// Per-call limit for live generations (whole response, or whole stream), before the request
+/// deadline shortens it.
+const DEFAULT_LLM_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(120);
 const DEFAULT_EMBEDDINGS_MODEL: &str = "text-embedding-3-small";
+const MOCK_EMBEDDING_MODEL: &str = "mock";
+const ANTHROPIC_VERSION: &str = "2023-06-01";
+/// `max_tokens` is required by the Anthropic Messages API.
+const ANTHROPIC_DEFAULT_MAX_TOKENS: u32 = 1024;
+/// Stand-in text when a live reply has no content (never cached).
+const NO_RESPONSE: &str = "[No response from LLM]";
+/// Characters per token when estimating usage a provider did not report.
+const CHARS_PER_TOKEN: usize = 4;
 
 /// Mode for LLM invocation: mock (returns simulated generation) or live (calls external API).
 #[derive(Clone, Copy, Debug, Default)]
@@ -46,12 +79,25 @@ struct ChatRequest {
     max_tokens: Option<u32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     stream: Option<bool>,
+    /// `{"type": "json_object"}` for JSON mode.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    response_format: Option<serde_json::Value>,
+    /// `{"include_usage": true}` when streaming, so the last chunk reports token usage.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stream_options: Option<serde_json::Value>,
+    /// Function definitions the model may call (tool mode), in OpenAI format.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tools: Option<Vec<serde_json::Value>>,
 }
 
 /// Streaming chunk from OpenAI-compatible API (SSE data format)
 #[derive(Deserialize, Debug)]
 struct StreamChunk {
+    #[serde(default)]
     choices: Vec<StreamChoice>,
+    /// Sent on the final chunk (with empty `choices`) when `stream_options.include_usage` is set.
+    #[serde(default)]
+    usage: Option<TokenUsage>,
 }
 
 #[derive(Deserialize, Debug)]
@@ -71,6 +117,23 @@ struct StreamDelta {
 struct ChatMessage {
     role: String,
     content: String,
+    /// Tools called by an assistant turn, in OpenAI format (tool mode).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool_calls: Option<Vec<serde_json::Value>>,
+    /// The call a `tool` message answers.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool_call_id: Option<String>,
+}
+
+impl ChatMessage {
+    fn new(role: &str, content: impl Into<String>) -> Self {
+        Self {
+            role: role.to_string(),
+            content: content.into(),
+            tool_calls: None,
+            tool_call_id: None,
+        }
+    }
 }
 
 // OpenAI-compatible embeddings request/response structures
@@ -104,10 +167,29 @@ struct ChatChoice {
 
 #[derive(Deserialize)]
 struct ChatMessageResponse {
-    content: String,
+    /// Null when the model only called tools.
+    #[serde(default)]
+    content: Option<String>,
+    #[serde(default)]
+    tool_calls: Vec<ToolCallResponse>,
 }
 
-#[derive(Deserialize, Default)]
+#[derive(Deserialize)]
+struct ToolCallResponse {
+    #[serde(default)]
+    id: String,
+    function: FunctionCallResponse,
+}
+
+#[derive(Deserialize)]
+struct FunctionCallResponse {
+    name: String,
+    /// JSON-encoded arguments.
+    #[serde(default)]
+    arguments: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
 struct TokenUsage {
     #[serde(default)]
     prompt_tokens: u32,
@@ -117,11 +199,406 @@ struct TokenUsage {
     total_tokens: u32,
 }
 
+impl TokenUsage {
+    /// Folds in counts reported part by part (Anthropic streams prompt and completion separately).
+    fn merge(&mut self, update: TokenUsage) {
+        if update.prompt_tokens > 0 {
+            self.prompt_tokens = update.prompt_tokens;
+        }
+        if update.completion_tokens > 0 {
+            self.completion_tokens = update.completion_tokens;
+        }
+        self.total_tokens = update.total_tokens.max(self.prompt_tokens + self.completion_tokens);
+    }
+}
+
+/// Rough token count of text whose usage the provider did not report.
+fn estimate_tokens(text: &str) -> u64 {
+    text.chars().count().div_ceil(CHARS_PER_TOKEN) as u64
+}
+
+/// Records a live generation's tokens in the tenant's monthly usage (KB-Soma).
+struct UsageMeter {
+    store: Arc<KnowledgeStore>,
+    tenant_id: String,
+    agent_id: String,
+    /// Used when the provider reports no usage (e.g. streams cut short).
+    estimated_prompt_tokens: u64,
+}
+
+impl UsageMeter {
+    fn record(&self, usage: Option<&TokenUsage>, generated: &str) {
+        let (prompt, completion, estimated) = match usage {
+            Some(u) => (u64::from(u.prompt_tokens), u64::from(u.completion_tokens), false),
+            None => (self.estimated_prompt_tokens, estimate_tokens(generated), true),
+        };
+        if let Err(e) = self
+            .store
+            .record_token_usage(&self.tenant_id, &self.agent_id, prompt, completion, estimated)
+        {
+            tracing::warn!(
+                target: "pagi::model_router",
+                tenant_id = %self.tenant_id,
+                error = %e,
+                "[ModelRouter] Failed to record token usage"
+            );
+        }
+    }
+}
+
+// Anthropic Messages API request/response structures
+#[derive(Serialize)]
+struct AnthropicRequest<'a> {
+    model: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    system: Option<&'a str>,
+    messages: Vec<serde_json::Value>,
+    max_tokens: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stream: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tools: Option<Vec<serde_json::Value>>,
+}
+
+/// Non-system messages in Anthropic format: assistant tool calls become `tool_use` blocks and
+/// tool results `tool_result` blocks of a user turn (consecutive results share one turn).
+fn anthropic_messages(messages: &[ChatMessage]) -> Vec<serde_json::Value> {
+    let mut out: Vec<serde_json::Value> = Vec::new();
+    for m in messages.iter().filter(|m| m.role != "system") {
+        if m.role == "tool" {
+            let block = serde_json::json!({
+                "type": "tool_result",
+                "tool_use_id": m.tool_call_id,
+                "content": m.content,
+            });
+            let previous = out.last_mut().filter(|p| p["role"] == "user" && p["content"].is_array());
+            match previous.and_then(|p| p["content"].as_array_mut()) {
+                Some(blocks) => blocks.push(block),
+                None => out.push(serde_json::json!({ "role": "user", "content": [block] })),
+            }
+            continue;
+        }
+        let Some(calls) = &m.tool_calls else {
+            out.push(serde_json::json!({ "role": m.role, "content": m.content }));
+            continue;
+        };
+        let mut blocks = Vec::new();
+        if !m.content.is_empty() {
+            blocks.push(serde_json::json!({ "type": "text", "text": m.content }));
+        }
+        for call in calls {
+            let arguments = call["function"]["arguments"].as_str().unwrap_or("{}");
+            blocks.push(serde_json::json!({
+                "type": "tool_use",
+                "id": call["id"],
+                "name": call["function"]["name"],
+                "input": serde_json::from_str::<serde_json::Value>(arguments).unwrap_or_else(|_| serde_json::json!({})),
+            }));
+        }
+        out.push(serde_json::json!({ "role": m.role, "content": blocks }));
+    }
+    out
+}
+
+#[derive(Deserialize, Default)]
+struct AnthropicResponse {
+    #[serde(default)]
+    content: Vec<AnthropicContent>,
+    #[serde(default)]
+    usage: Option<AnthropicUsage>,
+}
+
+/// A content block: `text` for text blocks, `id` / `name` / `input` for `tool_use` blocks.
+#[derive(Deserialize)]
+struct AnthropicContent {
+    #[serde(default)]
+    text: Option<String>,
+    #[serde(default)]
+    id: Option<String>,
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    input: Option<serde_json::Value>,
+}
+
+#[derive(Deserialize, Default)]
+struct AnthropicUsage {
+    #[serde(default)]
+    input_tokens: u32,
+    #[serde(default)]
+    output_tokens: u32,
+}
+
+impl From<AnthropicUsage> for TokenUsage {
+    fn from(u: AnthropicUsage) -> Self {
+        Self {
+            prompt_tokens: u.input_tokens,
+            completion_tokens: u.output_tokens,
+            total_tokens: u.input_tokens + u.output_tokens,
+        }
+    }
+}
+
+impl From<AnthropicResponse> for ChatResponse {
+    fn from(r: AnthropicResponse) -> Self {
+        let mut text = String::new();
+        let mut tool_calls = Vec::new();
+        for block in r.content {
+            match (block.text, block.name) {
+                (Some(t), _) => text.push_str(&t),
+                (None, Some(name)) => tool_calls.push(ToolCallResponse {
+                    id: block.id.unwrap_or_default(),
+                    function: FunctionCallResponse {
+                        name,
+                        arguments: block.input.unwrap_or_else(|| serde_json::json!({})).to_string(),
+                    },
+                }),
+                (None, None) => {}
+            }
+        }
+        Self {
+            choices: vec![ChatChoice {
+                message: ChatMessageResponse {
+                    content: Some(text),
+                    tool_calls,
+                },
+            }],
+            usage: r.usage.map(TokenUsage::from),
+        }
+    }
+}
+
+/// One server-sent event of an Anthropic stream.
+#[derive(Deserialize)]
+struct AnthropicEvent {
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    delta: Option<AnthropicDelta>,
+    /// `message_start`: the message so far, with the prompt's usage.
+    #[serde(default)]
+    message: Option<AnthropicResponse>,
+    /// `message_delta`: output tokens so far.
+    #[serde(default)]
+    usage: Option<AnthropicUsage>,
+}
+
+#[derive(Deserialize)]
+struct AnthropicDelta {
+    #[serde(default)]
+    text: Option<String>,
+}
+
+/// What one SSE `data:` line of a stream carries, whatever the provider's format.
+#[derive(Default)]
+struct StreamUpdate {
+    delta: Option<String>,
+    usage: Option<TokenUsage>,
+    done: bool,
+}
+
+impl StreamUpdate {
+    /// None when `data` does not parse.
+    fn parse(kind: LlmProviderKind, data: &str) -> Option<Self> {
+        if kind == LlmProviderKind::Anthropic {
+            let event: AnthropicEvent = serde_json::from_str(data).ok()?;
+            return Some(match event.kind.as_str() {
+                "message_start" => Self {
+                    usage: event.message.and_then(|m| m.usage).map(TokenUsage::from),
+                    ..Self::default()
+                },
+                "content_block_delta" => Self {
+                    delta: event.delta.and_then(|d| d.text),
+                    ..Self::default()
+                },
+                "message_delta" => Self {
+                    usage: event.usage.map(TokenUsage::from),
+                    ..Self::default()
+                },
+                "message_stop" => Self { done: true, ..Self::default() },
+                _ => Self::default(),
+            });
+        }
+        if data == "[DONE]" {
+            return Some(Self { done: true, ..Self::default() });
+        }
+        let chunk: StreamChunk = serde_json::from_str(data).ok()?;
+        Some(Self {
+            delta: chunk.choices.into_iter().next().and_then(|c| c.delta.content),
+            usage: chunk.usage,
+            done: false,
+        })
+    }
+}
+
+/// Per-instance overrides for the OpenAI-compatible API (e.g. a tenant's own key). Unset fields
+/// fall back to `PAGI_LLM_API_URL` / `PAGI_LLM_API_KEY` / `PAGI_LLM_MODEL`.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct LlmApiConfig {
+    pub api_url: Option<String>,
+    /// Literal key. Prefer `api_key_env` so keys stay out of config files.
+    pub api_key: Option<String>,
+    /// Name of the secret holding the key (see `[secrets]`).
+    pub api_key_env: Option<String>,
+    pub model: Option<String>,
+}
+
+impl LlmApiConfig {
+    /// Fallback provider from `PAGI_LLM_FALLBACK_API_URL` / `PAGI_LLM_FALLBACK_API_KEY_ENV` /
+    /// `PAGI_LLM_FALLBACK_MODEL`; None when none is set.
+    fn fallback_from_env() -> Option<Self> {
+        let var = |name| std::env::var(name).ok().filter(|v: &String| !v.trim().is_empty());
+        let api = Self {
+            api_url: var(ENV_LLM_FALLBACK_API_URL),
+            api_key: None,
+            api_key_env: var(ENV_LLM_FALLBACK_API_KEY_ENV),
+            model: var(ENV_LLM_FALLBACK_MODEL),
+        };
+        (api.api_url.is_some() || api.api_key_env.is_some() || api.model.is_some()).then_some(api)
+    }
+}
+
+/// An open live stream. `usage` resolves once the stream ends (or errors when the provider
+/// reported none).
+struct LiveStream {
+    tokens: mpsc::Receiver<String>,
+    model: String,
+    usage: tokio::sync::oneshot::Receiver<TokenUsage>,
+}
+
+/// Endpoint, key, and model for one live call.
+struct Route {
+    kind: LlmProviderKind,
+    url: String,
+    /// Empty for providers that take none (local servers).
+    key: String,
+    model: String,
+    /// True when the primary model is degraded and the call goes to the fallback provider.
+    fallback: bool,
+    /// Provider name when the model is an alias target (health is tracked per provider).
+    provider: Option<String>,
+}
+
+impl From<ResolvedTarget> for Route {
+    fn from(t: ResolvedTarget) -> Self {
+        Self {
+            kind: t.kind,
+            url: t.url,
+            key: t.key,
+            model: t.model,
+            fallback: false,
+            provider: Some(t.provider),
+        }
+    }
+}
+
+impl Route {
+    /// Name used in logs and errors.
+    fn label(&self) -> &str {
+        self.provider.as_deref().unwrap_or("OpenRouter")
+    }
+
+    /// Quality window key: fallback traffic is tracked apart from the degraded primary.
+    fn quality_key(&self) -> String {
+        if self.fallback {
+            format!("fallback:{}", self.model)
+        } else {
+            self.model.clone()
+        }
+    }
+}
+
+/// Records outcomes for one route and alerts when a model degrades. Cloned into streaming tasks.
+#[derive(Clone)]
+struct QualityProbe {
+    monitor: Arc<QualityMonitor>,
+    knowledge: Option<Arc<KnowledgeStore>>,
+    client: reqwest::Client,
+    key: String,
+    /// Model the router switches to while the recorded one is degraded.
+    fallback_model: Option<String>,
+}
+
+impl QualityProbe {
+    fn observe(&self, outcome: GenerationOutcome) {
+        if let Some(quality) = self.monitor.record(&self.key, outcome) {
+            self.alert(quality);
+        }
+    }
+
+    fn alert(&self, quality: ModelQuality) {
+        let reason = quality.degraded_reason.clone().unwrap_or_default();
+        tracing::error!(
+            target: "pagi::model_router",
+            model = %quality.model,
+            reason = %reason,
+            fallback = ?self.fallback_model,
+            "LLM quality degraded; {}",
+            if self.fallback_model.is_some() { "switching to fallback provider" } else { "no fallback provider configured" }
+        );
+        let payload = serde_json::json!({
+            "type": LLM_QUALITY_ALERT_TYPE,
+            "model": quality.model,
+            "reason": reason,
+            "quality": quality,
+            "fallback_model": self.fallback_model,
+        });
+        match &self.monitor.thresholds().notify {
+            Some(AlertTarget::Inbox { agent_id }) => {
+                if let Some(store) = &self.knowledge {
+                    if let Err(e) = store.push_agent_message(LLM_QUALITY_SENDER, agent_id, &payload) {
+                        tracing::warn!(target: "pagi::model_router", error = %e, "LLM quality alert not delivered");
+                    }
+                }
+            }
+            Some(AlertTarget::Webhook { url }) => {
+                let request = self
+                    .client
+                    .post(url)
+                    .timeout(std::time::Duration::from_secs(10))
+                    .json(&payload);
+                tokio::spawn(async move {
+                    if let Err(e) = request.send().await.and_then(|r| r.error_for_status()) {
+                        tracing::warn!(target: "pagi::model_router", error = %e, "LLM quality webhook failed");
+                    }
+                });
+            }
+            None => {}
+        }
+    }
+}
+
 /// Routes a prompt string to a mock LLM or a live API (OpenRouter/OpenAI-compatible).
+///
+/// Live generations feed a [`QualityMonitor`]; while the configured model is degraded, calls go
+/// to the fallback provider (see [`with_fallback_api`](Self::with_fallback_api)). A model that
+/// names a model alias (see [`with_providers`](Self::with_providers)) is sent to the alias's
+/// providers in turn until one answers.
 pub struct ModelRouter {
     mode: LlmMode,
     client: reqwest::Client,
     knowledge: Option<Arc<KnowledgeStore>>,
+    /// MentalState → generation parameter mapping (applied when `knowledge` is set).
+    modulation: GenerationModulation,
+    api: LlmApiConfig,
+    /// Provider used while the primary model is degraded. Unset fields fall back to the primary's.
+    fallback: Option<LlmApiConfig>,
+    quality: Arc<QualityMonitor>,
+    /// Providers and model aliases (OpenAI, Anthropic, Ollama, llama.cpp, Azure).
+    providers: Arc<LlmProviders>,
+    /// Monthly token budgets per tenant (enforced when `knowledge` is set).
+    budgets: TokenBudgetConfig,
+    /// Reply cache for identical live generations (`PAGI_LLM_CACHE`; used when `knowledge` is set).
+    cache: ResponseCacheConfig,
+    /// Live call limit (`PAGI_LLM_TIMEOUT_SECS`), bounded per call by the request deadline.
+    timeout: std::time::Duration,
+    /// PII masking for live prompts (`CoreConfig::redaction`).
+    redactor: Option<Redactor>,
+    /// Seeded mock replies (simulation mode); None = the built-in mock text.
+    fixtures: Option<Arc<MockFixtures>>,
 }
 
 impl ModelRouter {
@@ -130,24 +607,444 @@ impl ModelRouter {
             mode: LlmMode::from_env(),
             client: reqwest::Client::new(),
             knowledge: None,
+            modulation: GenerationModulation::from_env(),
+            api: LlmApiConfig::default(),
+            fallback: LlmApiConfig::fallback_from_env(),
+            quality: Arc::new(QualityMonitor::new(QualityThresholds::from_env())),
+            providers: Arc::new(LlmProviders::default()),
+            budgets: TokenBudgetConfig::default(),
+            cache: ResponseCacheConfig::from_env(),
+            timeout: std::env::var(ENV_LLM_TIMEOUT_SECS)
+                .ok()
+                .and_then(|v| v.trim().parse::<u64>().ok())
+                .filter(|&secs| secs > 0)
+                .map_or(DEFAULT_LLM_TIMEOUT, std::time::Duration::from_secs),
+            redactor: None,
+            fixtures: None,
         }
     }
 
     /// Constructs a ModelRouter that can query KB-5 Skill Registry to enrich prompts.
+    /// With a store attached, the agent's effective MentalState also modulates generation
+    /// parameters (see [`GenerationModulation`]).
     pub fn with_knowledge(store: Arc<KnowledgeStore>) -> Self {
         Self {
-            mode: LlmMode::from_env(),
-            client: reqwest::Client::new(),
             knowledge: Some(store),
+            ..Self::new()
         }
     }
 
+    /// The knowledge store this router reads (prompt templates, MentalState, budgets), if any.
+    pub fn knowledge(&self) -> Option<&Arc<KnowledgeStore>> {
+        self.knowledge.as_ref()
+    }
+
     pub fn with_mode(mode: LlmMode) -> Self {
         Self {
             mode,
-            client: reqwest::Client::new(),
-            knowledge: None,
+            ..Self::new()
+        }
+    }
+
+    /// Uses explicit API settings instead of the process-wide environment (tenant-scoped routers).
+    pub fn with_api_config(mut self, api: LlmApiConfig) -> Self {
+        self.api = api;
+        self
+    }
+
+    fn api_url(&self) -> String {
+        self.api
+            .api_url
+            .clone()
+            .or_else(|| std::env::var(ENV_LLM_API_URL).ok())
+            .unwrap_or_else(|| DEFAULT_API_URL.to_string())
+    }
+
+    fn api_key(&self) -> Result<String, std::env::VarError> {
+        if let Some(key) = &self.api.api_key {
+            return Ok(key.clone());
+        }
+        pagi_core::secret(self.api.api_key_env.as_deref().unwrap_or(ENV_LLM_API_KEY)).ok_or(std::env::VarError::NotPresent)
+    }
+
+    fn default_model(&self) -> Option<String> {
+        self.api.model.clone().or_else(|| std::env::var(ENV_LLM_MODEL).ok())
+    }
+
+    /// Provider to use while the primary model is degraded (replaces `PAGI_LLM_FALLBACK_*`).
+    pub fn with_fallback_api(mut self, api: LlmApiConfig) -> Self {
+        self.fallback = Some(api);
+        self
+    }
+
+    /// Shares a quality monitor with other routers (same provider health everywhere).
+    pub fn with_quality_monitor(mut self, monitor: Arc<QualityMonitor>) -> Self {
+        self.quality = monitor;
+        self
+    }
+
+    /// Rolling per-model generation quality.
+    pub fn quality_monitor(&self) -> &Arc<QualityMonitor> {
+        &self.quality
+    }
+
+    /// Providers and model aliases from config (`CoreConfig::llm_providers` / `model_aliases`).
+    /// Shared so every router, and the status endpoint, sees the same provider health.
+    pub fn with_providers(mut self, providers: Arc<LlmProviders>) -> Self {
+        self.providers = providers;
+        self
+    }
+
+    pub fn providers(&self) -> &Arc<LlmProviders> {
+        &self.providers
+    }
+
+    /// Monthly token budgets (`CoreConfig::token_budgets`). Live generations of a tenant over its
+    /// budget fail with [`TokenBudgetExceeded`]; usage is kept in KB-Soma, so this needs a store.
+    pub fn with_budgets(mut self, budgets: TokenBudgetConfig) -> Self {
+        self.budgets = budgets;
+        self
+    }
+
+    /// Caches live replies in KB-Soma (replaces `PAGI_LLM_CACHE`); needs a store.
+    pub fn with_response_cache(mut self, cache: ResponseCacheConfig) -> Self {
+        self.cache = cache;
+        self
+    }
+
+    /// Masks PII in prompts before live provider calls (`CoreConfig::redaction`). Each masking is
+    /// audited through the knowledge store, when one is attached.
+    pub fn with_redaction(mut self, redactor: Redactor) -> Self {
+        self.redactor = Some(redactor);
+        self
+    }
+
+    /// Switches to mock mode and answers generations from `fixtures` (see `llm_fixtures.rs`);
+    /// prompts no fixture matches get the built-in mock text.
+    pub fn with_fixtures(mut self, fixtures: Arc<MockFixtures>) -> Self {
+        self.mode = LlmMode::Mock;
+        self.fixtures = Some(fixtures);
+        self
+    }
+
+    /// Masks `texts` in place for a live call: patterns first, then the terms the classifier
+    /// model lists (if configured). A classifier that fails is skipped with a warning.
+    async fn redact_prompt(&self, ctx: &TenantContext, texts: &mut [&mut String]) -> RedactionReport {
+        let mut report = RedactionReport::default();
+        let Some(redactor) = &self.redactor else {
+            return report;
+        };
+        for text in texts.iter_mut() {
+            let (masked, found) = redactor.redact_text(text);
+            if !found.is_empty() {
+                **text = masked;
+                report.merge(&found);
+            }
+        }
+        if let Some(model) = redactor.classifier_model() {
+            let joined = texts.iter().map(|t| t.as_str()).filter(|t| !t.is_empty()).collect::<Vec<_>>().join("\n\n");
+            match self.classify_pii(model, &joined, ctx.bounded_timeout(self.timeout)).await {
+                Ok(terms) => {
+                    for text in texts.iter_mut() {
+                        let (masked, found) = redactor.mask_terms(text, &terms);
+                        if !found.is_empty() {
+                            **text = masked;
+                            report.merge(&found);
+                        }
+                    }
+                }
+                Err(e) => tracing::warn!(target: "pagi::redaction", model, error = %e, "PII classifier failed; patterns only"),
+            }
+        }
+        if let Some(store) = &self.knowledge {
+            store.record_redaction(
+                &ctx.tenant_id,
+                ctx.resolved_agent_id(),
+                ctx.correlation_id.as_deref(),
+                SKILL_NAME,
+                &report,
+            );
+        }
+        report
+    }
+
+    /// Asks `model` for the personal data in `text` the patterns cannot catch.
+    async fn classify_pii(
+        &self,
+        model: &str,
+        text: &str,
+        timeout: std::time::Duration,
+    ) -> Result<Vec<String>, Box<dyn std::error::Error + Send + Sync>> {
+        let (reply, _) = self
+            .live_generate(Some(PII_CLASSIFIER_PROMPT), text, Some(model), Some(0.0), Some(256), timeout)
+            .await?;
+        let parsed = parse_json_reply(&reply).ok_or("PII classifier reply was not a JSON object")?;
+        Ok(parsed["pii"]
+            .as_array()
+            .map(|terms| terms.iter().filter_map(|t| t.as_str().map(str::to_string)).collect())
+            .unwrap_or_default())
+    }
+
+    /// A cached reply for the generation hashed to `hash`.
+    fn cached_reply(&self, hash: &str) -> Option<LlmCacheEntry> {
+        self.cache.lookup(self.knowledge.as_deref()?, hash)
+    }
+
+    fn cache_reply(&self, hash: &str, model: &str, reply: &str) {
+        if let Some(store) = &self.knowledge {
+            self.cache.store(store, hash, model, reply);
+        }
+    }
+
+    /// Fails when the tenant has used up this month's token budget.
+    fn check_budget(&self, ctx: &TenantContext) -> Result<(), TokenBudgetExceeded> {
+        let (Some(store), Some(budget)) = (&self.knowledge, self.budgets.monthly_limit(&ctx.tenant_id)) else {
+            return Ok(());
+        };
+        let month = usage_month(store.now_ms());
+        let used = store.get_token_usage(&ctx.tenant_id, &month).map_or(0, |u| u.total_tokens);
+        if used < budget {
+            return Ok(());
+        }
+        Err(TokenBudgetExceeded {
+            tenant_id: ctx.tenant_id.clone(),
+            month,
+            used_tokens: used,
+            budget_tokens: budget,
+        })
+    }
+
+    /// Meter for a live generation (None without a store).
+    fn meter(&self, ctx: &TenantContext, estimated_prompt_tokens: u64) -> Option<UsageMeter> {
+        let store = self.knowledge.as_ref()?;
+        Some(UsageMeter {
+            store: Arc::clone(store),
+            tenant_id: ctx.tenant_id.clone(),
+            agent_id: ctx.resolved_agent_id().to_string(),
+            estimated_prompt_tokens,
+        })
+    }
+
+    /// Resolves the provider for a live call: the primary, or the fallback while the primary
+    /// model is degraded.
+    fn route(&self, model_override: Option<&str>) -> Result<Route, std::env::VarError> {
+        let model = self.requested_model(model_override);
+        match &self.fallback {
+            Some(fallback) if self.quality.is_degraded(&model) => {
+                let key = match (&fallback.api_key, &fallback.api_key_env) {
+                    (Some(key), _) => key.clone(),
+                    (None, Some(env)) => pagi_core::secret(env).ok_or(std::env::VarError::NotPresent)?,
+                    (None, None) => self.api_key()?,
+                };
+                Ok(Route {
+                    kind: LlmProviderKind::Openai,
+                    url: fallback.api_url.clone().unwrap_or_else(|| self.api_url()),
+                    key,
+                    model: fallback.model.clone().unwrap_or(model),
+                    fallback: true,
+                    provider: None,
+                })
+            }
+            _ => Ok(Route {
+                kind: LlmProviderKind::Openai,
+                url: self.api_url(),
+                key: self.api_key()?,
+                model,
+                fallback: false,
+                provider: None,
+            }),
+        }
+    }
+
+    /// The model (or model alias) a generation asks for, before fallback or failover.
+    fn requested_model(&self, model_override: Option<&str>) -> String {
+        model_override
+            .map(|s| s.to_string())
+            .or_else(|| self.default_model())
+            .unwrap_or_else(|| DEFAULT_MODEL.to_string())
+    }
+
+    /// Routes to try in order: the targets of a model alias (healthy providers first), or the
+    /// single route from [`route`](Self::route).
+    fn routes(&self, model_override: Option<&str>) -> Result<Vec<Route>, Box<dyn std::error::Error + Send + Sync>> {
+        let model = model_override.map(|s| s.to_string()).or_else(|| self.default_model());
+        if let Some(targets) = model.as_deref().and_then(|m| self.providers.targets(m)) {
+            if targets.is_empty() {
+                return Err(format!("No usable LLM provider for model alias '{}'", model.unwrap_or_default()).into());
+            }
+            return Ok(targets.into_iter().map(Route::from).collect());
+        }
+        Ok(vec![self.route(model_override)?])
+    }
+
+    /// The HTTP request for one live call on `route`, in the provider's format (`body.model` is
+    /// replaced by the route's model).
+    fn chat_request(
+        &self,
+        route: &Route,
+        body: &ChatRequest,
+        title: &str,
+        timeout: std::time::Duration,
+    ) -> reqwest::RequestBuilder {
+        let request = self
+            .client
+            .post(&route.url)
+            .timeout(timeout)
+            .header("Content-Type", "application/json")
+            .headers(trace_context_headers());
+        match route.kind {
+            LlmProviderKind::Anthropic => {
+                let system = body.messages.iter().find(|m| m.role == "system").map(|m| m.content.as_str());
+                request
+                    .header("x-api-key", &route.key)
+                    .header("anthropic-version", ANTHROPIC_VERSION)
+                    .json(&AnthropicRequest {
+                        model: &route.model,
+                        system,
+                        messages: anthropic_messages(&body.messages),
+                        max_tokens: body.max_tokens.unwrap_or(ANTHROPIC_DEFAULT_MAX_TOKENS),
+                        temperature: body.temperature,
+                        stream: body.stream,
+                        tools: body.tools.as_ref().map(|tools| {
+                            tools
+                                .iter()
+                                .map(|t| {
+                                    serde_json::json!({
+                                        "name": t["function"]["name"],
+                                        "description": t["function"]["description"],
+                                        "input_schema": t["function"]["parameters"],
+                                    })
+                                })
+                                .collect()
+                        }),
+                    })
+            }
+            kind => {
+                let mut json = serde_json::to_value(body).unwrap_or_default();
+                json["model"] = serde_json::Value::String(route.model.clone());
+                let request = match kind {
+                    LlmProviderKind::Azure => request.header("api-key", &route.key),
+                    _ if route.key.is_empty() => request,
+                    _ => request.header("Authorization", format!("Bearer {}", route.key)),
+                };
+                request
+                    .header("HTTP-Referer", "https://pagi-orchestrator.local")
+                    .header("X-Title", title)
+                    .json(&json)
+            }
+        }
+    }
+
+    /// Sends `body` on each route in turn until one answers with success. A 5xx, a timeout or an
+    /// unreachable provider moves on to the next route; any other error is returned at once.
+    /// Returns the response and the route that produced it.
+    async fn send_chat(
+        &self,
+        routes: Vec<Route>,
+        body: &ChatRequest,
+        title: &str,
+        timeout: std::time::Duration,
+    ) -> Result<(reqwest::Response, Route), Box<dyn std::error::Error + Send + Sync>> {
+        let mut last_error: Option<String> = None;
+        for route in routes {
+            if let Some(previous) = &last_error {
+                tracing::warn!(
+                    target: "pagi::model_router",
+                    provider = %route.label(),
+                    model = %route.model,
+                    error = %previous,
+                    "[ModelRouter] Failing over to next provider"
+                );
+            }
+            let probe = self.probe(&route);
+            let error = match self.chat_request(&route, body, title, timeout).send().await {
+                Ok(response) if response.status().is_success() => {
+                    if let Some(provider) = &route.provider {
+                        self.providers.record_success(provider);
+                    }
+                    return Ok((response, route));
+                }
+                Ok(response) => {
+                    let status = response.status();
+                    let text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+                    probe.observe(GenerationOutcome::Failed);
+                    let error = format!("{} API error ({}): {}", route.label(), status, text);
+                    if !status.is_server_error() {
+                        return Err(error.into());
+                    }
+                    error
+                }
+                Err(e) => {
+                    probe.observe(GenerationOutcome::Failed);
+                    if !(e.is_timeout() || e.is_connect()) {
+                        return Err(e.into());
+                    }
+                    format!("{} request failed: {}", route.label(), e)
+                }
+            };
+            if let Some(provider) = &route.provider {
+                self.providers.record_failure(provider, &error);
+            }
+            last_error = Some(error);
         }
+        Err(last_error.unwrap_or_else(|| "No LLM provider to call".to_string()).into())
+    }
+
+    /// Reads a successful non-streaming reply in the route's format.
+    async fn read_reply(
+        route: &Route,
+        response: reqwest::Response,
+    ) -> Result<ChatResponse, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(match route.kind {
+            LlmProviderKind::Anthropic => response.json::<AnthropicResponse>().await?.into(),
+            _ => response.json().await?,
+        })
+    }
+
+    fn probe(&self, route: &Route) -> QualityProbe {
+        let fallback_model = match (&self.fallback, route.fallback) {
+            (Some(fallback), false) => Some(fallback.model.clone().unwrap_or_else(|| route.model.clone())),
+            _ => None,
+        };
+        QualityProbe {
+            monitor: Arc::clone(&self.quality),
+            knowledge: self.knowledge.clone(),
+            client: self.client.clone(),
+            key: route.quality_key(),
+            fallback_model,
+        }
+    }
+
+    /// Replaces the MentalState → generation parameter mapping.
+    pub fn with_modulation(mut self, modulation: GenerationModulation) -> Self {
+        self.modulation = modulation;
+        self
+    }
+
+    /// Applies the Cognitive Governor to a request: reads the agent's effective MentalState and
+    /// rewrites temperature / max_tokens / length target. Returns the adjustment when anything changed.
+    fn modulate(&self, ctx: &TenantContext, req: &mut GenerationRequest) -> Option<GenerationAdjustment> {
+        let store = self.knowledge.as_ref()?;
+        let mental = store.get_effective_mental_state(ctx.resolved_agent_id());
+        let adjustment = self.modulation.apply(&mental, req.temperature, req.max_tokens);
+        if !adjustment.is_adjusted() {
+            return None;
+        }
+        req.temperature = adjustment.temperature;
+        req.max_tokens = adjustment.max_tokens;
+        if let Some(line) = adjustment.length_instruction() {
+            req.system_prompt = Some(match req.system_prompt.take() {
+                Some(sp) => format!("{}\n\n{}", sp, line),
+                None => line,
+            });
+        }
+        tracing::debug!(
+            target: "pagi::skills::model_router",
+            agent_id = ctx.resolved_agent_id(),
+            reasons = ?adjustment.reasons,
+            "Generation parameters modulated by MentalState"
+        );
+        Some(adjustment)
     }
 
     fn build_system_prompt_from_skills(&self) -> String {
@@ -171,6 +1068,9 @@ impl ModelRouter {
     /// Mock LLM: returns a deterministic response. Never inject the skill list into the prompt
     /// so the user never sees a "Skill Menu" — that was the "AI hallucination" (schema echo).
     fn mock_generate(&self, prompt: &str) -> String {
+        if let Some(reply) = self.fixtures.as_ref().and_then(|f| f.reply(prompt)) {
+            return reply.to_string();
+        }
         let preview = prompt
             .chars()
             .take(80)
@@ -201,11 +1101,11 @@ impl ModelRouter {
         };
         if let Some(s) = system_prompt.filter(|s| !s.is_empty()) {
             vec![
-                ChatMessage { role: "system".to_string(), content: s.to_string() },
-                ChatMessage { role: "user".to_string(), content: user_content },
+                ChatMessage::new("system", s),
+                ChatMessage::new("user", user_content),
             ]
         } else {
-            vec![ChatMessage { role: "user".to_string(), content: user_content }]
+            vec![ChatMessage::new("user", user_content)]
         }
     }
 
@@ -218,53 +1118,40 @@ impl ModelRouter {
         model_override: Option<&str>,
         temperature: Option<f32>,
         max_tokens: Option<u32>,
+        timeout: std::time::Duration,
     ) -> Result<(String, Option<TokenUsage>), Box<dyn std::error::Error + Send + Sync>> {
         let messages = self.build_messages(system_prompt, prompt, system_prompt.is_none());
-        let url = std::env::var(ENV_LLM_API_URL).unwrap_or_else(|_| DEFAULT_API_URL.to_string());
-        let key = std::env::var(ENV_LLM_API_KEY)?;
-        let model = model_override
-            .map(|s| s.to_string())
-            .or_else(|| std::env::var(ENV_LLM_MODEL).ok())
-            .unwrap_or_else(|| DEFAULT_MODEL.to_string());
+        let routes = self.routes(model_override)?;
 
-        eprintln!("[ModelRouter] Dispatching to OpenRouter (model: {})...", model);
+        eprintln!("[ModelRouter] Dispatching to {} (model: {})...", routes[0].label(), routes[0].model);
 
         let request_body = ChatRequest {
-            model: model.clone(),
+            model: routes[0].model.clone(),
             messages,
             temperature,
             max_tokens,
             stream: None, // Non-streaming mode
+            response_format: None,
+            stream_options: None,
+            tools: None,
         };
 
-        let response = self
-            .client
-            .post(&url)
-            .header("Authorization", format!("Bearer {}", key))
-            .header("HTTP-Referer", "https://pagi-orchestrator.local")
-            .header("X-Title", "PAGI-Master-Orchestrator")
-            .header("Content-Type", "application/json")
-            .json(&request_body)
-            .send()
-            .await?;
-
-        let status = response.status();
-        
-        if !status.is_success() {
-            let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
-            eprintln!("[ModelRouter] HTTP {} from OpenRouter: {}", status, error_text);
-            return Err(format!("OpenRouter API error ({}): {}", status, error_text).into());
-        }
-
-        eprintln!("[ModelRouter] HTTP {} OK from OpenRouter", status);
+        let (response, route) = self
+            .send_chat(routes, &request_body, "PAGI-Master-Orchestrator", timeout)
+            .await
+            .inspect_err(|e| eprintln!("[ModelRouter] {}", e))?;
+        eprintln!("[ModelRouter] HTTP {} OK from {}", response.status(), route.label());
+        let result = Self::read_reply(&route, response).await;
+        let probe = self.probe(&route);
+        let chat_response = result.inspect_err(|_| probe.observe(GenerationOutcome::Failed))?;
+        let content = chat_response.choices.first().and_then(|c| c.message.content.clone());
+        probe.observe(if content.as_deref().is_some_and(|c| !c.trim().is_empty()) {
+            GenerationOutcome::Ok
+        } else {
+            GenerationOutcome::Empty
+        });
 
-        let chat_response: ChatResponse = response.json().await?;
-        
-        let generated = chat_response
-            .choices
-            .first()
-            .map(|c| c.message.content.clone())
-            .unwrap_or_else(|| "[No response from LLM]".to_string());
+        let generated = content.unwrap_or_else(|| NO_RESPONSE.to_string());
 
         if let Some(ref usage) = chat_response.usage {
             eprintln!(
@@ -306,43 +1193,29 @@ impl ModelRouter {
                 )
             }
             LlmMode::Live => {
-                let url = std::env::var(ENV_LLM_API_URL).unwrap_or_else(|_| DEFAULT_API_URL.to_string());
-                let key = std::env::var(ENV_LLM_API_KEY)?;
-                let model = std::env::var(ENV_LLM_MODEL).unwrap_or_else(|_| DEFAULT_MODEL.to_string());
+                let routes = self.routes(None)?;
                 tracing::debug!(
                     target: "pagi::model_router",
                     len = prompt.len(),
                     "[ModelRouter] Reflection request (prompt length only; content not logged)"
                 );
                 let request_body = ChatRequest {
-                    model: model.clone(),
-                    messages: vec![ChatMessage {
-                        role: "user".to_string(),
-                        content: prompt.to_string(),
-                    }],
+                    model: routes[0].model.clone(),
+                    messages: vec![ChatMessage::new("user", prompt)],
                     temperature: Some(0.5),
                     max_tokens: Some(1024),
                     stream: None,
+                    response_format: None,
+                    stream_options: None,
+                    tools: None,
                 };
-                let response = self
-                    .client
-                    .post(&url)
-                    .header("Authorization", format!("Bearer {}", key))
-                    .header("HTTP-Referer", "https://pagi-orchestrator.local")
-                    .header("X-Title", "PAGI-Reflection")
-                    .header("Content-Type", "application/json")
-                    .json(&request_body)
-                    .send()
-                    .await?;
-                if !response.status().is_success() {
-                    let err = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
-                    return Err(format!("Reflection LLM error: {}", err).into());
-                }
-                let chat_response: ChatResponse = response.json().await?;
+                let (response, route) = self.send_chat(routes, &request_body, "PAGI-Reflection", self.timeout).await?;
+                let chat_response = Self::read_reply(&route, response).await?;
                 let text = chat_response
                     .choices
                     .first()
-                    .map(|c| c.message.content.trim().to_string())
+                    .and_then(|c| c.message.content.as_deref())
+                    .map(|c| c.trim().to_string())
                     .unwrap_or_else(|| String::new());
                 Ok(text)
             }
@@ -357,6 +1230,9 @@ impl ModelRouter {
     ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
         match self.mode {
             LlmMode::Mock => {
+                if let Some(reply) = self.fixtures.as_ref().and_then(|f| f.fixture_reply(prompt)) {
+                    return Ok(reply.to_string());
+                }
                 // Deterministic mock for classification: match on the user input only (between quotes after "Information to classify:").
                 let s = prompt.to_lowercase();
                 let content = s
@@ -386,44 +1262,73 @@ impl ModelRouter {
                 Ok(mock.to_string())
             }
             LlmMode::Live => {
-                let url = std::env::var(ENV_LLM_API_URL).unwrap_or_else(|_| DEFAULT_API_URL.to_string());
-                let key = std::env::var(ENV_LLM_API_KEY)?;
-                let model = std::env::var(ENV_LLM_MODEL).unwrap_or_else(|_| DEFAULT_MODEL.to_string());
+                let model = self.requested_model(None);
+                let cache_hash = LlmCacheEntry::cache_hash(&model, &["generate_text_raw", prompt]);
+                if let Some(hit) = self.cached_reply(&cache_hash) {
+                    return Ok(hit.response);
+                }
+                let routes = self.routes(None)?;
                 let request_body = ChatRequest {
-                    model: model.clone(),
-                    messages: vec![ChatMessage {
-                        role: "user".to_string(),
-                        content: prompt.to_string(),
-                    }],
+                    model: routes[0].model.clone(),
+                    messages: vec![ChatMessage::new("user", prompt)],
                     temperature: Some(0.0),
                     max_tokens: Some(32),
                     stream: None,
-                };
-                let response = self
-                    .client
-                    .post(&url)
-                    .header("Authorization", format!("Bearer {}", key))
-                    .header("HTTP-Referer", "https://pagi-orchestrator.local")
-                    .header("X-Title", "PAGI-Thalamus")
-                    .header("Content-Type", "application/json")
-                    .json(&request_body)
-                    .send()
-                    .await?;
-                if !response.status().is_success() {
-                    let err = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
-                    return Err(format!("LLM API error: {}", err).into());
-                }
-                let chat_response: ChatResponse = response.json().await?;
-                let text = chat_response
-                    .choices
-                    .first()
-                    .map(|c| c.message.content.trim().to_string())
-                    .unwrap_or_else(|| "Logos".to_string());
+                    response_format: None,
+                    stream_options: None,
+                    tools: None,
+                };
+                let (response, route) = self.send_chat(routes, &request_body, "PAGI-Thalamus", self.timeout).await?;
+                let chat_response = Self::read_reply(&route, response).await?;
+                let Some(text) = chat_response.choices.first().and_then(|c| c.message.content.as_deref()).map(|c| c.trim().to_string()) else {
+                    return Ok("Logos".to_string());
+                };
+                self.cache_reply(&cache_hash, &model, &text);
                 Ok(text)
             }
         }
     }
 
+    /// JSON mode: asks the LLM for a single JSON object (`response_format: json_object`) and
+    /// parses it (tolerating code fences). Mock mode has no structured output and returns an
+    /// error, so callers fall back to their deterministic parser.
+    pub async fn generate_json(
+        &self,
+        prompt: &str,
+    ) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>> {
+        if matches!(self.mode, LlmMode::Mock) {
+            return Err("JSON mode is not available in mock LLM mode".into());
+        }
+        let routes = self.routes(None)?;
+        let request_body = ChatRequest {
+            model: routes[0].model.clone(),
+            messages: vec![ChatMessage::new("user", prompt)],
+            temperature: Some(0.0),
+            max_tokens: Some(512),
+            stream: None,
+            response_format: Some(serde_json::json!({ "type": "json_object" })),
+            stream_options: None,
+            tools: None,
+        };
+        let (response, route) = self.send_chat(routes, &request_body, "PAGI-JSON", self.timeout).await?;
+        let result = Self::read_reply(&route, response).await;
+        let probe = self.probe(&route);
+        let chat_response = result.inspect_err(|_| probe.observe(GenerationOutcome::Failed))?;
+        let text = chat_response
+            .choices
+            .first()
+            .and_then(|c| c.message.content.clone())
+            .unwrap_or_default();
+        if text.trim().is_empty() {
+            probe.observe(GenerationOutcome::Empty);
+            return Err("LLM reply was empty".into());
+        }
+        // A reply that fails JSON post-processing counts as a block.
+        let parsed = parse_json_reply(&text);
+        probe.observe(if parsed.is_some() { GenerationOutcome::Ok } else { GenerationOutcome::Blocked });
+        parsed.ok_or_else(|| "LLM reply was not a JSON object".into())
+    }
+
     /// Live API with streaming: streams tokens via a channel.
     /// When system_prompt is Some, sends [system, user] (Sovereign); otherwise [user] only.
     pub async fn stream_generate(
@@ -434,61 +1339,56 @@ impl ModelRouter {
         temperature: Option<f32>,
         max_tokens: Option<u32>,
     ) -> Result<mpsc::Receiver<String>, Box<dyn std::error::Error + Send + Sync>> {
-        let messages = self.build_messages(system_prompt, prompt, system_prompt.is_none());
-        let url = std::env::var(ENV_LLM_API_URL).unwrap_or_else(|_| DEFAULT_API_URL.to_string());
-        let key = std::env::var(ENV_LLM_API_KEY)?;
-        let model = model_override
-            .map(|s| s.to_string())
-            .or_else(|| std::env::var(ENV_LLM_MODEL).ok())
-            .unwrap_or_else(|| DEFAULT_MODEL.to_string());
+        let stream = self
+            .open_stream(system_prompt, prompt, model_override, temperature, max_tokens, self.timeout)
+            .await?;
+        Ok(stream.tokens)
+    }
 
-        tracing::info!(
-            target: "pagi::model_router",
-            model = %model,
-            "[ModelRouter] Streaming session started for model: {}",
-            model
-        );
+    /// [`stream_generate`](Self::stream_generate) plus the model actually used (the fallback's
+    /// while the primary is degraded) and the usage reported at the end of the stream.
+    async fn open_stream(
+        &self,
+        system_prompt: Option<&str>,
+        prompt: &str,
+        model_override: Option<&str>,
+        temperature: Option<f32>,
+        max_tokens: Option<u32>,
+        timeout: std::time::Duration,
+    ) -> Result<LiveStream, Box<dyn std::error::Error + Send + Sync>> {
+        let messages = self.build_messages(system_prompt, prompt, system_prompt.is_none());
+        let routes = self.routes(model_override)?;
 
         let request_body = ChatRequest {
-            model: model.clone(),
+            model: routes[0].model.clone(),
             messages,
             temperature,
             max_tokens,
             stream: Some(true),
+            response_format: None,
+            stream_options: Some(serde_json::json!({ "include_usage": true })),
+            tools: None,
         };
 
-        let response = self
-            .client
-            .post(&url)
-            .header("Authorization", format!("Bearer {}", key))
-            .header("HTTP-Referer", "https://pagi-orchestrator.local")
-            .header("X-Title", "PAGI-Master-Orchestrator")
-            .header("Content-Type", "application/json")
-            .json(&request_body)
-            .send()
-            .await?;
-
-        let status = response.status();
-        if !status.is_success() {
-            let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
-            tracing::error!(
-                target: "pagi::model_router",
-                status = %status,
-                "[ModelRouter] HTTP {} from OpenRouter: {}",
-                status,
-                error_text
-            );
-            return Err(format!("OpenRouter API error ({}): {}", status, error_text).into());
-        }
+        // Failover happens while opening the stream; once tokens flow, the provider is kept.
+        let (response, route) = self
+            .send_chat(routes, &request_body, "PAGI-Master-Orchestrator", timeout)
+            .await
+            .inspect_err(|e| tracing::error!(target: "pagi::model_router", "[ModelRouter] {}", e))?;
+        let probe = self.probe(&route);
+        let Route { kind, model, .. } = route;
 
         tracing::info!(
             target: "pagi::model_router",
-            "[ModelRouter] HTTP {} OK - SSE stream established",
-            status
+            model = %model,
+            "[ModelRouter] HTTP {} OK - SSE stream established for model: {}",
+            response.status(),
+            model
         );
 
         // Create a channel to send tokens to the caller
         let (tx, rx) = mpsc::channel::<String>(100);
+        let (usage_tx, usage_rx) = tokio::sync::oneshot::channel();
 
         // Spawn a task to read the stream and send tokens
         let model_for_log = model.clone();
@@ -496,8 +1396,32 @@ impl ModelRouter {
             use futures_util::TryStreamExt;
             let mut stream = response.bytes_stream();
             let mut buffer = String::new();
-
-            while let Ok(Some(bytes)) = stream.try_next().await {
+            // Whether any non-whitespace token arrived (consumer aborts are not recorded).
+            let mut produced = false;
+            // Sent when the stream ends; some providers report it in parts.
+            let mut usage: Option<TokenUsage> = None;
+
+            'read: loop {
+                // Stop reading (and drop the HTTP stream) as soon as the consumer goes away, e.g.
+                // when the orchestrator cancels the request.
+                let bytes = tokio::select! {
+                    _ = tx.closed() => {
+                        tracing::info!(
+                            target: "pagi::model_router",
+                            "[ModelRouter] Stream aborted by consumer for model: {}",
+                            model_for_log
+                        );
+                        return;
+                    }
+                    next = stream.try_next() => match next {
+                        Ok(Some(bytes)) => bytes,
+                        Ok(None) => break,
+                        Err(_) => {
+                            probe.observe(GenerationOutcome::Failed);
+                            return;
+                        }
+                    },
+                };
                 let text = String::from_utf8_lossy(&bytes);
                 buffer.push_str(&text);
 
@@ -506,50 +1430,49 @@ impl ModelRouter {
                     let line = buffer[..newline_pos].trim().to_string();
                     buffer = buffer[newline_pos + 1..].to_string();
 
-                    if line.is_empty() {
+                    // SSE format: "data: {...}" (OpenAI-style streams end with "data: [DONE]")
+                    let Some(data) = line.strip_prefix("data: ") else {
                         continue;
+                    };
+                    let Some(update) = StreamUpdate::parse(kind, data) else {
+                        tracing::debug!(
+                            target: "pagi::model_router",
+                            "[ModelRouter] Failed to parse SSE chunk - data: {}",
+                            data
+                        );
+                        continue;
+                    };
+                    if let Some(update) = update.usage {
+                        usage.get_or_insert_with(TokenUsage::default).merge(update);
                     }
-
-                    // SSE format: "data: {...}" or "data: [DONE]"
-                    if let Some(data) = line.strip_prefix("data: ") {
-                        if data == "[DONE]" {
-                            tracing::info!(
-                                target: "pagi::model_router",
-                                "[ModelRouter] Stream completed for model: {}",
-                                model_for_log
-                            );
+                    if update.done {
+                        tracing::info!(
+                            target: "pagi::model_router",
+                            "[ModelRouter] Stream completed for model: {}",
+                            model_for_log
+                        );
+                        break 'read;
+                    }
+                    if let Some(content) = update.delta.filter(|c| !c.is_empty()) {
+                        produced |= !content.trim().is_empty();
+                        if tx.send(content).await.is_err() {
+                            // Receiver dropped, stop processing
                             return;
                         }
-
-                        // Parse the JSON chunk
-                        match serde_json::from_str::<StreamChunk>(data) {
-                            Ok(chunk) => {
-                                if let Some(choice) = chunk.choices.first() {
-                                    if let Some(content) = &choice.delta.content {
-                                        if !content.is_empty() {
-                                            if tx.send(content.clone()).await.is_err() {
-                                                // Receiver dropped, stop processing
-                                                return;
-                                            }
-                                        }
-                                    }
-                                }
-                            }
-                            Err(e) => {
-                                tracing::debug!(
-                                    target: "pagi::model_router",
-                                    "[ModelRouter] Failed to parse SSE chunk: {} - data: {}",
-                                    e,
-                                    data
-                                );
-                            }
-                        }
                     }
                 }
             }
+            if let Some(usage) = usage {
+                let _ = usage_tx.send(usage);
+            }
+            probe.observe(if produced { GenerationOutcome::Ok } else { GenerationOutcome::Empty });
         });
 
-        Ok(rx)
+        Ok(LiveStream {
+            tokens: rx,
+            model,
+            usage: usage_rx,
+        })
     }
 
     /// Mock streaming: yields words with delays to simulate streaming.
@@ -587,11 +1510,8 @@ impl ModelRouter {
     ) -> Result<Vec<f32>, Box<dyn std::error::Error + Send + Sync>> {
         let url = std::env::var(ENV_EMBEDDINGS_API_URL)
             .unwrap_or_else(|_| DEFAULT_EMBEDDINGS_API_URL.to_string());
-        let key = std::env::var(ENV_LLM_API_KEY)?;
-        let model = model_override
-            .map(|s| s.to_string())
-            .or_else(|| std::env::var(ENV_EMBEDDINGS_MODEL).ok())
-            .unwrap_or_else(|| DEFAULT_EMBEDDINGS_MODEL.to_string());
+        let key = self.api_key()?;
+        let model = Self::live_embedding_model(model_override);
 
         tracing::info!(
             target: "pagi::model_router",
@@ -611,6 +1531,7 @@ impl ModelRouter {
             .header("HTTP-Referer", "https://pagi-orchestrator.local")
             .header("X-Title", "PAGI-Master-Orchestrator")
             .header("Content-Type", "application/json")
+            .headers(trace_context_headers())
             .json(&request_body)
             .send()
             .await?;
@@ -642,6 +1563,13 @@ impl ModelRouter {
         Ok(embedding)
     }
 
+    fn live_embedding_model(model_override: Option<&str>) -> String {
+        model_override
+            .map(|s| s.to_string())
+            .or_else(|| std::env::var(ENV_EMBEDDINGS_MODEL).ok())
+            .unwrap_or_else(|| DEFAULT_EMBEDDINGS_MODEL.to_string())
+    }
+
     fn mock_embedding(input: &str) -> Vec<f32> {
         // Deterministic low-cost embedding for offline/mock mode.
         // Not semantically strong, but enables exercising the end-to-end pipeline.
@@ -661,6 +1589,15 @@ impl ModelRouter {
         v
     }
 
+    /// Name of the model [`Self::embedding`] uses for `model_override` (`mock` in mock mode), as
+    /// recorded in the `embedding_model` metadata of embedded records.
+    pub fn embedding_model_name(&self, model_override: Option<&str>) -> String {
+        match self.mode {
+            LlmMode::Mock => MOCK_EMBEDDING_MODEL.to_string(),
+            LlmMode::Live => Self::live_embedding_model(model_override),
+        }
+    }
+
     /// Embedding helper that respects the configured mode.
     ///
     /// - `Mock` => deterministic local embedding
@@ -675,6 +1612,285 @@ impl ModelRouter {
             LlmMode::Live => self.live_embedding(input, model_override).await,
         }
     }
+
+    /// Masks the prompt and system prompt of a generation.
+    async fn redact_request(&self, ctx: &TenantContext, req: &mut GenerationRequest) -> RedactionReport {
+        if self.redactor.is_none() {
+            return RedactionReport::default();
+        }
+        let mut system_prompt = req.system_prompt.take().unwrap_or_default();
+        let report = self.redact_prompt(ctx, &mut [&mut req.prompt, &mut system_prompt]).await;
+        req.system_prompt = Some(system_prompt).filter(|s| !s.is_empty());
+        report
+    }
+
+    /// Masks the system prompt and the message contents of a tool turn.
+    async fn redact_turn(&self, ctx: &TenantContext, turn: &mut ToolTurn) -> RedactionReport {
+        if self.redactor.is_none() {
+            return RedactionReport::default();
+        }
+        let mut contents: Vec<String> = turn
+            .messages
+            .iter()
+            .map(|m| m["content"].as_str().unwrap_or_default().to_string())
+            .collect();
+        let mut system_prompt = turn.system_prompt.take().unwrap_or_default();
+        let mut texts: Vec<&mut String> = contents.iter_mut().collect();
+        texts.push(&mut system_prompt);
+        let report = self.redact_prompt(ctx, &mut texts).await;
+        turn.system_prompt = Some(system_prompt).filter(|s| !s.is_empty());
+        for (message, content) in turn.messages.iter_mut().zip(contents) {
+            if message["content"].is_string() {
+                message["content"] = serde_json::Value::String(content);
+            }
+        }
+        report
+    }
+
+    /// Tool mode: one model turn that either answers or calls tools. Live provider errors are
+    /// returned (no mock stand-in) so the caller's loop can report them; replies are not cached.
+    async fn execute_tool_turn(
+        &self,
+        ctx: &TenantContext,
+        turn: ToolTurn,
+    ) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>> {
+        let mut redacted = RedactionReport::default();
+        let (generated, tool_calls, usage) = match self.mode {
+            LlmMode::Mock => {
+                let (generated, tool_calls) = turn.mock_reply(self);
+                (generated, tool_calls, None)
+            }
+            LlmMode::Live => {
+                self.check_budget(ctx)?;
+                let mut turn = turn;
+                redacted = self.redact_turn(ctx, &mut turn).await;
+                let routes = self.routes(turn.model_override.as_deref())?;
+                let request_body = ChatRequest {
+                    model: routes[0].model.clone(),
+                    messages: turn.chat_messages(),
+                    temperature: turn.temperature,
+                    max_tokens: turn.max_tokens,
+                    stream: None,
+                    response_format: None,
+                    stream_options: None,
+                    tools: turn.openai_tools(),
+                };
+                let timeout = ctx.bounded_timeout(self.timeout);
+                let (response, route) = self.send_chat(routes, &request_body, "PAGI-Tool-Chat", timeout).await?;
+                let result = Self::read_reply(&route, response).await;
+                let probe = self.probe(&route);
+                let ChatResponse { choices, usage } = result.inspect_err(|_| probe.observe(GenerationOutcome::Failed))?;
+                let (generated, calls) = match choices.into_iter().next() {
+                    Some(choice) => (choice.message.content.unwrap_or_default(), choice.message.tool_calls),
+                    None => (String::new(), Vec::new()),
+                };
+                probe.observe(if !calls.is_empty() || !generated.trim().is_empty() {
+                    GenerationOutcome::Ok
+                } else {
+                    GenerationOutcome::Empty
+                });
+                if let Some(meter) = self.meter(ctx, turn.estimated_prompt_tokens()) {
+                    meter.record(usage.as_ref(), &generated);
+                }
+                let tool_calls = calls
+                    .into_iter()
+                    .map(|c| {
+                        let arguments = serde_json::from_str(&c.function.arguments)
+                            .unwrap_or(serde_json::Value::String(c.function.arguments));
+                        serde_json::json!({ "id": c.id, "name": c.function.name, "arguments": arguments })
+                    })
+                    .collect();
+                (generated, tool_calls, usage)
+            }
+        };
+        let mut result = serde_json::json!({
+            "status": "ok",
+            "skill": SKILL_NAME,
+            "mode": format!("{:?}", self.mode).to_lowercase(),
+            "generated": generated,
+            "tool_calls": tool_calls,
+        });
+        if let Some(usage) = usage {
+            result["token_usage"] = serde_json::json!(usage);
+        }
+        if !redacted.is_empty() {
+            result["redacted"] = serde_json::json!(redacted);
+        }
+        Ok(result)
+    }
+}
+
+/// W3C trace context (`traceparent`, `tracestate`) of the current span, for outgoing LLM calls.
+/// Empty unless the gateway installed an OpenTelemetry propagator (OTLP export enabled).
+pub fn trace_context_headers() -> reqwest::header::HeaderMap {
+    use opentelemetry::propagation::Injector;
+    use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+    struct HeaderInjector(reqwest::header::HeaderMap);
+
+    impl Injector for HeaderInjector {
+        fn set(&mut self, key: &str, value: String) {
+            if let (Ok(name), Ok(value)) = (
+                reqwest::header::HeaderName::from_bytes(key.as_bytes()),
+                reqwest::header::HeaderValue::from_str(&value),
+            ) {
+                self.0.insert(name, value);
+            }
+        }
+    }
+
+    let context = tracing::Span::current().context();
+    let mut injector = HeaderInjector(reqwest::header::HeaderMap::new());
+    opentelemetry::global::get_text_map_propagator(|propagator| propagator.inject_context(&context, &mut injector));
+    injector.0
+}
+
+/// Extracts the JSON object from a JSON-mode reply (bare, or wrapped in prose / code fences).
+fn parse_json_reply(text: &str) -> Option<serde_json::Value> {
+    let start = text.find('{')?;
+    let end = text.rfind('}')?;
+    let value: serde_json::Value = serde_json::from_str(text.get(start..=end)?).ok()?;
+    value.is_object().then_some(value)
+}
+
+/// Generation parameters accepted in a ModelRouter skill payload.
+struct GenerationRequest {
+    prompt: String,
+    /// Sovereign: optional system prompt (Mission Directive from Gateway).
+    system_prompt: Option<String>,
+    model_override: Option<String>,
+    temperature: Option<f32>,
+    max_tokens: Option<u32>,
+}
+
+impl GenerationRequest {
+    fn from_payload(payload: Option<&serde_json::Value>) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let str_field = |key: &str| payload.and_then(|p| p.get(key)).and_then(|v| v.as_str()).map(|s| s.to_string());
+        let prompt = payload
+            .and_then(|p| p.get("prompt").or(p.get("draft")))
+            .and_then(|v| v.as_str())
+            .ok_or("ModelRouter requires payload: { prompt: string } (or draft)")?
+            .to_string();
+        Ok(Self {
+            prompt,
+            system_prompt: str_field("system_prompt"),
+            model_override: str_field("model"),
+            temperature: payload
+                .and_then(|p| p.get("temperature"))
+                .and_then(|v| v.as_f64())
+                .map(|t| t as f32),
+            max_tokens: payload
+                .and_then(|p| p.get("max_tokens"))
+                .and_then(|v| v.as_u64())
+                .map(|t| t as u32),
+        })
+    }
+
+    fn estimated_prompt_tokens(&self) -> u64 {
+        estimate_tokens(&self.prompt) + self.system_prompt.as_deref().map_or(0, estimate_tokens)
+    }
+}
+
+/// One turn of a tool-calling conversation: a payload with `messages` (and usually `tools`)
+/// instead of `prompt`, as sent by the orchestrator's `Goal::ToolChat` loop. Messages and tools
+/// are provider-neutral (`{ role, content, tool_calls?, tool_call_id? }` and `{ name,
+/// description, parameters }`).
+struct ToolTurn {
+    system_prompt: Option<String>,
+    messages: Vec<serde_json::Value>,
+    tools: Vec<serde_json::Value>,
+    model_override: Option<String>,
+    temperature: Option<f32>,
+    max_tokens: Option<u32>,
+}
+
+impl ToolTurn {
+    /// None unless the payload carries a `messages` array.
+    fn from_payload(payload: Option<&serde_json::Value>) -> Option<Self> {
+        let payload = payload?;
+        let messages = payload.get("messages")?.as_array()?.clone();
+        let str_field = |key: &str| payload.get(key).and_then(|v| v.as_str()).map(|s| s.to_string());
+        Some(Self {
+            system_prompt: str_field("system_prompt"),
+            messages,
+            tools: payload.get("tools").and_then(|v| v.as_array()).cloned().unwrap_or_default(),
+            model_override: str_field("model"),
+            temperature: payload.get("temperature").and_then(|v| v.as_f64()).map(|t| t as f32),
+            max_tokens: payload.get("max_tokens").and_then(|v| v.as_u64()).map(|t| t as u32),
+        })
+    }
+
+    /// The conversation in OpenAI format, system prompt first.
+    fn chat_messages(&self) -> Vec<ChatMessage> {
+        let mut out = Vec::with_capacity(self.messages.len() + 1);
+        if let Some(system_prompt) = self.system_prompt.as_deref().filter(|s| !s.is_empty()) {
+            out.push(ChatMessage::new("system", system_prompt));
+        }
+        for m in &self.messages {
+            let mut message = ChatMessage::new(m["role"].as_str().unwrap_or("user"), m["content"].as_str().unwrap_or(""));
+            message.tool_call_id = m["tool_call_id"].as_str().map(|s| s.to_string());
+            message.tool_calls = m["tool_calls"].as_array().map(|calls| {
+                calls
+                    .iter()
+                    .map(|c| {
+                        let arguments = match &c["arguments"] {
+                            serde_json::Value::String(s) => s.clone(),
+                            args => args.to_string(),
+                        };
+                        serde_json::json!({
+                            "id": c["id"],
+                            "type": "function",
+                            "function": { "name": c["name"], "arguments": arguments },
+                        })
+                    })
+                    .collect()
+            });
+            out.push(message);
+        }
+        out
+    }
+
+    fn openai_tools(&self) -> Option<Vec<serde_json::Value>> {
+        if self.tools.is_empty() {
+            return None;
+        }
+        Some(self.tools.iter().map(|t| serde_json::json!({ "type": "function", "function": t })).collect())
+    }
+
+    fn estimated_prompt_tokens(&self) -> u64 {
+        let text = |m: &serde_json::Value| m["content"].as_str().map_or(0, estimate_tokens);
+        self.messages.iter().map(text).sum::<u64>()
+            + self.system_prompt.as_deref().map_or(0, estimate_tokens)
+            + self.tools.iter().map(|t| estimate_tokens(&t.to_string())).sum::<u64>()
+    }
+
+    /// Mock tool mode: calls the first offered tool named in the latest user message, and answers
+    /// with the tool results once they come back.
+    fn mock_reply(&self, router: &ModelRouter) -> (String, Vec<serde_json::Value>) {
+        let last = self.messages.last();
+        if last.is_some_and(|m| m["role"] == "tool") {
+            let mut results: Vec<String> = self
+                .messages
+                .iter()
+                .rev()
+                .take_while(|m| m["role"] == "tool")
+                .map(|m| format!("- {}: {}", m["name"].as_str().unwrap_or("tool"), m["content"].as_str().unwrap_or("")))
+                .collect();
+            results.reverse();
+            return (format!("[Generated – Mock LLM]\n\nTool results:\n{}", results.join("\n")), Vec::new());
+        }
+        let content = last.and_then(|m| m["content"].as_str()).unwrap_or("");
+        let lower = content.to_lowercase();
+        let tool = self
+            .tools
+            .iter()
+            .filter_map(|t| t["name"].as_str())
+            .find(|name| lower.contains(&name.to_lowercase()));
+        match tool {
+            Some(name) => (String::new(), vec![serde_json::json!({ "id": "call_mock_1", "name": name, "arguments": {} })]),
+            None => (router.mock_generate(content), Vec::new()),
+        }
+    }
 }
 
 impl Default for ModelRouter {
@@ -691,49 +1907,72 @@ impl AgentSkill for ModelRouter {
 
     async fn execute(
         &self,
-        _ctx: &TenantContext,
+        ctx: &TenantContext,
         payload: Option<serde_json::Value>,
     ) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>> {
-        let prompt = payload
-            .as_ref()
-            .and_then(|p| p.get("prompt").or(p.get("draft")))
-            .and_then(|v| v.as_str())
-            .ok_or("ModelRouter requires payload: { prompt: string } (or draft)")?
-            .to_string();
-
-        // Sovereign: optional system prompt (Mission Directive from Gateway)
-        let system_prompt = payload
-            .as_ref()
-            .and_then(|p| p.get("system_prompt"))
-            .and_then(|v| v.as_str());
-
-        // Extract optional parameters from payload
-        let model_override = payload
-            .as_ref()
-            .and_then(|p| p.get("model"))
-            .and_then(|v| v.as_str());
-        let temperature = payload
-            .as_ref()
-            .and_then(|p| p.get("temperature"))
-            .and_then(|v| v.as_f64())
-            .map(|t| t as f32);
-        let max_tokens = payload
-            .as_ref()
-            .and_then(|p| p.get("max_tokens"))
-            .and_then(|v| v.as_u64())
-            .map(|t| t as u32);
+        if let Some(turn) = ToolTurn::from_payload(payload.as_ref()) {
+            return self.execute_tool_turn(ctx, turn).await;
+        }
+        let mut req = GenerationRequest::from_payload(payload.as_ref())?;
+        let adjustment = self.modulate(ctx, &mut req);
+        let redacted = match self.mode {
+            LlmMode::Live => self.redact_request(ctx, &mut req).await,
+            LlmMode::Mock => RedactionReport::default(),
+        };
+        let meter = self.meter(ctx, req.estimated_prompt_tokens());
+        let GenerationRequest {
+            prompt,
+            system_prompt,
+            model_override,
+            temperature,
+            max_tokens,
+        } = req;
+        let system_prompt = system_prompt.as_deref();
+        let model_override = model_override.as_deref();
 
+        // Set when the reply came from the response cache.
+        let mut cached_at_ms = None;
         let (generated, usage) = match self.mode {
             LlmMode::Mock => (self.mock_generate(&prompt), None),
             LlmMode::Live => {
-                match self.live_generate(system_prompt, &prompt, model_override, temperature, max_tokens).await {
-                    Ok((text, usage)) => (text, usage),
-                    Err(e) => {
-                        eprintln!("[ModelRouter] Live generation failed: {}. Falling back to mock.", e);
-                        (
-                            format!("[Live LLM Error: {}]\n\n{}", e, self.mock_generate(&prompt)),
-                            None,
-                        )
+                let model = self.requested_model(model_override);
+                let cache_hash = LlmCacheEntry::cache_hash(
+                    &model,
+                    &[
+                        SKILL_NAME,
+                        system_prompt.unwrap_or_default(),
+                        &prompt,
+                        &format!("{:?}/{:?}", temperature, max_tokens),
+                    ],
+                );
+                if let Some(hit) = self.cached_reply(&cache_hash) {
+                    cached_at_ms = Some(hit.created_at_ms);
+                    (hit.response, None)
+                } else {
+                    self.check_budget(ctx)?;
+                    let timeout = ctx.bounded_timeout(self.timeout);
+                    match self
+                        .live_generate(system_prompt, &prompt, model_override, temperature, max_tokens, timeout)
+                        .await
+                    {
+                        Ok((text, usage)) => {
+                            if let Some(meter) = &meter {
+                                meter.record(usage.as_ref(), &text);
+                            }
+                            if text != NO_RESPONSE {
+                                self.cache_reply(&cache_hash, &model, &text);
+                            }
+                            (text, usage)
+                        }
+                        // Out of time: no mock stand-in, the caller reports the deadline.
+                        Err(e) if ctx.deadline_exceeded() => return Err(e),
+                        Err(e) => {
+                            eprintln!("[ModelRouter] Live generation failed: {}. Falling back to mock.", e);
+                            (
+                                format!("[Live LLM Error: {}]\n\n{}", e, self.mock_generate(&prompt)),
+                                None,
+                            )
+                        }
                     }
                 }
             }
@@ -747,6 +1986,21 @@ impl AgentSkill for ModelRouter {
             "prompt_preview_len": prompt.len()
         });
 
+        if let Some(adjustment) = adjustment {
+            result["generation_adjustment"] = serde_json::json!(adjustment);
+        }
+
+        // Counts of what was masked before the provider call (never the values).
+        if !redacted.is_empty() {
+            result["redacted"] = serde_json::json!(redacted);
+        }
+
+        // Answered from the response cache: no provider call, no tokens used.
+        if let Some(cached_at_ms) = cached_at_ms {
+            result["cached"] = serde_json::json!(true);
+            result["cached_at_ms"] = serde_json::json!(cached_at_ms);
+        }
+
         // Add token usage if available
         if let Some(usage) = usage {
             result["token_usage"] = serde_json::json!({
@@ -758,4 +2012,556 @@ impl AgentSkill for ModelRouter {
 
         Ok(result)
     }
+
+    /// Streams generated text as `{ "delta": "..." }` chunks, then a final result chunk shaped like
+    /// [`execute`](Self::execute)'s output with `"done": true`.
+    async fn execute_stream(
+        &self,
+        ctx: &TenantContext,
+        payload: Option<serde_json::Value>,
+    ) -> Result<mpsc::Receiver<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>> {
+        let mut req = GenerationRequest::from_payload(payload.as_ref())?;
+        let adjustment = self.modulate(ctx, &mut req);
+        let mut redacted = RedactionReport::default();
+        let (mut tokens, model, usage, meter) = match self.mode {
+            LlmMode::Mock => (self.mock_stream_generate(&req.prompt), "mock".to_string(), None, None),
+            LlmMode::Live => {
+                self.check_budget(ctx)?;
+                redacted = self.redact_request(ctx, &mut req).await;
+                let stream = self
+                    .open_stream(
+                        req.system_prompt.as_deref(),
+                        &req.prompt,
+                        req.model_override.as_deref(),
+                        req.temperature,
+                        req.max_tokens,
+                        ctx.bounded_timeout(self.timeout),
+                    )
+                    .await?;
+                (stream.tokens, stream.model, Some(stream.usage), self.meter(ctx, req.estimated_prompt_tokens()))
+            }
+        };
+
+        let (tx, rx) = mpsc::channel::<serde_json::Value>(100);
+        let mode = format!("{:?}", self.mode).to_lowercase();
+        let prompt_len = req.prompt.len();
+        tokio::spawn(async move {
+            let mut generated = String::new();
+            let mut aborted = false;
+            loop {
+                let token = tokio::select! {
+                    // Dropping `tokens` ends the upstream reader (and its HTTP stream).
+                    _ = tx.closed() => {
+                        aborted = true;
+                        break;
+                    }
+                    token = tokens.recv() => match token {
+                        Some(token) => token,
+                        None => break,
+                    },
+                };
+                generated.push_str(&token);
+                if tx.send(serde_json::json!({ "delta": token })).await.is_err() {
+                    aborted = true;
+                    break;
+                }
+            }
+            // The token stream has ended, so the usage (if the provider sent one) is already there.
+            // A stream cut short has none; what was generated so far is estimated instead.
+            let usage = usage.and_then(|mut rx| rx.try_recv().ok());
+            if let Some(meter) = &meter {
+                meter.record(usage.as_ref(), &generated);
+            }
+            if aborted {
+                return;
+            }
+            let mut result = serde_json::json!({
+                "status": "ok",
+                "skill": SKILL_NAME,
+                "mode": mode,
+                "model": model,
+                "generated": generated,
+                "prompt_preview_len": prompt_len,
+                "done": true
+            });
+            if let Some(adjustment) = adjustment {
+                result["generation_adjustment"] = serde_json::json!(adjustment);
+            }
+            if !redacted.is_empty() {
+                result["redacted"] = serde_json::json!(redacted);
+            }
+            if let Some(usage) = usage {
+                result["token_usage"] = serde_json::json!(usage);
+            }
+            let _ = tx.send(result).await;
+        });
+        Ok(rx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn mock_execute_stream_sends_deltas_then_final_result() {
+        let router = ModelRouter::with_mode(LlmMode::Mock);
+        let ctx = TenantContext::new("test");
+        let mut rx = router
+            .execute_stream(&ctx, Some(serde_json::json!({ "prompt": "hello" })))
+            .await
+            .unwrap();
+
+        let mut chunks = Vec::new();
+        while let Some(chunk) = rx.recv().await {
+            chunks.push(chunk);
+        }
+        let last = chunks.pop().expect("final chunk");
+        assert_eq!(last["done"], true);
+        assert_eq!(last["skill"], SKILL_NAME);
+        let streamed: String = chunks
+            .iter()
+            .map(|c| c["delta"].as_str().expect("delta chunk"))
+            .collect();
+        assert!(!streamed.is_empty());
+        assert_eq!(last["generated"], streamed);
+    }
+
+    #[test]
+    fn json_reply_tolerates_fences() {
+        let v = parse_json_reply("```json\n{\"intent\": \"booking\"}\n```").unwrap();
+        assert_eq!(v["intent"], "booking");
+        assert!(parse_json_reply("no json here").is_none());
+        assert!(parse_json_reply("[1, 2]").is_none());
+    }
+
+    #[test]
+    fn degraded_model_routes_to_fallback_and_alerts_inbox() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = Arc::new(KnowledgeStore::open_path(dir.path()).unwrap());
+        let monitor = Arc::new(QualityMonitor::new(QualityThresholds {
+            min_samples: 2,
+            notify: Some(AlertTarget::Inbox { agent_id: "ops".to_string() }),
+            ..QualityThresholds::default()
+        }));
+        let api = |url: &str, key: &str, model: &str| LlmApiConfig {
+            api_url: Some(url.to_string()),
+            api_key: Some(key.to_string()),
+            api_key_env: None,
+            model: Some(model.to_string()),
+        };
+        let router = ModelRouter::with_knowledge(Arc::clone(&store))
+            .with_api_config(api("https://primary", "pk", "primary-model"))
+            .with_fallback_api(api("https://backup", "bk", "backup-model"))
+            .with_quality_monitor(Arc::clone(&monitor));
+
+        let route = router.route(None).unwrap();
+        assert!(!route.fallback);
+        let probe = router.probe(&route);
+        probe.observe(GenerationOutcome::Failed);
+        probe.observe(GenerationOutcome::Failed);
+
+        let route = router.route(None).unwrap();
+        assert!(route.fallback);
+        assert_eq!((route.url.as_str(), route.key.as_str()), ("https://backup", "bk"));
+        assert_eq!(route.quality_key(), "fallback:backup-model");
+
+        let alert = store.get_agent_messages("ops", 1).unwrap().remove(0);
+        assert_eq!(alert.from_agent_id, LLM_QUALITY_SENDER);
+        assert_eq!(alert.payload["type"], LLM_QUALITY_ALERT_TYPE);
+        assert_eq!(alert.payload["model"], "primary-model");
+        assert_eq!(alert.payload["fallback_model"], "backup-model");
+    }
+
+    /// Serves one canned HTTP response per connection; returns the URL and the requests received.
+    async fn canned_server(status: &'static str, body: &'static str) -> (String, Arc<std::sync::Mutex<Vec<String>>>) {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/v1/chat", listener.local_addr().unwrap());
+        let requests = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let seen = Arc::clone(&requests);
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let mut raw = Vec::new();
+                let mut buf = [0u8; 4096];
+                // Headers, then as many body bytes as Content-Length says.
+                loop {
+                    let n = socket.read(&mut buf).await.unwrap_or(0);
+                    raw.extend_from_slice(&buf[..n]);
+                    let text = String::from_utf8_lossy(&raw).to_string();
+                    if let Some(head_end) = text.find("\r\n\r\n") {
+                        let length = text
+                            .lines()
+                            .find_map(|l| l.to_ascii_lowercase().strip_prefix("content-length:").map(|v| v.trim().parse::<usize>().unwrap_or(0)))
+                            .unwrap_or(0);
+                        if n == 0 || raw.len() >= head_end + 4 + length {
+                            break;
+                        }
+                    } else if n == 0 {
+                        break;
+                    }
+                }
+                seen.lock().unwrap().push(String::from_utf8_lossy(&raw).to_string());
+                let response = format!(
+                    "HTTP/1.1 {}\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                );
+                let _ = socket.write_all(response.as_bytes()).await;
+            }
+        });
+        (url, requests)
+    }
+
+    #[tokio::test]
+    async fn alias_fails_over_on_5xx_and_tracks_provider_health() {
+        let (down_url, _) = canned_server("503 Service Unavailable", r#"{"error":"overloaded"}"#).await;
+        let (claude_url, claude_requests) = canned_server(
+            "200 OK",
+            r#"{"content":[{"type":"text","text":"Hello from the backup"}],"usage":{"input_tokens":7,"output_tokens":4}}"#,
+        )
+        .await;
+        std::env::set_var("PAGI_TEST_ANTHROPIC_KEY", "ak-test");
+        let provider = |kind, url: &str, key_env: Option<&str>| pagi_core::LlmProviderConfig {
+            kind,
+            api_url: Some(url.to_string()),
+            api_key_env: key_env.map(str::to_string),
+            api_version: None,
+        };
+        let target = |provider: &str, model: &str| pagi_core::ModelTarget {
+            provider: provider.to_string(),
+            model: model.to_string(),
+        };
+        let providers = Arc::new(LlmProviders::new(
+            [
+                ("local".to_string(), provider(LlmProviderKind::Ollama, &down_url, None)),
+                ("claude".to_string(), provider(LlmProviderKind::Anthropic, &claude_url, Some("PAGI_TEST_ANTHROPIC_KEY"))),
+            ]
+            .into_iter()
+            .collect(),
+            [("chat".to_string(), vec![target("local", "llama3"), target("claude", "claude-sonnet")])]
+                .into_iter()
+                .collect(),
+        ));
+        let router = ModelRouter::with_mode(LlmMode::Live).with_providers(Arc::clone(&providers));
+        let ctx = TenantContext::new("test");
+
+        let out = router
+            .execute(&ctx, Some(serde_json::json!({ "prompt": "hi", "system_prompt": "be brief", "model": "chat" })))
+            .await
+            .unwrap();
+        assert_eq!(out["generated"], "Hello from the backup");
+        assert_eq!(out["token_usage"]["total_tokens"], 11);
+
+        let request = claude_requests.lock().unwrap().remove(0);
+        assert!(request.contains("x-api-key: ak-test"), "{}", request);
+        let body: serde_json::Value = serde_json::from_str(&request[request.find("\r\n\r\n").unwrap() + 4..]).unwrap();
+        assert_eq!(body["model"], "claude-sonnet");
+        assert_eq!(body["system"], "be brief");
+        assert_eq!(body["messages"].as_array().unwrap().len(), 1);
+
+        let health = providers.health();
+        let status = |name: &str| health.iter().find(|h| h.provider == name).unwrap().clone();
+        assert_eq!((status("local").failures, status("local").status), (1, "up"));
+        assert!(status("local").last_error.unwrap().contains("503"));
+        assert_eq!((status("claude").requests, status("claude").failures), (1, 0));
+    }
+
+    #[tokio::test]
+    async fn usage_is_metered_per_tenant_until_the_budget_runs_out() {
+        let (url, requests) = canned_server(
+            "200 OK",
+            r#"{"choices":[{"message":{"content":"ok"}}],"usage":{"prompt_tokens":40,"completion_tokens":20,"total_tokens":60}}"#,
+        )
+        .await;
+        let store = Arc::new(KnowledgeStore::open_temporary().unwrap());
+        let router = ModelRouter {
+            mode: LlmMode::Live,
+            ..ModelRouter::with_knowledge(Arc::clone(&store))
+        }
+        .with_api_config(LlmApiConfig {
+            api_url: Some(url),
+            api_key: Some("k".to_string()),
+            api_key_env: None,
+            model: Some("m".to_string()),
+        })
+        .with_budgets(TokenBudgetConfig {
+            monthly_tokens: Some(100),
+            tenants: Default::default(),
+        });
+        let ctx = TenantContext {
+            tenant_id: "acme".to_string(),
+            correlation_id: None,
+            agent_id: Some("sales".to_string()),
+            ..Default::default()
+        };
+        let payload = serde_json::json!({ "prompt": "hi" });
+
+        router.execute(&ctx, Some(payload.clone())).await.unwrap();
+        router.execute(&ctx, Some(payload.clone())).await.unwrap();
+        let usage = &store.list_token_usage("acme").unwrap()[0];
+        assert_eq!((usage.total_tokens, usage.requests), (120, 2));
+        assert_eq!(usage.by_agent["sales"].prompt_tokens, 80);
+
+        // 120 of 100 used: the next call is refused before reaching the provider.
+        let err = router.execute(&ctx, Some(payload.clone())).await.unwrap_err();
+        let exceeded = err.downcast_ref::<TokenBudgetExceeded>().expect("budget error");
+        assert_eq!((exceeded.used_tokens, exceeded.budget_tokens), (120, 100));
+        assert!(router.execute_stream(&ctx, Some(payload)).await.is_err());
+        assert_eq!(requests.lock().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn live_prompts_are_masked_before_the_provider_sees_them() {
+        // The classifier and the generation get the same canned reply: a JSON list naming one person.
+        let (url, requests) = canned_server("200 OK", r#"{"choices":[{"message":{"content":"{\"pii\":[\"Jane Doe\"]}"}}]}"#).await;
+        let store = Arc::new(KnowledgeStore::open_temporary().unwrap());
+        let redactor = Redactor::from_config(&pagi_core::RedactionConfig {
+            enabled: true,
+            classifier_model: Some("local-pii".to_string()),
+            ..Default::default()
+        })
+        .unwrap();
+        let router = ModelRouter {
+            mode: LlmMode::Live,
+            ..ModelRouter::with_knowledge(Arc::clone(&store))
+        }
+        .with_api_config(LlmApiConfig {
+            api_url: Some(url),
+            api_key: Some("k".to_string()),
+            api_key_env: None,
+            model: Some("m".to_string()),
+        })
+        .with_redaction(redactor);
+        let ctx = TenantContext {
+            tenant_id: "acme".to_string(),
+            correlation_id: Some("c-1".to_string()),
+            agent_id: Some("sales".to_string()),
+            ..Default::default()
+        };
+
+        let out = router
+            .execute(&ctx, Some(serde_json::json!({ "prompt": "Write to Jane Doe at jane@example.com" })))
+            .await
+            .unwrap();
+        assert_eq!(out["redacted"], serde_json::json!({ "email": 1, "classified": 1 }));
+
+        let requests = requests.lock().unwrap();
+        let body = |i: usize| -> serde_json::Value {
+            let raw: &String = &requests[i];
+            serde_json::from_str(&raw[raw.find("\r\n\r\n").unwrap() + 4..]).unwrap()
+        };
+        // The classifier sees the pattern-masked prompt; the generation sees both masks.
+        assert_eq!(body(0)["model"], "local-pii");
+        assert_eq!(body(0)["messages"][1]["content"], "Write to Jane Doe at [REDACTED:email]");
+        let sent = body(1)["messages"][0]["content"].as_str().unwrap().to_string();
+        assert!(sent.starts_with("Write to [REDACTED:classified] at [REDACTED:email]"), "{}", sent);
+
+        let audit = store
+            .query_audit(&pagi_core::AuditQuery {
+                goal_type: Some(pagi_core::REDACTION_GOAL_TYPE.to_string()),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(audit.records.len(), 1);
+        assert_eq!(audit.records[0].skill.as_deref(), Some(SKILL_NAME));
+        assert_eq!(audit.records[0].correlation_id.as_deref(), Some("c-1"));
+    }
+
+    #[tokio::test]
+    async fn identical_generations_are_served_from_the_cache() {
+        let (url, requests) = canned_server(
+            "200 OK",
+            r#"{"choices":[{"message":{"content":"Kardia"}}],"usage":{"prompt_tokens":5,"completion_tokens":1,"total_tokens":6}}"#,
+        )
+        .await;
+        let store = Arc::new(KnowledgeStore::open_temporary().unwrap());
+        let router = ModelRouter {
+            mode: LlmMode::Live,
+            ..ModelRouter::with_knowledge(Arc::clone(&store))
+        }
+        .with_api_config(LlmApiConfig {
+            api_url: Some(url),
+            api_key: Some("k".to_string()),
+            api_key_env: None,
+            model: Some("m".to_string()),
+        })
+        .with_response_cache(ResponseCacheConfig {
+            ttl_secs: 60,
+            max_entries: 10,
+        });
+        let ctx = TenantContext::new("acme");
+        let payload = serde_json::json!({ "prompt": "hi", "system_prompt": "be brief" });
+
+        let first = router.execute(&ctx, Some(payload.clone())).await.unwrap();
+        assert!(first.get("cached").is_none());
+        let second = router.execute(&ctx, Some(payload)).await.unwrap();
+        assert_eq!((second["cached"].as_bool(), second["generated"].as_str()), (Some(true), Some("Kardia")));
+        assert!(second["cached_at_ms"].as_i64().is_some() && second.get("token_usage").is_none());
+        // Different parameters miss.
+        router
+            .execute(&ctx, Some(serde_json::json!({ "prompt": "hi", "system_prompt": "be brief", "temperature": 0.1 })))
+            .await
+            .unwrap();
+        assert_eq!(requests.lock().unwrap().len(), 2);
+
+        assert_eq!(router.generate_text_raw("classify").await.unwrap(), "Kardia");
+        assert_eq!(router.generate_text_raw("classify").await.unwrap(), "Kardia");
+        assert_eq!(requests.lock().unwrap().len(), 3);
+        // Cache hits are not metered.
+        assert_eq!(store.list_token_usage("acme").unwrap()[0].requests, 2);
+    }
+
+    #[test]
+    fn unreported_usage_is_estimated_from_text() {
+        assert_eq!(estimate_tokens(""), 0);
+        assert_eq!(estimate_tokens("abcd"), 1);
+        assert_eq!(estimate_tokens("abcde"), 2);
+    }
+
+    #[test]
+    fn anthropic_stream_events_carry_text_and_usage() {
+        let parse = |data: &str| StreamUpdate::parse(LlmProviderKind::Anthropic, data).unwrap();
+        let start = parse(r#"{"type":"message_start","message":{"content":[],"usage":{"input_tokens":12,"output_tokens":1}}}"#);
+        let delta = parse(r#"{"type":"content_block_delta","index":0,"delta":{"type":"text_delta","text":"Hi"}}"#);
+        let end = parse(r#"{"type":"message_delta","delta":{"stop_reason":"end_turn"},"usage":{"output_tokens":5}}"#);
+        assert_eq!(delta.delta.as_deref(), Some("Hi"));
+        let mut usage = TokenUsage::default();
+        usage.merge(start.usage.unwrap());
+        usage.merge(end.usage.unwrap());
+        assert_eq!((usage.prompt_tokens, usage.completion_tokens, usage.total_tokens), (12, 5, 17));
+        assert!(parse(r#"{"type":"message_stop"}"#).done);
+        assert!(StreamUpdate::parse(LlmProviderKind::Openai, "[DONE]").unwrap().done);
+    }
+
+    #[tokio::test]
+    async fn mental_state_modulates_generation_parameters() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = Arc::new(KnowledgeStore::open_path(dir.path()).unwrap());
+        let router = ModelRouter {
+            mode: LlmMode::Mock,
+            ..ModelRouter::with_knowledge(Arc::clone(&store))
+        }
+        .with_modulation(GenerationModulation::default());
+        let ctx = TenantContext {
+            tenant_id: "test".to_string(),
+            correlation_id: None,
+            agent_id: Some("stressed".to_string()),
+            ..Default::default()
+        };
+        let payload = serde_json::json!({ "prompt": "hello", "temperature": 0.8, "max_tokens": 1000 });
+
+        // Calm state: request untouched, nothing recorded.
+        let out = router.execute(&ctx, Some(payload.clone())).await.unwrap();
+        assert!(out.get("generation_adjustment").is_none());
+
+        let state = pagi_core::MentalState {
+            relational_stress: 0.9,
+            burnout_risk: 0.8,
+            grace_multiplier: 1.0,
+        };
+        store.set_mental_state("stressed", &state).unwrap();
+        let out = router.execute(&ctx, Some(payload)).await.unwrap();
+        let adj = &out["generation_adjustment"];
+        assert!((adj["temperature"].as_f64().unwrap() - 0.5).abs() < 1e-4);
+        assert_eq!(adj["max_tokens"], 600);
+        assert_eq!(adj["response_words"], 180);
+        assert_eq!(adj["reasons"].as_array().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn mock_tool_mode_calls_a_named_tool_then_answers_with_its_result() {
+        let router = ModelRouter::with_mode(LlmMode::Mock);
+        let ctx = TenantContext::new("test");
+        let tools = serde_json::json!([{ "name": "recall_past_actions", "description": "", "parameters": {} }]);
+        let call = router
+            .execute(
+                &ctx,
+                Some(serde_json::json!({
+                    "messages": [{ "role": "user", "content": "Use recall_past_actions please" }],
+                    "tools": tools,
+                })),
+            )
+            .await
+            .unwrap();
+        assert_eq!(call["tool_calls"][0]["name"], "recall_past_actions");
+
+        let answer = router
+            .execute(
+                &ctx,
+                Some(serde_json::json!({
+                    "messages": [
+                        { "role": "user", "content": "Use recall_past_actions please" },
+                        { "role": "assistant", "content": "", "tool_calls": call["tool_calls"] },
+                        { "role": "tool", "tool_call_id": "call_mock_1", "name": "recall_past_actions", "content": "3 events" },
+                    ],
+                    "tools": tools,
+                })),
+            )
+            .await
+            .unwrap();
+        assert_eq!(answer["tool_calls"], serde_json::json!([]));
+        assert!(answer["generated"].as_str().unwrap().contains("recall_past_actions: 3 events"));
+    }
+
+    #[tokio::test]
+    async fn anthropic_tool_turns_use_tool_use_and_tool_result_blocks() {
+        let (url, requests) = canned_server(
+            "200 OK",
+            r#"{"content":[{"type":"tool_use","id":"tu_2","name":"clock","input":{"zone":"UTC"}}],"usage":{"input_tokens":9,"output_tokens":3}}"#,
+        )
+        .await;
+        std::env::set_var("PAGI_TEST_ANTHROPIC_TOOLS_KEY", "ak-test");
+        let providers = Arc::new(LlmProviders::new(
+            [(
+                "claude".to_string(),
+                pagi_core::LlmProviderConfig {
+                    kind: LlmProviderKind::Anthropic,
+                    api_url: Some(url),
+                    api_key_env: Some("PAGI_TEST_ANTHROPIC_TOOLS_KEY".to_string()),
+                    api_version: None,
+                },
+            )]
+            .into_iter()
+            .collect(),
+            [(
+                "agent".to_string(),
+                vec![pagi_core::ModelTarget {
+                    provider: "claude".to_string(),
+                    model: "claude-sonnet".to_string(),
+                }],
+            )]
+            .into_iter()
+            .collect(),
+        ));
+        let router = ModelRouter::with_mode(LlmMode::Live).with_providers(providers);
+        let ctx = TenantContext::new("test");
+        let out = router
+            .execute(
+                &ctx,
+                Some(serde_json::json!({
+                    "model": "agent",
+                    "system_prompt": "be brief",
+                    "messages": [
+                        { "role": "user", "content": "time?" },
+                        { "role": "assistant", "content": "", "tool_calls": [{ "id": "tu_1", "name": "clock", "arguments": {} }] },
+                        { "role": "tool", "tool_call_id": "tu_1", "content": "12:00" },
+                    ],
+                    "tools": [{ "name": "clock", "description": "time", "parameters": { "type": "object" } }],
+                })),
+            )
+            .await
+            .unwrap();
+        assert_eq!(out["tool_calls"][0], serde_json::json!({ "id": "tu_2", "name": "clock", "arguments": { "zone": "UTC" } }));
+        assert_eq!(out["token_usage"]["total_tokens"], 12);
+
+        let request = requests.lock().unwrap().remove(0);
+        let body: serde_json::Value = serde_json::from_str(&request[request.find("\r\n\r\n").unwrap() + 4..]).unwrap();
+        assert_eq!(body["system"], "be brief");
+        assert_eq!(body["tools"][0]["input_schema"]["type"], "object");
+        assert_eq!(body["messages"][1]["content"][0]["type"], "tool_use");
+        assert_eq!(body["messages"][2]["role"], "user");
+        assert_eq!(body["messages"][2]["content"][0]["tool_use_id"], "tu_1");
+    }
 }
@@ -0,0 +1,136 @@
+//! Notify Chat skill: posts a message to a Slack or Discord channel webhook (`[chat]`).
+//!
+//! Payload: `{ text?, channel?, event?, data? }`. Without `channel` the acting agent's channel from
+//! `[chat.agents]` is used, else `[chat] default_channel`. Without `text` the message is written
+//! from `event` and `data`, which is how the orchestrator posts the events listed in
+//! `[chat] notify` (policy violations, Oikos maintenance tasks, completed goals).
+
+use pagi_core::{AgentSkill, ChatConfig, ChatKind, TenantContext, WebhookEvent, DEFAULT_TENANT_ID};
+use std::time::Duration;
+
+const SKILL_NAME: &str = "NotifyChat";
+/// Discord rejects longer `content`.
+const DISCORD_MAX_CHARS: usize = 2000;
+const POST_TIMEOUT: Duration = Duration::from_secs(10);
+
+fn field<'a>(data: &'a serde_json::Value, name: &str) -> &'a str {
+    data.get(name).and_then(|v| v.as_str()).unwrap_or("?")
+}
+
+/// One line describing `event`.
+fn event_message(event: WebhookEvent, data: &serde_json::Value) -> String {
+    match event {
+        WebhookEvent::PolicyViolated => format!(
+            "Policy violation: {} was blocked for agent {} ({})",
+            field(data, "skill"),
+            field(data, "agent_id"),
+            data.get("reason").and_then(|r| r.as_str()).unwrap_or("no reason given")
+        ),
+        WebhookEvent::MaintenanceOpened => format!("Maintenance task opened: {}", field(data, "task")),
+        WebhookEvent::GoalCompleted => {
+            let steps = data.get("plan_steps").and_then(|s| s.as_array()).map_or(0, Vec::len);
+            format!("Goal completed: \"{}\" ({} step(s))", field(data, "intent"), steps)
+        }
+        WebhookEvent::LeadCaptured => format!(
+            "New lead captured: {}",
+            data.pointer("/result/lead_id").and_then(|v| v.as_str()).unwrap_or("?")
+        ),
+        WebhookEvent::DraftGenerated => format!(
+            "Draft generated for lead {}",
+            data.pointer("/result/lead_id").and_then(|v| v.as_str()).unwrap_or("?")
+        ),
+    }
+}
+
+/// Posts messages to the configured chat channels.
+pub struct NotifyChat {
+    config: ChatConfig,
+    http: reqwest::Client,
+}
+
+impl NotifyChat {
+    pub fn new(config: ChatConfig) -> Self {
+        Self {
+            config,
+            http: reqwest::Client::new(),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl AgentSkill for NotifyChat {
+    fn name(&self) -> &str {
+        SKILL_NAME
+    }
+
+    async fn execute(
+        &self,
+        ctx: &TenantContext,
+        payload: Option<serde_json::Value>,
+    ) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>> {
+        let payload = payload.unwrap_or(serde_json::Value::Null);
+        let agent_id = ctx.resolved_agent_id();
+        let (channel_name, channel) = match payload.get("channel").and_then(|c| c.as_str()) {
+            Some(name) => (
+                name,
+                self.config.channels.get(name).ok_or_else(|| format!("NotifyChat: unknown channel {:?}", name))?,
+            ),
+            None => self
+                .config
+                .channel_for(agent_id)
+                .ok_or_else(|| format!("NotifyChat: no channel for agent {} and no default_channel", agent_id))?,
+        };
+        let text = match payload.get("text").and_then(|t| t.as_str()).map(str::trim).filter(|t| !t.is_empty()) {
+            Some(text) => text.to_string(),
+            None => {
+                let event: WebhookEvent = payload
+                    .get("event")
+                    .cloned()
+                    .and_then(|e| serde_json::from_value(e).ok())
+                    .ok_or("NotifyChat requires payload: { text } or { event, data }")?;
+                let message = event_message(event, payload.get("data").unwrap_or(&serde_json::Value::Null));
+                match ctx.tenant_id.as_str() {
+                    DEFAULT_TENANT_ID => message,
+                    tenant_id => format!("[{}] {}", tenant_id, message),
+                }
+            }
+        };
+        let url = pagi_core::secret(&channel.webhook_env)
+            .ok_or_else(|| format!("NotifyChat: webhook secret {:?} of channel {} is not set", channel.webhook_env, channel_name))?;
+        let body = match channel.kind {
+            ChatKind::Slack => serde_json::json!({ "text": text }),
+            ChatKind::Discord => serde_json::json!({ "content": text.chars().take(DISCORD_MAX_CHARS).collect::<String>() }),
+        };
+        let res = self.http.post(&url).timeout(POST_TIMEOUT).json(&body).send().await?;
+        if !res.status().is_success() {
+            return Err(format!("NotifyChat: channel {} answered {}", channel_name, res.status()).into());
+        }
+        Ok(serde_json::json!({
+            "status": "sent",
+            "skill": SKILL_NAME,
+            "channel": channel_name,
+            "kind": channel.kind,
+            "text": text,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn events_read_as_one_line_messages() {
+        let violation = serde_json::json!({ "skill": "SendEmail", "agent_id": "sales", "reason": "Outbound pricing needs review" });
+        assert_eq!(
+            event_message(WebhookEvent::PolicyViolated, &violation),
+            "Policy violation: SendEmail was blocked for agent sales (Outbound pricing needs review)"
+        );
+        let goal = serde_json::json!({ "intent": "respond to lead", "plan_steps": ["DraftResponse", "ModelRouter"] });
+        assert_eq!(event_message(WebhookEvent::GoalCompleted, &goal), "Goal completed: \"respond to lead\" (2 step(s))");
+        assert_eq!(
+            event_message(WebhookEvent::MaintenanceOpened, &serde_json::json!({ "task": "Rotate logs" })),
+            "Maintenance task opened: Rotate logs"
+        );
+    }
+}
@@ -142,7 +142,7 @@ impl AgentSkill for OikosTaskGovernor {
                 .as_deref()
                 .map(parse_difficulty)
                 .unwrap_or(TaskDifficulty::Medium);
-            let mut task = GovernedTask::new(&t.task_id, &t.title, difficulty)
+            let mut task = GovernedTask::new(&t.task_id, &t.title, difficulty, self.store.now_ms())
                 .with_description(&t.description)
                 .with_tags(t.tags.clone());
             if let Some(p) = t.base_priority {
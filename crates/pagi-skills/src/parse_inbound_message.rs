@@ -0,0 +1,361 @@
+//! Parse Inbound Message skill: extracts structured fields from free-form lead/email text.
+//!
+//! Fields: intent category, urgency, requested service, and mentioned dates. Live mode asks the
+//! ModelRouter in JSON mode; mock mode, LLM errors, and malformed replies fall back to a
+//! deterministic keyword parser. With a `lead_id`, the stored lead
+//! (`lead_history/{tenant}/{lead_id}`) is enriched with a `parsed` object before the response
+//! chain (DraftResponse → SalesCloser → ModelRouter) reads it.
+
+use pagi_core::{AgentSkill, MemoryManager, TenantContext};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+use crate::model_router::ModelRouter;
+
+const SKILL_NAME: &str = "ParseInboundMessage";
+const LEAD_HISTORY_PREFIX: &str = "lead_history";
+/// Lead fields that may hold the message text, in lookup order.
+const TEXT_FIELDS: [&str; 4] = ["text", "message", "body", "content"];
+
+/// Intent categories, in fallback-parser priority order (`other` when nothing matches).
+const INTENTS: [(&str, &[&str]); 6] = [
+    ("cancellation", &["cancel", "unsubscribe", "refund"]),
+    ("complaint", &["complaint", "unhappy", "disappointed", "terrible", "worst", "frustrated"]),
+    ("quote_request", &["quote", "price", "pricing", "cost", "estimate", "how much"]),
+    ("booking", &["book", "appointment", "schedule", "reserve", "availability", "available"]),
+    ("support", &["help", "issue", "problem", "broken", "not working", "error", "fix"]),
+    ("information", &["information", "info", "wondering", "question", "?"]),
+];
+const HIGH_URGENCY: [&str; 6] = ["urgent", "asap", "emergency", "immediately", "right away", "today"];
+const MEDIUM_URGENCY: [&str; 4] = ["soon", "tomorrow", "this week", "next week"];
+/// Phrases that introduce the requested service ("looking for a roof inspection").
+const SERVICE_CUES: [&str; 8] = [
+    "interested in",
+    "looking for",
+    "quote for",
+    "quote on",
+    "help with",
+    "would like",
+    "need",
+    "want",
+];
+const SERVICE_STOP_WORDS: [&str; 13] = [
+    "on", "by", "before", "asap", "urgently", "immediately", "soon", "today", "tomorrow", "tonight", "next",
+    "this", "please",
+];
+const SERVICE_ARTICLES: [&str; 6] = ["a", "an", "the", "some", "my", "our"];
+const WEEKDAYS: [&str; 7] = [
+    "monday", "tuesday", "wednesday", "thursday", "friday", "saturday", "sunday",
+];
+const MONTHS: [&str; 12] = [
+    "january", "february", "march", "april", "may", "june", "july", "august", "september", "october",
+    "november", "december",
+];
+
+/// Structured view of an inbound message.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ParsedMessage {
+    /// One of `cancellation`, `complaint`, `quote_request`, `booking`, `support`, `information`, `other`.
+    pub intent: String,
+    /// `high`, `medium`, or `low`.
+    pub urgency: String,
+    #[serde(default)]
+    pub requested_service: Option<String>,
+    /// Dates as mentioned (`2026-03-04`, `next friday`, `march 3`, ...).
+    #[serde(default)]
+    pub dates: Vec<String>,
+}
+
+impl ParsedMessage {
+    /// Deterministic keyword parser.
+    pub fn parse_fallback(text: &str) -> Self {
+        let lower = text.to_lowercase();
+        let intent = INTENTS
+            .iter()
+            .find(|(_, cues)| cues.iter().any(|c| lower.contains(c)))
+            .map(|(intent, _)| *intent)
+            .unwrap_or("other");
+        let urgency = if HIGH_URGENCY.iter().any(|c| lower.contains(c)) {
+            "high"
+        } else if MEDIUM_URGENCY.iter().any(|c| lower.contains(c)) {
+            "medium"
+        } else {
+            "low"
+        };
+        Self {
+            intent: intent.to_string(),
+            urgency: urgency.to_string(),
+            requested_service: extract_service(&lower),
+            dates: extract_dates(&lower),
+        }
+    }
+
+    /// Keeps LLM values that fit the schema and fills the rest from `fallback`.
+    fn normalized(self, fallback: &ParsedMessage) -> Self {
+        let intent = self.intent.trim().to_lowercase();
+        let urgency = self.urgency.trim().to_lowercase();
+        Self {
+            intent: if intent == "other" || INTENTS.iter().any(|(i, _)| *i == intent) {
+                intent
+            } else {
+                fallback.intent.clone()
+            },
+            urgency: if matches!(urgency.as_str(), "high" | "medium" | "low") {
+                urgency
+            } else {
+                fallback.urgency.clone()
+            },
+            requested_service: self
+                .requested_service
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .or_else(|| fallback.requested_service.clone()),
+            dates: self.dates,
+        }
+    }
+}
+
+fn extract_service(lower: &str) -> Option<String> {
+    let padded = format!(" {} ", lower);
+    let (pos, cue) = SERVICE_CUES
+        .iter()
+        .filter_map(|cue| padded.find(&format!(" {} ", cue)).map(|pos| (pos, *cue)))
+        .min_by_key(|(pos, _)| *pos)?;
+    let rest = &padded[pos + cue.len() + 2..];
+    let end = rest.find(['.', ',', '!', '?', ';', '\n']).unwrap_or(rest.len());
+    let words: Vec<&str> = rest[..end]
+        .split_whitespace()
+        .skip_while(|w| SERVICE_ARTICLES.contains(w) || *w == "to")
+        .take_while(|w| !SERVICE_STOP_WORDS.contains(w))
+        .take(6)
+        .collect();
+    (!words.is_empty()).then(|| words.join(" "))
+}
+
+fn is_weekday(word: &str) -> bool {
+    WEEKDAYS.contains(&word)
+}
+
+fn is_month(word: &str) -> bool {
+    MONTHS
+        .iter()
+        .any(|m| *m == word || (word.len() == 3 && m.starts_with(word)))
+        || word == "sept"
+}
+
+fn is_day(word: &str) -> bool {
+    let digits = word.trim_end_matches(|c: char| c.is_ascii_alphabetic());
+    let suffix = &word[digits.len()..];
+    matches!(suffix, "" | "st" | "nd" | "rd" | "th")
+        && digits.parse::<u8>().is_ok_and(|d| (1..=31).contains(&d))
+}
+
+/// `2026-03-04`, `3/4`, `03/04/2026`.
+fn is_numeric_date(word: &str) -> bool {
+    let iso: Vec<&str> = word.split('-').collect();
+    if iso.len() == 3 && [4, 2, 2] == [iso[0].len(), iso[1].len(), iso[2].len()] {
+        return iso.iter().all(|p| p.chars().all(|c| c.is_ascii_digit()));
+    }
+    let parts: Vec<&str> = word.split('/').collect();
+    (parts.len() == 2 || parts.len() == 3)
+        && parts.iter().all(|p| !p.is_empty() && p.chars().all(|c| c.is_ascii_digit()))
+        && parts[..2].iter().all(|p| p.len() <= 2 && p.parse::<u8>().is_ok_and(|n| (1..=31).contains(&n)))
+}
+
+/// Date mention starting at `word`: the text and how many words it spans.
+fn date_at(word: &str, next: Option<&str>) -> Option<(String, usize)> {
+    if is_numeric_date(word) || matches!(word, "today" | "tomorrow" | "tonight") || is_weekday(word) {
+        return Some((word.to_string(), 1));
+    }
+    let next = next?;
+    let relative = matches!(word, "next" | "this") && (matches!(next, "week" | "weekend" | "month") || is_weekday(next));
+    let calendar = (is_month(word) && is_day(next)) || (is_day(word) && is_month(next));
+    (relative || calendar).then(|| (format!("{} {}", word, next), 2))
+}
+
+fn extract_dates(lower: &str) -> Vec<String> {
+    let words: Vec<&str> = lower
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .map(|w| w.trim_matches(|c: char| matches!(c, '.' | '!' | '?' | ';' | ':' | '(' | ')' | '"')))
+        .filter(|w| !w.is_empty())
+        .collect();
+    let mut dates: Vec<String> = Vec::new();
+    let mut i = 0;
+    while i < words.len() {
+        match date_at(words[i], words.get(i + 1).copied()) {
+            Some((date, consumed)) => {
+                if !dates.contains(&date) {
+                    dates.push(date);
+                }
+                i += consumed;
+            }
+            None => i += 1,
+        }
+    }
+    dates
+}
+
+fn build_extraction_prompt(text: &str) -> String {
+    let intents: Vec<&str> = INTENTS.iter().map(|(i, _)| *i).chain(["other"]).collect();
+    format!(
+        r#"Extract structured fields from this inbound customer message. Reply with a single JSON object only:
+{{"intent": one of {:?}, "urgency": "high" | "medium" | "low", "requested_service": string or null, "dates": [dates or times mentioned, as written]}}
+
+Message:
+"""
+{}
+"""#,
+        intents, text
+    )
+}
+
+fn message_text(value: &serde_json::Value) -> Option<String> {
+    TEXT_FIELDS
+        .iter()
+        .find_map(|field| value.get(*field).and_then(|v| v.as_str()))
+        .map(|s| s.to_string())
+}
+
+/// Extracts intent, urgency, service, and dates from lead/email text and enriches the stored lead.
+pub struct ParseInboundMessage {
+    memory: Arc<MemoryManager>,
+    router: Arc<ModelRouter>,
+}
+
+impl ParseInboundMessage {
+    pub fn new(memory: Arc<MemoryManager>, router: Arc<ModelRouter>) -> Self {
+        Self { memory, router }
+    }
+
+    /// LLM JSON extraction with deterministic fallback. Returns the fields and the parser used.
+    async fn parse(&self, text: &str) -> (ParsedMessage, &'static str) {
+        let fallback = ParsedMessage::parse_fallback(text);
+        let prompt = build_extraction_prompt(&text.chars().take(4000).collect::<String>());
+        match self.router.generate_json(&prompt).await {
+            Ok(value) => match serde_json::from_value::<ParsedMessage>(value) {
+                Ok(parsed) => (parsed.normalized(&fallback), "llm"),
+                Err(e) => {
+                    tracing::debug!(target: "pagi::skills", error = %e, "ParseInboundMessage: LLM JSON did not match schema");
+                    (fallback, "fallback")
+                }
+            },
+            Err(e) => {
+                tracing::debug!(target: "pagi::skills", error = %e, "ParseInboundMessage: using fallback parser");
+                (fallback, "fallback")
+            }
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl AgentSkill for ParseInboundMessage {
+    fn name(&self) -> &str {
+        SKILL_NAME
+    }
+
+    async fn execute(
+        &self,
+        ctx: &TenantContext,
+        payload: Option<serde_json::Value>,
+    ) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>> {
+        let payload = payload.unwrap_or(serde_json::Value::Null);
+        let lead_id = payload.get("lead_id").and_then(|v| v.as_str()).map(|s| s.to_string());
+        let path = lead_id
+            .as_ref()
+            .map(|id| format!("{}/{}/{}", LEAD_HISTORY_PREFIX, ctx.tenant_id, id));
+        let mut lead: Option<serde_json::Value> = match &path {
+            Some(path) => self
+                .memory
+                .get_path(ctx, path)?
+                .and_then(|bytes| serde_json::from_slice(&bytes).ok()),
+            None => None,
+        };
+        let text = message_text(&payload)
+            .or_else(|| lead.as_ref().and_then(message_text))
+            .ok_or("ParseInboundMessage requires payload: { text } or { lead_id } of a lead with message text")?;
+
+        let (parsed, parser) = self.parse(&text).await;
+        let mut parsed_json = serde_json::to_value(&parsed)?;
+        parsed_json["parser"] = serde_json::json!(parser);
+
+        let mut enriched = false;
+        if let (Some(path), Some(serde_json::Value::Object(record))) = (&path, lead.as_mut()) {
+            record.insert("parsed".to_string(), parsed_json.clone());
+            self.memory.save_path(ctx, path, &serde_json::to_vec(record)?)?;
+            enriched = true;
+        }
+
+        Ok(serde_json::json!({
+            "status": "ok",
+            "skill": SKILL_NAME,
+            "lead_id": lead_id,
+            "parsed": parsed_json,
+            "parser": parser,
+            "enriched": enriched,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model_router::LlmMode;
+
+    #[test]
+    fn fallback_extracts_fields() {
+        let parsed = ParsedMessage::parse_fallback(
+            "Hi, I'm looking for a roof inspection, ideally next Friday or March 3rd. Can you send a quote ASAP?",
+        );
+        assert_eq!(parsed.intent, "quote_request");
+        assert_eq!(parsed.urgency, "high");
+        assert_eq!(parsed.requested_service.as_deref(), Some("roof inspection"));
+        assert_eq!(parsed.dates, ["next friday", "march 3rd"]);
+
+        let parsed = ParsedMessage::parse_fallback("Please cancel my appointment on 2026-05-01.");
+        assert_eq!(parsed.intent, "cancellation");
+        assert_eq!(parsed.urgency, "low");
+        assert_eq!(parsed.dates, ["2026-05-01"]);
+        assert_eq!(ParsedMessage::parse_fallback("hello there").intent, "other");
+    }
+
+    #[test]
+    fn llm_values_are_normalized_against_fallback() {
+        let fallback = ParsedMessage::parse_fallback("book a cleaning tomorrow");
+        let llm = ParsedMessage {
+            intent: "Scheduling".into(),
+            urgency: "HIGH".into(),
+            requested_service: Some(" ".into()),
+            dates: vec!["tomorrow".into()],
+        };
+        let parsed = llm.normalized(&fallback);
+        assert_eq!(parsed.intent, "booking");
+        assert_eq!(parsed.urgency, "high");
+        assert_eq!(parsed.requested_service, fallback.requested_service);
+    }
+
+    #[tokio::test]
+    async fn enriches_stored_lead() {
+        let dir = tempfile::tempdir().unwrap();
+        let memory = Arc::new(MemoryManager::open_path(dir.path()).unwrap());
+        let ctx = TenantContext::new("t1");
+        let path = format!("{}/t1/lead-1", LEAD_HISTORY_PREFIX);
+        let lead = serde_json::json!({ "email": "a@b.c", "message": "My heater is broken, need a repair urgently" });
+        memory.save_path(&ctx, &path, &serde_json::to_vec(&lead).unwrap()).unwrap();
+
+        let skill = ParseInboundMessage::new(Arc::clone(&memory), Arc::new(ModelRouter::with_mode(LlmMode::Mock)));
+        let out = skill
+            .execute(&ctx, Some(serde_json::json!({ "lead_id": "lead-1" })))
+            .await
+            .unwrap();
+        assert_eq!(out["parser"], "fallback");
+        assert_eq!(out["lead_id"], "lead-1");
+        assert_eq!(out["enriched"], true);
+
+        let stored: serde_json::Value =
+            serde_json::from_slice(&memory.get_path(&ctx, &path).unwrap().unwrap()).unwrap();
+        assert_eq!(stored["email"], "a@b.c");
+        assert_eq!(stored["parsed"]["intent"], "support");
+        assert_eq!(stored["parsed"]["urgency"], "high");
+        assert_eq!(stored["parsed"]["requested_service"], "repair");
+    }
+}
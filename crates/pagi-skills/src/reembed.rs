@@ -0,0 +1,243 @@
+//! Bulk re-embedding: regenerates the stored embeddings of KB records with the current provider
+//! after the embedding model changes (job record: [`pagi_core::ReembedJob`]).
+//!
+//! Records are read in batches in key order; each batch is embedded with at most
+//! `job.concurrency` requests in flight, written back in place, and the job's cursor is saved, so
+//! a stopped job resumes after its last finished batch.
+
+use futures_util::stream::{self, StreamExt};
+use pagi_core::{KbRecord, KnowledgeStore, ReembedCursor, ReembedJob, ReembedStatus, EMBEDDING_MODEL_METADATA_KEY};
+use std::sync::Arc;
+
+use crate::model_router::ModelRouter;
+
+/// Records read (and checkpointed) at a time.
+pub const REEMBED_BATCH_SIZE: usize = 64;
+pub const DEFAULT_REEMBED_CONCURRENCY: usize = 4;
+pub const MAX_REEMBED_CONCURRENCY: usize = 32;
+
+enum Outcome {
+    Embedded(Vec<f32>),
+    /// Already produced by the job's model.
+    Current,
+    Failed(String),
+}
+
+/// Runs [`ReembedJob`]s against a store with the embedding provider of a [`ModelRouter`].
+pub struct Reembedder {
+    store: Arc<KnowledgeStore>,
+    router: ModelRouter,
+}
+
+impl Reembedder {
+    pub fn new(store: Arc<KnowledgeStore>) -> Self {
+        Self::with_router(store, ModelRouter::new())
+    }
+
+    pub fn with_router(store: Arc<KnowledgeStore>, router: ModelRouter) -> Self {
+        Self { store, router }
+    }
+
+    /// Creates and saves a job over `slots` (counting their embedded records as its total).
+    /// `model` overrides the provider's configured embedding model.
+    pub fn create_job(
+        &self,
+        slots: Vec<u8>,
+        model: Option<String>,
+        concurrency: usize,
+        force: bool,
+    ) -> Result<ReembedJob, Box<dyn std::error::Error + Send + Sync>> {
+        let embedding_model = self.router.embedding_model_name(model.as_deref());
+        let mut job = ReembedJob::new(
+            slots,
+            model,
+            embedding_model,
+            concurrency.clamp(1, MAX_REEMBED_CONCURRENCY),
+            force,
+            self.store.now_ms(),
+        );
+        for slot_id in &job.slots {
+            job.total += self.store.count_embedded_records(*slot_id)?;
+        }
+        self.store.save_reembed_job(&job)?;
+        Ok(job)
+    }
+
+    /// Runs (or resumes) `job` to the end, saving it after every batch. `on_batch` sees the job
+    /// after each checkpoint. Returns the job as `Completed`, or `Failed` on a store error.
+    pub async fn run(&self, mut job: ReembedJob, mut on_batch: impl FnMut(&ReembedJob)) -> ReembedJob {
+        job.status = ReembedStatus::Running;
+        job.error = None;
+        self.checkpoint(&mut job);
+        tracing::info!(
+            target: "pagi::knowledge",
+            job_id = %job.id,
+            slots = ?job.slots,
+            model = %job.embedding_model,
+            resumed = job.cursor.is_some(),
+            "Re-embedding started"
+        );
+
+        let model = job.model.clone();
+        for slot_id in job.remaining_slots() {
+            loop {
+                let batch = match self
+                    .store
+                    .scan_embedded_records(slot_id, job.resume_after(slot_id), REEMBED_BATCH_SIZE)
+                {
+                    Ok(batch) => batch,
+                    Err(e) => return self.fail(job, e),
+                };
+                let Some(last_key) = batch.last().map(|(key, _)| key.clone()) else {
+                    break;
+                };
+                let outcomes: Vec<(String, Outcome)> = stream::iter(batch)
+                    .map(|(key, record)| self.embed(key, record, model.as_deref(), &job))
+                    .buffered(job.concurrency)
+                    .collect()
+                    .await;
+                for (key, outcome) in outcomes {
+                    job.processed += 1;
+                    match outcome {
+                        Outcome::Embedded(embedding) => {
+                            match self.store.set_record_embedding(slot_id, &key, embedding, &job.embedding_model) {
+                                Ok(true) => job.updated += 1,
+                                // Removed while the batch was in flight.
+                                Ok(false) => job.skipped += 1,
+                                Err(e) => return self.fail(job, e),
+                            }
+                        }
+                        Outcome::Current => job.skipped += 1,
+                        Outcome::Failed(e) => {
+                            tracing::warn!(target: "pagi::knowledge", job_id = %job.id, slot_id, key = %key, error = %e, "Re-embedding failed for record");
+                            job.failed += 1;
+                            job.error = Some(e);
+                        }
+                    }
+                }
+                job.cursor = Some(ReembedCursor { slot_id, key: last_key });
+                self.checkpoint(&mut job);
+                on_batch(&job);
+            }
+        }
+
+        // A new model can change the vector size, which leaves records out of the slot's vector
+        // index until it is rebuilt around the new size.
+        if job.updated > 0 {
+            for slot_id in job.slots.clone() {
+                if let Err(e) = self.store.rebuild_vector_index(slot_id) {
+                    return self.fail(job, e);
+                }
+            }
+        }
+
+        job.status = ReembedStatus::Completed;
+        self.checkpoint(&mut job);
+        tracing::info!(
+            target: "pagi::knowledge",
+            job_id = %job.id,
+            updated = job.updated,
+            skipped = job.skipped,
+            failed = job.failed,
+            "Re-embedding completed"
+        );
+        job
+    }
+
+    async fn embed(&self, key: String, record: KbRecord, model: Option<&str>, job: &ReembedJob) -> (String, Outcome) {
+        let current = record
+            .metadata
+            .get(EMBEDDING_MODEL_METADATA_KEY)
+            .and_then(|m| m.as_str())
+            .is_some_and(|m| m == job.embedding_model);
+        if current && !job.force {
+            return (key, Outcome::Current);
+        }
+        let outcome = match self.router.embedding(&record.content, model).await {
+            Ok(embedding) => Outcome::Embedded(embedding),
+            Err(e) => Outcome::Failed(e.to_string()),
+        };
+        (key, outcome)
+    }
+
+    fn fail(&self, mut job: ReembedJob, error: impl std::fmt::Display) -> ReembedJob {
+        tracing::warn!(target: "pagi::knowledge", job_id = %job.id, error = %error, "Re-embedding stopped");
+        job.status = ReembedStatus::Failed;
+        job.error = Some(error.to_string());
+        self.checkpoint(&mut job);
+        job
+    }
+
+    fn checkpoint(&self, job: &mut ReembedJob) {
+        job.touch(self.store.now_ms());
+        if let Err(e) = self.store.save_reembed_job(job) {
+            tracing::warn!(target: "pagi::knowledge", job_id = %job.id, error = %e, "Failed to save re-embedding job");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model_router::LlmMode;
+    use pagi_core::KbType;
+
+    fn seed(store: &KnowledgeStore, count: usize) {
+        let slot_id = KbType::Logos.slot_id();
+        for i in 0..count {
+            let record = KbRecord::with_embedding(
+                format!("finding {}", i),
+                serde_json::json!({ "embedding_model": "old-model" }),
+                vec![1.0, 0.0, 0.0],
+                store.now_ms(),
+            );
+            store.insert_record(slot_id, &format!("research/{:03}", i), &record).unwrap();
+        }
+        // Records without a vector are not part of a re-embedding job.
+        store.insert_record(slot_id, "research/plain", &KbRecord::new("no vector", store.now_ms())).unwrap();
+    }
+
+    #[tokio::test]
+    async fn reembeds_in_place_and_resumes_from_the_cursor() {
+        let store = Arc::new(KnowledgeStore::open_temporary().unwrap());
+        seed(&store, REEMBED_BATCH_SIZE + 6);
+        let slot_id = KbType::Logos.slot_id();
+        let reembedder = Reembedder::with_router(Arc::clone(&store), ModelRouter::with_mode(LlmMode::Mock));
+
+        let job = reembedder.create_job(vec![slot_id], None, 3, false).unwrap();
+        assert_eq!(job.total, REEMBED_BATCH_SIZE + 6);
+        assert_eq!(job.embedding_model, "mock");
+
+        // Simulate a run that stopped after the first batch.
+        let mut interrupted = job.clone();
+        interrupted.status = ReembedStatus::Interrupted;
+        interrupted.cursor = Some(ReembedCursor {
+            slot_id,
+            key: format!("research/{:03}", REEMBED_BATCH_SIZE - 1),
+        });
+        interrupted.processed = REEMBED_BATCH_SIZE;
+        let mut batches = 0;
+        let done = reembedder.run(interrupted, |_| batches += 1).await;
+        assert_eq!(done.status, ReembedStatus::Completed);
+        assert_eq!(batches, 1);
+        assert_eq!(done.updated, 6);
+        assert_eq!(done.processed, done.total);
+        assert_eq!(store.get_reembed_job(&done.id).unwrap().unwrap().status, ReembedStatus::Completed);
+
+        let first = store.get_record(slot_id, "research/000").unwrap().unwrap();
+        assert_eq!(first.embedding.as_ref().unwrap().len(), 3, "before the cursor: untouched");
+        let last = store
+            .get_record(slot_id, &format!("research/{:03}", REEMBED_BATCH_SIZE + 5))
+            .unwrap()
+            .unwrap();
+        assert_eq!(last.embedding.as_ref().unwrap().len(), 64);
+        assert_eq!(last.metadata["embedding_model"], "mock");
+        assert_eq!(last.content, format!("finding {}", REEMBED_BATCH_SIZE + 5));
+
+        // A fresh job updates the rest and skips what the model already produced.
+        let again = reembedder.create_job(vec![slot_id], None, 8, false).unwrap();
+        let again = reembedder.run(again, |_| {}).await;
+        assert_eq!((again.updated, again.skipped, again.failed), (REEMBED_BATCH_SIZE, 6, 0));
+        assert_eq!(store.list_reembed_jobs().unwrap().len(), 2);
+    }
+}
@@ -14,7 +14,7 @@
 //! 4. **Chronos recap:** Logs only "User performed a Shadow Reflection on record [ID]."
 
 use pagi_core::{
-    AgentSkill, EventRecord, KnowledgeStore, MentalState, ShadowStoreHandle, TenantContext,
+    AgentSkill, KnowledgeStore, MentalState, ShadowStoreHandle, TenantContext,
 };
 use crate::model_router::ModelRouter;
 use serde::Deserialize;
@@ -237,7 +237,7 @@ impl AgentSkill for ReflectShadowSkill {
         // Do not log or write prompt/raw_content anywhere.
 
         // Chronos: log only the fact of reflection, never the content.
-        let event = EventRecord::now("Chronos", format!("User performed a Shadow Reflection on record {}.", args.record_id))
+        let event = self.store.event("Chronos", format!("User performed a Shadow Reflection on record {}.", args.record_id))
             .with_skill(SKILL_NAME)
             .with_outcome("shadow_reflection");
         let _ = self.store.append_chronos_event(agent_id, &event);
@@ -294,6 +294,7 @@ mod tests {
             tenant_id: "test".to_string(),
             correlation_id: None,
             agent_id: Some("default".to_string()),
+            ..Default::default()
         };
         let payload = serde_json::json!({
             "record_id": "journal/12345",
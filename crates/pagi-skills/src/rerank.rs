@@ -0,0 +1,158 @@
+//! Reranking of retrieval candidates before they are put into a prompt (`[rag] reranker`).
+//!
+//! `lexical` scores each candidate locally by how much of the question it covers, weighting rare
+//! terms higher, blended with its similarity. `llm` asks the model to rate every candidate against
+//! the question in one JSON-mode call and falls back to `lexical` when the model cannot answer
+//! (mock mode, provider errors, unparseable replies).
+
+use crate::ModelRouter;
+use pagi_core::RerankerKind;
+use std::collections::HashSet;
+
+/// Characters of each candidate shown to the LLM scorer.
+const LLM_CANDIDATE_CHARS: usize = 600;
+
+/// Words too common to say anything about relevance.
+const STOPWORDS: &[&str] = &[
+    "about", "and", "are", "but", "can", "did", "does", "for", "from", "had", "has", "have", "how", "into", "its",
+    "not", "our", "should", "that", "the", "their", "them", "then", "there", "these", "they", "this", "was",
+    "were", "what", "when", "where", "which", "who", "why", "will", "with", "would", "you", "your",
+];
+
+/// Scores of one rerank: one per candidate, in input order (0.0–1.0).
+#[derive(Debug, Clone, PartialEq)]
+pub struct RerankScores {
+    /// Method that produced the scores; `Lexical` when the LLM scorer fell back.
+    pub method: RerankerKind,
+    pub scores: Vec<f32>,
+}
+
+/// Scores `candidates` (text and cosine similarity) against `query` with `kind`. `None` keeps
+/// the similarity, mapped to 0.0–1.0.
+pub async fn rerank(kind: RerankerKind, router: &ModelRouter, query: &str, candidates: &[(&str, f32)]) -> RerankScores {
+    match kind {
+        RerankerKind::None => RerankScores {
+            method: RerankerKind::None,
+            scores: candidates.iter().map(|&(_, similarity)| unit_similarity(similarity)).collect(),
+        },
+        RerankerKind::Lexical => lexical(query, candidates),
+        RerankerKind::Llm => match llm_scores(router, query, candidates).await {
+            Ok(scores) => RerankScores { method: RerankerKind::Llm, scores },
+            Err(e) => {
+                tracing::warn!(target: "pagi::rerank", error = %e, "[Rerank] LLM scoring failed; using lexical scores");
+                lexical(query, candidates)
+            }
+        },
+    }
+}
+
+fn unit_similarity(similarity: f32) -> f32 {
+    ((similarity + 1.0) / 2.0).clamp(0.0, 1.0)
+}
+
+fn terms(text: &str) -> HashSet<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|t| t.chars().count() >= 3)
+        .map(str::to_lowercase)
+        .filter(|t| !STOPWORDS.contains(&t.as_str()))
+        .collect()
+}
+
+/// Half IDF-weighted coverage of the question's terms, half similarity.
+fn lexical(query: &str, candidates: &[(&str, f32)]) -> RerankScores {
+    let query_terms = terms(query);
+    let candidate_terms: Vec<HashSet<String>> = candidates.iter().map(|&(text, _)| terms(text)).collect();
+    let n = candidates.len() as f32;
+    let weights: Vec<(&String, f32)> = query_terms
+        .iter()
+        .map(|t| {
+            let df = candidate_terms.iter().filter(|c| c.contains(t)).count() as f32;
+            (t, ((n + 1.0) / (df + 0.5)).ln() + 1.0)
+        })
+        .collect();
+    let total: f32 = weights.iter().map(|(_, w)| w).sum();
+    let scores = candidates
+        .iter()
+        .zip(&candidate_terms)
+        .map(|(&(_, similarity), found)| {
+            let coverage = if total > 0.0 {
+                weights.iter().filter(|(t, _)| found.contains(*t)).map(|(_, w)| w).sum::<f32>() / total
+            } else {
+                0.0
+            };
+            0.5 * coverage + 0.5 * unit_similarity(similarity)
+        })
+        .collect();
+    RerankScores { method: RerankerKind::Lexical, scores }
+}
+
+async fn llm_scores(
+    router: &ModelRouter,
+    query: &str,
+    candidates: &[(&str, f32)],
+) -> Result<Vec<f32>, Box<dyn std::error::Error + Send + Sync>> {
+    let mut prompt = String::from(
+        "Rate how well each passage helps answer the question, from 0 (unrelated) to 10 (answers it). \
+         Reply with only a JSON object {\"scores\": [{\"n\": 1, \"score\": 7}, ...]} covering every passage.\n\n",
+    );
+    prompt.push_str(&format!("Question: {}\n\nPassages:", query.trim()));
+    for (i, &(text, _)) in candidates.iter().enumerate() {
+        let passage: String = text.chars().take(LLM_CANDIDATE_CHARS).collect();
+        prompt.push_str(&format!("\n[{}] {}", i + 1, passage.split_whitespace().collect::<Vec<_>>().join(" ")));
+    }
+    let reply = router.generate_json(&prompt).await?;
+    parse_llm_scores(&reply, candidates.len()).ok_or_else(|| "LLM reply had no scores".into())
+}
+
+/// Reads `{"scores": [{"n", "score"}, ...]}` (or a bare list of numbers) on a 0–10 scale.
+/// Passages the reply skips score 0.
+fn parse_llm_scores(reply: &serde_json::Value, count: usize) -> Option<Vec<f32>> {
+    let entries = reply.get("scores")?.as_array()?;
+    let mut scores = vec![0.0; count];
+    let mut found = false;
+    for (i, entry) in entries.iter().enumerate() {
+        let (n, score) = match entry {
+            serde_json::Value::Number(score) => (i + 1, score.as_f64()),
+            entry => (
+                entry.get("n").and_then(|n| n.as_u64()).map_or(0, |n| n as usize),
+                entry.get("score").and_then(|s| s.as_f64()),
+            ),
+        };
+        if let (Some(slot), Some(score)) = (n.checked_sub(1).and_then(|i| scores.get_mut(i)), score) {
+            *slot = (score as f32 / 10.0).clamp(0.0, 1.0);
+            found = true;
+        }
+    }
+    found.then_some(scores)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::LlmMode;
+
+    #[tokio::test]
+    async fn lexical_rerank_prefers_candidates_covering_the_question() {
+        let router = ModelRouter::with_mode(LlmMode::Mock);
+        let candidates = [
+            ("Quarterly revenue grew in the northern region.", 0.60),
+            ("Refunds for annual plans are prorated to the day of cancellation.", 0.55),
+        ];
+        let reranked = rerank(RerankerKind::Llm, &router, "How are annual plan refunds calculated?", &candidates).await;
+        // Mock mode has no JSON output, so the LLM scorer falls back.
+        assert_eq!(reranked.method, RerankerKind::Lexical);
+        assert!(reranked.scores[1] > reranked.scores[0], "{:?}", reranked.scores);
+        assert!(reranked.scores.iter().all(|s| (0.0..=1.0).contains(s)));
+
+        let kept = rerank(RerankerKind::None, &router, "anything", &candidates).await;
+        assert!(kept.scores[0] > kept.scores[1]);
+    }
+
+    #[test]
+    fn llm_scores_are_read_by_passage_number() {
+        let reply = serde_json::json!({ "scores": [{ "n": 2, "score": 9 }, { "n": 1, "score": 3 }, { "n": 7, "score": 10 }] });
+        assert_eq!(parse_llm_scores(&reply, 3), Some(vec![0.3, 0.9, 0.0]));
+        assert_eq!(parse_llm_scores(&serde_json::json!({ "scores": [4, 12] }), 2), Some(vec![0.4, 1.0]));
+        assert_eq!(parse_llm_scores(&serde_json::json!({ "answer": "yes" }), 2), None);
+    }
+}
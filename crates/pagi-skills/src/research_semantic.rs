@@ -79,7 +79,7 @@ impl AgentSkill for ResearchEmbedInsert {
         md["embedding_model"] = serde_json::json!(args.embedding_model.clone().unwrap_or_else(|| "default".to_string()));
         md["vector_dims"] = serde_json::json!(embedding.len());
 
-        let record = KbRecord::with_embedding(args.content, md, embedding);
+        let record = KbRecord::with_embedding(args.content, md, embedding, self.store.now_ms());
         let slot_id = KbType::Logos.slot_id();
         self.store.insert_record(slot_id, &args.key, &record)?;
 
@@ -109,7 +109,8 @@ fn default_limit() -> usize {
     5
 }
 
-/// Performs a brute-force cosine-similarity search over KB-3 records that have embeddings.
+/// Cosine-similarity search over KB-3 records that have embeddings: live records through the
+/// store's HNSW index, archived ones by scan.
 pub struct ResearchSemanticSearch {
     store: Arc<KnowledgeStore>,
     router: ModelRouter,
@@ -144,10 +145,35 @@ impl AgentSkill for ResearchSemanticSearch {
             .await?;
 
         let slot_id = KbType::Logos.slot_id();
-        let records = self.store.scan_records(slot_id)?;
-
-        let mut scored: Vec<serde_json::Value> = Vec::new();
-        for (key, rec) in records {
+        let limit = args.limit.max(1);
+        let result = |key: String, score: f32, content: &str, metadata: serde_json::Value, timestamp: i64| {
+            serde_json::json!({
+                "key": key,
+                "score": score,
+                "preview": content.chars().take(200).collect::<String>(),
+                "metadata": metadata,
+                "timestamp": timestamp
+            })
+        };
+        // Live records through the store's vector index.
+        let mut scored: Vec<serde_json::Value> = self
+            .store
+            .semantic_search(slot_id, &qv, limit)?
+            .into_iter()
+            .map(|hit| result(hit.key, hit.score, &hit.content, hit.metadata, hit.timestamp))
+            .collect();
+        // Archived (cold) research is not indexed; scanning it is slower but keeps old findings
+        // searchable.
+        let archived = self
+            .store
+            .scan_archived_kv(slot_id)
+            .unwrap_or_else(|e| {
+                tracing::warn!(target: "pagi::skills", error = %e, "Archive read failed; searching live records only");
+                Vec::new()
+            })
+            .into_iter()
+            .filter_map(|(key, bytes)| KbRecord::from_bytes(&bytes).map(|rec| (key, rec)));
+        for (key, rec) in archived {
             let Some(ev) = rec.embedding.as_deref() else {
                 continue;
             };
@@ -155,14 +181,7 @@ impl AgentSkill for ResearchSemanticSearch {
                 continue;
             }
             let score = cosine_similarity(&qv, ev);
-            let preview = rec.content.chars().take(200).collect::<String>();
-            scored.push(serde_json::json!({
-                "key": key,
-                "score": score,
-                "preview": preview,
-                "metadata": rec.metadata,
-                "timestamp": rec.timestamp
-            }));
+            scored.push(result(key, score, &rec.content, rec.metadata, rec.timestamp));
         }
 
         scored.sort_by(|a, b| {
@@ -171,7 +190,7 @@ impl AgentSkill for ResearchSemanticSearch {
             sb.partial_cmp(&sa).unwrap_or(std::cmp::Ordering::Equal)
         });
 
-        scored.truncate(args.limit.max(1));
+        scored.truncate(limit);
 
         Ok(serde_json::json!({
             "status": "ok",
@@ -0,0 +1,541 @@
+//! Send Email skill: delivers a generated response over SMTP or the SendGrid API (`[email]`).
+//!
+//! Payload: `{ to?, lead_id?, subject?, body?, template?, vars? }`. `to` is an address or a list
+//! of them; without it the lead's `email` is used. A `template` from `[email.templates]` fills its
+//! `{{variable}}` placeholders from the lead's text fields, `body` and `vars`; otherwise `subject`
+//! and `body` are sent as given. The sender is the tenant's `[email.tenant_from]` entry, else
+//! `[email] from`.
+//!
+//! The rendered message is checked against the Ethos policy (as `{ to, subject, content }`) before
+//! it leaves; anything but an allow is held back. Every attempt is logged to KB_SOMA
+//! (`email_log/{tenant_id}/...`, see [`EmailDelivery`]) as sent, failed or blocked.
+
+use base64::Engine;
+use pagi_core::{
+    AgentSkill, EmailConfig, EmailDelivery, EmailDeliveryStatus, EmailProvider, KnowledgeStore, MemoryManager, PromptTemplate,
+    RuleAction, SmtpTls, TenantContext,
+};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+use tokio_rustls::rustls;
+
+const SKILL_NAME: &str = "SendEmail";
+const SENDGRID_API_URL: &str = "https://api.sendgrid.com/v3/mail/send";
+/// Recipients per message.
+const MAX_RECIPIENTS: usize = 20;
+
+type SkillError = Box<dyn std::error::Error + Send + Sync>;
+
+/// A mailbox: optional display name and the address.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Mailbox {
+    name: Option<String>,
+    address: String,
+}
+
+impl Mailbox {
+    /// Parses `addr@example.com` or `Display Name <addr@example.com>`.
+    fn parse(raw: &str) -> Result<Self, String> {
+        let raw = raw.trim();
+        if raw.contains(['\r', '\n']) {
+            return Err(format!("invalid email address {:?}", raw));
+        }
+        let (name, address) = match (raw.rfind('<'), raw.strip_suffix('>')) {
+            (Some(open), Some(inner)) => {
+                let name = raw[..open].trim().trim_matches('"').trim();
+                ((!name.is_empty()).then(|| name.to_string()), &inner[open + 1..])
+            }
+            _ => (None, raw),
+        };
+        let valid = address
+            .split_once('@')
+            .is_some_and(|(local, domain)| !local.is_empty() && domain.contains('.') && !domain.ends_with('.'))
+            && !address.contains(|c: char| c.is_whitespace() || matches!(c, '<' | '>' | ',' | ';' | '"'));
+        if !valid {
+            return Err(format!("invalid email address {:?}", raw));
+        }
+        Ok(Self { name, address: address.to_string() })
+    }
+
+    fn domain(&self) -> &str {
+        self.address.split_once('@').map_or("localhost", |(_, d)| d)
+    }
+
+    /// Header form, with a non-ASCII display name encoded.
+    fn header(&self) -> String {
+        match &self.name {
+            Some(name) => format!("{} <{}>", encode_header(&name.replace('"', "")), self.address),
+            None => self.address.clone(),
+        }
+    }
+}
+
+/// RFC 2047 `=?UTF-8?B?...?=` for non-ASCII header text; ASCII is kept as is.
+fn encode_header(text: &str) -> String {
+    if text.is_ascii() {
+        text.to_string()
+    } else {
+        format!("=?UTF-8?B?{}?=", base64::engine::general_purpose::STANDARD.encode(text))
+    }
+}
+
+/// A message ready to hand to the provider.
+#[derive(Debug, Clone)]
+struct OutgoingEmail {
+    from: Mailbox,
+    to: Vec<Mailbox>,
+    subject: String,
+    body: String,
+    message_id: String,
+}
+
+impl OutgoingEmail {
+    /// RFC 5322 message: plain-text UTF-8 body, base64 encoded so no line needs dot-stuffing.
+    fn to_mime(&self) -> String {
+        let to = self.to.iter().map(Mailbox::header).collect::<Vec<_>>().join(", ");
+        let encoded = base64::engine::general_purpose::STANDARD.encode(self.body.as_bytes());
+        let body = encoded
+            .as_bytes()
+            .chunks(76)
+            .map(|line| String::from_utf8_lossy(line).into_owned())
+            .collect::<Vec<_>>()
+            .join("\r\n");
+        format!(
+            "From: {}\r\nTo: {}\r\nSubject: {}\r\nMessage-ID: {}\r\nMIME-Version: 1.0\r\n\
+             Content-Type: text/plain; charset=utf-8\r\nContent-Transfer-Encoding: base64\r\n\r\n{}\r\n",
+            self.from.header(),
+            to,
+            encode_header(&self.subject),
+            self.message_id,
+            body
+        )
+    }
+}
+
+/// One SMTP session over any stream (plain or TLS).
+struct SmtpSession<S> {
+    stream: BufReader<S>,
+}
+
+impl<S: AsyncRead + AsyncWrite + Unpin> SmtpSession<S> {
+    fn new(stream: S) -> Self {
+        Self { stream: BufReader::new(stream) }
+    }
+
+    fn into_inner(self) -> S {
+        self.stream.into_inner()
+    }
+
+    /// Reads a (possibly multi-line) reply; fails unless its code is `expected`.
+    async fn expect(&mut self, expected: u16, during: &str) -> Result<(), SkillError> {
+        let mut reply = String::new();
+        loop {
+            let mut line = String::new();
+            if self.stream.read_line(&mut line).await? == 0 {
+                return Err(format!("SMTP server closed the connection during {}", during).into());
+            }
+            reply.push_str(line.trim_end());
+            // `250-...` continues, `250 ...` is the last line.
+            if line.as_bytes().get(3) != Some(&b'-') {
+                break;
+            }
+            reply.push(' ');
+        }
+        match reply.get(..3).and_then(|code| code.parse::<u16>().ok()) {
+            Some(code) if code == expected => Ok(()),
+            _ => Err(format!("SMTP {} failed: {}", during, reply).into()),
+        }
+    }
+
+    async fn command(&mut self, line: &str, expected: u16, during: &str) -> Result<(), SkillError> {
+        self.stream.get_mut().write_all(format!("{}\r\n", line).as_bytes()).await?;
+        self.stream.get_mut().flush().await?;
+        self.expect(expected, during).await
+    }
+
+    /// Authenticates (when configured) and submits `email`.
+    async fn submit(&mut self, email: &OutgoingEmail, auth: Option<(&str, &str)>) -> Result<(), SkillError> {
+        if let Some((user, password)) = auth {
+            let token = base64::engine::general_purpose::STANDARD.encode(format!("\0{}\0{}", user, password));
+            self.command(&format!("AUTH PLAIN {}", token), 235, "AUTH").await?;
+        }
+        self.command(&format!("MAIL FROM:<{}>", email.from.address), 250, "MAIL FROM").await?;
+        for to in &email.to {
+            self.command(&format!("RCPT TO:<{}>", to.address), 250, "RCPT TO").await?;
+        }
+        self.command("DATA", 354, "DATA").await?;
+        let data = format!("{}.\r\n", email.to_mime());
+        self.stream.get_mut().write_all(data.as_bytes()).await?;
+        self.stream.get_mut().flush().await?;
+        self.expect(250, "message submission").await?;
+        // The message is accepted; a failed QUIT changes nothing.
+        let _ = self.command("QUIT", 221, "QUIT").await;
+        Ok(())
+    }
+}
+
+async fn tls_connect(host: &str, tcp: TcpStream) -> Result<tokio_rustls::client::TlsStream<TcpStream>, SkillError> {
+    let roots = rustls::RootCertStore::from_iter(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+    let config = rustls::ClientConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+        .with_safe_default_protocol_versions()?
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+    let server_name = rustls::pki_types::ServerName::try_from(host.to_string())?;
+    Ok(tokio_rustls::TlsConnector::from(Arc::new(config)).connect(server_name, tcp).await?)
+}
+
+/// Sends `email` through the `[email.smtp]` server.
+async fn send_smtp(config: &EmailConfig, email: &OutgoingEmail) -> Result<(), SkillError> {
+    let smtp = &config.smtp;
+    let password = match &smtp.username {
+        Some(_) => Some(
+            pagi_core::secret(config.smtp_password_env())
+                .ok_or_else(|| format!("SMTP password secret {} is not set", config.smtp_password_env()))?,
+        ),
+        None => None,
+    };
+    let auth = smtp.username.as_deref().zip(password.as_deref());
+    let helo = format!("EHLO {}", email.from.domain());
+    let tcp = TcpStream::connect((smtp.host.as_str(), smtp.port)).await?;
+    match smtp.tls {
+        SmtpTls::None => {
+            let mut session = SmtpSession::new(tcp);
+            session.expect(220, "greeting").await?;
+            session.command(&helo, 250, "EHLO").await?;
+            session.submit(email, auth).await
+        }
+        SmtpTls::Implicit => {
+            let mut session = SmtpSession::new(tls_connect(&smtp.host, tcp).await?);
+            session.expect(220, "greeting").await?;
+            session.command(&helo, 250, "EHLO").await?;
+            session.submit(email, auth).await
+        }
+        SmtpTls::Starttls => {
+            let mut plain = SmtpSession::new(tcp);
+            plain.expect(220, "greeting").await?;
+            plain.command(&helo, 250, "EHLO").await?;
+            plain.command("STARTTLS", 220, "STARTTLS").await?;
+            let mut session = SmtpSession::new(tls_connect(&smtp.host, plain.into_inner()).await?);
+            session.command(&helo, 250, "EHLO").await?;
+            session.submit(email, auth).await
+        }
+    }
+}
+
+/// Sends `email` with the SendGrid v3 Mail Send API. Returns SendGrid's message id.
+async fn send_sendgrid(config: &EmailConfig, email: &OutgoingEmail) -> Result<Option<String>, SkillError> {
+    let key = pagi_core::secret(config.sendgrid_api_key_env())
+        .ok_or_else(|| format!("SendGrid API key secret {} is not set", config.sendgrid_api_key_env()))?;
+    let address = |m: &Mailbox| match &m.name {
+        Some(name) => serde_json::json!({ "email": m.address, "name": name }),
+        None => serde_json::json!({ "email": m.address }),
+    };
+    let body = serde_json::json!({
+        "personalizations": [{ "to": email.to.iter().map(address).collect::<Vec<_>>() }],
+        "from": address(&email.from),
+        "subject": email.subject,
+        "content": [{ "type": "text/plain", "value": email.body }],
+        "headers": { "Message-ID": email.message_id },
+    });
+    let url = config.sendgrid_api_url.as_deref().unwrap_or(SENDGRID_API_URL);
+    let res = reqwest::Client::new().post(url).bearer_auth(key).json(&body).send().await?;
+    let status = res.status();
+    let message_id = res.headers().get("x-message-id").and_then(|v| v.to_str().ok()).map(String::from);
+    if !status.is_success() {
+        let detail = res.text().await.unwrap_or_default();
+        return Err(format!("SendGrid answered {}: {}", status, detail.chars().take(300).collect::<String>()).into());
+    }
+    Ok(message_id)
+}
+
+/// Text value of a JSON field for template variables (strings as is, scalars printed).
+fn var_text(value: &serde_json::Value) -> Option<String> {
+    match value {
+        serde_json::Value::String(s) => Some(s.clone()),
+        serde_json::Value::Number(_) | serde_json::Value::Bool(_) => Some(value.to_string()),
+        _ => None,
+    }
+}
+
+/// Delivers generated responses by email.
+pub struct SendEmail {
+    knowledge: Arc<KnowledgeStore>,
+    memory: Arc<MemoryManager>,
+    config: EmailConfig,
+}
+
+impl SendEmail {
+    pub fn new(knowledge: Arc<KnowledgeStore>, memory: Arc<MemoryManager>) -> Self {
+        Self {
+            knowledge,
+            memory,
+            config: EmailConfig::default(),
+        }
+    }
+
+    pub fn with_config(mut self, config: EmailConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    fn log(&self, delivery: &EmailDelivery) {
+        if let Err(e) = self.knowledge.record_email_delivery(delivery) {
+            tracing::warn!(target: "pagi::skills", delivery_id = %delivery.id, error = %e, "SendEmail: failed to log delivery");
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl AgentSkill for SendEmail {
+    fn name(&self) -> &str {
+        SKILL_NAME
+    }
+
+    async fn execute(&self, ctx: &TenantContext, payload: Option<serde_json::Value>) -> Result<serde_json::Value, SkillError> {
+        let payload = payload.unwrap_or(serde_json::Value::Null);
+        let text = |field: &str| payload.get(field).and_then(|v| v.as_str()).map(str::trim).filter(|s| !s.is_empty());
+        let provider = match self.config.provider {
+            EmailProvider::None => return Err("SendEmail: no [email] provider is configured".into()),
+            EmailProvider::Smtp => "smtp",
+            EmailProvider::Sendgrid => "sendgrid",
+        };
+        let from = self
+            .config
+            .from_for(&ctx.tenant_id)
+            .ok_or_else(|| format!("SendEmail: no sender configured for tenant {}", ctx.tenant_id))?;
+        let from = Mailbox::parse(from)?;
+
+        let lead_id = text("lead_id");
+        let lead = match lead_id {
+            Some(id) => Some(self.memory.get_lead(ctx, id)?.ok_or_else(|| format!("SendEmail: unknown lead {}", id))?),
+            None => None,
+        };
+        let to: Vec<&str> = match payload.get("to") {
+            Some(serde_json::Value::String(to)) => vec![to.as_str()],
+            Some(serde_json::Value::Array(to)) => to.iter().filter_map(|t| t.as_str()).collect(),
+            _ => lead.as_ref().and_then(|l| l.fields.get("email")).and_then(|e| e.as_str()).into_iter().collect(),
+        };
+        if to.is_empty() {
+            return Err("SendEmail requires `to` or a `lead_id` whose lead has an email".into());
+        }
+        if to.len() > MAX_RECIPIENTS {
+            return Err(format!("SendEmail: at most {} recipients", MAX_RECIPIENTS).into());
+        }
+        let to = to.into_iter().map(Mailbox::parse).collect::<Result<Vec<_>, _>>()?;
+
+        let template_name = text("template");
+        let (subject, body) = match template_name {
+            Some(name) => {
+                let template = self
+                    .config
+                    .templates
+                    .get(name)
+                    .ok_or_else(|| format!("SendEmail: unknown template {:?}", name))?;
+                let mut vars: Vec<(String, String)> = lead
+                    .iter()
+                    .flat_map(|l| l.fields.iter())
+                    .filter_map(|(k, v)| Some((k.clone(), var_text(v)?)))
+                    .collect();
+                if let Some(body) = text("body") {
+                    vars.push(("body".to_string(), body.to_string()));
+                }
+                if let Some(serde_json::Value::Object(extra)) = payload.get("vars") {
+                    vars.extend(extra.iter().filter_map(|(k, v)| Some((k.clone(), var_text(v)?))));
+                }
+                // Later entries win: `vars` over `body` over lead fields.
+                vars.reverse();
+                let vars: Vec<(&str, &str)> = vars.iter().map(|(k, v)| (k.as_str(), v.as_str())).collect();
+                let render = |part: &str, source: &str| {
+                    PromptTemplate {
+                        name: format!("email/{}", name),
+                        template: source.to_string(),
+                        description: String::new(),
+                        version: 0,
+                        updated_at_ms: 0,
+                    }
+                    .render(&vars)
+                        .map_err(|e| format!("SendEmail: {} of template {:?}: {}", part, name, e))
+                };
+                (render("subject", &template.subject)?, render("body", &template.body)?)
+            }
+            None => (
+                text("subject").ok_or("SendEmail requires `subject` (or a `template`)")?.to_string(),
+                text("body").ok_or("SendEmail requires `body` (or a `template`)")?.to_string(),
+            ),
+        };
+        let subject = subject.lines().map(str::trim).collect::<Vec<_>>().join(" ");
+
+        let email = OutgoingEmail {
+            message_id: format!("<{}@{}>", uuid::Uuid::new_v4().simple(), from.domain()),
+            from,
+            to,
+            subject,
+            body,
+        };
+        let mut delivery = EmailDelivery {
+            id: uuid::Uuid::new_v4().simple().to_string(),
+            tenant_id: ctx.tenant_id.clone(),
+            provider: provider.to_string(),
+            from: email.from.header(),
+            to: email.to.iter().map(|m| m.address.clone()).collect(),
+            subject: email.subject.clone(),
+            body_bytes: email.body.len(),
+            template: template_name.map(String::from),
+            lead_id: lead_id.map(String::from),
+            ..Default::default()
+        };
+
+        let outbound = serde_json::json!({ "to": delivery.to, "subject": email.subject, "content": email.body });
+        if let Some(policy) = self.knowledge.active_ethos_policy() {
+            let decision = policy.evaluate(SKILL_NAME, Some(&outbound));
+            if decision.action != RuleAction::Allow {
+                let reason = decision.reason.clone().unwrap_or_else(|| format!("{:?} by Ethos policy", decision.action));
+                tracing::warn!(target: "pagi::skills", rule = decision.rule_id.as_deref().unwrap_or("default"), "SendEmail: message held back by Ethos policy");
+                delivery.status = EmailDeliveryStatus::Blocked;
+                delivery.error = Some(reason.clone());
+                self.log(&delivery);
+                return Ok(serde_json::json!({
+                    "status": "blocked",
+                    "skill": SKILL_NAME,
+                    "delivery_id": delivery.id,
+                    "rule_id": decision.rule_id,
+                    "reason": reason,
+                }));
+            }
+        }
+
+        let timeout = Duration::from_secs(self.config.timeout_secs.max(1));
+        let sent = match self.config.provider {
+            EmailProvider::Sendgrid => tokio::time::timeout(timeout, send_sendgrid(&self.config, &email)).await,
+            _ => tokio::time::timeout(timeout, async { send_smtp(&self.config, &email).await.map(|()| Some(email.message_id.clone())) }).await,
+        }
+        .unwrap_or_else(|_| Err(format!("{} delivery timed out after {}s", provider, timeout.as_secs()).into()));
+        match sent {
+            Ok(message_id) => {
+                delivery.message_id = message_id;
+                self.log(&delivery);
+                tracing::info!(target: "pagi::skills", delivery_id = %delivery.id, provider, recipients = delivery.to.len(), "SendEmail: message sent");
+                Ok(serde_json::json!({
+                    "status": "sent",
+                    "skill": SKILL_NAME,
+                    "delivery_id": delivery.id,
+                    "provider": provider,
+                    "to": delivery.to,
+                    "subject": delivery.subject,
+                    "message_id": delivery.message_id,
+                }))
+            }
+            Err(e) => {
+                delivery.status = EmailDeliveryStatus::Failed;
+                delivery.error = Some(e.to_string());
+                self.log(&delivery);
+                Err(format!("SendEmail: {}", e).into())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pagi_core::{EmailTemplate, LeadRecord, SmtpConfig};
+    use tokio::net::TcpListener;
+
+    /// Minimal SMTP server that accepts one message and returns the transcript.
+    async fn fake_smtp(listener: TcpListener) -> String {
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut stream = BufReader::new(stream);
+        let mut transcript = String::new();
+        stream.get_mut().write_all(b"220 test ESMTP\r\n").await.unwrap();
+        let mut in_data = false;
+        loop {
+            let mut line = String::new();
+            if stream.read_line(&mut line).await.unwrap() == 0 {
+                break;
+            }
+            transcript.push_str(&line);
+            let reply: &[u8] = if in_data {
+                if line != ".\r\n" {
+                    continue;
+                }
+                in_data = false;
+                b"250 queued\r\n"
+            } else if line.starts_with("EHLO") {
+                b"250-test\r\n250 AUTH PLAIN\r\n"
+            } else if line.starts_with("AUTH") {
+                b"235 ok\r\n"
+            } else if line.starts_with("DATA") {
+                in_data = true;
+                b"354 go ahead\r\n"
+            } else if line.starts_with("QUIT") {
+                stream.get_mut().write_all(b"221 bye\r\n").await.unwrap();
+                break;
+            } else {
+                b"250 ok\r\n"
+            };
+            stream.get_mut().write_all(reply).await.unwrap();
+        }
+        transcript
+    }
+
+    #[tokio::test]
+    async fn sends_templated_lead_email_over_smtp_and_logs_to_soma() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = tokio::spawn(fake_smtp(listener));
+
+        std::env::set_var("PAGI_TEST_SEND_EMAIL_SMTP_PASSWORD", "hunter2");
+        let knowledge = Arc::new(KnowledgeStore::open_temporary().unwrap());
+        let memory = Arc::new(MemoryManager::open_temporary().unwrap());
+        let ctx = TenantContext::new("acme");
+        let serde_json::Value::Object(fields) = serde_json::json!({ "name": "Ada", "email": "Ada Lovelace <ada@example.com>" }) else {
+            unreachable!()
+        };
+        let lead = memory.put_lead(&ctx, LeadRecord::new(fields)).unwrap();
+        let config = EmailConfig {
+            provider: EmailProvider::Smtp,
+            tenant_from: [("acme".to_string(), "Acme Sales <sales@acme.example>".to_string())].into_iter().collect(),
+            smtp: SmtpConfig {
+                host: "127.0.0.1".into(),
+                port,
+                tls: SmtpTls::None,
+                username: Some("acme".into()),
+                password_env: Some("PAGI_TEST_SEND_EMAIL_SMTP_PASSWORD".into()),
+            },
+            templates: [(
+                "lead_reply".to_string(),
+                EmailTemplate { subject: "Re: your request, {{name}}".into(), body: "Hi {{name}},\n\n{{body}}".into() },
+            )]
+            .into_iter()
+            .collect(),
+            ..EmailConfig::default()
+        };
+        let skill = SendEmail::new(Arc::clone(&knowledge), memory).with_config(config);
+
+        let payload = serde_json::json!({ "lead_id": lead.id, "template": "lead_reply", "body": "Thanks for reaching out." });
+        let out = skill.execute(&ctx, Some(payload)).await.unwrap();
+        assert_eq!(out["status"], "sent");
+        assert_eq!(out["to"][0], "ada@example.com");
+        assert_eq!(out["subject"], "Re: your request, Ada");
+
+        let transcript = server.await.unwrap();
+        assert!(transcript.contains("EHLO acme.example\r\n"));
+        assert!(transcript.contains(&format!("AUTH PLAIN {}\r\n", base64::engine::general_purpose::STANDARD.encode("\0acme\0hunter2"))));
+        assert!(transcript.contains("MAIL FROM:<sales@acme.example>\r\n"));
+        assert!(transcript.contains("RCPT TO:<ada@example.com>\r\n"));
+        assert!(transcript.contains("Subject: Re: your request, Ada\r\n"));
+        let encoded = base64::engine::general_purpose::STANDARD.encode("Hi Ada,\n\nThanks for reaching out.");
+        assert!(transcript.contains(&encoded));
+
+        let log = knowledge.list_email_deliveries("acme", 10).unwrap();
+        assert_eq!(log.len(), 1);
+        assert_eq!(log[0].status, EmailDeliveryStatus::Sent);
+        assert_eq!(log[0].lead_id.as_deref(), Some(lead.id.as_str()));
+        assert_eq!(log[0].template.as_deref(), Some("lead_reply"));
+
+        assert!(Mailbox::parse("ada@example.com\r\nBcc: x@evil.example").is_err());
+        assert_eq!(Mailbox::parse("\"Ada L\" <ada@example.com>").unwrap().name.as_deref(), Some("Ada L"));
+    }
+}
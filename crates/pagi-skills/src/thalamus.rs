@@ -3,7 +3,7 @@
 //! This module implements the Mapping Layer that classifies data into the Holistic Ontology:
 //! Logos, Soma, Pneuma, Kardia, Chronos, Techne, Oikos, Ethos.
 
-use pagi_core::KbType;
+use pagi_core::{render_prompt, KbType, THALAMUS_CLASSIFICATION_PROMPT};
 use serde::Deserialize;
 use std::collections::HashMap;
 
@@ -26,28 +26,14 @@ pub struct RouteMetadata {
     pub extra: HashMap<String, serde_json::Value>,
 }
 
-fn build_classification_prompt(input: &str, context: &str) -> String {
-    format!(
-        r#"You are a cognitive router. Given a piece of information, choose exactly ONE knowledge base where it belongs. Reply with only that word, nothing else.
-
-Knowledge bases (reply with exactly one word):
-- Logos: pure distilled information, research, papers, findings, code snippets, "Internal Wikipedia"
-- Soma: physical interface, execution, side effects on hardware, file writes, buffer/staging
-- Pneuma: identity, mission, goals, "why", evolving playbook, vision
-- Kardia: user preferences, "who", vibe, personal notes
-- Chronos: conversation history, temporal thread, short/long-term memory
-- Techne: skills registry, blueprints, how-to, specialized functions
-- Oikos: workspace context, "where", system logs, crate layout, project structure
-- Ethos: guardrails, security, audit, "should", constraints
-
-Information to classify:
-"{}"
-
-Context: {}
-Reply with exactly one word from the list above."#,
-        input,
-        context
+/// The `thalamus_classification` prompt template (stored override, else the built-in).
+fn build_classification_prompt(router: &ModelRouter, input: &str, context: &str) -> String {
+    render_prompt(
+        router.knowledge().map(|k| k.as_ref()),
+        THALAMUS_CLASSIFICATION_PROMPT,
+        &[("input", input), ("context", context)],
     )
+    .unwrap_or_default()
 }
 
 /// Routes text into one of the 8 holistic ontology domains using the LLM.
@@ -78,7 +64,7 @@ pub async fn route_information(
 ) -> Result<KbType, Box<dyn std::error::Error + Send + Sync>> {
     let context = build_context(metadata);
     let input_trimmed = input.chars().take(2000).collect::<String>();
-    let prompt = build_classification_prompt(&input_trimmed, &context);
+    let prompt = build_classification_prompt(router, &input_trimmed, &context);
     let raw = router.generate_text_raw(&prompt).await?;
     parse_kb_type_from_response(&raw)
 }
@@ -0,0 +1,479 @@
+//! Polite page fetching for CommunityScraper and FeedIngest (`[scraper]`): robots.txt is
+//! honoured, requests to one host are spaced out, and only the expected content types up to the
+//! size limit are read, decoded with the charset the server or the document declares.
+//!
+//! Every URL requested (robots.txt and each redirect hop included) must pass
+//! [`check_webhook_url`] like a webhook target, unless `[scraper] allow_private_hosts` is set;
+//! the client then resolves hosts through [`PublicResolver`], so a name cannot pass the check and
+//! connect to an internal address.
+//! Redirects are followed here rather than by the client so each hop is checked (and, for pages,
+//! matched against its own host's robots.txt).
+
+use pagi_core::{check_webhook_url, PublicResolver, ScraperConfig};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+/// How long a host's robots.txt is trusted before it is fetched again.
+const ROBOTS_TTL: Duration = Duration::from_secs(3_600);
+/// Largest robots.txt read; the rest is ignored.
+const MAX_ROBOTS_BYTES: usize = 512 * 1024;
+/// Longest `Crawl-delay` honoured.
+const MAX_CRAWL_DELAY: Duration = Duration::from_secs(60);
+/// Bytes searched for a `<meta charset>`.
+const META_SNIFF_BYTES: usize = 1_024;
+/// Most redirects followed for one page or robots.txt.
+const MAX_REDIRECTS: usize = 10;
+
+/// Content types read as HTML pages.
+pub(crate) const HTML_TYPES: &[&str] = &["text/html", "application/xhtml+xml", "text/plain"];
+/// Content types read as RSS/Atom feeds and sitemaps.
+pub(crate) const XML_TYPES: &[&str] = &[
+    "application/rss+xml",
+    "application/atom+xml",
+    "application/rdf+xml",
+    "application/xml",
+    "text/xml",
+    "text/plain",
+];
+
+type FetchError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Fetches pages on behalf of one skill; robots.txt and host timings are shared across calls.
+pub(crate) struct PoliteFetcher {
+    config: ScraperConfig,
+    client: reqwest::Client,
+    /// Host (`host:port`) → robots.txt rules and when they were fetched.
+    robots: Mutex<HashMap<String, (Instant, RobotsRules)>>,
+    /// Host → earliest time the next request may start.
+    next_slot: Mutex<HashMap<String, Instant>>,
+}
+
+impl PoliteFetcher {
+    pub(crate) fn new(config: ScraperConfig) -> Result<Self, FetchError> {
+        let mut builder = reqwest::Client::builder()
+            .user_agent(config.user_agent.clone())
+            .redirect(reqwest::redirect::Policy::none());
+        if !config.allow_private_hosts {
+            // Checked names are resolved again at connect time under the same rules.
+            builder = builder.dns_resolver(Arc::new(PublicResolver));
+        }
+        let client = builder.build()?;
+        Ok(Self {
+            config,
+            client,
+            robots: Mutex::new(HashMap::new()),
+            next_slot: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// [`check_webhook_url`], unless private hosts are allowed.
+    pub(crate) async fn check_url(&self, url: &str) -> Result<(), FetchError> {
+        if self.config.allow_private_hosts {
+            return Ok(());
+        }
+        check_webhook_url(url)
+            .await
+            .map_err(|e| format!("{} cannot be fetched: {}", url, e).into())
+    }
+
+    /// `host:port` of an http(s) `url` that passes [`Self::check_url`].
+    async fn checked_host(&self, url: &reqwest::Url) -> Result<String, FetchError> {
+        if !matches!(url.scheme(), "http" | "https") {
+            return Err(format!("only http(s) urls can be fetched, not {}", url.scheme()).into());
+        }
+        let host = host_key(url).ok_or("url has no host")?;
+        self.check_url(url.as_str()).await?;
+        Ok(host)
+    }
+
+    /// Body of `url` as text, refused unless its content type is one of `accept` (or missing).
+    /// `timeout` bounds each request (robots.txt and the document).
+    pub(crate) async fn fetch_text(
+        &self,
+        url: &str,
+        timeout: Duration,
+        max_bytes: usize,
+        accept: &[&str],
+    ) -> Result<String, FetchError> {
+        let mut url = reqwest::Url::parse(url).map_err(|e| format!("invalid url {}: {}", url, e))?;
+        let mut redirects = 0;
+        let mut resp = loop {
+            let host = self.checked_host(&url).await?;
+            let crawl_delay = if self.config.respect_robots {
+                let rules = self.robots_for(&url, &host, timeout).await;
+                let path = match url.query() {
+                    Some(query) => format!("{}?{}", url.path(), query),
+                    None => url.path().to_string(),
+                };
+                if !rules.allows(&path) {
+                    return Err(format!("{} is disallowed by robots.txt", url).into());
+                }
+                rules.crawl_delay
+            } else {
+                None
+            };
+            self.wait_turn(&host, crawl_delay).await;
+
+            let resp = self.client.get(url.clone()).timeout(timeout).send().await?;
+            match redirect_target(&url, &resp, &mut redirects)? {
+                Some(next) => url = next,
+                None => break resp.error_for_status()?,
+            }
+        };
+        let content_type = resp
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string);
+        if let Some(content_type) = &content_type {
+            let mime = content_type.split(';').next().unwrap_or("").trim().to_ascii_lowercase();
+            if !mime.is_empty() && !accept.contains(&mime.as_str()) {
+                return Err(format!("{} is {}, expected {}", url, mime, accept.join(" or ")).into());
+            }
+        }
+        if let Some(len) = resp.content_length().filter(|&len| len > max_bytes as u64) {
+            return Err(too_large(&url, len, max_bytes));
+        }
+        let mut body = Vec::new();
+        while let Some(chunk) = resp.chunk().await? {
+            body.extend_from_slice(&chunk);
+            if body.len() > max_bytes {
+                return Err(too_large(&url, format!("over {}", max_bytes), max_bytes));
+            }
+        }
+        Ok(decode(&body, content_type.as_deref()))
+    }
+
+    /// Rules for `host`, fetched once per [`ROBOTS_TTL`].
+    async fn robots_for(&self, url: &reqwest::Url, host: &str, timeout: Duration) -> RobotsRules {
+        if let Some((fetched, rules)) = self.robots.lock().unwrap_or_else(PoisonError::into_inner).get(host) {
+            if fetched.elapsed() < ROBOTS_TTL {
+                return rules.clone();
+            }
+        }
+        let mut robots_url = url.clone();
+        robots_url.set_path("/robots.txt");
+        robots_url.set_query(None);
+        robots_url.set_fragment(None);
+        self.wait_turn(host, None).await;
+        let rules = match self.get_robots(robots_url, timeout).await {
+            Ok(resp) if resp.status().is_success() => match read_capped(resp, MAX_ROBOTS_BYTES).await {
+                Ok(text) => RobotsRules::parse(&text, &self.config.user_agent),
+                Err(_) => RobotsRules::disallow_all(),
+            },
+            // RFC 9309: a missing robots.txt allows everything, an unreachable one nothing.
+            Ok(resp) if resp.status().is_client_error() => RobotsRules::default(),
+            Ok(_) | Err(_) => RobotsRules::disallow_all(),
+        };
+        self.robots
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(host.to_string(), (Instant::now(), rules.clone()));
+        rules
+    }
+
+    /// GET of a robots.txt, following redirects whose hops pass [`Self::checked_host`].
+    async fn get_robots(&self, mut url: reqwest::Url, timeout: Duration) -> Result<reqwest::Response, FetchError> {
+        let mut redirects = 0;
+        loop {
+            self.checked_host(&url).await?;
+            let resp = self.client.get(url.clone()).timeout(timeout).send().await?;
+            match redirect_target(&url, &resp, &mut redirects)? {
+                Some(next) => url = next,
+                None => return Ok(resp),
+            }
+        }
+    }
+
+    /// Waits until `host` may be contacted again and books the following slot.
+    async fn wait_turn(&self, host: &str, crawl_delay: Option<Duration>) {
+        let interval = Duration::from_millis(self.config.min_interval_ms).max(crawl_delay.unwrap_or_default());
+        let start = {
+            let mut slots = self.next_slot.lock().unwrap_or_else(PoisonError::into_inner);
+            let now = Instant::now();
+            let start = slots.get(host).copied().filter(|at| *at > now).unwrap_or(now);
+            slots.insert(host.to_string(), start + interval);
+            start
+        };
+        tokio::time::sleep_until(start.into()).await;
+    }
+}
+
+fn host_key(url: &reqwest::Url) -> Option<String> {
+    let host = url.host_str()?.to_ascii_lowercase();
+    Some(match url.port_or_known_default() {
+        Some(port) => format!("{}:{}", host, port),
+        None => host,
+    })
+}
+
+/// Where a redirect `resp` to `url` points (`None` for any other response), counting it against
+/// [`MAX_REDIRECTS`].
+fn redirect_target(
+    url: &reqwest::Url,
+    resp: &reqwest::Response,
+    redirects: &mut usize,
+) -> Result<Option<reqwest::Url>, FetchError> {
+    if !resp.status().is_redirection() {
+        return Ok(None);
+    }
+    let Some(location) = resp.headers().get(reqwest::header::LOCATION) else {
+        return Ok(None);
+    };
+    *redirects += 1;
+    if *redirects > MAX_REDIRECTS {
+        return Err(format!("{} redirected more than {} times", url, MAX_REDIRECTS).into());
+    }
+    let location = location.to_str().map_err(|_| format!("{} redirected to an invalid location", url))?;
+    let next = url.join(location).map_err(|e| format!("{} redirected to {}: {}", url, location, e))?;
+    Ok(Some(next))
+}
+
+fn too_large(url: &reqwest::Url, bytes: impl std::fmt::Display, max_bytes: usize) -> FetchError {
+    format!("{} is {} bytes, the limit is {} bytes", url, bytes, max_bytes).into()
+}
+
+async fn read_capped(mut resp: reqwest::Response, max_bytes: usize) -> Result<String, reqwest::Error> {
+    let mut body = Vec::new();
+    while let Some(chunk) = resp.chunk().await? {
+        body.extend_from_slice(&chunk);
+        if body.len() >= max_bytes {
+            body.truncate(max_bytes);
+            break;
+        }
+    }
+    Ok(String::from_utf8_lossy(&body).into_owned())
+}
+
+/// Decodes `body` with, in order: its byte-order mark, the `Content-Type` charset, a
+/// `<meta charset>` or XML `encoding` near the top of the document, else UTF-8.
+pub(crate) fn decode(body: &[u8], content_type: Option<&str>) -> String {
+    let encoding = encoding_rs::Encoding::for_bom(body)
+        .map(|(encoding, _)| encoding)
+        .or_else(|| content_type.and_then(charset_param).and_then(|c| encoding_rs::Encoding::for_label(c.as_bytes())))
+        .or_else(|| declared_charset(&body[..body.len().min(META_SNIFF_BYTES)]))
+        .unwrap_or(encoding_rs::UTF_8);
+    encoding.decode(body).0.into_owned()
+}
+
+fn charset_param(content_type: &str) -> Option<String> {
+    content_type.split(';').skip(1).find_map(|param| {
+        let (name, value) = param.split_once('=')?;
+        name.trim()
+            .eq_ignore_ascii_case("charset")
+            .then(|| value.trim().trim_matches(['"', '\'']).to_string())
+    })
+}
+
+/// Charset of `<meta charset="…">`, `<meta http-equiv="Content-Type" content="…; charset=…">` or
+/// `<?xml … encoding="…"?>`.
+fn declared_charset(head: &[u8]) -> Option<&'static encoding_rs::Encoding> {
+    let head = String::from_utf8_lossy(head).to_ascii_lowercase();
+    if let Some(decl) = head.trim_start().strip_prefix("<?xml") {
+        let decl = &decl[..decl.find("?>").unwrap_or(decl.len())];
+        if let Some(at) = decl.find("encoding=") {
+            let value = decl[at + 9..].trim_start_matches(['"', '\'']);
+            let end = value.find(['"', '\'']).unwrap_or(value.len());
+            if let Some(encoding) = encoding_rs::Encoding::for_label(&value.as_bytes()[..end]) {
+                return Some(encoding);
+            }
+        }
+    }
+    let mut rest = head.as_str();
+    while let Some(at) = rest.find("<meta") {
+        rest = &rest[at + 5..];
+        let tag = &rest[..rest.find('>').unwrap_or(rest.len())];
+        if let Some(at) = tag.find("charset=") {
+            let value = tag[at + 8..].trim_start_matches(['"', '\'']);
+            let end = value.find(|c: char| matches!(c, '"' | '\'' | ';' | '/' | '>') || c.is_whitespace()).unwrap_or(value.len());
+            if let Some(encoding) = encoding_rs::Encoding::for_label(&value.as_bytes()[..end]) {
+                return Some(encoding);
+            }
+        }
+    }
+    None
+}
+
+/// The robots.txt group that applies to one user agent.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct RobotsRules {
+    /// `(allow, pattern)`; the longest matching pattern decides, `allow` winning ties.
+    rules: Vec<(bool, String)>,
+    crawl_delay: Option<Duration>,
+}
+
+impl RobotsRules {
+    fn disallow_all() -> Self {
+        Self {
+            rules: vec![(false, "/".to_string())],
+            crawl_delay: None,
+        }
+    }
+
+    /// Rules of the group naming `user_agent`'s product token, else of the `*` group.
+    pub(crate) fn parse(text: &str, user_agent: &str) -> Self {
+        let token = user_agent.split('/').next().unwrap_or(user_agent).trim().to_ascii_lowercase();
+        let (mut named, mut wildcard) = (None::<Self>, None::<Self>);
+        let mut agents: Vec<String> = Vec::new();
+        let mut group = Self::default();
+        let mut in_rules = false;
+        let mut finish = |agents: &[String], group: &Self| {
+            if agents.iter().any(|a| a != "*" && token.contains(a.as_str())) {
+                named.get_or_insert_with(Self::default).merge(group);
+            } else if agents.iter().any(|a| a == "*") {
+                wildcard.get_or_insert_with(Self::default).merge(group);
+            }
+        };
+        for line in text.lines() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            let Some((field, value)) = line.split_once(':') else {
+                continue;
+            };
+            let (field, value) = (field.trim().to_ascii_lowercase(), value.trim());
+            match field.as_str() {
+                "user-agent" => {
+                    if in_rules {
+                        finish(&agents, &group);
+                        agents.clear();
+                        group = Self::default();
+                        in_rules = false;
+                    }
+                    agents.push(value.to_ascii_lowercase());
+                }
+                "allow" | "disallow" => {
+                    in_rules = true;
+                    // An empty Disallow allows everything and adds no rule.
+                    if !value.is_empty() {
+                        group.rules.push((field == "allow", value.to_string()));
+                    }
+                }
+                "crawl-delay" => {
+                    in_rules = true;
+                    group.crawl_delay = value
+                        .parse::<f64>()
+                        .ok()
+                        .filter(|secs| secs.is_finite() && *secs >= 0.0)
+                        .map(|secs| Duration::from_secs_f64(secs).min(MAX_CRAWL_DELAY));
+                }
+                _ => {}
+            }
+        }
+        if !agents.is_empty() {
+            finish(&agents, &group);
+        }
+        named.or(wildcard).unwrap_or_default()
+    }
+
+    fn merge(&mut self, other: &Self) {
+        self.rules.extend(other.rules.iter().cloned());
+        self.crawl_delay = self.crawl_delay.or(other.crawl_delay);
+    }
+
+    /// Whether `path` (with its query) may be fetched.
+    pub(crate) fn allows(&self, path: &str) -> bool {
+        if path == "/robots.txt" {
+            return true;
+        }
+        self.rules
+            .iter()
+            .filter(|(_, pattern)| pattern_matches(pattern, path))
+            .max_by_key(|(allow, pattern)| (pattern.len(), *allow))
+            .is_none_or(|(allow, _)| *allow)
+    }
+}
+
+/// robots.txt path matching: a prefix match where `*` matches any run and a trailing `$`
+/// anchors the end.
+fn pattern_matches(pattern: &str, path: &str) -> bool {
+    let (pattern, anchored) = match pattern.strip_suffix('$') {
+        Some(p) => (p, true),
+        None => (pattern, false),
+    };
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or("");
+    let Some(mut rest) = path.strip_prefix(first) else {
+        return false;
+    };
+    let parts: Vec<&str> = parts.collect();
+    for (i, part) in parts.iter().enumerate() {
+        let last = i + 1 == parts.len();
+        if last && anchored {
+            return rest.ends_with(part);
+        }
+        match rest.find(part) {
+            Some(at) => rest = &rest[at + part.len()..],
+            None => return false,
+        }
+    }
+    !anchored || rest.is_empty()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn robots_groups_and_longest_match() {
+        let text = "
+            User-agent: *
+            Disallow: /private
+            Crawl-delay: 2
+
+            User-agent: OtherBot
+            User-agent: UAC-CommunityScraper
+            Disallow: /news/drafts
+            Allow: /news/drafts/public
+            Disallow: /*.pdf$
+            Disallow: /search?
+        ";
+        let ours = RobotsRules::parse(text, "UAC-CommunityScraper/1.0");
+        assert!(ours.allows("/private"));
+        assert!(!ours.allows("/news/drafts/1"));
+        assert!(ours.allows("/news/drafts/public/1"));
+        assert!(!ours.allows("/files/report.pdf"));
+        assert!(ours.allows("/files/report.pdf.html"));
+        assert!(!ours.allows("/search?q=fair"));
+        assert_eq!(ours.crawl_delay, None);
+
+        let others = RobotsRules::parse(text, "SomeBot/2.0");
+        assert!(!others.allows("/private/x"));
+        assert!(others.allows("/news/drafts/1"));
+        assert_eq!(others.crawl_delay, Some(Duration::from_secs(2)));
+
+        assert!(RobotsRules::parse("User-agent: *\nDisallow:", "x").allows("/anything"));
+        assert!(!RobotsRules::disallow_all().allows("/"));
+        assert!(RobotsRules::disallow_all().allows("/robots.txt"));
+    }
+
+    #[tokio::test]
+    async fn client_refuses_names_resolving_to_internal_addresses() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://localhost:{}/", listener.local_addr().unwrap().port());
+        // The client itself refuses, not just the check in front of it.
+        let fetcher = PoliteFetcher::new(ScraperConfig::default()).unwrap();
+        assert!(fetcher.client.get(&url).send().await.is_err());
+        let config = ScraperConfig { allow_private_hosts: true, ..ScraperConfig::default() };
+        let fetcher = PoliteFetcher::new(config).unwrap();
+        tokio::spawn(async move {
+            use tokio::io::AsyncWriteExt;
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let _ = socket.write_all(b"HTTP/1.1 204 No Content\r\nconnection: close\r\n\r\n").await;
+        });
+        assert!(fetcher.client.get(&url).send().await.is_ok());
+    }
+
+    #[test]
+    fn decode_uses_bom_header_then_meta_charset() {
+        let latin1 = b"<html><head><meta charset=\"iso-8859-1\"></head><h1>Caf\xe9</h1></html>";
+        assert!(decode(latin1, Some("text/html")).contains("Café"));
+        let declared = b"<meta http-equiv=\"Content-Type\" content=\"text/html; charset=windows-1252\"><h1>\x93Fair\x94</h1>";
+        assert!(decode(declared, None).contains("\u{201c}Fair\u{201d}"));
+        // The header wins over the page.
+        assert!(decode("<meta charset=iso-8859-1><h1>Café</h1>".as_bytes(), Some("text/html; charset=\"utf-8\"")).contains("Café"));
+        assert_eq!(decode(b"\xef\xbb\xbfok", Some("text/html; charset=iso-8859-1")), "ok");
+        assert_eq!(decode(b"plain", None), "plain");
+        let xml = b"<?xml version=\"1.0\" encoding=\"ISO-8859-1\"?><rss><title>Caf\xe9</title></rss>";
+        assert!(decode(xml, Some("application/rss+xml")).contains("Café"));
+    }
+}
@@ -0,0 +1,613 @@
+//! Workspace reports: what `fs_workspace_analyzer` finds under its roots.
+//!
+//! Each root is walked breadth-first without following symlinks. Paths matching the exclusion
+//! rules are skipped: the built-in [`DEFAULT_EXCLUDES`], then the root's `.gitignore` (when
+//! `respect_gitignore` is on), then the configured `exclude` rules, the last matching rule
+//! winning as in git. Only the root's own `.gitignore` is read, not nested ones.
+//!
+//! The [`WorkspaceReport`] lists the crates found, files and non-blank lines per language, files
+//! of at least `large_file_bytes`, and local git branches whose last update (from the branch
+//! reflog, else the ref file's mtime) is older than `stale_branch_days`. The analyzer stores the
+//! latest report in KB_OIKOS under `workspace_scan/latest` for the guardian and dashboards
+//! ([`WorkspaceReport::latest`]).
+
+use pagi_core::{KbType, KnowledgeStore};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Key under KB_OIKOS where the latest workspace report is stored.
+pub const OIKOS_WORKSPACE_SCAN_KEY: &str = "workspace_scan/latest";
+
+/// Rules applied before the root's `.gitignore`: VCS metadata, build output, and sled/db
+/// fixtures (e.g. `add-ons/pagi-gateway/data`).
+pub const DEFAULT_EXCLUDES: &[&str] = &[".git/", "target/", "node_modules/", "data/", "db/", "blobs/"];
+
+/// Most large files one report lists (largest first).
+pub const MAX_LARGE_FILES: usize = 50;
+
+/// Files larger than this are counted but not read for line counts.
+const MAX_COUNTED_FILE_BYTES: u64 = 8 * 1024 * 1024;
+
+const DAY_MS: i64 = 24 * 60 * 60 * 1000;
+
+/// How a workspace is scanned. The analyzer's defaults come from
+/// `FsWorkspaceAnalyzer::with_options`; payload fields override them per call.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WorkspaceScanOptions {
+    /// Directories to scan (empty = the working directory).
+    pub roots: Vec<PathBuf>,
+    /// `.gitignore`-style rules applied after the root's `.gitignore`.
+    pub exclude: Vec<String>,
+    pub respect_gitignore: bool,
+    /// Deepest directory level walked (0 = just the root).
+    pub max_depth: usize,
+    /// Files at least this large are listed in `large_files` (bytes).
+    pub large_file_bytes: u64,
+    /// Branches not updated for this many days are listed in `stale_branches`.
+    pub stale_branch_days: u64,
+}
+
+impl Default for WorkspaceScanOptions {
+    fn default() -> Self {
+        Self {
+            roots: Vec::new(),
+            exclude: Vec::new(),
+            respect_gitignore: true,
+            max_depth: 25,
+            large_file_bytes: 1024 * 1024,
+            stale_branch_days: 90,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct IgnoreRule {
+    pattern: String,
+    negated: bool,
+    dir_only: bool,
+    /// Matched against the whole relative path rather than the file name.
+    anchored: bool,
+}
+
+/// `.gitignore`-style exclusion rules: `#` comments, `!` negation, a trailing `/` for directories
+/// only, a leading or inner `/` to anchor at the root, and `*`, `?`, `[a-z]` and `**` globs.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IgnoreRules {
+    rules: Vec<IgnoreRule>,
+}
+
+impl IgnoreRules {
+    pub fn parse(text: &str) -> Self {
+        let mut rules = Self::default();
+        rules.extend(text.lines());
+        rules
+    }
+
+    /// Appends rules; later rules win over earlier ones.
+    pub fn extend<'a>(&mut self, lines: impl IntoIterator<Item = &'a str>) {
+        for line in lines {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (negated, line) = match line.strip_prefix('!') {
+                Some(rest) => (true, rest),
+                None => (false, line),
+            };
+            let (dir_only, line) = match line.strip_suffix('/') {
+                Some(rest) => (true, rest),
+                None => (false, line),
+            };
+            let anchored = line.contains('/');
+            let pattern = line.trim_start_matches('/');
+            if pattern.is_empty() {
+                continue;
+            }
+            self.rules.push(IgnoreRule {
+                pattern: pattern.to_string(),
+                negated,
+                dir_only,
+                anchored,
+            });
+        }
+    }
+
+    /// Whether `rel` (relative to the root, `/`-separated) is excluded.
+    pub fn is_ignored(&self, rel: &str, is_dir: bool) -> bool {
+        let name = rel.rsplit('/').next().unwrap_or(rel);
+        let mut ignored = false;
+        for rule in &self.rules {
+            if rule.dir_only && !is_dir {
+                continue;
+            }
+            let subject = if rule.anchored { rel } else { name };
+            if glob_match(rule.pattern.as_bytes(), subject.as_bytes()) {
+                ignored = !rule.negated;
+            }
+        }
+        ignored
+    }
+}
+
+fn glob_match(pattern: &[u8], text: &[u8]) -> bool {
+    match pattern.first() {
+        None => text.is_empty(),
+        Some(b'*') if pattern.get(1) == Some(&b'*') => match pattern.get(2) {
+            // `**/` also matches no directory at all.
+            Some(b'/') => (0..=text.len())
+                .filter(|&i| i == 0 || text[i - 1] == b'/')
+                .any(|i| glob_match(&pattern[3..], &text[i..])),
+            _ => (0..=text.len()).any(|i| glob_match(&pattern[2..], &text[i..])),
+        },
+        Some(b'*') => (0..=text.len())
+            .take_while(|&i| i == 0 || text[i - 1] != b'/')
+            .any(|i| glob_match(&pattern[1..], &text[i..])),
+        Some(b'?') => text.first().is_some_and(|&c| c != b'/') && glob_match(&pattern[1..], &text[1..]),
+        Some(b'[') => match (text.first(), pattern.iter().skip(1).position(|&c| c == b']')) {
+            (Some(&c), Some(end)) if c != b'/' => {
+                let class = &pattern[1..end + 1];
+                let (negated, class) = match class.first() {
+                    Some(b'!' | b'^') => (true, &class[1..]),
+                    _ => (false, class),
+                };
+                let mut found = false;
+                let mut i = 0;
+                while i < class.len() {
+                    if i + 2 < class.len() && class[i + 1] == b'-' {
+                        found |= (class[i]..=class[i + 2]).contains(&c);
+                        i += 3;
+                    } else {
+                        found |= class[i] == c;
+                        i += 1;
+                    }
+                }
+                found != negated && glob_match(&pattern[end + 2..], &text[1..])
+            }
+            _ => false,
+        },
+        Some(b'\\') if pattern.len() > 1 => text.first() == Some(&pattern[1]) && glob_match(&pattern[2..], &text[1..]),
+        Some(&c) => text.first() == Some(&c) && glob_match(&pattern[1..], &text[1..]),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CrateInfo {
+    /// Best-effort crate/package name derived from Cargo.toml.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Path to the crate directory.
+    pub path: String,
+    /// Path to the manifest.
+    pub manifest: String,
+    /// Whether `src/` exists under the crate directory.
+    pub has_src: bool,
+    /// Whether this manifest looks like a workspace root.
+    pub is_workspace: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LanguageStats {
+    pub language: String,
+    pub files: u64,
+    /// Non-blank lines.
+    pub lines: u64,
+    pub bytes: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LargeFile {
+    pub path: String,
+    pub bytes: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StaleBranch {
+    /// Repository directory.
+    pub repo: String,
+    pub name: String,
+    /// Whether the branch is checked out.
+    pub current: bool,
+    pub last_updated_ms: i64,
+    pub age_days: u64,
+}
+
+/// What a workspace scan found. Paths are relative to the scanned root, or to the working
+/// directory when several roots were scanned.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WorkspaceReport {
+    pub generated_at_ms: i64,
+    /// The first root.
+    pub root: String,
+    pub roots: Vec<String>,
+    pub crate_count: usize,
+    pub crates: Vec<CrateInfo>,
+    pub workspace_manifest_count: usize,
+    pub add_ons_found: bool,
+    pub add_ons_path: Option<String>,
+    pub total_files: u64,
+    /// Non-blank lines in files of a known language.
+    pub total_lines: u64,
+    /// Most lines first.
+    pub languages: Vec<LanguageStats>,
+    /// Largest first, at most [`MAX_LARGE_FILES`].
+    pub large_files: Vec<LargeFile>,
+    pub stale_branches: Vec<StaleBranch>,
+    /// Files and directories skipped by the exclusion rules.
+    pub excluded: u64,
+    pub summary: String,
+}
+
+impl WorkspaceReport {
+    /// Scans `roots` (at least one); paths in the report are relative to `base`.
+    pub fn scan(base: &Path, roots: &[PathBuf], options: &WorkspaceScanOptions, now_ms: i64) -> Self {
+        let mut scan = Scan::default();
+        for root in roots {
+            scan.root(base, root, options, now_ms);
+        }
+        scan.crates.sort_by(|a, b| a.manifest.cmp(&b.manifest));
+        scan.large_files.sort_by(|a, b| b.bytes.cmp(&a.bytes).then_with(|| a.path.cmp(&b.path)));
+        scan.large_files.truncate(MAX_LARGE_FILES);
+        scan.stale_branches.sort_by(|a, b| b.age_days.cmp(&a.age_days).then_with(|| a.name.cmp(&b.name)));
+        let mut languages: Vec<LanguageStats> = scan.languages.into_values().collect();
+        languages.sort_by(|a, b| b.lines.cmp(&a.lines).then_with(|| a.language.cmp(&b.language)));
+
+        let roots: Vec<String> = roots.iter().map(|r| rel_or_abs(base, r)).collect();
+        let root = roots.first().cloned().unwrap_or_else(|| ".".to_string());
+        let workspace_manifest_count = scan.crates.iter().filter(|c| c.is_workspace).count();
+        let total_lines = languages.iter().map(|l| l.lines).sum();
+        let top_languages = languages
+            .iter()
+            .take(3)
+            .map(|l| format!("{} ({} lines)", l.language, l.lines))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let summary = format!(
+            "Workspace scan root: {}\nCrates/manifests found: {}\nWorkspace manifests: {}\nadd-ons present: {}\n\
+             Files: {} ({} lines){}\nLarge files: {}\nStale branches: {}",
+            roots.join(", "),
+            scan.crates.len(),
+            workspace_manifest_count,
+            scan.add_ons_path.is_some(),
+            scan.total_files,
+            total_lines,
+            if top_languages.is_empty() { String::new() } else { format!("; top languages: {}", top_languages) },
+            scan.large_files.len(),
+            scan.stale_branches.len(),
+        );
+
+        Self {
+            generated_at_ms: now_ms,
+            root,
+            roots,
+            crate_count: scan.crates.len(),
+            crates: scan.crates,
+            workspace_manifest_count,
+            add_ons_found: scan.add_ons_path.is_some(),
+            add_ons_path: scan.add_ons_path,
+            total_files: scan.total_files,
+            total_lines,
+            languages,
+            large_files: scan.large_files,
+            stale_branches: scan.stale_branches,
+            excluded: scan.excluded,
+            summary,
+        }
+    }
+
+    /// The report the analyzer stored last, if any.
+    pub fn latest(store: &KnowledgeStore) -> Option<Self> {
+        let record = store.get_record(KbType::Oikos.slot_id(), OIKOS_WORKSPACE_SCAN_KEY).ok()??;
+        serde_json::from_str(&record.content).ok()
+    }
+}
+
+#[derive(Default)]
+struct Scan {
+    crates: Vec<CrateInfo>,
+    add_ons_path: Option<String>,
+    total_files: u64,
+    languages: BTreeMap<&'static str, LanguageStats>,
+    large_files: Vec<LargeFile>,
+    stale_branches: Vec<StaleBranch>,
+    excluded: u64,
+}
+
+impl Scan {
+    fn root(&mut self, base: &Path, root: &Path, options: &WorkspaceScanOptions, now_ms: i64) {
+        let mut rules = IgnoreRules::default();
+        rules.extend(DEFAULT_EXCLUDES.iter().copied());
+        if options.respect_gitignore {
+            if let Ok(text) = fs::read_to_string(root.join(".gitignore")) {
+                rules.extend(text.lines());
+            }
+        }
+        rules.extend(options.exclude.iter().map(String::as_str));
+
+        if root.join(".git").is_dir() {
+            self.stale_branches.extend(stale_branches(base, root, options.stale_branch_days, now_ms));
+        }
+
+        let mut queue: VecDeque<(PathBuf, usize)> = VecDeque::new();
+        queue.push_back((root.to_path_buf(), 0));
+        while let Some((dir, depth)) = queue.pop_front() {
+            if self.add_ons_path.is_none() && dir.file_name().and_then(|s| s.to_str()) == Some("add-ons") {
+                self.add_ons_path = Some(rel_or_abs(base, &dir));
+            }
+            let manifest_path = dir.join("Cargo.toml");
+            if manifest_path.is_file() {
+                let (name, is_workspace) = read_manifest_metadata(&manifest_path);
+                self.crates.push(CrateInfo {
+                    name,
+                    path: rel_or_abs(base, &dir),
+                    manifest: rel_or_abs(base, &manifest_path),
+                    has_src: dir.join("src").is_dir(),
+                    is_workspace,
+                });
+            }
+
+            let Ok(entries) = fs::read_dir(&dir) else { continue };
+            for entry in entries.flatten() {
+                let Ok(file_type) = entry.file_type() else { continue };
+                if file_type.is_symlink() {
+                    continue;
+                }
+                let path = entry.path();
+                let rel = rel_or_abs(root, &path);
+                if rules.is_ignored(&rel, file_type.is_dir()) {
+                    self.excluded += 1;
+                } else if file_type.is_dir() {
+                    if depth < options.max_depth {
+                        queue.push_back((path, depth + 1));
+                    }
+                } else if let Ok(meta) = entry.metadata() {
+                    self.file(base, &path, meta.len(), options);
+                }
+            }
+        }
+    }
+
+    fn file(&mut self, base: &Path, path: &Path, bytes: u64, options: &WorkspaceScanOptions) {
+        self.total_files += 1;
+        if bytes >= options.large_file_bytes {
+            self.large_files.push(LargeFile {
+                path: rel_or_abs(base, path),
+                bytes,
+            });
+        }
+        let Some(language) = language_of(path) else { return };
+        let lines = match bytes <= MAX_COUNTED_FILE_BYTES {
+            true => fs::read(path)
+                .map(|content| {
+                    content
+                        .split(|&b| b == b'\n')
+                        .filter(|line| line.iter().any(|b| !b.is_ascii_whitespace()))
+                        .count() as u64
+                })
+                .unwrap_or(0),
+            false => 0,
+        };
+        let stats = self.languages.entry(language).or_insert_with(|| LanguageStats {
+            language: language.to_string(),
+            files: 0,
+            lines: 0,
+            bytes: 0,
+        });
+        stats.files += 1;
+        stats.lines += lines;
+        stats.bytes += bytes;
+    }
+}
+
+fn language_of(path: &Path) -> Option<&'static str> {
+    if path.file_name().and_then(|n| n.to_str()) == Some("Dockerfile") {
+        return Some("Dockerfile");
+    }
+    let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+    Some(match ext.as_str() {
+        "rs" => "Rust",
+        "ts" | "tsx" => "TypeScript",
+        "js" | "jsx" | "mjs" | "cjs" => "JavaScript",
+        "py" => "Python",
+        "go" => "Go",
+        "java" => "Java",
+        "c" | "h" => "C",
+        "cc" | "cpp" | "hpp" => "C++",
+        "sh" | "bash" => "Shell",
+        "ps1" => "PowerShell",
+        "sql" => "SQL",
+        "html" => "HTML",
+        "css" | "scss" => "CSS",
+        "md" => "Markdown",
+        "toml" => "TOML",
+        "json" => "JSON",
+        "yml" | "yaml" => "YAML",
+        _ => return None,
+    })
+}
+
+/// Local branches of the repository at `root` last updated at least `days` days before `now_ms`.
+fn stale_branches(base: &Path, root: &Path, days: u64, now_ms: i64) -> Vec<StaleBranch> {
+    let git = root.join(".git");
+    let current = fs::read_to_string(git.join("HEAD"))
+        .ok()
+        .and_then(|head| head.trim().strip_prefix("ref: refs/heads/").map(str::to_string));
+    let mut names = Vec::new();
+    let mut stack = vec![git.join("refs/heads")];
+    while let Some(dir) = stack.pop() {
+        let Ok(entries) = fs::read_dir(&dir) else { continue };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                stack.push(path);
+            } else {
+                names.push(rel_or_abs(&git.join("refs/heads"), &path));
+            }
+        }
+    }
+    // Branches only in packed-refs are found too; without a reflog they have no date and are skipped.
+    if let Ok(packed) = fs::read_to_string(git.join("packed-refs")) {
+        for line in packed.lines() {
+            if let Some(name) = line.split_once(' ').and_then(|(_, r)| r.strip_prefix("refs/heads/")) {
+                if !names.iter().any(|n| n == name) {
+                    names.push(name.to_string());
+                }
+            }
+        }
+    }
+
+    let repo = rel_or_abs(base, root);
+    names
+        .into_iter()
+        .filter_map(|name| {
+            let last_updated_ms = reflog_time_ms(&git.join("logs/refs/heads").join(&name)).or_else(|| {
+                let modified = fs::metadata(git.join("refs/heads").join(&name)).ok()?.modified().ok()?;
+                Some(modified.duration_since(std::time::UNIX_EPOCH).ok()?.as_millis() as i64)
+            })?;
+            let age_days = (now_ms.saturating_sub(last_updated_ms) / DAY_MS).max(0) as u64;
+            (age_days >= days).then(|| StaleBranch {
+                repo: repo.clone(),
+                current: current.as_deref() == Some(name.as_str()),
+                name,
+                last_updated_ms,
+                age_days,
+            })
+        })
+        .collect()
+}
+
+/// Time of the last entry of a reflog (`<old> <new> <name> <email> <unix secs> <tz>\t<message>`).
+fn reflog_time_ms(path: &Path) -> Option<i64> {
+    let text = fs::read_to_string(path).ok()?;
+    let line = text.lines().rev().find(|l| !l.trim().is_empty())?;
+    let head = line.split('\t').next()?;
+    let mut fields = head.rsplit(' ');
+    let _tz = fields.next()?;
+    let secs: i64 = fields.next()?.parse().ok()?;
+    Some(secs * 1000)
+}
+
+pub(crate) fn rel_or_abs(root: &Path, p: &Path) -> String {
+    p.strip_prefix(root)
+        .ok()
+        .map(|rp| {
+            let s = rp.to_string_lossy().to_string();
+            if s.is_empty() { ".".to_string() } else { s }
+        })
+        .unwrap_or_else(|| p.to_string_lossy().to_string())
+        .replace('\\', "/")
+}
+
+fn read_manifest_metadata(manifest_path: &Path) -> (Option<String>, bool) {
+    let Ok(text) = fs::read_to_string(manifest_path) else {
+        return (None, false);
+    };
+
+    let is_workspace = text.lines().any(|l| l.trim() == "[workspace]");
+    let mut in_package = false;
+    let mut name: Option<String> = None;
+
+    for raw in text.lines() {
+        let line = raw.trim();
+        if line.starts_with('[') && line.ends_with(']') {
+            in_package = line == "[package]";
+            // Stop scanning package name once we hit another section after [package].
+            if name.is_some() && !in_package {
+                break;
+            }
+            continue;
+        }
+        if !in_package {
+            continue;
+        }
+
+        // Very small TOML subset parse: `name = "..."`.
+        let Some(rest) = line.strip_prefix("name") else { continue };
+        let rest = rest.trim_start();
+        let Some(rest) = rest.strip_prefix('=') else { continue };
+        let rest = rest.trim();
+        if let Some(stripped) = rest.strip_prefix('"').and_then(|r| r.strip_suffix('"')) {
+            name = Some(stripped.to_string());
+            break;
+        }
+    }
+
+    (name, is_workspace)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ignore_rules_follow_gitignore_semantics() {
+        let rules = IgnoreRules::parse("# build\n*.log\n!keep.log\n/dist\nbuild/\ndocs/**/draft-?.md\nsrc/[a-c]*.tmp\n");
+        assert!(rules.is_ignored("app.log", false));
+        assert!(rules.is_ignored("nested/deep/app.log", false));
+        assert!(!rules.is_ignored("nested/keep.log", false));
+        assert!(rules.is_ignored("dist", true));
+        assert!(!rules.is_ignored("web/dist", true));
+        assert!(rules.is_ignored("web/build", true));
+        assert!(!rules.is_ignored("build", false));
+        assert!(rules.is_ignored("docs/draft-1.md", false));
+        assert!(rules.is_ignored("docs/a/b/draft-2.md", false));
+        assert!(!rules.is_ignored("docs/draft-10.md", false));
+        assert!(rules.is_ignored("src/b1.tmp", false));
+        assert!(!rules.is_ignored("src/d1.tmp", false));
+    }
+
+    #[test]
+    fn scan_reports_languages_large_files_and_stale_branches() {
+        let root = std::env::temp_dir().join(format!("pagi_workspace_report_{}", uuid::Uuid::new_v4().simple()));
+        let now_ms = 1_704_067_200_000;
+        fs::create_dir_all(root.join("crates/app/src")).unwrap();
+        fs::create_dir_all(root.join("target/debug")).unwrap();
+        fs::create_dir_all(root.join("logs")).unwrap();
+        fs::write(root.join("Cargo.toml"), "[workspace]\nmembers = [\"crates/app\"]\n").unwrap();
+        fs::write(root.join("crates/app/Cargo.toml"), "[package]\nname = \"app\"\n").unwrap();
+        fs::write(root.join("crates/app/src/main.rs"), "fn main() {\n\n    println!(\"hi\");\n}\n").unwrap();
+        fs::write(root.join("target/debug/app.rs"), "fn skipped() {}\n").unwrap();
+        fs::write(root.join("logs/run.log"), "x".repeat(2048)).unwrap();
+        fs::write(root.join("blob.bin"), vec![0u8; 4096]).unwrap();
+        fs::write(root.join(".gitignore"), "logs/\n").unwrap();
+
+        let git = root.join(".git");
+        fs::create_dir_all(git.join("refs/heads/feature")).unwrap();
+        fs::create_dir_all(git.join("logs/refs/heads/feature")).unwrap();
+        fs::write(git.join("HEAD"), "ref: refs/heads/main\n").unwrap();
+        for (name, secs) in [("main", now_ms / 1000 - 86_400), ("feature/old", now_ms / 1000 - 200 * 86_400)] {
+            fs::write(git.join("refs/heads").join(name), "0123abcd\n").unwrap();
+            let log = format!("0000 0123abcd Dev <dev@example.com> {} +0000\tcommit: work\n", secs);
+            fs::write(git.join("logs/refs/heads").join(name), log).unwrap();
+        }
+
+        let options = WorkspaceScanOptions {
+            large_file_bytes: 1024,
+            exclude: vec!["*.bin".into(), "!blob.bin".into()],
+            ..WorkspaceScanOptions::default()
+        };
+        let report = WorkspaceReport::scan(&root, std::slice::from_ref(&root), &options, now_ms);
+        assert_eq!(report.crate_count, 2);
+        assert_eq!(report.workspace_manifest_count, 1);
+        let rust = report.languages.iter().find(|l| l.language == "Rust").unwrap();
+        assert_eq!((rust.files, rust.lines), (1, 3));
+        assert_eq!(report.large_files, vec![LargeFile { path: "blob.bin".into(), bytes: 4096 }]);
+        // .git, target and logs (from .gitignore).
+        assert_eq!(report.excluded, 3);
+        assert_eq!(report.stale_branches.len(), 1);
+        let stale = &report.stale_branches[0];
+        assert_eq!((stale.name.as_str(), stale.age_days, stale.current), ("feature/old", 200, false));
+
+        let shallow = WorkspaceScanOptions {
+            max_depth: 0,
+            respect_gitignore: false,
+            ..WorkspaceScanOptions::default()
+        };
+        let report = WorkspaceReport::scan(&root, std::slice::from_ref(&root), &shallow, now_ms);
+        assert_eq!(report.crate_count, 1);
+        assert_eq!(report.excluded, 2);
+        let _ = fs::remove_dir_all(&root);
+    }
+}